@@ -0,0 +1,360 @@
+use crate::config::{AccessLevel, AuthConfig, DatasetAccessConfig};
+use crate::HttpError;
+use anyhow::{anyhow, bail, Context};
+use argon2::password_hash::{PasswordHashString, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2};
+use oxhttp::model::{HeaderName, Request, Status};
+use oxigraph::model::{GraphName, GraphNameRef, NamedNode};
+use oxsdatatypes::Base64Binary;
+use rand::random;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::str;
+use std::str::FromStr;
+
+/// Authentication and authorization rules of the server.
+pub struct Authorization {
+    anonymous: Permissions,
+    users: HashMap<String, (PasswordHashString, Permissions)>,
+    tokens: Vec<([u8; 32], Permissions)>,
+}
+
+impl Authorization {
+    pub fn new(config: &AuthConfig) -> anyhow::Result<Self> {
+        Ok(Self {
+            anonymous: Permissions {
+                store: DatasetPermissions {
+                    default: config.anonymous_access,
+                    graphs: HashMap::new(),
+                },
+                datasets: HashMap::new(),
+                other_datasets: DatasetPermissions {
+                    default: config.anonymous_access,
+                    graphs: HashMap::new(),
+                },
+            },
+            users: config
+                .users
+                .iter()
+                .map(|user| {
+                    Ok((
+                        user.name.clone(),
+                        (
+                            parse_password_hash(&user.password_hash).with_context(|| {
+                                format!("Invalid password-hash for user {}", user.name)
+                            })?,
+                            Permissions::new(user.access, &user.graphs, &user.datasets)?,
+                        ),
+                    ))
+                })
+                .collect::<anyhow::Result<_>>()?,
+            tokens: config
+                .tokens
+                .iter()
+                .map(|token| {
+                    Ok((
+                        parse_sha256(&token.token_sha256).context("Invalid token-sha256")?,
+                        Permissions::new(token.access, &token.graphs, &token.datasets)?,
+                    ))
+                })
+                .collect::<anyhow::Result<_>>()?,
+        })
+    }
+
+    /// Finds the permissions of the request issuer on the server store or on the given dataset
+    /// using the HTTP basic or bearer authentication.
+    ///
+    /// Fails if some credentials are given but are not valid.
+    pub fn authenticate(
+        &self,
+        request: &Request,
+        dataset: Option<&str>,
+    ) -> Result<Access<'_>, HttpError> {
+        let Some(header) = request.header(&HeaderName::AUTHORIZATION) else {
+            return Ok(Access {
+                permissions: self.anonymous.dataset(dataset),
+                principal: None,
+            });
+        };
+        let header = header
+            .to_str()
+            .map_err(|_| unauthorized("Invalid Authorization header"))?;
+        let (scheme, credentials) = header
+            .split_once(' ')
+            .ok_or_else(|| unauthorized("Invalid Authorization header"))?;
+        let (permissions, principal) = if scheme.eq_ignore_ascii_case("basic") {
+            let credentials = decode_base64(credentials.trim())
+                .ok_or_else(|| unauthorized("Invalid basic authentication credentials"))?;
+            let (name, password) = credentials
+                .split_once(':')
+                .ok_or_else(|| unauthorized("Invalid basic authentication credentials"))?;
+            let (expected_hash, permissions) = self
+                .users
+                .get(name)
+                .ok_or_else(|| unauthorized("Invalid user name or password"))?;
+            if Argon2::default()
+                .verify_password(password.as_bytes(), &expected_hash.password_hash())
+                .is_err()
+            {
+                return Err(unauthorized("Invalid user name or password"));
+            }
+            (permissions, Principal::User(name.into()))
+        } else if scheme.eq_ignore_ascii_case("bearer") {
+            let hash = Sha256::digest(credentials.trim().as_bytes());
+            let (i, (_, permissions)) = self
+                .tokens
+                .iter()
+                .enumerate()
+                .find(|(_, (expected_hash, _))| hash.as_slice() == expected_hash)
+                .ok_or_else(|| unauthorized("Invalid bearer token"))?;
+            (permissions, Principal::Token(i))
+        } else {
+            return Err(unauthorized(format!(
+                "Unsupported authentication scheme {scheme}"
+            )));
+        };
+        Ok(Access {
+            permissions: permissions.dataset(dataset),
+            principal: Some(principal),
+        })
+    }
+}
+
+/// An authenticated request issuer.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Principal {
+    /// A user authenticated with the HTTP basic authentication
+    User(String),
+    /// A bearer token, identified by its position in the configuration
+    Token(usize),
+}
+
+/// The permissions of a request issuer on the server store or on one of the datasets.
+pub struct Access<'a> {
+    permissions: &'a DatasetPermissions,
+    /// The authenticated request issuer, `None` if the request is anonymous
+    principal: Option<Principal>,
+}
+
+impl Access<'_> {
+    pub fn principal(&self) -> Option<&Principal> {
+        self.principal.as_ref()
+    }
+
+    /// Checks that the request issuer has the given access level to the given graph or to the full dataset if `graph` is `None`.
+    pub fn check(
+        &self,
+        graph: Option<GraphNameRef<'_>>,
+        level: AccessLevel,
+    ) -> Result<(), HttpError> {
+        let granted = if let Some(graph) = graph {
+            self.permissions.graph_access(graph)
+        } else {
+            self.permissions.dataset_access()
+        };
+        if granted >= level {
+            Ok(())
+        } else if self.principal.is_some() {
+            Err((
+                Status::FORBIDDEN,
+                format!(
+                    "You do not have {level} access to {}",
+                    describe_target(graph)
+                ),
+            ))
+        } else {
+            Err(unauthorized(format!(
+                "Authentication is required to get {level} access to {}",
+                describe_target(graph)
+            )))
+        }
+    }
+}
+
+struct Permissions {
+    /// Permissions on the server store
+    store: DatasetPermissions,
+    /// Permissions on the datasets managed with the `/datasets` API
+    datasets: HashMap<String, DatasetPermissions>,
+    /// Permissions on the datasets not listed in `datasets`
+    other_datasets: DatasetPermissions,
+}
+
+impl Permissions {
+    fn new(
+        default: AccessLevel,
+        graphs: &HashMap<String, AccessLevel>,
+        datasets: &HashMap<String, DatasetAccessConfig>,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            store: DatasetPermissions::new(default, graphs)?,
+            datasets: datasets
+                .iter()
+                .map(|(name, config)| {
+                    Ok((
+                        name.clone(),
+                        DatasetPermissions::new(config.access, &config.graphs)
+                            .with_context(|| format!("Invalid access to dataset {name}"))?,
+                    ))
+                })
+                .collect::<anyhow::Result<_>>()?,
+            other_datasets: DatasetPermissions {
+                default: AccessLevel::None,
+                graphs: HashMap::new(),
+            },
+        })
+    }
+
+    /// The permissions on the given dataset or on the server store if `dataset` is `None`
+    fn dataset(&self, dataset: Option<&str>) -> &DatasetPermissions {
+        let Some(dataset) = dataset else {
+            return &self.store;
+        };
+        self.datasets.get(dataset).unwrap_or(&self.other_datasets)
+    }
+}
+
+struct DatasetPermissions {
+    default: AccessLevel,
+    graphs: HashMap<GraphName, AccessLevel>,
+}
+
+impl DatasetPermissions {
+    fn new(default: AccessLevel, graphs: &HashMap<String, AccessLevel>) -> anyhow::Result<Self> {
+        Ok(Self {
+            default,
+            graphs: graphs
+                .iter()
+                .map(|(name, level)| {
+                    let name = if name == "default" {
+                        GraphName::DefaultGraph
+                    } else {
+                        NamedNode::new(name)
+                            .with_context(|| format!("Invalid graph name {name}"))?
+                            .into()
+                    };
+                    Ok((name, *level))
+                })
+                .collect::<anyhow::Result<_>>()?,
+        })
+    }
+
+    fn graph_access(&self, graph: GraphNameRef<'_>) -> AccessLevel {
+        self.graphs
+            .get(&graph.into_owned())
+            .copied()
+            .unwrap_or(self.default)
+    }
+
+    /// The access level granted to all the graphs of the dataset
+    fn dataset_access(&self) -> AccessLevel {
+        self.graphs
+            .values()
+            .copied()
+            .fold(self.default, AccessLevel::min)
+    }
+}
+
+fn describe_target(graph: Option<GraphNameRef<'_>>) -> String {
+    match graph {
+        Some(GraphNameRef::DefaultGraph) => "the default graph".into(),
+        Some(graph) => format!("the graph {graph}"),
+        None => "the full dataset".into(),
+    }
+}
+
+fn unauthorized(message: impl Into<String>) -> HttpError {
+    (Status::UNAUTHORIZED, message.into())
+}
+
+/// Hashes a password with Argon2id and a random salt into a PHC string usable as `password-hash`.
+pub fn hash_password(password: &str) -> anyhow::Result<String> {
+    let salt = SaltString::encode_b64(&random::<[u8; 16]>())
+        .map_err(|e| anyhow!("Not able to encode the salt: {e}"))?;
+    Ok(Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|e| anyhow!("Not able to hash the password: {e}"))?
+        .to_string())
+}
+
+fn parse_password_hash(hash: &str) -> anyhow::Result<PasswordHashString> {
+    let hash = PasswordHashString::new(hash)
+        .map_err(|e| anyhow!("A password hash must be a PHC string: {e}"))?;
+    Algorithm::try_from(hash.algorithm())
+        .map_err(|_| anyhow!("A password hash must use an Argon2 algorithm"))?;
+    Ok(hash)
+}
+
+fn parse_sha256(hex: &str) -> anyhow::Result<[u8; 32]> {
+    let mut hash = [0; 32];
+    if hex.len() != 64 {
+        bail!("A SHA-256 hash must be written with 64 hexadecimal digits");
+    }
+    for (byte, digits) in hash.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(str::from_utf8(digits)?, 16)
+            .context("A SHA-256 hash must be written with 64 hexadecimal digits")?;
+    }
+    Ok(hash)
+}
+
+/// Decodes the canonical base64 encoding of an UTF-8 string
+///
+/// The padding is required and the unused bits must be zeros.
+fn decode_base64(input: &str) -> Option<String> {
+    if input.contains(' ') {
+        // Allowed by xsd:base64Binary but not by the HTTP token68 syntax
+        return None;
+    }
+    String::from_utf8(Base64Binary::from_str(input).ok()?.into()).ok()
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_base64() {
+        assert_eq!(
+            decode_base64("YWxpY2U6c2VjcmV0").as_deref(),
+            Some("alice:secret")
+        );
+        assert_eq!(decode_base64("YQ==").as_deref(), Some("a"));
+        assert_eq!(decode_base64("YWI=").as_deref(), Some("ab"));
+        assert_eq!(decode_base64("").as_deref(), Some(""));
+        assert_eq!(decode_base64("Y"), None);
+        assert_eq!(decode_base64("Y!=="), None);
+        // Missing or non canonical padding
+        assert_eq!(decode_base64("YQ"), None);
+        assert_eq!(decode_base64("YQ="), None);
+        assert_eq!(decode_base64("YQ==="), None);
+        assert_eq!(decode_base64("YQ==YQ=="), None);
+        // Non zero unused bits
+        assert_eq!(decode_base64("YR=="), None);
+        assert_eq!(decode_base64("YWJ="), None);
+        // Spaces and invalid UTF-8
+        assert_eq!(decode_base64("YW Jj"), None);
+        assert_eq!(decode_base64("/w=="), None);
+        assert_eq!(
+            decode_base64(&Base64Binary::from("alice:\u{e9}t\u{e9}".as_bytes()).to_string())
+                .as_deref(),
+            Some("alice:\u{e9}t\u{e9}")
+        );
+    }
+
+    #[test]
+    fn test_hash_password() -> anyhow::Result<()> {
+        let hash = hash_password("secret")?;
+        assert_ne!(hash, hash_password("secret")?, "The salt must be random");
+        let hash = parse_password_hash(&hash)?;
+        Argon2::default()
+            .verify_password(b"secret", &hash.password_hash())
+            .map_err(|e| anyhow!("{e}"))?;
+        Argon2::default()
+            .verify_password(b"wrong", &hash.password_hash())
+            .unwrap_err();
+        parse_password_hash("2bb80d537b1da3e38bd30361aa855686bde0eacd7162fef6a25fe97bf527a25b")
+            .unwrap_err();
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{internal_server_error, HttpError};
+use oxhttp::model::Status;
+use oxigraph::store::{StorageError, Store};
+use std::fs::{create_dir_all, read_dir, remove_dir_all, rename};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{sleep, Builder};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Backups of the server store written in a directory, triggered with `/backups` or periodically.
+///
+/// Each backup is a subdirectory named after its creation time in milliseconds since the Unix epoch.
+/// The backups are incremental: the database files already in the latest backup are hard linked from it instead of being copied.
+/// A single backup is created at a time.
+#[derive(Clone)]
+pub struct BackupManager {
+    location: PathBuf,
+    running: Arc<AtomicBool>,
+}
+
+impl BackupManager {
+    pub fn new(location: &Path) -> io::Result<Self> {
+        create_dir_all(location)?;
+        Ok(Self {
+            location: location.into(),
+            running: Arc::default(),
+        })
+    }
+
+    /// Starts the creation of a backup of the store in a new thread and returns its name.
+    ///
+    /// Fails if a backup is already being created.
+    pub fn start(&self, store: Store) -> Result<String, HttpError> {
+        let running = self
+            .begin()
+            .ok_or_else(|| (Status::CONFLICT, "A backup is already being created".into()))?;
+        let name = self.next_name().map_err(internal_server_error)?;
+        let manager = self.clone();
+        let backup_name = name.clone();
+        Builder::new()
+            .name("backup".into())
+            .spawn(move || {
+                manager.create(&store, &backup_name);
+                drop(running);
+            })
+            .map_err(internal_server_error)?;
+        Ok(name)
+    }
+
+    /// Starts a thread creating a backup of the store every `interval`
+    #[allow(clippy::infinite_loop)]
+    pub fn schedule(&self, store: Store, interval: Duration) -> io::Result<()> {
+        let manager = self.clone();
+        Builder::new()
+            .name("backup-schedule".into())
+            .spawn(move || loop {
+                sleep(interval);
+                // We skip the backup if one triggered by a request is running
+                let Some(_running) = manager.begin() else {
+                    continue;
+                };
+                match manager.next_name() {
+                    Ok(name) => manager.create(&store, &name),
+                    Err(e) => eprintln!("Failed to create a backup: {e}"),
+                }
+            })?;
+        Ok(())
+    }
+
+    /// The names of the complete backups, from the oldest to the newest
+    pub fn list(&self) -> io::Result<Vec<String>> {
+        let mut names = Vec::new();
+        for entry in read_dir(&self.location)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            // The backups being created have a .partial suffix
+            if let Some(name) = entry.file_name().to_str() {
+                if !name.is_empty() && name.bytes().all(|b| b.is_ascii_digit()) {
+                    names.push(name.to_owned());
+                }
+            }
+        }
+        names.sort_unstable();
+        Ok(names)
+    }
+
+    fn begin(&self) -> Option<RunningBackup> {
+        (!self.running.swap(true, Ordering::AcqRel))
+            .then(|| RunningBackup(Arc::clone(&self.running)))
+    }
+
+    /// The current time in milliseconds, after the latest backup
+    fn next_name(&self) -> io::Result<String> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(io::Error::other)?
+            .as_millis();
+        let latest = self
+            .list()?
+            .last()
+            .and_then(|name| name.parse::<u128>().ok())
+            .map_or(0, |latest| latest + 1);
+        Ok(format!("{:020}", now.max(latest)))
+    }
+
+    /// Creates the backup and logs the outcome
+    fn create(&self, store: &Store, name: &str) {
+        let start = Instant::now();
+        match self.try_create(store, name) {
+            Ok(()) => eprintln!(
+                "Backup {name} created in {:.3} seconds",
+                start.elapsed().as_secs_f64()
+            ),
+            Err(e) => eprintln!("Failed to create the backup {name}: {e}"),
+        }
+    }
+
+    fn try_create(&self, store: &Store, name: &str) -> Result<(), StorageError> {
+        let previous = self.list()?.pop();
+        let partial = self.location.join(format!("{name}.partial"));
+        let result = if let Some(previous) = previous {
+            store.incremental_backup(&partial, self.location.join(previous))
+        } else {
+            store.backup(&partial)
+        };
+        if let Err(e) = result {
+            if partial.exists() {
+                remove_dir_all(&partial)?;
+            }
+            return Err(e);
+        }
+        rename(partial, self.location.join(name))?;
+        Ok(())
+    }
+}
+
+/// Marks a backup as being created until dropped
+struct RunningBackup(Arc<AtomicBool>);
+
+impl Drop for RunningBackup {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
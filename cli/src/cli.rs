@@ -18,17 +18,8 @@ pub enum Command {
         /// If not present, an in-memory storage will be used.
         #[arg(short, long, value_hint = ValueHint::DirPath)]
         location: Option<PathBuf>,
-        /// Host and port to listen to
-        #[arg(short, long, default_value = "localhost:7878", value_hint = ValueHint::Hostname)]
-        bind: String,
-        /// Allows cross-origin requests
-        #[arg(long)]
-        cors: bool,
-        /// If the SPARQL queries should look for triples in all the dataset graphs by default (ie. without `GRAPH` operations)
-        ///
-        /// This is equivalent as setting the union-default-graph option in all SPARQL queries
-        #[arg(long)]
-        union_default_graph: bool,
+        #[command(flatten)]
+        server: ServerArgs,
     },
     /// Start Oxigraph HTTP server in read-only mode
     ///
@@ -36,19 +27,37 @@ pub enum Command {
     /// Opening as read-only while having an other process writing the database is undefined behavior.
     ServeReadOnly {
         /// Directory in which Oxigraph data are persisted
+        ///
+        /// Must be set here or in the configuration file.
         #[arg(short, long, value_hint = ValueHint::DirPath)]
-        location: PathBuf,
-        /// Host and port to listen to
-        #[arg(short, long, default_value = "localhost:7878")]
-        bind: String,
-        /// Allow cross-origin requests
-        #[arg(long)]
-        cors: bool,
-        /// If the SPARQL queries should look for triples in all the dataset graphs by default (ie. without `GRAPH` operations)
+        location: Option<PathBuf>,
+        #[command(flatten)]
+        server: ServerArgs,
+    },
+    /// Start Oxigraph HTTP server in read-only mode on a replica of a primary server store
+    ///
+    /// The primary server must have its history enabled.
+    /// The replica store content is first replaced with a snapshot of the primary store, except if the replica store
+    /// has been created with the backup command from the primary store after its history has been enabled.
+    /// The transactions committed on the primary are then fetched from its `/history` endpoint and applied to the replica.
+    ServeReplica {
+        /// Directory in which the replica data are persisted
+        ///
+        /// Must be set here or in the configuration file.
+        #[arg(short, long, value_hint = ValueHint::DirPath)]
+        location: Option<PathBuf>,
+        /// Base URL of the primary server
         ///
-        /// This is equivalent as setting the union-default-graph option in all SPARQL queries
-        #[arg(long)]
-        union_default_graph: bool,
+        /// Must be set here or in the configuration file.
+        #[arg(long, value_hint = ValueHint::Url)]
+        primary: Option<String>,
+        /// Duration in seconds between two requests to the primary server history
+        ///
+        /// The default is 1 second.
+        #[arg(long, value_name = "SECONDS")]
+        replication_interval: Option<f64>,
+        #[command(flatten)]
+        server: ServerArgs,
     },
     /// Create a database backup into a target directory
     ///
@@ -68,6 +77,15 @@ pub enum Command {
         /// Directory in which the backup will be written
         #[arg(short, long, value_hint = ValueHint::DirPath)]
         destination: PathBuf,
+        /// Only copies the database files that are not already in the previous backup given with --since
+        ///
+        /// The other files are hard linked from the previous backup that must be on the same file system as the destination.
+        /// The new backup is still a full database independent of the previous one.
+        #[arg(long, requires = "since")]
+        incremental: bool,
+        /// Directory of a previous backup of the same database
+        #[arg(long, requires = "incremental", value_hint = ValueHint::DirPath)]
+        since: Option<PathBuf>,
     },
     /// Load file(s) into the store
     ///
@@ -105,8 +123,43 @@ pub enum Command {
         /// Only available when loading a graph file (N-Triples, Turtle...) and not a dataset file (N-Quads, TriG...).
         #[arg(long, value_hint = ValueHint::Url)]
         graph: Option<String>,
+        /// Renames a named graph of the loaded files
+        ///
+        /// The value is "FROM=TO" where FROM and TO are graph IRIs.
+        /// TO can be "default" to load the named graph content into the default graph.
+        /// Can be set multiple times.
+        #[arg(long, value_name = "FROM=TO")]
+        rename_graph: Vec<String>,
+        /// Replaces the skolem IRIs minted under this authority by blank nodes
+        ///
+        /// For example, with "http://example.com" the IRI <http://example.com/.well-known/genid/foo> is loaded as the blank node _:foo.
+        /// It is the reverse of the dump --skolemize option.
+        #[arg(long, value_name = "AUTHORITY", value_hint = ValueHint::Url)]
+        deskolemize: Option<String>,
+        /// Look for each loaded quad in the store to report the number of new quads and of duplicates
+        ///
+        /// By default, only the number of loaded quads is reported.
+        /// This option slows down the loading.
+        #[arg(long)]
+        detect_duplicates: bool,
+        /// Number of threads parsing each N-Triples or N-Quads file
+        ///
+        /// The file is split into chunks of lines parsed in parallel, which speeds up the loading of big files.
+        /// The line numbers in the parse errors are then relative to the start of the chunks.
+        #[arg(long, value_name = "COUNT", default_value_t = 1)]
+        parsing_threads: usize,
+        /// What to do when a file fails to load
+        ///
+        /// The command exits with a non-zero code if any file failed to load whatever the value of this option.
+        #[arg(long, value_enum, default_value_t = OnLoadError::Abort)]
+        on_error: OnLoadError,
+        /// File to write the loading errors to, as a JSON array of objects with the file, line, column and message keys
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        error_report: Option<PathBuf>,
     },
     /// Dump the store content into a file
+    ///
+    /// If the format supports it, prefixes are declared for the well-known vocabularies and the frequently used namespaces.
     Dump {
         /// Directory in which Oxigraph data are persisted
         #[arg(short, long, value_hint = ValueHint::DirPath)]
@@ -129,8 +182,38 @@ pub enum Command {
         ///
         /// By default all graphs are dumped if the output format supports datasets.
         /// If the format does not support named graph, then this parameter must be set.
+        ///
+        /// Can be set multiple times to dump a selection of graphs with their names, the output format must then support datasets.
         #[arg(long, value_hint = ValueHint::Url)]
-        graph: Option<String>,
+        graph: Vec<String>,
+        /// Regular expression selecting the named graphs to dump by their IRI
+        ///
+        /// For example, "^http://example.com/tenant1/" selects all the graphs whose IRI starts with "http://example.com/tenant1/".
+        /// The graphs are dumped with their names so the output format must support datasets.
+        /// It can be combined with --graph to also dump some other graphs.
+        #[arg(long, value_name = "REGEX")]
+        graph_pattern: Option<String>,
+        /// Replaces the blank nodes by skolem IRIs minted under this authority
+        ///
+        /// For example, with "http://example.com" the blank node _:foo is dumped as <http://example.com/.well-known/genid/foo>.
+        /// The blank node identifiers are stable in the store so the same IRIs are used by all the dumps.
+        #[arg(long, value_name = "AUTHORITY", value_hint = ValueHint::Url)]
+        skolemize: Option<String>,
+        /// Language range of the language-tagged strings to dump like "en" or "fr-CA"
+        ///
+        /// Can be set multiple times. If set, the triples whose object is a language-tagged string not matching any of the ranges are not dumped.
+        /// The range "en" matches the language tags "en" and "en-GB".
+        #[arg(long, value_name = "RANGE")]
+        language: Vec<String>,
+    },
+    /// Print the number of language-tagged strings used in the store per language tag
+    ///
+    /// Each line of the output contains, separated by tabulations, a language tag, the number of triples whose object has this language tag
+    /// and the number of distinct such objects.
+    Languages {
+        /// Directory in which Oxigraph data are persisted
+        #[arg(short, long, value_hint = ValueHint::DirPath)]
+        location: PathBuf,
     },
     /// Execute a SPARQL query against the store
     Query {
@@ -179,10 +262,28 @@ pub enum Command {
         /// Beware, computing the statistics adds some overhead to the evaluation runtime.
         #[arg(long)]
         stats: bool,
+        /// Write to the given file a trace of the query evaluation that can be replayed with the replay command
+        ///
+        /// The trace contains the query, its evaluation plan with statistics and a fingerprint of the store content but not the store content itself.
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        trace_file: Option<PathBuf>,
         /// If the SPARQL queries should look for triples in all the dataset graphs by default (ie. without `GRAPH` operations)
         #[arg(long)]
         union_default_graph: bool,
     },
+    /// Replay a query evaluation trace written by the query command against a store
+    ///
+    /// The differences between the traced and replayed evaluation plans and statistics are printed to stdout
+    /// and the command fails if there are some.
+    /// The results of the RAND, NOW, UUID, STRUUID and BNODE functions are not replayed.
+    Replay {
+        /// Directory in which Oxigraph data are persisted
+        #[arg(short, long, value_hint = ValueHint::DirPath)]
+        location: PathBuf,
+        /// File in which the trace is stored
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        trace_file: PathBuf,
+    },
     /// Execute a SPARQL update against the store
     Update {
         /// Directory in which Oxigraph data are persisted
@@ -202,6 +303,76 @@ pub enum Command {
         #[arg(long, value_hint = ValueHint::Url)]
         update_base: Option<String>,
     },
+    /// Execute a SPARQL query against two stores or SPARQL endpoints and print the differences between their results
+    ///
+    /// Blank nodes are matched up to a renaming and the solutions order is ignored.
+    /// The command fails if the results are different.
+    DiffResults {
+        /// Left side of the comparison: directory in which Oxigraph data are persisted or URL of a SPARQL endpoint
+        #[arg(long, value_hint = ValueHint::AnyPath)]
+        left: String,
+        /// Right side of the comparison: directory in which Oxigraph data are persisted or URL of a SPARQL endpoint
+        #[arg(long, value_hint = ValueHint::AnyPath)]
+        right: String,
+        /// The SPARQL query to execute
+        ///
+        /// If no query or query file are given, stdin is used.
+        #[arg(short, long, conflicts_with = "query_file")]
+        query: Option<String>,
+        /// File in which the query is stored
+        ///
+        /// If no query or query file are given, stdin is used.
+        #[arg(long, conflicts_with = "query", value_hint = ValueHint::FilePath)]
+        query_file: Option<PathBuf>,
+        /// Base IRI of the query
+        #[arg(long, value_hint = ValueHint::Url)]
+        query_base: Option<String>,
+    },
+    /// Check a SPARQL query for likely mistakes without evaluating it
+    ///
+    /// The warnings are printed to stdout and the command fails if there are some.
+    Lint {
+        /// The SPARQL query to check
+        ///
+        /// If no query or query file are given, stdin is used.
+        #[arg(short, long, conflicts_with = "query_file")]
+        query: Option<String>,
+        /// File in which the query is stored
+        ///
+        /// If no query or query file are given, stdin is used.
+        #[arg(long, conflicts_with = "query", value_hint = ValueHint::FilePath)]
+        query_file: Option<PathBuf>,
+        /// Base IRI of the query
+        #[arg(long, value_hint = ValueHint::Url)]
+        query_base: Option<String>,
+    },
+    /// Hash a password read from stdin to use it as the `password-hash` of a user in the configuration file
+    ///
+    /// The password is hashed with Argon2id and a random salt.
+    /// A single trailing newline is removed from the input.
+    HashPassword,
+    /// Check that the IRIs used as objects of some predicates are described in the store
+    ///
+    /// An IRI is described if it is the subject of at least one triple.
+    /// The quads with a dangling reference are written to stdout in N-Quads and the command fails if there are some.
+    CheckReferences {
+        /// Directory in which Oxigraph data are persisted
+        #[arg(short, long, value_hint = ValueHint::DirPath)]
+        location: PathBuf,
+        /// Predicate whose objects must be described
+        ///
+        /// Can be set multiple times.
+        #[arg(long, required = true, value_name = "IRI", value_hint = ValueHint::Url)]
+        predicate: Vec<String>,
+        /// Named graph in which the descriptions are looked for
+        ///
+        /// Can be set multiple times. By default, the descriptions are looked for in all the graphs.
+        #[arg(long, value_name = "IRI", value_hint = ValueHint::Url)]
+        description_graph: Vec<String>,
+        /// Look for the descriptions in the default graph
+        #[arg(long)]
+        description_default_graph: bool,
+    },
     /// Optimize the database storage
     ///
     /// Done by default in the background when serving requests.
@@ -211,6 +382,108 @@ pub enum Command {
         #[arg(short, long, value_hint = ValueHint::DirPath)]
         location: PathBuf,
     },
+    /// Print the disk usage of the database
+    ///
+    /// Prints the size of the database directory, the approximate number of quads
+    /// and, for each index, its approximate number of entries and the size of its files.
+    /// The values are estimations done by the storage engine.
+    Stats {
+        /// Directory in which Oxigraph data are persisted
+        #[arg(short, long, value_hint = ValueHint::DirPath)]
+        location: PathBuf,
+        /// Print the statistics as a JSON object instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Canonicalize a RDF file with the RDFC-1.0 algorithm and write it as canonical N-Quads
+    ///
+    /// The blank nodes are renamed to _:c14n0, _:c14n1... and the quads are sorted.
+    /// Isomorphic inputs give the same output so its SHA-256 digest can be used to identify the dataset content.
+    Canonicalize {
+        /// File to canonicalize
+        ///
+        /// If no file is given, stdin is read.
+        #[arg(short, long, value_hint = ValueHint::FilePath)]
+        file: Option<PathBuf>,
+        /// The format of the file to canonicalize
+        ///
+        /// It can be an extension like "nt" or a MIME type like "application/n-triples".
+        ///
+        /// By default the format is guessed from the input file extension.
+        #[arg(long, required_unless_present = "file")]
+        format: Option<String>,
+        /// Base IRI of the file to read
+        #[arg(long, value_hint = ValueHint::Url)]
+        base: Option<String>,
+        /// File to write the canonical N-Quads to
+        ///
+        /// If no file is given, stdout is written.
+        #[arg(short, long, value_hint = ValueHint::FilePath)]
+        output: Option<PathBuf>,
+        /// File to write the issued blank node identifiers to
+        ///
+        /// It is a JSON object mapping the blank node identifiers of the input to their canonical identifiers.
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        issued_identifiers: Option<PathBuf>,
+        /// Limits the work done on the blank nodes to protect against poison datasets
+        ///
+        /// With m the number of blank nodes without a unique first degree hash,
+        /// each blank node can be the reference of at most m^FACTOR calls to the Hash N-Degree Quads algorithm.
+        #[arg(
+            long,
+            value_name = "FACTOR",
+            default_value_t = 1,
+            conflicts_with = "no_work_limit"
+        )]
+        max_work_factor: u32,
+        /// Do not limit the work done by the canonicalization
+        ///
+        /// The canonicalization of a poison dataset might then never end.
+        #[arg(long)]
+        no_work_limit: bool,
+    },
+    /// Compute the differences between two RDF files and write them as a RDF Patch
+    ///
+    /// The patch removes the quads only in the first file and adds the quads only in the second one.
+    /// The blank nodes of the two files are matched when the parts of the datasets using them are isomorphic.
+    /// The patch can be applied to a store with the apply-patch command.
+    Diff {
+        /// File of the original dataset
+        #[arg(value_hint = ValueHint::FilePath)]
+        from_file: PathBuf,
+        /// File of the new dataset
+        #[arg(value_hint = ValueHint::FilePath)]
+        to_file: PathBuf,
+        /// The format of the two files
+        ///
+        /// It can be an extension like "nt" or a MIME type like "application/n-triples".
+        ///
+        /// By default the format is guessed from the files extensions.
+        #[arg(long)]
+        format: Option<String>,
+        /// Base IRI of the files to read
+        #[arg(long, value_hint = ValueHint::Url)]
+        base: Option<String>,
+        /// File to write the RDF Patch to
+        ///
+        /// If no file is given, stdout is written.
+        #[arg(short, long, value_hint = ValueHint::FilePath)]
+        output: Option<PathBuf>,
+    },
+    /// Apply a RDF Patch to the store
+    ///
+    /// All the operations of the patch are applied in a single transaction.
+    /// The operations of the aborted transactions of the patch are ignored.
+    ApplyPatch {
+        /// Directory in which Oxigraph data are persisted
+        #[arg(short, long, value_hint = ValueHint::DirPath)]
+        location: PathBuf,
+        /// File in which the RDF Patch is stored
+        ///
+        /// If no file is given, stdin is read.
+        #[arg(short, long, value_hint = ValueHint::FilePath)]
+        file: Option<PathBuf>,
+    },
     /// Convert a RDF serialization from one format to an other
     Convert {
         /// File to convert from
@@ -221,13 +494,23 @@ pub enum Command {
         /// The format of the file(s) to convert from
         ///
         /// It can be an extension like "nt" or a MIME type like "application/n-triples".
+        /// "csvw" converts a CSV or TSV file following the CSV on the Web recommendation.
         ///
         /// By default the format is guessed from the input file extension.
         #[arg(long, required_unless_present = "from_file")]
         from_format: Option<String>,
         /// Base IRI of the file to read
+        ///
+        /// With the "csvw" format, it is the IRI of the metadata against which the table url is resolved,
+        /// or the IRI of the table if there is no metadata.
         #[arg(long, value_hint = ValueHint::Url)]
         from_base: Option<String>,
+        /// CSVW metadata JSON file describing the CSV or TSV file to convert from
+        ///
+        /// By default the "<from_file>-metadata.json" file is used if it exists.
+        /// Otherwise, the columns are named after the header row titles.
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        csvw_metadata: Option<PathBuf>,
         /// File to convert to
         ///
         /// If no file is given, stdout is written.
@@ -261,3 +544,131 @@ pub enum Command {
         to_graph: Option<String>,
     },
 }
+
+/// Options shared by the `serve`, `serve-read-only` and `serve-replica` commands
+#[derive(clap::Args)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct ServerArgs {
+    /// Host and port to listen to [default: localhost:7878]
+    #[arg(short, long, value_hint = ValueHint::Hostname)]
+    pub bind: Option<String>,
+    /// Allows cross-origin requests
+    #[arg(long)]
+    pub cors: bool,
+    /// If the SPARQL queries should look for triples in all the dataset graphs by default (ie. without `GRAPH` operations)
+    ///
+    /// This is equivalent as setting the union-default-graph option in all SPARQL queries
+    #[arg(long)]
+    pub union_default_graph: bool,
+    /// TOML configuration file
+    ///
+    /// The options given on the command line take precedence over the ones set in the file.
+    #[arg(long, value_hint = ValueHint::FilePath)]
+    pub config: Option<PathBuf>,
+    /// Records the changes done by each transaction in the store history
+    ///
+    /// The history is persisted with the store and served by the `/history` endpoint used by the replicas.
+    /// It is only taken into account by the `serve` command.
+    #[arg(long)]
+    pub history: bool,
+    /// Directory in which the datasets managed with the `/datasets` API are persisted
+    ///
+    /// Each dataset is stored in a subdirectory named after it.
+    /// If not set, the datasets are only available if the server store is in memory and are kept in memory too.
+    /// It is not used by the `serve-replica` command.
+    #[arg(long, value_hint = ValueHint::DirPath)]
+    pub datasets_location: Option<PathBuf>,
+    /// Directory in which the backups of the store are written
+    ///
+    /// The backups are triggered with a `POST` request to `/backups` or every --backup-interval.
+    /// Each backup reuses the unchanged database files of the previous one.
+    #[arg(long, value_hint = ValueHint::DirPath)]
+    pub backup_location: Option<PathBuf>,
+    /// Duration in seconds between two automatic backups written in --backup-location
+    #[arg(long, value_name = "SECONDS", requires = "backup_location")]
+    pub backup_interval: Option<f64>,
+    /// Only validate the configuration and exit without starting the server
+    #[arg(long)]
+    pub check_config: bool,
+    /// Logs each SPARQL request on stderr with its duration and its number of results
+    #[arg(long)]
+    pub log_requests: bool,
+    /// Adds the normalized query text to the request log
+    #[arg(long)]
+    pub log_query_text: bool,
+    /// Duration in seconds after which a query is considered slow
+    ///
+    /// The slow queries are logged on stderr with the JSON explanation of their evaluation plan.
+    #[arg(long, value_name = "SECONDS")]
+    pub slow_query_threshold: Option<f64>,
+    /// Duration in seconds after which the evaluation of a SPARQL query or update is cancelled
+    #[arg(long, value_name = "SECONDS")]
+    pub query_timeout: Option<f64>,
+    /// Maximal number of solutions or triples returned by a SPARQL query
+    #[arg(long)]
+    pub max_results: Option<u64>,
+    /// Resident memory in MiB of the server process above which the running SPARQL evaluations are cancelled
+    ///
+    /// Only supported on Linux.
+    #[arg(long, value_name = "MIB")]
+    pub max_memory: Option<u64>,
+    /// Memory in MiB each SPARQL query can use to sort, group and join solutions
+    ///
+    /// Sorts, groupings and deduplications above it are done using temporary files, joins above it fail.
+    #[arg(long, value_name = "MIB")]
+    pub query_memory_budget: Option<u64>,
+    /// Duration in seconds after which a query cursor that has not been used is closed
+    ///
+    /// Cursors are opened by the queries with a `page-size` parameter. The default is 60 seconds.
+    #[arg(long, value_name = "SECONDS")]
+    pub cursor_ttl: Option<f64>,
+    /// Duration in seconds after which a transaction that has not been used is aborted
+    ///
+    /// Transactions are opened with the `/transactions` endpoint. The default is 60 seconds.
+    #[arg(long, value_name = "SECONDS")]
+    pub transaction_timeout: Option<f64>,
+    /// Allows the SPARQL queries to call remote SPARQL endpoints with `SERVICE`
+    #[arg(long)]
+    pub enable_federation: bool,
+    /// IRI prefix of the services the SPARQL queries are allowed to call
+    ///
+    /// Can be set multiple times. By default, all services are allowed if federation is enabled.
+    #[arg(long, value_name = "IRI", value_hint = ValueHint::Url)]
+    pub federation_allowed_service: Vec<String>,
+    /// Duration in seconds after which a request to a remote service is cancelled
+    #[arg(long, value_name = "SECONDS")]
+    pub federation_timeout: Option<f64>,
+    /// Predicate whose literal values are added to the full-text search index
+    ///
+    /// Can be set multiple times. The index is kept in memory and built when the server starts.
+    #[arg(long, value_name = "IRI", value_hint = ValueHint::Url)]
+    pub text_index_predicate: Vec<String>,
+    /// File of SPARQL function definitions usable in all queries and updates
+    ///
+    /// The functions are defined with `DEFINE FUNCTION ex:name(?param) { expression }` declarations
+    /// that might be mixed with `PREFIX` and `BASE` declarations.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub function_definitions: Option<PathBuf>,
+    /// Builds an in-memory R-tree of the `geo:asWKT`, `geo:asGeoJSON` and `geo:asGML` geometries
+    ///
+    /// It is used to evaluate the GeoSPARQL filters comparing a geometry with a constant like `FILTER(geof:sfIntersects(?g, "POINT(1 2)"^^geo:wktLiteral))`.
+    /// The index is built when the server starts.
+    #[arg(long)]
+    pub spatial_index: bool,
+    /// Reads the store indexes when the server starts to load them in the caches
+    ///
+    /// It avoids slow first queries after a restart but delays the server start.
+    #[arg(long)]
+    pub warm_up: bool,
+}
+
+/// What the `load` command does when a file fails to load
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum OnLoadError {
+    /// Stop loading all the files
+    Abort,
+    /// Skip the failing file and keep loading the other ones
+    Skip,
+    /// Log and skip the invalid statements and keep loading the rest of the file
+    Log,
+}
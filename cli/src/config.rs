@@ -0,0 +1,416 @@
+use anyhow::Context;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_BIND: &str = "localhost:7878";
+
+/// Configuration of the HTTP server, usually read from an `oxigraph.toml` file.
+///
+/// Example:
+/// ```toml
+/// [storage]
+/// location = "data"
+/// history = true
+/// datasets-location = "datasets"
+/// backup-location = "/mnt/backups/oxigraph"
+/// backup-interval = 86400.0
+///
+/// [server]
+/// bind = "0.0.0.0:7878"
+/// cors = true
+/// union-default-graph = false
+/// log-requests = true
+/// slow-query-threshold = 1.5
+/// query-timeout = 60.0
+/// max-results = 100000
+/// enable-federation = true
+/// federation-allowed-services = ["https://query.wikidata.org/sparql"]
+/// federation-timeout = 10.0
+/// text-index-predicates = ["http://www.w3.org/2000/01/rdf-schema#label"]
+/// function-definitions = "functions.rq"
+/// spatial-index = true
+/// warm-up = true
+///
+/// [replication]
+/// primary = "http://primary.example.com:7878"
+/// interval = 5.0
+/// max-lag = 60.0
+/// username = "replica"
+/// password = "secret"
+///
+/// [queries.labels]
+/// query = "SELECT ?label WHERE { ?s <http://www.w3.org/2000/01/rdf-schema#label> ?label }"
+/// parameters = ["s"]
+/// public = true
+///
+/// [auth]
+/// anonymous-access = "read"
+///
+/// [[auth.users]]
+/// name = "admin"
+/// password-hash = "$argon2id$v=19$m=19456,t=2,p=1$2goZj82xoFpdGOiKXDs1Zw$xcO4KqxkLbZ3lnQt8R1y60k/QobVOG+BV5U/t32A0Ms"
+/// access = "write"
+/// ```
+#[derive(Deserialize, Default, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    pub storage: StorageConfig,
+    pub server: ServerConfig,
+    /// Replication of a primary server, only used by the `serve-replica` command
+    pub replication: ReplicationConfig,
+    /// Authentication and authorization rules, if not set everyone has write access
+    pub auth: Option<AuthConfig>,
+    /// Parameterized queries evaluated with `GET /queries/{name}`
+    pub queries: HashMap<String, StoredQueryConfig>,
+}
+
+#[derive(Deserialize, Default, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct StorageConfig {
+    /// Directory in which the data are persisted
+    ///
+    /// Relative paths are resolved against the directory of the configuration file.
+    pub location: Option<PathBuf>,
+    /// Records the changes done by each transaction in the store history
+    pub history: bool,
+    /// Directory in which the datasets managed with the `/datasets` API are persisted
+    ///
+    /// Relative paths are resolved against the directory of the configuration file.
+    pub datasets_location: Option<PathBuf>,
+    /// Directory in which the backups of the store are written
+    ///
+    /// Relative paths are resolved against the directory of the configuration file.
+    pub backup_location: Option<PathBuf>,
+    /// Duration in seconds between two automatic backups
+    pub backup_interval: Option<f64>,
+}
+
+#[derive(Deserialize, Default, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+#[allow(clippy::struct_excessive_bools)]
+pub struct ServerConfig {
+    /// Host and port to listen to
+    pub bind: Option<String>,
+    /// Allows cross-origin requests
+    pub cors: bool,
+    /// If the SPARQL queries should look for triples in all the dataset graphs by default
+    pub union_default_graph: bool,
+    /// Logs each SPARQL request on stderr
+    pub log_requests: bool,
+    /// Adds the normalized query text to the request log
+    pub log_query_text: bool,
+    /// Duration in seconds after which a query is logged with its explanation
+    pub slow_query_threshold: Option<f64>,
+    /// Duration in seconds after which a query or an update evaluation is cancelled
+    pub query_timeout: Option<f64>,
+    /// Maximal number of results returned by a query
+    pub max_results: Option<u64>,
+    /// Resident memory in MiB of the server process above which the running evaluations are cancelled
+    pub max_memory: Option<u64>,
+    /// Memory in MiB each query can use to sort, group and join solutions before using temporary files or failing
+    pub query_memory_budget: Option<u64>,
+    /// Duration in seconds after which a query cursor that has not been used is closed
+    pub cursor_ttl: Option<f64>,
+    /// Duration in seconds after which a transaction that has not been used is aborted
+    pub transaction_timeout: Option<f64>,
+    /// Allows the queries to call remote SPARQL endpoints with `SERVICE`
+    pub enable_federation: bool,
+    /// IRI prefixes of the services that can be called, all services are allowed if empty
+    pub federation_allowed_services: Vec<String>,
+    /// Duration in seconds after which a request to a remote service is cancelled
+    pub federation_timeout: Option<f64>,
+    /// Predicates whose literal values are added to the full-text search index
+    pub text_index_predicates: Vec<String>,
+    /// File of SPARQL `DEFINE FUNCTION` declarations usable in all queries and updates
+    pub function_definitions: Option<PathBuf>,
+    /// Builds an in-memory R-tree of the geometries used to evaluate the GeoSPARQL filters
+    pub spatial_index: bool,
+    /// Reads the store indexes when the server starts to load them in the caches
+    pub warm_up: bool,
+}
+
+#[derive(Deserialize, Default, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ReplicationConfig {
+    /// Base URL of the primary server whose history is replicated
+    pub primary: Option<String>,
+    /// Duration in seconds between two requests to the primary server history
+    pub interval: Option<f64>,
+    /// Maximal duration in seconds since the replica has last caught up with the primary for `/readyz` to answer `200 OK`
+    pub max_lag: Option<f64>,
+    /// User name sent to the primary server with HTTP basic authentication, requires `password`
+    pub username: Option<String>,
+    /// Password of `username`
+    pub password: Option<String>,
+    /// Token sent to the primary server with HTTP bearer authentication, exclusive with `username`
+    pub token: Option<String>,
+}
+
+#[derive(Deserialize, Default, Debug, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct StoredQueryConfig {
+    /// The SPARQL query, exclusive with `file`
+    pub query: Option<String>,
+    /// File containing the SPARQL query, exclusive with `query`
+    ///
+    /// Relative paths are resolved against the directory of the configuration file.
+    pub file: Option<PathBuf>,
+    /// Variables of the query that could be substituted using the request parameters of the same name
+    pub parameters: Vec<String>,
+    /// Allows to evaluate the query without the read access to the dataset
+    pub public: bool,
+}
+
+#[derive(Deserialize, Default, Debug, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct AuthConfig {
+    /// Access granted to the requests without credentials
+    pub anonymous_access: AccessLevel,
+    /// Users authenticated with HTTP basic authentication
+    pub users: Vec<UserConfig>,
+    /// Tokens used with HTTP bearer authentication
+    pub tokens: Vec<TokenConfig>,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct UserConfig {
+    pub name: String,
+    /// Argon2 hash of the user password in the PHC string format, as written by the `hash-password` command
+    pub password_hash: String,
+    /// Access granted to the graphs not listed in `graphs`
+    #[serde(default)]
+    pub access: AccessLevel,
+    /// Access granted to specific graphs, the `default` key stands for the default graph
+    #[serde(default)]
+    pub graphs: HashMap<String, AccessLevel>,
+    /// Access granted to the datasets managed with the `/datasets` API, the datasets not listed are not accessible
+    #[serde(default)]
+    pub datasets: HashMap<String, DatasetAccessConfig>,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct TokenConfig {
+    /// Hexadecimal SHA-256 hash of the token
+    pub token_sha256: String,
+    /// Access granted to the graphs not listed in `graphs`
+    #[serde(default)]
+    pub access: AccessLevel,
+    /// Access granted to specific graphs, the `default` key stands for the default graph
+    #[serde(default)]
+    pub graphs: HashMap<String, AccessLevel>,
+    /// Access granted to the datasets managed with the `/datasets` API, the datasets not listed are not accessible
+    #[serde(default)]
+    pub datasets: HashMap<String, DatasetAccessConfig>,
+}
+
+/// Access granted to a dataset managed with the `/datasets` API
+#[derive(Deserialize, Default, Debug, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct DatasetAccessConfig {
+    /// Access granted to the graphs not listed in `graphs`
+    pub access: AccessLevel,
+    /// Access granted to specific graphs, the `default` key stands for the default graph
+    pub graphs: HashMap<String, AccessLevel>,
+}
+
+/// What a request issuer is allowed to do on a graph
+#[derive(Deserialize, Default, Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum AccessLevel {
+    #[default]
+    None,
+    Read,
+    Write,
+}
+
+impl fmt::Display for AccessLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::None => "no",
+            Self::Read => "read",
+            Self::Write => "write",
+        })
+    }
+}
+
+impl Config {
+    /// Reads the configuration from a TOML file.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read the configuration file {}", path.display()))?;
+        let mut config = Self::from_toml(&content)
+            .with_context(|| format!("Invalid configuration file {}", path.display()))?;
+        for location in [
+            &mut config.storage.location,
+            &mut config.storage.datasets_location,
+            &mut config.storage.backup_location,
+            &mut config.server.function_definitions,
+        ]
+        .into_iter()
+        .chain(config.queries.values_mut().map(|query| &mut query.file))
+        .flatten()
+        {
+            if location.is_relative() {
+                if let Some(parent) = path.parent() {
+                    *location = parent.join(&*location);
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn from_toml(content: &str) -> anyhow::Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+}
+
+impl ServerConfig {
+    pub fn bind(&self) -> &str {
+        self.bind.as_deref().unwrap_or(DEFAULT_BIND)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_full_config() -> anyhow::Result<()> {
+        assert_eq!(
+            Config::from_toml(
+                "[storage]\nlocation = \"/var/lib/oxigraph\"\nhistory = true\ndatasets-location = \"/var/lib/oxigraph-datasets\"\nbackup-location = \"/var/backups/oxigraph\"\nbackup-interval = 3600.0\n\n[server]\nbind = \"0.0.0.0:80\"\ncors = true\nunion-default-graph = true\nlog-requests = true\nslow-query-threshold = 0.5\nquery-timeout = 30.0\nmax-results = 10000\nenable-federation = true\nfederation-allowed-services = [\"http://example.com/sparql\"]\nfederation-timeout = 5.0\ntext-index-predicates = [\"http://example.com/p\"]\nfunction-definitions = \"/etc/oxigraph/functions.rq\"\nspatial-index = true\nwarm-up = true\n\n[replication]\nprimary = \"http://example.com:7878\"\ninterval = 2.0\nmax-lag = 30.0\ntoken = \"secret\"\n"
+            )?,
+            Config {
+                storage: StorageConfig {
+                    location: Some("/var/lib/oxigraph".into()),
+                    history: true,
+                    datasets_location: Some("/var/lib/oxigraph-datasets".into()),
+                    backup_location: Some("/var/backups/oxigraph".into()),
+                    backup_interval: Some(3600.)
+                },
+                server: ServerConfig {
+                    bind: Some("0.0.0.0:80".into()),
+                    cors: true,
+                    union_default_graph: true,
+                    log_requests: true,
+                    log_query_text: false,
+                    slow_query_threshold: Some(0.5),
+                    query_timeout: Some(30.),
+                    max_results: Some(10000),
+                    max_memory: None,
+                    query_memory_budget: None,
+                    cursor_ttl: None,
+                    transaction_timeout: None,
+                    enable_federation: true,
+                    federation_allowed_services: vec!["http://example.com/sparql".into()],
+                    federation_timeout: Some(5.),
+                    text_index_predicates: vec!["http://example.com/p".into()],
+                    function_definitions: Some("/etc/oxigraph/functions.rq".into()),
+                    spatial_index: true,
+                    warm_up: true
+                },
+                replication: ReplicationConfig {
+                    primary: Some("http://example.com:7878".into()),
+                    interval: Some(2.),
+                    max_lag: Some(30.),
+                    username: None,
+                    password: None,
+                    token: Some("secret".into())
+                },
+                auth: None,
+                queries: HashMap::new()
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_auth_config() -> anyhow::Result<()> {
+        let config = Config::from_toml(
+            "[auth]\nanonymous-access = \"read\"\n\n[[auth.users]]\nname = \"alice\"\npassword-hash = \"00\"\ngraphs = { default = \"none\", \"http://example.com/g\" = \"write\" }\ndatasets.ds = { access = \"read\" }\n\n[[auth.tokens]]\ntoken-sha256 = \"01\"\naccess = \"write\"\n",
+        )?;
+        assert_eq!(
+            config.auth,
+            Some(AuthConfig {
+                anonymous_access: AccessLevel::Read,
+                users: vec![UserConfig {
+                    name: "alice".into(),
+                    password_hash: "00".into(),
+                    access: AccessLevel::None,
+                    graphs: [
+                        ("default".into(), AccessLevel::None),
+                        ("http://example.com/g".into(), AccessLevel::Write)
+                    ]
+                    .into_iter()
+                    .collect(),
+                    datasets: [(
+                        "ds".into(),
+                        DatasetAccessConfig {
+                            access: AccessLevel::Read,
+                            graphs: HashMap::new()
+                        }
+                    )]
+                    .into_iter()
+                    .collect()
+                }],
+                tokens: vec![TokenConfig {
+                    token_sha256: "01".into(),
+                    access: AccessLevel::Write,
+                    graphs: HashMap::new(),
+                    datasets: HashMap::new()
+                }]
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_queries_config() -> anyhow::Result<()> {
+        let config = Config::from_toml(
+            "[queries.labels]\nquery = \"SELECT * WHERE { ?s ?p ?o }\"\nparameters = [\"s\"]\npublic = true\n\n[queries.other]\nfile = \"other.rq\"\n",
+        )?;
+        assert_eq!(
+            config.queries,
+            [
+                (
+                    "labels".into(),
+                    StoredQueryConfig {
+                        query: Some("SELECT * WHERE { ?s ?p ?o }".into()),
+                        file: None,
+                        parameters: vec!["s".into()],
+                        public: true
+                    }
+                ),
+                (
+                    "other".into(),
+                    StoredQueryConfig {
+                        file: Some("other.rq".into()),
+                        ..StoredQueryConfig::default()
+                    }
+                )
+            ]
+            .into_iter()
+            .collect()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_empty_config() -> anyhow::Result<()> {
+        let config = Config::from_toml("")?;
+        assert_eq!(config, Config::default());
+        assert_eq!(config.server.bind(), DEFAULT_BIND);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_config_with_unknown_key() {
+        Config::from_toml("[server]\nport = 80\n").unwrap_err();
+    }
+}
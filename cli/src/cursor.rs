@@ -0,0 +1,236 @@
+use crate::limits::EvaluationGuard;
+use crate::{internal_server_error, HttpError};
+use oxhttp::model::Status;
+use oxigraph::model::{Triple, Variable};
+use oxigraph::sparql::{
+    EvaluationError, QueryResults, QuerySolution, QuerySolutionIter, QueryTripleIter,
+};
+use rand::random;
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::sync::mpsc::{sync_channel, SyncSender};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::Builder;
+use std::time::Duration;
+
+/// Duration after which a cursor that has not been used is closed
+pub const DEFAULT_CURSOR_TTL: Duration = Duration::from_secs(60);
+/// Maximal number of cursors open at the same time
+const MAX_OPEN_CURSORS: usize = 1024;
+
+/// Server-side cursors returning the results of a query page by page.
+///
+/// Each cursor evaluates its query in its own thread that keeps the results iterator alive between the page requests.
+/// A cursor is closed once all its results have been returned or if no page has been requested during its time to live.
+#[derive(Clone)]
+pub struct CursorRegistry {
+    ttl: Duration,
+    cursors: Arc<Mutex<HashMap<String, SyncSender<PageRequest>>>>,
+}
+
+impl CursorRegistry {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            cursors: Arc::default(),
+        }
+    }
+
+    /// Evaluates the query on a new thread and returns its first page of results.
+    ///
+    /// The returned [`Page::cursor`] is set if there are more results.
+    pub fn open(
+        &self,
+        evaluate: impl FnOnce() -> Result<(QueryResults, EvaluationGuard), HttpError> + Send + 'static,
+        mut page_size: usize,
+    ) -> Result<Page, HttpError> {
+        let id = format!("{:032x}", random::<u128>());
+        let (request_sender, request_receiver) = sync_channel::<PageRequest>(0);
+        {
+            let mut cursors = self.cursors.lock().unwrap_or_else(PoisonError::into_inner);
+            if cursors.len() >= MAX_OPEN_CURSORS {
+                return Err((
+                    Status::SERVICE_UNAVAILABLE,
+                    format!("There are already {MAX_OPEN_CURSORS} open cursors"),
+                ));
+            }
+            cursors.insert(id.clone(), request_sender);
+        }
+        let (mut response_sender, response_receiver) = sync_channel(1);
+        let registry = self.clone();
+        let thread_id = id.clone();
+        Builder::new()
+            .name("query-cursor".into())
+            .spawn(move || {
+                let mut results = match evaluate() {
+                    Ok((results, guard)) => OpenResults::new(results, guard),
+                    Err(e) => {
+                        registry.close(&thread_id);
+                        // The requester might be gone
+                        drop(response_sender.send(Err(e)));
+                        return;
+                    }
+                };
+                loop {
+                    let page = results.next_page(page_size).map(|(page, has_more)| Page {
+                        results: page,
+                        cursor: has_more.then(|| thread_id.clone()),
+                    });
+                    let has_more = page.as_ref().is_ok_and(|page| page.cursor.is_some());
+                    if !has_more {
+                        registry.close(&thread_id);
+                    }
+                    if response_sender.send(page).is_err() || !has_more {
+                        break;
+                    }
+                    let Ok(request) = request_receiver.recv_timeout(registry.ttl) else {
+                        break; // Expired
+                    };
+                    response_sender = request.response;
+                    page_size = request.page_size.unwrap_or(page_size);
+                }
+                registry.close(&thread_id);
+            })
+            .map_err(|e| {
+                self.close(&id);
+                internal_server_error(e)
+            })?;
+        response_receiver.recv().map_err(|_| {
+            internal_server_error("The cursor evaluation has stopped without returning results")
+        })?
+    }
+
+    /// Returns the next page of results of a cursor.
+    ///
+    /// If `page_size` is not set, the page size of the previous request is used.
+    pub fn next(&self, id: &str, page_size: Option<usize>) -> Result<Page, HttpError> {
+        let not_found = || {
+            (
+                Status::NOT_FOUND,
+                format!("The cursor {id} does not exist or has expired"),
+            )
+        };
+        let request_sender = self
+            .cursors
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(id)
+            .cloned()
+            .ok_or_else(not_found)?;
+        let (response_sender, response_receiver) = sync_channel(1);
+        request_sender
+            .send(PageRequest {
+                page_size,
+                response: response_sender,
+            })
+            .map_err(|_| not_found())?;
+        response_receiver.recv().map_err(|_| not_found())?
+    }
+
+    fn close(&self, id: &str) {
+        self.cursors
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(id);
+    }
+}
+
+impl Default for CursorRegistry {
+    fn default() -> Self {
+        Self::new(DEFAULT_CURSOR_TTL)
+    }
+}
+
+struct PageRequest {
+    page_size: Option<usize>,
+    response: SyncSender<Result<Page, HttpError>>,
+}
+
+/// A page of results returned by a cursor
+pub struct Page {
+    pub results: PageResults,
+    /// The cursor to use to get the next page, not set if all the results have been returned
+    pub cursor: Option<String>,
+}
+
+pub enum PageResults {
+    Solutions {
+        variables: Vec<Variable>,
+        solutions: Vec<QuerySolution>,
+    },
+    Boolean(bool),
+    Triples(Vec<Triple>),
+}
+
+/// The results of a query kept by a cursor
+struct OpenResults {
+    kind: OpenResultsKind,
+    guard: EvaluationGuard,
+    /// Number of results already returned
+    count: u64,
+}
+
+enum OpenResultsKind {
+    Solutions {
+        variables: Vec<Variable>,
+        solutions: Peekable<QuerySolutionIter>,
+    },
+    Boolean(bool),
+    Triples(Peekable<QueryTripleIter>),
+}
+
+impl OpenResults {
+    fn new(results: QueryResults, guard: EvaluationGuard) -> Self {
+        Self {
+            kind: match results {
+                QueryResults::Solutions(solutions) => OpenResultsKind::Solutions {
+                    variables: solutions.variables().to_vec(),
+                    solutions: solutions.peekable(),
+                },
+                QueryResults::Boolean(value) => OpenResultsKind::Boolean(value),
+                QueryResults::Graph(triples) => OpenResultsKind::Triples(triples.peekable()),
+            },
+            guard,
+            count: 0,
+        }
+    }
+
+    /// Returns the next page and if there are more results after it
+    fn next_page(&mut self, page_size: usize) -> Result<(PageResults, bool), HttpError> {
+        Ok(match &mut self.kind {
+            OpenResultsKind::Solutions {
+                variables,
+                solutions,
+            } => {
+                let page = collect_page(solutions, page_size, &self.guard, &mut self.count)?;
+                (
+                    PageResults::Solutions {
+                        variables: variables.clone(),
+                        solutions: page,
+                    },
+                    solutions.peek().is_some(),
+                )
+            }
+            OpenResultsKind::Boolean(value) => (PageResults::Boolean(*value), false),
+            OpenResultsKind::Triples(triples) => {
+                let page = collect_page(triples, page_size, &self.guard, &mut self.count)?;
+                (PageResults::Triples(page), triples.peek().is_some())
+            }
+        })
+    }
+}
+
+fn collect_page<T>(
+    results: &mut impl Iterator<Item = Result<T, EvaluationError>>,
+    page_size: usize,
+    guard: &EvaluationGuard,
+    count: &mut u64,
+) -> Result<Vec<T>, HttpError> {
+    let mut page = Vec::new();
+    for result in results.take(page_size) {
+        page.push(result.map_err(|e| guard.evaluation_error(e))?);
+        *count += 1;
+        guard.check_result_count(*count)?;
+    }
+    Ok(page)
+}
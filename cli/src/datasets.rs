@@ -0,0 +1,199 @@
+use crate::{bad_request, internal_server_error, the_server_is_read_only, HttpError};
+use anyhow::Context;
+use oxhttp::model::Status;
+use oxigraph::store::Store;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, remove_dir_all};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// File listing the datasets, stored at the root of the datasets location
+const MANIFEST_FILE: &str = "datasets.json";
+/// Names that would make the dataset endpoints collide with the server ones
+const RESERVED_NAMES: [&str; 3] = ["datasets", "queries", "store"];
+
+/// Named datasets managed with the `/datasets` API, each one with its own store.
+///
+/// Their stores are persisted in a subdirectory of the datasets location named after the dataset,
+/// or kept in memory if there is no such location.
+#[derive(Clone, Default)]
+pub struct DatasetRegistry {
+    location: Option<PathBuf>,
+    read_only: bool,
+    datasets: Arc<Mutex<BTreeMap<String, Dataset>>>,
+}
+
+/// A dataset of a [`DatasetRegistry`]
+#[derive(Clone)]
+pub struct Dataset {
+    pub store: Store,
+    /// If the dataset rejects the updates and the Graph Store Protocol writes
+    pub read_only: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct DatasetDescription {
+    read_only: bool,
+}
+
+impl DatasetRegistry {
+    /// Opens the datasets persisted in the given directory.
+    ///
+    /// If `read_only` is set, their stores are opened in read-only mode and the datasets cannot be created or deleted.
+    pub fn open(location: &Path, read_only: bool) -> anyhow::Result<Self> {
+        if !read_only {
+            fs::create_dir_all(location)?;
+        }
+        let manifest = location.join(MANIFEST_FILE);
+        let descriptions = if manifest.exists() {
+            serde_json::from_slice::<BTreeMap<String, DatasetDescription>>(&fs::read(&manifest)?)
+                .with_context(|| format!("Invalid dataset list {}", manifest.display()))?
+        } else {
+            BTreeMap::new()
+        };
+        let mut datasets = BTreeMap::new();
+        for (name, description) in descriptions {
+            let path = location.join(&name);
+            let store = if read_only {
+                Store::open_read_only(&path)
+            } else {
+                Store::open(&path)
+            }
+            .with_context(|| format!("Failed to open the dataset {name}"))?;
+            datasets.insert(
+                name,
+                Dataset {
+                    store,
+                    read_only: description.read_only,
+                },
+            );
+        }
+        Ok(Self {
+            location: Some(location.into()),
+            read_only,
+            datasets: Arc::new(Mutex::new(datasets)),
+        })
+    }
+
+    pub fn get(&self, name: &str) -> Result<Dataset, HttpError> {
+        self.content()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| not_found(name))
+    }
+
+    /// The names of the datasets and if they are read-only
+    pub fn list(&self) -> Vec<(String, bool)> {
+        self.content()
+            .iter()
+            .map(|(name, dataset)| (name.clone(), dataset.read_only))
+            .collect()
+    }
+
+    pub fn create(&self, name: &str, read_only: bool) -> Result<(), HttpError> {
+        if !is_valid_name(name) {
+            return Err(bad_request(format!(
+                "Invalid dataset name '{name}': only ASCII letters, digits, '-' and '_' are allowed and it must not be one of {}",
+                RESERVED_NAMES.join(", ")
+            )));
+        }
+        if self.read_only {
+            return Err(the_server_is_read_only());
+        }
+        let mut datasets = self.content();
+        if datasets.contains_key(name) {
+            return Err((
+                Status::CONFLICT,
+                format!("The dataset {name} already exists"),
+            ));
+        }
+        let store = if let Some(location) = &self.location {
+            Store::open(location.join(name))
+        } else {
+            Store::new()
+        }
+        .map_err(internal_server_error)?;
+        datasets.insert(name.into(), Dataset { store, read_only });
+        self.save(&datasets)
+    }
+
+    /// Removes the dataset and deletes its data
+    pub fn delete(&self, name: &str) -> Result<(), HttpError> {
+        if self.read_only {
+            return Err(the_server_is_read_only());
+        }
+        let mut datasets = self.content();
+        if datasets.remove(name).is_none() {
+            return Err(not_found(name));
+        }
+        self.save(&datasets)?;
+        if let Some(location) = &self.location {
+            remove_dir_all(location.join(name)).map_err(internal_server_error)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the list of datasets in the manifest file
+    fn save(&self, datasets: &BTreeMap<String, Dataset>) -> Result<(), HttpError> {
+        let Some(location) = &self.location else {
+            return Ok(());
+        };
+        let descriptions = datasets
+            .iter()
+            .map(|(name, dataset)| {
+                (
+                    name,
+                    DatasetDescription {
+                        read_only: dataset.read_only,
+                    },
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+        // We write a temporary file and rename it to never leave a truncated manifest
+        let temporary = location.join(format!("{MANIFEST_FILE}.tmp"));
+        fs::write(
+            &temporary,
+            serde_json::to_vec_pretty(&descriptions).map_err(internal_server_error)?,
+        )
+        .map_err(internal_server_error)?;
+        fs::rename(temporary, location.join(MANIFEST_FILE)).map_err(internal_server_error)
+    }
+
+    fn content(&self) -> MutexGuard<'_, BTreeMap<String, Dataset>> {
+        self.datasets.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Splits a `/{dataset}/query`, `/{dataset}/update` or `/{dataset}/store` path into the dataset name and the path of the endpoint on the dataset store
+pub fn split_dataset_path(path: &str) -> Option<(&str, &'static str)> {
+    let (name, endpoint) = path.strip_prefix('/')?.split_once('/')?;
+    if !is_valid_name(name) {
+        return None;
+    }
+    Some((
+        name,
+        match endpoint {
+            "query" => "/query",
+            "update" => "/update",
+            "store" => "/store",
+            _ => return None,
+        },
+    ))
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_'))
+        && !RESERVED_NAMES.contains(&name)
+}
+
+fn not_found(name: &str) -> HttpError {
+    (
+        Status::NOT_FOUND,
+        format!("The dataset {name} does not exist"),
+    )
+}
@@ -0,0 +1,98 @@
+use oxigraph::model::NamedNode;
+use oxigraph::sparql::{EvaluationError, Query, QueryResults, ServiceHandler};
+use sparql_client::{QueryResponse, SparqlClient, SparqlClientError};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Evaluates the SPARQL `SERVICE` calls by sending the service queries to the remote SPARQL endpoints.
+///
+/// HTTP redirections are not followed to prevent them from bypassing the allow-list.
+#[derive(Clone)]
+pub struct FederatedServiceHandler {
+    client: Arc<SparqlClient>,
+    /// IRI prefixes of the services that are allowed to be called, all services are allowed if empty
+    allowed_services: Arc<[String]>,
+}
+
+impl FederatedServiceHandler {
+    pub fn new(allowed_services: Vec<String>, timeout: Option<Duration>) -> anyhow::Result<Self> {
+        let mut client =
+            SparqlClient::new().with_user_agent(concat!("Oxigraph/", env!("CARGO_PKG_VERSION")))?;
+        if let Some(timeout) = timeout {
+            client = client.with_timeout(timeout);
+        }
+        Ok(Self {
+            client: Arc::new(client),
+            allowed_services: allowed_services.into(),
+        })
+    }
+
+    fn is_allowed(&self, service_name: &NamedNode) -> bool {
+        self.allowed_services.is_empty()
+            || self
+                .allowed_services
+                .iter()
+                .any(|prefix| service_name.as_str().starts_with(prefix.as_str()))
+    }
+}
+
+impl ServiceHandler for FederatedServiceHandler {
+    type Error = EvaluationError;
+
+    fn handle(
+        &self,
+        service_name: NamedNode,
+        query: Query,
+    ) -> Result<QueryResults, EvaluationError> {
+        if !self.is_allowed(&service_name) {
+            return Err(EvaluationError::UnsupportedService(service_name));
+        }
+        match self
+            .client
+            .query(service_name.as_str(), &query.to_string())
+            .map_err(service_error)?
+        {
+            QueryResponse::Solutions(solutions) => Ok(QueryResults::Solutions((*solutions).into())),
+            QueryResponse::Boolean(value) => Ok(QueryResults::Boolean(value)),
+            QueryResponse::Graph(_) => Err(EvaluationError::ServiceDoesNotReturnSolutions),
+        }
+    }
+
+    fn supports_bound_join(&self) -> bool {
+        true
+    }
+}
+
+fn service_error(error: SparqlClientError) -> EvaluationError {
+    match error {
+        SparqlClientError::UnsupportedContentType { content_type, .. } => {
+            EvaluationError::UnsupportedContentType(content_type)
+        }
+        _ => EvaluationError::Service(Box::new(error)),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allow_list() -> anyhow::Result<()> {
+        let handler = FederatedServiceHandler::new(
+            vec![
+                "https://query.wikidata.org/sparql".into(),
+                "http://example.com/".into(),
+            ],
+            None,
+        )?;
+        assert!(handler.is_allowed(&NamedNode::new("https://query.wikidata.org/sparql")?));
+        assert!(handler.is_allowed(&NamedNode::new("http://example.com/sparql")?));
+        assert!(!handler.is_allowed(&NamedNode::new("http://example.org/sparql")?));
+        assert!(!handler.is_allowed(&NamedNode::new("http://localhost:7878/query")?));
+
+        let handler = FederatedServiceHandler::new(Vec::new(), None)?;
+        assert!(handler.is_allowed(&NamedNode::new("http://localhost:7878/query")?));
+        Ok(())
+    }
+}
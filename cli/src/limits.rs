@@ -0,0 +1,243 @@
+use crate::{bad_request, evaluation_error_to_http_error, HttpError};
+use anyhow::bail;
+use oxhttp::model::Status;
+use oxigraph::sparql::{CancellationToken, EvaluationError};
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, Weak};
+use std::thread::{sleep, Builder};
+use std::time::{Duration, Instant};
+
+const CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Limits applied to the evaluation of the SPARQL queries and updates.
+///
+/// The time and memory limits are enforced by a background thread cancelling the evaluations going above them.
+#[derive(Clone, Default)]
+pub struct QueryLimits {
+    timeout: Option<Duration>,
+    max_results: Option<u64>,
+    /// Maximal resident memory of the server process in bytes
+    max_memory: Option<u64>,
+    /// Memory in bytes each query can use to buffer solutions
+    query_memory_budget: Option<u64>,
+    running: Arc<Mutex<Vec<Arc<RunningEvaluation>>>>,
+}
+
+impl QueryLimits {
+    pub fn new(
+        timeout: Option<Duration>,
+        max_results: Option<u64>,
+        max_memory: Option<u64>,
+        query_memory_budget: Option<u64>,
+    ) -> anyhow::Result<Self> {
+        if max_memory.is_some() && resident_memory().is_none() {
+            bail!("The max-memory limit is not supported on this platform");
+        }
+        let limits = Self {
+            timeout,
+            max_results,
+            max_memory,
+            query_memory_budget,
+            running: Arc::default(),
+        };
+        if timeout.is_some() || max_memory.is_some() {
+            let running = Arc::downgrade(&limits.running);
+            Builder::new()
+                .name("query-limits".into())
+                .spawn(move || watch(&running, max_memory))?;
+        }
+        Ok(limits)
+    }
+
+    /// Memory in bytes each query can use to buffer solutions
+    pub fn query_memory_budget(&self) -> Option<u64> {
+        self.query_memory_budget
+    }
+
+    /// Registers a new evaluation, it is tracked until the returned guard is dropped.
+    pub fn start(&self) -> EvaluationGuard {
+        let evaluation = Arc::new(RunningEvaluation {
+            cancellation_token: CancellationToken::new(),
+            deadline: self.timeout.map(|timeout| Instant::now() + timeout),
+            memory_exceeded: AtomicBool::new(false),
+        });
+        if self.timeout.is_some() || self.max_memory.is_some() {
+            self.running
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(Arc::clone(&evaluation));
+        }
+        EvaluationGuard {
+            evaluation,
+            limits: self.clone(),
+        }
+    }
+}
+
+struct RunningEvaluation {
+    cancellation_token: CancellationToken,
+    deadline: Option<Instant>,
+    memory_exceeded: AtomicBool,
+}
+
+/// An evaluation tracked by [`QueryLimits`]
+pub struct EvaluationGuard {
+    evaluation: Arc<RunningEvaluation>,
+    limits: QueryLimits,
+}
+
+impl EvaluationGuard {
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.evaluation.cancellation_token.clone()
+    }
+
+    /// Fails if the number of returned results is above the limit
+    pub fn check_result_count(&self, count: u64) -> Result<(), HttpError> {
+        match self.limits.max_results {
+            Some(max_results) if count > max_results => Err((
+                Status::SERVICE_UNAVAILABLE,
+                format!("The query returns more than the limit of {max_results} results"),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Maps an evaluation error to an HTTP error, explaining why the evaluation has been cancelled if it is the case
+    pub fn evaluation_error(&self, error: EvaluationError) -> HttpError {
+        if !matches!(error, EvaluationError::Cancelled) {
+            return evaluation_error_to_http_error(error);
+        }
+        let message = if self.evaluation.memory_exceeded.load(Ordering::Relaxed) {
+            format!(
+                "The evaluation has been cancelled because the server memory usage went above the limit of {} MiB",
+                self.limits.max_memory.unwrap_or_default() / (1024 * 1024)
+            )
+        } else {
+            format!(
+                "The evaluation has been cancelled because it took more than the limit of {} seconds",
+                self.limits.timeout.unwrap_or_default().as_secs_f64()
+            )
+        };
+        (Status::SERVICE_UNAVAILABLE, message)
+    }
+}
+
+impl Drop for EvaluationGuard {
+    fn drop(&mut self) {
+        self.limits
+            .running
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|e| !Arc::ptr_eq(e, &self.evaluation));
+    }
+}
+
+/// A best-effort evaluation returning the results computed before the end of a time budget
+/// with a continuation token allowing to get the next ones.
+///
+/// The continuation evaluates the query again and skips the results already returned.
+pub struct TimeBudget {
+    budget: Duration,
+    /// Number of results already returned by the previous calls
+    offset: u64,
+    query_hash: u64,
+}
+
+impl TimeBudget {
+    /// Builds the budget from the `time-budget` and `continuation` request parameters
+    pub fn new(query: &str, budget: &str, continuation: Option<&str>) -> Result<Self, HttpError> {
+        let budget = budget
+            .parse::<f64>()
+            .ok()
+            .and_then(|budget| Duration::try_from_secs_f64(budget).ok())
+            .ok_or_else(|| bad_request(format!("Invalid time budget: {budget}")))?;
+        let mut hasher = DefaultHasher::new();
+        query.hash(&mut hasher);
+        let query_hash = hasher.finish();
+        let offset = if let Some(continuation) = continuation {
+            let (offset, hash) = continuation
+                .split_once('-')
+                .and_then(|(offset, hash)| {
+                    Some((offset.parse().ok()?, u64::from_str_radix(hash, 16).ok()?))
+                })
+                .ok_or_else(|| bad_request(format!("Invalid continuation: {continuation}")))?;
+            if hash != query_hash {
+                return Err(bad_request(
+                    "The continuation has been returned for another query",
+                ));
+            }
+            offset
+        } else {
+            0
+        };
+        Ok(Self {
+            budget,
+            offset,
+            query_hash,
+        })
+    }
+
+    /// Returns the results computed before the end of the budget, skipping the ones already returned,
+    /// and the continuation if the evaluation has been stopped before its end.
+    ///
+    /// At least one result is returned to ensure progress, the budget is only checked between results.
+    pub fn collect<T>(
+        &self,
+        results: impl IntoIterator<Item = Result<T, EvaluationError>>,
+        guard: &EvaluationGuard,
+    ) -> Result<(Vec<T>, Option<String>), HttpError> {
+        let deadline = Instant::now() + self.budget;
+        let mut collected = Vec::new();
+        for (count, result) in (1..).zip(results) {
+            let result = result.map_err(|e| guard.evaluation_error(e))?;
+            if count <= self.offset {
+                continue;
+            }
+            guard.check_result_count(count)?;
+            collected.push(result);
+            if Instant::now() >= deadline {
+                return Ok((collected, Some(format!("{count}-{:x}", self.query_hash))));
+            }
+        }
+        Ok((collected, None))
+    }
+}
+
+/// Cancels the running evaluations going above the limits until the [`QueryLimits`] are dropped
+fn watch(running: &Weak<Mutex<Vec<Arc<RunningEvaluation>>>>, max_memory: Option<u64>) {
+    loop {
+        sleep(CHECK_INTERVAL);
+        let Some(running) = running.upgrade() else {
+            return;
+        };
+        let now = Instant::now();
+        let memory_exceeded = max_memory
+            .is_some_and(|max_memory| resident_memory().is_some_and(|memory| memory > max_memory));
+        for evaluation in running
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+        {
+            if evaluation.deadline.is_some_and(|deadline| deadline <= now) {
+                evaluation.cancellation_token.cancel();
+            } else if memory_exceeded {
+                evaluation.memory_exceeded.store(true, Ordering::Relaxed);
+                evaluation.cancellation_token.cancel();
+            }
+        }
+    }
+}
+
+/// The resident memory of the current process in bytes
+fn resident_memory() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let value = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?
+        .trim()
+        .strip_suffix("kB")?;
+    Some(value.trim().parse::<u64>().ok()? * 1024)
+}
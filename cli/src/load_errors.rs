@@ -0,0 +1,159 @@
+use crate::cli::OnLoadError;
+use anyhow::{bail, Context};
+use oxigraph::io::RdfParseError;
+use oxigraph::store::LoaderError;
+use serde::Serialize;
+use std::cell::Cell;
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+/// Collects the errors raised while loading files and stops the loading if the [`OnLoadError`] policy asks for it
+pub struct LoadErrors {
+    policy: OnLoadError,
+    errors: Mutex<Vec<LoadError>>,
+    failed_files: AtomicUsize,
+    aborted_files: AtomicUsize,
+    aborted: AtomicBool,
+}
+
+/// An entry of the error report
+#[derive(Serialize)]
+struct LoadError {
+    file: String,
+    /// 1-based line of the error in the file, if known
+    line: Option<u64>,
+    /// 1-based column of the error in the file, if known
+    column: Option<u64>,
+    message: String,
+}
+
+impl LoadErrors {
+    pub fn new(policy: OnLoadError) -> Self {
+        Self {
+            policy,
+            errors: Mutex::default(),
+            failed_files: AtomicUsize::new(0),
+            aborted_files: AtomicUsize::new(0),
+            aborted: AtomicBool::new(false),
+        }
+    }
+
+    /// If the invalid statements are logged and skipped instead of failing the file loading
+    pub fn skips_invalid_statements(&self) -> bool {
+        matches!(self.policy, OnLoadError::Log)
+    }
+
+    /// If a file has failed while the policy is to abort
+    pub fn is_aborted(&self) -> bool {
+        self.aborted.load(Ordering::Acquire)
+    }
+
+    /// Wraps the reader of a file such that its loading stops as soon as the loading is aborted.
+    ///
+    /// `interrupted` is set if the reader has been stopped.
+    pub fn reader<'a, R: Read>(
+        &'a self,
+        inner: R,
+        interrupted: &'a Cell<bool>,
+    ) -> AbortableReader<'a, R> {
+        AbortableReader {
+            inner,
+            errors: self,
+            interrupted,
+        }
+    }
+
+    /// Records an invalid statement skipped with the [`OnLoadError::Log`] policy
+    pub fn invalid_statement(&self, file: &str, error: &RdfParseError) {
+        eprintln!("Parsing error on file {file}: {error}");
+        self.push(file, error_location(error), error.to_string());
+    }
+
+    /// Records that a file has not been loaded or has only been partially loaded because the loading has been aborted
+    pub fn file_aborted(&self, file: &str) {
+        eprintln!("The loading of {file} has been aborted");
+        self.aborted_files.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records that the loading of a file has failed
+    pub fn file_failed(&self, file: &str, error: &anyhow::Error) {
+        eprintln!("Error while loading file {file}: {error:#}");
+        let location = error
+            .downcast_ref::<LoaderError>()
+            .and_then(|error| match error {
+                LoaderError::Parsing(error) => error_location(error),
+                _ => None,
+            });
+        self.push(file, location, format!("{error:#}"));
+        self.failed_files.fetch_add(1, Ordering::Relaxed);
+        if matches!(self.policy, OnLoadError::Abort) {
+            self.aborted.store(true, Ordering::Release);
+        }
+    }
+
+    /// Writes the error report if asked and fails if some files have not been fully loaded
+    pub fn finish(self, file_count: usize, error_report: Option<&Path>) -> anyhow::Result<()> {
+        let errors = self
+            .errors
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(error_report) = error_report {
+            fs::write(error_report, serde_json::to_vec_pretty(&errors)?).with_context(|| {
+                format!(
+                    "Failed to write the error report {}",
+                    error_report.display()
+                )
+            })?;
+        }
+        let failed_files = self.failed_files.into_inner();
+        let aborted_files = self.aborted_files.into_inner();
+        if aborted_files > 0 {
+            bail!("{failed_files} of the {file_count} file(s) failed to load and the loading of {aborted_files} other file(s) has been aborted");
+        }
+        if failed_files > 0 {
+            bail!("{failed_files} of the {file_count} file(s) failed to load");
+        }
+        Ok(())
+    }
+
+    fn push(&self, file: &str, location: Option<(u64, u64)>, message: String) {
+        self.errors
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(LoadError {
+                file: file.into(),
+                line: location.map(|(line, _)| line),
+                column: location.map(|(_, column)| column),
+                message,
+            });
+    }
+}
+
+/// The 1-based line and column of the error start
+fn error_location(error: &RdfParseError) -> Option<(u64, u64)> {
+    let RdfParseError::Syntax(error) = error else {
+        return None;
+    };
+    let location = error.location()?;
+    Some((location.start.line + 1, location.start.column + 1))
+}
+
+/// A reader failing as soon as the loading is aborted
+pub struct AbortableReader<'a, R: Read> {
+    inner: R,
+    errors: &'a LoadErrors,
+    interrupted: &'a Cell<bool>,
+}
+
+impl<R: Read> Read for AbortableReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.errors.is_aborted() {
+            self.interrupted.set(true);
+            return Err(io::Error::other("the loading has been aborted"));
+        }
+        self.inner.read(buf)
+    }
+}
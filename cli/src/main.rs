@@ -1,47 +1,101 @@
 #![allow(clippy::print_stderr, clippy::cast_precision_loss, clippy::use_debug)]
-use crate::cli::{Args, Command};
+use crate::auth::{hash_password, Access, Authorization, Principal};
+use crate::backups::BackupManager;
+use crate::cli::{Args, Command, ServerArgs};
+use crate::config::{AccessLevel, Config, ReplicationConfig};
+use crate::cursor::{CursorRegistry, Page, PageResults, DEFAULT_CURSOR_TTL};
+use crate::datasets::{split_dataset_path, DatasetRegistry};
+use crate::federation::FederatedServiceHandler;
+use crate::limits::{EvaluationGuard, QueryLimits, TimeBudget};
+use crate::load_errors::LoadErrors;
+use crate::metrics::{Metrics, SparqlOperation};
+use crate::notifications::{ChangeNotifier, QuadPattern};
+use crate::rdf_patch::{parse_rdf_patch, serialize_rdf_patch, PatchOperation};
+use crate::replication::{
+    serialize_history, serialize_snapshot, start_replication, PrimaryCredentials,
+    ReplicationStatus, DEFAULT_REPLICATION_INTERVAL,
+};
+use crate::request_log::{QueryLogEntry, RequestLog};
 use crate::service_description::{generate_service_description, EndpointKind};
+use crate::stored_queries::StoredQueryRegistry;
+use crate::trace::{explanation_to_json, plan_differences, DatasetFingerprint, QueryTrace};
+use crate::transactions::{
+    Operation, OperationResponse, TransactionRegistry, DEFAULT_TRANSACTION_TIMEOUT,
+};
 use anyhow::{bail, ensure, Context};
 use clap::Parser;
 use flate2::read::MultiGzDecoder;
+use oxcsvw::CsvwParser;
 use oxhttp::model::{Body, HeaderName, HeaderValue, Method, Request, Response, Status};
 use oxhttp::Server;
-use oxigraph::io::{RdfFormat, RdfParser, RdfSerializer};
+use oxigraph::io::{RdfFormat, RdfParser, RdfSerializer, WriterQuadSerializer};
+use oxigraph::model::dataset::Rdfc10Options;
 use oxigraph::model::{
-    GraphName, GraphNameRef, IriParseError, NamedNode, NamedNodeRef, NamedOrBlankNode,
+    Dataset, GraphName, GraphNameRef, IriParseError, NamedNode, NamedOrBlankNode, Quad, Subject,
+    Term,
 };
 use oxigraph::sparql::results::{QueryResultsFormat, QueryResultsSerializer};
-use oxigraph::sparql::{Query, QueryOptions, QueryResults, Update};
-use oxigraph::store::{BulkLoader, LoaderError, Store};
+use oxigraph::sparql::{
+    EvaluationError, FunctionDefinition, InMemoryTextIndex, Query, QueryOptions, QueryResults,
+    QueryResultsDiff, QuerySolution, QueryWarning, Update,
+};
+use oxigraph::store::{
+    BulkLoader, DumpOptions, LoadReport, LoaderError, ReferenceCheckOptions, StorageError, Store,
+    WarmUpOptions,
+};
 use oxiri::Iri;
+use oxsdatatypes::{DateTime, Integer, TimezoneOffset};
 use rand::random;
 use rayon_core::ThreadPoolBuilder;
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::{json, Value};
 #[cfg(feature = "geosparql")]
-use spargeo::register_geosparql_functions;
+use spargeo::{register_geosparql_functions, register_spatial_index, SpatialIndex};
+use sparql_client::{QueryResponse, SparqlClient};
 use std::borrow::Cow;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::cmp::{max, min};
+use std::collections::HashMap;
 #[cfg(target_os = "linux")]
 use std::env;
+use std::error::Error;
 use std::ffi::OsStr;
 use std::fs::File;
-use std::io::{self, stdin, stdout, BufWriter, Read, Write};
+use std::io::{self, stdin, stdout, BufReader, BufWriter, Read, Write};
 use std::net::ToSocketAddrs;
 #[cfg(target_os = "linux")]
 use std::os::unix::net::UnixDatagram;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::str::FromStr;
+use std::sync::{Arc, Mutex, PoisonError};
 use std::thread::available_parallelism;
 use std::time::{Duration, Instant};
 use std::{fmt, fs, str};
-use url::form_urlencoded;
 
+mod auth;
+mod backups;
 mod cli;
+mod config;
+mod cursor;
+mod datasets;
+mod federation;
+mod limits;
+mod load_errors;
+mod metrics;
+mod notifications;
+mod rdf_patch;
+mod replication;
+mod request_log;
 mod service_description;
+mod stored_queries;
+mod trace;
+mod transactions;
 
 const MAX_SPARQL_BODY_SIZE: u64 = 1024 * 1024 * 128; // 128MB
 const HTTP_TIMEOUT: Duration = Duration::from_secs(60);
+const EXPLANATION_MEDIA_TYPE: &str = "application/x-oxigraph-explain";
 const HTML_ROOT_PAGE: &str = include_str!("../templates/query.html");
 #[allow(clippy::large_include_file)]
 const YASGUI_JS: &str = include_str!("../templates/yasgui/yasgui.min.js");
@@ -51,40 +105,128 @@ const LOGO: &str = include_str!("../logo.svg");
 pub fn main() -> anyhow::Result<()> {
     let matches = Args::parse();
     match matches.command {
-        Command::Serve {
-            location,
-            bind,
-            cors,
-            union_default_graph,
-        } => serve(
-            if let Some(location) = location {
+        Command::Serve { location, server } => {
+            let check_config = server.check_config;
+            let config = server_config(location, server)?;
+            let options = ServerOptions::new(&config, false)?;
+            let text_index_predicates = text_index_predicates(&config)?;
+            if check_config {
+                eprintln!("The configuration is valid");
+                return Ok(());
+            }
+            let store = if let Some(location) = &config.storage.location {
                 Store::open(location)
             } else {
                 Store::new()
-            }?,
-            &bind,
-            false,
-            cors,
-            union_default_graph,
-        ),
-        Command::ServeReadOnly {
+            }?;
+            if config.storage.history {
+                store.enable_history()?;
+            }
+            enable_text_index(&store, text_index_predicates)?;
+            #[cfg(feature = "geosparql")]
+            let options = options.with_spatial_index(spatial_index(&store, &config)?);
+            let options = options
+                .with_datasets(dataset_registry(&config, false)?)
+                .with_backups(backup_manager(&store, &config)?);
+            warm_up(&store, &config)?;
+            serve(store, config.server.bind(), config.server.cors, options)
+        }
+        Command::ServeReadOnly { location, server } => {
+            let check_config = server.check_config;
+            let config = server_config(location, server)?;
+            let location = config.storage.location.as_ref().context(
+                "The --location option or the storage.location configuration key must be set",
+            )?;
+            let options = ServerOptions::new(&config, true)?;
+            let text_index_predicates = text_index_predicates(&config)?;
+            if check_config {
+                eprintln!("The configuration is valid");
+                return Ok(());
+            }
+            let store = Store::open_read_only(location)?;
+            enable_text_index(&store, text_index_predicates)?;
+            #[cfg(feature = "geosparql")]
+            let options = options.with_spatial_index(spatial_index(&store, &config)?);
+            let options = options
+                .with_datasets(dataset_registry(&config, true)?)
+                .with_backups(backup_manager(&store, &config)?);
+            warm_up(&store, &config)?;
+            serve(store, config.server.bind(), config.server.cors, options)
+        }
+        Command::ServeReplica {
             location,
-            bind,
-            cors,
-            union_default_graph,
-        } => serve(
-            Store::open_read_only(location)?,
-            &bind,
-            true,
-            cors,
-            union_default_graph,
-        ),
+            primary,
+            replication_interval,
+            server,
+        } => {
+            let check_config = server.check_config;
+            let mut config = server_config(location, server)?;
+            if primary.is_some() {
+                config.replication.primary = primary;
+            }
+            if replication_interval.is_some() {
+                config.replication.interval = replication_interval;
+            }
+            let location = config.storage.location.as_ref().context(
+                "The --location option or the storage.location configuration key must be set",
+            )?;
+            let primary = config.replication.primary.as_deref().context(
+                "The --primary option or the replication.primary configuration key must be set",
+            )?;
+            let interval = config
+                .replication
+                .interval
+                .map(Duration::try_from_secs_f64)
+                .transpose()
+                .context("Invalid replication interval")?
+                .unwrap_or(DEFAULT_REPLICATION_INTERVAL);
+            let max_lag = config
+                .replication
+                .max_lag
+                .map(Duration::try_from_secs_f64)
+                .transpose()
+                .context("Invalid maximal replication lag")?;
+            let credentials = primary_credentials(&config.replication)?;
+            let status = Arc::new(ReplicationStatus::default());
+            // The replica store is written by the replication only
+            let options = ServerOptions::new(&config, true)?;
+            let text_index_predicates = text_index_predicates(&config)?;
+            if check_config {
+                eprintln!("The configuration is valid");
+                return Ok(());
+            }
+            let store = Store::open(location)?;
+            enable_text_index(&store, text_index_predicates)?;
+            #[cfg(feature = "geosparql")]
+            let options = options.with_spatial_index(spatial_index(&store, &config)?);
+            let options = options.with_backups(backup_manager(&store, &config)?);
+            let options = if let Some(max_lag) = max_lag {
+                options.with_replication_lag_check(Arc::clone(&status), max_lag)
+            } else {
+                options
+            };
+            warm_up(&store, &config)?;
+            start_replication(
+                store.clone(),
+                primary,
+                credentials.as_ref(),
+                interval,
+                status,
+            )?;
+            serve(store, config.server.bind(), config.server.cors, options)
+        }
         Command::Backup {
             location,
             destination,
+            since,
+            ..
         } => {
             let store = Store::open_read_only(location)?;
-            store.backup(destination)?;
+            if let Some(since) = since {
+                store.incremental_backup(destination, since)?;
+            } else {
+                store.backup(destination)?;
+            }
             Ok(())
         }
         Command::Load {
@@ -94,6 +236,12 @@ pub fn main() -> anyhow::Result<()> {
             format,
             base,
             graph,
+            rename_graph,
+            deskolemize,
+            detect_duplicates,
+            parsing_threads,
+            on_error,
+            error_report,
         } => {
             let store = Store::open(location)?;
             let format = if let Some(format) = format {
@@ -109,6 +257,11 @@ pub fn main() -> anyhow::Result<()> {
             } else {
                 None
             };
+            let renamed_graphs = rename_graph
+                .iter()
+                .map(|renaming| parse_graph_renaming(renaming))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let errors = Arc::new(LoadErrors::new(on_error));
             #[allow(clippy::cast_precision_loss)]
             if file.is_empty() {
                 // We read from stdin
@@ -121,31 +274,56 @@ pub fn main() -> anyhow::Result<()> {
                         ((size as f64) / elapsed.as_secs_f64()).round()
                     )
                 });
-                if lenient {
+                if lenient || errors.skips_invalid_statements() {
+                    let errors = Arc::clone(&errors);
                     loader = loader.on_parse_error(move |e| {
-                        eprintln!("Parsing error: {e}");
+                        errors.invalid_statement("stdin", &e);
                         Ok(())
                     })
                 }
-                bulk_load(
+                if detect_duplicates {
+                    loader = loader.with_duplicate_detection();
+                }
+                loader = loader.with_num_parsing_threads(parsing_threads);
+                let result = bulk_load(
                     &loader,
                     stdin().lock(),
                     format.context("The --format option must be set when loading from stdin")?,
                     base.as_deref(),
                     graph,
+                    &renamed_graphs,
                     lenient,
-                )
+                    deskolemize.as_deref(),
+                );
+                match result {
+                    Ok(report) => print_load_report(&report, "stdin"),
+                    Err(error) => errors.file_failed("stdin", &error),
+                }
+                drop(loader);
+                store.flush()?;
+                Arc::into_inner(errors)
+                    .context("The loading is not finished")?
+                    .finish(1, error_report.as_deref())
             } else {
+                let file_count = file.len();
                 ThreadPoolBuilder::new()
                     .num_threads(max(1, available_parallelism()?.get() / 2))
                     .thread_name(|i| format!("Oxigraph bulk loader thread {i}"))
                     .build()?
                     .scope(|s| {
+                        let renamed_graphs = renamed_graphs.as_slice();
                         for file in file {
                             let store = store.clone();
                             let graph = graph.clone();
                             let base = base.clone();
+                            let deskolemize = deskolemize.clone();
+                            let errors = Arc::clone(&errors);
                             s.spawn(move |_| {
+                                let file_name = file.display().to_string();
+                                if errors.is_aborted() {
+                                    errors.file_aborted(&file_name);
+                                    return;
+                                }
                                 let f = file.clone();
                                 let start = Instant::now();
                                 let mut loader = store.bulk_loader().on_progress(move |size| {
@@ -158,62 +336,67 @@ pub fn main() -> anyhow::Result<()> {
                                         f.display()
                                     )
                                 });
-                                if lenient {
-                                    let f = file.clone();
+                                if lenient || errors.skips_invalid_statements() {
+                                    let errors = Arc::clone(&errors);
+                                    let file_name = file_name.clone();
                                     loader = loader.on_parse_error(move |e| {
-                                        eprintln!("Parsing error on file {}: {}", f.display(), e);
+                                        errors.invalid_statement(&file_name, &e);
                                         Ok(())
                                     })
                                 }
-                                let fp = match File::open(&file) {
-                                    Ok(fp) => fp,
-                                    Err(error) => {
-                                        eprintln!(
-                                            "Error while opening file {}: {}",
-                                            file.display(),
-                                            error
-                                        );
-                                        return;
-                                    }
-                                };
-                                if let Err(error) = {
-                                    if file.extension().is_some_and(|e| e == OsStr::new("gz")) {
-                                        bulk_load(
-                                            &loader,
-                                            MultiGzDecoder::new(fp),
-                                            format.unwrap_or_else(|| {
-                                                rdf_format_from_path(&file.with_extension(""))
-                                                    .unwrap()
-                                            }),
-                                            base.as_deref(),
-                                            graph,
-                                            lenient,
-                                        )
-                                    } else {
-                                        bulk_load(
-                                            &loader,
-                                            fp,
-                                            format.unwrap_or_else(|| {
-                                                rdf_format_from_path(&file).unwrap()
-                                            }),
-                                            base.as_deref(),
-                                            graph,
-                                            lenient,
-                                        )
-                                    }
-                                } {
-                                    eprintln!(
-                                        "Error while loading file {}: {}",
-                                        file.display(),
-                                        error
-                                    )
-                                    // TODO: hard fail
+                                if detect_duplicates {
+                                    loader = loader.with_duplicate_detection();
+                                }
+                                loader = loader.with_num_parsing_threads(parsing_threads);
+                                let interrupted = Cell::new(false);
+                                let result = File::open(&file)
+                                    .context("Failed to open the file")
+                                    .and_then(|fp| {
+                                        let fp = errors.reader(fp, &interrupted);
+                                        if file.extension().is_some_and(|e| e == OsStr::new("gz")) {
+                                            bulk_load(
+                                                &loader,
+                                                MultiGzDecoder::new(fp),
+                                                match format {
+                                                    Some(format) => format,
+                                                    None => rdf_format_from_path(
+                                                        &file.with_extension(""),
+                                                    )?,
+                                                },
+                                                base.as_deref(),
+                                                graph,
+                                                renamed_graphs,
+                                                lenient,
+                                                deskolemize.as_deref(),
+                                            )
+                                        } else {
+                                            bulk_load(
+                                                &loader,
+                                                fp,
+                                                match format {
+                                                    Some(format) => format,
+                                                    None => rdf_format_from_path(&file)?,
+                                                },
+                                                base.as_deref(),
+                                                graph,
+                                                renamed_graphs,
+                                                lenient,
+                                                deskolemize.as_deref(),
+                                            )
+                                        }
+                                    });
+                                match result {
+                                    Ok(report) => print_load_report(&report, &file_name),
+                                    Err(_) if interrupted.get() => errors.file_aborted(&file_name),
+                                    Err(error) => errors.file_failed(&file_name, &error),
                                 }
                             })
                         }
                     });
                 store.flush()?;
-                Ok(())
+                Arc::into_inner(errors)
+                    .context("The loading is not finished")?
+                    .finish(file_count, error_report.as_deref())
             }
         }
         Command::Dump {
@@ -221,6 +404,9 @@ pub fn main() -> anyhow::Result<()> {
             file,
             format,
             graph,
+            graph_pattern,
+            skolemize,
+            language,
         } => {
             let store = Store::open_read_only(location)?;
             let format = if let Some(format) = format {
@@ -230,27 +416,64 @@ pub fn main() -> anyhow::Result<()> {
             } else {
                 bail!("The --format option must be set when writing to stdout")
             };
-            let graph = if let Some(graph) = &graph {
-                Some(if graph.eq_ignore_ascii_case("default") {
-                    GraphNameRef::DefaultGraph
-                } else {
-                    NamedNodeRef::new(graph)
-                        .with_context(|| format!("The target graph name {graph} is invalid"))?
-                        .into()
+            let graphs = graph
+                .iter()
+                .map(|graph| {
+                    Ok(if graph.eq_ignore_ascii_case("default") {
+                        GraphName::DefaultGraph
+                    } else {
+                        NamedNode::new(graph)
+                            .with_context(|| format!("The target graph name {graph} is invalid"))?
+                            .into()
+                    })
                 })
-            } else {
-                None
-            };
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let graph_pattern = graph_pattern
+                .map(|pattern| {
+                    Regex::new(&pattern).with_context(|| format!("Invalid graph pattern {pattern}"))
+                })
+                .transpose()?;
+            let mut serializer = RdfSerializer::from_format(format)
+                .with_auto_prefixes()
+                .with_well_known_prefixes();
+            if let Some(authority) = &skolemize {
+                serializer = serializer
+                    .with_skolemization(authority)
+                    .with_context(|| format!("Invalid skolemization authority {authority}"))?;
+            }
             if let Some(file) = file {
                 close_file_writer(dump(
                     &store,
                     BufWriter::new(File::create(file)?),
-                    format,
-                    graph,
+                    serializer,
+                    &graphs,
+                    graph_pattern.as_ref(),
+                    &language,
                 )?)?;
             } else {
-                dump(&store, stdout().lock(), format, graph)?.flush()?;
+                dump(
+                    &store,
+                    stdout().lock(),
+                    serializer,
+                    &graphs,
+                    graph_pattern.as_ref(),
+                    &language,
+                )?
+                .flush()?;
+            }
+            Ok(())
+        }
+        Command::Languages { location } => {
+            let store = Store::open_read_only(location)?;
+            let mut stdout = BufWriter::new(stdout().lock());
+            for (language, statistics) in store.language_statistics()? {
+                writeln!(
+                    stdout,
+                    "{language}\t{}\t{}",
+                    statistics.count, statistics.distinct_literals
+                )?;
             }
+            stdout.flush()?;
             Ok(())
         }
         Command::Query {
@@ -263,9 +486,10 @@ pub fn main() -> anyhow::Result<()> {
             explain,
             explain_file,
             stats,
+            trace_file,
             union_default_graph,
         } => {
-            let query = if let Some(query) = query {
+            let query_text = if let Some(query) = query {
                 query
             } else if let Some(query_file) = query_file {
                 fs::read_to_string(&query_file).with_context(|| {
@@ -274,13 +498,19 @@ pub fn main() -> anyhow::Result<()> {
             } else {
                 io::read_to_string(stdin().lock())?
             };
-            let mut query = Query::parse(&query, query_base.as_deref())?;
+            let mut query = Query::parse(&query_text, query_base.as_deref())?;
             if union_default_graph {
                 query.dataset_mut().set_default_graph_as_union();
             }
+            for warning in query.warnings() {
+                eprintln!("Warning: {warning}");
+            }
             let store = Store::open_read_only(location)?;
-            let (results, explanation) =
-                store.explain_query_opt(query, default_query_options(), stats)?;
+            let (results, explanation) = store.explain_query_opt(
+                query,
+                default_query_options(),
+                stats || trace_file.is_some(),
+            )?;
             let print_result = (|| {
                 match results? {
                     QueryResults::Solutions(solutions) => {
@@ -393,16 +623,149 @@ pub fn main() -> anyhow::Result<()> {
                         explanation.write_in_json(&mut file)?;
                     },
                     Some("txt") => {
-                        write!(file, "{explanation:?}")?;
+                        explanation.write_in_text(&mut file)?;
                     },
                     _ => bail!("The given explanation file {} must have an extension that is .json or .txt", explain_file.display())
                 }
                 close_file_writer(file)?;
             } else if explain || stats {
-                eprintln!("{explanation:#?}");
+                explanation.write_in_text(io::stderr().lock())?;
+            }
+            if let Some(trace_file) = trace_file {
+                let trace = QueryTrace {
+                    query: query_text,
+                    query_base,
+                    union_default_graph,
+                    dataset: DatasetFingerprint::compute(&store)?,
+                    explanation: explanation_to_json(&explanation)?,
+                };
+                let mut file = BufWriter::new(File::create(&trace_file)?);
+                serde_json::to_writer_pretty(&mut file, &trace)?;
+                close_file_writer(file)?;
             }
             print_result
         }
+        Command::Replay {
+            location,
+            trace_file,
+        } => {
+            let trace: QueryTrace =
+                serde_json::from_reader(BufReader::new(File::open(&trace_file)?))
+                    .with_context(|| format!("Invalid trace file {}", trace_file.display()))?;
+            let store = Store::open_read_only(location)?;
+            let fingerprint = DatasetFingerprint::compute(&store)?;
+            if fingerprint != trace.dataset {
+                eprintln!(
+                    "Warning: the store content is not the one of the trace ({} quads instead of {})",
+                    fingerprint.number_of_quads, trace.dataset.number_of_quads
+                );
+            }
+            let mut query = Query::parse(&trace.query, trace.query_base.as_deref())?;
+            if trace.union_default_graph {
+                query.dataset_mut().set_default_graph_as_union();
+            }
+            let (results, explanation) =
+                store.explain_query_opt(query, default_query_options(), true)?;
+            // We consume the results to fill the statistics
+            match results? {
+                QueryResults::Solutions(solutions) => {
+                    for solution in solutions {
+                        solution?;
+                    }
+                }
+                QueryResults::Graph(triples) => {
+                    for triple in triples {
+                        triple?;
+                    }
+                }
+                QueryResults::Boolean(_) => (),
+            }
+            let explanation = explanation_to_json(&explanation)?;
+            eprintln!(
+                "Evaluation duration in seconds: {} in the trace and {} in the replay",
+                trace.explanation["plan"]["duration in seconds"],
+                explanation["plan"]["duration in seconds"]
+            );
+            let differences = plan_differences(&trace.explanation, &explanation);
+            let mut stdout = stdout().lock();
+            for difference in &differences {
+                writeln!(stdout, "{difference}")?;
+            }
+            stdout.flush()?;
+            ensure!(
+                differences.is_empty(),
+                "{} differences found with the trace",
+                differences.len()
+            );
+            Ok(())
+        }
+        Command::DiffResults {
+            left,
+            right,
+            query,
+            query_file,
+            query_base,
+        } => {
+            let query = if let Some(query) = query {
+                query
+            } else if let Some(query_file) = query_file {
+                fs::read_to_string(&query_file).with_context(|| {
+                    format!("Not able to read query file {}", query_file.display())
+                })?
+            } else {
+                io::read_to_string(stdin().lock())?
+            };
+            let query = Query::parse(&query, query_base.as_deref())?;
+            let diff = QueryResultsDiff::new(
+                evaluate_query_on(&left, query.clone())
+                    .with_context(|| format!("Failed to evaluate the query against {left}"))?,
+                evaluate_query_on(&right, query)
+                    .with_context(|| format!("Failed to evaluate the query against {right}"))?,
+            )?;
+            if diff.is_empty() {
+                return Ok(());
+            }
+            let mut stdout = stdout().lock();
+            write!(stdout, "{diff}")?;
+            stdout.flush()?;
+            bail!("The results are different")
+        }
+        Command::Lint {
+            query,
+            query_file,
+            query_base,
+        } => {
+            let query = if let Some(query) = query {
+                query
+            } else if let Some(query_file) = query_file {
+                fs::read_to_string(&query_file).with_context(|| {
+                    format!("Not able to read query file {}", query_file.display())
+                })?
+            } else {
+                io::read_to_string(stdin().lock())?
+            };
+            let warnings = Query::parse(&query, query_base.as_deref())?.lint();
+            if warnings.is_empty() {
+                return Ok(());
+            }
+            let mut stdout = stdout().lock();
+            for warning in &warnings {
+                writeln!(stdout, "{warning}")?;
+            }
+            stdout.flush()?;
+            bail!("{} warnings found", warnings.len())
+        }
+        Command::HashPassword => {
+            let password = io::read_to_string(stdin().lock())?;
+            let password = password
+                .strip_suffix('\n')
+                .map_or(password.as_str(), |p| p.strip_suffix('\r').unwrap_or(p));
+            ensure!(!password.is_empty(), "The password must not be empty");
+            let mut stdout = stdout().lock();
+            writeln!(stdout, "{}", hash_password(password)?)?;
+            stdout.flush()?;
+            Ok(())
+        }
         Command::Update {
             location,
             update,
@@ -424,15 +787,210 @@ pub fn main() -> anyhow::Result<()> {
             store.flush()?;
             Ok(())
         }
+        Command::CheckReferences {
+            location,
+            predicate,
+            description_graph,
+            description_default_graph,
+        } => {
+            let store = Store::open_read_only(location)?;
+            let mut options = ReferenceCheckOptions::default();
+            for iri in predicate {
+                options = options.with_predicate(
+                    NamedNode::new(&iri)
+                        .with_context(|| format!("The predicate {iri} is invalid"))?,
+                );
+            }
+            for iri in description_graph {
+                options = options.with_description_graph(
+                    NamedNode::new(&iri)
+                        .with_context(|| format!("The description graph name {iri} is invalid"))?,
+                );
+            }
+            if description_default_graph {
+                options = options.with_description_graph(GraphName::DefaultGraph);
+            }
+            let mut serializer = RdfSerializer::from_format(RdfFormat::NQuads)
+                .for_writer(BufWriter::new(stdout().lock()));
+            let mut count = 0;
+            for quad in store.dangling_references(options) {
+                serializer.serialize_quad(&quad?)?;
+                count += 1;
+            }
+            serializer.finish()?.flush()?;
+            if count > 0 {
+                bail!("{count} dangling references found")
+            }
+            Ok(())
+        }
         Command::Optimize { location } => {
             let store = Store::open(location)?;
             store.optimize()?;
             Ok(())
         }
+        Command::Stats { location, json } => {
+            let statistics = Store::open_read_only(location)?.size_statistics()?;
+            let mut stdout = stdout().lock();
+            if json {
+                let indexes = statistics
+                    .indexes()
+                    .iter()
+                    .map(|index| {
+                        json!({
+                            "name": index.name(),
+                            "approximate-entries": index.approximate_entries(),
+                            "live-files-size": index.live_files_size(),
+                            "total-files-size": index.total_files_size(),
+                            "estimated-live-data-size": index.estimated_live_data_size(),
+                            "memtables-size": index.memtables_size(),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                serde_json::to_writer_pretty(
+                    &mut stdout,
+                    &json!({
+                        "directory-size": statistics.directory_size(),
+                        "approximate-quads": statistics.approximate_quads(),
+                        "indexes": indexes,
+                    }),
+                )?;
+                writeln!(stdout)?;
+            } else {
+                writeln!(
+                    stdout,
+                    "Directory size: {} bytes",
+                    statistics.directory_size()
+                )?;
+                writeln!(
+                    stdout,
+                    "Approximate number of quads: {}",
+                    statistics.approximate_quads()
+                )?;
+                writeln!(
+                    stdout,
+                    "{:<10} {:>15} {:>15} {:>15} {:>15} {:>15}",
+                    "index", "entries", "live files", "total files", "live data", "memtables"
+                )?;
+                for index in statistics.indexes() {
+                    writeln!(
+                        stdout,
+                        "{:<10} {:>15} {:>15} {:>15} {:>15} {:>15}",
+                        index.name(),
+                        index.approximate_entries(),
+                        index.live_files_size(),
+                        index.total_files_size(),
+                        index.estimated_live_data_size(),
+                        index.memtables_size()
+                    )?;
+                }
+            }
+            stdout.flush()?;
+            Ok(())
+        }
+        Command::Canonicalize {
+            file,
+            format,
+            base,
+            output,
+            issued_identifiers,
+            max_work_factor,
+            no_work_limit,
+        } => {
+            let format = if let Some(format) = format {
+                rdf_format_from_name(&format)?
+            } else if let Some(file) = &file {
+                rdf_format_from_path(file)?
+            } else {
+                bail!("The --format option must be set when reading from stdin")
+            };
+            let mut parser = RdfParser::from_format(format);
+            if let Some(base) = base {
+                parser = parser
+                    .with_base_iri(&base)
+                    .with_context(|| format!("Invalid base IRI {base}"))?;
+            }
+            let dataset = if let Some(file) = &file {
+                parser
+                    .for_reader(File::open(file)?)
+                    .collect::<Result<Dataset, _>>()?
+            } else {
+                parser
+                    .for_reader(stdin().lock())
+                    .collect::<Result<Dataset, _>>()?
+            };
+            let mut options = Rdfc10Options::default().with_max_work_factor(max_work_factor);
+            if no_work_limit {
+                options = options.without_work_limit();
+            }
+            let canonicalization = dataset.canonicalize_rdfc10(options)?;
+            if let Some(issued_identifiers) = issued_identifiers {
+                let mapping = canonicalization
+                    .issued_identifiers()
+                    .iter()
+                    .map(|(from, to)| (from.as_str().to_owned(), Value::from(to.as_str())))
+                    .collect::<serde_json::Map<_, _>>();
+                fs::write(issued_identifiers, serde_json::to_vec_pretty(&mapping)?)?;
+            }
+            let nquads = canonicalization.to_nquads();
+            if let Some(output) = output {
+                fs::write(output, nquads)?;
+            } else {
+                let mut stdout = stdout().lock();
+                stdout.write_all(nquads.as_bytes())?;
+                stdout.flush()?;
+            }
+            Ok(())
+        }
+        Command::Diff {
+            from_file,
+            to_file,
+            format,
+            base,
+            output,
+        } => {
+            let from = read_dataset_file(&from_file, format.as_deref(), base.as_deref())?;
+            let to = read_dataset_file(&to_file, format.as_deref(), base.as_deref())?;
+            let diff = from.diff(&to);
+            let operations = diff
+                .removed()
+                .iter()
+                .cloned()
+                .map(PatchOperation::Delete)
+                .chain(diff.added().iter().cloned().map(PatchOperation::Add))
+                .collect::<Vec<_>>();
+            let patch = serialize_rdf_patch(&operations);
+            if let Some(output) = output {
+                fs::write(output, patch)?;
+            } else {
+                let mut stdout = stdout().lock();
+                stdout.write_all(patch.as_bytes())?;
+                stdout.flush()?;
+            }
+            Ok(())
+        }
+        Command::ApplyPatch { location, file } => {
+            let patch = if let Some(file) = file {
+                fs::read_to_string(&file)
+                    .with_context(|| format!("Not able to read patch file {}", file.display()))?
+            } else {
+                io::read_to_string(stdin().lock())?
+            };
+            let operations = parse_rdf_patch(&patch)?;
+            let store = Store::open(location)?;
+            store.transaction(|mut transaction| {
+                for operation in &operations {
+                    operation.apply(&mut transaction)?;
+                }
+                Result::<_, StorageError>::Ok(())
+            })?;
+            store.flush()?;
+            Ok(())
+        }
         Command::Convert {
             from_file,
             from_format,
             from_base,
+            csvw_metadata,
             to_file,
             to_format,
             to_base,
@@ -441,19 +999,39 @@ pub fn main() -> anyhow::Result<()> {
             from_default_graph,
             to_graph,
         } => {
-            let from_format = if let Some(format) = from_format {
-                rdf_format_from_name(&format)?
-            } else if let Some(file) = &from_file {
-                rdf_format_from_path(file)?
+            let is_csvw = if let Some(format) = &from_format {
+                format == "csvw"
             } else {
-                bail!("The --from-format option must be set when reading from stdin")
+                from_file
+                    .as_deref()
+                    .is_some_and(|file| has_extension(file, "csv") || has_extension(file, "tsv"))
+            };
+            let parser = if is_csvw {
+                ConvertParser::Csvw(csvw_parser(
+                    from_file.as_deref(),
+                    csvw_metadata.as_deref(),
+                    from_base.as_deref(),
+                )?)
+            } else {
+                ensure!(
+                    csvw_metadata.is_none(),
+                    "The --csvw-metadata option is only allowed with the csvw format"
+                );
+                let from_format = if let Some(format) = from_format {
+                    rdf_format_from_name(&format)?
+                } else if let Some(file) = &from_file {
+                    rdf_format_from_path(file)?
+                } else {
+                    bail!("The --from-format option must be set when reading from stdin")
+                };
+                let mut parser = RdfParser::from_format(from_format);
+                if let Some(base) = from_base {
+                    parser = parser
+                        .with_base_iri(&base)
+                        .with_context(|| format!("Invalid base IRI {base}"))?;
+                }
+                ConvertParser::Rdf(parser)
             };
-            let mut parser = RdfParser::from_format(from_format);
-            if let Some(base) = from_base {
-                parser = parser
-                    .with_base_iri(&base)
-                    .with_context(|| format!("Invalid base IRI {base}"))?;
-            }
 
             let to_format = if let Some(format) = to_format {
                 rdf_format_from_name(&format)?
@@ -538,12 +1116,17 @@ fn bulk_load(
     format: RdfFormat,
     base_iri: Option<&str>,
     to_graph_name: Option<NamedNode>,
+    renamed_graphs: &[(NamedNode, GraphName)],
     lenient: bool,
-) -> anyhow::Result<()> {
+    deskolemize: Option<&str>,
+) -> anyhow::Result<LoadReport> {
     let mut parser = RdfParser::from_format(format);
     if let Some(to_graph_name) = to_graph_name {
         parser = parser.with_default_graph(to_graph_name);
     }
+    for (from, to) in renamed_graphs {
+        parser = parser.with_renamed_graph(from.clone(), to.clone());
+    }
     if let Some(base_iri) = base_iri {
         parser = parser
             .with_base_iri(base_iri)
@@ -552,29 +1135,119 @@ fn bulk_load(
     if lenient {
         parser = parser.unchecked();
     }
-    loader.load_from_reader(parser, reader)?;
-    Ok(())
+    if let Some(authority) = deskolemize {
+        parser = parser
+            .with_deskolemization(authority)
+            .with_context(|| format!("Invalid deskolemization authority {authority}"))?;
+    }
+    Ok(loader.load_from_reader(parser, reader)?)
+}
+
+/// Parses a `FROM=TO` value of the --rename-graph option
+///
+/// The IRIs might contain `=` so the first split giving two valid graph names is used.
+fn parse_graph_renaming(value: &str) -> anyhow::Result<(NamedNode, GraphName)> {
+    for (position, _) in value.match_indices('=') {
+        let (from, to) = (&value[..position], &value[position + 1..]);
+        let Ok(from) = NamedNode::new(from) else {
+            continue;
+        };
+        if to.eq_ignore_ascii_case("default") {
+            return Ok((from, GraphName::DefaultGraph));
+        }
+        if let Ok(to) = NamedNode::new(to) {
+            return Ok((from, to.into()));
+        }
+    }
+    bail!("Invalid graph renaming {value}, it must be FROM=TO where FROM is a graph IRI and TO a graph IRI or \"default\"")
+}
+
+/// Prints how many quads have been loaded and skipped as duplicates, in total and per graph
+fn print_load_report(report: &LoadReport, source: impl fmt::Display) {
+    eprintln!(
+        "{} from {source}",
+        format_load_counts(
+            report.loaded_quads(),
+            report.new_quads(),
+            report.duplicate_quads()
+        )
+    );
+    let mut graphs = report.graphs().collect::<Vec<_>>();
+    graphs.sort_by_cached_key(|(graph_name, _)| graph_name.to_string());
+    for (graph_name, graph_report) in graphs {
+        eprintln!(
+            "    {graph_name}: {}",
+            format_load_counts(
+                graph_report.loaded_quads(),
+                graph_report.new_quads(),
+                graph_report.duplicate_quads()
+            )
+        );
+    }
+}
+
+/// The new and duplicate quads are only known if duplicates have been detected
+fn format_load_counts(loaded: u64, new: Option<u64>, duplicates: Option<u64>) -> String {
+    if let (Some(new), Some(duplicates)) = (new, duplicates) {
+        format!("{new} new quads loaded and {duplicates} duplicates skipped")
+    } else {
+        format!("{loaded} quads loaded")
+    }
 }
 
 fn dump<W: Write>(
     store: &Store,
     writer: W,
-    format: RdfFormat,
-    from_graph_name: Option<GraphNameRef<'_>>,
+    serializer: RdfSerializer,
+    graphs: &[GraphName],
+    graph_pattern: Option<&Regex>,
+    languages: &[String],
 ) -> anyhow::Result<W> {
-    ensure!(
-        format.supports_datasets() || from_graph_name.is_some(),
-        "The --graph option is required when writing a format not supporting datasets like NTriples, Turtle or RDF/XML. Use --graph \"default\" to dump only the default graph."
-    );
-    Ok(if let Some(from_graph_name) = from_graph_name {
-        store.dump_graph_to_writer(from_graph_name, format, writer)
+    let mut options = DumpOptions::default();
+    if let ([graph], None) = (graphs, graph_pattern) {
+        options = options.with_graph(graph.clone());
+    } else if graphs.is_empty() && graph_pattern.is_none() {
+        ensure!(
+            serializer.format().supports_datasets(),
+            "The --graph option is required when writing a format not supporting datasets like NTriples, Turtle or RDF/XML. Use --graph \"default\" to dump only the default graph."
+        );
     } else {
-        store.dump_to_writer(format, writer)
-    }?)
+        ensure!(
+            serializer.format().supports_datasets(),
+            "Dumping multiple graphs or the graphs matching --graph-pattern requires a format supporting datasets like N-Quads or TriG"
+        );
+        let mut selected = graphs.to_vec();
+        if let Some(graph_pattern) = graph_pattern {
+            for graph_name in store.named_graphs() {
+                if let NamedOrBlankNode::NamedNode(graph_name) = graph_name? {
+                    if graph_pattern.is_match(graph_name.as_str()) {
+                        selected.push(graph_name.into());
+                    }
+                }
+            }
+        }
+        if selected.is_empty() {
+            // No graph matches the pattern
+            return Ok(serializer.for_writer(writer).finish()?);
+        }
+        for graph_name in selected {
+            options = options.with_dataset_graph(graph_name);
+        }
+    }
+    for language in languages {
+        options = options.with_language(language);
+    }
+    Ok(store.dump_to_writer_opt(serializer, writer, options)?)
+}
+
+/// The parser of the file to convert
+enum ConvertParser {
+    Rdf(RdfParser),
+    Csvw(CsvwParser),
 }
 
 fn do_convert<R: Read, W: Write>(
-    parser: RdfParser,
+    parser: ConvertParser,
     reader: R,
     mut serializer: RdfSerializer,
     writer: W,
@@ -583,20 +1256,57 @@ fn do_convert<R: Read, W: Write>(
     default_graph: &GraphName,
     to_base: Option<&str>,
 ) -> anyhow::Result<W> {
-    let mut parser = parser.for_reader(reader);
-    let first = parser.next(); // We read the first element to get prefixes and the base IRI
-    if let Some(base_iri) = to_base.or_else(|| parser.base_iri()) {
-        serializer = serializer
-            .with_base_iri(base_iri)
-            .with_context(|| format!("Invalid base IRI: {base_iri}"))?;
-    }
-    for (prefix_name, prefix_iri) in parser.prefixes() {
-        serializer = serializer
-            .with_prefix(prefix_name, prefix_iri)
-            .with_context(|| format!("Invalid IRI for prefix {prefix_name}: {prefix_iri}"))?;
+    match parser {
+        ConvertParser::Rdf(parser) => {
+            let mut parser = parser.for_reader(reader);
+            let first = parser.next(); // We read the first element to get prefixes and the base IRI
+            if let Some(base_iri) = to_base.or_else(|| parser.base_iri()) {
+                serializer = serializer
+                    .with_base_iri(base_iri)
+                    .with_context(|| format!("Invalid base IRI: {base_iri}"))?;
+            }
+            for (prefix_name, prefix_iri) in parser.prefixes() {
+                serializer = serializer
+                    .with_prefix(prefix_name, prefix_iri)
+                    .with_context(|| {
+                        format!("Invalid IRI for prefix {prefix_name}: {prefix_iri}")
+                    })?;
+            }
+            convert_quads(
+                first.into_iter().chain(parser),
+                serializer.for_writer(writer),
+                lenient,
+                from_graph,
+                default_graph,
+            )
+        }
+        ConvertParser::Csvw(parser) => {
+            if let Some(base_iri) = to_base {
+                serializer = serializer
+                    .with_base_iri(base_iri)
+                    .with_context(|| format!("Invalid base IRI: {base_iri}"))?;
+            }
+            convert_quads(
+                parser
+                    .for_reader(reader)
+                    .map(|triple| triple.map(|triple| triple.in_graph(GraphName::DefaultGraph))),
+                serializer.for_writer(writer),
+                lenient,
+                from_graph,
+                default_graph,
+            )
+        }
     }
-    let mut serializer = serializer.for_writer(writer);
-    for quad_result in first.into_iter().chain(parser) {
+}
+
+fn convert_quads<W: Write, E: Error + Send + Sync + 'static>(
+    quads: impl IntoIterator<Item = Result<Quad, E>>,
+    mut serializer: WriterQuadSerializer<W>,
+    lenient: bool,
+    from_graph: &Option<GraphName>,
+    default_graph: &GraphName,
+) -> anyhow::Result<W> {
+    for quad_result in quads {
         match quad_result {
             Ok(mut quad) => {
                 if let Some(from_graph) = from_graph {
@@ -623,6 +1333,41 @@ fn do_convert<R: Read, W: Write>(
     Ok(serializer.finish()?)
 }
 
+/// Builds the parser of a CSV or TSV file from its CSVW metadata
+fn csvw_parser(
+    file: Option<&Path>,
+    metadata: Option<&Path>,
+    base: Option<&str>,
+) -> anyhow::Result<CsvwParser> {
+    let mut parser = CsvwParser::new();
+    if file.is_some_and(|file| has_extension(file, "tsv")) {
+        parser = parser.with_delimiter(b'\t');
+    }
+    let metadata = metadata.map(Path::to_path_buf).or_else(|| {
+        let mut metadata = file?.as_os_str().to_owned();
+        metadata.push("-metadata.json");
+        let metadata = PathBuf::from(metadata);
+        metadata.exists().then_some(metadata)
+    });
+    if let Some(metadata) = metadata {
+        let content = fs::read_to_string(&metadata)
+            .with_context(|| format!("Failed to read {}", metadata.display()))?;
+        parser = parser.with_metadata(&content)?;
+    }
+    if let Some(base) = base {
+        parser = parser
+            .with_base_iri(base)
+            .with_context(|| format!("Invalid base IRI {base}"))?;
+    }
+    Ok(parser)
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension()
+        .and_then(OsStr::to_str)
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+}
+
 fn format_from_path<T>(
     path: &Path,
     from_extension: impl FnOnce(&str) -> anyhow::Result<T>,
@@ -658,21 +1403,429 @@ fn rdf_format_from_name(name: &str) -> anyhow::Result<RdfFormat> {
     bail!("The file format '{name}' is unknown")
 }
 
-fn serve(
-    store: Store,
-    bind: &str,
-    read_only: bool,
-    cors: bool,
-    union_default_graph: bool,
-) -> anyhow::Result<()> {
-    let mut server = if cors {
-        Server::new(cors_middleware(move |request| {
-            handle_request(request, store.clone(), read_only, union_default_graph)
-                .unwrap_or_else(|(status, message)| error(status, message))
+/// Parses a RDF file in memory, guessing its format from its extension if not given
+fn read_dataset_file(
+    file: &Path,
+    format: Option<&str>,
+    base: Option<&str>,
+) -> anyhow::Result<Dataset> {
+    let format = if let Some(format) = format {
+        rdf_format_from_name(format)?
+    } else {
+        rdf_format_from_path(file)?
+    };
+    let mut parser = RdfParser::from_format(format);
+    if let Some(base) = base {
+        parser = parser
+            .with_base_iri(base)
+            .with_context(|| format!("Invalid base IRI {base}"))?;
+    }
+    parser
+        .for_reader(File::open(file)?)
+        .collect::<Result<Dataset, _>>()
+        .with_context(|| format!("Failed to parse {}", file.display()))
+}
+
+/// Reads the server configuration file if any and applies the command line overrides.
+fn server_config(location: Option<PathBuf>, args: ServerArgs) -> anyhow::Result<Config> {
+    let mut config = if let Some(file) = &args.config {
+        Config::from_file(file)?
+    } else {
+        Config::default()
+    };
+    if location.is_some() {
+        config.storage.location = location;
+    }
+    config.storage.history |= args.history;
+    if args.datasets_location.is_some() {
+        config.storage.datasets_location = args.datasets_location;
+    }
+    if args.backup_location.is_some() {
+        config.storage.backup_location = args.backup_location;
+    }
+    if args.backup_interval.is_some() {
+        config.storage.backup_interval = args.backup_interval;
+    }
+    if args.bind.is_some() {
+        config.server.bind = args.bind;
+    }
+    config.server.cors |= args.cors;
+    config.server.union_default_graph |= args.union_default_graph;
+    config.server.log_requests |= args.log_requests;
+    config.server.log_query_text |= args.log_query_text;
+    if args.slow_query_threshold.is_some() {
+        config.server.slow_query_threshold = args.slow_query_threshold;
+    }
+    if args.query_timeout.is_some() {
+        config.server.query_timeout = args.query_timeout;
+    }
+    if args.max_results.is_some() {
+        config.server.max_results = args.max_results;
+    }
+    if args.max_memory.is_some() {
+        config.server.max_memory = args.max_memory;
+    }
+    if args.query_memory_budget.is_some() {
+        config.server.query_memory_budget = args.query_memory_budget;
+    }
+    if args.cursor_ttl.is_some() {
+        config.server.cursor_ttl = args.cursor_ttl;
+    }
+    if args.transaction_timeout.is_some() {
+        config.server.transaction_timeout = args.transaction_timeout;
+    }
+    config.server.enable_federation |= args.enable_federation;
+    config
+        .server
+        .federation_allowed_services
+        .extend(args.federation_allowed_service);
+    if args.federation_timeout.is_some() {
+        config.server.federation_timeout = args.federation_timeout;
+    }
+    config
+        .server
+        .text_index_predicates
+        .extend(args.text_index_predicate);
+    if args.function_definitions.is_some() {
+        config.server.function_definitions = args.function_definitions;
+    }
+    config.server.spatial_index |= args.spatial_index;
+    config.server.warm_up |= args.warm_up;
+    Ok(config)
+}
+
+fn text_index_predicates(config: &Config) -> anyhow::Result<Vec<NamedNode>> {
+    config
+        .server
+        .text_index_predicates
+        .iter()
+        .map(|iri| {
+            NamedNode::new(iri)
+                .with_context(|| format!("The text index predicate {iri} is invalid"))
+        })
+        .collect()
+}
+
+fn load_function_definitions(path: &Path) -> anyhow::Result<Vec<FunctionDefinition>> {
+    let content = fs::read_to_string(path).with_context(|| {
+        format!(
+            "Not able to read the function definitions file {}",
+            path.display()
+        )
+    })?;
+    FunctionDefinition::parse_list(&content, None)
+        .with_context(|| format!("Invalid function definitions file {}", path.display()))
+}
+
+/// Builds the full-text search index if some predicates are set
+fn enable_text_index(store: &Store, predicates: Vec<NamedNode>) -> anyhow::Result<()> {
+    if predicates.is_empty() {
+        return Ok(());
+    }
+    let start = Instant::now();
+    store.enable_text_index(predicates, InMemoryTextIndex::default())?;
+    eprintln!(
+        "Full-text search index built in {:.3} seconds",
+        start.elapsed().as_secs_f64()
+    );
+    Ok(())
+}
+
+/// Builds the GeoSPARQL spatial index if it is enabled
+#[cfg(feature = "geosparql")]
+fn spatial_index(store: &Store, config: &Config) -> anyhow::Result<Option<Arc<SpatialIndex>>> {
+    if !config.server.spatial_index {
+        return Ok(None);
+    }
+    let start = Instant::now();
+    let index = SpatialIndex::for_store(store)?;
+    eprintln!(
+        "Spatial index built in {:.3} seconds",
+        start.elapsed().as_secs_f64()
+    );
+    Ok(Some(index))
+}
+
+/// Opens the datasets managed with the `/datasets` API
+///
+/// They are kept in memory if the server store is in memory and no datasets location is set.
+fn dataset_registry(config: &Config, read_only: bool) -> anyhow::Result<Option<DatasetRegistry>> {
+    Ok(if let Some(location) = &config.storage.datasets_location {
+        Some(DatasetRegistry::open(location, read_only)?)
+    } else if config.storage.location.is_none() {
+        Some(DatasetRegistry::default())
+    } else {
+        None
+    })
+}
+
+/// The credentials sent by a replica to its primary server
+fn primary_credentials(config: &ReplicationConfig) -> anyhow::Result<Option<PrimaryCredentials>> {
+    Ok(match (&config.username, &config.password, &config.token) {
+        (None, None, None) => None,
+        (Some(username), Some(password), None) => Some(PrimaryCredentials::Basic {
+            username: username.clone(),
+            password: password.clone(),
+        }),
+        (None, None, Some(token)) => Some(PrimaryCredentials::Bearer(token.clone())),
+        (_, _, Some(_)) => {
+            bail!("The replication.token configuration key is exclusive with replication.username and replication.password")
+        }
+        _ => bail!(
+            "The replication.username and replication.password configuration keys must be set together"
+        ),
+    })
+}
+
+/// Sets up the backups of the store if a backup location is set and schedules them if a backup interval is set
+fn backup_manager(store: &Store, config: &Config) -> anyhow::Result<Option<BackupManager>> {
+    let Some(location) = &config.storage.backup_location else {
+        ensure!(
+            config.storage.backup_interval.is_none(),
+            "The backup interval requires a backup location"
+        );
+        return Ok(None);
+    };
+    ensure!(
+        config.storage.location.is_some(),
+        "The backups require the store to be persisted on disk"
+    );
+    let manager = BackupManager::new(location).with_context(|| {
+        format!(
+            "Failed to create the backup directory {}",
+            location.display()
+        )
+    })?;
+    if let Some(interval) = config
+        .storage
+        .backup_interval
+        .map(Duration::try_from_secs_f64)
+        .transpose()
+        .context("Invalid backup interval")?
+    {
+        manager.schedule(store.clone(), interval)?;
+    }
+    Ok(Some(manager))
+}
+
+/// Loads the store indexes in the caches if it is enabled
+fn warm_up(store: &Store, config: &Config) -> anyhow::Result<()> {
+    if !config.server.warm_up {
+        return Ok(());
+    }
+    let start = Instant::now();
+    store.warm_up(WarmUpOptions::default())?;
+    eprintln!(
+        "Store warmed up in {:.3} seconds",
+        start.elapsed().as_secs_f64()
+    );
+    Ok(())
+}
+
+/// Settings of the HTTP server that are shared by all requests
+#[derive(Clone, Default)]
+struct ServerOptions {
+    read_only: bool,
+    union_default_graph: bool,
+    auth: Option<Arc<Authorization>>,
+    metrics: Arc<Metrics>,
+    request_log: RequestLog,
+    limits: QueryLimits,
+    /// Query results kept alive between the requests returning their pages
+    cursors: CursorRegistry,
+    notifier: Arc<ChangeNotifier>,
+    /// Held by the Graph Store Protocol requests changing a graph while they check their preconditions and apply their changes
+    graph_store_lock: Arc<Mutex<()>>,
+    /// Transactions opened with `/transactions` and spanning multiple requests
+    transactions: TransactionRegistry,
+    /// Handler of the `SERVICE` calls, federation is disabled if not set
+    federation: Option<FederatedServiceHandler>,
+    /// Functions defined in SPARQL available to all queries and updates
+    function_definitions: Vec<FunctionDefinition>,
+    /// Parameterized queries served at `/queries/{name}`
+    stored_queries: StoredQueryRegistry,
+    /// Index used to evaluate the GeoSPARQL filters
+    #[cfg(feature = "geosparql")]
+    spatial_index: Option<Arc<SpatialIndex>>,
+    /// Named datasets served at `/{dataset}/query`, `/{dataset}/update` and `/{dataset}/store`, disabled if not set
+    datasets: Option<DatasetRegistry>,
+    /// Backups triggered with `/backups`, disabled if not set
+    backups: Option<BackupManager>,
+    /// Replication status of a replica and maximal lag behind its primary for `/readyz` to succeed
+    replication_lag_check: Option<(Arc<ReplicationStatus>, Duration)>,
+}
+
+impl ServerOptions {
+    fn new(config: &Config, read_only: bool) -> anyhow::Result<Self> {
+        #[cfg(not(feature = "geosparql"))]
+        ensure!(
+            !config.server.spatial_index,
+            "The spatial index requires the geosparql feature"
+        );
+        Ok(Self {
+            read_only,
+            union_default_graph: config.server.union_default_graph,
+            auth: config
+                .auth
+                .as_ref()
+                .map(|auth| anyhow::Ok(Arc::new(Authorization::new(auth)?)))
+                .transpose()
+                .context("Invalid auth configuration")?,
+            metrics: Arc::new(Metrics::new(config.storage.location.clone())),
+            request_log: RequestLog {
+                enabled: config.server.log_requests,
+                with_query_text: config.server.log_query_text,
+                slow_query_threshold: config
+                    .server
+                    .slow_query_threshold
+                    .map(Duration::try_from_secs_f64)
+                    .transpose()
+                    .context("Invalid slow query threshold")?,
+            },
+            limits: QueryLimits::new(
+                config
+                    .server
+                    .query_timeout
+                    .map(Duration::try_from_secs_f64)
+                    .transpose()
+                    .context("Invalid query timeout")?,
+                config.server.max_results,
+                config
+                    .server
+                    .max_memory
+                    .map(|max_memory| max_memory.saturating_mul(1024 * 1024)),
+                config
+                    .server
+                    .query_memory_budget
+                    .map(|budget| budget.saturating_mul(1024 * 1024)),
+            )?,
+            cursors: CursorRegistry::new(
+                config
+                    .server
+                    .cursor_ttl
+                    .map(Duration::try_from_secs_f64)
+                    .transpose()
+                    .context("Invalid cursor TTL")?
+                    .unwrap_or(DEFAULT_CURSOR_TTL),
+            ),
+            notifier: Arc::default(),
+            graph_store_lock: Arc::default(),
+            transactions: TransactionRegistry::new(
+                config
+                    .server
+                    .transaction_timeout
+                    .map(Duration::try_from_secs_f64)
+                    .transpose()
+                    .context("Invalid transaction timeout")?
+                    .unwrap_or(DEFAULT_TRANSACTION_TIMEOUT),
+            ),
+            federation: config
+                .server
+                .enable_federation
+                .then(|| {
+                    FederatedServiceHandler::new(
+                        config.server.federation_allowed_services.clone(),
+                        config
+                            .server
+                            .federation_timeout
+                            .map(Duration::try_from_secs_f64)
+                            .transpose()
+                            .context("Invalid federation timeout")?,
+                    )
+                })
+                .transpose()?,
+            function_definitions: config
+                .server
+                .function_definitions
+                .as_deref()
+                .map(load_function_definitions)
+                .transpose()?
+                .unwrap_or_default(),
+            stored_queries: StoredQueryRegistry::new(&config.queries)
+                .context("Invalid stored queries configuration")?,
+            #[cfg(feature = "geosparql")]
+            spatial_index: None,
+            datasets: None,
+            backups: None,
+            replication_lag_check: None,
+        })
+    }
+
+    #[cfg(feature = "geosparql")]
+    fn with_spatial_index(mut self, spatial_index: Option<Arc<SpatialIndex>>) -> Self {
+        self.spatial_index = spatial_index;
+        self
+    }
+
+    fn with_datasets(mut self, datasets: Option<DatasetRegistry>) -> Self {
+        self.datasets = datasets;
+        self
+    }
+
+    fn with_backups(mut self, backups: Option<BackupManager>) -> Self {
+        self.backups = backups;
+        self
+    }
+
+    fn with_replication_lag_check(
+        mut self,
+        status: Arc<ReplicationStatus>,
+        max_lag: Duration,
+    ) -> Self {
+        self.replication_lag_check = Some((status, max_lag));
+        self
+    }
+
+    /// The options of the requests to a dataset
+    ///
+    /// The spatial index only covers the server store, it is not used.
+    fn for_dataset(&self, dataset_read_only: bool) -> Self {
+        Self {
+            read_only: self.read_only || dataset_read_only,
+            #[cfg(feature = "geosparql")]
+            spatial_index: None,
+            datasets: None,
+            ..self.clone()
+        }
+    }
+
+    /// The query options shared by all the requests
+    fn base_query_options(&self) -> QueryOptions {
+        let mut options = default_query_options();
+        for definition in &self.function_definitions {
+            options = options.with_function_definition(definition.clone());
+        }
+        #[cfg(feature = "geosparql")]
+        if let Some(index) = &self.spatial_index {
+            options = register_spatial_index(options, Arc::clone(index));
+        }
+        options
+    }
+
+    fn query_options(&self, guard: &EvaluationGuard) -> QueryOptions {
+        let mut options = self
+            .base_query_options()
+            .with_cancellation_token(guard.cancellation_token());
+        if let Some(budget) = self.limits.query_memory_budget() {
+            options = options.with_memory_budget(usize::try_from(budget).unwrap_or(usize::MAX));
+        }
+        if let Some(federation) = &self.federation {
+            options.with_service_handler(federation.clone())
+        } else {
+            options.without_service_handler()
+        }
+    }
+}
+
+fn serve(store: Store, bind: &str, cors: bool, options: ServerOptions) -> anyhow::Result<()> {
+    let mut server = if cors {
+        Server::new(cors_middleware(move |request| {
+            handle_request(request, store.clone(), &options)
+                .unwrap_or_else(|(status, message)| error(status, message))
         }))
     } else {
         Server::new(move |request| {
-            handle_request(request, store.clone(), read_only, union_default_graph)
+            handle_request(request, store.clone(), &options)
                 .unwrap_or_else(|(status, message)| error(status, message))
         })
     }
@@ -739,10 +1892,83 @@ type HttpError = (Status, String);
 fn handle_request(
     request: &mut Request,
     store: Store,
-    read_only: bool,
-    union_default_graph: bool,
+    options: &ServerOptions,
+) -> Result<Response, HttpError> {
+    let _in_flight = options.metrics.request_started();
+    let path = request.url().path();
+    let path = split_dataset_path(path).map_or(path, |(_, path)| path);
+    let operation = match (path, request.method().as_ref()) {
+        ("/query", "GET") if !url_query(request).is_empty() => Some(SparqlOperation::Query),
+        ("/query", "POST") => Some(SparqlOperation::Query),
+        ("/update", "POST") => Some(SparqlOperation::Update),
+        (path, "GET") if path.starts_with("/queries/") => Some(SparqlOperation::Query),
+        _ => None,
+    };
+    let start = Instant::now();
+    let response = route_request(request, store, options);
+    if let Some(operation) = operation {
+        let duration = start.elapsed();
+        options
+            .metrics
+            .observe_sparql(operation, duration, response.is_ok());
+        // The successful queries are logged when all their results have been returned
+        if matches!(operation, SparqlOperation::Update) || response.is_err() {
+            let status = match &response {
+                Ok(response) => response.status(),
+                Err((status, _)) => *status,
+            };
+            options
+                .request_log
+                .log(operation.name(), status.into(), duration);
+        }
+    }
+    response
+}
+
+fn route_request(
+    request: &mut Request,
+    mut store: Store,
+    server_options: &ServerOptions,
 ) -> Result<Response, HttpError> {
-    match (request.url().path(), request.method().as_ref()) {
+    let mut path = request.url().path().to_owned();
+    let dataset_options;
+    let mut options = server_options;
+    let mut dataset_name = None;
+    if let Some(datasets) = &options.datasets {
+        if let Some((name, dataset_path)) = split_dataset_path(&path) {
+            let dataset = datasets.get(name)?;
+            if url_query_parameter(request, "transaction").is_some() {
+                return Err(bad_request(
+                    "Transactions are not supported on the datasets",
+                ));
+            }
+            store = dataset.store;
+            dataset_options = options.for_dataset(dataset.read_only);
+            options = &dataset_options;
+            dataset_name = Some(name.to_owned());
+            path = dataset_path.into();
+        }
+    }
+    let access = options
+        .auth
+        .as_ref()
+        .map(|auth| auth.authenticate(request, dataset_name.as_deref()))
+        .transpose()?;
+    if let Some(access) = access
+        .as_ref()
+        .filter(|_| !options.stored_queries.is_public(&path))
+    {
+        if let Some((graph, level)) = required_access(request, &path)? {
+            access.check(graph.as_ref().map(GraphName::as_ref), level)?;
+        }
+    }
+    let issuer = access.as_ref().and_then(Access::principal);
+    let ServerOptions {
+        read_only,
+        union_default_graph,
+        ..
+    } = *options;
+    match (path.as_str(), request.method().as_ref()) {
         ("/", "HEAD") => Ok(Response::builder(Status::OK)
             .with_header(HeaderName::CONTENT_TYPE, "text/html")
             .unwrap()
@@ -775,12 +2001,158 @@ fn handle_request(
             .with_header(HeaderName::CONTENT_TYPE, "image/svg+xml")
             .unwrap()
             .with_body(LOGO)),
+        ("/healthz", "HEAD") => Ok(Response::builder(Status::OK)
+            .with_header(HeaderName::CONTENT_TYPE, "text/plain")
+            .unwrap()
+            .build()),
+        ("/healthz", "GET") => Ok(Response::builder(Status::OK)
+            .with_header(HeaderName::CONTENT_TYPE, "text/plain")
+            .unwrap()
+            .with_body("OK")),
+        ("/readyz", "GET" | "HEAD") => {
+            assert_that_store_is_ready(&store, options)?;
+            let response = Response::builder(Status::OK)
+                .with_header(HeaderName::CONTENT_TYPE, "text/plain")
+                .unwrap();
+            Ok(if request.method() == &Method::HEAD {
+                response.build()
+            } else {
+                response.with_body("OK")
+            })
+        }
+        ("/metrics", "GET") => Ok(Response::builder(Status::OK)
+            .with_header(HeaderName::CONTENT_TYPE, "text/plain; version=0.0.4")
+            .unwrap()
+            .with_body(options.metrics.render().map_err(internal_server_error)?)),
+        ("/subscribe", "GET") => {
+            let pattern = subscription_pattern(request)?;
+            Ok(Response::builder(Status::OK)
+                .with_header(HeaderName::CONTENT_TYPE, "text/event-stream")
+                .map_err(internal_server_error)?
+                .with_body(Body::from_read(options.notifier.subscribe(&store, pattern))))
+        }
+        ("/history", "GET") => {
+            let since = url_query_parameter(request, "since")
+                .map(|since| {
+                    since
+                        .parse::<u64>()
+                        .map_err(|e| bad_request(format!("Invalid since parameter: {e}")))
+                })
+                .transpose()?
+                .unwrap_or(0);
+            if store
+                .history_version()
+                .map_err(internal_server_error)?
+                .is_none()
+            {
+                return Err((Status::NOT_FOUND, "The store history is not enabled".into()));
+            }
+            let Some(patch) = serialize_history(&store, since).map_err(internal_server_error)?
+            else {
+                return Err((
+                    Status::CONFLICT,
+                    format!("The history version {} is a bulk load, the full store must be fetched from /history/snapshot", since + 1),
+                ));
+            };
+            Ok(Response::builder(Status::OK)
+                .with_header(HeaderName::CONTENT_TYPE, "text/rdf-patch")
+                .unwrap()
+                .with_body(patch))
+        }
+        ("/history/snapshot", "GET") => {
+            if store
+                .history_version()
+                .map_err(internal_server_error)?
+                .is_none()
+            {
+                return Err((Status::NOT_FOUND, "The store history is not enabled".into()));
+            }
+            Ok(Response::builder(Status::OK)
+                .with_header(HeaderName::CONTENT_TYPE, "text/rdf-patch")
+                .unwrap()
+                .with_body(serialize_snapshot(&store).map_err(internal_server_error)?))
+        }
+        ("/metrics" | "/subscribe" | "/history" | "/history/snapshot", _) => {
+            Ok(method_not_allowed(request, "GET"))
+        }
+        ("/backups", "GET") => {
+            let backups = enabled_backups(options)?
+                .list()
+                .map_err(internal_server_error)?;
+            Ok(Response::builder(Status::OK)
+                .with_header(HeaderName::CONTENT_TYPE, "application/json")
+                .unwrap()
+                .with_body(Value::from(backups).to_string()))
+        }
+        ("/backups", "POST") => {
+            let name = enabled_backups(options)?.start(store)?;
+            Ok(Response::builder(Status::ACCEPTED)
+                .with_header(HeaderName::CONTENT_TYPE, "text/plain")
+                .unwrap()
+                .with_body(name))
+        }
+        ("/datasets", "GET") => {
+            let datasets = enabled_datasets(options)?
+                .list()
+                .into_iter()
+                .map(|(name, read_only)| json!({ "name": name, "read-only": read_only }))
+                .collect::<Vec<_>>();
+            Ok(Response::builder(Status::OK)
+                .with_header(HeaderName::CONTENT_TYPE, "application/json")
+                .unwrap()
+                .with_body(Value::from(datasets).to_string()))
+        }
+        ("/datasets", "POST") => {
+            if read_only {
+                return Err(the_server_is_read_only());
+            }
+            let name = url_query_parameter(request, "name")
+                .ok_or_else(|| bad_request("The name parameter is not set"))?
+                .into_owned();
+            let dataset_read_only = match url_query_parameter(request, "read-only").as_deref() {
+                None | Some("false") => false,
+                Some("true") => true,
+                Some(value) => {
+                    return Err(bad_request(format!(
+                        "Invalid read-only parameter {value}, expecting true or false"
+                    )))
+                }
+            };
+            enabled_datasets(options)?.create(&name, dataset_read_only)?;
+            Ok(Response::builder(Status::CREATED)
+                .with_header(HeaderName::LOCATION, format!("/datasets/{name}"))
+                .map_err(internal_server_error)?
+                .build())
+        }
+        ("/backups" | "/datasets", _) => Ok(method_not_allowed(request, "GET, POST")),
+        (path, "GET") if path.starts_with("/datasets/") => {
+            let name = &path["/datasets/".len()..];
+            let dataset = enabled_datasets(options)?.get(name)?;
+            Ok(Response::builder(Status::OK)
+                .with_header(HeaderName::CONTENT_TYPE, "application/json")
+                .unwrap()
+                .with_body(json!({ "name": name, "read-only": dataset.read_only }).to_string()))
+        }
+        (path, "DELETE") if path.starts_with("/datasets/") => {
+            if read_only {
+                return Err(the_server_is_read_only());
+            }
+            enabled_datasets(options)?.delete(&path["/datasets/".len()..])?;
+            Ok(Response::builder(Status::NO_CONTENT).build())
+        }
+        (path, _) if path.starts_with("/datasets/") => {
+            Ok(method_not_allowed(request, "GET, DELETE"))
+        }
         ("/query", "GET") => {
             let query = url_query(request);
             if query.is_empty() {
                 let format = rdf_content_negotiation(request)?;
-                let description =
-                    generate_service_description(format, EndpointKind::Query, union_default_graph);
+                let description = generate_service_description(
+                    format,
+                    EndpointKind::Query,
+                    union_default_graph,
+                    &options.base_query_options(),
+                );
                 Ok(Response::builder(Status::OK)
                     .with_header(HeaderName::CONTENT_TYPE, format.media_type())
                     .map_err(internal_server_error)?
@@ -791,7 +2163,8 @@ fn handle_request(
                     &[url_query(request)],
                     None,
                     request,
-                    union_default_graph,
+                    options,
+                    issuer,
                 )
             }
         }
@@ -805,7 +2178,8 @@ fn handle_request(
                     &[url_query(request)],
                     Some(query),
                     request,
-                    union_default_graph,
+                    options,
+                    issuer,
                 )
             } else if content_type == "application/x-www-form-urlencoded" {
                 let buffer = limited_body(request)?;
@@ -814,19 +2188,29 @@ fn handle_request(
                     &[url_query(request), &buffer],
                     None,
                     request,
-                    union_default_graph,
+                    options,
+                    issuer,
                 )
             } else {
                 Err(unsupported_media_type(&content_type))
             }
         }
+        ("/query", _) => Ok(method_not_allowed(request, "GET, POST")),
         ("/update", "GET") => {
             if read_only {
                 return Err(the_server_is_read_only());
             }
+            if url_query_parameter(request, "update").is_some() {
+                // The SPARQL protocol forbids to execute updates using GET
+                return Ok(method_not_allowed(request, "POST"));
+            }
             let format = rdf_content_negotiation(request)?;
-            let description =
-                generate_service_description(format, EndpointKind::Update, union_default_graph);
+            let description = generate_service_description(
+                format,
+                EndpointKind::Update,
+                union_default_graph,
+                &options.base_query_options(),
+            );
             Ok(Response::builder(Status::OK)
                 .with_header(HeaderName::CONTENT_TYPE, format.media_type())
                 .map_err(internal_server_error)?
@@ -845,7 +2229,8 @@ fn handle_request(
                     &[url_query(request)],
                     Some(update),
                     request,
-                    union_default_graph,
+                    options,
+                    issuer,
                 )
             } else if content_type == "application/x-www-form-urlencoded" {
                 let buffer = limited_body(request)?;
@@ -854,16 +2239,65 @@ fn handle_request(
                     &[url_query(request), &buffer],
                     None,
                     request,
-                    union_default_graph,
+                    options,
+                    issuer,
                 )
             } else {
                 Err(unsupported_media_type(&content_type))
             }
         }
+        ("/update", _) => Ok(method_not_allowed(request, "POST")),
+        (path, "GET") if path.starts_with("/queries/") => {
+            evaluate_stored_query(&store, &path["/queries/".len()..], request, options)
+        }
+        (path, _) if path.starts_with("/queries/") => Ok(method_not_allowed(request, "GET")),
+        ("/batch", "POST") => evaluate_sparql_batch(&store, request, options, access.as_ref()),
+        ("/transactions", "POST") => {
+            if read_only {
+                return Err(the_server_is_read_only());
+            }
+            let id = options.transactions.begin(store, issuer.cloned());
+            Ok(Response::builder(Status::CREATED)
+                .with_header(HeaderName::LOCATION, format!("/transactions/{id}"))
+                .map_err(internal_server_error)?
+                .with_header(HeaderName::CONTENT_TYPE, "text/plain")
+                .unwrap()
+                .with_body(id))
+        }
+        ("/batch" | "/transactions", _) => Ok(method_not_allowed(request, "POST")),
+        (path, "POST") if path.starts_with("/transactions/") => {
+            match path
+                .strip_prefix("/transactions/")
+                .and_then(|path| path.split_once('/'))
+            {
+                Some((id, "commit")) => options.transactions.commit(id, issuer)?,
+                Some((id, "abort")) => options.transactions.abort(id, issuer)?,
+                _ => {
+                    return Err((
+                        Status::NOT_FOUND,
+                        format!("{path} is not a transaction commit or abort endpoint"),
+                    ))
+                }
+            }
+            Ok(Response::builder(Status::NO_CONTENT).build())
+        }
+        (path, _) if path.starts_with("/transactions/") => Ok(method_not_allowed(request, "POST")),
+        (path, _)
+            if path.starts_with("/store")
+                && url_query_parameter(request, "transaction").is_some() =>
+        {
+            evaluate_graph_store_operation_in_transaction(request, options, issuer)
+        }
         (path, "GET") if path.starts_with("/store") => {
-            if let Some(target) = store_target(request)? {
+            if let Some(target) = store_target(request, path)? {
                 assert_that_graph_exists(&store, &target)?;
                 let format = rdf_content_negotiation(request)?;
+                let validators = GraphValidators::new(&store, &target)?;
+                if validators.is_not_modified(request, format) {
+                    let mut response = Response::builder(Status::NOT_MODIFIED).build();
+                    validators.add_to(&mut response, format)?;
+                    return Ok(response);
+                }
 
                 let quads = store.quads_for_pattern(
                     None,
@@ -871,7 +2305,7 @@ fn handle_request(
                     None,
                     Some(GraphName::from(target).as_ref()),
                 );
-                ReadForWrite::build_response(
+                let mut response = ReadForWrite::build_response(
                     move |w| Ok((RdfSerializer::from_format(format).for_writer(w), quads)),
                     |(mut serializer, mut quads)| {
                         Ok(if let Some(q) = quads.next() {
@@ -883,7 +2317,9 @@ fn handle_request(
                         })
                     },
                     format.media_type(),
-                )
+                )?;
+                validators.add_to(&mut response, format)?;
+                Ok(response)
             } else {
                 let format = rdf_content_negotiation(request)?;
                 if !format.supports_datasets() {
@@ -917,9 +2353,14 @@ fn handle_request(
             }
             let content_type =
                 content_type(request).ok_or_else(|| bad_request("No Content-Type given"))?;
-            if let Some(target) = store_target(request)? {
+            if let Some(target) = store_target(request, path)? {
                 let format = RdfFormat::from_media_type(&content_type)
                     .ok_or_else(|| unsupported_media_type(&content_type))?;
+                let _guard = options
+                    .graph_store_lock
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner);
+                check_graph_write_preconditions(&store, request, &target)?;
                 let new = !match &target {
                     NamedGraphName::NamedNode(target) => {
                         if store
@@ -942,7 +2383,7 @@ fn handle_request(
                         true
                     }
                 };
-                web_load_graph(&store, request, format, &GraphName::from(target))?;
+                web_load_graph(&store, request, format, &GraphName::from(target), options)?;
                 Ok(Response::builder(if new {
                     Status::CREATED
                 } else {
@@ -953,7 +2394,7 @@ fn handle_request(
                 let format = RdfFormat::from_media_type(&content_type)
                     .ok_or_else(|| unsupported_media_type(&content_type))?;
                 store.clear().map_err(internal_server_error)?;
-                web_load_dataset(&store, request, format)?;
+                web_load_dataset(&store, request, format, options)?;
                 Ok(Response::builder(Status::NO_CONTENT).build())
             }
         }
@@ -961,7 +2402,12 @@ fn handle_request(
             if read_only {
                 return Err(the_server_is_read_only());
             }
-            if let Some(target) = store_target(request)? {
+            if let Some(target) = store_target(request, path)? {
+                let _guard = options
+                    .graph_store_lock
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner);
+                check_graph_write_preconditions(&store, request, &target)?;
                 match target {
                     NamedGraphName::DefaultGraph => store
                         .clear_graph(GraphNameRef::DefaultGraph)
@@ -993,11 +2439,16 @@ fn handle_request(
             }
             let content_type =
                 content_type(request).ok_or_else(|| bad_request("No Content-Type given"))?;
-            if let Some(target) = store_target(request)? {
+            if let Some(target) = store_target(request, path)? {
                 let format = RdfFormat::from_media_type(&content_type)
                     .ok_or_else(|| unsupported_media_type(&content_type))?;
-                let new = assert_that_graph_exists(&store, &target).is_ok();
-                web_load_graph(&store, request, format, &GraphName::from(target))?;
+                let _guard = options
+                    .graph_store_lock
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner);
+                check_graph_write_preconditions(&store, request, &target)?;
+                let new = assert_that_graph_exists(&store, &target).is_err();
+                web_load_graph(&store, request, format, &GraphName::from(target), options)?;
                 Ok(Response::builder(if new {
                     Status::CREATED
                 } else {
@@ -1008,12 +2459,12 @@ fn handle_request(
                 let format = RdfFormat::from_media_type(&content_type)
                     .ok_or_else(|| unsupported_media_type(&content_type))?;
                 if format.supports_datasets() {
-                    web_load_dataset(&store, request, format)?;
+                    web_load_dataset(&store, request, format, options)?;
                     Ok(Response::builder(Status::NO_CONTENT).build())
                 } else {
                     let graph =
                         resolve_with_base(request, &format!("/store/{:x}", random::<u128>()))?;
-                    web_load_graph(&store, request, format, &graph.clone().into())?;
+                    web_load_graph(&store, request, format, &graph.clone().into(), options)?;
                     Ok(Response::builder(Status::CREATED)
                         .with_header(HeaderName::LOCATION, graph.into_string())
                         .unwrap()
@@ -1021,11 +2472,48 @@ fn handle_request(
                 }
             }
         }
-        (path, "HEAD") if path.starts_with("/store") => {
-            if let Some(target) = store_target(request)? {
-                assert_that_graph_exists(&store, &target)?;
+        (path, "PATCH") if path.starts_with("/store") => {
+            if read_only {
+                return Err(the_server_is_read_only());
+            }
+            let content_type =
+                content_type(request).ok_or_else(|| bad_request("No Content-Type given"))?;
+            if content_type != "text/rdf-patch" && content_type != "application/rdf-patch" {
+                return Err(unsupported_media_type(&content_type));
             }
-            Ok(Response::builder(Status::OK).build())
+            let target = store_target(request, path)?;
+            let operations =
+                parse_rdf_patch(&limited_string_body(request)?).map_err(bad_request)?;
+            let _guard = options
+                .graph_store_lock
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            if let Some(target) = &target {
+                check_graph_write_preconditions(&store, request, target)?;
+            }
+            let new = web_apply_rdf_patch(&store, target.as_ref(), operations)?;
+            Ok(Response::builder(if new {
+                Status::CREATED
+            } else {
+                Status::NO_CONTENT
+            })
+            .build())
+        }
+        (path, "HEAD") if path.starts_with("/store") => {
+            let Some(target) = store_target(request, path)? else {
+                return Ok(Response::builder(Status::OK).build());
+            };
+            assert_that_graph_exists(&store, &target)?;
+            let format = rdf_content_negotiation(request)?;
+            let validators = GraphValidators::new(&store, &target)?;
+            let mut response = Response::builder(if validators.is_not_modified(request, format) {
+                Status::NOT_MODIFIED
+            } else {
+                Status::OK
+            })
+            .build();
+            validators.add_to(&mut response, format)?;
+            Ok(response)
         }
         _ => Err((
             Status::NOT_FOUND,
@@ -1038,6 +2526,27 @@ fn handle_request(
     }
 }
 
+/// The datasets managed with the `/datasets` API
+fn enabled_datasets(options: &ServerOptions) -> Result<&DatasetRegistry, HttpError> {
+    options.datasets.as_ref().ok_or_else(|| {
+        (
+            Status::NOT_FOUND,
+            "The datasets are not enabled on this server, use the --datasets-location option"
+                .into(),
+        )
+    })
+}
+
+/// The backups triggered with `/backups`
+fn enabled_backups(options: &ServerOptions) -> Result<&BackupManager, HttpError> {
+    options.backups.as_ref().ok_or_else(|| {
+        (
+            Status::NOT_FOUND,
+            "The backups are not enabled on this server, use the --backup-location option".into(),
+        )
+    })
+}
+
 fn base_url(request: &Request) -> String {
     let mut url = request.url().clone();
     url.set_query(None);
@@ -1065,6 +2574,61 @@ fn url_query_parameter<'a>(request: &'a Request, param: &str) -> Option<Cow<'a,
         .map(|(_, v)| v)
 }
 
+/// Parses an `application/x-www-form-urlencoded` payload
+///
+/// Contrary to `url::form_urlencoded::parse`, invalid UTF-8 sequences are rejected instead of being replaced.
+fn parse_form_urlencoded(encoded: &[u8]) -> Result<Vec<(String, String)>, HttpError> {
+    encoded
+        .split(|c| *c == b'&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = if let Some(position) = pair.iter().position(|c| *c == b'=') {
+                (&pair[..position], &pair[position + 1..])
+            } else {
+                (pair, [].as_slice())
+            };
+            Ok((
+                percent_decode_form_component(key)?,
+                percent_decode_form_component(value)?,
+            ))
+        })
+        .collect()
+}
+
+fn percent_decode_form_component(input: &[u8]) -> Result<String, HttpError> {
+    fn hex_value(c: u8) -> Option<u8> {
+        match c {
+            b'0'..=b'9' => Some(c - b'0'),
+            b'a'..=b'f' => Some(c - b'a' + 10),
+            b'A'..=b'F' => Some(c - b'A' + 10),
+            _ => None,
+        }
+    }
+
+    let mut output = Vec::with_capacity(input.len());
+    let mut i = 0;
+    while i < input.len() {
+        match input[i] {
+            b'+' => output.push(b' '),
+            b'%' => {
+                if let (Some(high), Some(low)) = (
+                    input.get(i + 1).copied().and_then(hex_value),
+                    input.get(i + 2).copied().and_then(hex_value),
+                ) {
+                    output.push(high * 16 + low);
+                    i += 2;
+                } else {
+                    output.push(b'%');
+                }
+            }
+            c => output.push(c),
+        }
+        i += 1;
+    }
+    String::from_utf8(output)
+        .map_err(|e| bad_request(format!("Invalid UTF-8 in the form parameters: {e}")))
+}
+
 fn limited_string_body(request: &mut Request) -> Result<String, HttpError> {
     String::from_utf8(limited_body(request)?)
         .map_err(|e| bad_request(format!("Invalid UTF-8 body: {e}")))
@@ -1106,50 +2670,207 @@ fn configure_and_evaluate_sparql_query(
     encoded: &[&[u8]],
     mut query: Option<String>,
     request: &Request,
-    default_use_default_graph_as_union: bool,
+    options: &ServerOptions,
+    issuer: Option<&Principal>,
 ) -> Result<Response, HttpError> {
     let mut default_graph_uris = Vec::new();
     let mut named_graph_uris = Vec::new();
     let mut use_default_graph_as_union = false;
+    let start = Instant::now();
+    let mut time_budget = None;
+    let mut continuation = None;
+    let mut cursor = None;
+    let mut page_size = None;
+    let mut explain = None;
+    let mut transaction = None;
     for encoded in encoded {
-        for (k, v) in form_urlencoded::parse(encoded) {
-            match k.as_ref() {
+        for (k, v) in parse_form_urlencoded(encoded)? {
+            match k.as_str() {
                 "query" => {
                     if query.is_some() {
                         return Err(bad_request("Multiple query parameters provided"));
                     }
-                    query = Some(v.into_owned())
+                    query = Some(v)
                 }
-                "default-graph-uri" => default_graph_uris.push(v.into_owned()),
+                "default-graph-uri" => default_graph_uris.push(v),
                 "union-default-graph" => use_default_graph_as_union = true,
-                "named-graph-uri" => named_graph_uris.push(v.into_owned()),
-                _ => (),
-            }
-        }
-    }
-    if default_graph_uris.is_empty() && named_graph_uris.is_empty() {
-        use_default_graph_as_union |= default_use_default_graph_as_union;
+                "named-graph-uri" => named_graph_uris.push(v),
+                "time-budget" => time_budget = Some(v),
+                "continuation" => continuation = Some(v),
+                "cursor" => cursor = Some(v),
+                "page-size" => page_size = Some(v),
+                "transaction" => transaction = Some(v),
+                "explain" => {
+                    explain = Some(match v.as_str() {
+                        "true" => ExplainMode::Plan,
+                        "analyze" => ExplainMode::Analyze,
+                        _ => {
+                            return Err(bad_request(format!(
+                                "The explain parameter must be 'true' or 'analyze', found '{v}'"
+                            )))
+                        }
+                    })
+                }
+                "update" => {
+                    return Err(bad_request(
+                        "SPARQL updates must be sent to the update endpoint",
+                    ))
+                }
+                _ => (),
+            }
+        }
+    }
+    if default_graph_uris.is_empty() && named_graph_uris.is_empty() {
+        use_default_graph_as_union |= options.union_default_graph;
+    }
+    let page_size = page_size
+        .map(|page_size| {
+            page_size
+                .parse::<usize>()
+                .ok()
+                .filter(|page_size| *page_size > 0)
+                .ok_or_else(|| bad_request(format!("Invalid page size: {page_size}")))
+        })
+        .transpose()?;
+    if let Some(cursor) = cursor {
+        if query.is_some() || time_budget.is_some() || explain.is_some() || transaction.is_some() {
+            return Err(bad_request(
+                "The cursor parameter should not be set with the query, time-budget, explain or transaction parameters",
+            ));
+        }
+        let page = options.cursors.next(&cursor, page_size)?;
+        return cursor_page_response(page, Vec::new(), start, None, request, options);
     }
     let query = query.ok_or_else(|| bad_request("You should set the 'query' parameter"))?;
+    let time_budget = match (time_budget, continuation) {
+        (Some(time_budget), continuation) => Some(TimeBudget::new(
+            &query,
+            &time_budget,
+            continuation.as_deref(),
+        )?),
+        (None, Some(_)) => {
+            return Err(bad_request(
+                "The continuation parameter requires the time-budget parameter",
+            ))
+        }
+        (None, None) => None,
+    };
+    if explain.is_none() && accepts_explanation(request)? {
+        explain = Some(ExplainMode::Plan);
+    }
+    if explain.is_some() && time_budget.is_some() {
+        return Err(bad_request(
+            "The explain and time-budget parameters should not be set at the same time",
+        ));
+    }
+    if page_size.is_some() && (explain.is_some() || time_budget.is_some()) {
+        return Err(bad_request(
+            "The page-size parameter should not be set with the explain or time-budget parameters",
+        ));
+    }
+    if transaction.is_some() && (page_size.is_some() || explain.is_some() || time_budget.is_some())
+    {
+        return Err(bad_request(
+            "The transaction parameter should not be set with the page-size, explain or time-budget parameters",
+        ));
+    }
     evaluate_sparql_query(
         store,
         &query,
+        None,
         use_default_graph_as_union,
         default_graph_uris,
         named_graph_uris,
+        time_budget.as_ref(),
+        page_size,
+        explain,
+        transaction,
+        request,
+        options,
+        issuer,
+    )
+}
+
+/// Evaluates a stored query with the variables substituted by the URL query parameters
+fn evaluate_stored_query(
+    store: &Store,
+    name: &str,
+    request: &Request,
+    options: &ServerOptions,
+) -> Result<Response, HttpError> {
+    let stored_query = options.stored_queries.get(name)?;
+    let substitutions = stored_query.substitutions(parse_form_urlencoded(url_query(request))?)?;
+    let (variables, values): (Vec<_>, Vec<_>) = substitutions
+        .into_iter()
+        .map(|(variable, value)| (variable, Some(value)))
+        .unzip();
+    evaluate_sparql_query(
+        store,
+        stored_query.query(),
+        Some(QuerySolution::from((variables, values))),
+        options.union_default_graph,
+        Vec::new(),
+        Vec::new(),
+        None,
+        None,
+        None,
+        None,
         request,
+        options,
+        None,
     )
 }
 
+/// How a query explanation is requested through the SPARQL protocol
+#[derive(Clone, Copy)]
+enum ExplainMode {
+    /// Returns the query plan without evaluating the query
+    Plan,
+    /// Evaluates the query and returns the query plan with the evaluation statistics
+    Analyze,
+}
+
+/// Checks if the Accept header explicitly requests a query explanation
+fn accepts_explanation(request: &Request) -> Result<bool, HttpError> {
+    let Some(header) = request.header(&HeaderName::ACCEPT) else {
+        return Ok(false);
+    };
+    let header = header
+        .to_str()
+        .map_err(|_| bad_request("The Accept header should be a valid ASCII string"))?;
+    Ok(header.split(',').any(|possible| {
+        possible
+            .split_once(';')
+            .map_or(possible, |(media_type, _)| media_type)
+            .trim()
+            .eq_ignore_ascii_case(EXPLANATION_MEDIA_TYPE)
+    }))
+}
+
+#[allow(clippy::too_many_arguments)]
 fn evaluate_sparql_query(
     store: &Store,
     query: &str,
+    bindings: Option<QuerySolution>,
     use_default_graph_as_union: bool,
     default_graph_uris: Vec<String>,
     named_graph_uris: Vec<String>,
+    time_budget: Option<&TimeBudget>,
+    page_size: Option<usize>,
+    explain: Option<ExplainMode>,
+    transaction: Option<String>,
     request: &Request,
+    options: &ServerOptions,
+    issuer: Option<&Principal>,
 ) -> Result<Response, HttpError> {
+    let start = Instant::now();
     let mut query = Query::parse(query, Some(&base_url(request))).map_err(bad_request)?;
+    if let Some(bindings) = bindings {
+        // The bindings are joined below the projection so the bound variables do not need to be selected
+        query = query
+            .with_initial_bindings([bindings])
+            .map_err(bad_request)?;
+    }
 
     if use_default_graph_as_union {
         if !default_graph_uris.is_empty() || !named_graph_uris.is_empty() {
@@ -1175,26 +2896,111 @@ fn evaluate_sparql_query(
         );
     }
 
-    let results = store
-        .query_opt(query, default_query_options())
-        .map_err(internal_server_error)?;
-    match results {
+    let query_text = options
+        .request_log
+        .needs_query_text()
+        .then(|| query.to_string());
+    let warnings = query.warnings();
+    if let Some(transaction) = transaction {
+        let mut response = evaluate_sparql_query_in_transaction(
+            &transaction,
+            query,
+            start,
+            query_text,
+            request,
+            options,
+            issuer,
+        )?;
+        add_query_warnings(&mut response, warnings)?;
+        return Ok(response);
+    }
+    if let Some(page_size) = page_size {
+        let page = options.cursors.open(
+            {
+                let store = store.clone();
+                let options = options.clone();
+                move || {
+                    let guard = options.limits.start();
+                    let results = store
+                        .query_opt(query, options.query_options(&guard))
+                        .map_err(|e| guard.evaluation_error(e))?;
+                    Ok((results, guard))
+                }
+            },
+            page_size,
+        )?;
+        return cursor_page_response(page, warnings, start, query_text, request, options);
+    }
+    let guard = options.limits.start();
+    let query_options = options.query_options(&guard);
+    if let Some(explain) = explain {
+        return explain_sparql_query(store, query, query_options, explain, &guard);
+    }
+    let (results, explanation) = if options.request_log.slow_query_threshold.is_some() {
+        let (results, explanation) = store
+            .explain_query_opt(query, query_options, false)
+            .map_err(|e| guard.evaluation_error(e))?;
+        (results, Some(explanation))
+    } else {
+        (store.query_opt(query, query_options), None)
+    };
+    let results = results.map_err(|e| guard.evaluation_error(e))?;
+    let mut response = match results {
         QueryResults::Solutions(solutions) => {
             let format = query_results_content_negotiation(request)?;
+            let variables = solutions.variables().to_vec();
+            if let Some(time_budget) = time_budget {
+                let (solutions, continuation) = time_budget.collect(solutions, &guard)?;
+                let mut serializer = QueryResultsSerializer::from_format(format)
+                    .serialize_solutions_to_writer(Vec::new(), variables)
+                    .map_err(internal_server_error)?;
+                for solution in &solutions {
+                    serializer
+                        .serialize(solution)
+                        .map_err(internal_server_error)?;
+                }
+                let body = serializer.finish().map_err(internal_server_error)?;
+                QueryTracker {
+                    metrics: Arc::clone(&options.metrics),
+                    log_entry: options
+                        .request_log
+                        .start_query(start, query_text, explanation),
+                    guard,
+                }
+                .finish(solutions.len().try_into().unwrap_or(u64::MAX));
+                return partial_results_response(
+                    body,
+                    format.media_type(),
+                    "Oxigraph-Continuation",
+                    continuation,
+                    warnings,
+                );
+            }
+            let solutions = evaluate_first_result(solutions, &guard)?;
+            let tracker = QueryTracker {
+                metrics: Arc::clone(&options.metrics),
+                log_entry: options
+                    .request_log
+                    .start_query(start, query_text, explanation),
+                guard,
+            };
             ReadForWrite::build_response(
                 move |w| {
                     Ok((
                         QueryResultsSerializer::from_format(format)
-                            .serialize_solutions_to_writer(w, solutions.variables().to_vec())?,
+                            .serialize_solutions_to_writer(w, variables)?,
                         solutions,
+                        0,
+                        tracker,
                     ))
                 },
-                |(mut serializer, mut solutions)| {
+                |(mut serializer, mut solutions, count, tracker)| {
                     Ok(if let Some(solution) = solutions.next() {
-                        serializer.serialize(&solution?)?;
-                        Some((serializer, solutions))
+                        serializer.serialize(&tracker.check_result(solution, count + 1)?)?;
+                        Some((serializer, solutions, count + 1, tracker))
                     } else {
                         serializer.finish()?;
+                        tracker.finish(count);
                         None
                     })
                 },
@@ -1202,6 +3008,14 @@ fn evaluate_sparql_query(
             )
         }
         QueryResults::Boolean(result) => {
+            QueryTracker {
+                metrics: Arc::clone(&options.metrics),
+                log_entry: options
+                    .request_log
+                    .start_query(start, query_text, explanation),
+                guard,
+            }
+            .finish(1);
             let format = query_results_content_negotiation(request)?;
             let mut body = Vec::new();
             QueryResultsSerializer::from_format(format)
@@ -1214,21 +3028,513 @@ fn evaluate_sparql_query(
         }
         QueryResults::Graph(triples) => {
             let format = rdf_content_negotiation(request)?;
+            if let Some(time_budget) = time_budget {
+                let (triples, continuation) = time_budget.collect(triples, &guard)?;
+                let mut serializer = RdfSerializer::from_format(format).for_writer(Vec::new());
+                for triple in &triples {
+                    serializer
+                        .serialize_triple(triple)
+                        .map_err(internal_server_error)?;
+                }
+                let body = serializer.finish().map_err(internal_server_error)?;
+                QueryTracker {
+                    metrics: Arc::clone(&options.metrics),
+                    log_entry: options
+                        .request_log
+                        .start_query(start, query_text, explanation),
+                    guard,
+                }
+                .finish(triples.len().try_into().unwrap_or(u64::MAX));
+                return partial_results_response(
+                    body,
+                    format.media_type(),
+                    "Oxigraph-Continuation",
+                    continuation,
+                    warnings,
+                );
+            }
+            let triples = evaluate_first_result(triples, &guard)?;
+            let tracker = QueryTracker {
+                metrics: Arc::clone(&options.metrics),
+                log_entry: options
+                    .request_log
+                    .start_query(start, query_text, explanation),
+                guard,
+            };
             ReadForWrite::build_response(
-                move |w| Ok((RdfSerializer::from_format(format).for_writer(w), triples)),
-                |(mut serializer, mut triples)| {
+                move |w| {
+                    Ok((
+                        RdfSerializer::from_format(format).for_writer(w),
+                        triples,
+                        0,
+                        tracker,
+                    ))
+                },
+                |(mut serializer, mut triples, count, tracker)| {
                     Ok(if let Some(t) = triples.next() {
-                        serializer.serialize_triple(&t?)?;
-                        Some((serializer, triples))
+                        serializer.serialize_triple(&tracker.check_result(t, count + 1)?)?;
+                        Some((serializer, triples, count + 1, tracker))
                     } else {
                         serializer.finish()?;
+                        tracker.finish(count);
                         None
                     })
                 },
                 format.media_type(),
             )
         }
+    }?;
+    add_query_warnings(&mut response, warnings)?;
+    Ok(response)
+}
+
+fn explain_sparql_query(
+    store: &Store,
+    query: Query,
+    query_options: QueryOptions,
+    explain: ExplainMode,
+    guard: &EvaluationGuard,
+) -> Result<Response, HttpError> {
+    let with_stats = matches!(explain, ExplainMode::Analyze);
+    let (results, explanation) = store
+        .explain_query_opt(query, query_options, with_stats)
+        .map_err(|e| guard.evaluation_error(e))?;
+    if with_stats {
+        // The statistics are only complete once all the results have been computed
+        match results.map_err(|e| guard.evaluation_error(e))? {
+            QueryResults::Solutions(solutions) => {
+                for solution in solutions {
+                    solution.map_err(|e| guard.evaluation_error(e))?;
+                }
+            }
+            QueryResults::Boolean(_) => (),
+            QueryResults::Graph(triples) => {
+                for triple in triples {
+                    triple.map_err(|e| guard.evaluation_error(e))?;
+                }
+            }
+        }
+    } else {
+        drop(results);
+    }
+    let mut body = Vec::new();
+    explanation
+        .write_in_text(&mut body)
+        .map_err(internal_server_error)?;
+    Ok(Response::builder(Status::OK)
+        .with_header(HeaderName::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .unwrap()
+        .with_body(body))
+}
+
+/// Evaluates a query inside of a transaction opened with `/transactions`
+///
+/// The query sees the changes done by the transaction. Its results are fully serialized inside of the store transaction.
+fn evaluate_sparql_query_in_transaction(
+    transaction: &str,
+    query: Query,
+    start: Instant,
+    query_text: Option<String>,
+    request: &Request,
+    options: &ServerOptions,
+    issuer: Option<&Principal>,
+) -> Result<Response, HttpError> {
+    // The result kind is only known after the evaluation
+    let results_format = query_results_content_negotiation(request);
+    let graph_format = rdf_content_negotiation(request);
+    let operation_options = options.clone();
+    let operation = Operation::Read(Box::new(move |transaction| {
+        let options = operation_options;
+        let guard = options.limits.start();
+        let results = transaction
+            .query_opt(query, options.query_options(&guard))
+            .map_err(|e| guard.evaluation_error(e))?;
+        let (body, media_type, count) = match results {
+            QueryResults::Solutions(solutions) => {
+                let format = results_format?;
+                let mut serializer = QueryResultsSerializer::from_format(format)
+                    .serialize_solutions_to_writer(Vec::new(), solutions.variables().to_vec())
+                    .map_err(internal_server_error)?;
+                let mut count = 0;
+                for solution in solutions {
+                    let solution = solution.map_err(|e| guard.evaluation_error(e))?;
+                    count += 1;
+                    guard.check_result_count(count)?;
+                    serializer
+                        .serialize(&solution)
+                        .map_err(internal_server_error)?;
+                }
+                (
+                    serializer.finish().map_err(internal_server_error)?,
+                    format.media_type(),
+                    count,
+                )
+            }
+            QueryResults::Boolean(value) => {
+                let format = results_format?;
+                let mut body = Vec::new();
+                QueryResultsSerializer::from_format(format)
+                    .serialize_boolean_to_writer(&mut body, value)
+                    .map_err(internal_server_error)?;
+                (body, format.media_type(), 1)
+            }
+            QueryResults::Graph(triples) => {
+                let format = graph_format?;
+                let mut serializer = RdfSerializer::from_format(format).for_writer(Vec::new());
+                let mut count = 0;
+                for triple in triples {
+                    let triple = triple.map_err(|e| guard.evaluation_error(e))?;
+                    count += 1;
+                    guard.check_result_count(count)?;
+                    serializer
+                        .serialize_triple(&triple)
+                        .map_err(internal_server_error)?;
+                }
+                (
+                    serializer.finish().map_err(internal_server_error)?,
+                    format.media_type(),
+                    count,
+                )
+            }
+        };
+        options.metrics.observe_query_results(count);
+        options
+            .request_log
+            .start_query(start, query_text, None)
+            .finish(count);
+        Ok(OperationResponse {
+            status: Status::OK,
+            body: Some((media_type, body)),
+        })
+    }));
+    Ok(options
+        .transactions
+        .execute(transaction, issuer, operation)?
+        .into())
+}
+
+fn add_query_warnings(
+    response: &mut Response,
+    warnings: Vec<QueryWarning>,
+) -> Result<(), HttpError> {
+    for warning in warnings {
+        // Header values must be ASCII, the variable names are escaped
+        response.headers_mut().append(
+            HeaderName::from_str("Oxigraph-Query-Warning").map_err(internal_server_error)?,
+            HeaderValue::from_str(&warning.to_string().escape_default().to_string())
+                .map_err(internal_server_error)?,
+        );
+    }
+    Ok(())
+}
+
+/// Serializes a page of results returned by a cursor
+fn cursor_page_response(
+    page: Page,
+    warnings: Vec<QueryWarning>,
+    start: Instant,
+    query_text: Option<String>,
+    request: &Request,
+    options: &ServerOptions,
+) -> Result<Response, HttpError> {
+    let (body, media_type, count) = match page.results {
+        PageResults::Solutions {
+            variables,
+            solutions,
+        } => {
+            let format = query_results_content_negotiation(request)?;
+            let mut serializer = QueryResultsSerializer::from_format(format)
+                .serialize_solutions_to_writer(Vec::new(), variables)
+                .map_err(internal_server_error)?;
+            for solution in &solutions {
+                serializer
+                    .serialize(solution)
+                    .map_err(internal_server_error)?;
+            }
+            (
+                serializer.finish().map_err(internal_server_error)?,
+                format.media_type(),
+                solutions.len(),
+            )
+        }
+        PageResults::Boolean(value) => {
+            let format = query_results_content_negotiation(request)?;
+            let mut body = Vec::new();
+            QueryResultsSerializer::from_format(format)
+                .serialize_boolean_to_writer(&mut body, value)
+                .map_err(internal_server_error)?;
+            (body, format.media_type(), 1)
+        }
+        PageResults::Triples(triples) => {
+            let format = rdf_content_negotiation(request)?;
+            let mut serializer = RdfSerializer::from_format(format).for_writer(Vec::new());
+            for triple in &triples {
+                serializer
+                    .serialize_triple(triple)
+                    .map_err(internal_server_error)?;
+            }
+            (
+                serializer.finish().map_err(internal_server_error)?,
+                format.media_type(),
+                triples.len(),
+            )
+        }
+    };
+    let count = count.try_into().unwrap_or(u64::MAX);
+    options.metrics.observe_query_results(count);
+    options
+        .request_log
+        .start_query(start, query_text, None)
+        .finish(count);
+    partial_results_response(body, media_type, "Oxigraph-Cursor", page.cursor, warnings)
+}
+
+/// Builds the response of a time-boxed or paginated evaluation, marking it as partial if some results are missing
+///
+/// The token allowing to get the next results is returned in the `next_header` header.
+fn partial_results_response(
+    body: Vec<u8>,
+    media_type: &'static str,
+    next_header: &str,
+    next: Option<String>,
+    warnings: Vec<QueryWarning>,
+) -> Result<Response, HttpError> {
+    let mut response = Response::builder(Status::OK)
+        .with_header(HeaderName::CONTENT_TYPE, media_type)
+        .map_err(internal_server_error)?
+        .with_header(
+            HeaderName::from_str("Oxigraph-Partial-Results").map_err(internal_server_error)?,
+            if next.is_some() { "true" } else { "false" },
+        )
+        .map_err(internal_server_error)?;
+    if let Some(next) = next {
+        response = response
+            .with_header(
+                HeaderName::from_str(next_header).map_err(internal_server_error)?,
+                next,
+            )
+            .map_err(internal_server_error)?;
+    }
+    let mut response = response.with_body(body);
+    add_query_warnings(&mut response, warnings)?;
+    Ok(response)
+}
+
+/// Evaluates eagerly the first result to be able to return a proper HTTP error if the evaluation fails
+fn evaluate_first_result<T: 'static>(
+    mut results: impl Iterator<Item = Result<T, EvaluationError>> + 'static,
+    guard: &EvaluationGuard,
+) -> Result<impl Iterator<Item = Result<T, EvaluationError>>, HttpError> {
+    let first = results
+        .next()
+        .transpose()
+        .map_err(|e| guard.evaluation_error(e))?;
+    Ok(first.map(Ok).into_iter().chain(results))
+}
+
+/// Enforces the limits of a query while its results are streamed
+/// and records its metrics and log entry once all of them have been returned
+struct QueryTracker {
+    metrics: Arc<Metrics>,
+    log_entry: QueryLogEntry,
+    guard: EvaluationGuard,
+}
+
+impl QueryTracker {
+    fn check_result<T>(&self, result: Result<T, EvaluationError>, count: u64) -> io::Result<T> {
+        let result = result.map_err(|e| io::Error::other(self.guard.evaluation_error(e).1))?;
+        self.guard
+            .check_result_count(count)
+            .map_err(|(_, message)| io::Error::other(message))?;
+        Ok(result)
+    }
+
+    fn finish(self, result_count: u64) {
+        self.metrics.observe_query_results(result_count);
+        self.log_entry.finish(result_count);
+    }
+}
+
+/// Evaluates a query against a store directory or a SPARQL endpoint if the target is an HTTP(S) URL
+fn evaluate_query_on(target: &str, query: Query) -> anyhow::Result<QueryResults> {
+    if !target.starts_with("http://") && !target.starts_with("https://") {
+        return Ok(Store::open_read_only(target)?.query_opt(query, default_query_options())?);
+    }
+    let client = SparqlClient::new()
+        .with_user_agent(concat!("Oxigraph/", env!("CARGO_PKG_VERSION")))?
+        .with_timeout(HTTP_TIMEOUT);
+    Ok(match client.query(target, &query.to_string())? {
+        QueryResponse::Solutions(solutions) => QueryResults::Solutions((*solutions).into()),
+        QueryResponse::Boolean(value) => QueryResults::Boolean(value),
+        QueryResponse::Graph(quads) => {
+            // We load the returned graph into a temporary store to be able to return it as query results
+            let store = Store::new()?;
+            store.extend(quads.collect::<Result<Vec<_>, _>>()?)?;
+            store.query("CONSTRUCT WHERE { ?s ?p ?o }")?
+        }
+    })
+}
+
+/// An operation of a `/batch` request
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+enum BatchOperation {
+    Query(String),
+    Update(String),
+}
+
+/// Evaluates a JSON array of queries and updates in a single transaction.
+///
+/// The result of each query is returned in a JSON array in the same order as the operations.
+/// Query failures are reported in the array but any update failure aborts the full batch.
+fn evaluate_sparql_batch(
+    store: &Store,
+    request: &mut Request,
+    options: &ServerOptions,
+    access: Option<&Access<'_>>,
+) -> Result<Response, HttpError> {
+    let content_type = content_type(request).ok_or_else(|| bad_request("No Content-Type given"))?;
+    if content_type != "application/json" {
+        return Err(unsupported_media_type(&content_type));
     }
+    let operations = serde_json::from_slice::<Vec<BatchOperation>>(&limited_body(request)?)
+        .map_err(bad_request)?;
+    let base_iri = base_url(request);
+    // We parse the updates first to not start the transaction if one of them is invalid
+    let updates = operations
+        .iter()
+        .enumerate()
+        .filter_map(|(i, operation)| {
+            let BatchOperation::Update(update) = operation else {
+                return None;
+            };
+            Some(
+                Update::parse(update, Some(&base_iri))
+                    .map(|mut update| {
+                        if options.union_default_graph {
+                            for using in update.using_datasets_mut() {
+                                if using.is_default_dataset() {
+                                    using.set_default_graph_as_union()
+                                }
+                            }
+                        }
+                        (i, update)
+                    })
+                    .map_err(|e| bad_request(format!("Invalid update in operation {i}: {e}"))),
+            )
+        })
+        .collect::<Result<HashMap<_, _>, _>>()?;
+    let guard = options.limits.start();
+    let evaluate_query =
+        |query: &str, evaluate: &dyn Fn(Query) -> Result<QueryResults, EvaluationError>| -> Value {
+            let mut query = match Query::parse(query, Some(&base_iri)) {
+                Ok(query) => query,
+                Err(e) => return batch_error(bad_request(e)),
+            };
+            if options.union_default_graph && query.dataset().is_default_dataset() {
+                query.dataset_mut().set_default_graph_as_union()
+            }
+            match evaluate(query)
+                .map_err(|e| guard.evaluation_error(e))
+                .and_then(|results| batch_query_results(results).map_err(internal_server_error))
+            {
+                Ok(value) => value,
+                Err(e) => batch_error(e),
+            }
+        };
+    let query_options = || options.query_options(&guard);
+    let results = if updates.is_empty() && options.read_only {
+        // The store can't change so there is no need for a transaction
+        operations
+            .iter()
+            .map(|operation| match operation {
+                BatchOperation::Query(query) => {
+                    evaluate_query(query, &|query| store.query_opt(query, query_options()))
+                }
+                BatchOperation::Update(_) => batch_error(the_server_is_read_only()),
+            })
+            .collect::<Vec<_>>()
+    } else {
+        if options.read_only {
+            return Err(the_server_is_read_only());
+        }
+        if !updates.is_empty() {
+            if let Some(access) = access {
+                access.check(None, AccessLevel::Write)?;
+            }
+        }
+        let failed_operation = Cell::new(0);
+        store
+            .transaction(|mut transaction| {
+                let mut results = Vec::with_capacity(operations.len());
+                for (i, operation) in operations.iter().enumerate() {
+                    results.push(match operation {
+                        BatchOperation::Query(query) => evaluate_query(query, &|query| {
+                            transaction.query_opt(query, query_options())
+                        }),
+                        BatchOperation::Update(_) => {
+                            failed_operation.set(i);
+                            transaction.update_opt(updates[&i].clone(), query_options())?;
+                            json!({ "status": u16::from(Status::NO_CONTENT) })
+                        }
+                    });
+                }
+                Ok(results)
+            })
+            .map_err(|e| {
+                let (status, message) = guard.evaluation_error(e);
+                (
+                    status,
+                    format!(
+                        "The batch has been aborted because operation {} failed: {message}",
+                        failed_operation.get()
+                    ),
+                )
+            })?
+    };
+    Ok(Response::builder(Status::OK)
+        .with_header(HeaderName::CONTENT_TYPE, "application/json")
+        .unwrap()
+        .with_body(Value::Array(results).to_string()))
+}
+
+/// Serializes SELECT and ASK results using the SPARQL JSON results format and CONSTRUCT results using N-Triples
+fn batch_query_results(results: QueryResults) -> io::Result<Value> {
+    Ok(match results {
+        QueryResults::Solutions(solutions) => {
+            let mut serializer = QueryResultsSerializer::from_format(QueryResultsFormat::Json)
+                .serialize_solutions_to_writer(Vec::new(), solutions.variables().to_vec())?;
+            for solution in solutions {
+                serializer.serialize(&solution?)?;
+            }
+            json!({
+                "status": u16::from(Status::OK),
+                "results": serde_json::from_slice::<Value>(&serializer.finish()?)?
+            })
+        }
+        QueryResults::Boolean(result) => {
+            let body = QueryResultsSerializer::from_format(QueryResultsFormat::Json)
+                .serialize_boolean_to_writer(Vec::new(), result)?;
+            json!({
+                "status": u16::from(Status::OK),
+                "results": serde_json::from_slice::<Value>(&body)?
+            })
+        }
+        QueryResults::Graph(triples) => {
+            let mut serializer =
+                RdfSerializer::from_format(RdfFormat::NTriples).for_writer(Vec::new());
+            for triple in triples {
+                serializer.serialize_triple(&triple?)?;
+            }
+            let triples = String::from_utf8(serializer.finish()?)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            json!({ "status": u16::from(Status::OK), "triples": triples })
+        }
+    })
+}
+
+fn batch_error((status, message): HttpError) -> Value {
+    json!({ "status": u16::from(status), "message": message })
 }
 
 fn default_query_options() -> QueryOptions {
@@ -1245,29 +3551,37 @@ fn configure_and_evaluate_sparql_update(
     encoded: &[&[u8]],
     mut update: Option<String>,
     request: &Request,
-    default_use_default_graph_as_union: bool,
+    options: &ServerOptions,
+    issuer: Option<&Principal>,
 ) -> Result<Response, HttpError> {
     let mut use_default_graph_as_union = false;
     let mut default_graph_uris = Vec::new();
     let mut named_graph_uris = Vec::new();
+    let mut transaction = None;
     for encoded in encoded {
-        for (k, v) in form_urlencoded::parse(encoded) {
-            match k.as_ref() {
+        for (k, v) in parse_form_urlencoded(encoded)? {
+            match k.as_str() {
                 "update" => {
                     if update.is_some() {
                         return Err(bad_request("Multiple update parameters provided"));
                     }
-                    update = Some(v.into_owned())
+                    update = Some(v)
                 }
-                "using-graph-uri" => default_graph_uris.push(v.into_owned()),
+                "using-graph-uri" => default_graph_uris.push(v),
                 "using-union-graph" => use_default_graph_as_union = true,
-                "using-named-graph-uri" => named_graph_uris.push(v.into_owned()),
+                "using-named-graph-uri" => named_graph_uris.push(v),
+                "transaction" => transaction = Some(v),
+                "query" => {
+                    return Err(bad_request(
+                        "SPARQL queries must be sent to the query endpoint",
+                    ))
+                }
                 _ => (),
             }
         }
     }
     if default_graph_uris.is_empty() && named_graph_uris.is_empty() {
-        use_default_graph_as_union |= default_use_default_graph_as_union;
+        use_default_graph_as_union |= options.union_default_graph;
     }
     let update = update.ok_or_else(|| bad_request("You should set the 'update' parameter"))?;
     evaluate_sparql_update(
@@ -1276,17 +3590,24 @@ fn configure_and_evaluate_sparql_update(
         use_default_graph_as_union,
         default_graph_uris,
         named_graph_uris,
+        transaction,
         request,
+        options,
+        issuer,
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 fn evaluate_sparql_update(
     store: &Store,
     update: &str,
     use_default_graph_as_union: bool,
     default_graph_uris: Vec<String>,
     named_graph_uris: Vec<String>,
+    transaction: Option<String>,
     request: &Request,
+    options: &ServerOptions,
+    issuer: Option<&Principal>,
 ) -> Result<Response, HttpError> {
     let mut update =
         Update::parse(update, Some(base_url(request).as_str())).map_err(bad_request)?;
@@ -1326,43 +3647,119 @@ fn evaluate_sparql_update(
             using.set_available_named_graphs(named_graph_uris.clone());
         }
     }
+    if let Some(transaction) = transaction {
+        let operation_options = options.clone();
+        let operation = Operation::Write(Box::new(move |transaction| {
+            let guard = operation_options.limits.start();
+            transaction
+                .update_opt(update.clone(), operation_options.query_options(&guard))
+                .map_err(|e| guard.evaluation_error(e))?;
+            Ok(OperationResponse {
+                status: Status::NO_CONTENT,
+                body: None,
+            })
+        }));
+        return Ok(options
+            .transactions
+            .execute(&transaction, issuer, operation)?
+            .into());
+    }
+    let guard = options.limits.start();
     store
-        .update_opt(update, default_query_options())
-        .map_err(internal_server_error)?;
+        .update_opt(update, options.query_options(&guard))
+        .map_err(|e| guard.evaluation_error(e))?;
     Ok(Response::builder(Status::NO_CONTENT).build())
 }
 
-fn store_target(request: &Request) -> Result<Option<NamedGraphName>, HttpError> {
-    if request.url().path() == "/store" {
-        let mut graph = None;
-        let mut default = false;
-        for (k, v) in request.url().query_pairs() {
-            match k.as_ref() {
-                "graph" => graph = Some(v.into_owned()),
-                "default" => default = true,
-                _ => (),
+fn store_target(request: &Request, path: &str) -> Result<Option<NamedGraphName>, HttpError> {
+    if path == "/store" {
+        graph_parameter(request)
+    } else {
+        Ok(Some(NamedGraphName::NamedNode(resolve_with_base(
+            request, "",
+        )?)))
+    }
+}
+
+/// Reads the `graph` and `default` URL query parameters
+fn graph_parameter(request: &Request) -> Result<Option<NamedGraphName>, HttpError> {
+    let mut graph = None;
+    let mut default = false;
+    for (k, v) in request.url().query_pairs() {
+        match k.as_ref() {
+            "graph" => graph = Some(v.into_owned()),
+            "default" => default = true,
+            _ => (),
+        }
+    }
+    if let Some(graph) = graph {
+        if default {
+            Err(bad_request(
+                "Both graph and default parameters should not be set at the same time",
+            ))
+        } else {
+            Ok(Some(NamedGraphName::NamedNode(resolve_with_base(
+                request, &graph,
+            )?)))
+        }
+    } else if default {
+        Ok(Some(NamedGraphName::DefaultGraph))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Reads the quad pattern of a `/subscribe` request from its `subject`, `predicate`, `object`, `graph` and `default` URL query parameters
+fn subscription_pattern(request: &Request) -> Result<QuadPattern, HttpError> {
+    let mut pattern = QuadPattern {
+        graph_name: graph_parameter(request)?.map(GraphName::from),
+        ..QuadPattern::default()
+    };
+    for (k, v) in request.url().query_pairs() {
+        match k.as_ref() {
+            "subject" => {
+                pattern.subject = Some(
+                    Subject::try_from(Term::from_str(&v).map_err(bad_request)?)
+                        .map_err(bad_request)?,
+                )
             }
+            "predicate" => pattern.predicate = Some(NamedNode::from_str(&v).map_err(bad_request)?),
+            "object" => pattern.object = Some(Term::from_str(&v).map_err(bad_request)?),
+            _ => (),
         }
-        if let Some(graph) = graph {
-            if default {
-                Err(bad_request(
-                    "Both graph and default parameters should not be set at the same time",
+    }
+    Ok(pattern)
+}
+
+/// Runs a cheap read on the store to check that it is able to serve requests.
+fn assert_that_store_is_ready(store: &Store, options: &ServerOptions) -> Result<(), HttpError> {
+    store.is_empty().map_err(|e| {
+        (
+            Status::SERVICE_UNAVAILABLE,
+            format!("The store is not able to serve requests: {e}"),
+        )
+    })?;
+    if let Some((status, max_lag)) = &options.replication_lag_check {
+        match status.lag() {
+            Some(lag) if lag <= *max_lag => (),
+            Some(lag) => {
+                return Err((
+                    Status::SERVICE_UNAVAILABLE,
+                    format!(
+                        "The replica has not caught up with the primary for {}s",
+                        lag.as_secs()
+                    ),
+                ))
+            }
+            None => {
+                return Err((
+                    Status::SERVICE_UNAVAILABLE,
+                    "The replica has not caught up with the primary yet".into(),
                 ))
-            } else {
-                Ok(Some(NamedGraphName::NamedNode(resolve_with_base(
-                    request, &graph,
-                )?)))
             }
-        } else if default {
-            Ok(Some(NamedGraphName::DefaultGraph))
-        } else {
-            Ok(None)
         }
-    } else {
-        Ok(Some(NamedGraphName::NamedNode(resolve_with_base(
-            request, "",
-        )?)))
     }
+    Ok(())
 }
 
 fn assert_that_graph_exists(store: &Store, target: &NamedGraphName) -> Result<(), HttpError> {
@@ -1384,25 +3781,211 @@ fn assert_that_graph_exists(store: &Store, target: &NamedGraphName) -> Result<()
     }
 }
 
-#[derive(Eq, PartialEq, Debug, Clone, Hash)]
-enum NamedGraphName {
-    NamedNode(NamedNode),
-    DefaultGraph,
+/// The `ETag` and `Last-Modified` values of a graph used to answer the conditional requests of the graph store protocol
+///
+/// The ETags are strong: they are made of the graph state and of the format of the representation.
+struct GraphValidators {
+    /// Identifies the graph content, built from the graph creation time and its version counter
+    state: String,
+    last_modified: Option<String>,
 }
 
-impl From<NamedGraphName> for GraphName {
-    fn from(graph_name: NamedGraphName) -> Self {
-        match graph_name {
-            NamedGraphName::NamedNode(node) => node.into(),
-            NamedGraphName::DefaultGraph => Self::DefaultGraph,
+impl GraphValidators {
+    fn new(store: &Store, target: &NamedGraphName) -> Result<Self, HttpError> {
+        let metadata = store
+            .graph_metadata(GraphName::from(target.clone()).as_ref())
+            .map_err(internal_server_error)?
+            .unwrap_or_default();
+        Ok(Self {
+            // The creation time distinguishes the graphs removed and created again
+            state: format!(
+                "{}-{}",
+                metadata
+                    .created()
+                    .map(|c| c.to_string())
+                    .unwrap_or_default(),
+                metadata.version()
+            ),
+            last_modified: metadata
+                .modified()
+                .or(metadata.created())
+                .and_then(format_http_date),
+        })
+    }
+
+    /// The ETag of the graph representation in the given format
+    fn etag(&self, format: RdfFormat) -> String {
+        format!("\"{}-{}\"", self.state, format.file_extension())
+    }
+
+    /// Checks the `If-None-Match` and `If-Modified-Since` request headers of a request reading the graph in the given format
+    fn is_not_modified(&self, request: &Request, format: RdfFormat) -> bool {
+        if let Some(if_none_match) = request.header(&HeaderName::IF_NONE_MATCH) {
+            // Weak comparison
+            let etag = self.etag(format);
+            return if_none_match.to_str().is_ok_and(|if_none_match| {
+                if_none_match.trim() == "*"
+                    || if_none_match
+                        .split(',')
+                        .any(|tag| tag.trim().trim_start_matches("W/") == etag)
+            });
         }
+        let Some(last_modified) = self.last_modified.as_deref().and_then(parse_http_date) else {
+            return false;
+        };
+        request
+            .header(&HeaderName::IF_MODIFIED_SINCE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_http_date)
+            .is_some_and(|since| last_modified <= since)
     }
-}
+
+    /// Checks if a `If-Match` or `If-None-Match` header value of a request changing the graph matches its current state
+    ///
+    /// The ETags returned for all the formats match.
+    /// The weak ETags only match if `weak_comparison` is set.
+    fn matches_state(&self, header_value: &str, weak_comparison: bool) -> bool {
+        header_value.trim() == "*"
+            || header_value.split(',').any(|tag| {
+                let tag = tag.trim();
+                let tag = if weak_comparison {
+                    tag.trim_start_matches("W/")
+                } else {
+                    tag
+                };
+                tag.strip_prefix('"')
+                    .and_then(|tag| tag.strip_suffix('"'))
+                    .and_then(|tag| tag.strip_prefix(self.state.as_str()))
+                    .and_then(|tag| tag.strip_prefix('-'))
+                    .is_some_and(|extension| RdfFormat::from_extension(extension).is_some())
+            })
+    }
+
+    fn add_to(&self, response: &mut Response, format: RdfFormat) -> Result<(), HttpError> {
+        response.headers_mut().append(
+            HeaderName::ETAG,
+            HeaderValue::from_str(&self.etag(format)).map_err(internal_server_error)?,
+        );
+        if let Some(last_modified) = &self.last_modified {
+            response.headers_mut().append(
+                HeaderName::LAST_MODIFIED,
+                HeaderValue::from_str(last_modified).map_err(internal_server_error)?,
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Checks the `If-Match` and `If-None-Match` headers of a request changing a graph
+///
+/// The caller must hold [`ServerOptions::graph_store_lock`] until the change is applied.
+fn check_graph_write_preconditions(
+    store: &Store,
+    request: &Request,
+    target: &NamedGraphName,
+) -> Result<(), HttpError> {
+    let if_match = request
+        .header(&HeaderName::IF_MATCH)
+        .map(|value| value.to_str().map_err(bad_request))
+        .transpose()?;
+    let if_none_match = request
+        .header(&HeaderName::IF_NONE_MATCH)
+        .map(|value| value.to_str().map_err(bad_request))
+        .transpose()?;
+    if if_match.is_none() && if_none_match.is_none() {
+        return Ok(());
+    }
+    let current = if assert_that_graph_exists(store, target).is_ok() {
+        Some(GraphValidators::new(store, target)?)
+    } else {
+        None
+    };
+    if let Some(if_match) = if_match {
+        // If-Match uses the strong comparison
+        if !current
+            .as_ref()
+            .is_some_and(|current| current.matches_state(if_match, false))
+        {
+            return Err(precondition_failed(format!(
+                "The graph {} does not match the If-Match header",
+                GraphName::from(target.clone())
+            )));
+        }
+    }
+    if let Some(if_none_match) = if_none_match {
+        if current
+            .as_ref()
+            .is_some_and(|current| current.matches_state(if_none_match, true))
+        {
+            return Err(precondition_failed(format!(
+                "The graph {} matches the If-None-Match header",
+                GraphName::from(target.clone())
+            )));
+        }
+    }
+    Ok(())
+}
+
+const HTTP_DATE_WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+const HTTP_DATE_MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Formats a date time in the HTTP format, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`
+fn format_http_date(date_time: DateTime) -> Option<String> {
+    let date_time = date_time.adjust(Some(TimezoneOffset::UTC))?;
+    let (year, month, day) = (date_time.year(), date_time.month(), date_time.day());
+    // Sakamoto's algorithm
+    let month_offset = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4].get(usize::from(month) - 1)?;
+    let shifted_year = if month < 3 { year - 1 } else { year };
+    let weekday = (shifted_year + shifted_year.div_euclid(4) - shifted_year.div_euclid(100)
+        + shifted_year.div_euclid(400)
+        + month_offset
+        + i64::from(day))
+    .rem_euclid(7);
+    Some(format!(
+        "{}, {day:02} {} {year:04} {:02}:{:02}:{:02} GMT",
+        HTTP_DATE_WEEKDAYS.get(usize::try_from(weekday).ok()?)?,
+        HTTP_DATE_MONTHS.get(usize::from(month) - 1)?,
+        date_time.hour(),
+        date_time.minute(),
+        i64::from(Integer::try_from(date_time.second()).ok()?)
+    ))
+}
+
+/// Parses a date time in the HTTP format, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`
+fn parse_http_date(value: &str) -> Option<DateTime> {
+    let [_, day, month, year, time, "GMT"] = value.split_whitespace().collect::<Vec<_>>()[..]
+    else {
+        return None;
+    };
+    let month = HTTP_DATE_MONTHS.iter().position(|m| *m == month)? + 1;
+    DateTime::from_str(&format!("{year}-{month:02}-{day}T{time}Z")).ok()
+}
+
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
+enum NamedGraphName {
+    NamedNode(NamedNode),
+    DefaultGraph,
+}
+
+impl From<NamedGraphName> for GraphName {
+    fn from(graph_name: NamedGraphName) -> Self {
+        match graph_name {
+            NamedGraphName::NamedNode(node) => node.into(),
+            NamedGraphName::DefaultGraph => Self::DefaultGraph,
+        }
+    }
+}
 
 fn rdf_content_negotiation(request: &Request) -> Result<RdfFormat, HttpError> {
     content_negotiation(
         request,
-        RdfFormat::from_media_type,
+        // The HTML formats are not returned to avoid answering with them to the web browsers
+        |media_type| {
+            RdfFormat::from_media_type(media_type)
+                .filter(|format| format.media_type() != "text/html")
+        },
         RdfFormat::NQuads,
         &[
             ("application", RdfFormat::NQuads),
@@ -1510,50 +4093,235 @@ fn web_load_graph(
     request: &mut Request,
     format: RdfFormat,
     to_graph_name: &GraphName,
+    options: &ServerOptions,
 ) -> Result<(), HttpError> {
-    let base_iri = if let GraphName::NamedNode(graph_name) = to_graph_name {
-        Some(graph_name.as_str())
+    let parser = graph_parser(request, format, to_graph_name)?;
+    if url_query_parameter(request, "no_transaction").is_some() {
+        web_bulk_load(store, request, parser, options)
     } else {
-        None
-    };
+        store
+            .load_from_reader(parser, request.body_mut())
+            .map_err(loader_to_http_error)?;
+        Ok(())
+    }
+}
+
+/// Builds the parser of a graph sent using the Graph Store Protocol
+fn graph_parser(
+    request: &Request,
+    format: RdfFormat,
+    to_graph_name: &GraphName,
+) -> Result<RdfParser, HttpError> {
     let mut parser = RdfParser::from_format(format)
         .without_named_graphs()
         .with_default_graph(to_graph_name.clone());
     if url_query_parameter(request, "lenient").is_some() {
         parser = parser.unchecked();
     }
-    if let Some(base_iri) = base_iri {
-        parser = parser.with_base_iri(base_iri).map_err(bad_request)?;
+    if let GraphName::NamedNode(graph_name) = to_graph_name {
+        parser = parser
+            .with_base_iri(graph_name.as_str())
+            .map_err(bad_request)?;
     }
-    if url_query_parameter(request, "no_transaction").is_some() {
-        web_bulk_loader(store, request).load_from_reader(parser, request.body_mut())
-    } else {
-        store.load_from_reader(parser, request.body_mut())
+    Ok(parser)
+}
+
+/// Evaluates a Graph Store Protocol operation inside of the transaction set in the `transaction` parameter
+fn evaluate_graph_store_operation_in_transaction(
+    request: &mut Request,
+    options: &ServerOptions,
+    issuer: Option<&Principal>,
+) -> Result<Response, HttpError> {
+    let transaction = url_query_parameter(request, "transaction")
+        .ok_or_else(|| bad_request("The transaction parameter is not set"))?
+        .into_owned();
+    let target = store_target(request, request.url().path())?.ok_or_else(|| {
+        bad_request(
+            "The Graph Store Protocol operations inside of a transaction must target a graph",
+        )
+    })?;
+    let method = request.method().clone();
+    let operation = match method.as_ref() {
+        "GET" => {
+            let format = rdf_content_negotiation(request)?;
+            let graph_name = GraphName::from(target);
+            Operation::Read(Box::new(move |transaction| {
+                if let GraphName::NamedNode(graph_name) = &graph_name {
+                    if !transaction
+                        .contains_named_graph(graph_name)
+                        .map_err(internal_server_error)?
+                    {
+                        return Err((
+                            Status::NOT_FOUND,
+                            format!("The graph {graph_name} does not exists"),
+                        ));
+                    }
+                }
+                let mut serializer = RdfSerializer::from_format(format).for_writer(Vec::new());
+                for q in transaction.quads_for_pattern(None, None, None, Some(graph_name.as_ref()))
+                {
+                    serializer
+                        .serialize_triple(&q.map_err(internal_server_error)?.into())
+                        .map_err(internal_server_error)?;
+                }
+                Ok(OperationResponse {
+                    status: Status::OK,
+                    body: Some((
+                        format.media_type(),
+                        serializer.finish().map_err(internal_server_error)?,
+                    )),
+                })
+            }))
+        }
+        "PUT" | "POST" => {
+            let content_type =
+                content_type(request).ok_or_else(|| bad_request("No Content-Type given"))?;
+            let format = RdfFormat::from_media_type(&content_type)
+                .ok_or_else(|| unsupported_media_type(&content_type))?;
+            let graph_name = GraphName::from(target.clone());
+            let parser = graph_parser(request, format, &graph_name)?;
+            let body = limited_body(request)?;
+            let replace = method == Method::PUT;
+            Operation::Write(Box::new(move |transaction| {
+                let new = match &target {
+                    NamedGraphName::NamedNode(target) => transaction
+                        .insert_named_graph(target)
+                        .map_err(internal_server_error)?,
+                    NamedGraphName::DefaultGraph => false,
+                };
+                if replace && !new {
+                    transaction
+                        .clear_graph(graph_name.as_ref())
+                        .map_err(internal_server_error)?;
+                }
+                transaction
+                    .load_from_reader(parser.clone(), body.as_slice())
+                    .map_err(loader_to_http_error)?;
+                Ok(OperationResponse {
+                    status: if new {
+                        Status::CREATED
+                    } else {
+                        Status::NO_CONTENT
+                    },
+                    body: None,
+                })
+            }))
+        }
+        "DELETE" => Operation::Write(Box::new(move |transaction| {
+            match &target {
+                NamedGraphName::DefaultGraph => transaction
+                    .clear_graph(GraphNameRef::DefaultGraph)
+                    .map_err(internal_server_error)?,
+                NamedGraphName::NamedNode(target) => {
+                    if !transaction
+                        .remove_named_graph(target)
+                        .map_err(internal_server_error)?
+                    {
+                        return Err((
+                            Status::NOT_FOUND,
+                            format!("The graph {target} does not exists"),
+                        ));
+                    }
+                }
+            }
+            Ok(OperationResponse {
+                status: Status::NO_CONTENT,
+                body: None,
+            })
+        })),
+        _ => return Ok(method_not_allowed(request, "GET, PUT, POST, DELETE")),
+    };
+    Ok(options
+        .transactions
+        .execute(&transaction, issuer, operation)?
+        .into())
+}
+
+/// Applies atomically a RDF patch to the store.
+///
+/// If the target is a graph, the patch must only contain triples that are applied to this graph.
+///
+/// Returns `true` if the target graph has been created.
+fn web_apply_rdf_patch(
+    store: &Store,
+    target: Option<&NamedGraphName>,
+    mut operations: Vec<PatchOperation>,
+) -> Result<bool, HttpError> {
+    if let Some(target) = target {
+        for operation in &mut operations {
+            let (PatchOperation::Add(quad) | PatchOperation::Delete(quad)) = operation else {
+                return Err(bad_request(
+                    "The RDF patches applied to a graph must only contain triples",
+                ));
+            };
+            if !quad.graph_name.is_default_graph() {
+                return Err(bad_request(
+                    "The RDF patches applied to a graph must only contain triples",
+                ));
+            }
+            quad.graph_name = target.clone().into();
+        }
     }
-    .map_err(loader_to_http_error)
+    store
+        .transaction(|mut transaction| {
+            let new = if let Some(NamedGraphName::NamedNode(target)) = target {
+                transaction.insert_named_graph(target)?
+            } else {
+                false
+            };
+            for operation in &operations {
+                operation.apply(&mut transaction)?;
+            }
+            Result::<_, StorageError>::Ok(new)
+        })
+        .map_err(internal_server_error)
 }
 
 fn web_load_dataset(
     store: &Store,
     request: &mut Request,
     format: RdfFormat,
+    options: &ServerOptions,
 ) -> Result<(), HttpError> {
     let mut parser = RdfParser::from_format(format);
     if url_query_parameter(request, "lenient").is_some() {
         parser = parser.unchecked();
     }
     if url_query_parameter(request, "no_transaction").is_some() {
-        web_bulk_loader(store, request).load_from_reader(parser, request.body_mut())
+        web_bulk_load(store, request, parser, options)
     } else {
-        store.load_from_reader(parser, request.body_mut())
+        store
+            .load_from_reader(parser, request.body_mut())
+            .map_err(loader_to_http_error)?;
+        Ok(())
+    }
+}
+
+fn web_bulk_load(
+    store: &Store,
+    request: &mut Request,
+    parser: RdfParser,
+    options: &ServerOptions,
+) -> Result<(), HttpError> {
+    web_bulk_loader(store, request, &options.metrics)
+        .load_from_reader(parser, request.body_mut())
+        .map_err(loader_to_http_error)?;
+    // The bulk loads are not seen by the spatial index
+    #[cfg(feature = "geosparql")]
+    if let Some(index) = &options.spatial_index {
+        index.rebuild(store).map_err(internal_server_error)?;
     }
-    .map_err(loader_to_http_error)
+    Ok(())
 }
 
-fn web_bulk_loader(store: &Store, request: &Request) -> BulkLoader {
+fn web_bulk_loader(store: &Store, request: &Request, metrics: &Arc<Metrics>) -> BulkLoader {
     let start = Instant::now();
+    let metrics = Arc::clone(metrics);
+    let last_progress = Cell::new((0, Duration::ZERO));
     let mut loader = store.bulk_loader().on_progress(move |size| {
         let elapsed = start.elapsed();
+        let (last_size, last_elapsed) = last_progress.replace((size, elapsed));
+        metrics.observe_bulk_load(size - last_size, elapsed.saturating_sub(last_elapsed));
         eprintln!(
             "{} triples loaded in {}s ({} t/s)",
             size,
@@ -1570,21 +4338,86 @@ fn web_bulk_loader(store: &Store, request: &Request) -> BulkLoader {
     loader
 }
 
+/// The access level the request issuer must have on a graph, or on the full dataset if the graph is `None`, to execute the request
+fn required_access(
+    request: &Request,
+    path: &str,
+) -> Result<Option<(Option<GraphName>, AccessLevel)>, HttpError> {
+    Ok(
+        if path == "/query"
+            || path.starts_with("/queries/")
+            || path == "/batch"
+            || path == "/metrics"
+            || path == "/history"
+            || path == "/history/snapshot"
+        {
+            // The batches containing updates are checked by evaluate_sparql_batch
+            Some((None, AccessLevel::Read))
+        } else if path == "/subscribe" {
+            Some((
+                graph_parameter(request)?.map(GraphName::from),
+                AccessLevel::Read,
+            ))
+        } else if path == "/update" || path.starts_with("/transactions") {
+            Some((None, AccessLevel::Write))
+        } else if path == "/datasets" || path.starts_with("/datasets/") || path == "/backups" {
+            let level = if request.method().as_ref() == "GET" {
+                AccessLevel::Read
+            } else {
+                AccessLevel::Write
+            };
+            Some((None, level))
+        } else if path.starts_with("/store") {
+            let level = if matches!(request.method().as_ref(), "GET" | "HEAD") {
+                AccessLevel::Read
+            } else {
+                AccessLevel::Write
+            };
+            Some((store_target(request, path)?.map(GraphName::from), level))
+        } else {
+            None
+        },
+    )
+}
+
 fn error(status: Status, message: impl fmt::Display) -> Response {
-    Response::builder(status)
+    let mut response = Response::builder(status)
         .with_header(HeaderName::CONTENT_TYPE, "text/plain; charset=utf-8")
-        .unwrap()
-        .with_body(message.to_string())
+        .unwrap();
+    if status == Status::UNAUTHORIZED {
+        response = response
+            .with_header(HeaderName::WWW_AUTHENTICATE, "Basic realm=\"Oxigraph\"")
+            .unwrap();
+    }
+    response.with_body(message.to_string())
 }
 
 fn bad_request(message: impl fmt::Display) -> HttpError {
     (Status::BAD_REQUEST, message.to_string())
 }
 
+fn precondition_failed(message: impl fmt::Display) -> HttpError {
+    (Status::PRECONDITION_FAILED, message.to_string())
+}
+
 fn the_server_is_read_only() -> HttpError {
     (Status::FORBIDDEN, "The server is read-only".into())
 }
 
+/// Builds a 405 response with the `Allow` header required by [RFC 9110](https://www.rfc-editor.org/rfc/rfc9110#section-15.5.6)
+fn method_not_allowed(request: &Request, allowed: &'static str) -> Response {
+    Response::builder(Status::METHOD_NOT_ALLOWED)
+        .with_header(HeaderName::ALLOW, allowed)
+        .unwrap()
+        .with_header(HeaderName::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .unwrap()
+        .with_body(format!(
+            "{} is not allowed on {}, use {allowed}",
+            request.method(),
+            request.url().path()
+        ))
+}
+
 fn unsupported_media_type(content_type: &str) -> HttpError {
     (
         Status::UNSUPPORTED_MEDIA_TYPE,
@@ -1597,6 +4430,30 @@ fn internal_server_error(message: impl fmt::Display) -> HttpError {
     (Status::INTERNAL_SERVER_ERROR, message.to_string())
 }
 
+/// Maps SPARQL evaluation errors to HTTP errors following the [SPARQL 1.1 protocol](https://www.w3.org/TR/sparql11-protocol/)
+///
+/// Errors caused by the request content are client errors (4XX), the other ones are server errors (5XX).
+fn evaluation_error_to_http_error(e: EvaluationError) -> HttpError {
+    match &e {
+        EvaluationError::Parsing(_)
+        | EvaluationError::GraphAlreadyExists(_)
+        | EvaluationError::GraphDoesNotExist(_)
+        | EvaluationError::UnboundService
+        | EvaluationError::UnsupportedService(_) => bad_request(e),
+        EvaluationError::MemoryBudgetExceeded(_) => (Status::SERVICE_UNAVAILABLE, e.to_string()),
+        // The services rejected by the federation allow-list
+        EvaluationError::Service(error)
+            if matches!(
+                error.downcast_ref::<EvaluationError>(),
+                Some(EvaluationError::UnsupportedService(_))
+            ) =>
+        {
+            bad_request(e)
+        }
+        _ => internal_server_error(e),
+    }
+}
+
 fn loader_to_http_error(e: LoaderError) -> HttpError {
     match e {
         LoaderError::Parsing(e) => bad_request(e),
@@ -1703,15 +4560,18 @@ fn systemd_notify_ready() -> io::Result<()> {
 #[allow(clippy::panic_in_result_fn)]
 mod tests {
     use super::*;
-    use anyhow::Result;
+    use anyhow::{anyhow, Result};
     use assert_cmd::Command;
     use assert_fs::prelude::*;
     use assert_fs::{NamedTempFile, TempDir};
     use flate2::write::GzEncoder;
     use flate2::Compression;
+    use oxigraph::model::{NamedNodeRef, QuadRef};
+    use oxigraph_testsuite::protocol::check_protocol_testsuite;
     use predicates::prelude::*;
     use std::fs::remove_dir_all;
     use std::io::read_to_string;
+    use std::thread::sleep;
 
     fn cli_command() -> Command {
         let mut command = Command::new(env!("CARGO"));
@@ -1754,6 +4614,60 @@ mod tests {
             .success();
     }
 
+    #[test]
+    fn cli_serve_check_config() -> Result<()> {
+        let config_file = NamedTempFile::new("oxigraph.toml")?;
+        config_file.write_str(
+            "[storage]\nlocation = \"data\"\n\n[server]\nbind = \"localhost:7879\"\ncors = true\n",
+        )?;
+        cli_command()
+            .arg("serve")
+            .arg("--config")
+            .arg(config_file.path())
+            .arg("--check-config")
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("The configuration is valid"));
+        Ok(())
+    }
+
+    #[test]
+    fn cli_serve_check_invalid_config() -> Result<()> {
+        let config_file = NamedTempFile::new("oxigraph.toml")?;
+        config_file.write_str("[server]\nport = 7879\n")?;
+        cli_command()
+            .arg("serve")
+            .arg("--config")
+            .arg(config_file.path())
+            .arg("--check-config")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("port"));
+        Ok(())
+    }
+
+    #[test]
+    fn cli_serve_check_invalid_slow_query_threshold() {
+        cli_command()
+            .arg("serve")
+            .arg("--log-requests")
+            .arg("--slow-query-threshold=-1")
+            .arg("--check-config")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("slow query threshold"));
+    }
+
+    #[test]
+    fn cli_serve_read_only_check_config_without_location() {
+        cli_command()
+            .arg("serve-read-only")
+            .arg("--check-config")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("storage.location"));
+    }
+
     #[test]
     fn cli_help() {
         cli_command()
@@ -1894,6 +4808,100 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn cli_dump_graph_selection() -> Result<()> {
+        let store_dir = initialized_cli_store(
+            "<http://example.com/s> <http://example.com/p> <http://example.com/o> .\n\
+            <http://example.com/s> <http://example.com/p> <http://example.com/o> <http://example.com/tenant1/a> .\n\
+            <http://example.com/s> <http://example.com/p> <http://example.com/o> <http://example.com/tenant1/b> .\n\
+            <http://example.com/s> <http://example.com/p> <http://example.com/o> <http://example.com/tenant2/a> .",
+        )?;
+        cli_command()
+            .arg("dump")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--format")
+            .arg("nq")
+            .arg("--graph-pattern")
+            .arg("^http://example.com/tenant1/")
+            .arg("--graph")
+            .arg("default")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "<http://example.com/s> <http://example.com/p> <http://example.com/o> .\n",
+            ))
+            .stdout(predicate::str::contains("<http://example.com/tenant1/a>"))
+            .stdout(predicate::str::contains("<http://example.com/tenant1/b>"))
+            .stdout(predicate::str::contains("tenant2").not());
+
+        cli_command()
+            .arg("dump")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--format")
+            .arg("nq")
+            .arg("--graph-pattern")
+            .arg("^http://example.com/tenant3/")
+            .assert()
+            .success()
+            .stdout("");
+
+        cli_command()
+            .arg("dump")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--format")
+            .arg("nt")
+            .arg("--graph")
+            .arg("http://example.com/tenant1/a")
+            .arg("--graph")
+            .arg("http://example.com/tenant1/b")
+            .assert()
+            .failure();
+        Ok(())
+    }
+
+    #[test]
+    fn cli_load_with_renamed_graph() -> Result<()> {
+        let store_dir = TempDir::new()?;
+        cli_command()
+            .arg("load")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--format")
+            .arg("nq")
+            .arg("--rename-graph")
+            .arg("http://example.com/g?a=b=http://example.com/g2")
+            .arg("--rename-graph")
+            .arg("http://example.com/g3=default")
+            .write_stdin(
+                "<http://example.com/s> <http://example.com/p> <http://example.com/o> <http://example.com/g?a=b> .\n\
+                <http://example.com/s> <http://example.com/p> <http://example.com/o2> <http://example.com/g3> .",
+            )
+            .assert()
+            .success();
+        assert_cli_state(
+            &store_dir,
+            "<http://example.com/s> <http://example.com/p> <http://example.com/o2> .\n\
+            <http://example.com/s> <http://example.com/p> <http://example.com/o> <http://example.com/g2> .\n",
+        );
+
+        cli_command()
+            .arg("load")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--format")
+            .arg("nq")
+            .arg("--rename-graph")
+            .arg("http://example.com/g")
+            .write_stdin("")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Invalid graph renaming"));
+        Ok(())
+    }
+
     #[test]
     fn cli_load_and_dump_with_format() -> Result<()> {
         let store_dir = TempDir::new()?;
@@ -1955,125 +4963,263 @@ mod tests {
     }
 
     #[test]
-    fn cli_backup() -> Result<()> {
-        let store_dir = initialized_cli_store(
-            "<http://example.com/s> <http://example.com/p> <http://example.com/o> .",
-        )?;
-
-        let backup_dir = TempDir::new()?;
-        remove_dir_all(backup_dir.path())?; // The directory should not exist yet
+    fn cli_load_report() -> Result<()> {
+        let store_dir = TempDir::new()?;
+        let data = "<http://example.com/s> <http://example.com/p> <http://example.com/o> .\n<http://example.com/s> <http://example.com/p> <http://example.com/o> <http://example.com/g> .";
         cli_command()
-            .arg("backup")
+            .arg("load")
             .arg("--location")
             .arg(store_dir.path())
-            .arg("--destination")
-            .arg(backup_dir.path())
+            .arg("--format")
+            .arg("nq")
+            .write_stdin(data)
             .assert()
-            .success();
-
-        assert_cli_state(
-            &store_dir,
-            "<http://example.com/s> <http://example.com/p> <http://example.com/o> .\n",
-        );
-        Ok(())
-    }
+            .success()
+            .stderr(predicate::str::contains("2 quads loaded from stdin"));
 
-    #[test]
-    fn cli_ask_query_inline() -> Result<()> {
-        let store_dir = initialized_cli_store(
-            "<http://example.com/s> <http://example.com/p> <http://example.com/o> .",
+        cli_command()
+            .arg("load")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--format")
+            .arg("nq")
+            .arg("--detect-duplicates")
+            .write_stdin(data)
+            .assert()
+            .success()
+            .stderr(
+                predicate::str::contains("0 new quads loaded and 2 duplicates skipped from stdin")
+                    .and(predicate::str::contains(
+                        "<http://example.com/g>: 0 new quads loaded and 1 duplicates skipped",
+                    )),
+            );
+        Ok(())
+    }
+
+    #[test]
+    fn cli_load_with_parsing_threads() -> Result<()> {
+        let store_dir = TempDir::new()?;
+        let input_file = NamedTempFile::new("input.nt")?;
+        input_file.write_str(
+            "_:a <http://example.com/p> _:b .\n_:b <http://example.com/p> <http://example.com/o> .\n",
         )?;
         cli_command()
-            .arg("query")
+            .arg("load")
             .arg("--location")
             .arg(store_dir.path())
-            .arg("--query")
-            .arg("ASK { <s> <p> <o> }")
-            .arg("--query-base")
-            .arg("http://example.com/")
-            .arg("--results-format")
-            .arg("csv")
+            .arg("--file")
+            .arg(input_file.path())
+            .arg("--parsing-threads")
+            .arg("4")
             .assert()
-            .stdout("true")
-            .success();
+            .success()
+            .stderr(predicate::str::contains("2 quads loaded"));
         Ok(())
     }
 
     #[test]
-    fn cli_construct_query_stdin() -> Result<()> {
-        let store_dir = initialized_cli_store(
-            "<http://example.com/s> <http://example.com/p> <http://example.com/o> .",
+    fn cli_load_on_error_skip() -> Result<()> {
+        let store_dir = TempDir::new()?;
+        let valid_file = NamedTempFile::new("valid.nt")?;
+        valid_file
+            .write_str("<http://example.com/s> <http://example.com/p> <http://example.com/o> .")?;
+        let invalid_file = NamedTempFile::new("invalid.nt")?;
+        invalid_file.write_str(
+            "<http://example.com/s> <http://example.com/p> <http://example.com/o> .\n<http://example.com/s> <http://example.com/p> .",
         )?;
+        let report_file = NamedTempFile::new("report.json")?;
         cli_command()
-            .arg("query")
+            .arg("load")
             .arg("--location")
             .arg(store_dir.path())
-            .arg("--query-base")
-            .arg("http://example.com/")
-            .arg("--results-format")
+            .arg("--file")
+            .arg(valid_file.path())
+            .arg(invalid_file.path())
+            .arg("--on-error")
+            .arg("skip")
+            .arg("--error-report")
+            .arg(report_file.path())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "1 of the 2 file(s) failed to load",
+            ));
+        let report: Value = serde_json::from_str(&fs::read_to_string(report_file.path())?)?;
+        let errors = report.as_array().context("The report must be an array")?;
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0]["file"], invalid_file.path().display().to_string());
+        assert_eq!(errors[0]["line"], 2);
+
+        cli_command()
+            .arg("dump")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--format")
+            .arg("nq")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "<http://example.com/s> <http://example.com/p> <http://example.com/o> .",
+            ));
+        Ok(())
+    }
+
+    #[test]
+    fn cli_load_on_error_log() -> Result<()> {
+        let store_dir = TempDir::new()?;
+        let report_file = NamedTempFile::new("report.json")?;
+        cli_command()
+            .arg("load")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--format")
             .arg("nt")
-            .write_stdin("CONSTRUCT { ?s ?p ?o } WHERE { ?s ?p ?o }")
+            .arg("--on-error")
+            .arg("log")
+            .arg("--error-report")
+            .arg(report_file.path())
+            .write_stdin("<http://example.com/s> <http://example.com/p> .\n<http://example.com/s> <http://example.com/p> <http://example.com/o> .")
+            .assert()
+            .success()
+            .stderr(predicate::str::contains(
+                "1 quads loaded from stdin",
+            ));
+        let report: Value = serde_json::from_str(&fs::read_to_string(report_file.path())?)?;
+        assert_eq!(report[0]["file"], "stdin");
+        assert_eq!(report[0]["line"], 1);
+        Ok(())
+    }
+
+    #[test]
+    fn cli_load_on_error_abort() -> Result<()> {
+        let store_dir = TempDir::new()?;
+        cli_command()
+            .arg("load")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--format")
+            .arg("nt")
+            .write_stdin("<http://example.com/s> <http://example.com/p> .")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Error while loading file stdin"));
+        Ok(())
+    }
+
+    #[test]
+    fn cli_load_and_dump_with_skolemization() -> Result<()> {
+        let store_dir = TempDir::new()?;
+        cli_command()
+            .arg("load")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--format")
+            .arg("nt")
+            .arg("--deskolemize")
+            .arg("http://example.com")
+            .write_stdin("<http://example.com/.well-known/genid/b1> <http://example.com/p> _:b2 .")
             .assert()
-            .stdout("<http://example.com/s> <http://example.com/p> <http://example.com/o> .\n")
             .success();
+
+        cli_command()
+            .arg("dump")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--format")
+            .arg("nq")
+            .assert()
+            .success()
+            .stdout("_:b1 <http://example.com/p> _:b2 .\n");
+
+        cli_command()
+            .arg("dump")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--format")
+            .arg("nq")
+            .arg("--skolemize")
+            .arg("http://example.org/")
+            .assert()
+            .success()
+            .stdout("<http://example.org/.well-known/genid/b1> <http://example.com/p> <http://example.org/.well-known/genid/b2> .\n");
         Ok(())
     }
 
     #[test]
-    fn cli_select_query_file() -> Result<()> {
-        let store_dir = initialized_cli_store(
-            "<http://example.com/s> <http://example.com/p> <http://example.com/o> .",
-        )?;
-        let input_file = NamedTempFile::new("input.rq")?;
-        input_file.write_str("SELECT ?s WHERE { ?s ?p ?o }")?;
-        let output_file = NamedTempFile::new("output.tsv")?;
+    fn cli_dump_with_prefixes() -> Result<()> {
+        let store_dir = TempDir::new()?;
         cli_command()
-            .arg("query")
+            .arg("load")
             .arg("--location")
             .arg(store_dir.path())
-            .arg("--query-file")
-            .arg(input_file.path())
-            .arg("--results-file")
-            .arg(output_file.path())
+            .arg("--format")
+            .arg("nt")
+            .write_stdin("<http://example.com/ns#a> <http://example.com/ns#p> <http://www.w3.org/2002/07/owl#Thing> .")
             .assert()
             .success();
-        output_file.assert("?s\n<http://example.com/s>\n");
+
+        cli_command()
+            .arg("dump")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--format")
+            .arg("ttl")
+            .arg("--graph")
+            .arg("default")
+            .assert()
+            .success()
+            .stdout("@prefix ns: <http://example.com/ns#> .\n@prefix owl: <http://www.w3.org/2002/07/owl#> .\nns:a ns:p owl:Thing .\n");
         Ok(())
     }
 
     #[test]
-    fn cli_ask_union_default_graph() -> Result<()> {
+    fn cli_dump_languages() -> Result<()> {
         let store_dir = initialized_cli_store(
-            "GRAPH <http://example.com/g> { <http://example.com/s> <http://example.com/p> <http://example.com/o> }",
+            "<http://example.com/s> <http://example.com/p> \"a\"@en , \"b\"@en-GB , \"c\"@fr , \"d\" .\n\
+            <http://example.com/s> <http://example.com/q> \"c\"@fr .",
         )?;
         cli_command()
-            .arg("query")
+            .arg("languages")
             .arg("--location")
             .arg(store_dir.path())
-            .arg("--query")
-            .arg("ASK { ?s ?p ?o }")
-            .arg("--results-format")
-            .arg("tsv")
-            .arg("--union-default-graph")
             .assert()
-            .stdout("true")
-            .success();
+            .success()
+            .stdout("en\t1\t1\nen-gb\t1\t1\nfr\t2\t1\n");
+        cli_command()
+            .arg("dump")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--format")
+            .arg("nq")
+            .arg("--language")
+            .arg("fr")
+            .assert()
+            .success()
+            .stdout(
+                predicate::str::contains("\"c\"@fr")
+                    .and(predicate::str::contains("\"d\""))
+                    .and(predicate::str::contains("@en").not()),
+            );
         Ok(())
     }
 
     #[test]
-    fn cli_update_inline() -> Result<()> {
-        let store_dir = TempDir::new()?;
+    fn cli_backup() -> Result<()> {
+        let store_dir = initialized_cli_store(
+            "<http://example.com/s> <http://example.com/p> <http://example.com/o> .",
+        )?;
+
+        let backup_dir = TempDir::new()?;
+        remove_dir_all(backup_dir.path())?; // The directory should not exist yet
         cli_command()
-            .arg("update")
+            .arg("backup")
             .arg("--location")
             .arg(store_dir.path())
-            .arg("--update")
-            .arg("INSERT DATA { <s> <p> <o> }")
-            .arg("--update-base")
-            .arg("http://example.com/")
+            .arg("--destination")
+            .arg(backup_dir.path())
             .assert()
             .success();
+
         assert_cli_state(
             &store_dir,
             "<http://example.com/s> <http://example.com/p> <http://example.com/o> .\n",
@@ -2082,143 +5228,1371 @@ mod tests {
     }
 
     #[test]
-    fn cli_construct_update_stdin() -> Result<()> {
-        let store_dir = TempDir::new()?;
+    fn cli_stats() -> Result<()> {
+        let store_dir = initialized_cli_store(
+            "<http://example.com/s> <http://example.com/p> <http://example.com/o> .",
+        )?;
         cli_command()
-            .arg("update")
+            .arg("stats")
             .arg("--location")
             .arg(store_dir.path())
-            .arg("--update-base")
-            .arg("http://example.com/")
-            .write_stdin("INSERT DATA { <s> <p> <o> }")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Approximate number of quads: 1"))
+            .stdout(predicate::str::contains("dspo"));
+
+        let output = cli_command()
+            .arg("stats")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--json")
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let statistics = serde_json::from_slice::<Value>(&output)?;
+        assert_eq!(statistics["approximate-quads"], 1);
+        assert!(statistics["directory-size"]
+            .as_u64()
+            .is_some_and(|size| size > 0));
+        assert!(statistics["indexes"]
+            .as_array()
+            .is_some_and(|indexes| indexes.iter().any(|index| index["name"] == "id2str")));
+        Ok(())
+    }
+
+    #[test]
+    fn cli_canonicalize() -> Result<()> {
+        let input_file = NamedTempFile::new("input.ttl")?;
+        input_file.write_str(
+            "@prefix ex: <http://example.com/> .\n\
+            [ ex:p _:x ] .\n\
+            _:x ex:q \"o\" .",
+        )?;
+        let output_file = NamedTempFile::new("output.nq")?;
+        let issued_identifiers_file = NamedTempFile::new("issued.json")?;
+        cli_command()
+            .arg("canonicalize")
+            .arg("--file")
+            .arg(input_file.path())
+            .arg("--output")
+            .arg(output_file.path())
+            .arg("--issued-identifiers")
+            .arg(issued_identifiers_file.path())
             .assert()
             .success();
-        assert_cli_state(
-            &store_dir,
-            "<http://example.com/s> <http://example.com/p> <http://example.com/o> .\n",
+        let output = fs::read_to_string(output_file.path())?;
+        assert_eq!(output.lines().count(), 2);
+        assert!(output.contains("_:c14n0") && output.contains("_:c14n1"));
+        let issued_identifiers =
+            serde_json::from_slice::<Value>(&fs::read(issued_identifiers_file.path())?)?;
+        assert_eq!(
+            issued_identifiers.as_object().map(serde_json::Map::len),
+            Some(2)
         );
+        assert!(issued_identifiers["x"]
+            .as_str()
+            .is_some_and(|id| id.starts_with("c14n")));
+
+        // The same output is given for an isomorphic input
+        cli_command()
+            .arg("canonicalize")
+            .arg("--format")
+            .arg("nt")
+            .write_stdin(
+                "_:b2 <http://example.com/q> \"o\" .\n\
+                _:b1 <http://example.com/p> _:b2 .",
+            )
+            .assert()
+            .success()
+            .stdout(output);
         Ok(())
     }
 
     #[test]
-    fn cli_update_file() -> Result<()> {
-        let store_dir = TempDir::new()?;
-        let input_file = NamedTempFile::new("input.rq")?;
-        input_file.write_str(
-            "INSERT DATA { <http://example.com/s> <http://example.com/p> <http://example.com/o> }",
+    fn cli_diff_and_apply_patch() -> Result<()> {
+        let from_file = NamedTempFile::new("from.nq")?;
+        from_file.write_str(
+            "_:a <http://example.com/p> \"1\" .\n\
+            <http://example.com/s> <http://example.com/p> <http://example.com/o> .\n",
+        )?;
+        let to_file = NamedTempFile::new("to.nq")?;
+        to_file.write_str(
+            "_:x <http://example.com/p> \"1\" .\n\
+            <http://example.com/s> <http://example.com/p> <http://example.com/o2> <http://example.com/g> .\n",
         )?;
+        let patch_file = NamedTempFile::new("patch.rdfp")?;
         cli_command()
-            .arg("update")
+            .arg("diff")
+            .arg(from_file.path())
+            .arg(to_file.path())
+            .arg("--output")
+            .arg(patch_file.path())
+            .assert()
+            .success();
+        // The blank nodes are matched
+        assert_eq!(
+            fs::read_to_string(patch_file.path())?,
+            "TX .\n\
+            D <http://example.com/s> <http://example.com/p> <http://example.com/o> .\n\
+            A <http://example.com/s> <http://example.com/p> <http://example.com/o2> <http://example.com/g> .\n\
+            TC .\n"
+        );
+
+        let store_dir = initialized_cli_store(
+            "<http://example.com/s> <http://example.com/p> <http://example.com/o> .",
+        )?;
+        cli_command()
+            .arg("apply-patch")
             .arg("--location")
             .arg(store_dir.path())
-            .arg("--update-file")
-            .arg(input_file.path())
+            .arg("--file")
+            .arg(patch_file.path())
             .assert()
             .success();
         assert_cli_state(
             &store_dir,
-            "<http://example.com/s> <http://example.com/p> <http://example.com/o> .\n",
+            "<http://example.com/s> <http://example.com/p> <http://example.com/o2> <http://example.com/g> .\n",
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn cli_incremental_backup() -> Result<()> {
+        let store_dir = initialized_cli_store(
+            "<http://example.com/s> <http://example.com/p> <http://example.com/o> .",
+        )?;
+        let backups_dir = TempDir::new()?;
+        let full_backup = backups_dir.child("full");
+        let incremental_backup = backups_dir.child("incremental");
+        cli_command()
+            .arg("backup")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--destination")
+            .arg(full_backup.path())
+            .assert()
+            .success();
+        cli_command()
+            .arg("backup")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--destination")
+            .arg(incremental_backup.path())
+            .arg("--incremental")
+            .arg("--since")
+            .arg(full_backup.path())
+            .assert()
+            .success();
+
+        cli_command()
+            .arg("dump")
+            .arg("--location")
+            .arg(incremental_backup.path())
+            .arg("--format")
+            .arg("nq")
+            .assert()
+            .stdout("<http://example.com/s> <http://example.com/p> <http://example.com/o> .\n")
+            .success();
+        Ok(())
+    }
+
+    #[test]
+    fn cli_ask_query_inline() -> Result<()> {
+        let store_dir = initialized_cli_store(
+            "<http://example.com/s> <http://example.com/p> <http://example.com/o> .",
+        )?;
+        cli_command()
+            .arg("query")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--query")
+            .arg("ASK { <s> <p> <o> }")
+            .arg("--query-base")
+            .arg("http://example.com/")
+            .arg("--results-format")
+            .arg("csv")
+            .assert()
+            .stdout("true")
+            .success();
+        Ok(())
+    }
+
+    #[test]
+    fn cli_construct_query_stdin() -> Result<()> {
+        let store_dir = initialized_cli_store(
+            "<http://example.com/s> <http://example.com/p> <http://example.com/o> .",
+        )?;
+        cli_command()
+            .arg("query")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--query-base")
+            .arg("http://example.com/")
+            .arg("--results-format")
+            .arg("nt")
+            .write_stdin("CONSTRUCT { ?s ?p ?o } WHERE { ?s ?p ?o }")
+            .assert()
+            .stdout("<http://example.com/s> <http://example.com/p> <http://example.com/o> .\n")
+            .success();
+        Ok(())
+    }
+
+    #[test]
+    fn cli_diff_results_equal() -> Result<()> {
+        let left_dir = initialized_cli_store(
+            "<http://example.com/s> <http://example.com/p> [ <http://example.com/p> \"o\" ] .",
+        )?;
+        let right_dir = initialized_cli_store(
+            "<http://example.com/s> <http://example.com/p> [ <http://example.com/p> \"o\" ] .",
+        )?;
+        cli_command()
+            .arg("diff-results")
+            .arg("--left")
+            .arg(left_dir.path())
+            .arg("--right")
+            .arg(right_dir.path())
+            .arg("--query")
+            .arg("SELECT * WHERE { ?s ?p ?o }")
+            .assert()
+            .stdout("")
+            .success();
+        Ok(())
+    }
+
+    #[test]
+    fn cli_diff_results_different() -> Result<()> {
+        let left_dir = initialized_cli_store(
+            "<http://example.com/s> <http://example.com/p> \"o1\" , \"o2\" .",
+        )?;
+        let right_dir = initialized_cli_store(
+            "<http://example.com/s> <http://example.com/p> \"o1\" , \"o3\" .",
+        )?;
+        cli_command()
+            .arg("diff-results")
+            .arg("--left")
+            .arg(left_dir.path())
+            .arg("--right")
+            .arg(right_dir.path())
+            .write_stdin("SELECT ?o WHERE { ?s ?p ?o }")
+            .assert()
+            .stdout("- ?o=\"o2\"\n+ ?o=\"o3\"\n")
+            .failure();
+        Ok(())
+    }
+
+    #[test]
+    fn cli_lint() {
+        cli_command()
+            .arg("lint")
+            .arg("--query")
+            .arg("SELECT ?s WHERE { ?s <http://example.com/p> ?o FILTER(?o > 1) }")
+            .assert()
+            .stdout("")
+            .success();
+        cli_command()
+            .arg("lint")
+            .write_stdin("SELECT ?s WHERE { ?s <http://example.com/p> ?o FILTER(?oo > 1) }")
+            .assert()
+            .stdout(
+                "The FILTER using ?oo is applied to a pattern that does not bind ?oo
+\
+                The variable ?o is only used once
+\
+                The variable ?oo is only used once
+",
+            )
+            .failure();
+    }
+
+    #[test]
+    fn cli_hash_password() {
+        cli_command()
+            .arg("hash-password")
+            .write_stdin("secret\n")
+            .assert()
+            .stdout(predicate::str::starts_with("$argon2id$"))
+            .success();
+        cli_command()
+            .arg("hash-password")
+            .write_stdin("")
+            .assert()
+            .failure();
+    }
+
+    #[test]
+    fn cli_check_references() -> Result<()> {
+        let store_dir = initialized_cli_store(
+            "<http://example.com/a> <http://example.com/p> <http://example.com/b> , <http://example.com/c> .\n\
+            <http://example.com/b> <http://example.com/p> \"o\" .",
+        )?;
+        cli_command()
+            .arg("check-references")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--predicate")
+            .arg("http://example.com/p")
+            .assert()
+            .stdout("<http://example.com/a> <http://example.com/p> <http://example.com/c> .\n")
+            .failure();
+        cli_command()
+            .arg("check-references")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--predicate")
+            .arg("http://example.com/q")
+            .assert()
+            .stdout("")
+            .success();
+        Ok(())
+    }
+
+    #[test]
+    fn cli_query_trace_and_replay() -> Result<()> {
+        let store_dir = initialized_cli_store(
+            "<http://example.com/s> <http://example.com/p> <http://example.com/o> .",
+        )?;
+        let trace_file = NamedTempFile::new("trace.json")?;
+        cli_command()
+            .arg("query")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--query")
+            .arg("SELECT * WHERE { ?s ?p ?o }")
+            .arg("--results-format")
+            .arg("csv")
+            .arg("--trace-file")
+            .arg(trace_file.path())
+            .assert()
+            .success();
+        trace_file.assert(predicate::str::contains("number of results"));
+        cli_command()
+            .arg("replay")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--trace-file")
+            .arg(trace_file.path())
+            .assert()
+            .stdout("")
+            .success();
+
+        let other_store_dir = initialized_cli_store(
+            "<http://example.com/s> <http://example.com/p> <http://example.com/o1> , <http://example.com/o2> .",
+        )?;
+        cli_command()
+            .arg("replay")
+            .arg("--location")
+            .arg(other_store_dir.path())
+            .arg("--trace-file")
+            .arg(trace_file.path())
+            .assert()
+            .stderr(predicate::str::contains(
+                "the store content is not the one of the trace",
+            ))
+            .failure();
+        Ok(())
+    }
+
+    #[test]
+    fn cli_select_query_file() -> Result<()> {
+        let store_dir = initialized_cli_store(
+            "<http://example.com/s> <http://example.com/p> <http://example.com/o> .",
+        )?;
+        let input_file = NamedTempFile::new("input.rq")?;
+        input_file.write_str("SELECT ?s WHERE { ?s ?p ?o }")?;
+        let output_file = NamedTempFile::new("output.tsv")?;
+        cli_command()
+            .arg("query")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--query-file")
+            .arg(input_file.path())
+            .arg("--results-file")
+            .arg(output_file.path())
+            .assert()
+            .success();
+        output_file.assert("?s\n<http://example.com/s>\n");
+        Ok(())
+    }
+
+    #[test]
+    fn cli_ask_union_default_graph() -> Result<()> {
+        let store_dir = initialized_cli_store(
+            "GRAPH <http://example.com/g> { <http://example.com/s> <http://example.com/p> <http://example.com/o> }",
+        )?;
+        cli_command()
+            .arg("query")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--query")
+            .arg("ASK { ?s ?p ?o }")
+            .arg("--results-format")
+            .arg("tsv")
+            .arg("--union-default-graph")
+            .assert()
+            .stdout("true")
+            .success();
+        Ok(())
+    }
+
+    #[test]
+    fn cli_update_inline() -> Result<()> {
+        let store_dir = TempDir::new()?;
+        cli_command()
+            .arg("update")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--update")
+            .arg("INSERT DATA { <s> <p> <o> }")
+            .arg("--update-base")
+            .arg("http://example.com/")
+            .assert()
+            .success();
+        assert_cli_state(
+            &store_dir,
+            "<http://example.com/s> <http://example.com/p> <http://example.com/o> .\n",
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn cli_construct_update_stdin() -> Result<()> {
+        let store_dir = TempDir::new()?;
+        cli_command()
+            .arg("update")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--update-base")
+            .arg("http://example.com/")
+            .write_stdin("INSERT DATA { <s> <p> <o> }")
+            .assert()
+            .success();
+        assert_cli_state(
+            &store_dir,
+            "<http://example.com/s> <http://example.com/p> <http://example.com/o> .\n",
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn cli_update_file() -> Result<()> {
+        let store_dir = TempDir::new()?;
+        let input_file = NamedTempFile::new("input.rq")?;
+        input_file.write_str(
+            "INSERT DATA { <http://example.com/s> <http://example.com/p> <http://example.com/o> }",
+        )?;
+        cli_command()
+            .arg("update")
+            .arg("--location")
+            .arg(store_dir.path())
+            .arg("--update-file")
+            .arg(input_file.path())
+            .assert()
+            .success();
+        assert_cli_state(
+            &store_dir,
+            "<http://example.com/s> <http://example.com/p> <http://example.com/o> .\n",
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn cli_convert_file() -> Result<()> {
+        let input_file = NamedTempFile::new("input.ttl")?;
+        input_file.write_str("@prefix schema: <http://schema.org/> .\n<#me> a schema:Person ;\n\tschema:name \"Foo Bar\"@en .\n")?;
+        let output_file = NamedTempFile::new("output.rdf")?;
+        cli_command()
+            .arg("convert")
+            .arg("--from-file")
+            .arg(input_file.path())
+            .arg("--from-base")
+            .arg("http://example.com/")
+            .arg("--to-file")
+            .arg(output_file.path())
+            .assert()
+            .success();
+        output_file
+            .assert("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<rdf:RDF xml:base=\"http://example.com/\" xmlns:schema=\"http://schema.org/\" xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n\t<schema:Person rdf:about=\"#me\">\n\t\t<schema:name xml:lang=\"en\">Foo Bar</schema:name>\n\t</schema:Person>\n</rdf:RDF>");
+        Ok(())
+    }
+
+    #[test]
+    fn cli_convert_csvw() -> Result<()> {
+        let input_file = NamedTempFile::new("people.csv")?;
+        input_file.write_str("id,name\n1,Alice\n")?;
+        let metadata_file = NamedTempFile::new("people.csv-metadata.json")?;
+        metadata_file.write_str(
+            r#"{
+                "@context": "http://www.w3.org/ns/csvw",
+                "url": "people.csv",
+                "tableSchema": {
+                    "aboutUrl": "people/{id}",
+                    "columns": [
+                        {"name": "id", "suppressOutput": true},
+                        {"name": "name", "propertyUrl": "schema:name"},
+                        {"virtual": true, "propertyUrl": "rdf:type", "valueUrl": "schema:Person"}
+                    ]
+                }
+            }"#,
+        )?;
+        cli_command()
+            .arg("convert")
+            .arg("--from-file")
+            .arg(input_file.path())
+            .arg("--csvw-metadata")
+            .arg(metadata_file.path())
+            .arg("--from-base")
+            .arg("http://example.com/")
+            .arg("--to-format")
+            .arg("nt")
+            .assert()
+            .stdout("<http://example.com/people/1> <http://schema.org/name> \"Alice\" .\n<http://example.com/people/1> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://schema.org/Person> .\n")
+            .success();
+        cli_command()
+            .arg("convert")
+            .arg("--from-format")
+            .arg("csvw")
+            .arg("--from-base")
+            .arg("http://example.com/people.csv")
+            .arg("--to-format")
+            .arg("nq")
+            .arg("--to-graph")
+            .arg("http://example.com/g")
+            .write_stdin("name\nBob\n")
+            .assert()
+            .stdout(predicate::str::contains(
+                "<http://example.com/people.csv#name> \"Bob\" <http://example.com/g> .\n",
+            ))
+            .success();
+        Ok(())
+    }
+
+    #[test]
+    fn cli_convert_from_default_graph_to_named_graph() {
+        cli_command()
+            .arg("convert")
+            .arg("--from-format")
+            .arg("trig")
+            .arg("--to-format")
+            .arg("nq")
+            .arg("--from-default-graph")
+            .arg("--to-graph")
+            .arg("http://example.com/t")
+            .write_stdin("@base <http://example.com/> . <s> <p> <o> . <g> { <sg> <pg> <og> . }")
+            .assert()
+            .stdout("<http://example.com/s> <http://example.com/p> <http://example.com/o> <http://example.com/t> .\n")
+            .success();
+    }
+
+    #[test]
+    fn cli_convert_from_named_graph() {
+        cli_command()
+            .arg("convert")
+            .arg("--from-format")
+            .arg("trig")
+            .arg("--to-format")
+            .arg("nq")
+            .arg("--from-graph")
+            .arg("http://example.com/g")
+            .write_stdin("@base <http://example.com/> . <s> <p> <o> . <g> { <sg> <pg> <og> . }")
+            .assert()
+            .stdout("<http://example.com/sg> <http://example.com/pg> <http://example.com/og> .\n");
+    }
+
+    #[test]
+    fn cli_convert_to_base() {
+        cli_command()
+            .arg("convert")
+            .arg("--from-format")
+            .arg("ttl")
+            .arg("--to-format")
+            .arg("ttl")
+            .arg("--to-base")
+            .arg("http://example.com")
+            .write_stdin("@base <http://example.com/> . <s> <p> <o> .")
+            .assert()
+            .stdout("@base <http://example.com> .\n</s> </p> </o> .\n");
+    }
+
+    #[test]
+    fn get_ui() -> Result<()> {
+        ServerTest::new()?.test_status(
+            Request::builder(Method::GET, "http://localhost/".parse()?).build(),
+            Status::OK,
+        )
+    }
+
+    #[test]
+    fn post_dataset_file() -> Result<()> {
+        let request = Request::builder(Method::POST, "http://localhost/store".parse()?)
+            .with_header(HeaderName::CONTENT_TYPE, "application/trig")?
+            .with_body("<http://example.com> <http://example.com> <http://example.com> .");
+        ServerTest::new()?.test_status(request, Status::NO_CONTENT)
+    }
+
+    #[test]
+    fn post_wrong_file() -> Result<()> {
+        let request = Request::builder(Method::POST, "http://localhost/store".parse()?)
+            .with_header(HeaderName::CONTENT_TYPE, "application/trig")?
+            .with_body("<http://example.com>");
+        ServerTest::new()?.test_status(request, Status::BAD_REQUEST)
+    }
+
+    #[test]
+    fn post_unsupported_file() -> Result<()> {
+        let request = Request::builder(Method::POST, "http://localhost/store".parse()?)
+            .with_header(HeaderName::CONTENT_TYPE, "text/foo")?
+            .build();
+        ServerTest::new()?.test_status(request, Status::UNSUPPORTED_MEDIA_TYPE)
+    }
+
+    #[test]
+    fn get_healthz() -> Result<()> {
+        let request = Request::builder(Method::GET, "http://localhost/healthz".parse()?).build();
+        ServerTest::new()?.test_body(request, "OK")
+    }
+
+    #[test]
+    fn get_readyz() -> Result<()> {
+        let request = Request::builder(Method::GET, "http://localhost/readyz".parse()?).build();
+        ServerTest::new()?.test_body(request, "OK")
+    }
+
+    #[test]
+    fn head_readyz_read_only() -> Result<()> {
+        let request = Request::builder(Method::HEAD, "http://localhost/readyz".parse()?).build();
+        ServerTest::check_status(ServerTest::new()?.exec_read_only(request), Status::OK)
+    }
+
+    #[test]
+    fn get_readyz_replication_lag() -> Result<()> {
+        let server = ServerTest::new()?;
+        let status = Arc::new(ReplicationStatus::default());
+        let options = ServerOptions::default()
+            .with_replication_lag_check(Arc::clone(&status), Duration::from_secs(60));
+        let request = || -> Result<Request> {
+            Ok(Request::builder(Method::GET, "http://localhost/readyz".parse()?).build())
+        };
+        // Never caught up with the primary
+        ServerTest::check_status(
+            server.exec_with_options(request()?, &options),
+            Status::SERVICE_UNAVAILABLE,
+        )?;
+        status.record_sync();
+        ServerTest::check_status(server.exec_with_options(request()?, &options), Status::OK)?;
+        // Too far behind the primary
+        let options = ServerOptions::default()
+            .with_replication_lag_check(Arc::clone(&status), Duration::ZERO);
+        sleep(Duration::from_millis(10));
+        ServerTest::check_status(
+            server.exec_with_options(request()?, &options),
+            Status::SERVICE_UNAVAILABLE,
+        )
+    }
+
+    #[test]
+    fn subscribe() -> Result<()> {
+        let server = ServerTest::new()?;
+        let options = ServerOptions::default();
+        let request = Request::builder(
+            Method::GET,
+            "http://localhost/subscribe?predicate=%3Chttp%3A%2F%2Fexample.com%2Fp%3E".parse()?,
+        )
+        .build();
+        let mut response = server.exec_with_options(request, &options);
+        assert_eq!(response.status(), Status::OK);
+        server.store.update(
+            "INSERT DATA { <http://example.com/s> <http://example.com/p> 1 ; <http://example.com/p2> 2 }",
+        )?;
+        server
+            .store
+            .update("DELETE DATA { <http://example.com/s> <http://example.com/p> 1 }")?;
+        let expected = ": subscribed\n\nevent: insert\ndata: <http://example.com/s> <http://example.com/p> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> .\n\nevent: delete\ndata: <http://example.com/s> <http://example.com/p> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> .\n\n";
+        let mut body = vec![0; expected.len()];
+        response.body_mut().read_exact(&mut body)?;
+        assert_eq!(str::from_utf8(&body)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn subscribe_bad_pattern() -> Result<()> {
+        let request = Request::builder(
+            Method::GET,
+            "http://localhost/subscribe?subject=%22foo%22".parse()?,
+        )
+        .build();
+        ServerTest::new()?.test_status(request, Status::BAD_REQUEST)
+    }
+
+    #[test]
+    fn history() -> Result<()> {
+        let server = ServerTest::new()?;
+        server.store.enable_history()?;
+        server.store.update(
+            "INSERT DATA { <http://example.com/s> <http://example.com/p> <http://example.com/o> }",
+        )?;
+        server.store.update(
+            "DELETE DATA { <http://example.com/s> <http://example.com/p> <http://example.com/o> } ; INSERT DATA { GRAPH <http://example.com/g> { <http://example.com/s> <http://example.com/p> \"o\" } }",
+        )?;
+        let request =
+            Request::builder(Method::GET, "http://localhost/history?since=1".parse()?).build();
+        server.test_body(
+            request,
+            "H id <urn:oxigraph:history:2> .\nTX .\nD <http://example.com/s> <http://example.com/p> <http://example.com/o> .\nA <http://example.com/s> <http://example.com/p> \"o\" <http://example.com/g> .\nTC .\n",
+        )?;
+
+        // Empty named graphs are replicated too
+        server
+            .store
+            .update("CREATE GRAPH <http://example.com/empty>")?;
+
+        // We replicate the full history into a replica
+        let request = Request::builder(Method::GET, "http://localhost/history".parse()?).build();
+        let mut response = server.exec(request);
+        assert_eq!(response.status(), Status::OK);
+        let patch = read_to_string(response.body_mut())?;
+        assert!(patch.ends_with("TX .\nGA <http://example.com/empty> .\nTC .\n"));
+        let replica = Store::new()?;
+        replica.enable_history()?;
+        assert_eq!(replication::apply_history(&replica, &patch)?, 3);
+        assert_eq!(replica.history_version()?, Some(3));
+        assert_eq!(
+            replica.iter().collect::<Result<Dataset, _>>()?,
+            server.store.iter().collect::<Result<Dataset, _>>()?
+        );
+        assert!(replica.contains_named_graph(NamedNodeRef::new("http://example.com/empty")?)?);
+        Ok(())
+    }
+
+    #[test]
+    fn history_bulk_load() -> Result<()> {
+        let server = ServerTest::new()?;
+        server.store.enable_history()?;
+        server.store.update(
+            "INSERT DATA { <http://example.com/s> <http://example.com/p> <http://example.com/o> }",
+        )?;
+        let ex = NamedNodeRef::new("http://example.com/g")?;
+        server
+            .store
+            .bulk_loader()
+            .load_quads([QuadRef::new(ex, ex, ex, ex).into_owned()])?;
+        server
+            .store
+            .update("CREATE GRAPH <http://example.com/empty>")?;
+
+        // The history stops before the bulk load
+        let request =
+            Request::builder(Method::GET, "http://localhost/history?since=0".parse()?).build();
+        server.test_body(
+            request,
+            "H id <urn:oxigraph:history:1> .\nTX .\nA <http://example.com/s> <http://example.com/p> <http://example.com/o> .\nTC .\n",
+        )?;
+        let request =
+            Request::builder(Method::GET, "http://localhost/history?since=1".parse()?).build();
+        server.test_status(request, Status::CONFLICT)?;
+
+        // The replica is synchronized from a snapshot
+        let request =
+            Request::builder(Method::GET, "http://localhost/history/snapshot".parse()?).build();
+        let mut response = server.exec(request);
+        assert_eq!(response.status(), Status::OK);
+        let patch = read_to_string(response.body_mut())?;
+        let replica = Store::new()?;
+        replica.insert(QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph))?; // Removed by the snapshot
+        assert_eq!(replication::replicated_version(&replica)?, None);
+        assert_eq!(replication::apply_snapshot(&replica, &patch)?, 3);
+        assert_eq!(replication::replicated_version(&replica)?, Some(3));
+        assert_eq!(
+            replica.iter().collect::<Result<Dataset, _>>()?,
+            server.store.iter().collect::<Result<Dataset, _>>()?
+        );
+        assert!(replica.contains_named_graph(NamedNodeRef::new("http://example.com/empty")?)?);
+
+        // And then follows the history
+        server
+            .store
+            .update("DROP GRAPH <http://example.com/empty>")?;
+        let request =
+            Request::builder(Method::GET, "http://localhost/history?since=3".parse()?).build();
+        let mut response = server.exec(request);
+        assert_eq!(response.status(), Status::OK);
+        let patch = read_to_string(response.body_mut())?;
+        assert_eq!(replication::apply_history(&replica, &patch)?, 1);
+        assert_eq!(replication::replicated_version(&replica)?, Some(4));
+        assert!(!replica.contains_named_graph(NamedNodeRef::new("http://example.com/empty")?)?);
+        Ok(())
+    }
+
+    #[test]
+    fn history_bad_since() -> Result<()> {
+        let server = ServerTest::new()?;
+        server.store.enable_history()?;
+        let request =
+            Request::builder(Method::GET, "http://localhost/history?since=foo".parse()?).build();
+        server.test_status(request, Status::BAD_REQUEST)
+    }
+
+    #[test]
+    fn history_not_enabled() -> Result<()> {
+        let request = Request::builder(Method::GET, "http://localhost/history".parse()?).build();
+        ServerTest::new()?.test_status(request, Status::NOT_FOUND)
+    }
+
+    #[test]
+    fn get_metrics() -> Result<()> {
+        let server = ServerTest::new()?;
+        let options = ServerOptions::default();
+        let request = Request::builder(
+            Method::GET,
+            "http://localhost/query?query=SELECT%20*%20WHERE%20{%20?s%20?p%20?o%20}".parse()?,
+        )
+        .build();
+        let mut response = server.exec_with_options(request, &options);
+        read_to_string(response.body_mut())?; // We consume the results
+        let request = Request::builder(Method::GET, "http://localhost/metrics".parse()?).build();
+        let mut response = server.exec_with_options(request, &options);
+        assert_eq!(response.status(), Status::OK);
+        let metrics = read_to_string(response.body_mut())?;
+        assert!(metrics.contains("oxigraph_sparql_requests_total{operation=\"query\"} 1\n"));
+        assert!(metrics.contains("oxigraph_sparql_requests_total{operation=\"update\"} 0\n"));
+        assert!(metrics.contains("oxigraph_query_results_count 1\n"));
+        assert!(metrics.contains("oxigraph_http_requests_in_flight 1\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn get_query_with_request_log() -> Result<()> {
+        let server = ServerTest::new()?;
+        let options = ServerOptions {
+            request_log: RequestLog {
+                enabled: true,
+                with_query_text: true,
+                slow_query_threshold: Some(Duration::ZERO),
+            },
+            ..ServerOptions::default()
+        };
+        let request = Request::builder(
+            Method::GET,
+            "http://localhost/query?query=SELECT%20*%20WHERE%20{%20?s%20?p%20?o%20}".parse()?,
+        )
+        .build();
+        let mut response = server.exec_with_options(request, &options);
+        assert_eq!(response.status(), Status::OK);
+        read_to_string(response.body_mut())?; // We consume the results
+        let request = Request::builder(
+            Method::GET,
+            "http://localhost/query?query=SELECT%20*%20WHERE%20{".parse()?,
+        )
+        .build();
+        let response = server.exec_with_options(request, &options);
+        assert_eq!(response.status(), Status::BAD_REQUEST);
+        Ok(())
+    }
+
+    #[test]
+    fn get_query_max_results() -> Result<()> {
+        let server = ServerTest::new()?;
+        for i in 0..3 {
+            let node = NamedNode::new(format!("http://example.com/{i}"))?;
+            server.store.insert(QuadRef::new(
+                &node,
+                &node,
+                &node,
+                GraphNameRef::DefaultGraph,
+            ))?;
+        }
+        let options = ServerOptions {
+            limits: QueryLimits::new(None, Some(2), None, None)?,
+            ..ServerOptions::default()
+        };
+        let request = Request::builder(
+            Method::GET,
+            "http://localhost/query?query=SELECT%20*%20WHERE%20{%20?s%20?p%20?o%20}".parse()?,
+        )
+        .with_header(HeaderName::ACCEPT, "text/tab-separated-values")?
+        .build();
+        let mut response = server.exec_with_options(request, &options);
+        assert_eq!(response.status(), Status::OK);
+        let body = read_to_string(response.body_mut())?;
+        assert!(body.contains("more than the limit of 2 results"), "{body}");
+        Ok(())
+    }
+
+    #[test]
+    fn get_query_time_budget() -> Result<()> {
+        let server = ServerTest::new()?;
+        for i in 0..3 {
+            let node = NamedNode::new(format!("http://example.com/{i}"))?;
+            server.store.insert(QuadRef::new(
+                &node,
+                &node,
+                &node,
+                GraphNameRef::DefaultGraph,
+            ))?;
+        }
+        let url =
+            "http://localhost/query?query=SELECT%20?s%20WHERE%20{%20?s%20?p%20?o%20}&time-budget=0";
+        let mut continuation = None;
+        let mut results = Vec::new();
+        loop {
+            let request = Request::builder(
+                Method::GET,
+                if let Some(continuation) = &continuation {
+                    format!("{url}&continuation={continuation}")
+                } else {
+                    url.into()
+                }
+                .parse()?,
+            )
+            .with_header(HeaderName::ACCEPT, "text/tab-separated-values")?
+            .build();
+            let mut response = server.exec(request);
+            assert_eq!(response.status(), Status::OK);
+            let partial = response
+                .header(&HeaderName::from_str("Oxigraph-Partial-Results")?)
+                .context("No Oxigraph-Partial-Results header")?
+                .to_str()?
+                .to_owned();
+            continuation = response
+                .header(&HeaderName::from_str("Oxigraph-Continuation")?)
+                .map(|value| value.to_str().map(ToOwned::to_owned))
+                .transpose()?;
+            assert_eq!(partial == "true", continuation.is_some());
+            let body = read_to_string(response.body_mut())?;
+            results.extend(body.lines().skip(1).map(ToOwned::to_owned));
+            if continuation.is_none() {
+                break;
+            }
+        }
+        results.sort();
+        assert_eq!(
+            results,
+            [
+                "<http://example.com/0>",
+                "<http://example.com/1>",
+                "<http://example.com/2>"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn get_query_cursor() -> Result<()> {
+        let server = ServerTest::new()?;
+        let options = ServerOptions::default();
+        for i in 0..3 {
+            let node = NamedNode::new(format!("http://example.com/{i}"))?;
+            server.store.insert(QuadRef::new(
+                &node,
+                &node,
+                &node,
+                GraphNameRef::DefaultGraph,
+            ))?;
+        }
+        let mut cursor = None;
+        let mut results = Vec::new();
+        loop {
+            let request = Request::builder(
+                Method::GET,
+                if let Some(cursor) = &cursor {
+                    format!("http://localhost/query?cursor={cursor}")
+                } else {
+                    "http://localhost/query?query=SELECT%20?s%20WHERE%20{%20?s%20?p%20?o%20}&page-size=1".into()
+                }
+                .parse()?,
+            )
+            .with_header(HeaderName::ACCEPT, "text/tab-separated-values")?
+            .build();
+            let mut response = server.exec_with_options(request, &options);
+            assert_eq!(response.status(), Status::OK);
+            let partial = response
+                .header(&HeaderName::from_str("Oxigraph-Partial-Results")?)
+                .context("No Oxigraph-Partial-Results header")?
+                .to_str()?
+                .to_owned();
+            cursor = response
+                .header(&HeaderName::from_str("Oxigraph-Cursor")?)
+                .map(|value| value.to_str().map(ToOwned::to_owned))
+                .transpose()?;
+            assert_eq!(partial == "true", cursor.is_some());
+            let body = read_to_string(response.body_mut())?;
+            let page = body.lines().skip(1).collect::<Vec<_>>();
+            assert_eq!(page.len(), 1);
+            results.extend(page.into_iter().map(ToOwned::to_owned));
+            if cursor.is_none() {
+                break;
+            }
+        }
+        results.sort();
+        assert_eq!(
+            results,
+            [
+                "<http://example.com/0>",
+                "<http://example.com/1>",
+                "<http://example.com/2>"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn get_query_unknown_cursor() -> Result<()> {
+        let request =
+            Request::builder(Method::GET, "http://localhost/query?cursor=foo".parse()?).build();
+        ServerTest::new()?.test_status(request, Status::NOT_FOUND)
+    }
+
+    #[test]
+    fn get_query_bad_continuation() -> Result<()> {
+        let request = Request::builder(
+            Method::GET,
+            "http://localhost/query?query=SELECT%20*%20WHERE%20{%20?s%20?p%20?o%20}&time-budget=1&continuation=1-0".parse()?,
+        )
+        .build();
+        ServerTest::new()?.test_status(request, Status::BAD_REQUEST)
+    }
+
+    #[test]
+    fn get_query_explain() -> Result<()> {
+        let server = ServerTest::new()?;
+        server
+            .store
+            .update("INSERT DATA { <http://example.com/s> <http://example.com/p> 1 }")?;
+        let request = Request::builder(
+            Method::GET,
+            "http://localhost/query?query=SELECT%20*%20WHERE%20{%20?s%20?p%20?o%20}&explain=analyze"
+                .parse()?,
+        )
+        .build();
+        let mut response = server.exec(request);
+        assert_eq!(response.status(), Status::OK);
+        assert_eq!(
+            response
+                .header(&HeaderName::CONTENT_TYPE)
+                .map(HeaderValue::to_str)
+                .transpose()?,
+            Some("text/plain; charset=utf-8")
+        );
+        let body = read_to_string(response.body_mut())?;
+        assert!(body.contains("QuadPattern(?s ?p ?o) [inputs: 1, results: 1"));
+
+        let request = Request::builder(
+            Method::GET,
+            "http://localhost/query?query=SELECT%20*%20WHERE%20{%20?s%20?p%20?o%20}".parse()?,
+        )
+        .with_header(HeaderName::ACCEPT, EXPLANATION_MEDIA_TYPE)?
+        .build();
+        let mut response = server.exec(request);
+        assert_eq!(response.status(), Status::OK);
+        let body = read_to_string(response.body_mut())?;
+        assert!(body.contains("QuadPattern(?s ?p ?o)\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn get_query_bad_explain() -> Result<()> {
+        let request = Request::builder(
+            Method::GET,
+            "http://localhost/query?query=SELECT%20*%20WHERE%20{%20?s%20?p%20?o%20}&explain=foo"
+                .parse()?,
+        )
+        .build();
+        ServerTest::new()?.test_status(request, Status::BAD_REQUEST)?;
+        let request = Request::builder(
+            Method::GET,
+            "http://localhost/query?query=SELECT%20*%20WHERE%20{%20?s%20?p%20?o%20}&explain=true&time-budget=1"
+                .parse()?,
+        )
+        .build();
+        ServerTest::new()?.test_status(request, Status::BAD_REQUEST)
+    }
+
+    #[test]
+    fn get_query_timeout() -> Result<()> {
+        let server = ServerTest::new()?;
+        for i in 0..50 {
+            let node = NamedNode::new(format!("http://example.com/{i}"))?;
+            server.store.insert(QuadRef::new(
+                &node,
+                &node,
+                &node,
+                GraphNameRef::DefaultGraph,
+            ))?;
+        }
+        let options = ServerOptions {
+            limits: QueryLimits::new(Some(Duration::ZERO), None, None, None)?,
+            ..ServerOptions::default()
+        };
+        // A very large cartesian product
+        let request = Request::builder(
+            Method::POST,
+            "http://localhost/query".parse()?,
+        )
+        .with_header(HeaderName::CONTENT_TYPE, "application/sparql-query")?
+        .with_body("SELECT (COUNT(*) AS ?c) WHERE { ?a ?b ?c . ?d ?e ?f . ?g ?h ?i . ?j ?k ?l . ?m ?n ?o }");
+        let mut response = server.exec_with_options(request, &options);
+        let body = read_to_string(response.body_mut())?;
+        assert_eq!(response.status(), Status::SERVICE_UNAVAILABLE, "{body}");
+        assert!(body.contains("took more than the limit"), "{body}");
+        Ok(())
+    }
+
+    #[test]
+    fn batch() -> Result<()> {
+        let request = Request::builder(Method::POST, "http://localhost/batch".parse()?)
+            .with_header(HeaderName::CONTENT_TYPE, "application/json")?
+            .with_body(
+                r#"[
+                    {"update": "INSERT DATA { <http://example.com/s> <http://example.com/p> <http://example.com/o> }"},
+                    {"query": "ASK { ?s ?p ?o }"},
+                    {"query": "CONSTRUCT WHERE { ?s ?p ?o }"},
+                    {"query": "SELECT * WHERE {"}
+                ]"#,
+            );
+        let mut response = ServerTest::new()?.exec(request);
+        assert_eq!(response.status(), Status::OK);
+        let results: Value = serde_json::from_str(&read_to_string(response.body_mut())?)?;
+        assert_eq!(
+            results
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|r| r["status"].as_u64().unwrap())
+                .collect::<Vec<_>>(),
+            [204, 200, 200, 400]
+        );
+        assert_eq!(results[1]["results"]["boolean"], true);
+        assert_eq!(
+            results[2]["triples"],
+            "<http://example.com/s> <http://example.com/p> <http://example.com/o> .\n"
         );
         Ok(())
     }
 
     #[test]
-    fn cli_convert_file() -> Result<()> {
-        let input_file = NamedTempFile::new("input.ttl")?;
-        input_file.write_str("@prefix schema: <http://schema.org/> .\n<#me> a schema:Person ;\n\tschema:name \"Foo Bar\"@en .\n")?;
-        let output_file = NamedTempFile::new("output.rdf")?;
-        cli_command()
-            .arg("convert")
-            .arg("--from-file")
-            .arg(input_file.path())
-            .arg("--from-base")
-            .arg("http://example.com/")
-            .arg("--to-file")
-            .arg(output_file.path())
-            .assert()
-            .success();
-        output_file
-            .assert("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<rdf:RDF xml:base=\"http://example.com/\" xmlns:schema=\"http://schema.org/\" xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n\t<schema:Person rdf:about=\"#me\">\n\t\t<schema:name xml:lang=\"en\">Foo Bar</schema:name>\n\t</schema:Person>\n</rdf:RDF>");
+    fn batch_aborted() -> Result<()> {
+        let server = ServerTest::new()?;
+        let request = Request::builder(Method::POST, "http://localhost/batch".parse()?)
+            .with_header(HeaderName::CONTENT_TYPE, "application/json")?
+            .with_body(
+                r#"[
+                    {"update": "INSERT DATA { <http://example.com/s> <http://example.com/p> <http://example.com/o> }"},
+                    {"update": "CREATE GRAPH <http://example.com/g>"},
+                    {"update": "CREATE GRAPH <http://example.com/g>"}
+                ]"#,
+            );
+        server.test_status(request, Status::BAD_REQUEST)?;
+        assert!(server.store.is_empty()?);
         Ok(())
     }
 
     #[test]
-    fn cli_convert_from_default_graph_to_named_graph() {
-        cli_command()
-            .arg("convert")
-            .arg("--from-format")
-            .arg("trig")
-            .arg("--to-format")
-            .arg("nq")
-            .arg("--from-default-graph")
-            .arg("--to-graph")
-            .arg("http://example.com/t")
-            .write_stdin("@base <http://example.com/> . <s> <p> <o> . <g> { <sg> <pg> <og> . }")
-            .assert()
-            .stdout("<http://example.com/s> <http://example.com/p> <http://example.com/o> <http://example.com/t> .\n")
-            .success();
+    fn batch_update_read_only() -> Result<()> {
+        let request = Request::builder(Method::POST, "http://localhost/batch".parse()?)
+            .with_header(HeaderName::CONTENT_TYPE, "application/json")?
+            .with_body(r#"[{"update": "CLEAR ALL"}]"#);
+        ServerTest::check_status(
+            ServerTest::new()?.exec_read_only(request),
+            Status::FORBIDDEN,
+        )
     }
 
-    #[test]
-    fn cli_convert_from_named_graph() {
-        cli_command()
-            .arg("convert")
-            .arg("--from-format")
-            .arg("trig")
-            .arg("--to-format")
-            .arg("nq")
-            .arg("--from-graph")
-            .arg("http://example.com/g")
-            .write_stdin("@base <http://example.com/> . <s> <p> <o> . <g> { <sg> <pg> <og> . }")
-            .assert()
-            .stdout("<http://example.com/sg> <http://example.com/pg> <http://example.com/og> .\n");
+    fn auth_options() -> Result<ServerOptions> {
+        ServerOptions::new(
+            &Config::from_toml(
+                r#"
+[auth]
+anonymous-access = "none"
+
+[[auth.users]]
+name = "alice"
+password-hash = "$argon2id$v=19$m=19456,t=2,p=1$2goZj82xoFpdGOiKXDs1Zw$xcO4KqxkLbZ3lnQt8R1y60k/QobVOG+BV5U/t32A0Ms"
+access = "read"
+graphs = { "http://example.com/g" = "write" }
+datasets.ds = { access = "write" }
+
+[[auth.tokens]]
+token-sha256 = "3c469e9d6c5875d37a43f353d4f88e61fcf812c66eee3457465a40b0da4153e0"
+access = "write"
+"#,
+            )?,
+            false,
+        )
     }
 
     #[test]
-    fn cli_convert_to_base() {
-        cli_command()
-            .arg("convert")
-            .arg("--from-format")
-            .arg("ttl")
-            .arg("--to-format")
-            .arg("ttl")
-            .arg("--to-base")
-            .arg("http://example.com")
-            .write_stdin("@base <http://example.com/> . <s> <p> <o> .")
-            .assert()
-            .stdout("@base <http://example.com> .\n</s> </p> </o> .\n");
+    fn auth_anonymous() -> Result<()> {
+        let server = ServerTest::new()?;
+        let options = auth_options()?;
+        let request =
+            Request::builder(Method::GET, "http://localhost/query?query=ASK{}".parse()?).build();
+        let response = server.exec_with_options(request, &options);
+        assert_eq!(
+            response
+                .header(&HeaderName::WWW_AUTHENTICATE)
+                .map(|h| h.to_str())
+                .transpose()?,
+            Some("Basic realm=\"Oxigraph\"")
+        );
+        ServerTest::check_status(response, Status::UNAUTHORIZED)?;
+        let request = Request::builder(Method::GET, "http://localhost/healthz".parse()?).build();
+        ServerTest::check_status(server.exec_with_options(request, &options), Status::OK)
     }
 
     #[test]
-    fn get_ui() -> Result<()> {
-        ServerTest::new()?.test_status(
-            Request::builder(Method::GET, "http://localhost/".parse()?).build(),
-            Status::OK,
+    fn auth_basic() -> Result<()> {
+        let server = ServerTest::new()?;
+        let options = auth_options()?;
+        let request = Request::builder(Method::GET, "http://localhost/query?query=ASK{}".parse()?)
+            .with_header(HeaderName::AUTHORIZATION, "Basic YWxpY2U6c2VjcmV0")?
+            .build();
+        ServerTest::check_status(server.exec_with_options(request, &options), Status::OK)?;
+        let request = Request::builder(Method::GET, "http://localhost/query?query=ASK{}".parse()?)
+            .with_header(HeaderName::AUTHORIZATION, "Basic YWxpY2U6d3Jvbmc=")?
+            .build();
+        ServerTest::check_status(
+            server.exec_with_options(request, &options),
+            Status::UNAUTHORIZED,
+        )?;
+        let request = Request::builder(Method::GET, "http://localhost/query?query=ASK{}".parse()?)
+            .with_header(HeaderName::AUTHORIZATION, "Basic Ym9iOnNlY3JldA==")?
+            .build();
+        ServerTest::check_status(
+            server.exec_with_options(request, &options),
+            Status::UNAUTHORIZED,
         )
     }
 
     #[test]
-    fn post_dataset_file() -> Result<()> {
-        let request = Request::builder(Method::POST, "http://localhost/store".parse()?)
-            .with_header(HeaderName::CONTENT_TYPE, "application/trig")?
+    fn auth_per_graph() -> Result<()> {
+        let server = ServerTest::new()?;
+        let options = auth_options()?;
+        let request = Request::builder(
+            Method::PUT,
+            "http://localhost/store?graph=http://example.com/g".parse()?,
+        )
+        .with_header(HeaderName::AUTHORIZATION, "Basic YWxpY2U6c2VjcmV0")?
+        .with_header(HeaderName::CONTENT_TYPE, "text/turtle")?
+        .with_body("<http://example.com> <http://example.com> <http://example.com> .");
+        ServerTest::check_status(server.exec_with_options(request, &options), Status::CREATED)?;
+        let request = Request::builder(Method::PUT, "http://localhost/store?default".parse()?)
+            .with_header(HeaderName::AUTHORIZATION, "Basic YWxpY2U6c2VjcmV0")?
+            .with_header(HeaderName::CONTENT_TYPE, "text/turtle")?
             .with_body("<http://example.com> <http://example.com> <http://example.com> .");
-        ServerTest::new()?.test_status(request, Status::NO_CONTENT)
+        ServerTest::check_status(
+            server.exec_with_options(request, &options),
+            Status::FORBIDDEN,
+        )?;
+        let request = Request::builder(Method::POST, "http://localhost/update".parse()?)
+            .with_header(HeaderName::AUTHORIZATION, "Basic YWxpY2U6c2VjcmV0")?
+            .with_header(HeaderName::CONTENT_TYPE, "application/sparql-update")?
+            .with_body("CLEAR ALL");
+        ServerTest::check_status(
+            server.exec_with_options(request, &options),
+            Status::FORBIDDEN,
+        )
     }
 
     #[test]
-    fn post_wrong_file() -> Result<()> {
-        let request = Request::builder(Method::POST, "http://localhost/store".parse()?)
-            .with_header(HeaderName::CONTENT_TYPE, "application/trig")?
-            .with_body("<http://example.com>");
-        ServerTest::new()?.test_status(request, Status::BAD_REQUEST)
+    fn auth_per_dataset() -> Result<()> {
+        let server = ServerTest::new()?;
+        let options = ServerOptions {
+            datasets: Some(DatasetRegistry::default()),
+            ..auth_options()?
+        };
+        for name in ["ds", "other"] {
+            let request = Request::builder(
+                Method::POST,
+                format!("http://localhost/datasets?name={name}").parse()?,
+            )
+            .with_header(HeaderName::AUTHORIZATION, "Bearer token")?
+            .build();
+            ServerTest::check_status(server.exec_with_options(request, &options), Status::CREATED)?;
+        }
+        // Only write access to the server store graph http://example.com/g but to all the ds graphs
+        let request = Request::builder(Method::POST, "http://localhost/ds/update".parse()?)
+            .with_header(HeaderName::AUTHORIZATION, "Basic YWxpY2U6c2VjcmV0")?
+            .with_header(HeaderName::CONTENT_TYPE, "application/sparql-update")?
+            .with_body("CLEAR ALL");
+        ServerTest::check_status(
+            server.exec_with_options(request, &options),
+            Status::NO_CONTENT,
+        )?;
+        // No access to the datasets that are not listed
+        let request = Request::builder(
+            Method::GET,
+            "http://localhost/other/query?query=ASK{}".parse()?,
+        )
+        .with_header(HeaderName::AUTHORIZATION, "Basic YWxpY2U6c2VjcmV0")?
+        .build();
+        ServerTest::check_status(
+            server.exec_with_options(request, &options),
+            Status::FORBIDDEN,
+        )?;
+        // The server store access does not apply to the datasets
+        let request = Request::builder(Method::POST, "http://localhost/ds/update".parse()?)
+            .with_header(HeaderName::AUTHORIZATION, "Bearer token")?
+            .with_header(HeaderName::CONTENT_TYPE, "application/sparql-update")?
+            .with_body("CLEAR ALL");
+        ServerTest::check_status(
+            server.exec_with_options(request, &options),
+            Status::FORBIDDEN,
+        )
     }
 
     #[test]
-    fn post_unsupported_file() -> Result<()> {
-        let request = Request::builder(Method::POST, "http://localhost/store".parse()?)
-            .with_header(HeaderName::CONTENT_TYPE, "text/foo")?
+    fn auth_bearer() -> Result<()> {
+        let server = ServerTest::new()?;
+        let options = auth_options()?;
+        let request = Request::builder(Method::POST, "http://localhost/update".parse()?)
+            .with_header(HeaderName::AUTHORIZATION, "Bearer token")?
+            .with_header(HeaderName::CONTENT_TYPE, "application/sparql-update")?
+            .with_body("CLEAR ALL");
+        ServerTest::check_status(
+            server.exec_with_options(request, &options),
+            Status::NO_CONTENT,
+        )?;
+        let request = Request::builder(Method::POST, "http://localhost/update".parse()?)
+            .with_header(HeaderName::AUTHORIZATION, "Bearer wrong")?
+            .with_header(HeaderName::CONTENT_TYPE, "application/sparql-update")?
+            .with_body("CLEAR ALL");
+        ServerTest::check_status(
+            server.exec_with_options(request, &options),
+            Status::UNAUTHORIZED,
+        )
+    }
+
+    #[test]
+    fn auth_transaction_owner() -> Result<()> {
+        let server = ServerTest::new()?;
+        let options = auth_options()?;
+        let request = Request::builder(Method::POST, "http://localhost/transactions".parse()?)
+            .with_header(HeaderName::AUTHORIZATION, "Bearer token")?
             .build();
-        ServerTest::new()?.test_status(request, Status::UNSUPPORTED_MEDIA_TYPE)
+        let mut response = server.exec_with_options(request, &options);
+        let id = read_to_string(response.body_mut())?;
+        assert_eq!(response.status(), Status::CREATED, "Error message: {id}");
+        let request = Request::builder(
+            Method::GET,
+            format!("http://localhost/query?query=ASK{{}}&transaction={id}").parse()?,
+        )
+        .with_header(HeaderName::AUTHORIZATION, "Basic YWxpY2U6c2VjcmV0")?
+        .build();
+        ServerTest::check_status(
+            server.exec_with_options(request, &options),
+            Status::FORBIDDEN,
+        )?;
+        let request = Request::builder(
+            Method::POST,
+            format!("http://localhost/transactions/{id}/commit").parse()?,
+        )
+        .with_header(HeaderName::AUTHORIZATION, "Bearer token")?
+        .build();
+        ServerTest::check_status(
+            server.exec_with_options(request, &options),
+            Status::NO_CONTENT,
+        )
     }
 
     #[test]
@@ -2243,6 +6617,92 @@ mod tests {
         )
     }
 
+    #[test]
+    fn get_query_warnings() -> Result<()> {
+        let server = ServerTest::new()?;
+        let warning_header = HeaderName::from_str("Oxigraph-Query-Warning")?;
+
+        let request = Request::builder(
+            Method::GET,
+            "http://localhost/query?query=SELECT%20?s%20WHERE%20{%20?s%20?p%20?o%20.%20?a%20?b%20?c%20}"
+                .parse()?,
+        )
+        .build();
+        let response = server.exec(request);
+        assert_eq!(
+            response.header(&warning_header).map(HeaderValue::to_str).transpose()?,
+            Some("Cartesian product between the patterns using ?o ?p ?s and the patterns using ?a ?b ?c")
+        );
+        ServerTest::check_status(response, Status::OK)?;
+
+        let request = Request::builder(
+            Method::GET,
+            "http://localhost/query?query=SELECT%20?s%20WHERE%20{%20?s%20?p%20?o%20}".parse()?,
+        )
+        .build();
+        let response = server.exec(request);
+        assert!(response.header(&warning_header).is_none());
+        ServerTest::check_status(response, Status::OK)
+    }
+
+    #[test]
+    fn post_query_with_function_definitions() -> Result<()> {
+        let options = ServerOptions {
+            function_definitions: FunctionDefinition::parse_list(
+                "PREFIX ex: <http://example.com/> DEFINE FUNCTION ex:grade(?score) { IF(?score >= 10, 'pass', 'fail') }",
+                None,
+            )?,
+            ..ServerOptions::default()
+        };
+        let request = Request::builder(Method::POST, "http://localhost/query".parse()?)
+            .with_header(HeaderName::CONTENT_TYPE, "application/sparql-query")?
+            .with_header(HeaderName::ACCEPT, "text/csv")?
+            .with_body(
+                "SELECT (<http://example.com/grade>(?s) AS ?g) WHERE { VALUES ?s { 8 12 } }",
+            );
+        let mut response = ServerTest::new()?.exec_with_options(request, &options);
+        assert_eq!(
+            read_to_string(response.body_mut())?,
+            "g\r\nfail\r\npass\r\n"
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "geosparql")]
+    #[test]
+    fn post_query_with_spatial_index() -> Result<()> {
+        let server = ServerTest::new()?;
+        let options = ServerOptions::default()
+            .with_spatial_index(Some(SpatialIndex::for_store(&server.store)?));
+        // The bulk loads are indexed too
+        let request = Request::builder(
+            Method::POST,
+            "http://localhost/store?default&no_transaction".parse()?,
+        )
+        .with_header(HeaderName::CONTENT_TYPE, "text/turtle")?
+        .with_body(
+            "@prefix geo: <http://www.opengis.net/ont/geosparql#> .
+            <http://example.com/a> geo:asWKT \"POINT(1 1)\"^^geo:wktLiteral .
+            <http://example.com/b> geo:asWKT \"POINT(10 10)\"^^geo:wktLiteral .",
+        );
+        ServerTest::check_status(
+            server.exec_with_options(request, &options),
+            Status::NO_CONTENT,
+        )?;
+        let request = Request::builder(Method::POST, "http://localhost/query".parse()?)
+            .with_header(HeaderName::CONTENT_TYPE, "application/sparql-query")?
+            .with_header(HeaderName::ACCEPT, "text/csv")?
+            .with_body(
+                "PREFIX geo: <http://www.opengis.net/ont/geosparql#> PREFIX geof: <http://www.opengis.net/def/function/geosparql/> SELECT ?s WHERE { ?s geo:asWKT ?g FILTER(geof:sfWithin(?g, 'POLYGON((0 0, 2 0, 2 2, 0 2, 0 0))'^^geo:wktLiteral)) }",
+            );
+        let mut response = server.exec_with_options(request, &options);
+        assert_eq!(
+            read_to_string(response.body_mut())?,
+            "s\r\nhttp://example.com/a\r\n"
+        );
+        Ok(())
+    }
+
     #[test]
     fn get_query_accept_star() -> Result<()> {
         let request = Request::builder(
@@ -2291,6 +6751,25 @@ mod tests {
         )
     }
 
+    #[test]
+    fn get_query_accept_html() -> Result<()> {
+        let request = Request::builder(
+            Method::GET,
+            "http://localhost/query?query=CONSTRUCT%20WHERE%20{%20?s%20?p%20?o%20}".parse()?,
+        )
+        .with_header(HeaderName::ACCEPT, "text/html,application/n-triples;q=0.9")?
+        .build();
+        let response = ServerTest::new()?.exec(request);
+        assert_eq!(
+            response
+                .header(&HeaderName::CONTENT_TYPE)
+                .map(|h| h.to_str())
+                .transpose()?,
+            Some("application/n-triples")
+        );
+        ServerTest::check_status(response, Status::OK)
+    }
+
     #[test]
     fn get_query_accept_bad() -> Result<()> {
         let request = Request::builder(
@@ -2446,7 +6925,10 @@ mod tests {
         let request = Request::builder(Method::POST, "http://localhost/query".parse()?)
             .with_header(HeaderName::CONTENT_TYPE, "application/sparql-query")
             ?.with_body("SELECT * WHERE { SERVICE <https://query.wikidata.org/sparql> { <https://en.wikipedia.org/wiki/Paris> ?p ?o } }");
-        ServerTest::new()?.test_status(request, Status::OK)
+        ServerTest::check_status(
+            ServerTest::new()?.exec_with_options(request, &federation_options(Vec::new())?),
+            Status::OK,
+        )
     }
 
     #[test]
@@ -2454,7 +6936,111 @@ mod tests {
         let request = Request::builder(Method::POST, "http://localhost/query".parse()?)
             .with_header(HeaderName::CONTENT_TYPE, "application/sparql-query")
             ?.with_body("SELECT * WHERE { SERVICE <https://dbpedia.org/sparql> { <http://dbpedia.org/resource/Paris> ?p ?o } }");
-        ServerTest::new()?.test_status(request, Status::OK)
+        ServerTest::check_status(
+            ServerTest::new()?.exec_with_options(request, &federation_options(Vec::new())?),
+            Status::OK,
+        )
+    }
+
+    #[test]
+    fn post_federated_query_disabled() -> Result<()> {
+        let request = Request::builder(Method::POST, "http://localhost/query".parse()?)
+            .with_header(HeaderName::CONTENT_TYPE, "application/sparql-query")
+            ?.with_body("SELECT * WHERE { SERVICE <https://query.wikidata.org/sparql> { <https://en.wikipedia.org/wiki/Paris> ?p ?o } }");
+        ServerTest::new()?.test_status(request, Status::BAD_REQUEST)
+    }
+
+    #[test]
+    fn post_federated_query_not_allowed() -> Result<()> {
+        let server = ServerTest::new()?;
+        let options = federation_options(vec!["https://query.wikidata.org/".into()])?;
+        let request = Request::builder(Method::POST, "http://localhost/query".parse()?)
+            .with_header(HeaderName::CONTENT_TYPE, "application/sparql-query")
+            ?.with_body("SELECT * WHERE { SERVICE <https://dbpedia.org/sparql> { <http://dbpedia.org/resource/Paris> ?p ?o } }");
+        ServerTest::check_status(
+            server.exec_with_options(request, &options),
+            Status::BAD_REQUEST,
+        )?;
+        let request = Request::builder(Method::POST, "http://localhost/update".parse()?)
+            .with_header(HeaderName::CONTENT_TYPE, "application/sparql-update")?
+            .with_body(
+                "INSERT { ?s ?p ?o } WHERE { SERVICE <http://localhost:7878/query> { ?s ?p ?o } }",
+            );
+        ServerTest::check_status(
+            server.exec_with_options(request, &options),
+            Status::BAD_REQUEST,
+        )
+    }
+
+    fn federation_options(allowed_services: Vec<String>) -> Result<ServerOptions> {
+        Ok(ServerOptions {
+            federation: Some(FederatedServiceHandler::new(
+                allowed_services,
+                Some(Duration::from_secs(30)),
+            )?),
+            ..ServerOptions::default()
+        })
+    }
+
+    #[test]
+    fn get_stored_query() -> Result<()> {
+        let config = Config::from_toml(
+            "[auth]\nanonymous-access = \"none\"\n\n[queries.labels]\nquery = \"SELECT ?label WHERE { ?s <http://www.w3.org/2000/01/rdf-schema#label> ?label }\"\nparameters = [\"s\"]\npublic = true\n\n[queries.all]\nquery = \"SELECT * WHERE { ?s ?p ?o }\"\n",
+        )?;
+        let options = ServerOptions::new(&config, false)?;
+        let server = ServerTest::new()?;
+        server.store.update(
+            "INSERT DATA { <http://example.com/a> <http://www.w3.org/2000/01/rdf-schema#label> \"a\" . <http://example.com/b> <http://www.w3.org/2000/01/rdf-schema#label> \"b\" }",
+        )?;
+        let mut response = server.exec_with_options(
+            Request::builder(
+                Method::GET,
+                "http://localhost/queries/labels?s=%3Chttp%3A%2F%2Fexample.com%2Fa%3E".parse()?,
+            )
+            .with_header(HeaderName::ACCEPT, "text/csv")?
+            .build(),
+            &options,
+        );
+        assert_eq!(response.status(), Status::OK);
+        assert_eq!(read_to_string(response.body_mut())?, "label\r\na\r\n");
+        ServerTest::check_status(
+            server.exec_with_options(
+                Request::builder(
+                    Method::GET,
+                    "http://localhost/queries/labels?label=%22a%22".parse()?,
+                )
+                .build(),
+                &options,
+            ),
+            Status::BAD_REQUEST,
+        )?;
+        ServerTest::check_status(
+            server.exec_with_options(
+                Request::builder(Method::GET, "http://localhost/queries/labels?s=a".parse()?)
+                    .build(),
+                &options,
+            ),
+            Status::BAD_REQUEST,
+        )?;
+        // The non-public queries require the read access
+        ServerTest::check_status(
+            server.exec_with_options(
+                Request::builder(Method::GET, "http://localhost/queries/all".parse()?).build(),
+                &options,
+            ),
+            Status::UNAUTHORIZED,
+        )?;
+        ServerTest::check_status(
+            server.exec_with_options(
+                Request::builder(Method::POST, "http://localhost/queries/labels".parse()?).build(),
+                &options,
+            ),
+            Status::METHOD_NOT_ALLOWED,
+        )?;
+        server.test_status(
+            Request::builder(Method::GET, "http://localhost/queries/unknown".parse()?).build(),
+            Status::NOT_FOUND,
+        )
     }
 
     #[test]
@@ -2496,6 +7082,191 @@ mod tests {
         )
     }
 
+    #[test]
+    fn datasets() -> Result<()> {
+        let server = ServerTest::new()?;
+        let options = ServerOptions {
+            datasets: Some(DatasetRegistry::default()),
+            ..ServerOptions::default()
+        };
+        let request =
+            Request::builder(Method::POST, "http://localhost/datasets?name=ds".parse()?).build();
+        ServerTest::check_status(server.exec_with_options(request, &options), Status::CREATED)?;
+        let request =
+            Request::builder(Method::POST, "http://localhost/datasets?name=ds".parse()?).build();
+        ServerTest::check_status(
+            server.exec_with_options(request, &options),
+            Status::CONFLICT,
+        )?;
+        let request = Request::builder(
+            Method::POST,
+            "http://localhost/datasets?name=ro&read-only=true".parse()?,
+        )
+        .build();
+        ServerTest::check_status(server.exec_with_options(request, &options), Status::CREATED)?;
+        let request = Request::builder(
+            Method::POST,
+            "http://localhost/datasets?name=store".parse()?,
+        )
+        .build();
+        ServerTest::check_status(
+            server.exec_with_options(request, &options),
+            Status::BAD_REQUEST,
+        )?;
+        let request = Request::builder(Method::GET, "http://localhost/datasets".parse()?).build();
+        let mut response = server.exec_with_options(request, &options);
+        assert_eq!(response.status(), Status::OK);
+        assert_eq!(
+            read_to_string(response.body_mut())?,
+            r#"[{"name":"ds","read-only":false},{"name":"ro","read-only":true}]"#
+        );
+
+        // Each dataset has its own store
+        let request = Request::builder(Method::POST, "http://localhost/ds/update".parse()?)
+            .with_header(HeaderName::CONTENT_TYPE, "application/sparql-update")?
+            .with_body(
+                "INSERT DATA { <http://example.com> <http://example.com> <http://example.com> }",
+            );
+        ServerTest::check_status(
+            server.exec_with_options(request, &options),
+            Status::NO_CONTENT,
+        )?;
+        let request = Request::builder(Method::GET, "http://localhost/ds/store?default".parse()?)
+            .with_header(HeaderName::ACCEPT, "application/n-triples")?
+            .build();
+        let mut response = server.exec_with_options(request, &options);
+        assert_eq!(response.status(), Status::OK);
+        assert_eq!(
+            read_to_string(response.body_mut())?,
+            "<http://example.com> <http://example.com> <http://example.com> .\n"
+        );
+        assert!(server.store.is_empty()?);
+        let request = Request::builder(Method::POST, "http://localhost/ro/update".parse()?)
+            .with_header(HeaderName::CONTENT_TYPE, "application/sparql-update")?
+            .with_body(
+                "INSERT DATA { <http://example.com> <http://example.com> <http://example.com> }",
+            );
+        ServerTest::check_status(
+            server.exec_with_options(request, &options),
+            Status::FORBIDDEN,
+        )?;
+        let request = Request::builder(
+            Method::GET,
+            "http://localhost/ro/query?query=ASK%20{%20?s%20?p%20?o%20}".parse()?,
+        )
+        .with_header(HeaderName::ACCEPT, "text/tab-separated-values")?
+        .build();
+        let mut response = server.exec_with_options(request, &options);
+        assert_eq!(response.status(), Status::OK);
+        assert_eq!(read_to_string(response.body_mut())?, "false");
+
+        let request =
+            Request::builder(Method::DELETE, "http://localhost/datasets/ds".parse()?).build();
+        ServerTest::check_status(
+            server.exec_with_options(request, &options),
+            Status::NO_CONTENT,
+        )?;
+        let request = Request::builder(
+            Method::GET,
+            "http://localhost/ds/query?query=ASK%20{%20?s%20?p%20?o%20}".parse()?,
+        )
+        .build();
+        ServerTest::check_status(
+            server.exec_with_options(request, &options),
+            Status::NOT_FOUND,
+        )
+    }
+
+    #[test]
+    fn datasets_persistence() -> Result<()> {
+        let dir = TempDir::new()?;
+        DatasetRegistry::open(dir.path(), false)?
+            .create("ds", true)
+            .map_err(|(_, message)| anyhow!(message))?;
+        let datasets = DatasetRegistry::open(dir.path(), true)?;
+        assert_eq!(datasets.list(), [("ds".to_owned(), true)]);
+        Ok(())
+    }
+
+    #[test]
+    fn backups() -> Result<()> {
+        let store_dir = TempDir::new()?;
+        let backups_dir = TempDir::new()?;
+        let server = ServerTest {
+            store: Store::open(store_dir.path())?,
+        };
+        server
+            .store
+            .update("INSERT DATA { <http://example.com/s> <http://example.com/p> 1 }")?;
+        let options = ServerOptions {
+            backups: Some(BackupManager::new(backups_dir.path())?),
+            ..ServerOptions::default()
+        };
+        let wait_for_backups = |count: usize| -> Result<Vec<String>> {
+            for _ in 0..1000 {
+                let request =
+                    Request::builder(Method::GET, "http://localhost/backups".parse()?).build();
+                let mut response = server.exec_with_options(request, &options);
+                assert_eq!(response.status(), Status::OK);
+                let backups =
+                    serde_json::from_str::<Vec<String>>(&read_to_string(response.body_mut())?)?;
+                if backups.len() == count {
+                    return Ok(backups);
+                }
+                sleep(Duration::from_millis(10));
+            }
+            bail!("The backups have not been created")
+        };
+
+        let request = Request::builder(Method::POST, "http://localhost/backups".parse()?).build();
+        ServerTest::check_status(
+            server.exec_with_options(request, &options),
+            Status::ACCEPTED,
+        )?;
+        wait_for_backups(1)?;
+        server
+            .store
+            .update("INSERT DATA { <http://example.com/s> <http://example.com/p> 2 }")?;
+        let request = Request::builder(Method::POST, "http://localhost/backups".parse()?).build();
+        ServerTest::check_status(
+            server.exec_with_options(request, &options),
+            Status::ACCEPTED,
+        )?;
+        let backups = wait_for_backups(2)?;
+        let backup = Store::open_read_only(backups_dir.path().join(&backups[1]))?;
+        assert_eq!(backup.len()?, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn backups_not_enabled() -> Result<()> {
+        let request = Request::builder(Method::POST, "http://localhost/backups".parse()?).build();
+        ServerTest::new()?.test_status(request, Status::NOT_FOUND)
+    }
+
+    #[test]
+    fn datasets_not_enabled() -> Result<()> {
+        let request = Request::builder(Method::GET, "http://localhost/datasets".parse()?).build();
+        ServerTest::new()?.test_status(request, Status::NOT_FOUND)
+    }
+
+    // Tests from the W3C SPARQL 1.1 protocol test suite
+    #[test]
+    fn sparql_protocol_testsuite() -> Result<()> {
+        check_protocol_testsuite(|| {
+            let server = ServerTest::new()?;
+            Ok(move |request: Request| server.exec(request))
+        })
+    }
+
+    #[test]
+    fn get_update_service_description_with_parameters() -> Result<()> {
+        let request = Request::builder(Method::GET, "http://localhost/update?foo=bar".parse()?)
+            .with_header(HeaderName::ACCEPT, "text/turtle")?
+            .build();
+        ServerTest::new()?.test_status(request, Status::OK)
+    }
+
     #[test]
     fn graph_store_url_normalization() -> Result<()> {
         let server = ServerTest::new()?;
@@ -2541,7 +7312,7 @@ mod tests {
         )
         .with_header(HeaderName::CONTENT_TYPE, "text/turtle")?
         .with_body("<> <http://example.com/p> <http://example.com/o1> .");
-        server.test_status(request, Status::NO_CONTENT)?;
+        server.test_status(request, Status::CREATED)?;
 
         // GET
         let request = Request::builder(
@@ -2577,6 +7348,259 @@ mod tests {
         )
     }
 
+    #[test]
+    fn graph_store_conditional_get() -> Result<()> {
+        let server = ServerTest::new()?;
+        let request = Request::builder(Method::PUT, "http://localhost/store/g".parse()?)
+            .with_header(HeaderName::CONTENT_TYPE, "application/n-triples")?
+            .with_body("<http://example.com/s> <http://example.com/p> <http://example.com/o> .");
+        server.test_status(request, Status::CREATED)?;
+
+        let response = server.exec(
+            Request::builder(Method::GET, "http://localhost/store/g".parse()?)
+                .with_header(HeaderName::ACCEPT, "application/n-triples")?
+                .build(),
+        );
+        assert_eq!(response.status(), Status::OK);
+        let etag = response
+            .header(&HeaderName::ETAG)
+            .context("ETag expected")?
+            .to_str()?
+            .to_owned();
+        let last_modified = response
+            .header(&HeaderName::LAST_MODIFIED)
+            .context("Last-Modified expected")?
+            .to_str()?
+            .to_owned();
+        assert!(last_modified.ends_with(" GMT"));
+        assert!(!etag.starts_with("W/"), "The ETags must be strong");
+
+        // Not modified
+        server.test_status(
+            Request::builder(Method::GET, "http://localhost/store/g".parse()?)
+                .with_header(HeaderName::ACCEPT, "application/n-triples")?
+                .with_header(HeaderName::IF_NONE_MATCH, etag.clone())?
+                .build(),
+            Status::NOT_MODIFIED,
+        )?;
+        // The representations in other formats have different ETags
+        server.test_status(
+            Request::builder(Method::GET, "http://localhost/store/g".parse()?)
+                .with_header(HeaderName::ACCEPT, "text/turtle")?
+                .with_header(HeaderName::IF_NONE_MATCH, etag.clone())?
+                .build(),
+            Status::OK,
+        )?;
+        server.test_status(
+            Request::builder(Method::HEAD, "http://localhost/store/g".parse()?)
+                .with_header(HeaderName::IF_MODIFIED_SINCE, last_modified.clone())?
+                .build(),
+            Status::NOT_MODIFIED,
+        )?;
+
+        // Modified
+        let request = Request::builder(Method::POST, "http://localhost/store/g".parse()?)
+            .with_header(HeaderName::CONTENT_TYPE, "application/n-triples")?
+            .with_body("<http://example.com/s> <http://example.com/p> <http://example.com/o2> .");
+        server.test_status(request, Status::NO_CONTENT)?;
+        server.test_status(
+            Request::builder(Method::GET, "http://localhost/store/g".parse()?)
+                .with_header(HeaderName::ACCEPT, "application/n-triples")?
+                .with_header(HeaderName::IF_NONE_MATCH, etag.clone())?
+                .build(),
+            Status::OK,
+        )
+    }
+
+    #[test]
+    fn graph_store_preconditions() -> Result<()> {
+        let server = ServerTest::new()?;
+        let put = |if_match: Option<&str>, if_none_match: Option<&str>| -> Result<Request> {
+            let mut request = Request::builder(Method::PUT, "http://localhost/store/g".parse()?)
+                .with_header(HeaderName::CONTENT_TYPE, "application/n-triples")?;
+            if let Some(if_match) = if_match {
+                request = request.with_header(HeaderName::IF_MATCH, if_match.to_owned())?;
+            }
+            if let Some(if_none_match) = if_none_match {
+                request =
+                    request.with_header(HeaderName::IF_NONE_MATCH, if_none_match.to_owned())?;
+            }
+            Ok(request.with_body(
+                "<http://example.com/s> <http://example.com/p> <http://example.com/o> .",
+            ))
+        };
+
+        // Creation only if the graph does not exist
+        server.test_status(put(Some("*"), None)?, Status::PRECONDITION_FAILED)?;
+        server.test_status(put(None, Some("*"))?, Status::CREATED)?;
+        server.test_status(put(None, Some("*"))?, Status::PRECONDITION_FAILED)?;
+
+        // Update only if the graph has not been changed
+        let response = server
+            .exec(Request::builder(Method::HEAD, "http://localhost/store/g".parse()?).build());
+        let etag = response
+            .header(&HeaderName::ETAG)
+            .context("ETag expected")?
+            .to_str()?
+            .to_owned();
+        server.test_status(put(Some("W/\"foo\""), None)?, Status::PRECONDITION_FAILED)?;
+        // If-Match uses the strong comparison
+        server.test_status(
+            put(Some(&format!("W/{etag}")), None)?,
+            Status::PRECONDITION_FAILED,
+        )?;
+        let request = Request::builder(Method::POST, "http://localhost/store/g".parse()?)
+            .with_header(HeaderName::CONTENT_TYPE, "application/n-triples")?
+            .with_header(HeaderName::IF_MATCH, etag.clone())?
+            .with_body("<http://example.com/s> <http://example.com/p> <http://example.com/o2> .");
+        server.test_status(request, Status::NO_CONTENT)?;
+        server.test_status(
+            Request::builder(Method::DELETE, "http://localhost/store/g".parse()?)
+                .with_header(HeaderName::IF_MATCH, etag.clone())?
+                .build(),
+            Status::PRECONDITION_FAILED,
+        )?;
+        server.test_status(
+            Request::builder(Method::DELETE, "http://localhost/store/g".parse()?)
+                .with_header(HeaderName::IF_MATCH, "*")?
+                .build(),
+            Status::NO_CONTENT,
+        )
+    }
+
+    #[test]
+    fn transactions() -> Result<()> {
+        let server = ServerTest::new()?;
+        let options = ServerOptions::default();
+        let begin = || -> Result<String> {
+            let mut response = server.exec_with_options(
+                Request::builder(Method::POST, "http://localhost/transactions".parse()?).build(),
+                &options,
+            );
+            let id = read_to_string(response.body_mut())?;
+            assert_eq!(response.status(), Status::CREATED, "Error message: {id}");
+            Ok(id)
+        };
+        let update = |id: &str, update: &str| -> Result<Response> {
+            Ok(server.exec_with_options(
+                Request::builder(
+                    Method::POST,
+                    format!("http://localhost/update?transaction={id}").parse()?,
+                )
+                .with_header(HeaderName::CONTENT_TYPE, "application/sparql-update")?
+                .with_body(update.to_owned()),
+                &options,
+            ))
+        };
+        let end = |id: &str, action: &str| -> Result<Response> {
+            Ok(server.exec_with_options(
+                Request::builder(
+                    Method::POST,
+                    format!("http://localhost/transactions/{id}/{action}").parse()?,
+                )
+                .build(),
+                &options,
+            ))
+        };
+
+        // Commit
+        let id = begin()?;
+        ServerTest::check_status(
+            update(
+                &id,
+                "INSERT DATA { <http://example.com/s> <http://example.com/p> <http://example.com/o> }",
+            )?,
+            Status::NO_CONTENT,
+        )?;
+        ServerTest::check_status(
+            server.exec_with_options(
+                Request::builder(
+                    Method::PUT,
+                    format!("http://localhost/store/g?transaction={id}").parse()?,
+                )
+                .with_header(HeaderName::CONTENT_TYPE, "application/n-triples")?
+                .with_body(
+                    "<http://example.com/s> <http://example.com/p> <http://example.com/o> .",
+                ),
+                &options,
+            ),
+            Status::CREATED,
+        )?;
+        let mut response = server.exec_with_options(
+            Request::builder(
+                Method::GET,
+                format!(
+                    "http://localhost/query?query=SELECT%20%3Fg%20WHERE%20%7B%20GRAPH%20%3Fg%20%7B%20%3Fs%20%3Fp%20%3Fo%20%7D%20%7D&transaction={id}"
+                )
+                .parse()?,
+            )
+            .with_header(HeaderName::ACCEPT, "text/csv")?
+            .build(),
+            &options,
+        );
+        assert_eq!(
+            read_to_string(response.body_mut())?,
+            "g\r\nhttp://localhost/store/g\r\n"
+        );
+        assert!(
+            server.store.is_empty()?,
+            "The changes must not be visible before the commit"
+        );
+        ServerTest::check_status(end(&id, "commit")?, Status::NO_CONTENT)?;
+        assert_eq!(server.store.len()?, 2);
+        ServerTest::check_status(end(&id, "commit")?, Status::NOT_FOUND)?;
+
+        // Abort
+        let id = begin()?;
+        ServerTest::check_status(update(&id, "CLEAR ALL")?, Status::NO_CONTENT)?;
+        ServerTest::check_status(end(&id, "abort")?, Status::NO_CONTENT)?;
+        assert_eq!(server.store.len()?, 2);
+
+        // A failed operation aborts the transaction
+        let id = begin()?;
+        ServerTest::check_status(
+            update(
+                &id,
+                "INSERT DATA { <http://example.com/s> <http://example.com/p> <http://example.com/o2> }",
+            )?,
+            Status::NO_CONTENT,
+        )?;
+        ServerTest::check_status(
+            update(&id, "CREATE GRAPH <http://localhost/store/g>")?,
+            Status::BAD_REQUEST,
+        )?;
+        ServerTest::check_status(end(&id, "commit")?, Status::NOT_FOUND)?;
+        assert_eq!(server.store.len()?, 2);
+
+        // The store is not locked by an open transaction
+        let id = begin()?;
+        ServerTest::check_status(
+            update(&id, "CREATE GRAPH <http://example.com/g2>")?,
+            Status::NO_CONTENT,
+        )?;
+        server.store.insert(QuadRef::new(
+            &NamedNode::new("http://example.com/s")?,
+            &NamedNode::new("http://example.com/p")?,
+            &NamedNode::new("http://example.com/o3")?,
+            GraphNameRef::DefaultGraph,
+        ))?;
+        assert_eq!(server.store.len()?, 3);
+
+        // A write conflicting with a concurrent change aborts the transaction
+        server
+            .store
+            .insert_named_graph(&NamedNode::new("http://example.com/g2")?)?;
+        ServerTest::check_status(
+            update(&id, "INSERT DATA { <http://example.com/s> <http://example.com/p> <http://example.com/o4> }")?,
+            Status::CONFLICT,
+        )?;
+        ServerTest::check_status(end(&id, "commit")?, Status::NOT_FOUND)?;
+        assert_eq!(server.store.len()?, 3);
+
+        // Unknown transaction
+        ServerTest::check_status(update("foo", "CLEAR ALL")?, Status::NOT_FOUND)
+    }
+
     #[test]
     fn graph_store_protocol() -> Result<()> {
         // Tests from https://www.w3.org/2009/sparql/docs/tests/data-sparql11/http-rdf-update/
@@ -2781,6 +7805,113 @@ mod tests {
         server.test_status(request, Status::OK)
     }
 
+    #[test]
+    fn graph_store_empty_graph() -> Result<()> {
+        let server = ServerTest::new()?;
+
+        // PUT - empty graph
+        let request = Request::builder(Method::PUT, "http://localhost/store/empty".parse()?)
+            .with_header(HeaderName::CONTENT_TYPE, "text/turtle")?
+            .build();
+        server.test_status(request, Status::CREATED)?;
+
+        // GET and HEAD - the empty graph exists
+        server.test_status(
+            Request::builder(Method::GET, "http://localhost/store/empty".parse()?).build(),
+            Status::OK,
+        )?;
+        server.test_status(
+            Request::builder(Method::HEAD, "http://localhost/store/empty".parse()?).build(),
+            Status::OK,
+        )?;
+
+        // POST - existing empty graph
+        let request = Request::builder(Method::POST, "http://localhost/store/empty".parse()?)
+            .with_header(HeaderName::CONTENT_TYPE, "text/turtle")?
+            .build();
+        server.test_status(request, Status::NO_CONTENT)?;
+
+        // DELETE
+        server.test_status(
+            Request::builder(Method::DELETE, "http://localhost/store/empty".parse()?).build(),
+            Status::NO_CONTENT,
+        )?;
+
+        // GET, HEAD and DELETE - the graph does not exist anymore
+        server.test_status(
+            Request::builder(Method::GET, "http://localhost/store/empty".parse()?).build(),
+            Status::NOT_FOUND,
+        )?;
+        server.test_status(
+            Request::builder(Method::HEAD, "http://localhost/store/empty".parse()?).build(),
+            Status::NOT_FOUND,
+        )?;
+        server.test_status(
+            Request::builder(Method::DELETE, "http://localhost/store/empty".parse()?).build(),
+            Status::NOT_FOUND,
+        )
+    }
+
+    #[test]
+    fn graph_store_patch() -> Result<()> {
+        let server = ServerTest::new()?;
+
+        // PATCH - new graph
+        let request = Request::builder("PATCH".parse()?, "http://localhost/store/1".parse()?)
+            .with_header(HeaderName::CONTENT_TYPE, "text/rdf-patch")?
+            .with_body("PA ex: <http://example.com/> .\nA ex:s ex:p ex:o1 .\nA ex:s ex:p ex:o2 .");
+        server.test_status(request, Status::CREATED)?;
+
+        // PATCH - existing graph
+        let request = Request::builder("PATCH".parse()?, "http://localhost/store/1".parse()?)
+            .with_header(HeaderName::CONTENT_TYPE, "text/rdf-patch")?
+            .with_body("TX .\nD <http://example.com/s> <http://example.com/p> <http://example.com/o1> .\nTC .");
+        server.test_status(request, Status::NO_CONTENT)?;
+
+        // GET
+        let request = Request::builder(Method::GET, "http://localhost/store/1".parse()?)
+            .with_header(HeaderName::ACCEPT, "application/n-triples")?
+            .build();
+        server.test_body(
+            request,
+            "<http://example.com/s> <http://example.com/p> <http://example.com/o2> .\n",
+        )?;
+
+        // PATCH - quads are not allowed when targeting a graph
+        let request = Request::builder("PATCH".parse()?, "http://localhost/store/1".parse()?)
+            .with_header(HeaderName::CONTENT_TYPE, "text/rdf-patch")?
+            .with_body("A <http://example.com/s> <http://example.com/p> <http://example.com/o> <http://example.com/g> .");
+        server.test_status(request, Status::BAD_REQUEST)?;
+
+        // PATCH - dataset
+        let request = Request::builder("PATCH".parse()?, "http://localhost/store".parse()?)
+            .with_header(HeaderName::CONTENT_TYPE, "text/rdf-patch")?
+            .with_body("A <http://example.com/s> <http://example.com/p> <http://example.com/o> <http://example.com/g> .");
+        server.test_status(request, Status::NO_CONTENT)?;
+        let request = Request::builder(
+            Method::GET,
+            "http://localhost/store?graph=http://example.com/g".parse()?,
+        )
+        .with_header(HeaderName::ACCEPT, "application/n-triples")?
+        .build();
+        server.test_body(
+            request,
+            "<http://example.com/s> <http://example.com/p> <http://example.com/o> .\n",
+        )?;
+
+        // PATCH - invalid patch
+        let request = Request::builder("PATCH".parse()?, "http://localhost/store/1".parse()?)
+            .with_header(HeaderName::CONTENT_TYPE, "text/rdf-patch")?
+            .with_body("A <http://example.com/s> .");
+        server.test_status(request, Status::BAD_REQUEST)?;
+
+        // PATCH - unsupported media type
+        let request = Request::builder("PATCH".parse()?, "http://localhost/store/1".parse()?)
+            .with_header(HeaderName::CONTENT_TYPE, "text/turtle")?
+            .with_body("<http://example.com/s> <http://example.com/p> <http://example.com/o> .");
+        server.test_status(request, Status::UNSUPPORTED_MEDIA_TYPE)
+    }
+
     #[test]
     fn graph_store_lenient_bulk() -> Result<()> {
         let server = ServerTest::new()?;
@@ -2797,7 +7928,7 @@ mod tests {
         )
         .with_header(HeaderName::CONTENT_TYPE, "text/turtle; charset=utf-8")?
         .with_body(invalid_data);
-        server.test_status(request, Status::NO_CONTENT)?;
+        server.test_status(request, Status::CREATED)?;
 
         // GET of POST
         let request = Request::builder(
@@ -2877,7 +8008,7 @@ mod tests {
         )
         .with_header(HeaderName::CONTENT_TYPE, "text/turtle")?
         .with_body("< s> < p> \"\\uD83D\\uDC68\" .");
-        server.test_status(request, Status::NO_CONTENT)?;
+        server.test_status(request, Status::CREATED)?;
 
         // GET
         let request = Request::builder(
@@ -2936,13 +8067,22 @@ mod tests {
             })
         }
 
-        fn exec(&self, mut request: Request) -> Response {
-            handle_request(&mut request, self.store.clone(), false, false)
-                .unwrap_or_else(|(status, message)| error(status, message))
+        fn exec(&self, request: Request) -> Response {
+            self.exec_with_options(request, &ServerOptions::default())
+        }
+
+        fn exec_read_only(&self, request: Request) -> Response {
+            self.exec_with_options(
+                request,
+                &ServerOptions {
+                    read_only: true,
+                    ..ServerOptions::default()
+                },
+            )
         }
 
-        fn exec_read_only(&self, mut request: Request) -> Response {
-            handle_request(&mut request, self.store.clone(), true, false)
+        fn exec_with_options(&self, mut request: Request, options: &ServerOptions) -> Response {
+            handle_request(&mut request, self.store.clone(), options)
                 .unwrap_or_else(|(status, message)| error(status, message))
         }
 
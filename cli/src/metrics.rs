@@ -0,0 +1,334 @@
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use std::{fs, io};
+
+const DURATION_BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1., 2.5, 5., 10., 30.,
+];
+const SIZE_BUCKETS: &[f64] = &[
+    0.,
+    1.,
+    10.,
+    100.,
+    1_000.,
+    10_000.,
+    100_000.,
+    1_000_000.,
+    10_000_000.,
+];
+
+/// Kinds of SPARQL operations tracked by the metrics
+#[derive(Clone, Copy)]
+pub enum SparqlOperation {
+    Query,
+    Update,
+}
+
+impl SparqlOperation {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Query => "query",
+            Self::Update => "update",
+        }
+    }
+}
+
+/// In-memory registry of the server metrics.
+///
+/// They are exposed in the [Prometheus text format](https://prometheus.io/docs/instrumenting/exposition_formats/) by the `/metrics` endpoint.
+pub struct Metrics {
+    storage_location: Option<PathBuf>,
+    queries: Counters,
+    updates: Counters,
+    query_results: Histogram,
+    bulk_loaded_quads: AtomicU64,
+    bulk_load_duration_micros: AtomicU64,
+    requests_in_flight: AtomicU64,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl Metrics {
+    /// Builds a new registry, the size of the storage is reported if `storage_location` is set.
+    pub fn new(storage_location: Option<PathBuf>) -> Self {
+        Self {
+            storage_location,
+            queries: Counters::default(),
+            updates: Counters::default(),
+            query_results: Histogram::new(SIZE_BUCKETS),
+            bulk_loaded_quads: AtomicU64::new(0),
+            bulk_load_duration_micros: AtomicU64::new(0),
+            requests_in_flight: AtomicU64::new(0),
+        }
+    }
+
+    /// Tracks a request until the returned guard is dropped.
+    pub fn request_started(&self) -> InFlightRequest<'_> {
+        self.requests_in_flight.fetch_add(1, Ordering::Relaxed);
+        InFlightRequest { metrics: self }
+    }
+
+    /// Records the evaluation of a SPARQL operation.
+    ///
+    /// For queries, the duration is the time to the first results because they are streamed.
+    pub fn observe_sparql(&self, operation: SparqlOperation, duration: Duration, success: bool) {
+        let counters = self.counters(operation);
+        counters.total.fetch_add(1, Ordering::Relaxed);
+        if !success {
+            counters.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        counters.duration.observe(duration.as_secs_f64());
+    }
+
+    /// Records the number of solutions or triples returned by a query.
+    pub fn observe_query_results(&self, count: u64) {
+        self.query_results.observe(count as f64);
+    }
+
+    /// Records progress of a bulk load.
+    pub fn observe_bulk_load(&self, quads: u64, duration: Duration) {
+        self.bulk_loaded_quads.fetch_add(quads, Ordering::Relaxed);
+        self.bulk_load_duration_micros.fetch_add(
+            duration.as_micros().try_into().unwrap_or(u64::MAX),
+            Ordering::Relaxed,
+        );
+    }
+
+    /// Serializes the metrics using the Prometheus text format.
+    pub fn render(&self) -> Result<String, std::fmt::Error> {
+        let mut output = String::new();
+        writeln!(
+            output,
+            "# HELP oxigraph_sparql_requests_total Number of evaluated SPARQL operations."
+        )?;
+        writeln!(output, "# TYPE oxigraph_sparql_requests_total counter")?;
+        for operation in [SparqlOperation::Query, SparqlOperation::Update] {
+            writeln!(
+                output,
+                "oxigraph_sparql_requests_total{{operation=\"{}\"}} {}",
+                operation.name(),
+                self.counters(operation).total.load(Ordering::Relaxed)
+            )?;
+        }
+        writeln!(
+            output,
+            "# HELP oxigraph_sparql_errors_total Number of SPARQL operations that failed."
+        )?;
+        writeln!(output, "# TYPE oxigraph_sparql_errors_total counter")?;
+        for operation in [SparqlOperation::Query, SparqlOperation::Update] {
+            writeln!(
+                output,
+                "oxigraph_sparql_errors_total{{operation=\"{}\"}} {}",
+                operation.name(),
+                self.counters(operation).errors.load(Ordering::Relaxed)
+            )?;
+        }
+        writeln!(
+            output,
+            "# HELP oxigraph_sparql_duration_seconds Duration of the SPARQL operations evaluation."
+        )?;
+        writeln!(output, "# TYPE oxigraph_sparql_duration_seconds histogram")?;
+        for operation in [SparqlOperation::Query, SparqlOperation::Update] {
+            self.counters(operation).duration.render(
+                &mut output,
+                "oxigraph_sparql_duration_seconds",
+                &format!("operation=\"{}\",", operation.name()),
+            )?;
+        }
+        writeln!(
+            output,
+            "# HELP oxigraph_query_results Number of solutions or triples returned by the queries."
+        )?;
+        writeln!(output, "# TYPE oxigraph_query_results histogram")?;
+        self.query_results
+            .render(&mut output, "oxigraph_query_results", "")?;
+        writeln!(
+            output,
+            "# HELP oxigraph_bulk_loaded_quads_total Number of quads loaded using the bulk loader."
+        )?;
+        writeln!(output, "# TYPE oxigraph_bulk_loaded_quads_total counter")?;
+        writeln!(
+            output,
+            "oxigraph_bulk_loaded_quads_total {}",
+            self.bulk_loaded_quads.load(Ordering::Relaxed)
+        )?;
+        writeln!(
+            output,
+            "# HELP oxigraph_bulk_load_duration_seconds_total Time spent in the bulk loader."
+        )?;
+        writeln!(
+            output,
+            "# TYPE oxigraph_bulk_load_duration_seconds_total counter"
+        )?;
+        writeln!(
+            output,
+            "oxigraph_bulk_load_duration_seconds_total {}",
+            Duration::from_micros(self.bulk_load_duration_micros.load(Ordering::Relaxed))
+                .as_secs_f64()
+        )?;
+        writeln!(
+            output,
+            "# HELP oxigraph_http_requests_in_flight Number of HTTP requests being processed."
+        )?;
+        writeln!(output, "# TYPE oxigraph_http_requests_in_flight gauge")?;
+        writeln!(
+            output,
+            "oxigraph_http_requests_in_flight {}",
+            self.requests_in_flight.load(Ordering::Relaxed)
+        )?;
+        if let Some(location) = &self.storage_location {
+            if let Ok(size) = directory_size(location) {
+                writeln!(
+                    output,
+                    "# HELP oxigraph_storage_size_bytes Size of the storage directory."
+                )?;
+                writeln!(output, "# TYPE oxigraph_storage_size_bytes gauge")?;
+                writeln!(output, "oxigraph_storage_size_bytes {size}")?;
+            }
+        }
+        Ok(output)
+    }
+
+    fn counters(&self, operation: SparqlOperation) -> &Counters {
+        match operation {
+            SparqlOperation::Query => &self.queries,
+            SparqlOperation::Update => &self.updates,
+        }
+    }
+}
+
+/// Decrements the number of requests in flight when dropped
+pub struct InFlightRequest<'a> {
+    metrics: &'a Metrics,
+}
+
+impl Drop for InFlightRequest<'_> {
+    fn drop(&mut self) {
+        self.metrics
+            .requests_in_flight
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+struct Counters {
+    total: AtomicU64,
+    errors: AtomicU64,
+    duration: Histogram,
+}
+
+impl Default for Counters {
+    fn default() -> Self {
+        Self {
+            total: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+            duration: Histogram::new(DURATION_BUCKETS),
+        }
+    }
+}
+
+struct Histogram {
+    bounds: &'static [f64],
+    /// Number of observations per bucket, the last one is for the values greater than all bounds
+    counts: Vec<AtomicU64>,
+    /// Bits of the f64 sum of all observations
+    sum: AtomicU64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            counts: (0..=bounds.len()).map(|_| AtomicU64::new(0)).collect(),
+            sum: AtomicU64::new(0_f64.to_bits()),
+        }
+    }
+
+    fn observe(&self, value: f64) {
+        let bucket = self
+            .bounds
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(self.bounds.len());
+        self.counts[bucket].fetch_add(1, Ordering::Relaxed);
+        let mut sum = self.sum.load(Ordering::Relaxed);
+        while let Err(current) = self.sum.compare_exchange_weak(
+            sum,
+            (f64::from_bits(sum) + value).to_bits(),
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+        ) {
+            sum = current;
+        }
+    }
+
+    fn render(&self, output: &mut String, name: &str, labels: &str) -> std::fmt::Result {
+        let mut cumulative_count = 0;
+        for (bound, count) in self.bounds.iter().zip(&self.counts) {
+            cumulative_count += count.load(Ordering::Relaxed);
+            writeln!(
+                output,
+                "{name}_bucket{{{labels}le=\"{bound}\"}} {cumulative_count}"
+            )?;
+        }
+        if let Some(count) = self.counts.last() {
+            cumulative_count += count.load(Ordering::Relaxed);
+        }
+        writeln!(
+            output,
+            "{name}_bucket{{{labels}le=\"+Inf\"}} {cumulative_count}"
+        )?;
+        let labels = labels.trim_end_matches(',');
+        let labels = if labels.is_empty() {
+            String::new()
+        } else {
+            format!("{{{labels}}}")
+        };
+        writeln!(
+            output,
+            "{name}_sum{labels} {}",
+            f64::from_bits(self.sum.load(Ordering::Relaxed))
+        )?;
+        writeln!(output, "{name}_count{labels} {cumulative_count}")
+    }
+}
+
+fn directory_size(path: &Path) -> io::Result<u64> {
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        size += if metadata.is_dir() {
+            directory_size(&entry.path())?
+        } else {
+            metadata.len()
+        };
+    }
+    Ok(size)
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram() -> std::fmt::Result {
+        let histogram = Histogram::new(&[1., 10.]);
+        histogram.observe(0.5);
+        histogram.observe(5.);
+        histogram.observe(50.);
+        let mut output = String::new();
+        histogram.render(&mut output, "test", "a=\"b\",")?;
+        assert_eq!(
+            output,
+            "test_bucket{a=\"b\",le=\"1\"} 1\ntest_bucket{a=\"b\",le=\"10\"} 2\ntest_bucket{a=\"b\",le=\"+Inf\"} 3\ntest_sum{a=\"b\"} 55.5\ntest_count{a=\"b\"} 3\n"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,184 @@
+use oxigraph::model::{GraphName, NamedNode, QuadRef, Subject, Term};
+use oxigraph::store::{QuadChange, Store};
+use std::cmp::min;
+use std::io::{self, Read};
+use std::sync::mpsc::{sync_channel, Receiver, RecvTimeoutError, SyncSender};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::Duration;
+
+/// Interval after which a comment is sent to the idle subscribers to keep their connection open
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+/// Number of transactions waiting to be sent to a subscriber after which it is disconnected
+const MAX_PENDING_TRANSACTIONS: usize = 1024;
+
+/// Fan-out of the changes committed to the store to the subscribers of the `/subscribe` endpoint.
+///
+/// The changes are streamed using [Server-Sent Events](https://html.spec.whatwg.org/multipage/server-sent-events.html).
+#[derive(Default)]
+pub struct ChangeNotifier {
+    subscribers: Arc<Mutex<Vec<Subscriber>>>,
+    listening: OnceLock<()>,
+}
+
+struct Subscriber {
+    pattern: QuadPattern,
+    sender: SyncSender<String>,
+}
+
+/// The quads a subscriber is interested in
+#[derive(Default)]
+pub struct QuadPattern {
+    pub subject: Option<Subject>,
+    pub predicate: Option<NamedNode>,
+    pub object: Option<Term>,
+    pub graph_name: Option<GraphName>,
+}
+
+impl QuadPattern {
+    fn matches(&self, quad: QuadRef<'_>) -> bool {
+        self.subject
+            .as_ref()
+            .map_or(true, |s| s.as_ref() == quad.subject)
+            && self
+                .predicate
+                .as_ref()
+                .map_or(true, |p| p.as_ref() == quad.predicate)
+            && self
+                .object
+                .as_ref()
+                .map_or(true, |o| o.as_ref() == quad.object)
+            && self
+                .graph_name
+                .as_ref()
+                .map_or(true, |g| g.as_ref() == quad.graph_name)
+    }
+}
+
+impl ChangeNotifier {
+    /// Subscribes to the changes of the store matching the given pattern.
+    pub fn subscribe(&self, store: &Store, pattern: QuadPattern) -> Subscription {
+        self.listening.get_or_init(|| {
+            let subscribers = Arc::downgrade(&self.subscribers);
+            store.on_commit(move |changes| {
+                if let Some(subscribers) = subscribers.upgrade() {
+                    notify(&subscribers, changes);
+                }
+            });
+        });
+        let (sender, receiver) = sync_channel(MAX_PENDING_TRANSACTIONS);
+        self.subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(Subscriber { pattern, sender });
+        Subscription {
+            receiver,
+            // We send a comment straight away to let the client know the subscription is active
+            buffer: b": subscribed\n\n".to_vec(),
+            position: 0,
+        }
+    }
+}
+
+fn notify(subscribers: &Mutex<Vec<Subscriber>>, changes: &[QuadChange]) {
+    subscribers
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .retain(|subscriber| {
+            let events = serialize_changes(changes, &subscriber.pattern);
+            // The subscribers that are gone or too slow are dropped
+            events.is_empty() || subscriber.sender.try_send(events).is_ok()
+        });
+}
+
+/// Serializes the changes as `insert` and `delete` events whose data is the N-Quads serialization of the quads
+///
+/// Consecutive changes of the same kind are grouped in a single event.
+fn serialize_changes(changes: &[QuadChange], pattern: &QuadPattern) -> String {
+    let mut events = String::new();
+    let mut current_kind = None;
+    for change in changes {
+        let (kind, quad) = match change {
+            QuadChange::Insert(quad) => ("insert", quad),
+            QuadChange::Remove(quad) => ("delete", quad),
+            // Subscribers are only notified of the quad changes
+            QuadChange::InsertNamedGraph(_) | QuadChange::RemoveNamedGraph(_) => continue,
+        };
+        if !pattern.matches(quad.as_ref()) {
+            continue;
+        }
+        if current_kind != Some(kind) {
+            if current_kind.is_some() {
+                events.push('\n');
+            }
+            events.push_str("event: ");
+            events.push_str(kind);
+            events.push('\n');
+            current_kind = Some(kind);
+        }
+        events.push_str("data: ");
+        events.push_str(&quad.to_string());
+        events.push_str(" .\n");
+    }
+    if current_kind.is_some() {
+        events.push('\n');
+    }
+    events
+}
+
+/// The event stream of a subscription, it ends if the subscriber is too slow to read the events.
+pub struct Subscription {
+    receiver: Receiver<String>,
+    buffer: Vec<u8>,
+    position: usize,
+}
+
+impl Read for Subscription {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position == self.buffer.len() {
+            self.buffer = match self.receiver.recv_timeout(KEEP_ALIVE_INTERVAL) {
+                Ok(events) => events.into_bytes(),
+                // Writing something allows to detect closed connections
+                Err(RecvTimeoutError::Timeout) => b": keep-alive\n\n".to_vec(),
+                Err(RecvTimeoutError::Disconnected) => return Ok(0),
+            };
+            self.position = 0;
+        }
+        let len = min(self.buffer.len() - self.position, buf.len());
+        buf[..len].copy_from_slice(&self.buffer[self.position..self.position + len]);
+        self.position += len;
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxigraph::model::{GraphNameRef, NamedNodeRef, Quad};
+
+    #[test]
+    fn test_serialize_changes() {
+        let a = NamedNodeRef::new_unchecked("http://example.com/a");
+        let b = NamedNodeRef::new_unchecked("http://example.com/b");
+        let changes = [
+            QuadChange::Insert(Quad::new(a, a, a, GraphNameRef::DefaultGraph)),
+            QuadChange::Insert(Quad::new(b, a, a, GraphNameRef::DefaultGraph)),
+            QuadChange::Remove(Quad::new(a, a, a, b)),
+            QuadChange::Insert(Quad::new(b, b, b, b)),
+        ];
+        assert_eq!(
+            serialize_changes(&changes, &QuadPattern::default()),
+            "event: insert\ndata: <http://example.com/a> <http://example.com/a> <http://example.com/a> .\ndata: <http://example.com/b> <http://example.com/a> <http://example.com/a> .\n\nevent: delete\ndata: <http://example.com/a> <http://example.com/a> <http://example.com/a> <http://example.com/b> .\n\nevent: insert\ndata: <http://example.com/b> <http://example.com/b> <http://example.com/b> <http://example.com/b> .\n\n"
+        );
+        assert_eq!(
+            serialize_changes(
+                &changes,
+                &QuadPattern {
+                    predicate: Some(a.into_owned()),
+                    graph_name: Some(b.into()),
+                    ..QuadPattern::default()
+                }
+            ),
+            "event: delete\ndata: <http://example.com/a> <http://example.com/a> <http://example.com/a> <http://example.com/b> .\n\n"
+        );
+    }
+}
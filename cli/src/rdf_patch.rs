@@ -0,0 +1,562 @@
+//! A parser and a serializer for the [RDF Patch](https://afs.github.io/rdf-patch/) format.
+//!
+//! The `GA <graph> .` and `GD <graph> .` rows adding and deleting a named graph are an Oxigraph extension:
+//! they allow to keep the empty named graphs in sync.
+
+use oxigraph::model::{
+    BlankNode, GraphName, Literal, NamedNode, NamedOrBlankNode, Quad, Subject, Term,
+};
+use oxigraph::store::{StorageError, Transaction};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::iter::Peekable;
+use std::mem::take;
+use std::str::CharIndices;
+
+/// An operation of a RDF patch.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum PatchOperation {
+    Add(Quad),
+    Delete(Quad),
+    AddGraph(NamedOrBlankNode),
+    DeleteGraph(NamedOrBlankNode),
+}
+
+impl PatchOperation {
+    /// Applies the operation inside of a store transaction.
+    pub fn apply(&self, transaction: &mut Transaction<'_>) -> Result<(), StorageError> {
+        match self {
+            Self::Add(quad) => transaction.insert(quad)?,
+            Self::Delete(quad) => transaction.remove(quad)?,
+            Self::AddGraph(graph_name) => transaction.insert_named_graph(graph_name)?,
+            Self::DeleteGraph(graph_name) => transaction.remove_named_graph(graph_name)?,
+        };
+        Ok(())
+    }
+}
+
+/// Parses a [RDF Patch](https://afs.github.io/rdf-patch/) document.
+///
+/// Headers are ignored and the operations inside of aborted transactions (`TX` ... `TA`) are dropped.
+pub fn parse_rdf_patch(input: &str) -> Result<Vec<PatchOperation>, RdfPatchSyntaxError> {
+    Ok(parse_rdf_patch_transactions(input)?
+        .into_iter()
+        .flatten()
+        .collect())
+}
+
+/// Serializes the operations as a [RDF Patch](https://afs.github.io/rdf-patch/) document made of a single `TX` ... `TC` transaction.
+pub fn serialize_rdf_patch(operations: &[PatchOperation]) -> String {
+    let mut patch = String::from("TX .\n");
+    for operation in operations {
+        let (keyword, value) = match operation {
+            PatchOperation::Add(quad) => ("A", quad.to_string()),
+            PatchOperation::Delete(quad) => ("D", quad.to_string()),
+            PatchOperation::AddGraph(graph_name) => ("GA", graph_name.to_string()),
+            PatchOperation::DeleteGraph(graph_name) => ("GD", graph_name.to_string()),
+        };
+        patch.push_str(keyword);
+        patch.push(' ');
+        patch.push_str(&value);
+        patch.push_str(" .\n");
+    }
+    patch.push_str("TC .\n");
+    patch
+}
+
+/// Parses a [RDF Patch](https://afs.github.io/rdf-patch/) document into the operations of each of its transactions.
+///
+/// The operations outside of `TX` ... `TC` blocks are grouped into their own transactions.
+/// Headers are ignored and aborted transactions (`TX` ... `TA`) are dropped.
+pub fn parse_rdf_patch_transactions(
+    input: &str,
+) -> Result<Vec<Vec<PatchOperation>>, RdfPatchSyntaxError> {
+    let mut lexer = Lexer {
+        input,
+        chars: input.char_indices().peekable(),
+        line: 1,
+    };
+    let mut prefixes = HashMap::new();
+    let mut transactions = Vec::new();
+    let mut operations = Vec::new();
+    let mut in_transaction = false;
+    while let Some(token) = lexer.next_token()? {
+        let Token::Keyword(keyword) = token else {
+            return Err(lexer.error(format!("Expecting a row keyword, found {token:?}")));
+        };
+        let mut arguments = Vec::new();
+        loop {
+            match lexer.next_token()? {
+                Some(Token::Dot) => break,
+                Some(token) => arguments.push(token),
+                None => return Err(lexer.error("Unexpected end of file, a row must end with '.'")),
+            }
+        }
+        match keyword {
+            "A" | "D" => {
+                let quad = lexer.quad(arguments, &prefixes)?;
+                operations.push(if keyword == "A" {
+                    PatchOperation::Add(quad)
+                } else {
+                    PatchOperation::Delete(quad)
+                });
+            }
+            "GA" | "GD" => {
+                let graph_name = lexer.graph_name(arguments, &prefixes)?;
+                operations.push(if keyword == "GA" {
+                    PatchOperation::AddGraph(graph_name)
+                } else {
+                    PatchOperation::DeleteGraph(graph_name)
+                });
+            }
+            "PA" => {
+                let [Token::PrefixedName(prefix, ""), Token::Iri(iri)] = arguments.as_slice()
+                else {
+                    return Err(lexer.error("PA rows must be of the form 'PA prefix: <iri> .'"));
+                };
+                prefixes.insert(*prefix, *iri);
+            }
+            "PD" => {
+                let [Token::PrefixedName(prefix, "")] = arguments.as_slice() else {
+                    return Err(lexer.error("PD rows must be of the form 'PD prefix: .'"));
+                };
+                prefixes.remove(prefix);
+            }
+            "H" => (),
+            "TX" | "TC" => {
+                if !operations.is_empty() {
+                    transactions.push(take(&mut operations));
+                }
+                in_transaction = keyword == "TX";
+            }
+            "TA" => {
+                if in_transaction {
+                    operations.clear();
+                }
+                in_transaction = false;
+            }
+            _ => return Err(lexer.error(format!("Unknown row keyword {keyword}"))),
+        }
+    }
+    if !operations.is_empty() {
+        transactions.push(operations);
+    }
+    Ok(transactions)
+}
+
+#[derive(Debug)]
+enum Token<'a> {
+    Keyword(&'a str),
+    Iri(&'a str),
+    PrefixedName(&'a str, &'a str),
+    BlankNode(&'a str),
+    Literal {
+        value: String,
+        language: Option<&'a str>,
+        datatype: Option<Box<Token<'a>>>,
+    },
+    Dot,
+}
+
+struct Lexer<'a> {
+    input: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    line: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn next_token(&mut self) -> Result<Option<Token<'a>>, RdfPatchSyntaxError> {
+        loop {
+            let Some(&(start, c)) = self.chars.peek() else {
+                return Ok(None);
+            };
+            match c {
+                '\n' => {
+                    self.line += 1;
+                    self.chars.next();
+                }
+                '#' => while self.chars.next_if(|(_, c)| *c != '\n').is_some() {},
+                _ if c.is_whitespace() => {
+                    self.chars.next();
+                }
+                '<' => {
+                    self.chars.next();
+                    let end = self.consume_until('>')?;
+                    return Ok(Some(Token::Iri(&self.input[start + 1..end])));
+                }
+                '"' => {
+                    self.chars.next();
+                    return self.literal().map(Some);
+                }
+                '.' => {
+                    self.chars.next();
+                    return Ok(Some(Token::Dot));
+                }
+                _ => return Ok(Some(self.word(start))),
+            }
+        }
+    }
+
+    fn consume_until(&mut self, end: char) -> Result<usize, RdfPatchSyntaxError> {
+        for (i, c) in self.chars.by_ref() {
+            if c == end {
+                return Ok(i);
+            }
+            if c == '\n' {
+                break;
+            }
+        }
+        Err(self.error(format!("Expecting '{end}' before the end of the line")))
+    }
+
+    fn word(&mut self, start: usize) -> Token<'a> {
+        let is_word_char = |c: char| !c.is_whitespace() && !matches!(c, '<' | '>' | '"' | '#');
+        let mut end = start;
+        let mut chars = self.input[start..].chars().peekable();
+        while let Some(c) = chars.next() {
+            if !is_word_char(c) {
+                break;
+            }
+            if c == '.' && end > start && !chars.peek().is_some_and(|c| is_word_char(*c)) {
+                // A final dot is the end of the row
+                break;
+            }
+            end += c.len_utf8();
+        }
+        while self.chars.next_if(|(i, _)| *i < end).is_some() {}
+        let word = &self.input[start..end];
+        if let Some(label) = word.strip_prefix("_:") {
+            Token::BlankNode(label)
+        } else if let Some((prefix, local)) = word.split_once(':') {
+            Token::PrefixedName(prefix, local)
+        } else {
+            Token::Keyword(word)
+        }
+    }
+
+    fn literal(&mut self) -> Result<Token<'a>, RdfPatchSyntaxError> {
+        let mut value = String::new();
+        loop {
+            let Some((_, c)) = self.chars.next() else {
+                return Err(self.error("Unexpected end of file in a literal"));
+            };
+            match c {
+                '"' => break,
+                '\n' => return Err(self.error("Unexpected end of line in a literal")),
+                '\\' => value.push(self.escape()?),
+                _ => value.push(c),
+            }
+        }
+        let mut language = None;
+        let mut datatype = None;
+        if let Some(&(start, '@')) = self.chars.peek() {
+            self.chars.next();
+            let mut end = start + 1;
+            while let Some((i, c)) = self
+                .chars
+                .next_if(|(_, c)| c.is_ascii_alphanumeric() || *c == '-')
+            {
+                end = i + c.len_utf8();
+            }
+            language = Some(&self.input[start + 1..end]);
+        } else if self.chars.next_if(|(_, c)| *c == '^').is_some() {
+            if self.chars.next_if(|(_, c)| *c == '^').is_none() {
+                return Err(self.error("Expecting '^^' before a literal datatype"));
+            }
+            datatype = match self.next_token()? {
+                Some(token @ (Token::Iri(_) | Token::PrefixedName(..))) => Some(Box::new(token)),
+                _ => return Err(self.error("Expecting an IRI after '^^'")),
+            };
+        }
+        Ok(Token::Literal {
+            value,
+            language,
+            datatype,
+        })
+    }
+
+    fn escape(&mut self) -> Result<char, RdfPatchSyntaxError> {
+        let Some((_, c)) = self.chars.next() else {
+            return Err(self.error("Unexpected end of file in an escape sequence"));
+        };
+        Ok(match c {
+            't' => '\t',
+            'b' => '\u{8}',
+            'n' => '\n',
+            'r' => '\r',
+            'f' => '\u{C}',
+            '"' => '"',
+            '\'' => '\'',
+            '\\' => '\\',
+            'u' => self.unicode_escape(4)?,
+            'U' => self.unicode_escape(8)?,
+            _ => return Err(self.error(format!("Invalid escape sequence '\\{c}'"))),
+        })
+    }
+
+    fn unicode_escape(&mut self, len: usize) -> Result<char, RdfPatchSyntaxError> {
+        let mut code_point = 0;
+        for _ in 0..len {
+            let digit = self
+                .chars
+                .next()
+                .and_then(|(_, c)| c.to_digit(16))
+                .ok_or_else(|| self.error("Invalid unicode escape sequence"))?;
+            code_point = code_point * 16 + digit;
+        }
+        char::from_u32(code_point).ok_or_else(|| self.error("Invalid unicode code point"))
+    }
+
+    fn quad(
+        &self,
+        arguments: Vec<Token<'a>>,
+        prefixes: &HashMap<&str, &str>,
+    ) -> Result<Quad, RdfPatchSyntaxError> {
+        let mut arguments = arguments.into_iter();
+        let (Some(subject), Some(predicate), Some(object)) =
+            (arguments.next(), arguments.next(), arguments.next())
+        else {
+            return Err(self.error("A and D rows must contain a triple or a quad"));
+        };
+        let graph_name = arguments.next();
+        if arguments.next().is_some() {
+            return Err(self.error("A and D rows must contain a triple or a quad"));
+        }
+        Ok(Quad::new(
+            match self.term(subject, prefixes)? {
+                Term::NamedNode(node) => Subject::NamedNode(node),
+                Term::BlankNode(node) => Subject::BlankNode(node),
+                _ => return Err(self.error("Literals are not allowed in subject position")),
+            },
+            match self.term(predicate, prefixes)? {
+                Term::NamedNode(node) => node,
+                _ => return Err(self.error("Only IRIs are allowed in predicate position")),
+            },
+            self.term(object, prefixes)?,
+            if let Some(graph_name) = graph_name {
+                match self.term(graph_name, prefixes)? {
+                    Term::NamedNode(node) => GraphName::NamedNode(node),
+                    Term::BlankNode(node) => GraphName::BlankNode(node),
+                    _ => return Err(self.error("Literals are not allowed in graph name position")),
+                }
+            } else {
+                GraphName::DefaultGraph
+            },
+        ))
+    }
+
+    fn graph_name(
+        &self,
+        arguments: Vec<Token<'a>>,
+        prefixes: &HashMap<&str, &str>,
+    ) -> Result<NamedOrBlankNode, RdfPatchSyntaxError> {
+        let mut arguments = arguments.into_iter();
+        let (Some(graph_name), None) = (arguments.next(), arguments.next()) else {
+            return Err(self.error("GA and GD rows must contain a single graph name"));
+        };
+        match self.term(graph_name, prefixes)? {
+            Term::NamedNode(node) => Ok(node.into()),
+            Term::BlankNode(node) => Ok(node.into()),
+            _ => Err(self.error("Literals are not allowed as graph names")),
+        }
+    }
+
+    fn term(
+        &self,
+        token: Token<'a>,
+        prefixes: &HashMap<&str, &str>,
+    ) -> Result<Term, RdfPatchSyntaxError> {
+        Ok(match token {
+            Token::Iri(_) | Token::PrefixedName(..) => self.named_node(&token, prefixes)?.into(),
+            Token::BlankNode(label) => BlankNode::new(label)
+                .map_err(|e| self.error(format!("Invalid blank node label: {e}")))?
+                .into(),
+            Token::Literal {
+                value,
+                language,
+                datatype,
+            } => if let Some(language) = language {
+                Literal::new_language_tagged_literal(value, language)
+                    .map_err(|e| self.error(format!("Invalid language tag: {e}")))?
+            } else if let Some(datatype) = datatype {
+                Literal::new_typed_literal(value, self.named_node(&datatype, prefixes)?)
+            } else {
+                Literal::new_simple_literal(value)
+            }
+            .into(),
+            Token::Keyword(word) => return Err(self.error(format!("Unexpected term {word}"))),
+            Token::Dot => return Err(self.error("Unexpected '.'")),
+        })
+    }
+
+    fn named_node(
+        &self,
+        token: &Token<'a>,
+        prefixes: &HashMap<&str, &str>,
+    ) -> Result<NamedNode, RdfPatchSyntaxError> {
+        let iri = match token {
+            Token::Iri(iri) => (*iri).to_owned(),
+            Token::PrefixedName(prefix, local) => {
+                let Some(namespace) = prefixes.get(prefix) else {
+                    return Err(self.error(format!("The prefix {prefix}: is not defined")));
+                };
+                format!("{namespace}{local}")
+            }
+            _ => return Err(self.error("Expecting an IRI")),
+        };
+        NamedNode::new(iri).map_err(|e| self.error(format!("Invalid IRI: {e}")))
+    }
+
+    fn error(&self, message: impl Into<String>) -> RdfPatchSyntaxError {
+        RdfPatchSyntaxError {
+            line: self.line,
+            message: message.into(),
+        }
+    }
+}
+
+/// An error in the syntax of a RDF patch.
+#[derive(Debug)]
+pub struct RdfPatchSyntaxError {
+    line: usize,
+    message: String,
+}
+
+impl fmt::Display for RdfPatchSyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RDF patch error on line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for RdfPatchSyntaxError {}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+    use oxigraph::model::vocab::xsd;
+
+    #[test]
+    fn parse_patch() -> Result<(), RdfPatchSyntaxError> {
+        let s = NamedNode::new_unchecked("http://example.com/s");
+        let p = NamedNode::new_unchecked("http://example.com/p");
+        let g = NamedNode::new_unchecked("http://example.com/g");
+        assert_eq!(
+            parse_rdf_patch(
+                r#"H id <uuid:0a2b5e2c> .
+# A comment
+PA ex: <http://example.com/> .
+TX .
+A ex:s ex:p "foo\n"@en .
+D <http://example.com/s> ex:p _:b1 ex:g .
+TC .
+TX .
+A ex:s ex:p "aborted" .
+TA .
+PD ex: .
+A <http://example.com/s> <http://example.com/p> "1"^^<http://www.w3.org/2001/XMLSchema#integer>.
+"#
+            )?,
+            vec![
+                PatchOperation::Add(Quad::new(
+                    s.clone(),
+                    p.clone(),
+                    Literal::new_language_tagged_literal_unchecked("foo\n", "en"),
+                    GraphName::DefaultGraph
+                )),
+                PatchOperation::Delete(Quad::new(
+                    s.clone(),
+                    p.clone(),
+                    BlankNode::new_unchecked("b1"),
+                    g
+                )),
+                PatchOperation::Add(Quad::new(
+                    s,
+                    p,
+                    Literal::new_typed_literal("1", xsd::INTEGER),
+                    GraphName::DefaultGraph
+                )),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_patch_transactions() -> Result<(), RdfPatchSyntaxError> {
+        let ex = NamedNode::new_unchecked("http://example.com");
+        let quad = Quad::new(ex.clone(), ex.clone(), ex, GraphName::DefaultGraph);
+        assert_eq!(
+            parse_rdf_patch_transactions(
+                "A <http://example.com> <http://example.com> <http://example.com> .
+TX .
+D <http://example.com> <http://example.com> <http://example.com> .
+A <http://example.com> <http://example.com> <http://example.com> .
+TC .
+TX .
+D <http://example.com> <http://example.com> <http://example.com> .
+TA .
+TX .
+TC .
+"
+            )?,
+            vec![
+                vec![PatchOperation::Add(quad.clone())],
+                vec![
+                    PatchOperation::Delete(quad.clone()),
+                    PatchOperation::Add(quad)
+                ],
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn serialize_patch() -> Result<(), RdfPatchSyntaxError> {
+        let ex = NamedNode::new_unchecked("http://example.com");
+        let operations = vec![
+            PatchOperation::Delete(Quad::new(
+                ex.clone(),
+                ex.clone(),
+                BlankNode::new_unchecked("b1"),
+                ex.clone(),
+            )),
+            PatchOperation::Add(Quad::new(
+                ex.clone(),
+                ex.clone(),
+                Literal::new_language_tagged_literal_unchecked("foo\n", "en"),
+                GraphName::DefaultGraph,
+            )),
+            PatchOperation::AddGraph(BlankNode::new_unchecked("g").into()),
+            PatchOperation::DeleteGraph(ex.into()),
+        ];
+        let patch = serialize_rdf_patch(&operations);
+        assert_eq!(
+            patch,
+            "TX .
+D <http://example.com> <http://example.com> _:b1 <http://example.com> .
+A <http://example.com> <http://example.com> \"foo\\n\"@en .
+GA _:g .
+GD <http://example.com> .
+TC .
+"
+        );
+        assert_eq!(parse_rdf_patch(&patch)?, operations);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_invalid_patch() {
+        for patch in [
+            "A <http://example.com/s> <http://example.com/p> .",
+            "A <http://example.com/s> <http://example.com/p> <http://example.com/o>",
+            "A ex:s ex:p ex:o .",
+            "X <http://example.com/s> <http://example.com/p> <http://example.com/o> .",
+            "A \"s\" <http://example.com/p> <http://example.com/o> .",
+            "GA \"g\" .",
+            "GD <http://example.com/g> <http://example.com/g> .",
+        ] {
+            parse_rdf_patch(patch).unwrap_err();
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use crate::rdf_patch::parse_rdf_patch_transactions;
+use anyhow::{bail, Context};
+use oxhttp::model::{HeaderName, Method, Request, Status, Url};
+use oxhttp::Client;
+use oxigraph::model::GraphNameRef;
+use oxigraph::store::{QuadChange, StorageError, Store};
+use oxsdatatypes::Base64Binary;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::{sleep, Builder};
+use std::time::{Duration, Instant};
+
+/// Default duration between two requests of a replica to the primary history
+pub const DEFAULT_REPLICATION_INTERVAL: Duration = Duration::from_secs(1);
+/// Maximal number of history entries returned by a single request to `/history`
+const MAX_HISTORY_ENTRIES_PER_REQUEST: usize = 1000;
+/// Timeout of the requests of a replica to the primary
+const REPLICATION_TIMEOUT: Duration = Duration::from_secs(60);
+/// Default graph property storing the last primary history version applied to a replica
+const REPLICATED_VERSION_PROPERTY: &str = "urn:oxigraph:replication:version";
+/// Prefix of the `H id` header values identifying the primary history versions
+const HISTORY_VERSION_IRI_PREFIX: &str = "urn:oxigraph:history:";
+
+/// Serializes the history entries created after the given version as a [RDF Patch](https://afs.github.io/rdf-patch/).
+///
+/// Each entry is a `TX` ... `TC` transaction preceded by a `H id` header identifying the version it creates.
+/// At most 1000 entries are returned, the replicas request the next ones afterward.
+///
+/// The bulk loads are not recorded in the history: the serialization stops before them
+/// and `None` is returned if the entry following the given version is a bulk load.
+/// The replicas must then be synchronized again from [`serialize_snapshot`].
+pub fn serialize_history(store: &Store, since: u64) -> Result<Option<String>, StorageError> {
+    let mut patch = String::new();
+    for entry in store
+        .history_since(since)?
+        .take(MAX_HISTORY_ENTRIES_PER_REQUEST)
+    {
+        let entry = entry?;
+        if entry.is_bulk_load() {
+            if patch.is_empty() {
+                return Ok(None);
+            }
+            break;
+        }
+        write_version_header(&mut patch, entry.version());
+        patch.push_str("TX .\n");
+        for change in entry.changes() {
+            let (keyword, value) = match change {
+                QuadChange::Insert(quad) => ("A", quad.to_string()),
+                QuadChange::Remove(quad) => ("D", quad.to_string()),
+                QuadChange::InsertNamedGraph(graph_name) => ("GA", graph_name.to_string()),
+                QuadChange::RemoveNamedGraph(graph_name) => ("GD", graph_name.to_string()),
+            };
+            write_row(&mut patch, keyword, &value);
+        }
+        patch.push_str("TC .\n");
+    }
+    Ok(Some(patch))
+}
+
+/// Serializes the current content of the store as a [RDF Patch](https://afs.github.io/rdf-patch/)
+/// made of a single `TX` ... `TC` transaction adding all the named graphs and quads.
+///
+/// It is preceded by a `H id` header identifying the history version the content is at.
+/// Fails if the store history is not enabled.
+pub fn serialize_snapshot(store: &Store) -> Result<String, StorageError> {
+    let snapshot = store.snapshot();
+    let version = snapshot
+        .history_version()?
+        .ok_or_else(|| StorageError::Other("The store history is not enabled".into()))?;
+    let mut patch = String::new();
+    write_version_header(&mut patch, version);
+    patch.push_str("TX .\n");
+    for graph_name in snapshot.named_graphs() {
+        write_row(&mut patch, "GA", &graph_name?.to_string());
+    }
+    for quad in &snapshot {
+        write_row(&mut patch, "A", &quad?.to_string());
+    }
+    patch.push_str("TC .\n");
+    Ok(patch)
+}
+
+fn write_version_header(patch: &mut String, version: u64) {
+    patch.push_str("H id <");
+    patch.push_str(HISTORY_VERSION_IRI_PREFIX);
+    patch.push_str(&version.to_string());
+    patch.push_str("> .\n");
+}
+
+fn write_row(patch: &mut String, keyword: &str, value: &str) {
+    patch.push_str(keyword);
+    patch.push(' ');
+    patch.push_str(value);
+    patch.push_str(" .\n");
+}
+
+/// Returns the last primary history version applied to the store, `None` if the store has never been replicated.
+///
+/// The stores restored from a backup of the primary that have never been replicated are at their own history version.
+pub fn replicated_version(store: &Store) -> anyhow::Result<Option<u64>> {
+    if let Some(version) = store
+        .graph_metadata(GraphNameRef::DefaultGraph)?
+        .and_then(|metadata| {
+            metadata
+                .property(REPLICATED_VERSION_PROPERTY)
+                .map(str::to_owned)
+        })
+    {
+        return Ok(Some(version.parse().with_context(|| {
+            format!("Invalid replicated version {version}")
+        })?));
+    }
+    Ok(store.history_version()?)
+}
+
+/// Applies the transactions of a patch returned by [`serialize_history`] to the store, one store transaction per history entry.
+///
+/// Each store transaction also records the primary version it brings the store to, see [`replicated_version`].
+/// Returns the number of applied transactions.
+pub fn apply_history(store: &Store, patch: &str) -> anyhow::Result<usize> {
+    let transactions = parse_rdf_patch_transactions(patch)?;
+    let version = replicated_version(store)?
+        .context("The replica store must be synchronized from a snapshot of the primary first")?;
+    // The primary versions are consecutive
+    for (version, operations) in (version + 1..).zip(&transactions) {
+        store.transaction(|mut transaction| {
+            for operation in operations {
+                operation.apply(&mut transaction)?;
+            }
+            transaction.set_graph_property(
+                GraphNameRef::DefaultGraph,
+                REPLICATED_VERSION_PROPERTY,
+                Some(&version.to_string()),
+            )
+        })?;
+    }
+    Ok(transactions.len())
+}
+
+/// Replaces the content of the store with a patch returned by [`serialize_snapshot`] in a single store transaction.
+///
+/// Returns the primary version the store is now at.
+pub fn apply_snapshot(store: &Store, patch: &str) -> anyhow::Result<u64> {
+    let version = patch
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix("H id <"))
+        .and_then(|line| line.strip_prefix(HISTORY_VERSION_IRI_PREFIX))
+        .and_then(|line| line.strip_suffix("> ."))
+        .context("The snapshot must start with a 'H id' header with its history version")?
+        .parse::<u64>()
+        .context("Invalid snapshot history version")?;
+    let operations = parse_rdf_patch_transactions(patch)?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    store.transaction(|mut transaction| {
+        transaction.clear()?;
+        for operation in &operations {
+            operation.apply(&mut transaction)?;
+        }
+        transaction.set_graph_property(
+            GraphNameRef::DefaultGraph,
+            REPLICATED_VERSION_PROPERTY,
+            Some(&version.to_string()),
+        )
+    })?;
+    Ok(version)
+}
+
+/// Credentials used to authenticate to the primary server
+pub enum PrimaryCredentials {
+    /// HTTP basic authentication
+    Basic { username: String, password: String },
+    /// HTTP bearer authentication
+    Bearer(String),
+}
+
+impl PrimaryCredentials {
+    fn authorization(&self) -> String {
+        match self {
+            Self::Basic { username, password } => format!(
+                "Basic {}",
+                Base64Binary::from(format!("{username}:{password}").into_bytes())
+            ),
+            Self::Bearer(token) => format!("Bearer {token}"),
+        }
+    }
+}
+
+/// Tracks when a replica was last in sync with its primary server
+#[derive(Default)]
+pub struct ReplicationStatus {
+    last_sync: Mutex<Option<Instant>>,
+}
+
+impl ReplicationStatus {
+    /// Records that the replica has applied all the primary history
+    pub fn record_sync(&self) {
+        *self
+            .last_sync
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(Instant::now());
+    }
+
+    /// The duration since the replica has last applied all the primary history, `None` if it has never been the case
+    pub fn lag(&self) -> Option<Duration> {
+        self.last_sync
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .map(|last_sync| last_sync.elapsed())
+    }
+}
+
+/// Starts a thread that keeps the store in sync with a primary server by applying the new entries of its history.
+///
+/// The replication resumes from the last applied primary version, see [`replicated_version`].
+/// If the store has never been replicated or if the primary has done a bulk load since,
+/// the store content is first replaced by a snapshot of the primary.
+/// Each time the store has caught up with the primary history, it is recorded in `status`.
+#[allow(clippy::infinite_loop)]
+pub fn start_replication(
+    store: Store,
+    primary: &str,
+    credentials: Option<&PrimaryCredentials>,
+    interval: Duration,
+    status: Arc<ReplicationStatus>,
+) -> anyhow::Result<()> {
+    let mut history_url =
+        Url::parse(primary).with_context(|| format!("Invalid primary server URL {primary}"))?;
+    history_url
+        .path_segments_mut()
+        .map_err(|()| anyhow::anyhow!("Invalid primary server URL {primary}"))?
+        .pop_if_empty()
+        .push("history");
+    let authorization = credentials.map(PrimaryCredentials::authorization);
+    let client = Client::new()
+        .with_user_agent(concat!("Oxigraph/", env!("CARGO_PKG_VERSION")))?
+        .with_global_timeout(REPLICATION_TIMEOUT);
+    Builder::new()
+        .name("replication".into())
+        .spawn(move || loop {
+            match replicate(&client, &history_url, authorization.as_deref(), &store) {
+                // There might be more entries to fetch straight away
+                Ok(count) if count > 0 => (),
+                Ok(_) => {
+                    status.record_sync();
+                    sleep(interval)
+                }
+                Err(e) => {
+                    eprintln!("Replication from {history_url} failed: {e:#}");
+                    sleep(interval)
+                }
+            }
+        })?;
+    Ok(())
+}
+
+/// Fetches the primary history entries the store does not have yet and applies them
+///
+/// Falls back to a full synchronization from a snapshot if the history entries can't be applied.
+fn replicate(
+    client: &Client,
+    history_url: &Url,
+    authorization: Option<&str>,
+    store: &Store,
+) -> anyhow::Result<usize> {
+    if let Some(version) = replicated_version(store)? {
+        let mut url = history_url.clone();
+        url.query_pairs_mut()
+            .append_pair("since", &version.to_string());
+        let (status, body) = fetch_patch(client, url, authorization)?;
+        if status != Status::CONFLICT {
+            if !status.is_successful() {
+                bail!("The primary server returned {status}: {body}");
+            }
+            return apply_history(store, &body);
+        }
+    }
+    let mut url = history_url.clone();
+    url.path_segments_mut()
+        .map_err(|()| anyhow::anyhow!("Invalid primary server URL {history_url}"))?
+        .push("snapshot");
+    let (status, body) = fetch_patch(client, url, authorization)?;
+    if !status.is_successful() {
+        bail!("The primary server returned {status}: {body}");
+    }
+    apply_snapshot(store, &body)?;
+    Ok(1)
+}
+
+fn fetch_patch(
+    client: &Client,
+    url: Url,
+    authorization: Option<&str>,
+) -> anyhow::Result<(Status, String)> {
+    let mut request =
+        Request::builder(Method::GET, url).with_header(HeaderName::ACCEPT, "text/rdf-patch")?;
+    if let Some(authorization) = authorization {
+        request = request.with_header(HeaderName::AUTHORIZATION, authorization.to_owned())?;
+    }
+    let response = client.request(request.build())?;
+    let status = response.status();
+    Ok((status, response.into_body().to_string()?))
+}
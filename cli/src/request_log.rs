@@ -0,0 +1,107 @@
+use oxigraph::sparql::QueryExplanation;
+use std::time::{Duration, Instant};
+
+/// Logging of the SPARQL requests on stderr
+#[derive(Clone, Default)]
+pub struct RequestLog {
+    /// Logs all SPARQL requests
+    pub enabled: bool,
+    /// Adds the normalized query text to the log entries
+    pub with_query_text: bool,
+    /// Logs the queries slower than this duration with their explanation
+    pub slow_query_threshold: Option<Duration>,
+}
+
+impl RequestLog {
+    /// If the query text should be given to [`start_query`](Self::start_query)
+    pub fn needs_query_text(&self) -> bool {
+        self.with_query_text || self.slow_query_threshold.is_some()
+    }
+
+    /// Starts the log entry of a query.
+    ///
+    /// `explanation` should be set if [`slow_query_threshold`](Self::slow_query_threshold) is set.
+    pub fn start_query(
+        &self,
+        start: Instant,
+        query: Option<String>,
+        explanation: Option<QueryExplanation>,
+    ) -> QueryLogEntry {
+        QueryLogEntry {
+            log: self.clone(),
+            start,
+            query,
+            explanation,
+        }
+    }
+
+    /// Logs a SPARQL operation that is already done.
+    pub fn log(&self, operation: &str, status: u16, duration: Duration) {
+        if self.enabled {
+            write_entry(operation, status, duration, None, None);
+        }
+    }
+}
+
+/// The log entry of a query whose results are being streamed
+pub struct QueryLogEntry {
+    log: RequestLog,
+    start: Instant,
+    query: Option<String>,
+    explanation: Option<QueryExplanation>,
+}
+
+impl QueryLogEntry {
+    /// Writes the entry once all the results have been returned.
+    pub fn finish(self, result_count: u64) {
+        let duration = self.start.elapsed();
+        if self.log.enabled {
+            write_entry(
+                "query",
+                200,
+                duration,
+                Some(result_count),
+                self.query.as_deref().filter(|_| self.log.with_query_text),
+            );
+        }
+        if self
+            .log
+            .slow_query_threshold
+            .is_some_and(|threshold| duration >= threshold)
+        {
+            let mut explanation = Vec::new();
+            if let Some(e) = &self.explanation {
+                if let Err(e) = e.write_in_json(&mut explanation) {
+                    eprintln!("Failed to serialize the query explanation: {e}");
+                }
+            }
+            eprintln!(
+                "slow query duration={:.3} results={result_count} query={:?}\n{}",
+                duration.as_secs_f64(),
+                self.query.as_deref().unwrap_or_default(),
+                String::from_utf8_lossy(&explanation)
+            );
+        }
+    }
+}
+
+fn write_entry(
+    operation: &str,
+    status: u16,
+    duration: Duration,
+    result_count: Option<u64>,
+    query: Option<&str>,
+) {
+    let result_count = match result_count {
+        Some(result_count) => format!(" results={result_count}"),
+        None => String::new(),
+    };
+    let query = match query {
+        Some(query) => format!(" query={query:?}"),
+        None => String::new(),
+    };
+    eprintln!(
+        "sparql operation={operation} status={status} duration={:.3}{result_count}{query}",
+        duration.as_secs_f64()
+    );
+}
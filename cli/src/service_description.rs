@@ -1,11 +1,16 @@
 use oxigraph::io::{RdfFormat, RdfSerializer};
-use oxigraph::model::vocab::rdf;
-use oxigraph::model::{BlankNode, NamedNodeRef, TripleRef};
+use oxigraph::model::vocab::{rdf, rdfs};
+use oxigraph::model::{BlankNode, LiteralRef, NamedNodeRef, TripleRef};
 use oxigraph::sparql::results::QueryResultsFormat;
+use oxigraph::sparql::QueryOptions;
 
 mod sd {
     use oxigraph::model::NamedNodeRef;
 
+    pub const AGGREGATE: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/sparql-service-description#Aggregate");
+    pub const FUNCTION: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/sparql-service-description#Function");
     pub const SERVICE: NamedNodeRef<'_> =
         NamedNodeRef::new_unchecked("http://www.w3.org/ns/sparql-service-description#Service");
 
@@ -14,6 +19,9 @@ mod sd {
     );
     pub const ENDPOINT: NamedNodeRef<'_> =
         NamedNodeRef::new_unchecked("http://www.w3.org/ns/sparql-service-description#endpoint");
+    pub const EXTENSION_AGGREGATE: NamedNodeRef<'_> = NamedNodeRef::new_unchecked(
+        "http://www.w3.org/ns/sparql-service-description#extensionAggregate",
+    );
     pub const EXTENSION_FUNCTION: NamedNodeRef<'_> = NamedNodeRef::new_unchecked(
         "http://www.w3.org/ns/sparql-service-description#extensionFunction",
     );
@@ -54,6 +62,7 @@ pub fn generate_service_description(
     format: RdfFormat,
     kind: EndpointKind,
     union_default_graph: bool,
+    query_options: &QueryOptions,
 ) -> Vec<u8> {
     let mut graph = Vec::new();
     let root = BlankNode::default();
@@ -81,6 +90,7 @@ pub fn generate_service_description(
             QueryResultsFormat::Xml,
             QueryResultsFormat::Csv,
             QueryResultsFormat::Tsv,
+            QueryResultsFormat::Thrift,
         ] {
             graph.push(TripleRef::new(
                 &root,
@@ -126,9 +136,35 @@ pub fn generate_service_description(
         sd::DEFAULT_ENTAILMENT_REGIME,
         NamedNodeRef::new_unchecked("http://www.w3.org/ns/entailment/Simple"),
     ));
-    #[cfg(feature = "geosparql")]
-    for function_name in spargeo::GEOSPARQL_EXTENSION_FUNCTIONS {
-        graph.push(TripleRef::new(&root, sd::EXTENSION_FUNCTION, function_name));
+    let mut functions = query_options.custom_functions().collect::<Vec<_>>();
+    functions.sort_unstable_by_key(|(name, _)| *name);
+    let mut aggregate_functions = query_options
+        .custom_aggregate_functions()
+        .collect::<Vec<_>>();
+    aggregate_functions.sort_unstable_by_key(|(name, _)| *name);
+    for (property, class, functions) in [
+        (sd::EXTENSION_FUNCTION, sd::FUNCTION, &functions),
+        (sd::EXTENSION_AGGREGATE, sd::AGGREGATE, &aggregate_functions),
+    ] {
+        for (name, metadata) in functions {
+            graph.push(TripleRef::new(&root, property, name.as_ref()));
+            graph.push(TripleRef::new(name.as_ref(), rdf::TYPE, class));
+            if let Some(description) = metadata.description() {
+                graph.push(TripleRef::new(
+                    name.as_ref(),
+                    rdfs::COMMENT,
+                    LiteralRef::new_simple_literal(description),
+                ));
+            }
+        }
+    }
+    for definition in query_options.function_definitions() {
+        graph.push(TripleRef::new(
+            &root,
+            sd::EXTENSION_FUNCTION,
+            definition.name(),
+        ));
+        graph.push(TripleRef::new(definition.name(), rdf::TYPE, sd::FUNCTION));
     }
 
     let mut serializer = RdfSerializer::from_format(format)
@@ -140,3 +176,38 @@ pub fn generate_service_description(
     }
     serializer.finish().unwrap()
 }
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+    use oxigraph::model::NamedNode;
+    use oxigraph::sparql::CustomFunctionMetadata;
+
+    #[test]
+    fn test_custom_functions() -> anyhow::Result<()> {
+        let name = NamedNode::new("http://example.com/identity")?;
+        let options = QueryOptions::default()
+            .with_custom_function(name.clone(), |args| args.first().cloned())
+            .with_custom_function_metadata(
+                name,
+                CustomFunctionMetadata::new().with_description("Returns its argument"),
+            );
+        let description = String::from_utf8(generate_service_description(
+            RdfFormat::NTriples,
+            EndpointKind::Query,
+            false,
+            &options,
+        ))?;
+        assert!(description.contains(
+            "<http://www.w3.org/ns/sparql-service-description#extensionFunction> <http://example.com/identity> ."
+        ));
+        assert!(description.contains(
+            "<http://example.com/identity> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://www.w3.org/ns/sparql-service-description#Function> ."
+        ));
+        assert!(description.contains(
+            "<http://example.com/identity> <http://www.w3.org/2000/01/rdf-schema#comment> \"Returns its argument\" ."
+        ));
+        Ok(())
+    }
+}
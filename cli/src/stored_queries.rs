@@ -0,0 +1,192 @@
+use crate::config::StoredQueryConfig;
+use crate::{bad_request, HttpError};
+use anyhow::{bail, Context};
+use oxhttp::model::Status;
+use oxigraph::model::{Term, Variable};
+use oxigraph::sparql::Query;
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Arc;
+
+/// Parameterized SPARQL queries defined in the configuration and evaluated with `GET /queries/{name}`.
+///
+/// The request URL query parameters are bound to the query variables of the same name,
+/// even if these variables are not in the query projection.
+/// Only the declared parameters are allowed so the clients can't bind other variables of the query.
+#[derive(Clone, Default, Debug)]
+pub struct StoredQueryRegistry {
+    queries: Arc<HashMap<String, StoredQuery>>,
+}
+
+#[derive(Debug)]
+pub struct StoredQuery {
+    query: String,
+    parameters: Vec<Variable>,
+    /// If the query can be evaluated without the read access to the dataset
+    public: bool,
+}
+
+impl StoredQueryRegistry {
+    pub fn new(configs: &HashMap<String, StoredQueryConfig>) -> anyhow::Result<Self> {
+        let mut queries = HashMap::new();
+        for (name, config) in configs {
+            let query = match (&config.query, &config.file) {
+                (Some(query), None) => query.clone(),
+                (None, Some(file)) => fs::read_to_string(file).with_context(|| {
+                    format!(
+                        "Failed to read the file {} of the stored query {name}",
+                        file.display()
+                    )
+                })?,
+                _ => bail!("The stored query {name} must have exactly one of query or file set"),
+            };
+            Query::parse(&query, None).with_context(|| format!("Invalid stored query {name}"))?;
+            let parameters = config
+                .parameters
+                .iter()
+                .map(|parameter| {
+                    Variable::new(parameter).with_context(|| {
+                        format!("Invalid parameter {parameter} of the stored query {name}")
+                    })
+                })
+                .collect::<anyhow::Result<_>>()?;
+            queries.insert(
+                name.clone(),
+                StoredQuery {
+                    query,
+                    parameters,
+                    public: config.public,
+                },
+            );
+        }
+        Ok(Self {
+            queries: Arc::new(queries),
+        })
+    }
+
+    pub fn get(&self, name: &str) -> Result<&StoredQuery, HttpError> {
+        self.queries.get(name).ok_or_else(|| {
+            (
+                Status::NOT_FOUND,
+                format!("The stored query {name} does not exist"),
+            )
+        })
+    }
+
+    /// If the stored query served at the given path does not require the read access to the dataset
+    pub fn is_public(&self, path: &str) -> bool {
+        path.strip_prefix("/queries/")
+            .and_then(|name| self.queries.get(name))
+            .is_some_and(|query| query.public)
+    }
+}
+
+impl StoredQuery {
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Builds the variable substitutions from the request parameters.
+    ///
+    /// The parameter values are RDF terms in the N-Triples syntax like `<http://example.com>` or `"foo"@en`.
+    pub fn substitutions(
+        &self,
+        parameters: Vec<(String, String)>,
+    ) -> Result<Vec<(Variable, Term)>, HttpError> {
+        let mut substitutions = Vec::<(Variable, Term)>::with_capacity(parameters.len());
+        for (name, value) in parameters {
+            let Some(variable) = self.parameters.iter().find(|v| v.as_str() == name) else {
+                return Err(bad_request(format!(
+                    "The stored query has no parameter {name}"
+                )));
+            };
+            if substitutions.iter().any(|(v, _)| v == variable) {
+                return Err(bad_request(format!(
+                    "The parameter {name} is given multiple times"
+                )));
+            }
+            let value = value.parse::<Term>().map_err(|e| {
+                bad_request(format!(
+                    "Invalid value of the parameter {name}, it must be an RDF term in the N-Triples syntax: {e}"
+                ))
+            })?;
+            substitutions.push((variable.clone(), value));
+        }
+        Ok(substitutions)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+    use oxigraph::model::{Literal, NamedNode};
+
+    fn registry() -> anyhow::Result<StoredQueryRegistry> {
+        StoredQueryRegistry::new(
+            &[(
+                "labels".into(),
+                StoredQueryConfig {
+                    query: Some("SELECT ?label WHERE { ?s <http://www.w3.org/2000/01/rdf-schema#label> ?label }".into()),
+                    file: None,
+                    parameters: vec!["s".into()],
+                    public: true,
+                },
+            )]
+            .into_iter()
+            .collect(),
+        )
+    }
+
+    #[test]
+    fn test_substitutions() -> anyhow::Result<()> {
+        let registry = registry()?;
+        let query = registry
+            .get("labels")
+            .map_err(|(_, e)| anyhow::anyhow!(e))?;
+        assert_eq!(
+            query
+                .substitutions(vec![("s".into(), "<http://example.com>".into())])
+                .map_err(|(_, e)| anyhow::anyhow!(e))?,
+            vec![(
+                Variable::new("s")?,
+                NamedNode::new("http://example.com")?.into()
+            )]
+        );
+        assert_eq!(
+            query
+                .substitutions(vec![("s".into(), "\"foo\"".into())])
+                .map_err(|(_, e)| anyhow::anyhow!(e))?,
+            vec![(Variable::new("s")?, Literal::from("foo").into())]
+        );
+        query
+            .substitutions(vec![("label".into(), "\"foo\"".into())])
+            .unwrap_err();
+        query
+            .substitutions(vec![("s".into(), "foo".into())])
+            .unwrap_err();
+        Ok(())
+    }
+
+    #[test]
+    fn test_registry() -> anyhow::Result<()> {
+        let registry = registry()?;
+        assert!(registry.is_public("/queries/labels"));
+        assert!(!registry.is_public("/queries/other"));
+        assert!(!registry.is_public("/query"));
+        registry.get("other").unwrap_err();
+        StoredQueryRegistry::new(
+            &[(
+                "invalid".into(),
+                StoredQueryConfig {
+                    query: Some("SELECT".into()),
+                    ..StoredQueryConfig::default()
+                },
+            )]
+            .into_iter()
+            .collect(),
+        )
+        .unwrap_err();
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+use anyhow::Context;
+use oxigraph::model::{GraphNameRef, QuadRef, SubjectRef, TermRef};
+use oxigraph::sparql::QueryExplanation;
+use oxigraph::store::Store;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// A trace of a query evaluation, written by `oxigraph query --trace-file` and read by `oxigraph replay`
+///
+/// It allows to reproduce an evaluation without sharing the dataset.
+#[derive(Serialize, Deserialize)]
+pub struct QueryTrace {
+    pub query: String,
+    pub query_base: Option<String>,
+    pub union_default_graph: bool,
+    pub dataset: DatasetFingerprint,
+    /// The JSON explanation of the evaluation with its statistics
+    pub explanation: Value,
+}
+
+/// A summary of the dataset content allowing to check that a trace is replayed against the same data
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct DatasetFingerprint {
+    pub number_of_quads: usize,
+    /// Sum of the SHA-256 hashes of the quads, ignoring the blank node identifiers
+    pub hash: String,
+}
+
+impl DatasetFingerprint {
+    pub fn compute(store: &Store) -> anyhow::Result<Self> {
+        let mut number_of_quads = 0;
+        let mut hash = 0_u128;
+        for quad in store {
+            let quad = quad?;
+            let mut hasher = Sha256::new();
+            hash_quad(quad.as_ref(), &mut hasher);
+            let digest = hasher.finalize();
+            let mut prefix = [0; 16];
+            prefix.copy_from_slice(&digest[..16]);
+            // Addition is commutative so the quads order does not matter
+            hash = hash.wrapping_add(u128::from_be_bytes(prefix));
+            number_of_quads += 1;
+        }
+        Ok(Self {
+            number_of_quads,
+            hash: format!("{hash:032x}"),
+        })
+    }
+}
+
+fn hash_quad(quad: QuadRef<'_>, hasher: &mut Sha256) {
+    hash_subject(quad.subject, hasher);
+    hasher.update(quad.predicate.as_str());
+    hash_term(quad.object, hasher);
+    match quad.graph_name {
+        GraphNameRef::NamedNode(node) => hasher.update(node.as_str()),
+        GraphNameRef::BlankNode(_) => hasher.update("_:"),
+        GraphNameRef::DefaultGraph => (),
+    }
+}
+
+fn hash_subject(subject: SubjectRef<'_>, hasher: &mut Sha256) {
+    match subject {
+        SubjectRef::NamedNode(node) => hasher.update(node.as_str()),
+        SubjectRef::BlankNode(_) => hasher.update("_:"),
+        SubjectRef::Triple(triple) => {
+            hasher.update("<<");
+            hash_subject(triple.subject.as_ref(), hasher);
+            hasher.update(triple.predicate.as_str());
+            hash_term(triple.object.as_ref(), hasher);
+            hasher.update(">>");
+        }
+    }
+}
+
+fn hash_term(term: TermRef<'_>, hasher: &mut Sha256) {
+    match term {
+        TermRef::NamedNode(node) => hasher.update(node.as_str()),
+        TermRef::BlankNode(_) => hasher.update("_:"),
+        TermRef::Literal(literal) => hasher.update(literal.to_string()),
+        TermRef::Triple(triple) => hash_subject(SubjectRef::Triple(triple), hasher),
+    }
+}
+
+pub fn explanation_to_json(explanation: &QueryExplanation) -> anyhow::Result<Value> {
+    let mut buffer = Vec::new();
+    explanation.write_in_json(&mut buffer)?;
+    serde_json::from_slice(&buffer).context("Invalid JSON explanation")
+}
+
+/// Compares the plans of two JSON explanations and their number of inputs and results
+///
+/// Returns a human-readable description of each difference.
+pub fn plan_differences(expected: &Value, actual: &Value) -> Vec<String> {
+    let mut differences = Vec::new();
+    node_differences(&expected["plan"], &actual["plan"], "plan", &mut differences);
+    differences
+}
+
+fn node_differences(expected: &Value, actual: &Value, path: &str, differences: &mut Vec<String>) {
+    let path = format!("{path}/{}", expected["name"].as_str().unwrap_or_default());
+    if expected["name"] != actual["name"] {
+        differences.push(format!(
+            "{path}: the node is {} in the replay",
+            actual["name"]
+        ));
+        return;
+    }
+    for key in ["number of inputs", "number of results"] {
+        if expected[key] != actual[key] {
+            differences.push(format!(
+                "{path}: {key} is {} in the trace and {} in the replay",
+                expected[key], actual[key]
+            ));
+        }
+    }
+    let empty = Vec::new();
+    let expected_children = expected["children"].as_array().unwrap_or(&empty);
+    let actual_children = actual["children"].as_array().unwrap_or(&empty);
+    if expected_children.len() != actual_children.len() {
+        differences.push(format!(
+            "{path}: {} children in the trace and {} in the replay",
+            expected_children.len(),
+            actual_children.len()
+        ));
+        return;
+    }
+    for (expected, actual) in expected_children.iter().zip(actual_children) {
+        node_differences(expected, actual, &path, differences);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn same_plans() {
+        let plan = json!({"plan": {"name": "Union", "number of inputs": 1, "number of results": 2, "children": [
+            {"name": "QuadPattern", "number of inputs": 1, "number of results": 2, "children": []}
+        ]}});
+        assert!(plan_differences(&plan, &plan).is_empty());
+    }
+
+    #[test]
+    fn different_counts() {
+        let expected = json!({"plan": {"name": "Union", "number of inputs": 1, "number of results": 2, "children": [
+            {"name": "QuadPattern", "number of inputs": 1, "number of results": 2, "children": []}
+        ]}});
+        let actual = json!({"plan": {"name": "Union", "number of inputs": 1, "number of results": 3, "children": [
+            {"name": "QuadPattern", "number of inputs": 1, "number of results": 3, "children": []}
+        ]}});
+        assert_eq!(
+            plan_differences(&expected, &actual),
+            [
+                "plan/Union: number of results is 2 in the trace and 3 in the replay",
+                "plan/Union/QuadPattern: number of results is 2 in the trace and 3 in the replay"
+            ]
+        );
+    }
+
+    #[test]
+    fn different_nodes() {
+        let expected = json!({"plan": {"name": "Union", "children": []}});
+        let actual = json!({"plan": {"name": "Join", "children": []}});
+        assert_eq!(
+            plan_differences(&expected, &actual),
+            ["plan/Union: the node is \"Join\" in the replay"]
+        );
+    }
+}
@@ -0,0 +1,301 @@
+use crate::auth::Principal;
+use crate::{internal_server_error, HttpError};
+use oxhttp::model::{HeaderName, Response, Status};
+use oxigraph::store::{StorageError, Store, Transaction};
+use rand::random;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Duration after which a transaction that has not been used is aborted
+pub const DEFAULT_TRANSACTION_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// An operation evaluated inside of a transaction
+pub enum Operation {
+    /// An operation only reading the store
+    Read(Box<dyn FnOnce(&mut Transaction<'_>) -> Result<OperationResponse, HttpError> + Send>),
+    /// An operation writing to the store
+    ///
+    /// It is evaluated again by each following operation of the transaction and at commit so it must not consume its inputs.
+    Write(WriteOperation),
+}
+
+type WriteOperation =
+    Box<dyn Fn(&mut Transaction<'_>) -> Result<OperationResponse, HttpError> + Send>;
+
+/// Store transactions spanning multiple HTTP requests.
+///
+/// The store is not locked between the requests: the write operations are buffered and applied at commit in a single store transaction.
+/// Each operation is evaluated in a store transaction that applies the buffered writes first and that is then rolled back,
+/// so it sees the changes done by the previous operations of the transaction.
+/// The buffered writes are evaluated against the store state of each request:
+/// if one of them fails because of a concurrent change, the transaction is aborted with a conflict.
+/// If an operation fails, the transaction is aborted.
+///
+/// A transaction can only be used by the user or the token that opened it.
+#[derive(Clone)]
+pub struct TransactionRegistry {
+    timeout: Duration,
+    transactions: Arc<Mutex<HashMap<String, OwnedTransaction>>>,
+}
+
+/// A transaction with the request issuer that opened it, `None` if it is anonymous
+type OwnedTransaction = (Option<Principal>, Arc<Mutex<HttpTransaction>>);
+
+struct HttpTransaction {
+    store: Store,
+    writes: Vec<WriteOperation>,
+    last_use: Instant,
+}
+
+impl TransactionRegistry {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            transactions: Arc::default(),
+        }
+    }
+
+    /// Starts a new transaction owned by `owner` and returns its id.
+    pub fn begin(&self, store: Store, owner: Option<Principal>) -> String {
+        let id = format!("{:032x}", random::<u128>());
+        let mut transactions = self
+            .transactions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        // We take the opportunity to forget the expired transactions
+        transactions.retain(|_, (_, transaction)| {
+            !transaction
+                .try_lock()
+                .is_ok_and(|transaction| self.is_expired(&transaction))
+        });
+        transactions.insert(
+            id.clone(),
+            (
+                owner,
+                Arc::new(Mutex::new(HttpTransaction {
+                    store,
+                    writes: Vec::new(),
+                    last_use: Instant::now(),
+                })),
+            ),
+        );
+        id
+    }
+
+    /// Applies the buffered writes in a single store transaction
+    pub fn commit(&self, id: &str, issuer: Option<&Principal>) -> Result<(), HttpError> {
+        let transaction = self.take(id, issuer)?;
+        let transaction = transaction.lock().unwrap_or_else(PoisonError::into_inner);
+        if self.is_expired(&transaction) {
+            return Err(not_found(id));
+        }
+        transaction
+            .store
+            .transaction(|mut storage_transaction| {
+                for write in &transaction.writes {
+                    write(&mut storage_transaction).map_err(ReplayError::Replay)?;
+                }
+                Ok(())
+            })
+            .map_err(ReplayError::into_http_error)
+    }
+
+    pub fn abort(&self, id: &str, issuer: Option<&Principal>) -> Result<(), HttpError> {
+        self.take(id, issuer)?;
+        Ok(())
+    }
+
+    /// Evaluates an operation after the buffered writes in a store transaction that is rolled back.
+    ///
+    /// The write operations are then buffered until the commit.
+    /// The transaction is aborted if the evaluation fails.
+    pub fn execute(
+        &self,
+        id: &str,
+        issuer: Option<&Principal>,
+        operation: Operation,
+    ) -> Result<OperationResponse, HttpError> {
+        let transaction = {
+            let transactions = self
+                .transactions
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            let (owner, transaction) = transactions.get(id).ok_or_else(|| not_found(id))?;
+            check_owner(id, owner.as_ref(), issuer)?;
+            Arc::clone(transaction)
+        };
+        let mut transaction = transaction.lock().unwrap_or_else(PoisonError::into_inner);
+        if self.is_expired(&transaction) {
+            drop(transaction);
+            self.remove(id);
+            return Err(not_found(id));
+        }
+        let (read, write) = match operation {
+            Operation::Read(operation) => (Cell::new(Some(operation)), None),
+            Operation::Write(operation) => (Cell::new(None), Some(operation)),
+        };
+        let response = RefCell::new(None);
+        let result = transaction
+            .store
+            .transaction(|mut storage_transaction| {
+                for write in &transaction.writes {
+                    write(&mut storage_transaction).map_err(ReplayError::Replay)?;
+                }
+                let result = if let Some(write) = &write {
+                    write(&mut storage_transaction)
+                } else if let Some(read) = read.take() {
+                    read(&mut storage_transaction)
+                } else {
+                    Err(internal_server_error(
+                        "The operation has been interrupted by a conflict",
+                    ))
+                };
+                *response.borrow_mut() = Some(result.map_err(ReplayError::Operation)?);
+                // We roll back the store transaction, the writes are applied at commit
+                Err::<(), _>(ReplayError::Rollback)
+            })
+            .or_else(|e| match e {
+                ReplayError::Rollback => Ok(()),
+                _ => Err(e.into_http_error()),
+            });
+        let response = match (result, response.into_inner()) {
+            (Ok(()), Some(response)) => response,
+            (Err(e), _) => {
+                drop(transaction);
+                self.remove(id);
+                return Err(e);
+            }
+            (Ok(()), None) => {
+                return Err(internal_server_error(
+                    "The operation has been rolled back without being evaluated",
+                ))
+            }
+        };
+        transaction.last_use = Instant::now();
+        transaction.writes.extend(write);
+        Ok(response)
+    }
+
+    /// Removes the transaction if it has been opened by the request issuer
+    fn take(
+        &self,
+        id: &str,
+        issuer: Option<&Principal>,
+    ) -> Result<Arc<Mutex<HttpTransaction>>, HttpError> {
+        let mut transactions = self
+            .transactions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let (owner, _) = transactions.get(id).ok_or_else(|| not_found(id))?;
+        check_owner(id, owner.as_ref(), issuer)?;
+        let (_, transaction) = transactions.remove(id).ok_or_else(|| not_found(id))?;
+        Ok(transaction)
+    }
+
+    fn remove(&self, id: &str) {
+        self.transactions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(id);
+    }
+
+    fn is_expired(&self, transaction: &HttpTransaction) -> bool {
+        transaction.last_use.elapsed() > self.timeout
+    }
+}
+
+impl Default for TransactionRegistry {
+    fn default() -> Self {
+        Self::new(DEFAULT_TRANSACTION_TIMEOUT)
+    }
+}
+
+/// Error raised inside of the store transactions used to evaluate the operations
+#[derive(Debug)]
+enum ReplayError {
+    Storage(StorageError),
+    /// A buffered write fails on the current store state
+    Replay(HttpError),
+    /// The evaluated operation fails
+    Operation(HttpError),
+    /// The evaluation succeeded and the store transaction must be rolled back
+    Rollback,
+}
+
+impl ReplayError {
+    fn into_http_error(self) -> HttpError {
+        match self {
+            Self::Storage(e) => internal_server_error(e),
+            Self::Replay((_, message)) => (
+                Status::CONFLICT,
+                format!("The transaction conflicts with a concurrent change: {message}"),
+            ),
+            Self::Operation(e) => e,
+            Self::Rollback => internal_server_error("The transaction has been rolled back"),
+        }
+    }
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Storage(e) => e.fmt(f),
+            Self::Replay((_, message)) | Self::Operation((_, message)) => f.write_str(message),
+            Self::Rollback => f.write_str("The transaction has been rolled back"),
+        }
+    }
+}
+
+impl Error for ReplayError {}
+
+impl From<StorageError> for ReplayError {
+    fn from(error: StorageError) -> Self {
+        Self::Storage(error)
+    }
+}
+
+fn check_owner(
+    id: &str,
+    owner: Option<&Principal>,
+    issuer: Option<&Principal>,
+) -> Result<(), HttpError> {
+    if owner == issuer {
+        Ok(())
+    } else {
+        Err((
+            Status::FORBIDDEN,
+            format!("The transaction {id} has been opened by someone else"),
+        ))
+    }
+}
+
+fn not_found(id: &str) -> HttpError {
+    (
+        Status::NOT_FOUND,
+        format!("The transaction {id} does not exist or has been aborted"),
+    )
+}
+
+/// The response to an operation evaluated inside of a transaction
+pub struct OperationResponse {
+    pub status: Status,
+    /// The media type and the serialization of the operation results
+    pub body: Option<(&'static str, Vec<u8>)>,
+}
+
+impl From<OperationResponse> for Response {
+    fn from(response: OperationResponse) -> Self {
+        if let Some((media_type, body)) = response.body {
+            Response::builder(response.status)
+                .with_header(HeaderName::CONTENT_TYPE, media_type)
+                .unwrap()
+                .with_body(body)
+        } else {
+            Response::builder(response.status).build()
+        }
+    }
+}
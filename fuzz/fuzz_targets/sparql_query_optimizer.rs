@@ -0,0 +1,65 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use oxigraph::io::{RdfFormat, RdfParser};
+use oxigraph_fuzz::{canonical_solution_key, count_triple_blank_nodes};
+use oxrdf::graph::CanonicalizationAlgorithm;
+use oxrdf::{Dataset, Graph};
+use spareval::{QueryEvaluationError, QueryEvaluator, QueryResults};
+use std::sync::OnceLock;
+
+fuzz_target!(|data: sparql_smith::Query| {
+    static DATASET: OnceLock<Dataset> = OnceLock::new();
+    let dataset = DATASET.get_or_init(|| {
+        RdfParser::from(RdfFormat::TriG)
+            .for_slice(sparql_smith::DATA_TRIG.as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap()
+    });
+
+    let query_str = data.to_string();
+    if let Ok(query) = spargebra::Query::parse(&query_str, None) {
+        let with_opt = QueryEvaluator::new().execute(dataset.clone(), &query);
+        let without_opt = QueryEvaluator::new()
+            .without_optimizations()
+            .execute(dataset.clone(), &query);
+        let is_reduced = query_str.contains(" REDUCED ");
+        assert_eq!(
+            query_results_key(with_opt, is_reduced),
+            query_results_key(without_opt, is_reduced),
+            "Optimized and non optimized evaluations of {query_str} differ"
+        )
+    }
+});
+
+fn query_results_key(
+    results: Result<QueryResults, QueryEvaluationError>,
+    is_reduced: bool,
+) -> String {
+    match results {
+        Ok(QueryResults::Solutions(iter)) => {
+            // Solutions are compared as multisets, ORDER BY is not checked
+            let mut solutions = iter
+                .filter_map(Result::ok)
+                .map(|s| canonical_solution_key(&s))
+                .collect::<Vec<_>>();
+            solutions.sort_unstable();
+            if is_reduced {
+                solutions.dedup();
+            }
+            solutions.join("\n")
+        }
+        Ok(QueryResults::Graph(iter)) => {
+            let mut graph = iter.filter_map(Result::ok).collect::<Graph>();
+            if graph.iter().map(count_triple_blank_nodes).sum::<usize>() > 4 {
+                return String::new(); // canonicalization might be too slow
+            };
+            graph.canonicalize(CanonicalizationAlgorithm::Unstable);
+            let mut triples = graph.into_iter().map(|t| t.to_string()).collect::<Vec<_>>();
+            triples.sort_unstable();
+            triples.join("\n")
+        }
+        Ok(QueryResults::Boolean(bool)) => if bool { "true" } else { "" }.into(),
+        Err(_) => String::new(),
+    }
+}
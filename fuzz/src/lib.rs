@@ -1,4 +1,9 @@
-use oxrdf::{GraphNameRef, QuadRef, SubjectRef, TermRef, TripleRef};
+use oxrdf::{
+    BlankNode, GraphNameRef, QuadRef, Subject, SubjectRef, Term, TermRef, Triple, TripleRef,
+    Variable,
+};
+use std::collections::HashMap;
+use std::fmt::Write;
 
 pub mod result_format;
 
@@ -25,3 +30,62 @@ pub fn count_quad_blank_nodes(quad: QuadRef<'_>) -> usize {
         _ => 0,
     }) + usize::from(matches!(quad.graph_name, GraphNameRef::BlankNode(_)))
 }
+
+/// Serializes a query solution into a string that does not depend on the order of its bindings or on its blank node identifiers.
+///
+/// The bindings are sorted by variable name and the blank nodes are renamed following their order of appearance.
+/// Two solutions equal up to a blank node renaming get the same key.
+pub fn canonical_solution_key<'a>(
+    solution: impl IntoIterator<Item = (&'a Variable, &'a Term)>,
+) -> String {
+    let mut bindings = solution.into_iter().collect::<Vec<_>>();
+    bindings.sort_unstable_by(|(v1, _), (v2, _)| v1.cmp(v2));
+    let mut blank_nodes = HashMap::new();
+    let mut key = String::new();
+    for (variable, value) in bindings {
+        if !key.is_empty() {
+            key.push(' ');
+        }
+        write!(key, "{variable}: ").unwrap();
+        write_canonical_term(value, &mut blank_nodes, &mut key);
+    }
+    key
+}
+
+fn write_canonical_term(
+    term: &Term,
+    blank_nodes: &mut HashMap<BlankNode, usize>,
+    out: &mut String,
+) {
+    match term {
+        Term::NamedNode(_) | Term::Literal(_) => write!(out, "{term}").unwrap(),
+        Term::BlankNode(b) => write_canonical_blank_node(b, blank_nodes, out),
+        Term::Triple(t) => write_canonical_triple(t, blank_nodes, out),
+    }
+}
+
+fn write_canonical_triple(
+    triple: &Triple,
+    blank_nodes: &mut HashMap<BlankNode, usize>,
+    out: &mut String,
+) {
+    out.push_str("<< ");
+    match &triple.subject {
+        Subject::NamedNode(n) => write!(out, "{n}").unwrap(),
+        Subject::BlankNode(b) => write_canonical_blank_node(b, blank_nodes, out),
+        Subject::Triple(t) => write_canonical_triple(t, blank_nodes, out),
+    }
+    write!(out, " {} ", triple.predicate).unwrap();
+    write_canonical_term(&triple.object, blank_nodes, out);
+    out.push_str(" >>");
+}
+
+fn write_canonical_blank_node(
+    blank_node: &BlankNode,
+    blank_nodes: &mut HashMap<BlankNode, usize>,
+    out: &mut String,
+) {
+    let len = blank_nodes.len();
+    let id = *blank_nodes.entry(blank_node.clone()).or_insert(len);
+    write!(out, "_:c{id}").unwrap();
+}
@@ -1,13 +1,16 @@
 use crate::model::*;
 use crate::{console_warn, format_err};
-use js_sys::{try_iter, Array, Map, Reflect};
+use js_sys::{try_iter, Array, Function, Map, Object, Promise, Reflect, Symbol, Uint8Array};
 use oxigraph::io::{RdfFormat, RdfParser};
 use oxigraph::model::*;
 use oxigraph::sparql::results::QueryResultsFormat;
-use oxigraph::sparql::{Query, QueryOptions, QueryResults, Update};
+use oxigraph::sparql::{Query, QueryOptions, QueryResults, QuerySolution, Update};
 use oxigraph::store::Store;
 #[cfg(feature = "geosparql")]
 use spargeo::register_geosparql_functions;
+use std::cell::RefCell;
+use std::io::{self, BufWriter, Write};
+use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 
 // We skip_typescript on specific wasm_bindgen macros and provide custom TypeScript types for parts of this module in order to have narrower types
@@ -32,6 +35,16 @@ export class Store {
         }
     ): string;
 
+    dumpSnapshot(): Uint8Array;
+
+    dumpStream(
+        stream: WritableStream<Uint8Array>,
+        options: {
+            format: string;
+            from_graph_name?: BlankNode | DefaultGraph | NamedNode;
+        }
+    ): Promise<void>;
+
     has(quad: Quad): boolean;
 
     load(
@@ -45,6 +58,21 @@ export class Store {
         }
     ): void;
 
+    loadFromIndexedDB(databaseName: string, key?: string): Promise<boolean>;
+
+    loadSnapshot(snapshot: Uint8Array): void;
+
+    loadStream(
+        stream: ReadableStream<Uint8Array | string>,
+        options: {
+            base_iri?: NamedNode | string;
+            format: string;
+            no_transaction?: boolean;
+            to_graph_name?: BlankNode | DefaultGraph | NamedNode;
+            unchecked?: boolean;
+        }
+    ): Promise<void>;
+
     match(subject?: Term | null, predicate?: Term | null, object?: Term | null, graph?: Term | null): Quad[];
 
     query(
@@ -58,6 +86,18 @@ export class Store {
         }
     ): boolean | Map<string, Term>[] | Quad[] | string;
 
+    queryIterator(
+        query: string,
+        options?: {
+            base_iri?: NamedNode | string;
+            default_graph?: BlankNode | DefaultGraph | NamedNode | Iterable<BlankNode | DefaultGraph | NamedNode>;
+            named_graphs?: Iterable<BlankNode | NamedNode>;
+            use_default_graph_as_union?: boolean;
+        }
+    ): boolean | QueryResultsIterable<Map<string, Term>> | QueryResultsIterable<Quad>;
+
+    saveToIndexedDB(databaseName: string, key?: string): Promise<void>;
+
     update(
         update: string,
         options?: {
@@ -65,6 +105,8 @@ export class Store {
         }
     ): void;
 }
+
+export interface QueryResultsIterable<T> extends Iterable<T>, AsyncIterable<T> {}
 "###;
 
 #[wasm_bindgen(js_name = Store, skip_typescript)]
@@ -165,75 +207,10 @@ impl JsStore {
     }
 
     pub fn query(&self, query: &str, options: &JsValue) -> Result<JsValue, JsValue> {
-        // Parsing options
-        let mut base_iri = None;
-        let mut use_default_graph_as_union = false;
-        let mut results_format = None;
-        let mut default_graph = None;
-        let mut named_graphs = None;
-        if !options.is_undefined() {
-            base_iri = convert_base_iri(&Reflect::get(options, &JsValue::from_str("base_iri"))?)?;
-
-            let js_default_graph = Reflect::get(options, &JsValue::from_str("default_graph"))?;
-            default_graph = if js_default_graph.is_undefined() || js_default_graph.is_null() {
-                None
-            } else if let Some(iter) = try_iter(&js_default_graph)? {
-                Some(
-                    iter.map(|term| FROM_JS.with(|c| c.to_term(&term?))?.try_into())
-                        .collect::<Result<Vec<GraphName>, _>>()?,
-                )
-            } else {
-                Some(vec![FROM_JS
-                    .with(|c| c.to_term(&js_default_graph))?
-                    .try_into()?])
-            };
-
-            let js_named_graphs = Reflect::get(options, &JsValue::from_str("named_graphs"))?;
-            named_graphs = if js_named_graphs.is_null() || js_named_graphs.is_undefined() {
-                None
-            } else {
-                Some(
-                    try_iter(&Reflect::get(options, &JsValue::from_str("named_graphs"))?)?
-                        .ok_or_else(|| format_err!("named_graphs option must be iterable"))?
-                        .map(|term| FROM_JS.with(|c| c.to_term(&term?))?.try_into())
-                        .collect::<Result<Vec<NamedOrBlankNode>, _>>()?,
-                )
-            };
-
-            use_default_graph_as_union =
-                Reflect::get(options, &JsValue::from_str("use_default_graph_as_union"))?
-                    .is_truthy();
-
-            let js_results_format = Reflect::get(options, &JsValue::from_str("results_format"))?;
-            if !js_results_format.is_undefined() && !js_results_format.is_null() {
-                results_format = Some(
-                    js_results_format
-                        .as_string()
-                        .ok_or_else(|| format_err!("results_format option must be a string"))?,
-                );
-            }
-        }
-
-        let mut query = Query::parse(query, base_iri.as_deref()).map_err(JsError::from)?;
-        if use_default_graph_as_union {
-            query.dataset_mut().set_default_graph_as_union();
-        }
-        if let Some(default_graph) = default_graph {
-            query.dataset_mut().set_default_graph(default_graph);
-        }
-        if let Some(named_graphs) = named_graphs {
-            query.dataset_mut().set_available_named_graphs(named_graphs);
-        }
-
-        let mut options = QueryOptions::default();
-        #[cfg(feature = "geosparql")]
-        {
-            options = register_geosparql_functions(options);
-        }
-
+        let (query, results_format) = parse_query(query, options)?;
         let results = self
             .store
-            .query_opt(query, options)
+            .query_opt(query, query_options())
             .map_err(JsError::from)?;
 
         Ok(match results {
@@ -251,15 +228,7 @@ impl JsStore {
                 } else {
                     let results = Array::new();
                     for solution in solutions {
-                        let solution = solution.map_err(JsError::from)?;
-                        let result = Map::new();
-                        for (variable, value) in solution.iter() {
-                            result.set(
-                                &variable.as_str().into(),
-                                &JsTerm::from(value.clone()).into(),
-                            );
-                        }
-                        results.push(&result.into());
+                        results.push(&solution_to_js(solution.map_err(JsError::from)?));
                     }
                     results.into()
                 }
@@ -307,6 +276,30 @@ impl JsStore {
         })
     }
 
+    /// Evaluates a query and returns its solutions or triples lazily as an object that is both iterable and async iterable.
+    #[wasm_bindgen(js_name = queryIterator)]
+    pub fn query_iterator(&self, query: &str, options: &JsValue) -> Result<JsValue, JsValue> {
+        let (query, _) = parse_query(query, options)?;
+        let results = self
+            .store
+            .query_opt(query, query_options())
+            .map_err(JsError::from)?;
+        match results {
+            QueryResults::Solutions(solutions) => to_js_iterable(
+                solutions.map(|solution| Ok(solution_to_js(solution.map_err(JsError::from)?))),
+            ),
+            QueryResults::Graph(triples) => to_js_iterable(triples.map(|triple| {
+                Ok(JsQuad::from(
+                    triple
+                        .map_err(JsError::from)?
+                        .in_graph(GraphName::DefaultGraph),
+                )
+                .into())
+            })),
+            QueryResults::Boolean(b) => Ok(b.into()),
+        }
+    }
+
     pub fn update(&self, update: &str, options: &JsValue) -> Result<(), JsValue> {
         // Parsing options
         let mut base_iri = None;
@@ -315,16 +308,9 @@ impl JsStore {
         }
 
         let update = Update::parse(update, base_iri.as_deref()).map_err(JsError::from)?;
-
-        let mut options = QueryOptions::default();
-        #[cfg(feature = "geosparql")]
-        {
-            options = register_geosparql_functions(options);
-        }
-
         Ok(self
             .store
-            .update_opt(update, options)
+            .update_opt(update, query_options())
             .map_err(JsError::from)?)
     }
 
@@ -336,58 +322,47 @@ impl JsStore {
         to_graph_name: &JsValue,
     ) -> Result<(), JsValue> {
         // Parsing options
-        let mut format = None;
-        let mut parsed_base_iri = None;
-        let mut parsed_to_graph_name = None;
-        let mut unchecked = false;
-        let mut no_transaction = false;
-        if let Some(format_str) = options.as_string() {
+        let mut load_options = if let Some(format_str) = options.as_string() {
             // Backward compatibility with format as a string
             console_warn!("The format should be passed to Store.load in an option dictionary like store.load(my_content, {{format: 'nt'}})");
-            format = Some(rdf_format(&format_str)?);
-        } else if !options.is_undefined() && !options.is_null() {
-            if let Some(format_str) =
-                Reflect::get(options, &JsValue::from_str("format"))?.as_string()
-            {
-                format = Some(rdf_format(&format_str)?);
+            LoadOptions {
+                format: Some(rdf_format(&format_str)?),
+                ..LoadOptions::default()
             }
-            parsed_base_iri =
-                convert_base_iri(&Reflect::get(options, &JsValue::from_str("base_iri"))?)?;
-            let to_graph_name_js = Reflect::get(options, &JsValue::from_str("to_graph_name"))?;
-            parsed_to_graph_name = FROM_JS.with(|c| c.to_optional_term(&to_graph_name_js))?;
-            unchecked = Reflect::get(options, &JsValue::from_str("unchecked"))?.is_truthy();
-            no_transaction =
-                Reflect::get(options, &JsValue::from_str("no_transaction"))?.is_truthy();
-        }
-        let format = format
+        } else {
+            LoadOptions::from_js(options)?
+        };
+        let format = load_options.format
             .ok_or_else(|| format_err!("The format option should be provided as a second argument of Store.load like store.load(my_content, {{format: 'nt'}}"))?;
         if let Some(base_iri) = convert_base_iri(base_iri)? {
             console_warn!("The base_iri should be passed to Store.load in an option dictionary like store.load(my_content, {{format: 'nt', base_iri: 'http//example.com'}})");
-            parsed_base_iri = Some(base_iri);
+            load_options.base_iri = Some(base_iri);
         }
         if let Some(to_graph_name) = FROM_JS.with(|c| c.to_optional_term(to_graph_name))? {
             console_warn!("The target graph name should be passed to Store.load in an option dictionary like store.load(my_content, {{format: 'nt', to_graph_name: 'http//example.com'}})");
-            parsed_to_graph_name = Some(to_graph_name);
+            load_options.to_graph_name = Some(to_graph_name);
         }
+        load_from_slice(&self.store, data.as_bytes(), format, load_options)
+    }
 
-        let mut parser = RdfParser::from_format(format);
-        if let Some(to_graph_name) = parsed_to_graph_name {
-            parser = parser.with_default_graph(GraphName::try_from(to_graph_name)?);
-        }
-        if let Some(base_iri) = parsed_base_iri {
-            parser = parser.with_base_iri(base_iri).map_err(JsError::from)?;
-        }
-        if unchecked {
-            parser = parser.unchecked();
-        }
-        Ok(if no_transaction {
-            self.store
-                .bulk_loader()
-                .load_from_reader(parser, data.as_bytes())
-        } else {
-            self.store.load_from_reader(parser, data.as_bytes())
-        }
-        .map_err(JsError::from)?)
+    /// Loads an RDF serialization read from a `ReadableStream` of `Uint8Array` or strings.
+    ///
+    /// The stream is read until its end before loading its content.
+    #[wasm_bindgen(js_name = loadStream)]
+    pub fn load_stream(&self, stream: &JsValue, options: &JsValue) -> Result<JsValue, JsValue> {
+        let load_options = LoadOptions::from_js(options)?;
+        let format = load_options.format
+            .ok_or_else(|| format_err!("The format option should be provided as a second argument of Store.loadStream like store.loadStream(my_stream, {{format: 'nt'}}"))?;
+        let reader = call_method(stream, "getReader", &[])?;
+        let store = self.store.clone();
+        read_stream(
+            reader,
+            Vec::new(),
+            Box::new(move |data| {
+                load_from_slice(&store, &data, format, load_options)?;
+                Ok(JsValue::UNDEFINED)
+            }),
+        )
     }
 
     pub fn dump(&self, options: &JsValue, from_graph_name: &JsValue) -> Result<String, JsValue> {
@@ -426,6 +401,502 @@ impl JsStore {
         .map_err(JsError::from)?;
         Ok(String::from_utf8(buffer).map_err(JsError::from)?)
     }
+
+    /// Serializes the store content into a `WritableStream` of `Uint8Array`.
+    ///
+    /// The returned promise is resolved when the stream is closed.
+    #[wasm_bindgen(js_name = dumpStream)]
+    pub fn dump_stream(&self, stream: &JsValue, options: &JsValue) -> Result<JsValue, JsValue> {
+        // Serialization options
+        let mut format = None;
+        let mut from_graph_name = None;
+        if !options.is_undefined() && !options.is_null() {
+            if let Some(format_str) =
+                Reflect::get(options, &JsValue::from_str("format"))?.as_string()
+            {
+                format = Some(rdf_format(&format_str)?);
+            }
+            let from_graph_name_js = Reflect::get(options, &JsValue::from_str("from_graph_name"))?;
+            from_graph_name = FROM_JS.with(|c| c.to_optional_term(&from_graph_name_js))?;
+        }
+        let format = format
+            .ok_or_else(|| format_err!("The format option should be provided as a second argument of Store.dumpStream like store.dumpStream(my_stream, {{format: 'nt'}}"))?;
+
+        let writer = call_method(stream, "getWriter", &[])?;
+        let mut buffer = BufWriter::new(JsStreamWriter {
+            writer: writer.clone(),
+            // The write errors are reported by the promise returned by close
+            ignore_error: Closure::<dyn FnMut(JsValue)>::new(|_| ()).into_js_value(),
+        });
+        if let Some(from_graph_name) = from_graph_name {
+            self.store.dump_graph_to_writer(
+                &GraphName::try_from(from_graph_name)?,
+                format,
+                &mut buffer,
+            )
+        } else {
+            self.store.dump_to_writer(format, &mut buffer)
+        }
+        .map_err(JsError::from)?;
+        buffer.flush().map_err(JsError::from)?;
+        call_method(&writer, "close", &[])
+    }
+
+    #[wasm_bindgen(js_name = dumpSnapshot)]
+    pub fn dump_snapshot(&self) -> Result<Vec<u8>, JsValue> {
+        Ok(self
+            .store
+            .dump_snapshot_to_writer(Vec::new())
+            .map_err(JsError::from)?)
+    }
+
+    #[wasm_bindgen(js_name = loadSnapshot)]
+    pub fn load_snapshot(&self, snapshot: &[u8]) -> Result<(), JsValue> {
+        self.store
+            .load_snapshot_from_reader(snapshot)
+            .map_err(JsError::from)?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = saveToIndexedDB)]
+    pub fn save_to_indexed_db(
+        &self,
+        database_name: &str,
+        key: &JsValue,
+    ) -> Result<JsValue, JsValue> {
+        let snapshot = JsValue::from(Uint8Array::from(
+            self.store
+                .dump_snapshot_to_writer(Vec::new())
+                .map_err(JsError::from)?
+                .as_slice(),
+        ));
+        let key = indexed_db_key(key);
+        call_method(
+            &open_indexed_db(database_name)?,
+            "then",
+            &[&Closure::once_into_js(
+                move |database: JsValue| -> Result<JsValue, JsValue> {
+                    let object_store = indexed_db_object_store(&database, "readwrite")?;
+                    let saved = indexed_db_request_result(&call_method(
+                        &object_store,
+                        "put",
+                        &[&snapshot, &key],
+                    )?)?;
+                    // Pending transactions are still completed after close
+                    call_method(&database, "close", &[])?;
+                    call_method(
+                        &saved,
+                        "then",
+                        &[&Closure::once_into_js(|_: JsValue| JsValue::UNDEFINED)],
+                    )
+                },
+            )],
+        )
+    }
+
+    #[wasm_bindgen(js_name = loadFromIndexedDB)]
+    pub fn load_from_indexed_db(
+        &self,
+        database_name: &str,
+        key: &JsValue,
+    ) -> Result<JsValue, JsValue> {
+        let store = self.store.clone();
+        let key = indexed_db_key(key);
+        call_method(
+            &open_indexed_db(database_name)?,
+            "then",
+            &[&Closure::once_into_js(
+                move |database: JsValue| -> Result<JsValue, JsValue> {
+                    let object_store = indexed_db_object_store(&database, "readonly")?;
+                    let snapshot =
+                        indexed_db_request_result(&call_method(&object_store, "get", &[&key])?)?;
+                    call_method(&database, "close", &[])?;
+                    call_method(
+                        &snapshot,
+                        "then",
+                        &[&Closure::once_into_js(
+                            move |snapshot: JsValue| -> Result<JsValue, JsValue> {
+                                if snapshot.is_undefined() {
+                                    return Ok(JsValue::FALSE);
+                                }
+                                store
+                                    .load_snapshot_from_reader(
+                                        Uint8Array::new(&snapshot).to_vec().as_slice(),
+                                    )
+                                    .map_err(JsError::from)?;
+                                Ok(JsValue::TRUE)
+                            },
+                        )],
+                    )
+                },
+            )],
+        )
+    }
+}
+
+fn parse_query(query: &str, options: &JsValue) -> Result<(Query, Option<String>), JsValue> {
+    // Parsing options
+    let mut base_iri = None;
+    let mut use_default_graph_as_union = false;
+    let mut results_format = None;
+    let mut default_graph = None;
+    let mut named_graphs = None;
+    if !options.is_undefined() {
+        base_iri = convert_base_iri(&Reflect::get(options, &JsValue::from_str("base_iri"))?)?;
+
+        let js_default_graph = Reflect::get(options, &JsValue::from_str("default_graph"))?;
+        default_graph = if js_default_graph.is_undefined() || js_default_graph.is_null() {
+            None
+        } else if let Some(iter) = try_iter(&js_default_graph)? {
+            Some(
+                iter.map(|term| FROM_JS.with(|c| c.to_term(&term?))?.try_into())
+                    .collect::<Result<Vec<GraphName>, _>>()?,
+            )
+        } else {
+            Some(vec![FROM_JS
+                .with(|c| c.to_term(&js_default_graph))?
+                .try_into()?])
+        };
+
+        let js_named_graphs = Reflect::get(options, &JsValue::from_str("named_graphs"))?;
+        named_graphs = if js_named_graphs.is_null() || js_named_graphs.is_undefined() {
+            None
+        } else {
+            Some(
+                try_iter(&Reflect::get(options, &JsValue::from_str("named_graphs"))?)?
+                    .ok_or_else(|| format_err!("named_graphs option must be iterable"))?
+                    .map(|term| FROM_JS.with(|c| c.to_term(&term?))?.try_into())
+                    .collect::<Result<Vec<NamedOrBlankNode>, _>>()?,
+            )
+        };
+
+        use_default_graph_as_union =
+            Reflect::get(options, &JsValue::from_str("use_default_graph_as_union"))?.is_truthy();
+
+        let js_results_format = Reflect::get(options, &JsValue::from_str("results_format"))?;
+        if !js_results_format.is_undefined() && !js_results_format.is_null() {
+            results_format = Some(
+                js_results_format
+                    .as_string()
+                    .ok_or_else(|| format_err!("results_format option must be a string"))?,
+            );
+        }
+    }
+
+    let mut query = Query::parse(query, base_iri.as_deref()).map_err(JsError::from)?;
+    if use_default_graph_as_union {
+        query.dataset_mut().set_default_graph_as_union();
+    }
+    if let Some(default_graph) = default_graph {
+        query.dataset_mut().set_default_graph(default_graph);
+    }
+    if let Some(named_graphs) = named_graphs {
+        query.dataset_mut().set_available_named_graphs(named_graphs);
+    }
+    Ok((query, results_format))
+}
+
+fn query_options() -> QueryOptions {
+    let mut options = QueryOptions::default();
+    #[cfg(feature = "geosparql")]
+    {
+        options = register_geosparql_functions(options);
+    }
+    options
+}
+
+fn solution_to_js(solution: QuerySolution) -> JsValue {
+    let result = Map::new();
+    for (variable, value) in solution.iter() {
+        result.set(
+            &variable.as_str().into(),
+            &JsTerm::from(value.clone()).into(),
+        );
+    }
+    result.into()
+}
+
+type JsResultIterator = RefCell<dyn Iterator<Item = Result<JsValue, JsValue>>>;
+
+/// Builds an object that is both iterable and async iterable from a Rust iterator.
+///
+/// Each call to `[Symbol.iterator]` and `[Symbol.asyncIterator]` returns a new iterator object to avoid reference cycles between JS and Rust.
+fn to_js_iterable(
+    iter: impl Iterator<Item = Result<JsValue, JsValue>> + 'static,
+) -> Result<JsValue, JsValue> {
+    let iter: Rc<JsResultIterator> = Rc::new(RefCell::new(iter));
+    let iterable = Object::new();
+    let sync_iter = Rc::clone(&iter);
+    Reflect::set(
+        &iterable,
+        &Symbol::iterator(),
+        &Closure::<dyn Fn() -> Result<JsValue, JsValue>>::new(move || {
+            to_js_iterator(Rc::clone(&sync_iter), false)
+        })
+        .into_js_value(),
+    )?;
+    Reflect::set(
+        &iterable,
+        &Symbol::async_iterator(),
+        &Closure::<dyn Fn() -> Result<JsValue, JsValue>>::new(move || {
+            to_js_iterator(Rc::clone(&iter), true)
+        })
+        .into_js_value(),
+    )?;
+    Ok(iterable.into())
+}
+
+fn to_js_iterator(iter: Rc<JsResultIterator>, is_async: bool) -> Result<JsValue, JsValue> {
+    let iterator = Object::new();
+    Reflect::set(
+        &iterator,
+        &JsValue::from_str("next"),
+        &Closure::<dyn Fn() -> Result<JsValue, JsValue>>::new(move || {
+            let result = iter
+                .borrow_mut()
+                .next()
+                .transpose()
+                .and_then(iterator_result);
+            if is_async {
+                Ok(match result {
+                    Ok(result) => Promise::resolve(&result),
+                    Err(error) => Promise::reject(&error),
+                }
+                .into())
+            } else {
+                result
+            }
+        })
+        .into_js_value(),
+    )?;
+    Ok(iterator.into())
+}
+
+fn iterator_result(value: Option<JsValue>) -> Result<JsValue, JsValue> {
+    let result = Object::new();
+    Reflect::set(
+        &result,
+        &JsValue::from_str("done"),
+        &JsValue::from_bool(value.is_none()),
+    )?;
+    Reflect::set(
+        &result,
+        &JsValue::from_str("value"),
+        &value.unwrap_or(JsValue::UNDEFINED),
+    )?;
+    Ok(result.into())
+}
+
+/// Options of [`JsStore::load`] and [`JsStore::load_stream`]
+#[derive(Default)]
+struct LoadOptions {
+    format: Option<RdfFormat>,
+    base_iri: Option<String>,
+    to_graph_name: Option<JsTerm>,
+    unchecked: bool,
+    no_transaction: bool,
+}
+
+impl LoadOptions {
+    fn from_js(options: &JsValue) -> Result<Self, JsValue> {
+        let mut parsed = Self::default();
+        if options.is_undefined() || options.is_null() {
+            return Ok(parsed);
+        }
+        if let Some(format_str) = Reflect::get(options, &JsValue::from_str("format"))?.as_string() {
+            parsed.format = Some(rdf_format(&format_str)?);
+        }
+        parsed.base_iri =
+            convert_base_iri(&Reflect::get(options, &JsValue::from_str("base_iri"))?)?;
+        let to_graph_name_js = Reflect::get(options, &JsValue::from_str("to_graph_name"))?;
+        parsed.to_graph_name = FROM_JS.with(|c| c.to_optional_term(&to_graph_name_js))?;
+        parsed.unchecked = Reflect::get(options, &JsValue::from_str("unchecked"))?.is_truthy();
+        parsed.no_transaction =
+            Reflect::get(options, &JsValue::from_str("no_transaction"))?.is_truthy();
+        Ok(parsed)
+    }
+}
+
+fn load_from_slice(
+    store: &Store,
+    data: &[u8],
+    format: RdfFormat,
+    options: LoadOptions,
+) -> Result<(), JsValue> {
+    let mut parser = RdfParser::from_format(format);
+    if let Some(to_graph_name) = options.to_graph_name {
+        parser = parser.with_default_graph(GraphName::try_from(to_graph_name)?);
+    }
+    if let Some(base_iri) = options.base_iri {
+        parser = parser.with_base_iri(base_iri).map_err(JsError::from)?;
+    }
+    if options.unchecked {
+        parser = parser.unchecked();
+    }
+    let result = if options.no_transaction {
+        store.bulk_loader().load_from_reader(parser, data)
+    } else {
+        store.load_from_reader(parser, data)
+    };
+    result.map_err(JsError::from)?;
+    Ok(())
+}
+
+/// Reads a `ReadableStreamDefaultReader` until its end and calls `on_end` with the read bytes
+fn read_stream(
+    reader: JsValue,
+    mut data: Vec<u8>,
+    on_end: Box<dyn FnOnce(Vec<u8>) -> Result<JsValue, JsValue>>,
+) -> Result<JsValue, JsValue> {
+    let chunk = call_method(&reader, "read", &[])?;
+    call_method(
+        &chunk,
+        "then",
+        &[&Closure::once_into_js(
+            move |result: JsValue| -> Result<JsValue, JsValue> {
+                if Reflect::get(&result, &JsValue::from_str("done"))?.is_truthy() {
+                    return on_end(data);
+                }
+                let value = Reflect::get(&result, &JsValue::from_str("value"))?;
+                if let Some(value) = value.as_string() {
+                    data.extend_from_slice(value.as_bytes());
+                } else {
+                    data.extend(Uint8Array::new(&value).to_vec());
+                }
+                read_stream(reader, data, on_end)
+            },
+        )],
+    )
+}
+
+/// Adapter from [`Write`] to a `WritableStreamDefaultWriter`
+struct JsStreamWriter {
+    writer: JsValue,
+    ignore_error: JsValue,
+}
+
+impl Write for JsStreamWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = call_method(
+            &self.writer,
+            "write",
+            &[&JsValue::from(Uint8Array::from(buf))],
+        )
+        .map_err(|e| io::Error::other(format!("{e:?}")))?;
+        call_method(&written, "catch", &[&self.ignore_error])
+            .map_err(|e| io::Error::other(format!("{e:?}")))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn call_method(target: &JsValue, name: &str, args: &[&JsValue]) -> Result<JsValue, JsValue> {
+    Reflect::get(target, &JsValue::from_str(name))?
+        .dyn_into::<Function>()
+        .map_err(|_| format_err!("{} must be a function", name))?
+        .apply(target, &args.iter().copied().collect::<Array>())
+}
+
+/// Name of the IndexedDB object store used by [`JsStore::save_to_indexed_db`] and [`JsStore::load_from_indexed_db`]
+const INDEXED_DB_OBJECT_STORE: &str = "oxigraph";
+
+fn indexed_db_key(key: &JsValue) -> JsValue {
+    if key.is_undefined() || key.is_null() {
+        JsValue::from_str("store")
+    } else {
+        key.clone()
+    }
+}
+
+/// Opens an IndexedDB database, creating the object store if needed, and returns a promise of the database
+fn open_indexed_db(name: &str) -> Result<JsValue, JsValue> {
+    let factory = Reflect::get(&js_sys::global(), &JsValue::from_str("indexedDB"))?;
+    if factory.is_undefined() || factory.is_null() {
+        return Err(format_err!(
+            "IndexedDB is not available in this environment"
+        ));
+    }
+    let request = call_method(
+        &factory,
+        "open",
+        &[&JsValue::from_str(name), &JsValue::from(1)],
+    )?;
+    Reflect::set(
+        &request,
+        &JsValue::from_str("onupgradeneeded"),
+        &Closure::once_into_js(|event: JsValue| -> Result<(), JsValue> {
+            let database = Reflect::get(&event_target(&event)?, &JsValue::from_str("result"))?;
+            call_method(
+                &database,
+                "createObjectStore",
+                &[&JsValue::from_str(INDEXED_DB_OBJECT_STORE)],
+            )?;
+            Ok(())
+        }),
+    )?;
+    indexed_db_request_result(&request)
+}
+
+fn indexed_db_object_store(database: &JsValue, mode: &str) -> Result<JsValue, JsValue> {
+    let transaction = call_method(
+        database,
+        "transaction",
+        &[
+            &JsValue::from_str(INDEXED_DB_OBJECT_STORE),
+            &JsValue::from_str(mode),
+        ],
+    )?;
+    call_method(
+        &transaction,
+        "objectStore",
+        &[&JsValue::from_str(INDEXED_DB_OBJECT_STORE)],
+    )
+}
+
+/// Returns a promise resolved with the result of an `IDBRequest` or rejected with its error
+fn indexed_db_request_result(request: &JsValue) -> Result<JsValue, JsValue> {
+    let mut result = Ok(());
+    let promise = Promise::new(&mut |resolve, reject| {
+        result = set_indexed_db_request_callbacks(request, resolve, reject);
+    });
+    result?;
+    Ok(promise.into())
+}
+
+fn set_indexed_db_request_callbacks(
+    request: &JsValue,
+    resolve: Function,
+    reject: Function,
+) -> Result<(), JsValue> {
+    // The callbacks get the request from the event to avoid reference cycles with it
+    Reflect::set(
+        request,
+        &JsValue::from_str("onsuccess"),
+        &Closure::once_into_js(move |event: JsValue| -> Result<JsValue, JsValue> {
+            resolve.call1(
+                &JsValue::UNDEFINED,
+                &Reflect::get(&event_target(&event)?, &JsValue::from_str("result"))?,
+            )
+        }),
+    )?;
+    Reflect::set(
+        request,
+        &JsValue::from_str("onerror"),
+        &Closure::once_into_js(move |event: JsValue| -> Result<JsValue, JsValue> {
+            reject.call1(
+                &JsValue::UNDEFINED,
+                &Reflect::get(&event_target(&event)?, &JsValue::from_str("error"))?,
+            )
+        }),
+    )?;
+    Ok(())
+}
+
+fn event_target(event: &JsValue) -> Result<JsValue, JsValue> {
+    Reflect::get(event, &JsValue::from_str("target"))
 }
 
 fn rdf_format(format: &str) -> Result<RdfFormat, JsValue> {
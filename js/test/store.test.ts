@@ -171,6 +171,40 @@ describe("Store", () => {
         });
     });
 
+    describe("#queryIterator()", () => {
+        it("SELECT", () => {
+            const store = new Store([dataModel.quad(ex, ex, ex)]);
+            const results = store.queryIterator("SELECT ?s WHERE { ?s ?p ?o }");
+            const solutions = [...(results as Iterable<Map<string, Term>>)];
+            assert.strictEqual(1, solutions.length);
+            assert(ex.equals(solutions[0]?.get("s")));
+        });
+
+        it("SELECT with for await", async () => {
+            const store = new Store([dataModel.quad(ex, ex, ex), dataModel.quad(ex, ex, ex2)]);
+            const results = store.queryIterator("SELECT ?o WHERE { ?s ?p ?o }");
+            let count = 0;
+            for await (const solution of results as AsyncIterable<Map<string, Term>>) {
+                assert(solution.has("o"));
+                count += 1;
+            }
+            assert.strictEqual(2, count);
+        });
+
+        it("CONSTRUCT", () => {
+            const store = new Store([dataModel.quad(ex, ex, ex)]);
+            const results = store.queryIterator("CONSTRUCT { ?s ?p ?o } WHERE { ?s ?p ?o }");
+            const quads = [...(results as Iterable<Quad>)];
+            assert.strictEqual(1, quads.length);
+            assert(dataModel.quad(ex, ex, ex).equals(quads[0]));
+        });
+
+        it("ASK", () => {
+            const store = new Store([dataModel.quad(ex, ex, ex)]);
+            assert.strictEqual(true, store.queryIterator("ASK { ?s ?s ?s }"));
+        });
+    });
+
     describe("#update()", () => {
         it("INSERT DATA", () => {
             const store = new Store();
@@ -261,6 +295,34 @@ describe("Store", () => {
         });
     });
 
+    describe("#loadStream()", () => {
+        it("load NTriples from a stream", async () => {
+            const store = new Store();
+            const stream = new ReadableStream({
+                start(controller) {
+                    controller.enqueue(
+                        new TextEncoder().encode("<http://example.com> <http://example.com> "),
+                    );
+                    controller.enqueue("<http://example.com> .");
+                    controller.close();
+                },
+            });
+            await store.loadStream(stream, { format: "application/n-triples", to_graph_name: ex });
+            assert(store.has(dataModel.quad(ex, ex, ex, ex)));
+        });
+
+        it("load invalid data from a stream", async () => {
+            const store = new Store();
+            const stream = new ReadableStream({
+                start(controller) {
+                    controller.enqueue("<http://example.com> .");
+                    controller.close();
+                },
+            });
+            await assert.rejects(store.loadStream(stream, { format: "application/n-triples" }));
+        });
+    });
+
     describe("#dump()", () => {
         it("dump dataset content", () => {
             const store = new Store([dataModel.quad(ex, ex, ex, ex)]);
@@ -297,4 +359,43 @@ describe("Store", () => {
             );
         });
     });
+
+    describe("#dumpStream()", () => {
+        it("dump dataset content to a stream", async () => {
+            const store = new Store([dataModel.quad(ex, ex, ex, ex)]);
+            const chunks: Uint8Array[] = [];
+            const stream = new WritableStream<Uint8Array>({
+                write(chunk) {
+                    chunks.push(chunk);
+                },
+            });
+            await store.dumpStream(stream, { format: "application/n-quads" });
+            assert.strictEqual(
+                "<http://example.com> <http://example.com> <http://example.com> <http://example.com> .\n",
+                Buffer.concat(chunks).toString(),
+            );
+        });
+    });
+
+    describe("#dumpSnapshot()", () => {
+        it("dump and load snapshot", () => {
+            const store = new Store([dataModel.quad(ex, ex, ex, ex), triple]);
+            const restored = new Store();
+            restored.loadSnapshot(store.dumpSnapshot());
+            assert.strictEqual(2, restored.size);
+            assert(restored.has(dataModel.quad(ex, ex, ex, ex)));
+            assert(restored.has(triple));
+        });
+
+        it("load invalid snapshot", () => {
+            assert.throws(() => new Store().loadSnapshot(new Uint8Array([1, 2, 3])));
+        });
+    });
+
+    describe("#saveToIndexedDB()", () => {
+        it.skipIf("indexedDB" in globalThis)("fails without IndexedDB", () => {
+            assert.throws(() => new Store().saveToIndexedDB("test"));
+            assert.throws(() => new Store().loadFromIndexedDB("test"));
+        });
+    });
 });
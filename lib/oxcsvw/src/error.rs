@@ -0,0 +1,73 @@
+use std::error::Error;
+use std::{fmt, io};
+
+/// An error in the [CSVW metadata](https://www.w3.org/TR/tabular-metadata/) given to a [`CsvwParser`](crate::CsvwParser).
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid CSVW metadata: {message}")]
+pub struct CsvwMetadataError {
+    message: String,
+}
+
+impl CsvwMetadataError {
+    pub(crate) fn msg(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+/// Error returned during the conversion of a CSV file.
+#[derive(Debug, thiserror::Error)]
+pub enum CsvwParseError {
+    /// I/O error during parsing (file not found...).
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// An error in the file content or in its conversion to RDF.
+    #[error(transparent)]
+    Syntax(#[from] CsvwSyntaxError),
+}
+
+impl From<CsvwParseError> for io::Error {
+    #[inline]
+    fn from(error: CsvwParseError) -> Self {
+        match error {
+            CsvwParseError::Io(error) => error,
+            CsvwParseError::Syntax(error) => Self::new(io::ErrorKind::InvalidData, error),
+        }
+    }
+}
+
+/// An error in the CSV file or in its conversion to RDF.
+#[derive(Debug)]
+pub struct CsvwSyntaxError {
+    message: String,
+    line: Option<u64>,
+}
+
+impl CsvwSyntaxError {
+    pub(crate) fn new(message: impl Into<String>, line: Option<u64>) -> Self {
+        Self {
+            message: message.into(),
+            line,
+        }
+    }
+
+    /// The 1-based line in the CSV file of the row the error is about, if known.
+    #[inline]
+    pub fn line(&self) -> Option<u64> {
+        self.line
+    }
+}
+
+impl fmt::Display for CsvwSyntaxError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "Error on line {line}: {}", self.message)
+        } else {
+            f.write_str(&self.message)
+        }
+    }
+}
+
+impl Error for CsvwSyntaxError {}
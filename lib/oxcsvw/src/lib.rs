@@ -0,0 +1,13 @@
+#![doc = include_str!("../README.md")]
+#![doc(test(attr(deny(warnings))))]
+#![cfg_attr(docsrs, feature(doc_auto_cfg))]
+#![doc(html_favicon_url = "https://raw.githubusercontent.com/oxigraph/oxigraph/main/logo.svg")]
+#![doc(html_logo_url = "https://raw.githubusercontent.com/oxigraph/oxigraph/main/logo.svg")]
+
+mod error;
+mod metadata;
+mod parser;
+mod template;
+
+pub use crate::error::{CsvwMetadataError, CsvwParseError, CsvwSyntaxError};
+pub use crate::parser::{CsvwParser, ReaderCsvwParser};
@@ -0,0 +1,531 @@
+//! Parsing of the [CSVW metadata](https://www.w3.org/TR/tabular-metadata/) JSON documents.
+
+use crate::error::CsvwMetadataError;
+use crate::template::UriTemplate;
+use oxrdf::vocab::{rdf, xsd};
+use oxrdf::NamedNode;
+use serde_json::{Map, Value};
+
+const CSVW_NAMESPACE: &str = "http://www.w3.org/ns/csvw";
+const XSD_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema#";
+
+/// The description of the converted table
+#[derive(Clone, Default)]
+pub struct TableMetadata {
+    /// The table URL, relative to the metadata base IRI
+    pub url: Option<String>,
+    /// The `@base` set in the metadata context
+    pub base: Option<String>,
+    pub dialect: Dialect,
+    /// The columns described in the table schema, empty if they should be read from the header
+    pub columns: Vec<ColumnMetadata>,
+    /// The properties inherited by the columns not described in the schema
+    pub inherited: Inherited,
+    pub suppress_output: bool,
+}
+
+/// How the CSV file is formatted
+#[derive(Clone)]
+pub struct Dialect {
+    pub delimiter: Option<u8>,
+    /// `None` if the quoting is disabled
+    pub quote_char: Option<u8>,
+    pub double_quote: bool,
+    pub comment_prefix: Option<u8>,
+    pub header_row_count: usize,
+    pub skip_rows: usize,
+    pub skip_columns: usize,
+    pub skip_blank_rows: bool,
+    pub trim: Trim,
+}
+
+impl Default for Dialect {
+    fn default() -> Self {
+        Self {
+            delimiter: None,
+            quote_char: Some(b'"'),
+            double_quote: true,
+            comment_prefix: Some(b'#'),
+            header_row_count: 1,
+            skip_rows: 0,
+            skip_columns: 0,
+            skip_blank_rows: false,
+            trim: Trim::Both,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Trim {
+    None,
+    Start,
+    End,
+    Both,
+}
+
+impl Trim {
+    pub fn apply(self, value: &str) -> &str {
+        match self {
+            Self::None => value,
+            Self::Start => value.trim_start(),
+            Self::End => value.trim_end(),
+            Self::Both => value.trim(),
+        }
+    }
+}
+
+/// A column of the table schema
+#[derive(Clone)]
+pub struct ColumnMetadata {
+    pub name: Option<String>,
+    pub titles: Vec<String>,
+    pub suppress_output: bool,
+    pub is_virtual: bool,
+    pub inherited: Inherited,
+}
+
+/// The [inherited properties](https://www.w3.org/TR/tabular-metadata/#inherited-properties) of a column
+#[derive(Clone, Default)]
+pub struct Inherited {
+    pub about_url: Option<UriTemplate>,
+    pub property_url: Option<UriTemplate>,
+    pub value_url: Option<UriTemplate>,
+    pub datatype: Option<Datatype>,
+    pub default: Option<String>,
+    pub lang: Option<String>,
+    pub null: Option<Vec<String>>,
+    pub separator: Option<String>,
+    pub ordered: Option<bool>,
+}
+
+impl Inherited {
+    /// The properties of `self` completed by the ones of `parent`
+    fn inheriting(self, parent: &Self) -> Self {
+        Self {
+            about_url: self.about_url.or_else(|| parent.about_url.clone()),
+            property_url: self.property_url.or_else(|| parent.property_url.clone()),
+            value_url: self.value_url.or_else(|| parent.value_url.clone()),
+            datatype: self.datatype.or_else(|| parent.datatype.clone()),
+            default: self.default.or_else(|| parent.default.clone()),
+            lang: self.lang.or_else(|| parent.lang.clone()),
+            null: self.null.or_else(|| parent.null.clone()),
+            separator: self.separator.or_else(|| parent.separator.clone()),
+            ordered: self.ordered.or(parent.ordered),
+        }
+    }
+}
+
+/// The datatype of the cell values
+#[derive(Clone)]
+pub struct Datatype {
+    pub iri: NamedNode,
+    /// The lexical forms of true and false set by a `format` on a boolean datatype
+    pub boolean_format: Option<(String, String)>,
+}
+
+impl TableMetadata {
+    /// Parses a table description or a table group description with a single table
+    pub fn parse(json: &str) -> Result<Self, CsvwMetadataError> {
+        let value = serde_json::from_str::<Value>(json)
+            .map_err(|e| CsvwMetadataError::msg(format!("Invalid JSON: {e}")))?;
+        let Value::Object(root) = value else {
+            return Err(CsvwMetadataError::msg("The metadata must be a JSON object"));
+        };
+        let base = parse_context(&root)?;
+        let (table, group) = if let Some(tables) = root.get("tables") {
+            let Value::Array(tables) = tables else {
+                return Err(CsvwMetadataError::msg("tables must be an array"));
+            };
+            let [Value::Object(table)] = tables.as_slice() else {
+                return Err(CsvwMetadataError::msg(
+                    "Only table groups with a single table are supported",
+                ));
+            };
+            (table, Some(&root))
+        } else {
+            (&root, None)
+        };
+
+        let mut inherited = Inherited::default();
+        let mut dialect = Dialect::default();
+        if let Some(group) = group {
+            inherited = parse_inherited(group)?;
+            if let Some(group_dialect) = group.get("dialect") {
+                dialect = parse_dialect(group_dialect)?;
+            }
+        }
+        inherited = parse_inherited(table)?.inheriting(&inherited);
+        if let Some(table_dialect) = table.get("dialect") {
+            dialect = parse_dialect(table_dialect)?;
+        }
+        let url = optional_string(table, "url")?;
+        let suppress_output = optional_bool(table, "suppressOutput")?.unwrap_or(false);
+        let mut columns = Vec::new();
+        match table.get("tableSchema") {
+            None => (),
+            Some(Value::Object(schema)) => {
+                inherited = parse_inherited(schema)?.inheriting(&inherited);
+                if let Some(schema_columns) = schema.get("columns") {
+                    let Value::Array(schema_columns) = schema_columns else {
+                        return Err(CsvwMetadataError::msg("columns must be an array"));
+                    };
+                    for column in schema_columns {
+                        let Value::Object(column) = column else {
+                            return Err(CsvwMetadataError::msg(
+                                "The columns must be JSON objects",
+                            ));
+                        };
+                        columns.push(parse_column(column, &inherited)?);
+                    }
+                }
+            }
+            Some(Value::String(_)) => {
+                return Err(CsvwMetadataError::msg(
+                    "Table schemas referenced by URL are not supported, the schema must be embedded in the metadata",
+                ))
+            }
+            Some(_) => return Err(CsvwMetadataError::msg("tableSchema must be an object")),
+        }
+        Ok(Self {
+            url,
+            base,
+            dialect,
+            columns,
+            inherited,
+            suppress_output,
+        })
+    }
+}
+
+impl ColumnMetadata {
+    /// A column not described in the metadata
+    pub fn from_title(title: Option<String>, inherited: &Inherited) -> Self {
+        Self {
+            name: None,
+            titles: title.into_iter().collect(),
+            suppress_output: false,
+            is_virtual: false,
+            inherited: inherited.clone(),
+        }
+    }
+}
+
+/// Returns the `@base` of the context after validating it is the CSVW one
+fn parse_context(root: &Map<String, Value>) -> Result<Option<String>, CsvwMetadataError> {
+    match root.get("@context") {
+        None => Ok(None),
+        Some(Value::String(context)) => {
+            check_context_iri(context)?;
+            Ok(None)
+        }
+        Some(Value::Array(context)) => {
+            let [Value::String(context), Value::Object(local)] = context.as_slice() else {
+                return Err(CsvwMetadataError::msg(
+                    "The @context must be the CSVW namespace or an array with the CSVW namespace and an object",
+                ));
+            };
+            check_context_iri(context)?;
+            optional_string(local, "@base")
+        }
+        Some(_) => Err(CsvwMetadataError::msg(
+            "The @context must be the CSVW namespace or an array with the CSVW namespace and an object",
+        )),
+    }
+}
+
+fn check_context_iri(context: &str) -> Result<(), CsvwMetadataError> {
+    if context == CSVW_NAMESPACE {
+        Ok(())
+    } else {
+        Err(CsvwMetadataError::msg(format!(
+            "The @context must be {CSVW_NAMESPACE}, found {context}"
+        )))
+    }
+}
+
+fn parse_column(
+    column: &Map<String, Value>,
+    inherited: &Inherited,
+) -> Result<ColumnMetadata, CsvwMetadataError> {
+    let titles = match column.get("titles") {
+        None => Vec::new(),
+        Some(titles) => parse_natural_language(titles)?,
+    };
+    Ok(ColumnMetadata {
+        name: optional_string(column, "name")?,
+        titles,
+        suppress_output: optional_bool(column, "suppressOutput")?.unwrap_or(false),
+        is_virtual: optional_bool(column, "virtual")?.unwrap_or(false),
+        inherited: parse_inherited(column)?.inheriting(inherited),
+    })
+}
+
+fn parse_inherited(object: &Map<String, Value>) -> Result<Inherited, CsvwMetadataError> {
+    Ok(Inherited {
+        about_url: optional_template(object, "aboutUrl")?,
+        property_url: optional_template(object, "propertyUrl")?,
+        value_url: optional_template(object, "valueUrl")?,
+        datatype: object.get("datatype").map(parse_datatype).transpose()?,
+        default: optional_string(object, "default")?,
+        lang: optional_string(object, "lang")?,
+        null: match object.get("null") {
+            None => None,
+            Some(Value::String(null)) => Some(vec![null.clone()]),
+            Some(Value::Array(nulls)) => Some(
+                nulls
+                    .iter()
+                    .map(|null| {
+                        null.as_str().map(ToOwned::to_owned).ok_or_else(|| {
+                            CsvwMetadataError::msg("null must be a string or an array of strings")
+                        })
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            Some(_) => {
+                return Err(CsvwMetadataError::msg(
+                    "null must be a string or an array of strings",
+                ))
+            }
+        },
+        separator: optional_string(object, "separator")?,
+        ordered: optional_bool(object, "ordered")?,
+    })
+}
+
+fn parse_dialect(dialect: &Value) -> Result<Dialect, CsvwMetadataError> {
+    let Value::Object(dialect) = dialect else {
+        return Err(CsvwMetadataError::msg(
+            "Dialects referenced by URL are not supported, the dialect must be embedded in the metadata",
+        ));
+    };
+    let mut result = Dialect::default();
+    if let Some(encoding) = optional_string(dialect, "encoding")? {
+        if !encoding.eq_ignore_ascii_case("utf-8") {
+            return Err(CsvwMetadataError::msg(format!(
+                "Only the UTF-8 encoding is supported, found {encoding}"
+            )));
+        }
+    }
+    if let Some(delimiter) = optional_string(dialect, "delimiter")? {
+        result.delimiter = Some(single_byte(&delimiter, "delimiter")?);
+    }
+    match dialect.get("quoteChar") {
+        None => (),
+        Some(Value::Null) => result.quote_char = None,
+        Some(Value::String(quote_char)) => {
+            result.quote_char = Some(single_byte(quote_char, "quoteChar")?)
+        }
+        Some(_) => return Err(CsvwMetadataError::msg("quoteChar must be a string or null")),
+    }
+    if let Some(double_quote) = optional_bool(dialect, "doubleQuote")? {
+        result.double_quote = double_quote;
+    }
+    match dialect.get("commentPrefix") {
+        None => (),
+        Some(Value::Null) => result.comment_prefix = None,
+        Some(Value::String(comment_prefix)) => {
+            result.comment_prefix = Some(single_byte(comment_prefix, "commentPrefix")?)
+        }
+        Some(_) => {
+            return Err(CsvwMetadataError::msg(
+                "commentPrefix must be a string or null",
+            ))
+        }
+    }
+    if let Some(header) = optional_bool(dialect, "header")? {
+        result.header_row_count = usize::from(header);
+    }
+    if let Some(header_row_count) = optional_usize(dialect, "headerRowCount")? {
+        result.header_row_count = header_row_count;
+    }
+    if let Some(skip_rows) = optional_usize(dialect, "skipRows")? {
+        result.skip_rows = skip_rows;
+    }
+    if let Some(skip_columns) = optional_usize(dialect, "skipColumns")? {
+        result.skip_columns = skip_columns;
+    }
+    if let Some(skip_blank_rows) = optional_bool(dialect, "skipBlankRows")? {
+        result.skip_blank_rows = skip_blank_rows;
+    }
+    match dialect.get("skipInitialSpace") {
+        Some(Value::Bool(true)) if dialect.get("trim").is_none() => result.trim = Trim::Start,
+        _ => (),
+    }
+    match dialect.get("trim") {
+        None => (),
+        Some(Value::Bool(true)) => result.trim = Trim::Both,
+        Some(Value::Bool(false)) => result.trim = Trim::None,
+        Some(Value::String(trim)) => {
+            result.trim = match trim.as_str() {
+                "true" => Trim::Both,
+                "false" => Trim::None,
+                "start" => Trim::Start,
+                "end" => Trim::End,
+                _ => {
+                    return Err(CsvwMetadataError::msg(format!(
+                        "Unsupported trim value {trim}"
+                    )))
+                }
+            }
+        }
+        Some(_) => return Err(CsvwMetadataError::msg("trim must be a boolean or a string")),
+    }
+    Ok(result)
+}
+
+fn parse_datatype(datatype: &Value) -> Result<Datatype, CsvwMetadataError> {
+    match datatype {
+        Value::String(name) => Ok(Datatype {
+            iri: builtin_datatype(name)?,
+            boolean_format: None,
+        }),
+        Value::Object(datatype) => {
+            let iri = if let Some(id) = optional_string(datatype, "@id")? {
+                NamedNode::new(&id).map_err(|e| {
+                    CsvwMetadataError::msg(format!("Invalid datatype IRI {id}: {e}"))
+                })?
+            } else {
+                builtin_datatype(
+                    optional_string(datatype, "base")?
+                        .as_deref()
+                        .unwrap_or("string"),
+                )?
+            };
+            let boolean_format = match optional_string(datatype, "format")? {
+                Some(format) if iri.as_ref() == xsd::BOOLEAN => {
+                    let Some((true_value, false_value)) = format.split_once('|') else {
+                        return Err(CsvwMetadataError::msg(format!(
+                            "The boolean format must be of the form true|false, found {format}"
+                        )));
+                    };
+                    Some((true_value.into(), false_value.into()))
+                }
+                _ => None,
+            };
+            Ok(Datatype {
+                iri,
+                boolean_format,
+            })
+        }
+        _ => Err(CsvwMetadataError::msg(
+            "datatype must be a string or an object",
+        )),
+    }
+}
+
+/// The IRI of the [built-in datatypes](https://www.w3.org/TR/tabular-metadata/#built-in-datatypes)
+fn builtin_datatype(name: &str) -> Result<NamedNode, CsvwMetadataError> {
+    Ok(match name {
+        "any" => NamedNode::new_unchecked(format!("{XSD_NAMESPACE}anyAtomicType")),
+        "binary" => xsd::BASE_64_BINARY.into_owned(),
+        "datetime" => xsd::DATE_TIME.into_owned(),
+        "number" => xsd::DOUBLE.into_owned(),
+        "json" => NamedNode::new_unchecked(format!("{CSVW_NAMESPACE}#JSON")),
+        "xml" => rdf::XML_LITERAL.into_owned(),
+        "html" => rdf::HTML.into_owned(),
+        "anyAtomicType" | "anyURI" | "base64Binary" | "boolean" | "byte" | "date" | "dateTime"
+        | "dateTimeStamp" | "dayTimeDuration" | "decimal" | "double" | "duration" | "float"
+        | "gDay" | "gMonth" | "gMonthDay" | "gYear" | "gYearMonth" | "hexBinary" | "int"
+        | "integer" | "language" | "long" | "Name" | "NCName" | "negativeInteger" | "NMTOKEN"
+        | "nonNegativeInteger" | "nonPositiveInteger" | "normalizedString" | "positiveInteger"
+        | "QName" | "short" | "string" | "time" | "token" | "unsignedByte" | "unsignedInt"
+        | "unsignedLong" | "unsignedShort" | "yearMonthDuration" => {
+            NamedNode::new_unchecked(format!("{XSD_NAMESPACE}{name}"))
+        }
+        _ => {
+            return Err(CsvwMetadataError::msg(format!(
+                "Unsupported datatype {name}, use an object with an @id for custom datatypes"
+            )))
+        }
+    })
+}
+
+/// Parses a [natural language property](https://www.w3.org/TR/tabular-metadata/#natural-language-properties) into its values
+fn parse_natural_language(value: &Value) -> Result<Vec<String>, CsvwMetadataError> {
+    match value {
+        Value::String(value) => Ok(vec![value.clone()]),
+        Value::Array(values) => values
+            .iter()
+            .map(|value| {
+                value
+                    .as_str()
+                    .map(ToOwned::to_owned)
+                    .ok_or_else(|| CsvwMetadataError::msg("The titles must be strings"))
+            })
+            .collect(),
+        Value::Object(values) => {
+            let mut result = Vec::new();
+            for value in values.values() {
+                result.extend(parse_natural_language(value)?);
+            }
+            Ok(result)
+        }
+        _ => Err(CsvwMetadataError::msg(
+            "titles must be a string, an array or an object",
+        )),
+    }
+}
+
+fn single_byte(value: &str, property: &str) -> Result<u8, CsvwMetadataError> {
+    match value.as_bytes() {
+        [byte] => Ok(*byte),
+        _ => Err(CsvwMetadataError::msg(format!(
+            "{property} must be a single ASCII character, found {value}"
+        ))),
+    }
+}
+
+fn optional_string(
+    object: &Map<String, Value>,
+    property: &str,
+) -> Result<Option<String>, CsvwMetadataError> {
+    match object.get(property) {
+        None => Ok(None),
+        Some(Value::String(value)) => Ok(Some(value.clone())),
+        Some(_) => Err(CsvwMetadataError::msg(format!(
+            "{property} must be a string"
+        ))),
+    }
+}
+
+fn optional_bool(
+    object: &Map<String, Value>,
+    property: &str,
+) -> Result<Option<bool>, CsvwMetadataError> {
+    match object.get(property) {
+        None => Ok(None),
+        Some(Value::Bool(value)) => Ok(Some(*value)),
+        Some(_) => Err(CsvwMetadataError::msg(format!(
+            "{property} must be a boolean"
+        ))),
+    }
+}
+
+fn optional_usize(
+    object: &Map<String, Value>,
+    property: &str,
+) -> Result<Option<usize>, CsvwMetadataError> {
+    match object.get(property) {
+        None => Ok(None),
+        Some(value) => value
+            .as_u64()
+            .and_then(|value| usize::try_from(value).ok())
+            .map(Some)
+            .ok_or_else(|| {
+                CsvwMetadataError::msg(format!("{property} must be a non-negative integer"))
+            }),
+    }
+}
+
+fn optional_template(
+    object: &Map<String, Value>,
+    property: &str,
+) -> Result<Option<UriTemplate>, CsvwMetadataError> {
+    optional_string(object, property)?
+        .map(|template| {
+            UriTemplate::parse(&template).map_err(|e| {
+                CsvwMetadataError::msg(format!("Invalid {property} template {template}: {e}"))
+            })
+        })
+        .transpose()
+}
@@ -0,0 +1,507 @@
+use crate::error::{CsvwMetadataError, CsvwParseError, CsvwSyntaxError};
+use crate::metadata::{ColumnMetadata, TableMetadata};
+use crate::template::{percent_encode, UriTemplate};
+use csv::{Position, ReaderBuilder, StringRecord};
+use oxiri::{Iri, IriParseError};
+use oxrdf::vocab::{rdf, xsd};
+use oxrdf::{BlankNode, Literal, NamedNode, Subject, Term, Triple};
+use std::collections::VecDeque;
+use std::io::Read;
+
+/// The prefixes of the [RDFa initial context](https://www.w3.org/2011/rdfa-context/rdfa-1.1) that can be used in the URI templates.
+const PREFIXES: [(&str, &str); 17] = [
+    ("csvw", "http://www.w3.org/ns/csvw#"),
+    ("dc", "http://purl.org/dc/terms/"),
+    ("dcat", "http://www.w3.org/ns/dcat#"),
+    ("dcterms", "http://purl.org/dc/terms/"),
+    ("foaf", "http://xmlns.com/foaf/0.1/"),
+    ("org", "http://www.w3.org/ns/org#"),
+    ("owl", "http://www.w3.org/2002/07/owl#"),
+    ("prov", "http://www.w3.org/ns/prov#"),
+    ("qb", "http://purl.org/linked-data/cube#"),
+    ("rdf", "http://www.w3.org/1999/02/22-rdf-syntax-ns#"),
+    ("rdfs", "http://www.w3.org/2000/01/rdf-schema#"),
+    ("schema", "http://schema.org/"),
+    ("sioc", "http://rdfs.org/sioc/ns#"),
+    ("skos", "http://www.w3.org/2004/02/skos/core#"),
+    ("vcard", "http://www.w3.org/2006/vcard/ns#"),
+    ("void", "http://rdfs.org/ns/void#"),
+    ("xsd", "http://www.w3.org/2001/XMLSchema#"),
+];
+
+/// A converter from CSV files to RDF following the [CSV on the Web](https://www.w3.org/TR/csv2rdf/) minimal mode.
+///
+/// The conversion is driven by [CSVW metadata](https://www.w3.org/TR/tabular-metadata/) describing the table.
+/// If no metadata is given, the columns are named after the header row titles.
+///
+/// ```
+/// use oxcsvw::CsvwParser;
+///
+/// let metadata = r#"{
+///     "@context": "http://www.w3.org/ns/csvw",
+///     "url": "people.csv",
+///     "tableSchema": {
+///         "aboutUrl": "people/{id}",
+///         "columns": [
+///             {"name": "id", "suppressOutput": true},
+///             {"name": "name", "propertyUrl": "schema:name"},
+///             {"name": "age", "propertyUrl": "schema:age", "datatype": "integer"}
+///         ]
+///     }
+/// }"#;
+/// let file = b"id,name,age\n1,Alice,42\n";
+///
+/// let triples = CsvwParser::new()
+///     .with_metadata(metadata)?
+///     .with_base_iri("http://example.com/")?
+///     .for_reader(file.as_ref())
+///     .map(|t| t.map(|t| t.to_string()))
+///     .collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(
+///     triples,
+///     [
+///         "<http://example.com/people/1> <http://schema.org/name> \"Alice\"",
+///         "<http://example.com/people/1> <http://schema.org/age> \"42\"^^<http://www.w3.org/2001/XMLSchema#integer>"
+///     ]
+/// );
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[derive(Default, Clone)]
+#[must_use]
+pub struct CsvwParser {
+    metadata: TableMetadata,
+    base: Option<Iri<String>>,
+    delimiter: Option<u8>,
+}
+
+impl CsvwParser {
+    /// Builds a new [`CsvwParser`].
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the [CSVW metadata](https://www.w3.org/TR/tabular-metadata/) JSON document describing the table.
+    ///
+    /// It might be a table description or a table group description with a single table.
+    /// The table schema and the dialect must be embedded in the document.
+    pub fn with_metadata(mut self, metadata: &str) -> Result<Self, CsvwMetadataError> {
+        self.metadata = TableMetadata::parse(metadata)?;
+        Ok(self)
+    }
+
+    /// Sets the IRI of the metadata document, against which the table `url` is resolved.
+    ///
+    /// If the metadata does not set a table `url`, it is the IRI of the table.
+    #[inline]
+    pub fn with_base_iri(mut self, base_iri: impl Into<String>) -> Result<Self, IriParseError> {
+        self.base = Some(Iri::parse(base_iri.into())?);
+        Ok(self)
+    }
+
+    /// Sets the cell delimiter to use if the metadata dialect does not set one.
+    ///
+    /// By default `,` is used.
+    #[inline]
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = Some(delimiter);
+        self
+    }
+
+    /// Converts a CSV file from a [`Read`] implementation.
+    pub fn for_reader<R: Read>(self, reader: R) -> ReaderCsvwParser<R> {
+        let dialect = &self.metadata.dialect;
+        let mut builder = ReaderBuilder::new();
+        builder
+            .has_headers(false)
+            .flexible(true)
+            .delimiter(dialect.delimiter.or(self.delimiter).unwrap_or(b','))
+            .double_quote(dialect.double_quote)
+            .comment(dialect.comment_prefix);
+        if let Some(quote_char) = dialect.quote_char {
+            builder.quote(quote_char);
+        } else {
+            builder.quoting(false);
+        }
+        let (table_url, pending_error) = match table_url(&self.metadata, self.base.as_ref()) {
+            Ok(table_url) => (Some(table_url), None),
+            Err(error) => (None, Some(error.into())),
+        };
+        ReaderCsvwParser {
+            ended: table_url.is_none(),
+            reader: builder.from_reader(reader),
+            metadata: self.metadata,
+            table_url,
+            columns: None,
+            titles: Vec::new(),
+            record: StringRecord::new(),
+            read_rows: 0,
+            data_rows: 0,
+            buffer: VecDeque::new(),
+            pending_error,
+        }
+    }
+}
+
+/// Converts a CSV file from a [`Read`] implementation. Can be built using [`CsvwParser::for_reader`].
+///
+/// The triples are returned in the order of the rows.
+#[must_use]
+pub struct ReaderCsvwParser<R: Read> {
+    reader: csv::Reader<R>,
+    metadata: TableMetadata,
+    /// `None` if the table has no absolute URL
+    table_url: Option<Iri<String>>,
+    /// Built on the first data row
+    columns: Option<Vec<Column>>,
+    /// The titles read from the first header row
+    titles: Vec<String>,
+    record: StringRecord,
+    /// The number of records read, including the skipped and header ones
+    read_rows: usize,
+    data_rows: u64,
+    buffer: VecDeque<Triple>,
+    pending_error: Option<CsvwParseError>,
+    ended: bool,
+}
+
+struct Column {
+    name: String,
+    metadata: ColumnMetadata,
+    /// The predicate used if the column has no `propertyUrl`
+    default_property: NamedNode,
+}
+
+impl<R: Read> ReaderCsvwParser<R> {
+    /// Reads the next record and buffers its triples. Returns `false` at the end of the file.
+    fn read_row(&mut self) -> Result<bool, CsvwParseError> {
+        let Some(table_url) = &self.table_url else {
+            return Ok(false);
+        };
+        if !self
+            .reader
+            .read_record(&mut self.record)
+            .map_err(csv_error)?
+        {
+            return Ok(false);
+        }
+        let line = self.record.position().map(Position::line);
+        let dialect = &self.metadata.dialect;
+        self.read_rows += 1;
+        if self.read_rows <= dialect.skip_rows {
+            return Ok(true);
+        }
+        let cells = self
+            .record
+            .iter()
+            .skip(dialect.skip_columns)
+            .map(|cell| dialect.trim.apply(cell))
+            .collect::<Vec<_>>();
+        if self.read_rows <= dialect.skip_rows + dialect.header_row_count {
+            if self.read_rows == dialect.skip_rows + 1 {
+                self.titles = cells.iter().map(|cell| (*cell).to_owned()).collect();
+            }
+            return Ok(true);
+        }
+        if dialect.skip_blank_rows && cells.iter().all(|cell| cell.is_empty()) {
+            return Ok(true);
+        }
+        self.data_rows += 1;
+
+        let columns = self.columns.get_or_insert_with(|| {
+            let mut columns = self.metadata.columns.clone();
+            if columns.is_empty() {
+                columns = self
+                    .titles
+                    .drain(..)
+                    .map(|title| ColumnMetadata::from_title(Some(title), &self.metadata.inherited))
+                    .collect();
+            }
+            columns
+                .into_iter()
+                .enumerate()
+                .map(|(i, metadata)| Column::new(i, metadata, table_url))
+                .collect()
+        });
+        let cell_columns = columns.iter().filter(|c| !c.metadata.is_virtual).count();
+        if cells.len() > cell_columns {
+            if !self.metadata.columns.is_empty() {
+                return Err(CsvwSyntaxError::new(
+                    format!(
+                        "The row has {} cells but the table schema only describes {cell_columns} columns",
+                        cells.len()
+                    ),
+                    line,
+                )
+                .into());
+            }
+            // No schema: we add the missing columns
+            for i in columns.len()..cells.len() {
+                columns.push(Column::new(
+                    i,
+                    ColumnMetadata::from_title(None, &self.metadata.inherited),
+                    table_url,
+                ));
+            }
+        }
+        if self.metadata.suppress_output {
+            return Ok(true);
+        }
+
+        // We compute the cell values
+        let mut values = Vec::with_capacity(columns.len());
+        let mut cells = cells.into_iter();
+        for column in &*columns {
+            values.push(if column.metadata.is_virtual {
+                None
+            } else {
+                column.value(cells.next().unwrap_or(""))
+            });
+        }
+
+        // We build the triples
+        let row = self.data_rows.to_string();
+        let source_row = line.map(|line| line.to_string());
+        let row_node = Subject::from(BlankNode::default());
+        for (i, (column, value)) in columns.iter().zip(&values).enumerate() {
+            if column.metadata.suppress_output {
+                continue;
+            }
+            let column_number = (i + 1).to_string();
+            let source_column_number = (i + 1 + dialect.skip_columns).to_string();
+            let variable = |name: &str| match name {
+                "_row" => Some(row.as_str()),
+                "_sourceRow" => source_row.as_deref(),
+                "_column" => Some(column_number.as_str()),
+                "_sourceColumn" => Some(source_column_number.as_str()),
+                "_name" => Some(column.name.as_str()),
+                _ => columns
+                    .iter()
+                    .position(|c| c.name == name)
+                    .and_then(|i| values[i]),
+            };
+            let inherited = &column.metadata.inherited;
+            let objects = if let Some(value_url) = &inherited.value_url {
+                if value.is_none() && !column.metadata.is_virtual {
+                    continue;
+                }
+                vec![expand_template(value_url, variable, table_url, line)?.into()]
+            } else if let (Some(value), false) = (value, column.metadata.is_virtual) {
+                column.objects(value, &mut self.buffer, line)?
+            } else {
+                continue;
+            };
+            let subject = if let Some(about_url) = &inherited.about_url {
+                expand_template(about_url, variable, table_url, line)?.into()
+            } else {
+                row_node.clone()
+            };
+            let predicate = if let Some(property_url) = &inherited.property_url {
+                expand_template(property_url, variable, table_url, line)?
+            } else {
+                column.default_property.clone()
+            };
+            for object in objects {
+                self.buffer
+                    .push_back(Triple::new(subject.clone(), predicate.clone(), object));
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl<R: Read> Iterator for ReaderCsvwParser<R> {
+    type Item = Result<Triple, CsvwParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(triple) = self.buffer.pop_front() {
+                return Some(Ok(triple));
+            }
+            if let Some(error) = self.pending_error.take() {
+                return Some(Err(error));
+            }
+            if self.ended {
+                return None;
+            }
+            match self.read_row() {
+                Ok(true) => (),
+                Ok(false) => self.ended = true,
+                Err(error) => {
+                    if matches!(error, CsvwParseError::Io(_)) {
+                        self.ended = true;
+                    }
+                    self.pending_error = Some(error);
+                }
+            }
+        }
+    }
+}
+
+impl Column {
+    fn new(index: usize, metadata: ColumnMetadata, table_url: &Iri<String>) -> Self {
+        let name = metadata
+            .name
+            .clone()
+            .or_else(|| metadata.titles.first().cloned())
+            .unwrap_or_else(|| format!("_col.{}", index + 1));
+        let mut default_property = table_url
+            .as_str()
+            .split_once('#')
+            .map_or(table_url.as_str(), |(without_fragment, _)| without_fragment)
+            .to_owned();
+        default_property.push('#');
+        percent_encode(&name, false, &mut default_property);
+        Self {
+            name,
+            metadata,
+            default_property: NamedNode::new_unchecked(default_property),
+        }
+    }
+
+    /// The value of a cell, `None` if it is null
+    fn value<'a>(&'a self, cell: &'a str) -> Option<&'a str> {
+        let inherited = &self.metadata.inherited;
+        let value = if cell.is_empty() {
+            inherited.default.as_deref().unwrap_or("")
+        } else {
+            cell
+        };
+        if self.is_null(value) {
+            None
+        } else {
+            Some(value)
+        }
+    }
+
+    fn is_null(&self, value: &str) -> bool {
+        match &self.metadata.inherited.null {
+            Some(nulls) => nulls.iter().any(|null| null == value),
+            None => value.is_empty(),
+        }
+    }
+
+    /// The objects for a non-null cell value: a literal per value, or a RDF list if the values are ordered
+    fn objects(
+        &self,
+        value: &str,
+        buffer: &mut VecDeque<Triple>,
+        line: Option<u64>,
+    ) -> Result<Vec<Term>, CsvwSyntaxError> {
+        let inherited = &self.metadata.inherited;
+        let Some(separator) = &inherited.separator else {
+            return Ok(vec![self.literal(value, line)?.into()]);
+        };
+        let values = value
+            .split(separator.as_str())
+            .map(str::trim)
+            .filter(|value| !self.is_null(value));
+        if !inherited.ordered.unwrap_or(false) {
+            return values
+                .map(|value| Ok(self.literal(value, line)?.into()))
+                .collect();
+        }
+        let mut list = Term::from(rdf::NIL.into_owned());
+        for value in values.collect::<Vec<_>>().into_iter().rev() {
+            let node = BlankNode::default();
+            buffer.push_back(Triple::new(
+                node.clone(),
+                rdf::FIRST,
+                self.literal(value, line)?,
+            ));
+            buffer.push_back(Triple::new(node.clone(), rdf::REST, list));
+            list = node.into();
+        }
+        Ok(vec![list])
+    }
+
+    fn literal(&self, value: &str, line: Option<u64>) -> Result<Literal, CsvwSyntaxError> {
+        let inherited = &self.metadata.inherited;
+        let Some(datatype) = &inherited.datatype else {
+            return self.string_literal(value, line);
+        };
+        if datatype.iri.as_ref() == xsd::STRING {
+            return self.string_literal(value, line);
+        }
+        if let Some((true_value, false_value)) = &datatype.boolean_format {
+            if value == true_value {
+                return Ok(true.into());
+            }
+            if value == false_value {
+                return Ok(false.into());
+            }
+        }
+        Ok(Literal::new_typed_literal(value, datatype.iri.clone()))
+    }
+
+    fn string_literal(&self, value: &str, line: Option<u64>) -> Result<Literal, CsvwSyntaxError> {
+        match self.metadata.inherited.lang.as_deref() {
+            None | Some("und") => Ok(Literal::new_simple_literal(value)),
+            Some(lang) => Literal::new_language_tagged_literal(value, lang).map_err(|e| {
+                CsvwSyntaxError::new(format!("Invalid language tag {lang}: {e}"), line)
+            }),
+        }
+    }
+}
+
+/// Resolves the table URL against the metadata `@base` and the parser base IRI
+fn table_url(
+    metadata: &TableMetadata,
+    base: Option<&Iri<String>>,
+) -> Result<Iri<String>, CsvwSyntaxError> {
+    let base = match (&metadata.base, base) {
+        (Some(metadata_base), Some(base)) => Some(base.resolve(metadata_base).map_err(|e| {
+            CsvwSyntaxError::new(format!("Invalid metadata @base {metadata_base}: {e}"), None)
+        })?),
+        (Some(metadata_base), None) => Some(Iri::parse(metadata_base.clone()).map_err(|e| {
+            CsvwSyntaxError::new(format!("Invalid metadata @base {metadata_base}: {e}"), None)
+        })?),
+        (None, base) => base.cloned(),
+    };
+    match (&metadata.url, base) {
+        (Some(url), Some(base)) => base
+            .resolve(url)
+            .map_err(|e| CsvwSyntaxError::new(format!("Invalid table url {url}: {e}"), None)),
+        (Some(url), None) => Iri::parse(url.clone()).map_err(|e| {
+            CsvwSyntaxError::new(
+                format!("The table url {url} is not an absolute IRI and no base IRI is set: {e}"),
+                None,
+            )
+        }),
+        (None, Some(base)) => Ok(base),
+        (None, None) => Err(CsvwSyntaxError::new(
+            "The table has no url, a base IRI must be set to identify it",
+            None,
+        )),
+    }
+}
+
+/// Expands the template, expands the prefixed names and resolves the result against the table URL
+fn expand_template<'a>(
+    template: &UriTemplate,
+    variable: impl Fn(&str) -> Option<&'a str>,
+    table_url: &Iri<String>,
+    line: Option<u64>,
+) -> Result<NamedNode, CsvwSyntaxError> {
+    let mut expanded = template.expand(variable);
+    if let Some((prefix, local)) = expanded.split_once(':') {
+        if let Some((_, namespace)) = PREFIXES.iter().find(|(p, _)| *p == prefix) {
+            expanded = format!("{namespace}{local}");
+        }
+    }
+    let iri = table_url
+        .resolve(&expanded)
+        .map_err(|e| CsvwSyntaxError::new(format!("Invalid IRI {expanded}: {e}"), line))?;
+    Ok(NamedNode::new_unchecked(iri.into_inner()))
+}
+
+fn csv_error(error: csv::Error) -> CsvwParseError {
+    let line = error.position().map(Position::line);
+    if error.is_io_error() {
+        if let csv::ErrorKind::Io(error) = error.into_kind() {
+            return error.into();
+        }
+        return CsvwSyntaxError::new("Unexpected I/O error", line).into();
+    }
+    CsvwSyntaxError::new(error.to_string(), line).into()
+}
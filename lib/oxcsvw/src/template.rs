@@ -0,0 +1,150 @@
+//! The [URI templates](https://www.rfc-editor.org/rfc/rfc6570) used by the `aboutUrl`, `propertyUrl` and `valueUrl` properties.
+//!
+//! Only the simple string, reserved and fragment expansions are supported.
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+#[derive(Clone)]
+pub struct UriTemplate {
+    parts: Vec<TemplatePart>,
+}
+
+#[derive(Clone)]
+enum TemplatePart {
+    Literal(String),
+    Expression {
+        operator: Operator,
+        variables: Vec<String>,
+    },
+}
+
+#[derive(Clone, Copy)]
+enum Operator {
+    /// `{var}`
+    Simple,
+    /// `{+var}`
+    Reserved,
+    /// `{#var}`
+    Fragment,
+}
+
+impl UriTemplate {
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                parts.push(TemplatePart::Literal(rest[..start].into()));
+            }
+            let Some(end) = rest[start..].find('}') else {
+                return Err("unclosed expression".into());
+            };
+            let expression = &rest[start + 1..start + end];
+            let (operator, variables) = match expression.chars().next() {
+                Some('+') => (Operator::Reserved, &expression[1..]),
+                Some('#') => (Operator::Fragment, &expression[1..]),
+                Some('.' | '/' | ';' | '?' | '&' | '=' | ',' | '!' | '@' | '|') => {
+                    return Err(format!("unsupported expression {{{expression}}}"))
+                }
+                _ => (Operator::Simple, expression),
+            };
+            let variables = variables
+                .split(',')
+                .map(|variable| {
+                    if variable.is_empty() || variable.contains([':', '*']) {
+                        Err(format!("unsupported variable {variable}"))
+                    } else {
+                        Ok(variable.to_owned())
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            parts.push(TemplatePart::Expression {
+                operator,
+                variables,
+            });
+            rest = &rest[start + end + 1..];
+        }
+        if rest.contains('}') {
+            return Err("unopened expression".into());
+        }
+        if !rest.is_empty() {
+            parts.push(TemplatePart::Literal(rest.into()));
+        }
+        Ok(Self { parts })
+    }
+
+    /// Expands the template, `value` returning the value of a variable or `None` if it is undefined
+    pub fn expand<'a>(&self, value: impl Fn(&str) -> Option<&'a str>) -> String {
+        let mut result = String::new();
+        for part in &self.parts {
+            match part {
+                TemplatePart::Literal(literal) => result.push_str(literal),
+                TemplatePart::Expression {
+                    operator,
+                    variables,
+                } => {
+                    let mut first = true;
+                    for variable in variables {
+                        let Some(value) = value(variable) else {
+                            continue;
+                        };
+                        if first {
+                            if matches!(operator, Operator::Fragment) {
+                                result.push('#');
+                            }
+                            first = false;
+                        } else {
+                            result.push(',');
+                        }
+                        match operator {
+                            Operator::Simple => percent_encode(value, false, &mut result),
+                            Operator::Reserved | Operator::Fragment => {
+                                percent_encode(value, true, &mut result)
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Percent-encodes the characters that are not unreserved, and not reserved if `allow_reserved` is set
+pub fn percent_encode(value: &str, allow_reserved: bool, output: &mut String) {
+    let bytes = value.as_bytes();
+    for (i, byte) in bytes.iter().enumerate() {
+        let keep = byte.is_ascii_alphanumeric()
+            || matches!(byte, b'-' | b'.' | b'_' | b'~')
+            || (allow_reserved
+                && (matches!(
+                    byte,
+                    b':' | b'/'
+                        | b'?'
+                        | b'#'
+                        | b'['
+                        | b']'
+                        | b'@'
+                        | b'!'
+                        | b'$'
+                        | b'&'
+                        | b'\''
+                        | b'('
+                        | b')'
+                        | b'*'
+                        | b'+'
+                        | b','
+                        | b';'
+                        | b'='
+                ) || (*byte == b'%'
+                    && bytes.get(i + 1).is_some_and(u8::is_ascii_hexdigit)
+                    && bytes.get(i + 2).is_some_and(u8::is_ascii_hexdigit))));
+        if keep {
+            output.push(char::from(*byte));
+        } else {
+            output.push('%');
+            output.push(char::from(HEX_DIGITS[usize::from(byte >> 4)]));
+            output.push(char::from(HEX_DIGITS[usize::from(byte & 0xF)]));
+        }
+    }
+}
@@ -0,0 +1,194 @@
+#![cfg(test)]
+#![allow(clippy::panic_in_result_fn)]
+
+use oxcsvw::{CsvwParseError, CsvwParser};
+use oxrdf::{Graph, Literal, NamedNode, Subject, TermRef, Triple};
+
+fn convert(parser: CsvwParser, file: &str) -> Result<Vec<Triple>, CsvwParseError> {
+    parser.for_reader(file.as_bytes()).collect()
+}
+
+fn nn(iri: &str) -> NamedNode {
+    NamedNode::new_unchecked(iri)
+}
+
+#[test]
+fn test_without_metadata() -> Result<(), Box<dyn std::error::Error>> {
+    let triples = convert(
+        CsvwParser::new().with_base_iri("http://example.com/table.csv")?,
+        "name,first name\nAlice,A\n\"Bob, Jr\",\n",
+    )?;
+    assert_eq!(triples.len(), 3);
+    assert_eq!(
+        triples[0].predicate,
+        nn("http://example.com/table.csv#name")
+    );
+    assert_eq!(
+        triples[1].predicate,
+        nn("http://example.com/table.csv#first%20name")
+    );
+    assert_eq!(triples[2].object, Literal::from("Bob, Jr").into());
+    // A blank node per row
+    assert!(matches!(triples[0].subject, Subject::BlankNode(_)));
+    assert_eq!(triples[0].subject, triples[1].subject);
+    assert_ne!(triples[0].subject, triples[2].subject);
+    Ok(())
+}
+
+#[test]
+fn test_with_metadata() -> Result<(), Box<dyn std::error::Error>> {
+    let metadata = r#"{
+        "@context": ["http://www.w3.org/ns/csvw", {"@base": "http://example.com/data/"}],
+        "url": "cities.csv",
+        "tableSchema": {
+            "aboutUrl": "city/{id}",
+            "columns": [
+                {"name": "id", "titles": "ID", "suppressOutput": true},
+                {"name": "label", "propertyUrl": "rdfs:label", "lang": "fr"},
+                {"name": "population", "propertyUrl": "schema:population", "datatype": "integer", "null": "N/A"},
+                {"name": "capital", "propertyUrl": "http://example.com/capital", "datatype": {"base": "boolean", "format": "Y|N"}, "default": "N"},
+                {"name": "country", "propertyUrl": "schema:containedInPlace", "valueUrl": "country/{country}"},
+                {"name": "tags", "propertyUrl": "schema:keywords", "separator": "|"},
+                {"name": "type", "virtual": true, "propertyUrl": "rdf:type", "valueUrl": "schema:City"}
+            ]
+        }
+    }"#;
+    let triples = convert(
+        CsvwParser::new().with_metadata(metadata)?,
+        "ID,Label,Population,Capital,Country,Tags\n1,Paris,2100000,Y,fr,big|old\n2,Lyon,N/A,,fr,\n",
+    )?;
+    let paris = nn("http://example.com/data/city/1");
+    let lyon = nn("http://example.com/data/city/2");
+    let expected = [
+        Triple::new(
+            paris.clone(),
+            nn("http://www.w3.org/2000/01/rdf-schema#label"),
+            Literal::new_language_tagged_literal("Paris", "fr")?,
+        ),
+        Triple::new(
+            paris.clone(),
+            nn("http://schema.org/population"),
+            Literal::new_typed_literal(
+                "2100000",
+                nn("http://www.w3.org/2001/XMLSchema#integer"),
+            ),
+        ),
+        Triple::new(
+            paris.clone(),
+            nn("http://example.com/capital"),
+            Literal::from(true),
+        ),
+        Triple::new(
+            paris.clone(),
+            nn("http://schema.org/containedInPlace"),
+            nn("http://example.com/data/country/fr"),
+        ),
+        Triple::new(
+            paris.clone(),
+            nn("http://schema.org/keywords"),
+            Literal::from("big"),
+        ),
+        Triple::new(
+            paris.clone(),
+            nn("http://schema.org/keywords"),
+            Literal::from("old"),
+        ),
+        Triple::new(
+            paris,
+            nn("http://www.w3.org/1999/02/22-rdf-syntax-ns#type"),
+            nn("http://schema.org/City"),
+        ),
+        Triple::new(
+            lyon.clone(),
+            nn("http://www.w3.org/2000/01/rdf-schema#label"),
+            Literal::new_language_tagged_literal("Lyon", "fr")?,
+        ),
+        Triple::new(
+            lyon.clone(),
+            nn("http://example.com/capital"),
+            Literal::from(false),
+        ),
+        Triple::new(
+            lyon.clone(),
+            nn("http://schema.org/containedInPlace"),
+            nn("http://example.com/data/country/fr"),
+        ),
+        Triple::new(
+            lyon,
+            nn("http://www.w3.org/1999/02/22-rdf-syntax-ns#type"),
+            nn("http://schema.org/City"),
+        ),
+    ];
+    assert_eq!(triples, expected);
+    Ok(())
+}
+
+#[test]
+fn test_ordered_list() -> Result<(), Box<dyn std::error::Error>> {
+    let metadata = r##"{
+        "url": "http://example.com/t.tsv",
+        "dialect": {"delimiter": "\t", "header": false},
+        "tableSchema": {
+            "aboutUrl": "#{_row}",
+            "columns": [{"name": "list", "separator": " ", "ordered": true, "datatype": "integer"}]
+        }
+    }"##;
+    let graph = convert(CsvwParser::new().with_metadata(metadata)?, "1 2\n")?
+        .into_iter()
+        .collect::<Graph>();
+    assert_eq!(graph.len(), 5);
+    let Some(TermRef::BlankNode(head)) = graph.object_for_subject_predicate(
+        &nn("http://example.com/t.tsv#1"),
+        &nn("http://example.com/t.tsv#list"),
+    ) else {
+        return Err("The list head should be a blank node".into());
+    };
+    assert_eq!(
+        graph.object_for_subject_predicate(
+            head,
+            &nn("http://www.w3.org/1999/02/22-rdf-syntax-ns#first")
+        ),
+        Some(
+            Literal::new_typed_literal("1", nn("http://www.w3.org/2001/XMLSchema#integer"))
+                .as_ref()
+                .into()
+        )
+    );
+    Ok(())
+}
+
+#[test]
+fn test_tsv_delimiter() -> Result<(), Box<dyn std::error::Error>> {
+    let triples = convert(
+        CsvwParser::new()
+            .with_base_iri("http://example.com/t.tsv")?
+            .with_delimiter(b'\t'),
+        "a\tb\n1,2\t3\n",
+    )?;
+    assert_eq!(triples.len(), 2);
+    assert_eq!(triples[0].object, Literal::from("1,2").into());
+    Ok(())
+}
+
+#[test]
+fn test_errors() -> Result<(), Box<dyn std::error::Error>> {
+    assert!(CsvwParser::new().with_metadata("[]").is_err());
+    assert!(CsvwParser::new()
+        .with_metadata(r#"{"tableSchema": "schema.json"}"#)
+        .is_err());
+    // No base IRI to identify the table
+    convert(CsvwParser::new(), "a\n1\n").unwrap_err();
+    // More cells than columns
+    let parser = CsvwParser::new()
+        .with_metadata(r#"{"url": "http://example.com/t.csv", "tableSchema": {"columns": [{"name": "a"}]}}"#)?;
+    let results = parser.for_reader(b"a\n1,2\n3\n".as_ref()).collect::<Vec<_>>();
+    assert_eq!(results.len(), 2);
+    let Err(CsvwParseError::Syntax(error)) = &results[0] else {
+        return Err("The first row should be invalid".into());
+    };
+    assert_eq!(error.line(), Some(2));
+    let Ok(_) = &results[1] else {
+        return Err("The second row should be valid".into());
+    };
+    Ok(())
+}
@@ -27,6 +27,7 @@
 //! ```
 
 mod format;
+pub(crate) mod parallel;
 pub mod read;
 pub mod write;
 
@@ -0,0 +1,221 @@
+//! Parallel parsing of the line-based formats (N-Triples and N-Quads).
+//!
+//! The input is split into chunks of full lines that are parsed by a pool of threads.
+
+use crate::io::{RdfFormat, RdfParseError, RdfParser};
+use crate::model::{BlankNode, GraphName, Quad, Subject, Term, Triple};
+use rand::random;
+use siphasher::sip128::{Hasher128, SipHasher24};
+use std::hash::Hasher;
+use std::io::{self, Read};
+use std::mem::{replace, take};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::vec;
+
+/// Size of the chunks read from the input. They are extended to the end of their last line.
+const CHUNK_SIZE: u64 = 4 * 1024 * 1024;
+
+/// If the format has one statement per line, allowing to split the input between lines
+pub fn is_line_based(format: RdfFormat) -> bool {
+    matches!(format, RdfFormat::NTriples | RdfFormat::NQuads)
+}
+
+/// Parses the input of a line-based format with `num_threads` threads and gives the parsed quads to `consume`.
+///
+/// The blank nodes are renamed to fresh ones, consistently across the chunks.
+/// The quads are not returned in the input order and the error positions are relative to the start of their chunk.
+pub fn parse_lines_in_parallel<R: Read, T>(
+    parser: &RdfParser,
+    reader: R,
+    num_threads: usize,
+    consume: impl FnOnce(ParallelQuads<R>) -> T,
+) -> T {
+    let (chunk_sender, chunk_receiver) = channel::<Vec<u8>>();
+    let (result_sender, result_receiver) = channel();
+    let chunk_receiver = Arc::new(Mutex::new(chunk_receiver));
+    let renamer = BlankNodeRenamer::new();
+    thread::scope(|scope| {
+        for _ in 0..num_threads {
+            let parser = parser.clone();
+            let chunk_receiver = Arc::clone(&chunk_receiver);
+            let result_sender = result_sender.clone();
+            scope.spawn(move || parse_chunks(&parser, &chunk_receiver, &result_sender, renamer));
+        }
+        drop(result_sender);
+        // The quads iterator owns the chunk sender: the workers stop as soon as it is dropped
+        consume(ParallelQuads {
+            reader,
+            buffer: Vec::new(),
+            reader_done: false,
+            chunk_sender: Some(chunk_sender),
+            result_receiver,
+            in_flight: 0,
+            max_in_flight: 2 * num_threads,
+            current: Vec::new().into_iter(),
+        })
+    })
+}
+
+fn parse_chunks(
+    parser: &RdfParser,
+    chunk_receiver: &Mutex<Receiver<Vec<u8>>>,
+    result_sender: &Sender<Vec<Result<Quad, RdfParseError>>>,
+    renamer: BlankNodeRenamer,
+) {
+    loop {
+        let Ok(chunk) = chunk_receiver
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .recv()
+        else {
+            return; // No more chunks
+        };
+        let results = parser
+            .clone()
+            .for_slice(&chunk)
+            .map(|quad| quad.map(|quad| renamer.quad(quad)).map_err(Into::into))
+            .collect();
+        if result_sender.send(results).is_err() {
+            return; // The consumer has stopped
+        }
+    }
+}
+
+/// The quads parsed by [`parse_lines_in_parallel`]
+pub struct ParallelQuads<R: Read> {
+    reader: R,
+    /// The beginning of the line following the last sent chunk
+    buffer: Vec<u8>,
+    reader_done: bool,
+    chunk_sender: Option<Sender<Vec<u8>>>,
+    result_receiver: Receiver<Vec<Result<Quad, RdfParseError>>>,
+    in_flight: usize,
+    max_in_flight: usize,
+    current: vec::IntoIter<Result<Quad, RdfParseError>>,
+}
+
+impl<R: Read> ParallelQuads<R> {
+    /// Reads the next chunk of full lines
+    fn read_chunk(&mut self) -> io::Result<Option<Vec<u8>>> {
+        loop {
+            let start = self.buffer.len();
+            if (&mut self.reader)
+                .take(CHUNK_SIZE)
+                .read_to_end(&mut self.buffer)?
+                == 0
+            {
+                self.reader_done = true;
+                return Ok((!self.buffer.is_empty()).then(|| take(&mut self.buffer)));
+            }
+            if let Some(end) = self.buffer[start..].iter().rposition(|b| *b == b'\n') {
+                let rest = self.buffer.split_off(start + end + 1);
+                return Ok(Some(replace(&mut self.buffer, rest)));
+            }
+        }
+    }
+}
+
+impl<R: Read> Iterator for ParallelQuads<R> {
+    type Item = Result<Quad, RdfParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(quad) = self.current.next() {
+                return Some(quad);
+            }
+            // We keep the workers busy
+            while !self.reader_done && self.in_flight < self.max_in_flight {
+                match self.read_chunk() {
+                    Ok(Some(chunk)) => {
+                        let sent = self
+                            .chunk_sender
+                            .as_ref()
+                            .is_some_and(|sender| sender.send(chunk).is_ok());
+                        if !sent {
+                            return Some(Err(
+                                io::Error::other("The parsing threads have stopped").into()
+                            ));
+                        }
+                        self.in_flight += 1;
+                    }
+                    Ok(None) => (),
+                    Err(e) => {
+                        // We still return the quads of the chunks being parsed
+                        self.reader_done = true;
+                        return Some(Err(e.into()));
+                    }
+                }
+            }
+            if self.reader_done {
+                self.chunk_sender = None;
+            }
+            if self.in_flight == 0 {
+                return None;
+            }
+            let Ok(results) = self.result_receiver.recv() else {
+                self.in_flight = 0;
+                return Some(Err(
+                    io::Error::other("The parsing threads have stopped").into()
+                ));
+            };
+            self.in_flight -= 1;
+            self.current = results.into_iter();
+        }
+    }
+}
+
+/// Maps the blank nodes to fresh ones using a keyed hash of their identifier to get the same mapping in all threads
+#[derive(Clone, Copy)]
+struct BlankNodeRenamer {
+    key: (u64, u64),
+}
+
+impl BlankNodeRenamer {
+    fn new() -> Self {
+        Self { key: random() }
+    }
+
+    fn blank_node(self, node: &BlankNode) -> BlankNode {
+        let mut hasher = SipHasher24::new_with_keys(self.key.0, self.key.1);
+        hasher.write(node.as_str().as_bytes());
+        BlankNode::new_from_unique_id(u128::from(hasher.finish128()))
+    }
+
+    fn subject(self, subject: Subject) -> Subject {
+        match subject {
+            Subject::BlankNode(node) => self.blank_node(&node).into(),
+            Subject::Triple(triple) => self.triple(*triple).into(),
+            Subject::NamedNode(_) => subject,
+        }
+    }
+
+    fn term(self, term: Term) -> Term {
+        match term {
+            Term::BlankNode(node) => self.blank_node(&node).into(),
+            Term::Triple(triple) => self.triple(*triple).into(),
+            Term::NamedNode(_) | Term::Literal(_) => term,
+        }
+    }
+
+    fn triple(self, triple: Triple) -> Triple {
+        Triple::new(
+            self.subject(triple.subject),
+            triple.predicate,
+            self.term(triple.object),
+        )
+    }
+
+    fn quad(self, quad: Quad) -> Quad {
+        Quad::new(
+            self.subject(quad.subject),
+            quad.predicate,
+            self.term(quad.object),
+            match quad.graph_name {
+                GraphName::BlankNode(node) => self.blank_node(&node).into(),
+                graph_name => graph_name,
+            },
+        )
+    }
+}
@@ -7,6 +7,7 @@
 
 pub mod io;
 pub mod model;
+pub mod reasoning;
 pub mod sparql;
 mod storage;
 pub mod store;
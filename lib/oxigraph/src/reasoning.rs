@@ -0,0 +1,996 @@
+//! Forward chaining reasoning with [N3](https://w3c.github.io/N3/spec/) rules.
+//!
+//! The rules are the `{ body } => { head }` statements of a N3 document (i.e. the [`log:implies`](https://www.w3.org/2000/10/swap/log#implies) triples between two formulas).
+//! They are applied to the default graph of a [`Dataset`] or a [`Store`] until no new triple is inferred.
+//!
+//! Usage example:
+//! ```
+//! use oxigraph::model::*;
+//! use oxigraph::reasoning::{N3ReasoningOptions, N3Rules};
+//!
+//! let rules = N3Rules::parse(
+//!     b"@prefix ex: <http://example.com/> .
+//!     @prefix math: <http://www.w3.org/2000/10/swap/math#> .
+//!     { ?x ex:parent ?y } => { ?y ex:child ?x } .
+//!     { ?x ex:age ?a . (?a 1) math:sum ?b } => { ?x ex:nextAge ?b } ."
+//!         .as_slice(),
+//!     None,
+//! )?;
+//!
+//! let ex = |name: &str| NamedNode::new_unchecked(format!("http://example.com/{name}"));
+//! let mut dataset = Dataset::new();
+//! dataset.insert(QuadRef::new(&ex("alice"), &ex("parent"), &ex("bob"), GraphNameRef::DefaultGraph));
+//! dataset.insert(QuadRef::new(&ex("alice"), &ex("age"), &Literal::from(41), GraphNameRef::DefaultGraph));
+//!
+//! let report = rules.apply_to_dataset(&mut dataset, N3ReasoningOptions::default())?;
+//! assert_eq!(report.inferred_triples(), 2);
+//! assert!(dataset.contains(QuadRef::new(&ex("bob"), &ex("child"), &ex("alice"), GraphNameRef::DefaultGraph)));
+//! assert!(dataset.contains(QuadRef::new(&ex("alice"), &ex("nextAge"), &Literal::from(42), GraphNameRef::DefaultGraph)));
+//! # Result::<_, Box<dyn std::error::Error>>::Ok(())
+//! ```
+//!
+//! The rule bodies might use these builtin predicates:
+//! * `math:sum`, `math:difference`, `math:product` and `math:quotient` with a list of numbers as subject,
+//! * `math:equalTo`, `math:notEqualTo`, `math:greaterThan`, `math:lessThan`, `math:notGreaterThan` and `math:notLessThan` between two numbers,
+//! * `string:concatenation` with a list of terms as subject, `string:contains`, `string:startsWith` and `string:endsWith` between two strings,
+//! * `log:equalTo` and `log:notEqualTo` between two terms.
+//!
+//! A builtin is evaluated as soon as its inputs are bound: its subject for the functions (`math:sum`, `string:concatenation`, `log:equalTo`...) that bind their object,
+//! and both its subject and object for the other ones.
+
+use crate::io::RdfParseError;
+use crate::model::vocab::{rdf, xsd};
+use crate::model::*;
+use crate::store::{StorageError, Store};
+use oxttl::n3::{N3Parser, N3Quad, N3Term};
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+
+const LOG_IMPLIES: &str = "http://www.w3.org/2000/10/swap/log#implies";
+
+/// A set of [N3](https://w3c.github.io/N3/spec/) rules.
+///
+/// See [the module documentation](self) for a usage example.
+#[derive(Debug, Clone, Default)]
+pub struct N3Rules {
+    rules: Vec<Rule>,
+}
+
+impl N3Rules {
+    /// Parses the rules of a N3 document.
+    ///
+    /// The statements that are not rules are ignored.
+    /// The blank nodes of a rule body are existentially quantified and a new blank node is created for each blank node of a rule head each time the rule is applied.
+    pub fn parse(reader: impl Read, base_iri: Option<&str>) -> Result<Self, N3RulesError> {
+        let mut parser = N3Parser::new();
+        if let Some(base_iri) = base_iri {
+            parser = parser
+                .with_base_iri(base_iri)
+                .map_err(|e| N3RulesError::InvalidBaseIri {
+                    iri: base_iri.into(),
+                    error: e,
+                })?;
+        }
+        let quads = parser
+            .for_reader(reader)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(RdfParseError::from)?;
+        Self::from_quads(quads)
+    }
+
+    /// Builds the rules from the quads returned by a N3 parser.
+    ///
+    /// The formulas are the blank node graph names of the quads.
+    pub fn from_quads(quads: impl IntoIterator<Item = N3Quad>) -> Result<Self, N3RulesError> {
+        let mut formulas = HashMap::<BlankNode, Vec<N3Quad>>::new();
+        let mut statements = Vec::new();
+        for quad in quads {
+            match &quad.graph_name {
+                GraphName::BlankNode(formula) => {
+                    formulas.entry(formula.clone()).or_default().push(quad);
+                }
+                GraphName::DefaultGraph => statements.push(quad),
+                GraphName::NamedNode(_) => (),
+            }
+        }
+        let mut rules = Vec::new();
+        for statement in statements {
+            if !matches!(&statement.predicate, N3Term::NamedNode(p) if p.as_str() == LOG_IMPLIES) {
+                continue;
+            }
+            let (N3Term::BlankNode(body), N3Term::BlankNode(head)) =
+                (&statement.subject, &statement.object)
+            else {
+                return Err(N3RulesError::invalid_rule(
+                    "The subject and the object of log:implies must be formulas",
+                ));
+            };
+            rules.push(Rule::new(
+                formulas.get(body).map_or(&[], Vec::as_slice),
+                formulas.get(head).map_or(&[], Vec::as_slice),
+                &formulas,
+            )?);
+        }
+        Ok(Self { rules })
+    }
+
+    /// The number of rules.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Checks if there are no rules.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Applies the rules to the default graph of the dataset and inserts the inferred triples into it until a fixpoint is reached.
+    ///
+    /// If a limit of the options is exceeded an error is returned and the triples inferred so far are kept in the dataset.
+    pub fn apply_to_dataset(
+        &self,
+        dataset: &mut Dataset,
+        options: N3ReasoningOptions,
+    ) -> Result<N3ReasoningReport, N3ReasoningError> {
+        self.saturate(&mut DatasetTriples(dataset), options)
+    }
+
+    /// Applies the rules to the default graph of the store and inserts the inferred triples into it until a fixpoint is reached.
+    ///
+    /// The triples inferred during an iteration are inserted in a single transaction.
+    /// If a limit of the options is exceeded an error is returned and the triples inferred so far are kept in the store.
+    pub fn apply_to_store(
+        &self,
+        store: &Store,
+        options: N3ReasoningOptions,
+    ) -> Result<N3ReasoningReport, N3ReasoningError> {
+        self.saturate(&mut StoreTriples(store), options)
+    }
+
+    fn saturate(
+        &self,
+        triples: &mut impl TripleSource,
+        options: N3ReasoningOptions,
+    ) -> Result<N3ReasoningReport, N3ReasoningError> {
+        // The applications of the rules with blank nodes in their head, to not create new blank nodes each time
+        let mut applied = HashSet::new();
+        let mut report = N3ReasoningReport::default();
+        loop {
+            let mut new_triples = Vec::new();
+            let mut seen = HashSet::new();
+            for (i, rule) in self.rules.iter().enumerate() {
+                for solution in rule.solutions(&*triples)? {
+                    if rule.head_blank_nodes > 0 && !applied.insert((i, solution.clone())) {
+                        continue;
+                    }
+                    let blank_nodes = (0..rule.head_blank_nodes)
+                        .map(|_| BlankNode::default())
+                        .collect::<Vec<_>>();
+                    for pattern in &rule.head {
+                        let Some(triple) = pattern.instantiate(&solution, &blank_nodes) else {
+                            continue; // Not a valid RDF triple, e.g. with a literal subject
+                        };
+                        if !seen.contains(&triple) && !triples.contains(&triple)? {
+                            seen.insert(triple.clone());
+                            new_triples.push(triple);
+                        }
+                    }
+                }
+            }
+            if new_triples.is_empty() {
+                return Ok(report);
+            }
+            if report.iterations >= options.max_iterations {
+                return Err(N3ReasoningError::TooManyIterations(options.max_iterations));
+            }
+            report.iterations += 1;
+            report.inferred_triples += new_triples.len();
+            triples.insert(new_triples)?;
+            if let Some(max_inferred_triples) = options.max_inferred_triples {
+                if report.inferred_triples > max_inferred_triples {
+                    return Err(N3ReasoningError::TooManyInferredTriples(
+                        max_inferred_triples,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// Options of the application of [`N3Rules`].
+#[derive(Debug, Clone, Copy)]
+#[must_use]
+pub struct N3ReasoningOptions {
+    max_iterations: usize,
+    max_inferred_triples: Option<usize>,
+}
+
+impl Default for N3ReasoningOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_iterations: 1000,
+            max_inferred_triples: None,
+        }
+    }
+}
+
+impl N3ReasoningOptions {
+    /// Sets the maximal number of iterations inferring new triples, by default 1000.
+    ///
+    /// Each iteration applies all the rules to the triples inferred so far.
+    /// It protects against recursive rules creating new blank nodes forever.
+    #[inline]
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// Sets the maximal number of triples that might be inferred, by default there is no limit.
+    #[inline]
+    pub fn with_max_inferred_triples(mut self, max_inferred_triples: usize) -> Self {
+        self.max_inferred_triples = Some(max_inferred_triples);
+        self
+    }
+}
+
+/// Statistics about an application of [`N3Rules`].
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct N3ReasoningReport {
+    iterations: usize,
+    inferred_triples: usize,
+}
+
+impl N3ReasoningReport {
+    /// The number of iterations that have inferred new triples.
+    #[inline]
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    /// The number of inferred triples that were not already in the data.
+    #[inline]
+    pub fn inferred_triples(&self) -> usize {
+        self.inferred_triples
+    }
+}
+
+/// An error raised while loading [`N3Rules`].
+#[derive(Debug, thiserror::Error)]
+pub enum N3RulesError {
+    /// The N3 document is invalid.
+    #[error(transparent)]
+    Parsing(#[from] RdfParseError),
+    /// The base IRI is invalid.
+    #[error("Invalid base IRI '{iri}': {error}")]
+    InvalidBaseIri {
+        iri: String,
+        #[source]
+        error: IriParseError,
+    },
+    /// A rule is not supported.
+    #[error("Invalid N3 rule: {0}")]
+    InvalidRule(String),
+}
+
+impl N3RulesError {
+    fn invalid_rule(message: impl Into<String>) -> Self {
+        Self::InvalidRule(message.into())
+    }
+}
+
+/// An error raised while applying [`N3Rules`].
+#[derive(Debug, thiserror::Error)]
+pub enum N3ReasoningError {
+    /// An error from the storage.
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+    /// No fixpoint has been reached after the maximal number of iterations.
+    #[error("No fixpoint has been reached after {0} iterations")]
+    TooManyIterations(usize),
+    /// More triples than the maximal number of inferred triples have been inferred.
+    #[error("More than {0} triples have been inferred")]
+    TooManyInferredTriples(usize),
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    /// The body triple patterns and builtins in evaluation order
+    steps: Vec<Step>,
+    head: Vec<TriplePattern>,
+    variables: usize,
+    head_blank_nodes: usize,
+}
+
+#[derive(Debug, Clone)]
+enum Step {
+    Match(TriplePattern),
+    Builtin {
+        builtin: Builtin,
+        subject: PatternTerm,
+        object: PatternTerm,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct TriplePattern {
+    subject: PatternTerm,
+    predicate: PatternTerm,
+    object: PatternTerm,
+}
+
+#[derive(Debug, Clone)]
+enum PatternTerm {
+    Constant(Term),
+    Variable(usize),
+    /// A blank node of the head, a new one is created for each application of the rule
+    BlankNode(usize),
+    /// A list used as builtin argument
+    List(Vec<PatternTerm>),
+}
+
+type Solution = Vec<Option<Term>>;
+
+impl Rule {
+    fn new(
+        body: &[N3Quad],
+        head: &[N3Quad],
+        formulas: &HashMap<BlankNode, Vec<N3Quad>>,
+    ) -> Result<Self, N3RulesError> {
+        let mut builder = RuleBuilder {
+            formulas,
+            variables: HashMap::new(),
+            head_blank_nodes: HashMap::new(),
+        };
+
+        // We find the lists in the body to pass them to the builtins
+        let mut firsts = HashMap::new();
+        let mut rests = HashMap::new();
+        for quad in body {
+            if let N3Term::BlankNode(subject) = &quad.subject {
+                match &quad.predicate {
+                    N3Term::NamedNode(p) if *p == rdf::FIRST => {
+                        firsts.insert(subject, &quad.object);
+                    }
+                    N3Term::NamedNode(p) if *p == rdf::REST => {
+                        rests.insert(subject, &quad.object);
+                    }
+                    _ => (),
+                }
+            }
+        }
+        let mut list_nodes = HashSet::new();
+        let mut builtins = Vec::new();
+        let mut patterns = Vec::new();
+        for quad in body {
+            let builtin = if let N3Term::NamedNode(p) = &quad.predicate {
+                Builtin::from_iri(p.as_str())
+            } else {
+                None
+            };
+            if let Some(builtin) = builtin {
+                let subject = if let Some(list) =
+                    builder.body_list(&quad.subject, &firsts, &rests, &mut list_nodes)?
+                {
+                    list
+                } else {
+                    builder.body_term(&quad.subject)?
+                };
+                builtins.push((builtin, subject, builder.body_term(&quad.object)?));
+            } else {
+                patterns.push(quad);
+            }
+        }
+        let mut pending_patterns = Vec::new();
+        for quad in patterns {
+            if matches!(&quad.subject, N3Term::BlankNode(b) if list_nodes.contains(b)) {
+                continue; // Consumed by a builtin
+            }
+            pending_patterns.push(TriplePattern {
+                subject: builder.body_term(&quad.subject)?,
+                predicate: builder.body_term(&quad.predicate)?,
+                object: builder.body_term(&quad.object)?,
+            });
+        }
+
+        // We evaluate the builtins as soon as their inputs are bound
+        let mut steps = Vec::new();
+        let mut bound = HashSet::new();
+        let mut pending_patterns = pending_patterns.into_iter();
+        loop {
+            if let Some(position) = builtins.iter().position(|(builtin, subject, object)| {
+                subject.variables().all(|v| bound.contains(&v))
+                    && (builtin.binds_object() || object.variables().all(|v| bound.contains(&v)))
+            }) {
+                let (builtin, subject, object) = builtins.remove(position);
+                bound.extend(object.variables());
+                steps.push(Step::Builtin {
+                    builtin,
+                    subject,
+                    object,
+                });
+            } else if let Some(pattern) = pending_patterns.next() {
+                bound.extend(pattern.subject.variables());
+                bound.extend(pattern.predicate.variables());
+                bound.extend(pattern.object.variables());
+                steps.push(Step::Match(pattern));
+            } else if builtins.is_empty() {
+                break;
+            } else {
+                return Err(N3RulesError::invalid_rule(
+                    "The inputs of some builtins are never bound",
+                ));
+            }
+        }
+
+        let head = head
+            .iter()
+            .map(|quad| {
+                Ok(TriplePattern {
+                    subject: builder.head_term(&quad.subject, &bound)?,
+                    predicate: builder.head_term(&quad.predicate, &bound)?,
+                    object: builder.head_term(&quad.object, &bound)?,
+                })
+            })
+            .collect::<Result<Vec<_>, N3RulesError>>()?;
+        Ok(Self {
+            steps,
+            head,
+            variables: builder.variables.len(),
+            head_blank_nodes: builder.head_blank_nodes.len(),
+        })
+    }
+
+    fn solutions(&self, triples: &impl TripleSource) -> Result<Vec<Solution>, N3ReasoningError> {
+        let mut solutions = vec![vec![None; self.variables]];
+        for step in &self.steps {
+            let mut new_solutions = Vec::new();
+            for mut solution in solutions {
+                match step {
+                    Step::Match(pattern) => {
+                        let subject = match pattern.subject.resolve(&solution) {
+                            Some(term) => match Subject::try_from(term) {
+                                Ok(subject) => Some(subject),
+                                Err(_) => continue,
+                            },
+                            None => None,
+                        };
+                        let predicate = match pattern.predicate.resolve(&solution) {
+                            Some(term) => match NamedNode::try_from(term) {
+                                Ok(predicate) => Some(predicate),
+                                Err(_) => continue,
+                            },
+                            None => None,
+                        };
+                        let object = pattern.object.resolve(&solution);
+                        for triple in triples.triples_for_pattern(
+                            subject.as_ref(),
+                            predicate.as_ref(),
+                            object.as_ref(),
+                        )? {
+                            let mut solution = solution.clone();
+                            if pattern.subject.bind(triple.subject.into(), &mut solution)
+                                && pattern
+                                    .predicate
+                                    .bind(triple.predicate.into(), &mut solution)
+                                && pattern.object.bind(triple.object, &mut solution)
+                            {
+                                new_solutions.push(solution);
+                            }
+                        }
+                    }
+                    Step::Builtin {
+                        builtin,
+                        subject,
+                        object,
+                    } => {
+                        if builtin.evaluate(subject, object, &mut solution) {
+                            new_solutions.push(solution);
+                        }
+                    }
+                }
+            }
+            solutions = new_solutions;
+        }
+        Ok(solutions)
+    }
+}
+
+struct RuleBuilder<'a> {
+    formulas: &'a HashMap<BlankNode, Vec<N3Quad>>,
+    variables: HashMap<String, usize>,
+    head_blank_nodes: HashMap<BlankNode, usize>,
+}
+
+impl RuleBuilder<'_> {
+    fn body_term(&mut self, term: &N3Term) -> Result<PatternTerm, N3RulesError> {
+        Ok(match term {
+            N3Term::Variable(variable) => self.variable(variable.as_str()),
+            N3Term::BlankNode(node) => {
+                self.check_not_formula(node)?;
+                // The body blank nodes are existential variables, their names do not collide with the variable names
+                self.variable(&format!("_:{}", node.as_str()))
+            }
+            N3Term::NamedNode(node) => PatternTerm::Constant(node.clone().into()),
+            N3Term::Literal(literal) => PatternTerm::Constant(literal.clone().into()),
+            N3Term::Triple(triple) => PatternTerm::Constant(triple.as_ref().clone().into()),
+        })
+    }
+
+    /// Returns the list whose head is the given term if it is a list of the body
+    fn body_list(
+        &mut self,
+        term: &N3Term,
+        firsts: &HashMap<&BlankNode, &N3Term>,
+        rests: &HashMap<&BlankNode, &N3Term>,
+        list_nodes: &mut HashSet<BlankNode>,
+    ) -> Result<Option<PatternTerm>, N3RulesError> {
+        if matches!(term, N3Term::NamedNode(node) if *node == rdf::NIL) {
+            return Ok(Some(PatternTerm::List(Vec::new())));
+        }
+        let N3Term::BlankNode(head) = term else {
+            return Ok(None);
+        };
+        if !firsts.contains_key(head) {
+            return Ok(None);
+        }
+        let mut node = head;
+        let mut elements = Vec::new();
+        loop {
+            let (Some(first), Some(rest)) = (firsts.get(node), rests.get(node)) else {
+                return Err(N3RulesError::invalid_rule("Invalid list in a rule body"));
+            };
+            if !list_nodes.insert(node.clone()) {
+                return Err(N3RulesError::invalid_rule("Cyclic list in a rule body"));
+            }
+            elements.push(self.body_term(first)?);
+            match rest {
+                N3Term::NamedNode(rest) if *rest == rdf::NIL => {
+                    return Ok(Some(PatternTerm::List(elements)))
+                }
+                N3Term::BlankNode(rest) => node = rest,
+                _ => return Err(N3RulesError::invalid_rule("Invalid list in a rule body")),
+            }
+        }
+    }
+
+    fn head_term(
+        &mut self,
+        term: &N3Term,
+        bound: &HashSet<usize>,
+    ) -> Result<PatternTerm, N3RulesError> {
+        Ok(match term {
+            N3Term::Variable(variable) => {
+                let term = self.variable(variable.as_str());
+                if !term.variables().all(|v| bound.contains(&v)) {
+                    return Err(N3RulesError::invalid_rule(format!(
+                        "The variable {variable} of a rule head is not bound by its body"
+                    )));
+                }
+                term
+            }
+            N3Term::BlankNode(node) => {
+                self.check_not_formula(node)?;
+                let id = self.head_blank_nodes.len();
+                PatternTerm::BlankNode(*self.head_blank_nodes.entry(node.clone()).or_insert(id))
+            }
+            N3Term::NamedNode(node) => PatternTerm::Constant(node.clone().into()),
+            N3Term::Literal(literal) => PatternTerm::Constant(literal.clone().into()),
+            N3Term::Triple(triple) => PatternTerm::Constant(triple.as_ref().clone().into()),
+        })
+    }
+
+    fn variable(&mut self, name: &str) -> PatternTerm {
+        let id = self.variables.len();
+        PatternTerm::Variable(*self.variables.entry(name.into()).or_insert(id))
+    }
+
+    fn check_not_formula(&self, node: &BlankNode) -> Result<(), N3RulesError> {
+        if self.formulas.contains_key(node) {
+            return Err(N3RulesError::invalid_rule(
+                "Nested formulas are not supported",
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl PatternTerm {
+    fn variables(&self) -> Box<dyn Iterator<Item = usize> + '_> {
+        match self {
+            Self::Variable(v) => Box::new(std::iter::once(*v)),
+            Self::List(elements) => Box::new(elements.iter().flat_map(Self::variables)),
+            Self::Constant(_) | Self::BlankNode(_) => Box::new(std::iter::empty()),
+        }
+    }
+
+    /// The term value if it is bound
+    fn resolve(&self, solution: &Solution) -> Option<Term> {
+        match self {
+            Self::Constant(term) => Some(term.clone()),
+            Self::Variable(v) => solution[*v].clone(),
+            Self::BlankNode(_) | Self::List(_) => None,
+        }
+    }
+
+    /// Binds the term to the value, returns `false` if it is not compatible with the solution
+    fn bind(&self, value: Term, solution: &mut Solution) -> bool {
+        match self {
+            Self::Constant(term) => *term == value,
+            Self::Variable(v) => {
+                if let Some(current) = &solution[*v] {
+                    *current == value
+                } else {
+                    solution[*v] = Some(value);
+                    true
+                }
+            }
+            Self::BlankNode(_) | Self::List(_) => false,
+        }
+    }
+
+    fn instantiate(&self, solution: &Solution, blank_nodes: &[BlankNode]) -> Option<Term> {
+        match self {
+            Self::BlankNode(id) => Some(blank_nodes[*id].clone().into()),
+            _ => self.resolve(solution),
+        }
+    }
+}
+
+impl TriplePattern {
+    fn instantiate(&self, solution: &Solution, blank_nodes: &[BlankNode]) -> Option<Triple> {
+        Some(Triple::new(
+            Subject::try_from(self.subject.instantiate(solution, blank_nodes)?).ok()?,
+            NamedNode::try_from(self.predicate.instantiate(solution, blank_nodes)?).ok()?,
+            self.object.instantiate(solution, blank_nodes)?,
+        ))
+    }
+}
+
+/// The triples the rules are applied to
+trait TripleSource {
+    fn triples_for_pattern(
+        &self,
+        subject: Option<&Subject>,
+        predicate: Option<&NamedNode>,
+        object: Option<&Term>,
+    ) -> Result<Vec<Triple>, N3ReasoningError>;
+
+    fn contains(&self, triple: &Triple) -> Result<bool, N3ReasoningError>;
+
+    fn insert(&mut self, triples: Vec<Triple>) -> Result<(), N3ReasoningError>;
+}
+
+struct DatasetTriples<'a>(&'a mut Dataset);
+
+impl TripleSource for DatasetTriples<'_> {
+    fn triples_for_pattern(
+        &self,
+        subject: Option<&Subject>,
+        predicate: Option<&NamedNode>,
+        object: Option<&Term>,
+    ) -> Result<Vec<Triple>, N3ReasoningError> {
+        let quads: Box<dyn Iterator<Item = QuadRef<'_>>> = if let Some(subject) = subject {
+            Box::new(self.0.quads_for_subject(subject))
+        } else if let Some(object) = object {
+            Box::new(self.0.quads_for_object(object))
+        } else if let Some(predicate) = predicate {
+            Box::new(self.0.quads_for_predicate(predicate))
+        } else {
+            Box::new(self.0.iter())
+        };
+        Ok(quads
+            .filter(|quad| {
+                quad.graph_name.is_default_graph()
+                    && subject.map_or(true, |s| quad.subject == s.as_ref())
+                    && predicate.map_or(true, |p| quad.predicate == p.as_ref())
+                    && object.map_or(true, |o| quad.object == o.as_ref())
+            })
+            .map(|quad| TripleRef::from(quad).into_owned())
+            .collect())
+    }
+
+    fn contains(&self, triple: &Triple) -> Result<bool, N3ReasoningError> {
+        Ok(self
+            .0
+            .contains(triple.as_ref().in_graph(GraphNameRef::DefaultGraph)))
+    }
+
+    fn insert(&mut self, triples: Vec<Triple>) -> Result<(), N3ReasoningError> {
+        for triple in triples {
+            self.0
+                .insert(triple.as_ref().in_graph(GraphNameRef::DefaultGraph));
+        }
+        Ok(())
+    }
+}
+
+struct StoreTriples<'a>(&'a Store);
+
+impl TripleSource for StoreTriples<'_> {
+    fn triples_for_pattern(
+        &self,
+        subject: Option<&Subject>,
+        predicate: Option<&NamedNode>,
+        object: Option<&Term>,
+    ) -> Result<Vec<Triple>, N3ReasoningError> {
+        Ok(self
+            .0
+            .quads_for_pattern(
+                subject.map(Subject::as_ref),
+                predicate.map(NamedNode::as_ref),
+                object.map(Term::as_ref),
+                Some(GraphNameRef::DefaultGraph),
+            )
+            .map(|quad| quad.map(Triple::from))
+            .collect::<Result<Vec<_>, _>>()?)
+    }
+
+    fn contains(&self, triple: &Triple) -> Result<bool, N3ReasoningError> {
+        Ok(self
+            .0
+            .contains(triple.as_ref().in_graph(GraphNameRef::DefaultGraph))?)
+    }
+
+    fn insert(&mut self, triples: Vec<Triple>) -> Result<(), N3ReasoningError> {
+        Ok(self.0.extend(
+            triples
+                .into_iter()
+                .map(|triple| triple.in_graph(GraphName::DefaultGraph)),
+        )?)
+    }
+}
+
+/// The supported builtin predicates
+#[derive(Debug, Clone, Copy)]
+enum Builtin {
+    MathSum,
+    MathDifference,
+    MathProduct,
+    MathQuotient,
+    MathEqualTo,
+    MathNotEqualTo,
+    MathGreaterThan,
+    MathLessThan,
+    MathNotGreaterThan,
+    MathNotLessThan,
+    StringConcatenation,
+    StringContains,
+    StringStartsWith,
+    StringEndsWith,
+    LogEqualTo,
+    LogNotEqualTo,
+}
+
+impl Builtin {
+    fn from_iri(iri: &str) -> Option<Self> {
+        Some(match iri {
+            "http://www.w3.org/2000/10/swap/math#sum" => Self::MathSum,
+            "http://www.w3.org/2000/10/swap/math#difference" => Self::MathDifference,
+            "http://www.w3.org/2000/10/swap/math#product" => Self::MathProduct,
+            "http://www.w3.org/2000/10/swap/math#quotient" => Self::MathQuotient,
+            "http://www.w3.org/2000/10/swap/math#equalTo" => Self::MathEqualTo,
+            "http://www.w3.org/2000/10/swap/math#notEqualTo" => Self::MathNotEqualTo,
+            "http://www.w3.org/2000/10/swap/math#greaterThan" => Self::MathGreaterThan,
+            "http://www.w3.org/2000/10/swap/math#lessThan" => Self::MathLessThan,
+            "http://www.w3.org/2000/10/swap/math#notGreaterThan" => Self::MathNotGreaterThan,
+            "http://www.w3.org/2000/10/swap/math#notLessThan" => Self::MathNotLessThan,
+            "http://www.w3.org/2000/10/swap/string#concatenation" => Self::StringConcatenation,
+            "http://www.w3.org/2000/10/swap/string#contains" => Self::StringContains,
+            "http://www.w3.org/2000/10/swap/string#startsWith" => Self::StringStartsWith,
+            "http://www.w3.org/2000/10/swap/string#endsWith" => Self::StringEndsWith,
+            "http://www.w3.org/2000/10/swap/log#equalTo" => Self::LogEqualTo,
+            "http://www.w3.org/2000/10/swap/log#notEqualTo" => Self::LogNotEqualTo,
+            _ => return None,
+        })
+    }
+
+    /// If the builtin computes its object from its subject
+    fn binds_object(self) -> bool {
+        matches!(
+            self,
+            Self::MathSum
+                | Self::MathDifference
+                | Self::MathProduct
+                | Self::MathQuotient
+                | Self::StringConcatenation
+                | Self::LogEqualTo
+        )
+    }
+
+    /// Evaluates the builtin, binding its object if it is a function, and returns `false` if it does not hold
+    fn evaluate(
+        self,
+        subject: &PatternTerm,
+        object: &PatternTerm,
+        solution: &mut Solution,
+    ) -> bool {
+        if self.binds_object() {
+            let Some(value) = self.compute(subject, solution) else {
+                return false;
+            };
+            return match object.resolve(solution) {
+                // Numbers with different datatypes might be equal
+                Some(expected) if !matches!(self, Self::StringConcatenation | Self::LogEqualTo) => {
+                    Number::from_term(&expected)
+                        .zip(Number::from_term(&value))
+                        .is_some_and(|(expected, value)| expected.to_f64() == value.to_f64())
+                }
+                _ => object.bind(value, solution),
+            };
+        }
+        let (Some(subject), Some(object)) = (subject.resolve(solution), object.resolve(solution))
+        else {
+            return false;
+        };
+        match self {
+            Self::MathEqualTo
+            | Self::MathNotEqualTo
+            | Self::MathGreaterThan
+            | Self::MathLessThan
+            | Self::MathNotGreaterThan
+            | Self::MathNotLessThan => {
+                let (Some(left), Some(right)) =
+                    (Number::from_term(&subject), Number::from_term(&object))
+                else {
+                    return false;
+                };
+                let (left, right) = (left.to_f64(), right.to_f64());
+                match self {
+                    Self::MathEqualTo => left == right,
+                    Self::MathNotEqualTo => left != right,
+                    Self::MathGreaterThan => left > right,
+                    Self::MathLessThan => left < right,
+                    Self::MathNotGreaterThan => left <= right,
+                    _ => left >= right,
+                }
+            }
+            Self::StringContains | Self::StringStartsWith | Self::StringEndsWith => {
+                let (Some(left), Some(right)) = (string_value(&subject), string_value(&object))
+                else {
+                    return false;
+                };
+                match self {
+                    Self::StringContains => left.contains(right),
+                    Self::StringStartsWith => left.starts_with(right),
+                    _ => left.ends_with(right),
+                }
+            }
+            Self::LogNotEqualTo => subject != object,
+            _ => false,
+        }
+    }
+
+    /// Computes the object of a function builtin
+    fn compute(self, subject: &PatternTerm, solution: &Solution) -> Option<Term> {
+        if matches!(self, Self::LogEqualTo) {
+            return subject.resolve(solution);
+        }
+        let PatternTerm::List(arguments) = subject else {
+            return None;
+        };
+        let arguments = arguments
+            .iter()
+            .map(|argument| argument.resolve(solution))
+            .collect::<Option<Vec<_>>>()?;
+        if matches!(self, Self::StringConcatenation) {
+            return Some(
+                Literal::new_simple_literal(
+                    arguments
+                        .iter()
+                        .map(string_value)
+                        .collect::<Option<String>>()?,
+                )
+                .into(),
+            );
+        }
+        let numbers = arguments
+            .iter()
+            .map(Number::from_term)
+            .collect::<Option<Vec<_>>>()?;
+        let result = match self {
+            Self::MathSum => numbers
+                .into_iter()
+                .try_fold(Number::Integer(0), Number::checked_add)?,
+            Self::MathProduct => numbers
+                .into_iter()
+                .try_fold(Number::Integer(1), Number::checked_mul)?,
+            Self::MathDifference => {
+                let [left, right] = numbers.as_slice() else {
+                    return None;
+                };
+                left.checked_sub(*right)?
+            }
+            Self::MathQuotient => {
+                let [left, right] = numbers.as_slice() else {
+                    return None;
+                };
+                left.checked_div(*right)?
+            }
+            _ => return None,
+        };
+        Some(result.into())
+    }
+}
+
+/// A numeric value of a builtin
+#[derive(Debug, Clone, Copy)]
+enum Number {
+    Integer(i64),
+    Double(f64),
+}
+
+impl Number {
+    fn from_term(term: &Term) -> Option<Self> {
+        let Term::Literal(literal) = term else {
+            return None;
+        };
+        let datatype = literal.datatype();
+        if datatype == xsd::INTEGER || datatype == xsd::LONG || datatype == xsd::INT {
+            literal.value().parse().ok().map(Self::Integer)
+        } else if datatype == xsd::DECIMAL || datatype == xsd::DOUBLE || datatype == xsd::FLOAT {
+            literal.value().parse().ok().map(Self::Double)
+        } else {
+            None
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn to_f64(self) -> f64 {
+        match self {
+            Self::Integer(value) => value as f64,
+            Self::Double(value) => value,
+        }
+    }
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        Some(match (self, other) {
+            (Self::Integer(left), Self::Integer(right)) => Self::Integer(left.checked_add(right)?),
+            _ => Self::Double(self.to_f64() + other.to_f64()),
+        })
+    }
+
+    fn checked_sub(self, other: Self) -> Option<Self> {
+        Some(match (self, other) {
+            (Self::Integer(left), Self::Integer(right)) => Self::Integer(left.checked_sub(right)?),
+            _ => Self::Double(self.to_f64() - other.to_f64()),
+        })
+    }
+
+    fn checked_mul(self, other: Self) -> Option<Self> {
+        Some(match (self, other) {
+            (Self::Integer(left), Self::Integer(right)) => Self::Integer(left.checked_mul(right)?),
+            _ => Self::Double(self.to_f64() * other.to_f64()),
+        })
+    }
+
+    fn checked_div(self, other: Self) -> Option<Self> {
+        if let (Self::Integer(left), Self::Integer(right)) = (self, other) {
+            if right != 0 && left.checked_rem(right)? == 0 {
+                return Some(Self::Integer(left.checked_div(right)?));
+            }
+        }
+        let right = other.to_f64();
+        (right != 0.).then(|| Self::Double(self.to_f64() / right))
+    }
+}
+
+impl From<Number> for Term {
+    fn from(number: Number) -> Self {
+        match number {
+            Number::Integer(value) => Literal::from(value).into(),
+            Number::Double(value) => Literal::from(value).into(),
+        }
+    }
+}
+
+/// The string value of a literal or of an IRI
+fn string_value(term: &Term) -> Option<&str> {
+    match term {
+        Term::Literal(literal) => Some(literal.value()),
+        Term::NamedNode(node) => Some(node.as_str()),
+        _ => None,
+    }
+}
@@ -3,9 +3,12 @@
 //! The root type for SPARQL queries is [`Query`] and the root type for updates is [`Update`].
 
 use crate::model::*;
+use crate::sparql::{EvaluationError, QuerySolution};
+use spargebra::algebra::GraphPattern;
+use spargebra::term::GroundTerm;
 use spargebra::GraphUpdateOperation;
-use std::fmt;
 use std::str::FromStr;
+use std::{fmt, mem};
 
 /// A parsed [SPARQL query](https://www.w3.org/TR/sparql11-query/).
 ///
@@ -56,6 +59,149 @@ impl Query {
     pub fn dataset_mut(&mut self) -> &mut QueryDataset {
         &mut self.dataset
     }
+
+    /// Looks for likely mistakes in the query like non-well-designed `OPTIONAL`s, cartesian products or unbound selected variables.
+    ///
+    /// ```
+    /// use oxigraph::sparql::{Query, QueryWarning};
+    ///
+    /// let query = Query::parse(
+    ///     "SELECT ?s ?name WHERE { ?s a ?t OPTIONAL { ?t ?p ?name } ?s ?p ?o }",
+    ///     None,
+    /// )?;
+    /// assert!(matches!(
+    ///     query.warnings().as_slice(),
+    ///     [QueryWarning::NonWellDesignedOptional { variable }] if variable.as_str() == "p"
+    /// ));
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn warnings(&self) -> Vec<spargebra::QueryWarning> {
+        self.inner.warnings()
+    }
+
+    /// Returns the [`warnings`](Self::warnings) of the query and also the findings of stricter checks like the variables only used once.
+    ///
+    /// ```
+    /// use oxigraph::sparql::{Query, QueryWarning};
+    ///
+    /// let query = Query::parse("SELECT ?s WHERE { ?s ?p ?o }", None)?;
+    /// assert!(query.warnings().is_empty());
+    /// assert!(matches!(
+    ///     query.lint().as_slice(),
+    ///     [QueryWarning::UnusedVariable { variable: o }, QueryWarning::UnusedVariable { variable: p }] if o.as_str() == "o" && p.as_str() == "p"
+    /// ));
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn lint(&self) -> Vec<spargebra::QueryWarning> {
+        self.inner.lint()
+    }
+
+    /// Joins the query with a sequence of initial solutions, like a trailing `VALUES` clause would do.
+    ///
+    /// The solutions are joined with the query pattern before the projection and the solution modifiers (`ORDER BY`, `DISTINCT`, `LIMIT`...).
+    /// It allows to evaluate the same query against a lot of input bindings at once (e.g. to look up thousands of IRIs)
+    /// without building a `VALUES` clause by string concatenation.
+    /// If the solution sequence is empty, the query returns no solution.
+    ///
+    /// Blank nodes are not allowed in the initial bindings, an [`EvaluationError::BlankNodeInInitialBindings`] error is returned if there is one.
+    ///
+    /// ```
+    /// use oxigraph::model::{GraphName, Literal, NamedNode, Quad};
+    /// use oxigraph::sparql::{Query, QueryResults, QuerySolution, Variable};
+    /// use oxigraph::store::Store;
+    ///
+    /// let store = Store::new()?;
+    /// let name = NamedNode::new("http://schema.org/name")?;
+    /// for i in 0..10 {
+    ///     store.insert(&Quad::new(
+    ///         NamedNode::new(format!("http://example.com/{i}"))?,
+    ///         name.clone(),
+    ///         Literal::from(format!("name {i}")),
+    ///         GraphName::DefaultGraph,
+    ///     ))?;
+    /// }
+    ///
+    /// let s = Variable::new("s")?;
+    /// let query = Query::parse(
+    ///     "SELECT ?name WHERE { ?s <http://schema.org/name> ?name } ORDER BY ?name",
+    ///     None,
+    /// )?
+    /// .with_initial_bindings([1, 3].into_iter().map(|i| {
+    ///     QuerySolution::from((
+    ///         vec![s.clone()],
+    ///         vec![Some(NamedNode::new_unchecked(format!("http://example.com/{i}")).into())],
+    ///     ))
+    /// }))?;
+    /// if let QueryResults::Solutions(solutions) = store.query(query)? {
+    ///     assert_eq!(
+    ///         solutions
+    ///             .map(|s| Ok(s?.get("name").cloned()))
+    ///             .collect::<Result<Vec<_>, oxigraph::sparql::EvaluationError>>()?,
+    ///         [
+    ///             Some(Literal::from("name 1").into()),
+    ///             Some(Literal::from("name 3").into())
+    ///         ]
+    ///     );
+    /// }
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn with_initial_bindings(
+        mut self,
+        bindings: impl IntoIterator<Item = QuerySolution>,
+    ) -> Result<Self, EvaluationError> {
+        let mut variables = Vec::<Variable>::new();
+        let mut rows = Vec::new();
+        for solution in bindings {
+            let mut row = vec![None; variables.len()];
+            for (variable, value) in &solution {
+                let position = if let Some(position) = variables.iter().position(|v| v == variable)
+                {
+                    position
+                } else {
+                    variables.push(variable.clone());
+                    row.push(None);
+                    variables.len() - 1
+                };
+                row[position] =
+                    Some(GroundTerm::try_from(value.clone()).map_err(|()| {
+                        EvaluationError::BlankNodeInInitialBindings(variable.clone())
+                    })?);
+            }
+            rows.push(row);
+        }
+        for row in &mut rows {
+            row.resize(variables.len(), None);
+        }
+        let (spargebra::Query::Select { pattern, .. }
+        | spargebra::Query::Construct { pattern, .. }
+        | spargebra::Query::Describe { pattern, .. }
+        | spargebra::Query::Ask { pattern, .. }) = &mut self.inner;
+        join_before_modifiers(
+            pattern,
+            GraphPattern::Values {
+                variables,
+                bindings: rows,
+            },
+        );
+        Ok(self)
+    }
+}
+
+/// Joins `values` with `pattern` below its projection and solution modifiers
+fn join_before_modifiers(pattern: &mut GraphPattern, values: GraphPattern) {
+    match pattern {
+        GraphPattern::Slice { inner, .. }
+        | GraphPattern::Distinct { inner }
+        | GraphPattern::Reduced { inner }
+        | GraphPattern::Project { inner, .. }
+        | GraphPattern::OrderBy { inner, .. } => join_before_modifiers(inner, values),
+        _ => {
+            *pattern = GraphPattern::Join {
+                left: Box::new(mem::take(pattern)),
+                right: Box::new(values),
+            }
+        }
+    }
 }
 
 impl fmt::Display for Query {
@@ -0,0 +1,475 @@
+use crate::model::{Term, Triple, Variable};
+use crate::sparql::algebra::Query;
+use crate::sparql::model::QueryResults;
+use oxsdatatypes::{DateTime, DayTimeDuration};
+use sparesults::QuerySolution;
+use spareval::{
+    QuerySolutionIter as EvalQuerySolutionIter, QueryTripleIter as EvalQueryTripleIter,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+/// A cache of query results, set with [`QueryOptions::with_results_cache`](super::QueryOptions::with_results_cache).
+///
+/// The results are indexed by query, including its dataset specification and base IRI.
+/// They are only returned as long as the store has not been modified by a transaction or a bulk load since their computation.
+/// Only the results of fully consumed iterators are added to the cache.
+/// When the cache is full, the least recently used results are evicted.
+/// Clones of this struct share the same cache.
+///
+/// A cache must not be shared between different stores or between evaluations with different options, like different custom functions.
+///
+/// <div class="warning">The results of the queries calling non-deterministic functions like <code>NOW()</code> or <code>RAND()</code> or remote <code>SERVICE</code>s are cached too.</div>
+#[derive(Clone)]
+pub struct QueryResultsCache {
+    inner: Arc<Mutex<QueryResultsCacheContent>>,
+}
+
+struct QueryResultsCacheContent {
+    capacity: usize,
+    max_results: usize,
+    ttl: Option<DayTimeDuration>,
+    /// The store version the cached results have been computed on
+    version: u64,
+    entries: HashMap<Query, CacheEntry>,
+    clock: u64,
+}
+
+struct CacheEntry {
+    results: CachedResults,
+    inserted_at: DateTime,
+    last_use: u64,
+}
+
+#[derive(Clone)]
+enum CachedResults {
+    Solutions {
+        variables: Arc<[Variable]>,
+        /// The solution values, in the same order as `variables`
+        solutions: Arc<[Arc<[Option<Term>]>]>,
+    },
+    Boolean(bool),
+    Graph(Arc<[Triple]>),
+}
+
+impl QueryResultsCache {
+    /// Builds a cache keeping the results of at most `capacity` queries.
+    ///
+    /// By default, only the results with at most 10 000 solutions or triples are cached and they do not expire.
+    #[inline]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(QueryResultsCacheContent {
+                capacity,
+                max_results: 10_000,
+                ttl: None,
+                version: 0,
+                entries: HashMap::new(),
+                clock: 0,
+            })),
+        }
+    }
+
+    /// Only caches the results with at most `max_results` solutions or triples.
+    #[inline]
+    #[must_use]
+    pub fn with_max_results(self, max_results: usize) -> Self {
+        self.content().max_results = max_results;
+        self
+    }
+
+    /// Sets the duration after which cached results are not returned anymore even if the store has not been modified.
+    #[inline]
+    #[must_use]
+    pub fn with_ttl(self, ttl: Duration) -> Self {
+        // A time to live too large to be represented is the same as no time to live
+        self.content().ttl = DayTimeDuration::try_from(ttl).ok();
+        self
+    }
+
+    /// The maximal number of query results kept in the cache.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.content().capacity
+    }
+
+    /// The number of query results currently in the cache.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.content().entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.content().entries.is_empty()
+    }
+
+    /// Removes all the results from the cache.
+    #[inline]
+    pub fn clear(&self) {
+        self.content().entries.clear();
+    }
+
+    /// Returns the cached results of the query if they have been computed on the given store version.
+    pub(crate) fn get(&self, query: &Query, version: u64) -> Option<QueryResults> {
+        let mut content = self.content();
+        if !content.sync_version(version) {
+            return None;
+        }
+        let ttl = content.ttl;
+        content.clock += 1;
+        let clock = content.clock;
+        let entry = content.entries.get_mut(query)?;
+        if let Some(ttl) = ttl {
+            if DateTime::now()
+                .checked_sub(entry.inserted_at)
+                .map_or(true, |age| age > ttl)
+            {
+                content.entries.remove(query);
+                return None;
+            }
+        }
+        entry.last_use = clock;
+        Some(match entry.results.clone() {
+            CachedResults::Solutions {
+                variables,
+                solutions,
+            } => QueryResults::Solutions(
+                EvalQuerySolutionIter::new(
+                    Arc::clone(&variables),
+                    (0..solutions.len()).filter_map(move |i| {
+                        Some(Ok(QuerySolution::from((
+                            Arc::clone(&variables),
+                            solutions.get(i)?.to_vec(),
+                        ))))
+                    }),
+                )
+                .into(),
+            ),
+            CachedResults::Boolean(value) => QueryResults::Boolean(value),
+            CachedResults::Graph(triples) => QueryResults::Graph(
+                EvalQueryTripleIter::new(
+                    (0..triples.len()).filter_map(move |i| Some(Ok(triples.get(i)?.clone()))),
+                )
+                .into(),
+            ),
+        })
+    }
+
+    /// Wraps the results to add them to the cache once they have all been read.
+    pub(crate) fn record(&self, query: Query, version: u64, results: QueryResults) -> QueryResults {
+        let max_results = self.content().max_results;
+        match results {
+            QueryResults::Solutions(solutions) => {
+                let solutions = EvalQuerySolutionIter::from(solutions);
+                let variables = Arc::<[Variable]>::from(solutions.variables());
+                let cache = self.clone();
+                let cached_variables = Arc::clone(&variables);
+                QueryResults::Solutions(
+                    EvalQuerySolutionIter::new(
+                        variables,
+                        RecordingIterator::new(
+                            solutions,
+                            max_results,
+                            move |solutions: Vec<Arc<[Option<Term>]>>| {
+                                cache.insert(
+                                    query,
+                                    version,
+                                    CachedResults::Solutions {
+                                        variables: cached_variables,
+                                        solutions: solutions.into(),
+                                    },
+                                )
+                            },
+                        ),
+                    )
+                    .into(),
+                )
+            }
+            QueryResults::Boolean(value) => {
+                self.insert(query, version, CachedResults::Boolean(value));
+                QueryResults::Boolean(value)
+            }
+            QueryResults::Graph(triples) => {
+                let cache = self.clone();
+                QueryResults::Graph(
+                    EvalQueryTripleIter::new(RecordingIterator::new(
+                        EvalQueryTripleIter::from(triples),
+                        max_results,
+                        move |triples: Vec<Triple>| {
+                            cache.insert(query, version, CachedResults::Graph(triples.into()))
+                        },
+                    ))
+                    .into(),
+                )
+            }
+        }
+    }
+
+    fn insert(&self, query: Query, version: u64, results: CachedResults) {
+        let mut content = self.content();
+        if content.capacity == 0 || !content.sync_version(version) {
+            return;
+        }
+        if content.entries.len() >= content.capacity && !content.entries.contains_key(&query) {
+            if let Some(least_recently_used) = content
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_use)
+                .map(|(query, _)| query.clone())
+            {
+                content.entries.remove(&least_recently_used);
+            }
+        }
+        content.clock += 1;
+        let last_use = content.clock;
+        content.entries.insert(
+            query,
+            CacheEntry {
+                results,
+                inserted_at: DateTime::now(),
+                last_use,
+            },
+        );
+    }
+
+    fn content(&self) -> MutexGuard<'_, QueryResultsCacheContent> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl QueryResultsCacheContent {
+    /// Drops the cached results if the store has been modified since their computation.
+    ///
+    /// Returns `false` if the given version is older than the cached results one.
+    fn sync_version(&mut self, version: u64) -> bool {
+        if version > self.version {
+            self.entries.clear();
+            self.version = version;
+        }
+        version == self.version
+    }
+}
+
+/// Records the results returned by an iterator and calls `on_end` with them once the iterator is exhausted.
+///
+/// Nothing is recorded if the iterator returns an error or more than `max_results` results.
+struct RecordingIterator<I, R, F> {
+    inner: I,
+    max_results: usize,
+    recorded: Option<Vec<R>>,
+    on_end: Option<F>,
+}
+
+impl<I, R, F> RecordingIterator<I, R, F> {
+    fn new(inner: I, max_results: usize, on_end: F) -> Self {
+        Self {
+            inner,
+            max_results,
+            recorded: Some(Vec::new()),
+            on_end: Some(on_end),
+        }
+    }
+}
+
+impl<E, I: Iterator<Item = Result<T, E>>, T: Recordable, F: FnOnce(Vec<T::Record>)> Iterator
+    for RecordingIterator<I, T::Record, F>
+{
+    type Item = Result<T, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Some(result) = self.inner.next() else {
+            if let (Some(recorded), Some(on_end)) = (self.recorded.take(), self.on_end.take()) {
+                on_end(recorded);
+            }
+            return None;
+        };
+        match &result {
+            Ok(value) => {
+                if let Some(recorded) = &mut self.recorded {
+                    if recorded.len() < self.max_results {
+                        recorded.push(value.record());
+                    } else {
+                        self.recorded = None;
+                    }
+                }
+            }
+            Err(_) => self.recorded = None,
+        }
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// A result that can be kept in the cache.
+trait Recordable {
+    type Record;
+
+    fn record(&self) -> Self::Record;
+}
+
+impl Recordable for QuerySolution {
+    type Record = Arc<[Option<Term>]>;
+
+    /// The variables are shared by all the solutions, so only the values are kept
+    fn record(&self) -> Self::Record {
+        self.values().into()
+    }
+}
+
+impl Recordable for Triple {
+    type Record = Self;
+
+    fn record(&self) -> Self {
+        self.clone()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic, clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+    use crate::model::{GraphNameRef, NamedNodeRef, QuadRef};
+    use crate::sparql::{EvaluationError, QueryOptions};
+    use crate::store::Store;
+
+    fn solutions(
+        store: &Store,
+        cache: &QueryResultsCache,
+        query: &str,
+    ) -> Result<Vec<Vec<Option<Term>>>, EvaluationError> {
+        let QueryResults::Solutions(solutions) = store.query_opt(
+            query,
+            QueryOptions::default().with_results_cache(cache.clone()),
+        )?
+        else {
+            panic!("solutions expected")
+        };
+        solutions
+            .map(|solution| Ok(solution?.values().to_vec()))
+            .collect()
+    }
+
+    fn insert(store: &Store, id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let node = NamedNodeRef::new(id)?;
+        store.insert(QuadRef::new(node, node, node, GraphNameRef::DefaultGraph))?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_solutions() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Store::new()?;
+        insert(&store, "http://example.com/a")?;
+        let cache = QueryResultsCache::new(10);
+        let query = "SELECT ?s ?o WHERE { ?s ?p ?s OPTIONAL { ?s ?p \"foo\" } }";
+        let computed = solutions(&store, &cache, query)?;
+        assert_eq!(cache.len(), 1);
+        let cached = solutions(&store, &cache, query)?;
+        assert_eq!(cached, computed);
+        assert_eq!(
+            cached,
+            [vec![
+                Some(NamedNodeRef::new("http://example.com/a")?.into()),
+                None
+            ]]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalidation() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Store::new()?;
+        let cache = QueryResultsCache::new(10);
+        let query = "SELECT * WHERE { ?s ?p ?o }";
+        assert_eq!(solutions(&store, &cache, query)?.len(), 0);
+        insert(&store, "http://example.com/a")?;
+        assert_eq!(solutions(&store, &cache, query)?.len(), 1);
+        insert(&store, "http://example.com/b")?;
+        assert_eq!(solutions(&store, &cache, query)?.len(), 2);
+        assert_eq!(cache.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_results() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Store::new()?;
+        insert(&store, "http://example.com/a")?;
+        insert(&store, "http://example.com/b")?;
+        let cache = QueryResultsCache::new(10).with_max_results(1);
+        assert_eq!(
+            solutions(&store, &cache, "SELECT * WHERE { ?s ?p ?o }")?.len(),
+            2
+        );
+        assert!(cache.is_empty());
+        assert_eq!(
+            solutions(&store, &cache, "SELECT * WHERE { ?s ?p ?o } LIMIT 1")?.len(),
+            1
+        );
+        assert_eq!(cache.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_partial_consumption() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Store::new()?;
+        insert(&store, "http://example.com/a")?;
+        insert(&store, "http://example.com/b")?;
+        let cache = QueryResultsCache::new(10);
+        let QueryResults::Solutions(mut solutions) = store.query_opt(
+            "SELECT * WHERE { ?s ?p ?o }",
+            QueryOptions::default().with_results_cache(cache.clone()),
+        )?
+        else {
+            panic!("solutions expected")
+        };
+        assert!(solutions.next().is_some());
+        drop(solutions);
+        assert!(cache.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_eviction() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Store::new()?;
+        let cache = QueryResultsCache::new(2);
+        solutions(&store, &cache, "SELECT * WHERE { ?s ?p ?o }")?;
+        solutions(&store, &cache, "SELECT ?s WHERE { ?s ?p ?o }")?;
+        solutions(&store, &cache, "SELECT * WHERE { ?s ?p ?o }")?;
+        solutions(&store, &cache, "SELECT ?o WHERE { ?s ?p ?o }")?;
+        assert_eq!(cache.len(), 2);
+        // The least recently used query has been evicted
+        let content = cache.content();
+        assert!(content
+            .entries
+            .keys()
+            .all(|query| !query.to_string().contains("SELECT ?s ")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_boolean_and_graph() -> Result<(), Box<dyn std::error::Error>> {
+        let store = Store::new()?;
+        insert(&store, "http://example.com/a")?;
+        let cache = QueryResultsCache::new(10);
+        let options = || QueryOptions::default().with_results_cache(cache.clone());
+        for _ in 0..2 {
+            let QueryResults::Boolean(result) = store.query_opt("ASK { ?s ?p ?o }", options())?
+            else {
+                panic!("boolean expected")
+            };
+            assert!(result);
+            let QueryResults::Graph(triples) =
+                store.query_opt("CONSTRUCT WHERE { ?s ?p ?o }", options())?
+            else {
+                panic!("graph expected")
+            };
+            assert_eq!(triples.collect::<Result<Vec<_>, _>>()?.len(), 1);
+        }
+        assert_eq!(cache.len(), 2);
+        Ok(())
+    }
+}
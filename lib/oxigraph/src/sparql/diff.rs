@@ -0,0 +1,565 @@
+use crate::model::dataset::CanonicalizationAlgorithm;
+use crate::model::{BlankNode, Graph, Subject, Term, TermRef, Triple};
+use crate::sparql::{EvaluationError, QueryResults, QuerySolution, Variable};
+use std::collections::HashMap;
+use std::fmt;
+
+/// The differences between two [`QueryResults`].
+///
+/// Blank nodes are compared up to a renaming: a blank node of the left results is considered equal to a blank node of the right results
+/// if it is always used in the same positions.
+/// The order of the solutions is ignored.
+///
+/// Usage example:
+/// ```
+/// use oxigraph::sparql::QueryResultsDiff;
+/// use oxigraph::store::Store;
+///
+/// let store = Store::new()?;
+/// let diff = QueryResultsDiff::new(
+///     store.query("SELECT ?s ?o WHERE { VALUES (?s ?o) { (\"a\" \"1\") (\"b\" \"2\") } }")?,
+///     store.query("SELECT ?s ?o WHERE { VALUES (?s ?o) { (\"a\" \"1\") (\"b\" \"3\") } }")?,
+/// )?;
+/// assert!(!diff.is_empty());
+/// assert_eq!(diff.to_string(), "~ ?s=\"b\" ?o=\"2\" => ?s=\"b\" ?o=\"3\"\n");
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[derive(Debug)]
+pub enum QueryResultsDiff {
+    /// The differences between two solution sequences.
+    Solutions(SolutionsDiff),
+    /// The results of two `ASK` queries.
+    Boolean { left: bool, right: bool },
+    /// The differences between two graphs.
+    Graph(GraphDiff),
+    /// The results are not of the same kind (e.g. a boolean and some solutions).
+    DifferentKinds,
+}
+
+impl QueryResultsDiff {
+    /// Computes the differences between two query results.
+    pub fn new(left: QueryResults, right: QueryResults) -> Result<Self, EvaluationError> {
+        Ok(match (left, right) {
+            (QueryResults::Solutions(left), QueryResults::Solutions(right)) => {
+                let left_variables = left.variables().to_vec();
+                let right_variables = right.variables().to_vec();
+                Self::Solutions(SolutionsDiff::new(
+                    &left_variables,
+                    left.collect::<Result<Vec<_>, _>>()?,
+                    &right_variables,
+                    right.collect::<Result<Vec<_>, _>>()?,
+                ))
+            }
+            (QueryResults::Boolean(left), QueryResults::Boolean(right)) => {
+                Self::Boolean { left, right }
+            }
+            (QueryResults::Graph(left), QueryResults::Graph(right)) => Self::Graph(GraphDiff::new(
+                left.collect::<Result<_, _>>()?,
+                right.collect::<Result<_, _>>()?,
+            )),
+            _ => Self::DifferentKinds,
+        })
+    }
+
+    /// Returns `true` if the two results are equal.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Self::Solutions(diff) => diff.is_empty(),
+            Self::Boolean { left, right } => left == right,
+            Self::Graph(diff) => diff.is_empty(),
+            Self::DifferentKinds => false,
+        }
+    }
+}
+
+impl fmt::Display for QueryResultsDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Solutions(diff) => write!(f, "{diff}"),
+            Self::Boolean { left, right } => {
+                if left != right {
+                    writeln!(f, "- {left}")?;
+                    writeln!(f, "+ {right}")?;
+                }
+                Ok(())
+            }
+            Self::Graph(diff) => write!(f, "{diff}"),
+            Self::DifferentKinds => writeln!(f, "The results are not of the same kind"),
+        }
+    }
+}
+
+/// The differences between two solution sequences.
+///
+/// See [`QueryResultsDiff`].
+#[derive(Debug, Default)]
+pub struct SolutionsDiff {
+    missing_variables: Vec<Variable>,
+    extra_variables: Vec<Variable>,
+    missing: Vec<QuerySolution>,
+    extra: Vec<QuerySolution>,
+    changed: Vec<(QuerySolution, QuerySolution)>,
+}
+
+impl SolutionsDiff {
+    fn new(
+        left_variables: &[Variable],
+        left: Vec<QuerySolution>,
+        right_variables: &[Variable],
+        right: Vec<QuerySolution>,
+    ) -> Self {
+        let missing_variables = left_variables
+            .iter()
+            .filter(|v| !right_variables.contains(v))
+            .cloned()
+            .collect();
+        let extra_variables = right_variables
+            .iter()
+            .filter(|v| !left_variables.contains(v))
+            .cloned()
+            .collect();
+
+        // We first match the solutions without blank nodes using hashing
+        let mut right = right.into_iter().map(Some).collect::<Vec<_>>();
+        let mut right_without_bnodes = HashMap::<_, Vec<_>>::new();
+        for (i, solution) in right.iter().enumerate() {
+            if let Some(solution) = solution {
+                if !has_blank_nodes(solution) {
+                    right_without_bnodes
+                        .entry(solution_key(solution))
+                        .or_default()
+                        .push(i);
+                }
+            }
+        }
+        let mut left_with_bnodes = Vec::new();
+        let mut missing = Vec::new();
+        for solution in left {
+            if has_blank_nodes(&solution) {
+                left_with_bnodes.push(solution);
+            } else if let Some(i) = right_without_bnodes
+                .get_mut(&solution_key(&solution))
+                .and_then(Vec::pop)
+            {
+                right[i] = None;
+            } else {
+                missing.push(solution);
+            }
+        }
+
+        // Then we match greedily the solutions with blank nodes while building a blank node mapping
+        let mut mapping = BlankNodeMapping::default();
+        for solution in left_with_bnodes {
+            let matched = right.iter_mut().find(|candidate| {
+                candidate
+                    .as_ref()
+                    .is_some_and(|candidate| mapping.try_match_solutions(&solution, candidate))
+            });
+            if let Some(matched) = matched {
+                *matched = None;
+            } else {
+                missing.push(solution);
+            }
+        }
+
+        // We pair the remaining solutions that share some bindings
+        let mut changed = Vec::new();
+        let mut unpaired = Vec::new();
+        for solution in missing {
+            let best = right
+                .iter()
+                .enumerate()
+                .filter_map(|(i, candidate)| {
+                    let count = shared_bindings_count(&solution, candidate.as_ref()?);
+                    (count > 0).then_some((count, i))
+                })
+                .max_by_key(|(count, i)| (*count, usize::MAX - i));
+            if let Some((_, i)) = best {
+                if let Some(candidate) = right[i].take() {
+                    changed.push((solution, candidate));
+                    continue;
+                }
+            }
+            unpaired.push(solution);
+        }
+        Self {
+            missing_variables,
+            extra_variables,
+            missing: unpaired,
+            extra: right.into_iter().flatten().collect(),
+            changed,
+        }
+    }
+
+    /// The variables only present in the left solutions.
+    #[inline]
+    pub fn missing_variables(&self) -> &[Variable] {
+        &self.missing_variables
+    }
+
+    /// The variables only present in the right solutions.
+    #[inline]
+    pub fn extra_variables(&self) -> &[Variable] {
+        &self.extra_variables
+    }
+
+    /// The solutions only present in the left solutions.
+    #[inline]
+    pub fn missing(&self) -> &[QuerySolution] {
+        &self.missing
+    }
+
+    /// The solutions only present in the right solutions.
+    #[inline]
+    pub fn extra(&self) -> &[QuerySolution] {
+        &self.extra
+    }
+
+    /// The pairs of left and right solutions that share some bindings but are not equal.
+    #[inline]
+    pub fn changed(&self) -> &[(QuerySolution, QuerySolution)] {
+        &self.changed
+    }
+
+    /// Returns `true` if the two solution sequences are equal.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.missing_variables.is_empty()
+            && self.extra_variables.is_empty()
+            && self.missing.is_empty()
+            && self.extra.is_empty()
+            && self.changed.is_empty()
+    }
+}
+
+impl fmt::Display for SolutionsDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for variable in &self.missing_variables {
+            writeln!(f, "- variable {variable}")?;
+        }
+        for variable in &self.extra_variables {
+            writeln!(f, "+ variable {variable}")?;
+        }
+        for solution in &self.missing {
+            writeln!(f, "- {}", DisplaySolution(solution))?;
+        }
+        for solution in &self.extra {
+            writeln!(f, "+ {}", DisplaySolution(solution))?;
+        }
+        for (left, right) in &self.changed {
+            writeln!(
+                f,
+                "~ {} => {}",
+                DisplaySolution(left),
+                DisplaySolution(right)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+struct DisplaySolution<'a>(&'a QuerySolution);
+
+impl fmt::Display for DisplaySolution<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (variable, value)) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{variable}={value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The differences between two graphs.
+///
+/// The blank nodes are canonicalized before computing the differences.
+/// Hence, if the two graphs are not isomorphic, the triples with blank nodes might be reported even if they are similar.
+///
+/// See [`QueryResultsDiff`].
+#[derive(Debug, Clone, Default)]
+pub struct GraphDiff {
+    missing: Vec<Triple>,
+    extra: Vec<Triple>,
+}
+
+impl GraphDiff {
+    fn new(mut left: Graph, mut right: Graph) -> Self {
+        left.canonicalize(CanonicalizationAlgorithm::Unstable);
+        right.canonicalize(CanonicalizationAlgorithm::Unstable);
+        Self {
+            missing: left
+                .iter()
+                .filter(|t| !right.contains(*t))
+                .map(Into::into)
+                .collect(),
+            extra: right
+                .iter()
+                .filter(|t| !left.contains(*t))
+                .map(Into::into)
+                .collect(),
+        }
+    }
+
+    /// The triples only present in the left graph.
+    #[inline]
+    pub fn missing(&self) -> &[Triple] {
+        &self.missing
+    }
+
+    /// The triples only present in the right graph.
+    #[inline]
+    pub fn extra(&self) -> &[Triple] {
+        &self.extra
+    }
+
+    /// Returns `true` if the two graphs are equal.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty()
+    }
+}
+
+impl fmt::Display for GraphDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for triple in &self.missing {
+            writeln!(f, "- {triple} .")?;
+        }
+        for triple in &self.extra {
+            writeln!(f, "+ {triple} .")?;
+        }
+        Ok(())
+    }
+}
+
+fn has_blank_nodes(solution: &QuerySolution) -> bool {
+    solution
+        .values()
+        .iter()
+        .flatten()
+        .any(is_or_contains_blank_node)
+}
+
+fn is_or_contains_blank_node(term: &Term) -> bool {
+    match term {
+        Term::BlankNode(_) => true,
+        Term::Triple(triple) => triple_contains_blank_node(triple),
+        Term::NamedNode(_) | Term::Literal(_) => false,
+    }
+}
+
+fn triple_contains_blank_node(triple: &Triple) -> bool {
+    let subject_contains_blank_node = match &triple.subject {
+        Subject::BlankNode(_) => true,
+        Subject::Triple(triple) => triple_contains_blank_node(triple),
+        Subject::NamedNode(_) => false,
+    };
+    subject_contains_blank_node || is_or_contains_blank_node(&triple.object)
+}
+
+/// Key used to compare solutions regardless of the variables order
+fn solution_key(solution: &QuerySolution) -> Vec<(Variable, Term)> {
+    let mut key = solution
+        .iter()
+        .map(|(v, t)| (v.clone(), t.clone()))
+        .collect::<Vec<_>>();
+    key.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+    key
+}
+
+fn shared_bindings_count(left: &QuerySolution, right: &QuerySolution) -> usize {
+    left.iter()
+        .filter(|(variable, value)| right.get(*variable) == Some(*value))
+        .count()
+}
+
+/// A bijection between the left and right blank nodes
+#[derive(Default)]
+struct BlankNodeMapping {
+    left_to_right: HashMap<BlankNode, BlankNode>,
+    right_to_left: HashMap<BlankNode, BlankNode>,
+}
+
+impl BlankNodeMapping {
+    /// Checks if the two solutions are equal modulo the mapping and extends the mapping if needed
+    fn try_match_solutions(&mut self, left: &QuerySolution, right: &QuerySolution) -> bool {
+        if left.iter().count() != right.iter().count() {
+            return false;
+        }
+        let mut new_mappings = Vec::new();
+        for (variable, left_value) in left.iter() {
+            let Some(right_value) = right.get(variable) else {
+                return false;
+            };
+            if !self.try_match_terms(left_value.as_ref(), right_value.as_ref(), &mut new_mappings) {
+                return false;
+            }
+        }
+        for (left, right) in new_mappings {
+            self.left_to_right.insert(left.clone(), right.clone());
+            self.right_to_left.insert(right, left);
+        }
+        true
+    }
+
+    fn try_match_terms(
+        &self,
+        left: TermRef<'_>,
+        right: TermRef<'_>,
+        new_mappings: &mut Vec<(BlankNode, BlankNode)>,
+    ) -> bool {
+        match (left, right) {
+            (TermRef::BlankNode(left), TermRef::BlankNode(right)) => {
+                if let Some(mapped) = self.left_to_right.get(&left.into_owned()) {
+                    return mapped.as_ref() == right;
+                }
+                if self.right_to_left.contains_key(&right.into_owned()) {
+                    return false;
+                }
+                if let Some((_, mapped)) = new_mappings.iter().find(|(l, _)| l.as_ref() == left) {
+                    return mapped.as_ref() == right;
+                }
+                if new_mappings.iter().any(|(_, r)| r.as_ref() == right) {
+                    return false;
+                }
+                new_mappings.push((left.into_owned(), right.into_owned()));
+                true
+            }
+            (TermRef::Triple(left), TermRef::Triple(right)) => {
+                self.try_match_terms(
+                    left.subject.as_ref().into(),
+                    right.subject.as_ref().into(),
+                    new_mappings,
+                ) && left.predicate == right.predicate
+                    && self.try_match_terms(
+                        left.object.as_ref(),
+                        right.object.as_ref(),
+                        new_mappings,
+                    )
+            }
+            (left, right) => left == right,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+    use crate::store::Store;
+
+    fn diff(left: &str, right: &str) -> Result<QueryResultsDiff, EvaluationError> {
+        let store = Store::new()?;
+        QueryResultsDiff::new(store.query(left)?, store.query(right)?)
+    }
+
+    #[test]
+    fn test_equal_solutions() -> Result<(), EvaluationError> {
+        let diff = diff(
+            "SELECT ?a ?b WHERE { VALUES (?a ?b) { (\"1\" \"2\") (\"3\" UNDEF) } }",
+            "SELECT ?b ?a WHERE { VALUES (?a ?b) { (\"3\" UNDEF) (\"1\" \"2\") } }",
+        )?;
+        assert!(diff.is_empty(), "{diff}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_and_extra_solutions() -> Result<(), EvaluationError> {
+        let diff = diff(
+            "SELECT ?a ?b WHERE { VALUES (?a ?b) { (\"1\" \"2\") (\"3\" \"4\") (\"5\" \"6\") } }",
+            "SELECT ?a ?b WHERE { VALUES (?a ?b) { (\"1\" \"2\") (\"3\" \"5\") (\"7\" \"8\") } }",
+        )?;
+        assert_eq!(
+            diff.to_string(),
+            "- ?a=\"5\" ?b=\"6\"\n+ ?a=\"7\" ?b=\"8\"\n~ ?a=\"3\" ?b=\"4\" => ?a=\"3\" ?b=\"5\"\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_duplicated_solutions() -> Result<(), EvaluationError> {
+        let diff = diff(
+            "SELECT ?a WHERE { VALUES ?a { \"1\" \"1\" } }",
+            "SELECT ?a WHERE { VALUES ?a { \"1\" } }",
+        )?;
+        assert_eq!(diff.to_string(), "- ?a=\"1\"\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_variables() -> Result<(), EvaluationError> {
+        let diff = diff("SELECT ?a WHERE {}", "SELECT ?b WHERE {}")?;
+        assert_eq!(diff.to_string(), "- variable ?a\n+ variable ?b\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_blank_nodes() -> Result<(), EvaluationError> {
+        assert!(diff(
+            "SELECT ?a ?b WHERE { VALUES ?a { 1 2 } BIND(BNODE() AS ?b) }",
+            "SELECT ?a ?b WHERE { VALUES ?a { 2 1 } BIND(BNODE() AS ?b) }",
+        )?
+        .is_empty());
+        assert!(!diff(
+            "SELECT ?a ?b WHERE { VALUES ?a { 1 2 } BIND(BNODE() AS ?b) }",
+            "SELECT ?a ?b WHERE { BIND(BNODE() AS ?b) VALUES ?a { 1 2 } }",
+        )?
+        .is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_blank_nodes_in_triples() -> Result<(), EvaluationError> {
+        assert!(diff(
+            "SELECT ?t WHERE { BIND(BNODE() AS ?b) BIND(<<?b <http://example.com/p> 1>> AS ?t) }",
+            "SELECT ?t WHERE { BIND(BNODE() AS ?b) BIND(<<?b <http://example.com/p> 1>> AS ?t) }",
+        )?
+        .is_empty());
+        assert_eq!(
+            diff(
+                "SELECT ?t WHERE { BIND(BNODE() AS ?b) BIND(<<?b <http://example.com/p> 1>> AS ?t) }",
+                "SELECT ?t WHERE { BIND(BNODE() AS ?b) BIND(<<?b <http://example.com/p> 2>> AS ?t) }",
+            )?
+            .to_string()
+            .lines()
+            .count(),
+            2
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_boolean() -> Result<(), EvaluationError> {
+        assert!(diff("ASK {}", "ASK {}")?.is_empty());
+        assert_eq!(
+            diff("ASK {}", "ASK { FILTER(false) }")?.to_string(),
+            "- true\n+ false\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_graph() -> Result<(), EvaluationError> {
+        assert!(diff(
+            "CONSTRUCT { [] <http://example.com/p> 1 } WHERE {}",
+            "CONSTRUCT { [] <http://example.com/p> 1 } WHERE {}"
+        )?
+        .is_empty());
+        assert_eq!(
+            diff(
+                "CONSTRUCT { <http://example.com/s> <http://example.com/p> \"1\" } WHERE {}",
+                "CONSTRUCT { <http://example.com/s> <http://example.com/p> \"2\" } WHERE {}"
+            )?
+            .to_string(),
+            "- <http://example.com/s> <http://example.com/p> \"1\" .\n+ <http://example.com/s> <http://example.com/p> \"2\" .\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_different_kinds() -> Result<(), EvaluationError> {
+        assert!(!diff("ASK {}", "SELECT * {}")?.is_empty());
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 use crate::io::RdfParseError;
-use crate::model::NamedNode;
+use crate::model::{NamedNode, Variable};
 use crate::sparql::results::QueryResultsParseError as ResultsParseError;
 use crate::sparql::SparqlSyntaxError;
 use crate::store::{CorruptionError, StorageError};
@@ -51,6 +51,19 @@ pub enum EvaluationError {
     /// The results are not a RDF graph
     #[error("The query results are not a RDF graph")]
     NotAGraph,
+    /// The evaluation has been cancelled using a [`CancellationToken`](crate::sparql::CancellationToken)
+    #[error("The query evaluation has been cancelled")]
+    Cancelled,
+    /// The initial bindings given to [`Query::with_initial_bindings`](crate::sparql::Query::with_initial_bindings) contain a blank node
+    #[error("The initial binding of {0} contains a blank node")]
+    BlankNodeInInitialBindings(Variable),
+    /// The query requires more memory than allowed by [`QueryOptions::with_memory_budget`](crate::sparql::QueryOptions::with_memory_budget)
+    #[error("The query evaluation requires more than {0} bytes of memory")]
+    MemoryBudgetExceeded(usize),
+    /// An error while converting solutions into [Apache Arrow](https://arrow.apache.org/) record batches
+    #[cfg(feature = "arrow")]
+    #[error(transparent)]
+    Arrow(#[from] arrow_schema::ArrowError),
     #[doc(hidden)]
     #[error(transparent)]
     Unexpected(Box<dyn Error + Send + Sync>),
@@ -71,6 +84,25 @@ impl From<QueryEvaluationError> for EvaluationError {
                 Err(error) => Self::Unexpected(error),
             },
             QueryEvaluationError::Service(error) => Self::Service(error),
+            QueryEvaluationError::Load(error) => match error.downcast() {
+                Ok(error) => *error,
+                Err(error) => Self::Service(error),
+            },
+            QueryEvaluationError::UnboundService => Self::UnboundService,
+            QueryEvaluationError::UnsupportedService(service_name) => {
+                Self::UnsupportedService(service_name)
+            }
+            QueryEvaluationError::Cancelled => Self::Cancelled,
+            QueryEvaluationError::MemoryBudgetExceeded(max_bytes) => {
+                Self::MemoryBudgetExceeded(max_bytes)
+            }
+            QueryEvaluationError::Spill(error) => Self::Storage(error.into()),
+            QueryEvaluationError::GraphAlreadyExists(graph_name) => {
+                Self::GraphAlreadyExists(graph_name)
+            }
+            QueryEvaluationError::GraphDoesNotExist(graph_name) => {
+                Self::GraphDoesNotExist(graph_name)
+            }
             QueryEvaluationError::UnexpectedDefaultGraph => Self::Storage(
                 CorruptionError::new("Unexpected default graph in SPARQL results").into(),
             ),
@@ -102,7 +134,16 @@ impl From<EvaluationError> for io::Error {
             | EvaluationError::UnsupportedService(_)
             | EvaluationError::UnsupportedContentType(_)
             | EvaluationError::ServiceDoesNotReturnSolutions
-            | EvaluationError::NotAGraph => Self::new(io::ErrorKind::InvalidInput, error),
+            | EvaluationError::NotAGraph
+            | EvaluationError::BlankNodeInInitialBindings(_) => {
+                Self::new(io::ErrorKind::InvalidInput, error)
+            }
+            EvaluationError::Cancelled => Self::new(io::ErrorKind::Interrupted, error),
+            EvaluationError::MemoryBudgetExceeded(_) => {
+                Self::new(io::ErrorKind::OutOfMemory, error)
+            }
+            #[cfg(feature = "arrow")]
+            EvaluationError::Arrow(error) => Self::new(io::ErrorKind::InvalidData, error),
         }
     }
 }
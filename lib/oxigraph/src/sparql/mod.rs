@@ -3,45 +3,75 @@
 //! Stores execute SPARQL. See [`Store`](crate::store::Store::query()) for an example.
 
 mod algebra;
+mod cache;
 mod dataset;
+mod diff;
 mod error;
 mod http;
 mod model;
 pub mod results;
 mod service;
+mod text;
 mod update;
 
 use crate::model::{NamedNode, Term};
 pub use crate::sparql::algebra::{Query, QueryDataset, Update};
+pub use crate::sparql::cache::QueryResultsCache;
 use crate::sparql::dataset::DatasetView;
+pub use crate::sparql::diff::{GraphDiff, QueryResultsDiff, SolutionsDiff};
 pub use crate::sparql::error::EvaluationError;
+#[cfg(feature = "arrow")]
+pub use crate::sparql::model::RecordBatchIter;
 pub use crate::sparql::model::{QueryResults, QuerySolution, QuerySolutionIter, QueryTripleIter};
 pub use crate::sparql::service::ServiceHandler;
-use crate::sparql::service::{EmptyServiceHandler, WrappedDefaultServiceHandler};
+use crate::sparql::service::{
+    EmptyServiceHandler, StoreServiceHandler, WrappedDefaultServiceHandler,
+};
+use crate::sparql::text::TextSearchServiceHandler;
+pub use crate::sparql::text::{InMemoryTextIndex, TextIndex, TextMatch};
 pub(crate) use crate::sparql::update::evaluate_update;
-use crate::storage::StorageReader;
+use crate::storage::{Storage, StorageReader};
+use crate::store::Store;
 pub use oxrdf::{Variable, VariableNameParseError};
 use spareval::QueryEvaluator;
-pub use spareval::QueryExplanation;
-pub use spargebra::SparqlSyntaxError;
+pub use spareval::{
+    AggregateFunctionAccumulator, CancellationToken, CustomFunctionMetadata, DatasetStatistics,
+    ExistsStrategy, PredicateStatistics, QueryExplanation, QueryPlanCache, StatisticsProvider,
+};
+pub use spargebra::{FunctionDefinition, QueryWarning, SparqlSyntaxError};
+use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
 
+/// Evaluates the query on the reader snapshot.
+///
+/// If parallel execution is enabled and `storage` is set, the query is evaluated on new snapshots of it instead.
 #[allow(clippy::needless_pass_by_value)]
 pub(crate) fn evaluate_query(
     reader: StorageReader,
+    storage: Option<&Storage>,
     query: impl TryInto<Query, Error = impl Into<EvaluationError>>,
     options: QueryOptions,
     run_stats: bool,
     substitutions: impl IntoIterator<Item = (Variable, Term)>,
 ) -> Result<(Result<QueryResults, EvaluationError>, QueryExplanation), EvaluationError> {
     let query = query.try_into().map_err(Into::into)?;
-    let dataset = DatasetView::new(reader, &query.dataset);
+    let parallel_storage = storage.filter(|_| options.parallel_execution).cloned();
     let mut evaluator = options.into_evaluator();
     if run_stats {
         evaluator = evaluator.compute_statistics();
     }
-    let (results, explanation) =
-        evaluator.explain_with_substituted_variables(dataset, &query.inner, substitutions);
+    let (results, explanation) = if let Some(storage) = parallel_storage {
+        let dataset = query.dataset.clone();
+        evaluator.explain_parallel_with_substituted_variables(
+            move || DatasetView::new(storage.snapshot(), &dataset),
+            &query.inner,
+            substitutions,
+        )
+    } else {
+        let dataset = DatasetView::new(reader, &query.dataset);
+        evaluator.explain_with_substituted_variables(dataset, &query.inner, substitutions)
+    };
     let results = results.map_err(Into::into).map(Into::into);
     Ok((results, explanation))
 }
@@ -68,6 +98,8 @@ pub(crate) fn evaluate_query(
 pub struct QueryOptions {
     http_timeout: Option<Duration>,
     http_redirection_limit: usize,
+    parallel_execution: bool,
+    results_cache: Option<QueryResultsCache>,
     inner: QueryEvaluator,
 }
 
@@ -82,6 +114,41 @@ impl QueryOptions {
         self
     }
 
+    /// Evaluates the `SERVICE` calls to `service_name` against another local [`Store`].
+    ///
+    /// It allows to join in a single query data kept in different stores without going through HTTP.
+    /// The `SERVICE` calls to the other IRIs are still evaluated by the default service handler.
+    ///
+    /// ```
+    /// use oxigraph::model::*;
+    /// use oxigraph::sparql::{QueryOptions, QueryResults};
+    /// use oxigraph::store::Store;
+    ///
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// let reference_data = Store::new()?;
+    /// reference_data.insert(QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph))?;
+    ///
+    /// let store = Store::new()?;
+    /// if let QueryResults::Solutions(mut solutions) = store.query_opt(
+    ///     "SELECT ?s WHERE { SERVICE <http://example.com/reference> { ?s ?p ?o } }",
+    ///     QueryOptions::default().with_store_service(
+    ///         NamedNode::new("http://example.com/reference")?,
+    ///         reference_data,
+    ///     ),
+    /// )? {
+    ///     assert_eq!(solutions.next().unwrap()?.get("s"), Some(&ex.into()));
+    /// }
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_store_service(mut self, service_name: NamedNode, store: Store) -> Self {
+        self.inner = self
+            .inner
+            .with_service_handler(service_name, StoreServiceHandler::new(store));
+        self
+    }
+
     /// Disables the `SERVICE` calls
     #[inline]
     #[must_use]
@@ -157,6 +224,396 @@ impl QueryOptions {
         self
     }
 
+    /// Adds a custom SPARQL evaluation aggregate function.
+    ///
+    /// The `evaluator` is called to build a new [`AggregateFunctionAccumulator`] for each group.
+    ///
+    /// Example with a function counting the literals:
+    /// ```
+    /// use oxigraph::model::*;
+    /// use oxigraph::sparql::{AggregateFunctionAccumulator, QueryOptions, QueryResults};
+    /// use oxigraph::store::Store;
+    ///
+    /// #[derive(Default)]
+    /// struct LiteralCount(i64);
+    ///
+    /// impl AggregateFunctionAccumulator for LiteralCount {
+    ///     fn accumulate(&mut self, element: Term) {
+    ///         if element.is_literal() {
+    ///             self.0 += 1;
+    ///         }
+    ///     }
+    ///
+    ///     fn finish(&mut self) -> Option<Term> {
+    ///         Some(Literal::from(self.0).into())
+    ///     }
+    /// }
+    ///
+    /// let store = Store::new()?;
+    ///
+    /// if let QueryResults::Solutions(mut solutions) = store.query_opt(
+    ///     "SELECT ?g (<http://example.com/literalCount>(?v) AS ?c) WHERE { VALUES (?g ?v) { (1 1) (1 <http://example.com>) (1 'a') } } GROUP BY ?g",
+    ///     QueryOptions::default().with_custom_aggregate_function(
+    ///         NamedNode::new("http://example.com/literalCount")?,
+    ///         || Box::<LiteralCount>::default(),
+    ///     ),
+    /// )? {
+    ///     assert_eq!(
+    ///         solutions.next().unwrap()?.get("c"),
+    ///         Some(&Literal::from(2).into())
+    ///     );
+    /// }
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_custom_aggregate_function(
+        mut self,
+        name: NamedNode,
+        evaluator: impl Fn() -> Box<dyn AggregateFunctionAccumulator + Send + Sync>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.inner = self.inner.with_custom_aggregate_function(name, evaluator);
+        self
+    }
+
+    /// Adds a function defined by a SPARQL expression.
+    ///
+    /// Its calls are replaced by its body before evaluation.
+    /// If a function with the same name is already defined, it is replaced.
+    ///
+    /// ```
+    /// use oxigraph::model::*;
+    /// use oxigraph::sparql::{FunctionDefinition, QueryOptions, QueryResults};
+    /// use oxigraph::store::Store;
+    ///
+    /// let store = Store::new()?;
+    /// let mut options = QueryOptions::default();
+    /// for definition in FunctionDefinition::parse_list(
+    ///     "PREFIX ex: <http://example.com/> DEFINE FUNCTION ex:grade(?score) { IF(?score >= 10, 'pass', 'fail') }",
+    ///     None,
+    /// )? {
+    ///     options = options.with_function_definition(definition);
+    /// }
+    /// if let QueryResults::Solutions(mut solutions) = store.query_opt(
+    ///     "SELECT (<http://example.com/grade>(8) AS ?g) WHERE {}",
+    ///     options,
+    /// )? {
+    ///     assert_eq!(
+    ///         solutions.next().unwrap()?.get("g"),
+    ///         Some(&Literal::from("fail").into())
+    ///     );
+    /// }
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_function_definition(mut self, definition: FunctionDefinition) -> Self {
+        self.inner = self.inner.with_function_definition(definition);
+        self
+    }
+
+    /// Returns the functions added with [`with_function_definition`](Self::with_function_definition).
+    #[inline]
+    pub fn function_definitions(&self) -> &[FunctionDefinition] {
+        self.inner.function_definitions()
+    }
+
+    /// Adds a function rewriting the graph pattern of the queries before their evaluation.
+    ///
+    /// It allows to plug query optimizations based on external indexes.
+    /// The rewriters are applied in the order they have been added and must not change the query results.
+    #[inline]
+    #[must_use]
+    pub fn with_graph_pattern_rewriter(
+        mut self,
+        rewriter: impl Fn(spargebra::algebra::GraphPattern) -> spargebra::algebra::GraphPattern
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.inner = self.inner.with_graph_pattern_rewriter(rewriter);
+        self
+    }
+
+    /// Sets the metadata of a custom function or custom aggregate function.
+    ///
+    /// The metadata is only informative and returned by [`custom_functions`](Self::custom_functions) and [`custom_aggregate_functions`](Self::custom_aggregate_functions).
+    ///
+    /// ```
+    /// use oxigraph::model::*;
+    /// use oxigraph::sparql::{CustomFunctionMetadata, QueryOptions};
+    ///
+    /// let name = NamedNode::new("http://example.com/identity")?;
+    /// let options = QueryOptions::default()
+    ///     .with_custom_function(name.clone(), |args| args.first().cloned())
+    ///     .with_custom_function_metadata(
+    ///         name.clone(),
+    ///         CustomFunctionMetadata::new()
+    ///             .with_arity(1)
+    ///             .with_description("Returns its argument"),
+    ///     );
+    /// assert_eq!(
+    ///     options
+    ///         .custom_functions()
+    ///         .map(|(name, metadata)| (name.as_str(), metadata.arity()))
+    ///         .collect::<Vec<_>>(),
+    ///     [("http://example.com/identity", Some(1))]
+    /// );
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_custom_function_metadata(
+        mut self,
+        name: NamedNode,
+        metadata: CustomFunctionMetadata,
+    ) -> Self {
+        self.inner = self.inner.with_custom_function_metadata(name, metadata);
+        self
+    }
+
+    /// Returns the custom functions registered with [`with_custom_function`](Self::with_custom_function) and their metadata.
+    pub fn custom_functions(
+        &self,
+    ) -> impl Iterator<Item = (&NamedNode, &CustomFunctionMetadata)> + '_ {
+        self.inner.custom_functions()
+    }
+
+    /// Returns the custom aggregate functions registered with [`with_custom_aggregate_function`](Self::with_custom_aggregate_function) and their metadata.
+    pub fn custom_aggregate_functions(
+        &self,
+    ) -> impl Iterator<Item = (&NamedNode, &CustomFunctionMetadata)> + '_ {
+        self.inner.custom_aggregate_functions()
+    }
+
+    /// Allows to cancel the query evaluation using the given [`CancellationToken`].
+    ///
+    /// Once [`CancellationToken::cancel`] is called, the evaluation fails with an [`EvaluationError::Cancelled`] error.
+    ///
+    /// ```
+    /// use oxigraph::model::*;
+    /// use oxigraph::sparql::{CancellationToken, EvaluationError, QueryOptions, QueryResults};
+    /// use oxigraph::store::Store;
+    ///
+    /// let store = Store::new()?;
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// store.insert(QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph))?;
+    ///
+    /// let cancellation_token = CancellationToken::new();
+    /// if let QueryResults::Solutions(mut solutions) = store.query_opt(
+    ///     "SELECT * WHERE { ?s ?p ?o }",
+    ///     QueryOptions::default().with_cancellation_token(cancellation_token.clone()),
+    /// )? {
+    ///     cancellation_token.cancel();
+    ///     assert!(matches!(
+    ///         solutions.next(),
+    ///         Some(Err(EvaluationError::Cancelled))
+    ///     ));
+    /// }
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_cancellation_token(mut self, cancellation_token: CancellationToken) -> Self {
+        self.inner = self.inner.with_cancellation_token(cancellation_token);
+        self
+    }
+
+    /// Evaluates the queries using several threads.
+    ///
+    /// The branches of the `UNION`s and the two sides of the hash joins are evaluated in parallel
+    /// on the [rayon](https://docs.rs/rayon) global thread pool.
+    /// It is useful for large analytical queries.
+    ///
+    /// Each thread reads its own snapshot of the store taken when it starts.
+    /// Hence, the parts of a query might see different versions of the store if it is written concurrently.
+    /// This option has no effect on the queries evaluated inside of [transactions](crate::store::Transaction).
+    ///
+    /// ```
+    /// use oxigraph::model::*;
+    /// use oxigraph::sparql::{QueryOptions, QueryResults};
+    /// use oxigraph::store::Store;
+    ///
+    /// let store = Store::new()?;
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// store.insert(QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph))?;
+    /// if let QueryResults::Solutions(solutions) = store.query_opt(
+    ///     "SELECT * WHERE { { ?s ?p ?o } UNION { ?o ?p ?s } }",
+    ///     QueryOptions::default().with_parallel_execution(),
+    /// )? {
+    ///     assert_eq!(solutions.collect::<Result<Vec<_>, _>>()?.len(), 2);
+    /// }
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_parallel_execution(mut self) -> Self {
+        self.parallel_execution = true;
+        self
+    }
+
+    /// Caches the optimized query plans in the given [`QueryPlanCache`].
+    ///
+    /// The cache can be shared between the evaluations of different queries to avoid optimizing again the ones evaluated often.
+    /// The plans built with different [statistics](Self::with_statistics) are kept apart.
+    ///
+    /// ```
+    /// use oxigraph::sparql::{QueryOptions, QueryPlanCache};
+    /// use oxigraph::store::Store;
+    ///
+    /// let store = Store::new()?;
+    /// let cache = QueryPlanCache::new(1000);
+    /// for _ in 0..3 {
+    ///     store.query_opt(
+    ///         "ASK { ?s ?p ?o }",
+    ///         QueryOptions::default().with_plan_cache(cache.clone()),
+    ///     )?;
+    /// }
+    /// assert_eq!(cache.len(), 1);
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_plan_cache(mut self, plan_cache: QueryPlanCache) -> Self {
+        self.inner = self.inner.with_plan_cache(plan_cache);
+        self
+    }
+
+    /// Makes the query optimizer use the given statistics to order the joins and pick the join algorithms.
+    ///
+    /// The statistics of a [`Store`](crate::store::Store) are computed with [`Store::statistics`](crate::store::Store::statistics).
+    #[inline]
+    #[must_use]
+    pub fn with_statistics(mut self, statistics: Arc<dyn StatisticsProvider>) -> Self {
+        self.inner = self.inner.with_statistics(statistics);
+        self
+    }
+
+    /// Sets how the `EXISTS` patterns get the values of the variables bound outside of them.
+    ///
+    /// The default is [`ExistsStrategy::Substitution`], following the SPARQL 1.1 specification.
+    /// [`ExistsStrategy::FilterScoped`] eases the migration of queries written for engines evaluating the `EXISTS` patterns on their own.
+    ///
+    /// ```
+    /// use oxigraph::model::*;
+    /// use oxigraph::sparql::{ExistsStrategy, QueryOptions, QueryResults};
+    /// use oxigraph::store::Store;
+    ///
+    /// let store = Store::new()?;
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// store.insert(QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph))?;
+    ///
+    /// let query = "ASK { ?s ?p ?o FILTER EXISTS { ?s ?p ?o2 FILTER(?o = ?o2) } }";
+    /// assert!(matches!(store.query(query)?, QueryResults::Boolean(true)));
+    /// assert!(matches!(
+    ///     store.query_opt(
+    ///         query,
+    ///         QueryOptions::default().with_exists_strategy(ExistsStrategy::FilterScoped)
+    ///     )?,
+    ///     QueryResults::Boolean(false)
+    /// ));
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_exists_strategy(mut self, exists_strategy: ExistsStrategy) -> Self {
+        self.inner = self.inner.with_exists_strategy(exists_strategy);
+        self
+    }
+
+    /// Limits the memory used by the `ORDER BY`, `GROUP BY`, `DISTINCT` and join operators to roughly `max_bytes` per query.
+    ///
+    /// When the budget is exceeded, sorts, aggregations and deduplications write intermediate solutions to temporary files instead of failing,
+    /// and hash joins fail with [`EvaluationError::MemoryBudgetExceeded`].
+    ///
+    /// ```
+    /// use oxigraph::model::*;
+    /// use oxigraph::sparql::{QueryOptions, QueryResults};
+    /// use oxigraph::store::Store;
+    ///
+    /// let store = Store::new()?;
+    /// let ex = NamedNode::new("http://example.com")?;
+    /// for i in 0..2000 {
+    ///     store.insert(&Quad::new(
+    ///         ex.clone(),
+    ///         ex.clone(),
+    ///         Literal::from(i),
+    ///         GraphName::DefaultGraph,
+    ///     ))?;
+    /// }
+    /// if let QueryResults::Solutions(solutions) = store.query_opt(
+    ///     "SELECT ?o WHERE { ?s ?p ?o } ORDER BY DESC(?o)",
+    ///     QueryOptions::default().with_memory_budget(10_000),
+    /// )? {
+    ///     let solutions = solutions.collect::<Result<Vec<_>, _>>()?;
+    ///     assert_eq!(solutions.len(), 2000);
+    ///     assert_eq!(solutions[0].get("o"), Some(&Literal::from(1999).into()));
+    /// }
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_memory_budget(mut self, max_bytes: usize) -> Self {
+        self.inner = self.inner.with_memory_budget(max_bytes);
+        self
+    }
+
+    /// Sets the directory where the intermediate solutions not fitting in the [memory budget](Self::with_memory_budget) are written.
+    ///
+    /// By default, the system temporary directory is used.
+    #[inline]
+    #[must_use]
+    pub fn with_spill_directory(mut self, path: impl Into<PathBuf>) -> Self {
+        self.inner = self.inner.with_spill_directory(path);
+        self
+    }
+
+    /// Caches the query results in the given [`QueryResultsCache`].
+    ///
+    /// Repeated evaluations of the same query return the cached results as long as the store has not been modified.
+    /// The cache is only used by [`Store::query`](crate::store::Store::query) and [`Store::query_opt`](crate::store::Store::query_opt),
+    /// the queries evaluated inside of transactions, with substituted variables or with an explanation are always evaluated.
+    ///
+    /// ```
+    /// use oxigraph::model::*;
+    /// use oxigraph::sparql::{QueryOptions, QueryResults, QueryResultsCache};
+    /// use oxigraph::store::Store;
+    ///
+    /// let store = Store::new()?;
+    /// let cache = QueryResultsCache::new(100);
+    /// let count = || -> Result<_, Box<dyn std::error::Error>> {
+    ///     let QueryResults::Solutions(solutions) = store.query_opt(
+    ///         "SELECT * WHERE { ?s ?p ?o }",
+    ///         QueryOptions::default().with_results_cache(cache.clone()),
+    ///     )?
+    ///     else {
+    ///         unreachable!()
+    ///     };
+    ///     Ok(solutions.count())
+    /// };
+    /// assert_eq!(count()?, 0);
+    /// assert_eq!(cache.len(), 1);
+    ///
+    /// // The insertion invalidates the cached results
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// store.insert(QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph))?;
+    /// assert_eq!(count()?, 1);
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_results_cache(mut self, results_cache: QueryResultsCache) -> Self {
+        self.results_cache = Some(results_cache);
+        self
+    }
+
+    pub(crate) fn results_cache(&self) -> Option<&QueryResultsCache> {
+        self.results_cache.as_ref()
+    }
+
     #[doc(hidden)]
     #[inline]
     #[must_use]
@@ -164,6 +621,16 @@ impl QueryOptions {
         self.inner = self.inner.without_optimizations();
         self
     }
+
+    /// Evaluates the `SERVICE ofts:search` calls using the given index
+    #[must_use]
+    pub(crate) fn with_text_index(mut self, index: Arc<dyn TextIndex>) -> Self {
+        self.inner = self.inner.with_service_handler(
+            TextSearchServiceHandler::SERVICE_NAME,
+            TextSearchServiceHandler::new(index),
+        );
+        self
+    }
 }
 
 impl Default for QueryOptions {
@@ -171,6 +638,8 @@ impl Default for QueryOptions {
         let mut options = Self {
             http_timeout: None,
             http_redirection_limit: 0,
+            parallel_execution: false,
+            results_cache: None,
             inner: QueryEvaluator::new(),
         };
         if cfg!(feature = "http-client") {
@@ -198,3 +667,11 @@ impl From<QueryOptions> for UpdateOptions {
         Self { query_options }
     }
 }
+
+impl UpdateOptions {
+    #[must_use]
+    pub(crate) fn with_text_index(mut self, index: Arc<dyn TextIndex>) -> Self {
+        self.query_options = self.query_options.with_text_index(index);
+        self
+    }
+}
@@ -1,11 +1,17 @@
 use crate::io::{RdfFormat, RdfSerializer};
 use crate::model::*;
 use crate::sparql::error::EvaluationError;
+#[cfg(feature = "arrow")]
+use crate::sparql::results::ArrowSolutionsConverter;
 use crate::sparql::results::{
     QueryResultsFormat, QueryResultsParseError, QueryResultsParser, QueryResultsSerializer,
     ReaderQueryResultsParserOutput, ReaderSolutionsParser,
 };
 pub use sparesults::QuerySolution;
+#[cfg(feature = "arrow")]
+use arrow_array::RecordBatch;
+#[cfg(feature = "arrow")]
+use arrow_schema::SchemaRef;
 use spareval::{
     QueryEvaluationError, QueryResults as EvalQueryResults,
     QuerySolutionIter as EvalQuerySolutionIter, QueryTripleIter as EvalQueryTripleIter,
@@ -144,6 +150,26 @@ impl QueryResults {
             Err(EvaluationError::NotAGraph)
         }
     }
+
+    /// Computes eagerly all the results and keeps them in memory.
+    ///
+    /// The returned results are not impacted anymore by the changes done to the queried dataset.
+    pub(crate) fn materialize(self) -> Result<Self, EvaluationError> {
+        Ok(match self {
+            Self::Solutions(solutions) => {
+                let variables = Arc::<[Variable]>::from(solutions.variables());
+                let solutions = solutions.collect::<Result<Vec<_>, _>>()?;
+                Self::Solutions(
+                    EvalQuerySolutionIter::new(variables, solutions.into_iter().map(Ok)).into(),
+                )
+            }
+            Self::Boolean(value) => Self::Boolean(value),
+            Self::Graph(triples) => {
+                let triples = triples.collect::<Result<Vec<_>, _>>()?;
+                Self::Graph(EvalQueryTripleIter::new(triples.into_iter().map(Ok)).into())
+            }
+        })
+    }
 }
 
 impl From<EvalQueryResults> for QueryResults {
@@ -228,6 +254,37 @@ impl QuerySolutionIter {
     pub fn variables(&self) -> &[Variable] {
         self.inner.variables()
     }
+
+    /// Converts the solutions into [Apache Arrow](https://arrow.apache.org/) record batches of at most `batch_size` rows.
+    ///
+    /// The column types are inferred from the first batch.
+    /// See [`ArrowSolutionsConverter`] for the conversion rules.
+    ///
+    /// ```
+    /// use oxigraph::sparql::QueryResults;
+    /// use oxigraph::store::Store;
+    ///
+    /// let store = Store::new()?;
+    /// if let QueryResults::Solutions(solutions) =
+    ///     store.query("SELECT ?i WHERE { VALUES ?i { 1 2 3 } }")?
+    /// {
+    ///     let batches = solutions
+    ///         .into_record_batches(2)
+    ///         .collect::<Result<Vec<_>, _>>()?;
+    ///     assert_eq!(batches.len(), 2);
+    ///     assert_eq!(batches[0].num_rows(), 2);
+    ///     assert_eq!(batches[1].num_rows(), 1);
+    /// }
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[cfg(feature = "arrow")]
+    pub fn into_record_batches(self, batch_size: usize) -> RecordBatchIter {
+        RecordBatchIter {
+            converter: ArrowSolutionsConverter::new(self.variables().to_vec()),
+            solutions: self,
+            batch_size: batch_size.max(1),
+        }
+    }
 }
 
 impl From<EvalQuerySolutionIter> for QuerySolutionIter {
@@ -269,6 +326,45 @@ impl Iterator for QuerySolutionIter {
     }
 }
 
+/// An iterator over [Apache Arrow](https://arrow.apache.org/) record batches of query solutions.
+///
+/// Built using [`QuerySolutionIter::into_record_batches`].
+/// A single empty batch is returned if there are no solutions.
+#[cfg(feature = "arrow")]
+pub struct RecordBatchIter {
+    solutions: QuerySolutionIter,
+    converter: ArrowSolutionsConverter,
+    batch_size: usize,
+}
+
+#[cfg(feature = "arrow")]
+impl RecordBatchIter {
+    /// The schema of the record batches or `None` if no batch has been returned yet.
+    #[inline]
+    pub fn schema(&self) -> Option<SchemaRef> {
+        self.converter.schema()
+    }
+}
+
+#[cfg(feature = "arrow")]
+impl Iterator for RecordBatchIter {
+    type Item = Result<RecordBatch, EvaluationError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut solutions = Vec::new();
+        for solution in self.solutions.by_ref().take(self.batch_size) {
+            match solution {
+                Ok(solution) => solutions.push(solution),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        if solutions.is_empty() && self.converter.schema().is_some() {
+            return None;
+        }
+        Some(self.converter.convert(&solutions).map_err(Into::into))
+    }
+}
+
 /// An iterator over the triples that compose a graph solution.
 ///
 /// ```
@@ -5,6 +5,7 @@ use crate::sparql::http::Client;
 use crate::sparql::model::QueryResults;
 use crate::sparql::results::QueryResultsFormat;
 use crate::sparql::QueryDataset;
+use crate::store::Store;
 use oxiri::Iri;
 use sparesults::{QueryResultsParser, ReaderQueryResultsParserOutput};
 use spareval::{DefaultServiceHandler, QueryEvaluationError, QuerySolutionIter};
@@ -65,6 +66,16 @@ pub trait ServiceHandler: Send + Sync {
 
     /// Evaluates a [`Query`] against a given service identified by a [`NamedNode`].
     fn handle(&self, service_name: NamedNode, query: Query) -> Result<QueryResults, Self::Error>;
+
+    /// If the handler is able to evaluate queries whose pattern is joined with a `VALUES` clause.
+    ///
+    /// If `true`, when a non-silent `SERVICE` is joined with other patterns, the values of the join variables
+    /// are sent to the service by batches in a `VALUES` clause, so only the relevant solutions are returned.
+    /// If `false` (the default), the service is always called with the pattern written in the query.
+    #[inline]
+    fn supports_bound_join(&self) -> bool {
+        false
+    }
 }
 
 pub struct WrappedDefaultServiceHandler<H: ServiceHandler>(pub H);
@@ -102,6 +113,51 @@ impl<H: ServiceHandler> DefaultServiceHandler for WrappedDefaultServiceHandler<H
         };
         Ok(solutions.into())
     }
+
+    fn supports_bound_join(&self) -> bool {
+        self.0.supports_bound_join()
+    }
+}
+
+/// Evaluates the SERVICE calls against a local [`Store`]
+pub struct StoreServiceHandler {
+    store: Store,
+}
+
+impl StoreServiceHandler {
+    pub fn new(store: Store) -> Self {
+        Self { store }
+    }
+}
+
+impl spareval::ServiceHandler for StoreServiceHandler {
+    type Error = EvaluationError;
+
+    fn handle(
+        &self,
+        pattern: GraphPattern,
+        base_iri: Option<String>,
+    ) -> Result<QuerySolutionIter, Self::Error> {
+        let QueryResults::Solutions(solutions) = self.store.query(Query {
+            inner: spargebra::Query::Select {
+                dataset: None,
+                pattern,
+                base_iri: base_iri
+                    .map(Iri::parse)
+                    .transpose()
+                    .map_err(|e| EvaluationError::Service(Box::new(e)))?,
+            },
+            dataset: QueryDataset::new(),
+        })?
+        else {
+            return Err(EvaluationError::ServiceDoesNotReturnSolutions);
+        };
+        Ok(solutions.into())
+    }
+
+    fn supports_bound_join(&self) -> bool {
+        true
+    }
 }
 
 pub struct EmptyServiceHandler;
@@ -171,4 +227,8 @@ impl DefaultServiceHandler for SimpleServiceHandler {
             Box::new(reader.map(|t| t.map_err(|e| QueryEvaluationError::Service(Box::new(e))))),
         ))
     }
+
+    fn supports_bound_join(&self) -> bool {
+        true
+    }
 }
@@ -0,0 +1,341 @@
+use crate::model::{GraphNameRef, Literal, NamedNodeRef, Quad, QuadRef, Term, TermRef, Variable};
+use crate::sparql::results::QuerySolution;
+use spareval::{QueryEvaluationError, QuerySolutionIter, ServiceHandler};
+use spargebra::algebra::GraphPattern;
+use spargebra::term::{NamedNodePattern, TermPattern};
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::{Arc, PoisonError, RwLock};
+
+const SEARCH: NamedNodeRef<'_> = NamedNodeRef::new_unchecked("http://oxigraph.org/fts#search");
+const QUERY: NamedNodeRef<'_> = NamedNodeRef::new_unchecked("http://oxigraph.org/fts#query");
+const LIMIT: NamedNodeRef<'_> = NamedNodeRef::new_unchecked("http://oxigraph.org/fts#limit");
+const SCORE: NamedNodeRef<'_> = NamedNodeRef::new_unchecked("http://oxigraph.org/fts#score");
+const PREDICATE: NamedNodeRef<'_> =
+    NamedNodeRef::new_unchecked("http://oxigraph.org/fts#predicate");
+const LITERAL: NamedNodeRef<'_> = NamedNodeRef::new_unchecked("http://oxigraph.org/fts#literal");
+const GRAPH: NamedNodeRef<'_> = NamedNodeRef::new_unchecked("http://oxigraph.org/fts#graph");
+
+/// A full-text index of the literals of a [`Store`](crate::store::Store).
+///
+/// It is maintained by the store after being registered with [`Store::enable_text_index`](crate::store::Store::enable_text_index).
+/// Oxigraph provides the [`InMemoryTextIndex`] implementation.
+/// This trait allows to plug other search engines.
+pub trait TextIndex: Send + Sync {
+    /// Adds to the index a quad whose object is a literal.
+    ///
+    /// Adding a quad already in the index must not change the index.
+    fn insert(&self, quad: QuadRef<'_>);
+
+    /// Removes from the index a quad whose object is a literal.
+    fn remove(&self, quad: QuadRef<'_>);
+
+    /// Returns the indexed quads whose literal matches the given query, sorted by decreasing relevance.
+    fn search(
+        &self,
+        query: &str,
+        limit: Option<usize>,
+    ) -> Result<Vec<TextMatch>, Box<dyn Error + Send + Sync>>;
+}
+
+/// A result of [`TextIndex::search`].
+#[derive(PartialEq, Debug, Clone)]
+pub struct TextMatch {
+    /// The quad whose object is the matching literal
+    pub quad: Quad,
+    /// The relevance of the match, higher is better
+    pub score: f64,
+}
+
+/// A simple [`TextIndex`] kept in memory.
+///
+/// The literals are split into lowercase words on the non-alphanumeric characters.
+/// A literal matches a query if it contains all the query words.
+/// The matches are ranked using [TF-IDF](https://en.wikipedia.org/wiki/Tf%E2%80%93idf).
+///
+/// ```
+/// use oxigraph::model::*;
+/// use oxigraph::sparql::{InMemoryTextIndex, TextIndex};
+///
+/// let ex = NamedNodeRef::new("http://example.com")?;
+/// let paris = QuadRef::new(
+///     ex,
+///     ex,
+///     LiteralRef::new_simple_literal("Paris, the capital of France"),
+///     GraphNameRef::DefaultGraph,
+/// );
+///
+/// let index = InMemoryTextIndex::default();
+/// index.insert(paris);
+/// assert_eq!(index.search("capital paris", None)?[0].quad, paris.into_owned());
+/// assert!(index.search("capital london", None)?.is_empty());
+/// # Result::<_, Box<dyn std::error::Error + Send + Sync>>::Ok(())
+/// ```
+#[derive(Default)]
+pub struct InMemoryTextIndex {
+    content: RwLock<InMemoryTextIndexContent>,
+}
+
+#[derive(Default)]
+struct InMemoryTextIndexContent {
+    /// Number of words of each indexed literal
+    lengths: HashMap<Quad, usize>,
+    /// Number of occurrences of each word in the indexed literals
+    postings: HashMap<String, HashMap<Quad, usize>>,
+}
+
+impl TextIndex for InMemoryTextIndex {
+    fn insert(&self, quad: QuadRef<'_>) {
+        let TermRef::Literal(literal) = quad.object else {
+            return;
+        };
+        let mut content = self.content.write().unwrap_or_else(PoisonError::into_inner);
+        let quad = quad.into_owned();
+        if content.lengths.contains_key(&quad) {
+            return;
+        }
+        let mut length = 0;
+        for word in words(literal.value()) {
+            *content
+                .postings
+                .entry(word)
+                .or_default()
+                .entry(quad.clone())
+                .or_default() += 1;
+            length += 1;
+        }
+        content.lengths.insert(quad, length);
+    }
+
+    fn remove(&self, quad: QuadRef<'_>) {
+        let TermRef::Literal(literal) = quad.object else {
+            return;
+        };
+        let mut content = self.content.write().unwrap_or_else(PoisonError::into_inner);
+        let quad = quad.into_owned();
+        if content.lengths.remove(&quad).is_none() {
+            return;
+        }
+        for word in words(literal.value()) {
+            if let Some(posting) = content.postings.get_mut(&word) {
+                posting.remove(&quad);
+                if posting.is_empty() {
+                    content.postings.remove(&word);
+                }
+            }
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn search(
+        &self,
+        query: &str,
+        limit: Option<usize>,
+    ) -> Result<Vec<TextMatch>, Box<dyn Error + Send + Sync>> {
+        let content = self.content.read().unwrap_or_else(PoisonError::into_inner);
+        let mut words = words(query).collect::<Vec<_>>();
+        words.sort_unstable();
+        words.dedup();
+        let Some(postings) = words
+            .iter()
+            .map(|word| content.postings.get(word))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return Ok(Vec::new()); // A word is not in the index
+        };
+        let Some(smallest) = postings.iter().min_by_key(|posting| posting.len()) else {
+            return Ok(Vec::new()); // Empty query
+        };
+        let document_count = content.lengths.len() as f64;
+        let mut matches = smallest
+            .keys()
+            .filter_map(|quad| {
+                let length = *content.lengths.get(quad)? as f64;
+                let mut score = 0.;
+                for posting in &postings {
+                    let frequency = *posting.get(quad)? as f64 / length;
+                    let inverse_document_frequency =
+                        (1. + document_count / posting.len() as f64).ln();
+                    score += frequency * inverse_document_frequency;
+                }
+                Some(TextMatch {
+                    quad: quad.clone(),
+                    score,
+                })
+            })
+            .collect::<Vec<_>>();
+        matches.sort_unstable_by(|a, b| b.score.total_cmp(&a.score));
+        if let Some(limit) = limit {
+            matches.truncate(limit);
+        }
+        Ok(matches)
+    }
+}
+
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
+
+/// Evaluates the `SERVICE ofts:search { ?s ofts:query "..." }` calls using a [`TextIndex`]
+pub struct TextSearchServiceHandler {
+    index: Arc<dyn TextIndex>,
+}
+
+impl TextSearchServiceHandler {
+    pub const SERVICE_NAME: NamedNodeRef<'static> = SEARCH;
+
+    pub fn new(index: Arc<dyn TextIndex>) -> Self {
+        Self { index }
+    }
+}
+
+impl ServiceHandler for TextSearchServiceHandler {
+    type Error = QueryEvaluationError;
+
+    fn handle(
+        &self,
+        pattern: GraphPattern,
+        _: Option<String>,
+    ) -> Result<QuerySolutionIter, QueryEvaluationError> {
+        let GraphPattern::Bgp { patterns } = pattern else {
+            return Err(search_error(
+                "The full-text search service only supports basic graph patterns",
+            ));
+        };
+        let mut subject = None;
+        let mut query = None;
+        let mut limit = None;
+        let mut outputs = Vec::new();
+        for pattern in patterns {
+            match &subject {
+                Some(subject) if *subject != pattern.subject => {
+                    return Err(search_error(
+                        "All the full-text search triple patterns must have the same subject",
+                    ))
+                }
+                Some(_) => (),
+                None => subject = Some(pattern.subject.clone()),
+            }
+            let NamedNodePattern::NamedNode(predicate) = pattern.predicate else {
+                return Err(search_error(
+                    "The full-text search triple patterns must have a constant predicate",
+                ));
+            };
+            match predicate.as_ref() {
+                QUERY => {
+                    let TermPattern::Literal(literal) = pattern.object else {
+                        return Err(search_error("ofts:query value must be a literal"));
+                    };
+                    query = Some(literal.value().to_owned());
+                }
+                LIMIT => {
+                    let TermPattern::Literal(literal) = pattern.object else {
+                        return Err(search_error("ofts:limit value must be a literal"));
+                    };
+                    limit = Some(literal.value().parse::<usize>().map_err(|_| {
+                        search_error("ofts:limit value must be a non-negative integer")
+                    })?);
+                }
+                SCORE => outputs.push((Output::Score, pattern.object)),
+                PREDICATE => outputs.push((Output::Predicate, pattern.object)),
+                LITERAL => outputs.push((Output::Literal, pattern.object)),
+                GRAPH => outputs.push((Output::Graph, pattern.object)),
+                _ => {
+                    return Err(search_error(format!(
+                        "{predicate} is not supported by the full-text search service"
+                    )))
+                }
+            }
+        }
+        let Some(query) = query else {
+            return Err(search_error(
+                "The full-text search service requires an ofts:query triple pattern",
+            ));
+        };
+        if let Some(subject) = subject {
+            outputs.push((Output::Subject, subject));
+        }
+        let mut variables = Vec::new();
+        for (_, pattern) in &outputs {
+            if let TermPattern::Variable(variable) = pattern {
+                if !variables.contains(variable) {
+                    variables.push(variable.clone());
+                }
+            }
+        }
+        let variables = Arc::<[Variable]>::from(variables);
+        let matches = self
+            .index
+            .search(&query, limit)
+            .map_err(QueryEvaluationError::Service)?;
+        let solutions_variables = Arc::clone(&variables);
+        Ok(QuerySolutionIter::new(
+            variables,
+            matches.into_iter().filter_map(move |m| {
+                let mut values = vec![None; solutions_variables.len()];
+                for (output, pattern) in &outputs {
+                    let value = match output {
+                        Output::Subject => m.quad.subject.clone().into(),
+                        Output::Score => Literal::from(m.score).into(),
+                        Output::Predicate => m.quad.predicate.clone().into(),
+                        Output::Literal => m.quad.object.clone(),
+                        Output::Graph => match m.quad.graph_name.as_ref() {
+                            GraphNameRef::NamedNode(g) => g.into_owned().into(),
+                            GraphNameRef::BlankNode(g) => g.into_owned().into(),
+                            // The default graph is not a term, the variable is left unbound
+                            GraphNameRef::DefaultGraph => continue,
+                        },
+                    };
+                    if !bind(pattern, value, &solutions_variables, &mut values) {
+                        return None;
+                    }
+                }
+                Some(Ok(QuerySolution::from((
+                    Arc::clone(&solutions_variables),
+                    values,
+                ))))
+            }),
+        ))
+    }
+}
+
+/// The value of a search match bound to a pattern
+enum Output {
+    Subject,
+    Score,
+    Predicate,
+    Literal,
+    Graph,
+}
+
+/// Binds the value to the pattern, returns `false` if they are not compatible
+fn bind(
+    pattern: &TermPattern,
+    value: Term,
+    variables: &[Variable],
+    values: &mut [Option<Term>],
+) -> bool {
+    match pattern {
+        TermPattern::Variable(variable) => {
+            let Some(position) = variables.iter().position(|v| v == variable) else {
+                return true;
+            };
+            if let Some(current) = &values[position] {
+                *current == value
+            } else {
+                values[position] = Some(value);
+                true
+            }
+        }
+        TermPattern::NamedNode(node) => Term::from(node.clone()) == value,
+        TermPattern::Literal(literal) => Term::from(literal.clone()) == value,
+        TermPattern::BlankNode(_) => true,
+        TermPattern::Triple(_) => false,
+    }
+}
+
+fn search_error(message: impl Into<String>) -> QueryEvaluationError {
+    QueryEvaluationError::Service(message.into().into())
+}
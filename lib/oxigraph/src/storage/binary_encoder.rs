@@ -1,13 +1,15 @@
 use crate::storage::error::{CorruptionError, StorageError};
 use crate::storage::numeric_encoder::{EncodedQuad, EncodedTerm, EncodedTriple, StrHash};
 use crate::storage::small_string::SmallString;
+#[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+use crate::storage::GraphMetadata;
 use oxsdatatypes::*;
 use std::io::Read;
 use std::mem::size_of;
 use std::sync::Arc;
 
 #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
-pub const LATEST_STORAGE_VERSION: u64 = 1;
+pub const LATEST_STORAGE_VERSION: u64 = 3;
 pub const WRITTEN_TERM_MAX_SIZE: usize = size_of::<u8>() + 2 * size_of::<StrHash>();
 
 // Encoded term type blocks
@@ -755,3 +757,88 @@ mod tests {
         }
     }
 }
+
+#[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+const GRAPH_METADATA_HAS_CREATED: u8 = 1;
+#[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+const GRAPH_METADATA_HAS_MODIFIED: u8 = 2;
+#[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+const GRAPH_METADATA_HAS_VERSION: u8 = 4;
+
+#[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+pub fn encode_graph_metadata(metadata: &GraphMetadata) -> Vec<u8> {
+    let mut flags = 0;
+    if metadata.created.is_some() {
+        flags |= GRAPH_METADATA_HAS_CREATED;
+    }
+    if metadata.modified.is_some() {
+        flags |= GRAPH_METADATA_HAS_MODIFIED;
+    }
+    if metadata.version > 0 {
+        flags |= GRAPH_METADATA_HAS_VERSION;
+    }
+    let mut vec = vec![flags];
+    for date_time in [metadata.created, metadata.modified].into_iter().flatten() {
+        vec.extend_from_slice(&date_time.to_be_bytes());
+    }
+    if metadata.version > 0 {
+        vec.extend_from_slice(&metadata.version.to_be_bytes());
+    }
+    vec.extend_from_slice(&metadata.len.to_be_bytes());
+    vec.extend_from_slice(&(metadata.properties.len() as u64).to_be_bytes());
+    for (key, value) in &metadata.properties {
+        for part in [key, value] {
+            vec.extend_from_slice(&(part.len() as u64).to_be_bytes());
+            vec.extend_from_slice(part.as_bytes());
+        }
+    }
+    vec
+}
+
+#[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+pub fn decode_graph_metadata(mut buffer: &[u8]) -> Result<GraphMetadata, StorageError> {
+    let mut flags = [0; 1];
+    buffer.read_exact(&mut flags)?;
+    let mut metadata = GraphMetadata::default();
+    if flags[0] & GRAPH_METADATA_HAS_CREATED != 0 {
+        let mut date_time = [0; 18];
+        buffer.read_exact(&mut date_time)?;
+        metadata.created = Some(DateTime::from_be_bytes(date_time));
+    }
+    if flags[0] & GRAPH_METADATA_HAS_MODIFIED != 0 {
+        let mut date_time = [0; 18];
+        buffer.read_exact(&mut date_time)?;
+        metadata.modified = Some(DateTime::from_be_bytes(date_time));
+    }
+    if flags[0] & GRAPH_METADATA_HAS_VERSION != 0 {
+        let mut version = [0; 8];
+        buffer.read_exact(&mut version)?;
+        metadata.version = u64::from_be_bytes(version);
+    }
+    let mut len = [0; 8];
+    buffer.read_exact(&mut len)?;
+    metadata.len = u64::from_be_bytes(len);
+    let mut count = [0; 8];
+    buffer.read_exact(&mut count)?;
+    for _ in 0..u64::from_be_bytes(count) {
+        let key = read_graph_metadata_string(&mut buffer)?;
+        let value = read_graph_metadata_string(&mut buffer)?;
+        metadata.properties.insert(key, value);
+    }
+    Ok(metadata)
+}
+
+#[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+fn read_graph_metadata_string(buffer: &mut &[u8]) -> Result<String, StorageError> {
+    let mut len = [0; 8];
+    buffer.read_exact(&mut len)?;
+    let len: usize = u64::from_be_bytes(len)
+        .try_into()
+        .map_err(CorruptionError::new)?;
+    if buffer.len() < len {
+        return Err(CorruptionError::msg("Truncated graph metadata property").into());
+    }
+    let (value, rest) = buffer.split_at(len);
+    *buffer = rest;
+    Ok(String::from_utf8(value.to_vec()).map_err(CorruptionError::new)?)
+}
@@ -3,17 +3,18 @@ pub use crate::storage::error::StorageError;
 use crate::storage::numeric_encoder::{
     insert_term, Decoder, EncodedQuad, EncodedTerm, StrHash, StrHashHasher, StrLookup,
 };
-use crate::storage::CorruptionError;
+use crate::storage::{CorruptionError, GraphMetadata, HistoryEntry, LoadReport, QuadChange};
 use dashmap::iter::Iter;
 use dashmap::mapref::entry::Entry;
 use dashmap::{DashMap, DashSet};
 use oxrdf::Quad;
-use rustc_hash::FxHasher;
+use oxsdatatypes::DateTime;
+use rustc_hash::{FxHashMap, FxHasher};
 use std::borrow::Borrow;
 use std::error::Error;
 use std::hash::{BuildHasherDefault, Hash, Hasher};
 use std::mem::transmute;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, RwLock, Weak};
 
 /// In-memory storage working with MVCC
@@ -40,6 +41,11 @@ struct Content {
     last_quad_by_graph_name:
         DashMap<EncodedTerm, (Weak<QuadListNode>, u64), BuildHasherDefault<FxHasher>>,
     graphs: DashMap<EncodedTerm, VersionRange>,
+    /// The successive versions of each graph metadata, `None` if the graph has been removed
+    graph_metadata: DashMap<EncodedTerm, Vec<(usize, Option<GraphMetadata>)>>,
+    history_enabled: AtomicBool,
+    /// The history entries with the version of the transaction that has written them
+    history: RwLock<Vec<(usize, HistoryEntry)>>,
 }
 
 impl MemoryStorage {
@@ -53,6 +59,9 @@ impl MemoryStorage {
                 last_quad_by_object: DashMap::default(),
                 last_quad_by_graph_name: DashMap::default(),
                 graphs: DashMap::default(),
+                graph_metadata: DashMap::default(),
+                history_enabled: AtomicBool::new(false),
+                history: RwLock::new(Vec::new()),
             }),
             id2str: Arc::new(DashMap::default()),
             version_counter: Arc::new(AtomicUsize::new(0)),
@@ -78,9 +87,13 @@ impl MemoryStorage {
         let transaction_id = *transaction_mutex;
         let snapshot_id = self.version_counter.load(Ordering::Acquire);
         let mut operations = Vec::new();
+        let mut graph_metadata = FxHashMap::default();
+        let mut history = None;
         let result = f(MemoryStorageWriter {
             storage: self,
             log: &mut operations,
+            graph_metadata: &mut graph_metadata,
+            history: &mut history,
             transaction_id,
         });
         if result.is_ok() {
@@ -102,6 +115,18 @@ impl MemoryStorage {
                     }
                 }
             }
+            for (graph_name, metadata) in graph_metadata {
+                self.content
+                    .graph_metadata
+                    .entry(graph_name)
+                    .or_default()
+                    .push((new_version_id, metadata));
+            }
+            if let Some(mut entry) = history {
+                let mut history = self.content.history.write().unwrap();
+                entry.set_version(history.last().map_or(0, |(_, entry)| entry.version()) + 1);
+                history.push((new_version_id, entry));
+            }
             self.version_counter
                 .store(new_version_id, Ordering::Release);
         } else {
@@ -125,6 +150,14 @@ impl MemoryStorage {
         result
     }
 
+    pub fn enable_history(&self) {
+        self.content.history_enabled.store(true, Ordering::Release);
+    }
+
+    pub fn is_history_enabled(&self) -> bool {
+        self.content.history_enabled.load(Ordering::Acquire)
+    }
+
     pub fn bulk_loader(&self) -> MemoryStorageBulkLoader {
         MemoryStorageBulkLoader {
             storage: self.clone(),
@@ -264,6 +297,42 @@ impl MemoryStorageReader {
         self.storage.id2str.contains_key(key)
     }
 
+    pub fn graph_metadata(&self, graph_name: &EncodedTerm) -> Option<GraphMetadata> {
+        self.storage
+            .content
+            .graph_metadata
+            .get(graph_name)?
+            .iter()
+            .rev()
+            .find(|(version, _)| *version <= self.snapshot_id)?
+            .1
+            .clone()
+    }
+
+    pub fn history_version(&self) -> Option<u64> {
+        if !self.storage.is_history_enabled() {
+            return None;
+        }
+        Some(
+            self.storage
+                .content
+                .history
+                .read()
+                .unwrap()
+                .iter()
+                .rev()
+                .find(|(version, _)| *version <= self.snapshot_id)
+                .map_or(0, |(_, entry)| entry.version()),
+        )
+    }
+
+    pub fn history_entry(&self, version: u64) -> Option<HistoryEntry> {
+        let history = self.storage.content.history.read().unwrap();
+        let (transaction_version, entry) =
+            history.get(usize::try_from(version).ok()?.checked_sub(1)?)?;
+        (*transaction_version <= self.snapshot_id).then(|| entry.clone())
+    }
+
     /// Validates that all the storage invariants held in the data
     #[allow(clippy::unwrap_in_result)]
     pub fn validate(&self) -> Result<(), StorageError> {
@@ -454,6 +523,10 @@ impl StrLookup for MemoryStorageReader {
 pub struct MemoryStorageWriter<'a> {
     storage: &'a MemoryStorage,
     log: &'a mut Vec<LogEntry>,
+    /// The graph metadata written by the transaction, applied on commit
+    graph_metadata: &'a mut FxHashMap<EncodedTerm, Option<GraphMetadata>>,
+    /// The history entry of the transaction, its version is set on commit
+    history: &'a mut Option<HistoryEntry>,
     transaction_id: usize,
 }
 
@@ -706,6 +779,36 @@ impl MemoryStorageWriter<'_> {
             });
     }
 
+    pub fn graph_metadata_mut(&mut self, graph_name: &EncodedTerm) -> &mut Option<GraphMetadata> {
+        let reader = || MemoryStorageReader {
+            storage: self.storage.clone(),
+            snapshot_id: self.transaction_id,
+        };
+        self.graph_metadata
+            .entry(graph_name.clone())
+            .or_insert_with(|| reader().graph_metadata(graph_name))
+    }
+
+    pub fn set_graph_metadata(
+        &mut self,
+        graph_name: &EncodedTerm,
+        metadata: Option<GraphMetadata>,
+    ) {
+        self.graph_metadata.insert(graph_name.clone(), metadata);
+    }
+
+    pub fn append_history(&mut self, change: &QuadChange, timestamp: DateTime) {
+        self.history
+            .get_or_insert_with(|| HistoryEntry::new(0, timestamp, Vec::new()))
+            .push(change.clone());
+    }
+
+    pub fn append_bulk_load_history(&mut self, timestamp: DateTime) {
+        self.history
+            .get_or_insert_with(|| HistoryEntry::new(0, timestamp, Vec::new()))
+            .set_bulk_load();
+    }
+
     pub fn clear(&mut self) {
         self.clear_all_graphs();
         self.do_remove_graphs();
@@ -802,23 +905,33 @@ impl MemoryStorageBulkLoader {
         self
     }
 
+    /// Loads the quads and adds them to `report`, including the ones loaded before a failure
     #[allow(clippy::unwrap_in_result)]
     pub fn load<EI, EO: From<StorageError> + From<EI>>(
         &self,
         quads: impl IntoIterator<Item = Result<Quad, EI>>,
+        report: &mut LoadReport,
     ) -> Result<(), EO> {
         // We lock content here to make sure there is not a transaction committing at the same time
         let _transaction_lock = self.storage.transaction_counter.lock().unwrap();
         let mut done_counter = 0;
         let version_id = self.storage.version_counter.load(Ordering::Acquire) + 1;
         let mut log = Vec::new();
-        for quad in quads {
-            MemoryStorageWriter {
+        // The graph metadata is updated after the load
+        let mut graph_metadata = FxHashMap::default();
+        // The bulk loads are only marked in the history after the load
+        let mut history = None;
+        let result = quads.into_iter().try_for_each(|quad| {
+            let quad = quad?;
+            let is_new = MemoryStorageWriter {
                 storage: &self.storage,
                 log: &mut log,
+                graph_metadata: &mut graph_metadata,
+                history: &mut history,
                 transaction_id: version_id,
             }
-            .insert(quad?.as_ref());
+            .insert(quad.as_ref());
+            report.add(quad.graph_name.as_ref(), is_new);
             log.clear();
             done_counter += 1;
             if done_counter % 1_000_000 == 0 {
@@ -826,11 +939,13 @@ impl MemoryStorageBulkLoader {
                     hook(done_counter);
                 }
             }
-        }
+            Ok(())
+        });
+        // The quads loaded before a failure are kept
         self.storage
             .version_counter
             .store(version_id, Ordering::Release);
-        Ok(())
+        result
     }
 }
 
@@ -1167,10 +1282,13 @@ mod tests {
         storage.snapshot().validate()?;
 
         // We add quads and graph, then clear
-        storage.bulk_loader().load::<StorageError, StorageError>([
-            Ok(default_quad.into_owned()),
-            Ok(named_graph_quad.into_owned()),
-        ])?;
+        storage.bulk_loader().load::<StorageError, StorageError>(
+            [
+                Ok(default_quad.into_owned()),
+                Ok(named_graph_quad.into_owned()),
+            ],
+            &mut LoadReport::default(),
+        )?;
         storage.transaction(|mut writer| {
             writer.insert_named_graph(example2.into());
             Ok::<_, StorageError>(())
@@ -1,21 +1,29 @@
-use crate::model::{GraphNameRef, NamedOrBlankNodeRef, QuadRef};
+use crate::model::{GraphName, GraphNameRef, NamedOrBlankNode, NamedOrBlankNodeRef, QuadRef};
 pub use crate::storage::error::{CorruptionError, LoaderError, SerializerError, StorageError};
 use crate::storage::memory::{
     MemoryDecodingGraphIterator, MemoryStorage, MemoryStorageBulkLoader, MemoryStorageReader,
     MemoryStorageWriter, QuadIterator,
 };
-use crate::storage::numeric_encoder::{EncodedQuad, EncodedTerm, StrHash, StrLookup};
+use crate::storage::numeric_encoder::{Decoder, EncodedQuad, EncodedTerm, StrHash, StrLookup};
 #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
 use crate::storage::rocksdb::{
     RocksDbChainedDecodingQuadIterator, RocksDbDecodingGraphIterator, RocksDbStorage,
     RocksDbStorageBulkLoader, RocksDbStorageReader, RocksDbStorageWriter,
 };
+pub use crate::storage::snapshot::{write_snapshot, SnapshotReader, SnapshotRecord};
 use oxrdf::Quad;
+use oxsdatatypes::DateTime;
+use std::collections::{BTreeMap, HashMap};
 use std::error::Error;
 #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
 
-#[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+#[cfg_attr(
+    not(all(not(target_family = "wasm"), feature = "rocksdb")),
+    allow(dead_code)
+)]
 mod binary_encoder;
 mod error;
 mod memory;
@@ -25,11 +33,343 @@ mod rocksdb;
 #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
 mod rocksdb_wrapper;
 pub mod small_string;
+mod snapshot;
+
+/// A change done by a committed transaction.
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
+pub enum QuadChange {
+    /// The quad has been inserted
+    Insert(Quad),
+    /// The quad has been removed
+    Remove(Quad),
+    /// The named graph has been inserted while it did not exist, it is empty
+    InsertNamedGraph(NamedOrBlankNode),
+    /// The named graph has been removed, the removals of its quads are recorded before
+    RemoveNamedGraph(NamedOrBlankNode),
+}
+
+/// The changes done by a committed transaction, as recorded in the store history.
+///
+/// It is returned by [`Store::history_since`](crate::store::Store::history_since).
+#[derive(PartialEq, Debug, Clone)]
+pub struct HistoryEntry {
+    version: u64,
+    timestamp: DateTime,
+    changes: Vec<QuadChange>,
+    bulk_load: bool,
+}
+
+impl HistoryEntry {
+    /// The history version created by the transaction.
+    ///
+    /// Versions are consecutive, version 0 is the store state when the history has been enabled.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// When the transaction has been executed.
+    pub fn timestamp(&self) -> DateTime {
+        self.timestamp
+    }
+
+    /// The quads inserted and removed by the transaction in the order of their execution.
+    pub fn changes(&self) -> &[QuadChange] {
+        &self.changes
+    }
+
+    /// If the version has been created by a [`BulkLoader`](crate::store::BulkLoader).
+    ///
+    /// The quads written by bulk loads are not part of the [`changes`](Self::changes)
+    /// and [`Store::dataset_at_version`](crate::store::Store::dataset_at_version) fails for the versions before them.
+    pub fn is_bulk_load(&self) -> bool {
+        self.bulk_load
+    }
+
+    pub(crate) fn new(version: u64, timestamp: DateTime, changes: Vec<QuadChange>) -> Self {
+        Self {
+            version,
+            timestamp,
+            changes,
+            bulk_load: false,
+        }
+    }
+
+    pub(crate) fn set_bulk_load(&mut self) {
+        self.bulk_load = true;
+    }
+
+    pub(crate) fn set_version(&mut self, version: u64) {
+        self.version = version;
+    }
+
+    pub(crate) fn push(&mut self, change: QuadChange) {
+        self.changes.push(change);
+    }
+
+    pub(crate) fn into_changes(self) -> Vec<QuadChange> {
+        self.changes
+    }
+}
+
+/// A report of the quads written by a load operation.
+///
+/// It is returned by [`Store::load_from_reader`](crate::store::Store::load_from_reader)
+/// and by the [`BulkLoader`](crate::store::BulkLoader) load methods.
+///
+/// The loaded quads are only split between the new and the duplicate ones if each of them has been looked for in the store,
+/// this is not the case with the [`BulkLoader`](crate::store::BulkLoader) without [`with_duplicate_detection`](crate::store::BulkLoader::with_duplicate_detection).
+///
+/// ```
+/// use oxigraph::io::RdfFormat;
+/// use oxigraph::model::GraphNameRef;
+/// use oxigraph::store::Store;
+///
+/// let store = Store::new()?;
+/// let file = b"<http://example.com/s> <http://example.com/p> <http://example.com/o> .";
+/// let report = store.load_from_reader(RdfFormat::NTriples, file.as_ref())?;
+/// assert_eq!(report.loaded_quads(), 1);
+/// assert_eq!(report.new_quads(), Some(1));
+/// let report = store.load_from_reader(RdfFormat::NTriples, file.as_ref())?;
+/// assert_eq!(report.new_quads(), Some(0));
+/// assert_eq!(report.duplicate_quads(), Some(1));
+/// assert_eq!(
+///     report.graph(GraphNameRef::DefaultGraph).duplicate_quads(),
+///     Some(1)
+/// );
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[derive(Eq, PartialEq, Debug, Clone, Default)]
+pub struct LoadReport {
+    graphs: HashMap<GraphName, GraphLoadReport>,
+}
+
+impl LoadReport {
+    /// The number of quads read from the input, including the ones already in the store or repeated in the input.
+    pub fn loaded_quads(&self) -> u64 {
+        self.graphs.values().map(|g| g.loaded_quads()).sum()
+    }
+
+    /// The number of loaded quads that were not already in the store.
+    ///
+    /// `None` if some quads have been loaded without looking for them in the store.
+    pub fn new_quads(&self) -> Option<u64> {
+        self.graphs.values().map(|g| g.new_quads()).sum()
+    }
+
+    /// The number of loaded quads that were skipped because they were already in the store or repeated in the input.
+    ///
+    /// `None` if some quads have been loaded without looking for them in the store.
+    pub fn duplicate_quads(&self) -> Option<u64> {
+        self.graphs.values().map(|g| g.duplicate_quads()).sum()
+    }
+
+    /// The report for a given graph.
+    pub fn graph<'a>(&self, graph_name: impl Into<GraphNameRef<'a>>) -> GraphLoadReport {
+        self.graphs
+            .get(&graph_name.into().into_owned())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The reports of all the graphs the load operation has written to.
+    pub fn graphs(&self) -> impl Iterator<Item = (GraphNameRef<'_>, GraphLoadReport)> {
+        self.graphs
+            .iter()
+            .map(|(graph_name, report)| (graph_name.as_ref(), *report))
+    }
+
+    pub(crate) fn add(&mut self, graph_name: GraphNameRef<'_>, is_new: bool) {
+        let report = self.graphs.entry(graph_name.into_owned()).or_default();
+        if is_new {
+            report.new += 1;
+        } else {
+            report.duplicates += 1;
+        }
+    }
+
+    #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+    pub(crate) fn merge(&mut self, other: Self) {
+        for (graph_name, other) in other.graphs {
+            let report = self.graphs.entry(graph_name).or_default();
+            report.new += other.new;
+            report.duplicates += other.duplicates;
+            report.unchecked += other.unchecked;
+        }
+    }
+}
+
+/// The part of a [`LoadReport`] about a single graph.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Default)]
+pub struct GraphLoadReport {
+    new: u64,
+    duplicates: u64,
+    /// Quads written without looking for them in the store, they might have been already there
+    unchecked: u64,
+}
+
+impl GraphLoadReport {
+    /// The number of quads read from the input for the graph, including the ones already in the store or repeated in the input.
+    pub fn loaded_quads(self) -> u64 {
+        self.new + self.duplicates + self.unchecked
+    }
+
+    /// The number of quads loaded in the graph that were not already in the store.
+    ///
+    /// `None` if some quads have been loaded without looking for them in the store.
+    pub fn new_quads(self) -> Option<u64> {
+        (self.unchecked == 0).then_some(self.new)
+    }
+
+    /// The number of quads loaded in the graph that were skipped because they were already in the store or repeated in the input.
+    ///
+    /// `None` if some quads have been loaded without looking for them in the store.
+    pub fn duplicate_quads(self) -> Option<u64> {
+        (self.unchecked == 0).then_some(self.duplicates)
+    }
+}
+
+/// The disk usage of an on-disk store, returned by [`Store::size_statistics`](crate::store::Store::size_statistics).
+///
+/// The values are estimations provided by RocksDB.
+#[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+#[derive(Debug, Clone)]
+pub struct SizeStatistics {
+    directory_size: u64,
+    approximate_quads: u64,
+    indexes: Vec<IndexSizeStatistics>,
+}
+
+#[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+impl SizeStatistics {
+    /// The total size in bytes of the files in the database directory, including the logs and the obsolete files not deleted yet.
+    pub fn directory_size(&self) -> u64 {
+        self.directory_size
+    }
+
+    /// The approximate number of quads in the store.
+    pub fn approximate_quads(&self) -> u64 {
+        self.approximate_quads
+    }
+
+    /// The statistics of each index (i.e. RocksDB column family) of the store.
+    pub fn indexes(&self) -> &[IndexSizeStatistics] {
+        &self.indexes
+    }
+}
+
+/// The disk usage of an index of the store, i.e. a RocksDB column family.
+#[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+#[derive(Debug, Clone)]
+pub struct IndexSizeStatistics {
+    name: &'static str,
+    approximate_entries: u64,
+    live_files_size: u64,
+    total_files_size: u64,
+    estimated_live_data_size: u64,
+    memtables_size: u64,
+}
+
+#[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+impl IndexSizeStatistics {
+    /// The index name like `spog` or `id2str`.
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    /// The approximate number of entries in the index.
+    pub fn approximate_entries(&self) -> u64 {
+        self.approximate_entries
+    }
+
+    /// The size in bytes of the files of the index that are part of its current version.
+    pub fn live_files_size(&self) -> u64 {
+        self.live_files_size
+    }
+
+    /// The size in bytes of all the files of the index, including the ones only kept for the running reads.
+    pub fn total_files_size(&self) -> u64 {
+        self.total_files_size
+    }
+
+    /// The estimated size in bytes of the live data of the index.
+    ///
+    /// A live files size much greater than it means that a compaction would free space.
+    pub fn estimated_live_data_size(&self) -> u64 {
+        self.estimated_live_data_size
+    }
+
+    /// The size in bytes of the in-memory tables of the index that are not written to disk yet.
+    pub fn memtables_size(&self) -> u64 {
+        self.memtables_size
+    }
+}
+
+/// The metadata maintained by the store about a graph.
+///
+/// It is returned by [`Store::graph_metadata`](crate::store::Store::graph_metadata).
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct GraphMetadata {
+    created: Option<DateTime>,
+    modified: Option<DateTime>,
+    version: u64,
+    len: u64,
+    properties: BTreeMap<String, String>,
+}
+
+impl GraphMetadata {
+    /// When the graph has been created.
+    ///
+    /// The default graph is created by its first change.
+    /// It is not set for the graphs created by Oxigraph versions not tracking it.
+    pub fn created(&self) -> Option<DateTime> {
+        self.created
+    }
+
+    /// When the graph quads have been changed for the last time.
+    ///
+    /// It is not set if the graph has not been changed since its creation by a version of Oxigraph tracking it.
+    pub fn modified(&self) -> Option<DateTime> {
+        self.modified
+    }
+
+    /// A counter increased by each change to the graph quads since its creation.
+    ///
+    /// Unlike the modification time, it always changes when the graph is changed, even by two changes done at the same time.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// The number of quads in the graph.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The value of a property set with [`Store::set_graph_property`](crate::store::Store::set_graph_property).
+    pub fn property(&self, key: &str) -> Option<&str> {
+        self.properties.get(key).map(String::as_str)
+    }
+
+    /// All the properties set with [`Store::set_graph_property`](crate::store::Store::set_graph_property), sorted by key.
+    pub fn properties(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.properties
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+}
+
+type CommitListener = Arc<dyn Fn(&[QuadChange]) + Send + Sync>;
 
 /// Low level storage primitives
 #[derive(Clone)]
 pub struct Storage {
     kind: StorageKind,
+    commit_listeners: Arc<RwLock<Vec<CommitListener>>>,
+    /// Incremented after each committed transaction and bulk load
+    version: Arc<AtomicU64>,
 }
 
 #[derive(Clone)]
@@ -44,6 +384,8 @@ impl Storage {
     pub fn new() -> Result<Self, StorageError> {
         Ok(Self {
             kind: StorageKind::Memory(MemoryStorage::new()),
+            commit_listeners: Arc::default(),
+            version: Arc::default(),
         })
     }
 
@@ -51,6 +393,8 @@ impl Storage {
     pub fn open(path: &Path) -> Result<Self, StorageError> {
         Ok(Self {
             kind: StorageKind::RocksDb(RocksDbStorage::open(path)?),
+            commit_listeners: Arc::default(),
+            version: Arc::default(),
         })
     }
 
@@ -58,6 +402,8 @@ impl Storage {
     pub fn open_read_only(path: &Path) -> Result<Self, StorageError> {
         Ok(Self {
             kind: StorageKind::RocksDb(RocksDbStorage::open_read_only(path)?),
+            commit_listeners: Arc::default(),
+            version: Arc::default(),
         })
     }
 
@@ -77,18 +423,92 @@ impl Storage {
         &self,
         f: impl for<'a> Fn(StorageWriter<'a>) -> Result<T, E>,
     ) -> Result<T, E> {
-        match &self.kind {
+        let listeners = self
+            .commit_listeners
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        let record_history = self.is_history_enabled();
+        // The changes are only tracked if someone is listening to them or if they are recorded in the history
+        let changes =
+            (!listeners.is_empty() || record_history).then(|| Arc::new(Mutex::new(Vec::new())));
+        let start_writer = || {
+            if let Some(changes) = &changes {
+                // The transaction might be retried
+                changes
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .clear();
+            }
+            changes.clone()
+        };
+        let result = match &self.kind {
             #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
             StorageKind::RocksDb(storage) => storage.transaction(|transaction| {
                 f(StorageWriter {
                     kind: StorageWriterKind::RocksDb(transaction),
+                    changes: start_writer(),
+                    record_history,
+                    now: DateTime::now(),
                 })
             }),
             StorageKind::Memory(storage) => storage.transaction(|transaction| {
                 f(StorageWriter {
                     kind: StorageWriterKind::Memory(transaction),
+                    changes: start_writer(),
+                    record_history,
+                    now: DateTime::now(),
                 })
             }),
+        }?;
+        self.version.fetch_add(1, Ordering::Release);
+        if let Some(changes) = changes {
+            let changes = changes.lock().unwrap_or_else(PoisonError::into_inner);
+            if !changes.is_empty() && !listeners.is_empty() {
+                for listener in listeners {
+                    listener(&changes);
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// The number of transactions and bulk loads committed since the storage has been opened.
+    ///
+    /// It is read before taking a snapshot to know if some data computed on the snapshot is still up to date.
+    pub fn version(&self) -> u64 {
+        self.version.load(Ordering::Acquire)
+    }
+
+    /// Registers a function called with the changes done by each committed transaction
+    pub fn on_commit(&self, listener: impl Fn(&[QuadChange]) + Send + Sync + 'static) {
+        self.commit_listeners
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(Arc::new(listener));
+    }
+
+    /// Starts to record the changes done by the transactions in the store history, does nothing if it is already the case
+    #[cfg_attr(
+        any(target_family = "wasm", not(feature = "rocksdb")),
+        allow(clippy::unnecessary_wraps)
+    )]
+    pub fn enable_history(&self) -> Result<(), StorageError> {
+        match &self.kind {
+            #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+            StorageKind::RocksDb(storage) => storage.enable_history(),
+            StorageKind::Memory(storage) => {
+                storage.enable_history();
+                Ok(())
+            }
+        }
+    }
+
+    fn is_history_enabled(&self) -> bool {
+        match &self.kind {
+            #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+            StorageKind::RocksDb(storage) => storage.is_history_enabled(),
+            StorageKind::Memory(storage) => storage.is_history_enabled(),
         }
     }
 
@@ -110,6 +530,26 @@ impl Storage {
         }
     }
 
+    #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+    pub fn compact_graph(&self, graph_name: GraphNameRef<'_>) -> Result<(), StorageError> {
+        match &self.kind {
+            #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+            StorageKind::RocksDb(storage) => storage.compact_graph(&graph_name.into()),
+            StorageKind::Memory(_) => Ok(()),
+        }
+    }
+
+    #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+    pub fn size_statistics(&self) -> Result<SizeStatistics, StorageError> {
+        match &self.kind {
+            #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+            StorageKind::RocksDb(storage) => storage.size_statistics(),
+            StorageKind::Memory(_) => Err(StorageError::Other(
+                "Size statistics are only available for on-disk databases".into(),
+            )),
+        }
+    }
+
     #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
     pub fn backup(&self, target_directory: &Path) -> Result<(), StorageError> {
         match &self.kind {
@@ -121,23 +561,44 @@ impl Storage {
         }
     }
 
+    #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+    pub fn incremental_backup(
+        &self,
+        target_directory: &Path,
+        previous_backup: &Path,
+    ) -> Result<(), StorageError> {
+        match &self.kind {
+            #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+            StorageKind::RocksDb(storage) => {
+                storage.incremental_backup(target_directory, previous_backup)
+            }
+            StorageKind::Memory(_) => Err(StorageError::Other(
+                "It is not possible to backup an in-memory database".into(),
+            )),
+        }
+    }
+
     pub fn bulk_loader(&self) -> StorageBulkLoader {
         match &self.kind {
             #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
             StorageKind::RocksDb(storage) => StorageBulkLoader {
                 kind: StorageBulkLoaderKind::RocksDb(storage.bulk_loader()),
+                storage: self.clone(),
             },
             StorageKind::Memory(storage) => StorageBulkLoader {
                 kind: StorageBulkLoaderKind::Memory(storage.bulk_loader()),
+                storage: self.clone(),
             },
         }
     }
 }
 
+#[derive(Clone)]
 pub struct StorageReader {
     kind: StorageReaderKind,
 }
 
+#[derive(Clone)]
 enum StorageReaderKind {
     #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
     RocksDb(RocksDbStorageReader),
@@ -218,6 +679,35 @@ impl StorageReader {
         }
     }
 
+    pub fn graph_metadata(
+        &self,
+        graph_name: &EncodedTerm,
+    ) -> Result<Option<GraphMetadata>, StorageError> {
+        match &self.kind {
+            #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+            StorageReaderKind::RocksDb(reader) => reader.graph_metadata(graph_name),
+            StorageReaderKind::Memory(reader) => Ok(reader.graph_metadata(graph_name)),
+        }
+    }
+
+    /// The last version of the store history, `None` if the history is not enabled
+    pub fn history_version(&self) -> Result<Option<u64>, StorageError> {
+        match &self.kind {
+            #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+            StorageReaderKind::RocksDb(reader) => reader.history_version(),
+            StorageReaderKind::Memory(reader) => Ok(reader.history_version()),
+        }
+    }
+
+    /// The changes done to create the given history version, `None` if the version does not exist
+    pub fn history_entry(&self, version: u64) -> Result<Option<HistoryEntry>, StorageError> {
+        match &self.kind {
+            #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+            StorageReaderKind::RocksDb(reader) => reader.history_entry(version),
+            StorageReaderKind::Memory(reader) => Ok(reader.history_entry(version)),
+        }
+    }
+
     /// Validates that all the storage invariants held in the data
     pub fn validate(&self) -> Result<(), StorageError> {
         match &self.kind {
@@ -226,6 +716,19 @@ impl StorageReader {
             StorageReaderKind::Memory(reader) => reader.validate(),
         }
     }
+
+    /// Reads the beginning of the indexes to load them in the caches
+    #[cfg_attr(
+        not(all(not(target_family = "wasm"), feature = "rocksdb")),
+        allow(unused_variables, clippy::unused_self, clippy::unnecessary_wraps)
+    )]
+    pub fn warm_up(&self, max_size: Option<usize>) -> Result<(), StorageError> {
+        match &self.kind {
+            #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+            StorageReaderKind::RocksDb(reader) => reader.warm_up(max_size),
+            StorageReaderKind::Memory(_) => Ok(()), // Everything is already in memory
+        }
+    }
 }
 
 pub struct DecodingQuadIterator {
@@ -284,6 +787,12 @@ impl StrLookup for StorageReader {
 
 pub struct StorageWriter<'a> {
     kind: StorageWriterKind<'a>,
+    /// The changes done by the transaction if they are tracked
+    changes: Option<Arc<Mutex<Vec<QuadChange>>>>,
+    /// If the changes are written to the store history
+    record_history: bool,
+    /// The modification time of the graphs changed by the transaction and the timestamp of its history entry
+    now: DateTime,
 }
 
 enum StorageWriterKind<'a> {
@@ -307,33 +816,55 @@ impl StorageWriter<'_> {
     }
 
     pub fn insert(&mut self, quad: QuadRef<'_>) -> Result<bool, StorageError> {
-        match &mut self.kind {
+        let inserted = match &mut self.kind {
             #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
-            StorageWriterKind::RocksDb(writer) => writer.insert(quad),
-            StorageWriterKind::Memory(writer) => Ok(writer.insert(quad)),
+            StorageWriterKind::RocksDb(writer) => writer.insert(quad)?,
+            StorageWriterKind::Memory(writer) => writer.insert(quad),
+        };
+        if inserted {
+            self.record(|_| Ok(vec![QuadChange::Insert(quad.into_owned())]))?;
+            self.update_graph_metadata(&quad.graph_name.into(), |metadata| {
+                metadata.len += 1;
+            })?;
         }
+        Ok(inserted)
     }
 
     pub fn insert_named_graph(
         &mut self,
         graph_name: NamedOrBlankNodeRef<'_>,
     ) -> Result<bool, StorageError> {
-        match &mut self.kind {
+        let inserted = match &mut self.kind {
             #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
-            StorageWriterKind::RocksDb(writer) => writer.insert_named_graph(graph_name),
-            StorageWriterKind::Memory(writer) => Ok(writer.insert_named_graph(graph_name)),
+            StorageWriterKind::RocksDb(writer) => writer.insert_named_graph(graph_name)?,
+            StorageWriterKind::Memory(writer) => writer.insert_named_graph(graph_name),
+        };
+        if inserted {
+            self.record(|_| Ok(vec![QuadChange::InsertNamedGraph(graph_name.into_owned())]))?;
+            let metadata = self.new_graph_metadata();
+            self.set_graph_metadata(&graph_name.into(), Some(metadata));
         }
+        Ok(inserted)
     }
 
     pub fn remove(&mut self, quad: QuadRef<'_>) -> Result<bool, StorageError> {
-        match &mut self.kind {
+        let removed = match &mut self.kind {
             #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
-            StorageWriterKind::RocksDb(writer) => writer.remove(quad),
-            StorageWriterKind::Memory(writer) => Ok(writer.remove(quad)),
+            StorageWriterKind::RocksDb(writer) => writer.remove(quad)?,
+            StorageWriterKind::Memory(writer) => writer.remove(quad),
+        };
+        if removed {
+            self.record(|_| Ok(vec![QuadChange::Remove(quad.into_owned())]))?;
+            self.update_graph_metadata(&quad.graph_name.into(), |metadata| {
+                metadata.len = metadata.len.saturating_sub(1);
+            })?;
         }
+        Ok(removed)
     }
 
     pub fn clear_graph(&mut self, graph_name: GraphNameRef<'_>) -> Result<(), StorageError> {
+        self.record(|writer| writer.quads_to_remove(Some(&graph_name.into()), |_| true))?;
+        self.empty_graph_metadata(&graph_name.into())?;
         match &mut self.kind {
             #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
             StorageWriterKind::RocksDb(writer) => writer.clear_graph(graph_name),
@@ -345,6 +876,14 @@ impl StorageWriter<'_> {
     }
 
     pub fn clear_all_named_graphs(&mut self) -> Result<(), StorageError> {
+        self.record(|writer| writer.quads_to_remove(None, |q| !q.graph_name.is_default_graph()))?;
+        for graph_name in self
+            .reader()
+            .named_graphs()
+            .collect::<Result<Vec<_>, _>>()?
+        {
+            self.empty_graph_metadata(&graph_name)?;
+        }
         match &mut self.kind {
             #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
             StorageWriterKind::RocksDb(writer) => writer.clear_all_named_graphs(),
@@ -356,6 +895,15 @@ impl StorageWriter<'_> {
     }
 
     pub fn clear_all_graphs(&mut self) -> Result<(), StorageError> {
+        self.record(|writer| writer.quads_to_remove(None, |_| true))?;
+        for graph_name in self
+            .reader()
+            .named_graphs()
+            .collect::<Result<Vec<_>, _>>()?
+        {
+            self.empty_graph_metadata(&graph_name)?;
+        }
+        self.empty_graph_metadata(&EncodedTerm::DefaultGraph)?;
         match &mut self.kind {
             #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
             StorageWriterKind::RocksDb(writer) => writer.clear_all_graphs(),
@@ -370,14 +918,29 @@ impl StorageWriter<'_> {
         &mut self,
         graph_name: NamedOrBlankNodeRef<'_>,
     ) -> Result<bool, StorageError> {
-        match &mut self.kind {
+        self.record(|writer| writer.quads_to_remove(Some(&graph_name.into()), |_| true))?;
+        let removed = match &mut self.kind {
             #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
-            StorageWriterKind::RocksDb(writer) => writer.remove_named_graph(graph_name),
-            StorageWriterKind::Memory(writer) => Ok(writer.remove_named_graph(graph_name)),
+            StorageWriterKind::RocksDb(writer) => writer.remove_named_graph(graph_name)?,
+            StorageWriterKind::Memory(writer) => writer.remove_named_graph(graph_name),
+        };
+        if removed {
+            self.record(|_| Ok(vec![QuadChange::RemoveNamedGraph(graph_name.into_owned())]))?;
+            self.set_graph_metadata(&graph_name.into(), None);
         }
+        Ok(removed)
     }
 
     pub fn remove_all_named_graphs(&mut self) -> Result<(), StorageError> {
+        self.record(|writer| writer.quads_to_remove(None, |q| !q.graph_name.is_default_graph()))?;
+        self.record(Self::named_graphs_to_remove)?;
+        for graph_name in self
+            .reader()
+            .named_graphs()
+            .collect::<Result<Vec<_>, _>>()?
+        {
+            self.set_graph_metadata(&graph_name, None);
+        }
         match &mut self.kind {
             #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
             StorageWriterKind::RocksDb(writer) => writer.remove_all_named_graphs(),
@@ -389,6 +952,16 @@ impl StorageWriter<'_> {
     }
 
     pub fn clear(&mut self) -> Result<(), StorageError> {
+        self.record(|writer| writer.quads_to_remove(None, |_| true))?;
+        self.record(Self::named_graphs_to_remove)?;
+        for graph_name in self
+            .reader()
+            .named_graphs()
+            .collect::<Result<Vec<_>, _>>()?
+        {
+            self.set_graph_metadata(&graph_name, None);
+        }
+        self.empty_graph_metadata(&EncodedTerm::DefaultGraph)?;
         match &mut self.kind {
             #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
             StorageWriterKind::RocksDb(writer) => writer.clear(),
@@ -398,11 +971,168 @@ impl StorageWriter<'_> {
             }
         }
     }
+
+    /// Sets a property of the graph metadata, removes it if `value` is `None`.
+    ///
+    /// The named graph is created if it does not exist.
+    pub fn set_graph_property(
+        &mut self,
+        graph_name: GraphNameRef<'_>,
+        key: &str,
+        value: Option<&str>,
+    ) -> Result<(), StorageError> {
+        match graph_name {
+            GraphNameRef::NamedNode(graph_name) => {
+                self.insert_named_graph(graph_name.into())?;
+            }
+            GraphNameRef::BlankNode(graph_name) => {
+                self.insert_named_graph(graph_name.into())?;
+            }
+            GraphNameRef::DefaultGraph => (),
+        }
+        let new_metadata = self.new_graph_metadata();
+        let metadata = self
+            .graph_metadata_mut(&graph_name.into())?
+            .get_or_insert(new_metadata);
+        if let Some(value) = value {
+            metadata.properties.insert(key.into(), value.into());
+        } else {
+            metadata.properties.remove(key);
+        }
+        Ok(())
+    }
+
+    /// Changes the metadata of a graph whose quads have been modified, creating it if the graph is new
+    fn update_graph_metadata(
+        &mut self,
+        graph_name: &EncodedTerm,
+        update: impl FnOnce(&mut GraphMetadata),
+    ) -> Result<(), StorageError> {
+        let now = self.now;
+        let new_metadata = self.new_graph_metadata();
+        let metadata = self
+            .graph_metadata_mut(graph_name)?
+            .get_or_insert(new_metadata);
+        update(metadata);
+        metadata.modified = Some(now);
+        metadata.version += 1;
+        Ok(())
+    }
+
+    /// Sets the number of quads of an existing graph to zero
+    fn empty_graph_metadata(&mut self, graph_name: &EncodedTerm) -> Result<(), StorageError> {
+        let now = self.now;
+        if let Some(metadata) = self.graph_metadata_mut(graph_name)? {
+            if !metadata.is_empty() {
+                metadata.len = 0;
+                metadata.modified = Some(now);
+                metadata.version += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn new_graph_metadata(&self) -> GraphMetadata {
+        GraphMetadata {
+            created: Some(self.now),
+            ..GraphMetadata::default()
+        }
+    }
+
+    /// The graph metadata as changed by the transaction, `None` if the graph does not exist
+    ///
+    /// The changes are written to the storage once per graph when the transaction is committed.
+    fn graph_metadata_mut(
+        &mut self,
+        graph_name: &EncodedTerm,
+    ) -> Result<&mut Option<GraphMetadata>, StorageError> {
+        match &mut self.kind {
+            #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+            StorageWriterKind::RocksDb(writer) => writer.graph_metadata_mut(graph_name),
+            StorageWriterKind::Memory(writer) => Ok(writer.graph_metadata_mut(graph_name)),
+        }
+    }
+
+    /// Replaces the graph metadata, removes it if `metadata` is `None`
+    fn set_graph_metadata(&mut self, graph_name: &EncodedTerm, metadata: Option<GraphMetadata>) {
+        match &mut self.kind {
+            #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+            StorageWriterKind::RocksDb(writer) => writer.set_graph_metadata(graph_name, metadata),
+            StorageWriterKind::Memory(writer) => writer.set_graph_metadata(graph_name, metadata),
+        }
+    }
+
+    /// Adds some changes to the transaction log and to the store history, they are only computed if the changes are tracked
+    fn record(
+        &mut self,
+        changes: impl FnOnce(&Self) -> Result<Vec<QuadChange>, StorageError>,
+    ) -> Result<(), StorageError> {
+        let Some(log) = self.changes.clone() else {
+            return Ok(());
+        };
+        let changes = changes(self)?;
+        if self.record_history {
+            for change in &changes {
+                match &mut self.kind {
+                    #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+                    StorageWriterKind::RocksDb(writer) => {
+                        writer.append_history(change, self.now)?
+                    }
+                    StorageWriterKind::Memory(writer) => writer.append_history(change, self.now),
+                }
+            }
+        }
+        log.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .extend(changes);
+        Ok(())
+    }
+
+    /// Adds to the store history a version marking a bulk load, its quads are not recorded
+    fn record_bulk_load(&mut self) -> Result<(), StorageError> {
+        if self.record_history {
+            match &mut self.kind {
+                #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+                StorageWriterKind::RocksDb(writer) => writer.append_bulk_load_history(self.now)?,
+                StorageWriterKind::Memory(writer) => writer.append_bulk_load_history(self.now),
+            }
+        }
+        Ok(())
+    }
+
+    fn quads_to_remove(
+        &self,
+        graph_name: Option<&EncodedTerm>,
+        filter: impl Fn(&EncodedQuad) -> bool,
+    ) -> Result<Vec<QuadChange>, StorageError> {
+        let reader = self.reader();
+        let mut changes = Vec::new();
+        for quad in reader.quads_for_pattern(None, None, None, graph_name) {
+            let quad = quad?;
+            if filter(&quad) {
+                changes.push(QuadChange::Remove(reader.decode_quad(&quad)?));
+            }
+        }
+        Ok(changes)
+    }
+
+    fn named_graphs_to_remove(&self) -> Result<Vec<QuadChange>, StorageError> {
+        let reader = self.reader();
+        reader
+            .named_graphs()
+            .map(|graph_name| {
+                Ok(QuadChange::RemoveNamedGraph(
+                    reader.decode_named_or_blank_node(&graph_name?)?,
+                ))
+            })
+            .collect()
+    }
 }
 
 #[must_use]
 pub struct StorageBulkLoader {
     kind: StorageBulkLoaderKind,
+    storage: Storage,
 }
 
 enum StorageBulkLoaderKind {
@@ -417,9 +1147,11 @@ impl StorageBulkLoader {
             #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
             StorageBulkLoaderKind::RocksDb(loader) => Self {
                 kind: StorageBulkLoaderKind::RocksDb(loader.with_num_threads(num_threads)),
+                storage: self.storage,
             },
             StorageBulkLoaderKind::Memory(loader) => Self {
                 kind: StorageBulkLoaderKind::Memory(loader),
+                storage: self.storage,
             },
         }
     }
@@ -432,9 +1164,11 @@ impl StorageBulkLoader {
                 kind: StorageBulkLoaderKind::RocksDb(
                     loader.with_max_memory_size_in_megabytes(max_memory_size),
                 ),
+                storage: self.storage,
             },
             StorageBulkLoaderKind::Memory(loader) => Self {
                 kind: StorageBulkLoaderKind::Memory(loader),
+                storage: self.storage,
             },
         }
     }
@@ -444,9 +1178,26 @@ impl StorageBulkLoader {
             #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
             StorageBulkLoaderKind::RocksDb(loader) => Self {
                 kind: StorageBulkLoaderKind::RocksDb(loader.on_progress(callback)),
+                storage: self.storage,
             },
             StorageBulkLoaderKind::Memory(loader) => Self {
                 kind: StorageBulkLoaderKind::Memory(loader.on_progress(callback)),
+                storage: self.storage,
+            },
+        }
+    }
+
+    pub fn with_duplicate_detection(self) -> Self {
+        match self.kind {
+            #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+            StorageBulkLoaderKind::RocksDb(loader) => Self {
+                kind: StorageBulkLoaderKind::RocksDb(loader.with_duplicate_detection()),
+                storage: self.storage,
+            },
+            // The in-memory loader always detects duplicates
+            StorageBulkLoaderKind::Memory(loader) => Self {
+                kind: StorageBulkLoaderKind::Memory(loader),
+                storage: self.storage,
             },
         }
     }
@@ -455,11 +1206,54 @@ impl StorageBulkLoader {
     pub fn load<EI, EO: From<StorageError> + From<EI>>(
         &self,
         quads: impl IntoIterator<Item = Result<Quad, EI>>,
-    ) -> Result<(), EO> {
-        match &self.kind {
+    ) -> Result<LoadReport, EO> {
+        let mut report = LoadReport::default();
+        let result: Result<(), EO> = match &self.kind {
             #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
-            StorageBulkLoaderKind::RocksDb(loader) => loader.load(quads),
-            StorageBulkLoaderKind::Memory(loader) => loader.load(quads),
-        }
+            StorageBulkLoaderKind::RocksDb(loader) => loader.load(quads, &mut report),
+            StorageBulkLoaderKind::Memory(loader) => loader.load(quads, &mut report),
+        };
+        // Even a failed load might have written some quads
+        self.storage.version.fetch_add(1, Ordering::Release);
+        // The bulk loaders do not maintain the graph metadata, we add the quads they have written to it
+        let metadata_result = self.storage.transaction(|mut writer| {
+            if report.new_quads() != Some(0) {
+                writer.record_bulk_load()?;
+            }
+            for (graph_name, graph_report) in report.graphs() {
+                let graph_name = EncodedTerm::from(graph_name);
+                if let Some(new_quads) = graph_report.new_quads() {
+                    if new_quads > 0 {
+                        writer.update_graph_metadata(&graph_name, |metadata| {
+                            metadata.len += new_quads;
+                        })?;
+                    }
+                } else {
+                    // Some loaded quads might have already been in the graph, we count its quads again
+                    let mut len = 0;
+                    for quad in
+                        writer
+                            .reader()
+                            .quads_for_pattern(None, None, None, Some(&graph_name))
+                    {
+                        quad?;
+                        len += 1;
+                    }
+                    if !writer
+                        .graph_metadata_mut(&graph_name)?
+                        .as_ref()
+                        .is_some_and(|metadata| metadata.len == len)
+                    {
+                        writer.update_graph_metadata(&graph_name, |metadata| {
+                            metadata.len = len;
+                        })?;
+                    }
+                }
+            }
+            Ok::<_, StorageError>(())
+        });
+        result?;
+        metadata_result?;
+        Ok(report)
     }
 }
@@ -1,9 +1,9 @@
-use crate::model::{GraphNameRef, NamedOrBlankNodeRef, Quad, QuadRef, TermRef};
+use crate::model::{GraphName, GraphNameRef, NamedOrBlankNodeRef, Quad, QuadRef, TermRef};
 use crate::storage::binary_encoder::{
-    decode_term, encode_term, encode_term_pair, encode_term_quad, encode_term_triple,
-    write_gosp_quad, write_gpos_quad, write_gspo_quad, write_osp_quad, write_ospg_quad,
-    write_pos_quad, write_posg_quad, write_spo_quad, write_spog_quad, write_term, QuadEncoding,
-    LATEST_STORAGE_VERSION, WRITTEN_TERM_MAX_SIZE,
+    decode_graph_metadata, decode_term, encode_graph_metadata, encode_term, encode_term_pair,
+    encode_term_quad, encode_term_triple, write_gosp_quad, write_gpos_quad, write_gspo_quad,
+    write_osp_quad, write_ospg_quad, write_pos_quad, write_posg_quad, write_spo_quad,
+    write_spog_quad, write_term, QuadEncoding, LATEST_STORAGE_VERSION, WRITTEN_TERM_MAX_SIZE,
 };
 pub use crate::storage::error::{CorruptionError, StorageError};
 use crate::storage::numeric_encoder::{
@@ -12,13 +12,22 @@ use crate::storage::numeric_encoder::{
 use crate::storage::rocksdb_wrapper::{
     ColumnFamily, ColumnFamilyDefinition, Db, Iter, Reader, Transaction,
 };
-use rustc_hash::{FxBuildHasher, FxHashSet};
+use crate::storage::{
+    GraphLoadReport, GraphMetadata, HistoryEntry, IndexSizeStatistics, LoadReport, QuadChange,
+    SizeStatistics,
+};
+use oxsdatatypes::DateTime;
+use rustc_hash::{FxBuildHasher, FxHashMap, FxHashSet};
+use std::cell::Cell;
+use std::collections::hash_map::Entry;
 use std::collections::{HashMap, VecDeque};
 use std::error::Error;
 use std::hash::BuildHasherDefault;
 use std::mem::{swap, take};
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
 use std::{io, thread};
 
 const ID2STR_CF: &str = "id2str";
@@ -32,8 +41,23 @@ const DSPO_CF: &str = "dspo";
 const DPOS_CF: &str = "dpos";
 const DOSP_CF: &str = "dosp";
 const GRAPHS_CF: &str = "graphs";
+const GRAPH_METADATA_CF: &str = "graphmeta";
+const HISTORY_CF: &str = "history";
 const DEFAULT_CF: &str = "default";
 const DEFAULT_BULK_LOAD_BATCH_SIZE: usize = 1_000_000;
+/// Key in the default column family of the last history version, only set if the history is enabled
+const HISTORY_VERSION_KEY: &[u8] = b"history";
+/// Key in the default column family without value, written when graphs are created or removed to lock their list
+const GRAPH_LIST_KEY: &[u8] = b"graphs";
+/// Key in the default column family without value, written by each transaction changing a graph to lock the whole dataset
+const DATASET_LOCK_KEY: &[u8] = b"dataset";
+const HISTORY_INSERT: u8 = 1;
+const HISTORY_REMOVE: u8 = 2;
+/// Change kind without quad marking a bulk load
+const HISTORY_BULK_LOAD: u8 = 3;
+/// Change kinds followed by a graph name instead of a quad
+const HISTORY_INSERT_NAMED_GRAPH: u8 = 4;
+const HISTORY_REMOVE_NAMED_GRAPH: u8 = 5;
 
 /// Low level storage primitives
 #[derive(Clone)]
@@ -51,15 +75,20 @@ pub struct RocksDbStorage {
     dpos_cf: ColumnFamily,
     dosp_cf: ColumnFamily,
     graphs_cf: ColumnFamily,
+    graph_metadata_cf: ColumnFamily,
+    history_cf: ColumnFamily,
+    history_enabled: Arc<AtomicBool>,
+    /// Held by the bulk loaders detecting duplicates while they look for quads and ingest their files
+    bulk_load_ingestion_lock: Arc<Mutex<()>>,
 }
 
 impl RocksDbStorage {
     pub fn open(path: &Path) -> Result<Self, StorageError> {
-        Self::setup(Db::open_read_write(path, Self::column_families())?)
+        Self::setup(Db::open_read_write(path, Self::column_families())?, false)
     }
 
     pub fn open_read_only(path: &Path) -> Result<Self, StorageError> {
-        Self::setup(Db::open_read_only(path, Self::column_families())?)
+        Self::setup(Db::open_read_only(path, Self::column_families())?, true)
     }
 
     fn column_families() -> Vec<ColumnFamilyDefinition> {
@@ -130,10 +159,22 @@ impl RocksDbStorage {
                 min_prefix_size: 17, // named or blank node start
                 unordered_writes: false,
             },
+            ColumnFamilyDefinition {
+                name: GRAPH_METADATA_CF,
+                use_iter: false,
+                min_prefix_size: 0,
+                unordered_writes: false,
+            },
+            ColumnFamilyDefinition {
+                name: HISTORY_CF,
+                use_iter: true,
+                min_prefix_size: 8, // history version
+                unordered_writes: false,
+            },
         ]
     }
 
-    fn setup(db: Db) -> Result<Self, StorageError> {
+    fn setup(db: Db, read_only: bool) -> Result<Self, StorageError> {
         let this = Self {
             default_cf: db.column_family(DEFAULT_CF)?,
             id2str_cf: db.column_family(ID2STR_CF)?,
@@ -147,9 +188,29 @@ impl RocksDbStorage {
             dpos_cf: db.column_family(DPOS_CF)?,
             dosp_cf: db.column_family(DOSP_CF)?,
             graphs_cf: db.column_family(GRAPHS_CF)?,
+            graph_metadata_cf: db.column_family(GRAPH_METADATA_CF)?,
+            history_cf: db.column_family(HISTORY_CF)?,
+            history_enabled: Arc::default(),
             db,
+            bulk_load_ingestion_lock: Arc::default(),
         };
-        this.migrate()?;
+        if read_only {
+            // Read-only instances can't migrate the database
+            if let Some(version) = this.read_version()? {
+                if version < LATEST_STORAGE_VERSION {
+                    return Err(StorageError::Other(format!(
+                        "The RocksDB database is using the older version {version}, it must be opened once in read-write mode to be migrated to the version {LATEST_STORAGE_VERSION}"
+                    ).into()));
+                }
+                Self::check_version(version)?;
+            }
+        } else {
+            this.migrate()?;
+        }
+        this.history_enabled.store(
+            this.snapshot().history_version()?.is_some(),
+            Ordering::Release,
+        );
         Ok(this)
     }
 
@@ -178,7 +239,38 @@ impl RocksDbStorage {
             version = 1;
             self.update_version(version)?;
         }
+        if version == 1 {
+            // We migrate to v2: the graph metadata are computed from the graph content
+            let snapshot = self.snapshot();
+            let mut graph_lens = FxHashMap::default();
+            for graph_name in snapshot.named_graphs() {
+                graph_lens.insert(graph_name?, 0);
+            }
+            for quad in snapshot.quads() {
+                *graph_lens.entry(quad?.graph_name).or_insert(0) += 1;
+            }
+            for (graph_name, len) in graph_lens {
+                self.db.insert(
+                    &self.graph_metadata_cf,
+                    &encode_term(&graph_name),
+                    &encode_graph_metadata(&GraphMetadata {
+                        len,
+                        ..GraphMetadata::default()
+                    }),
+                )?;
+            }
+            version = 2;
+            self.update_version(version)?;
+        }
+        if version == 2 {
+            // We migrate to v3: the history column family is created when opening the database
+            version = 3;
+            self.update_version(version)?;
+        }
+        Self::check_version(version)
+    }
 
+    fn check_version(version: u64) -> Result<(), StorageError> {
         match version {
             _ if version < LATEST_STORAGE_VERSION => Err(CorruptionError::msg(format!(
                 "The RocksDB database is using the outdated encoding version {version}. Automated migration is not supported, please dump the store dataset using a compatible Oxigraph version and load it again using the current version"
@@ -193,16 +285,23 @@ impl RocksDbStorage {
     }
 
     fn ensure_version(&self) -> Result<u64, StorageError> {
-        Ok(
-            if let Some(version) = self.db.get(&self.default_cf, b"oxversion")? {
-                u64::from_be_bytes(version.as_ref().try_into().map_err(|e| {
-                    CorruptionError::new(format!("Error while parsing the version key: {e}"))
-                })?)
-            } else {
-                self.update_version(LATEST_STORAGE_VERSION)?;
-                LATEST_STORAGE_VERSION
-            },
-        )
+        Ok(if let Some(version) = self.read_version()? {
+            version
+        } else {
+            self.update_version(LATEST_STORAGE_VERSION)?;
+            LATEST_STORAGE_VERSION
+        })
+    }
+
+    fn read_version(&self) -> Result<Option<u64>, StorageError> {
+        let Some(version) = self.db.get(&self.default_cf, b"oxversion")? else {
+            return Ok(None);
+        };
+        Ok(Some(u64::from_be_bytes(
+            version.as_ref().try_into().map_err(|e| {
+                CorruptionError::new(format!("Error while parsing the version key: {e}"))
+            })?,
+        )))
     }
 
     fn update_version(&self, version: u64) -> Result<(), StorageError> {
@@ -215,6 +314,7 @@ impl RocksDbStorage {
         RocksDbStorageReader {
             reader: self.db.snapshot(),
             storage: self.clone(),
+            all_graphs_locked: None,
         }
     }
 
@@ -222,15 +322,67 @@ impl RocksDbStorage {
         &self,
         f: impl for<'a> Fn(RocksDbStorageWriter<'a>) -> Result<T, E>,
     ) -> Result<T, E> {
-        self.db.transaction(|transaction| {
-            f(RocksDbStorageWriter {
+        // RocksDB only detects write-write conflicts.
+        // To avoid write skews, the reads lock the metadata of the graphs they read from
+        // and every write to a graph also writes its metadata.
+        // The reads from all the graphs lock a single dataset key written by every transaction changing a graph.
+        self.db.transaction(|mut transaction| {
+            let mut graph_metadata = FxHashMap::default();
+            let result = f(RocksDbStorageWriter {
                 buffer: Vec::new(),
-                transaction,
+                transaction: transaction.clone(),
                 storage: self,
-            })
+                history: None,
+                graph_metadata: &mut graph_metadata,
+                all_graphs_locked: Rc::default(),
+            })?;
+            if !graph_metadata.is_empty() {
+                transaction.insert_empty(&self.default_cf, DATASET_LOCK_KEY)?;
+            }
+            // The graph metadata is written once per graph touched by the transaction
+            for (graph_name, metadata) in graph_metadata {
+                let key = encode_term(&graph_name);
+                if transaction
+                    .get_for_update(&self.graph_metadata_cf, &key)?
+                    .is_some()
+                    != metadata.is_some()
+                {
+                    // The graph is created or removed
+                    transaction.insert_empty(&self.default_cf, GRAPH_LIST_KEY)?;
+                }
+                if let Some(metadata) = metadata {
+                    transaction.insert(
+                        &self.graph_metadata_cf,
+                        &key,
+                        &encode_graph_metadata(&metadata),
+                    )?;
+                } else {
+                    transaction.remove(&self.graph_metadata_cf, &key)?;
+                }
+            }
+            Ok(result)
         })
     }
 
+    pub fn enable_history(&self) -> Result<(), StorageError> {
+        self.transaction(|mut writer| {
+            if writer.reader().history_version()?.is_none() {
+                writer.transaction.insert(
+                    &self.default_cf,
+                    HISTORY_VERSION_KEY,
+                    &0_u64.to_be_bytes(),
+                )?;
+            }
+            Ok::<_, StorageError>(())
+        })?;
+        self.history_enabled.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    pub fn is_history_enabled(&self) -> bool {
+        self.history_enabled.load(Ordering::Acquire)
+    }
+
     pub fn flush(&self) -> Result<(), StorageError> {
         self.db.flush()
     }
@@ -246,39 +398,147 @@ impl RocksDbStorage {
         self.db.compact(&self.dspo_cf)?;
         self.db.compact(&self.dpos_cf)?;
         self.db.compact(&self.dosp_cf)?;
+        self.db.compact(&self.graph_metadata_cf)?;
+        self.db.compact(&self.history_cf)?;
         self.db.compact(&self.id2str_cf)
     }
 
+    /// Compacts the quads of the given graph: the whole `d*` indexes for the default graph and the graph key range of the `g*` indexes for a named graph
+    pub fn compact_graph(&self, graph_name: &EncodedTerm) -> Result<(), StorageError> {
+        if graph_name.is_default_graph() {
+            self.db.compact(&self.dspo_cf)?;
+            self.db.compact(&self.dpos_cf)?;
+            return self.db.compact(&self.dosp_cf);
+        }
+        let start = encode_term(graph_name);
+        let end = next_prefix(&start);
+        for column_family in [&self.gspo_cf, &self.gpos_cf, &self.gosp_cf] {
+            self.db
+                .compact_range(column_family, Some(start.as_slice()), end.as_deref())?;
+        }
+        Ok(())
+    }
+
+    pub fn size_statistics(&self) -> Result<SizeStatistics, StorageError> {
+        let mut indexes = Vec::new();
+        for (name, column_family) in [
+            (ID2STR_CF, &self.id2str_cf),
+            (SPOG_CF, &self.spog_cf),
+            (POSG_CF, &self.posg_cf),
+            (OSPG_CF, &self.ospg_cf),
+            (GSPO_CF, &self.gspo_cf),
+            (GPOS_CF, &self.gpos_cf),
+            (GOSP_CF, &self.gosp_cf),
+            (DSPO_CF, &self.dspo_cf),
+            (DPOS_CF, &self.dpos_cf),
+            (DOSP_CF, &self.dosp_cf),
+            (GRAPHS_CF, &self.graphs_cf),
+            (GRAPH_METADATA_CF, &self.graph_metadata_cf),
+            (HISTORY_CF, &self.history_cf),
+            (DEFAULT_CF, &self.default_cf),
+        ] {
+            let property = |name: &str| {
+                Ok::<_, StorageError>(self.db.property_int(column_family, name)?.unwrap_or(0))
+            };
+            indexes.push(IndexSizeStatistics {
+                name,
+                approximate_entries: property("rocksdb.estimate-num-keys")?,
+                live_files_size: property("rocksdb.live-sst-files-size")?,
+                total_files_size: property("rocksdb.total-sst-files-size")?,
+                estimated_live_data_size: property("rocksdb.estimate-live-data-size")?,
+                memtables_size: property("rocksdb.cur-size-all-mem-tables")?,
+            });
+        }
+        // Each quad is in the spog index or in the dspo index
+        let approximate_quads = indexes
+            .iter()
+            .filter(|index| matches!(index.name, SPOG_CF | DSPO_CF))
+            .map(|index| index.approximate_entries)
+            .sum();
+        Ok(SizeStatistics {
+            directory_size: self.db.directory_size()?,
+            approximate_quads,
+            indexes,
+        })
+    }
+
     pub fn backup(&self, target_directory: &Path) -> Result<(), StorageError> {
         self.db.backup(target_directory)
     }
 
+    pub fn incremental_backup(
+        &self,
+        target_directory: &Path,
+        previous_backup: &Path,
+    ) -> Result<(), StorageError> {
+        self.db
+            .incremental_backup(target_directory, previous_backup)
+    }
+
     pub fn bulk_loader(&self) -> RocksDbStorageBulkLoader {
         RocksDbStorageBulkLoader {
             storage: self.clone(),
             hooks: Vec::new(),
             num_threads: None,
             max_memory_size: None,
+            detect_duplicates: false,
         }
     }
 }
 
+#[derive(Clone)]
 pub struct RocksDbStorageReader {
     reader: Reader,
     storage: RocksDbStorage,
+    /// Set if reading from a read-write transaction, then the graphs read from are locked until its end
+    all_graphs_locked: Option<Rc<Cell<bool>>>,
 }
 
 impl RocksDbStorageReader {
     pub fn len(&self) -> Result<usize, StorageError> {
+        self.lock_all_graphs()?;
         Ok(self.reader.len(&self.storage.gspo_cf)? + self.reader.len(&self.storage.dspo_cf)?)
     }
 
     pub fn is_empty(&self) -> Result<bool, StorageError> {
+        self.lock_all_graphs()?;
         Ok(self.reader.is_empty(&self.storage.gspo_cf)?
             && self.reader.is_empty(&self.storage.dspo_cf)?)
     }
 
+    /// In a read-write transaction, locks the graph until the transaction end
+    ///
+    /// Fails with a conflict error if the graph has been written since the transaction start.
+    fn lock_graph(&self, graph_name: &EncodedTerm) -> Result<(), StorageError> {
+        if self
+            .all_graphs_locked
+            .as_ref()
+            .is_some_and(|all_graphs_locked| !all_graphs_locked.get())
+        {
+            self.reader
+                .lock_for_update(&self.storage.graph_metadata_cf, &encode_term(graph_name))?;
+        }
+        Ok(())
+    }
+
+    /// Same as [`lock_graph`](Self::lock_graph) for all the graphs, including the ones that do not exist yet
+    ///
+    /// A single dataset key is locked: it is written by all the transactions changing a graph.
+    fn lock_all_graphs(&self) -> Result<(), StorageError> {
+        let Some(all_graphs_locked) = &self.all_graphs_locked else {
+            return Ok(());
+        };
+        if all_graphs_locked.get() {
+            return Ok(());
+        }
+        self.reader
+            .lock_for_update(&self.storage.default_cf, DATASET_LOCK_KEY)?;
+        all_graphs_locked.set(true);
+        Ok(())
+    }
+
     pub fn contains(&self, quad: &EncodedQuad) -> Result<bool, StorageError> {
+        self.lock_graph(&quad.graph_name)?;
         let mut buffer = Vec::with_capacity(4 * WRITTEN_TERM_MAX_SIZE);
         if quad.graph_name.is_default_graph() {
             write_spo_quad(&mut buffer, quad);
@@ -296,7 +556,12 @@ impl RocksDbStorageReader {
         object: Option<&EncodedTerm>,
         graph_name: Option<&EncodedTerm>,
     ) -> RocksDbChainedDecodingQuadIterator {
-        match subject {
+        let lock_result = if let Some(graph_name) = graph_name {
+            self.lock_graph(graph_name)
+        } else {
+            self.lock_all_graphs()
+        };
+        let mut iter = match subject {
             Some(subject) => match predicate {
                 Some(predicate) => match object {
                     Some(object) => match graph_name {
@@ -349,7 +614,9 @@ impl RocksDbStorageReader {
                     },
                 },
             },
-        }
+        };
+        iter.error = lock_result.err();
+        iter
     }
 
     pub fn quads(&self) -> RocksDbChainedDecodingQuadIterator {
@@ -525,15 +792,90 @@ impl RocksDbStorageReader {
 
     pub fn named_graphs(&self) -> RocksDbDecodingGraphIterator {
         RocksDbDecodingGraphIterator {
+            error: self
+                .reader
+                .lock_for_update(&self.storage.default_cf, GRAPH_LIST_KEY)
+                .err(),
             iter: self.reader.iter(&self.storage.graphs_cf).unwrap(), // TODO: propagate error?
         }
     }
 
     pub fn contains_named_graph(&self, graph_name: &EncodedTerm) -> Result<bool, StorageError> {
+        self.lock_graph(graph_name)?;
         self.reader
             .contains_key(&self.storage.graphs_cf, &encode_term(graph_name))
     }
 
+    pub fn graph_metadata(
+        &self,
+        graph_name: &EncodedTerm,
+    ) -> Result<Option<GraphMetadata>, StorageError> {
+        self.lock_graph(graph_name)?;
+        self.reader
+            .get(&self.storage.graph_metadata_cf, &encode_term(graph_name))?
+            .map(|value| decode_graph_metadata(&value))
+            .transpose()
+    }
+
+    pub fn history_version(&self) -> Result<Option<u64>, StorageError> {
+        self.reader
+            .get(&self.storage.default_cf, HISTORY_VERSION_KEY)?
+            .map(|version| {
+                Ok(u64::from_be_bytes(version.as_ref().try_into().map_err(
+                    |e| {
+                        CorruptionError::new(format!(
+                            "Error while parsing the history version: {e}"
+                        ))
+                    },
+                )?))
+            })
+            .transpose()
+    }
+
+    /// The history entries are stored as a timestamp at the key `version`
+    /// followed by a key `version ‖ position ‖ insert or remove ‖ spog quad` per quad change
+    /// and `version ‖ position ‖ insert or remove ‖ graph name` per named graph change.
+    pub fn history_entry(&self, version: u64) -> Result<Option<HistoryEntry>, StorageError> {
+        let prefix = version.to_be_bytes();
+        let Some(timestamp) = self.reader.get(&self.storage.history_cf, &prefix)? else {
+            return Ok(None);
+        };
+        let timestamp = DateTime::from_be_bytes(timestamp.as_ref().try_into().map_err(|e| {
+            CorruptionError::new(format!("Error while parsing a history timestamp: {e}"))
+        })?);
+        let mut entry = HistoryEntry::new(version, timestamp, Vec::new());
+        let mut iter = self.reader.scan_prefix(&self.storage.history_cf, &prefix)?;
+        while let Some(key) = iter.key() {
+            // The key without position is the timestamp one
+            if let Some((kind, quad)) = key.get(16..).and_then(<[u8]>::split_first) {
+                match *kind {
+                    HISTORY_INSERT => entry.push(QuadChange::Insert(
+                        self.decode_quad(&QuadEncoding::Spog.decode(quad)?)?,
+                    )),
+                    HISTORY_REMOVE => entry.push(QuadChange::Remove(
+                        self.decode_quad(&QuadEncoding::Spog.decode(quad)?)?,
+                    )),
+                    HISTORY_BULK_LOAD => entry.set_bulk_load(),
+                    HISTORY_INSERT_NAMED_GRAPH => entry.push(QuadChange::InsertNamedGraph(
+                        self.decode_named_or_blank_node(&decode_term(quad)?)?,
+                    )),
+                    HISTORY_REMOVE_NAMED_GRAPH => entry.push(QuadChange::RemoveNamedGraph(
+                        self.decode_named_or_blank_node(&decode_term(quad)?)?,
+                    )),
+                    _ => {
+                        return Err(CorruptionError::msg(format!(
+                            "Unexpected history change kind {kind}"
+                        ))
+                        .into())
+                    }
+                }
+            }
+            iter.next();
+        }
+        iter.status()?;
+        Ok(Some(entry))
+    }
+
     fn spog_quads(&self, prefix: &[u8]) -> RocksDbDecodingQuadIterator {
         self.inner_quads(&self.storage.spog_cf, prefix, QuadEncoding::Spog)
     }
@@ -588,6 +930,38 @@ impl RocksDbStorageReader {
             .contains_key(&self.storage.id2str_cf, &key.to_be_bytes())
     }
 
+    /// Iterates on the beginning of each column family to load its blocks in the block cache
+    pub fn warm_up(&self, max_size: Option<usize>) -> Result<(), StorageError> {
+        // The dictionary goes first, it is used by almost all queries
+        let column_families = [
+            &self.storage.id2str_cf,
+            &self.storage.dspo_cf,
+            &self.storage.dpos_cf,
+            &self.storage.dosp_cf,
+            &self.storage.gspo_cf,
+            &self.storage.gpos_cf,
+            &self.storage.gosp_cf,
+            &self.storage.spog_cf,
+            &self.storage.posg_cf,
+            &self.storage.ospg_cf,
+            &self.storage.graphs_cf,
+        ];
+        let max_size_per_column_family = max_size.map(|size| size / column_families.len());
+        for column_family in column_families {
+            let mut iter = self.reader.iter(column_family)?;
+            let mut size = 0;
+            while let Some(key) = iter.key() {
+                size += key.len();
+                if max_size_per_column_family.is_some_and(|max_size| size > max_size) {
+                    break;
+                }
+                iter.next();
+            }
+            iter.status()?; // We make sure there is no read problem
+        }
+        Ok(())
+    }
+
     /// Validates that all the storage invariants held in the data
     pub fn validate(&self) -> Result<(), StorageError> {
         // triples
@@ -703,6 +1077,8 @@ impl RocksDbStorageReader {
 pub struct RocksDbChainedDecodingQuadIterator {
     first: RocksDbDecodingQuadIterator,
     second: Option<RocksDbDecodingQuadIterator>,
+    /// Returned before the quads, used if the read lock could not be taken
+    error: Option<StorageError>,
 }
 
 impl RocksDbChainedDecodingQuadIterator {
@@ -710,6 +1086,7 @@ impl RocksDbChainedDecodingQuadIterator {
         Self {
             first,
             second: None,
+            error: None,
         }
     }
 
@@ -717,6 +1094,7 @@ impl RocksDbChainedDecodingQuadIterator {
         Self {
             first,
             second: Some(second),
+            error: None,
         }
     }
 }
@@ -725,7 +1103,9 @@ impl Iterator for RocksDbChainedDecodingQuadIterator {
     type Item = Result<EncodedQuad, StorageError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some(result) = self.first.next() {
+        if let Some(error) = self.error.take() {
+            Some(Err(error))
+        } else if let Some(result) = self.first.next() {
             Some(result)
         } else if let Some(second) = &mut self.second {
             second.next()
@@ -755,12 +1135,17 @@ impl Iterator for RocksDbDecodingQuadIterator {
 
 pub struct RocksDbDecodingGraphIterator {
     iter: Iter,
+    /// Returned before the graphs, used if the read lock could not be taken
+    error: Option<StorageError>,
 }
 
 impl Iterator for RocksDbDecodingGraphIterator {
     type Item = Result<EncodedTerm, StorageError>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if let Some(error) = self.error.take() {
+            return Some(Err(error));
+        }
         if let Err(e) = self.iter.status() {
             return Some(Err(e));
         }
@@ -786,6 +1171,12 @@ pub struct RocksDbStorageWriter<'a> {
     buffer: Vec<u8>,
     transaction: Transaction<'a>,
     storage: &'a RocksDbStorage,
+    /// The history version written by the transaction and its number of changes, set on its first recorded change
+    history: Option<(u64, u64)>,
+    /// The graph metadata read or changed by the transaction, written on commit
+    graph_metadata: &'a mut FxHashMap<EncodedTerm, Option<GraphMetadata>>,
+    /// Shared with the readers to lock all the graphs only once
+    all_graphs_locked: Rc<Cell<bool>>,
 }
 
 impl RocksDbStorageWriter<'_> {
@@ -793,6 +1184,7 @@ impl RocksDbStorageWriter<'_> {
         RocksDbStorageReader {
             reader: self.transaction.reader(),
             storage: self.storage.clone(),
+            all_graphs_locked: Some(Rc::clone(&self.all_graphs_locked)),
         }
     }
 
@@ -1076,6 +1468,94 @@ impl RocksDbStorageWriter<'_> {
         Ok(result)
     }
 
+    pub fn graph_metadata_mut(
+        &mut self,
+        graph_name: &EncodedTerm,
+    ) -> Result<&mut Option<GraphMetadata>, StorageError> {
+        Ok(match self.graph_metadata.entry(graph_name.clone()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(
+                self.transaction
+                    .get_for_update(&self.storage.graph_metadata_cf, &encode_term(graph_name))?
+                    .map(|value| decode_graph_metadata(&value))
+                    .transpose()?,
+            ),
+        })
+    }
+
+    pub fn set_graph_metadata(
+        &mut self,
+        graph_name: &EncodedTerm,
+        metadata: Option<GraphMetadata>,
+    ) {
+        self.graph_metadata.insert(graph_name.clone(), metadata);
+    }
+
+    pub fn append_history(
+        &mut self,
+        change: &QuadChange,
+        timestamp: DateTime,
+    ) -> Result<(), StorageError> {
+        let (version, position) = self.next_history_position(timestamp)?;
+        self.buffer.clear();
+        self.buffer.extend_from_slice(&version.to_be_bytes());
+        self.buffer.extend_from_slice(&position.to_be_bytes());
+        match change {
+            QuadChange::Insert(quad) => {
+                self.buffer.push(HISTORY_INSERT);
+                write_spog_quad(&mut self.buffer, &quad.as_ref().into());
+            }
+            QuadChange::Remove(quad) => {
+                self.buffer.push(HISTORY_REMOVE);
+                write_spog_quad(&mut self.buffer, &quad.as_ref().into());
+            }
+            QuadChange::InsertNamedGraph(graph_name) => {
+                self.buffer.push(HISTORY_INSERT_NAMED_GRAPH);
+                write_term(&mut self.buffer, &graph_name.as_ref().into());
+            }
+            QuadChange::RemoveNamedGraph(graph_name) => {
+                self.buffer.push(HISTORY_REMOVE_NAMED_GRAPH);
+                write_term(&mut self.buffer, &graph_name.as_ref().into());
+            }
+        }
+        self.transaction
+            .insert_empty(&self.storage.history_cf, &self.buffer)
+    }
+
+    pub fn append_bulk_load_history(&mut self, timestamp: DateTime) -> Result<(), StorageError> {
+        let (version, position) = self.next_history_position(timestamp)?;
+        self.buffer.clear();
+        self.buffer.extend_from_slice(&version.to_be_bytes());
+        self.buffer.extend_from_slice(&position.to_be_bytes());
+        self.buffer.push(HISTORY_BULK_LOAD);
+        self.transaction
+            .insert_empty(&self.storage.history_cf, &self.buffer)
+    }
+
+    /// The history version written by the transaction and the position of its next change, creating the version if needed
+    fn next_history_position(&mut self, timestamp: DateTime) -> Result<(u64, u64), StorageError> {
+        let (version, position) = if let Some(history) = self.history {
+            history
+        } else {
+            let version = self.reader().history_version()?.ok_or_else(|| {
+                CorruptionError::msg("The history is written while it is not enabled")
+            })? + 1;
+            self.transaction.insert(
+                &self.storage.default_cf,
+                HISTORY_VERSION_KEY,
+                &version.to_be_bytes(),
+            )?;
+            self.transaction.insert(
+                &self.storage.history_cf,
+                &version.to_be_bytes(),
+                &timestamp.to_be_bytes(),
+            )?;
+            (version, 0)
+        };
+        self.history = Some((version, position + 1));
+        Ok((version, position))
+    }
+
     pub fn remove_all_named_graphs(&mut self) -> Result<(), StorageError> {
         for graph_name in self.reader().named_graphs() {
             self.remove_encoded_named_graph(&graph_name?)?;
@@ -1100,6 +1580,7 @@ pub struct RocksDbStorageBulkLoader {
     hooks: Vec<Box<dyn Fn(u64)>>,
     num_threads: Option<usize>,
     max_memory_size: Option<usize>,
+    detect_duplicates: bool,
 }
 
 impl RocksDbStorageBulkLoader {
@@ -1118,10 +1599,17 @@ impl RocksDbStorageBulkLoader {
         self
     }
 
+    pub fn with_duplicate_detection(mut self) -> Self {
+        self.detect_duplicates = true;
+        self
+    }
+
+    /// Loads the quads and adds them to `report`, including the ones loaded before a failure
     #[allow(clippy::trait_duplication_in_bounds)]
     pub fn load<EI, EO: From<StorageError> + From<EI>>(
         &self,
         quads: impl IntoIterator<Item = Result<Quad, EI>>,
+        report: &mut LoadReport,
     ) -> Result<(), EO> {
         let num_threads = self.num_threads.unwrap_or(2);
         if num_threads < 2 {
@@ -1142,7 +1630,8 @@ impl RocksDbStorageBulkLoader {
         }
         let done_counter = Mutex::new(0);
         let mut done_and_displayed_counter = 0;
-        thread::scope(|thread_scope| {
+        let batches_report = Mutex::new(LoadReport::default());
+        let result = thread::scope(|thread_scope| -> Result<(), EO> {
             let mut threads = VecDeque::with_capacity(num_threads - 1);
             let mut buffer = Vec::with_capacity(batch_size);
             for quad in quads {
@@ -1155,6 +1644,7 @@ impl RocksDbStorageBulkLoader {
                         thread_scope,
                         &done_counter,
                         &mut done_and_displayed_counter,
+                        &batches_report,
                         num_threads,
                         batch_size,
                     )?;
@@ -1166,6 +1656,7 @@ impl RocksDbStorageBulkLoader {
                 thread_scope,
                 &done_counter,
                 &mut done_and_displayed_counter,
+                &batches_report,
                 num_threads,
                 batch_size,
             )?;
@@ -1174,7 +1665,14 @@ impl RocksDbStorageBulkLoader {
                 self.on_possible_progress(&done_counter, &mut done_and_displayed_counter)?;
             }
             Ok(())
-        })
+        });
+        // The batches written before a failure are also reported
+        report.merge(
+            batches_report
+                .into_inner()
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+        result
     }
 
     fn spawn_load_thread<'scope>(
@@ -1184,6 +1682,7 @@ impl RocksDbStorageBulkLoader {
         thread_scope: &'scope thread::Scope<'scope, '_>,
         done_counter: &'scope Mutex<u64>,
         done_and_displayed_counter: &mut u64,
+        report: &'scope Mutex<LoadReport>,
         num_threads: usize,
         batch_size: usize,
     ) -> Result<(), StorageError> {
@@ -1198,8 +1697,13 @@ impl RocksDbStorageBulkLoader {
         let mut buffer_to_load = Vec::with_capacity(batch_size);
         swap(buffer, &mut buffer_to_load);
         let storage = &self.storage;
+        let detect_duplicates = self.detect_duplicates;
         threads.push_back(thread_scope.spawn(move || {
-            FileBulkLoader::new(storage, batch_size).load(buffer_to_load, done_counter)
+            FileBulkLoader::new(storage, batch_size, detect_duplicates).load(
+                buffer_to_load,
+                done_counter,
+                report,
+            )
         }));
         Ok(())
     }
@@ -1228,11 +1732,14 @@ struct FileBulkLoader<'a> {
     id2str: HashMap<StrHash, Box<str>, BuildHasherDefault<StrHashHasher>>,
     quads: FxHashSet<EncodedQuad>,
     triples: FxHashSet<EncodedQuad>,
-    graphs: FxHashSet<EncodedTerm>,
+    graphs: FxHashMap<EncodedTerm, GraphName>,
+    /// Number of quads repeated in the batch per graph
+    duplicates: FxHashMap<EncodedTerm, u64>,
+    detect_duplicates: bool,
 }
 
 impl<'a> FileBulkLoader<'a> {
-    fn new(storage: &'a RocksDbStorage, batch_size: usize) -> Self {
+    fn new(storage: &'a RocksDbStorage, batch_size: usize, detect_duplicates: bool) -> Self {
         Self {
             storage,
             id2str: HashMap::with_capacity_and_hasher(
@@ -1241,18 +1748,29 @@ impl<'a> FileBulkLoader<'a> {
             ),
             quads: FxHashSet::with_capacity_and_hasher(batch_size, FxBuildHasher),
             triples: FxHashSet::with_capacity_and_hasher(batch_size, FxBuildHasher),
-            graphs: FxHashSet::default(),
+            graphs: FxHashMap::default(),
+            duplicates: FxHashMap::default(),
+            detect_duplicates,
         }
     }
 
-    fn load(&mut self, quads: Vec<Quad>, counter: &Mutex<u64>) -> Result<(), StorageError> {
+    fn load(
+        &mut self,
+        quads: Vec<Quad>,
+        counter: &Mutex<u64>,
+        report: &Mutex<LoadReport>,
+    ) -> Result<(), StorageError> {
         self.encode(quads)?;
         let size = self.triples.len() + self.quads.len();
-        self.save()?;
+        let batch_report = self.save()?;
         *counter
             .lock()
             .map_err(|_| io::Error::other("Mutex poisoned"))? +=
             size.try_into().unwrap_or(u64::MAX);
+        report
+            .lock()
+            .map_err(|_| io::Error::other("Mutex poisoned"))?
+            .merge(batch_report);
         Ok(())
     }
 
@@ -1264,13 +1782,17 @@ impl<'a> FileBulkLoader<'a> {
                     self.insert_term(quad.subject.as_ref().into(), &encoded.subject)?;
                     self.insert_term(quad.predicate.as_ref().into(), &encoded.predicate)?;
                     self.insert_term(quad.object.as_ref(), &encoded.object)?;
+                } else {
+                    *self.duplicates.entry(encoded.graph_name).or_default() += 1;
                 }
             } else if self.quads.insert(encoded.clone()) {
                 self.insert_term(quad.subject.as_ref().into(), &encoded.subject)?;
                 self.insert_term(quad.predicate.as_ref().into(), &encoded.predicate)?;
                 self.insert_term(quad.object.as_ref(), &encoded.object)?;
 
-                if self.graphs.insert(encoded.graph_name.clone()) {
+                if !self.graphs.contains_key(&encoded.graph_name) {
+                    self.graphs
+                        .insert(encoded.graph_name.clone(), quad.graph_name.clone());
                     self.insert_term(
                         match quad.graph_name.as_ref() {
                             GraphNameRef::NamedNode(n) => n.into(),
@@ -1285,12 +1807,15 @@ impl<'a> FileBulkLoader<'a> {
                         &encoded.graph_name,
                     )?;
                 }
+            } else {
+                *self.duplicates.entry(encoded.graph_name).or_default() += 1;
             }
         }
         Ok(())
     }
 
-    fn save(&mut self) -> Result<(), StorageError> {
+    /// Writes the batch to the store and returns its load report
+    fn save(&mut self) -> Result<LoadReport, StorageError> {
         let mut to_load = Vec::new();
 
         // id2str
@@ -1332,15 +1857,13 @@ impl<'a> FileBulkLoader<'a> {
                     }),
                 )?,
             ));
-            self.triples.clear();
         }
 
         if !self.quads.is_empty() {
             to_load.push((
                 &self.storage.graphs_cf,
-                self.build_sst_for_keys(self.graphs.iter().map(encode_term))?,
+                self.build_sst_for_keys(self.graphs.keys().map(encode_term))?,
             ));
-            self.graphs.clear();
 
             to_load.push((
                 &self.storage.gspo_cf,
@@ -1408,10 +1931,76 @@ impl<'a> FileBulkLoader<'a> {
                     )
                 }))?,
             ));
-            self.quads.clear();
         }
 
-        self.storage.db.insert_stt_files(&to_load)
+        // Lookups and ingestions must not be interleaved between loaders to not miss duplicates
+        let _guard = if self.detect_duplicates {
+            Some(
+                self.storage
+                    .bulk_load_ingestion_lock
+                    .lock()
+                    .map_err(|_| io::Error::other("Mutex poisoned"))?,
+            )
+        } else {
+            None
+        };
+        let report = self.report()?;
+        self.storage.db.insert_stt_files(&to_load)?;
+        self.triples.clear();
+        self.quads.clear();
+        self.graphs.clear();
+        Ok(report)
+    }
+
+    /// Counts the new and duplicate quads of the batch if duplicates are detected, the quads are unchecked otherwise
+    fn report(&mut self) -> Result<LoadReport, StorageError> {
+        let mut counts = FxHashMap::<EncodedTerm, GraphLoadReport>::default();
+        for (graph_name, count) in take(&mut self.duplicates) {
+            counts.entry(graph_name).or_default().duplicates += count;
+        }
+        for quad in &self.triples {
+            let report = counts.entry(EncodedTerm::DefaultGraph).or_default();
+            if !self.detect_duplicates {
+                report.unchecked += 1;
+            } else if self.storage.db.contains_key(
+                &self.storage.dspo_cf,
+                &encode_term_triple(&quad.subject, &quad.predicate, &quad.object),
+            )? {
+                report.duplicates += 1;
+            } else {
+                report.new += 1;
+            }
+        }
+        for quad in &self.quads {
+            let report = counts.entry(quad.graph_name.clone()).or_default();
+            if !self.detect_duplicates {
+                report.unchecked += 1;
+            } else if self.storage.db.contains_key(
+                &self.storage.gspo_cf,
+                &encode_term_quad(
+                    &quad.graph_name,
+                    &quad.subject,
+                    &quad.predicate,
+                    &quad.object,
+                ),
+            )? {
+                report.duplicates += 1;
+            } else {
+                report.new += 1;
+            }
+        }
+        let mut report = LoadReport::default();
+        for (graph_name, counts) in counts {
+            let graph_name = if graph_name == EncodedTerm::DefaultGraph {
+                GraphName::DefaultGraph
+            } else {
+                self.graphs.get(&graph_name).cloned().ok_or_else(|| {
+                    CorruptionError::msg("A loaded quad graph name has not been registered")
+                })?
+            };
+            report.graphs.insert(graph_name, counts);
+        }
+        Ok(report)
     }
 
     fn insert_term(
@@ -1439,6 +2028,14 @@ impl<'a> FileBulkLoader<'a> {
     }
 }
 
+/// The smallest key greater than all the keys starting with the prefix, if it exists
+fn next_prefix(prefix: &[u8]) -> Option<Vec<u8>> {
+    let last = prefix.iter().rposition(|c| *c < u8::MAX)?;
+    let mut next = prefix[..=last].to_vec();
+    next[last] += 1;
+    Some(next)
+}
+
 fn map_thread_result<R>(result: thread::Result<R>) -> io::Result<R> {
     result.map_err(|e| {
         io::Error::other(if let Ok(e) = e.downcast::<&dyn std::fmt::Display>() {
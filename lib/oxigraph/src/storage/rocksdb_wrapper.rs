@@ -24,7 +24,7 @@ use std::path::{Path, PathBuf};
 use std::rc::{Rc, Weak};
 use std::sync::{Arc, OnceLock};
 use std::thread::{available_parallelism, yield_now};
-use std::{fmt, io, ptr, slice};
+use std::{fmt, fs, io, ptr, slice};
 
 macro_rules! ffi_result {
     ( $($function:ident)::*( $arg1:expr $(, $arg:expr)* $(,)? ) ) => {{
@@ -114,6 +114,7 @@ struct RoDbHandler {
     column_family_names: Vec<&'static str>,
     cf_handles: Vec<*mut rocksdb_column_family_handle_t>,
     cf_options: Vec<*mut rocksdb_options_t>,
+    path: PathBuf,
 }
 
 unsafe impl Send for RoDbHandler {}
@@ -225,6 +226,7 @@ impl Db {
                 "rocksdb_transaction_options_create returned null"
             );
             rocksdb_transaction_options_set_set_snapshot(transaction_options, 1);
+            rocksdb_transaction_options_set_deadlock_detect(transaction_options, 1);
 
             let flush_options = rocksdb_flushoptions_create();
             assert!(
@@ -328,6 +330,7 @@ impl Db {
                     column_family_names,
                     cf_handles,
                     cf_options,
+                    path: path.to_path_buf(),
                 })),
             })
         }
@@ -522,9 +525,9 @@ impl Db {
                     while let Some(e) = error.source() {
                         error = e;
                     }
+                    // A lock wait timeout is not retried: the lock might be held by a transaction waiting for this one to end
                     let is_conflict_error = error.downcast_ref::<ErrorStatus>().is_some_and(|e| {
                         e.0.code == rocksdb_status_code_t_rocksdb_status_code_busy
-                            || e.0.code == rocksdb_status_code_t_rocksdb_status_code_timed_out
                             || e.0.code == rocksdb_status_code_t_rocksdb_status_code_try_again
                     });
                     if is_conflict_error {
@@ -624,6 +627,16 @@ impl Db {
     }
 
     pub fn compact(&self, column_family: &ColumnFamily) -> Result<(), StorageError> {
+        self.compact_range(column_family, None, None)
+    }
+
+    /// Compacts the keys of the column family between `start` (included) and `end` (excluded)
+    pub fn compact_range(
+        &self,
+        column_family: &ColumnFamily,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+    ) -> Result<(), StorageError> {
         let DbKind::ReadWrite(db) = &self.inner else {
             return Err(StorageError::Other(
                 "Compact are only possible on read-write instances".into(),
@@ -634,15 +647,59 @@ impl Db {
                 db.db,
                 column_family.0,
                 db.compaction_options,
-                ptr::null(),
-                0,
-                ptr::null(),
-                0,
+                start.map_or(ptr::null(), |k| k.as_ptr().cast()),
+                start.map_or(0, <[u8]>::len),
+                end.map_or(ptr::null(), |k| k.as_ptr().cast()),
+                end.map_or(0, <[u8]>::len),
             ))
         }?;
         Ok(())
     }
 
+    /// Returns the value of an integer RocksDB property of the column family like `rocksdb.estimate-num-keys`
+    ///
+    /// Returns `None` if the property is not known.
+    pub fn property_int(
+        &self,
+        column_family: &ColumnFamily,
+        name: &str,
+    ) -> Result<Option<u64>, StorageError> {
+        let name = CString::new(name).map_err(|e| StorageError::Other(Box::new(e)))?;
+        let mut value = 0;
+        let found = unsafe {
+            match &self.inner {
+                DbKind::ReadOnly(db) => {
+                    rocksdb_property_int_cf(db.db, column_family.0, name.as_ptr(), &mut value) == 0
+                }
+                DbKind::ReadWrite(db) => {
+                    rocksdb_transactiondb_property_int_cf(
+                        db.db,
+                        column_family.0,
+                        name.as_ptr(),
+                        &mut value,
+                    ) != 0
+                }
+            }
+        };
+        Ok(found.then_some(value))
+    }
+
+    /// The total size in bytes of the files in the database directory
+    pub fn directory_size(&self) -> Result<u64, StorageError> {
+        let path = match &self.inner {
+            DbKind::ReadOnly(db) => &db.path,
+            DbKind::ReadWrite(db) => &db.path,
+        };
+        let mut size = 0;
+        for entry in fs::read_dir(path)? {
+            let metadata = entry?.metadata()?;
+            if metadata.is_file() {
+                size += metadata.len();
+            }
+        }
+        Ok(size)
+    }
+
     pub fn new_sst_file(&self) -> Result<SstFileWriter, StorageError> {
         let DbKind::ReadWrite(db) = &self.inner else {
             return Err(StorageError::Other(
@@ -721,6 +778,61 @@ impl Db {
         }?;
         Ok(())
     }
+
+    /// Creates a checkpoint like [`backup`](Self::backup) but hard links the immutable files already in `previous_backup` instead of copying them.
+    ///
+    /// The checkpoint is first created in the database directory to only get hard links to the database files.
+    pub fn incremental_backup(
+        &self,
+        target_directory: &Path,
+        previous_backup: &Path,
+    ) -> Result<(), StorageError> {
+        let db_path = match &self.inner {
+            DbKind::ReadOnly(db) => &db.path,
+            DbKind::ReadWrite(db) => &db.path,
+        };
+        let staging_directory = db_path.join(format!("backup-{}", random::<u128>()));
+        self.backup(&staging_directory)?;
+        let result = link_or_copy_backup(&staging_directory, target_directory, previous_backup);
+        fs::remove_dir_all(&staging_directory)?;
+        result
+    }
+}
+
+/// Fills `target_directory` with the files of the checkpoint in `staging_directory`.
+///
+/// The SST and blob files are immutable and their names are never reused by a database:
+/// if `previous_backup` has a file with the same name and size, it is the same file and it is hard linked.
+/// The other files are hard linked from the staging directory if possible and copied otherwise.
+#[allow(clippy::create_dir)] // Like for the checkpoints, the target directory must not exist yet
+fn link_or_copy_backup(
+    staging_directory: &Path,
+    target_directory: &Path,
+    previous_backup: &Path,
+) -> Result<(), StorageError> {
+    fs::create_dir(target_directory)?;
+    for entry in fs::read_dir(staging_directory)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let source = entry.path();
+        let target = target_directory.join(&file_name);
+        let is_immutable = source
+            .extension()
+            .is_some_and(|extension| extension == "sst" || extension == "blob");
+        if is_immutable {
+            let previous = previous_backup.join(&file_name);
+            let size = entry.metadata()?.len();
+            if fs::metadata(&previous).is_ok_and(|metadata| metadata.len() == size)
+                && fs::hard_link(&previous, &target).is_ok()
+            {
+                continue;
+            }
+        }
+        if fs::hard_link(&source, &target).is_err() {
+            fs::copy(&source, &target)?;
+        }
+    }
+    Ok(())
 }
 
 // It is fine to not keep a lifetime: there is no way to use this type without the database being still in scope.
@@ -826,6 +938,37 @@ impl Reader {
         Ok(self.get(column_family, key)?.is_some()) // TODO: optimize
     }
 
+    /// Locks the key until the end of the transaction if the reader is reading from a transaction.
+    ///
+    /// Fails with a conflict error if the key has been written since the transaction snapshot.
+    pub fn lock_for_update(
+        &self,
+        column_family: &ColumnFamily,
+        key: &[u8],
+    ) -> Result<(), StorageError> {
+        let InnerReader::Transaction(inner) = &self.inner else {
+            return Ok(());
+        };
+        let Some(inner) = inner.upgrade() else {
+            return Err(StorageError::Other(
+                "The transaction is already ended".into(),
+            ));
+        };
+        unsafe {
+            let slice = ffi_result!(rocksdb_transaction_get_for_update_pinned_cf_with_status(
+                *inner,
+                self.options,
+                column_family.0,
+                key.as_ptr().cast(),
+                key.len()
+            ))?;
+            if !slice.is_null() {
+                rocksdb_pinnableslice_destroy(slice);
+            }
+        }
+        Ok(())
+    }
+
     #[allow(clippy::iter_not_returning_iterator)]
     pub fn iter(&self, column_family: &ColumnFamily) -> Result<Iter, StorageError> {
         self.scan_prefix(column_family, &[])
@@ -914,6 +1057,10 @@ impl Reader {
     }
 }
 
+/// A RocksDB transaction.
+///
+/// Its clones share the same underlying transaction.
+#[derive(Clone)]
 pub struct Transaction<'a> {
     inner: Rc<*mut rocksdb_transaction_t>,
     read_options: *mut rocksdb_readoptions_t,
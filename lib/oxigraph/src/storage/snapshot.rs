@@ -0,0 +1,206 @@
+//! A compact binary serialization of the storage content.
+//!
+//! It is made of a header followed by records, each one starting with its kind byte:
+//! * strings referenced by the terms of the next records: hash, length as a 32 bits big endian integer, UTF-8 value,
+//! * named graphs: graph name,
+//! * quads in the default graph: subject, predicate, object,
+//! * quads in a named graph: subject, predicate, object, graph name,
+//! * the end of the snapshot.
+//!
+//! The terms are written using the storage binary encoding.
+
+use crate::model::{NamedOrBlankNode, Quad};
+use crate::storage::binary_encoder::{write_term, TermReader};
+use crate::storage::error::{CorruptionError, StorageError};
+use crate::storage::numeric_encoder::{insert_term, Decoder, EncodedTerm, StrHash, StrLookup};
+use crate::storage::StorageReader;
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+
+const MAGIC: &[u8] = b"OXIGRAPH-SNAPSHOT";
+const FORMAT_VERSION: u8 = 1;
+const RECORD_END: u8 = 0;
+const RECORD_STRING: u8 = 1;
+const RECORD_NAMED_GRAPH: u8 = 2;
+const RECORD_DEFAULT_GRAPH_QUAD: u8 = 3;
+const RECORD_NAMED_GRAPH_QUAD: u8 = 4;
+/// Size from which the written records are flushed to the writer
+const WRITE_BUFFER_SIZE: usize = 1 << 16;
+
+/// Writes all the named graphs and quads visible from the reader
+pub fn write_snapshot(reader: &StorageReader, mut writer: impl Write) -> Result<(), StorageError> {
+    writer.write_all(MAGIC)?;
+    writer.write_all(&[FORMAT_VERSION])?;
+    let mut written_strings = HashSet::new();
+    let mut buffer = Vec::new();
+    for graph_name in reader.named_graphs() {
+        let graph_name = graph_name?;
+        write_strings(reader, &graph_name, &mut written_strings, &mut buffer)?;
+        buffer.push(RECORD_NAMED_GRAPH);
+        write_term(&mut buffer, &graph_name);
+        flush_if_full(&mut writer, &mut buffer)?;
+    }
+    for quad in reader.quads_for_pattern(None, None, None, None) {
+        let quad = quad?;
+        write_strings(reader, &quad.subject, &mut written_strings, &mut buffer)?;
+        write_strings(reader, &quad.predicate, &mut written_strings, &mut buffer)?;
+        write_strings(reader, &quad.object, &mut written_strings, &mut buffer)?;
+        if quad.graph_name.is_default_graph() {
+            buffer.push(RECORD_DEFAULT_GRAPH_QUAD);
+        } else {
+            write_strings(reader, &quad.graph_name, &mut written_strings, &mut buffer)?;
+            buffer.push(RECORD_NAMED_GRAPH_QUAD);
+        }
+        write_term(&mut buffer, &quad.subject);
+        write_term(&mut buffer, &quad.predicate);
+        write_term(&mut buffer, &quad.object);
+        write_term(&mut buffer, &quad.graph_name); // Writes nothing for the default graph
+        flush_if_full(&mut writer, &mut buffer)?;
+    }
+    buffer.push(RECORD_END);
+    writer.write_all(&buffer)?;
+    Ok(())
+}
+
+fn flush_if_full(writer: &mut impl Write, buffer: &mut Vec<u8>) -> Result<(), StorageError> {
+    if buffer.len() >= WRITE_BUFFER_SIZE {
+        writer.write_all(buffer)?;
+        buffer.clear();
+    }
+    Ok(())
+}
+
+/// Writes the string records for the strings of the term that have not been written yet
+fn write_strings(
+    reader: &StorageReader,
+    term: &EncodedTerm,
+    written_strings: &mut HashSet<StrHash>,
+    buffer: &mut Vec<u8>,
+) -> Result<(), StorageError> {
+    let decoded = reader.decode_term(term)?;
+    insert_term(decoded.as_ref(), term, &mut |hash, value| {
+        if written_strings.insert(*hash) {
+            buffer.push(RECORD_STRING);
+            buffer.extend_from_slice(&hash.to_be_bytes());
+            buffer.extend_from_slice(
+                &u32::try_from(value.len())
+                    .map_err(|_| StorageError::Other("Too long string in the store".into()))?
+                    .to_be_bytes(),
+            );
+            buffer.extend_from_slice(value.as_bytes());
+        }
+        Ok(())
+    })
+}
+
+/// Reads a snapshot written by [`write_snapshot`]
+pub struct SnapshotReader<R: Read> {
+    reader: R,
+    strings: SnapshotStrings,
+    ended: bool,
+}
+
+/// A named graph or a quad read from a snapshot
+pub enum SnapshotRecord {
+    NamedGraph(NamedOrBlankNode),
+    Quad(Quad),
+}
+
+impl<R: Read> SnapshotReader<R> {
+    pub fn new(mut reader: R) -> Result<Self, StorageError> {
+        let mut header = [0; MAGIC.len() + 1];
+        reader.read_exact(&mut header)?;
+        if !header.starts_with(MAGIC) {
+            return Err(CorruptionError::msg("The data is not an Oxigraph snapshot").into());
+        }
+        if header[MAGIC.len()] != FORMAT_VERSION {
+            return Err(CorruptionError::msg(format!(
+                "The snapshot format version {} is not supported, only the version {FORMAT_VERSION} is",
+                header[MAGIC.len()]
+            ))
+            .into());
+        }
+        Ok(Self {
+            reader,
+            strings: SnapshotStrings::default(),
+            ended: false,
+        })
+    }
+
+    fn read_next(&mut self) -> Result<Option<SnapshotRecord>, StorageError> {
+        loop {
+            let mut kind = [0];
+            self.reader.read_exact(&mut kind)?;
+            match kind[0] {
+                RECORD_END => return Ok(None),
+                RECORD_STRING => {
+                    let mut hash = [0; 16];
+                    self.reader.read_exact(&mut hash)?;
+                    let mut len = [0; 4];
+                    self.reader.read_exact(&mut len)?;
+                    let mut value = vec![
+                        0;
+                        u32::from_be_bytes(len).try_into().map_err(|_| {
+                            CorruptionError::msg("Too long string in the snapshot")
+                        },)?
+                    ];
+                    self.reader.read_exact(&mut value)?;
+                    let value = String::from_utf8(value).map_err(CorruptionError::new)?;
+                    let hash = StrHash::from_be_bytes(hash);
+                    if StrHash::new(&value) != hash {
+                        return Err(CorruptionError::msg(format!(
+                            "The snapshot string {value} does not match its hash"
+                        ))
+                        .into());
+                    }
+                    self.strings.0.insert(hash, value);
+                }
+                RECORD_NAMED_GRAPH => {
+                    let graph_name = self.reader.read_term()?;
+                    return Ok(Some(SnapshotRecord::NamedGraph(
+                        self.strings.decode_named_or_blank_node(&graph_name)?,
+                    )));
+                }
+                RECORD_DEFAULT_GRAPH_QUAD => {
+                    let quad = self.reader.read_dspo_quad()?;
+                    return Ok(Some(SnapshotRecord::Quad(self.strings.decode_quad(&quad)?)));
+                }
+                RECORD_NAMED_GRAPH_QUAD => {
+                    let quad = self.reader.read_spog_quad()?;
+                    return Ok(Some(SnapshotRecord::Quad(self.strings.decode_quad(&quad)?)));
+                }
+                kind => {
+                    return Err(CorruptionError::msg(format!(
+                        "Unexpected snapshot record kind {kind}"
+                    ))
+                    .into())
+                }
+            }
+        }
+    }
+}
+
+impl<R: Read> Iterator for SnapshotReader<R> {
+    type Item = Result<SnapshotRecord, StorageError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.ended {
+            return None;
+        }
+        let result = self.read_next().transpose();
+        if !matches!(result, Some(Ok(_))) {
+            self.ended = true;
+        }
+        result
+    }
+}
+
+/// The strings read so far from a snapshot
+#[derive(Default)]
+struct SnapshotStrings(HashMap<StrHash, String>);
+
+impl StrLookup for SnapshotStrings {
+    fn get_str(&self, key: &StrHash) -> Result<Option<String>, StorageError> {
+        Ok(self.0.get(key).cloned())
+    }
+}
@@ -25,22 +25,31 @@
 //! };
 //! # Result::<_, Box<dyn std::error::Error>>::Ok(())
 //! ```
+use crate::io::parallel::{is_line_based, parse_lines_in_parallel};
 use crate::io::{RdfFormat, RdfParseError, RdfParser, RdfSerializer};
+use crate::model::dataset::DatasetDiff;
 use crate::model::*;
 use crate::sparql::{
-    evaluate_query, evaluate_update, EvaluationError, Query, QueryExplanation, QueryOptions,
-    QueryResults, Update, UpdateOptions,
+    evaluate_query, evaluate_update, DatasetStatistics, EvaluationError, PredicateStatistics,
+    Query, QueryExplanation, QueryOptions, QueryResults, TextIndex, Update, UpdateOptions,
 };
 use crate::storage::numeric_encoder::{Decoder, EncodedQuad, EncodedTerm};
-pub use crate::storage::{CorruptionError, LoaderError, SerializerError, StorageError};
 use crate::storage::{
-    DecodingGraphIterator, DecodingQuadIterator, Storage, StorageBulkLoader, StorageReader,
-    StorageWriter,
+    write_snapshot, DecodingGraphIterator, DecodingQuadIterator, SnapshotReader, SnapshotRecord,
+    Storage, StorageBulkLoader, StorageReader, StorageWriter,
 };
+pub use crate::storage::{
+    CorruptionError, GraphLoadReport, GraphMetadata, HistoryEntry, LoadReport, LoaderError,
+    QuadChange, SerializerError, StorageError,
+};
+#[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+pub use crate::storage::{IndexSizeStatistics, SizeStatistics};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::error::Error;
-use std::io::{Read, Write};
+use std::io::{BufReader, Read, Write};
 #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
 use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
 use std::{fmt, str};
 
 /// An on-disk [RDF dataset](https://www.w3.org/TR/rdf11-concepts/#dfn-rdf-dataset).
@@ -82,6 +91,8 @@ use std::{fmt, str};
 #[derive(Clone)]
 pub struct Store {
     storage: Storage,
+    graph_hashes: Arc<OnceLock<Arc<GraphHashCache>>>,
+    text_index: Arc<OnceLock<Arc<StoreTextIndex>>>,
 }
 
 impl Store {
@@ -89,6 +100,8 @@ impl Store {
     pub fn new() -> Result<Self, StorageError> {
         Ok(Self {
             storage: Storage::new()?,
+            graph_hashes: Arc::default(),
+            text_index: Arc::default(),
         })
     }
 
@@ -97,20 +110,30 @@ impl Store {
     /// Only one read-write [`Store`] can exist at the same time.
     /// If you want to have extra [`Store`] instance opened on the same data
     /// use [`Store::open_read_only`].
+    ///
+    /// A database written by an older Oxigraph version is migrated to the current storage format.
+    /// This migration is one-way: older Oxigraph versions are not able to open the database anymore.
     #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
     pub fn open(path: impl AsRef<Path>) -> Result<Self, StorageError> {
         Ok(Self {
             storage: Storage::open(path.as_ref())?,
+            graph_hashes: Arc::default(),
+            text_index: Arc::default(),
         })
     }
 
     /// Opens a read-only [`Store`] from disk.
     ///
     /// Opening as read-only while having an other process writing the database is undefined behavior.
+    ///
+    /// A database written by an older Oxigraph version is not migrated and fails to open:
+    /// it must first be opened once with [`Store::open`].
     #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
     pub fn open_read_only(path: impl AsRef<Path>) -> Result<Self, StorageError> {
         Ok(Self {
             storage: Storage::open_read_only(path.as_ref())?,
+            graph_hashes: Arc::default(),
+            text_index: Arc::default(),
         })
     }
 
@@ -172,6 +195,16 @@ impl Store {
         query: impl TryInto<Query, Error = impl Into<EvaluationError>>,
         options: QueryOptions,
     ) -> Result<QueryResults, EvaluationError> {
+        if let Some(results_cache) = options.results_cache().cloned() {
+            let query = query.try_into().map_err(Into::into)?;
+            // The version is read before the evaluation snapshot is taken so that cached results are never older than it
+            let version = self.storage.version();
+            if let Some(results) = results_cache.get(&query, version) {
+                return Ok(results);
+            }
+            let (results, _) = self.explain_query_opt(query.clone(), options, false)?;
+            return Ok(results_cache.record(query, version, results?));
+        }
         let (results, _) = self.explain_query_opt(query, options, false)?;
         results
     }
@@ -280,8 +313,14 @@ impl Store {
         with_stats: bool,
         substitutions: impl IntoIterator<Item = (Variable, Term)>,
     ) -> Result<(Result<QueryResults, EvaluationError>, QueryExplanation), EvaluationError> {
+        let options = if let Some(text_index) = self.text_index.get() {
+            options.with_text_index(Arc::clone(&text_index.index))
+        } else {
+            options
+        };
         evaluate_query(
             self.storage.snapshot(),
+            Some(&self.storage),
             query,
             options,
             with_stats,
@@ -413,12 +452,60 @@ impl Store {
         self.storage.snapshot().is_empty()
     }
 
+    /// Returns a read-only view of the store content at the time of the call.
+    ///
+    /// The changes done to the store after the snapshot creation, by transactions or by bulk loads, are not visible through it.
+    /// Snapshots are cheap: they do not copy the store content but keep alive the data they see.
+    /// It is useful to run multiple queries on a consistent state of the store while it is being modified.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::model::*;
+    /// use oxigraph::sparql::QueryResults;
+    /// use oxigraph::store::Store;
+    ///
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// let store = Store::new()?;
+    /// store.insert(QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph))?;
+    /// let snapshot = store.snapshot();
+    /// store.remove(QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph))?;
+    ///
+    /// assert!(store.is_empty()?);
+    /// assert_eq!(snapshot.len()?, 1);
+    /// if let QueryResults::Boolean(result) = snapshot.query("ASK { ?s ?p ?o }")? {
+    ///     assert!(result);
+    /// }
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn snapshot(&self) -> StoreSnapshot {
+        StoreSnapshot {
+            reader: self.storage.snapshot(),
+            text_index: self.text_index.get().cloned(),
+        }
+    }
+
     /// Executes a transaction.
     ///
     /// Transactions ensure the "repeatable read" isolation level: the store only exposes changes that have
     /// been "committed" (i.e. no partial writes) and the exposed state does not change for the complete duration
     /// of a read operation (e.g. a SPARQL query) or a read/write operation (e.g. a SPARQL update).
     ///
+    /// Read/write transactions are also serializable: a transaction never misses the changes of a concurrent transaction it has read data from.
+    /// With the on-disk storage, a transaction conflicts with the concurrent transactions writing to the graphs it reads
+    /// and is retried if they commit first. The in-memory storage executes the read/write transactions one after the other.
+    ///
+    /// <div class="warning">With the on-disk storage, the read/write transactions changing the store are in practice executed one after the other:
+    /// they all update a dataset-wide key when they commit, so if two of them run concurrently one is retried.
+    /// The transactions writing to the same graph, for example the default graph, also wait for each other because they all update the graph metadata.</div>
+    ///
+    /// The given function must not start another transaction on the same store.
+    /// With the on-disk storage, it fails with a conflict error if both transactions use the same graphs,
+    /// with the in-memory storage it never ends.
+    ///
+    /// The reads done through the [`Transaction`] object, including SPARQL queries and updates, see the changes already done in the transaction.
+    /// If the function returns an error, the transaction is rolled back.
+    /// The function might be called multiple times if the transaction has to be retried.
+    ///
     /// Usage example:
     /// ```
     /// use oxigraph::model::*;
@@ -442,7 +529,392 @@ impl Store {
         &self,
         f: impl for<'a> Fn(Transaction<'a>) -> Result<T, E>,
     ) -> Result<T, E> {
-        self.storage.transaction(|writer| f(Transaction { writer }))
+        self.storage.transaction(|writer| {
+            f(Transaction {
+                writer,
+                text_index: self.text_index.get().cloned(),
+            })
+        })
+    }
+
+    /// Registers a function called after each committed transaction with the quads it inserted and removed.
+    ///
+    /// The changes are given in the order they have been done in the transaction.
+    /// Transactions without any change are not reported.
+    ///
+    /// <div class="warning">
+    ///
+    /// The function is called in the thread that committed the transaction so it should be fast.
+    /// The loads done with the [`bulk_loader`](Store::bulk_loader) are not reported.</div>
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::model::*;
+    /// use oxigraph::store::{QuadChange, Store};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let store = Store::new()?;
+    /// let changes = Arc::new(Mutex::new(Vec::new()));
+    /// let changes_clone = Arc::clone(&changes);
+    /// store.on_commit(move |c| changes_clone.lock().unwrap().extend_from_slice(c));
+    ///
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// let quad = QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph);
+    /// store.insert(quad)?;
+    /// store.clear()?;
+    /// assert_eq!(
+    ///     *changes.lock().unwrap(),
+    ///     [
+    ///         QuadChange::Insert(quad.into_owned()),
+    ///         QuadChange::Remove(quad.into_owned())
+    ///     ]
+    /// );
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn on_commit(&self, listener: impl Fn(&[QuadChange]) + Send + Sync + 'static) {
+        self.storage.on_commit(listener)
+    }
+
+    /// Starts to record in the store history the quads inserted and removed by each committed transaction.
+    ///
+    /// Each transaction changing the store creates a new history version with its changes and its timestamp.
+    /// Version 0 is the store state when the history has been enabled.
+    /// The history of an on-disk store is persisted and stays enabled when the store is opened again.
+    /// Calling this method when the history is already enabled does nothing.
+    ///
+    /// The quads written by the <a href="#method.bulk_loader"><code>bulk_loader</code></a> are not recorded:
+    /// each bulk load creates a version without changes for which [`HistoryEntry::is_bulk_load`] is `true`
+    /// and [`dataset_at_version`](Store::dataset_at_version) fails for the versions before it.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::model::*;
+    /// use oxigraph::store::{QuadChange, StorageError, Store};
+    ///
+    /// let store = Store::new()?;
+    /// store.enable_history()?;
+    /// assert_eq!(store.history_version()?, Some(0));
+    ///
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// let quad = QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph);
+    /// store.insert(quad)?;
+    /// store.remove(quad)?;
+    /// assert_eq!(store.history_version()?, Some(2));
+    ///
+    /// let changes = store
+    ///     .history_since(0)?
+    ///     .map(|entry| Ok(entry?.changes().to_vec()))
+    ///     .collect::<Result<Vec<_>, StorageError>>()?;
+    /// assert_eq!(
+    ///     changes,
+    ///     [
+    ///         [QuadChange::Insert(quad.into_owned())],
+    ///         [QuadChange::Remove(quad.into_owned())]
+    ///     ]
+    /// );
+    /// assert!(store.dataset_at_version(1)?.contains(quad));
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn enable_history(&self) -> Result<(), StorageError> {
+        self.storage.enable_history()
+    }
+
+    /// Returns the last version of the store history, `None` if the history is not enabled.
+    ///
+    /// See [`enable_history`](Store::enable_history).
+    pub fn history_version(&self) -> Result<Option<u64>, StorageError> {
+        self.storage.snapshot().history_version()
+    }
+
+    /// Returns the history entries created after the given version, from the oldest to the newest.
+    ///
+    /// Fails if the history is not enabled.
+    /// See [`enable_history`](Store::enable_history) for a usage example.
+    pub fn history_since(&self, version: u64) -> Result<HistoryIter, StorageError> {
+        let reader = self.storage.snapshot();
+        let last_version = reader
+            .history_version()?
+            .ok_or_else(|| StorageError::Other("The store history is not enabled".into()))?;
+        Ok(HistoryIter {
+            reader,
+            next_version: version.saturating_add(1),
+            last_version,
+        })
+    }
+
+    /// Returns the content of the store at the given history version.
+    ///
+    /// The dataset is computed by undoing the changes done after the version on the current store content.
+    /// Fails if the history is not enabled, if the version does not exist yet
+    /// or if a bulk load happened after it (see [`HistoryEntry::is_bulk_load`]).
+    /// See [`enable_history`](Store::enable_history) for a usage example.
+    pub fn dataset_at_version(&self, version: u64) -> Result<Dataset, StorageError> {
+        let reader = self.storage.snapshot();
+        let last_version = reader
+            .history_version()?
+            .ok_or_else(|| StorageError::Other("The store history is not enabled".into()))?;
+        if version > last_version {
+            return Err(StorageError::Other(
+                format!(
+                    "The history version {version} does not exist, the last one is {last_version}"
+                )
+                .into(),
+            ));
+        }
+        let mut dataset = QuadIter {
+            iter: reader.quads_for_pattern(None, None, None, None),
+            reader: reader.clone(),
+        }
+        .collect::<Result<Dataset, _>>()?;
+        for entry in (version + 1..=last_version).rev() {
+            let entry = reader.history_entry(entry)?.ok_or_else(|| {
+                CorruptionError::msg(format!("The history version {entry} is missing"))
+            })?;
+            if entry.is_bulk_load() {
+                return Err(StorageError::Other(
+                    format!(
+                        "The history version {version} can't be rebuilt, the bulk load of version {} has not been recorded",
+                        entry.version()
+                    )
+                    .into(),
+                ));
+            }
+            for change in entry.into_changes().into_iter().rev() {
+                match change {
+                    QuadChange::Insert(quad) => {
+                        dataset.remove(&quad);
+                    }
+                    QuadChange::Remove(quad) => {
+                        dataset.insert(&quad);
+                    }
+                    // Datasets do not contain empty graphs
+                    QuadChange::InsertNamedGraph(_) | QuadChange::RemoveNamedGraph(_) => (),
+                }
+            }
+        }
+        Ok(dataset)
+    }
+
+    /// Returns the quads to remove from the store and the quads to add to it to get the given dataset.
+    ///
+    /// The store content is loaded in memory and compared with [`Dataset::diff`]:
+    /// isomorphic parts with blank nodes do not show up in the diff.
+    /// The returned diff can be applied with [`apply_diff`](Store::apply_diff).
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::model::*;
+    /// use oxigraph::store::Store;
+    ///
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// let old_quad = QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph);
+    /// let new_quad = QuadRef::new(ex, ex, LiteralRef::from("new"), GraphNameRef::DefaultGraph);
+    ///
+    /// let store = Store::new()?;
+    /// store.insert(QuadRef::new(&BlankNode::default(), ex, ex, GraphNameRef::DefaultGraph))?;
+    /// store.insert(old_quad)?;
+    ///
+    /// let mut target = Dataset::new();
+    /// target.insert(QuadRef::new(&BlankNode::default(), ex, ex, GraphNameRef::DefaultGraph));
+    /// target.insert(new_quad);
+    ///
+    /// let diff = store.diff(&target)?;
+    /// assert_eq!(diff.removed(), [old_quad.into_owned()]);
+    /// assert_eq!(diff.added(), [new_quad.into_owned()]);
+    ///
+    /// store.apply_diff(&diff)?;
+    /// assert!(store.diff(&target)?.is_empty());
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    ///
+    /// <div class="warning">The computation worst-case complexity is in *O(b!)* with *b* the number of blank nodes in the store and the dataset.</div>
+    pub fn diff(&self, target: &Dataset) -> Result<DatasetDiff, StorageError> {
+        Ok(self.iter().collect::<Result<Dataset, _>>()?.diff(target))
+    }
+
+    /// Removes the removed quads of the diff and inserts its added quads in a single transaction.
+    ///
+    /// See [`diff`](Store::diff) for a usage example.
+    pub fn apply_diff(&self, diff: &DatasetDiff) -> Result<(), StorageError> {
+        self.transaction(|mut transaction| {
+            for quad in diff.removed() {
+                transaction.remove(quad)?;
+            }
+            for quad in diff.added() {
+                transaction.insert(quad)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Makes the store keep the hashes returned by [`graph_canonical_hash`](Store::graph_canonical_hash) in memory.
+    ///
+    /// The hash of a graph is computed on its first request and kept until a transaction or a bulk load changes the graph.
+    /// The cache is shared by all the clones of this [`Store`] value.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::model::*;
+    /// use oxigraph::store::Store;
+    ///
+    /// let store = Store::new()?;
+    /// store.enable_graph_hash_cache();
+    ///
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// let empty_hash = store.graph_canonical_hash(ex)?;
+    /// store.insert(QuadRef::new(ex, ex, ex, ex))?;
+    /// assert_ne!(store.graph_canonical_hash(ex)?, empty_hash);
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn enable_graph_hash_cache(&self) {
+        self.graph_hashes.get_or_init(|| {
+            let cache = Arc::new(GraphHashCache::default());
+            let listener_cache = Arc::clone(&cache);
+            self.storage.on_commit(move |changes| {
+                // Empty and non-existing graphs have the same hash
+                listener_cache.invalidate(changes.iter().filter_map(|change| match change {
+                    QuadChange::Insert(quad) | QuadChange::Remove(quad) => {
+                        Some(quad.graph_name.as_ref())
+                    }
+                    QuadChange::InsertNamedGraph(_) | QuadChange::RemoveNamedGraph(_) => None,
+                }))
+            });
+            cache
+        });
+    }
+
+    /// Returns the SHA-256 digest of the [RDFC-1.0](https://www.w3.org/TR/rdf-canon/) canonical form of a graph of the store.
+    ///
+    /// Two graphs have the same hash if and only if they are isomorphic, making it a content-based identifier of the graph.
+    /// See [`Graph::canonical_hash`] for more details.
+    /// Empty and non-existing graphs have the same hash.
+    ///
+    /// The hash is computed from the graph content on each call except if [`enable_graph_hash_cache`](Store::enable_graph_hash_cache) has been called.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::model::*;
+    /// use oxigraph::store::Store;
+    ///
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// let g1 = NamedNodeRef::new("http://example.com/g1")?;
+    /// let g2 = NamedNodeRef::new("http://example.com/g2")?;
+    ///
+    /// let store = Store::new()?;
+    /// store.insert(QuadRef::new(ex, ex, &BlankNode::default(), g1))?;
+    /// store.insert(QuadRef::new(ex, ex, &BlankNode::default(), g2))?;
+    /// assert_eq!(
+    ///     store.graph_canonical_hash(g1)?,
+    ///     store.graph_canonical_hash(g2)?
+    /// );
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    ///
+    /// <div class="warning">The computation worst-case complexity is in *O(b!)* with *b* the number of blank nodes in the graph.</div>
+    pub fn graph_canonical_hash<'a>(
+        &self,
+        graph_name: impl Into<GraphNameRef<'a>>,
+    ) -> Result<[u8; 32], StorageError> {
+        let graph_name = graph_name.into();
+        let Some(cache) = self.graph_hashes.get() else {
+            return self.compute_graph_canonical_hash(graph_name);
+        };
+        let generation = {
+            let state = cache.state.lock().unwrap_or_else(PoisonError::into_inner);
+            if let Some(hash) = state.hashes.get(&graph_name.into_owned()) {
+                return Ok(*hash);
+            }
+            state.generation
+        };
+        let hash = self.compute_graph_canonical_hash(graph_name)?;
+        let mut state = cache.state.lock().unwrap_or_else(PoisonError::into_inner);
+        // We do not cache the hash if the store has been modified during its computation
+        if state.generation == generation {
+            state.hashes.insert(graph_name.into_owned(), hash);
+        }
+        Ok(hash)
+    }
+
+    /// Maintains a full-text index of the literal values of the given predicates.
+    ///
+    /// The index is filled with the current store content and then updated after each transaction and bulk load.
+    /// It can be queried from SPARQL using the `SERVICE <http://oxigraph.org/fts#search>` service with the following triple patterns:
+    /// * `?s ofts:query "words"` (required) binds `?s` to the subjects of the matching literals.
+    /// * `?s ofts:literal ?l`, `?s ofts:predicate ?p` and `?s ofts:graph ?g` bind the matching literal, its predicate and its graph name.
+    /// * `?s ofts:score ?score` binds the relevance of the match.
+    /// * `?s ofts:limit 10` limits the number of matches returned by the index.
+    ///
+    /// `ofts:` is the `http://oxigraph.org/fts#` prefix. The index only contains committed data:
+    /// the changes done in a not yet committed [`Transaction`] are not visible to the search.
+    ///
+    /// Only one index can be enabled per store, this function fails if an index is already enabled.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::model::vocab::rdfs;
+    /// use oxigraph::model::*;
+    /// use oxigraph::sparql::{InMemoryTextIndex, QueryResults};
+    /// use oxigraph::store::Store;
+    ///
+    /// let store = Store::new()?;
+    /// store.enable_text_index([rdfs::LABEL.into_owned()], InMemoryTextIndex::default())?;
+    ///
+    /// let ex = NamedNodeRef::new("http://example.com/paris")?;
+    /// store.insert(QuadRef::new(
+    ///     ex,
+    ///     rdfs::LABEL,
+    ///     LiteralRef::new_simple_literal("Paris, capital of France"),
+    ///     GraphNameRef::DefaultGraph,
+    /// ))?;
+    ///
+    /// if let QueryResults::Solutions(mut solutions) = store.query(
+    ///     "PREFIX ofts: <http://oxigraph.org/fts#>
+    ///     SELECT ?s ?score WHERE { SERVICE ofts:search { ?s ofts:query 'capital' ; ofts:score ?score } }",
+    /// )? {
+    ///     assert_eq!(solutions.next().unwrap()?.get("s"), Some(&ex.into()));
+    /// }
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn enable_text_index(
+        &self,
+        predicates: impl IntoIterator<Item = NamedNode>,
+        index: impl TextIndex + 'static,
+    ) -> Result<(), StorageError> {
+        let text_index = Arc::new(StoreTextIndex {
+            index: Arc::new(index),
+            predicates: predicates.into_iter().collect(),
+        });
+        self.text_index
+            .set(Arc::clone(&text_index))
+            .map_err(|_| StorageError::Other("A text index is already enabled".into()))?;
+        // We register the listener before reading the store to not miss any change
+        let listener_index = Arc::clone(&text_index);
+        self.storage.on_commit(move |changes| {
+            for change in changes {
+                match change {
+                    QuadChange::Insert(quad) => listener_index.insert(quad.as_ref()),
+                    QuadChange::Remove(quad) => listener_index.remove(quad.as_ref()),
+                    QuadChange::InsertNamedGraph(_) | QuadChange::RemoveNamedGraph(_) => (),
+                }
+            }
+        });
+        for predicate in &text_index.predicates {
+            for quad in self.quads_for_pattern(None, Some(predicate.as_ref()), None, None) {
+                text_index.insert(quad?.as_ref());
+            }
+        }
+        Ok(())
+    }
+
+    fn compute_graph_canonical_hash(
+        &self,
+        graph_name: GraphNameRef<'_>,
+    ) -> Result<[u8; 32], StorageError> {
+        let graph = self
+            .quads_for_pattern(None, None, None, Some(graph_name))
+            .map(|quad| Ok(Triple::from(quad?)))
+            .collect::<Result<Graph, StorageError>>()?;
+        Ok(graph.canonical_hash())
     }
 
     /// Executes a [SPARQL 1.1 update](https://www.w3.org/TR/sparql11-update/).
@@ -493,7 +965,10 @@ impl Store {
         options: impl Into<UpdateOptions>,
     ) -> Result<(), EvaluationError> {
         let update = update.try_into().map_err(Into::into)?;
-        let options = options.into();
+        let mut options = options.into();
+        if let Some(text_index) = self.text_index.get() {
+            options = options.with_text_index(Arc::clone(&text_index.index));
+        }
         self.storage
             .transaction(|mut t| evaluate_update(&mut t, &update, &options))
     }
@@ -502,6 +977,8 @@ impl Store {
     ///
     /// This function is atomic, quite slow and memory hungry. To get much better performances you might want to use the [`bulk_loader`](Store::bulk_loader).
     ///
+    /// Returns a [`LoadReport`] with the number of new and duplicate quads.
+    ///
     /// Usage example:
     /// ```
     /// use oxigraph::store::Store;
@@ -535,17 +1012,18 @@ impl Store {
         &self,
         parser: impl Into<RdfParser>,
         reader: impl Read,
-    ) -> Result<(), LoaderError> {
+    ) -> Result<LoadReport, LoaderError> {
         let quads = parser
             .into()
             .rename_blank_nodes()
             .for_reader(reader)
             .collect::<Result<Vec<_>, _>>()?;
         self.storage.transaction(move |mut t| {
+            let mut report = LoadReport::default();
             for quad in &quads {
-                t.insert(quad.as_ref())?;
+                report.add(quad.graph_name.as_ref(), t.insert(quad.as_ref())?);
             }
-            Ok(())
+            Ok(report)
         })
     }
 
@@ -594,7 +1072,8 @@ impl Store {
                     error: e,
                 })?;
         }
-        self.load_from_reader(parser, reader)
+        self.load_from_reader(parser, reader)?;
+        Ok(())
     }
 
     /// Loads a dataset file (i.e. quads) into the store.
@@ -635,7 +1114,8 @@ impl Store {
                     error: e,
                 })?;
         }
-        self.load_from_reader(parser, reader)
+        self.load_from_reader(parser, reader)?;
+        Ok(())
     }
 
     /// Adds a quad to this store.
@@ -662,6 +1142,59 @@ impl Store {
         self.transaction(|mut t| t.insert(quad))
     }
 
+    /// Adds atomically a quad to this store and annotates it with the quad `<< triple >> predicate object graph_name`,
+    /// like `triple {| predicate object |}` does in TriG.
+    ///
+    /// The annotation is added to the same graph as the annotated quad.
+    /// Returns `true` if the annotation was not already in the store.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::model::*;
+    /// use oxigraph::store::Store;
+    ///
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// let source = NamedNodeRef::new("http://example.com/source")?;
+    /// let quad = QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph);
+    ///
+    /// let store = Store::new()?;
+    /// assert!(store.annotate(quad, source, ex)?);
+    /// assert!(store.contains(quad)?);
+    ///
+    /// let annotations = store.annotations(quad).collect::<Result<Vec<_>, _>>()?;
+    /// assert_eq!(annotations.len(), 1);
+    /// assert_eq!(annotations[0].predicate, source);
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn annotate<'a>(
+        &self,
+        quad: impl Into<QuadRef<'a>>,
+        predicate: impl Into<NamedNodeRef<'a>>,
+        object: impl Into<TermRef<'a>>,
+    ) -> Result<bool, StorageError> {
+        let quad = quad.into();
+        let subject = Subject::Triple(Box::new(TripleRef::from(quad).into_owned()));
+        let annotation =
+            QuadRef::new(&subject, predicate.into(), object.into(), quad.graph_name).into_owned();
+        self.transaction(|mut t| {
+            t.insert(quad)?;
+            t.insert(&annotation)
+        })
+    }
+
+    /// Returns the quads whose subject is the triple of the given quad and that are in the same graph.
+    ///
+    /// See [`annotate`](Self::annotate) for an example.
+    pub fn annotations<'a>(&self, quad: impl Into<QuadRef<'a>>) -> QuadIter {
+        let quad = quad.into();
+        self.quads_for_pattern(
+            Some(SubjectRef::Triple(&TripleRef::from(quad).into_owned())),
+            None,
+            None,
+            Some(quad.graph_name),
+        )
+    }
+
     /// Adds atomically a set of quads to this store.
     ///
     /// <div class="warning">
@@ -733,6 +1266,81 @@ impl Store {
         Ok(serializer.finish()?)
     }
 
+    /// Dumps the store into a file with some [`DumpOptions`].
+    ///
+    /// It allows to dump only a graph or only the language-tagged strings of some languages.
+    ///
+    /// ```
+    /// use oxigraph::io::RdfFormat;
+    /// use oxigraph::store::{DumpOptions, Store};
+    ///
+    /// let file = "<http://example.com> <http://example.com> \"foo\"@en-GB .
+    /// <http://example.com> <http://example.com> \"toto\"@fr .
+    /// "
+    /// .as_bytes();
+    ///
+    /// let store = Store::new()?;
+    /// store.load_from_reader(RdfFormat::NQuads, file)?;
+    ///
+    /// let buffer = store.dump_to_writer_opt(
+    ///     RdfFormat::NQuads,
+    ///     Vec::new(),
+    ///     DumpOptions::default().with_language("en"),
+    /// )?;
+    /// assert_eq!(
+    ///     buffer,
+    ///     b"<http://example.com> <http://example.com> \"foo\"@en-gb .\n"
+    /// );
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn dump_to_writer_opt<W: Write>(
+        &self,
+        serializer: impl Into<RdfSerializer>,
+        writer: W,
+        options: DumpOptions,
+    ) -> Result<W, SerializerError> {
+        let DumpOptions {
+            graph_name,
+            dataset_graphs,
+            languages,
+        } = options;
+        let serializer = serializer.into();
+        if graph_name.is_none() && !serializer.format().supports_datasets() {
+            return Err(SerializerError::DatasetFormatExpected(serializer.format()));
+        }
+        let mut serializer = serializer.for_writer(writer);
+        let is_single_graph = graph_name.is_some();
+        let graph_names = if let Some(graph_name) = graph_name {
+            vec![Some(graph_name)]
+        } else if dataset_graphs.is_empty() {
+            vec![None]
+        } else {
+            // Each graph is dumped once even if given multiple times
+            let mut seen = HashSet::new();
+            dataset_graphs
+                .into_iter()
+                .filter(|graph_name| seen.insert(graph_name.clone()))
+                .map(Some)
+                .collect()
+        };
+        for graph_name in graph_names {
+            for quad in
+                self.quads_for_pattern(None, None, None, graph_name.as_ref().map(GraphName::as_ref))
+            {
+                let quad = quad?;
+                if !is_language_kept(&languages, quad.object.as_ref()) {
+                    continue;
+                }
+                if is_single_graph {
+                    serializer.serialize_triple(quad.as_ref())?;
+                } else {
+                    serializer.serialize_quad(&quad)?;
+                }
+            }
+        }
+        Ok(serializer.finish()?)
+    }
+
     /// Dumps a store graph into a file.
     ///    
     /// Usage example:
@@ -764,16 +1372,72 @@ impl Store {
         Ok(serializer.finish()?)
     }
 
-    /// Dumps a store graph into a file.
-    ///    
-    /// Usage example:
+    /// Dumps the store into a compact binary snapshot.
+    ///
+    /// The snapshot contains all the quads and named graphs, including the empty ones, with their blank nodes kept as is.
+    /// It is much faster to write and to read back with [`load_snapshot_from_reader`](Self::load_snapshot_from_reader) than the RDF formats,
+    /// making it a way to persist the in-memory stores.
+    /// The history and the graph metadata are not included.
+    ///
+    /// The format is specific to Oxigraph and is versioned: it is only guaranteed to be readable by the same Oxigraph version.
+    ///
     /// ```
-    /// use oxigraph::io::RdfFormat;
     /// use oxigraph::model::*;
     /// use oxigraph::store::Store;
     ///
-    /// let file = "<http://example.com> <http://example.com> <http://example.com> .\n".as_bytes();
-    ///
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// let store = Store::new()?;
+    /// store.insert(QuadRef::new(ex, ex, BlankNode::default().as_ref(), ex))?;
+    /// let snapshot = store.dump_snapshot_to_writer(Vec::new())?;
+    ///
+    /// let restored = Store::new()?;
+    /// restored.load_snapshot_from_reader(snapshot.as_slice())?;
+    /// assert_eq!(
+    ///     store.iter().collect::<Result<Vec<_>, _>>()?,
+    ///     restored.iter().collect::<Result<Vec<_>, _>>()?
+    /// );
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn dump_snapshot_to_writer<W: Write>(&self, mut writer: W) -> Result<W, StorageError> {
+        write_snapshot(&self.storage.snapshot(), &mut writer)?;
+        writer.flush()?;
+        Ok(writer)
+    }
+
+    /// Loads a snapshot written by [`dump_snapshot_to_writer`](Self::dump_snapshot_to_writer) into the store.
+    ///
+    /// The content of the snapshot is added to the store content using the [bulk loader](Self::bulk_loader).
+    ///
+    /// <div class="warning">This method is not atomic.
+    /// If the snapshot is invalid or truncated, only a part of it may have been written to the store.</div>
+    pub fn load_snapshot_from_reader(&self, reader: impl Read) -> Result<LoadReport, StorageError> {
+        let mut named_graphs = Vec::new();
+        let report = self.bulk_loader().load_ok_quads::<_, StorageError>(
+            SnapshotReader::new(BufReader::new(reader))?.filter_map(|record| match record {
+                Ok(SnapshotRecord::Quad(quad)) => Some(Ok(quad)),
+                Ok(SnapshotRecord::NamedGraph(graph_name)) => {
+                    named_graphs.push(graph_name);
+                    None
+                }
+                Err(e) => Some(Err(e)),
+            }),
+        )?;
+        for graph_name in &named_graphs {
+            self.insert_named_graph(graph_name)?;
+        }
+        Ok(report)
+    }
+
+    /// Dumps a store graph into a file.
+    ///    
+    /// Usage example:
+    /// ```
+    /// use oxigraph::io::RdfFormat;
+    /// use oxigraph::model::*;
+    /// use oxigraph::store::Store;
+    ///
+    /// let file = "<http://example.com> <http://example.com> <http://example.com> .\n".as_bytes();
+    ///
     /// let store = Store::new()?;
     /// store.load_graph(file, RdfFormat::NTriples, GraphName::DefaultGraph, None)?;
     ///
@@ -820,6 +1484,11 @@ impl Store {
 
     /// Returns all the store named graphs.
     ///
+    /// Named graphs are kept even if they do not contain any quad anymore
+    /// (e.g. after a [`clear_graph`](Self::clear_graph) call).
+    /// Use [`GraphNameIter::include_empty`] to skip them
+    /// and [`vacuum`](Self::vacuum) to remove them from the store.
+    ///
     /// Usage example:
     /// ```
     /// use oxigraph::model::*;
@@ -840,6 +1509,7 @@ impl Store {
         GraphNameIter {
             iter: reader.named_graphs(),
             reader,
+            include_empty: true,
         }
     }
 
@@ -891,6 +1561,69 @@ impl Store {
         self.transaction(|mut t| t.insert_named_graph(graph_name))
     }
 
+    /// Returns the metadata of a graph: its creation and last modification times, its number of quads and its properties.
+    ///
+    /// Returns `None` if the graph is a named graph that is not in the store.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::model::{NamedNodeRef, QuadRef};
+    /// use oxigraph::store::Store;
+    ///
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// let store = Store::new()?;
+    /// store.insert(QuadRef::new(ex, ex, ex, ex))?;
+    ///
+    /// let metadata = store.graph_metadata(ex)?.unwrap();
+    /// assert_eq!(metadata.len(), 1);
+    /// assert!(metadata.modified().is_some());
+    /// assert!(store.graph_metadata(NamedNodeRef::new("http://example.com/g")?)?.is_none());
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn graph_metadata<'a>(
+        &self,
+        graph_name: impl Into<GraphNameRef<'a>>,
+    ) -> Result<Option<GraphMetadata>, StorageError> {
+        let graph_name = EncodedTerm::from(graph_name.into());
+        let snapshot = self.storage.snapshot();
+        if let Some(metadata) = snapshot.graph_metadata(&graph_name)? {
+            return Ok(Some(metadata));
+        }
+        // The graphs without recorded metadata are empty
+        Ok(
+            (graph_name.is_default_graph() || snapshot.contains_named_graph(&graph_name)?)
+                .then(GraphMetadata::default),
+        )
+    }
+
+    /// Sets a property of a graph metadata, removes it if `value` is `None`.
+    ///
+    /// The named graph is inserted into the store if it does not exist.
+    /// Setting a property does not change the graph modification time.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::model::NamedNodeRef;
+    /// use oxigraph::store::Store;
+    ///
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// let store = Store::new()?;
+    /// store.set_graph_property(ex, "source", Some("http://example.com/dump.nt"))?;
+    ///
+    /// let metadata = store.graph_metadata(ex)?.unwrap();
+    /// assert_eq!(metadata.property("source"), Some("http://example.com/dump.nt"));
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn set_graph_property<'a>(
+        &self,
+        graph_name: impl Into<GraphNameRef<'a>>,
+        key: &str,
+        value: Option<&str>,
+    ) -> Result<(), StorageError> {
+        let graph_name = graph_name.into();
+        self.transaction(|mut t| t.set_graph_property(graph_name, key, value))
+    }
+
     /// Clears a graph from this store.
     ///
     /// Usage example:
@@ -966,6 +1699,33 @@ impl Store {
         self.transaction(|mut t| t.clear())
     }
 
+    /// Removes from this store all the named graphs that do not contain any quad.
+    ///
+    /// Named graphs inserted with [`insert_named_graph`](Self::insert_named_graph)
+    /// or emptied with [`clear_graph`](Self::clear_graph) are kept in the store until they are explicitly removed.
+    /// This method removes all of them at once.
+    ///
+    /// Returns the number of removed named graphs.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::model::*;
+    /// use oxigraph::store::Store;
+    ///
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// let store = Store::new()?;
+    /// store.insert(QuadRef::new(ex, ex, ex, ex))?;
+    /// store.clear_graph(ex)?;
+    /// assert_eq!(1, store.named_graphs().count());
+    ///
+    /// assert_eq!(1, store.vacuum()?);
+    /// assert_eq!(0, store.named_graphs().count());
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn vacuum(&self) -> Result<usize, StorageError> {
+        self.transaction(|mut t| t.vacuum())
+    }
+
     /// Flushes all buffers and ensures that all writes are saved on disk.
     ///
     /// Flushes are automatically done using background threads but might lag a little bit.
@@ -984,6 +1744,56 @@ impl Store {
         self.storage.compact()
     }
 
+    /// Compacts the part of the database storing the given graph.
+    ///
+    /// It is a cheaper alternative to [`Store::optimize`] after a large change to a single graph like [`Store::clear_graph`].
+    /// Only the indexes starting with the graph name are compacted, the other indexes are left untouched.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::model::*;
+    /// use oxigraph::store::Store;
+    ///
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// let store = Store::open("compact_example.db")?;
+    /// store.insert(QuadRef::new(ex, ex, ex, ex))?;
+    /// store.clear_graph(ex)?;
+    /// store.compact_range(ex)?;
+    /// # std::fs::remove_dir_all("compact_example.db")?;
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+    pub fn compact_range<'a>(
+        &self,
+        graph_name: impl Into<GraphNameRef<'a>>,
+    ) -> Result<(), StorageError> {
+        self.storage.compact_graph(graph_name.into())
+    }
+
+    /// Returns statistics about the disk usage of the store.
+    ///
+    /// They are estimations and are only available for on-disk stores.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::model::*;
+    /// use oxigraph::store::Store;
+    ///
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// let store = Store::open("statistics_example.db")?;
+    /// store.insert(QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph))?;
+    /// store.flush()?;
+    /// let statistics = store.size_statistics()?;
+    /// assert!(statistics.approximate_quads() > 0);
+    /// assert!(statistics.indexes().iter().any(|index| index.name() == "dspo"));
+    /// # std::fs::remove_dir_all("statistics_example.db")?;
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+    pub fn size_statistics(&self) -> Result<SizeStatistics, StorageError> {
+        self.storage.size_statistics()
+    }
+
     /// Creates database backup into the `target_directory`.
     ///
     /// After its creation, the backup is usable using [`Store::open`]
@@ -1007,6 +1817,248 @@ impl Store {
         self.storage.backup(target_directory.as_ref())
     }
 
+    /// Creates database backup into the `target_directory` reusing the files of a previous backup.
+    ///
+    /// The database files are immutable: the ones that have not changed since the `previous_backup` creation
+    /// are hard linked from it instead of being copied, making regular backups of large databases cheap
+    /// even if they are written to another file system than the database.
+    /// `previous_backup` must be a backup of the same database created with [`Store::backup`] or [`Store::incremental_backup`] on the same file system as `target_directory`.
+    /// Each backup is a full database usable with [`Store::open`] and deleting a backup does not impact the other ones.
+    ///
+    /// The backup is first created in a temporary directory inside of the database directory, that must be writable.
+    ///
+    /// <div class="warning">An error is raised if the `target_directory` already exists.</div>
+    #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+    pub fn incremental_backup(
+        &self,
+        target_directory: impl AsRef<Path>,
+        previous_backup: impl AsRef<Path>,
+    ) -> Result<(), StorageError> {
+        self.storage
+            .incremental_backup(target_directory.as_ref(), previous_backup.as_ref())
+    }
+
+    /// Loads the store indexes into the caches to make the first queries faster, e.g. right after the store opening.
+    ///
+    /// The beginning of each index is read up to the size set with [`WarmUpOptions::with_max_size_in_megabytes`].
+    /// The queries added with [`WarmUpOptions::with_query`], for example the ones usually sent to the store, are then evaluated and their results discarded.
+    ///
+    /// Reading the indexes is a no-op for in-memory stores.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::sparql::Query;
+    /// use oxigraph::store::{Store, WarmUpOptions};
+    ///
+    /// let store = Store::new()?;
+    /// store.warm_up(
+    ///     WarmUpOptions::default()
+    ///         .with_max_size_in_megabytes(512)
+    ///         .with_query(Query::parse("SELECT * WHERE { ?s a ?type }", None)?),
+    /// )?;
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn warm_up(&self, options: WarmUpOptions) -> Result<(), EvaluationError> {
+        self.storage.snapshot().warm_up(
+            options
+                .max_size
+                .map(|size| size.saturating_mul(1024 * 1024)),
+        )?;
+        for query in options.queries {
+            match self.query(query)? {
+                QueryResults::Solutions(solutions) => {
+                    for solution in solutions {
+                        solution?;
+                    }
+                }
+                QueryResults::Graph(triples) => {
+                    for triple in triples {
+                        triple?;
+                    }
+                }
+                QueryResults::Boolean(_) => (),
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the quads whose object is an IRI without description,
+    /// i.e. that is not the subject of any quad in the graphs given by [`ReferenceCheckOptions::with_description_graph`].
+    ///
+    /// Only the quads using the predicates given by [`ReferenceCheckOptions::with_predicate`] are checked.
+    /// The check is done in a single streaming pass over these quads with an index lookup per quad
+    /// and does not need to keep the references in memory.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::model::*;
+    /// use oxigraph::store::{ReferenceCheckOptions, Store};
+    ///
+    /// let store = Store::new()?;
+    /// let knows = NamedNodeRef::new("http://schema.org/knows")?;
+    /// let name = NamedNodeRef::new("http://schema.org/name")?;
+    /// let alice = NamedNodeRef::new("http://example.com/alice")?;
+    /// let bob = NamedNodeRef::new("http://example.com/bob")?;
+    /// let carol = NamedNodeRef::new("http://example.com/carol")?;
+    /// let alice_name = LiteralRef::new_simple_literal("Alice");
+    /// store.insert(QuadRef::new(alice, name, alice_name, GraphNameRef::DefaultGraph))?;
+    /// store.insert(QuadRef::new(bob, knows, alice, GraphNameRef::DefaultGraph))?;
+    /// store.insert(QuadRef::new(alice, knows, carol, GraphNameRef::DefaultGraph))?;
+    ///
+    /// // carol is not described
+    /// let dangling = store
+    ///     .dangling_references(ReferenceCheckOptions::default().with_predicate(knows))
+    ///     .collect::<Result<Vec<_>, _>>()?;
+    /// assert_eq!(
+    ///     dangling,
+    ///     [QuadRef::new(alice, knows, carol, GraphNameRef::DefaultGraph).into_owned()]
+    /// );
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn dangling_references(&self, options: ReferenceCheckOptions) -> DanglingReferenceIter {
+        DanglingReferenceIter {
+            predicates: options
+                .predicates
+                .into_iter()
+                .map(|p| EncodedTerm::from(p.as_ref()))
+                .collect::<Vec<_>>()
+                .into_iter(),
+            description_graphs: options
+                .description_graphs
+                .into_iter()
+                .map(|g| EncodedTerm::from(g.as_ref()))
+                .collect(),
+            iter: None,
+            reader: self.storage.snapshot(),
+        }
+    }
+
+    /// Computes the number of quads and of distinct subjects and objects of each predicate.
+    ///
+    /// These statistics can be given to the query optimizer using [`QueryOptions::with_statistics`].
+    /// They are not updated automatically and should be computed again after large changes to the store.
+    ///
+    /// <div class="warning">This function executes a full scan and keeps the distinct subjects and objects of all predicates in memory.</div>
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::model::*;
+    /// use oxigraph::sparql::{QueryOptions, QueryResults};
+    /// use oxigraph::store::Store;
+    /// use std::sync::Arc;
+    ///
+    /// let store = Store::new()?;
+    /// let knows = NamedNodeRef::new("http://schema.org/knows")?;
+    /// let alice = NamedNodeRef::new("http://example.com/alice")?;
+    /// let bob = NamedNodeRef::new("http://example.com/bob")?;
+    /// store.insert(QuadRef::new(alice, knows, bob, GraphNameRef::DefaultGraph))?;
+    /// store.insert(QuadRef::new(bob, knows, alice, GraphNameRef::DefaultGraph))?;
+    ///
+    /// let statistics = store.statistics()?;
+    /// let knows_statistics = statistics.get(&knows.into_owned()).unwrap();
+    /// assert_eq!(knows_statistics.count, 2);
+    /// assert_eq!(knows_statistics.distinct_subjects, 2);
+    /// assert_eq!(knows_statistics.distinct_objects, 2);
+    ///
+    /// if let QueryResults::Solutions(solutions) = store.query_opt(
+    ///     "SELECT * WHERE { ?a <http://schema.org/knows> ?b . ?b <http://schema.org/knows> ?a }",
+    ///     QueryOptions::default().with_statistics(Arc::new(statistics)),
+    /// )? {
+    ///     assert_eq!(solutions.count(), 2);
+    /// }
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn statistics(&self) -> Result<DatasetStatistics, StorageError> {
+        let reader = self.storage.snapshot();
+        let mut predicates =
+            HashMap::<EncodedTerm, (usize, HashSet<EncodedTerm>, HashSet<EncodedTerm>)>::new();
+        for quad in reader.quads_for_pattern(None, None, None, None) {
+            let quad = quad?;
+            let (count, subjects, objects) = predicates.entry(quad.predicate).or_default();
+            *count += 1;
+            subjects.insert(quad.subject);
+            objects.insert(quad.object);
+        }
+        predicates
+            .into_iter()
+            .map(|(predicate, (count, subjects, objects))| {
+                Ok((
+                    reader.decode_named_node(&predicate)?,
+                    PredicateStatistics {
+                        count,
+                        distinct_subjects: subjects.len(),
+                        distinct_objects: objects.len(),
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    /// Returns the number of language-tagged strings in the store, grouped by language tag.
+    ///
+    /// Only the language-tagged strings used as quad objects are counted.
+    /// The language tags are in lower case.
+    ///
+    /// <div class="warning">This operation reads all the quads of the store.</div>
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::model::*;
+    /// use oxigraph::store::{LanguageStatistics, Store};
+    ///
+    /// let store = Store::new()?;
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// let label = NamedNodeRef::new("http://www.w3.org/2000/01/rdf-schema#label")?;
+    /// let foo = LiteralRef::new_language_tagged_literal_unchecked("foo", "en");
+    /// store.insert(QuadRef::new(ex, label, foo, GraphNameRef::DefaultGraph))?;
+    /// store.insert(QuadRef::new(ex, label, foo, ex))?;
+    ///
+    /// let statistics = store.language_statistics()?;
+    /// assert_eq!(
+    ///     statistics.get("en"),
+    ///     Some(&LanguageStatistics {
+    ///         count: 2,
+    ///         distinct_literals: 1
+    ///     })
+    /// );
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn language_statistics(
+        &self,
+    ) -> Result<BTreeMap<String, LanguageStatistics>, StorageError> {
+        let reader = self.storage.snapshot();
+        let mut literals = HashMap::<EncodedTerm, usize>::new();
+        for quad in reader.quads_for_pattern(None, None, None, None) {
+            let quad = quad?;
+            if matches!(
+                quad.object,
+                EncodedTerm::SmallSmallLangStringLiteral { .. }
+                    | EncodedTerm::SmallBigLangStringLiteral { .. }
+                    | EncodedTerm::BigSmallLangStringLiteral { .. }
+                    | EncodedTerm::BigBigLangStringLiteral { .. }
+            ) {
+                *literals.entry(quad.object).or_default() += 1;
+            }
+        }
+        let mut languages = BTreeMap::<String, LanguageStatistics>::new();
+        for (literal, count) in literals {
+            let Term::Literal(literal) = reader.decode_term(&literal)? else {
+                return Err(
+                    CorruptionError::msg("A language-tagged string is not a literal").into(),
+                );
+            };
+            let Some(language) = literal.language() else {
+                return Err(
+                    CorruptionError::msg("A language-tagged string has no language tag").into(),
+                );
+            };
+            let statistics = languages.entry(language.to_owned()).or_default();
+            statistics.count += count;
+            statistics.distinct_literals += 1;
+        }
+        Ok(languages)
+    }
+
     /// Creates a bulk loader allowing to load at lot of data quickly into the store.
     ///
     /// Usage example:
@@ -1033,6 +2085,9 @@ impl Store {
         BulkLoader {
             storage: self.storage.bulk_loader(),
             on_parse_error: None,
+            num_parsing_threads: 1,
+            graph_hashes: self.graph_hashes.get().cloned(),
+            text_index: self.text_index.get().cloned(),
         }
     }
 
@@ -1062,16 +2117,178 @@ impl IntoIterator for &Store {
     }
 }
 
+/// Options of [`Store::warm_up`].
+#[derive(Default, Clone)]
+#[must_use]
+pub struct WarmUpOptions {
+    max_size: Option<usize>,
+    queries: Vec<Query>,
+}
+
+impl WarmUpOptions {
+    /// Sets the maximal size of the index data read in megabytes.
+    ///
+    /// By default, all the indexes are read.
+    /// It is useful to not read more than the size of the storage caches.
+    #[inline]
+    pub fn with_max_size_in_megabytes(mut self, max_size: usize) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Adds a query to evaluate during the warm-up.
+    ///
+    /// It allows to replay an access profile like the queries recorded before a restart.
+    #[inline]
+    pub fn with_query(mut self, query: Query) -> Self {
+        self.queries.push(query);
+        self
+    }
+}
+
+/// Options of [`Store::dangling_references`].
+#[derive(Default, Clone)]
+#[must_use]
+pub struct ReferenceCheckOptions {
+    predicates: Vec<NamedNode>,
+    description_graphs: Vec<GraphName>,
+}
+
+impl ReferenceCheckOptions {
+    /// Adds a predicate whose objects must be described.
+    ///
+    /// No quad is checked if no predicate is given.
+    #[inline]
+    pub fn with_predicate(mut self, predicate: impl Into<NamedNode>) -> Self {
+        self.predicates.push(predicate.into());
+        self
+    }
+
+    /// Adds a graph in which the descriptions are looked for.
+    ///
+    /// By default, the descriptions are looked for in all the graphs.
+    #[inline]
+    pub fn with_description_graph(mut self, graph_name: impl Into<GraphName>) -> Self {
+        self.description_graphs.push(graph_name.into());
+        self
+    }
+}
+
+/// Options of [`Store::dump_to_writer_opt`].
+#[derive(Default, Clone)]
+#[must_use]
+pub struct DumpOptions {
+    graph_name: Option<GraphName>,
+    dataset_graphs: Vec<GraphName>,
+    languages: Vec<String>,
+}
+
+impl DumpOptions {
+    /// Dumps only the given graph.
+    ///
+    /// Its triples are serialized so the serialization format does not need to support datasets.
+    #[inline]
+    pub fn with_graph(mut self, graph_name: impl Into<GraphName>) -> Self {
+        self.graph_name = Some(graph_name.into());
+        self
+    }
+
+    /// Adds a graph to dump with its name, the other graphs are then not dumped.
+    ///
+    /// Can be called multiple times to dump a selection of graphs as a dataset, so the serialization format must support datasets.
+    /// Ignored if [`with_graph`](Self::with_graph) is used.
+    #[inline]
+    pub fn with_dataset_graph(mut self, graph_name: impl Into<GraphName>) -> Self {
+        self.dataset_graphs.push(graph_name.into());
+        self
+    }
+
+    /// Adds a language range whose language-tagged strings are dumped.
+    ///
+    /// If at least one language range is given, the quads whose object is a language-tagged string not matching any of them are skipped.
+    /// The other quads are always dumped.
+    /// The ranges are matched using [RFC 4647 basic filtering](https://www.rfc-editor.org/rfc/rfc4647#section-3.3.1), i.e. `en` matches `en` and `en-GB`, and `*` matches any language tag.
+    #[inline]
+    pub fn with_language(mut self, language_range: impl Into<String>) -> Self {
+        self.languages
+            .push(language_range.into().to_ascii_lowercase());
+        self
+    }
+}
+
+/// Checks if the object is not a language-tagged string or has a language tag matching one of the language ranges
+fn is_language_kept(languages: &[String], object: TermRef<'_>) -> bool {
+    if languages.is_empty() {
+        return true;
+    }
+    let TermRef::Literal(literal) = object else {
+        return true;
+    };
+    let Some(language) = literal.language() else {
+        return true;
+    };
+    languages.iter().any(|range| {
+        range == "*"
+            || language.eq_ignore_ascii_case(range)
+            || (language.as_bytes().get(range.len()) == Some(&b'-')
+                && language
+                    .get(..range.len())
+                    .is_some_and(|prefix| prefix.eq_ignore_ascii_case(range)))
+    })
+}
+
+/// The number of language-tagged strings with a given language tag returned by [`Store::language_statistics`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct LanguageStatistics {
+    /// The number of quads whose object is a language-tagged string with the language tag.
+    pub count: usize,
+    /// The number of distinct language-tagged strings with the language tag used as quad objects.
+    pub distinct_literals: usize,
+}
+
 /// An object to do operations during a transaction.
 ///
+/// All the read operations, including the SPARQL queries and the `WHERE` clauses of the SPARQL updates,
+/// see the changes done previously in the same transaction ("read your writes").
+/// The SPARQL query results are computed when the query is executed:
+/// the changes done while iterating on them do not modify them.
+///
+/// <div class="warning">
+///
+/// The full-text index enabled with [`Store::enable_text_index`] is only updated when the transaction is committed.</div>
+///
 /// See [`Store::transaction`] for a more detailed description.
+///
+/// ```
+/// use oxigraph::sparql::{EvaluationError, QueryResults};
+/// use oxigraph::store::Store;
+///
+/// let store = Store::new()?;
+/// store.transaction(|mut transaction| {
+///     transaction.update("INSERT DATA { <http://example.com/a> <http://example.com/p> 1 }")?;
+///     // The query sees the insertion even if the transaction is not committed yet
+///     assert!(matches!(
+///         transaction.query("ASK { <http://example.com/a> <http://example.com/p> 1 }")?,
+///         QueryResults::Boolean(true)
+///     ));
+///     Result::<_, EvaluationError>::Ok(())
+/// })?;
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
 pub struct Transaction<'a> {
     writer: StorageWriter<'a>,
+    text_index: Option<Arc<StoreTextIndex>>,
 }
 
 impl Transaction<'_> {
     /// Executes a [SPARQL 1.1 query](https://www.w3.org/TR/sparql11-query/).
     ///
+    /// The query sees the changes done previously in the transaction.
+    /// Its results are computed eagerly and kept in memory so the transaction can be modified while iterating on them.
+    ///
+    /// <div class="warning">The uncommitted changes of the transaction can't be snapshotted so the results can't be streamed:
+    /// use [`Store::query`] outside of a transaction to stream large results.</div>
+    ///
     /// Usage example:
     /// ```
     /// use oxigraph::model::*;
@@ -1145,8 +2362,15 @@ impl Transaction<'_> {
         query: impl TryInto<Query, Error = impl Into<EvaluationError>>,
         options: QueryOptions,
     ) -> Result<QueryResults, EvaluationError> {
-        let (results, _) = evaluate_query(self.writer.reader(), query, options, false, [])?;
-        results
+        let options = if let Some(text_index) = &self.text_index {
+            options.with_text_index(Arc::clone(&text_index.index))
+        } else {
+            options
+        };
+        let (results, _) = evaluate_query(self.writer.reader(), None, query, options, false, [])?;
+        // The evaluation reads the pending writes of the transaction, which have no snapshot:
+        // we compute the results now in order to not see the changes done while iterating on them
+        results?.materialize()
     }
 
     /// Retrieves quads with a filter on each quad component.
@@ -1247,10 +2471,14 @@ impl Transaction<'_> {
         update: impl TryInto<Update, Error = impl Into<EvaluationError>>,
         options: impl Into<UpdateOptions>,
     ) -> Result<(), EvaluationError> {
+        let mut options = options.into();
+        if let Some(text_index) = &self.text_index {
+            options = options.with_text_index(Arc::clone(&text_index.index));
+        }
         evaluate_update(
             &mut self.writer,
             &update.try_into().map_err(Into::into)?,
-            &options.into(),
+            &options,
         )
     }
 
@@ -1451,6 +2679,7 @@ impl Transaction<'_> {
         GraphNameIter {
             iter: reader.named_graphs(),
             reader,
+            include_empty: true,
         }
     }
 
@@ -1464,29 +2693,60 @@ impl Transaction<'_> {
             .contains_named_graph(&EncodedTerm::from(graph_name.into()))
     }
 
-    /// Inserts a graph into this store.
+    /// Inserts a graph into this store.
+    ///
+    /// Returns `true` if the graph was not already in the store.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::model::NamedNodeRef;
+    /// use oxigraph::store::Store;
+    ///
+    /// let ex = NamedNodeRef::new_unchecked("http://example.com");
+    /// let store = Store::new()?;
+    /// store.transaction(|mut transaction| transaction.insert_named_graph(ex))?;
+    /// assert_eq!(
+    ///     store.named_graphs().collect::<Result<Vec<_>, _>>()?,
+    ///     vec![ex.into_owned().into()]
+    /// );
+    /// # Result::<_,oxigraph::store::StorageError>::Ok(())
+    /// ```
+    pub fn insert_named_graph<'b>(
+        &mut self,
+        graph_name: impl Into<NamedOrBlankNodeRef<'b>>,
+    ) -> Result<bool, StorageError> {
+        self.writer.insert_named_graph(graph_name.into())
+    }
+
+    /// Sets a property of a graph metadata, removes it if `value` is `None`.
     ///
-    /// Returns `true` if the graph was not already in the store.
+    /// The named graph is inserted into the store if it does not exist.
     ///
     /// Usage example:
     /// ```
-    /// use oxigraph::model::NamedNodeRef;
+    /// use oxigraph::model::GraphNameRef;
     /// use oxigraph::store::Store;
     ///
-    /// let ex = NamedNodeRef::new_unchecked("http://example.com");
     /// let store = Store::new()?;
-    /// store.transaction(|mut transaction| transaction.insert_named_graph(ex))?;
+    /// store.transaction(|mut transaction| {
+    ///     transaction.set_graph_property(GraphNameRef::DefaultGraph, "license", Some("CC0"))
+    /// })?;
     /// assert_eq!(
-    ///     store.named_graphs().collect::<Result<Vec<_>, _>>()?,
-    ///     vec![ex.into_owned().into()]
+    ///     store
+    ///         .graph_metadata(GraphNameRef::DefaultGraph)?
+    ///         .and_then(|m| m.property("license").map(ToOwned::to_owned)),
+    ///     Some("CC0".into())
     /// );
     /// # Result::<_,oxigraph::store::StorageError>::Ok(())
     /// ```
-    pub fn insert_named_graph<'b>(
+    pub fn set_graph_property<'b>(
         &mut self,
-        graph_name: impl Into<NamedOrBlankNodeRef<'b>>,
-    ) -> Result<bool, StorageError> {
-        self.writer.insert_named_graph(graph_name.into())
+        graph_name: impl Into<GraphNameRef<'b>>,
+        key: &str,
+        value: Option<&str>,
+    ) -> Result<(), StorageError> {
+        self.writer
+            .set_graph_property(graph_name.into(), key, value)
     }
 
     /// Clears a graph from this store.
@@ -1560,6 +2820,49 @@ impl Transaction<'_> {
     pub fn clear(&mut self) -> Result<(), StorageError> {
         self.writer.clear()
     }
+
+    /// Removes from this store all the named graphs that do not contain any quad.
+    ///
+    /// Returns the number of removed named graphs.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::model::NamedNodeRef;
+    /// use oxigraph::store::Store;
+    ///
+    /// let ex = NamedNodeRef::new_unchecked("http://example.com");
+    /// let store = Store::new()?;
+    /// store.transaction(|mut transaction| {
+    ///     transaction.insert_named_graph(ex)?;
+    ///     assert_eq!(1, transaction.vacuum()?);
+    ///     Ok::<_, oxigraph::store::StorageError>(())
+    /// })?;
+    /// assert_eq!(0, store.named_graphs().count());
+    /// # Result::<_,oxigraph::store::StorageError>::Ok(())
+    /// ```
+    pub fn vacuum(&mut self) -> Result<usize, StorageError> {
+        let empty_graphs = self
+            .named_graphs()
+            .filter_map(|graph_name| {
+                let graph_name = match graph_name {
+                    Ok(graph_name) => graph_name,
+                    Err(e) => return Some(Err(e)),
+                };
+                match self
+                    .quads_for_pattern(None, None, None, Some(graph_name.as_ref().into()))
+                    .next()
+                {
+                    None => Some(Ok(graph_name)),
+                    Some(Ok(_)) => None,
+                    Some(Err(e)) => Some(Err(e)),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        for graph_name in &empty_graphs {
+            self.writer.remove_named_graph(graph_name.as_ref())?;
+        }
+        Ok(empty_graphs.len())
+    }
 }
 
 impl IntoIterator for &Transaction<'_> {
@@ -1572,6 +2875,155 @@ impl IntoIterator for &Transaction<'_> {
     }
 }
 
+/// A read-only view of a [`Store`] content at a given point in time, returned by [`Store::snapshot`].
+///
+/// The changes done to the store after the snapshot creation are not visible.
+/// Clones of a snapshot share the same view.
+///
+/// Usage example:
+/// ```
+/// use oxigraph::model::*;
+/// use oxigraph::store::Store;
+///
+/// let ex = NamedNodeRef::new("http://example.com")?;
+/// let store = Store::new()?;
+/// let snapshot = store.snapshot();
+/// store.insert(QuadRef::new(ex, ex, ex, ex))?;
+///
+/// assert!(store.contains(QuadRef::new(ex, ex, ex, ex))?);
+/// assert!(!snapshot.contains(QuadRef::new(ex, ex, ex, ex))?);
+/// assert!(!snapshot.contains_named_graph(ex)?);
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[derive(Clone)]
+pub struct StoreSnapshot {
+    reader: StorageReader,
+    text_index: Option<Arc<StoreTextIndex>>,
+}
+
+impl StoreSnapshot {
+    /// Executes a [SPARQL 1.1 query](https://www.w3.org/TR/sparql11-query/) on the snapshot.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::model::*;
+    /// use oxigraph::sparql::QueryResults;
+    /// use oxigraph::store::Store;
+    ///
+    /// let store = Store::new()?;
+    /// let snapshot = store.snapshot();
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// store.insert(QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph))?;
+    ///
+    /// if let QueryResults::Solutions(mut solutions) = snapshot.query("SELECT ?s WHERE { ?s ?p ?o }")? {
+    ///     assert!(solutions.next().is_none());
+    /// }
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn query(
+        &self,
+        query: impl TryInto<Query, Error = impl Into<EvaluationError>>,
+    ) -> Result<QueryResults, EvaluationError> {
+        self.query_opt(query, QueryOptions::default())
+    }
+
+    /// Executes a [SPARQL 1.1 query](https://www.w3.org/TR/sparql11-query/) on the snapshot with some options.
+    ///
+    /// The [results cache](QueryOptions::with_results_cache) and the [parallel evaluation](QueryOptions::with_parallel_execution) are not used.
+    pub fn query_opt(
+        &self,
+        query: impl TryInto<Query, Error = impl Into<EvaluationError>>,
+        options: QueryOptions,
+    ) -> Result<QueryResults, EvaluationError> {
+        let options = if let Some(text_index) = &self.text_index {
+            options.with_text_index(Arc::clone(&text_index.index))
+        } else {
+            options
+        };
+        let (results, _) = evaluate_query(self.reader.clone(), None, query, options, false, [])?;
+        results
+    }
+
+    /// Retrieves quads with a filter on each quad component.
+    pub fn quads_for_pattern(
+        &self,
+        subject: Option<SubjectRef<'_>>,
+        predicate: Option<NamedNodeRef<'_>>,
+        object: Option<TermRef<'_>>,
+        graph_name: Option<GraphNameRef<'_>>,
+    ) -> QuadIter {
+        let reader = self.reader.clone();
+        QuadIter {
+            iter: reader.quads_for_pattern(
+                subject.map(EncodedTerm::from).as_ref(),
+                predicate.map(EncodedTerm::from).as_ref(),
+                object.map(EncodedTerm::from).as_ref(),
+                graph_name.map(EncodedTerm::from).as_ref(),
+            ),
+            reader,
+        }
+    }
+
+    /// Returns all the quads contained in the snapshot.
+    pub fn iter(&self) -> QuadIter {
+        self.quads_for_pattern(None, None, None, None)
+    }
+
+    /// Checks if the snapshot contains a given quad.
+    pub fn contains<'a>(&self, quad: impl Into<QuadRef<'a>>) -> Result<bool, StorageError> {
+        let quad = EncodedQuad::from(quad.into());
+        self.reader.contains(&quad)
+    }
+
+    /// Returns the number of quads in the snapshot.
+    ///
+    /// <div class="warning">this function executes a full scan.</div>
+    pub fn len(&self) -> Result<usize, StorageError> {
+        self.reader.len()
+    }
+
+    /// Returns if the snapshot is empty.
+    pub fn is_empty(&self) -> Result<bool, StorageError> {
+        self.reader.is_empty()
+    }
+
+    /// Returns all the named graphs contained in the snapshot.
+    pub fn named_graphs(&self) -> GraphNameIter {
+        let reader = self.reader.clone();
+        GraphNameIter {
+            iter: reader.named_graphs(),
+            reader,
+            include_empty: true,
+        }
+    }
+
+    /// Checks if the snapshot contains a given graph.
+    pub fn contains_named_graph<'a>(
+        &self,
+        graph_name: impl Into<NamedOrBlankNodeRef<'a>>,
+    ) -> Result<bool, StorageError> {
+        let graph_name = EncodedTerm::from(graph_name.into());
+        self.reader.contains_named_graph(&graph_name)
+    }
+
+    /// Returns the last version of the store history in the snapshot, `None` if the history is not enabled.
+    ///
+    /// See [`Store::enable_history`].
+    pub fn history_version(&self) -> Result<Option<u64>, StorageError> {
+        self.reader.history_version()
+    }
+}
+
+impl IntoIterator for &StoreSnapshot {
+    type IntoIter = QuadIter;
+    type Item = Result<Quad, StorageError>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 /// An iterator returning the quads contained in a [`Store`].
 pub struct QuadIter {
     iter: DecodingQuadIterator,
@@ -1589,25 +3041,157 @@ impl Iterator for QuadIter {
     }
 }
 
+/// An iterator returning the entries of the history of a [`Store`].
+///
+/// See [`Store::history_since`].
+pub struct HistoryIter {
+    reader: StorageReader,
+    next_version: u64,
+    last_version: u64,
+}
+
+impl Iterator for HistoryIter {
+    type Item = Result<HistoryEntry, StorageError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_version > self.last_version {
+            return None;
+        }
+        let version = self.next_version;
+        self.next_version += 1;
+        Some(self.reader.history_entry(version).and_then(|entry| {
+            entry.ok_or_else(|| {
+                CorruptionError::msg(format!("The history version {version} is missing")).into()
+            })
+        }))
+    }
+}
+
+/// An iterator returning the quads with a dangling reference in a [`Store`].
+///
+/// See [`Store::dangling_references`].
+pub struct DanglingReferenceIter {
+    predicates: std::vec::IntoIter<EncodedTerm>,
+    description_graphs: Vec<EncodedTerm>,
+    iter: Option<DecodingQuadIterator>,
+    reader: StorageReader,
+}
+
+impl DanglingReferenceIter {
+    fn is_described(&self, node: &EncodedTerm) -> Result<bool, StorageError> {
+        if self.description_graphs.is_empty() {
+            return Ok(self
+                .reader
+                .quads_for_pattern(Some(node), None, None, None)
+                .next()
+                .transpose()?
+                .is_some());
+        }
+        for graph_name in &self.description_graphs {
+            if self
+                .reader
+                .quads_for_pattern(Some(node), None, None, Some(graph_name))
+                .next()
+                .transpose()?
+                .is_some()
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+impl Iterator for DanglingReferenceIter {
+    type Item = Result<Quad, StorageError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(quad) = self.iter.as_mut().and_then(Iterator::next) {
+                let quad = match quad {
+                    Ok(quad) => quad,
+                    Err(e) => return Some(Err(e)),
+                };
+                if !matches!(quad.object, EncodedTerm::NamedNode { .. }) {
+                    continue; // Only IRIs are references
+                }
+                match self.is_described(&quad.object) {
+                    Ok(true) => continue,
+                    Ok(false) => return Some(self.reader.decode_quad(&quad)),
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+            let predicate = self.predicates.next()?;
+            self.iter = Some(
+                self.reader
+                    .quads_for_pattern(None, Some(&predicate), None, None),
+            );
+        }
+    }
+}
+
 /// An iterator returning the graph names contained in a [`Store`].
 pub struct GraphNameIter {
     iter: DecodingGraphIterator,
     reader: StorageReader,
+    include_empty: bool,
+}
+
+impl GraphNameIter {
+    /// Sets if the named graphs that do not contain any quad should be returned.
+    ///
+    /// They are returned by default.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::model::*;
+    /// use oxigraph::store::Store;
+    ///
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// let store = Store::new()?;
+    /// store.insert_named_graph(ex)?;
+    /// assert_eq!(1, store.named_graphs().count());
+    /// assert_eq!(0, store.named_graphs().include_empty(false).count());
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[must_use]
+    pub fn include_empty(mut self, include_empty: bool) -> Self {
+        self.include_empty = include_empty;
+        self
+    }
 }
 
 impl Iterator for GraphNameIter {
     type Item = Result<NamedOrBlankNode, StorageError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        Some(
-            self.iter
-                .next()?
-                .and_then(|graph_name| self.reader.decode_named_or_blank_node(&graph_name)),
-        )
+        loop {
+            let graph_name = match self.iter.next()? {
+                Ok(graph_name) => graph_name,
+                Err(e) => return Some(Err(e)),
+            };
+            if !self.include_empty {
+                match self
+                    .reader
+                    .quads_for_pattern(None, None, None, Some(&graph_name))
+                    .next()
+                {
+                    None => continue,
+                    Some(Ok(_)) => (),
+                    Some(Err(e)) => return Some(Err(e)),
+                }
+            }
+            return Some(self.reader.decode_named_or_blank_node(&graph_name));
+        }
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        self.iter.size_hint()
+        let (min, max) = self.iter.size_hint();
+        if self.include_empty {
+            (min, max)
+        } else {
+            (0, max)
+        }
     }
 }
 
@@ -1617,6 +3201,9 @@ impl Iterator for GraphNameIter {
 /// If the operation fails in the middle, only a part of the data may be written to the store.
 /// Results might get weird if you delete data during the loading process.</div>
 ///
+/// If the store history is enabled, each load creates a history version without the loaded quads
+/// (see [`HistoryEntry::is_bulk_load`]).
+///
 /// Memory usage is configurable using [`with_max_memory_size_in_megabytes`](Self::with_max_memory_size_in_megabytes)
 /// and the number of used threads with [`with_num_threads`](Self::with_num_threads).
 /// By default the memory consumption target (excluding the system and RocksDB internal consumption)
@@ -1647,6 +3234,9 @@ impl Iterator for GraphNameIter {
 pub struct BulkLoader {
     storage: StorageBulkLoader,
     on_parse_error: Option<Box<dyn Fn(RdfParseError) -> Result<(), RdfParseError>>>,
+    num_parsing_threads: usize,
+    graph_hashes: Option<Arc<GraphHashCache>>,
+    text_index: Option<Arc<StoreTextIndex>>,
 }
 
 impl BulkLoader {
@@ -1666,6 +3256,18 @@ impl BulkLoader {
         self.with_num_threads(num_threads)
     }
 
+    /// Sets the number of threads used to parse the N-Triples and N-Quads files.
+    ///
+    /// These files are split into chunks of lines parsed in parallel, in addition to the threads writing to the store.
+    /// The quads are then not loaded in the file order and the positions in the parse errors are relative to the start of the chunk they are in.
+    /// The other formats are always parsed by a single thread.
+    ///
+    /// The default value is 1.
+    pub fn with_num_parsing_threads(mut self, num_parsing_threads: usize) -> Self {
+        self.num_parsing_threads = num_parsing_threads;
+        self
+    }
+
     /// Sets a rough idea of the maximal amount of memory to be used by this operation.
     ///
     /// This number must be at last a few megabytes per thread.
@@ -1694,6 +3296,16 @@ impl BulkLoader {
         self
     }
 
+    /// Makes the loader look for each loaded quad in the store to report the quads already present as duplicates in the returned [`LoadReport`].
+    ///
+    /// Without it, the returned [`LoadReport`] only provides the number of loaded quads
+    /// and the number of quads of the graphs returned by [`Store::graph_metadata`] is counted again after the load.
+    /// This option slows down the loading because it adds a lookup per loaded quad.
+    pub fn with_duplicate_detection(mut self) -> Self {
+        self.storage = self.storage.with_duplicate_detection();
+        self
+    }
+
     /// Adds a `callback` catching all parse errors and choosing if the parsing should continue
     /// by returning `Ok` or fail by returning `Err`.
     ///
@@ -1718,6 +3330,8 @@ impl BulkLoader {
     ///
     /// To get better speed on valid datasets, consider enabling [`RdfParser::unchecked`] option to skip some validations.
     ///
+    /// Returns a [`LoadReport`] with the number of loaded quads, split between new and duplicate quads only with [`with_duplicate_detection`](Self::with_duplicate_detection).
+    ///
     /// Usage example:
     /// ```
     /// use oxigraph::store::Store;
@@ -1753,29 +3367,42 @@ impl BulkLoader {
         &self,
         parser: impl Into<RdfParser>,
         reader: impl Read,
-    ) -> Result<(), LoaderError> {
+    ) -> Result<LoadReport, LoaderError> {
+        let parser = parser.into();
+        if self.num_parsing_threads > 1 && is_line_based(parser.format()) {
+            return parse_lines_in_parallel(&parser, reader, self.num_parsing_threads, |quads| {
+                self.load_ok_quads(quads.filter_map(|r| self.handle_parse_error(r)))
+            });
+        }
         self.load_ok_quads(
             parser
-                .into()
                 .rename_blank_nodes()
                 .for_reader(reader)
-                .filter_map(|r| match r {
-                    Ok(q) => Some(Ok(q)),
-                    Err(e) => {
-                        if let Some(callback) = &self.on_parse_error {
-                            if let Err(e) = callback(e) {
-                                Some(Err(e))
-                            } else {
-                                None
-                            }
-                        } else {
-                            Some(Err(e))
-                        }
-                    }
-                }),
+                .filter_map(|r| self.handle_parse_error(r)),
         )
     }
 
+    /// Calls the [`on_parse_error`](Self::on_parse_error) callback on errors, returning `None` if the error must be ignored
+    fn handle_parse_error(
+        &self,
+        result: Result<Quad, RdfParseError>,
+    ) -> Option<Result<Quad, RdfParseError>> {
+        match result {
+            Ok(q) => Some(Ok(q)),
+            Err(e) => {
+                if let Some(callback) = &self.on_parse_error {
+                    if let Err(e) = callback(e) {
+                        Some(Err(e))
+                    } else {
+                        None
+                    }
+                } else {
+                    Some(Err(e))
+                }
+            }
+        }
+    }
+
     /// Loads a dataset file using the bulk loader.
     ///
     /// This function is optimized for large dataset loading speed. For small files, [`Store::load_dataset`] might be more convenient.
@@ -1822,7 +3449,7 @@ impl BulkLoader {
                     error: e,
                 })?;
         }
-        self.load_ok_quads(parser.for_reader(reader).filter_map(|r| match r {
+        self.load_ok_quads::<_, LoaderError>(parser.for_reader(reader).filter_map(|r| match r {
             Ok(q) => Some(Ok(q)),
             Err(e) => {
                 if let Some(callback) = &self.on_parse_error {
@@ -1835,7 +3462,8 @@ impl BulkLoader {
                     Some(Err(e))
                 }
             }
-        }))
+        }))?;
+        Ok(())
     }
 
     /// Loads a graph file using the bulk loader.
@@ -1890,7 +3518,7 @@ impl BulkLoader {
                     error: e,
                 })?;
         }
-        self.load_ok_quads(parser.for_reader(reader).filter_map(|r| match r {
+        self.load_ok_quads::<_, LoaderError>(parser.for_reader(reader).filter_map(|r| match r {
             Ok(q) => Some(Ok(q)),
             Err(e) => {
                 if let Some(callback) = &self.on_parse_error {
@@ -1903,7 +3531,8 @@ impl BulkLoader {
                     Some(Err(e))
                 }
             }
-        }))
+        }))?;
+        Ok(())
     }
 
     /// Adds a set of quads using the bulk loader.
@@ -1916,7 +3545,7 @@ impl BulkLoader {
     pub fn load_quads(
         &self,
         quads: impl IntoIterator<Item = impl Into<Quad>>,
-    ) -> Result<(), StorageError> {
+    ) -> Result<LoadReport, StorageError> {
         self.load_ok_quads(quads.into_iter().map(Ok::<_, StorageError>))
     }
 
@@ -1930,9 +3559,68 @@ impl BulkLoader {
     pub fn load_ok_quads<EI, EO: From<StorageError> + From<EI>>(
         &self,
         quads: impl IntoIterator<Item = Result<impl Into<Quad>, EI>>,
-    ) -> Result<(), EO> {
-        self.storage
-            .load(quads.into_iter().map(|q| q.map(Into::into)))
+    ) -> Result<LoadReport, EO> {
+        let result = self.storage.load(quads.into_iter().map(|q| {
+            let q = q.map(Into::into);
+            if let (Ok(quad), Some(text_index)) = (&q, &self.text_index) {
+                text_index.insert(quad.as_ref());
+            }
+            q
+        }));
+        if let Some(graph_hashes) = &self.graph_hashes {
+            // The loaded graphs are not tracked, we invalidate everything
+            graph_hashes.clear();
+        }
+        result
+    }
+}
+
+/// The text index enabled by [`Store::enable_text_index`]
+struct StoreTextIndex {
+    index: Arc<dyn TextIndex>,
+    predicates: Vec<NamedNode>,
+}
+
+impl StoreTextIndex {
+    fn insert(&self, quad: QuadRef<'_>) {
+        if quad.object.is_literal() && self.predicates.iter().any(|p| *p == quad.predicate) {
+            self.index.insert(quad);
+        }
+    }
+
+    fn remove(&self, quad: QuadRef<'_>) {
+        if quad.object.is_literal() && self.predicates.iter().any(|p| *p == quad.predicate) {
+            self.index.remove(quad);
+        }
+    }
+}
+
+/// The cache of the graph hashes enabled by [`Store::enable_graph_hash_cache`]
+#[derive(Default)]
+struct GraphHashCache {
+    state: Mutex<GraphHashCacheState>,
+}
+
+#[derive(Default)]
+struct GraphHashCacheState {
+    hashes: HashMap<GraphName, [u8; 32]>,
+    /// Incremented on each invalidation to avoid caching hashes computed concurrently to a change
+    generation: u64,
+}
+
+impl GraphHashCache {
+    fn invalidate<'a>(&self, graph_names: impl IntoIterator<Item = GraphNameRef<'a>>) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.generation += 1;
+        for graph_name in graph_names {
+            state.hashes.remove(&graph_name.into_owned());
+        }
+    }
+
+    fn clear(&self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.generation += 1;
+        state.hashes.clear();
     }
 }
 
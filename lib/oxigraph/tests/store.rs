@@ -4,14 +4,25 @@
 use oxigraph::io::RdfFormat;
 use oxigraph::model::vocab::{rdf, xsd};
 use oxigraph::model::*;
-use oxigraph::store::Store;
+use oxigraph::reasoning::{N3ReasoningError, N3ReasoningOptions, N3Rules};
+use oxigraph::sparql::{
+    EvaluationError, InMemoryTextIndex, PredicateStatistics, Query, QueryOptions, QueryResults,
+    QueryResultsCache, QuerySolution, Update,
+};
+#[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+use oxigraph::store::WarmUpOptions;
+use oxigraph::store::{
+    DumpOptions, HistoryEntry, LanguageStatistics, QuadChange, ReferenceCheckOptions, StorageError,
+    Store,
+};
 #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
 use rand::random;
+use std::collections::HashSet;
 #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
 use std::env::temp_dir;
 use std::error::Error;
 #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
-use std::fs::{create_dir_all, remove_dir_all, File};
+use std::fs::{create_dir_all, read_dir, remove_dir_all, File};
 #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
 use std::io::Write;
 use std::iter::empty;
@@ -21,6 +32,7 @@ use std::iter::once;
 use std::path::{Path, PathBuf};
 #[cfg(all(target_os = "linux", feature = "rocksdb"))]
 use std::process::Command;
+use std::sync::{Arc, Mutex};
 
 #[allow(clippy::non_ascii_literal)]
 const DATA: &str = r#"
@@ -55,6 +67,19 @@ GRAPH <http://www.wikidata.org/wiki/Special:EntityData/Q90> {
 "#;
 const NUMBER_OF_TRIPLES: usize = 8;
 
+/// Runs the check on a new in-memory store and, if RocksDB is enabled, on a new on-disk store
+fn check_on_each_backend(
+    check: impl Fn(&Store) -> Result<(), Box<dyn Error>>,
+) -> Result<(), Box<dyn Error>> {
+    check(&Store::new()?)?;
+    #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+    {
+        let dir = TempDir::default();
+        check(&Store::open(&dir.0)?)?;
+    }
+    Ok(())
+}
+
 fn quads(graph_name: impl Into<GraphNameRef<'static>>) -> Vec<QuadRef<'static>> {
     let graph_name = graph_name.into();
     let paris = NamedNodeRef::new_unchecked("http://www.wikidata.org/entity/Q90");
@@ -160,6 +185,42 @@ fn test_bulk_load_graph_on_disk() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn test_bulk_load_with_parsing_threads() -> Result<(), Box<dyn Error>> {
+    // Big enough to be split into multiple chunks
+    let mut data = (0..200_000)
+        .map(|i| {
+            format!(
+                "_:b{} <http://example.com/p> \"{i}\" <http://example.com/g> .\n",
+                i % 1000
+            )
+        })
+        .collect::<String>();
+    data.push_str("_:b0 <http://example.com/p> _:b1 .");
+    let store = Store::new()?;
+    let report = store
+        .bulk_loader()
+        .with_num_parsing_threads(4)
+        .load_from_reader(RdfFormat::NQuads, data.as_bytes())?;
+    assert_eq!(report.loaded_quads(), 200_001);
+    assert_eq!(store.len()?, 200_001);
+    // The blank nodes are renamed consistently across the chunks
+    let subjects = store
+        .iter()
+        .map(|q| Ok(q?.subject))
+        .collect::<Result<HashSet<_>, StorageError>>()?;
+    assert_eq!(subjects.len(), 1000);
+    let link = store
+        .quads_for_pattern(None, None, None, Some(GraphNameRef::DefaultGraph))
+        .next()
+        .ok_or("The default graph quad is missing")??;
+    assert!(subjects.contains(&link.subject));
+    assert!(subjects.contains(&Subject::try_from(link.object)?));
+    assert!(!subjects.contains(&BlankNode::new("b0")?.into()));
+    store.validate()?;
+    Ok(())
+}
+
 #[test]
 fn test_bulk_load_graph_lenient() -> Result<(), Box<dyn Error>> {
     let store = Store::new()?;
@@ -216,6 +277,267 @@ fn test_bulk_load_dataset() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+const DUPLICATED_DATA: &str = "
+<http://example.com/s> <http://example.com/p> <http://example.com/o> .
+<http://example.com/s> <http://example.com/p> <http://example.com/o> .
+<http://example.com/s> <http://example.com/p> <http://example.com/o> <http://example.com/g> .
+";
+
+#[test]
+fn test_load_report() -> Result<(), Box<dyn Error>> {
+    let store = Store::new()?;
+    let graph_name = NamedNodeRef::new_unchecked("http://example.com/g");
+    let report = store.load_from_reader(RdfFormat::NQuads, DUPLICATED_DATA.as_bytes())?;
+    assert_eq!(report.loaded_quads(), 3);
+    assert_eq!(report.new_quads(), Some(2));
+    assert_eq!(report.duplicate_quads(), Some(1));
+    assert_eq!(
+        report.graph(GraphNameRef::DefaultGraph).duplicate_quads(),
+        Some(1)
+    );
+    assert_eq!(report.graph(graph_name).new_quads(), Some(1));
+    let report = store.load_from_reader(RdfFormat::NQuads, DUPLICATED_DATA.as_bytes())?;
+    assert_eq!(report.new_quads(), Some(0));
+    assert_eq!(report.duplicate_quads(), Some(3));
+    assert_eq!(report.graphs().count(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_bulk_load_report() -> Result<(), Box<dyn Error>> {
+    check_on_each_backend(check_bulk_load_report)
+}
+
+fn check_bulk_load_report(store: &Store) -> Result<(), Box<dyn Error>> {
+    let graph_name = NamedNodeRef::new_unchecked("http://example.com/g");
+    let report = store
+        .bulk_loader()
+        .load_from_reader(RdfFormat::NQuads, DUPLICATED_DATA.as_bytes())?;
+    assert_eq!(report.loaded_quads(), 3);
+    assert_eq!(report.graph(graph_name).loaded_quads(), 1);
+    let loader = store.bulk_loader().with_duplicate_detection();
+    let report = loader.load_from_reader(RdfFormat::NQuads, DUPLICATED_DATA.as_bytes())?;
+    assert_eq!(report.loaded_quads(), 3);
+    assert_eq!(report.new_quads(), Some(0));
+    assert_eq!(report.duplicate_quads(), Some(3));
+    assert_eq!(
+        report.graph(GraphNameRef::DefaultGraph).duplicate_quads(),
+        Some(2)
+    );
+    assert_eq!(report.graph(graph_name).duplicate_quads(), Some(1));
+    let report = loader.load_from_reader(RdfFormat::NQuads, DUPLICATED_DATA.as_bytes())?;
+    assert_eq!(report.new_quads(), Some(0));
+    assert_eq!(report.duplicate_quads(), Some(3));
+    store.validate()?;
+    Ok(())
+}
+
+#[test]
+fn test_dangling_references() -> Result<(), Box<dyn Error>> {
+    let store = Store::new()?;
+    store.load_from_reader(
+        RdfFormat::NQuads,
+        b"<http://example.com/a> <http://example.com/p> <http://example.com/b> .
+<http://example.com/a> <http://example.com/p> <http://example.com/c> <http://example.com/g> .
+<http://example.com/a> <http://example.com/p> \"c\" .
+<http://example.com/a> <http://example.com/q> <http://example.com/d> .
+<http://example.com/b> <http://example.com/q> <http://example.com/a> <http://example.com/g> .
+"
+        .as_slice(),
+    )?;
+    let p = NamedNodeRef::new_unchecked("http://example.com/p");
+    let q = NamedNodeRef::new_unchecked("http://example.com/q");
+    let g = NamedNodeRef::new_unchecked("http://example.com/g");
+    let objects = |options| {
+        let mut objects = store
+            .dangling_references(options)
+            .map(|quad| Ok(quad?.object.to_string()))
+            .collect::<Result<Vec<_>, StorageError>>()?;
+        objects.sort();
+        Ok::<_, StorageError>(objects)
+    };
+    assert!(objects(ReferenceCheckOptions::default())?.is_empty());
+    assert_eq!(
+        objects(ReferenceCheckOptions::default().with_predicate(p))?,
+        ["<http://example.com/c>"]
+    );
+    assert_eq!(
+        objects(
+            ReferenceCheckOptions::default()
+                .with_predicate(p)
+                .with_predicate(q)
+        )?,
+        ["<http://example.com/c>", "<http://example.com/d>"]
+    );
+    assert_eq!(
+        objects(
+            ReferenceCheckOptions::default()
+                .with_predicate(p)
+                .with_description_graph(GraphName::DefaultGraph)
+        )?,
+        ["<http://example.com/b>", "<http://example.com/c>"]
+    );
+    assert_eq!(
+        objects(
+            ReferenceCheckOptions::default()
+                .with_predicate(p)
+                .with_description_graph(g)
+        )?,
+        ["<http://example.com/c>"]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_statistics() -> Result<(), Box<dyn Error>> {
+    let store = Store::new()?;
+    store.load_from_reader(
+        RdfFormat::NQuads,
+        b"<http://example.com/a> <http://example.com/p> <http://example.com/b> .
+<http://example.com/a> <http://example.com/p> <http://example.com/b> <http://example.com/g> .
+<http://example.com/a> <http://example.com/p> <http://example.com/c> .
+<http://example.com/b> <http://example.com/q> <http://example.com/c> .
+"
+        .as_slice(),
+    )?;
+    let statistics = store.statistics()?;
+    assert_eq!(statistics.len(), 2);
+    assert_eq!(
+        statistics.get(&NamedNode::new_unchecked("http://example.com/p")),
+        Some(&PredicateStatistics {
+            count: 3,
+            distinct_subjects: 1,
+            distinct_objects: 2
+        })
+    );
+    assert_eq!(
+        statistics.get(&NamedNode::new_unchecked("http://example.com/q")),
+        Some(&PredicateStatistics {
+            count: 1,
+            distinct_subjects: 1,
+            distinct_objects: 1
+        })
+    );
+
+    let query = "SELECT ?x WHERE { ?x <http://example.com/p> ?y . ?y <http://example.com/q> ?z . ?x <http://example.com/p> ?z }";
+    let QueryResults::Solutions(solutions) = store.query_opt(
+        query,
+        QueryOptions::default().with_statistics(Arc::new(statistics)),
+    )?
+    else {
+        unreachable!()
+    };
+    assert_eq!(solutions.count(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_language_statistics_and_dump() -> Result<(), Box<dyn Error>> {
+    let store = Store::new()?;
+    store.load_from_reader(
+        RdfFormat::NQuads,
+        b"<http://example.com/a> <http://example.com/p> \"a\"@en .
+<http://example.com/a> <http://example.com/p> \"a\"@en <http://example.com/g> .
+<http://example.com/a> <http://example.com/p> \"a very long string that is not inlined\"@en-GB .
+<http://example.com/a> <http://example.com/p> \"b\"@fr .
+<http://example.com/a> <http://example.com/p> \"c\" .
+"
+        .as_slice(),
+    )?;
+    let statistics = store.language_statistics()?;
+    assert_eq!(
+        statistics.into_iter().collect::<Vec<_>>(),
+        [
+            (
+                "en".to_owned(),
+                LanguageStatistics {
+                    count: 2,
+                    distinct_literals: 1
+                }
+            ),
+            (
+                "en-gb".to_owned(),
+                LanguageStatistics {
+                    count: 1,
+                    distinct_literals: 1
+                }
+            ),
+            (
+                "fr".to_owned(),
+                LanguageStatistics {
+                    count: 1,
+                    distinct_literals: 1
+                }
+            )
+        ]
+    );
+
+    let dump = store.dump_to_writer_opt(
+        RdfFormat::NTriples,
+        Vec::new(),
+        DumpOptions::default()
+            .with_graph(GraphName::DefaultGraph)
+            .with_language("EN"),
+    )?;
+    let mut lines = String::from_utf8(dump)?
+        .lines()
+        .map(ToOwned::to_owned)
+        .collect::<Vec<_>>();
+    lines.sort();
+    assert_eq!(
+        lines,
+        [
+            "<http://example.com/a> <http://example.com/p> \"a very long string that is not inlined\"@en-gb .",
+            "<http://example.com/a> <http://example.com/p> \"a\"@en .",
+            "<http://example.com/a> <http://example.com/p> \"c\" ."
+        ]
+    );
+    store
+        .dump_to_writer_opt(RdfFormat::NTriples, Vec::new(), DumpOptions::default())
+        .unwrap_err();
+    Ok(())
+}
+
+#[test]
+fn test_binary_snapshot() -> Result<(), Box<dyn Error>> {
+    let store = Store::new()?;
+    store.load_from_reader(RdfFormat::TriG, DATA.as_bytes())?;
+    let ex = NamedNodeRef::new("http://example.com")?;
+    let bnode = BlankNode::default();
+    let long =
+        Literal::new_language_tagged_literal("a very long string that is not inlined", "en")?;
+    let triple = Triple::new(bnode.clone(), ex, long.clone());
+    store.insert(QuadRef::new(&bnode, ex, &long, ex))?;
+    store.insert(QuadRef::new(&triple, ex, &triple, &bnode))?;
+    let empty_graph = NamedNodeRef::new("http://example.com/empty")?;
+    store.insert_named_graph(empty_graph)?;
+
+    let snapshot = store.dump_snapshot_to_writer(Vec::new())?;
+    let restored = Store::new()?;
+    restored.load_snapshot_from_reader(snapshot.as_slice())?;
+    let mut expected = store.iter().collect::<Result<Vec<_>, _>>()?;
+    expected.sort_by_key(ToString::to_string);
+    let mut actual = restored.iter().collect::<Result<Vec<_>, _>>()?;
+    actual.sort_by_key(ToString::to_string);
+    assert_eq!(actual, expected);
+    let mut graphs = restored.named_graphs().collect::<Result<Vec<_>, _>>()?;
+    graphs.sort_by_key(ToString::to_string);
+    let mut expected_graphs = store.named_graphs().collect::<Result<Vec<_>, _>>()?;
+    expected_graphs.sort_by_key(ToString::to_string);
+    assert_eq!(graphs, expected_graphs);
+    assert!(restored.contains_named_graph(empty_graph)?);
+    restored.validate()?;
+
+    // Truncated snapshots are rejected
+    Store::new()?
+        .load_snapshot_from_reader(&snapshot[..snapshot.len() - 1])
+        .unwrap_err();
+    Store::new()?
+        .load_snapshot_from_reader(b"not a snapshot".as_slice())
+        .unwrap_err();
+    Ok(())
+}
+
 #[test]
 fn test_load_graph_generates_new_blank_nodes() -> Result<(), Box<dyn Error>> {
     let store = Store::new()?;
@@ -260,6 +582,95 @@ fn test_dump_dataset() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn test_dump_dataset_graphs() -> Result<(), Box<dyn Error>> {
+    let store = Store::new()?;
+    let g1 = NamedNodeRef::new("http://example.com/g1")?;
+    let g2 = NamedNodeRef::new("http://example.com/g2")?;
+    for q in quads(GraphNameRef::DefaultGraph)
+        .into_iter()
+        .chain(quads(g1))
+        .chain(quads(g2))
+    {
+        store.insert(q)?;
+    }
+
+    let buffer = store.dump_to_writer_opt(
+        RdfFormat::NQuads,
+        Vec::new(),
+        DumpOptions::default()
+            .with_dataset_graph(g1)
+            .with_dataset_graph(GraphName::DefaultGraph)
+            .with_dataset_graph(g1),
+    )?;
+    let dumped = Store::new()?;
+    dumped.load_from_reader(RdfFormat::NQuads, buffer.as_slice())?;
+    assert_eq!(dumped.len()?, 2 * NUMBER_OF_TRIPLES);
+    for q in quads(GraphNameRef::DefaultGraph)
+        .into_iter()
+        .chain(quads(g1))
+    {
+        assert!(dumped.contains(q)?);
+    }
+    store
+        .dump_to_writer_opt(
+            RdfFormat::NTriples,
+            Vec::new(),
+            DumpOptions::default().with_dataset_graph(g1),
+        )
+        .unwrap_err();
+    Ok(())
+}
+
+#[test]
+fn test_n3_rules_on_store() -> Result<(), Box<dyn Error>> {
+    let rules = N3Rules::parse(
+        b"@prefix ex: <http://example.com/> .
+        { ?x ex:parent ?y } => { ?x ex:ancestor ?y } .
+        { ?x ex:ancestor ?y . ?y ex:ancestor ?z } => { ?x ex:ancestor ?z } .
+        { ?x a ex:Person } => { ?x ex:hasParent [ a ex:Person ] } ."
+            .as_slice(),
+        None,
+    )?;
+    assert_eq!(rules.len(), 3);
+    let parent = NamedNodeRef::new("http://example.com/parent")?;
+    let ancestor = NamedNodeRef::new("http://example.com/ancestor")?;
+    let a = NamedNodeRef::new("http://example.com/a")?;
+    let b = NamedNodeRef::new("http://example.com/b")?;
+    let c = NamedNodeRef::new("http://example.com/c")?;
+
+    let store = Store::new()?;
+    store.insert(QuadRef::new(a, parent, b, GraphNameRef::DefaultGraph))?;
+    store.insert(QuadRef::new(b, parent, c, GraphNameRef::DefaultGraph))?;
+    let report = rules.apply_to_store(&store, N3ReasoningOptions::default())?;
+    assert_eq!(report.inferred_triples(), 3);
+    assert_eq!(report.iterations(), 2);
+    assert!(store.contains(QuadRef::new(a, ancestor, c, GraphNameRef::DefaultGraph))?);
+    // A fixpoint is reached immediately
+    assert_eq!(
+        rules
+            .apply_to_store(&store, N3ReasoningOptions::default())?
+            .inferred_triples(),
+        0
+    );
+
+    // The rule creating a new person for each person never ends
+    store.insert(QuadRef::new(
+        a,
+        rdf::TYPE,
+        NamedNodeRef::new("http://example.com/Person")?,
+        GraphNameRef::DefaultGraph,
+    ))?;
+    assert!(matches!(
+        rules.apply_to_store(
+            &store,
+            N3ReasoningOptions::default().with_max_iterations(10)
+        ),
+        Err(N3ReasoningError::TooManyIterations(10))
+    ));
+    Ok(())
+}
+
 #[test]
 fn test_snapshot_isolation_iterator() -> Result<(), Box<dyn Error>> {
     let quad = QuadRef::new(
@@ -334,6 +745,825 @@ fn test_bulk_load_on_existing_delete_overrides_the_delete_on_disk() -> Result<()
     Ok(())
 }
 
+#[test]
+fn test_vacuum_empty_named_graphs() -> Result<(), Box<dyn Error>> {
+    check_on_each_backend(check_vacuum_empty_named_graphs)
+}
+
+fn check_vacuum_empty_named_graphs(store: &Store) -> Result<(), Box<dyn Error>> {
+    let empty = NamedNodeRef::new_unchecked("http://example.com/empty");
+    let cleared = NamedNodeRef::new_unchecked("http://example.com/cleared");
+    let full = NamedNodeRef::new_unchecked("http://example.com/full");
+    store.insert_named_graph(empty)?;
+    store.insert(QuadRef::new(cleared, cleared, cleared, cleared))?;
+    store.clear_graph(cleared)?;
+    store.insert(QuadRef::new(full, full, full, full))?;
+    assert_eq!(store.named_graphs().count(), 3);
+    assert_eq!(
+        store
+            .named_graphs()
+            .include_empty(false)
+            .collect::<Result<Vec<_>, _>>()?,
+        vec![NamedOrBlankNode::from(full)]
+    );
+    assert_eq!(store.vacuum()?, 2);
+    assert!(!store.contains_named_graph(empty)?);
+    assert!(!store.contains_named_graph(cleared)?);
+    assert!(store.contains_named_graph(full)?);
+    assert_eq!(store.vacuum()?, 0);
+    assert_eq!(store.len()?, 1);
+    store.validate()?;
+    Ok(())
+}
+
+#[test]
+fn test_on_commit() -> Result<(), Box<dyn Error>> {
+    check_on_each_backend(check_on_commit)
+}
+
+fn check_on_commit(store: &Store) -> Result<(), Box<dyn Error>> {
+    let commits = Arc::new(Mutex::new(Vec::new()));
+    let commits_clone = Arc::clone(&commits);
+    store.on_commit(move |changes| commits_clone.lock().unwrap().push(changes.to_vec()));
+    let ex = NamedNodeRef::new_unchecked("http://example.com");
+    let in_default = QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph);
+    let in_named = QuadRef::new(ex, ex, ex, ex);
+    store.extend([in_default.into_owned(), in_named.into_owned()])?;
+    store.insert(in_default)?; // No change
+    store
+        .transaction(|mut t| {
+            t.remove(in_default)?;
+            Err::<(), _>(StorageError::Other("rollback".into()))
+        })
+        .unwrap_err();
+    store.update("DROP NAMED")?;
+    store.remove(in_default)?;
+    assert_eq!(
+        *commits.lock().map_err(|e| e.to_string())?,
+        [
+            vec![
+                QuadChange::Insert(in_default.into_owned()),
+                QuadChange::Insert(in_named.into_owned())
+            ],
+            vec![
+                QuadChange::Remove(in_named.into_owned()),
+                QuadChange::RemoveNamedGraph(ex.into())
+            ],
+            vec![QuadChange::Remove(in_default.into_owned())]
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_transaction_read_your_writes() -> Result<(), Box<dyn Error>> {
+    check_on_each_backend(check_transaction_read_your_writes)
+}
+
+fn check_transaction_read_your_writes(store: &Store) -> Result<(), Box<dyn Error>> {
+    let a = NamedNodeRef::new_unchecked("http://example.com/a");
+    let b = NamedNodeRef::new_unchecked("http://example.com/b");
+    let p = NamedNodeRef::new_unchecked("http://example.com/p");
+    store.transaction(|mut t| {
+        t.insert(QuadRef::new(a, p, a, GraphNameRef::DefaultGraph))?;
+        assert!(matches!(
+            t.query("ASK { <http://example.com/a> <http://example.com/p> ?o }")?,
+            QueryResults::Boolean(true)
+        ));
+        // The changes done while iterating on the results are not visible in them
+        let QueryResults::Solutions(solutions) = t.query("SELECT ?s WHERE { ?s ?p ?o }")? else {
+            unreachable!()
+        };
+        let mut count = 0;
+        for solution in solutions {
+            solution?;
+            t.insert(QuadRef::new(b, p, b, GraphNameRef::DefaultGraph))?;
+            count += 1;
+        }
+        assert_eq!(count, 1);
+        // The updates see the previous changes
+        t.update("DELETE { ?s ?p ?o } WHERE { ?s ?p ?o FILTER(?s = <http://example.com/b>) }")?;
+        assert_eq!(t.len()?, 1);
+        Result::<_, EvaluationError>::Ok(())
+    })?;
+    assert_eq!(store.len()?, 1);
+
+    // The rolled back changes are not visible anymore
+    store
+        .transaction(|mut t| {
+            t.update("INSERT DATA { <http://example.com/b> <http://example.com/p> 1 }")?;
+            assert_eq!(t.len()?, 2);
+            Err::<(), _>(EvaluationError::Storage(StorageError::Other(
+                "rollback".into(),
+            )))
+        })
+        .unwrap_err();
+    assert_eq!(store.len()?, 1);
+    Ok(())
+}
+
+#[test]
+fn test_transaction_serializable() -> Result<(), Box<dyn Error>> {
+    check_on_each_backend(check_transaction_serializable)
+}
+
+fn check_transaction_serializable(store: &Store) -> Result<(), Box<dyn Error>> {
+    // Each transaction inserts a quad if there is none: only the first one should do it
+    std::thread::scope(|scope| {
+        let threads = (0..8)
+            .map(|i| {
+                let store = store.clone();
+                scope.spawn(move || {
+                    store.transaction(|mut t| {
+                        if matches!(
+                            t.query("ASK { ?s <http://example.com/p> ?o }")?,
+                            QueryResults::Boolean(false)
+                        ) {
+                            t.update(&format!(
+                                "INSERT DATA {{ <http://example.com/{i}> <http://example.com/p> {i} }}"
+                            ))?;
+                        }
+                        Result::<_, EvaluationError>::Ok(())
+                    })
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            thread.join().unwrap()?;
+        }
+        Result::<_, EvaluationError>::Ok(())
+    })?;
+    assert_eq!(store.len()?, 1);
+    Ok(())
+}
+
+#[test]
+#[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+fn test_nested_transaction_conflict() -> Result<(), Box<dyn Error>> {
+    let dir = TempDir::default();
+    let store = Store::open(&dir.0)?;
+    let ex = NamedNodeRef::new("http://example.com")?;
+    let quad = QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph);
+    // The inner transaction writes to the graph read by the outer one: it fails instead of waiting forever
+    store
+        .transaction(|t| {
+            assert!(!t.contains(quad)?);
+            store.insert(quad)
+        })
+        .unwrap_err();
+    assert!(!store.contains(quad)?);
+    Ok(())
+}
+
+#[test]
+fn test_query_with_initial_bindings() -> Result<(), Box<dyn Error>> {
+    let store = Store::new()?;
+    store.load_from_reader(RdfFormat::Turtle, DATA.as_bytes())?;
+    let o = Variable::new("o")?;
+    let query = Query::parse("SELECT DISTINCT ?p WHERE { ?s ?p ?o } LIMIT 10", None)?;
+
+    // The bindings restrict the query like a VALUES clause, even if they bind unselected variables
+    let bindings = ["75001", "75002"]
+        .map(|code| QuerySolution::from((vec![o.clone()], vec![Some(Literal::from(code).into())])));
+    let QueryResults::Solutions(solutions) =
+        store.query(query.clone().with_initial_bindings(bindings)?)?
+    else {
+        return Err("Solutions expected".into());
+    };
+    let solutions = solutions.collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(solutions.len(), 1);
+    assert_eq!(
+        solutions[0].get("p"),
+        Some(&NamedNode::new("http://schema.org/postalCode")?.into())
+    );
+
+    // No bindings means no solution
+    let QueryResults::Solutions(mut solutions) =
+        store.query(query.clone().with_initial_bindings([])?)?
+    else {
+        return Err("Solutions expected".into());
+    };
+    assert!(solutions.next().is_none());
+
+    // Blank nodes are not allowed
+    assert!(matches!(
+        query.with_initial_bindings([QuerySolution::from((
+            vec![o.clone()],
+            vec![Some(BlankNode::default().into())]
+        ))]),
+        Err(EvaluationError::BlankNodeInInitialBindings(v)) if v == o
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_graph_canonical_hash() -> Result<(), Box<dyn Error>> {
+    let ex = NamedNodeRef::new_unchecked("http://example.com");
+    let g1 = NamedNodeRef::new_unchecked("http://example.com/g1");
+    let g2 = NamedNodeRef::new_unchecked("http://example.com/g2");
+    let store = Store::new()?;
+    store.enable_graph_hash_cache();
+    let empty_hash = store.graph_canonical_hash(g1)?;
+    assert_eq!(
+        store.graph_canonical_hash(GraphNameRef::DefaultGraph)?,
+        empty_hash
+    );
+
+    // Transactions invalidate the changed graphs
+    store.insert(QuadRef::new(ex, ex, &BlankNode::default(), g1))?;
+    let hash = store.graph_canonical_hash(g1)?;
+    assert_ne!(hash, empty_hash);
+    assert_eq!(store.graph_canonical_hash(g1)?, hash);
+    assert_eq!(store.graph_canonical_hash(g2)?, empty_hash);
+    store.insert(QuadRef::new(ex, ex, &BlankNode::default(), g2))?;
+    assert_eq!(store.graph_canonical_hash(g2)?, hash);
+    store.update("DROP GRAPH <http://example.com/g1>")?;
+    assert_eq!(store.graph_canonical_hash(g1)?, empty_hash);
+
+    // Bulk loads invalidate everything
+    store
+        .bulk_loader()
+        .load_quads([Quad::new(ex, ex, ex, g2)])?;
+    let mut expected = Graph::new();
+    expected.insert(TripleRef::new(ex, ex, &BlankNode::default()));
+    expected.insert(TripleRef::new(ex, ex, ex));
+    assert_eq!(store.graph_canonical_hash(g2)?, expected.canonical_hash());
+    Ok(())
+}
+
+#[test]
+fn test_annotations() -> Result<(), Box<dyn Error>> {
+    check_on_each_backend(check_annotations)
+}
+
+fn check_annotations(store: &Store) -> Result<(), Box<dyn Error>> {
+    let ex = NamedNodeRef::new_unchecked("http://example.com");
+    let source = NamedNodeRef::new_unchecked("http://example.com/source");
+    let g = NamedNodeRef::new_unchecked("http://example.com/g");
+    let quad = QuadRef::new(ex, ex, ex, g);
+    let quoted = Subject::Triple(Box::new(TripleRef::from(quad).into_owned()));
+    let annotation = QuadRef::new(&quoted, source, ex, g);
+
+    // Annotating a missing quad adds it
+    assert_eq!(store.annotations(quad).count(), 0);
+    assert!(store.annotate(quad, source, ex)?);
+    assert!(store.contains(quad)?);
+    assert_eq!(store.len()?, 2);
+    assert_eq!(
+        store.annotations(quad).collect::<Result<Vec<_>, _>>()?,
+        [annotation.into_owned()]
+    );
+    // The annotations are only looked for in the graph of the quad
+    assert_eq!(
+        store
+            .annotations(QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph))
+            .count(),
+        0
+    );
+
+    // Duplicate annotations are not added twice
+    assert!(!store.annotate(quad, source, ex)?);
+    assert_eq!(store.annotations(quad).count(), 1);
+
+    // Removing the annotated quad keeps its annotations
+    assert!(store.remove(quad)?);
+    assert_eq!(store.annotations(quad).count(), 1);
+    assert!(store.remove(annotation)?);
+    assert_eq!(store.annotations(quad).count(), 0);
+    assert!(store.is_empty()?);
+    Ok(())
+}
+
+#[test]
+fn test_query_results_cache() -> Result<(), Box<dyn Error>> {
+    let ex = NamedNodeRef::new_unchecked("http://example.com");
+    let store = Store::new()?;
+    let cache = QueryResultsCache::new(1).with_max_results(1);
+    let select = |query: &str| -> Result<Vec<QuerySolution>, Box<dyn Error>> {
+        let QueryResults::Solutions(solutions) = store.query_opt(
+            query,
+            QueryOptions::default().with_results_cache(cache.clone()),
+        )?
+        else {
+            return Err("SELECT query expected".into());
+        };
+        Ok(solutions.collect::<Result<Vec<_>, _>>()?)
+    };
+
+    // Only fully consumed results are cached
+    store.insert(QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph))?;
+    assert_eq!(select("SELECT * WHERE { ?s ?p ?o }")?.len(), 1);
+    assert_eq!(cache.len(), 1);
+    assert_eq!(select("SELECT * WHERE { ?s ?p ?o }")?.len(), 1);
+
+    // Transactions invalidate the cache
+    store.insert(QuadRef::new(ex, ex, ex, ex))?;
+    assert_eq!(select("SELECT * WHERE { GRAPH ?g { ?s ?p ?o } }")?.len(), 1);
+    assert_eq!(cache.len(), 1);
+
+    // Bulk loads invalidate the cache
+    store.bulk_loader().load_quads([Quad::new(
+        ex,
+        ex,
+        NamedNode::new("http://example.com/o")?,
+        ex,
+    )])?;
+    assert_eq!(select("SELECT * WHERE { GRAPH ?g { ?s ?p ?o } }")?.len(), 2);
+
+    // Results larger than the limit are not cached
+    assert!(cache.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_graph_metadata() -> Result<(), Box<dyn Error>> {
+    check_on_each_backend(check_graph_metadata)
+}
+
+#[test]
+fn test_snapshot() -> Result<(), Box<dyn Error>> {
+    check_on_each_backend(check_snapshot)
+}
+
+fn check_snapshot(store: &Store) -> Result<(), Box<dyn Error>> {
+    let ex = NamedNodeRef::new_unchecked("http://example.com");
+    let g = NamedNodeRef::new_unchecked("http://example.com/g");
+    store.insert(QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph))?;
+    let snapshot = store.snapshot();
+
+    // Changes done after the snapshot creation
+    store.remove(QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph))?;
+    store.insert(QuadRef::new(ex, ex, ex, g))?;
+    store
+        .bulk_loader()
+        .load_quads([QuadRef::new(ex, ex, g, GraphNameRef::DefaultGraph).into_owned()])?;
+    assert_eq!(store.len()?, 2);
+
+    assert_eq!(
+        snapshot.iter().collect::<Result<Vec<_>, _>>()?,
+        [QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph).into_owned()]
+    );
+    assert!(snapshot.contains(QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph))?);
+    assert!(!snapshot.contains_named_graph(g)?);
+    assert_eq!(snapshot.named_graphs().count(), 0);
+    let QueryResults::Solutions(solutions) =
+        snapshot.query("SELECT ?o WHERE { { GRAPH ?g { ?s ?p ?o } } UNION { ?s ?p ?o } }")?
+    else {
+        return Err("solutions expected".into());
+    };
+    assert_eq!(
+        solutions
+            .map(|s| Ok(s?.get("o").cloned()))
+            .collect::<Result<Vec<_>, EvaluationError>>()?,
+        [Some(ex.into_owned().into())]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_history() -> Result<(), Box<dyn Error>> {
+    check_on_each_backend(check_history)
+}
+
+#[test]
+#[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+fn test_history_is_persisted() -> Result<(), Box<dyn Error>> {
+    let dir = TempDir::default();
+    check_history(&Store::open(&dir.0)?)?;
+    let store = Store::open(&dir.0)?;
+    assert_eq!(store.history_version()?, Some(5));
+    store.insert(QuadRef::new(
+        NamedNodeRef::new_unchecked("http://example.com"),
+        NamedNodeRef::new_unchecked("http://example.com"),
+        NamedNodeRef::new_unchecked("http://example.com"),
+        GraphNameRef::DefaultGraph,
+    ))?;
+    assert_eq!(store.history_version()?, Some(6));
+    Ok(())
+}
+
+fn check_history(store: &Store) -> Result<(), Box<dyn Error>> {
+    let ex = NamedNodeRef::new_unchecked("http://example.com");
+    let g = NamedNodeRef::new_unchecked("http://example.com/g");
+    let initial = QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph);
+    store.insert(initial)?;
+    assert_eq!(store.history_version()?, None);
+    assert!(store.history_since(0).is_err());
+
+    store.enable_history()?;
+    store.enable_history()?;
+    assert_eq!(store.history_version()?, Some(0));
+    let snapshot = store.snapshot();
+    store.insert(QuadRef::new(ex, ex, ex, g))?;
+    assert_eq!(snapshot.history_version()?, Some(0));
+    store.remove(QuadRef::new(ex, ex, ex, g))?; // Checks that the changes are undone in the right order
+    store.insert(QuadRef::new(ex, ex, g, g))?;
+    store.transaction(|mut t| {
+        t.remove(initial)?;
+        t.insert(QuadRef::new(ex, g, ex, GraphNameRef::DefaultGraph))
+    })?;
+    store.insert(QuadRef::new(ex, ex, g, g))?; // No change, no new version
+    store.clear_graph(g)?;
+    assert_eq!(store.history_version()?, Some(5));
+
+    let entries = store.history_since(3)?.collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(
+        entries
+            .iter()
+            .map(HistoryEntry::version)
+            .collect::<Vec<_>>(),
+        [4, 5]
+    );
+    assert_eq!(
+        entries[0].changes(),
+        [
+            QuadChange::Remove(initial.into_owned()),
+            QuadChange::Insert(QuadRef::new(ex, g, ex, GraphNameRef::DefaultGraph).into_owned())
+        ]
+    );
+    assert_eq!(
+        entries[1].changes(),
+        [QuadChange::Remove(QuadRef::new(ex, ex, g, g).into_owned())]
+    );
+    assert!(entries[0].timestamp() <= entries[1].timestamp());
+    assert_eq!(store.history_since(5)?.count(), 0);
+
+    assert_eq!(
+        store.dataset_at_version(0)?,
+        [initial].into_iter().collect::<Dataset>()
+    );
+    assert_eq!(
+        store.dataset_at_version(2)?,
+        [initial].into_iter().collect::<Dataset>()
+    );
+    assert_eq!(
+        store.dataset_at_version(3)?,
+        [initial, QuadRef::new(ex, ex, g, g)]
+            .into_iter()
+            .collect::<Dataset>()
+    );
+    assert_eq!(
+        store.dataset_at_version(5)?,
+        [QuadRef::new(ex, g, ex, GraphNameRef::DefaultGraph)]
+            .into_iter()
+            .collect::<Dataset>()
+    );
+    store.dataset_at_version(6).unwrap_err();
+
+    // The bulk loads are a boundary of the history
+    store
+        .bulk_loader()
+        .load_quads([QuadRef::new(g, g, g, g).into_owned()])?;
+    assert_eq!(store.history_version()?, Some(6));
+    let entry = store.history_since(5)?.next().unwrap()?;
+    assert!(entry.is_bulk_load());
+    assert!(entry.changes().is_empty());
+    assert!(!store.history_since(4)?.next().unwrap()?.is_bulk_load());
+    assert_eq!(store.dataset_at_version(6)?.len(), 2);
+    store.dataset_at_version(5).unwrap_err();
+
+    // Empty named graphs
+    let empty = NamedNodeRef::new_unchecked("http://example.com/empty");
+    store.insert_named_graph(empty)?;
+    store.insert_named_graph(empty)?; // No change, no new version
+    store.remove_named_graph(empty)?;
+    assert_eq!(
+        store
+            .history_since(6)?
+            .map(|entry| Ok(entry?.changes().to_vec()))
+            .collect::<Result<Vec<_>, StorageError>>()?,
+        [
+            vec![QuadChange::InsertNamedGraph(empty.into())],
+            vec![QuadChange::RemoveNamedGraph(empty.into())]
+        ]
+    );
+    assert_eq!(store.dataset_at_version(7)?.len(), 2);
+    store.validate()?;
+    Ok(())
+}
+
+fn check_graph_metadata(store: &Store) -> Result<(), Box<dyn Error>> {
+    let ex = NamedNodeRef::new_unchecked("http://example.com");
+    let g = NamedNodeRef::new_unchecked("http://example.com/g");
+    assert!(store.graph_metadata(g)?.is_none());
+    assert!(store
+        .graph_metadata(GraphNameRef::DefaultGraph)?
+        .is_some_and(|m| m.is_empty()));
+
+    // Quad count and timestamps
+    store.insert(QuadRef::new(ex, ex, ex, g))?;
+    store.insert(QuadRef::new(ex, ex, g, g))?;
+    store.remove(QuadRef::new(ex, ex, ex, g))?;
+    let metadata = store.graph_metadata(g)?.ok_or("graph metadata expected")?;
+    assert_eq!(metadata.len(), 1);
+    assert!(metadata.created().is_some());
+    assert!(metadata.modified() >= metadata.created());
+    assert_eq!(metadata.version(), 3);
+
+    // Properties
+    store.set_graph_property(g, "source", Some("http://example.com/dump.nt"))?;
+    store.set_graph_property(g, "license", Some("CC0"))?;
+    store.set_graph_property(g, "license", None)?;
+    let metadata = store.graph_metadata(g)?.ok_or("graph metadata expected")?;
+    assert_eq!(
+        metadata.properties().collect::<Vec<_>>(),
+        [("source", "http://example.com/dump.nt")]
+    );
+    assert_eq!(metadata.len(), 1);
+
+    // Bulk loads
+    store
+        .bulk_loader()
+        .load_quads([Quad::new(ex, ex, ex, g), Quad::new(ex, g, ex, g)])?;
+    assert_eq!(store.graph_metadata(g)?.map(|m| m.len()), Some(3));
+    // The quads already in the graph are not counted again
+    store.bulk_loader().load_quads([Quad::new(ex, g, ex, g)])?;
+    assert_eq!(store.graph_metadata(g)?.map(|m| m.len()), Some(3));
+
+    // Clear and removal
+    store.clear_graph(g)?;
+    let metadata = store.graph_metadata(g)?.ok_or("graph metadata expected")?;
+    assert!(metadata.is_empty());
+    assert_eq!(
+        metadata.property("source"),
+        Some("http://example.com/dump.nt")
+    );
+    assert_eq!(metadata.version(), 5);
+    store.remove_named_graph(g)?;
+    assert!(store.graph_metadata(g)?.is_none());
+    Ok(())
+}
+
+#[test]
+fn test_text_index() -> Result<(), Box<dyn Error>> {
+    let label = NamedNodeRef::new_unchecked("http://www.w3.org/2000/01/rdf-schema#label");
+    let comment = NamedNodeRef::new_unchecked("http://www.w3.org/2000/01/rdf-schema#comment");
+    let paris = NamedNodeRef::new_unchecked("http://example.com/paris");
+    let lyon = NamedNodeRef::new_unchecked("http://example.com/lyon");
+    let graph = NamedNodeRef::new_unchecked("http://example.com/g");
+    let store = Store::new()?;
+    store.insert(QuadRef::new(
+        paris,
+        label,
+        LiteralRef::new_simple_literal("Paris, the capital city of France"),
+        graph,
+    ))?;
+    store.enable_text_index([label.into_owned()], InMemoryTextIndex::default())?;
+    store
+        .enable_text_index([label.into_owned()], InMemoryTextIndex::default())
+        .unwrap_err();
+    store.insert(QuadRef::new(
+        lyon,
+        label,
+        LiteralRef::new_language_tagged_literal_unchecked("Lyon, a city of France", "en"),
+        GraphNameRef::DefaultGraph,
+    ))?;
+    store.insert(QuadRef::new(
+        lyon,
+        comment,
+        LiteralRef::new_simple_literal("Not the capital"),
+        GraphNameRef::DefaultGraph,
+    ))?;
+
+    let search = |words: &str| -> Result<Vec<Term>, Box<dyn Error>> {
+        let QueryResults::Solutions(solutions) = store.query(format!(
+            "PREFIX ofts: <http://oxigraph.org/fts#> SELECT ?s WHERE {{ SERVICE ofts:search {{ ?s ofts:query \"{words}\" }} }}"
+        ).as_str())? else {
+            unreachable!()
+        };
+        let mut subjects = solutions
+            .map(|s| Ok(s?.get("s").cloned().unwrap()))
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+        subjects.sort_by_key(ToString::to_string);
+        Ok(subjects)
+    };
+    assert_eq!(search("capital")?, [Term::from(paris)]); // The comments are not indexed
+    assert_eq!(
+        search("FRANCE city")?,
+        [Term::from(lyon), Term::from(paris)]
+    );
+    assert!(search("France London")?.is_empty());
+
+    // Join with a local pattern
+    store.insert(QuadRef::new(
+        paris,
+        rdf::TYPE,
+        NamedNodeRef::new_unchecked("http://example.com/City"),
+        GraphNameRef::DefaultGraph,
+    ))?;
+    let QueryResults::Solutions(solutions) = store.query(
+        "PREFIX ofts: <http://oxigraph.org/fts#> SELECT ?s WHERE { ?s a <http://example.com/City> . SERVICE ofts:search { ?s ofts:query 'capital' } }",
+    )? else {
+        unreachable!()
+    };
+    assert_eq!(
+        solutions
+            .map(|s| Ok(s?.get("s").cloned()))
+            .collect::<Result<Vec<_>, EvaluationError>>()?,
+        [Some(Term::from(paris))]
+    );
+
+    // All the bindings
+    let QueryResults::Solutions(mut solutions) = store.query(
+        "PREFIX ofts: <http://oxigraph.org/fts#> SELECT * WHERE { SERVICE ofts:search { ?s ofts:query 'capital' ; ofts:literal ?l ; ofts:predicate ?p ; ofts:graph ?g ; ofts:score ?score ; ofts:limit 1 } }",
+    )? else {
+        unreachable!()
+    };
+    let solution = solutions.next().unwrap()?;
+    assert_eq!(solution.get("s"), Some(&paris.into()));
+    assert_eq!(
+        solution.get("l"),
+        Some(&Literal::new_simple_literal("Paris, the capital city of France").into())
+    );
+    assert_eq!(solution.get("p"), Some(&label.into()));
+    assert_eq!(solution.get("g"), Some(&graph.into()));
+    assert!(solution.get("score").is_some());
+    assert!(solutions.next().is_none());
+
+    // Invalid patterns
+    store
+        .query("PREFIX ofts: <http://oxigraph.org/fts#> SELECT * WHERE { SERVICE ofts:search { ?s ofts:literal ?l } }")
+        .and_then(|results| match results {
+            QueryResults::Solutions(mut solutions) => solutions.next().transpose().map(|_| ()),
+            _ => Ok(()),
+        })
+        .unwrap_err();
+
+    // Changes
+    store.update("DELETE WHERE { <http://example.com/lyon> ?p ?o }")?;
+    assert_eq!(search("city")?, [Term::from(paris)]);
+    store.bulk_loader().load_quads([Quad::new(
+        lyon,
+        label,
+        Literal::new_simple_literal("Lyon"),
+        GraphName::DefaultGraph,
+    )])?;
+    assert_eq!(search("lyon")?, [Term::from(lyon)]);
+    Ok(())
+}
+
+#[test]
+fn test_store_service() -> Result<(), Box<dyn Error>> {
+    let name = NamedNodeRef::new_unchecked("http://schema.org/name");
+    let country = NamedNodeRef::new_unchecked("http://schema.org/country");
+    let paris = NamedNodeRef::new_unchecked("http://example.com/paris");
+    let lyon = NamedNodeRef::new_unchecked("http://example.com/lyon");
+    let france = NamedNodeRef::new_unchecked("http://example.com/france");
+    let reference = NamedNode::new_unchecked("http://example.com/reference");
+
+    let reference_data = Store::new()?;
+    reference_data.insert(QuadRef::new(
+        france,
+        name,
+        LiteralRef::new_simple_literal("France"),
+        GraphNameRef::DefaultGraph,
+    ))?;
+    let store = Store::new()?;
+    for city in [paris, lyon] {
+        store.insert(QuadRef::new(
+            city,
+            country,
+            france,
+            GraphNameRef::DefaultGraph,
+        ))?;
+    }
+    let options =
+        QueryOptions::default().with_store_service(reference.clone(), reference_data.clone());
+
+    let select =
+        |query: &str, options: QueryOptions| -> Result<Vec<QuerySolution>, Box<dyn Error>> {
+            let QueryResults::Solutions(solutions) = store.query_opt(query, options)? else {
+                unreachable!()
+            };
+            let mut solutions = solutions.collect::<Result<Vec<_>, _>>()?;
+            solutions.sort_by_key(|s| s.get("city").map(ToString::to_string));
+            Ok(solutions)
+        };
+
+    // Join with the local data
+    let query = "SELECT ?city ?name WHERE { ?city <http://schema.org/country> ?c SERVICE <http://example.com/reference> { ?c <http://schema.org/name> ?name } }";
+    let solutions = select(query, options.clone())?;
+    assert_eq!(solutions.len(), 2);
+    assert_eq!(solutions[0].get("city"), Some(&lyon.into()));
+    assert_eq!(solutions[1].get("city"), Some(&paris.into()));
+    for solution in &solutions {
+        assert_eq!(
+            solution.get("name"),
+            Some(&Literal::new_simple_literal("France").into())
+        );
+    }
+
+    // The local data is not visible from the service and the reverse
+    assert!(select(
+        "SELECT * WHERE { SERVICE <http://example.com/reference> { ?city <http://schema.org/country> ?c } }",
+        options.clone()
+    )?
+    .is_empty());
+    assert!(select(
+        "SELECT * WHERE { ?c <http://schema.org/name> ?name }",
+        options.clone()
+    )?
+    .is_empty());
+
+    // The changes to the service store are visible
+    reference_data.remove(QuadRef::new(
+        france,
+        name,
+        LiteralRef::new_simple_literal("France"),
+        GraphNameRef::DefaultGraph,
+    ))?;
+    assert!(select(query, options.clone())?.is_empty());
+
+    // The other service names are not routed to the store
+    store
+        .query_opt(
+            "SELECT * WHERE { SERVICE <http://example.com/other> { ?s ?p ?o } }",
+            options.clone().without_service_handler(),
+        )
+        .and_then(|results| match results {
+            QueryResults::Solutions(mut solutions) => solutions.next().transpose().map(|_| ()),
+            _ => Ok(()),
+        })
+        .unwrap_err();
+    assert_eq!(
+        select(
+            "SELECT * WHERE { SERVICE SILENT <http://example.com/other> { ?s ?p ?o } }",
+            options.without_service_handler()
+        )?
+        .len(),
+        1
+    );
+    Ok(())
+}
+
+#[test]
+fn test_update_using() -> Result<(), Box<dyn Error>> {
+    check_on_each_backend(check_update_using)
+}
+
+fn check_update_using(store: &Store) -> Result<(), Box<dyn Error>> {
+    store.update(
+        "PREFIX ex: <http://example.com/>
+        INSERT DATA { ex:s ex:p ex:o . GRAPH ex:g1 { ex:s1 ex:p ex:o1 } GRAPH ex:g2 { ex:s2 ex:p ex:o2 } }",
+    )?;
+    let graph_subjects = |graph_name: &str| {
+        let mut subjects = store
+            .quads_for_pattern(
+                None,
+                None,
+                None,
+                Some(NamedNodeRef::new_unchecked(graph_name).into()),
+            )
+            .map(|q| Ok(q?.subject.to_string()))
+            .collect::<Result<Vec<_>, StorageError>>()?;
+        subjects.sort();
+        Ok::<_, StorageError>(subjects)
+    };
+
+    // USING and USING NAMED select the graphs of the WHERE clause
+    store.update(
+        "PREFIX ex: <http://example.com/>
+        INSERT { GRAPH ex:r1 { ?s ex:in ?g } } USING ex:g1 USING NAMED ex:g2
+        WHERE { { ?s ?p ?o BIND(ex:default AS ?g) } UNION { GRAPH ?g { ?s ?p ?o } } }",
+    )?;
+    assert_eq!(
+        graph_subjects("http://example.com/r1")?,
+        ["<http://example.com/s1>", "<http://example.com/s2>"]
+    );
+
+    // The USING clauses can be changed after parsing
+    let mut update = Update::parse(
+        "PREFIX ex: <http://example.com/> INSERT { GRAPH ex:r2 { ?s ex:p ?o } } WHERE { ?s ex:p ?o }",
+        None,
+    )?;
+    for using in update.using_datasets_mut() {
+        using.set_default_graph_as_union();
+    }
+    store.update(update)?;
+    assert_eq!(
+        graph_subjects("http://example.com/r2")?,
+        [
+            "<http://example.com/s1>",
+            "<http://example.com/s2>",
+            "<http://example.com/s>"
+        ]
+    );
+
+    // Errors
+    assert!(matches!(
+        store.update("CREATE GRAPH <http://example.com/g1>"),
+        Err(EvaluationError::GraphAlreadyExists(_))
+    ));
+    store.update(
+        "CREATE SILENT GRAPH <http://example.com/g1> ; LOAD SILENT <http://example.com/nothing>",
+    )?;
+    assert!(store
+        .update("LOAD <http://example.com/nothing> INTO GRAPH <http://example.com/g3>")
+        .is_err());
+    assert!(!store.contains_named_graph(NamedNodeRef::new_unchecked("http://example.com/g3"))?);
+    Ok(())
+}
+
 #[test]
 #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
 fn test_open_bad_dir() -> Result<(), Box<dyn Error>> {
@@ -364,6 +1594,22 @@ fn test_bad_stt_open() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+#[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+fn test_warm_up_on_disk() -> Result<(), Box<dyn Error>> {
+    let dir = TempDir::default();
+    let store = Store::open(&dir.0)?;
+    store.load_from_reader(RdfFormat::Turtle, DATA.as_bytes())?;
+    store.warm_up(WarmUpOptions::default())?;
+    store.warm_up(
+        WarmUpOptions::default()
+            .with_max_size_in_megabytes(0)
+            .with_query(Query::parse("SELECT * WHERE { ?s ?p ?o }", None)?)
+            .with_query(Query::parse("CONSTRUCT WHERE { ?s ?p ?o }", None)?),
+    )?;
+    Ok(())
+}
+
 #[test]
 #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
 fn test_backup() -> Result<(), Box<dyn Error>> {
@@ -395,6 +1641,57 @@ fn test_backup() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+#[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+fn test_incremental_backup() -> Result<(), Box<dyn Error>> {
+    let quad = QuadRef::new(
+        NamedNodeRef::new_unchecked("http://example.com/s"),
+        NamedNodeRef::new_unchecked("http://example.com/p"),
+        NamedNodeRef::new_unchecked("http://example.com/o"),
+        GraphNameRef::DefaultGraph,
+    );
+    let other_quad = QuadRef::new(
+        NamedNodeRef::new_unchecked("http://example.com/s"),
+        NamedNodeRef::new_unchecked("http://example.com/p"),
+        NamedNodeRef::new_unchecked("http://example.com/o2"),
+        GraphNameRef::DefaultGraph,
+    );
+    let store_dir = TempDir::default();
+    let full_backup_dir = TempDir::default();
+    let incremental_backup_dir = TempDir::default();
+    let incremental_backup_from_ro_dir = TempDir::default();
+
+    let store = Store::open(&store_dir)?;
+    store.insert(quad)?;
+    store.flush()?;
+    store.backup(&full_backup_dir)?;
+    store.insert(other_quad)?;
+    store.flush()?;
+    store.incremental_backup(&incremental_backup_dir, &full_backup_dir)?;
+    // The backup directories must not be left in the store directory
+    assert!(
+        read_dir(&store_dir)?.all(|entry| entry.map_or(true, |entry| !entry
+            .file_name()
+            .to_string_lossy()
+            .starts_with("backup-")))
+    );
+
+    let incremental_backup = Store::open_read_only(&incremental_backup_dir.0)?;
+    incremental_backup.validate()?;
+    assert!(incremental_backup.contains(quad)?);
+    assert!(incremental_backup.contains(other_quad)?);
+    incremental_backup.incremental_backup(&incremental_backup_from_ro_dir, &full_backup_dir)?;
+
+    // The previous backup can be removed without impacting the new ones
+    remove_dir_all(&full_backup_dir.0)?;
+    let incremental_backup_from_ro = Store::open_read_only(&incremental_backup_from_ro_dir.0)?;
+    incremental_backup_from_ro.validate()?;
+    assert!(incremental_backup_from_ro.contains(quad)?);
+    assert!(incremental_backup_from_ro.contains(other_quad)?);
+
+    Ok(())
+}
+
 #[test]
 #[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
 fn test_bad_backup() -> Result<(), Box<dyn Error>> {
@@ -414,6 +1711,55 @@ fn test_backup_on_in_memory() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+#[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+fn test_size_statistics_and_compact_range() -> Result<(), Box<dyn Error>> {
+    let store_dir = TempDir::default();
+    let store = Store::open(&store_dir)?;
+    for graph_name in [
+        GraphNameRef::DefaultGraph,
+        NamedNodeRef::new_unchecked("http://www.wikidata.org/wiki/Special:EntityData/Q90").into(),
+    ] {
+        for quad in quads(graph_name) {
+            store.insert(quad)?;
+        }
+    }
+    store.flush()?;
+
+    let statistics = store.size_statistics()?;
+    assert!(statistics.directory_size() > 0);
+    assert!(statistics.approximate_quads() > 0);
+    for name in ["id2str", "spog", "dspo", "gspo"] {
+        let index = statistics
+            .indexes()
+            .iter()
+            .find(|index| index.name() == name)
+            .ok_or("index not found")?;
+        assert!(index.approximate_entries() > 0);
+        assert!(index.live_files_size() > 0);
+    }
+
+    store.clear_graph(NamedNodeRef::new_unchecked(
+        "http://www.wikidata.org/wiki/Special:EntityData/Q90",
+    ))?;
+    store.compact_range(NamedNodeRef::new_unchecked(
+        "http://www.wikidata.org/wiki/Special:EntityData/Q90",
+    ))?;
+    store.compact_range(GraphNameRef::DefaultGraph)?;
+    store.validate()?;
+    for quad in quads(GraphNameRef::DefaultGraph) {
+        assert!(store.contains(quad)?);
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg(all(not(target_family = "wasm"), feature = "rocksdb"))]
+fn test_size_statistics_on_in_memory() -> Result<(), Box<dyn Error>> {
+    Store::new()?.size_statistics().unwrap_err();
+    Ok(())
+}
+
 #[test]
 #[cfg(all(target_os = "linux", feature = "rocksdb"))]
 fn test_backward_compatibility() -> Result<(), Box<dyn Error>> {
@@ -0,0 +1,17 @@
+#![doc = include_str!("../README.md")]
+#![doc(test(attr(deny(warnings))))]
+#![cfg_attr(docsrs, feature(doc_auto_cfg))]
+#![doc(html_favicon_url = "https://raw.githubusercontent.com/oxigraph/oxigraph/main/logo.svg")]
+#![doc(html_logo_url = "https://raw.githubusercontent.com/oxigraph/oxigraph/main/logo.svg")]
+
+mod error;
+mod parser;
+mod serializer;
+
+pub use error::{MicrodataParseError, MicrodataSyntaxError};
+#[cfg(feature = "async-tokio")]
+pub use parser::TokioAsyncReaderMicrodataParser;
+pub use parser::{MicrodataParser, ReaderMicrodataParser, SliceMicrodataParser};
+#[cfg(feature = "async-tokio")]
+pub use serializer::TokioAsyncWriterMicrodataSerializer;
+pub use serializer::{MicrodataSerializer, WriterMicrodataSerializer};
@@ -0,0 +1,958 @@
+use crate::error::{MicrodataParseError, MicrodataSyntaxError};
+use oxilangtag::LanguageTag;
+use oxiri::{Iri, IriParseError};
+use oxrdf::vocab::{rdf, xsd};
+use oxrdf::{BlankNode, Literal, NamedNode, NamedOrBlankNode, Term, Triple};
+use oxsdatatypes::{Date, DateTime, Double, Duration, GYear, GYearMonth, Integer, Time};
+use quick_xml::escape::{resolve_html5_entity, unescape_with};
+use quick_xml::events::attributes::Attribute;
+use quick_xml::events::{BytesStart, BytesText, Event};
+use quick_xml::Reader;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{BufReader, Read};
+use std::str::FromStr;
+#[cfg(feature = "async-tokio")]
+use tokio::io::{AsyncRead, BufReader as AsyncBufReader};
+
+/// A [HTML Microdata](https://html.spec.whatwg.org/multipage/microdata.html) parser
+/// following the [Microdata to RDF](https://www.w3.org/TR/microdata-rdf/) conversion.
+///
+/// Items might refer to any element of the document using `@itemref`.
+/// Hence, the parser keeps in memory the element tree (without the text not used as property value)
+/// and returns the triples when the end of the document is reached.
+///
+/// The document is read with a lenient XML tokenizer that supports the most common HTML constructs:
+/// void elements like `<br>`, missing end tags, unquoted attribute values, HTML entities and `<script>` and `<style>` content.
+///
+/// The IRIs of the properties that are not absolute IRIs are built from the first type of the item
+/// (or of the closest ancestor item with a type):
+/// the `name` property of an item of type `http://schema.org/Person` is `http://schema.org/name`.
+/// These properties are ignored if there is no such type.
+///
+/// Invalid `@itemid`, `@href` and `@src` values and invalid language tags are ignored.
+///
+/// Count the number of people:
+/// ```
+/// use oxrdf::vocab::rdf;
+/// use oxrdf::NamedNodeRef;
+/// use oxmicrodata::MicrodataParser;
+///
+/// let file = br#"<!DOCTYPE html>
+/// <html>
+///   <body>
+///     <div itemscope itemtype="http://schema.org/Person" itemid="http://example.com/foo">
+///       <span itemprop="name">Foo</span>
+///       <a itemprop="knows" itemscope itemtype="http://schema.org/Person" itemid="http://example.com/bar">Bar</a>
+///     </div>
+///   </body>
+/// </html>"#;
+///
+/// let schema_person = NamedNodeRef::new("http://schema.org/Person")?;
+/// let mut count = 0;
+/// for triple in MicrodataParser::new()
+///     .with_base_iri("http://example.com/page.html")?
+///     .for_reader(file.as_ref())
+/// {
+///     let triple = triple?;
+///     if triple.predicate == rdf::TYPE && triple.object == schema_person.into() {
+///         count += 1;
+///     }
+/// }
+/// assert_eq!(2, count);
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[derive(Default, Clone)]
+#[must_use]
+pub struct MicrodataParser {
+    unchecked: bool,
+    base: Option<Iri<String>>,
+}
+
+impl MicrodataParser {
+    /// Builds a new [`MicrodataParser`].
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Assumes the file is valid to make parsing faster.
+    ///
+    /// It will skip some validations.
+    ///
+    /// Note that if the file is actually not valid, broken RDF might be emitted by the parser.
+    #[inline]
+    pub fn unchecked(mut self) -> Self {
+        self.unchecked = true;
+        self
+    }
+
+    /// Sets the document IRI used to resolve relative IRIs.
+    ///
+    /// It might be overridden by a `<base href>` element.
+    #[inline]
+    pub fn with_base_iri(mut self, base_iri: impl Into<String>) -> Result<Self, IriParseError> {
+        self.base = Some(Iri::parse(base_iri.into())?);
+        Ok(self)
+    }
+
+    /// Parses a HTML file from a [`Read`] implementation.
+    ///
+    /// Count the number of people:
+    /// ```
+    /// use oxrdf::vocab::rdf;
+    /// use oxrdf::NamedNodeRef;
+    /// use oxmicrodata::MicrodataParser;
+    ///
+    /// let file = br#"<html>
+    /// <p itemscope itemtype="http://schema.org/Person">
+    ///   <span itemprop="name">Foo</span><br>
+    /// </p>
+    /// </html>"#;
+    ///
+    /// let schema_person = NamedNodeRef::new("http://schema.org/Person")?;
+    /// let mut count = 0;
+    /// for triple in MicrodataParser::new().for_reader(file.as_ref()) {
+    ///     let triple = triple?;
+    ///     if triple.predicate == rdf::TYPE && triple.object == schema_person.into() {
+    ///         count += 1;
+    ///     }
+    /// }
+    /// assert_eq!(1, count);
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn for_reader<R: Read>(self, reader: R) -> ReaderMicrodataParser<R> {
+        ReaderMicrodataParser {
+            results: Vec::new(),
+            parser: self.parse(BufReader::new(reader)),
+            reader_buffer: Vec::default(),
+        }
+    }
+
+    /// Parses a HTML file from a [`AsyncRead`] implementation.
+    ///
+    /// Count the number of people:
+    /// ```
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use oxrdf::vocab::rdf;
+    /// use oxrdf::NamedNodeRef;
+    /// use oxmicrodata::MicrodataParser;
+    ///
+    /// let file = br#"<html>
+    /// <p itemscope itemtype="http://schema.org/Person">
+    ///   <span itemprop="name">Foo</span><br>
+    /// </p>
+    /// </html>"#;
+    ///
+    /// let schema_person = NamedNodeRef::new("http://schema.org/Person")?;
+    /// let mut count = 0;
+    /// let mut parser = MicrodataParser::new().for_tokio_async_reader(file.as_ref());
+    /// while let Some(triple) = parser.next().await {
+    ///     let triple = triple?;
+    ///     if triple.predicate == rdf::TYPE && triple.object == schema_person.into() {
+    ///         count += 1;
+    ///     }
+    /// }
+    /// assert_eq!(1, count);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "async-tokio")]
+    pub fn for_tokio_async_reader<R: AsyncRead + Unpin>(
+        self,
+        reader: R,
+    ) -> TokioAsyncReaderMicrodataParser<R> {
+        TokioAsyncReaderMicrodataParser {
+            results: Vec::new(),
+            parser: self.parse(AsyncBufReader::new(reader)),
+            reader_buffer: Vec::default(),
+        }
+    }
+
+    /// Parses a HTML file from a byte slice.
+    ///
+    /// Count the number of people:
+    /// ```
+    /// use oxrdf::vocab::rdf;
+    /// use oxrdf::NamedNodeRef;
+    /// use oxmicrodata::MicrodataParser;
+    ///
+    /// let file = br#"<html>
+    /// <p itemscope itemtype="http://schema.org/Person">
+    ///   <span itemprop="name">Foo</span><br>
+    /// </p>
+    /// </html>"#;
+    ///
+    /// let schema_person = NamedNodeRef::new("http://schema.org/Person")?;
+    /// let mut count = 0;
+    /// for triple in MicrodataParser::new().for_slice(file) {
+    ///     let triple = triple?;
+    ///     if triple.predicate == rdf::TYPE && triple.object == schema_person.into() {
+    ///         count += 1;
+    ///     }
+    /// }
+    /// assert_eq!(1, count);
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn for_slice(self, slice: &[u8]) -> SliceMicrodataParser<'_> {
+        SliceMicrodataParser {
+            results: Vec::new(),
+            parser: self.parse(slice),
+            reader_buffer: Vec::default(),
+        }
+    }
+
+    fn parse<T>(&self, reader: T) -> InternalMicrodataParser<T> {
+        let mut reader = Reader::from_reader(reader);
+        let config = reader.config_mut();
+        config.check_end_names = false;
+        config.allow_unmatched_ends = true;
+        InternalMicrodataParser {
+            reader,
+            base_iri: self.base.clone(),
+            has_base_element: false,
+            elements: Vec::new(),
+            stack: Vec::new(),
+            ids: HashMap::new(),
+            raw_text_element: None,
+            errors: Vec::new(),
+            is_end: false,
+            unchecked: self.unchecked,
+        }
+    }
+}
+
+/// Parses a HTML file from a [`Read`] implementation.
+///
+/// Can be built using [`MicrodataParser::for_reader`].
+///
+/// Count the number of people:
+/// ```
+/// use oxrdf::vocab::rdf;
+/// use oxrdf::NamedNodeRef;
+/// use oxmicrodata::MicrodataParser;
+///
+/// let file = br#"<html>
+/// <p itemscope itemtype="http://schema.org/Person">
+///   <span itemprop="name">Foo</span><br>
+/// </p>
+/// </html>"#;
+///
+/// let schema_person = NamedNodeRef::new("http://schema.org/Person")?;
+/// let mut count = 0;
+/// for triple in MicrodataParser::new().for_reader(file.as_ref()) {
+///     let triple = triple?;
+///     if triple.predicate == rdf::TYPE && triple.object == schema_person.into() {
+///         count += 1;
+///     }
+/// }
+/// assert_eq!(1, count);
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[must_use]
+pub struct ReaderMicrodataParser<R: Read> {
+    results: Vec<Triple>,
+    parser: InternalMicrodataParser<BufReader<R>>,
+    reader_buffer: Vec<u8>,
+}
+
+impl<R: Read> Iterator for ReaderMicrodataParser<R> {
+    type Item = Result<Triple, MicrodataParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(error) = self.parser.errors.pop() {
+                return Some(Err(error.into()));
+            } else if let Some(triple) = self.results.pop() {
+                return Some(Ok(triple));
+            } else if self.parser.is_end {
+                return None;
+            }
+            if let Err(e) = self.parse_step() {
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+impl<R: Read> ReaderMicrodataParser<R> {
+    /// The base IRI, if set by [`MicrodataParser::with_base_iri`] or a `<base href>` element.
+    pub fn base_iri(&self) -> Option<&str> {
+        Some(self.parser.base_iri.as_ref()?.as_str())
+    }
+
+    pub fn buffer_position(&self) -> u64 {
+        self.parser.reader.buffer_position()
+    }
+
+    fn parse_step(&mut self) -> Result<(), MicrodataParseError> {
+        self.reader_buffer.clear();
+        let event = self
+            .parser
+            .reader
+            .read_event_into(&mut self.reader_buffer)?;
+        self.parser.parse_event(event, &mut self.results);
+        // We reverse the results to pop them in document order
+        self.results.reverse();
+        Ok(())
+    }
+}
+
+/// Parses a HTML file from a [`AsyncRead`] implementation.
+///
+/// Can be built using [`MicrodataParser::for_tokio_async_reader`].
+///
+/// Count the number of people:
+/// ```
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use oxrdf::vocab::rdf;
+/// use oxrdf::NamedNodeRef;
+/// use oxmicrodata::MicrodataParser;
+///
+/// let file = br#"<html>
+/// <p itemscope itemtype="http://schema.org/Person">
+///   <span itemprop="name">Foo</span><br>
+/// </p>
+/// </html>"#;
+///
+/// let schema_person = NamedNodeRef::new("http://schema.org/Person")?;
+/// let mut count = 0;
+/// let mut parser = MicrodataParser::new().for_tokio_async_reader(file.as_ref());
+/// while let Some(triple) = parser.next().await {
+///     let triple = triple?;
+///     if triple.predicate == rdf::TYPE && triple.object == schema_person.into() {
+///         count += 1;
+///     }
+/// }
+/// assert_eq!(1, count);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "async-tokio")]
+#[must_use]
+pub struct TokioAsyncReaderMicrodataParser<R: AsyncRead + Unpin> {
+    results: Vec<Triple>,
+    parser: InternalMicrodataParser<AsyncBufReader<R>>,
+    reader_buffer: Vec<u8>,
+}
+
+#[cfg(feature = "async-tokio")]
+impl<R: AsyncRead + Unpin> TokioAsyncReaderMicrodataParser<R> {
+    pub async fn next(&mut self) -> Option<Result<Triple, MicrodataParseError>> {
+        loop {
+            if let Some(error) = self.parser.errors.pop() {
+                return Some(Err(error.into()));
+            } else if let Some(triple) = self.results.pop() {
+                return Some(Ok(triple));
+            } else if self.parser.is_end {
+                return None;
+            }
+            if let Err(e) = self.parse_step().await {
+                return Some(Err(e));
+            }
+        }
+    }
+
+    /// The base IRI, if set by [`MicrodataParser::with_base_iri`] or a `<base href>` element.
+    pub fn base_iri(&self) -> Option<&str> {
+        Some(self.parser.base_iri.as_ref()?.as_str())
+    }
+
+    pub fn buffer_position(&self) -> u64 {
+        self.parser.reader.buffer_position()
+    }
+
+    async fn parse_step(&mut self) -> Result<(), MicrodataParseError> {
+        self.reader_buffer.clear();
+        let event = self
+            .parser
+            .reader
+            .read_event_into_async(&mut self.reader_buffer)
+            .await?;
+        self.parser.parse_event(event, &mut self.results);
+        // We reverse the results to pop them in document order
+        self.results.reverse();
+        Ok(())
+    }
+}
+
+/// Parses a HTML file from a byte slice.
+///
+/// Can be built using [`MicrodataParser::for_slice`].
+///
+/// Count the number of people:
+/// ```
+/// use oxrdf::vocab::rdf;
+/// use oxrdf::NamedNodeRef;
+/// use oxmicrodata::MicrodataParser;
+///
+/// let file = br#"<html>
+/// <p itemscope itemtype="http://schema.org/Person">
+///   <span itemprop="name">Foo</span><br>
+/// </p>
+/// </html>"#;
+///
+/// let schema_person = NamedNodeRef::new("http://schema.org/Person")?;
+/// let mut count = 0;
+/// for triple in MicrodataParser::new().for_slice(file) {
+///     let triple = triple?;
+///     if triple.predicate == rdf::TYPE && triple.object == schema_person.into() {
+///         count += 1;
+///     }
+/// }
+/// assert_eq!(1, count);
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[must_use]
+pub struct SliceMicrodataParser<'a> {
+    results: Vec<Triple>,
+    parser: InternalMicrodataParser<&'a [u8]>,
+    reader_buffer: Vec<u8>,
+}
+
+impl Iterator for SliceMicrodataParser<'_> {
+    type Item = Result<Triple, MicrodataSyntaxError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(error) = self.parser.errors.pop() {
+                return Some(Err(error));
+            } else if let Some(triple) = self.results.pop() {
+                return Some(Ok(triple));
+            } else if self.parser.is_end {
+                return None;
+            }
+            if let Err(MicrodataParseError::Syntax(e)) = self.parse_step() {
+                // I/O errors can't happen
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+impl SliceMicrodataParser<'_> {
+    /// The base IRI, if set by [`MicrodataParser::with_base_iri`] or a `<base href>` element.
+    pub fn base_iri(&self) -> Option<&str> {
+        Some(self.parser.base_iri.as_ref()?.as_str())
+    }
+
+    pub fn buffer_position(&self) -> u64 {
+        self.parser.reader.buffer_position()
+    }
+
+    fn parse_step(&mut self) -> Result<(), MicrodataParseError> {
+        self.reader_buffer.clear();
+        let event = self
+            .parser
+            .reader
+            .read_event_into(&mut self.reader_buffer)?;
+        self.parser.parse_event(event, &mut self.results);
+        // We reverse the results to pop them in document order
+        self.results.reverse();
+        Ok(())
+    }
+}
+
+/// The HTML elements without content and end tag
+const VOID_ELEMENTS: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// The HTML elements whose content is not HTML
+const RAW_TEXT_ELEMENTS: [&str; 2] = ["script", "style"];
+
+struct Element {
+    /// The lowercase element name
+    name: String,
+    attributes: Attributes,
+    language: Option<String>,
+    /// The text content of the element, if it is a property that is not an item
+    text: Option<String>,
+    /// The indexes of the child elements in [`InternalMicrodataParser::elements`]
+    children: Vec<usize>,
+}
+
+/// The attributes of an element used by Microdata
+#[derive(Default)]
+struct Attributes {
+    content: Option<String>,
+    data: Option<String>,
+    datetime: Option<String>,
+    href: Option<String>,
+    id: Option<String>,
+    itemid: Option<String>,
+    itemprop: Option<String>,
+    itemref: Option<String>,
+    itemscope: bool,
+    itemtype: Option<String>,
+    lang: Option<String>,
+    src: Option<String>,
+    value: Option<String>,
+    xml_lang: Option<String>,
+}
+
+struct InternalMicrodataParser<T> {
+    reader: Reader<T>,
+    base_iri: Option<Iri<String>>,
+    /// If a `<base href>` element has already been read
+    has_base_element: bool,
+    /// All the document elements in document order
+    elements: Vec<Element>,
+    /// The indexes of the elements being read, the current one being the last
+    stack: Vec<usize>,
+    /// The first element with a given `@id`
+    ids: HashMap<String, usize>,
+    /// The name of the current element if its content is not HTML
+    raw_text_element: Option<String>,
+    errors: Vec<MicrodataSyntaxError>,
+    is_end: bool,
+    unchecked: bool,
+}
+
+impl<T> InternalMicrodataParser<T> {
+    fn parse_event(&mut self, event: Event<'_>, results: &mut Vec<Triple>) {
+        if let Err(e) = self.parse_event_inner(event, results) {
+            self.errors.push(e);
+        }
+    }
+
+    fn parse_event_inner(
+        &mut self,
+        event: Event<'_>,
+        results: &mut Vec<Triple>,
+    ) -> Result<(), MicrodataSyntaxError> {
+        match event {
+            Event::Start(event) => {
+                let name = self.element_name(&event)?;
+                if self.raw_text_element.is_some() {
+                    return Ok(());
+                }
+                if VOID_ELEMENTS.contains(&name.as_str()) {
+                    self.parse_start(name, &event, true)
+                } else {
+                    if RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+                        self.raw_text_element = Some(name.clone());
+                    }
+                    self.parse_start(name, &event, false)
+                }
+            }
+            Event::Empty(event) => {
+                let name = self.element_name(&event)?;
+                if self.raw_text_element.is_some() {
+                    return Ok(());
+                }
+                self.parse_start(name, &event, true)
+            }
+            Event::End(event) => {
+                let name = self
+                    .reader
+                    .decoder()
+                    .decode(event.local_name().as_ref())
+                    .map_err(MicrodataSyntaxError::xml)?
+                    .to_ascii_lowercase();
+                if let Some(raw_text_element) = &self.raw_text_element {
+                    if *raw_text_element != name {
+                        return Ok(());
+                    }
+                    self.raw_text_element = None;
+                }
+                // We close all the elements up to the matching start tag, if it exists
+                if let Some(position) = self
+                    .stack
+                    .iter()
+                    .rposition(|e| self.elements[*e].name == name)
+                {
+                    self.stack.truncate(position);
+                }
+                Ok(())
+            }
+            Event::Text(event) => {
+                if self.is_collecting_text() {
+                    let text = self.text(&event)?;
+                    self.push_text(&text);
+                }
+                Ok(())
+            }
+            Event::CData(event) => {
+                if self.is_collecting_text() {
+                    let text = self
+                        .reader
+                        .decoder()
+                        .decode(&event)
+                        .map_err(MicrodataSyntaxError::xml)?
+                        .into_owned();
+                    self.push_text(&text);
+                }
+                Ok(())
+            }
+            Event::Eof => {
+                self.stack.clear();
+                self.convert_items(results);
+                self.is_end = true;
+                Ok(())
+            }
+            Event::Comment(_) | Event::PI(_) | Event::Decl(_) | Event::DocType(_) => Ok(()),
+        }
+    }
+
+    fn element_name(&self, event: &BytesStart<'_>) -> Result<String, MicrodataSyntaxError> {
+        Ok(self
+            .reader
+            .decoder()
+            .decode(event.local_name().as_ref())
+            .map_err(MicrodataSyntaxError::xml)?
+            .to_ascii_lowercase())
+    }
+
+    fn text(&self, event: &BytesText<'_>) -> Result<String, MicrodataSyntaxError> {
+        let text = self
+            .reader
+            .decoder()
+            .decode(event)
+            .map_err(MicrodataSyntaxError::xml)?;
+        // HTML allows unescaped &
+        Ok(unescape_with(&text, resolve_html5_entity)
+            .unwrap_or(Cow::Borrowed(&text))
+            .into_owned())
+    }
+
+    fn attribute_value(&self, attribute: &Attribute<'_>) -> Result<String, MicrodataSyntaxError> {
+        let value = self
+            .reader
+            .decoder()
+            .decode(&attribute.value)
+            .map_err(MicrodataSyntaxError::xml)?;
+        // HTML allows unescaped &
+        Ok(unescape_with(&value, resolve_html5_entity)
+            .unwrap_or(Cow::Borrowed(&value))
+            .into_owned())
+    }
+
+    fn is_collecting_text(&self) -> bool {
+        self.stack
+            .iter()
+            .any(|element| self.elements[*element].text.is_some())
+    }
+
+    fn push_text(&mut self, text: &str) {
+        for element in &self.stack {
+            if let Some(content) = &mut self.elements[*element].text {
+                content.push_str(text);
+            }
+        }
+    }
+
+    fn parse_attributes(&self, event: &BytesStart<'_>) -> Result<Attributes, MicrodataSyntaxError> {
+        let mut attributes = Attributes::default();
+        for attribute in event.html_attributes() {
+            let attribute = attribute.map_err(MicrodataSyntaxError::xml)?;
+            let key = self
+                .reader
+                .decoder()
+                .decode(attribute.key.as_ref())
+                .map_err(MicrodataSyntaxError::xml)?
+                .to_ascii_lowercase();
+            let value = self.attribute_value(&attribute)?;
+            match key.as_str() {
+                "content" => attributes.content = Some(value),
+                "data" => attributes.data = Some(value),
+                "datetime" => attributes.datetime = Some(value),
+                "href" => attributes.href = Some(value),
+                "id" => attributes.id = Some(value),
+                "itemid" => attributes.itemid = Some(value),
+                "itemprop" => attributes.itemprop = Some(value),
+                "itemref" => attributes.itemref = Some(value),
+                "itemscope" => attributes.itemscope = true,
+                "itemtype" => attributes.itemtype = Some(value),
+                "lang" => attributes.lang = Some(value),
+                "src" => attributes.src = Some(value),
+                "value" => attributes.value = Some(value),
+                "xml:lang" => attributes.xml_lang = Some(value),
+                _ => (),
+            }
+        }
+        Ok(attributes)
+    }
+
+    fn parse_start(
+        &mut self,
+        name: String,
+        event: &BytesStart<'_>,
+        is_empty: bool,
+    ) -> Result<(), MicrodataSyntaxError> {
+        let attributes = self.parse_attributes(event)?;
+        let parent = self.stack.last().copied();
+        let language = if let Some(lang) = attributes.xml_lang.as_ref().or(attributes.lang.as_ref())
+        {
+            if lang.is_empty() || LanguageTag::parse(lang.as_str()).is_err() {
+                None
+            } else {
+                Some(lang.to_ascii_lowercase())
+            }
+        } else {
+            parent.and_then(|parent| self.elements[parent].language.clone())
+        };
+        // Only the first <base> element is used
+        if name == "base" && !self.has_base_element {
+            if let Some(href) = &attributes.href {
+                self.has_base_element = true;
+                let base_iri = if let Some(base) = &self.base_iri {
+                    base.resolve(href.trim()).ok()
+                } else {
+                    Iri::parse(href.trim().to_owned()).ok()
+                };
+                if base_iri.is_some() {
+                    self.base_iri = base_iri;
+                }
+            }
+        }
+
+        let index = self.elements.len();
+        if let Some(id) = &attributes.id {
+            self.ids.entry(id.clone()).or_insert(index);
+        }
+        if let Some(parent) = parent {
+            self.elements[parent].children.push(index);
+        }
+        let text = (attributes.itemprop.is_some() && !attributes.itemscope).then(String::new);
+        self.elements.push(Element {
+            name,
+            attributes,
+            language,
+            text,
+            children: Vec::new(),
+        });
+        if !is_empty {
+            self.stack.push(index);
+        }
+        Ok(())
+    }
+
+    /// Converts the top-level items following the [Microdata to RDF](https://www.w3.org/TR/microdata-rdf/#generate-the-triples) algorithm
+    fn convert_items(&mut self, results: &mut Vec<Triple>) {
+        // The subject of the items already seen
+        let mut subjects = HashMap::new();
+        // The items whose triples are still to generate with their vocabulary
+        let mut pending = VecDeque::new();
+        for item in 0..self.elements.len() {
+            let attributes = &self.elements[item].attributes;
+            if attributes.itemscope && attributes.itemprop.is_none() {
+                subjects.insert(item, self.item_subject(item));
+                pending.push_back((item, None));
+                while let Some((item, vocabulary)) = pending.pop_front() {
+                    self.convert_item(item, vocabulary, &mut subjects, &mut pending, results);
+                }
+            }
+        }
+    }
+
+    fn convert_item(
+        &mut self,
+        item: usize,
+        vocabulary: Option<String>,
+        subjects: &mut HashMap<usize, NamedOrBlankNode>,
+        pending: &mut VecDeque<(usize, Option<String>)>,
+        results: &mut Vec<Triple>,
+    ) {
+        let Some(subject) = subjects.get(&item).cloned() else {
+            return;
+        };
+        let mut types = Vec::new();
+        if let Some(itemtype) = self.elements[item].attributes.itemtype.clone() {
+            for r#type in itemtype.split_ascii_whitespace() {
+                if let Some(r#type) = self.parse_iri(r#type.into()) {
+                    results.push(Triple::new(subject.clone(), rdf::TYPE, r#type.clone()));
+                    types.push(r#type);
+                }
+            }
+        }
+        // The vocabulary is given by the first type or inherited from the parent item
+        let vocabulary = types.first().map(type_vocabulary).or(vocabulary);
+
+        for property in self.item_properties(item) {
+            let Some(names) = self.elements[property].attributes.itemprop.clone() else {
+                continue;
+            };
+            let mut value = None;
+            for name in names.split_ascii_whitespace() {
+                let predicate = if name.contains(':') {
+                    self.parse_iri(name.into())
+                } else if let Some(vocabulary) = &vocabulary {
+                    self.parse_iri(format!("{vocabulary}{name}"))
+                } else {
+                    None // We don't know how to build the property IRI
+                };
+                let Some(predicate) = predicate else {
+                    continue;
+                };
+                let value = value.get_or_insert_with(|| {
+                    self.property_value(property, vocabulary.as_deref(), subjects, pending)
+                });
+                results.push(Triple::new(subject.clone(), predicate, value.clone()));
+            }
+        }
+    }
+
+    /// The [properties of an item](https://html.spec.whatwg.org/multipage/microdata.html#the-properties-of-an-item) in document order
+    fn item_properties(&self, root: usize) -> Vec<usize> {
+        let mut results = Vec::new();
+        let mut visited = HashSet::from([root]);
+        let mut pending = self.elements[root].children.clone();
+        if let Some(itemref) = &self.elements[root].attributes.itemref {
+            pending.extend(
+                itemref
+                    .split_ascii_whitespace()
+                    .filter_map(|id| self.ids.get(id).copied()),
+            );
+        }
+        while let Some(current) = pending.pop() {
+            if !visited.insert(current) {
+                continue; // The element has already been crawled
+            }
+            let element = &self.elements[current];
+            if !element.attributes.itemscope {
+                pending.extend_from_slice(&element.children);
+            }
+            if element.attributes.itemprop.is_some() {
+                results.push(current);
+            }
+        }
+        // The elements are stored in document order
+        results.sort_unstable();
+        results
+    }
+
+    /// The [property value](https://www.w3.org/TR/microdata-rdf/#dfn-generate-property-values) of an element
+    fn property_value(
+        &self,
+        property: usize,
+        vocabulary: Option<&str>,
+        subjects: &mut HashMap<usize, NamedOrBlankNode>,
+        pending: &mut VecDeque<(usize, Option<String>)>,
+    ) -> Term {
+        let element = &self.elements[property];
+        let attributes = &element.attributes;
+        if attributes.itemscope {
+            if let Some(subject) = subjects.get(&property) {
+                return subject.clone().into();
+            }
+            let subject = self.item_subject(property);
+            subjects.insert(property, subject.clone());
+            pending.push_back((property, vocabulary.map(ToOwned::to_owned)));
+            return subject.into();
+        }
+        let language = element.language.clone();
+        match element.name.as_str() {
+            "meta" => plain_literal(attributes.content.clone().unwrap_or_default(), language),
+            "audio" | "embed" | "iframe" | "img" | "source" | "track" | "video" => {
+                self.url_value(attributes.src.as_deref())
+            }
+            "a" | "area" | "link" => self.url_value(attributes.href.as_deref()),
+            "object" => self.url_value(attributes.data.as_deref()),
+            "data" => plain_literal(attributes.value.clone().unwrap_or_default(), language),
+            "meter" => meter_literal(attributes.value.clone().unwrap_or_default()).into(),
+            "time" => time_literal(
+                attributes
+                    .datetime
+                    .clone()
+                    .or_else(|| element.text.clone())
+                    .unwrap_or_default(),
+                language,
+            )
+            .into(),
+            _ => plain_literal(element.text.clone().unwrap_or_default(), language),
+        }
+    }
+
+    fn item_subject(&self, item: usize) -> NamedOrBlankNode {
+        self.elements[item]
+            .attributes
+            .itemid
+            .as_deref()
+            .and_then(|itemid| self.resolve_html_iri(itemid))
+            .map_or_else(|| BlankNode::default().into(), Into::into)
+    }
+
+    /// The value of the URL properties, the empty string if the URL is not valid
+    fn url_value(&self, url: Option<&str>) -> Term {
+        url.and_then(|url| self.resolve_html_iri(url))
+            .map_or_else(|| Literal::new_simple_literal("").into(), Into::into)
+    }
+
+    /// Resolves the IRIs of the HTML attributes like `@href` that are ignored if invalid
+    fn resolve_html_iri(&self, iri: &str) -> Option<NamedNode> {
+        let iri = if let Some(base) = &self.base_iri {
+            base.resolve(iri.trim()).ok()?
+        } else {
+            Iri::parse(iri.trim().to_owned()).ok()?
+        };
+        Some(NamedNode::new_unchecked(iri.into_inner()))
+    }
+
+    /// Parses the absolute IRIs of `@itemtype` and `@itemprop`
+    fn parse_iri(&mut self, iri: String) -> Option<NamedNode> {
+        if self.unchecked {
+            return Some(NamedNode::new_unchecked(iri));
+        }
+        if let Err(error) = Iri::parse(iri.as_str()) {
+            self.errors
+                .push(MicrodataSyntaxError::invalid_iri(iri, error));
+            return None;
+        }
+        Some(NamedNode::new_unchecked(iri))
+    }
+}
+
+/// The vocabulary of an item type: the type IRI up to its last `#` or `/`
+fn type_vocabulary(r#type: &NamedNode) -> String {
+    let iri = r#type.as_str();
+    if let Some(position) = iri.rfind('#').or_else(|| iri.rfind('/')) {
+        iri[..=position].into()
+    } else {
+        format!("{iri}#")
+    }
+}
+
+fn plain_literal(value: String, language: Option<String>) -> Term {
+    if let Some(language) = language {
+        Literal::new_language_tagged_literal_unchecked(value, language).into()
+    } else {
+        Literal::new_simple_literal(value).into()
+    }
+}
+
+/// A literal for a `<time>` value, with the datatype guessed from its lexical form
+pub(crate) fn time_literal(value: String, language: Option<String>) -> Literal {
+    let datatype = if Duration::from_str(&value).is_ok() {
+        xsd::DURATION
+    } else if DateTime::from_str(&value).is_ok() {
+        xsd::DATE_TIME
+    } else if Date::from_str(&value).is_ok() {
+        xsd::DATE
+    } else if Time::from_str(&value).is_ok() {
+        xsd::TIME
+    } else if GYearMonth::from_str(&value).is_ok() {
+        xsd::G_YEAR_MONTH
+    } else if GYear::from_str(&value).is_ok() {
+        xsd::G_YEAR
+    } else if let Some(language) = language {
+        return Literal::new_language_tagged_literal_unchecked(value, language);
+    } else {
+        return Literal::new_simple_literal(value);
+    };
+    Literal::new_typed_literal(value, datatype)
+}
+
+/// A literal for a `<meter>` value, a `xsd:integer` or a `xsd:double` depending on its lexical form
+pub(crate) fn meter_literal(value: String) -> Literal {
+    if Integer::from_str(&value).is_ok() {
+        Literal::new_typed_literal(value, xsd::INTEGER)
+    } else if Double::from_str(&value).is_ok() {
+        Literal::new_typed_literal(value, xsd::DOUBLE)
+    } else {
+        Literal::new_simple_literal(value)
+    }
+}
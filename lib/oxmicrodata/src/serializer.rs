@@ -0,0 +1,480 @@
+use crate::parser::{meter_literal, time_literal};
+use oxiri::{Iri, IriParseError};
+use oxrdf::vocab::rdf;
+use oxrdf::{BlankNode, NamedNode, NamedNodeRef, SubjectRef, Term, TermRef, TripleRef};
+use quick_xml::escape::escape;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::io::Write;
+#[cfg(feature = "async-tokio")]
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// A [HTML Microdata](https://html.spec.whatwg.org/multipage/microdata.html) serializer.
+///
+/// It writes a HTML document with an item per subject containing an element per triple.
+/// IRIs are written with `<link>` elements and literals with `<meta>` elements,
+/// except the date, time, integer and double literals that are written with `<time>` and `<meter>` elements.
+/// The datatype of the other literals is lost.
+///
+/// The blank nodes objects are written as nested items referring with `@itemref` to the element describing them.
+/// For this reason, the triples whose subject is a blank node are kept in memory until the end of the serialization.
+/// Blank nodes that are the object of multiple triples are duplicated and cycles of blank nodes are lost.
+///
+/// ```
+/// use oxrdf::{LiteralRef, NamedNodeRef, TripleRef};
+/// use oxrdf::vocab::rdf;
+/// use oxmicrodata::MicrodataSerializer;
+///
+/// let mut serializer = MicrodataSerializer::new().for_writer(Vec::new());
+/// serializer.serialize_triple(TripleRef::new(
+///     NamedNodeRef::new("http://example.com#me")?,
+///     rdf::TYPE,
+///     NamedNodeRef::new("http://schema.org/Person")?,
+/// ))?;
+/// serializer.serialize_triple(TripleRef::new(
+///     NamedNodeRef::new("http://example.com#me")?,
+///     NamedNodeRef::new("http://schema.org/name")?,
+///     LiteralRef::new_language_tagged_literal_unchecked("Foo Bar", "en"),
+/// ))?;
+/// assert_eq!(
+///     "<!DOCTYPE html>\n<html>\n\t<head>\n\t\t<meta charset=\"utf-8\">\n\t</head>\n\t<body>\n\t\t<div itemscope itemid=\"http://example.com#me\" itemtype=\"http://schema.org/Person\">\n\t\t\t<meta itemprop=\"http://schema.org/name\" content=\"Foo Bar\" lang=\"en\">\n\t\t</div>\n\t</body>\n</html>\n",
+///     String::from_utf8(serializer.finish()?)?
+/// );
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[derive(Default, Clone)]
+#[must_use]
+pub struct MicrodataSerializer {
+    base_iri: Option<Iri<String>>,
+}
+
+impl MicrodataSerializer {
+    /// Builds a new [`MicrodataSerializer`].
+    #[inline]
+    pub fn new() -> Self {
+        Self { base_iri: None }
+    }
+
+    /// Sets the document IRI using a `<base>` element, the item ids and the objects IRIs are written relative to it.
+    ///
+    /// ```
+    /// use oxrdf::{NamedNodeRef, TripleRef};
+    /// use oxmicrodata::MicrodataSerializer;
+    ///
+    /// let mut serializer = MicrodataSerializer::new()
+    ///     .with_base_iri("http://example.com")?
+    ///     .for_writer(Vec::new());
+    /// serializer.serialize_triple(TripleRef::new(
+    ///     NamedNodeRef::new("http://example.com#me")?,
+    ///     NamedNodeRef::new("http://example.com/ns#parent")?,
+    ///     NamedNodeRef::new("http://example.com#other")?,
+    /// ))?;
+    /// assert_eq!(
+    ///     "<!DOCTYPE html>\n<html>\n\t<head>\n\t\t<meta charset=\"utf-8\">\n\t\t<base href=\"http://example.com\">\n\t</head>\n\t<body>\n\t\t<div itemscope itemid=\"#me\">\n\t\t\t<link itemprop=\"http://example.com/ns#parent\" href=\"#other\">\n\t\t</div>\n\t</body>\n</html>\n",
+    ///     String::from_utf8(serializer.finish()?)?
+    /// );
+    /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    pub fn with_base_iri(mut self, base_iri: impl Into<String>) -> Result<Self, IriParseError> {
+        self.base_iri = Some(Iri::parse(base_iri.into())?);
+        Ok(self)
+    }
+
+    /// Serializes a HTML Microdata file to a [`Write`] implementation.
+    ///
+    /// This writer does unbuffered writes.
+    ///
+    /// ```
+    /// use oxrdf::{LiteralRef, NamedNodeRef, TripleRef};
+    /// use oxmicrodata::{MicrodataParser, MicrodataSerializer};
+    ///
+    /// let triple = TripleRef::new(
+    ///     NamedNodeRef::new("http://example.com#me")?,
+    ///     NamedNodeRef::new("http://schema.org/name")?,
+    ///     LiteralRef::new_simple_literal("Foo & Bar"),
+    /// );
+    /// let mut serializer = MicrodataSerializer::new().for_writer(Vec::new());
+    /// serializer.serialize_triple(triple)?;
+    /// let file = serializer.finish()?;
+    ///
+    /// let triples = MicrodataParser::new()
+    ///     .for_slice(&file)
+    ///     .collect::<Result<Vec<_>, _>>()?;
+    /// assert_eq!(triples, [triple.into_owned()]);
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn for_writer<W: Write>(self, writer: W) -> WriterMicrodataSerializer<W> {
+        WriterMicrodataSerializer {
+            writer,
+            inner: self.inner_writer(),
+        }
+    }
+
+    /// Serializes a HTML Microdata file to a [`AsyncWrite`] implementation.
+    ///
+    /// This writer does unbuffered writes.
+    ///
+    /// ```
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use oxrdf::{LiteralRef, NamedNodeRef, TripleRef};
+    /// use oxmicrodata::{MicrodataParser, MicrodataSerializer};
+    ///
+    /// let triple = TripleRef::new(
+    ///     NamedNodeRef::new("http://example.com#me")?,
+    ///     NamedNodeRef::new("http://schema.org/name")?,
+    ///     LiteralRef::new_simple_literal("Foo & Bar"),
+    /// );
+    /// let mut serializer = MicrodataSerializer::new().for_tokio_async_writer(Vec::new());
+    /// serializer.serialize_triple(triple).await?;
+    /// let file = serializer.finish().await?;
+    ///
+    /// let triples = MicrodataParser::new()
+    ///     .for_slice(&file)
+    ///     .collect::<Result<Vec<_>, _>>()?;
+    /// assert_eq!(triples, [triple.into_owned()]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "async-tokio")]
+    pub fn for_tokio_async_writer<W: AsyncWrite + Unpin>(
+        self,
+        writer: W,
+    ) -> TokioAsyncWriterMicrodataSerializer<W> {
+        TokioAsyncWriterMicrodataSerializer {
+            writer,
+            inner: self.inner_writer(),
+        }
+    }
+
+    fn inner_writer(self) -> InnerMicrodataWriter {
+        InnerMicrodataWriter {
+            current_item: None,
+            blank_nodes: Vec::new(),
+            blank_node_positions: HashMap::new(),
+            referenced_blank_nodes: HashSet::new(),
+            is_started: false,
+            base_iri: self.base_iri,
+        }
+    }
+}
+
+/// Serializes a HTML Microdata file to a [`Write`] implementation.
+///
+/// Can be built using [`MicrodataSerializer::for_writer`].
+///
+/// ```
+/// use oxrdf::{LiteralRef, NamedNodeRef, TripleRef};
+/// use oxmicrodata::{MicrodataParser, MicrodataSerializer};
+///
+/// let triple = TripleRef::new(
+///     NamedNodeRef::new("http://example.com#me")?,
+///     NamedNodeRef::new("http://schema.org/name")?,
+///     LiteralRef::new_simple_literal("Foo & Bar"),
+/// );
+/// let mut serializer = MicrodataSerializer::new().for_writer(Vec::new());
+/// serializer.serialize_triple(triple)?;
+/// let file = serializer.finish()?;
+///
+/// let triples = MicrodataParser::new()
+///     .for_slice(&file)
+///     .collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(triples, [triple.into_owned()]);
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[must_use]
+pub struct WriterMicrodataSerializer<W: Write> {
+    writer: W,
+    inner: InnerMicrodataWriter,
+}
+
+impl<W: Write> WriterMicrodataSerializer<W> {
+    /// Serializes an extra triple.
+    pub fn serialize_triple<'a>(&mut self, t: impl Into<TripleRef<'a>>) -> io::Result<()> {
+        let mut buffer = String::new();
+        self.inner.serialize_triple(t, &mut buffer)?;
+        self.writer.write_all(buffer.as_bytes())
+    }
+
+    /// Ends the write process and returns the underlying [`Write`].
+    pub fn finish(mut self) -> io::Result<W> {
+        let mut buffer = String::new();
+        self.inner.finish(&mut buffer);
+        self.writer.write_all(buffer.as_bytes())?;
+        Ok(self.writer)
+    }
+}
+
+/// Serializes a HTML Microdata file to a [`AsyncWrite`] implementation.
+///
+/// Can be built using [`MicrodataSerializer::for_tokio_async_writer`].
+///
+/// ```
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use oxrdf::{LiteralRef, NamedNodeRef, TripleRef};
+/// use oxmicrodata::{MicrodataParser, MicrodataSerializer};
+///
+/// let triple = TripleRef::new(
+///     NamedNodeRef::new("http://example.com#me")?,
+///     NamedNodeRef::new("http://schema.org/name")?,
+///     LiteralRef::new_simple_literal("Foo & Bar"),
+/// );
+/// let mut serializer = MicrodataSerializer::new().for_tokio_async_writer(Vec::new());
+/// serializer.serialize_triple(triple).await?;
+/// let file = serializer.finish().await?;
+///
+/// let triples = MicrodataParser::new()
+///     .for_slice(&file)
+///     .collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(triples, [triple.into_owned()]);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "async-tokio")]
+#[must_use]
+pub struct TokioAsyncWriterMicrodataSerializer<W: AsyncWrite + Unpin> {
+    writer: W,
+    inner: InnerMicrodataWriter,
+}
+
+#[cfg(feature = "async-tokio")]
+impl<W: AsyncWrite + Unpin> TokioAsyncWriterMicrodataSerializer<W> {
+    /// Serializes an extra triple.
+    pub async fn serialize_triple<'a>(&mut self, t: impl Into<TripleRef<'a>>) -> io::Result<()> {
+        let mut buffer = String::new();
+        self.inner.serialize_triple(t, &mut buffer)?;
+        self.writer.write_all(buffer.as_bytes()).await
+    }
+
+    /// Ends the write process and returns the underlying [`AsyncWrite`].
+    pub async fn finish(mut self) -> io::Result<W> {
+        let mut buffer = String::new();
+        self.inner.finish(&mut buffer);
+        self.writer.write_all(buffer.as_bytes()).await?;
+        Ok(self.writer)
+    }
+}
+
+/// The properties of a subject
+type Properties = Vec<(NamedNode, Term)>;
+
+struct InnerMicrodataWriter {
+    /// The subject being written with its properties, written when the subject changes
+    current_item: Option<(NamedNode, Properties)>,
+    /// The properties of the blank nodes, written at the end
+    blank_nodes: Vec<(BlankNode, Properties)>,
+    /// The position of the blank nodes in `blank_nodes`
+    blank_node_positions: HashMap<BlankNode, usize>,
+    /// The blank nodes that are the object of a triple
+    referenced_blank_nodes: HashSet<BlankNode>,
+    is_started: bool,
+    base_iri: Option<Iri<String>>,
+}
+
+impl InnerMicrodataWriter {
+    #[allow(clippy::match_wildcard_for_single_variants, unreachable_patterns)]
+    fn serialize_triple<'a>(
+        &mut self,
+        t: impl Into<TripleRef<'a>>,
+        output: &mut String,
+    ) -> io::Result<()> {
+        if !self.is_started {
+            self.write_start(output);
+        }
+        let triple = t.into();
+        let object = match triple.object {
+            TermRef::NamedNode(node) => Term::from(node.into_owned()),
+            TermRef::BlankNode(node) => {
+                self.referenced_blank_nodes.insert(node.into_owned());
+                node.into_owned().into()
+            }
+            TermRef::Literal(literal) => literal.into_owned().into(),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Microdata only supports named, blank or literal object",
+                ))
+            }
+        };
+        let property = (triple.predicate.into_owned(), object);
+        match triple.subject {
+            SubjectRef::NamedNode(subject) => {
+                // We write the previous item if the subject changes
+                if self
+                    .current_item
+                    .as_ref()
+                    .map(|(current, _)| current.as_ref())
+                    != Some(subject)
+                {
+                    if let Some((current, properties)) = self.current_item.take() {
+                        self.write_item(Some(current.as_ref()), &properties, output);
+                    }
+                    self.current_item = Some((subject.into_owned(), Vec::new()));
+                }
+                if let Some((_, properties)) = &mut self.current_item {
+                    properties.push(property);
+                }
+            }
+            SubjectRef::BlankNode(subject) => {
+                let position = *self
+                    .blank_node_positions
+                    .entry(subject.into_owned())
+                    .or_insert_with(|| {
+                        self.blank_nodes.push((subject.into_owned(), Vec::new()));
+                        self.blank_nodes.len() - 1
+                    });
+                self.blank_nodes[position].1.push(property);
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Microdata only supports named or blank subject",
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    fn write_start(&mut self, output: &mut String) {
+        output.push_str("<!DOCTYPE html>\n<html>\n\t<head>\n\t\t<meta charset=\"utf-8\">\n");
+        if let Some(base_iri) = &self.base_iri {
+            output.push_str("\t\t<base href=\"");
+            output.push_str(&escape(base_iri.as_str()));
+            output.push_str("\">\n");
+        }
+        output.push_str("\t</head>\n\t<body>\n");
+        self.is_started = true;
+    }
+
+    fn finish(&mut self, output: &mut String) {
+        if !self.is_started {
+            self.write_start(output);
+        }
+        if let Some((subject, properties)) = self.current_item.take() {
+            self.write_item(Some(subject.as_ref()), &properties, output);
+        }
+        for (node, properties) in &self.blank_nodes {
+            if self.referenced_blank_nodes.contains(node) {
+                // The nested items referring to the blank node copy the element properties
+                output.push_str("\t\t<div id=\"");
+                output.push_str(&escape(node.as_str()));
+                output.push_str("\">\n");
+                for (predicate, object) in properties {
+                    self.write_property(predicate, object, output);
+                }
+                output.push_str("\t\t</div>\n");
+            } else {
+                self.write_item(None, properties, output);
+            }
+        }
+        output.push_str("\t</body>\n</html>\n");
+    }
+
+    /// Writes a top-level item with its types in `@itemtype`
+    fn write_item(
+        &self,
+        subject: Option<NamedNodeRef<'_>>,
+        properties: &[(NamedNode, Term)],
+        output: &mut String,
+    ) {
+        output.push_str("\t\t<div itemscope");
+        if let Some(subject) = subject {
+            output.push_str(" itemid=\"");
+            output.push_str(&escape(&*self.relative_iri(subject)));
+            output.push('"');
+        }
+        let types = properties
+            .iter()
+            .filter_map(|(predicate, object)| match object {
+                Term::NamedNode(object) if *predicate == rdf::TYPE => Some(object.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if !types.is_empty() {
+            output.push_str(" itemtype=\"");
+            output.push_str(&escape(types.join(" ").as_str()));
+            output.push('"');
+        }
+        output.push_str(">\n");
+        for (predicate, object) in properties {
+            if *predicate == rdf::TYPE && object.is_named_node() {
+                continue;
+            }
+            self.write_property(predicate, object, output);
+        }
+        output.push_str("\t\t</div>\n");
+    }
+
+    #[allow(clippy::match_wildcard_for_single_variants, unreachable_patterns)]
+    fn write_property(&self, predicate: &NamedNode, object: &Term, output: &mut String) {
+        let itemprop = escape(predicate.as_str());
+        match object {
+            Term::NamedNode(node) => {
+                output.push_str("\t\t\t<link itemprop=\"");
+                output.push_str(&itemprop);
+                output.push_str("\" href=\"");
+                output.push_str(&escape(&*self.relative_iri(node.as_ref())));
+                output.push_str("\">\n");
+            }
+            Term::BlankNode(node) => {
+                output.push_str("\t\t\t<div itemprop=\"");
+                output.push_str(&itemprop);
+                output.push_str("\" itemscope itemref=\"");
+                output.push_str(&escape(node.as_str()));
+                output.push_str("\"></div>\n");
+            }
+            Term::Literal(literal) => {
+                let value = escape(literal.value());
+                if literal.is_plain() {
+                    output.push_str("\t\t\t<meta itemprop=\"");
+                    output.push_str(&itemprop);
+                    output.push_str("\" content=\"");
+                    output.push_str(&value);
+                    output.push('"');
+                    if let Some(language) = literal.language() {
+                        output.push_str(" lang=\"");
+                        output.push_str(&escape(language));
+                        output.push('"');
+                    }
+                    output.push_str(">\n");
+                } else if time_literal(literal.value().into(), None).datatype()
+                    == literal.datatype()
+                {
+                    output.push_str("\t\t\t<time itemprop=\"");
+                    output.push_str(&itemprop);
+                    output.push_str("\" datetime=\"");
+                    output.push_str(&value);
+                    output.push_str("\"></time>\n");
+                } else if meter_literal(literal.value().into()).datatype() == literal.datatype() {
+                    output.push_str("\t\t\t<meter itemprop=\"");
+                    output.push_str(&itemprop);
+                    output.push_str("\" value=\"");
+                    output.push_str(&value);
+                    output.push_str("\"></meter>\n");
+                } else {
+                    // The datatype can't be encoded
+                    output.push_str("\t\t\t<meta itemprop=\"");
+                    output.push_str(&itemprop);
+                    output.push_str("\" content=\"");
+                    output.push_str(&value);
+                    output.push_str("\">\n");
+                }
+            }
+            _ => (), // Rejected by serialize_triple
+        }
+    }
+
+    /// The IRI relative to the base IRI if possible
+    fn relative_iri<'a>(&self, node: NamedNodeRef<'a>) -> Cow<'a, str> {
+        let iri = node.as_str();
+        if let Some(base_iri) = &self.base_iri {
+            if let Ok(relative) = base_iri.relativize(&Iri::parse_unchecked(iri)) {
+                return relative.into_inner().into();
+            }
+        }
+        iri.into()
+    }
+}
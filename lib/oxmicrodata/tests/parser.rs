@@ -0,0 +1,288 @@
+#![cfg(test)]
+#![allow(clippy::panic_in_result_fn)]
+
+use oxmicrodata::{MicrodataParser, MicrodataSerializer, MicrodataSyntaxError};
+use oxrdf::vocab::{rdf, xsd};
+use oxrdf::{BlankNode, Graph, Literal, NamedNode, Term, Triple};
+
+fn parse(parser: MicrodataParser, file: &str) -> Result<Vec<Triple>, MicrodataSyntaxError> {
+    parser.for_slice(file.as_bytes()).collect()
+}
+
+fn nn(iri: &str) -> NamedNode {
+    NamedNode::new_unchecked(iri)
+}
+
+#[test]
+fn test_nested_items() -> Result<(), Box<dyn std::error::Error>> {
+    let triples = parse(
+        MicrodataParser::new().with_base_iri("http://example.com/page")?,
+        r##"<html>
+        <body>
+            <div itemscope itemtype="http://schema.org/Person" itemid="#alice">
+                <span itemprop="name">Alice</span>
+                <div itemprop="address" itemscope>
+                    <span itemprop="addressLocality">Paris</span>
+                </div>
+                <p itemprop="knows" itemscope itemtype="http://xmlns.com/foaf/0.1/Person">
+                    <span itemprop="name">Bob</span>
+                </p>
+            </div>
+        </body>
+        </html>"##,
+    )?;
+    assert_eq!(triples.len(), 7);
+    let alice = nn("http://example.com/page#alice");
+    assert_eq!(
+        triples[..2],
+        [
+            Triple::new(alice.clone(), rdf::TYPE, nn("http://schema.org/Person")),
+            Triple::new(
+                alice.clone(),
+                nn("http://schema.org/name"),
+                Literal::from("Alice")
+            ),
+        ]
+    );
+    let Term::BlankNode(address) = triples[2].object.clone() else {
+        return Err("The address should be a blank node".into());
+    };
+    assert_eq!(
+        triples[2],
+        Triple::new(
+            alice.clone(),
+            nn("http://schema.org/address"),
+            address.clone()
+        )
+    );
+    let Term::BlankNode(bob) = triples[3].object.clone() else {
+        return Err("Bob should be a blank node".into());
+    };
+    assert_eq!(
+        triples[3],
+        Triple::new(alice, nn("http://schema.org/knows"), bob.clone())
+    );
+    // The item without type inherits the vocabulary
+    assert_eq!(
+        triples[4],
+        Triple::new(
+            address,
+            nn("http://schema.org/addressLocality"),
+            Literal::from("Paris")
+        )
+    );
+    // The item type changes the vocabulary
+    assert_eq!(
+        triples[5..],
+        [
+            Triple::new(
+                bob.clone(),
+                rdf::TYPE,
+                nn("http://xmlns.com/foaf/0.1/Person")
+            ),
+            Triple::new(
+                bob,
+                nn("http://xmlns.com/foaf/0.1/name"),
+                Literal::from("Bob")
+            ),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_values() -> Result<(), Box<dyn std::error::Error>> {
+    let triples = parse(
+        MicrodataParser::new(),
+        r#"<!DOCTYPE html>
+        <html lang="en">
+        <head><base href="http://example.com/dir/"></head>
+        <body>
+            <div itemscope itemid="http://example.com/s" itemtype="http://example.com/ns#Thing">
+                <span itemprop="p1">hello &amp; <b>world</b></span><br>
+                <meta itemprop="p2" content="bonjour" lang="fr">
+                <a itemprop="p3" href="page">link</a>
+                <img itemprop="p4" src="http://example.com/img.png">
+                <time itemprop="p5">2012-03-04</time>
+                <time itemprop="p6" datetime="PT1H">one hour</time>
+                <meter itemprop="p7" value="12">12</meter>
+                <data itemprop="p8" value="42">forty two</data>
+                <span itemprop="http://example.com/other p9" lang="">x</span>
+            </div>
+        </body>
+        </html>"#,
+    )?;
+    let s = nn("http://example.com/s");
+    let ns = |name: &str| nn(&format!("http://example.com/ns#{name}"));
+    assert_eq!(
+        triples,
+        [
+            Triple::new(s.clone(), rdf::TYPE, ns("Thing")),
+            Triple::new(
+                s.clone(),
+                ns("p1"),
+                Literal::new_language_tagged_literal("hello & world", "en")?
+            ),
+            Triple::new(
+                s.clone(),
+                ns("p2"),
+                Literal::new_language_tagged_literal("bonjour", "fr")?
+            ),
+            Triple::new(s.clone(), ns("p3"), nn("http://example.com/dir/page")),
+            Triple::new(s.clone(), ns("p4"), nn("http://example.com/img.png")),
+            Triple::new(
+                s.clone(),
+                ns("p5"),
+                Literal::new_typed_literal("2012-03-04", xsd::DATE)
+            ),
+            Triple::new(
+                s.clone(),
+                ns("p6"),
+                Literal::new_typed_literal("PT1H", xsd::DURATION)
+            ),
+            Triple::new(
+                s.clone(),
+                ns("p7"),
+                Literal::new_typed_literal("12", xsd::INTEGER)
+            ),
+            Triple::new(
+                s.clone(),
+                ns("p8"),
+                Literal::new_language_tagged_literal("42", "en")?
+            ),
+            Triple::new(
+                s.clone(),
+                nn("http://example.com/other"),
+                Literal::from("x")
+            ),
+            Triple::new(s, ns("p9"), Literal::from("x")),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_itemref() -> Result<(), Box<dyn std::error::Error>> {
+    let triples = parse(
+        MicrodataParser::new(),
+        r#"<div itemscope itemid="http://example.com/s" itemtype="http://schema.org/Thing" itemref="a b"></div>
+        <p id="a"><span itemprop="name">Name</span></p>
+        <p id="b" itemprop="description">Description</p>
+        <span itemprop="ignored">Not in an item</span>"#,
+    )?;
+    let s = nn("http://example.com/s");
+    assert_eq!(
+        triples,
+        [
+            Triple::new(s.clone(), rdf::TYPE, nn("http://schema.org/Thing")),
+            Triple::new(
+                s.clone(),
+                nn("http://schema.org/name"),
+                Literal::from("Name")
+            ),
+            Triple::new(
+                s,
+                nn("http://schema.org/description"),
+                Literal::from("Description")
+            ),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_no_vocabulary() -> Result<(), Box<dyn std::error::Error>> {
+    let triples = parse(
+        MicrodataParser::new(),
+        r#"<div itemscope itemid="http://example.com/s">
+            <span itemprop="name">Ignored</span>
+            <span itemprop="http://example.com/p">Kept</span>
+        </div>"#,
+    )?;
+    assert_eq!(
+        triples,
+        [Triple::new(
+            nn("http://example.com/s"),
+            nn("http://example.com/p"),
+            Literal::from("Kept")
+        )]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_invalid_type() {
+    let results = MicrodataParser::new()
+        .for_slice(br#"<p itemscope itemtype="foo bar" itemid="http://example.com/s"><span itemprop="http://example.com/p">v</span></p>"#)
+        .collect::<Vec<_>>();
+    // The errors are returned and the types are ignored
+    assert_eq!(results.len(), 3);
+    results[0].as_ref().unwrap_err();
+    results[1].as_ref().unwrap_err();
+    assert!(matches!(&results[2], Ok(t) if t.predicate == nn("http://example.com/p")));
+}
+
+#[test]
+fn test_serializer_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let node = BlankNode::default();
+    let nested = BlankNode::default();
+    let triples = [
+        Triple::new(
+            nn("http://example.com/s"),
+            rdf::TYPE,
+            nn("http://schema.org/Person"),
+        ),
+        Triple::new(
+            nn("http://example.com/s"),
+            nn("http://schema.org/knows"),
+            node.clone(),
+        ),
+        Triple::new(node.clone(), rdf::TYPE, nn("http://schema.org/Person")),
+        Triple::new(
+            node.clone(),
+            nn("http://schema.org/name"),
+            Literal::new_language_tagged_literal("<Bob> \"Jr\"", "en")?,
+        ),
+        Triple::new(
+            node.clone(),
+            nn("http://schema.org/address"),
+            nested.clone(),
+        ),
+        Triple::new(
+            nested,
+            nn("http://schema.org/addressLocality"),
+            Literal::from("Paris"),
+        ),
+        Triple::new(
+            node,
+            nn("http://schema.org/birthDate"),
+            Literal::new_typed_literal("2000-01-01", xsd::DATE),
+        ),
+        Triple::new(
+            nn("http://example.com/other"),
+            nn("http://schema.org/age"),
+            Literal::new_typed_literal("12", xsd::INTEGER),
+        ),
+        Triple::new(
+            BlankNode::default(),
+            nn("http://schema.org/url"),
+            nn("http://example.com/"),
+        ),
+    ];
+    let mut serializer = MicrodataSerializer::new()
+        .with_base_iri("http://example.com/page")?
+        .for_writer(Vec::new());
+    for triple in &triples {
+        serializer.serialize_triple(triple)?;
+    }
+    let file = serializer.finish()?;
+    let parsed = MicrodataParser::new()
+        .with_base_iri("http://example.com/")?
+        .for_slice(&file)
+        .collect::<Result<Graph, _>>()?;
+    assert_eq!(
+        parsed.canonical_hash(),
+        triples.iter().collect::<Graph>().canonical_hash()
+    );
+    Ok(())
+}
@@ -31,6 +31,7 @@
 
 use crate::interning::*;
 use crate::*;
+use sha2::{Digest, Sha256};
 use std::collections::hash_map::DefaultHasher;
 use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt;
@@ -407,6 +408,61 @@ impl Dataset {
         self.ospg.remove(&(o, s, p, g))
     }
 
+    /// Adds a quad to the dataset and annotates it with the quad `<< triple >> predicate object graph_name`,
+    /// like `triple {| predicate object |}` does in TriG.
+    ///
+    /// The annotation is added to the same graph as the annotated quad.
+    /// Returns `true` if the annotation was not already in the dataset.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxrdf::*;
+    ///
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// let source = NamedNodeRef::new("http://example.com/source")?;
+    /// let quad = QuadRef::new(ex, ex, ex, ex);
+    ///
+    /// let mut dataset = Dataset::new();
+    /// dataset.annotate(quad, source, ex);
+    /// assert!(dataset.contains(quad));
+    /// assert_eq!(
+    ///     dataset.annotations(quad).collect::<Vec<_>>(),
+    ///     vec![(source, ex.into())]
+    /// );
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[cfg(feature = "rdf-star")]
+    pub fn annotate<'a>(
+        &mut self,
+        quad: impl Into<QuadRef<'a>>,
+        predicate: impl Into<NamedNodeRef<'a>>,
+        object: impl Into<TermRef<'a>>,
+    ) -> bool {
+        let quad = quad.into();
+        self.insert(quad);
+        let subject = Subject::Triple(Box::new(TripleRef::from(quad).into_owned()));
+        self.insert(QuadRef::new(
+            &subject,
+            predicate.into(),
+            object.into(),
+            quad.graph_name,
+        ))
+    }
+
+    /// Returns the `(predicate, object)` pairs of the quads whose subject is the triple of the given quad
+    /// and that are in the same graph.
+    #[cfg(feature = "rdf-star")]
+    pub fn annotations<'a, 'b>(
+        &'a self,
+        quad: impl Into<QuadRef<'b>>,
+    ) -> impl Iterator<Item = (NamedNodeRef<'a>, TermRef<'a>)> + 'a {
+        let quad = quad.into();
+        let subject = Subject::Triple(Box::new(TripleRef::from(quad).into_owned()));
+        self.graph(quad.graph_name)
+            .triples_for_interned_subject(self.encoded_subject(&subject))
+            .map(|t| (t.predicate, t.object))
+    }
+
     /// Clears the dataset.
     pub fn clear(&mut self) {
         self.gspo.clear();
@@ -576,9 +632,201 @@ impl Dataset {
                     .map(|(from, to)| (from, BlankNode::new_from_unique_id(to.into())))
                     .collect()
             }
+            CanonicalizationAlgorithm::Rdfc10 => {
+                // Never fails without work limit
+                rdfc10::canonicalize_blank_nodes(self.iter(), None)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|(from, to)| {
+                        Some((InternedBlankNode::encoded_from(from, &self.interner)?, to))
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Returns the SHA-256 digest of the canonical N-Quads serialization of the dataset canonicalized with [RDFC-1.0](https://www.w3.org/TR/rdf-canon/).
+    ///
+    /// Two datasets have the same hash if and only if they are isomorphic (up to a SHA-256 collision).
+    /// It allows cheap change detection and content-addressed storage.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxrdf::*;
+    ///
+    /// let iri = NamedNodeRef::new("http://example.com")?;
+    ///
+    /// let mut dataset1 = Dataset::new();
+    /// dataset1.insert(QuadRef::new(iri, iri, &BlankNode::default(), iri));
+    ///
+    /// let mut dataset2 = Dataset::new();
+    /// dataset2.insert(QuadRef::new(iri, iri, &BlankNode::default(), iri));
+    ///
+    /// assert_ne!(dataset1, dataset2);
+    /// assert_eq!(dataset1.canonical_hash(), dataset2.canonical_hash());
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    ///
+    /// <div class="warning">This implementation worst-case complexity is in *O(b!)* with *b* the number of blank nodes in the input dataset.</div>
+    pub fn canonical_hash(&self) -> [u8; 32] {
+        // Never fails without work limit
+        self.canonicalize_rdfc10(Rdfc10Options::default().without_work_limit())
+            .map(|canonicalization| canonicalization.hash())
+            .unwrap_or_default()
+    }
+
+    /// Canonicalizes the dataset with [RDFC-1.0](https://www.w3.org/TR/rdf-canon/) and returns its canonical N-Quads serialization and the issued blank node identifiers.
+    ///
+    /// The dataset is not modified.
+    /// By default, the amount of work is limited to protect against poison datasets crafted to make the canonicalization never end,
+    /// see [`Rdfc10Options::with_max_work_factor`].
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxrdf::dataset::Rdfc10Options;
+    /// use oxrdf::*;
+    ///
+    /// let iri = NamedNodeRef::new("http://example.com")?;
+    /// let bnode = BlankNode::new("b")?;
+    ///
+    /// let mut dataset = Dataset::new();
+    /// dataset.insert(QuadRef::new(iri, iri, &bnode, iri));
+    ///
+    /// let canonicalization = dataset.canonicalize_rdfc10(Rdfc10Options::default())?;
+    /// assert_eq!(
+    ///     canonicalization.to_nquads(),
+    ///     "<http://example.com> <http://example.com> _:c14n0 <http://example.com> .\n"
+    /// );
+    /// assert_eq!(
+    ///     canonicalization.issued_identifiers(),
+    ///     [(bnode.as_ref(), BlankNode::new("c14n0")?)]
+    /// );
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn canonicalize_rdfc10(
+        &self,
+        options: Rdfc10Options,
+    ) -> Result<Rdfc10Canonicalization<'_>, CanonicalizationError> {
+        let issued_identifiers =
+            rdfc10::canonicalize_blank_nodes(self.iter(), options.max_work_factor)?;
+        let bnode_mapping = issued_identifiers
+            .iter()
+            .map(|(from, to)| (from.as_str(), to.as_str()))
+            .collect::<HashMap<_, _>>();
+        let label = |bnode: BlankNodeRef<'_>| {
+            bnode_mapping
+                .get(bnode.as_str())
+                .map_or_else(String::new, |b| (*b).into())
+        };
+        let mut nquads = self
+            .iter()
+            .map(|quad| {
+                let mut nquad = String::new();
+                rdfc10::serialize_quad(quad, &label, &mut nquad);
+                nquad
+            })
+            .collect::<Vec<_>>();
+        nquads.sort_unstable();
+        Ok(Rdfc10Canonicalization {
+            nquads,
+            issued_identifiers,
+        })
+    }
+
+    /// Returns the quads to remove from this dataset and the quads to add to it to get the `other` dataset.
+    ///
+    /// The blank nodes of the two datasets are matched using their canonical identifiers (see [`CanonicalizationAlgorithm::Unstable`]):
+    /// isomorphic parts of the datasets do not show up in the diff even if their blank node identifiers differ.
+    /// The removed quads and the added quads referring to a matched blank node use the identifiers of this dataset
+    /// so that the diff can be applied to it.
+    /// The blank nodes only in `other` keep their identifier if it is not used in this dataset.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxrdf::*;
+    ///
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// let old_quad = QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph);
+    /// let new_quad = QuadRef::new(ex, ex, LiteralRef::from("new"), GraphNameRef::DefaultGraph);
+    /// let b1 = BlankNode::new("b1")?;
+    /// let b2 = BlankNode::new("b2")?;
+    ///
+    /// let mut dataset1 = Dataset::new();
+    /// dataset1.insert(QuadRef::new(&b1, ex, ex, GraphNameRef::DefaultGraph));
+    /// dataset1.insert(old_quad);
+    ///
+    /// let mut dataset2 = Dataset::new();
+    /// dataset2.insert(QuadRef::new(&b2, ex, ex, GraphNameRef::DefaultGraph));
+    /// dataset2.insert(new_quad);
+    ///
+    /// let diff = dataset1.diff(&dataset2);
+    /// assert_eq!(diff.removed(), [old_quad.into_owned()]);
+    /// assert_eq!(diff.added(), [new_quad.into_owned()]);
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    ///
+    /// <div class="warning">The canonical identifiers depend on the whole shape of the datasets:
+    /// if the blank node structure changes a lot, the quads with blank nodes might all be removed and added again.</div>
+    ///
+    /// <div class="warning">This implementation worst-case complexity is in *O(b!)* with *b* the number of blank nodes in the input datasets.</div>
+    pub fn diff(&self, other: &Self) -> DatasetDiff {
+        let self_mapping = self.canonical_blank_node_ids();
+        let other_mapping = other.canonical_blank_node_ids();
+        let canonicalize = |quad: QuadRef<'_>, mapping: &HashMap<&str, BlankNode>| {
+            rename_quad_blank_nodes(quad, &|bnode| {
+                mapping
+                    .get(bnode.as_str())
+                    .cloned()
+                    .unwrap_or_else(|| bnode.into_owned())
+            })
+        };
+        let canonical_self = self
+            .iter()
+            .map(|quad| canonicalize(quad, &self_mapping))
+            .collect::<Self>();
+        let canonical_other = other
+            .iter()
+            .map(|quad| canonicalize(quad, &other_mapping))
+            .collect::<Self>();
+
+        // We map back the canonical identifiers to the original ones
+        let self_ids = self_mapping
+            .iter()
+            .map(|(from, to)| (to.as_str(), *from))
+            .collect::<HashMap<_, _>>();
+        let other_ids = other_mapping
+            .iter()
+            .filter(|(from, _)| !self_mapping.contains_key(*from))
+            .map(|(from, to)| (to.as_str(), *from))
+            .collect::<HashMap<_, _>>();
+        let original = |bnode: BlankNodeRef<'_>| {
+            self_ids
+                .get(bnode.as_str())
+                .or_else(|| other_ids.get(bnode.as_str()))
+                .map_or_else(|| bnode.into_owned(), |id| BlankNode::new_unchecked(*id))
+        };
+        DatasetDiff {
+            removed: canonical_self
+                .iter()
+                .filter(|quad| !canonical_other.contains(*quad))
+                .map(|quad| rename_quad_blank_nodes(quad, &original))
+                .collect(),
+            added: canonical_other
+                .iter()
+                .filter(|quad| !canonical_self.contains(*quad))
+                .map(|quad| rename_quad_blank_nodes(quad, &original))
+                .collect(),
         }
     }
 
+    /// The canonical blank node computed with [`CanonicalizationAlgorithm::Unstable`] for each blank node identifier
+    fn canonical_blank_node_ids(&self) -> HashMap<&str, BlankNode> {
+        self.canonicalize_blank_nodes(CanonicalizationAlgorithm::Unstable)
+            .into_iter()
+            .map(|(from, to)| (from.as_str(), to))
+            .collect()
+    }
+
     fn blank_nodes(&self) -> HashSet<InternedBlankNode> {
         let mut bnodes = HashSet::new();
         for (g, s, _, o) in &self.gspo {
@@ -1600,11 +1848,180 @@ pub enum CanonicalizationAlgorithm {
     /// <div class="warning">The canonicalization algorithm is not stable and canonical blank node ids might change between Oxigraph version.</div>
     #[default]
     Unstable,
+    /// The [RDFC-1.0](https://www.w3.org/TR/rdf-canon/) algorithm using SHA-256.
+    ///
+    /// The blank nodes are renamed to `c14n0`, `c14n1`...
+    Rdfc10,
+}
+
+/// Options of the [RDFC-1.0](https://www.w3.org/TR/rdf-canon/) canonicalization done by [`Dataset::canonicalize_rdfc10`].
+#[derive(Debug, Clone, Copy)]
+#[must_use]
+pub struct Rdfc10Options {
+    max_work_factor: Option<u32>,
+}
+
+impl Default for Rdfc10Options {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_work_factor: Some(1),
+        }
+    }
+}
+
+impl Rdfc10Options {
+    /// Limits the amount of work done by the canonicalization to protect against [poison datasets](https://www.w3.org/TR/rdf-canon/#dataset-poisoning).
+    ///
+    /// With `m` the number of blank nodes whose first degree hash is not unique,
+    /// each blank node can be the reference of at most `m^max_work_factor` calls to the [Hash N-Degree Quads](https://www.w3.org/TR/rdf-canon/#hash-nd-quads) algorithm.
+    /// The canonicalization fails with a [`CanonicalizationError`] if this limit is exceeded.
+    ///
+    /// The default factor is 1, enough for the datasets whose blank nodes are not too symmetric.
+    #[inline]
+    pub fn with_max_work_factor(mut self, max_work_factor: u32) -> Self {
+        self.max_work_factor = Some(max_work_factor);
+        self
+    }
+
+    /// Removes the limit on the amount of work done by the canonicalization.
+    ///
+    /// <div class="warning">The canonicalization of a poison dataset might then never end.</div>
+    #[inline]
+    pub fn without_work_limit(mut self) -> Self {
+        self.max_work_factor = None;
+        self
+    }
+}
+
+/// The result of the [RDFC-1.0](https://www.w3.org/TR/rdf-canon/) canonicalization of a dataset returned by [`Dataset::canonicalize_rdfc10`].
+#[derive(Debug, Clone)]
+pub struct Rdfc10Canonicalization<'a> {
+    /// The sorted canonical N-Quads lines
+    nquads: Vec<String>,
+    issued_identifiers: Vec<(BlankNodeRef<'a>, BlankNode)>,
+}
+
+impl<'a> Rdfc10Canonicalization<'a> {
+    /// The canonical N-Quads serialization of the dataset, i.e. its quads with the canonical blank node identifiers, sorted in code point order, each one followed by a line feed.
+    pub fn to_nquads(&self) -> String {
+        self.nquads.concat()
+    }
+
+    /// The canonical blank node identifier issued for each blank node of the dataset, in the order they have been issued.
+    pub fn issued_identifiers(&self) -> &[(BlankNodeRef<'a>, BlankNode)] {
+        &self.issued_identifiers
+    }
+
+    /// The SHA-256 digest of the canonical N-Quads serialization.
+    pub fn hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        for nquad in &self.nquads {
+            hasher.update(nquad.as_bytes());
+        }
+        hasher.finalize().into()
+    }
+}
+
+/// The differences between two datasets returned by [`Dataset::diff`].
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct DatasetDiff {
+    removed: Vec<Quad>,
+    added: Vec<Quad>,
+}
+
+impl DatasetDiff {
+    /// The quads to remove from the first dataset.
+    #[inline]
+    pub fn removed(&self) -> &[Quad] {
+        &self.removed
+    }
+
+    /// The quads to add to the first dataset.
+    #[inline]
+    pub fn added(&self) -> &[Quad] {
+        &self.added
+    }
+
+    /// Checks if the two datasets are the same, up to blank node renaming.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty()
+    }
+}
+
+fn rename_quad_blank_nodes(
+    quad: QuadRef<'_>,
+    rename: &impl Fn(BlankNodeRef<'_>) -> BlankNode,
+) -> Quad {
+    Quad::new(
+        rename_subject_blank_nodes(quad.subject, rename),
+        quad.predicate,
+        rename_term_blank_nodes(quad.object, rename),
+        match quad.graph_name {
+            GraphNameRef::BlankNode(bnode) => rename(bnode).into(),
+            graph_name => graph_name.into_owned(),
+        },
+    )
+}
+
+fn rename_subject_blank_nodes(
+    subject: SubjectRef<'_>,
+    rename: &impl Fn(BlankNodeRef<'_>) -> BlankNode,
+) -> Subject {
+    match subject {
+        SubjectRef::BlankNode(bnode) => rename(bnode).into(),
+        #[cfg(feature = "rdf-star")]
+        SubjectRef::Triple(triple) => rename_triple_blank_nodes(triple, rename).into(),
+        SubjectRef::NamedNode(_) => subject.into_owned(),
+    }
+}
+
+fn rename_term_blank_nodes(
+    term: TermRef<'_>,
+    rename: &impl Fn(BlankNodeRef<'_>) -> BlankNode,
+) -> Term {
+    match term {
+        TermRef::BlankNode(bnode) => rename(bnode).into(),
+        #[cfg(feature = "rdf-star")]
+        TermRef::Triple(triple) => rename_triple_blank_nodes(triple, rename).into(),
+        TermRef::NamedNode(_) | TermRef::Literal(_) => term.into_owned(),
+    }
+}
+
+#[cfg(feature = "rdf-star")]
+fn rename_triple_blank_nodes(
+    triple: &Triple,
+    rename: &impl Fn(BlankNodeRef<'_>) -> BlankNode,
+) -> Triple {
+    Triple::new(
+        rename_subject_blank_nodes(triple.subject.as_ref(), rename),
+        triple.predicate.clone(),
+        rename_term_blank_nodes(triple.object.as_ref(), rename),
+    )
+}
+
+/// An error raised when the [RDFC-1.0](https://www.w3.org/TR/rdf-canon/) canonicalization exceeds its work limit, see [`Rdfc10Options::with_max_work_factor`].
+#[derive(Debug, thiserror::Error)]
+#[error("The canonicalization has been stopped after {max_calls} calls to Hash N-Degree Quads for the blank node {blank_node}, the dataset might be poisoned")]
+pub struct CanonicalizationError {
+    blank_node: String,
+    max_calls: u64,
+}
+
+impl CanonicalizationError {
+    pub(crate) fn too_much_work(blank_node: BlankNodeRef<'_>, max_calls: u64) -> Self {
+        Self {
+            blank_node: blank_node.to_string(),
+            max_calls,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::str::FromStr;
 
     #[test]
     fn test_canon() {
@@ -1638,4 +2055,227 @@ mod tests {
         dataset2.canonicalize(CanonicalizationAlgorithm::Unstable);
         assert_eq!(dataset, dataset2);
     }
+
+    fn parse_dataset(nquads: &[&str]) -> Dataset {
+        nquads
+            .iter()
+            .map(|quad| Quad::from_str(quad).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_rdfc10_unique_hashes() {
+        // Example from the RDFC-1.0 specification
+        let mut dataset = parse_dataset(&[
+            "<http://example.com/#p> <http://example.com/#q> _:e0",
+            "<http://example.com/#p> <http://example.com/#r> _:e1",
+            "_:e0 <http://example.com/#s> <http://example.com/#u>",
+            "_:e1 <http://example.com/#t> <http://example.com/#u>",
+        ]);
+        let hash = dataset.canonical_hash();
+        dataset.canonicalize(CanonicalizationAlgorithm::Rdfc10);
+        assert_eq!(
+            dataset,
+            parse_dataset(&[
+                "<http://example.com/#p> <http://example.com/#q> _:c14n0",
+                "<http://example.com/#p> <http://example.com/#r> _:c14n1",
+                "_:c14n0 <http://example.com/#s> <http://example.com/#u>",
+                "_:c14n1 <http://example.com/#t> <http://example.com/#u>",
+            ])
+        );
+        assert_eq!(dataset.canonical_hash(), hash);
+    }
+
+    #[test]
+    fn test_rdfc10_shared_hashes() {
+        // Example from the RDFC-1.0 specification
+        let mut dataset = parse_dataset(&[
+            "<http://example.com/#p> <http://example.com/#q> _:e0",
+            "<http://example.com/#p> <http://example.com/#q> _:e1",
+            "_:e0 <http://example.com/#p> _:e2",
+            "_:e1 <http://example.com/#p> _:e3",
+            "_:e2 <http://example.com/#r> _:e3",
+        ]);
+        let hash = dataset.canonical_hash();
+        dataset.canonicalize(CanonicalizationAlgorithm::Rdfc10);
+        assert_eq!(
+            dataset,
+            parse_dataset(&[
+                "<http://example.com/#p> <http://example.com/#q> _:c14n2",
+                "<http://example.com/#p> <http://example.com/#q> _:c14n3",
+                "_:c14n0 <http://example.com/#r> _:c14n1",
+                "_:c14n2 <http://example.com/#p> _:c14n1",
+                "_:c14n3 <http://example.com/#p> _:c14n0",
+            ])
+        );
+        assert_eq!(dataset.canonical_hash(), hash);
+    }
+
+    #[test]
+    fn test_canonical_hash_isomorphism() {
+        let cycle = parse_dataset(&[
+            "_:a <http://example.com/p> _:b <http://example.com/g>",
+            "_:b <http://example.com/p> _:c <http://example.com/g>",
+            "_:c <http://example.com/p> _:a <http://example.com/g>",
+        ]);
+        let renamed_cycle = parse_dataset(&[
+            "_:x <http://example.com/p> _:z <http://example.com/g>",
+            "_:z <http://example.com/p> _:y <http://example.com/g>",
+            "_:y <http://example.com/p> _:x <http://example.com/g>",
+        ]);
+        let other_graph = parse_dataset(&[
+            "_:a <http://example.com/p> _:b <http://example.com/g2>",
+            "_:b <http://example.com/p> _:c <http://example.com/g2>",
+            "_:c <http://example.com/p> _:a <http://example.com/g2>",
+        ]);
+        let two_cycles = parse_dataset(&[
+            "_:a <http://example.com/p> _:b <http://example.com/g>",
+            "_:b <http://example.com/p> _:a <http://example.com/g>",
+            "_:c <http://example.com/p> _:c <http://example.com/g>",
+        ]);
+        assert_eq!(cycle.canonical_hash(), renamed_cycle.canonical_hash());
+        assert_ne!(cycle.canonical_hash(), other_graph.canonical_hash());
+        assert_ne!(cycle.canonical_hash(), two_cycles.canonical_hash());
+        assert_ne!(cycle.canonical_hash(), Dataset::new().canonical_hash());
+    }
+
+    #[test]
+    fn test_canonicalize_rdfc10_work_limit() {
+        let cycle = parse_dataset(&[
+            "_:a <http://example.com/p> _:b",
+            "_:b <http://example.com/p> _:c",
+            "_:c <http://example.com/p> _:a",
+        ]);
+        let canonicalization = cycle.canonicalize_rdfc10(Rdfc10Options::default()).unwrap();
+        assert_eq!(canonicalization.issued_identifiers().len(), 3);
+        assert_eq!(canonicalization.hash(), cycle.canonical_hash());
+        let renamed_cycle = parse_dataset(&[
+            "_:x <http://example.com/p> _:z",
+            "_:z <http://example.com/p> _:y",
+            "_:y <http://example.com/p> _:x",
+        ]);
+        assert_eq!(
+            canonicalization.to_nquads(),
+            renamed_cycle
+                .canonicalize_rdfc10(Rdfc10Options::default())
+                .unwrap()
+                .to_nquads()
+        );
+        cycle
+            .canonicalize_rdfc10(Rdfc10Options::default().with_max_work_factor(0))
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_diff() {
+        let dataset1 = parse_dataset(&[
+            "_:a <http://example.com/p> \"1\"",
+            "_:a <http://example.com/p> _:c",
+            "<http://example.com/s> <http://example.com/p> <http://example.com/o>",
+        ]);
+        let dataset2 = parse_dataset(&[
+            "_:x <http://example.com/p> \"1\"",
+            "_:x <http://example.com/p> _:z",
+            "_:c <http://example.com/q> \"2\"",
+            "<http://example.com/s> <http://example.com/p> <http://example.com/o2>",
+        ]);
+        let diff = dataset1.diff(&dataset2);
+        assert_eq!(
+            diff.removed(),
+            parse_dataset(&[
+                "<http://example.com/s> <http://example.com/p> <http://example.com/o>"
+            ])
+            .iter()
+            .map(QuadRef::into_owned)
+            .collect::<Vec<_>>()
+        );
+        assert_eq!(diff.added().len(), 2);
+        assert!(diff.added().contains(
+            &parse_dataset(&[
+                "<http://example.com/s> <http://example.com/p> <http://example.com/o2>"
+            ])
+            .iter()
+            .next()
+            .unwrap()
+            .into_owned()
+        ));
+        // The new blank node must not reuse the identifier of a blank node of the first dataset
+        assert!(diff.added().iter().any(|quad| matches!(
+            &quad.subject,
+            Subject::BlankNode(bnode) if bnode.as_str() != "a" && bnode.as_str() != "c"
+        )));
+
+        let mut patched = dataset1.clone();
+        for quad in diff.removed() {
+            patched.remove(quad);
+        }
+        patched.extend(diff.added());
+        assert!(patched.diff(&dataset2).is_empty());
+        assert!(dataset1.diff(&dataset1).is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "rdf-star")]
+    fn test_annotate_missing_quad() {
+        let ex = NamedNodeRef::new_unchecked("http://example.com");
+        let source = NamedNodeRef::new_unchecked("http://example.com/source");
+        let quad = QuadRef::new(ex, ex, ex, ex);
+
+        let mut dataset = Dataset::new();
+        assert_eq!(dataset.annotations(quad).count(), 0);
+        assert!(dataset.annotate(quad, source, ex));
+        assert!(dataset.contains(quad));
+        assert_eq!(dataset.len(), 2);
+        assert_eq!(
+            dataset.annotations(quad).collect::<Vec<_>>(),
+            vec![(source, ex.into())]
+        );
+        // The annotations are only looked for in the graph of the quad
+        assert_eq!(
+            dataset
+                .annotations(QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph))
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "rdf-star")]
+    fn test_annotate_duplicate() {
+        let ex = NamedNodeRef::new_unchecked("http://example.com");
+        let source = NamedNodeRef::new_unchecked("http://example.com/source");
+        let other = NamedNodeRef::new_unchecked("http://example.com/other");
+        let quad = QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph);
+
+        let mut dataset = Dataset::new();
+        dataset.insert(quad);
+        assert!(dataset.annotate(quad, source, ex));
+        assert!(!dataset.annotate(quad, source, ex));
+        assert!(dataset.annotate(quad, source, other));
+        assert_eq!(dataset.len(), 3);
+        let mut annotations = dataset.annotations(quad).collect::<Vec<_>>();
+        annotations.sort_by_key(|(_, o)| o.to_string());
+        assert_eq!(
+            annotations,
+            vec![(source, other.into()), (source, ex.into())]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "rdf-star")]
+    fn test_annotation_removal() {
+        let ex = NamedNodeRef::new_unchecked("http://example.com");
+        let source = NamedNodeRef::new_unchecked("http://example.com/source");
+        let quad = QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph);
+        let quoted = Subject::Triple(Box::new(TripleRef::from(quad).into_owned()));
+
+        let mut dataset = Dataset::new();
+        dataset.annotate(quad, source, ex);
+        // Removing the annotated quad keeps its annotations
+        assert!(dataset.remove(quad));
+        assert_eq!(dataset.annotations(quad).count(), 1);
+        assert!(dataset.remove(QuadRef::new(&quoted, source, ex, quad.graph_name)));
+        assert_eq!(dataset.annotations(quad).count(), 0);
+        assert!(dataset.is_empty());
+    }
 }
@@ -25,8 +25,10 @@
 //!
 //! See also [`Dataset`] if you want to get support of multiple RDF graphs at the same time.
 
-pub use crate::dataset::CanonicalizationAlgorithm;
 use crate::dataset::*;
+pub use crate::dataset::{
+    CanonicalizationAlgorithm, CanonicalizationError, Rdfc10Canonicalization, Rdfc10Options,
+};
 use crate::*;
 use std::fmt;
 
@@ -184,6 +186,53 @@ impl Graph {
         self.dataset.clear()
     }
 
+    /// Adds a triple to the graph and annotates it with the triple `<< triple >> predicate object`,
+    /// like `triple {| predicate object |}` does in Turtle.
+    ///
+    /// Returns `true` if the annotation was not already in the graph.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxrdf::*;
+    ///
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// let source = NamedNodeRef::new("http://example.com/source")?;
+    /// let triple = TripleRef::new(ex, ex, ex);
+    ///
+    /// let mut graph = Graph::new();
+    /// graph.annotate(triple, source, ex);
+    /// assert!(graph.contains(triple));
+    /// assert_eq!(
+    ///     graph.annotations(triple).collect::<Vec<_>>(),
+    ///     vec![(source, ex.into())]
+    /// );
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[cfg(feature = "rdf-star")]
+    pub fn annotate<'a>(
+        &mut self,
+        triple: impl Into<TripleRef<'a>>,
+        predicate: impl Into<NamedNodeRef<'a>>,
+        object: impl Into<TermRef<'a>>,
+    ) -> bool {
+        let triple = triple.into();
+        self.insert(triple);
+        let subject = Subject::Triple(Box::new(triple.into_owned()));
+        self.insert(TripleRef::new(&subject, predicate.into(), object.into()))
+    }
+
+    /// Returns the `(predicate, object)` pairs of the triples whose subject is the given quoted triple.
+    #[cfg(feature = "rdf-star")]
+    pub fn annotations<'a, 'b>(
+        &'a self,
+        triple: impl Into<TripleRef<'b>>,
+    ) -> impl Iterator<Item = (NamedNodeRef<'a>, TermRef<'a>)> + 'a {
+        let subject = Subject::Triple(Box::new(triple.into().into_owned()));
+        self.graph()
+            .triples_for_interned_subject(self.dataset.encoded_subject(&subject))
+            .map(|t| (t.predicate, t.object))
+    }
+
     /// Canonicalizes the dataset by renaming blank nodes.
     ///
     /// Usage example ([Graph isomorphism](https://www.w3.org/TR/rdf11-concepts/#dfn-graph-isomorphism)):
@@ -217,6 +266,42 @@ impl Graph {
     pub fn canonicalize(&mut self, algorithm: CanonicalizationAlgorithm) {
         self.dataset.canonicalize(algorithm)
     }
+
+    /// Returns the SHA-256 digest of the canonical N-Triples serialization of the graph canonicalized with [RDFC-1.0](https://www.w3.org/TR/rdf-canon/).
+    ///
+    /// Two graphs have the same hash if and only if they are isomorphic (up to a SHA-256 collision).
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxrdf::*;
+    ///
+    /// let iri = NamedNodeRef::new("http://example.com")?;
+    ///
+    /// let mut graph1 = Graph::new();
+    /// graph1.insert(TripleRef::new(iri, iri, &BlankNode::default()));
+    ///
+    /// let mut graph2 = Graph::new();
+    /// graph2.insert(TripleRef::new(iri, iri, &BlankNode::default()));
+    ///
+    /// assert_ne!(graph1, graph2);
+    /// assert_eq!(graph1.canonical_hash(), graph2.canonical_hash());
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    ///
+    /// <div class="warning">This implementation worst-case complexity is in *O(b!)* with *b* the number of blank nodes in the input graph.</div>
+    pub fn canonical_hash(&self) -> [u8; 32] {
+        self.dataset.canonical_hash()
+    }
+
+    /// Canonicalizes the graph with [RDFC-1.0](https://www.w3.org/TR/rdf-canon/) and returns its canonical N-Triples serialization and the issued blank node identifiers.
+    ///
+    /// See [`Dataset::canonicalize_rdfc10`] for more details.
+    pub fn canonicalize_rdfc10(
+        &self,
+        options: Rdfc10Options,
+    ) -> Result<Rdfc10Canonicalization<'_>, CanonicalizationError> {
+        self.dataset.canonicalize_rdfc10(options)
+    }
 }
 
 impl PartialEq for Graph {
@@ -282,3 +367,55 @@ impl<'a> Iterator for Iter<'a> {
         self.inner.next()
     }
 }
+
+#[cfg(all(test, feature = "rdf-star"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annotate_missing_triple() {
+        let ex = NamedNodeRef::new_unchecked("http://example.com");
+        let source = NamedNodeRef::new_unchecked("http://example.com/source");
+        let triple = TripleRef::new(ex, ex, ex);
+
+        let mut graph = Graph::new();
+        assert_eq!(graph.annotations(triple).count(), 0);
+        assert!(graph.annotate(triple, source, ex));
+        assert!(graph.contains(triple));
+        assert_eq!(graph.len(), 2);
+        assert_eq!(
+            graph.annotations(triple).collect::<Vec<_>>(),
+            vec![(source, ex.into())]
+        );
+    }
+
+    #[test]
+    fn test_annotate_duplicate() {
+        let ex = NamedNodeRef::new_unchecked("http://example.com");
+        let source = NamedNodeRef::new_unchecked("http://example.com/source");
+        let triple = TripleRef::new(ex, ex, ex);
+
+        let mut graph = Graph::new();
+        assert!(graph.annotate(triple, source, ex));
+        assert!(!graph.annotate(triple, source, ex));
+        assert_eq!(graph.len(), 2);
+        assert_eq!(graph.annotations(triple).count(), 1);
+    }
+
+    #[test]
+    fn test_annotation_removal() {
+        let ex = NamedNodeRef::new_unchecked("http://example.com");
+        let source = NamedNodeRef::new_unchecked("http://example.com/source");
+        let triple = TripleRef::new(ex, ex, ex);
+        let quoted = Subject::Triple(Box::new(triple.into_owned()));
+
+        let mut graph = Graph::new();
+        graph.annotate(triple, source, ex);
+        // Removing the annotated triple keeps its annotations
+        assert!(graph.remove(triple));
+        assert_eq!(graph.annotations(triple).count(), 1);
+        assert!(graph.remove(TripleRef::new(&quoted, source, ex)));
+        assert_eq!(graph.annotations(triple).count(), 0);
+        assert!(graph.is_empty());
+    }
+}
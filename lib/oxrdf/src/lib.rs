@@ -11,6 +11,7 @@ mod interning;
 mod literal;
 mod named_node;
 mod parser;
+mod rdfc10;
 mod triple;
 mod variable;
 pub mod vocab;
@@ -420,6 +420,22 @@ impl From<DayTimeDuration> for Literal {
     }
 }
 
+#[cfg(feature = "oxsdatatypes")]
+impl From<HexBinary> for Literal {
+    #[inline]
+    fn from(value: HexBinary) -> Self {
+        Self::new_typed_literal(value.to_string(), xsd::HEX_BINARY)
+    }
+}
+
+#[cfg(feature = "oxsdatatypes")]
+impl From<Base64Binary> for Literal {
+    #[inline]
+    fn from(value: Base64Binary) -> Self {
+        Self::new_typed_literal(value.to_string(), xsd::BASE_64_BINARY)
+    }
+}
+
 /// A borrowed RDF [literal](https://www.w3.org/TR/rdf11-concepts/#dfn-literal).
 ///
 /// The default string formatter is returning an N-Triples, Turtle, and SPARQL compatible representation:
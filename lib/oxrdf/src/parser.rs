@@ -423,7 +423,10 @@ fn read_literal(s: &str) -> Result<(Literal, &str), TermParseError> {
                 cursor += 1;
             }
             if count_exponent > 0 {
-                Ok((Literal::new_typed_literal(s, xsd::DOUBLE), &s[cursor..]))
+                Ok((
+                    Literal::new_typed_literal(&s[..cursor], xsd::DOUBLE),
+                    &s[cursor..],
+                ))
             } else {
                 Err(TermParseError::msg(
                     "Double serialization with an invalid exponent",
@@ -431,14 +434,20 @@ fn read_literal(s: &str) -> Result<(Literal, &str), TermParseError> {
             }
         } else if with_dot {
             if count_after > 0 {
-                Ok((Literal::new_typed_literal(s, xsd::DECIMAL), &s[cursor..]))
+                Ok((
+                    Literal::new_typed_literal(&s[..cursor], xsd::DECIMAL),
+                    &s[cursor..],
+                ))
             } else {
                 Err(TermParseError::msg(
                     "Decimal serialization without floating part",
                 ))
             }
         } else if count_before > 0 {
-            Ok((Literal::new_typed_literal(s, xsd::INTEGER), &s[cursor..]))
+            Ok((
+                Literal::new_typed_literal(&s[..cursor], xsd::INTEGER),
+                &s[cursor..],
+            ))
         } else {
             Err(TermParseError::msg("Empty integer serialization"))
         }
@@ -456,14 +465,24 @@ fn read_term(s: &str, number_of_recursive_calls: usize) -> Result<(Term, &str),
     if let Some(remain) = s.strip_prefix("<<") {
         #[cfg(feature = "rdf-star")]
         {
+            // RDF 1.2 triple terms are written <<( s p o )>>
+            let (remain, end) = if let Some(remain) = remain.strip_prefix('(') {
+                (remain, ")>>")
+            } else {
+                (remain, ">>")
+            };
             let (triple, remain) = read_triple(remain, number_of_recursive_calls + 1)?;
             let remain = remain.trim_start();
-            if let Some(remain) = remain.strip_prefix(">>") {
+            if let Some(remain) = remain.strip_prefix(end) {
                 Ok((triple.into(), remain))
-            } else {
+            } else if end == ">>" {
                 Err(TermParseError::msg(
                     "Nested triple serialization must be enclosed between << and >>",
                 ))
+            } else {
+                Err(TermParseError::msg(
+                    "Triple term serialization must be enclosed between <<( and )>>",
+                ))
             }
         }
         #[cfg(not(feature = "rdf-star"))]
@@ -591,5 +610,22 @@ mod tests {
             )
             .into()
         );
+        assert_eq!(
+            Term::from_str(
+                "<<( <<(_:s <http://example.com/p> 1)>> <http://example.com/p> \"o\"@en )>>"
+            )
+            .unwrap(),
+            Triple::new(
+                Triple::new(
+                    BlankNode::new("s").unwrap(),
+                    NamedNode::new("http://example.com/p").unwrap(),
+                    Literal::new_typed_literal("1", xsd::INTEGER),
+                ),
+                NamedNode::new("http://example.com/p").unwrap(),
+                Literal::new_language_tagged_literal("o", "en").unwrap(),
+            )
+            .into()
+        );
+        Term::from_str("<<( _:s <http://example.com/p> \"o\" >>").unwrap_err();
     }
 }
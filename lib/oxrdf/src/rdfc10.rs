@@ -0,0 +1,431 @@
+//! Implementation of the [RDFC-1.0](https://www.w3.org/TR/rdf-canon/) canonicalization algorithm using SHA-256.
+
+use crate::dataset::CanonicalizationError;
+#[cfg(feature = "rdf-star")]
+use crate::TripleRef;
+use crate::{BlankNode, BlankNodeRef, GraphNameRef, QuadRef, SubjectRef, TermRef};
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+
+/// Returns the canonical identifiers of the blank nodes of the given quads in the order they have been issued.
+///
+/// If `max_work_factor` is set, each blank node can be the reference of at most `m^max_work_factor` calls to Hash N-Degree Quads,
+/// `m` being the number of blank nodes without a unique first degree hash.
+pub fn canonicalize_blank_nodes<'a>(
+    quads: impl IntoIterator<Item = QuadRef<'a>>,
+    max_work_factor: Option<u32>,
+) -> Result<Vec<(BlankNodeRef<'a>, BlankNode)>, CanonicalizationError> {
+    let mut state = CanonicalizationState {
+        quads_per_blank_node: HashMap::new(),
+        first_degree_hashes: HashMap::new(),
+        canonical_issuer: IdentifierIssuer::new("c14n"),
+        max_n_degree_calls: None,
+        n_degree_calls: RefCell::default(),
+    };
+    for quad in quads {
+        let mut blank_nodes = Vec::new();
+        add_subject_blank_nodes(quad.subject, 's', &mut blank_nodes);
+        add_term_blank_nodes(quad.object, 'o', &mut blank_nodes);
+        if let GraphNameRef::BlankNode(bnode) = quad.graph_name {
+            blank_nodes.push((bnode, 'g'));
+        }
+        for (i, (bnode, _)) in blank_nodes.iter().enumerate() {
+            // The quad is only added once per blank node
+            if blank_nodes[..i].iter().all(|(b, _)| b != bnode) {
+                state
+                    .quads_per_blank_node
+                    .entry(*bnode)
+                    .or_default()
+                    .push(quad);
+            }
+        }
+    }
+
+    let mut blank_nodes_per_hash = BTreeMap::<_, Vec<_>>::new();
+    for bnode in state.quads_per_blank_node.keys() {
+        let hash = state.hash_first_degree_quads(*bnode);
+        blank_nodes_per_hash.entry(hash).or_default().push(*bnode);
+    }
+    for (hash, bnodes) in &blank_nodes_per_hash {
+        state
+            .first_degree_hashes
+            .extend(bnodes.iter().map(|bnode| (*bnode, hash.clone())));
+    }
+
+    // The blank nodes with a unique first degree hash get their identifier first
+    let mut shared_hashes = Vec::new();
+    for (_, bnodes) in blank_nodes_per_hash {
+        if let [bnode] = bnodes.as_slice() {
+            state.canonical_issuer.issue(*bnode);
+        } else {
+            shared_hashes.push(bnodes);
+        }
+    }
+
+    if let Some(max_work_factor) = max_work_factor {
+        let shared_count =
+            u64::try_from(shared_hashes.iter().map(Vec::len).sum::<usize>()).unwrap_or(u64::MAX);
+        state.max_n_degree_calls = Some(shared_count.saturating_pow(max_work_factor));
+    }
+    for bnodes in shared_hashes {
+        let mut hash_path_list = Vec::new();
+        for bnode in bnodes {
+            if state.canonical_issuer.get(bnode).is_some() {
+                continue;
+            }
+            let mut temporary_issuer = IdentifierIssuer::new("b");
+            temporary_issuer.issue(bnode);
+            hash_path_list.push(state.hash_n_degree_quads(bnode, &temporary_issuer)?);
+        }
+        hash_path_list.sort_by(|(h1, _), (h2, _)| h1.cmp(h2));
+        for (_, issuer) in hash_path_list {
+            for bnode in issuer.issued {
+                state.canonical_issuer.issue(bnode);
+            }
+        }
+    }
+
+    Ok(state
+        .canonical_issuer
+        .issued
+        .into_iter()
+        .zip(state.canonical_issuer.identifiers)
+        .map(|(bnode, id)| (bnode, BlankNode::new_unchecked(id)))
+        .collect())
+}
+
+/// Serializes a quad in canonical N-Quads, using `label` to get the blank node identifiers.
+pub fn serialize_quad<'a>(
+    quad: QuadRef<'a>,
+    label: &impl Fn(BlankNodeRef<'a>) -> String,
+    output: &mut String,
+) {
+    write_subject(quad.subject, label, output);
+    output.push(' ');
+    output.push_str(&quad.predicate.to_string());
+    output.push(' ');
+    write_term(quad.object, label, output);
+    match quad.graph_name {
+        GraphNameRef::NamedNode(node) => {
+            output.push(' ');
+            output.push_str(&node.to_string());
+        }
+        GraphNameRef::BlankNode(bnode) => {
+            output.push_str(" _:");
+            output.push_str(&label(bnode));
+        }
+        GraphNameRef::DefaultGraph => (),
+    }
+    output.push_str(" .\n");
+}
+
+fn write_subject<'a>(
+    subject: SubjectRef<'a>,
+    label: &impl Fn(BlankNodeRef<'a>) -> String,
+    output: &mut String,
+) {
+    match subject {
+        SubjectRef::NamedNode(node) => output.push_str(&node.to_string()),
+        SubjectRef::BlankNode(bnode) => {
+            output.push_str("_:");
+            output.push_str(&label(bnode));
+        }
+        #[cfg(feature = "rdf-star")]
+        SubjectRef::Triple(triple) => write_triple(triple.as_ref(), label, output),
+    }
+}
+
+fn write_term<'a>(
+    term: TermRef<'a>,
+    label: &impl Fn(BlankNodeRef<'a>) -> String,
+    output: &mut String,
+) {
+    match term {
+        TermRef::NamedNode(node) => output.push_str(&node.to_string()),
+        TermRef::BlankNode(bnode) => {
+            output.push_str("_:");
+            output.push_str(&label(bnode));
+        }
+        TermRef::Literal(literal) => output.push_str(&literal.to_string()),
+        #[cfg(feature = "rdf-star")]
+        TermRef::Triple(triple) => write_triple(triple.as_ref(), label, output),
+    }
+}
+
+#[cfg(feature = "rdf-star")]
+fn write_triple<'a>(
+    triple: TripleRef<'a>,
+    label: &impl Fn(BlankNodeRef<'a>) -> String,
+    output: &mut String,
+) {
+    output.push_str("<< ");
+    write_subject(triple.subject, label, output);
+    output.push(' ');
+    output.push_str(&triple.predicate.to_string());
+    output.push(' ');
+    write_term(triple.object, label, output);
+    output.push_str(" >>");
+}
+
+/// Adds the blank nodes of the subject with their position, including the ones in quoted triples
+fn add_subject_blank_nodes<'a>(
+    subject: SubjectRef<'a>,
+    position: char,
+    blank_nodes: &mut Vec<(BlankNodeRef<'a>, char)>,
+) {
+    match subject {
+        SubjectRef::NamedNode(_) => (),
+        SubjectRef::BlankNode(bnode) => blank_nodes.push((bnode, position)),
+        #[cfg(feature = "rdf-star")]
+        SubjectRef::Triple(triple) => {
+            add_subject_blank_nodes(triple.subject.as_ref(), position, blank_nodes);
+            add_term_blank_nodes(triple.object.as_ref(), position, blank_nodes);
+        }
+    }
+}
+
+fn add_term_blank_nodes<'a>(
+    term: TermRef<'a>,
+    position: char,
+    blank_nodes: &mut Vec<(BlankNodeRef<'a>, char)>,
+) {
+    match term {
+        TermRef::NamedNode(_) | TermRef::Literal(_) => (),
+        TermRef::BlankNode(bnode) => blank_nodes.push((bnode, position)),
+        #[cfg(feature = "rdf-star")]
+        TermRef::Triple(triple) => {
+            add_subject_blank_nodes(triple.subject.as_ref(), position, blank_nodes);
+            add_term_blank_nodes(triple.object.as_ref(), position, blank_nodes);
+        }
+    }
+}
+
+struct CanonicalizationState<'a> {
+    quads_per_blank_node: HashMap<BlankNodeRef<'a>, Vec<QuadRef<'a>>>,
+    first_degree_hashes: HashMap<BlankNodeRef<'a>, String>,
+    canonical_issuer: IdentifierIssuer<'a>,
+    /// Limit on the number of calls to Hash N-Degree Quads per reference blank node to protect against poison datasets
+    max_n_degree_calls: Option<u64>,
+    n_degree_calls: RefCell<HashMap<BlankNodeRef<'a>, u64>>,
+}
+
+impl<'a> CanonicalizationState<'a> {
+    /// [Hash First Degree Quads](https://www.w3.org/TR/rdf-canon/#hash-1d-quads)
+    fn hash_first_degree_quads(&self, reference: BlankNodeRef<'a>) -> String {
+        let label =
+            |bnode: BlankNodeRef<'a>| String::from(if bnode == reference { "a" } else { "z" });
+        let mut nquads = self
+            .quads_per_blank_node
+            .get(&reference)
+            .into_iter()
+            .flatten()
+            .map(|quad| {
+                let mut nquad = String::new();
+                serialize_quad(*quad, &label, &mut nquad);
+                nquad
+            })
+            .collect::<Vec<_>>();
+        nquads.sort_unstable();
+        hash(&nquads.concat())
+    }
+
+    /// [Hash Related Blank Node](https://www.w3.org/TR/rdf-canon/#hash-related-blank-node)
+    fn hash_related_blank_node(
+        &self,
+        related: BlankNodeRef<'a>,
+        quad: QuadRef<'a>,
+        issuer: &IdentifierIssuer<'a>,
+        position: char,
+    ) -> String {
+        let mut input = String::new();
+        input.push(position);
+        if position != 'g' {
+            input.push_str(&quad.predicate.to_string());
+        }
+        if let Some(id) = self
+            .canonical_issuer
+            .get(related)
+            .or_else(|| issuer.get(related))
+        {
+            input.push_str("_:");
+            input.push_str(id);
+        } else if let Some(hash) = self.first_degree_hashes.get(&related) {
+            input.push_str(hash);
+        }
+        hash(&input)
+    }
+
+    /// [Hash N-Degree Quads](https://www.w3.org/TR/rdf-canon/#hash-nd-quads)
+    fn hash_n_degree_quads(
+        &self,
+        reference: BlankNodeRef<'a>,
+        issuer: &IdentifierIssuer<'a>,
+    ) -> Result<(String, IdentifierIssuer<'a>), CanonicalizationError> {
+        if let Some(max_n_degree_calls) = self.max_n_degree_calls {
+            let mut n_degree_calls = self.n_degree_calls.borrow_mut();
+            let calls = n_degree_calls.entry(reference).or_default();
+            if *calls >= max_n_degree_calls {
+                return Err(CanonicalizationError::too_much_work(
+                    reference,
+                    max_n_degree_calls,
+                ));
+            }
+            *calls += 1;
+        }
+        let mut issuer = issuer.clone();
+        let mut related_blank_nodes_per_hash = BTreeMap::<_, Vec<_>>::new();
+        for quad in self
+            .quads_per_blank_node
+            .get(&reference)
+            .into_iter()
+            .flatten()
+        {
+            let mut blank_nodes = Vec::new();
+            add_subject_blank_nodes(quad.subject, 's', &mut blank_nodes);
+            add_term_blank_nodes(quad.object, 'o', &mut blank_nodes);
+            if let GraphNameRef::BlankNode(bnode) = quad.graph_name {
+                blank_nodes.push((bnode, 'g'));
+            }
+            for (related, position) in blank_nodes {
+                if related != reference {
+                    related_blank_nodes_per_hash
+                        .entry(self.hash_related_blank_node(related, *quad, &issuer, position))
+                        .or_default()
+                        .push(related);
+                }
+            }
+        }
+
+        let mut data_to_hash = String::new();
+        for (related_hash, blank_nodes) in related_blank_nodes_per_hash {
+            data_to_hash.push_str(&related_hash);
+            let mut chosen_path = String::new();
+            let mut chosen_issuer = None;
+            let mut permutation = (0..blank_nodes.len()).collect::<Vec<_>>();
+            loop {
+                if let Some((path, issuer_copy)) =
+                    self.permutation_path(&permutation, &blank_nodes, &issuer, &chosen_path)?
+                {
+                    if chosen_path.is_empty() || path < chosen_path {
+                        chosen_path = path;
+                        chosen_issuer = Some(issuer_copy);
+                    }
+                }
+                if !next_permutation(&mut permutation) {
+                    break;
+                }
+            }
+            data_to_hash.push_str(&chosen_path);
+            if let Some(chosen_issuer) = chosen_issuer {
+                issuer = chosen_issuer;
+            }
+        }
+        Ok((hash(&data_to_hash), issuer))
+    }
+
+    /// Computes the path of a permutation in Hash N-Degree Quads, returns `None` if it is greater than the chosen path
+    fn permutation_path(
+        &self,
+        permutation: &[usize],
+        blank_nodes: &[BlankNodeRef<'a>],
+        issuer: &IdentifierIssuer<'a>,
+        chosen_path: &str,
+    ) -> Result<Option<(String, IdentifierIssuer<'a>)>, CanonicalizationError> {
+        let is_greater_than_chosen_path = |path: &str| {
+            !chosen_path.is_empty() && path.len() >= chosen_path.len() && path > chosen_path
+        };
+        let mut issuer_copy = issuer.clone();
+        let mut path = String::new();
+        let mut recursion_list = Vec::new();
+        for i in permutation {
+            let related = blank_nodes[*i];
+            path.push_str("_:");
+            if let Some(id) = self.canonical_issuer.get(related) {
+                path.push_str(id);
+            } else {
+                if issuer_copy.get(related).is_none() {
+                    recursion_list.push(related);
+                }
+                path.push_str(issuer_copy.issue(related));
+            }
+            if is_greater_than_chosen_path(&path) {
+                return Ok(None);
+            }
+        }
+        for related in recursion_list {
+            let (result_hash, result_issuer) = self.hash_n_degree_quads(related, &issuer_copy)?;
+            path.push_str("_:");
+            path.push_str(issuer_copy.issue(related));
+            path.push('<');
+            path.push_str(&result_hash);
+            path.push('>');
+            issuer_copy = result_issuer;
+            if is_greater_than_chosen_path(&path) {
+                return Ok(None);
+            }
+        }
+        Ok(Some((path, issuer_copy)))
+    }
+}
+
+/// [Identifier Issuer](https://www.w3.org/TR/rdf-canon/#issue-identifier)
+#[derive(Clone)]
+struct IdentifierIssuer<'a> {
+    prefix: &'static str,
+    /// The blank nodes in the order their identifier has been issued
+    issued: Vec<BlankNodeRef<'a>>,
+    identifiers: Vec<String>,
+    positions: HashMap<BlankNodeRef<'a>, usize>,
+}
+
+impl<'a> IdentifierIssuer<'a> {
+    fn new(prefix: &'static str) -> Self {
+        Self {
+            prefix,
+            issued: Vec::new(),
+            identifiers: Vec::new(),
+            positions: HashMap::new(),
+        }
+    }
+
+    fn get(&self, bnode: BlankNodeRef<'a>) -> Option<&str> {
+        Some(&self.identifiers[*self.positions.get(&bnode)?])
+    }
+
+    fn issue(&mut self, bnode: BlankNodeRef<'a>) -> &str {
+        let position = *self.positions.entry(bnode).or_insert_with(|| {
+            self.issued.push(bnode);
+            self.identifiers
+                .push(format!("{}{}", self.prefix, self.identifiers.len()));
+            self.identifiers.len() - 1
+        });
+        &self.identifiers[position]
+    }
+}
+
+/// Lexicographic next permutation, returns `false` if the permutation was the last one
+fn next_permutation(permutation: &mut [usize]) -> bool {
+    let Some(i) = permutation
+        .windows(2)
+        .rposition(|w| matches!(w, [a, b] if a < b))
+    else {
+        return false;
+    };
+    let Some(j) = permutation.iter().rposition(|v| *v > permutation[i]) else {
+        return false;
+    };
+    permutation.swap(i, j);
+    permutation[i + 1..].reverse();
+    true
+}
+
+fn hash(data: &str) -> String {
+    const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut result = String::with_capacity(64);
+    for byte in Sha256::digest(data.as_bytes()) {
+        result.push(char::from(HEX_DIGITS[usize::from(byte >> 4)]));
+        result.push(char::from(HEX_DIGITS[usize::from(byte & 0xF)]));
+    }
+    result
+}
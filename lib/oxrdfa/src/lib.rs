@@ -0,0 +1,17 @@
+#![doc = include_str!("../README.md")]
+#![doc(test(attr(deny(warnings))))]
+#![cfg_attr(docsrs, feature(doc_auto_cfg))]
+#![doc(html_favicon_url = "https://raw.githubusercontent.com/oxigraph/oxigraph/main/logo.svg")]
+#![doc(html_logo_url = "https://raw.githubusercontent.com/oxigraph/oxigraph/main/logo.svg")]
+
+mod error;
+mod parser;
+mod serializer;
+
+pub use error::{RdfaParseError, RdfaSyntaxError};
+#[cfg(feature = "async-tokio")]
+pub use parser::TokioAsyncReaderRdfaParser;
+pub use parser::{RdfaParser, RdfaPrefixesIter, ReaderRdfaParser, SliceRdfaParser};
+#[cfg(feature = "async-tokio")]
+pub use serializer::TokioAsyncWriterRdfaSerializer;
+pub use serializer::{RdfaSerializer, WriterRdfaSerializer};
@@ -0,0 +1,1592 @@
+use crate::error::{RdfaParseError, RdfaSyntaxError};
+use oxilangtag::LanguageTag;
+use oxiri::{Iri, IriParseError};
+use oxrdf::vocab::{rdf, xsd};
+use oxrdf::{BlankNode, Literal, NamedNode, NamedNodeRef, NamedOrBlankNode, Term, Triple};
+use oxsdatatypes::{Date, DateTime, Duration, GYear, GYearMonth, Time};
+use quick_xml::escape::{escape, resolve_html5_entity, unescape_with};
+use quick_xml::events::attributes::Attribute;
+use quick_xml::events::{BytesStart, BytesText, Event};
+use quick_xml::Reader;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::{BufReader, Read};
+use std::str::FromStr;
+use std::sync::Arc;
+#[cfg(feature = "async-tokio")]
+use tokio::io::{AsyncRead, BufReader as AsyncBufReader};
+
+/// A [RDFa 1.1 Core](https://www.w3.org/TR/rdfa-core/) streaming parser for HTML and XHTML documents.
+///
+/// It reads the file in streaming.
+/// It does not keep data in memory except a stack for handling the nested HTML elements,
+/// the text of the elements whose content is the value of a property and the lists built with `@inlist`.
+///
+/// The document is read with a lenient XML tokenizer that supports the most common HTML constructs:
+/// void elements like `<br>`, missing end tags, unquoted attribute values, HTML entities and `<script>` and `<style>` content.
+/// The [HTML+RDFa](https://www.w3.org/TR/html-rdfa/) extensions are supported:
+/// `<base href>`, `@lang`, `@datetime` and `<time>` elements, and the removal of the terms from `@rel` and `@rev` when `@property` is set.
+///
+/// A base IRI is required to resolve the document IRI, the subject of the properties set on the root element.
+///
+/// Invalid `@href` and `@src` values and invalid language tags are ignored.
+///
+/// Count the number of people:
+/// ```
+/// use oxrdf::vocab::rdf;
+/// use oxrdf::NamedNodeRef;
+/// use oxrdfa::RdfaParser;
+///
+/// let file = br#"<!DOCTYPE html>
+/// <html>
+///   <body vocab="http://schema.org/">
+///     <div typeof="Person" resource="http://example.com/foo">
+///       <span property="name">Foo</span>
+///       <a property="knows" typeof="Person" href="http://example.com/bar">Bar</a>
+///     </div>
+///   </body>
+/// </html>"#;
+///
+/// let schema_person = NamedNodeRef::new("http://schema.org/Person")?;
+/// let mut count = 0;
+/// for triple in RdfaParser::new()
+///     .with_base_iri("http://example.com/page.html")?
+///     .for_reader(file.as_ref())
+/// {
+///     let triple = triple?;
+///     if triple.predicate == rdf::TYPE && triple.object == schema_person.into() {
+///         count += 1;
+///     }
+/// }
+/// assert_eq!(2, count);
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[derive(Default, Clone)]
+#[must_use]
+pub struct RdfaParser {
+    unchecked: bool,
+    base: Option<Iri<String>>,
+}
+
+impl RdfaParser {
+    /// Builds a new [`RdfaParser`].
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Assumes the file is valid to make parsing faster.
+    ///
+    /// It will skip some validations.
+    ///
+    /// Note that if the file is actually not valid, broken RDF might be emitted by the parser.
+    #[inline]
+    pub fn unchecked(mut self) -> Self {
+        self.unchecked = true;
+        self
+    }
+
+    /// Sets the document IRI.
+    ///
+    /// It might be overridden by a `<base href>` element.
+    #[inline]
+    pub fn with_base_iri(mut self, base_iri: impl Into<String>) -> Result<Self, IriParseError> {
+        self.base = Some(Iri::parse(base_iri.into())?);
+        Ok(self)
+    }
+
+    /// Parses a HTML or XHTML file from a [`Read`] implementation.
+    ///
+    /// Count the number of people:
+    /// ```
+    /// use oxrdf::vocab::rdf;
+    /// use oxrdf::NamedNodeRef;
+    /// use oxrdfa::RdfaParser;
+    ///
+    /// let file = br#"<html prefix="schema: http://schema.org/">
+    /// <p about="http://example.com/foo" typeof="schema:Person">
+    ///   <span property="schema:name">Foo</span><br>
+    /// </p>
+    /// </html>"#;
+    ///
+    /// let schema_person = NamedNodeRef::new("http://schema.org/Person")?;
+    /// let mut count = 0;
+    /// for triple in RdfaParser::new().for_reader(file.as_ref()) {
+    ///     let triple = triple?;
+    ///     if triple.predicate == rdf::TYPE && triple.object == schema_person.into() {
+    ///         count += 1;
+    ///     }
+    /// }
+    /// assert_eq!(1, count);
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn for_reader<R: Read>(self, reader: R) -> ReaderRdfaParser<R> {
+        ReaderRdfaParser {
+            results: Vec::new(),
+            parser: self.parse(BufReader::new(reader)),
+            reader_buffer: Vec::default(),
+        }
+    }
+
+    /// Parses a HTML or XHTML file from a [`AsyncRead`] implementation.
+    ///
+    /// Count the number of people:
+    /// ```
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use oxrdf::vocab::rdf;
+    /// use oxrdf::NamedNodeRef;
+    /// use oxrdfa::RdfaParser;
+    ///
+    /// let file = br#"<html prefix="schema: http://schema.org/">
+    /// <p about="http://example.com/foo" typeof="schema:Person">
+    ///   <span property="schema:name">Foo</span><br>
+    /// </p>
+    /// </html>"#;
+    ///
+    /// let schema_person = NamedNodeRef::new("http://schema.org/Person")?;
+    /// let mut count = 0;
+    /// let mut parser = RdfaParser::new().for_tokio_async_reader(file.as_ref());
+    /// while let Some(triple) = parser.next().await {
+    ///     let triple = triple?;
+    ///     if triple.predicate == rdf::TYPE && triple.object == schema_person.into() {
+    ///         count += 1;
+    ///     }
+    /// }
+    /// assert_eq!(1, count);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "async-tokio")]
+    pub fn for_tokio_async_reader<R: AsyncRead + Unpin>(
+        self,
+        reader: R,
+    ) -> TokioAsyncReaderRdfaParser<R> {
+        TokioAsyncReaderRdfaParser {
+            results: Vec::new(),
+            parser: self.parse(AsyncBufReader::new(reader)),
+            reader_buffer: Vec::default(),
+        }
+    }
+
+    /// Parses a HTML or XHTML file from a byte slice.
+    ///
+    /// Count the number of people:
+    /// ```
+    /// use oxrdf::vocab::rdf;
+    /// use oxrdf::NamedNodeRef;
+    /// use oxrdfa::RdfaParser;
+    ///
+    /// let file = br#"<html prefix="schema: http://schema.org/">
+    /// <p about="http://example.com/foo" typeof="schema:Person">
+    ///   <span property="schema:name">Foo</span><br>
+    /// </p>
+    /// </html>"#;
+    ///
+    /// let schema_person = NamedNodeRef::new("http://schema.org/Person")?;
+    /// let mut count = 0;
+    /// for triple in RdfaParser::new().for_slice(file) {
+    ///     let triple = triple?;
+    ///     if triple.predicate == rdf::TYPE && triple.object == schema_person.into() {
+    ///         count += 1;
+    ///     }
+    /// }
+    /// assert_eq!(1, count);
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn for_slice(self, slice: &[u8]) -> SliceRdfaParser<'_> {
+        SliceRdfaParser {
+            results: Vec::new(),
+            parser: self.parse(slice),
+            reader_buffer: Vec::default(),
+        }
+    }
+
+    fn parse<T>(&self, reader: T) -> InternalRdfaParser<T> {
+        let mut reader = Reader::from_reader(reader);
+        let config = reader.config_mut();
+        config.check_end_names = false;
+        config.allow_unmatched_ends = true;
+        let document = self
+            .base
+            .as_ref()
+            .map(|base| NamedOrBlankNode::from(NamedNode::new_unchecked(base.as_str())));
+        InternalRdfaParser {
+            reader,
+            base_iri: self.base.clone(),
+            document_context: Context {
+                parent_subject: document.clone(),
+                parent_object: document,
+                prefixes: Arc::default(),
+                vocabulary: None,
+                language: None,
+                incomplete_triples: Arc::default(),
+                list_mapping: 0,
+            },
+            stack: Vec::new(),
+            list_mappings: vec![Vec::new()],
+            blank_nodes: HashMap::new(),
+            raw_text_element: None,
+            content_collectors: 0,
+            errors: Vec::new(),
+            is_end: false,
+            unchecked: self.unchecked,
+        }
+    }
+}
+
+/// Parses a HTML or XHTML file from a [`Read`] implementation.
+///
+/// Can be built using [`RdfaParser::for_reader`].
+///
+/// Count the number of people:
+/// ```
+/// use oxrdf::vocab::rdf;
+/// use oxrdf::NamedNodeRef;
+/// use oxrdfa::RdfaParser;
+///
+/// let file = br#"<html prefix="schema: http://schema.org/">
+/// <p about="http://example.com/foo" typeof="schema:Person">
+///   <span property="schema:name">Foo</span><br>
+/// </p>
+/// </html>"#;
+///
+/// let schema_person = NamedNodeRef::new("http://schema.org/Person")?;
+/// let mut count = 0;
+/// for triple in RdfaParser::new().for_reader(file.as_ref()) {
+///     let triple = triple?;
+///     if triple.predicate == rdf::TYPE && triple.object == schema_person.into() {
+///         count += 1;
+///     }
+/// }
+/// assert_eq!(1, count);
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[must_use]
+pub struct ReaderRdfaParser<R: Read> {
+    results: Vec<Triple>,
+    parser: InternalRdfaParser<BufReader<R>>,
+    reader_buffer: Vec<u8>,
+}
+
+impl<R: Read> Iterator for ReaderRdfaParser<R> {
+    type Item = Result<Triple, RdfaParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(error) = self.parser.errors.pop() {
+                return Some(Err(error.into()));
+            } else if let Some(triple) = self.results.pop() {
+                return Some(Ok(triple));
+            } else if self.parser.is_end {
+                return None;
+            }
+            if let Err(e) = self.parse_step() {
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+impl<R: Read> ReaderRdfaParser<R> {
+    /// The prefixes declared with `@prefix` and `xmlns:` attributes on the current element and its ancestors.
+    ///
+    /// The prefixes of the [RDFa initial context](https://www.w3.org/2011/rdfa-context/rdfa-1.1) are not returned.
+    pub fn prefixes(&self) -> RdfaPrefixesIter<'_> {
+        self.parser.prefixes()
+    }
+
+    /// The base IRI, if set by [`RdfaParser::with_base_iri`] or a `<base href>` element.
+    pub fn base_iri(&self) -> Option<&str> {
+        Some(self.parser.base_iri.as_ref()?.as_str())
+    }
+
+    pub fn buffer_position(&self) -> u64 {
+        self.parser.reader.buffer_position()
+    }
+
+    fn parse_step(&mut self) -> Result<(), RdfaParseError> {
+        self.reader_buffer.clear();
+        let event = self
+            .parser
+            .reader
+            .read_event_into(&mut self.reader_buffer)?;
+        self.parser.parse_event(event, &mut self.results);
+        // We reverse the results to pop them in document order
+        self.results.reverse();
+        Ok(())
+    }
+}
+
+/// Parses a HTML or XHTML file from a [`AsyncRead`] implementation.
+///
+/// Can be built using [`RdfaParser::for_tokio_async_reader`].
+///
+/// Count the number of people:
+/// ```
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use oxrdf::vocab::rdf;
+/// use oxrdf::NamedNodeRef;
+/// use oxrdfa::RdfaParser;
+///
+/// let file = br#"<html prefix="schema: http://schema.org/">
+/// <p about="http://example.com/foo" typeof="schema:Person">
+///   <span property="schema:name">Foo</span><br>
+/// </p>
+/// </html>"#;
+///
+/// let schema_person = NamedNodeRef::new("http://schema.org/Person")?;
+/// let mut count = 0;
+/// let mut parser = RdfaParser::new().for_tokio_async_reader(file.as_ref());
+/// while let Some(triple) = parser.next().await {
+///     let triple = triple?;
+///     if triple.predicate == rdf::TYPE && triple.object == schema_person.into() {
+///         count += 1;
+///     }
+/// }
+/// assert_eq!(1, count);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "async-tokio")]
+#[must_use]
+pub struct TokioAsyncReaderRdfaParser<R: AsyncRead + Unpin> {
+    results: Vec<Triple>,
+    parser: InternalRdfaParser<AsyncBufReader<R>>,
+    reader_buffer: Vec<u8>,
+}
+
+#[cfg(feature = "async-tokio")]
+impl<R: AsyncRead + Unpin> TokioAsyncReaderRdfaParser<R> {
+    pub async fn next(&mut self) -> Option<Result<Triple, RdfaParseError>> {
+        loop {
+            if let Some(error) = self.parser.errors.pop() {
+                return Some(Err(error.into()));
+            } else if let Some(triple) = self.results.pop() {
+                return Some(Ok(triple));
+            } else if self.parser.is_end {
+                return None;
+            }
+            if let Err(e) = self.parse_step().await {
+                return Some(Err(e));
+            }
+        }
+    }
+
+    /// The prefixes declared with `@prefix` and `xmlns:` attributes on the current element and its ancestors.
+    ///
+    /// The prefixes of the [RDFa initial context](https://www.w3.org/2011/rdfa-context/rdfa-1.1) are not returned.
+    pub fn prefixes(&self) -> RdfaPrefixesIter<'_> {
+        self.parser.prefixes()
+    }
+
+    /// The base IRI, if set by [`RdfaParser::with_base_iri`] or a `<base href>` element.
+    pub fn base_iri(&self) -> Option<&str> {
+        Some(self.parser.base_iri.as_ref()?.as_str())
+    }
+
+    pub fn buffer_position(&self) -> u64 {
+        self.parser.reader.buffer_position()
+    }
+
+    async fn parse_step(&mut self) -> Result<(), RdfaParseError> {
+        self.reader_buffer.clear();
+        let event = self
+            .parser
+            .reader
+            .read_event_into_async(&mut self.reader_buffer)
+            .await?;
+        self.parser.parse_event(event, &mut self.results);
+        // We reverse the results to pop them in document order
+        self.results.reverse();
+        Ok(())
+    }
+}
+
+/// Parses a HTML or XHTML file from a byte slice.
+///
+/// Can be built using [`RdfaParser::for_slice`].
+///
+/// Count the number of people:
+/// ```
+/// use oxrdf::vocab::rdf;
+/// use oxrdf::NamedNodeRef;
+/// use oxrdfa::RdfaParser;
+///
+/// let file = br#"<html prefix="schema: http://schema.org/">
+/// <p about="http://example.com/foo" typeof="schema:Person">
+///   <span property="schema:name">Foo</span><br>
+/// </p>
+/// </html>"#;
+///
+/// let schema_person = NamedNodeRef::new("http://schema.org/Person")?;
+/// let mut count = 0;
+/// for triple in RdfaParser::new().for_slice(file) {
+///     let triple = triple?;
+///     if triple.predicate == rdf::TYPE && triple.object == schema_person.into() {
+///         count += 1;
+///     }
+/// }
+/// assert_eq!(1, count);
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[must_use]
+pub struct SliceRdfaParser<'a> {
+    results: Vec<Triple>,
+    parser: InternalRdfaParser<&'a [u8]>,
+    reader_buffer: Vec<u8>,
+}
+
+impl Iterator for SliceRdfaParser<'_> {
+    type Item = Result<Triple, RdfaSyntaxError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(error) = self.parser.errors.pop() {
+                return Some(Err(error));
+            } else if let Some(triple) = self.results.pop() {
+                return Some(Ok(triple));
+            } else if self.parser.is_end {
+                return None;
+            }
+            if let Err(RdfaParseError::Syntax(e)) = self.parse_step() {
+                // I/O errors can't happen
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+impl SliceRdfaParser<'_> {
+    /// The prefixes declared with `@prefix` and `xmlns:` attributes on the current element and its ancestors.
+    ///
+    /// The prefixes of the [RDFa initial context](https://www.w3.org/2011/rdfa-context/rdfa-1.1) are not returned.
+    pub fn prefixes(&self) -> RdfaPrefixesIter<'_> {
+        self.parser.prefixes()
+    }
+
+    /// The base IRI, if set by [`RdfaParser::with_base_iri`] or a `<base href>` element.
+    pub fn base_iri(&self) -> Option<&str> {
+        Some(self.parser.base_iri.as_ref()?.as_str())
+    }
+
+    pub fn buffer_position(&self) -> u64 {
+        self.parser.reader.buffer_position()
+    }
+
+    fn parse_step(&mut self) -> Result<(), RdfaParseError> {
+        self.reader_buffer.clear();
+        let event = self
+            .parser
+            .reader
+            .read_event_into(&mut self.reader_buffer)?;
+        self.parser.parse_event(event, &mut self.results);
+        // We reverse the results to pop them in document order
+        self.results.reverse();
+        Ok(())
+    }
+}
+
+/// Iterator on the file prefixes.
+///
+/// See [`ReaderRdfaParser::prefixes`].
+pub struct RdfaPrefixesIter<'a> {
+    inner: std::collections::hash_map::Iter<'a, String, String>,
+}
+
+impl<'a> Iterator for RdfaPrefixesIter<'a> {
+    type Item = (&'a str, &'a str);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (name, iri) = self.inner.next()?;
+        Some((name, iri))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+const RDFA_USES_VOCABULARY: NamedNodeRef<'_> =
+    NamedNodeRef::new_unchecked("http://www.w3.org/ns/rdfa#usesVocabulary");
+const XHV_NAMESPACE: &str = "http://www.w3.org/1999/xhtml/vocab#";
+
+/// The prefixes of the [RDFa 1.1 initial context](https://www.w3.org/2011/rdfa-context/rdfa-1.1), sorted by name
+const INITIAL_PREFIXES: [(&str, &str); 46] = [
+    ("as", "https://www.w3.org/ns/activitystreams#"),
+    ("cc", "http://creativecommons.org/ns#"),
+    ("csvw", "http://www.w3.org/ns/csvw#"),
+    ("ctag", "http://commontag.org/ns#"),
+    ("dc", "http://purl.org/dc/terms/"),
+    ("dc11", "http://purl.org/dc/elements/1.1/"),
+    ("dcat", "http://www.w3.org/ns/dcat#"),
+    ("dcterms", "http://purl.org/dc/terms/"),
+    ("dqv", "http://www.w3.org/ns/dqv#"),
+    ("duv", "https://www.w3.org/ns/duv#"),
+    ("foaf", "http://xmlns.com/foaf/0.1/"),
+    ("gr", "http://purl.org/goodrelations/v1#"),
+    ("grddl", "http://www.w3.org/2003/g/data-view#"),
+    ("ical", "http://www.w3.org/2002/12/cal/icaltzd#"),
+    ("jsonld", "http://www.w3.org/ns/json-ld#"),
+    ("ldp", "http://www.w3.org/ns/ldp#"),
+    ("ma", "http://www.w3.org/ns/ma-ont#"),
+    ("oa", "http://www.w3.org/ns/oa#"),
+    ("odrl", "http://www.w3.org/ns/odrl/2/"),
+    ("og", "http://ogp.me/ns#"),
+    ("org", "http://www.w3.org/ns/org#"),
+    ("owl", "http://www.w3.org/2002/07/owl#"),
+    ("prov", "http://www.w3.org/ns/prov#"),
+    ("qb", "http://purl.org/linked-data/cube#"),
+    ("rdf", "http://www.w3.org/1999/02/22-rdf-syntax-ns#"),
+    ("rdfa", "http://www.w3.org/ns/rdfa#"),
+    ("rdfs", "http://www.w3.org/2000/01/rdf-schema#"),
+    ("rev", "http://purl.org/stuff/rev#"),
+    ("rif", "http://www.w3.org/2007/rif#"),
+    ("rr", "http://www.w3.org/ns/r2rml#"),
+    ("schema", "http://schema.org/"),
+    ("sd", "http://www.w3.org/ns/sparql-service-description#"),
+    ("sioc", "http://rdfs.org/sioc/ns#"),
+    ("skos", "http://www.w3.org/2004/02/skos/core#"),
+    ("skosxl", "http://www.w3.org/2008/05/skos-xl#"),
+    ("sosa", "http://www.w3.org/ns/sosa/"),
+    ("ssn", "http://www.w3.org/ns/ssn/"),
+    ("time", "http://www.w3.org/2006/time#"),
+    ("v", "http://rdf.data-vocabulary.org/#"),
+    ("vcard", "http://www.w3.org/2006/vcard/ns#"),
+    ("void", "http://rdfs.org/ns/void#"),
+    ("wdr", "http://www.w3.org/2007/05/powder#"),
+    ("wdrs", "http://www.w3.org/2007/05/powder-s#"),
+    ("xhv", "http://www.w3.org/1999/xhtml/vocab#"),
+    ("xml", "http://www.w3.org/XML/1998/namespace"),
+    ("xsd", "http://www.w3.org/2001/XMLSchema#"),
+];
+
+/// The terms of the [RDFa 1.1 initial context](https://www.w3.org/2011/rdfa-context/rdfa-1.1)
+const INITIAL_TERMS: [(&str, &str); 3] = [
+    (
+        "describedby",
+        "http://www.w3.org/2007/05/powder-s#describedby",
+    ),
+    ("license", "http://www.w3.org/1999/xhtml/vocab#license"),
+    ("role", "http://www.w3.org/1999/xhtml/vocab#role"),
+];
+
+/// The HTML elements without content and end tag
+const VOID_ELEMENTS: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// The HTML elements whose content is not HTML
+const RAW_TEXT_ELEMENTS: [&str; 2] = ["script", "style"];
+
+/// The evaluation context passed by an element to its children
+#[derive(Clone)]
+struct Context {
+    parent_subject: Option<NamedOrBlankNode>,
+    parent_object: Option<NamedOrBlankNode>,
+    /// The prefixes declared in the document
+    prefixes: Arc<HashMap<String, String>>,
+    vocabulary: Option<String>,
+    language: Option<String>,
+    incomplete_triples: Arc<[IncompleteTriple]>,
+    /// The index of the list mapping in [`InternalRdfaParser::list_mappings`]
+    list_mapping: usize,
+}
+
+#[derive(Clone)]
+enum IncompleteTriple {
+    Forward(NamedNode),
+    Backward(NamedNode),
+    /// The list `list` of the list mapping `mapping`
+    List {
+        mapping: usize,
+        list: usize,
+    },
+}
+
+/// For each predicate, the list elements, `None` being a slot for a value not known yet
+type ListMapping = Vec<(NamedNode, Vec<Option<Term>>)>;
+
+struct Element {
+    /// The lowercase element name
+    name: String,
+    /// The evaluation context of the children
+    context: Context,
+    /// The subject of the lists of the list mapping created by this element
+    list_owner: Option<NamedOrBlankNode>,
+    /// The property whose value is built from the element content
+    content_property: Option<ContentProperty>,
+    /// The text content of the element, if needed by the content property
+    text: Option<String>,
+    /// The XML content of the element, if needed by the content property
+    xml: Option<String>,
+}
+
+struct ContentProperty {
+    subject: NamedOrBlankNode,
+    predicates: Vec<NamedNode>,
+    kind: ContentKind,
+    /// The list slots to fill if `@inlist` is set
+    list_slots: Vec<(usize, usize, usize)>,
+    inlist: bool,
+}
+
+enum ContentKind {
+    Plain {
+        language: Option<String>,
+    },
+    Typed {
+        datatype: NamedNode,
+    },
+    Xml {
+        datatype: NamedNode,
+    },
+    /// A `<time>` element whose datatype is guessed from its content
+    Time {
+        language: Option<String>,
+    },
+}
+
+/// The RDFa attributes of an element
+#[derive(Default)]
+struct Attributes {
+    about: Option<String>,
+    content: Option<String>,
+    datatype: Option<String>,
+    datetime: Option<String>,
+    href: Option<String>,
+    inlist: bool,
+    lang: Option<String>,
+    prefix: Option<String>,
+    property: Option<String>,
+    rel: Option<String>,
+    resource: Option<String>,
+    rev: Option<String>,
+    src: Option<String>,
+    r#typeof: Option<String>,
+    vocab: Option<String>,
+    xml_lang: Option<String>,
+    xmlns: Vec<(String, String)>,
+}
+
+struct InternalRdfaParser<T> {
+    reader: Reader<T>,
+    base_iri: Option<Iri<String>>,
+    /// The evaluation context of the root element
+    document_context: Context,
+    /// The elements being read, the current one being the last
+    stack: Vec<Element>,
+    list_mappings: Vec<ListMapping>,
+    blank_nodes: HashMap<String, BlankNode>,
+    /// The name of the current element if its content is not HTML
+    raw_text_element: Option<String>,
+    /// The number of elements in the stack collecting their content
+    content_collectors: usize,
+    errors: Vec<RdfaSyntaxError>,
+    is_end: bool,
+    unchecked: bool,
+}
+
+impl<T> InternalRdfaParser<T> {
+    fn prefixes(&self) -> RdfaPrefixesIter<'_> {
+        RdfaPrefixesIter {
+            inner: self.context().prefixes.iter(),
+        }
+    }
+
+    fn context(&self) -> &Context {
+        self.stack
+            .last()
+            .map_or(&self.document_context, |element| &element.context)
+    }
+
+    fn parse_event(&mut self, event: Event<'_>, results: &mut Vec<Triple>) {
+        if let Err(e) = self.parse_event_inner(event, results) {
+            self.errors.push(e);
+        }
+    }
+
+    fn parse_event_inner(
+        &mut self,
+        event: Event<'_>,
+        results: &mut Vec<Triple>,
+    ) -> Result<(), RdfaSyntaxError> {
+        match event {
+            Event::Start(event) => {
+                let name = self.element_name(&event)?;
+                if self.raw_text_element.is_some() {
+                    return Ok(());
+                }
+                if VOID_ELEMENTS.contains(&name.as_str()) {
+                    self.parse_start(name, &event, true, results)
+                } else {
+                    if RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+                        self.raw_text_element = Some(name.clone());
+                    }
+                    self.parse_start(name, &event, false, results)
+                }
+            }
+            Event::Empty(event) => {
+                let name = self.element_name(&event)?;
+                if self.raw_text_element.is_some() {
+                    return Ok(());
+                }
+                self.parse_start(name, &event, true, results)
+            }
+            Event::End(event) => {
+                let name = self
+                    .reader
+                    .decoder()
+                    .decode(event.local_name().as_ref())
+                    .map_err(RdfaSyntaxError::xml)?
+                    .to_ascii_lowercase();
+                if let Some(raw_text_element) = &self.raw_text_element {
+                    if *raw_text_element != name {
+                        return Ok(());
+                    }
+                    self.raw_text_element = None;
+                }
+                // We close all the elements up to the matching start tag, if it exists
+                if let Some(position) = self.stack.iter().rposition(|e| e.name == name) {
+                    while self.stack.len() > position {
+                        self.parse_end(results);
+                    }
+                }
+                Ok(())
+            }
+            Event::Text(event) => {
+                if self.content_collectors > 0 {
+                    let text = self.text(&event)?;
+                    self.push_text(&text);
+                }
+                Ok(())
+            }
+            Event::CData(event) => {
+                if self.content_collectors > 0 {
+                    let text = self
+                        .reader
+                        .decoder()
+                        .decode(&event)
+                        .map_err(RdfaSyntaxError::xml)?
+                        .into_owned();
+                    self.push_text(&text);
+                }
+                Ok(())
+            }
+            Event::Eof => {
+                while !self.stack.is_empty() {
+                    self.parse_end(results);
+                }
+                self.is_end = true;
+                Ok(())
+            }
+            Event::Comment(_) | Event::PI(_) | Event::Decl(_) | Event::DocType(_) => Ok(()),
+        }
+    }
+
+    fn element_name(&self, event: &BytesStart<'_>) -> Result<String, RdfaSyntaxError> {
+        Ok(self
+            .reader
+            .decoder()
+            .decode(event.local_name().as_ref())
+            .map_err(RdfaSyntaxError::xml)?
+            .to_ascii_lowercase())
+    }
+
+    fn text(&self, event: &BytesText<'_>) -> Result<String, RdfaSyntaxError> {
+        let text = self
+            .reader
+            .decoder()
+            .decode(event)
+            .map_err(RdfaSyntaxError::xml)?;
+        // HTML allows unescaped &
+        Ok(unescape_with(&text, resolve_html5_entity)
+            .unwrap_or(Cow::Borrowed(&text))
+            .into_owned())
+    }
+
+    fn attribute_value(&self, attribute: &Attribute<'_>) -> Result<String, RdfaSyntaxError> {
+        let value = self
+            .reader
+            .decoder()
+            .decode(&attribute.value)
+            .map_err(RdfaSyntaxError::xml)?;
+        // HTML allows unescaped &
+        Ok(unescape_with(&value, resolve_html5_entity)
+            .unwrap_or(Cow::Borrowed(&value))
+            .into_owned())
+    }
+
+    fn push_text(&mut self, text: &str) {
+        for element in &mut self.stack {
+            if let Some(content) = &mut element.text {
+                content.push_str(text);
+            }
+            if let Some(content) = &mut element.xml {
+                content.push_str(&escape(text));
+            }
+        }
+    }
+
+    fn parse_attributes(&self, event: &BytesStart<'_>) -> Result<Attributes, RdfaSyntaxError> {
+        let mut attributes = Attributes::default();
+        for attribute in event.html_attributes() {
+            let attribute = attribute.map_err(RdfaSyntaxError::xml)?;
+            let key = self
+                .reader
+                .decoder()
+                .decode(attribute.key.as_ref())
+                .map_err(RdfaSyntaxError::xml)?
+                .to_ascii_lowercase();
+            let value = self.attribute_value(&attribute)?;
+            match key.as_str() {
+                "about" => attributes.about = Some(value),
+                "content" => attributes.content = Some(value),
+                "datatype" => attributes.datatype = Some(value),
+                "datetime" => attributes.datetime = Some(value),
+                "href" => attributes.href = Some(value),
+                "inlist" => attributes.inlist = true,
+                "lang" => attributes.lang = Some(value),
+                "prefix" => attributes.prefix = Some(value),
+                "property" => attributes.property = Some(value),
+                "rel" => attributes.rel = Some(value),
+                "resource" => attributes.resource = Some(value),
+                "rev" => attributes.rev = Some(value),
+                "src" => attributes.src = Some(value),
+                "typeof" => attributes.r#typeof = Some(value),
+                "vocab" => attributes.vocab = Some(value),
+                "xml:lang" => attributes.xml_lang = Some(value),
+                _ => {
+                    if let Some(prefix) = key.strip_prefix("xmlns:") {
+                        attributes.xmlns.push((prefix.into(), value));
+                    }
+                }
+            }
+        }
+        // HTML+RDFa: the terms are removed from @rel and @rev if @property is set
+        if attributes.property.is_some() {
+            for value in [&mut attributes.rel, &mut attributes.rev] {
+                if let Some(v) = value {
+                    let filtered = v
+                        .split_ascii_whitespace()
+                        .filter(|v| v.contains(':'))
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    *value = (!filtered.is_empty()).then_some(filtered);
+                }
+            }
+        }
+        Ok(attributes)
+    }
+
+    /// The [RDFa processing sequence](https://www.w3.org/TR/rdfa-core/#s_sequence) for a start tag
+    #[allow(clippy::too_many_lines)]
+    fn parse_start(
+        &mut self,
+        name: String,
+        event: &BytesStart<'_>,
+        is_empty: bool,
+        results: &mut Vec<Triple>,
+    ) -> Result<(), RdfaSyntaxError> {
+        if self.content_collectors > 0 {
+            let raw = self
+                .reader
+                .decoder()
+                .decode(event)
+                .map_err(RdfaSyntaxError::xml)?;
+            let raw = raw.trim_end_matches('/');
+            for element in &mut self.stack {
+                if let Some(xml) = &mut element.xml {
+                    xml.push('<');
+                    xml.push_str(raw);
+                    xml.push('>');
+                }
+            }
+        }
+        let attributes = match self.parse_attributes(event) {
+            Ok(attributes) => attributes,
+            Err(e) => {
+                // We still push the element to keep the stack consistent
+                self.errors.push(e);
+                Attributes::default()
+            }
+        };
+        let parent = self.context().clone();
+        let is_root = self.stack.is_empty();
+        let is_head_or_body = name == "head" || name == "body";
+
+        // HTML+RDFa: the <base> element sets the document IRI
+        if name == "base" {
+            if let Some(href) = &attributes.href {
+                self.set_base_iri(href);
+            }
+        }
+
+        // Step 2: @vocab
+        let mut vocabulary = parent.vocabulary.clone();
+        if let Some(vocab) = &attributes.vocab {
+            if vocab.is_empty() {
+                vocabulary = None;
+            } else if let Some(vocab) = self.resolve_iri(vocab) {
+                if let Some(document) = self.document_iri() {
+                    results.push(Triple::new(document, RDFA_USES_VOCABULARY, vocab.clone()));
+                }
+                vocabulary = Some(vocab.into_string());
+            }
+        }
+
+        // Step 3: prefixes
+        let mut prefixes = Arc::clone(&parent.prefixes);
+        if !attributes.xmlns.is_empty() || attributes.prefix.is_some() {
+            let mut new_prefixes = HashMap::clone(&prefixes);
+            for (prefix, iri) in &attributes.xmlns {
+                self.add_prefix(&mut new_prefixes, prefix, iri);
+            }
+            if let Some(prefix) = &attributes.prefix {
+                let mut tokens = prefix.split_ascii_whitespace();
+                while let Some(name) = tokens.next() {
+                    let Some(name) = name.strip_suffix(':') else {
+                        self.errors.push(RdfaSyntaxError::msg(format!(
+                            "Invalid prefix declaration '{name}' in @prefix"
+                        )));
+                        continue;
+                    };
+                    let Some(iri) = tokens.next() else {
+                        self.errors.push(RdfaSyntaxError::msg(format!(
+                            "The prefix '{name}' has no IRI in @prefix"
+                        )));
+                        break;
+                    };
+                    self.add_prefix(&mut new_prefixes, name, iri);
+                }
+            }
+            prefixes = Arc::new(new_prefixes);
+        }
+
+        // Step 4: language
+        let mut language = parent.language.clone();
+        if let Some(lang) = attributes.xml_lang.as_ref().or(attributes.lang.as_ref()) {
+            language = if lang.is_empty() || LanguageTag::parse(lang.as_str()).is_err() {
+                None
+            } else {
+                Some(lang.to_ascii_lowercase())
+            };
+        }
+
+        let about = attributes
+            .about
+            .as_deref()
+            .and_then(|v| self.resolve_safe_curie_or_curie_or_iri(v, &prefixes));
+        let resource = attributes
+            .resource
+            .as_deref()
+            .and_then(|v| self.resolve_safe_curie_or_curie_or_iri(v, &prefixes));
+        let href = attributes
+            .href
+            .as_deref()
+            .and_then(|v| self.resolve_html_iri(v));
+        let src = attributes
+            .src
+            .as_deref()
+            .and_then(|v| self.resolve_html_iri(v));
+        let rel = attributes.rel.as_deref().map_or_else(Vec::new, |v| {
+            self.resolve_predicates(v, &prefixes, vocabulary.as_deref())
+        });
+        let rev = attributes.rev.as_deref().map_or_else(Vec::new, |v| {
+            self.resolve_predicates(v, &prefixes, vocabulary.as_deref())
+        });
+        let has_rel_or_rev = attributes.rel.is_some() || attributes.rev.is_some();
+        let has_typeof = attributes.r#typeof.is_some();
+
+        let mut new_subject = None;
+        let mut current_object = None;
+        let mut typed_resource = None;
+        let mut skip_element = false;
+        if has_rel_or_rev {
+            // Step 6
+            new_subject.clone_from(&about);
+            if has_typeof {
+                typed_resource.clone_from(&new_subject);
+            }
+            if new_subject.is_none() {
+                new_subject = if is_root {
+                    self.document_iri()
+                } else {
+                    parent.parent_object.clone()
+                };
+            }
+            current_object = resource
+                .clone()
+                .or_else(|| href.clone())
+                .or_else(|| src.clone());
+            if has_typeof && about.is_none() {
+                if current_object.is_none() {
+                    current_object = Some(BlankNode::default().into());
+                }
+                typed_resource.clone_from(&current_object);
+            }
+        } else {
+            if attributes.property.is_some()
+                && attributes.content.is_none()
+                && attributes.datatype.is_none()
+            {
+                // Step 5.1
+                new_subject = if about.is_some() {
+                    about.clone()
+                } else if is_root {
+                    self.document_iri()
+                } else {
+                    parent.parent_object.clone()
+                };
+                if has_typeof {
+                    typed_resource = Some(
+                        about
+                            .clone()
+                            .or_else(|| if is_root { self.document_iri() } else { None })
+                            .or_else(|| resource.clone())
+                            .or_else(|| href.clone())
+                            .or_else(|| src.clone())
+                            .unwrap_or_else(|| BlankNode::default().into()),
+                    );
+                    current_object.clone_from(&typed_resource);
+                }
+            } else {
+                // Step 5.2
+                new_subject = about
+                    .clone()
+                    .or_else(|| resource.clone())
+                    .or_else(|| href.clone())
+                    .or_else(|| src.clone());
+                if new_subject.is_none() {
+                    if is_root {
+                        new_subject = self.document_iri();
+                    } else if is_head_or_body {
+                        new_subject.clone_from(&parent.parent_object);
+                    } else if has_typeof {
+                        new_subject = Some(BlankNode::default().into());
+                    } else if parent.parent_object.is_some() {
+                        new_subject.clone_from(&parent.parent_object);
+                        skip_element = attributes.property.is_none();
+                    }
+                }
+                if has_typeof {
+                    typed_resource.clone_from(&new_subject);
+                }
+            }
+        }
+
+        // Step 7: types
+        if let (Some(typed_resource), Some(types)) = (&typed_resource, &attributes.r#typeof) {
+            for r#type in self.resolve_predicates(types, &prefixes, vocabulary.as_deref()) {
+                results.push(Triple::new(typed_resource.clone(), rdf::TYPE, r#type));
+            }
+        }
+
+        // Step 8: new list mapping
+        let mut list_mapping = parent.list_mapping;
+        let mut list_owner = None;
+        if new_subject.is_some() && new_subject != parent.parent_object {
+            self.list_mappings.push(Vec::new());
+            list_mapping = self.list_mappings.len() - 1;
+            list_owner.clone_from(&new_subject);
+        }
+
+        // Steps 9 and 10: @rel and @rev
+        let mut incomplete_triples = Vec::new();
+        if let Some(object) = &current_object {
+            if attributes.inlist && attributes.rel.is_some() {
+                for predicate in rel {
+                    self.push_to_list(list_mapping, predicate, Some(object.clone().into()));
+                }
+            } else if let Some(subject) = &new_subject {
+                for predicate in rel {
+                    results.push(Triple::new(subject.clone(), predicate, object.clone()));
+                }
+            }
+            if let Some(subject) = &new_subject {
+                for predicate in rev {
+                    results.push(Triple::new(object.clone(), predicate, subject.clone()));
+                }
+            }
+        } else if has_rel_or_rev {
+            if attributes.inlist && attributes.rel.is_some() {
+                for predicate in rel {
+                    let list = self.list(list_mapping, predicate);
+                    incomplete_triples.push(IncompleteTriple::List {
+                        mapping: list_mapping,
+                        list,
+                    });
+                }
+            } else {
+                incomplete_triples.extend(rel.into_iter().map(IncompleteTriple::Forward));
+            }
+            incomplete_triples.extend(rev.into_iter().map(IncompleteTriple::Backward));
+            current_object = Some(BlankNode::default().into());
+        }
+
+        // Step 11: @property
+        let mut content_property = None;
+        if let (Some(property), Some(subject)) = (&attributes.property, &new_subject) {
+            let predicates = self.resolve_predicates(property, &prefixes, vocabulary.as_deref());
+            let mut content_kind = None;
+            let datatype = attributes.datatype.as_deref().map(|datatype| {
+                self.resolve_term_or_curie_or_absolute_iri(
+                    datatype,
+                    &prefixes,
+                    vocabulary.as_deref(),
+                )
+            });
+            let value: Option<Term> = match datatype {
+                Some(Some(datatype))
+                    if datatype.as_ref() != rdf::XML_LITERAL && datatype.as_ref() != rdf::HTML =>
+                {
+                    if let Some(value) =
+                        attributes.content.as_ref().or(attributes.datetime.as_ref())
+                    {
+                        Some(Literal::new_typed_literal(value, datatype).into())
+                    } else {
+                        content_kind = Some(ContentKind::Typed { datatype });
+                        None
+                    }
+                }
+                Some(Some(datatype)) => {
+                    content_kind = Some(ContentKind::Xml { datatype });
+                    None
+                }
+                Some(None) => {
+                    if let Some(value) = &attributes.content {
+                        Some(plain_literal(value.clone(), language.clone()).into())
+                    } else {
+                        content_kind = Some(ContentKind::Plain {
+                            language: language.clone(),
+                        });
+                        None
+                    }
+                }
+                None => {
+                    if let Some(value) = &attributes.content {
+                        Some(plain_literal(value.clone(), language.clone()).into())
+                    } else if let Some(value) = &attributes.datetime {
+                        Some(time_literal(value.clone(), language.clone()).into())
+                    } else if let (false, Some(object)) = (
+                        has_rel_or_rev,
+                        resource.as_ref().or(href.as_ref()).or(src.as_ref()),
+                    ) {
+                        Some(object.clone().into())
+                    } else if let (true, None, Some(typed_resource)) =
+                        (has_typeof, &about, &typed_resource)
+                    {
+                        Some(typed_resource.clone().into())
+                    } else if name == "time" {
+                        content_kind = Some(ContentKind::Time {
+                            language: language.clone(),
+                        });
+                        None
+                    } else {
+                        content_kind = Some(ContentKind::Plain {
+                            language: language.clone(),
+                        });
+                        None
+                    }
+                }
+            };
+            if let Some(value) = value {
+                for predicate in predicates {
+                    if attributes.inlist {
+                        self.push_to_list(list_mapping, predicate, Some(value.clone()));
+                    } else {
+                        results.push(Triple::new(subject.clone(), predicate, value.clone()));
+                    }
+                }
+            } else if let Some(kind) = content_kind {
+                let mut list_slots = Vec::new();
+                if attributes.inlist {
+                    for predicate in &predicates {
+                        let list = self.list(list_mapping, predicate.clone());
+                        let elements = &mut self.list_mappings[list_mapping][list].1;
+                        list_slots.push((list_mapping, list, elements.len()));
+                        elements.push(None);
+                    }
+                }
+                content_property = Some(ContentProperty {
+                    subject: subject.clone(),
+                    predicates,
+                    kind,
+                    list_slots,
+                    inlist: attributes.inlist,
+                });
+            }
+        }
+
+        // Step 12: completion of the parent incomplete triples
+        if !skip_element {
+            if let Some(subject) = &new_subject {
+                for incomplete_triple in parent.incomplete_triples.iter() {
+                    match incomplete_triple {
+                        IncompleteTriple::Forward(predicate) => {
+                            if let Some(parent_subject) = &parent.parent_subject {
+                                results.push(Triple::new(
+                                    parent_subject.clone(),
+                                    predicate.clone(),
+                                    subject.clone(),
+                                ));
+                            }
+                        }
+                        IncompleteTriple::Backward(predicate) => {
+                            if let Some(parent_subject) = &parent.parent_subject {
+                                results.push(Triple::new(
+                                    subject.clone(),
+                                    predicate.clone(),
+                                    parent_subject.clone(),
+                                ));
+                            }
+                        }
+                        IncompleteTriple::List { mapping, list } => {
+                            self.list_mappings[*mapping][*list]
+                                .1
+                                .push(Some(subject.clone().into()));
+                        }
+                    }
+                }
+            }
+        }
+
+        // Step 13: the context of the children
+        let context = if skip_element {
+            Context {
+                prefixes,
+                vocabulary,
+                language,
+                list_mapping,
+                ..parent
+            }
+        } else {
+            Context {
+                parent_subject: new_subject.clone().or(parent.parent_subject),
+                parent_object: current_object
+                    .or_else(|| new_subject.clone())
+                    .or(parent.parent_object),
+                prefixes,
+                vocabulary,
+                language,
+                incomplete_triples: incomplete_triples.into(),
+                list_mapping,
+            }
+        };
+        let (text, xml) = match content_property.as_ref().map(|p| &p.kind) {
+            Some(ContentKind::Xml { .. }) => (None, Some(String::new())),
+            Some(_) => (Some(String::new()), None),
+            None => (None, None),
+        };
+        if content_property.is_some() {
+            self.content_collectors += 1;
+        }
+        self.stack.push(Element {
+            name,
+            context,
+            list_owner,
+            content_property,
+            text,
+            xml,
+        });
+        if is_empty {
+            self.parse_end(results);
+        }
+        Ok(())
+    }
+
+    /// Closes the current element
+    fn parse_end(&mut self, results: &mut Vec<Triple>) {
+        let Some(element) = self.stack.pop() else {
+            return;
+        };
+        if self.content_collectors > 0 {
+            for parent in &mut self.stack {
+                if let Some(xml) = &mut parent.xml {
+                    xml.push_str("</");
+                    xml.push_str(&element.name);
+                    xml.push('>');
+                }
+            }
+        }
+        if let Some(property) = element.content_property {
+            self.content_collectors -= 1;
+            let value: Term = match property.kind {
+                ContentKind::Plain { language } => {
+                    plain_literal(element.text.unwrap_or_default(), language).into()
+                }
+                ContentKind::Typed { datatype } => {
+                    Literal::new_typed_literal(element.text.unwrap_or_default(), datatype).into()
+                }
+                ContentKind::Xml { datatype } => {
+                    Literal::new_typed_literal(element.xml.unwrap_or_default(), datatype).into()
+                }
+                ContentKind::Time { language } => {
+                    time_literal(element.text.unwrap_or_default(), language).into()
+                }
+            };
+            if property.inlist {
+                for (mapping, list, position) in property.list_slots {
+                    if let Some(slot) = self
+                        .list_mappings
+                        .get_mut(mapping)
+                        .and_then(|m| m.get_mut(list))
+                        .and_then(|(_, elements)| elements.get_mut(position))
+                    {
+                        *slot = Some(value.clone());
+                    }
+                }
+            } else {
+                for predicate in property.predicates {
+                    results.push(Triple::new(
+                        property.subject.clone(),
+                        predicate,
+                        value.clone(),
+                    ));
+                }
+            }
+        }
+        // Step 14: the lists created by this element
+        if let Some(subject) = element.list_owner {
+            let Some(lists) = self.list_mappings.pop() else {
+                return;
+            };
+            for (predicate, elements) in lists {
+                let mut head: Term = rdf::NIL.into_owned().into();
+                let mut list = Vec::new();
+                for element in elements.into_iter().flatten().rev() {
+                    let node = BlankNode::default();
+                    list.push(Triple::new(node.clone(), rdf::REST, head));
+                    list.push(Triple::new(node.clone(), rdf::FIRST, element));
+                    head = node.into();
+                }
+                results.push(Triple::new(subject.clone(), predicate, head));
+                // We emit the list from its head
+                results.extend(list.into_iter().rev());
+            }
+        }
+    }
+
+    fn set_base_iri(&mut self, href: &str) {
+        let Some(new_base) = (if let Some(base) = &self.base_iri {
+            base.resolve(href).ok()
+        } else {
+            Iri::parse(href.to_owned()).ok()
+        }) else {
+            return;
+        };
+        let old_document = self.document_iri();
+        self.base_iri = Some(new_base);
+        let new_document = self.document_iri();
+        // The root element subject is the document IRI
+        for context in self
+            .stack
+            .iter_mut()
+            .map(|e| &mut e.context)
+            .chain([&mut self.document_context])
+        {
+            for node in [&mut context.parent_subject, &mut context.parent_object] {
+                if *node == old_document {
+                    node.clone_from(&new_document);
+                }
+            }
+        }
+    }
+
+    fn document_iri(&self) -> Option<NamedOrBlankNode> {
+        Some(NamedNode::new_unchecked(self.base_iri.as_ref()?.as_str()).into())
+    }
+
+    fn add_prefix(&mut self, prefixes: &mut HashMap<String, String>, name: &str, iri: &str) {
+        let name = name.to_ascii_lowercase();
+        if name == "_" {
+            return; // The blank node prefix can't be overridden
+        }
+        if !self.unchecked && Iri::parse(iri).is_err() {
+            self.errors.push(RdfaSyntaxError::msg(format!(
+                "The prefix {name} is mapped to the invalid IRI '{iri}'"
+            )));
+            return;
+        }
+        prefixes.insert(name, iri.into());
+    }
+
+    fn resolve_iri(&mut self, iri: &str) -> Option<NamedNode> {
+        let result = if self.unchecked {
+            Ok(if let Some(base) = &self.base_iri {
+                base.resolve_unchecked(iri).into_inner()
+            } else {
+                iri.to_owned()
+            })
+        } else if let Some(base) = &self.base_iri {
+            base.resolve(iri).map(Iri::into_inner)
+        } else {
+            Iri::parse(iri.to_owned()).map(Iri::into_inner)
+        };
+        match result {
+            Ok(iri) => Some(NamedNode::new_unchecked(iri)),
+            Err(error) => {
+                self.errors
+                    .push(RdfaSyntaxError::invalid_iri(iri.into(), error));
+                None
+            }
+        }
+    }
+
+    /// Resolves the IRIs of the HTML attributes like `@href` that are ignored if invalid
+    fn resolve_html_iri(&self, iri: &str) -> Option<NamedOrBlankNode> {
+        let iri = if let Some(base) = &self.base_iri {
+            base.resolve(iri.trim()).ok()?
+        } else {
+            Iri::parse(iri.trim().to_owned()).ok()?
+        };
+        Some(NamedNode::new_unchecked(iri.into_inner()).into())
+    }
+
+    /// Resolves a CURIE, returns `None` if the value is not a CURIE and `Some(None)` if the CURIE is invalid
+    #[allow(clippy::option_option)]
+    fn resolve_curie(
+        &mut self,
+        value: &str,
+        prefixes: &HashMap<String, String>,
+    ) -> Option<Option<NamedOrBlankNode>> {
+        let (prefix, reference) = value.split_once(':')?;
+        if prefix == "_" {
+            return Some(Some(
+                self.blank_nodes
+                    .entry(reference.into())
+                    .or_default()
+                    .clone()
+                    .into(),
+            ));
+        }
+        if reference.starts_with("//") {
+            return None; // It's an IRI
+        }
+        let namespace = if prefix.is_empty() {
+            XHV_NAMESPACE
+        } else {
+            let prefix = prefix.to_ascii_lowercase();
+            if let Some(namespace) = prefixes.get(&prefix) {
+                namespace.as_str()
+            } else {
+                let position = INITIAL_PREFIXES
+                    .binary_search_by(|(p, _)| (*p).cmp(prefix.as_str()))
+                    .ok()?;
+                INITIAL_PREFIXES[position].1
+            }
+        };
+        let iri = format!("{namespace}{reference}");
+        if self.unchecked {
+            return Some(Some(NamedNode::new_unchecked(iri).into()));
+        }
+        match NamedNode::new(iri.clone()) {
+            Ok(iri) => Some(Some(iri.into())),
+            Err(error) => {
+                self.errors.push(RdfaSyntaxError::invalid_iri(iri, error));
+                Some(None)
+            }
+        }
+    }
+
+    /// Resolves the values of `@about` and `@resource`
+    fn resolve_safe_curie_or_curie_or_iri(
+        &mut self,
+        value: &str,
+        prefixes: &HashMap<String, String>,
+    ) -> Option<NamedOrBlankNode> {
+        if let Some(curie) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            return self.resolve_curie(curie, prefixes).flatten();
+        }
+        if let Some(resolved) = self.resolve_curie(value, prefixes) {
+            return resolved;
+        }
+        Some(self.resolve_iri(value)?.into())
+    }
+
+    /// Resolves the values of `@property`, `@rel`, `@rev`, `@typeof` and `@datatype`
+    fn resolve_term_or_curie_or_absolute_iri(
+        &mut self,
+        value: &str,
+        prefixes: &HashMap<String, String>,
+        vocabulary: Option<&str>,
+    ) -> Option<NamedNode> {
+        if value.contains(':') {
+            return match self.resolve_curie(value, prefixes) {
+                Some(Some(NamedOrBlankNode::NamedNode(iri))) => Some(iri),
+                Some(_) => None, // Blank nodes are not allowed
+                None => NamedNode::new(value).ok(),
+            };
+        }
+        if value.is_empty() {
+            return None;
+        }
+        if let Some(vocabulary) = vocabulary {
+            return NamedNode::new(format!("{vocabulary}{value}")).ok();
+        }
+        INITIAL_TERMS
+            .iter()
+            .find(|(term, _)| term.eq_ignore_ascii_case(value))
+            .map(|(_, iri)| NamedNode::new_unchecked(*iri))
+    }
+
+    fn resolve_predicates(
+        &mut self,
+        value: &str,
+        prefixes: &HashMap<String, String>,
+        vocabulary: Option<&str>,
+    ) -> Vec<NamedNode> {
+        value
+            .split_ascii_whitespace()
+            .filter_map(|v| self.resolve_term_or_curie_or_absolute_iri(v, prefixes, vocabulary))
+            .collect()
+    }
+
+    /// The index of the list for the given predicate in the list mapping, creating it if needed
+    fn list(&mut self, mapping: usize, predicate: NamedNode) -> usize {
+        let lists = &mut self.list_mappings[mapping];
+        if let Some(position) = lists.iter().position(|(p, _)| *p == predicate) {
+            position
+        } else {
+            lists.push((predicate, Vec::new()));
+            lists.len() - 1
+        }
+    }
+
+    fn push_to_list(&mut self, mapping: usize, predicate: NamedNode, value: Option<Term>) {
+        let list = self.list(mapping, predicate);
+        self.list_mappings[mapping][list].1.push(value);
+    }
+}
+
+fn plain_literal(value: String, language: Option<String>) -> Literal {
+    if let Some(language) = language {
+        Literal::new_language_tagged_literal_unchecked(value, language)
+    } else {
+        Literal::new_simple_literal(value)
+    }
+}
+
+/// A literal for a `@datetime` value or a `<time>` content, with the datatype guessed from its lexical form
+fn time_literal(value: String, language: Option<String>) -> Literal {
+    let datatype = if Duration::from_str(&value).is_ok() {
+        xsd::DURATION
+    } else if DateTime::from_str(&value).is_ok() {
+        xsd::DATE_TIME
+    } else if Date::from_str(&value).is_ok() {
+        xsd::DATE
+    } else if Time::from_str(&value).is_ok() {
+        xsd::TIME
+    } else if GYearMonth::from_str(&value).is_ok() {
+        xsd::G_YEAR_MONTH
+    } else if GYear::from_str(&value).is_ok() {
+        xsd::G_YEAR
+    } else {
+        return plain_literal(value, language);
+    };
+    Literal::new_typed_literal(value, datatype)
+}
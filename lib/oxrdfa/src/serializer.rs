@@ -0,0 +1,427 @@
+use oxiri::{Iri, IriParseError};
+use oxrdf::vocab::rdf;
+use oxrdf::{NamedOrBlankNode, NamedOrBlankNodeRef, SubjectRef, TermRef, TripleRef};
+use quick_xml::escape::escape;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::io;
+use std::io::Write;
+#[cfg(feature = "async-tokio")]
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// A [HTML+RDFa](https://www.w3.org/TR/html-rdfa/) serializer.
+///
+/// It writes a HTML document with a `<div>` element per subject containing a `<span>` element per triple.
+///
+/// ```
+/// use oxrdf::{LiteralRef, NamedNodeRef, TripleRef};
+/// use oxrdf::vocab::rdf;
+/// use oxrdfa::RdfaSerializer;
+///
+/// let mut serializer = RdfaSerializer::new().with_prefix("schema", "http://schema.org/")?.for_writer(Vec::new());
+/// serializer.serialize_triple(TripleRef::new(
+///     NamedNodeRef::new("http://example.com#me")?,
+///     rdf::TYPE,
+///     NamedNodeRef::new("http://schema.org/Person")?,
+/// ))?;
+/// serializer.serialize_triple(TripleRef::new(
+///     NamedNodeRef::new("http://example.com#me")?,
+///     NamedNodeRef::new("http://schema.org/name")?,
+///     LiteralRef::new_language_tagged_literal_unchecked("Foo Bar", "en"),
+/// ))?;
+/// assert_eq!(
+///     "<!DOCTYPE html>\n<html prefix=\"schema: http://schema.org/\">\n\t<head>\n\t\t<meta charset=\"utf-8\">\n\t</head>\n\t<body>\n\t\t<div about=\"http://example.com#me\">\n\t\t\t<span property=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#type\" resource=\"schema:Person\"></span>\n\t\t\t<span property=\"schema:name\" content=\"Foo Bar\" lang=\"en\"></span>\n\t\t</div>\n\t</body>\n</html>\n",
+///     String::from_utf8(serializer.finish()?)?
+/// );
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[derive(Default, Clone)]
+#[must_use]
+pub struct RdfaSerializer {
+    prefixes: BTreeMap<String, String>,
+    base_iri: Option<Iri<String>>,
+}
+
+impl RdfaSerializer {
+    /// Builds a new [`RdfaSerializer`].
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            prefixes: BTreeMap::new(),
+            base_iri: None,
+        }
+    }
+
+    /// Declares a prefix using the `@prefix` attribute of the `<html>` element.
+    ///
+    /// The properties, types and datatypes starting with the prefix IRI are written as CURIEs.
+    #[inline]
+    pub fn with_prefix(
+        mut self,
+        prefix_name: impl Into<String>,
+        prefix_iri: impl Into<String>,
+    ) -> Result<Self, IriParseError> {
+        let prefix_name = prefix_name.into();
+        if prefix_name.is_empty() || prefix_name == "_" {
+            return Ok(self); // They can't be set with @prefix
+        }
+        self.prefixes
+            .insert(prefix_name, Iri::parse(prefix_iri.into())?.into_inner());
+        Ok(self)
+    }
+
+    /// Sets the document IRI using a `<base>` element, the subjects and objects IRIs are written relative to it.
+    ///
+    /// ```
+    /// use oxrdf::{NamedNodeRef, TripleRef};
+    /// use oxrdfa::RdfaSerializer;
+    ///
+    /// let mut serializer = RdfaSerializer::new()
+    ///     .with_base_iri("http://example.com")?
+    ///     .for_writer(Vec::new());
+    /// serializer.serialize_triple(TripleRef::new(
+    ///     NamedNodeRef::new("http://example.com#me")?,
+    ///     NamedNodeRef::new("http://example.com/ns#parent")?,
+    ///     NamedNodeRef::new("http://example.com#other")?,
+    /// ))?;
+    /// assert_eq!(
+    ///     "<!DOCTYPE html>\n<html>\n\t<head>\n\t\t<meta charset=\"utf-8\">\n\t\t<base href=\"http://example.com\">\n\t</head>\n\t<body>\n\t\t<div about=\"#me\">\n\t\t\t<span property=\"http://example.com/ns#parent\" resource=\"#other\"></span>\n\t\t</div>\n\t</body>\n</html>\n",
+    ///     String::from_utf8(serializer.finish()?)?
+    /// );
+    /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    pub fn with_base_iri(mut self, base_iri: impl Into<String>) -> Result<Self, IriParseError> {
+        self.base_iri = Some(Iri::parse(base_iri.into())?);
+        Ok(self)
+    }
+
+    /// Serializes a HTML+RDFa file to a [`Write`] implementation.
+    ///
+    /// This writer does unbuffered writes.
+    ///
+    /// ```
+    /// use oxrdf::{LiteralRef, NamedNodeRef, TripleRef};
+    /// use oxrdfa::{RdfaParser, RdfaSerializer};
+    ///
+    /// let triple = TripleRef::new(
+    ///     NamedNodeRef::new("http://example.com#me")?,
+    ///     NamedNodeRef::new("http://schema.org/name")?,
+    ///     LiteralRef::new_simple_literal("Foo & Bar"),
+    /// );
+    /// let mut serializer = RdfaSerializer::new().for_writer(Vec::new());
+    /// serializer.serialize_triple(triple)?;
+    /// let file = serializer.finish()?;
+    ///
+    /// let triples = RdfaParser::new()
+    ///     .for_slice(&file)
+    ///     .collect::<Result<Vec<_>, _>>()?;
+    /// assert_eq!(triples, [triple.into_owned()]);
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn for_writer<W: Write>(self, writer: W) -> WriterRdfaSerializer<W> {
+        WriterRdfaSerializer {
+            writer,
+            inner: self.inner_writer(),
+        }
+    }
+
+    /// Serializes a HTML+RDFa file to a [`AsyncWrite`] implementation.
+    ///
+    /// This writer does unbuffered writes.
+    ///
+    /// ```
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use oxrdf::{LiteralRef, NamedNodeRef, TripleRef};
+    /// use oxrdfa::{RdfaParser, RdfaSerializer};
+    ///
+    /// let triple = TripleRef::new(
+    ///     NamedNodeRef::new("http://example.com#me")?,
+    ///     NamedNodeRef::new("http://schema.org/name")?,
+    ///     LiteralRef::new_simple_literal("Foo & Bar"),
+    /// );
+    /// let mut serializer = RdfaSerializer::new().for_tokio_async_writer(Vec::new());
+    /// serializer.serialize_triple(triple).await?;
+    /// let file = serializer.finish().await?;
+    ///
+    /// let triples = RdfaParser::new()
+    ///     .for_slice(&file)
+    ///     .collect::<Result<Vec<_>, _>>()?;
+    /// assert_eq!(triples, [triple.into_owned()]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "async-tokio")]
+    pub fn for_tokio_async_writer<W: AsyncWrite + Unpin>(
+        self,
+        writer: W,
+    ) -> TokioAsyncWriterRdfaSerializer<W> {
+        TokioAsyncWriterRdfaSerializer {
+            writer,
+            inner: self.inner_writer(),
+        }
+    }
+
+    fn inner_writer(self) -> InnerRdfaWriter {
+        InnerRdfaWriter {
+            current_subject: None,
+            is_started: false,
+            prefixes: self.prefixes,
+            base_iri: self.base_iri,
+        }
+    }
+}
+
+/// Serializes a HTML+RDFa file to a [`Write`] implementation.
+///
+/// Can be built using [`RdfaSerializer::for_writer`].
+///
+/// ```
+/// use oxrdf::{LiteralRef, NamedNodeRef, TripleRef};
+/// use oxrdfa::{RdfaParser, RdfaSerializer};
+///
+/// let triple = TripleRef::new(
+///     NamedNodeRef::new("http://example.com#me")?,
+///     NamedNodeRef::new("http://schema.org/name")?,
+///     LiteralRef::new_simple_literal("Foo & Bar"),
+/// );
+/// let mut serializer = RdfaSerializer::new().for_writer(Vec::new());
+/// serializer.serialize_triple(triple)?;
+/// let file = serializer.finish()?;
+///
+/// let triples = RdfaParser::new()
+///     .for_slice(&file)
+///     .collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(triples, [triple.into_owned()]);
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[must_use]
+pub struct WriterRdfaSerializer<W: Write> {
+    writer: W,
+    inner: InnerRdfaWriter,
+}
+
+impl<W: Write> WriterRdfaSerializer<W> {
+    /// Serializes an extra triple.
+    pub fn serialize_triple<'a>(&mut self, t: impl Into<TripleRef<'a>>) -> io::Result<()> {
+        let mut buffer = String::new();
+        self.inner.serialize_triple(t, &mut buffer)?;
+        self.writer.write_all(buffer.as_bytes())
+    }
+
+    /// Ends the write process and returns the underlying [`Write`].
+    pub fn finish(mut self) -> io::Result<W> {
+        let mut buffer = String::new();
+        self.inner.finish(&mut buffer);
+        self.writer.write_all(buffer.as_bytes())?;
+        Ok(self.writer)
+    }
+}
+
+/// Serializes a HTML+RDFa file to a [`AsyncWrite`] implementation.
+///
+/// Can be built using [`RdfaSerializer::for_tokio_async_writer`].
+///
+/// ```
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use oxrdf::{LiteralRef, NamedNodeRef, TripleRef};
+/// use oxrdfa::{RdfaParser, RdfaSerializer};
+///
+/// let triple = TripleRef::new(
+///     NamedNodeRef::new("http://example.com#me")?,
+///     NamedNodeRef::new("http://schema.org/name")?,
+///     LiteralRef::new_simple_literal("Foo & Bar"),
+/// );
+/// let mut serializer = RdfaSerializer::new().for_tokio_async_writer(Vec::new());
+/// serializer.serialize_triple(triple).await?;
+/// let file = serializer.finish().await?;
+///
+/// let triples = RdfaParser::new()
+///     .for_slice(&file)
+///     .collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(triples, [triple.into_owned()]);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "async-tokio")]
+#[must_use]
+pub struct TokioAsyncWriterRdfaSerializer<W: AsyncWrite + Unpin> {
+    writer: W,
+    inner: InnerRdfaWriter,
+}
+
+#[cfg(feature = "async-tokio")]
+impl<W: AsyncWrite + Unpin> TokioAsyncWriterRdfaSerializer<W> {
+    /// Serializes an extra triple.
+    pub async fn serialize_triple<'a>(&mut self, t: impl Into<TripleRef<'a>>) -> io::Result<()> {
+        let mut buffer = String::new();
+        self.inner.serialize_triple(t, &mut buffer)?;
+        self.writer.write_all(buffer.as_bytes()).await
+    }
+
+    /// Ends the write process and returns the underlying [`AsyncWrite`].
+    pub async fn finish(mut self) -> io::Result<W> {
+        let mut buffer = String::new();
+        self.inner.finish(&mut buffer);
+        self.writer.write_all(buffer.as_bytes()).await?;
+        Ok(self.writer)
+    }
+}
+
+struct InnerRdfaWriter {
+    current_subject: Option<NamedOrBlankNode>,
+    is_started: bool,
+    prefixes: BTreeMap<String, String>,
+    base_iri: Option<Iri<String>>,
+}
+
+impl InnerRdfaWriter {
+    #[allow(clippy::match_wildcard_for_single_variants, unreachable_patterns)]
+    fn serialize_triple<'a>(
+        &mut self,
+        t: impl Into<TripleRef<'a>>,
+        output: &mut String,
+    ) -> io::Result<()> {
+        if !self.is_started {
+            self.write_start(output);
+        }
+        let triple = t.into();
+        let subject = match triple.subject {
+            SubjectRef::NamedNode(node) => NamedOrBlankNodeRef::from(node),
+            SubjectRef::BlankNode(node) => node.into(),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "RDFa only supports named or blank subject",
+                ))
+            }
+        };
+        // We open a new <div> if the subject changes
+        if self.current_subject.as_ref().map(NamedOrBlankNode::as_ref) != Some(subject) {
+            if self.current_subject.is_some() {
+                output.push_str("\t\t</div>\n");
+            }
+            output.push_str("\t\t<div about=\"");
+            output.push_str(&escape(&*self.resource(subject)));
+            output.push_str("\">\n");
+            self.current_subject = Some(subject.into_owned());
+        }
+
+        output.push_str("\t\t\t<span property=\"");
+        output.push_str(&escape(&*self.curie(triple.predicate.as_str())));
+        output.push('"');
+        let mut content = None;
+        match triple.object {
+            TermRef::NamedNode(node) => {
+                output.push_str(" resource=\"");
+                // The types are written as CURIEs like the properties
+                if triple.predicate == rdf::TYPE {
+                    output.push_str(&escape(&*self.curie(node.as_str())));
+                } else {
+                    output.push_str(&escape(&*self.resource(node.into())));
+                }
+                output.push('"');
+            }
+            TermRef::BlankNode(node) => {
+                output.push_str(" resource=\"");
+                output.push_str(&escape(&*self.resource(node.into())));
+                output.push('"');
+            }
+            TermRef::Literal(literal) => {
+                if literal.datatype() == rdf::XML_LITERAL || literal.datatype() == rdf::HTML {
+                    // The value is the element content
+                    content = Some(literal.value());
+                } else {
+                    output.push_str(" content=\"");
+                    output.push_str(&escape(literal.value()));
+                    output.push('"');
+                }
+                if let Some(language) = literal.language() {
+                    output.push_str(" lang=\"");
+                    output.push_str(&escape(language));
+                    output.push('"');
+                } else if !literal.is_plain() {
+                    output.push_str(" datatype=\"");
+                    output.push_str(&escape(&*self.curie(literal.datatype().as_str())));
+                    output.push('"');
+                }
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "RDFa only supports named, blank or literal object",
+                ))
+            }
+        }
+        output.push('>');
+        if let Some(content) = content {
+            output.push_str(content);
+        }
+        output.push_str("</span>\n");
+        Ok(())
+    }
+
+    fn write_start(&mut self, output: &mut String) {
+        output.push_str("<!DOCTYPE html>\n<html");
+        if !self.prefixes.is_empty() {
+            output.push_str(" prefix=\"");
+            for (i, (name, iri)) in self.prefixes.iter().enumerate() {
+                if i > 0 {
+                    output.push(' ');
+                }
+                output.push_str(&escape(name.as_str()));
+                output.push_str(": ");
+                output.push_str(&escape(iri.as_str()));
+            }
+            output.push('"');
+        }
+        output.push_str(">\n\t<head>\n\t\t<meta charset=\"utf-8\">\n");
+        if let Some(base_iri) = &self.base_iri {
+            output.push_str("\t\t<base href=\"");
+            output.push_str(&escape(base_iri.as_str()));
+            output.push_str("\">\n");
+        }
+        output.push_str("\t</head>\n\t<body>\n");
+        self.is_started = true;
+    }
+
+    fn finish(&mut self, output: &mut String) {
+        if !self.is_started {
+            self.write_start(output);
+        }
+        if self.current_subject.is_some() {
+            output.push_str("\t\t</div>\n");
+        }
+        output.push_str("\t</body>\n</html>\n");
+    }
+
+    /// The value of `@about` and `@resource` for a node
+    fn resource<'a>(&self, node: NamedOrBlankNodeRef<'a>) -> Cow<'a, str> {
+        match node {
+            NamedOrBlankNodeRef::NamedNode(node) => {
+                let iri = node.as_str();
+                if let Some(base_iri) = &self.base_iri {
+                    if let Ok(relative) = base_iri.relativize(&Iri::parse_unchecked(iri)) {
+                        return relative.into_inner().into();
+                    }
+                }
+                iri.into()
+            }
+            NamedOrBlankNodeRef::BlankNode(node) => format!("[_:{}]", node.as_str()).into(),
+        }
+    }
+
+    /// A CURIE for the IRI if one of the prefixes matches, the IRI itself if not
+    fn curie<'a>(&self, iri: &'a str) -> Cow<'a, str> {
+        for (name, prefix_iri) in &self.prefixes {
+            if let Some(local) = iri.strip_prefix(prefix_iri.as_str()) {
+                if !local.starts_with("//") {
+                    return format!("{name}:{local}").into();
+                }
+            }
+        }
+        iri.into()
+    }
+}
@@ -0,0 +1,261 @@
+#![cfg(test)]
+#![allow(clippy::panic_in_result_fn)]
+
+use oxrdf::vocab::{rdf, xsd};
+use oxrdf::{BlankNode, Graph, Literal, NamedNode, Triple};
+use oxrdfa::{RdfaParser, RdfaSerializer, RdfaSyntaxError};
+
+fn parse(parser: RdfaParser, file: &str) -> Result<Vec<Triple>, RdfaSyntaxError> {
+    parser.for_slice(file.as_bytes()).collect()
+}
+
+fn nn(iri: &str) -> NamedNode {
+    NamedNode::new_unchecked(iri)
+}
+
+#[test]
+fn test_chaining() -> Result<(), Box<dyn std::error::Error>> {
+    let triples = parse(
+        RdfaParser::new(),
+        r#"<html>
+        <body prefix="foaf: http://xmlns.com/foaf/0.1/">
+            <div about="http://example.com/alice" rel="foaf:knows">
+                <span about="http://example.com/bob" property="foaf:name">Bob</span>
+            </div>
+            <div about="http://example.com/alice" rev="foaf:knows" resource="http://example.com/carol"></div>
+        </body>
+        </html>"#,
+    )?;
+    assert_eq!(
+        triples,
+        [
+            Triple::new(
+                nn("http://example.com/alice"),
+                nn("http://xmlns.com/foaf/0.1/knows"),
+                nn("http://example.com/bob")
+            ),
+            Triple::new(
+                nn("http://example.com/bob"),
+                nn("http://xmlns.com/foaf/0.1/name"),
+                Literal::from("Bob")
+            ),
+            Triple::new(
+                nn("http://example.com/carol"),
+                nn("http://xmlns.com/foaf/0.1/knows"),
+                nn("http://example.com/alice")
+            ),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_literals() -> Result<(), Box<dyn std::error::Error>> {
+    let triples = parse(
+        RdfaParser::new(),
+        r#"<!DOCTYPE html>
+        <html lang="en">
+        <body>
+            <p about="http://example.com/s">
+                <span property="http://example.com/p1">hello &amp; <b>world</b></span><br>
+                <span property="http://example.com/p2" lang="">x</span>
+                <span property="http://example.com/p3" datatype="xsd:integer" content="12"></span>
+                <time property="http://example.com/p4">2012-03-04</time>
+                <span property="http://example.com/p5" datetime="2012-03-04T10:00:00Z">now</span>
+                <span property="http://example.com/p6" datatype="rdf:XMLLiteral">a<b>b</b></span>
+            </p>
+        </body>
+        </html>"#,
+    )?;
+    let s = nn("http://example.com/s");
+    assert_eq!(
+        triples,
+        [
+            Triple::new(
+                s.clone(),
+                nn("http://example.com/p1"),
+                Literal::new_language_tagged_literal("hello & world", "en")?
+            ),
+            Triple::new(s.clone(), nn("http://example.com/p2"), Literal::from("x")),
+            Triple::new(
+                s.clone(),
+                nn("http://example.com/p3"),
+                Literal::new_typed_literal("12", xsd::INTEGER)
+            ),
+            Triple::new(
+                s.clone(),
+                nn("http://example.com/p4"),
+                Literal::new_typed_literal("2012-03-04", xsd::DATE)
+            ),
+            Triple::new(
+                s.clone(),
+                nn("http://example.com/p5"),
+                Literal::new_typed_literal("2012-03-04T10:00:00Z", xsd::DATE_TIME)
+            ),
+            Triple::new(
+                s,
+                nn("http://example.com/p6"),
+                Literal::new_typed_literal("a<b>b</b>", rdf::XML_LITERAL)
+            ),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_vocab_typeof_and_base() -> Result<(), Box<dyn std::error::Error>> {
+    let triples = parse(
+        RdfaParser::new(),
+        r#"<html>
+        <head><base href="http://example.com/page"><title property="http://purl.org/dc/terms/title">Page</title></head>
+        <body vocab="http://schema.org/">
+            <div typeof="Person">
+                <span property="name">Alice</span>
+                <a property="url" href="alice">home</a>
+            </div>
+        </body>
+        </html>"#,
+    )?;
+    assert_eq!(triples.len(), 5);
+    let page = nn("http://example.com/page");
+    assert_eq!(
+        triples[0],
+        Triple::new(
+            page.clone(),
+            nn("http://purl.org/dc/terms/title"),
+            Literal::from("Page")
+        )
+    );
+    assert_eq!(
+        triples[1],
+        Triple::new(
+            page,
+            nn("http://www.w3.org/ns/rdfa#usesVocabulary"),
+            nn("http://schema.org/")
+        )
+    );
+    let person = triples[2].subject.clone();
+    assert!(matches!(person, oxrdf::Subject::BlankNode(_)));
+    assert_eq!(
+        triples[2],
+        Triple::new(person.clone(), rdf::TYPE, nn("http://schema.org/Person"))
+    );
+    assert_eq!(
+        triples[3],
+        Triple::new(
+            person.clone(),
+            nn("http://schema.org/name"),
+            Literal::from("Alice")
+        )
+    );
+    assert_eq!(
+        triples[4],
+        Triple::new(
+            person,
+            nn("http://schema.org/url"),
+            nn("http://example.com/alice")
+        )
+    );
+    Ok(())
+}
+
+#[test]
+fn test_inlist() -> Result<(), Box<dyn std::error::Error>> {
+    let triples = parse(
+        RdfaParser::new(),
+        r#"<div about="http://example.com/s">
+            <span property="http://example.com/p" inlist>a</span>
+            <span property="http://example.com/p" inlist>b</span>
+            <span rel="http://example.com/empty" inlist></span>
+        </div>"#,
+    )?;
+    let mut expected = Graph::new();
+    let list = [BlankNode::default(), BlankNode::default()];
+    expected.insert(&Triple::new(
+        nn("http://example.com/s"),
+        nn("http://example.com/p"),
+        list[0].clone(),
+    ));
+    expected.insert(&Triple::new(
+        list[0].clone(),
+        rdf::FIRST,
+        Literal::from("a"),
+    ));
+    expected.insert(&Triple::new(list[0].clone(), rdf::REST, list[1].clone()));
+    expected.insert(&Triple::new(
+        list[1].clone(),
+        rdf::FIRST,
+        Literal::from("b"),
+    ));
+    expected.insert(&Triple::new(list[1].clone(), rdf::REST, rdf::NIL));
+    expected.insert(&Triple::new(
+        nn("http://example.com/s"),
+        nn("http://example.com/empty"),
+        rdf::NIL,
+    ));
+    assert_eq!(
+        triples.iter().collect::<Graph>().canonical_hash(),
+        expected.canonical_hash()
+    );
+    Ok(())
+}
+
+#[test]
+fn test_invalid_prefix() {
+    let results = RdfaParser::new()
+        .for_slice(br#"<p prefix="ex: ::" about="http://example.com/s" property="ex:p">v</p>"#)
+        .collect::<Vec<_>>();
+    // The error is returned and the prefix is ignored
+    assert_eq!(results.len(), 2);
+    results[0].as_ref().unwrap_err();
+    assert!(matches!(&results[1], Ok(t) if t.predicate == nn("ex:p")));
+}
+
+#[test]
+fn test_serializer_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let node = BlankNode::default();
+    let triples = [
+        Triple::new(
+            nn("http://example.com/s"),
+            rdf::TYPE,
+            nn("http://schema.org/Person"),
+        ),
+        Triple::new(
+            nn("http://example.com/s"),
+            nn("http://schema.org/knows"),
+            node.clone(),
+        ),
+        Triple::new(
+            node.clone(),
+            nn("http://schema.org/name"),
+            Literal::new_language_tagged_literal("<Bob> \"Jr\"", "en")?,
+        ),
+        Triple::new(
+            node,
+            nn("http://schema.org/age"),
+            Literal::new_typed_literal("12", xsd::INTEGER),
+        ),
+        Triple::new(
+            nn("http://example.com/other"),
+            nn("http://schema.org/description"),
+            Literal::new_typed_literal("<b>bold</b>", rdf::HTML),
+        ),
+    ];
+    let mut serializer = RdfaSerializer::new()
+        .with_prefix("schema", "http://schema.org/")?
+        .with_base_iri("http://example.com/page")?
+        .for_writer(Vec::new());
+    for triple in &triples {
+        serializer.serialize_triple(triple)?;
+    }
+    let file = serializer.finish()?;
+    let parsed = RdfaParser::new()
+        .with_base_iri("http://example.com/")?
+        .for_slice(&file)
+        .collect::<Result<Graph, _>>()?;
+    assert_eq!(
+        parsed.canonical_hash(),
+        triples.iter().collect::<Graph>().canonical_hash()
+    );
+    Ok(())
+}
@@ -32,6 +32,27 @@ impl From<oxrdfxml::RdfXmlParseError> for RdfParseError {
     }
 }
 
+impl From<oxmicrodata::MicrodataParseError> for RdfParseError {
+    #[inline]
+    fn from(error: oxmicrodata::MicrodataParseError) -> Self {
+        match error {
+            oxmicrodata::MicrodataParseError::Syntax(e) => Self::Syntax(e.into()),
+            oxmicrodata::MicrodataParseError::Io(e) => Self::Io(e),
+        }
+    }
+}
+
+#[cfg(feature = "rdfa")]
+impl From<oxrdfa::RdfaParseError> for RdfParseError {
+    #[inline]
+    fn from(error: oxrdfa::RdfaParseError) -> Self {
+        match error {
+            oxrdfa::RdfaParseError::Syntax(e) => Self::Syntax(e.into()),
+            oxrdfa::RdfaParseError::Io(e) => Self::Io(e),
+        }
+    }
+}
+
 impl From<RdfParseError> for io::Error {
     #[inline]
     fn from(error: RdfParseError) -> Self {
@@ -53,6 +74,11 @@ enum SyntaxErrorKind {
     #[error(transparent)]
     Turtle(#[from] oxttl::TurtleSyntaxError),
     #[error(transparent)]
+    Microdata(#[from] oxmicrodata::MicrodataSyntaxError),
+    #[error(transparent)]
+    #[cfg(feature = "rdfa")]
+    Rdfa(#[from] oxrdfa::RdfaSyntaxError),
+    #[error(transparent)]
     RdfXml(#[from] oxrdfxml::RdfXmlSyntaxError),
     #[error("{0}")]
     Msg(&'static str),
@@ -77,7 +103,11 @@ impl RdfSyntaxError {
                     },
                 )
             }
-            SyntaxErrorKind::RdfXml(_) | SyntaxErrorKind::Msg(_) => None,
+            SyntaxErrorKind::Microdata(_)
+            | SyntaxErrorKind::RdfXml(_)
+            | SyntaxErrorKind::Msg(_) => None,
+            #[cfg(feature = "rdfa")]
+            SyntaxErrorKind::Rdfa(_) => None,
         }
     }
 
@@ -93,6 +123,21 @@ impl From<oxttl::TurtleSyntaxError> for RdfSyntaxError {
     }
 }
 
+impl From<oxmicrodata::MicrodataSyntaxError> for RdfSyntaxError {
+    #[inline]
+    fn from(error: oxmicrodata::MicrodataSyntaxError) -> Self {
+        Self(SyntaxErrorKind::Microdata(error))
+    }
+}
+
+#[cfg(feature = "rdfa")]
+impl From<oxrdfa::RdfaSyntaxError> for RdfSyntaxError {
+    #[inline]
+    fn from(error: oxrdfa::RdfaSyntaxError) -> Self {
+        Self(SyntaxErrorKind::Rdfa(error))
+    }
+}
+
 impl From<oxrdfxml::RdfXmlSyntaxError> for RdfSyntaxError {
     #[inline]
     fn from(error: oxrdfxml::RdfXmlSyntaxError) -> Self {
@@ -105,6 +150,9 @@ impl From<RdfSyntaxError> for io::Error {
     fn from(error: RdfSyntaxError) -> Self {
         match error.0 {
             SyntaxErrorKind::Turtle(error) => error.into(),
+            SyntaxErrorKind::Microdata(error) => error.into(),
+            #[cfg(feature = "rdfa")]
+            SyntaxErrorKind::Rdfa(error) => error.into(),
             SyntaxErrorKind::RdfXml(error) => error.into(),
             SyntaxErrorKind::Msg(msg) => Self::new(io::ErrorKind::InvalidData, msg),
         }
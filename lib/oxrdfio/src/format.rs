@@ -12,6 +12,11 @@ pub enum RdfFormat {
     NQuads,
     /// [N-Triples](https://www.w3.org/TR/n-triples/)
     NTriples,
+    /// [Microdata](https://html.spec.whatwg.org/multipage/microdata.html) embedded in HTML
+    Microdata,
+    /// [RDFa](https://www.w3.org/TR/rdfa-core/) embedded in HTML
+    #[cfg(feature = "rdfa")]
+    Rdfa,
     /// [RDF/XML](https://www.w3.org/TR/rdf-syntax-grammar/)
     RdfXml,
     /// [TriG](https://www.w3.org/TR/trig/)
@@ -37,6 +42,9 @@ impl RdfFormat {
             Self::N3 => "http://www.w3.org/ns/formats/N3",
             Self::NQuads => "http://www.w3.org/ns/formats/N-Quads",
             Self::NTriples => "http://www.w3.org/ns/formats/N-Triples",
+            Self::Microdata => "http://www.w3.org/ns/formats/microdata",
+            #[cfg(feature = "rdfa")]
+            Self::Rdfa => "http://www.w3.org/ns/formats/RDFa",
             Self::RdfXml => "http://www.w3.org/ns/formats/RDF_XML",
             Self::TriG => "http://www.w3.org/ns/formats/TriG",
             Self::Turtle => "http://www.w3.org/ns/formats/Turtle",
@@ -56,6 +64,9 @@ impl RdfFormat {
             Self::N3 => "text/n3",
             Self::NQuads => "application/n-quads",
             Self::NTriples => "application/n-triples",
+            Self::Microdata => "text/html",
+            #[cfg(feature = "rdfa")]
+            Self::Rdfa => "text/html",
             Self::RdfXml => "application/rdf+xml",
             Self::TriG => "application/trig",
             Self::Turtle => "text/turtle",
@@ -75,6 +86,9 @@ impl RdfFormat {
             Self::N3 => "n3",
             Self::NQuads => "nq",
             Self::NTriples => "nt",
+            Self::Microdata => "html",
+            #[cfg(feature = "rdfa")]
+            Self::Rdfa => "html",
             Self::RdfXml => "rdf",
             Self::TriG => "trig",
             Self::Turtle => "ttl",
@@ -94,6 +108,9 @@ impl RdfFormat {
             Self::N3 => "N3",
             Self::NQuads => "N-Quads",
             Self::NTriples => "N-Triples",
+            Self::Microdata => "Microdata",
+            #[cfg(feature = "rdfa")]
+            Self::Rdfa => "RDFa",
             Self::RdfXml => "RDF/XML",
             Self::TriG => "TriG",
             Self::Turtle => "Turtle",
@@ -146,7 +163,9 @@ impl RdfFormat {
     /// ```
     #[inline]
     pub fn from_media_type(media_type: &str) -> Option<Self> {
-        const MEDIA_SUBTYPES: [(&str, RdfFormat); 10] = [
+        const MEDIA_SUBTYPES: &[(&str, RdfFormat)] = &[
+            #[cfg(feature = "rdfa")]
+            ("html", RdfFormat::Rdfa),
             ("n-quads", RdfFormat::NQuads),
             ("n-triples", RdfFormat::NTriples),
             ("n3", RdfFormat::N3),
@@ -156,6 +175,8 @@ impl RdfFormat {
             ("rdf+xml", RdfFormat::RdfXml),
             ("trig", RdfFormat::TriG),
             ("turtle", RdfFormat::Turtle),
+            #[cfg(feature = "rdfa")]
+            ("xhtml+xml", RdfFormat::Rdfa),
             ("xml", RdfFormat::RdfXml),
         ];
 
@@ -170,7 +191,7 @@ impl RdfFormat {
         }
         let subtype = subtype.trim();
         let subtype = subtype.strip_prefix("x-").unwrap_or(subtype);
-        for (candidate_subtype, candidate_id) in MEDIA_SUBTYPES {
+        for &(candidate_subtype, candidate_id) in MEDIA_SUBTYPES {
             if candidate_subtype.eq_ignore_ascii_case(subtype) {
                 return Some(candidate_id);
             }
@@ -190,7 +211,11 @@ impl RdfFormat {
     /// ```
     #[inline]
     pub fn from_extension(extension: &str) -> Option<Self> {
-        const MEDIA_TYPES: [(&str, RdfFormat); 8] = [
+        const MEDIA_TYPES: &[(&str, RdfFormat)] = &[
+            #[cfg(feature = "rdfa")]
+            ("htm", RdfFormat::Rdfa),
+            #[cfg(feature = "rdfa")]
+            ("html", RdfFormat::Rdfa),
             ("n3", RdfFormat::N3),
             ("nq", RdfFormat::NQuads),
             ("nt", RdfFormat::NTriples),
@@ -198,9 +223,11 @@ impl RdfFormat {
             ("trig", RdfFormat::TriG),
             ("ttl", RdfFormat::Turtle),
             ("txt", RdfFormat::NTriples),
+            #[cfg(feature = "rdfa")]
+            ("xhtml", RdfFormat::Rdfa),
             ("xml", RdfFormat::RdfXml),
         ];
-        for (candidate_extension, candidate_id) in MEDIA_TYPES {
+        for &(candidate_extension, candidate_id) in MEDIA_TYPES {
             if candidate_extension.eq_ignore_ascii_case(extension) {
                 return Some(candidate_id);
             }
@@ -7,6 +7,9 @@
 mod error;
 mod format;
 mod parser;
+mod prefixes;
+#[cfg(feature = "rdf-star")]
+mod reification;
 mod serializer;
 
 pub use error::{RdfParseError, RdfSyntaxError, TextPosition};
@@ -2,8 +2,17 @@
 
 pub use crate::error::RdfParseError;
 use crate::format::RdfFormat;
+#[cfg(feature = "rdf-star")]
+use crate::reification::QuotedTripleUnreifier;
 use crate::RdfSyntaxError;
-use oxrdf::{BlankNode, GraphName, IriParseError, Quad, Subject, Term, Triple};
+#[cfg(feature = "async-tokio")]
+use oxmicrodata::TokioAsyncReaderMicrodataParser;
+use oxmicrodata::{MicrodataParser, ReaderMicrodataParser, SliceMicrodataParser};
+use oxrdf::{BlankNode, GraphName, IriParseError, NamedNode, Quad, Subject, Term, Triple};
+#[cfg(all(feature = "async-tokio", feature = "rdfa"))]
+use oxrdfa::TokioAsyncReaderRdfaParser;
+#[cfg(feature = "rdfa")]
+use oxrdfa::{RdfaParser, RdfaPrefixesIter, ReaderRdfaParser, SliceRdfaParser};
 #[cfg(feature = "async-tokio")]
 use oxrdfxml::TokioAsyncReaderRdfXmlParser;
 use oxrdfxml::{RdfXmlParser, RdfXmlPrefixesIter, ReaderRdfXmlParser, SliceRdfXmlParser};
@@ -33,6 +42,8 @@ use tokio::io::AsyncRead;
 /// * [N3](https://w3c.github.io/N3/spec/) ([`RdfFormat::N3`])
 /// * [N-Quads](https://www.w3.org/TR/n-quads/) ([`RdfFormat::NQuads`])
 /// * [N-Triples](https://www.w3.org/TR/n-triples/) ([`RdfFormat::NTriples`])
+/// * [Microdata](https://html.spec.whatwg.org/multipage/microdata.html) ([`RdfFormat::Microdata`])
+/// * [RDFa](https://www.w3.org/TR/rdfa-core/) ([`RdfFormat::Rdfa`])
 /// * [RDF/XML](https://www.w3.org/TR/rdf-syntax-grammar/) ([`RdfFormat::RdfXml`])
 /// * [TriG](https://www.w3.org/TR/trig/) ([`RdfFormat::TriG`])
 /// * [Turtle](https://www.w3.org/TR/turtle/) ([`RdfFormat::Turtle`])
@@ -40,6 +51,9 @@ use tokio::io::AsyncRead;
 /// Note the useful options:
 /// - [`with_base_iri`](Self::with_base_iri) to resolve the relative IRIs.
 /// - [`rename_blank_nodes`](Self::rename_blank_nodes) to rename the blank nodes to auto-generated numbers to avoid conflicts when merging RDF graphs together.
+/// - [`with_deskolemization`](Self::with_deskolemization) to replace the skolem IRIs by blank nodes.
+/// - [`unreify_quoted_triples`](Self::unreify_quoted_triples) to replace the RDF reifications by quoted triples.
+/// - [`with_renamed_graph`](Self::with_renamed_graph) to change the name of some named graphs.
 /// - [`without_named_graphs`](Self::without_named_graphs) to parse a single graph.
 /// - [`unchecked`](Self::unchecked) to skip some validations if the file is already known to be valid.
 ///
@@ -63,6 +77,10 @@ pub struct RdfParser {
     default_graph: GraphName,
     without_named_graphs: bool,
     rename_blank_nodes: bool,
+    skolem_iri_prefix: Option<String>,
+    #[cfg(feature = "rdf-star")]
+    unreify_quoted_triples: bool,
+    renamed_graphs: HashMap<NamedNode, GraphName>,
 }
 
 #[derive(Clone)]
@@ -70,6 +88,9 @@ enum RdfParserKind {
     N3(N3Parser),
     NQuads(NQuadsParser),
     NTriples(NTriplesParser),
+    Microdata(MicrodataParser),
+    #[cfg(feature = "rdfa")]
+    Rdfa(RdfaParser),
     RdfXml(RdfXmlParser),
     TriG(TriGParser),
     Turtle(TurtleParser),
@@ -102,6 +123,9 @@ impl RdfParser {
                         NTriplesParser::new()
                     }
                 }),
+                RdfFormat::Microdata => RdfParserKind::Microdata(MicrodataParser::new()),
+                #[cfg(feature = "rdfa")]
+                RdfFormat::Rdfa => RdfParserKind::Rdfa(RdfaParser::new()),
                 RdfFormat::RdfXml => RdfParserKind::RdfXml(RdfXmlParser::new()),
                 RdfFormat::TriG => RdfParserKind::TriG({
                     #[cfg(feature = "rdf-star")]
@@ -127,6 +151,10 @@ impl RdfParser {
             default_graph: GraphName::DefaultGraph,
             without_named_graphs: false,
             rename_blank_nodes: false,
+            skolem_iri_prefix: None,
+            #[cfg(feature = "rdf-star")]
+            unreify_quoted_triples: false,
+            renamed_graphs: HashMap::new(),
         }
     }
 
@@ -145,6 +173,9 @@ impl RdfParser {
             RdfParserKind::N3(_) => RdfFormat::N3,
             RdfParserKind::NQuads(_) => RdfFormat::NQuads,
             RdfParserKind::NTriples(_) => RdfFormat::NTriples,
+            RdfParserKind::Microdata(_) => RdfFormat::Microdata,
+            #[cfg(feature = "rdfa")]
+            RdfParserKind::Rdfa(_) => RdfFormat::Rdfa,
             RdfParserKind::RdfXml(_) => RdfFormat::RdfXml,
             RdfParserKind::TriG(_) => RdfFormat::TriG,
             RdfParserKind::Turtle(_) => RdfFormat::Turtle,
@@ -173,6 +204,9 @@ impl RdfParser {
             RdfParserKind::N3(p) => RdfParserKind::N3(p.with_base_iri(base_iri)?),
             RdfParserKind::NTriples(p) => RdfParserKind::NTriples(p),
             RdfParserKind::NQuads(p) => RdfParserKind::NQuads(p),
+            RdfParserKind::Microdata(p) => RdfParserKind::Microdata(p.with_base_iri(base_iri)?),
+            #[cfg(feature = "rdfa")]
+            RdfParserKind::Rdfa(p) => RdfParserKind::Rdfa(p.with_base_iri(base_iri)?),
             RdfParserKind::RdfXml(p) => RdfParserKind::RdfXml(p.with_base_iri(base_iri)?),
             RdfParserKind::TriG(p) => RdfParserKind::TriG(p.with_base_iri(base_iri)?),
             RdfParserKind::Turtle(p) => RdfParserKind::Turtle(p.with_base_iri(base_iri)?),
@@ -203,6 +237,38 @@ impl RdfParser {
         self
     }
 
+    /// Replaces the named graph `from` by `to` in the returned quads.
+    ///
+    /// Can be called multiple times to rename multiple graphs.
+    /// The graph `to` might be the default graph to merge the named graph into it.
+    ///
+    /// ```
+    /// use oxrdf::NamedNode;
+    /// use oxrdfio::{RdfFormat, RdfParser};
+    ///
+    /// let file = "<http://example.com/s> <http://example.com/p> <http://example.com/o> <http://example.com/g> .";
+    ///
+    /// let quads = RdfParser::from_format(RdfFormat::NQuads)
+    ///     .with_renamed_graph(
+    ///         NamedNode::new("http://example.com/g")?,
+    ///         NamedNode::new("http://example.com/g2")?,
+    ///     )
+    ///     .for_reader(file.as_bytes())
+    ///     .collect::<Result<Vec<_>, _>>()?;
+    ///
+    /// assert_eq!(quads[0].graph_name.to_string(), "<http://example.com/g2>");
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    pub fn with_renamed_graph(
+        mut self,
+        from: impl Into<NamedNode>,
+        to: impl Into<GraphName>,
+    ) -> Self {
+        self.renamed_graphs.insert(from.into(), to.into());
+        self
+    }
+
     /// Sets that the parser must fail if parsing a named graph.
     ///
     /// This function restricts the parser to only parse a single [RDF graph](https://www.w3.org/TR/rdf11-concepts/#dfn-rdf-graph) and not an [RDF dataset](https://www.w3.org/TR/rdf11-concepts/#dfn-rdf-dataset).
@@ -247,6 +313,67 @@ impl RdfParser {
         self
     }
 
+    /// Replaces the [skolem IRIs](https://www.w3.org/TR/rdf11-concepts/#section-skolemization) minted under the given authority by blank nodes.
+    ///
+    /// The IRI `{authority}/.well-known/genid/{id}` becomes the blank node `_:{id}`.
+    /// It is the reverse of [`RdfSerializer::with_skolemization`](crate::RdfSerializer::with_skolemization).
+    ///
+    /// ```
+    /// use oxrdfio::{RdfFormat, RdfParser};
+    ///
+    /// let file = "<http://example.com/.well-known/genid/a> <http://example.com/p> <http://example.com/o> .";
+    ///
+    /// let quads = RdfParser::from_format(RdfFormat::NTriples)
+    ///     .with_deskolemization("http://example.com")?
+    ///     .for_reader(file.as_bytes())
+    ///     .collect::<Result<Vec<_>, _>>()?;
+    /// assert_eq!(quads[0].subject.to_string(), "_:a");
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    pub fn with_deskolemization(
+        mut self,
+        authority: impl Into<String>,
+    ) -> Result<Self, IriParseError> {
+        self.skolem_iri_prefix = Some(skolem_iri_prefix(authority.into())?);
+        Ok(self)
+    }
+
+    /// Replaces the blank nodes described using the [RDF reification vocabulary](https://www.w3.org/TR/rdf11-schema/#ch_reificationvocab) by the quoted triples they describe.
+    ///
+    /// A blank node is replaced if it is an `rdf:Statement` with exactly one `rdf:subject`, `rdf:predicate` and `rdf:object` in the same graph and if it is used outside of this description.
+    /// The description is then removed from the output.
+    /// It is the reverse of [`RdfSerializer::reify_quoted_triples`](crate::RdfSerializer::reify_quoted_triples).
+    ///
+    /// <div class="warning">The full file is loaded in memory before returning the first quad.</div>
+    ///
+    /// ```
+    /// use oxrdfio::{RdfFormat, RdfParser};
+    ///
+    /// let file = r#"_:r <http://example.com/source> <http://example.com/doc> .
+    /// _:r <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://www.w3.org/1999/02/22-rdf-syntax-ns#Statement> .
+    /// _:r <http://www.w3.org/1999/02/22-rdf-syntax-ns#subject> <http://example.com/s> .
+    /// _:r <http://www.w3.org/1999/02/22-rdf-syntax-ns#predicate> <http://example.com/p> .
+    /// _:r <http://www.w3.org/1999/02/22-rdf-syntax-ns#object> <http://example.com/o> ."#;
+    ///
+    /// let quads = RdfParser::from_format(RdfFormat::NTriples)
+    ///     .unreify_quoted_triples()
+    ///     .for_reader(file.as_bytes())
+    ///     .collect::<Result<Vec<_>, _>>()?;
+    /// assert_eq!(quads.len(), 1);
+    /// assert_eq!(
+    ///     quads[0].subject.to_string(),
+    ///     "<<<http://example.com/s> <http://example.com/p> <http://example.com/o>>>"
+    /// );
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[cfg(feature = "rdf-star")]
+    #[inline]
+    pub fn unreify_quoted_triples(mut self) -> Self {
+        self.unreify_quoted_triples = true;
+        self
+    }
+
     /// Assumes the file is valid to make parsing faster.
     ///
     /// It will skip some validations.
@@ -258,6 +385,9 @@ impl RdfParser {
             RdfParserKind::N3(p) => RdfParserKind::N3(p.unchecked()),
             RdfParserKind::NTriples(p) => RdfParserKind::NTriples(p.unchecked()),
             RdfParserKind::NQuads(p) => RdfParserKind::NQuads(p.unchecked()),
+            RdfParserKind::Microdata(p) => RdfParserKind::Microdata(p.unchecked()),
+            #[cfg(feature = "rdfa")]
+            RdfParserKind::Rdfa(p) => RdfParserKind::Rdfa(p.unchecked()),
             RdfParserKind::RdfXml(p) => RdfParserKind::RdfXml(p.unchecked()),
             RdfParserKind::TriG(p) => RdfParserKind::TriG(p.unchecked()),
             RdfParserKind::Turtle(p) => RdfParserKind::Turtle(p.unchecked()),
@@ -288,6 +418,11 @@ impl RdfParser {
                 RdfParserKind::N3(p) => ReaderQuadParserKind::N3(p.for_reader(reader)),
                 RdfParserKind::NQuads(p) => ReaderQuadParserKind::NQuads(p.for_reader(reader)),
                 RdfParserKind::NTriples(p) => ReaderQuadParserKind::NTriples(p.for_reader(reader)),
+                RdfParserKind::Microdata(p) => {
+                    ReaderQuadParserKind::Microdata(p.for_reader(reader))
+                }
+                #[cfg(feature = "rdfa")]
+                RdfParserKind::Rdfa(p) => ReaderQuadParserKind::Rdfa(p.for_reader(reader)),
                 RdfParserKind::RdfXml(p) => ReaderQuadParserKind::RdfXml(p.for_reader(reader)),
                 RdfParserKind::TriG(p) => ReaderQuadParserKind::TriG(p.for_reader(reader)),
                 RdfParserKind::Turtle(p) => ReaderQuadParserKind::Turtle(p.for_reader(reader)),
@@ -296,7 +431,13 @@ impl RdfParser {
                 default_graph: self.default_graph.clone(),
                 without_named_graphs: self.without_named_graphs,
                 blank_node_map: self.rename_blank_nodes.then(HashMap::new),
+                skolem_iri_prefix: self.skolem_iri_prefix,
+                renamed_graphs: self.renamed_graphs,
             },
+            #[cfg(feature = "rdf-star")]
+            unreifier: self
+                .unreify_quoted_triples
+                .then(QuotedTripleUnreifier::default),
         }
     }
 
@@ -335,6 +476,15 @@ impl RdfParser {
                 RdfParserKind::NTriples(p) => {
                     TokioAsyncReaderQuadParserKind::NTriples(p.for_tokio_async_reader(reader))
                 }
+                RdfParserKind::Microdata(p) => {
+                    TokioAsyncReaderQuadParserKind::Microdata(p.for_tokio_async_reader(reader))
+                }
+                #[cfg(feature = "rdfa")]
+                RdfParserKind::Rdfa(p) =>
+                {
+                    #[cfg(feature = "rdfa")]
+                    TokioAsyncReaderQuadParserKind::Rdfa(p.for_tokio_async_reader(reader))
+                }
                 RdfParserKind::RdfXml(p) => {
                     TokioAsyncReaderQuadParserKind::RdfXml(p.for_tokio_async_reader(reader))
                 }
@@ -349,7 +499,13 @@ impl RdfParser {
                 default_graph: self.default_graph.clone(),
                 without_named_graphs: self.without_named_graphs,
                 blank_node_map: self.rename_blank_nodes.then(HashMap::new),
+                skolem_iri_prefix: self.skolem_iri_prefix,
+                renamed_graphs: self.renamed_graphs,
             },
+            #[cfg(feature = "rdf-star")]
+            unreifier: self
+                .unreify_quoted_triples
+                .then(QuotedTripleUnreifier::default),
         }
     }
 
@@ -374,6 +530,9 @@ impl RdfParser {
                 RdfParserKind::N3(p) => SliceQuadParserKind::N3(p.for_slice(slice)),
                 RdfParserKind::NQuads(p) => SliceQuadParserKind::NQuads(p.for_slice(slice)),
                 RdfParserKind::NTriples(p) => SliceQuadParserKind::NTriples(p.for_slice(slice)),
+                RdfParserKind::Microdata(p) => SliceQuadParserKind::Microdata(p.for_slice(slice)),
+                #[cfg(feature = "rdfa")]
+                RdfParserKind::Rdfa(p) => SliceQuadParserKind::Rdfa(p.for_slice(slice)),
                 RdfParserKind::RdfXml(p) => SliceQuadParserKind::RdfXml(p.for_slice(slice)),
                 RdfParserKind::TriG(p) => SliceQuadParserKind::TriG(p.for_slice(slice)),
                 RdfParserKind::Turtle(p) => SliceQuadParserKind::Turtle(p.for_slice(slice)),
@@ -382,7 +541,13 @@ impl RdfParser {
                 default_graph: self.default_graph.clone(),
                 without_named_graphs: self.without_named_graphs,
                 blank_node_map: self.rename_blank_nodes.then(HashMap::new),
+                skolem_iri_prefix: self.skolem_iri_prefix,
+                renamed_graphs: self.renamed_graphs,
             },
+            #[cfg(feature = "rdf-star")]
+            unreifier: self
+                .unreify_quoted_triples
+                .then(QuotedTripleUnreifier::default),
         }
     }
 }
@@ -416,12 +581,17 @@ impl From<RdfFormat> for RdfParser {
 pub struct ReaderQuadParser<R: Read> {
     inner: ReaderQuadParserKind<R>,
     mapper: QuadMapper,
+    #[cfg(feature = "rdf-star")]
+    unreifier: Option<QuotedTripleUnreifier>,
 }
 
 enum ReaderQuadParserKind<R: Read> {
     N3(ReaderN3Parser<R>),
     NQuads(ReaderNQuadsParser<R>),
     NTriples(ReaderNTriplesParser<R>),
+    Microdata(ReaderMicrodataParser<R>),
+    #[cfg(feature = "rdfa")]
+    Rdfa(ReaderRdfaParser<R>),
     RdfXml(ReaderRdfXmlParser<R>),
     TriG(ReaderTriGParser<R>),
     Turtle(ReaderTurtleParser<R>),
@@ -431,6 +601,27 @@ impl<R: Read> Iterator for ReaderQuadParser<R> {
     type Item = Result<Quad, RdfParseError>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        #[cfg(feature = "rdf-star")]
+        if let Some(mut unreifier) = self.unreifier.take() {
+            let result = loop {
+                if unreifier.is_finished() {
+                    break unreifier.next().map(Ok);
+                }
+                match self.parse_next() {
+                    Some(Ok(quad)) => unreifier.push(quad),
+                    Some(Err(e)) => break Some(Err(e)),
+                    None => unreifier.finish(),
+                }
+            };
+            self.unreifier = Some(unreifier);
+            return result;
+        }
+        self.parse_next()
+    }
+}
+
+impl<R: Read> ReaderQuadParser<R> {
+    fn parse_next(&mut self) -> Option<Result<Quad, RdfParseError>> {
         Some(match &mut self.inner {
             ReaderQuadParserKind::N3(parser) => match parser.next()? {
                 Ok(quad) => self.mapper.map_n3_quad(quad).map_err(Into::into),
@@ -444,6 +635,15 @@ impl<R: Read> Iterator for ReaderQuadParser<R> {
                 Ok(triple) => Ok(self.mapper.map_triple_to_quad(triple)),
                 Err(e) => Err(e.into()),
             },
+            ReaderQuadParserKind::Microdata(parser) => match parser.next()? {
+                Ok(triple) => Ok(self.mapper.map_triple_to_quad(triple)),
+                Err(e) => Err(e.into()),
+            },
+            #[cfg(feature = "rdfa")]
+            ReaderQuadParserKind::Rdfa(parser) => match parser.next()? {
+                Ok(triple) => Ok(self.mapper.map_triple_to_quad(triple)),
+                Err(e) => Err(e.into()),
+            },
             ReaderQuadParserKind::RdfXml(parser) => match parser.next()? {
                 Ok(triple) => Ok(self.mapper.map_triple_to_quad(triple)),
                 Err(e) => Err(e.into()),
@@ -458,9 +658,7 @@ impl<R: Read> Iterator for ReaderQuadParser<R> {
             },
         })
     }
-}
 
-impl<R: Read> ReaderQuadParser<R> {
     /// The list of IRI prefixes considered at the current step of the parsing.
     ///
     /// This method returns (prefix name, prefix value) tuples.
@@ -493,10 +691,12 @@ impl<R: Read> ReaderQuadParser<R> {
                 ReaderQuadParserKind::N3(p) => PrefixesIterKind::N3(p.prefixes()),
                 ReaderQuadParserKind::TriG(p) => PrefixesIterKind::TriG(p.prefixes()),
                 ReaderQuadParserKind::Turtle(p) => PrefixesIterKind::Turtle(p.prefixes()),
+                #[cfg(feature = "rdfa")]
+                ReaderQuadParserKind::Rdfa(p) => PrefixesIterKind::Rdfa(p.prefixes()),
                 ReaderQuadParserKind::RdfXml(p) => PrefixesIterKind::RdfXml(p.prefixes()),
-                ReaderQuadParserKind::NQuads(_) | ReaderQuadParserKind::NTriples(_) => {
-                    PrefixesIterKind::None
-                }
+                ReaderQuadParserKind::Microdata(_)
+                | ReaderQuadParserKind::NQuads(_)
+                | ReaderQuadParserKind::NTriples(_) => PrefixesIterKind::None,
             },
         }
     }
@@ -525,6 +725,9 @@ impl<R: Read> ReaderQuadParser<R> {
             ReaderQuadParserKind::N3(p) => p.base_iri(),
             ReaderQuadParserKind::TriG(p) => p.base_iri(),
             ReaderQuadParserKind::Turtle(p) => p.base_iri(),
+            ReaderQuadParserKind::Microdata(p) => p.base_iri(),
+            #[cfg(feature = "rdfa")]
+            ReaderQuadParserKind::Rdfa(p) => p.base_iri(),
             ReaderQuadParserKind::RdfXml(p) => p.base_iri(),
             ReaderQuadParserKind::NQuads(_) | ReaderQuadParserKind::NTriples(_) => None,
         }
@@ -557,6 +760,8 @@ impl<R: Read> ReaderQuadParser<R> {
 pub struct TokioAsyncReaderQuadParser<R: AsyncRead + Unpin> {
     inner: TokioAsyncReaderQuadParserKind<R>,
     mapper: QuadMapper,
+    #[cfg(feature = "rdf-star")]
+    unreifier: Option<QuotedTripleUnreifier>,
 }
 
 #[cfg(feature = "async-tokio")]
@@ -564,6 +769,9 @@ enum TokioAsyncReaderQuadParserKind<R: AsyncRead + Unpin> {
     N3(TokioAsyncReaderN3Parser<R>),
     NQuads(TokioAsyncReaderNQuadsParser<R>),
     NTriples(TokioAsyncReaderNTriplesParser<R>),
+    Microdata(TokioAsyncReaderMicrodataParser<R>),
+    #[cfg(feature = "rdfa")]
+    Rdfa(TokioAsyncReaderRdfaParser<R>),
     RdfXml(TokioAsyncReaderRdfXmlParser<R>),
     TriG(TokioAsyncReaderTriGParser<R>),
     Turtle(TokioAsyncReaderTurtleParser<R>),
@@ -572,6 +780,25 @@ enum TokioAsyncReaderQuadParserKind<R: AsyncRead + Unpin> {
 #[cfg(feature = "async-tokio")]
 impl<R: AsyncRead + Unpin> TokioAsyncReaderQuadParser<R> {
     pub async fn next(&mut self) -> Option<Result<Quad, RdfParseError>> {
+        #[cfg(feature = "rdf-star")]
+        if let Some(mut unreifier) = self.unreifier.take() {
+            let result = loop {
+                if unreifier.is_finished() {
+                    break unreifier.next().map(Ok);
+                }
+                match self.parse_next().await {
+                    Some(Ok(quad)) => unreifier.push(quad),
+                    Some(Err(e)) => break Some(Err(e)),
+                    None => unreifier.finish(),
+                }
+            };
+            self.unreifier = Some(unreifier);
+            return result;
+        }
+        self.parse_next().await
+    }
+
+    async fn parse_next(&mut self) -> Option<Result<Quad, RdfParseError>> {
         Some(match &mut self.inner {
             TokioAsyncReaderQuadParserKind::N3(parser) => match parser.next().await? {
                 Ok(quad) => self.mapper.map_n3_quad(quad).map_err(Into::into),
@@ -585,6 +812,15 @@ impl<R: AsyncRead + Unpin> TokioAsyncReaderQuadParser<R> {
                 Ok(triple) => Ok(self.mapper.map_triple_to_quad(triple)),
                 Err(e) => Err(e.into()),
             },
+            TokioAsyncReaderQuadParserKind::Microdata(parser) => match parser.next().await? {
+                Ok(triple) => Ok(self.mapper.map_triple_to_quad(triple)),
+                Err(e) => Err(e.into()),
+            },
+            #[cfg(feature = "rdfa")]
+            TokioAsyncReaderQuadParserKind::Rdfa(parser) => match parser.next().await? {
+                Ok(triple) => Ok(self.mapper.map_triple_to_quad(triple)),
+                Err(e) => Err(e.into()),
+            },
             TokioAsyncReaderQuadParserKind::RdfXml(parser) => match parser.next().await? {
                 Ok(triple) => Ok(self.mapper.map_triple_to_quad(triple)),
                 Err(e) => Err(e.into()),
@@ -636,8 +872,11 @@ impl<R: AsyncRead + Unpin> TokioAsyncReaderQuadParser<R> {
                 TokioAsyncReaderQuadParserKind::N3(p) => PrefixesIterKind::N3(p.prefixes()),
                 TokioAsyncReaderQuadParserKind::TriG(p) => PrefixesIterKind::TriG(p.prefixes()),
                 TokioAsyncReaderQuadParserKind::Turtle(p) => PrefixesIterKind::Turtle(p.prefixes()),
+                #[cfg(feature = "rdfa")]
+                TokioAsyncReaderQuadParserKind::Rdfa(p) => PrefixesIterKind::Rdfa(p.prefixes()),
                 TokioAsyncReaderQuadParserKind::RdfXml(p) => PrefixesIterKind::RdfXml(p.prefixes()),
-                TokioAsyncReaderQuadParserKind::NQuads(_)
+                TokioAsyncReaderQuadParserKind::Microdata(_)
+                | TokioAsyncReaderQuadParserKind::NQuads(_)
                 | TokioAsyncReaderQuadParserKind::NTriples(_) => PrefixesIterKind::None,
             },
         }
@@ -671,6 +910,9 @@ impl<R: AsyncRead + Unpin> TokioAsyncReaderQuadParser<R> {
             TokioAsyncReaderQuadParserKind::N3(p) => p.base_iri(),
             TokioAsyncReaderQuadParserKind::TriG(p) => p.base_iri(),
             TokioAsyncReaderQuadParserKind::Turtle(p) => p.base_iri(),
+            TokioAsyncReaderQuadParserKind::Microdata(p) => p.base_iri(),
+            #[cfg(feature = "rdfa")]
+            TokioAsyncReaderQuadParserKind::Rdfa(p) => p.base_iri(),
             TokioAsyncReaderQuadParserKind::RdfXml(p) => p.base_iri(),
             TokioAsyncReaderQuadParserKind::NQuads(_)
             | TokioAsyncReaderQuadParserKind::NTriples(_) => None,
@@ -699,12 +941,17 @@ impl<R: AsyncRead + Unpin> TokioAsyncReaderQuadParser<R> {
 pub struct SliceQuadParser<'a> {
     inner: SliceQuadParserKind<'a>,
     mapper: QuadMapper,
+    #[cfg(feature = "rdf-star")]
+    unreifier: Option<QuotedTripleUnreifier>,
 }
 
 enum SliceQuadParserKind<'a> {
     N3(SliceN3Parser<'a>),
     NQuads(SliceNQuadsParser<'a>),
     NTriples(SliceNTriplesParser<'a>),
+    Microdata(SliceMicrodataParser<'a>),
+    #[cfg(feature = "rdfa")]
+    Rdfa(SliceRdfaParser<'a>),
     RdfXml(SliceRdfXmlParser<'a>),
     TriG(SliceTriGParser<'a>),
     Turtle(SliceTurtleParser<'a>),
@@ -714,6 +961,27 @@ impl Iterator for SliceQuadParser<'_> {
     type Item = Result<Quad, RdfSyntaxError>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        #[cfg(feature = "rdf-star")]
+        if let Some(mut unreifier) = self.unreifier.take() {
+            let result = loop {
+                if unreifier.is_finished() {
+                    break unreifier.next().map(Ok);
+                }
+                match self.parse_next() {
+                    Some(Ok(quad)) => unreifier.push(quad),
+                    Some(Err(e)) => break Some(Err(e)),
+                    None => unreifier.finish(),
+                }
+            };
+            self.unreifier = Some(unreifier);
+            return result;
+        }
+        self.parse_next()
+    }
+}
+
+impl SliceQuadParser<'_> {
+    fn parse_next(&mut self) -> Option<Result<Quad, RdfSyntaxError>> {
         Some(match &mut self.inner {
             SliceQuadParserKind::N3(parser) => match parser.next()? {
                 Ok(quad) => self.mapper.map_n3_quad(quad),
@@ -727,6 +995,15 @@ impl Iterator for SliceQuadParser<'_> {
                 Ok(triple) => Ok(self.mapper.map_triple_to_quad(triple)),
                 Err(e) => Err(e.into()),
             },
+            SliceQuadParserKind::Microdata(parser) => match parser.next()? {
+                Ok(triple) => Ok(self.mapper.map_triple_to_quad(triple)),
+                Err(e) => Err(e.into()),
+            },
+            #[cfg(feature = "rdfa")]
+            SliceQuadParserKind::Rdfa(parser) => match parser.next()? {
+                Ok(triple) => Ok(self.mapper.map_triple_to_quad(triple)),
+                Err(e) => Err(e.into()),
+            },
             SliceQuadParserKind::RdfXml(parser) => match parser.next()? {
                 Ok(triple) => Ok(self.mapper.map_triple_to_quad(triple)),
                 Err(e) => Err(e.into()),
@@ -741,9 +1018,7 @@ impl Iterator for SliceQuadParser<'_> {
             },
         })
     }
-}
 
-impl SliceQuadParser<'_> {
     /// The list of IRI prefixes considered at the current step of the parsing.
     ///
     /// This method returns (prefix name, prefix value) tuples.
@@ -776,10 +1051,12 @@ impl SliceQuadParser<'_> {
                 SliceQuadParserKind::N3(p) => PrefixesIterKind::N3(p.prefixes()),
                 SliceQuadParserKind::TriG(p) => PrefixesIterKind::TriG(p.prefixes()),
                 SliceQuadParserKind::Turtle(p) => PrefixesIterKind::Turtle(p.prefixes()),
+                #[cfg(feature = "rdfa")]
+                SliceQuadParserKind::Rdfa(p) => PrefixesIterKind::Rdfa(p.prefixes()),
                 SliceQuadParserKind::RdfXml(p) => PrefixesIterKind::RdfXml(p.prefixes()),
-                SliceQuadParserKind::NQuads(_) | SliceQuadParserKind::NTriples(_) => {
-                    PrefixesIterKind::None
-                }
+                SliceQuadParserKind::Microdata(_)
+                | SliceQuadParserKind::NQuads(_)
+                | SliceQuadParserKind::NTriples(_) => PrefixesIterKind::None,
             },
         }
     }
@@ -808,6 +1085,9 @@ impl SliceQuadParser<'_> {
             SliceQuadParserKind::N3(p) => p.base_iri(),
             SliceQuadParserKind::TriG(p) => p.base_iri(),
             SliceQuadParserKind::Turtle(p) => p.base_iri(),
+            SliceQuadParserKind::Microdata(p) => p.base_iri(),
+            #[cfg(feature = "rdfa")]
+            SliceQuadParserKind::Rdfa(p) => p.base_iri(),
             SliceQuadParserKind::RdfXml(p) => p.base_iri(),
             SliceQuadParserKind::NQuads(_) | SliceQuadParserKind::NTriples(_) => None,
         }
@@ -825,6 +1105,8 @@ enum PrefixesIterKind<'a> {
     Turtle(TurtlePrefixesIter<'a>),
     TriG(TriGPrefixesIter<'a>),
     N3(N3PrefixesIter<'a>),
+    #[cfg(feature = "rdfa")]
+    Rdfa(RdfaPrefixesIter<'a>),
     RdfXml(RdfXmlPrefixesIter<'a>),
     None,
 }
@@ -838,6 +1120,8 @@ impl<'a> Iterator for PrefixesIter<'a> {
             PrefixesIterKind::Turtle(iter) => iter.next(),
             PrefixesIterKind::TriG(iter) => iter.next(),
             PrefixesIterKind::N3(iter) => iter.next(),
+            #[cfg(feature = "rdfa")]
+            PrefixesIterKind::Rdfa(iter) => iter.next(),
             PrefixesIterKind::RdfXml(iter) => iter.next(),
             PrefixesIterKind::None => None,
         }
@@ -849,19 +1133,44 @@ impl<'a> Iterator for PrefixesIter<'a> {
             PrefixesIterKind::Turtle(iter) => iter.size_hint(),
             PrefixesIterKind::TriG(iter) => iter.size_hint(),
             PrefixesIterKind::N3(iter) => iter.size_hint(),
+            #[cfg(feature = "rdfa")]
+            PrefixesIterKind::Rdfa(iter) => iter.size_hint(),
             PrefixesIterKind::RdfXml(iter) => iter.size_hint(),
             PrefixesIterKind::None => (0, Some(0)),
         }
     }
 }
 
+/// Builds the prefix of the skolem IRIs minted under the given authority
+pub(crate) fn skolem_iri_prefix(authority: String) -> Result<String, IriParseError> {
+    let mut prefix = authority;
+    if prefix.ends_with('/') {
+        prefix.pop();
+    }
+    prefix.push_str("/.well-known/genid/");
+    Ok(NamedNode::new(prefix)?.into_string())
+}
+
 struct QuadMapper {
     default_graph: GraphName,
     without_named_graphs: bool,
     blank_node_map: Option<HashMap<BlankNode, BlankNode>>,
+    skolem_iri_prefix: Option<String>,
+    renamed_graphs: HashMap<NamedNode, GraphName>,
 }
 
 impl QuadMapper {
+    fn map_named_node<T: From<NamedNode> + From<BlankNode>>(&mut self, node: NamedNode) -> T {
+        if let Some(skolem_iri_prefix) = &self.skolem_iri_prefix {
+            if let Some(id) = node.as_str().strip_prefix(skolem_iri_prefix.as_str()) {
+                if let Ok(node) = BlankNode::new(id) {
+                    return self.map_blank_node(node).into();
+                }
+            }
+        }
+        node.into()
+    }
+
     fn map_blank_node(&mut self, node: BlankNode) -> BlankNode {
         if let Some(blank_node_map) = &mut self.blank_node_map {
             blank_node_map
@@ -875,7 +1184,7 @@ impl QuadMapper {
 
     fn map_subject(&mut self, node: Subject) -> Subject {
         match node {
-            Subject::NamedNode(node) => node.into(),
+            Subject::NamedNode(node) => self.map_named_node(node),
             Subject::BlankNode(node) => self.map_blank_node(node).into(),
             #[cfg(feature = "rdf-star")]
             Subject::Triple(triple) => self.map_triple(*triple).into(),
@@ -884,7 +1193,7 @@ impl QuadMapper {
 
     fn map_term(&mut self, node: Term) -> Term {
         match node {
-            Term::NamedNode(node) => node.into(),
+            Term::NamedNode(node) => self.map_named_node(node),
             Term::BlankNode(node) => self.map_blank_node(node).into(),
             Term::Literal(literal) => literal.into(),
             #[cfg(feature = "rdf-star")]
@@ -905,8 +1214,10 @@ impl QuadMapper {
             GraphName::NamedNode(node) => {
                 if self.without_named_graphs {
                     Err(RdfSyntaxError::msg("Named graphs are not allowed"))
+                } else if let Some(graph_name) = self.renamed_graphs.get(&node) {
+                    Ok(graph_name.clone())
                 } else {
-                    Ok(node.into())
+                    Ok(self.map_named_node(node))
                 }
             }
             GraphName::BlankNode(node) => {
@@ -936,7 +1247,7 @@ impl QuadMapper {
     fn map_n3_quad(&mut self, quad: N3Quad) -> Result<Quad, RdfSyntaxError> {
         Ok(Quad {
             subject: match quad.subject {
-                N3Term::NamedNode(s) => Ok(s.into()),
+                N3Term::NamedNode(s) => Ok(self.map_named_node(s)),
                 N3Term::BlankNode(s) => Ok(self.map_blank_node(s).into()),
                 N3Term::Literal(_) => Err(RdfSyntaxError::msg(
                     "literals are not allowed in regular RDF subjects",
@@ -964,7 +1275,7 @@ impl QuadMapper {
                 )),
             }?,
             object: match quad.object {
-                N3Term::NamedNode(o) => Ok(o.into()),
+                N3Term::NamedNode(o) => Ok(self.map_named_node(o)),
                 N3Term::BlankNode(o) => Ok(self.map_blank_node(o).into()),
                 N3Term::Literal(o) => Ok(o.into()),
                 #[cfg(feature = "rdf-star")]
@@ -0,0 +1,204 @@
+//! Discovery of the prefixes to declare from the IRIs used in the serialized data.
+
+use oxrdf::vocab::{rdf, xsd};
+use oxrdf::{GraphNameRef, NamedNodeRef, Quad, QuadRef, SubjectRef, TermRef};
+use std::collections::{HashMap, HashSet};
+use std::mem::take;
+
+/// Number of quads buffered to discover the prefixes
+const SAMPLE_SIZE: usize = 10_000;
+
+/// Prefixes of some well-known vocabularies, taken from [prefix.cc](https://prefix.cc/)
+const WELL_KNOWN_PREFIXES: [(&str, &str); 33] = [
+    ("as", "https://www.w3.org/ns/activitystreams#"),
+    ("bibo", "http://purl.org/ontology/bibo/"),
+    ("dbo", "http://dbpedia.org/ontology/"),
+    ("dbr", "http://dbpedia.org/resource/"),
+    ("dc", "http://purl.org/dc/elements/1.1/"),
+    ("dcat", "http://www.w3.org/ns/dcat#"),
+    ("dcterms", "http://purl.org/dc/terms/"),
+    ("doap", "http://usefulinc.com/ns/doap#"),
+    ("foaf", "http://xmlns.com/foaf/0.1/"),
+    ("geo", "http://www.opengis.net/ont/geosparql#"),
+    ("gr", "http://purl.org/goodrelations/v1#"),
+    ("ldp", "http://www.w3.org/ns/ldp#"),
+    ("odrl", "http://www.w3.org/ns/odrl/2/"),
+    ("org", "http://www.w3.org/ns/org#"),
+    ("owl", "http://www.w3.org/2002/07/owl#"),
+    ("prov", "http://www.w3.org/ns/prov#"),
+    ("qb", "http://purl.org/linked-data/cube#"),
+    ("rdf", "http://www.w3.org/1999/02/22-rdf-syntax-ns#"),
+    ("rdfs", "http://www.w3.org/2000/01/rdf-schema#"),
+    ("schema", "http://schema.org/"),
+    ("schema", "https://schema.org/"),
+    ("sh", "http://www.w3.org/ns/shacl#"),
+    ("sioc", "http://rdfs.org/sioc/ns#"),
+    ("skos", "http://www.w3.org/2004/02/skos/core#"),
+    ("sosa", "http://www.w3.org/ns/sosa/"),
+    ("ssn", "http://www.w3.org/ns/ssn/"),
+    ("time", "http://www.w3.org/2006/time#"),
+    ("vcard", "http://www.w3.org/2006/vcard/ns#"),
+    ("void", "http://rdfs.org/ns/void#"),
+    ("wd", "http://www.wikidata.org/entity/"),
+    ("wdt", "http://www.wikidata.org/prop/direct/"),
+    ("wgs", "http://www.w3.org/2003/01/geo/wgs84_pos#"),
+    ("xsd", "http://www.w3.org/2001/XMLSchema#"),
+];
+
+/// Buffers the first quads to serialize and chooses the prefixes to declare from the IRIs they use.
+pub struct PrefixDiscovery {
+    /// Add a prefix for all the namespaces used at least twice
+    auto: bool,
+    /// Add a prefix for the used namespaces of the well-known prefix table
+    well_known: bool,
+    /// The prefixes already declared by the user
+    declared: Vec<(String, String)>,
+    quads: Vec<Quad>,
+}
+
+impl PrefixDiscovery {
+    pub fn new(auto: bool, well_known: bool, declared: Vec<(String, String)>) -> Self {
+        Self {
+            auto,
+            well_known,
+            declared,
+            quads: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, quad: QuadRef<'_>) {
+        self.quads.push(quad.into_owned());
+    }
+
+    /// Enough quads have been buffered to choose the prefixes
+    pub fn is_complete(&self) -> bool {
+        self.quads.len() >= SAMPLE_SIZE
+    }
+
+    /// Returns the prefixes to declare and the buffered quads
+    pub fn finish(&mut self) -> (Vec<(String, String)>, Vec<Quad>) {
+        let mut counts = HashMap::new();
+        for quad in &self.quads {
+            count_subject(quad.subject.as_ref(), &mut counts);
+            if quad.predicate != rdf::TYPE {
+                count_iri(quad.predicate.as_ref(), &mut counts);
+            }
+            count_term(quad.object.as_ref(), &mut counts);
+            if let GraphNameRef::NamedNode(graph_name) = quad.graph_name.as_ref() {
+                count_iri(graph_name, &mut counts);
+            }
+        }
+        // The most used namespaces are handled first to get the best names
+        let mut namespaces = counts.into_iter().collect::<Vec<_>>();
+        namespaces.sort_unstable_by(|(n1, c1), (n2, c2)| c2.cmp(c1).then_with(|| n1.cmp(n2)));
+
+        let mut used_names = self
+            .declared
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect::<HashSet<_>>();
+        let declared_iris = self
+            .declared
+            .iter()
+            .map(|(_, iri)| iri.as_str())
+            .collect::<HashSet<_>>();
+        let mut prefixes = Vec::new();
+        for (namespace, count) in namespaces {
+            if declared_iris.contains(namespace) {
+                continue;
+            }
+            let well_known_name = WELL_KNOWN_PREFIXES
+                .iter()
+                .find(|(_, iri)| *iri == namespace)
+                .map(|(name, _)| *name);
+            let name = match well_known_name {
+                Some(name) if self.well_known || (self.auto && count >= 2) => {
+                    fresh_name(name, &used_names)
+                }
+                None if self.auto && count >= 2 => fresh_name(&derive_name(namespace), &used_names),
+                _ => continue,
+            };
+            used_names.insert(name.clone());
+            prefixes.push((name, namespace.to_owned()));
+        }
+        prefixes.sort_unstable();
+        (prefixes, take(&mut self.quads))
+    }
+}
+
+fn count_subject<'a>(subject: SubjectRef<'a>, counts: &mut HashMap<&'a str, usize>) {
+    match subject {
+        SubjectRef::NamedNode(node) => count_iri(node, counts),
+        SubjectRef::BlankNode(_) => (),
+        #[cfg(feature = "rdf-star")]
+        SubjectRef::Triple(triple) => {
+            count_subject(triple.subject.as_ref(), counts);
+            count_iri(triple.predicate.as_ref(), counts);
+            count_term(triple.object.as_ref(), counts);
+        }
+    }
+}
+
+fn count_term<'a>(term: TermRef<'a>, counts: &mut HashMap<&'a str, usize>) {
+    match term {
+        TermRef::NamedNode(node) => count_iri(node, counts),
+        TermRef::BlankNode(_) => (),
+        TermRef::Literal(literal) => {
+            let datatype = literal.datatype();
+            if datatype != xsd::STRING && datatype != rdf::LANG_STRING {
+                count_iri(datatype, counts);
+            }
+        }
+        #[cfg(feature = "rdf-star")]
+        TermRef::Triple(triple) => {
+            count_subject(triple.subject.as_ref(), counts);
+            count_iri(triple.predicate.as_ref(), counts);
+            count_term(triple.object.as_ref(), counts);
+        }
+    }
+}
+
+/// Counts the namespace of the IRI i.e. everything up to its last `/` or `#`
+fn count_iri<'a>(iri: NamedNodeRef<'a>, counts: &mut HashMap<&'a str, usize>) {
+    let iri = iri.as_str();
+    if let Some(position) = iri.rfind(['/', '#']) {
+        if position + 1 < iri.len() {
+            *counts.entry(&iri[..=position]).or_default() += 1;
+        }
+    }
+}
+
+/// Builds a prefix name from the last segment of the namespace or from its host
+///
+/// For example `http://example.com/ns#` gives `ns` and `http://www.example.com/` gives `example`.
+fn derive_name(namespace: &str) -> String {
+    let segment = namespace
+        .trim_end_matches(['/', '#'])
+        .rsplit(['/', '#'])
+        .next()
+        .unwrap_or_default();
+    let segment = segment.strip_prefix("www.").unwrap_or(segment);
+    let name = segment
+        .chars()
+        .take_while(char::is_ascii_alphanumeric)
+        .collect::<String>()
+        .to_ascii_lowercase();
+    // We avoid the names reserved by XML
+    if name.starts_with(|c: char| c.is_ascii_alphabetic()) && !name.starts_with("xml") {
+        name
+    } else {
+        "ns".into()
+    }
+}
+
+/// Appends a number to the name if it is already used
+fn fresh_name(name: &str, used_names: &HashSet<String>) -> String {
+    if !used_names.contains(name) {
+        return name.into();
+    }
+    // One of the first used_names.len() + 1 numbers is always free
+    (1..=used_names.len() + 1)
+        .map(|i| format!("{name}{i}"))
+        .find(|name| !used_names.contains(name))
+        .unwrap_or_else(|| name.into())
+}
@@ -0,0 +1,498 @@
+//! Encoding of quoted triples using the [RDF reification vocabulary](https://www.w3.org/TR/rdf11-schema/#ch_reificationvocab).
+
+use oxrdf::vocab::rdf;
+use oxrdf::{
+    BlankNode, GraphName, Quad, QuadRef, Subject, SubjectRef, Term, TermRef, Triple, TripleRef,
+};
+use std::collections::{HashMap, HashSet};
+use std::mem::take;
+use std::vec;
+
+/// Replaces quoted triples by blank nodes described with the reification vocabulary.
+///
+/// The same quoted triple is always replaced by the same blank node inside of a given graph.
+#[derive(Default)]
+pub struct QuotedTripleReifier {
+    reifiers: HashMap<(Triple, GraphName), BlankNode>,
+}
+
+impl QuotedTripleReifier {
+    /// Returns the quads to write in place of the given one or `None` if it contains no quoted triple.
+    pub fn reify_quad(&mut self, quad: QuadRef<'_>) -> Option<Vec<Quad>> {
+        if !quad.subject.is_triple() && !quad.object.is_triple() {
+            return None;
+        }
+        let graph_name = quad.graph_name.into_owned();
+        let mut output = Vec::new();
+        let subject = self.reify_subject(quad.subject, &graph_name, &mut output);
+        let object = self.reify_term(quad.object, &graph_name, &mut output);
+        output.push(Quad::new(subject, quad.predicate, object, graph_name));
+        Some(output)
+    }
+
+    fn reify_subject(
+        &mut self,
+        subject: SubjectRef<'_>,
+        graph_name: &GraphName,
+        output: &mut Vec<Quad>,
+    ) -> Subject {
+        if let SubjectRef::Triple(triple) = subject {
+            self.reify_triple(triple.as_ref(), graph_name, output)
+                .into()
+        } else {
+            subject.into_owned()
+        }
+    }
+
+    fn reify_term(
+        &mut self,
+        term: TermRef<'_>,
+        graph_name: &GraphName,
+        output: &mut Vec<Quad>,
+    ) -> Term {
+        if let TermRef::Triple(triple) = term {
+            self.reify_triple(triple.as_ref(), graph_name, output)
+                .into()
+        } else {
+            term.into_owned()
+        }
+    }
+
+    fn reify_triple(
+        &mut self,
+        triple: TripleRef<'_>,
+        graph_name: &GraphName,
+        output: &mut Vec<Quad>,
+    ) -> BlankNode {
+        let key = (triple.into_owned(), graph_name.clone());
+        if let Some(reifier) = self.reifiers.get(&key) {
+            return reifier.clone();
+        }
+        let reifier = BlankNode::default();
+        let subject = self.reify_subject(triple.subject, graph_name, output);
+        let object = self.reify_term(triple.object, graph_name, output);
+        output.extend([
+            Quad::new(
+                reifier.clone(),
+                rdf::TYPE,
+                rdf::STATEMENT,
+                graph_name.clone(),
+            ),
+            Quad::new(reifier.clone(), rdf::SUBJECT, subject, graph_name.clone()),
+            Quad::new(
+                reifier.clone(),
+                rdf::PREDICATE,
+                triple.predicate,
+                graph_name.clone(),
+            ),
+            Quad::new(reifier.clone(), rdf::OBJECT, object, graph_name.clone()),
+        ]);
+        self.reifiers.insert(key, reifier.clone());
+        reifier
+    }
+}
+
+/// Replaces the blank nodes described with the reification vocabulary by the quoted triples they describe.
+///
+/// A blank node is replaced if it is used outside of its description, if it is an `rdf:Statement`
+/// and if it has exactly one `rdf:subject`, `rdf:predicate` and `rdf:object` in the same graph.
+///
+/// All quads have to be collected before being able to return the first one.
+#[derive(Default)]
+pub struct QuotedTripleUnreifier {
+    input: Vec<Quad>,
+    output: Option<vec::IntoIter<Quad>>,
+}
+
+impl QuotedTripleUnreifier {
+    pub fn is_finished(&self) -> bool {
+        self.output.is_some()
+    }
+
+    pub fn push(&mut self, quad: Quad) {
+        self.input.push(quad);
+    }
+
+    /// Marks the end of the input
+    pub fn finish(&mut self) {
+        let input = take(&mut self.input);
+        let mut resolver = Resolver::default();
+        for quad in &input {
+            if let Subject::BlankNode(reifier) = &quad.subject {
+                if is_description(quad) {
+                    let description = resolver
+                        .descriptions
+                        .entry((reifier.clone(), quad.graph_name.clone()))
+                        .or_default();
+                    if quad.predicate == rdf::TYPE {
+                        description.is_statement = true;
+                    } else if quad.predicate == rdf::SUBJECT {
+                        description.subjects.push(quad.object.clone());
+                    } else if quad.predicate == rdf::PREDICATE {
+                        description.predicates.push(quad.object.clone());
+                    } else {
+                        description.objects.push(quad.object.clone());
+                    }
+                }
+            }
+        }
+
+        // We only replace the reifiers used outside of their own descriptions
+        let mut to_visit = Vec::new();
+        for quad in &input {
+            if resolver.is_reifier_description(quad) {
+                continue;
+            }
+            if let Subject::BlankNode(node) = &quad.subject {
+                to_visit.push((node.clone(), quad.graph_name.clone()));
+            }
+            if let Term::BlankNode(node) = &quad.object {
+                to_visit.push((node.clone(), quad.graph_name.clone()));
+            }
+        }
+        let mut used = HashMap::new();
+        while let Some(key) = to_visit.pop() {
+            if used.contains_key(&key) {
+                continue;
+            }
+            let Some(triple) = resolver.triple(&key.0, &key.1) else {
+                continue;
+            };
+            if let Some(description) = resolver.descriptions.get(&key) {
+                for term in description.subjects.iter().chain(&description.objects) {
+                    if let Term::BlankNode(node) = term {
+                        to_visit.push((node.clone(), key.1.clone()));
+                    }
+                }
+            }
+            used.insert(key, triple);
+        }
+
+        self.output = Some(
+            input
+                .into_iter()
+                .filter_map(|quad| {
+                    if let Subject::BlankNode(node) = &quad.subject {
+                        if is_description(&quad)
+                            && used.contains_key(&(node.clone(), quad.graph_name.clone()))
+                        {
+                            return None;
+                        }
+                    }
+                    let subject = match quad.subject {
+                        Subject::BlankNode(node) => {
+                            match used.get(&(node.clone(), quad.graph_name.clone())) {
+                                Some(triple) => triple.clone().into(),
+                                None => node.into(),
+                            }
+                        }
+                        subject => subject,
+                    };
+                    let object = match quad.object {
+                        Term::BlankNode(node) => {
+                            match used.get(&(node.clone(), quad.graph_name.clone())) {
+                                Some(triple) => triple.clone().into(),
+                                None => node.into(),
+                            }
+                        }
+                        object => object,
+                    };
+                    Some(Quad::new(subject, quad.predicate, object, quad.graph_name))
+                })
+                .collect::<Vec<_>>()
+                .into_iter(),
+        );
+    }
+
+    pub fn next(&mut self) -> Option<Quad> {
+        self.output.as_mut()?.next()
+    }
+}
+
+fn is_description(quad: &Quad) -> bool {
+    if quad.predicate == rdf::TYPE {
+        quad.object.as_ref() == rdf::STATEMENT.into()
+    } else {
+        quad.predicate == rdf::SUBJECT
+            || quad.predicate == rdf::PREDICATE
+            || quad.predicate == rdf::OBJECT
+    }
+}
+
+#[derive(Default)]
+struct Description {
+    is_statement: bool,
+    subjects: Vec<Term>,
+    predicates: Vec<Term>,
+    objects: Vec<Term>,
+}
+
+#[derive(Default)]
+struct Resolver {
+    descriptions: HashMap<(BlankNode, GraphName), Description>,
+    triples: HashMap<(BlankNode, GraphName), Option<Triple>>,
+    visiting: HashSet<(BlankNode, GraphName)>,
+}
+
+impl Resolver {
+    fn is_reifier_description(&mut self, quad: &Quad) -> bool {
+        let Subject::BlankNode(node) = &quad.subject else {
+            return false;
+        };
+        is_description(quad) && self.triple(node, &quad.graph_name).is_some()
+    }
+
+    /// The quoted triple described by the reifier if its description is valid
+    fn triple(&mut self, reifier: &BlankNode, graph_name: &GraphName) -> Option<Triple> {
+        let key = (reifier.clone(), graph_name.clone());
+        if let Some(triple) = self.triples.get(&key) {
+            return triple.clone();
+        }
+        if !self.visiting.insert(key.clone()) {
+            return None; // Cycle
+        }
+        let triple = self.build_triple(&key);
+        self.visiting.remove(&key);
+        self.triples.insert(key, triple.clone());
+        triple
+    }
+
+    fn build_triple(&mut self, key: &(BlankNode, GraphName)) -> Option<Triple> {
+        let description = self.descriptions.get(key)?;
+        if !description.is_statement {
+            return None;
+        }
+        let ([subject], [Term::NamedNode(predicate)], [object]) = (
+            description.subjects.as_slice(),
+            description.predicates.as_slice(),
+            description.objects.as_slice(),
+        ) else {
+            return None;
+        };
+        let (subject, predicate, object) = (subject.clone(), predicate.clone(), object.clone());
+        let subject: Subject = match self.resolve_term(subject, &key.1) {
+            Term::NamedNode(node) => node.into(),
+            Term::BlankNode(node) => node.into(),
+            Term::Triple(triple) => triple.into(),
+            Term::Literal(_) => return None,
+        };
+        let object = self.resolve_term(object, &key.1);
+        Some(Triple::new(subject, predicate, object))
+    }
+
+    fn resolve_term(&mut self, term: Term, graph_name: &GraphName) -> Term {
+        if let Term::BlankNode(node) = &term {
+            if let Some(triple) = self.triple(node, graph_name) {
+                return triple.into();
+            }
+        }
+        term
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+    use crate::{RdfFormat, RdfParser, RdfSerializer};
+    use oxrdf::{Literal, NamedNode};
+
+    fn ex(name: &str) -> NamedNode {
+        NamedNode::new_unchecked(format!("http://example.com/{name}"))
+    }
+
+    fn round_trip(quads: &[Quad]) -> Vec<Quad> {
+        let mut reifier = QuotedTripleReifier::default();
+        let mut unreifier = QuotedTripleUnreifier::default();
+        for quad in quads {
+            for reified in reifier
+                .reify_quad(quad.as_ref())
+                .unwrap_or_else(|| vec![quad.clone()])
+            {
+                assert!(!reified.subject.is_triple() && !reified.object.is_triple());
+                unreifier.push(reified);
+            }
+        }
+        unreifier.finish();
+        let mut output = Vec::new();
+        while let Some(quad) = unreifier.next() {
+            output.push(quad);
+        }
+        output
+    }
+
+    #[test]
+    fn test_round_trip_without_quoted_triple() {
+        let quad = Quad::new(ex("s"), ex("p"), ex("o"), GraphName::DefaultGraph);
+        assert!(QuotedTripleReifier::default()
+            .reify_quad(quad.as_ref())
+            .is_none());
+        assert_eq!(round_trip(std::slice::from_ref(&quad)), [quad]);
+    }
+
+    #[test]
+    fn test_round_trip_quoted_subject_and_object() {
+        let quoted = Triple::new(ex("s"), ex("p"), ex("o"));
+        let quads = [
+            Quad::new(
+                quoted.clone(),
+                ex("source"),
+                ex("doc"),
+                GraphName::DefaultGraph,
+            ),
+            Quad::new(ex("a"), ex("says"), quoted, GraphName::DefaultGraph),
+        ];
+        assert_eq!(round_trip(&quads), quads);
+    }
+
+    #[test]
+    fn test_reifier_reused_inside_graph() {
+        let quoted = Triple::new(ex("s"), ex("p"), ex("o"));
+        let mut reifier = QuotedTripleReifier::default();
+        let first = reifier
+            .reify_quad(Quad::new(quoted.clone(), ex("p1"), ex("o1"), ex("g")).as_ref())
+            .unwrap();
+        let second = reifier
+            .reify_quad(Quad::new(quoted, ex("p2"), ex("o2"), ex("g")).as_ref())
+            .unwrap();
+        assert_eq!(first.len(), 5);
+        assert_eq!(second.len(), 1);
+        assert_eq!(first[4].subject, second[0].subject);
+    }
+
+    #[test]
+    fn test_round_trip_nested_quoted_triples() {
+        let inner = Triple::new(ex("s"), ex("p"), ex("o"));
+        let middle = Triple::new(inner.clone(), ex("q"), Literal::from(1));
+        let outer = Triple::new(ex("a"), ex("says"), middle.clone());
+        let quads = [
+            Quad::new(outer, ex("source"), ex("doc"), GraphName::DefaultGraph),
+            Quad::new(ex("b"), ex("says"), middle, GraphName::DefaultGraph),
+            Quad::new(
+                inner,
+                ex("r"),
+                BlankNode::default(),
+                GraphName::DefaultGraph,
+            ),
+        ];
+        assert_eq!(round_trip(&quads), quads);
+    }
+
+    #[test]
+    fn test_round_trip_named_graphs() {
+        let quoted = Triple::new(ex("s"), ex("p"), Triple::new(ex("s"), ex("p"), ex("o")));
+        let quads = [
+            Quad::new(quoted.clone(), ex("in"), ex("g1"), ex("g1")),
+            Quad::new(quoted.clone(), ex("in"), ex("g2"), ex("g2")),
+            Quad::new(quoted, ex("in"), ex("blank"), BlankNode::default()),
+        ];
+        let mut reifier = QuotedTripleReifier::default();
+        let reifiers = quads
+            .iter()
+            .map(|quad| reifier.reify_quad(quad.as_ref()).unwrap())
+            .map(|reified| reified.last().unwrap().subject.clone())
+            .collect::<HashSet<_>>();
+        assert_eq!(reifiers.len(), 3, "each graph should have its own reifier");
+        assert_eq!(round_trip(&quads), quads);
+    }
+
+    #[test]
+    fn test_unused_or_invalid_descriptions_are_kept() {
+        let reifier = BlankNode::default();
+        let invalid = BlankNode::default();
+        let quads = [
+            // A valid description not used elsewhere
+            Quad::new(
+                reifier.clone(),
+                rdf::TYPE,
+                rdf::STATEMENT,
+                GraphName::DefaultGraph,
+            ),
+            Quad::new(
+                reifier.clone(),
+                rdf::SUBJECT,
+                ex("s"),
+                GraphName::DefaultGraph,
+            ),
+            Quad::new(
+                reifier.clone(),
+                rdf::PREDICATE,
+                ex("p"),
+                GraphName::DefaultGraph,
+            ),
+            Quad::new(reifier, rdf::OBJECT, ex("o"), GraphName::DefaultGraph),
+            // A description with two objects
+            Quad::new(
+                invalid.clone(),
+                rdf::TYPE,
+                rdf::STATEMENT,
+                GraphName::DefaultGraph,
+            ),
+            Quad::new(
+                invalid.clone(),
+                rdf::SUBJECT,
+                ex("s"),
+                GraphName::DefaultGraph,
+            ),
+            Quad::new(
+                invalid.clone(),
+                rdf::PREDICATE,
+                ex("p"),
+                GraphName::DefaultGraph,
+            ),
+            Quad::new(
+                invalid.clone(),
+                rdf::OBJECT,
+                ex("o1"),
+                GraphName::DefaultGraph,
+            ),
+            Quad::new(
+                invalid.clone(),
+                rdf::OBJECT,
+                ex("o2"),
+                GraphName::DefaultGraph,
+            ),
+            Quad::new(ex("a"), ex("says"), invalid, GraphName::DefaultGraph),
+        ];
+        let mut unreifier = QuotedTripleUnreifier::default();
+        for quad in &quads {
+            unreifier.push(quad.clone());
+        }
+        assert!(!unreifier.is_finished());
+        unreifier.finish();
+        assert!(unreifier.is_finished());
+        let mut output = Vec::new();
+        while let Some(quad) = unreifier.next() {
+            output.push(quad);
+        }
+        assert_eq!(output, quads);
+    }
+
+    #[test]
+    fn test_serializer_and_parser_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let inner = Triple::new(ex("s"), ex("p"), ex("o"));
+        let quads = [
+            Quad::new(
+                Triple::new(inner.clone(), ex("q"), ex("v")),
+                ex("source"),
+                ex("doc"),
+                ex("g"),
+            ),
+            Quad::new(ex("a"), ex("says"), inner, GraphName::DefaultGraph),
+        ];
+        let mut serializer = RdfSerializer::from_format(RdfFormat::NQuads)
+            .reify_quoted_triples()
+            .for_writer(Vec::new());
+        for quad in &quads {
+            serializer.serialize_quad(quad)?;
+        }
+        let serialized = String::from_utf8(serializer.finish()?)?;
+        assert!(!serialized.contains("<<"));
+        let parsed = RdfParser::from_format(RdfFormat::NQuads)
+            .unreify_quoted_triples()
+            .for_slice(serialized.as_bytes())
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(parsed, quads);
+        Ok(())
+    }
+}
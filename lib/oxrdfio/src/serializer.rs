@@ -1,7 +1,23 @@
 //! Utilities to write RDF graphs and datasets.
 
 use crate::format::RdfFormat;
-use oxrdf::{GraphNameRef, IriParseError, QuadRef, TripleRef};
+use crate::parser::skolem_iri_prefix;
+use crate::prefixes::PrefixDiscovery;
+#[cfg(feature = "rdf-star")]
+use crate::reification::QuotedTripleReifier;
+#[cfg(feature = "async-tokio")]
+use oxmicrodata::TokioAsyncWriterMicrodataSerializer;
+use oxmicrodata::{MicrodataSerializer, WriterMicrodataSerializer};
+#[cfg(feature = "rdf-star")]
+use oxrdf::Triple;
+use oxrdf::{
+    BlankNodeRef, GraphName, GraphNameRef, IriParseError, NamedNode, Quad, QuadRef, Subject,
+    SubjectRef, Term, TermRef, TripleRef,
+};
+#[cfg(all(feature = "async-tokio", feature = "rdfa"))]
+use oxrdfa::TokioAsyncWriterRdfaSerializer;
+#[cfg(feature = "rdfa")]
+use oxrdfa::{RdfaSerializer, WriterRdfaSerializer};
 #[cfg(feature = "async-tokio")]
 use oxrdfxml::TokioAsyncWriterdfXmlSerializer;
 use oxrdfxml::{RdfXmlSerializer, WriterRdfXmlSerializer};
@@ -27,6 +43,8 @@ use tokio::io::AsyncWrite;
 /// * [N3](https://w3c.github.io/N3/spec/) ([`RdfFormat::N3`])
 /// * [N-Quads](https://www.w3.org/TR/n-quads/) ([`RdfFormat::NQuads`])
 /// * [canonical](https://www.w3.org/TR/n-triples/#canonical-ntriples) [N-Triples](https://www.w3.org/TR/n-triples/) ([`RdfFormat::NTriples`])
+/// * [Microdata](https://html.spec.whatwg.org/multipage/microdata.html) ([`RdfFormat::Microdata`])
+/// * [RDFa](https://www.w3.org/TR/rdfa-core/) ([`RdfFormat::Rdfa`])
 /// * [RDF/XML](https://www.w3.org/TR/rdf-syntax-grammar/) ([`RdfFormat::RdfXml`])
 /// * [TriG](https://www.w3.org/TR/trig/) ([`RdfFormat::TriG`])
 /// * [Turtle](https://www.w3.org/TR/turtle/) ([`RdfFormat::Turtle`])
@@ -49,12 +67,21 @@ use tokio::io::AsyncWrite;
 #[derive(Clone)]
 pub struct RdfSerializer {
     inner: RdfSerializerKind,
+    prefixes: Vec<(String, String)>,
+    auto_prefixes: bool,
+    well_known_prefixes: bool,
+    skolem_iri_prefix: Option<String>,
+    #[cfg(feature = "rdf-star")]
+    reify_quoted_triples: bool,
 }
 
 #[derive(Clone)]
 enum RdfSerializerKind {
     NQuads(NQuadsSerializer),
     NTriples(NTriplesSerializer),
+    Microdata(MicrodataSerializer),
+    #[cfg(feature = "rdfa")]
+    Rdfa(RdfaSerializer),
     RdfXml(RdfXmlSerializer),
     TriG(TriGSerializer),
     Turtle(TurtleSerializer),
@@ -68,12 +95,21 @@ impl RdfSerializer {
             inner: match format {
                 RdfFormat::NQuads => RdfSerializerKind::NQuads(NQuadsSerializer::new()),
                 RdfFormat::NTriples => RdfSerializerKind::NTriples(NTriplesSerializer::new()),
+                RdfFormat::Microdata => RdfSerializerKind::Microdata(MicrodataSerializer::new()),
+                #[cfg(feature = "rdfa")]
+                RdfFormat::Rdfa => RdfSerializerKind::Rdfa(RdfaSerializer::new()),
                 RdfFormat::RdfXml => RdfSerializerKind::RdfXml(RdfXmlSerializer::new()),
                 RdfFormat::TriG => RdfSerializerKind::TriG(TriGSerializer::new()),
                 RdfFormat::Turtle | RdfFormat::N3 => {
                     RdfSerializerKind::Turtle(TurtleSerializer::new())
                 }
             },
+            prefixes: Vec::new(),
+            auto_prefixes: false,
+            well_known_prefixes: false,
+            skolem_iri_prefix: None,
+            #[cfg(feature = "rdf-star")]
+            reify_quoted_triples: false,
         }
     }
 
@@ -91,6 +127,9 @@ impl RdfSerializer {
         match &self.inner {
             RdfSerializerKind::NQuads(_) => RdfFormat::NQuads,
             RdfSerializerKind::NTriples(_) => RdfFormat::NTriples,
+            RdfSerializerKind::Microdata(_) => RdfFormat::Microdata,
+            #[cfg(feature = "rdfa")]
+            RdfSerializerKind::Rdfa(_) => RdfFormat::Rdfa,
             RdfSerializerKind::RdfXml(_) => RdfFormat::RdfXml,
             RdfSerializerKind::TriG(_) => RdfFormat::TriG,
             RdfSerializerKind::Turtle(_) => RdfFormat::Turtle,
@@ -124,22 +163,86 @@ impl RdfSerializer {
         prefix_name: impl Into<String>,
         prefix_iri: impl Into<String>,
     ) -> Result<Self, IriParseError> {
-        self.inner = match self.inner {
-            RdfSerializerKind::NQuads(s) => RdfSerializerKind::NQuads(s),
-            RdfSerializerKind::NTriples(s) => RdfSerializerKind::NTriples(s),
-            RdfSerializerKind::RdfXml(s) => {
-                RdfSerializerKind::RdfXml(s.with_prefix(prefix_name, prefix_iri)?)
-            }
-            RdfSerializerKind::TriG(s) => {
-                RdfSerializerKind::TriG(s.with_prefix(prefix_name, prefix_iri)?)
-            }
-            RdfSerializerKind::Turtle(s) => {
-                RdfSerializerKind::Turtle(s.with_prefix(prefix_name, prefix_iri)?)
-            }
-        };
+        let (prefix_name, prefix_iri) = (prefix_name.into(), prefix_iri.into());
+        self.inner = self.inner.with_prefix(&prefix_name, &prefix_iri)?;
+        self.prefixes.push((prefix_name, prefix_iri));
         Ok(self)
     }
 
+    /// If the format supports it, declares prefixes for the namespaces frequently used in the serialized data.
+    ///
+    /// The IRIs are split into a namespace and a local name after their last `/` or `#`
+    /// and a prefix is added for each namespace used at least twice.
+    /// The prefix names are taken from a built-in table of well-known vocabularies like `rdfs`, `owl` or `schema`
+    /// or derived from the namespace IRI.
+    /// The prefixes set with [`with_prefix`](Self::with_prefix) are kept.
+    ///
+    /// <div class="warning">
+    ///
+    /// The prefixes are chosen from the first 10,000 quads that are kept in memory until then.
+    /// Nothing is written to the writer before.</div>
+    ///
+    /// ```
+    /// use oxrdf::vocab::rdf;
+    /// use oxrdf::{NamedNodeRef, TripleRef};
+    /// use oxrdfio::{RdfFormat, RdfSerializer};
+    ///
+    /// let mut serializer = RdfSerializer::from_format(RdfFormat::Turtle)
+    ///     .with_auto_prefixes()
+    ///     .for_writer(Vec::new());
+    /// serializer.serialize_triple(TripleRef::new(
+    ///     NamedNodeRef::new("http://example.com/ns#me")?,
+    ///     rdf::TYPE,
+    ///     NamedNodeRef::new("http://example.com/ns#Person")?,
+    /// ))?;
+    /// assert_eq!(
+    ///     serializer.finish()?,
+    ///     b"@prefix ns: <http://example.com/ns#> .\nns:me a ns:Person .\n"
+    /// );
+    /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    pub fn with_auto_prefixes(mut self) -> Self {
+        self.auto_prefixes = true;
+        self
+    }
+
+    /// If the format supports it, declares the prefixes of the well-known vocabularies used in the serialized data.
+    ///
+    /// The prefixes are taken from a built-in table following [prefix.cc](https://prefix.cc/)
+    /// that includes among others `rdf`, `rdfs`, `xsd`, `owl`, `skos`, `dcterms`, `foaf` and `schema`.
+    /// The prefixes set with [`with_prefix`](Self::with_prefix) are kept.
+    ///
+    /// <div class="warning">
+    ///
+    /// The prefixes are chosen from the first 10,000 quads that are kept in memory until then.
+    /// Nothing is written to the writer before.</div>
+    ///
+    /// ```
+    /// use oxrdf::vocab::rdfs;
+    /// use oxrdf::{NamedNodeRef, TripleRef};
+    /// use oxrdfio::{RdfFormat, RdfSerializer};
+    ///
+    /// let mut serializer = RdfSerializer::from_format(RdfFormat::Turtle)
+    ///     .with_well_known_prefixes()
+    ///     .for_writer(Vec::new());
+    /// serializer.serialize_triple(TripleRef::new(
+    ///     NamedNodeRef::new("http://example.com/me")?,
+    ///     rdfs::SEE_ALSO,
+    ///     NamedNodeRef::new("http://schema.org/Person")?,
+    /// ))?;
+    /// assert_eq!(
+    ///     serializer.finish()?,
+    ///     b"@prefix schema: <http://schema.org/> .\n@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .\n<http://example.com/me> rdfs:seeAlso schema:Person .\n"
+    /// );
+    /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    pub fn with_well_known_prefixes(mut self) -> Self {
+        self.well_known_prefixes = true;
+        self
+    }
+
     /// If the format supports it, sets a base IRI.
     ///
     /// ```
@@ -167,6 +270,11 @@ impl RdfSerializer {
         self.inner = match self.inner {
             RdfSerializerKind::NQuads(s) => RdfSerializerKind::NQuads(s),
             RdfSerializerKind::NTriples(s) => RdfSerializerKind::NTriples(s),
+            RdfSerializerKind::Microdata(s) => {
+                RdfSerializerKind::Microdata(s.with_base_iri(base_iri)?)
+            }
+            #[cfg(feature = "rdfa")]
+            RdfSerializerKind::Rdfa(s) => RdfSerializerKind::Rdfa(s.with_base_iri(base_iri)?),
             RdfSerializerKind::RdfXml(s) => RdfSerializerKind::RdfXml(s.with_base_iri(base_iri)?),
             RdfSerializerKind::TriG(s) => RdfSerializerKind::TriG(s.with_base_iri(base_iri)?),
             RdfSerializerKind::Turtle(s) => RdfSerializerKind::Turtle(s.with_base_iri(base_iri)?),
@@ -174,6 +282,81 @@ impl RdfSerializer {
         Ok(self)
     }
 
+    /// Replaces the blank nodes by [skolem IRIs](https://www.w3.org/TR/rdf11-concepts/#section-skolemization) minted under the given authority.
+    ///
+    /// The blank node `_:{id}` becomes the IRI `{authority}/.well-known/genid/{id}`.
+    /// The output is stable across serializations as long as the blank node ids are.
+    /// [`RdfParser::with_deskolemization`](crate::RdfParser::with_deskolemization) allows to do the reverse operation.
+    ///
+    /// ```
+    /// use oxrdf::{BlankNodeRef, NamedNodeRef, TripleRef};
+    /// use oxrdfio::{RdfFormat, RdfSerializer};
+    ///
+    /// let mut serializer = RdfSerializer::from_format(RdfFormat::NTriples)
+    ///     .with_skolemization("http://example.com")?
+    ///     .for_writer(Vec::new());
+    /// serializer.serialize_triple(TripleRef::new(
+    ///     BlankNodeRef::new("a")?,
+    ///     NamedNodeRef::new("http://example.com/p")?,
+    ///     NamedNodeRef::new("http://example.com/o")?,
+    /// ))?;
+    /// assert_eq!(
+    ///     serializer.finish()?,
+    ///     b"<http://example.com/.well-known/genid/a> <http://example.com/p> <http://example.com/o> .\n"
+    /// );
+    /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    pub fn with_skolemization(
+        mut self,
+        authority: impl Into<String>,
+    ) -> Result<Self, IriParseError> {
+        self.skolem_iri_prefix = Some(skolem_iri_prefix(authority.into())?);
+        Ok(self)
+    }
+
+    /// Replaces the quoted triples by blank nodes described using the [RDF reification vocabulary](https://www.w3.org/TR/rdf11-schema/#ch_reificationvocab).
+    ///
+    /// It allows to write RDF-star data to plain RDF 1.1 files, including in formats that do not support quoted triples like RDF/XML.
+    /// Each quoted triple becomes a blank node `_:r` with the description `_:r rdf:type rdf:Statement ; rdf:subject ... ; rdf:predicate ... ; rdf:object ...`
+    /// written in the same graph just before its first usage.
+    /// [`RdfParser::unreify_quoted_triples`](crate::RdfParser::unreify_quoted_triples) allows to do the reverse operation.
+    ///
+    /// ```
+    /// use oxrdf::{GraphName, NamedNodeRef, Triple, TripleRef};
+    /// use oxrdfio::{RdfFormat, RdfParser, RdfSerializer};
+    ///
+    /// let quoted = Triple::new(
+    ///     NamedNodeRef::new("http://example.com/s")?,
+    ///     NamedNodeRef::new("http://example.com/p")?,
+    ///     NamedNodeRef::new("http://example.com/o")?,
+    /// );
+    /// let triple = TripleRef::new(
+    ///     &quoted,
+    ///     NamedNodeRef::new("http://example.com/source")?,
+    ///     NamedNodeRef::new("http://example.com/doc")?,
+    /// );
+    ///
+    /// let mut serializer = RdfSerializer::from_format(RdfFormat::RdfXml)
+    ///     .reify_quoted_triples()
+    ///     .for_writer(Vec::new());
+    /// serializer.serialize_triple(triple)?;
+    /// let file = serializer.finish()?;
+    ///
+    /// let quads = RdfParser::from_format(RdfFormat::RdfXml)
+    ///     .unreify_quoted_triples()
+    ///     .for_slice(&file)
+    ///     .collect::<Result<Vec<_>, _>>()?;
+    /// assert_eq!(quads, [triple.into_owned().in_graph(GraphName::DefaultGraph)]);
+    /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[cfg(feature = "rdf-star")]
+    #[inline]
+    pub fn reify_quoted_triples(mut self) -> Self {
+        self.reify_quoted_triples = true;
+        self
+    }
+
     /// Serializes to a [`Write`] implementation.
     ///
     /// <div class="warning">
@@ -200,21 +383,17 @@ impl RdfSerializer {
     /// ```
     pub fn for_writer<W: Write>(self, writer: W) -> WriterQuadSerializer<W> {
         WriterQuadSerializer {
-            inner: match self.inner {
-                RdfSerializerKind::NQuads(s) => {
-                    WriterQuadSerializerKind::NQuads(s.for_writer(writer))
-                }
-                RdfSerializerKind::NTriples(s) => {
-                    WriterQuadSerializerKind::NTriples(s.for_writer(writer))
-                }
-                RdfSerializerKind::RdfXml(s) => {
-                    WriterQuadSerializerKind::RdfXml(s.for_writer(writer))
-                }
-                RdfSerializerKind::TriG(s) => WriterQuadSerializerKind::TriG(s.for_writer(writer)),
-                RdfSerializerKind::Turtle(s) => {
-                    WriterQuadSerializerKind::Turtle(s.for_writer(writer))
-                }
+            inner: if let Some(discovery) = self.prefix_discovery() {
+                WriterQuadSerializerKind::PrefixDiscovery(Box::new(PendingSerializer {
+                    target: Some((self.inner, writer)),
+                    discovery,
+                }))
+            } else {
+                self.inner.for_writer(writer)
             },
+            skolem_iri_prefix: self.skolem_iri_prefix,
+            #[cfg(feature = "rdf-star")]
+            reifier: self.reify_quoted_triples.then(QuotedTripleReifier::default),
         }
     }
 
@@ -251,27 +430,120 @@ impl RdfSerializer {
         writer: W,
     ) -> TokioAsyncWriterQuadSerializer<W> {
         TokioAsyncWriterQuadSerializer {
-            inner: match self.inner {
-                RdfSerializerKind::NQuads(s) => {
-                    TokioAsyncWriterQuadSerializerKind::NQuads(s.for_tokio_async_writer(writer))
-                }
-                RdfSerializerKind::NTriples(s) => {
-                    TokioAsyncWriterQuadSerializerKind::NTriples(s.for_tokio_async_writer(writer))
-                }
-                RdfSerializerKind::RdfXml(s) => {
-                    TokioAsyncWriterQuadSerializerKind::RdfXml(s.for_tokio_async_writer(writer))
-                }
-                RdfSerializerKind::TriG(s) => {
-                    TokioAsyncWriterQuadSerializerKind::TriG(s.for_tokio_async_writer(writer))
-                }
-                RdfSerializerKind::Turtle(s) => {
-                    TokioAsyncWriterQuadSerializerKind::Turtle(s.for_tokio_async_writer(writer))
-                }
+            inner: if let Some(discovery) = self.prefix_discovery() {
+                TokioAsyncWriterQuadSerializerKind::PrefixDiscovery(Box::new(PendingSerializer {
+                    target: Some((self.inner, writer)),
+                    discovery,
+                }))
+            } else {
+                self.inner.for_tokio_async_writer(writer)
+            },
+            skolem_iri_prefix: self.skolem_iri_prefix,
+            #[cfg(feature = "rdf-star")]
+            reifier: self.reify_quoted_triples.then(QuotedTripleReifier::default),
+        }
+    }
+
+    fn prefix_discovery(&self) -> Option<PrefixDiscovery> {
+        ((self.auto_prefixes || self.well_known_prefixes) && self.inner.supports_prefixes()).then(
+            || {
+                PrefixDiscovery::new(
+                    self.auto_prefixes,
+                    self.well_known_prefixes,
+                    self.prefixes.clone(),
+                )
             },
+        )
+    }
+}
+
+impl RdfSerializerKind {
+    fn supports_prefixes(&self) -> bool {
+        match self {
+            #[cfg(feature = "rdfa")]
+            Self::Rdfa(_) => true,
+            Self::RdfXml(_) | Self::TriG(_) | Self::Turtle(_) => true,
+            _ => false,
+        }
+    }
+
+    fn with_prefix(self, prefix_name: &str, prefix_iri: &str) -> Result<Self, IriParseError> {
+        Ok(match self {
+            Self::NQuads(s) => Self::NQuads(s),
+            Self::NTriples(s) => Self::NTriples(s),
+            Self::Microdata(s) => Self::Microdata(s),
+            #[cfg(feature = "rdfa")]
+            Self::Rdfa(s) => Self::Rdfa(s.with_prefix(prefix_name, prefix_iri)?),
+            Self::RdfXml(s) => Self::RdfXml(s.with_prefix(prefix_name, prefix_iri)?),
+            Self::TriG(s) => Self::TriG(s.with_prefix(prefix_name, prefix_iri)?),
+            Self::Turtle(s) => Self::Turtle(s.with_prefix(prefix_name, prefix_iri)?),
+        })
+    }
+
+    fn with_discovered_prefixes(mut self, prefixes: Vec<(String, String)>) -> Self {
+        for (prefix_name, prefix_iri) in prefixes {
+            // Invalid IRIs are ignored
+            if let Ok(serializer) = self.clone().with_prefix(&prefix_name, &prefix_iri) {
+                self = serializer;
+            }
+        }
+        self
+    }
+
+    fn for_writer<W: Write>(self, writer: W) -> WriterQuadSerializerKind<W> {
+        match self {
+            Self::NQuads(s) => WriterQuadSerializerKind::NQuads(s.for_writer(writer)),
+            Self::NTriples(s) => WriterQuadSerializerKind::NTriples(s.for_writer(writer)),
+            Self::Microdata(s) => WriterQuadSerializerKind::Microdata(s.for_writer(writer)),
+            #[cfg(feature = "rdfa")]
+            Self::Rdfa(s) => WriterQuadSerializerKind::Rdfa(s.for_writer(writer)),
+            Self::RdfXml(s) => WriterQuadSerializerKind::RdfXml(s.for_writer(writer)),
+            Self::TriG(s) => WriterQuadSerializerKind::TriG(s.for_writer(writer)),
+            Self::Turtle(s) => WriterQuadSerializerKind::Turtle(s.for_writer(writer)),
+        }
+    }
+
+    #[cfg(feature = "async-tokio")]
+    fn for_tokio_async_writer<W: AsyncWrite + Unpin>(
+        self,
+        writer: W,
+    ) -> TokioAsyncWriterQuadSerializerKind<W> {
+        match self {
+            Self::NQuads(s) => {
+                TokioAsyncWriterQuadSerializerKind::NQuads(s.for_tokio_async_writer(writer))
+            }
+            Self::NTriples(s) => {
+                TokioAsyncWriterQuadSerializerKind::NTriples(s.for_tokio_async_writer(writer))
+            }
+            Self::Microdata(s) => {
+                TokioAsyncWriterQuadSerializerKind::Microdata(s.for_tokio_async_writer(writer))
+            }
+            #[cfg(feature = "rdfa")]
+            Self::Rdfa(s) =>
+            {
+                #[cfg(feature = "rdfa")]
+                TokioAsyncWriterQuadSerializerKind::Rdfa(s.for_tokio_async_writer(writer))
+            }
+            Self::RdfXml(s) => {
+                TokioAsyncWriterQuadSerializerKind::RdfXml(s.for_tokio_async_writer(writer))
+            }
+            Self::TriG(s) => {
+                TokioAsyncWriterQuadSerializerKind::TriG(s.for_tokio_async_writer(writer))
+            }
+            Self::Turtle(s) => {
+                TokioAsyncWriterQuadSerializerKind::Turtle(s.for_tokio_async_writer(writer))
+            }
         }
     }
 }
 
+/// Buffers the first quads until the prefixes to declare are known
+struct PendingSerializer<W> {
+    /// The serializer to use once the prefixes are known and the writer to write to
+    target: Option<(RdfSerializerKind, W)>,
+    discovery: PrefixDiscovery,
+}
+
 impl From<RdfFormat> for RdfSerializer {
     fn from(format: RdfFormat) -> Self {
         Self::from_format(format)
@@ -307,24 +579,69 @@ impl From<RdfFormat> for RdfSerializer {
 #[must_use]
 pub struct WriterQuadSerializer<W: Write> {
     inner: WriterQuadSerializerKind<W>,
+    skolem_iri_prefix: Option<String>,
+    #[cfg(feature = "rdf-star")]
+    reifier: Option<QuotedTripleReifier>,
 }
 
 enum WriterQuadSerializerKind<W: Write> {
     NQuads(WriterNQuadsSerializer<W>),
     NTriples(WriterNTriplesSerializer<W>),
+    Microdata(WriterMicrodataSerializer<W>),
+    #[cfg(feature = "rdfa")]
+    Rdfa(WriterRdfaSerializer<W>),
     RdfXml(WriterRdfXmlSerializer<W>),
     TriG(WriterTriGSerializer<W>),
     Turtle(WriterTurtleSerializer<W>),
+    PrefixDiscovery(Box<PendingSerializer<W>>),
 }
 
 impl<W: Write> WriterQuadSerializer<W> {
     /// Serializes a [`QuadRef`]
     pub fn serialize_quad<'a>(&mut self, quad: impl Into<QuadRef<'a>>) -> io::Result<()> {
+        let quad = quad.into();
+        #[cfg(feature = "rdf-star")]
+        if let Some(reifier) = &mut self.reifier {
+            if let Some(quads) = reifier.reify_quad(quad) {
+                for quad in &quads {
+                    self.serialize_reified_quad(quad.as_ref())?;
+                }
+                return Ok(());
+            }
+        }
+        self.serialize_reified_quad(quad)
+    }
+
+    fn serialize_reified_quad(&mut self, quad: QuadRef<'_>) -> io::Result<()> {
+        let skolemized;
+        let quad = if let Some(skolem_iri_prefix) = &self.skolem_iri_prefix {
+            skolemized = skolemize_quad(quad, skolem_iri_prefix);
+            skolemized.as_ref()
+        } else {
+            quad
+        };
+        self.write_quad(quad)?;
+        if let WriterQuadSerializerKind::PrefixDiscovery(pending) = &self.inner {
+            if pending.discovery.is_complete() {
+                self.end_prefix_discovery()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_quad(&mut self, quad: QuadRef<'_>) -> io::Result<()> {
         match &mut self.inner {
             WriterQuadSerializerKind::NQuads(serializer) => serializer.serialize_quad(quad),
             WriterQuadSerializerKind::NTriples(serializer) => {
                 serializer.serialize_triple(to_triple(quad)?)
             }
+            WriterQuadSerializerKind::Microdata(serializer) => {
+                serializer.serialize_triple(to_triple(quad)?)
+            }
+            #[cfg(feature = "rdfa")]
+            WriterQuadSerializerKind::Rdfa(serializer) => {
+                serializer.serialize_triple(to_triple(quad)?)
+            }
             WriterQuadSerializerKind::RdfXml(serializer) => {
                 serializer.serialize_triple(to_triple(quad)?)
             }
@@ -332,9 +649,31 @@ impl<W: Write> WriterQuadSerializer<W> {
             WriterQuadSerializerKind::Turtle(serializer) => {
                 serializer.serialize_triple(to_triple(quad)?)
             }
+            WriterQuadSerializerKind::PrefixDiscovery(pending) => {
+                pending.discovery.push(quad);
+                Ok(())
+            }
         }
     }
 
+    /// Builds the actual serializer with the discovered prefixes and writes to it the buffered quads
+    fn end_prefix_discovery(&mut self) -> io::Result<()> {
+        let WriterQuadSerializerKind::PrefixDiscovery(pending) = &mut self.inner else {
+            return Ok(());
+        };
+        let Some((serializer, writer)) = pending.target.take() else {
+            return Ok(());
+        };
+        let (prefixes, quads) = pending.discovery.finish();
+        self.inner = serializer
+            .with_discovered_prefixes(prefixes)
+            .for_writer(writer);
+        for quad in &quads {
+            self.write_quad(quad.as_ref())?;
+        }
+        Ok(())
+    }
+
     /// Serializes a [`TripleRef`]
     pub fn serialize_triple<'a>(&mut self, triple: impl Into<TripleRef<'a>>) -> io::Result<()> {
         self.serialize_quad(triple.into().in_graph(GraphNameRef::DefaultGraph))
@@ -343,13 +682,18 @@ impl<W: Write> WriterQuadSerializer<W> {
     /// Writes the last bytes of the file
     ///
     /// Note that this function does not flush the writer. You need to do that if you are using a [`BufWriter`](io::BufWriter).
-    pub fn finish(self) -> io::Result<W> {
+    pub fn finish(mut self) -> io::Result<W> {
+        self.end_prefix_discovery()?;
         Ok(match self.inner {
             WriterQuadSerializerKind::NQuads(serializer) => serializer.finish(),
             WriterQuadSerializerKind::NTriples(serializer) => serializer.finish(),
+            WriterQuadSerializerKind::Microdata(serializer) => serializer.finish()?,
+            #[cfg(feature = "rdfa")]
+            WriterQuadSerializerKind::Rdfa(serializer) => serializer.finish()?,
             WriterQuadSerializerKind::RdfXml(serializer) => serializer.finish()?,
             WriterQuadSerializerKind::TriG(serializer) => serializer.finish()?,
             WriterQuadSerializerKind::Turtle(serializer) => serializer.finish()?,
+            WriterQuadSerializerKind::PrefixDiscovery(_) => unreachable!(),
         })
     }
 }
@@ -387,21 +731,59 @@ impl<W: Write> WriterQuadSerializer<W> {
 #[cfg(feature = "async-tokio")]
 pub struct TokioAsyncWriterQuadSerializer<W: AsyncWrite + Unpin> {
     inner: TokioAsyncWriterQuadSerializerKind<W>,
+    skolem_iri_prefix: Option<String>,
+    #[cfg(feature = "rdf-star")]
+    reifier: Option<QuotedTripleReifier>,
 }
 
 #[cfg(feature = "async-tokio")]
 enum TokioAsyncWriterQuadSerializerKind<W: AsyncWrite + Unpin> {
     NQuads(TokioAsyncWriterNQuadsSerializer<W>),
     NTriples(TokioAsyncWriterNTriplesSerializer<W>),
+    Microdata(TokioAsyncWriterMicrodataSerializer<W>),
+    #[cfg(feature = "rdfa")]
+    Rdfa(TokioAsyncWriterRdfaSerializer<W>),
     RdfXml(TokioAsyncWriterdfXmlSerializer<W>),
     TriG(TokioAsyncWriterTriGSerializer<W>),
     Turtle(TokioAsyncWriterTurtleSerializer<W>),
+    PrefixDiscovery(Box<PendingSerializer<W>>),
 }
 
 #[cfg(feature = "async-tokio")]
 impl<W: AsyncWrite + Unpin> TokioAsyncWriterQuadSerializer<W> {
     /// Serializes a [`QuadRef`]
     pub async fn serialize_quad<'a>(&mut self, quad: impl Into<QuadRef<'a>>) -> io::Result<()> {
+        let quad = quad.into();
+        #[cfg(feature = "rdf-star")]
+        if let Some(reifier) = &mut self.reifier {
+            if let Some(quads) = reifier.reify_quad(quad) {
+                for quad in &quads {
+                    self.serialize_reified_quad(quad.as_ref()).await?;
+                }
+                return Ok(());
+            }
+        }
+        self.serialize_reified_quad(quad).await
+    }
+
+    async fn serialize_reified_quad(&mut self, quad: QuadRef<'_>) -> io::Result<()> {
+        let skolemized;
+        let quad = if let Some(skolem_iri_prefix) = &self.skolem_iri_prefix {
+            skolemized = skolemize_quad(quad, skolem_iri_prefix);
+            skolemized.as_ref()
+        } else {
+            quad
+        };
+        self.write_quad(quad).await?;
+        if let TokioAsyncWriterQuadSerializerKind::PrefixDiscovery(pending) = &self.inner {
+            if pending.discovery.is_complete() {
+                self.end_prefix_discovery().await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn write_quad(&mut self, quad: QuadRef<'_>) -> io::Result<()> {
         match &mut self.inner {
             TokioAsyncWriterQuadSerializerKind::NQuads(serializer) => {
                 serializer.serialize_quad(quad).await
@@ -409,6 +791,13 @@ impl<W: AsyncWrite + Unpin> TokioAsyncWriterQuadSerializer<W> {
             TokioAsyncWriterQuadSerializerKind::NTriples(serializer) => {
                 serializer.serialize_triple(to_triple(quad)?).await
             }
+            TokioAsyncWriterQuadSerializerKind::Microdata(serializer) => {
+                serializer.serialize_triple(to_triple(quad)?).await
+            }
+            #[cfg(feature = "rdfa")]
+            TokioAsyncWriterQuadSerializerKind::Rdfa(serializer) => {
+                serializer.serialize_triple(to_triple(quad)?).await
+            }
             TokioAsyncWriterQuadSerializerKind::RdfXml(serializer) => {
                 serializer.serialize_triple(to_triple(quad)?).await
             }
@@ -418,7 +807,29 @@ impl<W: AsyncWrite + Unpin> TokioAsyncWriterQuadSerializer<W> {
             TokioAsyncWriterQuadSerializerKind::Turtle(serializer) => {
                 serializer.serialize_triple(to_triple(quad)?).await
             }
+            TokioAsyncWriterQuadSerializerKind::PrefixDiscovery(pending) => {
+                pending.discovery.push(quad);
+                Ok(())
+            }
+        }
+    }
+
+    /// Builds the actual serializer with the discovered prefixes and writes to it the buffered quads
+    async fn end_prefix_discovery(&mut self) -> io::Result<()> {
+        let TokioAsyncWriterQuadSerializerKind::PrefixDiscovery(pending) = &mut self.inner else {
+            return Ok(());
+        };
+        let Some((serializer, writer)) = pending.target.take() else {
+            return Ok(());
+        };
+        let (prefixes, quads) = pending.discovery.finish();
+        self.inner = serializer
+            .with_discovered_prefixes(prefixes)
+            .for_tokio_async_writer(writer);
+        for quad in &quads {
+            self.write_quad(quad.as_ref()).await?;
         }
+        Ok(())
     }
 
     /// Serializes a [`TripleRef`]
@@ -433,17 +844,69 @@ impl<W: AsyncWrite + Unpin> TokioAsyncWriterQuadSerializer<W> {
     /// Writes the last bytes of the file
     ///
     /// Note that this function does not flush the writer. You need to do that if you are using a [`BufWriter`](io::BufWriter).
-    pub async fn finish(self) -> io::Result<W> {
+    pub async fn finish(mut self) -> io::Result<W> {
+        self.end_prefix_discovery().await?;
         Ok(match self.inner {
             TokioAsyncWriterQuadSerializerKind::NQuads(serializer) => serializer.finish(),
             TokioAsyncWriterQuadSerializerKind::NTriples(serializer) => serializer.finish(),
+            TokioAsyncWriterQuadSerializerKind::Microdata(serializer) => {
+                serializer.finish().await?
+            }
+            #[cfg(feature = "rdfa")]
+            TokioAsyncWriterQuadSerializerKind::Rdfa(serializer) => serializer.finish().await?,
             TokioAsyncWriterQuadSerializerKind::RdfXml(serializer) => serializer.finish().await?,
             TokioAsyncWriterQuadSerializerKind::TriG(serializer) => serializer.finish().await?,
             TokioAsyncWriterQuadSerializerKind::Turtle(serializer) => serializer.finish().await?,
+            TokioAsyncWriterQuadSerializerKind::PrefixDiscovery(_) => unreachable!(),
         })
     }
 }
 
+fn skolemize_quad(quad: QuadRef<'_>, skolem_iri_prefix: &str) -> Quad {
+    Quad {
+        subject: skolemize_subject(quad.subject, skolem_iri_prefix),
+        predicate: quad.predicate.into_owned(),
+        object: skolemize_term(quad.object, skolem_iri_prefix),
+        graph_name: match quad.graph_name {
+            GraphNameRef::NamedNode(node) => node.into_owned().into(),
+            GraphNameRef::BlankNode(node) => skolem_iri(node, skolem_iri_prefix).into(),
+            GraphNameRef::DefaultGraph => GraphName::DefaultGraph,
+        },
+    }
+}
+
+#[cfg(feature = "rdf-star")]
+fn skolemize_triple(triple: TripleRef<'_>, skolem_iri_prefix: &str) -> Triple {
+    Triple {
+        subject: skolemize_subject(triple.subject, skolem_iri_prefix),
+        predicate: triple.predicate.into_owned(),
+        object: skolemize_term(triple.object, skolem_iri_prefix),
+    }
+}
+
+fn skolemize_subject(subject: SubjectRef<'_>, skolem_iri_prefix: &str) -> Subject {
+    match subject {
+        SubjectRef::NamedNode(node) => node.into_owned().into(),
+        SubjectRef::BlankNode(node) => skolem_iri(node, skolem_iri_prefix).into(),
+        #[cfg(feature = "rdf-star")]
+        SubjectRef::Triple(triple) => skolemize_triple(triple.as_ref(), skolem_iri_prefix).into(),
+    }
+}
+
+fn skolemize_term(term: TermRef<'_>, skolem_iri_prefix: &str) -> Term {
+    match term {
+        TermRef::NamedNode(node) => node.into_owned().into(),
+        TermRef::BlankNode(node) => skolem_iri(node, skolem_iri_prefix).into(),
+        TermRef::Literal(literal) => literal.into_owned().into(),
+        #[cfg(feature = "rdf-star")]
+        TermRef::Triple(triple) => skolemize_triple(triple.as_ref(), skolem_iri_prefix).into(),
+    }
+}
+
+fn skolem_iri(node: BlankNodeRef<'_>, skolem_iri_prefix: &str) -> NamedNode {
+    NamedNode::new_unchecked(format!("{skolem_iri_prefix}{}", node.as_str()))
+}
+
 fn to_triple<'a>(quad: impl Into<QuadRef<'a>>) -> io::Result<TripleRef<'a>> {
     let quad = quad.into();
     if quad.graph_name.is_default_graph() {
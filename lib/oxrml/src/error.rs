@@ -0,0 +1,46 @@
+use crate::source::LogicalSource;
+use oxttl::TurtleParseError;
+use std::error::Error;
+use std::io;
+
+/// An error raised while parsing a [R2RML](https://www.w3.org/TR/r2rml/) or [RML](https://rml.io/specs/rml/) mapping document.
+#[derive(Debug, thiserror::Error)]
+pub enum RmlMappingError {
+    /// The mapping document is not valid Turtle.
+    #[error(transparent)]
+    Parsing(#[from] TurtleParseError),
+    /// The mapping document does not describe a valid mapping.
+    #[error("Invalid mapping: {0}")]
+    Invalid(String),
+}
+
+impl RmlMappingError {
+    pub(crate) fn invalid(message: impl Into<String>) -> Self {
+        Self::Invalid(message.into())
+    }
+}
+
+/// An error raised while executing a mapping.
+#[derive(Debug, thiserror::Error)]
+pub enum RmlExecutionError {
+    /// I/O error while reading a source (file not found...).
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// Error raised by a source connector (invalid file, database error...).
+    #[error("Error while reading a source: {0}")]
+    Connector(#[source] Box<dyn Error + Send + Sync + 'static>),
+    /// None of the connectors supports the logical source.
+    #[error("No connector supports the logical source {0}")]
+    UnsupportedSource(LogicalSource),
+    /// The term generated from a row is invalid (invalid IRI, invalid language tag...).
+    #[error("{0}")]
+    InvalidTerm(String),
+}
+
+impl RmlExecutionError {
+    /// Wraps an error raised by a source connector.
+    #[inline]
+    pub fn connector(error: impl Into<Box<dyn Error + Send + Sync + 'static>>) -> Self {
+        Self::Connector(error.into())
+    }
+}
@@ -0,0 +1,91 @@
+//! The subset of [JSONPath](https://www.rfc-editor.org/rfc/rfc9535) used by the RML iterators and references.
+//!
+//! The supported segments are the member names (`.name` or `['name']`), the array indexes (`[0]`) and the wildcards (`.*` or `[*]`).
+//! The paths not starting with `$` are relative to the current value.
+
+use serde_json::Value;
+
+#[derive(Debug, Clone)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Member(String),
+    Index(usize),
+    Wildcard,
+}
+
+impl JsonPath {
+    pub fn parse(path: &str) -> Result<Self, String> {
+        let mut segments = Vec::new();
+        let mut rest = path.strip_prefix('$').unwrap_or(path);
+        if !rest.is_empty() && !rest.starts_with(['.', '[']) {
+            // Relative path starting with a member name
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            segments.push(Segment::Member(rest[..end].into()));
+            rest = &rest[end..];
+        }
+        while !rest.is_empty() {
+            if let Some(r) = rest.strip_prefix(".*") {
+                segments.push(Segment::Wildcard);
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix('.') {
+                let end = r.find(['.', '[']).unwrap_or(r.len());
+                if end == 0 {
+                    return Err("empty member name".into());
+                }
+                segments.push(Segment::Member(r[..end].into()));
+                rest = &r[end..];
+            } else if let Some(r) = rest.strip_prefix('[') {
+                let Some(end) = r.find(']') else {
+                    return Err("unclosed [".into());
+                };
+                let selector = r[..end].trim();
+                segments.push(if selector == "*" {
+                    Segment::Wildcard
+                } else if let Some(name) = selector
+                    .strip_prefix('\'')
+                    .and_then(|s| s.strip_suffix('\''))
+                    .or_else(|| selector.strip_prefix('"').and_then(|s| s.strip_suffix('"')))
+                {
+                    Segment::Member(name.into())
+                } else {
+                    Segment::Index(
+                        selector
+                            .parse()
+                            .map_err(|_| format!("unsupported selector [{selector}]"))?,
+                    )
+                });
+                rest = &r[end + 1..];
+            } else {
+                return Err(format!("unexpected characters {rest}"));
+            }
+        }
+        Ok(Self { segments })
+    }
+
+    /// The values matched by the path, in document order
+    pub fn evaluate<'a>(&self, value: &'a Value) -> Vec<&'a Value> {
+        let mut current = vec![value];
+        for segment in &self.segments {
+            let mut next = Vec::new();
+            for value in current {
+                match (segment, value) {
+                    (Segment::Member(name), Value::Object(object)) => {
+                        next.extend(object.get(name));
+                    }
+                    (Segment::Index(index), Value::Array(array)) => {
+                        next.extend(array.get(*index));
+                    }
+                    (Segment::Wildcard, Value::Array(array)) => next.extend(array),
+                    (Segment::Wildcard, Value::Object(object)) => next.extend(object.values()),
+                    _ => (),
+                }
+            }
+            current = next;
+        }
+        current
+    }
+}
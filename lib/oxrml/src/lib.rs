@@ -0,0 +1,22 @@
+#![doc = include_str!("../README.md")]
+#![doc(test(attr(deny(warnings))))]
+#![cfg_attr(docsrs, feature(doc_auto_cfg))]
+#![doc(html_favicon_url = "https://raw.githubusercontent.com/oxigraph/oxigraph/main/logo.svg")]
+#![doc(html_logo_url = "https://raw.githubusercontent.com/oxigraph/oxigraph/main/logo.svg")]
+
+mod error;
+mod json_path;
+mod mapping;
+mod processor;
+mod source;
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+mod sql;
+
+pub use crate::error::{RmlExecutionError, RmlMappingError};
+pub use crate::mapping::RmlMapping;
+pub use crate::processor::{MappedQuads, RmlProcessor};
+pub use crate::source::{FileConnector, LogicalSource, Row, Rows, SourceConnector};
+#[cfg(feature = "postgres")]
+pub use crate::sql::PostgresConnector;
+#[cfg(feature = "sqlite")]
+pub use crate::sql::SqliteConnector;
@@ -0,0 +1,599 @@
+use crate::error::RmlMappingError;
+use crate::source::{LogicalSource, Reference};
+use oxrdf::{Graph, Literal, NamedNode, NamedNodeRef, SubjectRef, Term, TermRef};
+use oxttl::TurtleParser;
+use std::io::Read;
+use std::path::Path;
+
+mod rr {
+    use oxrdf::NamedNodeRef;
+
+    pub const BLANK_NODE: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#BlankNode");
+    pub const CHILD: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#child");
+    pub const CLASS: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#class");
+    pub const COLUMN: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#column");
+    pub const CONSTANT: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#constant");
+    pub const DATATYPE: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#datatype");
+    pub const DEFAULT_GRAPH: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#defaultGraph");
+    pub const GRAPH: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#graph");
+    pub const GRAPH_MAP: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#graphMap");
+    pub const IRI: NamedNodeRef<'_> = NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#IRI");
+    pub const JOIN_CONDITION: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#joinCondition");
+    pub const LANGUAGE: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#language");
+    pub const LITERAL: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#Literal");
+    pub const LOGICAL_TABLE: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#logicalTable");
+    pub const OBJECT: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#object");
+    pub const OBJECT_MAP: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#objectMap");
+    pub const PARENT: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#parent");
+    pub const PARENT_TRIPLES_MAP: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#parentTriplesMap");
+    pub const PREDICATE: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#predicate");
+    pub const PREDICATE_MAP: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#predicateMap");
+    pub const PREDICATE_OBJECT_MAP: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#predicateObjectMap");
+    pub const SQL_QUERY: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#sqlQuery");
+    pub const SUBJECT: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#subject");
+    pub const SUBJECT_MAP: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#subjectMap");
+    pub const TABLE_NAME: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#tableName");
+    pub const TEMPLATE: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#template");
+    pub const TERM_TYPE: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.w3.org/ns/r2rml#termType");
+}
+
+mod rml {
+    use oxrdf::NamedNodeRef;
+
+    pub const ITERATOR: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://semweb.mmlab.be/ns/rml#iterator");
+    pub const LOGICAL_SOURCE: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://semweb.mmlab.be/ns/rml#logicalSource");
+    pub const QUERY: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://semweb.mmlab.be/ns/rml#query");
+    pub const REFERENCE: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://semweb.mmlab.be/ns/rml#reference");
+    pub const REFERENCE_FORMULATION: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://semweb.mmlab.be/ns/rml#referenceFormulation");
+    pub const SOURCE: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://semweb.mmlab.be/ns/rml#source");
+}
+
+mod ql {
+    use oxrdf::NamedNodeRef;
+
+    pub const CSV: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://semweb.mmlab.be/ns/ql#CSV");
+    pub const JSON_PATH: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://semweb.mmlab.be/ns/ql#JSONPath");
+}
+
+/// A [R2RML](https://www.w3.org/TR/r2rml/) or [RML](https://rml.io/specs/rml/) mapping.
+///
+/// ```
+/// use oxrml::{LogicalSource, RmlMapping};
+///
+/// let mapping = RmlMapping::parse(
+///     r#"
+///     @prefix rr: <http://www.w3.org/ns/r2rml#> .
+///     <#Person> rr:logicalTable [ rr:tableName "PERSON" ] ;
+///         rr:subjectMap [ rr:template "http://example.com/person/{ID}" ] ;
+///         rr:predicateObjectMap [
+///             rr:predicate <http://schema.org/name> ;
+///             rr:objectMap [ rr:column "NAME" ]
+///         ] ."#
+///         .as_bytes(),
+///     Some("http://example.com/mapping"),
+/// )?;
+/// assert_eq!(
+///     mapping.logical_sources().collect::<Vec<_>>(),
+///     [&LogicalSource::Table {
+///         name: "PERSON".into()
+///     }]
+/// );
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[derive(Debug, Clone)]
+#[allow(clippy::field_scoped_visibility_modifiers)]
+pub struct RmlMapping {
+    pub(crate) triples_maps: Vec<TriplesMap>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct TriplesMap {
+    pub source: LogicalSource,
+    pub subject_map: TermMap,
+    pub classes: Vec<NamedNode>,
+    pub graph_maps: Vec<TermMap>,
+    pub predicate_object_maps: Vec<PredicateObjectMap>,
+}
+
+#[derive(Debug, Clone)]
+#[allow(clippy::struct_field_names)]
+pub(crate) struct PredicateObjectMap {
+    pub predicate_maps: Vec<TermMap>,
+    pub object_maps: Vec<ObjectMap>,
+    pub graph_maps: Vec<TermMap>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum ObjectMap {
+    Term(TermMap),
+    /// A referencing object map, `parent` being the index of the parent triples map
+    Reference {
+        parent: usize,
+        join_conditions: Vec<JoinCondition>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct JoinCondition {
+    pub child: Reference,
+    pub parent: Reference,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct TermMap {
+    pub value: TermMapValue,
+    pub term_type: TermType,
+    pub datatype: Option<NamedNode>,
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum TermMapValue {
+    /// `None` for `rr:defaultGraph`
+    Constant(Option<Term>),
+    Reference(Reference),
+    Template(Vec<TemplatePart>),
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum TemplatePart {
+    String(String),
+    Reference(Reference),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TermType {
+    Iri,
+    BlankNode,
+    Literal,
+}
+
+/// The position of a term map in a triples map, that changes its default term type
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Position {
+    Subject,
+    Predicate,
+    Object,
+    Graph,
+}
+
+impl RmlMapping {
+    /// Parses a mapping document serialized in Turtle.
+    pub fn parse(reader: impl Read, base_iri: Option<&str>) -> Result<Self, RmlMappingError> {
+        let mut parser = TurtleParser::new();
+        if let Some(base_iri) = base_iri {
+            parser = parser.with_base_iri(base_iri).map_err(|e| {
+                RmlMappingError::invalid(format!("Invalid base IRI {base_iri}: {e}"))
+            })?;
+        }
+        let graph = parser.for_reader(reader).collect::<Result<Graph, _>>()?;
+        Self::from_graph(&graph)
+    }
+
+    /// Reads the triples maps described in a graph.
+    pub fn from_graph(graph: &Graph) -> Result<Self, RmlMappingError> {
+        let mut nodes = Vec::<SubjectRef<'_>>::new();
+        for predicate in [rr::LOGICAL_TABLE, rml::LOGICAL_SOURCE] {
+            for triple in graph.triples_for_predicate(predicate) {
+                if !nodes.contains(&triple.subject) {
+                    nodes.push(triple.subject);
+                }
+            }
+        }
+        if nodes.is_empty() {
+            return Err(RmlMappingError::invalid(
+                "The mapping does not contain any triples map with a logical table or source",
+            ));
+        }
+        let triples_maps = nodes
+            .iter()
+            .map(|node| parse_triples_map(graph, *node, &nodes))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { triples_maps })
+    }
+
+    /// The logical sources read by the mapping, in the triples maps order.
+    pub fn logical_sources(&self) -> impl Iterator<Item = &LogicalSource> {
+        self.triples_maps.iter().map(|map| &map.source)
+    }
+}
+
+fn parse_triples_map(
+    graph: &Graph,
+    node: SubjectRef<'_>,
+    triples_maps: &[SubjectRef<'_>],
+) -> Result<TriplesMap, RmlMappingError> {
+    let source = parse_logical_source(graph, node)?;
+    let (subject_map, classes, graph_maps) =
+        if let Some(subject) = graph.object_for_subject_predicate(node, rr::SUBJECT) {
+            (constant_map(subject), Vec::new(), Vec::new())
+        } else {
+            let subject_map = single_node(graph, node, rr::SUBJECT_MAP)?.ok_or_else(|| {
+                RmlMappingError::invalid(format!("The triples map {node} has no subject map"))
+            })?;
+            let classes = graph
+                .objects_for_subject_predicate(subject_map, rr::CLASS)
+                .map(|class| match class {
+                    TermRef::NamedNode(class) => Ok(class.into_owned()),
+                    _ => Err(RmlMappingError::invalid(format!(
+                        "The rr:class {class} must be an IRI"
+                    ))),
+                })
+                .collect::<Result<_, _>>()?;
+            (
+                parse_term_map(graph, subject_map, Position::Subject)?,
+                classes,
+                parse_graph_maps(graph, subject_map)?,
+            )
+        };
+    if subject_map.term_type == TermType::Literal {
+        return Err(RmlMappingError::invalid(format!(
+            "The subject map of {node} must not generate literals"
+        )));
+    }
+    let mut predicate_object_maps = Vec::new();
+    for predicate_object_map in graph.objects_for_subject_predicate(node, rr::PREDICATE_OBJECT_MAP)
+    {
+        let predicate_object_map = as_node(predicate_object_map)?;
+        let mut predicate_maps = Vec::new();
+        for predicate in graph.objects_for_subject_predicate(predicate_object_map, rr::PREDICATE) {
+            predicate_maps.push(constant_map(predicate));
+        }
+        for predicate_map in
+            graph.objects_for_subject_predicate(predicate_object_map, rr::PREDICATE_MAP)
+        {
+            predicate_maps.push(parse_term_map(
+                graph,
+                as_node(predicate_map)?,
+                Position::Predicate,
+            )?);
+        }
+        let mut object_maps = Vec::new();
+        for object in graph.objects_for_subject_predicate(predicate_object_map, rr::OBJECT) {
+            object_maps.push(ObjectMap::Term(constant_map(object)));
+        }
+        for object_map in graph.objects_for_subject_predicate(predicate_object_map, rr::OBJECT_MAP)
+        {
+            let object_map = as_node(object_map)?;
+            object_maps.push(
+                if let Some(parent) =
+                    graph.object_for_subject_predicate(object_map, rr::PARENT_TRIPLES_MAP)
+                {
+                    let parent = as_node(parent)?;
+                    let parent = triples_maps
+                        .iter()
+                        .position(|map| *map == parent)
+                        .ok_or_else(|| {
+                            RmlMappingError::invalid(format!(
+                                "The parent triples map {parent} does not exist"
+                            ))
+                        })?;
+                    let mut join_conditions = Vec::new();
+                    for condition in
+                        graph.objects_for_subject_predicate(object_map, rr::JOIN_CONDITION)
+                    {
+                        let condition = as_node(condition)?;
+                        join_conditions.push(JoinCondition {
+                            child: Reference::new(&required_string(graph, condition, rr::CHILD)?),
+                            parent: Reference::new(&required_string(graph, condition, rr::PARENT)?),
+                        });
+                    }
+                    ObjectMap::Reference {
+                        parent,
+                        join_conditions,
+                    }
+                } else {
+                    ObjectMap::Term(parse_term_map(graph, object_map, Position::Object)?)
+                },
+            );
+        }
+        if predicate_maps.is_empty() || object_maps.is_empty() {
+            return Err(RmlMappingError::invalid(format!(
+                "The predicate-object map {predicate_object_map} must have at least a predicate and an object"
+            )));
+        }
+        predicate_object_maps.push(PredicateObjectMap {
+            predicate_maps,
+            object_maps,
+            graph_maps: parse_graph_maps(graph, predicate_object_map)?,
+        });
+    }
+    Ok(TriplesMap {
+        source,
+        subject_map,
+        classes,
+        graph_maps,
+        predicate_object_maps,
+    })
+}
+
+fn parse_logical_source(
+    graph: &Graph,
+    node: SubjectRef<'_>,
+) -> Result<LogicalSource, RmlMappingError> {
+    let source = if let Some(source) = single_node(graph, node, rr::LOGICAL_TABLE)? {
+        source
+    } else if let Some(source) = single_node(graph, node, rml::LOGICAL_SOURCE)? {
+        source
+    } else {
+        return Err(RmlMappingError::invalid(format!(
+            "The triples map {node} has no logical table or source"
+        )));
+    };
+    if let Some(name) = optional_string(graph, source, rr::TABLE_NAME)? {
+        return Ok(LogicalSource::Table { name });
+    }
+    for predicate in [rr::SQL_QUERY, rml::QUERY] {
+        if let Some(query) = optional_string(graph, source, predicate)? {
+            return Ok(LogicalSource::Query { query });
+        }
+    }
+    let Some(path) = optional_string(graph, source, rml::SOURCE)? else {
+        return Err(RmlMappingError::invalid(format!(
+            "The logical source {source} must have a rr:tableName, a rr:sqlQuery, a rml:query or a rml:source"
+        )));
+    };
+    let formulation = graph.object_for_subject_predicate(source, rml::REFERENCE_FORMULATION);
+    let is_json = match formulation {
+        Some(TermRef::NamedNode(formulation)) if formulation == ql::CSV => false,
+        Some(TermRef::NamedNode(formulation)) if formulation == ql::JSON_PATH => true,
+        None if has_extension(&path, "csv") => false,
+        None if has_extension(&path, "json") => true,
+        Some(formulation) => {
+            return Err(RmlMappingError::invalid(format!(
+                "Unsupported reference formulation {formulation}, only ql:CSV and ql:JSONPath are supported"
+            )))
+        }
+        None => {
+            return Err(RmlMappingError::invalid(format!(
+                "The logical source {source} has no rml:referenceFormulation"
+            )))
+        }
+    };
+    Ok(if is_json {
+        LogicalSource::Json {
+            path,
+            iterator: optional_string(graph, source, rml::ITERATOR)?.unwrap_or_else(|| "$".into()),
+        }
+    } else {
+        LogicalSource::Csv { path }
+    })
+}
+
+fn parse_graph_maps(graph: &Graph, node: SubjectRef<'_>) -> Result<Vec<TermMap>, RmlMappingError> {
+    let mut graph_maps = Vec::new();
+    for graph_name in graph.objects_for_subject_predicate(node, rr::GRAPH) {
+        graph_maps.push(constant_map(graph_name));
+    }
+    for graph_map in graph.objects_for_subject_predicate(node, rr::GRAPH_MAP) {
+        graph_maps.push(parse_term_map(graph, as_node(graph_map)?, Position::Graph)?);
+    }
+    Ok(graph_maps)
+}
+
+/// A term map for a `rr:subject`, `rr:predicate`, `rr:object` or `rr:graph` shortcut
+#[allow(clippy::match_wildcard_for_single_variants, unreachable_patterns)]
+fn constant_map(term: TermRef<'_>) -> TermMap {
+    TermMap {
+        term_type: match term {
+            TermRef::Literal(_) => TermType::Literal,
+            TermRef::BlankNode(_) => TermType::BlankNode,
+            _ => TermType::Iri,
+        },
+        value: TermMapValue::Constant(if term == TermRef::from(rr::DEFAULT_GRAPH) {
+            None
+        } else {
+            Some(term.into_owned())
+        }),
+        datatype: None,
+        language: None,
+    }
+}
+
+fn parse_term_map(
+    graph: &Graph,
+    node: SubjectRef<'_>,
+    position: Position,
+) -> Result<TermMap, RmlMappingError> {
+    if let Some(constant) = graph.object_for_subject_predicate(node, rr::CONSTANT) {
+        return Ok(constant_map(constant));
+    }
+    let column = optional_string(graph, node, rr::COLUMN)?;
+    let reference = optional_string(graph, node, rml::REFERENCE)?;
+    let template = optional_string(graph, node, rr::TEMPLATE)?;
+    let (value, is_reference) = match (column.or(reference), template) {
+        (Some(reference), None) => (TermMapValue::Reference(Reference::new(&reference)), true),
+        (None, Some(template)) => (TermMapValue::Template(parse_template(&template)?), false),
+        _ => {
+            return Err(RmlMappingError::invalid(format!(
+                "The term map {node} must have exactly one of rr:constant, rr:column, rml:reference and rr:template"
+            )))
+        }
+    };
+    let datatype = match graph.object_for_subject_predicate(node, rr::DATATYPE) {
+        None => None,
+        Some(TermRef::NamedNode(datatype)) => Some(datatype.into_owned()),
+        Some(datatype) => {
+            return Err(RmlMappingError::invalid(format!(
+                "The rr:datatype {datatype} must be an IRI"
+            )))
+        }
+    };
+    let language = optional_string(graph, node, rr::LANGUAGE)?;
+    let term_type = match graph.object_for_subject_predicate(node, rr::TERM_TYPE) {
+        Some(TermRef::NamedNode(term_type)) if term_type == rr::IRI => TermType::Iri,
+        Some(TermRef::NamedNode(term_type)) if term_type == rr::BLANK_NODE => TermType::BlankNode,
+        Some(TermRef::NamedNode(term_type)) if term_type == rr::LITERAL => TermType::Literal,
+        Some(term_type) => {
+            return Err(RmlMappingError::invalid(format!(
+                "Unsupported rr:termType {term_type}"
+            )))
+        }
+        None => {
+            if position == Position::Object
+                && (is_reference || datatype.is_some() || language.is_some())
+            {
+                TermType::Literal
+            } else {
+                TermType::Iri
+            }
+        }
+    };
+    let allowed = match position {
+        Position::Subject | Position::Graph => term_type != TermType::Literal,
+        Position::Predicate => term_type == TermType::Iri,
+        Position::Object => true,
+    };
+    if !allowed {
+        return Err(RmlMappingError::invalid(format!(
+            "The term type of the term map {node} is not allowed at its position"
+        )));
+    }
+    if (datatype.is_some() || language.is_some()) && term_type != TermType::Literal {
+        return Err(RmlMappingError::invalid(format!(
+            "The term map {node} has a datatype or a language but does not generate literals"
+        )));
+    }
+    if let Some(language) = &language {
+        Literal::new_language_tagged_literal("", language).map_err(|e| {
+            RmlMappingError::invalid(format!("Invalid language tag {language}: {e}"))
+        })?;
+    }
+    Ok(TermMap {
+        value,
+        term_type,
+        datatype,
+        language,
+    })
+}
+
+/// Parses a [string template](https://www.w3.org/TR/r2rml/#from-template)
+fn parse_template(template: &str) -> Result<Vec<TemplatePart>, RmlMappingError> {
+    let mut parts = Vec::new();
+    let mut buffer = String::new();
+    let mut in_reference = false;
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                let Some(escaped) = chars.next() else {
+                    return Err(RmlMappingError::invalid(format!(
+                        "The template {template} ends with a \\"
+                    )));
+                };
+                buffer.push(escaped);
+            }
+            '{' if !in_reference => {
+                if !buffer.is_empty() {
+                    parts.push(TemplatePart::String(std::mem::take(&mut buffer)));
+                }
+                in_reference = true;
+            }
+            '}' if in_reference => {
+                parts.push(TemplatePart::Reference(Reference::new(&buffer)));
+                buffer.clear();
+                in_reference = false;
+            }
+            '{' | '}' => {
+                return Err(RmlMappingError::invalid(format!(
+                    "Unbalanced braces in the template {template}"
+                )))
+            }
+            _ => buffer.push(c),
+        }
+    }
+    if in_reference {
+        return Err(RmlMappingError::invalid(format!(
+            "Unclosed reference in the template {template}"
+        )));
+    }
+    if !buffer.is_empty() {
+        parts.push(TemplatePart::String(buffer));
+    }
+    Ok(parts)
+}
+
+#[allow(clippy::match_wildcard_for_single_variants, unreachable_patterns)]
+fn as_node(term: TermRef<'_>) -> Result<SubjectRef<'_>, RmlMappingError> {
+    match term {
+        TermRef::NamedNode(node) => Ok(node.into()),
+        TermRef::BlankNode(node) => Ok(node.into()),
+        _ => Err(RmlMappingError::invalid(format!(
+            "{term} must be an IRI or a blank node"
+        ))),
+    }
+}
+
+fn single_node<'a>(
+    graph: &'a Graph,
+    node: SubjectRef<'_>,
+    predicate: NamedNodeRef<'_>,
+) -> Result<Option<SubjectRef<'a>>, RmlMappingError> {
+    graph
+        .object_for_subject_predicate(node, predicate)
+        .map(as_node)
+        .transpose()
+}
+
+fn optional_string(
+    graph: &Graph,
+    node: SubjectRef<'_>,
+    predicate: NamedNodeRef<'_>,
+) -> Result<Option<String>, RmlMappingError> {
+    match graph.object_for_subject_predicate(node, predicate) {
+        None => Ok(None),
+        Some(TermRef::Literal(value)) => Ok(Some(value.value().into())),
+        Some(value) => Err(RmlMappingError::invalid(format!(
+            "The value {value} of {predicate} must be a literal"
+        ))),
+    }
+}
+
+fn required_string(
+    graph: &Graph,
+    node: SubjectRef<'_>,
+    predicate: NamedNodeRef<'_>,
+) -> Result<String, RmlMappingError> {
+    optional_string(graph, node, predicate)?
+        .ok_or_else(|| RmlMappingError::invalid(format!("{node} must have a {predicate}")))
+}
+
+fn has_extension(path: &str, extension: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case(extension))
+}
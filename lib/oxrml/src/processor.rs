@@ -0,0 +1,467 @@
+use crate::error::RmlExecutionError;
+use crate::mapping::{
+    JoinCondition, ObjectMap, RmlMapping, TemplatePart, TermMap, TermMapValue, TermType, TriplesMap,
+};
+use crate::source::{Reference, Row, Rows, SourceConnector};
+use oxiri::{Iri, IriParseError};
+use oxrdf::vocab::rdf;
+use oxrdf::{BlankNode, GraphName, Literal, NamedNode, Quad, Subject, Term};
+use std::collections::{HashMap, VecDeque};
+
+/// Executes a [`RmlMapping`] on the rows returned by some [`SourceConnector`]s.
+///
+/// The generated quads might be added to an Oxigraph store using `store.bulk_loader().load_ok_quads(processor.execute())`.
+///
+/// ```
+/// use oxrml::{LogicalSource, RmlExecutionError, RmlMapping, RmlProcessor, Row, Rows, SourceConnector};
+/// use oxrdf::Literal;
+///
+/// /// A connector returning a single row for the PERSON table
+/// struct PersonTable;
+///
+/// impl SourceConnector for PersonTable {
+///     fn supports(&self, source: &LogicalSource) -> bool {
+///         *source == LogicalSource::Table { name: "PERSON".into() }
+///     }
+///
+///     fn rows(&self, _: &LogicalSource) -> Result<Rows<'_>, RmlExecutionError> {
+///         Ok(Box::new([Ok(Row::from_columns([
+///             ("ID", Some(Literal::from(1))),
+///             ("NAME", Some(Literal::from("Alice"))),
+///         ]))].into_iter()))
+///     }
+/// }
+///
+/// let mapping = RmlMapping::parse(
+///     r#"
+///     @prefix rr: <http://www.w3.org/ns/r2rml#> .
+///     <#Person> rr:logicalTable [ rr:tableName "PERSON" ] ;
+///         rr:subjectMap [ rr:template "person/{ID}" ; rr:class <http://schema.org/Person> ] ;
+///         rr:predicateObjectMap [
+///             rr:predicate <http://schema.org/name> ;
+///             rr:objectMap [ rr:column "NAME" ]
+///         ] ."#
+///         .as_bytes(),
+///     Some("http://example.com/mapping"),
+/// )?;
+/// let quads = RmlProcessor::new(mapping)
+///     .with_base_iri("http://example.com/")?
+///     .with_connector(PersonTable)
+///     .execute()
+///     .map(|q| q.map(|q| q.to_string()))
+///     .collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(
+///     quads,
+///     [
+///         "<http://example.com/person/1> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://schema.org/Person>",
+///         "<http://example.com/person/1> <http://schema.org/name> \"Alice\""
+///     ]
+/// );
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[must_use]
+pub struct RmlProcessor {
+    mapping: RmlMapping,
+    connectors: Vec<Box<dyn SourceConnector>>,
+    base_iri: Option<Iri<String>>,
+}
+
+impl RmlProcessor {
+    /// Builds a processor for the given mapping, without any connector.
+    #[inline]
+    pub fn new(mapping: RmlMapping) -> Self {
+        Self {
+            mapping,
+            connectors: Vec::new(),
+            base_iri: None,
+        }
+    }
+
+    /// Sets the base IRI prepended to the relative IRIs generated by the mapping.
+    #[inline]
+    pub fn with_base_iri(mut self, base_iri: impl Into<String>) -> Result<Self, IriParseError> {
+        self.base_iri = Some(Iri::parse(base_iri.into())?);
+        Ok(self)
+    }
+
+    /// Adds a connector to read the logical sources.
+    ///
+    /// The first connector supporting a logical source is used to read it.
+    #[inline]
+    pub fn with_connector(mut self, connector: impl SourceConnector + 'static) -> Self {
+        self.connectors.push(Box::new(connector));
+        self
+    }
+
+    /// Executes the mapping and returns the generated quads.
+    ///
+    /// The triples maps are executed one after the other.
+    /// The rows of the parent triples maps of the referencing object maps are loaded in memory.
+    pub fn execute(&self) -> MappedQuads<'_> {
+        MappedQuads {
+            processor: self,
+            next_map: 0,
+            current: None,
+            buffer: VecDeque::new(),
+            blank_nodes: HashMap::new(),
+            join_indexes: HashMap::new(),
+        }
+    }
+
+    fn rows(&self, map: &TriplesMap) -> Result<Rows<'_>, RmlExecutionError> {
+        self.connectors
+            .iter()
+            .find(|connector| connector.supports(&map.source))
+            .ok_or_else(|| RmlExecutionError::UnsupportedSource(map.source.clone()))?
+            .rows(&map.source)
+    }
+}
+
+/// The quads generated by a [`RmlProcessor`]. Can be built using [`RmlProcessor::execute`].
+///
+/// An error does not stop the execution: the next quads are returned by the next calls.
+#[must_use]
+pub struct MappedQuads<'a> {
+    processor: &'a RmlProcessor,
+    next_map: usize,
+    /// The triples map being executed and its remaining rows
+    current: Option<(usize, Rows<'a>)>,
+    buffer: VecDeque<Quad>,
+    /// The blank nodes generated for each value
+    blank_nodes: HashMap<String, BlankNode>,
+    /// For each referencing object map identified by its triples map, predicate-object map and object map indexes,
+    /// the parent subjects for each parent join values
+    join_indexes: HashMap<(usize, usize, usize), HashMap<Vec<String>, Vec<Term>>>,
+}
+
+impl MappedQuads<'_> {
+    fn process_row(&mut self, map_index: usize, row: &Row) -> Result<(), RmlExecutionError> {
+        let processor = self.processor;
+        let map = &processor.mapping.triples_maps[map_index];
+        let subjects = self
+            .generate(&map.subject_map, row)?
+            .into_iter()
+            .map(term_to_subject)
+            .collect::<Result<Vec<_>, _>>()?;
+        if subjects.is_empty() {
+            return Ok(());
+        }
+        let subject_graphs = self.graph_names(&map.graph_maps, row)?;
+        for subject in &subjects {
+            for class in &map.classes {
+                for graph_name in or_default_graph(&subject_graphs) {
+                    self.buffer.push_back(Quad::new(
+                        subject.clone(),
+                        rdf::TYPE,
+                        class.clone(),
+                        graph_name.clone(),
+                    ));
+                }
+            }
+        }
+        for (predicate_object_index, predicate_object_map) in
+            map.predicate_object_maps.iter().enumerate()
+        {
+            let mut predicates = Vec::new();
+            for predicate_map in &predicate_object_map.predicate_maps {
+                for predicate in self.generate(predicate_map, row)? {
+                    let Term::NamedNode(predicate) = predicate else {
+                        return Err(RmlExecutionError::InvalidTerm(format!(
+                            "The predicate {predicate} is not an IRI"
+                        )));
+                    };
+                    predicates.push(predicate);
+                }
+            }
+            let mut objects = Vec::new();
+            for (object_index, object_map) in predicate_object_map.object_maps.iter().enumerate() {
+                match object_map {
+                    ObjectMap::Term(object_map) => objects.extend(self.generate(object_map, row)?),
+                    ObjectMap::Reference {
+                        parent,
+                        join_conditions,
+                    } => {
+                        let parent_map = &processor.mapping.triples_maps[*parent];
+                        if join_conditions.is_empty() && parent_map.source == map.source {
+                            objects.extend(self.generate(&parent_map.subject_map, row)?);
+                        } else {
+                            let key = (map_index, predicate_object_index, object_index);
+                            if !self.join_indexes.contains_key(&key) {
+                                let index = self.join_index(parent_map, join_conditions)?;
+                                self.join_indexes.insert(key, index);
+                            }
+                            let index = &self.join_indexes[&key];
+                            let children = join_keys(
+                                row,
+                                join_conditions.iter().map(|condition| &condition.child),
+                            )?;
+                            for child in children {
+                                for object in index.get(&child).into_iter().flatten() {
+                                    if !objects.contains(object) {
+                                        objects.push(object.clone());
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            let mut graph_names = subject_graphs.clone();
+            graph_names.extend(self.graph_names(&predicate_object_map.graph_maps, row)?);
+            for subject in &subjects {
+                for predicate in &predicates {
+                    for object in &objects {
+                        for graph_name in or_default_graph(&graph_names) {
+                            self.buffer.push_back(Quad::new(
+                                subject.clone(),
+                                predicate.clone(),
+                                object.clone(),
+                                graph_name.clone(),
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Indexes the subjects of the parent triples map by their join values
+    fn join_index(
+        &mut self,
+        parent_map: &TriplesMap,
+        join_conditions: &[JoinCondition],
+    ) -> Result<HashMap<Vec<String>, Vec<Term>>, RmlExecutionError> {
+        let processor = self.processor;
+        let mut index = HashMap::<_, Vec<_>>::new();
+        for row in processor.rows(parent_map)? {
+            let row = row?;
+            let subjects = self.generate(&parent_map.subject_map, &row)?;
+            for key in join_keys(
+                &row,
+                join_conditions.iter().map(|condition| &condition.parent),
+            )? {
+                index
+                    .entry(key)
+                    .or_default()
+                    .extend(subjects.iter().cloned());
+            }
+        }
+        Ok(index)
+    }
+
+    #[allow(clippy::match_wildcard_for_single_variants, unreachable_patterns)]
+    fn graph_names(
+        &mut self,
+        graph_maps: &[TermMap],
+        row: &Row,
+    ) -> Result<Vec<GraphName>, RmlExecutionError> {
+        let mut graph_names = Vec::new();
+        for graph_map in graph_maps {
+            if let TermMapValue::Constant(None) = graph_map.value {
+                graph_names.push(GraphName::DefaultGraph);
+                continue;
+            }
+            for graph_name in self.generate(graph_map, row)? {
+                graph_names.push(match graph_name {
+                    Term::NamedNode(graph_name) => graph_name.into(),
+                    Term::BlankNode(graph_name) => graph_name.into(),
+                    _ => {
+                        return Err(RmlExecutionError::InvalidTerm(format!(
+                            "The graph name {graph_name} is not an IRI or a blank node"
+                        )))
+                    }
+                });
+            }
+        }
+        Ok(graph_names)
+    }
+
+    /// The terms generated by the term map for the row
+    fn generate(&mut self, map: &TermMap, row: &Row) -> Result<Vec<Term>, RmlExecutionError> {
+        match &map.value {
+            TermMapValue::Constant(term) => Ok(term.iter().cloned().collect()),
+            TermMapValue::Reference(reference) => row
+                .values(reference)?
+                .into_iter()
+                .map(|value| {
+                    if map.term_type == TermType::Literal
+                        && map.datatype.is_none()
+                        && map.language.is_none()
+                    {
+                        // We keep the natural literal
+                        Ok(value.into())
+                    } else {
+                        self.term(map, value.value())
+                    }
+                })
+                .collect(),
+            TermMapValue::Template(parts) => {
+                expand_template(parts, row, map.term_type == TermType::Iri)?
+                    .into_iter()
+                    .map(|value| self.term(map, &value))
+                    .collect()
+            }
+        }
+    }
+
+    fn term(&mut self, map: &TermMap, value: &str) -> Result<Term, RmlExecutionError> {
+        Ok(match map.term_type {
+            TermType::Iri => self.iri(value)?.into(),
+            TermType::BlankNode => self
+                .blank_nodes
+                .entry(value.into())
+                .or_default()
+                .clone()
+                .into(),
+            TermType::Literal => if let Some(datatype) = &map.datatype {
+                Literal::new_typed_literal(value, datatype.clone())
+            } else if let Some(language) = &map.language {
+                Literal::new_language_tagged_literal_unchecked(value, language.to_lowercase())
+            } else {
+                Literal::new_simple_literal(value)
+            }
+            .into(),
+        })
+    }
+
+    /// Builds an IRI, prepending the base IRI if the value is not an absolute IRI
+    fn iri(&self, value: &str) -> Result<NamedNode, RmlExecutionError> {
+        if let Ok(iri) = NamedNode::new(value) {
+            return Ok(iri);
+        }
+        let Some(base_iri) = &self.processor.base_iri else {
+            return Err(RmlExecutionError::InvalidTerm(format!(
+                "{value} is not an absolute IRI and no base IRI is set"
+            )));
+        };
+        NamedNode::new(format!("{}{value}", base_iri.as_str())).map_err(|e| {
+            RmlExecutionError::InvalidTerm(format!("Invalid generated IRI {base_iri}{value}: {e}"))
+        })
+    }
+}
+
+impl Iterator for MappedQuads<'_> {
+    type Item = Result<Quad, RmlExecutionError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(quad) = self.buffer.pop_front() {
+                return Some(Ok(quad));
+            }
+            if let Some((current_map, rows)) = &mut self.current {
+                let map_index = *current_map;
+                match rows.next() {
+                    Some(Ok(row)) => {
+                        if let Err(e) = self.process_row(map_index, &row) {
+                            return Some(Err(e));
+                        }
+                    }
+                    Some(Err(e)) => return Some(Err(e)),
+                    None => self.current = None,
+                }
+                continue;
+            }
+            let processor = self.processor;
+            let map = processor.mapping.triples_maps.get(self.next_map)?;
+            self.next_map += 1;
+            match processor.rows(map) {
+                Ok(rows) => self.current = Some((self.next_map - 1, rows)),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+#[allow(clippy::match_wildcard_for_single_variants, unreachable_patterns)]
+fn term_to_subject(term: Term) -> Result<Subject, RmlExecutionError> {
+    match term {
+        Term::NamedNode(node) => Ok(node.into()),
+        Term::BlankNode(node) => Ok(node.into()),
+        _ => Err(RmlExecutionError::InvalidTerm(format!(
+            "The subject {term} is not an IRI or a blank node"
+        ))),
+    }
+}
+
+fn or_default_graph(graph_names: &[GraphName]) -> &[GraphName] {
+    const DEFAULT_GRAPH: &[GraphName] = &[GraphName::DefaultGraph];
+    if graph_names.is_empty() {
+        DEFAULT_GRAPH
+    } else {
+        graph_names
+    }
+}
+
+/// All the combinations of the lexical values of the references
+fn join_keys<'a>(
+    row: &Row,
+    references: impl IntoIterator<Item = &'a Reference>,
+) -> Result<Vec<Vec<String>>, RmlExecutionError> {
+    let mut keys = vec![Vec::new()];
+    for reference in references {
+        let values = row.values(reference)?;
+        keys = keys
+            .into_iter()
+            .flat_map(|key| {
+                values.iter().map(move |value| {
+                    let mut key = key.clone();
+                    key.push(value.value().to_owned());
+                    key
+                })
+            })
+            .collect();
+    }
+    Ok(keys)
+}
+
+/// All the strings generated by the template, the values being percent-encoded if `iri_safe` is set
+fn expand_template(
+    parts: &[TemplatePart],
+    row: &Row,
+    iri_safe: bool,
+) -> Result<Vec<String>, RmlExecutionError> {
+    let mut results = vec![String::new()];
+    for part in parts {
+        match part {
+            TemplatePart::String(string) => {
+                for result in &mut results {
+                    result.push_str(string);
+                }
+            }
+            TemplatePart::Reference(reference) => {
+                let values = row.values(reference)?;
+                results = results
+                    .iter()
+                    .flat_map(|prefix| {
+                        values.iter().map(move |value| {
+                            let mut result = prefix.clone();
+                            if iri_safe {
+                                push_iri_safe(value.value(), &mut result);
+                            } else {
+                                result.push_str(value.value());
+                            }
+                            result
+                        })
+                    })
+                    .collect();
+            }
+        }
+    }
+    Ok(results)
+}
+
+/// Percent-encodes the characters that are not [unreserved](https://www.w3.org/TR/r2rml/#dfn-iri-safe) in IRIs
+fn push_iri_safe(value: &str, output: &mut String) {
+    const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+    for c in value.chars() {
+        if c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~') || !c.is_ascii() {
+            output.push(c);
+        } else {
+            let byte = c as u8;
+            output.push('%');
+            output.push(char::from(HEX_DIGITS[usize::from(byte >> 4)]));
+            output.push(char::from(HEX_DIGITS[usize::from(byte & 0xF)]));
+        }
+    }
+}
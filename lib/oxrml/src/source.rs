@@ -0,0 +1,229 @@
+use crate::error::RmlExecutionError;
+use crate::json_path::JsonPath;
+use oxrdf::Literal;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+/// The rows returned by a [`SourceConnector`].
+pub type Rows<'a> = Box<dyn Iterator<Item = Result<Row, RmlExecutionError>> + 'a>;
+
+/// Where the rows of a triples map come from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LogicalSource {
+    /// A table or a view of the database (`rr:tableName`).
+    Table { name: String },
+    /// A SQL query on the database (`rr:sqlQuery` or `rml:query`).
+    Query { query: String },
+    /// A CSV file, each line after the header being a row (`rml:source` with the `ql:CSV` reference formulation).
+    Csv { path: String },
+    /// A JSON file, each value matched by the JSONPath iterator being a row (`rml:source` with the `ql:JSONPath` reference formulation).
+    Json { path: String, iterator: String },
+}
+
+impl fmt::Display for LogicalSource {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Table { name } => write!(f, "table {name}"),
+            Self::Query { query } => write!(f, "query {query}"),
+            Self::Csv { path } => write!(f, "CSV file {path}"),
+            Self::Json { path, iterator } => write!(f, "JSON file {path} with iterator {iterator}"),
+        }
+    }
+}
+
+/// A connector returning the rows of some kinds of [`LogicalSource`].
+///
+/// Built-in connectors are [`FileConnector`] for CSV and JSON files,
+/// `SqliteConnector` behind the `sqlite` feature and `PostgresConnector` behind the `postgres` feature.
+pub trait SourceConnector {
+    /// If the connector is able to read the logical source.
+    fn supports(&self, source: &LogicalSource) -> bool;
+
+    /// Returns the rows of the logical source.
+    fn rows(&self, source: &LogicalSource) -> Result<Rows<'_>, RmlExecutionError>;
+}
+
+/// A row of a logical source.
+///
+/// The values of the references are literals following the [natural RDF mapping](https://www.w3.org/TR/r2rml/#natural-mapping) of the source values,
+/// e.g. an `xsd:integer` for a SQL `INTEGER` or a JSON integer.
+#[derive(Debug, Clone)]
+pub struct Row(RowKind);
+
+#[derive(Debug, Clone)]
+enum RowKind {
+    Columns(HashMap<String, Literal>),
+    Json(Value),
+}
+
+impl Row {
+    /// Builds a row from its column values, `None` being the SQL `NULL`.
+    ///
+    /// The references are the column names.
+    pub fn from_columns(
+        columns: impl IntoIterator<Item = (impl Into<String>, Option<Literal>)>,
+    ) -> Self {
+        Self(RowKind::Columns(
+            columns
+                .into_iter()
+                .filter_map(|(name, value)| Some((name.into(), value?)))
+                .collect(),
+        ))
+    }
+
+    /// Builds a row from a JSON value.
+    ///
+    /// The references are JSONPath expressions relative to the value.
+    #[inline]
+    pub fn from_json(value: Value) -> Self {
+        Self(RowKind::Json(value))
+    }
+
+    /// The values of the reference, empty if it is null.
+    pub(crate) fn values(&self, reference: &Reference) -> Result<Vec<Literal>, RmlExecutionError> {
+        match &self.0 {
+            RowKind::Columns(columns) => Ok(columns
+                .get(&reference.column)
+                .cloned()
+                .into_iter()
+                .collect()),
+            RowKind::Json(value) => {
+                let path = reference.json_path.as_ref().map_err(|e| {
+                    RmlExecutionError::InvalidTerm(format!(
+                        "Invalid JSONPath reference {}: {e}",
+                        reference.column
+                    ))
+                })?;
+                let mut values = Vec::new();
+                for value in path.evaluate(value) {
+                    json_literals(value, &mut values);
+                }
+                Ok(values)
+            }
+        }
+    }
+}
+
+fn json_literals(value: &Value, output: &mut Vec<Literal>) {
+    match value {
+        Value::Null | Value::Object(_) => (),
+        Value::Bool(value) => output.push((*value).into()),
+        Value::Number(value) => output.push(if let Some(value) = value.as_i64() {
+            value.into()
+        } else if let Some(value) = value.as_u64() {
+            Literal::new_typed_literal(value.to_string(), oxrdf::vocab::xsd::INTEGER)
+        } else {
+            value.as_f64().unwrap_or(f64::NAN).into()
+        }),
+        Value::String(value) => output.push(Literal::new_simple_literal(value)),
+        Value::Array(values) => {
+            for value in values {
+                json_literals(value, output);
+            }
+        }
+    }
+}
+
+/// A reference to a column or a JSONPath expression
+#[derive(Debug, Clone)]
+pub(crate) struct Reference {
+    /// The column name, without the SQL quotes
+    column: String,
+    json_path: Result<JsonPath, String>,
+}
+
+impl Reference {
+    pub fn new(reference: &str) -> Self {
+        Self {
+            column: reference
+                .strip_prefix('"')
+                .and_then(|r| r.strip_suffix('"'))
+                .unwrap_or(reference)
+                .to_owned(),
+            json_path: JsonPath::parse(reference),
+        }
+    }
+}
+
+/// A connector for the CSV and JSON files, the paths being resolved against a base directory.
+///
+/// ```
+/// use oxrml::{FileConnector, LogicalSource, SourceConnector};
+///
+/// let connector = FileConnector::new("data");
+/// assert!(connector.supports(&LogicalSource::Csv {
+///     path: "people.csv".into()
+/// }));
+/// ```
+#[derive(Debug, Clone)]
+pub struct FileConnector {
+    base_directory: PathBuf,
+}
+
+impl FileConnector {
+    /// Builds a connector resolving the relative paths against `base_directory`.
+    #[inline]
+    pub fn new(base_directory: impl Into<PathBuf>) -> Self {
+        Self {
+            base_directory: base_directory.into(),
+        }
+    }
+
+    fn path(&self, path: &str) -> PathBuf {
+        let path = path.strip_prefix("file://").unwrap_or(path);
+        self.base_directory.join(Path::new(path))
+    }
+}
+
+impl SourceConnector for FileConnector {
+    fn supports(&self, source: &LogicalSource) -> bool {
+        matches!(
+            source,
+            LogicalSource::Csv { .. } | LogicalSource::Json { .. }
+        )
+    }
+
+    fn rows(&self, source: &LogicalSource) -> Result<Rows<'_>, RmlExecutionError> {
+        match source {
+            LogicalSource::Csv { path } => {
+                let mut reader = csv::Reader::from_reader(File::open(self.path(path))?);
+                let headers = reader
+                    .headers()
+                    .map_err(RmlExecutionError::connector)?
+                    .iter()
+                    .map(ToOwned::to_owned)
+                    .collect::<Vec<_>>();
+                Ok(Box::new(reader.into_records().map(move |record| {
+                    let record = record.map_err(RmlExecutionError::connector)?;
+                    Ok(Row::from_columns(headers.iter().zip(&record).map(
+                        |(name, value)| (name.as_str(), Some(Literal::new_simple_literal(value))),
+                    )))
+                })))
+            }
+            LogicalSource::Json { path, iterator } => {
+                let iterator = JsonPath::parse(iterator).map_err(|e| {
+                    RmlExecutionError::connector(format!(
+                        "Invalid JSONPath iterator {iterator}: {e}"
+                    ))
+                })?;
+                let value = serde_json::from_reader::<_, Value>(BufReader::new(File::open(
+                    self.path(path),
+                )?))
+                .map_err(RmlExecutionError::connector)?;
+                let rows = iterator
+                    .evaluate(&value)
+                    .into_iter()
+                    .map(|value| Ok(Row::from_json(value.clone())))
+                    .collect::<Vec<_>>();
+                Ok(Box::new(rows.into_iter()))
+            }
+            _ => Err(RmlExecutionError::UnsupportedSource(source.clone())),
+        }
+    }
+}
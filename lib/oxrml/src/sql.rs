@@ -0,0 +1,223 @@
+//! Connectors for the relational databases.
+
+use crate::error::RmlExecutionError;
+use crate::source::{LogicalSource, Row, Rows, SourceConnector};
+use oxrdf::vocab::xsd;
+use oxrdf::Literal;
+#[cfg(feature = "postgres")]
+use std::sync::Mutex;
+
+/// The SQL query returning the rows of a table or query logical source
+fn sql_query(source: &LogicalSource) -> Result<String, RmlExecutionError> {
+    match source {
+        LogicalSource::Table { name } => Ok(format!("SELECT * FROM {name}")),
+        LogicalSource::Query { query } => Ok(query.clone()),
+        _ => Err(RmlExecutionError::UnsupportedSource(source.clone())),
+    }
+}
+
+/// A connector for the tables and queries of a [SQLite](https://www.sqlite.org/) database.
+///
+/// The `INTEGER`, `REAL`, `TEXT` and `BLOB` values are mapped to `xsd:integer`, `xsd:double`, `xsd:string` and `xsd:hexBinary` literals.
+///
+/// ```
+/// use oxrml::{LogicalSource, SourceConnector, SqliteConnector};
+///
+/// let connector = SqliteConnector::open_in_memory()?;
+/// assert!(connector.supports(&LogicalSource::Table {
+///     name: "PERSON".into()
+/// }));
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[cfg(feature = "sqlite")]
+pub struct SqliteConnector {
+    connection: rusqlite::Connection,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteConnector {
+    /// Opens the database file at the given path.
+    pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self, RmlExecutionError> {
+        Ok(Self::from_connection(
+            rusqlite::Connection::open(path).map_err(RmlExecutionError::connector)?,
+        ))
+    }
+
+    /// Opens a new in-memory database.
+    pub fn open_in_memory() -> Result<Self, RmlExecutionError> {
+        Ok(Self::from_connection(
+            rusqlite::Connection::open_in_memory().map_err(RmlExecutionError::connector)?,
+        ))
+    }
+
+    /// Wraps an existing connection.
+    #[inline]
+    pub fn from_connection(connection: rusqlite::Connection) -> Self {
+        Self { connection }
+    }
+
+    /// The underlying connection, e.g. to fill the database.
+    #[inline]
+    pub fn connection(&self) -> &rusqlite::Connection {
+        &self.connection
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl SourceConnector for SqliteConnector {
+    fn supports(&self, source: &LogicalSource) -> bool {
+        matches!(
+            source,
+            LogicalSource::Table { .. } | LogicalSource::Query { .. }
+        )
+    }
+
+    fn rows(&self, source: &LogicalSource) -> Result<Rows<'_>, RmlExecutionError> {
+        use rusqlite::types::ValueRef;
+
+        let mut statement = self
+            .connection
+            .prepare(&sql_query(source)?)
+            .map_err(RmlExecutionError::connector)?;
+        let columns = statement
+            .column_names()
+            .into_iter()
+            .map(ToOwned::to_owned)
+            .collect::<Vec<_>>();
+        let mut results = statement.query([]).map_err(RmlExecutionError::connector)?;
+        let mut rows = Vec::new();
+        while let Some(result) = results.next().map_err(RmlExecutionError::connector)? {
+            let mut values = Vec::with_capacity(columns.len());
+            for (i, column) in columns.iter().enumerate() {
+                let value = match result.get_ref(i).map_err(RmlExecutionError::connector)? {
+                    ValueRef::Null => None,
+                    ValueRef::Integer(value) => Some(value.into()),
+                    ValueRef::Real(value) => Some(value.into()),
+                    ValueRef::Text(value) => {
+                        Some(Literal::new_simple_literal(String::from_utf8_lossy(value)))
+                    }
+                    ValueRef::Blob(value) => Some(Literal::new_typed_literal(
+                        hex_encode(value),
+                        xsd::HEX_BINARY,
+                    )),
+                };
+                values.push((column.as_str(), value));
+            }
+            rows.push(Ok(Row::from_columns(values)));
+        }
+        Ok(Box::new(rows.into_iter()))
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn hex_encode(value: &[u8]) -> String {
+    const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+    let mut result = String::with_capacity(value.len() * 2);
+    for byte in value {
+        result.push(char::from(HEX_DIGITS[usize::from(byte >> 4)]));
+        result.push(char::from(HEX_DIGITS[usize::from(byte & 0xF)]));
+    }
+    result
+}
+
+/// A connector for the tables and queries of a [PostgreSQL](https://www.postgresql.org/) database.
+///
+/// The values are mapped following the [R2RML natural mapping](https://www.w3.org/TR/r2rml/#natural-mapping),
+/// e.g. `BOOLEAN` to `xsd:boolean`, `INTEGER` to `xsd:integer` and `TIMESTAMP` to `xsd:dateTime`.
+/// The other types are mapped to `xsd:string` literals.
+///
+/// ```no_run
+/// use oxrml::{LogicalSource, PostgresConnector, SourceConnector};
+///
+/// let connector = PostgresConnector::connect("host=localhost user=postgres dbname=example")?;
+/// assert!(connector.supports(&LogicalSource::Table {
+///     name: "person".into()
+/// }));
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[cfg(feature = "postgres")]
+pub struct PostgresConnector {
+    client: Mutex<postgres::Client>,
+}
+
+#[cfg(feature = "postgres")]
+impl PostgresConnector {
+    /// Connects without TLS to the database described by the [connection parameters](https://docs.rs/postgres/latest/postgres/config/struct.Config.html).
+    pub fn connect(params: &str) -> Result<Self, RmlExecutionError> {
+        Ok(Self::from_client(
+            postgres::Client::connect(params, postgres::NoTls)
+                .map_err(RmlExecutionError::connector)?,
+        ))
+    }
+
+    /// Wraps an existing client.
+    #[inline]
+    pub fn from_client(client: postgres::Client) -> Self {
+        Self {
+            client: Mutex::new(client),
+        }
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl SourceConnector for PostgresConnector {
+    fn supports(&self, source: &LogicalSource) -> bool {
+        matches!(
+            source,
+            LogicalSource::Table { .. } | LogicalSource::Query { .. }
+        )
+    }
+
+    fn rows(&self, source: &LogicalSource) -> Result<Rows<'_>, RmlExecutionError> {
+        use postgres::types::Type;
+        use postgres::SimpleQueryMessage;
+
+        let query = sql_query(source)?;
+        let mut client = self
+            .client
+            .lock()
+            .map_err(|_| RmlExecutionError::connector("The PostgreSQL client lock is poisoned"))?;
+        // We prepare the query to get the column types and use the simple query protocol to get the values as text
+        let types = client
+            .prepare(&query)
+            .map_err(RmlExecutionError::connector)?
+            .columns()
+            .iter()
+            .map(|column| column.type_().clone())
+            .collect::<Vec<_>>();
+        let mut rows = Vec::new();
+        for message in client
+            .simple_query(&query)
+            .map_err(RmlExecutionError::connector)?
+        {
+            let SimpleQueryMessage::Row(row) = message else {
+                continue;
+            };
+            let mut values = Vec::with_capacity(types.len());
+            for (i, (column, r#type)) in row.columns().iter().zip(&types).enumerate() {
+                let value = row.get(i).map(|value| {
+                    if *r#type == Type::BOOL {
+                        Literal::from(value == "t")
+                    } else if [Type::INT2, Type::INT4, Type::INT8].contains(r#type) {
+                        Literal::new_typed_literal(value, xsd::INTEGER)
+                    } else if [Type::FLOAT4, Type::FLOAT8].contains(r#type) {
+                        Literal::new_typed_literal(value, xsd::DOUBLE)
+                    } else if *r#type == Type::NUMERIC {
+                        Literal::new_typed_literal(value, xsd::DECIMAL)
+                    } else if *r#type == Type::DATE {
+                        Literal::new_typed_literal(value, xsd::DATE)
+                    } else if *r#type == Type::TIME {
+                        Literal::new_typed_literal(value, xsd::TIME)
+                    } else if *r#type == Type::TIMESTAMP {
+                        Literal::new_typed_literal(value.replace(' ', "T"), xsd::DATE_TIME)
+                    } else {
+                        Literal::new_simple_literal(value)
+                    }
+                });
+                values.push((column.name(), value));
+            }
+            rows.push(Ok(Row::from_columns(values)));
+        }
+        Ok(Box::new(rows.into_iter()))
+    }
+}
@@ -0,0 +1,336 @@
+#![cfg(test)]
+#![allow(clippy::panic_in_result_fn)]
+
+use oxrdf::vocab::{rdf, xsd};
+use oxrdf::{Dataset, GraphName, Literal, NamedNode, Quad, Subject};
+use oxrml::{
+    FileConnector, LogicalSource, RmlExecutionError, RmlMapping, RmlMappingError, RmlProcessor,
+    Row, Rows, SourceConnector,
+};
+use std::collections::HashMap;
+use std::env::temp_dir;
+use std::fs;
+
+/// A connector returning fixed rows for each table
+#[derive(Default)]
+struct MemoryConnector {
+    tables: HashMap<String, Vec<Vec<(&'static str, Option<Literal>)>>>,
+}
+
+impl MemoryConnector {
+    fn with_table(
+        mut self,
+        name: &str,
+        rows: impl IntoIterator<Item = Vec<(&'static str, Option<Literal>)>>,
+    ) -> Self {
+        self.tables.insert(name.into(), rows.into_iter().collect());
+        self
+    }
+}
+
+impl SourceConnector for MemoryConnector {
+    fn supports(&self, source: &LogicalSource) -> bool {
+        matches!(source, LogicalSource::Table { name } if self.tables.contains_key(name))
+    }
+
+    fn rows(&self, source: &LogicalSource) -> Result<Rows<'_>, RmlExecutionError> {
+        let LogicalSource::Table { name } = source else {
+            return Err(RmlExecutionError::UnsupportedSource(source.clone()));
+        };
+        Ok(Box::new(
+            self.tables[name]
+                .iter()
+                .map(|row| Ok(Row::from_columns(row.iter().cloned()))),
+        ))
+    }
+}
+
+fn people() -> MemoryConnector {
+    MemoryConnector::default()
+        .with_table(
+            "PERSON",
+            [
+                vec![
+                    ("ID", Some(Literal::from(1))),
+                    ("NAME", Some(Literal::from("Alice"))),
+                    ("CITY", Some(Literal::from(10))),
+                ],
+                vec![
+                    ("ID", Some(Literal::from(2))),
+                    ("NAME", Some(Literal::from("Bob Jr"))),
+                    ("CITY", None),
+                ],
+            ],
+        )
+        .with_table(
+            "CITY",
+            [vec![
+                ("ID", Some(Literal::from(10))),
+                ("NAME", Some(Literal::from("Lyon"))),
+            ]],
+        )
+}
+
+fn execute(
+    mapping: &str,
+    connector: MemoryConnector,
+) -> Result<Vec<Quad>, Box<dyn std::error::Error>> {
+    let mapping = RmlMapping::parse(mapping.as_bytes(), Some("http://example.com/mapping"))?;
+    Ok(RmlProcessor::new(mapping)
+        .with_base_iri("http://example.com/")?
+        .with_connector(connector)
+        .execute()
+        .collect::<Result<_, _>>()?)
+}
+
+fn nn(iri: &str) -> NamedNode {
+    NamedNode::new_unchecked(iri)
+}
+
+#[test]
+fn test_template_and_column() -> Result<(), Box<dyn std::error::Error>> {
+    let quads = execute(
+        r#"
+        @prefix rr: <http://www.w3.org/ns/r2rml#> .
+        <#Person> rr:logicalTable [ rr:tableName "PERSON" ] ;
+            rr:subjectMap [ rr:template "person/{NAME}" ; rr:class <http://schema.org/Person> ] ;
+            rr:predicateObjectMap [
+                rr:predicate <http://schema.org/identifier> ;
+                rr:objectMap [ rr:column "ID" ]
+            ] ."#,
+        people(),
+    )?;
+    // The quads order depends on the order of the mapping rules
+    assert_eq!(
+        Dataset::from_iter(quads),
+        Dataset::from_iter([
+            Quad::new(
+                nn("http://example.com/person/Alice"),
+                rdf::TYPE,
+                nn("http://schema.org/Person"),
+                GraphName::DefaultGraph
+            ),
+            Quad::new(
+                nn("http://example.com/person/Alice"),
+                nn("http://schema.org/identifier"),
+                Literal::from(1),
+                GraphName::DefaultGraph
+            ),
+            Quad::new(
+                nn("http://example.com/person/Bob%20Jr"),
+                rdf::TYPE,
+                nn("http://schema.org/Person"),
+                GraphName::DefaultGraph
+            ),
+            Quad::new(
+                nn("http://example.com/person/Bob%20Jr"),
+                nn("http://schema.org/identifier"),
+                Literal::from(2),
+                GraphName::DefaultGraph
+            ),
+        ])
+    );
+    Ok(())
+}
+
+#[test]
+fn test_datatype_language_and_graph() -> Result<(), Box<dyn std::error::Error>> {
+    let quads = execute(
+        r#"
+        @prefix rr: <http://www.w3.org/ns/r2rml#> .
+        @prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+        <#Person> rr:logicalTable [ rr:tableName "PERSON" ] ;
+            rr:subjectMap [ rr:column "NAME" ; rr:termType rr:BlankNode ; rr:graph <http://example.com/g> ] ;
+            rr:predicateObjectMap [
+                rr:predicate <http://schema.org/identifier> ;
+                rr:objectMap [ rr:column "ID" ; rr:datatype xsd:string ]
+            ], [
+                rr:predicate <http://schema.org/name> ;
+                rr:objectMap [ rr:template "{NAME}!" ; rr:language "en" ]
+            ] ."#,
+        people(),
+    )?;
+    // The blank node subjects are compared through the descriptions they have
+    let mut descriptions = HashMap::<_, Vec<_>>::new();
+    for quad in quads {
+        assert!(matches!(quad.subject, Subject::BlankNode(_)));
+        assert_eq!(quad.graph_name, nn("http://example.com/g").into());
+        descriptions
+            .entry(quad.subject)
+            .or_default()
+            .push(format!("{} {}", quad.predicate, quad.object));
+    }
+    let mut descriptions = descriptions
+        .into_values()
+        .map(|mut description| {
+            description.sort();
+            description
+        })
+        .collect::<Vec<_>>();
+    descriptions.sort();
+    assert_eq!(
+        descriptions,
+        [
+            [
+                format!(
+                    "<http://schema.org/identifier> {}",
+                    Literal::new_typed_literal("1", xsd::STRING)
+                ),
+                format!(
+                    "<http://schema.org/name> {}",
+                    Literal::new_language_tagged_literal("Alice!", "en")?
+                ),
+            ],
+            [
+                format!(
+                    "<http://schema.org/identifier> {}",
+                    Literal::new_typed_literal("2", xsd::STRING)
+                ),
+                format!(
+                    "<http://schema.org/name> {}",
+                    Literal::new_language_tagged_literal("Bob Jr!", "en")?
+                ),
+            ],
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_join() -> Result<(), Box<dyn std::error::Error>> {
+    let quads = execute(
+        r#"
+        @prefix rr: <http://www.w3.org/ns/r2rml#> .
+        <#Person> rr:logicalTable [ rr:tableName "PERSON" ] ;
+            rr:subjectMap [ rr:template "person/{ID}" ] ;
+            rr:predicateObjectMap [
+                rr:predicate <http://schema.org/homeLocation> ;
+                rr:objectMap [
+                    rr:parentTriplesMap <#City> ;
+                    rr:joinCondition [ rr:child "CITY" ; rr:parent "ID" ]
+                ]
+            ] .
+        <#City> rr:logicalTable [ rr:tableName "CITY" ] ;
+            rr:subjectMap [ rr:template "city/{NAME}" ] ."#,
+        people(),
+    )?;
+    assert_eq!(
+        quads,
+        [Quad::new(
+            nn("http://example.com/person/1"),
+            nn("http://schema.org/homeLocation"),
+            nn("http://example.com/city/Lyon"),
+            GraphName::DefaultGraph
+        )]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_unsupported_source() -> Result<(), Box<dyn std::error::Error>> {
+    let mapping = RmlMapping::parse(
+        r#"
+        @prefix rr: <http://www.w3.org/ns/r2rml#> .
+        <#Person> rr:logicalTable [ rr:tableName "OTHER" ] ;
+            rr:subjectMap [ rr:template "http://example.com/{ID}" ] ."#
+            .as_bytes(),
+        Some("http://example.com/mapping"),
+    )?;
+    let results = RmlProcessor::new(mapping)
+        .with_connector(people())
+        .execute()
+        .collect::<Vec<_>>();
+    assert!(matches!(
+        results.as_slice(),
+        [Err(RmlExecutionError::UnsupportedSource(_))]
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_invalid_mapping() {
+    assert!(matches!(
+        RmlMapping::parse(
+            r#"
+            @prefix rr: <http://www.w3.org/ns/r2rml#> .
+            <#Person> rr:logicalTable [ rr:tableName "PERSON" ] ."#
+                .as_bytes(),
+            Some("http://example.com/mapping"),
+        ),
+        Err(RmlMappingError::Invalid(_))
+    ));
+    assert!(matches!(
+        RmlMapping::parse("<#Person> rr:logicalTable".as_bytes(), None),
+        Err(RmlMappingError::Parsing(_))
+    ));
+}
+
+#[test]
+fn test_csv_and_json_files() -> Result<(), Box<dyn std::error::Error>> {
+    let directory = temp_dir().join("oxrml_test_files");
+    fs::create_dir_all(&directory)?;
+    fs::write(directory.join("cities.csv"), "id,name\n10,Lyon\n11,Paris\n")?;
+    fs::write(
+        directory.join("people.json"),
+        r#"{"people": [{"name": "Alice", "city": 10, "emails": ["a@example.com", "alice@example.com"]}]}"#,
+    )?;
+    let mapping = RmlMapping::parse(
+        r#"
+        @prefix rr: <http://www.w3.org/ns/r2rml#> .
+        @prefix rml: <http://semweb.mmlab.be/ns/rml#> .
+        @prefix ql: <http://semweb.mmlab.be/ns/ql#> .
+        <#Person> rml:logicalSource [
+                rml:source "people.json" ;
+                rml:referenceFormulation ql:JSONPath ;
+                rml:iterator "$.people[*]"
+            ] ;
+            rr:subjectMap [ rr:template "http://example.com/person/{name}" ] ;
+            rr:predicateObjectMap [
+                rr:predicate <http://schema.org/email> ;
+                rr:objectMap [ rml:reference "emails[*]" ]
+            ], [
+                rr:predicate <http://schema.org/homeLocation> ;
+                rr:objectMap [
+                    rr:parentTriplesMap <#City> ;
+                    rr:joinCondition [ rr:child "city" ; rr:parent "id" ]
+                ]
+            ] .
+        <#City> rml:logicalSource [
+                rml:source "cities.csv" ;
+                rml:referenceFormulation ql:CSV
+            ] ;
+            rr:subjectMap [ rr:template "http://example.com/city/{name}" ] ."#
+            .as_bytes(),
+        Some("http://example.com/mapping"),
+    )?;
+    let quads = RmlProcessor::new(mapping)
+        .with_connector(FileConnector::new(&directory))
+        .execute()
+        .collect::<Result<Vec<_>, _>>()?;
+    let person = nn("http://example.com/person/Alice");
+    // The quads order depends on the order of the mapping rules
+    assert_eq!(
+        Dataset::from_iter(quads),
+        Dataset::from_iter([
+            Quad::new(
+                person.clone(),
+                nn("http://schema.org/email"),
+                Literal::from("a@example.com"),
+                GraphName::DefaultGraph
+            ),
+            Quad::new(
+                person.clone(),
+                nn("http://schema.org/email"),
+                Literal::from("alice@example.com"),
+                GraphName::DefaultGraph
+            ),
+            Quad::new(
+                person,
+                nn("http://schema.org/homeLocation"),
+                nn("http://example.com/city/Lyon"),
+                GraphName::DefaultGraph
+            ),
+        ])
+    );
+    Ok(())
+}
@@ -0,0 +1,316 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// [XML Schema `hexBinary` datatype](https://www.w3.org/TR/xmlschema11-2/#hexBinary)
+///
+/// Uses internally a [`Vec<u8>`].
+///
+/// The order is the one of [XPath `op:binary-less-than`](https://www.w3.org/TR/xpath-functions-31/#func-binary-less-than),
+/// i.e. the octets are compared one by one.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct HexBinary {
+    value: Vec<u8>,
+}
+
+impl HexBinary {
+    /// The binary value.
+    #[inline]
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.value
+    }
+
+    /// Checks if the two values are [identical](https://www.w3.org/TR/xmlschema11-2/#identity).
+    #[inline]
+    #[must_use]
+    pub fn is_identical_with(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl From<Vec<u8>> for HexBinary {
+    #[inline]
+    fn from(value: Vec<u8>) -> Self {
+        Self { value }
+    }
+}
+
+impl From<&[u8]> for HexBinary {
+    #[inline]
+    fn from(value: &[u8]) -> Self {
+        value.to_vec().into()
+    }
+}
+
+impl From<Base64Binary> for HexBinary {
+    #[inline]
+    fn from(value: Base64Binary) -> Self {
+        value.value.into()
+    }
+}
+
+impl From<HexBinary> for Vec<u8> {
+    #[inline]
+    fn from(value: HexBinary) -> Self {
+        value.value
+    }
+}
+
+impl FromStr for HexBinary {
+    type Err = ParseBinaryError;
+
+    /// Parses hexBinary lexical mapping
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        // ([0-9a-fA-F]{2})*
+        let input = input.as_bytes();
+        if input.len() % 2 != 0 {
+            return Err(PARSE_UNEXPECTED_END);
+        }
+        Ok(input
+            .chunks_exact(2)
+            .map(|pair| {
+                pair.iter().try_fold(0, |byte, c| {
+                    Ok::<_, ParseBinaryError>((byte << 4) | hex_digit_value(*c)?)
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .into())
+    }
+}
+
+impl fmt::Display for HexBinary {
+    /// Writes the canonical mapping, using upper case digits
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in &self.value {
+            write!(f, "{byte:02X}")?;
+        }
+        Ok(())
+    }
+}
+
+/// [XML Schema `base64Binary` datatype](https://www.w3.org/TR/xmlschema11-2/#base64Binary)
+///
+/// Uses internally a [`Vec<u8>`].
+///
+/// The order is the one of [XPath `op:binary-less-than`](https://www.w3.org/TR/xpath-functions-31/#func-binary-less-than),
+/// i.e. the octets are compared one by one.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Base64Binary {
+    value: Vec<u8>,
+}
+
+impl Base64Binary {
+    /// The binary value.
+    #[inline]
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.value
+    }
+
+    /// Checks if the two values are [identical](https://www.w3.org/TR/xmlschema11-2/#identity).
+    #[inline]
+    #[must_use]
+    pub fn is_identical_with(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl From<Vec<u8>> for Base64Binary {
+    #[inline]
+    fn from(value: Vec<u8>) -> Self {
+        Self { value }
+    }
+}
+
+impl From<&[u8]> for Base64Binary {
+    #[inline]
+    fn from(value: &[u8]) -> Self {
+        value.to_vec().into()
+    }
+}
+
+impl From<HexBinary> for Base64Binary {
+    #[inline]
+    fn from(value: HexBinary) -> Self {
+        value.value.into()
+    }
+}
+
+impl From<Base64Binary> for Vec<u8> {
+    #[inline]
+    fn from(value: Base64Binary) -> Self {
+        value.value
+    }
+}
+
+impl FromStr for Base64Binary {
+    type Err = ParseBinaryError;
+
+    /// Parses base64Binary lexical mapping
+    ///
+    /// The spaces are ignored and the padding is required.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.bytes().filter(|c| *c != b' ').collect::<Vec<_>>();
+        if input.len() % 4 != 0 {
+            return Err(PARSE_UNEXPECTED_END);
+        }
+        let mut value = Vec::with_capacity(input.len() / 4 * 3);
+        let chunk_count = input.len() / 4;
+        for (i, chunk) in input.chunks_exact(4).enumerate() {
+            let padding = chunk.iter().rev().take_while(|c| **c == b'=').count();
+            if padding > 2 || (padding > 0 && i + 1 != chunk_count) {
+                return Err(PARSE_UNEXPECTED_CHAR);
+            }
+            let mut block = 0_u32;
+            for c in &chunk[..4 - padding] {
+                block = (block << 6) | u32::from(base64_digit_value(*c)?);
+            }
+            block <<= 6 * padding;
+            let bytes = block.to_be_bytes();
+            // The unused bits of the last digit must be zeros
+            if padding > 0 && bytes[4 - padding..].iter().any(|b| *b != 0) {
+                return Err(PARSE_UNEXPECTED_CHAR);
+            }
+            value.extend_from_slice(&bytes[1..4 - padding]);
+        }
+        Ok(value.into())
+    }
+}
+
+impl fmt::Display for Base64Binary {
+    /// Writes the canonical mapping, without spaces and with padding
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for chunk in self.value.chunks(3) {
+            let mut bytes = [0; 3];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            let [b0, b1, b2] = bytes;
+            let digits = [
+                b0 >> 2,
+                ((b0 & 0x03) << 4) | (b1 >> 4),
+                ((b1 & 0x0F) << 2) | (b2 >> 6),
+                b2 & 0x3F,
+            ];
+            for (i, digit) in digits.into_iter().enumerate() {
+                if i <= chunk.len() {
+                    write!(f, "{}", char::from(BASE64_DIGITS[usize::from(digit)]))?;
+                } else {
+                    f.write_str("=")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+const BASE64_DIGITS: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn hex_digit_value(c: u8) -> Result<u8, ParseBinaryError> {
+    match c {
+        b'0'..=b'9' => Ok(c - b'0'),
+        b'a'..=b'f' => Ok(c - b'a' + 10),
+        b'A'..=b'F' => Ok(c - b'A' + 10),
+        _ => Err(PARSE_UNEXPECTED_CHAR),
+    }
+}
+
+fn base64_digit_value(c: u8) -> Result<u8, ParseBinaryError> {
+    match c {
+        b'A'..=b'Z' => Ok(c - b'A'),
+        b'a'..=b'z' => Ok(c - b'a' + 26),
+        b'0'..=b'9' => Ok(c - b'0' + 52),
+        b'+' => Ok(62),
+        b'/' => Ok(63),
+        _ => Err(PARSE_UNEXPECTED_CHAR),
+    }
+}
+
+/// An error when parsing a [`HexBinary`] or a [`Base64Binary`].
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub struct ParseBinaryError(#[from] BinaryParseErrorKind);
+
+#[derive(Debug, Clone, thiserror::Error)]
+enum BinaryParseErrorKind {
+    #[error("Unexpected character")]
+    UnexpectedChar,
+    #[error("Unexpected end of string")]
+    UnexpectedEnd,
+}
+
+const PARSE_UNEXPECTED_CHAR: ParseBinaryError =
+    ParseBinaryError(BinaryParseErrorKind::UnexpectedChar);
+const PARSE_UNEXPECTED_END: ParseBinaryError =
+    ParseBinaryError(BinaryParseErrorKind::UnexpectedEnd);
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_from_str() -> Result<(), ParseBinaryError> {
+        assert_eq!(HexBinary::from_str("")?.as_bytes(), b"");
+        assert_eq!(HexBinary::from_str("0fB7")?.as_bytes(), [0x0F, 0xB7]);
+        assert_eq!(HexBinary::from_str("0fb7")?.to_string(), "0FB7");
+        HexBinary::from_str("0fb").unwrap_err();
+        HexBinary::from_str("0g").unwrap_err();
+        HexBinary::from_str("0f b7").unwrap_err();
+        Ok(())
+    }
+
+    #[test]
+    fn base64_from_str() -> Result<(), ParseBinaryError> {
+        assert_eq!(Base64Binary::from_str("")?.as_bytes(), b"");
+        assert_eq!(Base64Binary::from_str("Zg==")?.as_bytes(), b"f");
+        assert_eq!(Base64Binary::from_str("Zm8=")?.as_bytes(), b"fo");
+        assert_eq!(Base64Binary::from_str("Zm9v")?.as_bytes(), b"foo");
+        assert_eq!(Base64Binary::from_str("Zm9v YmFy")?.as_bytes(), b"foobar");
+        assert_eq!(
+            Base64Binary::from_str("Zm9v YmE =")?.to_string(),
+            "Zm9vYmE="
+        );
+        Base64Binary::from_str("Zm9").unwrap_err();
+        Base64Binary::from_str("Zh==").unwrap_err();
+        Base64Binary::from_str("Zg==Zg==").unwrap_err();
+        Base64Binary::from_str("Z===").unwrap_err();
+        Base64Binary::from_str("Zm9-").unwrap_err();
+        Ok(())
+    }
+
+    #[test]
+    fn to_string() {
+        assert_eq!(Base64Binary::from(b"".as_slice()).to_string(), "");
+        assert_eq!(Base64Binary::from(b"f".as_slice()).to_string(), "Zg==");
+        assert_eq!(Base64Binary::from(b"fo".as_slice()).to_string(), "Zm8=");
+        assert_eq!(Base64Binary::from(b"foo".as_slice()).to_string(), "Zm9v");
+        assert_eq!(
+            Base64Binary::from(b"foob".as_slice()).to_string(),
+            "Zm9vYg=="
+        );
+        assert_eq!(HexBinary::from(b"\x00\xff".as_slice()).to_string(), "00FF");
+    }
+
+    #[test]
+    fn cmp() -> Result<(), ParseBinaryError> {
+        assert!(HexBinary::from_str("00")? < HexBinary::from_str("01")?);
+        assert!(HexBinary::from_str("01")? < HexBinary::from_str("0100")?);
+        assert!(HexBinary::from_str("0100")? < HexBinary::from_str("02")?);
+        assert_eq!(HexBinary::from_str("ff")?, HexBinary::from_str("FF")?);
+        assert!(Base64Binary::from_str("AA==")? < Base64Binary::from_str("AQ==")?);
+        Ok(())
+    }
+
+    #[test]
+    fn conversions() -> Result<(), ParseBinaryError> {
+        assert_eq!(
+            Base64Binary::from(HexBinary::from_str("666F6F")?).to_string(),
+            "Zm9v"
+        );
+        assert_eq!(
+            HexBinary::from(Base64Binary::from_str("Zm9v")?).to_string(),
+            "666F6F"
+        );
+        Ok(())
+    }
+}
@@ -8,12 +8,15 @@ use crate::toolkit::TokioAsyncReaderIterator;
 use crate::toolkit::{Parser, ReaderIterator, SliceIterator, TurtleParseError, TurtleSyntaxError};
 use oxiri::{Iri, IriParseError};
 use oxrdf::vocab::{rdf, xsd};
+#[cfg(feature = "rdf-star")]
+use oxrdf::Triple;
 use oxrdf::{
-    GraphName, GraphNameRef, LiteralRef, NamedNode, NamedNodeRef, Quad, QuadRef, Subject, TermRef,
+    BlankNode, GraphName, GraphNameRef, LiteralRef, NamedNode, NamedNodeRef, Quad, QuadRef,
+    Subject, Term, TermRef,
 };
 use std::borrow::Cow;
 use std::collections::hash_map::Iter;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
 use std::io::{self, Read, Write};
 #[cfg(feature = "async-tokio")]
@@ -757,6 +760,7 @@ impl<'a> Iterator for TriGPrefixesIter<'a> {
 pub struct TriGSerializer {
     base_iri: Option<Iri<String>>,
     prefixes: BTreeMap<String, String>,
+    pretty: bool,
 }
 
 impl TriGSerializer {
@@ -766,6 +770,7 @@ impl TriGSerializer {
         Self {
             base_iri: None,
             prefixes: BTreeMap::new(),
+            pretty: false,
         }
     }
 
@@ -811,6 +816,61 @@ impl TriGSerializer {
         Ok(self)
     }
 
+    /// Writes the file in a more human-friendly way.
+    ///
+    /// The triples are grouped by subject and predicate using `;` and `,`, the predicates are sorted with `rdf:type` first,
+    /// the blank nodes used only once as object are written inline using `[ ... ]` and the RDF lists using `( ... )`.
+    ///
+    /// <div class="warning">All the quads are kept in memory and only written when the serializer is finished.</div>
+    ///
+    /// ```
+    /// use oxrdf::vocab::rdf;
+    /// use oxrdf::{BlankNode, LiteralRef, NamedNodeRef, QuadRef};
+    /// use oxttl::TriGSerializer;
+    ///
+    /// let me = NamedNodeRef::new("http://example.com#me")?;
+    /// let graph = NamedNodeRef::new("http://example.com")?;
+    /// let address = BlankNode::default();
+    /// let mut serializer = TriGSerializer::new()
+    ///     .with_prefix("schema", "http://schema.org/")?
+    ///     .pretty()
+    ///     .for_writer(Vec::new());
+    /// serializer.serialize_quad(QuadRef::new(
+    ///     me,
+    ///     NamedNodeRef::new("http://schema.org/name")?,
+    ///     LiteralRef::new_simple_literal("Foo"),
+    ///     graph,
+    /// ))?;
+    /// serializer.serialize_quad(QuadRef::new(
+    ///     address.as_ref(),
+    ///     NamedNodeRef::new("http://schema.org/addressLocality")?,
+    ///     LiteralRef::new_simple_literal("Paris"),
+    ///     graph,
+    /// ))?;
+    /// serializer.serialize_quad(QuadRef::new(
+    ///     me,
+    ///     NamedNodeRef::new("http://schema.org/address")?,
+    ///     address.as_ref(),
+    ///     graph,
+    /// ))?;
+    /// serializer.serialize_quad(QuadRef::new(
+    ///     me,
+    ///     rdf::TYPE,
+    ///     NamedNodeRef::new("http://schema.org/Person")?,
+    ///     graph,
+    /// ))?;
+    /// assert_eq!(
+    ///     "@prefix schema: <http://schema.org/> .\n<http://example.com> {\n\t<http://example.com#me> a schema:Person ;\n\t\tschema:address [\n\t\t\tschema:addressLocality \"Paris\"\n\t\t] ;\n\t\tschema:name \"Foo\" .\n}\n",
+    ///     String::from_utf8(serializer.finish()?)?
+    /// );
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    pub fn pretty(mut self) -> Self {
+        self.pretty = true;
+        self
+    }
+
     /// Writes a TriG file to a [`Write`] implementation.
     ///
     /// ```
@@ -913,6 +973,7 @@ impl TriGSerializer {
             prelude_written: false,
             current_graph_name: GraphName::DefaultGraph,
             current_subject_predicate: None,
+            pretty_quads: self.pretty.then(Vec::new),
         }
     }
 }
@@ -1051,6 +1112,7 @@ pub struct LowLevelTriGSerializer {
     prelude_written: bool,
     current_graph_name: GraphName,
     current_subject_predicate: Option<(Subject, NamedNode)>,
+    pretty_quads: Option<Vec<Quad>>,
 }
 
 impl LowLevelTriGSerializer {
@@ -1060,20 +1122,12 @@ impl LowLevelTriGSerializer {
         q: impl Into<QuadRef<'a>>,
         mut writer: impl Write,
     ) -> io::Result<()> {
-        if !self.prelude_written {
-            self.prelude_written = true;
-            if let Some(base_iri) = &self.base_iri {
-                writeln!(writer, "@base <{base_iri}> .")?;
-            }
-            for (prefix_iri, prefix_name) in &self.prefixes {
-                writeln!(
-                    writer,
-                    "@prefix {prefix_name}: <{}> .",
-                    relative_iri(prefix_iri, &self.base_iri)
-                )?;
-            }
-        }
         let q = q.into();
+        if let Some(pretty_quads) = &mut self.pretty_quads {
+            pretty_quads.push(q.into_owned());
+            return Ok(());
+        }
+        self.write_prelude(&mut writer)?;
         if q.graph_name == self.current_graph_name.as_ref() {
             if let Some((current_subject, current_predicate)) =
                 self.current_subject_predicate.take()
@@ -1173,8 +1227,28 @@ impl LowLevelTriGSerializer {
         }
     }
 
+    fn write_prelude(&mut self, mut writer: impl Write) -> io::Result<()> {
+        if !self.prelude_written {
+            self.prelude_written = true;
+            if let Some(base_iri) = &self.base_iri {
+                writeln!(writer, "@base <{base_iri}> .")?;
+            }
+            for (prefix_iri, prefix_name) in &self.prefixes {
+                writeln!(
+                    writer,
+                    "@prefix {prefix_name}: <{}> .",
+                    relative_iri(prefix_iri, &self.base_iri)
+                )?;
+            }
+        }
+        Ok(())
+    }
+
     /// Finishes to write the file.
     pub fn finish(&mut self, mut writer: impl Write) -> io::Result<()> {
+        if let Some(quads) = self.pretty_quads.take() {
+            return self.write_pretty(quads, writer);
+        }
         if self.current_subject_predicate.is_some() {
             writeln!(writer, " .")?;
         }
@@ -1183,6 +1257,309 @@ impl LowLevelTriGSerializer {
         }
         Ok(())
     }
+
+    fn write_pretty(&mut self, quads: Vec<Quad>, mut writer: impl Write) -> io::Result<()> {
+        if quads.is_empty() {
+            return Ok(());
+        }
+        self.write_prelude(&mut writer)?;
+        let dataset = PrettyDataset::new(quads);
+        let mut inlined = HashSet::new();
+        for (graph_name, graph) in &dataset.graphs {
+            let indent = match graph_name.as_ref() {
+                GraphNameRef::NamedNode(g) => {
+                    writeln!(writer, "{} {{", self.term(g))?;
+                    1
+                }
+                GraphNameRef::BlankNode(g) => {
+                    writeln!(writer, "{} {{", self.term(g))?;
+                    1
+                }
+                GraphNameRef::DefaultGraph => 0,
+            };
+            for subject in &graph.subjects {
+                if !dataset.is_inlinable(subject) {
+                    self.write_pretty_subject(
+                        subject,
+                        graph,
+                        &dataset,
+                        &mut inlined,
+                        indent,
+                        &mut writer,
+                    )?;
+                }
+            }
+            // The blank nodes only reachable from themselves
+            for subject in &graph.subjects {
+                if let Subject::BlankNode(node) = subject {
+                    if dataset.is_inlinable(subject) && inlined.insert(node.clone()) {
+                        self.write_pretty_subject(
+                            subject,
+                            graph,
+                            &dataset,
+                            &mut inlined,
+                            indent,
+                            &mut writer,
+                        )?;
+                    }
+                }
+            }
+            if !graph_name.is_default_graph() {
+                writeln!(writer, "}}")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_pretty_subject(
+        &self,
+        subject: &Subject,
+        graph: &PrettyGraph,
+        dataset: &PrettyDataset,
+        inlined: &mut HashSet<BlankNode>,
+        indent: usize,
+        writer: &mut impl Write,
+    ) -> io::Result<()> {
+        write_indent(indent, writer)?;
+        write!(writer, "{} ", self.term(subject.as_ref()))?;
+        self.write_pretty_description(
+            &graph.descriptions[subject],
+            graph,
+            dataset,
+            inlined,
+            indent + 1,
+            writer,
+        )?;
+        writeln!(writer, " .")
+    }
+
+    fn write_pretty_description(
+        &self,
+        description: &[(NamedNode, Vec<Term>)],
+        graph: &PrettyGraph,
+        dataset: &PrettyDataset,
+        inlined: &mut HashSet<BlankNode>,
+        indent: usize,
+        writer: &mut impl Write,
+    ) -> io::Result<()> {
+        for (i, (predicate, objects)) in description.iter().enumerate() {
+            if i > 0 {
+                writeln!(writer, " ;")?;
+                write_indent(indent, writer)?;
+            }
+            write!(writer, "{}", self.predicate(predicate))?;
+            for (j, object) in objects.iter().enumerate() {
+                write!(writer, "{}", if j > 0 { " , " } else { " " })?;
+                self.write_pretty_object(object, graph, dataset, inlined, indent, writer)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_pretty_object(
+        &self,
+        object: &Term,
+        graph: &PrettyGraph,
+        dataset: &PrettyDataset,
+        inlined: &mut HashSet<BlankNode>,
+        indent: usize,
+        writer: &mut impl Write,
+    ) -> io::Result<()> {
+        let Term::BlankNode(node) = object else {
+            return write!(writer, "{}", self.term(object));
+        };
+        if !dataset.inlinable.contains(node) || inlined.contains(node) {
+            return write!(writer, "{}", self.term(node));
+        }
+        if let Some((items, nodes)) = graph.list(node, dataset, inlined) {
+            inlined.extend(nodes);
+            write!(writer, "(")?;
+            for item in items {
+                write!(writer, " ")?;
+                self.write_pretty_object(item, graph, dataset, inlined, indent, writer)?;
+            }
+            return write!(writer, " )");
+        }
+        inlined.insert(node.clone());
+        let Some(description) = graph.descriptions.get(&Subject::from(node.clone())) else {
+            return write!(writer, "[]");
+        };
+        writeln!(writer, "[")?;
+        write_indent(indent + 1, writer)?;
+        self.write_pretty_description(description, graph, dataset, inlined, indent + 1, writer)?;
+        writeln!(writer)?;
+        write_indent(indent, writer)?;
+        write!(writer, "]")
+    }
+}
+
+/// The quads to write in pretty mode
+struct PrettyDataset {
+    graphs: Vec<(GraphName, PrettyGraph)>,
+    /// The blank nodes used exactly once as object, not used as graph name or inside of quoted triples and only used as subject in the same graph.
+    inlinable: HashSet<BlankNode>,
+}
+
+#[derive(Default)]
+struct PrettyGraph {
+    /// The subjects in the order of their first appearance
+    subjects: Vec<Subject>,
+    /// The predicates (sorted) and objects of each subject
+    descriptions: HashMap<Subject, Vec<(NamedNode, Vec<Term>)>>,
+}
+
+impl PrettyDataset {
+    fn new(quads: Vec<Quad>) -> Self {
+        let mut object_graphs = HashMap::<BlankNode, Option<GraphName>>::new();
+        let mut pinned = HashSet::new();
+        for quad in &quads {
+            if let Term::BlankNode(node) = &quad.object {
+                object_graphs
+                    .entry(node.clone())
+                    .and_modify(|g| *g = None)
+                    .or_insert_with(|| Some(quad.graph_name.clone()));
+            }
+            if let GraphName::BlankNode(node) = &quad.graph_name {
+                pinned.insert(node.clone());
+            }
+            #[cfg(feature = "rdf-star")]
+            {
+                if let Subject::Triple(triple) = &quad.subject {
+                    pin_blank_nodes(triple, &mut pinned);
+                }
+                if let Term::Triple(triple) = &quad.object {
+                    pin_blank_nodes(triple, &mut pinned);
+                }
+            }
+        }
+        for quad in &quads {
+            if let Subject::BlankNode(node) = &quad.subject {
+                if let Some(graph_name) = object_graphs.get_mut(node) {
+                    if graph_name.as_ref() != Some(&quad.graph_name) {
+                        *graph_name = None;
+                    }
+                }
+            }
+        }
+        let inlinable = object_graphs
+            .into_iter()
+            .filter_map(|(node, graph_name)| {
+                (graph_name.is_some() && !pinned.contains(&node)).then_some(node)
+            })
+            .collect();
+
+        let mut graphs = Vec::<(GraphName, PrettyGraph)>::new();
+        let mut graph_ids = HashMap::new();
+        for quad in quads {
+            let graph_id = *graph_ids.entry(quad.graph_name.clone()).or_insert_with(|| {
+                graphs.push((quad.graph_name.clone(), PrettyGraph::default()));
+                graphs.len() - 1
+            });
+            let graph = &mut graphs[graph_id].1;
+            let description = graph
+                .descriptions
+                .entry(quad.subject.clone())
+                .or_insert_with(|| {
+                    graph.subjects.push(quad.subject);
+                    Vec::new()
+                });
+            if let Some((_, objects)) = description
+                .iter_mut()
+                .find(|(predicate, _)| *predicate == quad.predicate)
+            {
+                objects.push(quad.object);
+            } else {
+                description.push((quad.predicate, vec![quad.object]));
+            }
+        }
+        for (_, graph) in &mut graphs {
+            for description in graph.descriptions.values_mut() {
+                description.sort_by(|(p1, _), (p2, _)| {
+                    (*p2 == rdf::TYPE)
+                        .cmp(&(*p1 == rdf::TYPE))
+                        .then_with(|| p1.as_str().cmp(p2.as_str()))
+                });
+            }
+        }
+        Self { graphs, inlinable }
+    }
+
+    fn is_inlinable(&self, subject: &Subject) -> bool {
+        if let Subject::BlankNode(node) = subject {
+            self.inlinable.contains(node)
+        } else {
+            false
+        }
+    }
+}
+
+impl PrettyGraph {
+    /// Returns the items of the RDF list starting with the given node and the nodes of the list
+    fn list(
+        &self,
+        head: &BlankNode,
+        dataset: &PrettyDataset,
+        inlined: &HashSet<BlankNode>,
+    ) -> Option<(Vec<&Term>, Vec<BlankNode>)> {
+        let mut items = Vec::new();
+        let mut nodes = Vec::new();
+        let mut visited = HashSet::new();
+        let mut current = head;
+        loop {
+            let [(first_predicate, first), (rest_predicate, rest)] = self
+                .descriptions
+                .get(&Subject::from(current.clone()))?
+                .as_slice()
+            else {
+                return None;
+            };
+            let ([first], [rest]) = (first.as_slice(), rest.as_slice()) else {
+                return None;
+            };
+            if *first_predicate != rdf::FIRST || *rest_predicate != rdf::REST {
+                return None;
+            }
+            items.push(first);
+            nodes.push(current.clone());
+            visited.insert(current);
+            match rest {
+                Term::NamedNode(node) if *node == rdf::NIL => return Some((items, nodes)),
+                Term::BlankNode(node)
+                    if dataset.inlinable.contains(node)
+                        && !inlined.contains(node)
+                        && !visited.contains(node) =>
+                {
+                    current = node;
+                }
+                _ => return None,
+            }
+        }
+    }
+}
+
+#[cfg(feature = "rdf-star")]
+fn pin_blank_nodes(triple: &Triple, pinned: &mut HashSet<BlankNode>) {
+    match &triple.subject {
+        Subject::BlankNode(node) => {
+            pinned.insert(node.clone());
+        }
+        Subject::Triple(triple) => pin_blank_nodes(triple, pinned),
+        Subject::NamedNode(_) => (),
+    }
+    match &triple.object {
+        Term::BlankNode(node) => {
+            pinned.insert(node.clone());
+        }
+        Term::Triple(triple) => pin_blank_nodes(triple, pinned),
+        Term::NamedNode(_) | Term::Literal(_) => (),
+    }
+}
+
+fn write_indent(indent: usize, writer: &mut impl Write) -> io::Result<()> {
+    for _ in 0..indent {
+        write!(writer, "\t")?;
+    }
+    Ok(())
 }
 
 struct TurtlePredicate<'a> {
@@ -1479,4 +1856,51 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn test_write_pretty() -> Result<(), Box<dyn std::error::Error>> {
+        let ex = |name: &str| NamedNode::new_unchecked(format!("http://example.com/{name}"));
+        let b = BlankNode::new_unchecked;
+        let quads = [
+            Quad::new(ex("s"), ex("p"), b("l1"), GraphName::DefaultGraph),
+            Quad::new(
+                b("l1"),
+                rdf::FIRST,
+                LiteralRef::new_simple_literal("a"),
+                GraphName::DefaultGraph,
+            ),
+            Quad::new(b("l1"), rdf::REST, b("l2"), GraphName::DefaultGraph),
+            Quad::new(b("l2"), rdf::FIRST, b("inner"), GraphName::DefaultGraph),
+            Quad::new(b("l2"), rdf::REST, rdf::NIL, GraphName::DefaultGraph),
+            Quad::new(
+                b("inner"),
+                ex("q"),
+                LiteralRef::new_simple_literal("x"),
+                GraphName::DefaultGraph,
+            ),
+            Quad::new(ex("s"), rdf::TYPE, ex("C"), GraphName::DefaultGraph),
+            Quad::new(ex("s"), ex("p"), b("shared"), GraphName::DefaultGraph),
+            Quad::new(ex("t"), ex("p"), b("shared"), GraphName::DefaultGraph),
+            Quad::new(b("c1"), ex("p"), b("c2"), GraphName::DefaultGraph),
+            Quad::new(b("c2"), ex("p"), b("c1"), GraphName::DefaultGraph),
+            Quad::new(
+                ex("s"),
+                ex("p"),
+                LiteralRef::new_simple_literal("y"),
+                ex("g"),
+            ),
+        ];
+        let mut serializer = TriGSerializer::new()
+            .with_prefix("ex", "http://example.com/")?
+            .pretty()
+            .for_writer(Vec::new());
+        for quad in &quads {
+            serializer.serialize_quad(quad)?;
+        }
+        assert_eq!(
+            String::from_utf8(serializer.finish()?)?,
+            "@prefix ex: <http://example.com/> .\nex:s a ex:C ;\n\tex:p ( \"a\" [\n\t\tex:q \"x\"\n\t] ) , _:shared .\nex:t ex:p _:shared .\n_:c1 ex:p [\n\t\tex:p _:c1\n\t] .\nex:g {\n\tex:s ex:p \"y\" .\n}\n"
+        );
+        Ok(())
+    }
 }
@@ -863,6 +863,45 @@ impl TurtleSerializer {
         Ok(self)
     }
 
+    /// Writes the file in a more human-friendly way.
+    ///
+    /// The triples are grouped by subject and predicate using `;` and `,`, the predicates are sorted with `rdf:type` first,
+    /// the blank nodes used only once as object are written inline using `[ ... ]` and the RDF lists using `( ... )`.
+    ///
+    /// <div class="warning">All the triples are kept in memory and only written when the serializer is finished.</div>
+    ///
+    /// ```
+    /// use oxrdf::vocab::rdf;
+    /// use oxrdf::{BlankNode, LiteralRef, NamedNodeRef, TripleRef};
+    /// use oxttl::TurtleSerializer;
+    ///
+    /// let s = NamedNodeRef::new("http://example.com/s")?;
+    /// let p = NamedNodeRef::new("http://example.com/p")?;
+    /// let list = BlankNode::default();
+    /// let rest = BlankNode::default();
+    /// let mut serializer = TurtleSerializer::new()
+    ///     .with_prefix("ex", "http://example.com/")?
+    ///     .pretty()
+    ///     .for_writer(Vec::new());
+    /// serializer.serialize_triple(TripleRef::new(s, p, NamedNodeRef::new("http://example.com/o")?))?;
+    /// serializer.serialize_triple(TripleRef::new(&list, rdf::FIRST, LiteralRef::new_simple_literal("a")))?;
+    /// serializer.serialize_triple(TripleRef::new(&list, rdf::REST, &rest))?;
+    /// serializer.serialize_triple(TripleRef::new(&rest, rdf::FIRST, LiteralRef::new_simple_literal("b")))?;
+    /// serializer.serialize_triple(TripleRef::new(&rest, rdf::REST, rdf::NIL))?;
+    /// serializer.serialize_triple(TripleRef::new(s, p, &list))?;
+    /// serializer.serialize_triple(TripleRef::new(s, rdf::TYPE, NamedNodeRef::new("http://example.com/C")?))?;
+    /// assert_eq!(
+    ///     b"@prefix ex: <http://example.com/> .\nex:s a ex:C ;\n\tex:p ex:o , ( \"a\" \"b\" ) .\n",
+    ///     serializer.finish()?.as_slice()
+    /// );
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    pub fn pretty(mut self) -> Self {
+        self.inner = self.inner.pretty();
+        self
+    }
+
     /// Writes a Turtle file to a [`Write`] implementation.
     ///
     /// ```
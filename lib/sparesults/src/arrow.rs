@@ -0,0 +1,441 @@
+//! Conversion of query solutions into [Apache Arrow](https://arrow.apache.org/) record batches.
+
+use crate::solution::QuerySolution;
+use arrow_array::builder::{
+    BooleanBuilder, Date32Builder, Float64Builder, Int64Builder, StringBuilder,
+    TimestampMicrosecondBuilder,
+};
+use arrow_array::{ArrayRef, RecordBatch, RecordBatchOptions};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef, TimeUnit};
+use oxrdf::vocab::xsd;
+use oxrdf::{NamedNodeRef, Term, Variable};
+use oxsdatatypes::{Boolean, Date, DateTime, Decimal, Double, Integer};
+use std::str::FromStr;
+use std::sync::Arc;
+
+const INTEGER_DATATYPES: [NamedNodeRef<'static>; 13] = [
+    xsd::INTEGER,
+    xsd::BYTE,
+    xsd::INT,
+    xsd::LONG,
+    xsd::NEGATIVE_INTEGER,
+    xsd::NON_NEGATIVE_INTEGER,
+    xsd::NON_POSITIVE_INTEGER,
+    xsd::POSITIVE_INTEGER,
+    xsd::SHORT,
+    xsd::UNSIGNED_BYTE,
+    xsd::UNSIGNED_INT,
+    xsd::UNSIGNED_LONG,
+    xsd::UNSIGNED_SHORT,
+];
+
+/// Converts [`QuerySolution`]s into [Apache Arrow](https://arrow.apache.org/) [`RecordBatch`]es.
+///
+/// Each variable is converted to a nullable column, unbound values being nulls.
+/// The column types are inferred from the values of the first converted batch:
+/// * IRIs: [`DataType::Utf8`] with the IRIs.
+/// * `xsd:string` literals: [`DataType::Utf8`] with the literal values.
+/// * `xsd:integer` and its derived datatypes: [`DataType::Int64`].
+/// * `xsd:decimal`, `xsd:float` and `xsd:double` (and integers mixed with them): [`DataType::Float64`].
+/// * `xsd:boolean`: [`DataType::Boolean`].
+/// * `xsd:dateTime`: [`DataType::Timestamp`] in microseconds, in UTC if all values have a timezone, without timezone if none has one.
+/// * `xsd:date`: [`DataType::Date32`], the timezone is ignored.
+/// * Other values, invalid literals and mixes of these kinds of values: [`DataType::Utf8`] with the N-Triples serialization of the terms.
+///
+/// The values of the next batches must fit into the inferred types, an error is returned otherwise.
+///
+/// ```
+/// use arrow_array::cast::AsArray;
+/// use arrow_array::types::Int64Type;
+/// use oxrdf::{Literal, NamedNode, Variable};
+/// use sparesults::{ArrowSolutionsConverter, QuerySolution};
+///
+/// let variables = vec![Variable::new("s")?, Variable::new("count")?];
+/// let solutions = [QuerySolution::from((
+///     variables.clone(),
+///     vec![
+///         Some(NamedNode::new("http://example.com/s")?.into()),
+///         Some(Literal::from(12).into()),
+///     ],
+/// ))];
+/// let mut converter = ArrowSolutionsConverter::new(variables);
+/// let batch = converter.convert(&solutions)?;
+/// assert_eq!(batch.num_rows(), 1);
+/// assert_eq!(batch.column(0).as_string::<i32>().value(0), "http://example.com/s");
+/// assert_eq!(batch.column(1).as_primitive::<Int64Type>().value(0), 12);
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+pub struct ArrowSolutionsConverter {
+    variables: Arc<[Variable]>,
+    columns: Option<(SchemaRef, Vec<ColumnKind>)>,
+}
+
+impl ArrowSolutionsConverter {
+    /// Builds a converter for solutions of the given variables.
+    pub fn new(variables: impl Into<Arc<[Variable]>>) -> Self {
+        Self {
+            variables: variables.into(),
+            columns: None,
+        }
+    }
+
+    /// The schema of the record batches or `None` if no batch has been converted yet.
+    pub fn schema(&self) -> Option<SchemaRef> {
+        Some(Arc::clone(&self.columns.as_ref()?.0))
+    }
+
+    /// Converts solutions into a record batch.
+    ///
+    /// The first call infers the column types from the given solutions.
+    pub fn convert(&mut self, solutions: &[QuerySolution]) -> Result<RecordBatch, ArrowError> {
+        let (schema, kinds) = self.columns.get_or_insert_with(|| {
+            let kinds = self
+                .variables
+                .iter()
+                .map(|variable| {
+                    solutions
+                        .iter()
+                        .filter_map(|solution| solution.get(variable))
+                        .map(value_kind)
+                        .reduce(ColumnKind::merge)
+                        .unwrap_or(ColumnKind::Term)
+                })
+                .collect::<Vec<_>>();
+            let schema = Schema::new(
+                self.variables
+                    .iter()
+                    .zip(&kinds)
+                    .map(|(variable, kind)| Field::new(variable.as_str(), kind.data_type(), true))
+                    .collect::<Vec<_>>(),
+            );
+            (Arc::new(schema), kinds)
+        });
+        let columns = self
+            .variables
+            .iter()
+            .zip(kinds.iter())
+            .map(|(variable, kind)| {
+                build_column(
+                    *kind,
+                    variable,
+                    solutions.iter().map(|solution| solution.get(variable)),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        RecordBatch::try_new_with_options(
+            Arc::clone(schema),
+            columns,
+            &RecordBatchOptions::new().with_row_count(Some(solutions.len())),
+        )
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ColumnKind {
+    Iri,
+    String,
+    Integer,
+    Double,
+    Boolean,
+    DateTime,
+    LocalDateTime,
+    Date,
+    Term,
+}
+
+impl ColumnKind {
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Self::Integer, Self::Double) | (Self::Double, Self::Integer) => Self::Double,
+            _ => Self::Term,
+        }
+    }
+
+    fn data_type(self) -> DataType {
+        match self {
+            Self::Iri | Self::String | Self::Term => DataType::Utf8,
+            Self::Integer => DataType::Int64,
+            Self::Double => DataType::Float64,
+            Self::Boolean => DataType::Boolean,
+            Self::DateTime => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            Self::LocalDateTime => DataType::Timestamp(TimeUnit::Microsecond, None),
+            Self::Date => DataType::Date32,
+        }
+    }
+}
+
+fn value_kind(term: &Term) -> ColumnKind {
+    let Term::Literal(literal) = term else {
+        return if term.is_named_node() {
+            ColumnKind::Iri
+        } else {
+            ColumnKind::Term
+        };
+    };
+    let (value, datatype) = (literal.value(), literal.datatype());
+    if datatype == xsd::STRING {
+        ColumnKind::String
+    } else if INTEGER_DATATYPES.contains(&datatype) {
+        if Integer::from_str(value).is_ok() {
+            ColumnKind::Integer
+        } else {
+            ColumnKind::Term
+        }
+    } else if datatype == xsd::DECIMAL || datatype == xsd::DOUBLE || datatype == xsd::FLOAT {
+        if Double::from_str(value).is_ok() {
+            ColumnKind::Double
+        } else {
+            ColumnKind::Term
+        }
+    } else if datatype == xsd::BOOLEAN {
+        if Boolean::from_str(value).is_ok() {
+            ColumnKind::Boolean
+        } else {
+            ColumnKind::Term
+        }
+    } else if datatype == xsd::DATE_TIME || datatype == xsd::DATE_TIME_STAMP {
+        match DateTime::from_str(value) {
+            Ok(value) if value.timezone_offset().is_some() => ColumnKind::DateTime,
+            Ok(_) => ColumnKind::LocalDateTime,
+            Err(_) => ColumnKind::Term,
+        }
+    } else if datatype == xsd::DATE {
+        if Date::from_str(value).is_ok() {
+            ColumnKind::Date
+        } else {
+            ColumnKind::Term
+        }
+    } else {
+        ColumnKind::Term
+    }
+}
+
+fn build_column<'a>(
+    kind: ColumnKind,
+    variable: &Variable,
+    values: impl ExactSizeIterator<Item = Option<&'a Term>>,
+) -> Result<ArrayRef, ArrowError> {
+    let len = values.len();
+    // The value if it fits into the column type
+    let fitting = move |term: Option<&'a Term>| -> Result<Option<&'a str>, ArrowError> {
+        let Some(term) = term else {
+            return Ok(None);
+        };
+        let value_kind = value_kind(term);
+        if value_kind == kind || (kind == ColumnKind::Double && value_kind == ColumnKind::Integer)
+        {
+            Ok(Some(if let Term::Literal(literal) = term {
+                literal.value()
+            } else if let Term::NamedNode(node) = term {
+                node.as_str()
+            } else {
+                ""
+            }))
+        } else {
+            Err(ArrowError::InvalidArgumentError(format!(
+                "The value {term} of {variable} does not fit into the {} column inferred from the first solutions",
+                kind.data_type()
+            )))
+        }
+    };
+    Ok(match kind {
+        ColumnKind::Iri | ColumnKind::String => {
+            let mut builder = StringBuilder::with_capacity(len, 0);
+            for value in values {
+                builder.append_option(fitting(value)?);
+            }
+            Arc::new(builder.finish())
+        }
+        ColumnKind::Term => {
+            let mut builder = StringBuilder::with_capacity(len, 0);
+            for value in values {
+                builder.append_option(value.map(Term::to_string));
+            }
+            Arc::new(builder.finish())
+        }
+        ColumnKind::Integer => {
+            let mut builder = Int64Builder::with_capacity(len);
+            for value in values {
+                builder.append_option(
+                    fitting(value)?
+                        .map(|v| Integer::from_str(v).map(i64::from))
+                        .transpose()
+                        .map_err(|e| ArrowError::ParseError(e.to_string()))?,
+                );
+            }
+            Arc::new(builder.finish())
+        }
+        ColumnKind::Double => {
+            let mut builder = Float64Builder::with_capacity(len);
+            for value in values {
+                builder.append_option(
+                    fitting(value)?
+                        .map(|v| Double::from_str(v).map(f64::from))
+                        .transpose()
+                        .map_err(|e| ArrowError::ParseError(e.to_string()))?,
+                );
+            }
+            Arc::new(builder.finish())
+        }
+        ColumnKind::Boolean => {
+            let mut builder = BooleanBuilder::with_capacity(len);
+            for value in values {
+                builder.append_option(
+                    fitting(value)?
+                        .map(|v| Boolean::from_str(v).map(bool::from))
+                        .transpose()
+                        .map_err(|e| ArrowError::ParseError(e.to_string()))?,
+                );
+            }
+            Arc::new(builder.finish())
+        }
+        ColumnKind::DateTime | ColumnKind::LocalDateTime => {
+            let epoch = DateTime::from_str(if kind == ColumnKind::DateTime {
+                "1970-01-01T00:00:00Z"
+            } else {
+                "1970-01-01T00:00:00"
+            })
+            .map_err(|e| ArrowError::ParseError(e.to_string()))?;
+            let mut builder = TimestampMicrosecondBuilder::with_capacity(len);
+            for value in values {
+                builder.append_option(
+                    fitting(value)?
+                        .map(|v| {
+                            let value = DateTime::from_str(v)
+                                .map_err(|e| ArrowError::ParseError(e.to_string()))?;
+                            value
+                                .checked_sub(epoch)
+                                .and_then(|d| d.as_seconds().checked_mul(Decimal::from(1_000_000)))
+                                .and_then(|d| Integer::try_from(d).ok())
+                                .map(i64::from)
+                                .ok_or_else(|| {
+                                    ArrowError::ComputeError(format!(
+                                        "The date time {v} is out of the timestamp range"
+                                    ))
+                                })
+                        })
+                        .transpose()?,
+                );
+            }
+            Arc::new(if kind == ColumnKind::DateTime {
+                builder.finish().with_timezone("UTC")
+            } else {
+                builder.finish()
+            })
+        }
+        ColumnKind::Date => {
+            let mut builder = Date32Builder::with_capacity(len);
+            for value in values {
+                builder.append_option(
+                    fitting(value)?
+                        .map(|v| {
+                            let value = Date::from_str(v)
+                                .map_err(|e| ArrowError::ParseError(e.to_string()))?;
+                            days_since_epoch(value.year(), value.month(), value.day()).ok_or_else(
+                                || {
+                                    ArrowError::ComputeError(format!(
+                                        "The date {v} is out of the Date32 range"
+                                    ))
+                                },
+                            )
+                        })
+                        .transpose()?,
+                );
+            }
+            Arc::new(builder.finish())
+        }
+    })
+}
+
+/// Number of days between 1970-01-01 and the given proleptic Gregorian date
+///
+/// Uses the algorithm from <https://howardhinnant.github.io/date_algorithms.html#days_from_civil>.
+fn days_since_epoch(year: i64, month: u8, day: u8) -> Option<i32> {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = i64::from(month);
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5
+        + i64::from(day)
+        - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    i32::try_from(
+        era.checked_mul(146_097)?
+            .checked_add(day_of_era)?
+            .checked_sub(719_468)?,
+    )
+    .ok()
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Date32Type, Float64Type, TimestampMicrosecondType};
+    use arrow_array::Array;
+    use oxrdf::{BlankNode, Literal, NamedNode};
+    use std::error::Error;
+
+    #[test]
+    fn test_column_types() -> Result<(), Box<dyn Error>> {
+        let variables: Arc<[Variable]> = [
+            Variable::new("number")?,
+            Variable::new("date")?,
+            Variable::new("time")?,
+            Variable::new("mixed")?,
+        ]
+        .into();
+        let solutions = [
+            QuerySolution::from((
+                Arc::clone(&variables),
+                vec![
+                    Some(Literal::new_typed_literal("1", xsd::INTEGER).into()),
+                    Some(Literal::new_typed_literal("2000-03-01", xsd::DATE).into()),
+                    Some(Literal::new_typed_literal("1970-01-01T00:00:01+01:00", xsd::DATE_TIME).into()),
+                    Some(NamedNode::new("http://example.com")?.into()),
+                ],
+            )),
+            QuerySolution::from((
+                Arc::clone(&variables),
+                vec![
+                    Some(Literal::new_typed_literal("2.5", xsd::DECIMAL).into()),
+                    None,
+                    None,
+                    Some(BlankNode::new("b")?.into()),
+                ],
+            )),
+        ];
+        let mut converter = ArrowSolutionsConverter::new(Arc::clone(&variables));
+        let batch = converter.convert(&solutions)?;
+        let numbers = batch.column(0).as_primitive::<Float64Type>();
+        assert_eq!(numbers.values().to_vec(), vec![1., 2.5]);
+        let dates = batch.column(1).as_primitive::<Date32Type>();
+        assert_eq!(dates.value(0), 11_017);
+        assert!(dates.is_null(1));
+        let times = batch.column(2).as_primitive::<TimestampMicrosecondType>();
+        assert_eq!(times.value(0), -3_599_000_000);
+        assert_eq!(times.timezone(), Some("UTC"));
+        let mixed = batch.column(3).as_string::<i32>();
+        assert_eq!(mixed.value(0), "<http://example.com>");
+        assert_eq!(mixed.value(1), "_:b");
+
+        // The next batches must fit into the inferred types
+        converter
+            .convert(&[QuerySolution::from((
+                Arc::clone(&variables),
+                vec![Some(Literal::from("foo").into()), None, None, None],
+            ))])
+            .unwrap_err();
+        Ok(())
+    }
+
+    #[test]
+    fn test_days_since_epoch() {
+        assert_eq!(days_since_epoch(1970, 1, 1), Some(0));
+        assert_eq!(days_since_epoch(1969, 12, 31), Some(-1));
+        assert_eq!(days_since_epoch(2000, 3, 1), Some(11_017));
+        assert_eq!(days_since_epoch(i64::MAX / 2, 1, 1), None);
+    }
+}
@@ -152,11 +152,10 @@ fn write_csv_term<'a>(output: &mut String, term: impl Into<TermRef<'a>>) {
         TermRef::Literal(literal) => write_escaped_csv_string(output, literal.value()),
         #[cfg(feature = "rdf-star")]
         TermRef::Triple(triple) => {
-            write_csv_term(output, &triple.subject);
-            output.push(' ');
-            write_csv_term(output, &triple.predicate);
-            output.push(' ');
-            write_csv_term(output, &triple.object)
+            // There is no lossy serialization of triple terms, we use the TSV one
+            let mut value = String::new();
+            write_tsv_term(&mut value, triple);
+            write_escaped_csv_string(output, &value)
         }
     }
 }
@@ -689,6 +688,382 @@ impl TsvInnerSolutionsParser {
     }
 }
 
+/// The kinds of values that are converted into RDF terms other than plain strings when parsing CSV results.
+#[derive(Clone, Copy, Default)]
+pub struct CsvTyping {
+    /// Values written like Turtle numbers become `xsd:integer`, `xsd:decimal` or `xsd:double` literals
+    pub numbers: bool,
+    /// `true` and `false` become `xsd:boolean` literals
+    pub booleans: bool,
+    /// Absolute IRIs, IRIs between angle brackets and `_:` blank nodes become IRIs and blank nodes
+    pub nodes: bool,
+}
+
+pub enum ReaderCsvQueryResultsParserOutput<R: Read> {
+    Solutions {
+        variables: Vec<Variable>,
+        solutions: ReaderCsvSolutionsParser<R>,
+    },
+    Boolean(bool),
+}
+
+impl<R: Read> ReaderCsvQueryResultsParserOutput<R> {
+    pub fn read(mut reader: R, typing: CsvTyping) -> Result<Self, QueryResultsParseError> {
+        let mut line_reader = LineReader::new();
+        let mut buffer = Vec::new();
+        let line = line_reader.next_line_from_reader(&mut buffer, &mut reader)?;
+        Ok(
+            match inner_read_csv_first_line(line_reader, line, typing)? {
+                CsvInnerQueryResults::Solutions {
+                    variables,
+                    solutions,
+                } => Self::Solutions {
+                    variables,
+                    solutions: ReaderCsvSolutionsParser {
+                        reader,
+                        inner: solutions,
+                        buffer,
+                    },
+                },
+                CsvInnerQueryResults::Boolean(value) => Self::Boolean(value),
+            },
+        )
+    }
+}
+
+pub struct ReaderCsvSolutionsParser<R: Read> {
+    reader: R,
+    inner: CsvInnerSolutionsParser,
+    buffer: Vec<u8>,
+}
+
+impl<R: Read> ReaderCsvSolutionsParser<R> {
+    pub fn parse_next(&mut self) -> Result<Option<Vec<Option<Term>>>, QueryResultsParseError> {
+        self.inner.start_row();
+        loop {
+            let line = self
+                .inner
+                .line_reader
+                .next_line_from_reader(&mut self.buffer, &mut self.reader)?;
+            if self.inner.push_line(line) {
+                return Ok(self.inner.parse_row()?);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async-tokio")]
+pub enum TokioAsyncReaderCsvQueryResultsParserOutput<R: AsyncRead + Unpin> {
+    Solutions {
+        variables: Vec<Variable>,
+        solutions: TokioAsyncReaderCsvSolutionsParser<R>,
+    },
+    Boolean(bool),
+}
+
+#[cfg(feature = "async-tokio")]
+impl<R: AsyncRead + Unpin> TokioAsyncReaderCsvQueryResultsParserOutput<R> {
+    pub async fn read(mut reader: R, typing: CsvTyping) -> Result<Self, QueryResultsParseError> {
+        let mut line_reader = LineReader::new();
+        let mut buffer = Vec::new();
+        let line = line_reader
+            .next_line_from_tokio_async_read(&mut buffer, &mut reader)
+            .await?;
+        Ok(
+            match inner_read_csv_first_line(line_reader, line, typing)? {
+                CsvInnerQueryResults::Solutions {
+                    variables,
+                    solutions,
+                } => Self::Solutions {
+                    variables,
+                    solutions: TokioAsyncReaderCsvSolutionsParser {
+                        reader,
+                        inner: solutions,
+                        buffer,
+                    },
+                },
+                CsvInnerQueryResults::Boolean(value) => Self::Boolean(value),
+            },
+        )
+    }
+}
+
+#[cfg(feature = "async-tokio")]
+pub struct TokioAsyncReaderCsvSolutionsParser<R: AsyncRead + Unpin> {
+    reader: R,
+    inner: CsvInnerSolutionsParser,
+    buffer: Vec<u8>,
+}
+
+#[cfg(feature = "async-tokio")]
+impl<R: AsyncRead + Unpin> TokioAsyncReaderCsvSolutionsParser<R> {
+    pub async fn parse_next(
+        &mut self,
+    ) -> Result<Option<Vec<Option<Term>>>, QueryResultsParseError> {
+        self.inner.start_row();
+        loop {
+            let line = self
+                .inner
+                .line_reader
+                .next_line_from_tokio_async_read(&mut self.buffer, &mut self.reader)
+                .await?;
+            if self.inner.push_line(line) {
+                return Ok(self.inner.parse_row()?);
+            }
+        }
+    }
+}
+
+pub enum SliceCsvQueryResultsParserOutput<'a> {
+    Solutions {
+        variables: Vec<Variable>,
+        solutions: SliceCsvSolutionsParser<'a>,
+    },
+    Boolean(bool),
+}
+
+impl<'a> SliceCsvQueryResultsParserOutput<'a> {
+    pub fn read(slice: &'a [u8], typing: CsvTyping) -> Result<Self, QueryResultsSyntaxError> {
+        let mut reader = LineReader::new();
+        let line = reader.next_line_from_slice(slice)?;
+        Ok(match inner_read_csv_first_line(reader, line, typing)? {
+            CsvInnerQueryResults::Solutions {
+                variables,
+                solutions,
+            } => Self::Solutions {
+                variables,
+                solutions: SliceCsvSolutionsParser {
+                    slice,
+                    inner: solutions,
+                },
+            },
+            CsvInnerQueryResults::Boolean(value) => Self::Boolean(value),
+        })
+    }
+}
+
+pub struct SliceCsvSolutionsParser<'a> {
+    slice: &'a [u8],
+    inner: CsvInnerSolutionsParser,
+}
+
+impl SliceCsvSolutionsParser<'_> {
+    pub fn parse_next(&mut self) -> Result<Option<Vec<Option<Term>>>, QueryResultsSyntaxError> {
+        self.inner.start_row();
+        loop {
+            let line = self.inner.line_reader.next_line_from_slice(self.slice)?;
+            if self.inner.push_line(line) {
+                return self.inner.parse_row();
+            }
+        }
+    }
+}
+
+enum CsvInnerQueryResults {
+    Solutions {
+        variables: Vec<Variable>,
+        solutions: CsvInnerSolutionsParser,
+    },
+    Boolean(bool),
+}
+
+fn inner_read_csv_first_line(
+    reader: LineReader,
+    line: &str,
+    typing: CsvTyping,
+) -> Result<CsvInnerQueryResults, QueryResultsSyntaxError> {
+    let line = line.trim_matches(|c| matches!(c, ' ' | '\r' | '\n'));
+    if line.eq_ignore_ascii_case("true") {
+        return Ok(CsvInnerQueryResults::Boolean(true));
+    }
+    if line.eq_ignore_ascii_case("false") {
+        return Ok(CsvInnerQueryResults::Boolean(false));
+    }
+    let mut variables = Vec::new();
+    if !line.is_empty() {
+        for (v, _) in split_csv_row(line)? {
+            let v = v.trim();
+            if v.is_empty() {
+                return Err(QueryResultsSyntaxError::msg("Empty column on the first row. The first row should be a list of variable names like foo or bar"));
+            }
+            // Some implementations write the variables with their ? or $ prefix
+            let name = v.strip_prefix(['?', '$']).unwrap_or(v);
+            let variable = Variable::new(name).map_err(|e| {
+                QueryResultsSyntaxError::msg(format!("Invalid variable declaration '{v}': {e}"))
+            })?;
+            if variables.contains(&variable) {
+                return Err(QueryResultsSyntaxError::msg(format!(
+                    "The variable {variable} is declared twice"
+                )));
+            }
+            variables.push(variable);
+        }
+    }
+    let column_len = variables.len();
+    Ok(CsvInnerQueryResults::Solutions {
+        variables,
+        solutions: CsvInnerSolutionsParser {
+            line_reader: reader,
+            column_len,
+            typing,
+            row: String::new(),
+            row_start: TextPosition {
+                line: 0,
+                column: 0,
+                offset: 0,
+            },
+        },
+    })
+}
+
+struct CsvInnerSolutionsParser {
+    line_reader: LineReader,
+    column_len: usize,
+    typing: CsvTyping,
+    /// The current row, it might span multiple lines if it contains quoted line jumps
+    row: String,
+    row_start: TextPosition,
+}
+
+impl CsvInnerSolutionsParser {
+    fn start_row(&mut self) {
+        self.row.clear();
+        self.row_start = TextPosition {
+            line: self.line_reader.line_count,
+            column: 0,
+            offset: self.line_reader.last_line_end,
+        };
+    }
+
+    /// Adds a line to the current row and returns if the row is complete
+    fn push_line(&mut self, line: &str) -> bool {
+        self.row.push_str(line);
+        // The row is complete if all quoted values are closed
+        line.is_empty() || self.row.bytes().filter(|c| *c == b'"').count() % 2 == 0
+    }
+
+    #[allow(clippy::unwrap_in_result)]
+    fn parse_row(&self) -> Result<Option<Vec<Option<Term>>>, QueryResultsSyntaxError> {
+        if self.row.is_empty() {
+            return Ok(None); // EOF
+        }
+        let row = self.row.strip_suffix('\n').unwrap_or(&self.row);
+        let row = row.strip_suffix('\r').unwrap_or(row);
+        let location = self.row_start..TextPosition {
+            line: self.line_reader.line_count - 1,
+            column: row
+                .rsplit('\n')
+                .next()
+                .unwrap_or_default()
+                .chars()
+                .count()
+                .try_into()
+                .unwrap(),
+            offset: self.line_reader.last_line_end,
+        };
+        let fields = split_csv_row(row).map_err(|e| {
+            QueryResultsSyntaxError::located_message(e.to_string(), location.clone())
+        })?;
+        if fields.len() == self.column_len {
+            Ok(Some(
+                fields
+                    .into_iter()
+                    .map(|(value, quoted)| csv_value_to_term(value, quoted, self.typing))
+                    .collect(),
+            ))
+        } else if self.column_len == 0 && fields.len() == 1 && fields[0] == (String::new(), false) {
+            Ok(Some(Vec::new())) // Zero columns case
+        } else {
+            Err(QueryResultsSyntaxError::located_message(
+                format!(
+                    "This CSV files has {} columns but we found a row on line {} with {} columns: {}",
+                    self.column_len,
+                    self.row_start.line,
+                    fields.len(),
+                    row
+                ),
+                location,
+            ))
+        }
+    }
+}
+
+/// Splits a CSV row into its values, each value is returned with a boolean that is true if it was quoted
+fn split_csv_row(row: &str) -> Result<Vec<(String, bool)>, QueryResultsSyntaxError> {
+    let mut values = Vec::new();
+    let mut rest = row;
+    loop {
+        if let Some(mut quoted) = rest.strip_prefix('"') {
+            let mut value = String::new();
+            loop {
+                let Some(end) = quoted.find('"') else {
+                    return Err(QueryResultsSyntaxError::msg(
+                        "A quoted CSV value is not closed",
+                    ));
+                };
+                value.push_str(&quoted[..end]);
+                quoted = &quoted[end + 1..];
+                // "" is an escaped quote
+                if let Some(q) = quoted.strip_prefix('"') {
+                    value.push('"');
+                    quoted = q;
+                } else {
+                    break;
+                }
+            }
+            values.push((value, true));
+            rest = quoted;
+        } else {
+            let end = rest.find(',').unwrap_or(rest.len());
+            values.push((rest[..end].to_owned(), false));
+            rest = &rest[end..];
+        }
+        if rest.is_empty() {
+            return Ok(values);
+        }
+        rest = rest.strip_prefix(',').ok_or_else(|| {
+            QueryResultsSyntaxError::msg("A quoted CSV value must be followed by a comma")
+        })?;
+    }
+}
+
+fn csv_value_to_term(value: String, quoted: bool, typing: CsvTyping) -> Option<Term> {
+    if value.is_empty() {
+        // Unbound values are empty, "" is an empty string
+        return quoted.then(|| Literal::new_simple_literal("").into());
+    }
+    if typing.nodes {
+        if let Some(iri) = value.strip_prefix('<').and_then(|v| v.strip_suffix('>')) {
+            if let Ok(node) = NamedNode::new(iri) {
+                return Some(node.into());
+            }
+        }
+        if let Some(id) = value.strip_prefix("_:") {
+            if let Ok(node) = BlankNode::new(id) {
+                return Some(node.into());
+            }
+        }
+        if NamedNode::new(value.as_str()).is_ok() {
+            return Some(NamedNode::new_unchecked(value).into());
+        }
+    }
+    if typing.booleans && is_turtle_boolean(&value) {
+        return Some(Literal::new_typed_literal(value, xsd::BOOLEAN).into());
+    }
+    if typing.numbers {
+        if is_turtle_integer(&value) {
+            return Some(Literal::new_typed_literal(value, xsd::INTEGER).into());
+        }
+        if is_turtle_decimal(&value) {
+            return Some(Literal::new_typed_literal(value, xsd::DECIMAL).into());
+        }
+        if is_turtle_double(&value) {
+            return Some(Literal::new_typed_literal(value, xsd::DOUBLE).into());
+        }
+    }
+    Some(Literal::new_simple_literal(value).into())
+}
+
 struct LineReader {
     buffer_start: usize,
     buffer_end: usize,
@@ -740,7 +1115,8 @@ impl LineReader {
             self.buffer_end += read;
         };
         let result = str::from_utf8(&buffer[self.buffer_start..line_end]).map_err(|e| {
-            QueryResultsSyntaxError::msg(format!("Invalid UTF-8 in the TSV file: {e}")).into()
+            QueryResultsSyntaxError::msg(format!("Invalid UTF-8 in the CSV or TSV file: {e}"))
+                .into()
         });
         self.line_count += 1;
         self.last_line_start = self.last_line_end;
@@ -782,7 +1158,8 @@ impl LineReader {
             self.buffer_end += read;
         };
         let result = str::from_utf8(&buffer[self.buffer_start..line_end]).map_err(|e| {
-            QueryResultsSyntaxError::msg(format!("Invalid UTF-8 in the TSV file: {e}")).into()
+            QueryResultsSyntaxError::msg(format!("Invalid UTF-8 in the CSV or TSV file: {e}"))
+                .into()
         });
         self.line_count += 1;
         self.last_line_start = self.last_line_end;
@@ -799,7 +1176,7 @@ impl LineReader {
         let line_end = memchr(b'\n', &slice[self.buffer_start..])
             .map_or_else(|| slice.len(), |eol| self.buffer_start + eol + 1);
         let result = str::from_utf8(&slice[self.buffer_start..line_end]).map_err(|e| {
-            QueryResultsSyntaxError::msg(format!("Invalid UTF-8 in the TSV file: {e}"))
+            QueryResultsSyntaxError::msg(format!("Invalid UTF-8 in the CSV or TSV file: {e}"))
         });
         self.line_count += 1;
         self.last_line_start = self.last_line_end;
@@ -879,6 +1256,147 @@ mod tests {
         assert_eq!(buffer, "x,literal\r\nhttp://example/x,String\r\nhttp://example/x,\"String-with-dquote\"\"\"\r\n_:b0,Blank node\r\n,Missing 'x'\r\n,\r\nhttp://example/x,\r\n_:b1,String-with-lang\r\n_:b1,123\r\n,\"escape,\t\r\n\"\r\n");
     }
 
+    #[test]
+    fn test_csv_parsing() -> Result<(), Box<dyn Error>> {
+        let typing = CsvTyping {
+            numbers: true,
+            booleans: true,
+            nodes: true,
+        };
+        let (variables, solutions) = build_example();
+        let expected_solutions = solutions
+            .into_iter()
+            .map(|solution| {
+                solution
+                    .into_iter()
+                    .map(|term| match term {
+                        // Language tags are lost
+                        Some(Term::Literal(literal)) if literal.language().is_some() => {
+                            Some(Literal::new_simple_literal(literal.value()).into())
+                        }
+                        other => other,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let csv = "x,literal\r\nhttp://example/x,String\r\nhttp://example/x,\"String-with-dquote\"\"\"\r\n_:b0,Blank node\r\n,Missing 'x'\r\n,\r\nhttp://example/x,\r\n_:b1,String-with-lang\r\n_:b1,123\r\n,\"escape,\t\r\n\"\r\n";
+
+        // Reader
+        if let ReaderCsvQueryResultsParserOutput::Solutions {
+            solutions: mut solutions_iter,
+            variables: actual_variables,
+        } = ReaderCsvQueryResultsParserOutput::read(csv.as_bytes(), typing)?
+        {
+            assert_eq!(actual_variables.as_slice(), variables.as_slice());
+            let mut rows = Vec::new();
+            while let Some(row) = solutions_iter.parse_next()? {
+                rows.push(row);
+            }
+            assert_eq!(rows, expected_solutions);
+        } else {
+            unreachable!()
+        }
+
+        // Slice
+        if let SliceCsvQueryResultsParserOutput::Solutions {
+            solutions: mut solutions_iter,
+            variables: actual_variables,
+        } = SliceCsvQueryResultsParserOutput::read(csv.as_bytes(), typing)?
+        {
+            assert_eq!(actual_variables.as_slice(), variables.as_slice());
+            let mut rows = Vec::new();
+            while let Some(row) = solutions_iter.parse_next()? {
+                rows.push(row);
+            }
+            assert_eq!(rows, expected_solutions);
+        } else {
+            unreachable!()
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_parsing_without_typing() -> Result<(), Box<dyn Error>> {
+        if let ReaderCsvQueryResultsParserOutput::Solutions {
+            mut solutions,
+            variables,
+        } = ReaderCsvQueryResultsParserOutput::read(
+            b"?a,b\r\nhttp://example.com,1\r\n\"\",true\r\n".as_slice(),
+            CsvTyping::default(),
+        )? {
+            assert_eq!(
+                variables,
+                vec![Variable::new_unchecked("a"), Variable::new_unchecked("b")]
+            );
+            assert_eq!(
+                solutions.parse_next()?,
+                Some(vec![
+                    Some(Literal::new_simple_literal("http://example.com").into()),
+                    Some(Literal::new_simple_literal("1").into())
+                ])
+            );
+            assert_eq!(
+                solutions.parse_next()?,
+                Some(vec![
+                    Some(Literal::new_simple_literal("").into()),
+                    Some(Literal::new_simple_literal("true").into())
+                ])
+            );
+            assert_eq!(solutions.parse_next()?, None);
+        } else {
+            unreachable!()
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_typing() {
+        let typing = CsvTyping {
+            numbers: true,
+            booleans: true,
+            nodes: true,
+        };
+        for (value, expected) in [
+            (
+                "true",
+                Literal::new_typed_literal("true", xsd::BOOLEAN).into(),
+            ),
+            (
+                "-12",
+                Literal::new_typed_literal("-12", xsd::INTEGER).into(),
+            ),
+            (
+                "1.5",
+                Literal::new_typed_literal("1.5", xsd::DECIMAL).into(),
+            ),
+            ("1e3", Literal::new_typed_literal("1e3", xsd::DOUBLE).into()),
+            (
+                "<http://example.com>",
+                NamedNode::new_unchecked("http://example.com").into(),
+            ),
+            ("<foo>", Literal::new_simple_literal("<foo>").into()),
+            ("_:b", BlankNode::new_unchecked("b").into()),
+            ("1 2", Literal::new_simple_literal("1 2").into()),
+        ] {
+            assert_eq!(
+                csv_value_to_term(value.into(), false, typing),
+                Some(expected),
+                "{value}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_bad_csv() {
+        for bad_csv in ["a,a", "?", "a\n\"", "a\n\"1\"2", "a\n1,2\n", "a,b\n1\n"] {
+            if let Ok(ReaderCsvQueryResultsParserOutput::Solutions { mut solutions, .. }) =
+                ReaderCsvQueryResultsParserOutput::read(bad_csv.as_bytes(), CsvTyping::default())
+            {
+                solutions.parse_next().unwrap_err();
+            }
+        }
+    }
+
     #[test]
     fn test_tsv_roundtrip() -> Result<(), Box<dyn Error>> {
         let (variables, solutions) = build_example();
@@ -916,6 +1434,90 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(feature = "rdf-star")]
+    fn build_triple_term_example() -> (Vec<Variable>, Vec<Vec<Option<Term>>>) {
+        let triple = Triple::new(
+            BlankNode::new_unchecked("b0"),
+            NamedNode::new_unchecked("http://example/p"),
+            Literal::new_language_tagged_literal_unchecked("a, \"b\"", "en"),
+        );
+        (
+            vec![Variable::new_unchecked("t")],
+            vec![
+                vec![Some(triple.clone().into())],
+                vec![Some(
+                    Triple::new(
+                        triple,
+                        NamedNode::new_unchecked("http://example/p"),
+                        Literal::new_typed_literal("1", xsd::INTEGER),
+                    )
+                    .into(),
+                )],
+            ],
+        )
+    }
+
+    #[cfg(feature = "rdf-star")]
+    #[test]
+    fn test_csv_triple_term_serialization() {
+        let (variables, solutions) = build_triple_term_example();
+        let mut buffer = String::new();
+        let serializer = InnerCsvSolutionsSerializer::start(&mut buffer, variables.clone());
+        for solution in solutions {
+            serializer.write(
+                &mut buffer,
+                variables
+                    .iter()
+                    .zip(&solution)
+                    .filter_map(|(v, s)| s.as_ref().map(|s| (v.as_ref(), s.as_ref()))),
+            );
+        }
+        assert_eq!(buffer, "t\r\n\"<< _:b0 <http://example/p> \"\"a, \\\"\"b\\\"\"\"\"@en >>\"\r\n\"<< << _:b0 <http://example/p> \"\"a, \\\"\"b\\\"\"\"\"@en >> <http://example/p> 1 >>\"\r\n");
+    }
+
+    #[cfg(feature = "rdf-star")]
+    #[test]
+    fn test_tsv_triple_term_roundtrip() -> Result<(), Box<dyn Error>> {
+        let (variables, solutions) = build_triple_term_example();
+
+        // Write
+        let mut buffer = String::new();
+        let serializer = InnerTsvSolutionsSerializer::start(&mut buffer, variables.clone());
+        for solution in &solutions {
+            serializer.write(
+                &mut buffer,
+                variables
+                    .iter()
+                    .zip(solution)
+                    .filter_map(|(v, s)| s.as_ref().map(|s| (v.as_ref(), s.as_ref()))),
+            );
+        }
+        assert_eq!(buffer, "?t\n<< _:b0 <http://example/p> \"a, \\\"b\\\"\"@en >>\n<< << _:b0 <http://example/p> \"a, \\\"b\\\"\"@en >> <http://example/p> 1 >>\n");
+
+        // Read, also with the RDF 1.2 triple term syntax
+        for buffer in [
+            buffer.clone(),
+            buffer.replace("<< ", "<<( ").replace(" >>", " )>>"),
+        ] {
+            if let SliceTsvQueryResultsParserOutput::Solutions {
+                solutions: mut solutions_iter,
+                variables: actual_variables,
+            } = SliceTsvQueryResultsParserOutput::read(buffer.as_bytes())?
+            {
+                assert_eq!(actual_variables.as_slice(), variables.as_slice());
+                let mut rows = Vec::new();
+                while let Some(row) = solutions_iter.parse_next()? {
+                    rows.push(row);
+                }
+                assert_eq!(rows, solutions);
+            } else {
+                unreachable!()
+            }
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn test_bad_tsv() {
         let mut bad_tsvs = vec![
@@ -973,6 +1575,23 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_no_columns_csv_parsing() -> io::Result<()> {
+        if let ReaderCsvQueryResultsParserOutput::Solutions {
+            mut solutions,
+            variables,
+        } =
+            ReaderCsvQueryResultsParserOutput::read(b"\r\n\r\n".as_slice(), CsvTyping::default())?
+        {
+            assert_eq!(variables, Vec::<Variable>::new());
+            assert_eq!(solutions.parse_next()?, Some(Vec::new()));
+            assert_eq!(solutions.parse_next()?, None);
+        } else {
+            unreachable!()
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_no_results_csv_serialization() {
         let mut buffer = String::new();
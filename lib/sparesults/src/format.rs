@@ -12,6 +12,8 @@ pub enum QueryResultsFormat {
     Csv,
     /// [SPARQL Query Results TSV Format](https://www.w3.org/TR/sparql11-results-csv-tsv/)
     Tsv,
+    /// [Apache Jena SPARQL Query Results Thrift Format](https://jena.apache.org/documentation/io/rdf-binary.html)
+    Thrift,
 }
 
 impl QueryResultsFormat {
@@ -32,6 +34,7 @@ impl QueryResultsFormat {
             Self::Json => "http://www.w3.org/ns/formats/SPARQL_Results_JSON",
             Self::Csv => "http://www.w3.org/ns/formats/SPARQL_Results_CSV",
             Self::Tsv => "http://www.w3.org/ns/formats/SPARQL_Results_TSV",
+            Self::Thrift => "https://jena.apache.org/documentation/io/rdf-binary.html",
         }
     }
 
@@ -52,6 +55,7 @@ impl QueryResultsFormat {
             Self::Json => "application/sparql-results+json",
             Self::Csv => "text/csv; charset=utf-8",
             Self::Tsv => "text/tab-separated-values; charset=utf-8",
+            Self::Thrift => "application/sparql-results+thrift",
         }
     }
 
@@ -69,6 +73,7 @@ impl QueryResultsFormat {
             Self::Json => "srj",
             Self::Csv => "csv",
             Self::Tsv => "tsv",
+            Self::Thrift => "srt",
         }
     }
 
@@ -86,6 +91,7 @@ impl QueryResultsFormat {
             Self::Json => "SPARQL Results in JSON",
             Self::Csv => "SPARQL Results in CSV",
             Self::Tsv => "SPARQL Results in TSV",
+            Self::Thrift => "SPARQL Results in Thrift",
         }
     }
 
@@ -105,11 +111,12 @@ impl QueryResultsFormat {
     /// ```
     #[inline]
     pub fn from_media_type(media_type: &str) -> Option<Self> {
-        const MEDIA_SUBTYPES: [(&str, QueryResultsFormat); 8] = [
+        const MEDIA_SUBTYPES: [(&str, QueryResultsFormat); 9] = [
             ("csv", QueryResultsFormat::Csv),
             ("json", QueryResultsFormat::Json),
             ("plain", QueryResultsFormat::Csv),
             ("sparql-results+json", QueryResultsFormat::Json),
+            ("sparql-results+thrift", QueryResultsFormat::Thrift),
             ("sparql-results+xml", QueryResultsFormat::Xml),
             ("tab-separated-values", QueryResultsFormat::Tsv),
             ("tsv", QueryResultsFormat::Tsv),
@@ -151,10 +158,11 @@ impl QueryResultsFormat {
     /// ```
     #[inline]
     pub fn from_extension(extension: &str) -> Option<Self> {
-        const MEDIA_TYPES: [(&str, QueryResultsFormat); 7] = [
+        const MEDIA_TYPES: [(&str, QueryResultsFormat); 8] = [
             ("csv", QueryResultsFormat::Csv),
             ("json", QueryResultsFormat::Json),
             ("srj", QueryResultsFormat::Json),
+            ("srt", QueryResultsFormat::Thrift),
             ("srx", QueryResultsFormat::Xml),
             ("tsv", QueryResultsFormat::Tsv),
             ("txt", QueryResultsFormat::Csv),
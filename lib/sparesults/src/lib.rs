@@ -4,6 +4,8 @@
 #![doc(html_favicon_url = "https://raw.githubusercontent.com/oxigraph/oxigraph/main/logo.svg")]
 #![doc(html_logo_url = "https://raw.githubusercontent.com/oxigraph/oxigraph/main/logo.svg")]
 
+#[cfg(feature = "arrow")]
+mod arrow;
 mod csv;
 mod error;
 mod format;
@@ -11,8 +13,11 @@ mod json;
 mod parser;
 mod serializer;
 pub mod solution;
+mod thrift;
 mod xml;
 
+#[cfg(feature = "arrow")]
+pub use crate::arrow::ArrowSolutionsConverter;
 pub use crate::error::{QueryResultsParseError, QueryResultsSyntaxError, TextPosition};
 pub use crate::format::QueryResultsFormat;
 pub use crate::parser::{
@@ -27,3 +32,60 @@ pub use crate::parser::{
 pub use crate::serializer::TokioAsyncWriterSolutionsSerializer;
 pub use crate::serializer::{QueryResultsSerializer, WriterSolutionsSerializer};
 pub use crate::solution::QuerySolution;
+
+#[cfg(test)]
+#[cfg(feature = "rdf-star")]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+    use oxrdf::vocab::xsd;
+    use oxrdf::{BlankNode, Literal, NamedNode, Term, Triple, Variable};
+    use std::error::Error;
+
+    #[test]
+    fn test_triple_term_roundtrip() -> Result<(), Box<dyn Error>> {
+        let variables = vec![Variable::new("s")?, Variable::new("t")?];
+        let triple = Triple::new(
+            BlankNode::new("b0")?,
+            NamedNode::new("http://example.com/p")?,
+            Literal::new_language_tagged_literal("a, \"b\"\n", "en")?,
+        );
+        let terms: Vec<Term> = vec![
+            triple.clone().into(),
+            Triple::new(
+                triple,
+                NamedNode::new("http://example.com/p")?,
+                Literal::new_typed_literal("1", xsd::INTEGER),
+            )
+            .into(),
+        ];
+        for format in [
+            QueryResultsFormat::Json,
+            QueryResultsFormat::Xml,
+            QueryResultsFormat::Tsv,
+            QueryResultsFormat::Thrift,
+        ] {
+            let mut serializer = QueryResultsSerializer::from_format(format)
+                .serialize_solutions_to_writer(Vec::new(), variables.clone())?;
+            for term in &terms {
+                serializer.serialize([(&variables[0], term), (&variables[1], term)])?;
+            }
+            let buffer = serializer.finish()?;
+            let SliceQueryResultsParserOutput::Solutions(solutions) =
+                QueryResultsParser::from_format(format).for_slice(&buffer)?
+            else {
+                unreachable!()
+            };
+            assert_eq!(solutions.variables(), variables.as_slice());
+            let actual = solutions
+                .map(|solution| Ok(solution?.get(1).cloned()))
+                .collect::<Result<Vec<_>, QueryResultsParseError>>()?;
+            assert_eq!(
+                actual,
+                terms.iter().cloned().map(Some).collect::<Vec<_>>(),
+                "Round trip failed for {format}"
+            );
+        }
+        Ok(())
+    }
+}
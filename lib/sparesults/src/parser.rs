@@ -1,9 +1,13 @@
 use crate::csv::{
-    ReaderTsvQueryResultsParserOutput, ReaderTsvSolutionsParser, SliceTsvQueryResultsParserOutput,
-    SliceTsvSolutionsParser,
+    CsvTyping, ReaderCsvQueryResultsParserOutput, ReaderCsvSolutionsParser,
+    ReaderTsvQueryResultsParserOutput, ReaderTsvSolutionsParser, SliceCsvQueryResultsParserOutput,
+    SliceCsvSolutionsParser, SliceTsvQueryResultsParserOutput, SliceTsvSolutionsParser,
 };
 #[cfg(feature = "async-tokio")]
-use crate::csv::{TokioAsyncReaderTsvQueryResultsParserOutput, TokioAsyncReaderTsvSolutionsParser};
+use crate::csv::{
+    TokioAsyncReaderCsvQueryResultsParserOutput, TokioAsyncReaderCsvSolutionsParser,
+    TokioAsyncReaderTsvQueryResultsParserOutput, TokioAsyncReaderTsvSolutionsParser,
+};
 use crate::error::{QueryResultsParseError, QueryResultsSyntaxError};
 use crate::format::QueryResultsFormat;
 use crate::json::{
@@ -15,6 +19,9 @@ use crate::json::{
     TokioAsyncReaderJsonQueryResultsParserOutput, TokioAsyncReaderJsonSolutionsParser,
 };
 use crate::solution::QuerySolution;
+#[cfg(feature = "async-tokio")]
+use crate::thrift::TokioAsyncReaderThriftSolutionsParser;
+use crate::thrift::{ReaderThriftSolutionsParser, SliceThriftSolutionsParser};
 use crate::xml::{
     ReaderXmlQueryResultsParserOutput, ReaderXmlSolutionsParser, SliceXmlQueryResultsParserOutput,
     SliceXmlSolutionsParser,
@@ -32,7 +39,9 @@ use tokio::io::AsyncRead;
 /// It currently supports the following formats:
 /// * [SPARQL Query Results XML Format](https://www.w3.org/TR/rdf-sparql-XMLres/) ([`QueryResultsFormat::Xml`](QueryResultsFormat::Xml)).
 /// * [SPARQL Query Results JSON Format](https://www.w3.org/TR/sparql11-results-json/) ([`QueryResultsFormat::Json`](QueryResultsFormat::Json)).
+/// * [SPARQL Query Results CSV Format](https://www.w3.org/TR/sparql11-results-csv-tsv/) ([`QueryResultsFormat::Csv`](QueryResultsFormat::Csv)).
 /// * [SPARQL Query Results TSV Format](https://www.w3.org/TR/sparql11-results-csv-tsv/) ([`QueryResultsFormat::Tsv`](QueryResultsFormat::Tsv)).
+/// * [Apache Jena SPARQL Query Results Thrift Format](https://jena.apache.org/documentation/io/rdf-binary.html) ([`QueryResultsFormat::Thrift`](QueryResultsFormat::Thrift)).
 ///
 /// Example in JSON (the API is the same for XML and TSV):
 /// ```
@@ -53,17 +62,89 @@ use tokio::io::AsyncRead;
 /// }
 /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
 /// ```
+///
+/// The CSV format is lossy: by default all its values are parsed as plain strings.
+/// Use [`detect_csv_numbers`](Self::detect_csv_numbers), [`detect_csv_booleans`](Self::detect_csv_booleans)
+/// and [`detect_csv_nodes`](Self::detect_csv_nodes) to recover more precise terms.
 #[must_use]
 #[derive(Clone)]
 pub struct QueryResultsParser {
     format: QueryResultsFormat,
+    csv_typing: CsvTyping,
 }
 
 impl QueryResultsParser {
     /// Builds a parser for the given format.
     #[inline]
     pub fn from_format(format: QueryResultsFormat) -> Self {
-        Self { format }
+        Self {
+            format,
+            csv_typing: CsvTyping::default(),
+        }
+    }
+
+    /// Parses the CSV values written like Turtle numbers (e.g. `12`, `1.2` or `1e2`) as `xsd:integer`, `xsd:decimal` or `xsd:double` literals.
+    ///
+    /// Only used with the [CSV format](QueryResultsFormat::Csv).
+    ///
+    /// ```
+    /// use oxrdf::vocab::xsd;
+    /// use oxrdf::Literal;
+    /// use sparesults::{QueryResultsFormat, QueryResultsParser, SliceQueryResultsParserOutput};
+    ///
+    /// let parser = QueryResultsParser::from_format(QueryResultsFormat::Csv).detect_csv_numbers();
+    /// if let SliceQueryResultsParserOutput::Solutions(mut solutions) =
+    ///     parser.for_slice(b"foo\r\n12\r\n")?
+    /// {
+    ///     assert_eq!(
+    ///         solutions.next().unwrap()?.get("foo"),
+    ///         Some(&Literal::new_typed_literal("12", xsd::INTEGER).into())
+    ///     );
+    /// }
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    pub fn detect_csv_numbers(mut self) -> Self {
+        self.csv_typing.numbers = true;
+        self
+    }
+
+    /// Parses the CSV values `true` and `false` as `xsd:boolean` literals.
+    ///
+    /// Only used with the [CSV format](QueryResultsFormat::Csv).
+    #[inline]
+    pub fn detect_csv_booleans(mut self) -> Self {
+        self.csv_typing.booleans = true;
+        self
+    }
+
+    /// Parses the CSV values that are absolute IRIs (e.g. `http://example.com`) or IRIs between angle brackets (e.g. `<http://example.com>`) as IRIs
+    /// and the ones starting with `_:` as blank nodes.
+    ///
+    /// Only used with the [CSV format](QueryResultsFormat::Csv).
+    /// Note that string literals that are valid absolute IRIs are then also parsed as IRIs.
+    ///
+    /// ```
+    /// use oxrdf::{BlankNode, NamedNode};
+    /// use sparesults::{QueryResultsFormat, QueryResultsParser, SliceQueryResultsParserOutput};
+    ///
+    /// let parser = QueryResultsParser::from_format(QueryResultsFormat::Csv).detect_csv_nodes();
+    /// if let SliceQueryResultsParserOutput::Solutions(mut solutions) =
+    ///     parser.for_slice(b"s,o\r\nhttp://example.com/s,_:b\r\n")?
+    /// {
+    ///     let solution = solutions.next().unwrap()?;
+    ///     assert_eq!(
+    ///         solution.get("s"),
+    ///         Some(&NamedNode::new("http://example.com/s")?.into())
+    ///     );
+    ///     assert_eq!(solution.get("o"), Some(&BlankNode::new("b")?.into()));
+    /// }
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    pub fn detect_csv_nodes(mut self) -> Self {
+        self.csv_typing.nodes = true;
+        self
     }
 
     /// Reads a result file from a [`Read`] implementation.
@@ -97,7 +178,9 @@ impl QueryResultsParser {
     ) -> Result<ReaderQueryResultsParserOutput<R>, QueryResultsParseError> {
         Ok(match self.format {
             QueryResultsFormat::Xml => match ReaderXmlQueryResultsParserOutput::read(reader)? {
-                ReaderXmlQueryResultsParserOutput::Boolean(r) => ReaderQueryResultsParserOutput::Boolean(r),
+                ReaderXmlQueryResultsParserOutput::Boolean(r) => {
+                    ReaderQueryResultsParserOutput::Boolean(r)
+                }
                 ReaderXmlQueryResultsParserOutput::Solutions {
                     solutions,
                     variables,
@@ -107,7 +190,9 @@ impl QueryResultsParser {
                 }),
             },
             QueryResultsFormat::Json => match ReaderJsonQueryResultsParserOutput::read(reader)? {
-                ReaderJsonQueryResultsParserOutput::Boolean(r) => ReaderQueryResultsParserOutput::Boolean(r),
+                ReaderJsonQueryResultsParserOutput::Boolean(r) => {
+                    ReaderQueryResultsParserOutput::Boolean(r)
+                }
                 ReaderJsonQueryResultsParserOutput::Solutions {
                     solutions,
                     variables,
@@ -116,9 +201,24 @@ impl QueryResultsParser {
                     solutions: ReaderSolutionsParserKind::Json(solutions),
                 }),
             },
-            QueryResultsFormat::Csv => return Err(QueryResultsSyntaxError::msg("CSV SPARQL results syntax is lossy and can't be parsed to a proper RDF representation").into()),
+            QueryResultsFormat::Csv => {
+                match ReaderCsvQueryResultsParserOutput::read(reader, self.csv_typing)? {
+                    ReaderCsvQueryResultsParserOutput::Boolean(r) => {
+                        ReaderQueryResultsParserOutput::Boolean(r)
+                    }
+                    ReaderCsvQueryResultsParserOutput::Solutions {
+                        solutions,
+                        variables,
+                    } => ReaderQueryResultsParserOutput::Solutions(ReaderSolutionsParser {
+                        variables: variables.into(),
+                        solutions: ReaderSolutionsParserKind::Csv(solutions),
+                    }),
+                }
+            }
             QueryResultsFormat::Tsv => match ReaderTsvQueryResultsParserOutput::read(reader)? {
-                ReaderTsvQueryResultsParserOutput::Boolean(r) => ReaderQueryResultsParserOutput::Boolean(r),
+                ReaderTsvQueryResultsParserOutput::Boolean(r) => {
+                    ReaderQueryResultsParserOutput::Boolean(r)
+                }
                 ReaderTsvQueryResultsParserOutput::Solutions {
                     solutions,
                     variables,
@@ -127,6 +227,13 @@ impl QueryResultsParser {
                     solutions: ReaderSolutionsParserKind::Tsv(solutions),
                 }),
             },
+            QueryResultsFormat::Thrift => {
+                let (variables, solutions) = ReaderThriftSolutionsParser::read(reader)?;
+                ReaderQueryResultsParserOutput::Solutions(ReaderSolutionsParser {
+                    variables: variables.into(),
+                    solutions: ReaderSolutionsParserKind::Thrift(solutions),
+                })
+            }
         })
     }
 
@@ -172,37 +279,82 @@ impl QueryResultsParser {
         reader: R,
     ) -> Result<TokioAsyncReaderQueryResultsParserOutput<R>, QueryResultsParseError> {
         Ok(match self.format {
-            QueryResultsFormat::Xml => match TokioAsyncReaderXmlQueryResultsParserOutput::read(reader).await? {
-                TokioAsyncReaderXmlQueryResultsParserOutput::Boolean(r) => TokioAsyncReaderQueryResultsParserOutput::Boolean(r),
-                TokioAsyncReaderXmlQueryResultsParserOutput::Solutions {
-                    solutions,
-                    variables,
-                } => TokioAsyncReaderQueryResultsParserOutput::Solutions(TokioAsyncReaderSolutionsParser {
-                    variables: variables.into(),
-                    solutions: TokioAsyncReaderSolutionsParserKind::Xml(solutions),
-                }),
-            },
-            QueryResultsFormat::Json => match TokioAsyncReaderJsonQueryResultsParserOutput::read(reader).await? {
-                TokioAsyncReaderJsonQueryResultsParserOutput::Boolean(r) => TokioAsyncReaderQueryResultsParserOutput::Boolean(r),
-                TokioAsyncReaderJsonQueryResultsParserOutput::Solutions {
-                    solutions,
-                    variables,
-                } => TokioAsyncReaderQueryResultsParserOutput::Solutions(TokioAsyncReaderSolutionsParser {
-                    variables: variables.into(),
-                    solutions: TokioAsyncReaderSolutionsParserKind::Json(solutions),
-                }),
-            },
-            QueryResultsFormat::Csv => return Err(QueryResultsSyntaxError::msg("CSV SPARQL results syntax is lossy and can't be parsed to a proper RDF representation").into()),
-            QueryResultsFormat::Tsv => match TokioAsyncReaderTsvQueryResultsParserOutput::read(reader).await? {
-                TokioAsyncReaderTsvQueryResultsParserOutput::Boolean(r) => TokioAsyncReaderQueryResultsParserOutput::Boolean(r),
-                TokioAsyncReaderTsvQueryResultsParserOutput::Solutions {
-                    solutions,
-                    variables,
-                } => TokioAsyncReaderQueryResultsParserOutput::Solutions(TokioAsyncReaderSolutionsParser {
-                    variables: variables.into(),
-                    solutions: TokioAsyncReaderSolutionsParserKind::Tsv(solutions),
-                }),
-            },
+            QueryResultsFormat::Xml => {
+                match TokioAsyncReaderXmlQueryResultsParserOutput::read(reader).await? {
+                    TokioAsyncReaderXmlQueryResultsParserOutput::Boolean(r) => {
+                        TokioAsyncReaderQueryResultsParserOutput::Boolean(r)
+                    }
+                    TokioAsyncReaderXmlQueryResultsParserOutput::Solutions {
+                        solutions,
+                        variables,
+                    } => TokioAsyncReaderQueryResultsParserOutput::Solutions(
+                        TokioAsyncReaderSolutionsParser {
+                            variables: variables.into(),
+                            solutions: TokioAsyncReaderSolutionsParserKind::Xml(solutions),
+                        },
+                    ),
+                }
+            }
+            QueryResultsFormat::Json => {
+                match TokioAsyncReaderJsonQueryResultsParserOutput::read(reader).await? {
+                    TokioAsyncReaderJsonQueryResultsParserOutput::Boolean(r) => {
+                        TokioAsyncReaderQueryResultsParserOutput::Boolean(r)
+                    }
+                    TokioAsyncReaderJsonQueryResultsParserOutput::Solutions {
+                        solutions,
+                        variables,
+                    } => TokioAsyncReaderQueryResultsParserOutput::Solutions(
+                        TokioAsyncReaderSolutionsParser {
+                            variables: variables.into(),
+                            solutions: TokioAsyncReaderSolutionsParserKind::Json(solutions),
+                        },
+                    ),
+                }
+            }
+            QueryResultsFormat::Csv => {
+                match TokioAsyncReaderCsvQueryResultsParserOutput::read(reader, self.csv_typing)
+                    .await?
+                {
+                    TokioAsyncReaderCsvQueryResultsParserOutput::Boolean(r) => {
+                        TokioAsyncReaderQueryResultsParserOutput::Boolean(r)
+                    }
+                    TokioAsyncReaderCsvQueryResultsParserOutput::Solutions {
+                        solutions,
+                        variables,
+                    } => TokioAsyncReaderQueryResultsParserOutput::Solutions(
+                        TokioAsyncReaderSolutionsParser {
+                            variables: variables.into(),
+                            solutions: TokioAsyncReaderSolutionsParserKind::Csv(solutions),
+                        },
+                    ),
+                }
+            }
+            QueryResultsFormat::Tsv => {
+                match TokioAsyncReaderTsvQueryResultsParserOutput::read(reader).await? {
+                    TokioAsyncReaderTsvQueryResultsParserOutput::Boolean(r) => {
+                        TokioAsyncReaderQueryResultsParserOutput::Boolean(r)
+                    }
+                    TokioAsyncReaderTsvQueryResultsParserOutput::Solutions {
+                        solutions,
+                        variables,
+                    } => TokioAsyncReaderQueryResultsParserOutput::Solutions(
+                        TokioAsyncReaderSolutionsParser {
+                            variables: variables.into(),
+                            solutions: TokioAsyncReaderSolutionsParserKind::Tsv(solutions),
+                        },
+                    ),
+                }
+            }
+            QueryResultsFormat::Thrift => {
+                let (variables, solutions) =
+                    TokioAsyncReaderThriftSolutionsParser::read(reader).await?;
+                TokioAsyncReaderQueryResultsParserOutput::Solutions(
+                    TokioAsyncReaderSolutionsParser {
+                        variables: variables.into(),
+                        solutions: TokioAsyncReaderSolutionsParserKind::Thrift(solutions),
+                    },
+                )
+            }
         })
     }
 
@@ -237,7 +389,9 @@ impl QueryResultsParser {
     ) -> Result<SliceQueryResultsParserOutput<'_>, QueryResultsSyntaxError> {
         Ok(match self.format {
             QueryResultsFormat::Xml => match SliceXmlQueryResultsParserOutput::read(slice)? {
-                SliceXmlQueryResultsParserOutput::Boolean(r) => SliceQueryResultsParserOutput::Boolean(r),
+                SliceXmlQueryResultsParserOutput::Boolean(r) => {
+                    SliceQueryResultsParserOutput::Boolean(r)
+                }
                 SliceXmlQueryResultsParserOutput::Solutions {
                     solutions,
                     variables,
@@ -247,7 +401,9 @@ impl QueryResultsParser {
                 }),
             },
             QueryResultsFormat::Json => match SliceJsonQueryResultsParserOutput::read(slice)? {
-                SliceJsonQueryResultsParserOutput::Boolean(r) => SliceQueryResultsParserOutput::Boolean(r),
+                SliceJsonQueryResultsParserOutput::Boolean(r) => {
+                    SliceQueryResultsParserOutput::Boolean(r)
+                }
                 SliceJsonQueryResultsParserOutput::Solutions {
                     solutions,
                     variables,
@@ -256,9 +412,24 @@ impl QueryResultsParser {
                     solutions: SliceSolutionsParserKind::Json(solutions),
                 }),
             },
-            QueryResultsFormat::Csv => return Err(QueryResultsSyntaxError::msg("CSV SPARQL results syntax is lossy and can't be parsed to a proper RDF representation")),
+            QueryResultsFormat::Csv => {
+                match SliceCsvQueryResultsParserOutput::read(slice, self.csv_typing)? {
+                    SliceCsvQueryResultsParserOutput::Boolean(r) => {
+                        SliceQueryResultsParserOutput::Boolean(r)
+                    }
+                    SliceCsvQueryResultsParserOutput::Solutions {
+                        solutions,
+                        variables,
+                    } => SliceQueryResultsParserOutput::Solutions(SliceSolutionsParser {
+                        variables: variables.into(),
+                        solutions: SliceSolutionsParserKind::Csv(solutions),
+                    }),
+                }
+            }
             QueryResultsFormat::Tsv => match SliceTsvQueryResultsParserOutput::read(slice)? {
-                SliceTsvQueryResultsParserOutput::Boolean(r) => SliceQueryResultsParserOutput::Boolean(r),
+                SliceTsvQueryResultsParserOutput::Boolean(r) => {
+                    SliceQueryResultsParserOutput::Boolean(r)
+                }
                 SliceTsvQueryResultsParserOutput::Solutions {
                     solutions,
                     variables,
@@ -267,6 +438,13 @@ impl QueryResultsParser {
                     solutions: SliceSolutionsParserKind::Tsv(solutions),
                 }),
             },
+            QueryResultsFormat::Thrift => {
+                let (variables, solutions) = SliceThriftSolutionsParser::read(slice)?;
+                SliceQueryResultsParserOutput::Solutions(SliceSolutionsParser {
+                    variables: variables.into(),
+                    solutions: SliceSolutionsParserKind::Thrift(solutions),
+                })
+            }
         })
     }
 }
@@ -343,7 +521,9 @@ pub struct ReaderSolutionsParser<R: Read> {
 enum ReaderSolutionsParserKind<R: Read> {
     Xml(ReaderXmlSolutionsParser<R>),
     Json(ReaderJsonSolutionsParser<R>),
+    Csv(ReaderCsvSolutionsParser<R>),
     Tsv(ReaderTsvSolutionsParser<R>),
+    Thrift(ReaderThriftSolutionsParser<R>),
 }
 
 impl<R: Read> ReaderSolutionsParser<R> {
@@ -379,7 +559,9 @@ impl<R: Read> Iterator for ReaderSolutionsParser<R> {
             match &mut self.solutions {
                 ReaderSolutionsParserKind::Xml(reader) => reader.parse_next(),
                 ReaderSolutionsParserKind::Json(reader) => reader.parse_next(),
+                ReaderSolutionsParserKind::Csv(reader) => reader.parse_next(),
                 ReaderSolutionsParserKind::Tsv(reader) => reader.parse_next(),
+                ReaderSolutionsParserKind::Thrift(reader) => reader.parse_next(),
             }
             .transpose()?
             .map(|values| (Arc::clone(&self.variables), values).into()),
@@ -467,7 +649,9 @@ pub struct TokioAsyncReaderSolutionsParser<R: AsyncRead + Unpin> {
 enum TokioAsyncReaderSolutionsParserKind<R: AsyncRead + Unpin> {
     Json(TokioAsyncReaderJsonSolutionsParser<R>),
     Xml(TokioAsyncReaderXmlSolutionsParser<R>),
+    Csv(TokioAsyncReaderCsvSolutionsParser<R>),
     Tsv(TokioAsyncReaderTsvSolutionsParser<R>),
+    Thrift(TokioAsyncReaderThriftSolutionsParser<R>),
 }
 
 #[cfg(feature = "async-tokio")]
@@ -507,7 +691,9 @@ impl<R: AsyncRead + Unpin> TokioAsyncReaderSolutionsParser<R> {
             match &mut self.solutions {
                 TokioAsyncReaderSolutionsParserKind::Json(reader) => reader.parse_next().await,
                 TokioAsyncReaderSolutionsParserKind::Xml(reader) => reader.parse_next().await,
+                TokioAsyncReaderSolutionsParserKind::Csv(reader) => reader.parse_next().await,
                 TokioAsyncReaderSolutionsParserKind::Tsv(reader) => reader.parse_next().await,
+                TokioAsyncReaderSolutionsParserKind::Thrift(reader) => reader.parse_next().await,
             }
             .transpose()?
             .map(|values| (Arc::clone(&self.variables), values).into()),
@@ -581,7 +767,9 @@ pub struct SliceSolutionsParser<'a> {
 enum SliceSolutionsParserKind<'a> {
     Xml(SliceXmlSolutionsParser<'a>),
     Json(SliceJsonSolutionsParser<'a>),
+    Csv(SliceCsvSolutionsParser<'a>),
     Tsv(SliceTsvSolutionsParser<'a>),
+    Thrift(SliceThriftSolutionsParser<'a>),
 }
 
 impl SliceSolutionsParser<'_> {
@@ -617,7 +805,9 @@ impl Iterator for SliceSolutionsParser<'_> {
             match &mut self.solutions {
                 SliceSolutionsParserKind::Xml(reader) => reader.parse_next(),
                 SliceSolutionsParserKind::Json(reader) => reader.parse_next(),
+                SliceSolutionsParserKind::Csv(reader) => reader.parse_next(),
                 SliceSolutionsParserKind::Tsv(reader) => reader.parse_next(),
+                SliceSolutionsParserKind::Thrift(reader) => reader.parse_next(),
             }
             .transpose()?
             .map(|values| (Arc::clone(&self.variables), values).into()),
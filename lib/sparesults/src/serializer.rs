@@ -11,6 +11,9 @@ use crate::format::QueryResultsFormat;
 use crate::json::{tokio_async_write_boolean_json_result, TokioAsyncWriterJsonSolutionsSerializer};
 use crate::json::{write_boolean_json_result, WriterJsonSolutionsSerializer};
 #[cfg(feature = "async-tokio")]
+use crate::thrift::TokioAsyncWriterThriftSolutionsSerializer;
+use crate::thrift::WriterThriftSolutionsSerializer;
+#[cfg(feature = "async-tokio")]
 use crate::xml::{tokio_async_write_boolean_xml_result, TokioAsyncWriterXmlSolutionsSerializer};
 use crate::xml::{write_boolean_xml_result, WriterXmlSolutionsSerializer};
 use oxrdf::{TermRef, Variable, VariableRef};
@@ -25,6 +28,7 @@ use tokio::io::AsyncWrite;
 /// * [SPARQL Query Results JSON Format](https://www.w3.org/TR/sparql11-results-json/) ([`QueryResultsFormat::Json`](QueryResultsFormat::Json))
 /// * [SPARQL Query Results CSV Format](https://www.w3.org/TR/sparql11-results-csv-tsv/) ([`QueryResultsFormat::Csv`](QueryResultsFormat::Csv))
 /// * [SPARQL Query Results TSV Format](https://www.w3.org/TR/sparql11-results-csv-tsv/) ([`QueryResultsFormat::Tsv`](QueryResultsFormat::Tsv))
+/// * [Apache Jena SPARQL Query Results Thrift Format](https://jena.apache.org/documentation/io/rdf-binary.html) ([`QueryResultsFormat::Thrift`](QueryResultsFormat::Thrift)), only for solutions
 ///
 /// Example in JSON (the API is the same for XML, CSV and TSV):
 /// ```
@@ -79,6 +83,7 @@ impl QueryResultsSerializer {
             QueryResultsFormat::Csv | QueryResultsFormat::Tsv => {
                 write_boolean_csv_result(writer, value)
             }
+            QueryResultsFormat::Thrift => Err(thrift_boolean_error()),
         }
     }
 
@@ -111,6 +116,7 @@ impl QueryResultsSerializer {
             QueryResultsFormat::Csv | QueryResultsFormat::Tsv => {
                 tokio_async_write_boolean_csv_result(writer, value).await
             }
+            QueryResultsFormat::Thrift => Err(thrift_boolean_error()),
         }
     }
 
@@ -162,6 +168,9 @@ impl QueryResultsSerializer {
                 QueryResultsFormat::Tsv => WriterSolutionsSerializerKind::Tsv(
                     WriterTsvSolutionsSerializer::start(writer, variables)?,
                 ),
+                QueryResultsFormat::Thrift => WriterSolutionsSerializerKind::Thrift(
+                    WriterThriftSolutionsSerializer::start(writer, variables)?,
+                ),
             },
         })
     }
@@ -213,6 +222,9 @@ impl QueryResultsSerializer {
                 QueryResultsFormat::Tsv => TokioAsyncWriterSolutionsSerializerKind::Tsv(
                     TokioAsyncWriterTsvSolutionsSerializer::start(writer, variables).await?,
                 ),
+                QueryResultsFormat::Thrift => TokioAsyncWriterSolutionsSerializerKind::Thrift(
+                    TokioAsyncWriterThriftSolutionsSerializer::start(writer, variables).await?,
+                ),
             },
         })
     }
@@ -275,6 +287,7 @@ enum WriterSolutionsSerializerKind<W: Write> {
     Json(WriterJsonSolutionsSerializer<W>),
     Csv(WriterCsvSolutionsSerializer<W>),
     Tsv(WriterTsvSolutionsSerializer<W>),
+    Thrift(WriterThriftSolutionsSerializer<W>),
 }
 
 impl<W: Write> WriterSolutionsSerializer<W> {
@@ -305,6 +318,7 @@ impl<W: Write> WriterSolutionsSerializer<W> {
             WriterSolutionsSerializerKind::Json(writer) => writer.serialize(solution),
             WriterSolutionsSerializerKind::Csv(writer) => writer.serialize(solution),
             WriterSolutionsSerializerKind::Tsv(writer) => writer.serialize(solution),
+            WriterSolutionsSerializerKind::Thrift(writer) => writer.serialize(solution),
         }
     }
 
@@ -315,6 +329,7 @@ impl<W: Write> WriterSolutionsSerializer<W> {
             WriterSolutionsSerializerKind::Json(serializer) => serializer.finish(),
             WriterSolutionsSerializerKind::Csv(serializer) => Ok(serializer.finish()),
             WriterSolutionsSerializerKind::Tsv(serializer) => Ok(serializer.finish()),
+            WriterSolutionsSerializerKind::Thrift(serializer) => Ok(serializer.finish()),
         }
     }
 }
@@ -367,6 +382,7 @@ enum TokioAsyncWriterSolutionsSerializerKind<W: AsyncWrite + Unpin> {
     Json(TokioAsyncWriterJsonSolutionsSerializer<W>),
     Csv(TokioAsyncWriterCsvSolutionsSerializer<W>),
     Tsv(TokioAsyncWriterTsvSolutionsSerializer<W>),
+    Thrift(TokioAsyncWriterThriftSolutionsSerializer<W>),
 }
 
 #[cfg(feature = "async-tokio")]
@@ -409,6 +425,9 @@ impl<W: AsyncWrite + Unpin> TokioAsyncWriterSolutionsSerializer<W> {
             TokioAsyncWriterSolutionsSerializerKind::Tsv(writer) => {
                 writer.serialize(solution).await
             }
+            TokioAsyncWriterSolutionsSerializerKind::Thrift(writer) => {
+                writer.serialize(solution).await
+            }
         }
     }
 
@@ -419,6 +438,14 @@ impl<W: AsyncWrite + Unpin> TokioAsyncWriterSolutionsSerializer<W> {
             TokioAsyncWriterSolutionsSerializerKind::Json(serializer) => serializer.finish().await,
             TokioAsyncWriterSolutionsSerializerKind::Csv(serializer) => Ok(serializer.finish()),
             TokioAsyncWriterSolutionsSerializerKind::Tsv(serializer) => Ok(serializer.finish()),
+            TokioAsyncWriterSolutionsSerializerKind::Thrift(serializer) => Ok(serializer.finish()),
         }
     }
 }
+
+fn thrift_boolean_error() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "The SPARQL results Thrift format does not support boolean results",
+    )
+}
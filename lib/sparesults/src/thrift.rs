@@ -0,0 +1,1019 @@
+//! Implementation of the [Apache Jena](https://jena.apache.org/documentation/io/rdf-binary.html) SPARQL Query Results Thrift Format
+//!
+//! The file is a sequence of structs encoded with the [Thrift compact protocol](https://github.com/apache/thrift/blob/master/doc/specs/thrift-compact-protocol.md):
+//! a `RDF_VarTuple` with the variables followed by a `RDF_DataTuple` for each solution.
+
+use crate::error::{QueryResultsParseError, QueryResultsSyntaxError};
+use oxrdf::vocab::xsd;
+#[cfg(feature = "rdf-star")]
+use oxrdf::Subject;
+use oxrdf::{BlankNode, Literal, NamedNode, Term, TermRef, Variable, VariableRef};
+use std::io::{self, Read, Write};
+use std::str;
+#[cfg(feature = "async-tokio")]
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+const MAX_BUFFER_SIZE: usize = 4096 * 4096;
+const MAX_NESTING: usize = 128;
+const MAX_DECIMAL_SCALE: u32 = 1024;
+
+// Thrift compact protocol types
+const STOP: u8 = 0;
+const BOOLEAN_TRUE: u8 = 1;
+const BOOLEAN_FALSE: u8 = 2;
+const BYTE: u8 = 3;
+const I16: u8 = 4;
+const I32: u8 = 5;
+const I64: u8 = 6;
+const DOUBLE: u8 = 7;
+const BINARY: u8 = 8;
+const LIST: u8 = 9;
+const SET: u8 = 10;
+const MAP: u8 = 11;
+const STRUCT: u8 = 12;
+
+// RDF_Term union fields
+const TERM_IRI: u8 = 1;
+const TERM_BNODE: u8 = 2;
+const TERM_LITERAL: u8 = 3;
+const TERM_PREFIX_NAME: u8 = 4;
+const TERM_VARIABLE: u8 = 5;
+const TERM_ANY: u8 = 6;
+const TERM_UNDEFINED: u8 = 7;
+const TERM_REPEAT: u8 = 8;
+const TERM_TRIPLE: u8 = 9;
+const TERM_VAL_INTEGER: u8 = 10;
+const TERM_VAL_DOUBLE: u8 = 11;
+const TERM_VAL_DECIMAL: u8 = 12;
+
+pub struct WriterThriftSolutionsSerializer<W: Write> {
+    inner: InnerThriftSolutionsSerializer,
+    writer: W,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> WriterThriftSolutionsSerializer<W> {
+    pub fn start(mut writer: W, variables: Vec<Variable>) -> io::Result<Self> {
+        let mut buffer = Vec::new();
+        let inner = InnerThriftSolutionsSerializer::start(&mut buffer, variables);
+        writer.write_all(&buffer)?;
+        buffer.clear();
+        Ok(Self {
+            inner,
+            writer,
+            buffer,
+        })
+    }
+
+    pub fn serialize<'a>(
+        &mut self,
+        solution: impl IntoIterator<Item = (VariableRef<'a>, TermRef<'a>)>,
+    ) -> io::Result<()> {
+        self.inner.write(&mut self.buffer, solution);
+        self.writer.write_all(&self.buffer)?;
+        self.buffer.clear();
+        Ok(())
+    }
+
+    pub fn finish(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(feature = "async-tokio")]
+pub struct TokioAsyncWriterThriftSolutionsSerializer<W: AsyncWrite + Unpin> {
+    inner: InnerThriftSolutionsSerializer,
+    writer: W,
+    buffer: Vec<u8>,
+}
+
+#[cfg(feature = "async-tokio")]
+impl<W: AsyncWrite + Unpin> TokioAsyncWriterThriftSolutionsSerializer<W> {
+    pub async fn start(mut writer: W, variables: Vec<Variable>) -> io::Result<Self> {
+        let mut buffer = Vec::new();
+        let inner = InnerThriftSolutionsSerializer::start(&mut buffer, variables);
+        writer.write_all(&buffer).await?;
+        buffer.clear();
+        Ok(Self {
+            inner,
+            writer,
+            buffer,
+        })
+    }
+
+    pub async fn serialize<'a>(
+        &mut self,
+        solution: impl IntoIterator<Item = (VariableRef<'a>, TermRef<'a>)>,
+    ) -> io::Result<()> {
+        self.inner.write(&mut self.buffer, solution);
+        self.writer.write_all(&self.buffer).await?;
+        self.buffer.clear();
+        Ok(())
+    }
+
+    pub fn finish(self) -> W {
+        self.writer
+    }
+}
+
+struct InnerThriftSolutionsSerializer {
+    variables: Vec<Variable>,
+}
+
+impl InnerThriftSolutionsSerializer {
+    fn start(output: &mut Vec<u8>, variables: Vec<Variable>) -> Self {
+        // RDF_VarTuple { 1: list<RDF_VAR> vars }
+        write_field_header(output, 0, 1, LIST);
+        write_list_header(output, variables.len(), STRUCT);
+        for variable in &variables {
+            write_string_struct(output, variable.as_str());
+        }
+        output.push(STOP);
+        Self { variables }
+    }
+
+    fn write<'a>(
+        &self,
+        output: &mut Vec<u8>,
+        solution: impl IntoIterator<Item = (VariableRef<'a>, TermRef<'a>)>,
+    ) {
+        let mut values = vec![None; self.variables.len()];
+        for (variable, value) in solution {
+            if let Some(position) = self.variables.iter().position(|v| *v == variable) {
+                values[position] = Some(value);
+            }
+        }
+        // RDF_DataTuple { 1: list<RDF_Term> row }
+        write_field_header(output, 0, 1, LIST);
+        write_list_header(output, values.len(), STRUCT);
+        for value in values {
+            write_term(output, value);
+        }
+        output.push(STOP);
+    }
+}
+
+fn write_term(output: &mut Vec<u8>, term: Option<TermRef<'_>>) {
+    match term {
+        Some(TermRef::NamedNode(node)) => {
+            write_field_header(output, 0, TERM_IRI, STRUCT);
+            write_string_struct(output, node.as_str());
+        }
+        Some(TermRef::BlankNode(node)) => {
+            write_field_header(output, 0, TERM_BNODE, STRUCT);
+            write_string_struct(output, node.as_str());
+        }
+        Some(TermRef::Literal(literal)) => {
+            // RDF_Literal { 1: string lex, 2: optional string langtag, 3: optional string datatype }
+            write_field_header(output, 0, TERM_LITERAL, STRUCT);
+            write_field_header(output, 0, 1, BINARY);
+            write_binary(output, literal.value());
+            if let Some(language) = literal.language() {
+                write_field_header(output, 1, 2, BINARY);
+                write_binary(output, language);
+            } else if !literal.is_plain() {
+                write_field_header(output, 1, 3, BINARY);
+                write_binary(output, literal.datatype().as_str());
+            }
+            output.push(STOP);
+        }
+        #[cfg(feature = "rdf-star")]
+        Some(TermRef::Triple(triple)) => {
+            // RDF_Triple { 1: RDF_Term S, 2: RDF_Term P, 3: RDF_Term O }
+            write_field_header(output, 0, TERM_TRIPLE, STRUCT);
+            write_field_header(output, 0, 1, STRUCT);
+            write_term(output, Some(triple.subject.as_ref().into()));
+            write_field_header(output, 1, 2, STRUCT);
+            write_term(output, Some(triple.predicate.as_ref().into()));
+            write_field_header(output, 2, 3, STRUCT);
+            write_term(output, Some(triple.object.as_ref()));
+            output.push(STOP);
+        }
+        None => {
+            write_field_header(output, 0, TERM_UNDEFINED, STRUCT);
+            output.push(STOP);
+        }
+    }
+    output.push(STOP);
+}
+
+/// Writes a struct with a single string field with id 1 like `RDF_IRI`, `RDF_BNode` or `RDF_VAR`
+fn write_string_struct(output: &mut Vec<u8>, value: &str) {
+    write_field_header(output, 0, 1, BINARY);
+    write_binary(output, value);
+    output.push(STOP);
+}
+
+/// Writes a field header using the short form i.e. assuming that the field id is at most 15 more than the previous one
+fn write_field_header(output: &mut Vec<u8>, last_id: u8, id: u8, field_type: u8) {
+    output.push(((id - last_id) << 4) | field_type);
+}
+
+fn write_list_header(output: &mut Vec<u8>, size: usize, element_type: u8) {
+    match u8::try_from(size) {
+        Ok(size) if size < 15 => output.push((size << 4) | element_type),
+        _ => {
+            output.push(0xF0 | element_type);
+            write_varint(output, size.try_into().unwrap_or(u64::MAX));
+        }
+    }
+}
+
+fn write_binary(output: &mut Vec<u8>, value: &str) {
+    write_varint(output, value.len().try_into().unwrap_or(u64::MAX));
+    output.extend_from_slice(value.as_bytes());
+}
+
+fn write_varint(output: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        output.push(value.to_le_bytes()[0] | 0x80);
+        value >>= 7;
+    }
+    output.push(value.to_le_bytes()[0]);
+}
+
+pub struct ReaderThriftSolutionsParser<R: Read> {
+    reader: R,
+    inner: ThriftInnerSolutionsParser,
+    buffer: Vec<u8>,
+}
+
+impl<R: Read> ReaderThriftSolutionsParser<R> {
+    #[allow(clippy::redundant_closure_for_method_calls)] // The method is not generic enough over lifetimes
+    pub fn read(mut reader: R) -> Result<(Vec<Variable>, Self), QueryResultsParseError> {
+        let mut struct_reader = StructReader::new();
+        let mut buffer = Vec::new();
+        let variables = struct_reader
+            .next_from_reader(&mut buffer, &mut reader, |decoder| decoder.variables())?
+            .ok_or_else(empty_file_error)?;
+        Ok((
+            variables.clone(),
+            Self {
+                reader,
+                inner: ThriftInnerSolutionsParser::new(struct_reader, &variables),
+                buffer,
+            },
+        ))
+    }
+
+    pub fn parse_next(&mut self) -> Result<Option<Vec<Option<Term>>>, QueryResultsParseError> {
+        let variable_count = self.inner.variable_count;
+        self.inner
+            .struct_reader
+            .next_from_reader(&mut self.buffer, &mut self.reader, |decoder| {
+                decoder.solution(variable_count)
+            })
+    }
+}
+
+#[cfg(feature = "async-tokio")]
+pub struct TokioAsyncReaderThriftSolutionsParser<R: AsyncRead + Unpin> {
+    reader: R,
+    inner: ThriftInnerSolutionsParser,
+    buffer: Vec<u8>,
+}
+
+#[cfg(feature = "async-tokio")]
+impl<R: AsyncRead + Unpin> TokioAsyncReaderThriftSolutionsParser<R> {
+    #[allow(clippy::redundant_closure_for_method_calls)] // The method is not generic enough over lifetimes
+    pub async fn read(mut reader: R) -> Result<(Vec<Variable>, Self), QueryResultsParseError> {
+        let mut struct_reader = StructReader::new();
+        let mut buffer = Vec::new();
+        let variables = struct_reader
+            .next_from_tokio_async_read(&mut buffer, &mut reader, |decoder| decoder.variables())
+            .await?
+            .ok_or_else(empty_file_error)?;
+        Ok((
+            variables.clone(),
+            Self {
+                reader,
+                inner: ThriftInnerSolutionsParser::new(struct_reader, &variables),
+                buffer,
+            },
+        ))
+    }
+
+    pub async fn parse_next(
+        &mut self,
+    ) -> Result<Option<Vec<Option<Term>>>, QueryResultsParseError> {
+        let variable_count = self.inner.variable_count;
+        self.inner
+            .struct_reader
+            .next_from_tokio_async_read(&mut self.buffer, &mut self.reader, |decoder| {
+                decoder.solution(variable_count)
+            })
+            .await
+    }
+}
+
+pub struct SliceThriftSolutionsParser<'a> {
+    slice: &'a [u8],
+    inner: ThriftInnerSolutionsParser,
+}
+
+impl<'a> SliceThriftSolutionsParser<'a> {
+    #[allow(clippy::redundant_closure_for_method_calls)] // The method is not generic enough over lifetimes
+    pub fn read(slice: &'a [u8]) -> Result<(Vec<Variable>, Self), QueryResultsSyntaxError> {
+        let mut struct_reader = StructReader::new();
+        let variables = struct_reader
+            .next_from_slice(slice, |decoder| decoder.variables())?
+            .ok_or_else(empty_file_error)?;
+        Ok((
+            variables.clone(),
+            Self {
+                slice,
+                inner: ThriftInnerSolutionsParser::new(struct_reader, &variables),
+            },
+        ))
+    }
+
+    pub fn parse_next(&mut self) -> Result<Option<Vec<Option<Term>>>, QueryResultsSyntaxError> {
+        let variable_count = self.inner.variable_count;
+        self.inner
+            .struct_reader
+            .next_from_slice(self.slice, |decoder| decoder.solution(variable_count))
+    }
+}
+
+fn empty_file_error() -> QueryResultsSyntaxError {
+    QueryResultsSyntaxError::msg(
+        "Empty SPARQL results Thrift file, it should start with the list of variables",
+    )
+}
+
+struct ThriftInnerSolutionsParser {
+    struct_reader: StructReader,
+    variable_count: usize,
+}
+
+impl ThriftInnerSolutionsParser {
+    fn new(struct_reader: StructReader, variables: &[Variable]) -> Self {
+        Self {
+            struct_reader,
+            variable_count: variables.len(),
+        }
+    }
+}
+
+/// Reads the top-level structs of the file
+///
+/// The file has no delimiters so we try to decode a struct from the buffered data and read more data if it is incomplete.
+struct StructReader {
+    buffer_start: usize,
+    buffer_end: usize,
+    is_ended: bool,
+}
+
+impl StructReader {
+    fn new() -> Self {
+        Self {
+            buffer_start: 0,
+            buffer_end: 0,
+            is_ended: false,
+        }
+    }
+
+    fn next_from_reader<T>(
+        &mut self,
+        buffer: &mut Vec<u8>,
+        reader: &mut impl Read,
+        decode: impl Fn(&mut Decoder<'_>) -> Result<T, DecodeError>,
+    ) -> Result<Option<T>, QueryResultsParseError> {
+        if self.is_ended {
+            return Ok(None);
+        }
+        loop {
+            if let Some(result) = self.decode_buffered(buffer, &decode)? {
+                return Ok(Some(result));
+            }
+            self.prepare_buffer(buffer)?;
+            let read = reader.read(&mut buffer[self.buffer_end..])?;
+            if read == 0 {
+                return self.end().map_err(Into::into);
+            }
+            self.buffer_end += read;
+        }
+    }
+
+    #[cfg(feature = "async-tokio")]
+    async fn next_from_tokio_async_read<T>(
+        &mut self,
+        buffer: &mut Vec<u8>,
+        reader: &mut (impl AsyncRead + Unpin),
+        decode: impl Fn(&mut Decoder<'_>) -> Result<T, DecodeError>,
+    ) -> Result<Option<T>, QueryResultsParseError> {
+        if self.is_ended {
+            return Ok(None);
+        }
+        loop {
+            if let Some(result) = self.decode_buffered(buffer, &decode)? {
+                return Ok(Some(result));
+            }
+            self.prepare_buffer(buffer)?;
+            let read = reader.read(&mut buffer[self.buffer_end..]).await?;
+            if read == 0 {
+                return self.end().map_err(Into::into);
+            }
+            self.buffer_end += read;
+        }
+    }
+
+    fn next_from_slice<T>(
+        &mut self,
+        slice: &[u8],
+        decode: impl Fn(&mut Decoder<'_>) -> Result<T, DecodeError>,
+    ) -> Result<Option<T>, QueryResultsSyntaxError> {
+        if self.is_ended {
+            return Ok(None);
+        }
+        self.buffer_end = slice.len();
+        if let Some(result) = self.decode_buffered(slice, &decode)? {
+            return Ok(Some(result));
+        }
+        self.end()
+    }
+
+    /// Returns `None` if more data is required to decode the next struct
+    fn decode_buffered<T>(
+        &mut self,
+        buffer: &[u8],
+        decode: &impl Fn(&mut Decoder<'_>) -> Result<T, DecodeError>,
+    ) -> Result<Option<T>, QueryResultsSyntaxError> {
+        if self.buffer_start == self.buffer_end {
+            return Ok(None);
+        }
+        let mut decoder = Decoder {
+            data: &buffer[self.buffer_start..self.buffer_end],
+            position: 0,
+        };
+        match decode(&mut decoder) {
+            Ok(result) => {
+                self.buffer_start += decoder.position;
+                Ok(Some(result))
+            }
+            Err(DecodeError::Incomplete) => Ok(None),
+            Err(DecodeError::Syntax(e)) => {
+                // We can't recover from a binary encoding error
+                self.is_ended = true;
+                Err(e)
+            }
+        }
+    }
+
+    fn prepare_buffer(&mut self, buffer: &mut Vec<u8>) -> io::Result<()> {
+        if self.buffer_start > 0 {
+            buffer.copy_within(self.buffer_start..self.buffer_end, 0);
+            self.buffer_end -= self.buffer_start;
+            self.buffer_start = 0;
+        }
+        // We at least double the buffer size to avoid decoding again and again large structs
+        let min_size = self.buffer_end + self.buffer_end.max(1024);
+        if min_size > buffer.len() {
+            if min_size > MAX_BUFFER_SIZE {
+                self.is_ended = true;
+                return Err(io::Error::new(
+                    io::ErrorKind::OutOfMemory,
+                    format!("Reached the buffer maximal size of {MAX_BUFFER_SIZE}"),
+                ));
+            }
+            buffer.resize(min_size, 0);
+        }
+        Ok(())
+    }
+
+    fn end<T>(&mut self) -> Result<Option<T>, QueryResultsSyntaxError> {
+        self.is_ended = true;
+        if self.buffer_start == self.buffer_end {
+            Ok(None)
+        } else {
+            Err(QueryResultsSyntaxError::msg(
+                "Unexpected end of the SPARQL results Thrift file",
+            ))
+        }
+    }
+}
+
+enum DecodeError {
+    /// More data is needed
+    Incomplete,
+    Syntax(QueryResultsSyntaxError),
+}
+
+fn syntax_error(msg: impl Into<String>) -> DecodeError {
+    DecodeError::Syntax(QueryResultsSyntaxError::msg(msg))
+}
+
+/// Decodes Thrift compact protocol values from a byte slice
+struct Decoder<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Decoder<'a> {
+    /// Decodes a `RDF_VarTuple`
+    fn variables(&mut self) -> Result<Vec<Variable>, DecodeError> {
+        let mut variables = Vec::new();
+        let mut last_id = 0;
+        while let Some((id, field_type)) = self.field_header(&mut last_id)? {
+            if (id, field_type) != (1, LIST) {
+                self.skip(field_type, 0)?;
+                continue;
+            }
+            let (size, element_type) = self.list_header()?;
+            if element_type != STRUCT {
+                return Err(syntax_error(
+                    "The RDF_VarTuple variables should be RDF_VAR structs",
+                ));
+            }
+            for _ in 0..size {
+                let name = self.string_struct()?;
+                let variable = Variable::new(name).map_err(|e| {
+                    syntax_error(format!("Invalid variable declaration '{name}': {e}"))
+                })?;
+                if variables.contains(&variable) {
+                    return Err(syntax_error(format!(
+                        "The variable {variable} is declared twice"
+                    )));
+                }
+                variables.push(variable);
+            }
+        }
+        Ok(variables)
+    }
+
+    /// Decodes a `RDF_DataTuple`
+    fn solution(&mut self, variable_count: usize) -> Result<Vec<Option<Term>>, DecodeError> {
+        let mut values = Vec::new();
+        let mut last_id = 0;
+        while let Some((id, field_type)) = self.field_header(&mut last_id)? {
+            if (id, field_type) != (1, LIST) {
+                self.skip(field_type, 0)?;
+                continue;
+            }
+            let (size, element_type) = self.list_header()?;
+            if element_type != STRUCT {
+                return Err(syntax_error(
+                    "The RDF_DataTuple values should be RDF_Term structs",
+                ));
+            }
+            for _ in 0..size {
+                values.push(self.term(0)?);
+            }
+        }
+        if values.len() != variable_count {
+            return Err(syntax_error(format!(
+                "This solution has {} values but {variable_count} variables have been declared",
+                values.len()
+            )));
+        }
+        Ok(values)
+    }
+
+    /// Decodes a `RDF_Term`, `None` meaning an unbound value
+    fn term(&mut self, depth: usize) -> Result<Option<Term>, DecodeError> {
+        if depth > MAX_NESTING {
+            return Err(syntax_error("Too many nested triple terms"));
+        }
+        let mut last_id = 0;
+        let Some((id, field_type)) = self.field_header(&mut last_id)? else {
+            return Err(syntax_error("Empty RDF_Term"));
+        };
+        let term = match (id, field_type) {
+            (TERM_IRI, STRUCT) => {
+                let iri = self.string_struct()?;
+                Some(
+                    NamedNode::new(iri)
+                        .map_err(|e| syntax_error(format!("Invalid IRI '{iri}': {e}")))?
+                        .into(),
+                )
+            }
+            (TERM_BNODE, STRUCT) => {
+                let id = self.string_struct()?;
+                Some(
+                    BlankNode::new(id)
+                        .map_err(|e| {
+                            syntax_error(format!("Invalid blank node identifier '{id}': {e}"))
+                        })?
+                        .into(),
+                )
+            }
+            (TERM_LITERAL, STRUCT) => Some(self.literal()?.into()),
+            (TERM_UNDEFINED, STRUCT) => {
+                self.skip(STRUCT, 0)?;
+                None
+            }
+            #[cfg(feature = "rdf-star")]
+            (TERM_TRIPLE, STRUCT) => Some(self.triple(depth)?.into()),
+            #[cfg(not(feature = "rdf-star"))]
+            (TERM_TRIPLE, _) => {
+                return Err(syntax_error(
+                    "Triple terms are only supported when the 'rdf-star' feature is enabled",
+                ))
+            }
+            (TERM_VAL_INTEGER, I64) => {
+                Some(Literal::new_typed_literal(self.i64()?.to_string(), xsd::INTEGER).into())
+            }
+            (TERM_VAL_DOUBLE, DOUBLE) => Some(
+                Literal::new_typed_literal(double_lexical_form(self.double()?), xsd::DOUBLE).into(),
+            ),
+            (TERM_VAL_DECIMAL, STRUCT) => Some(self.decimal()?.into()),
+            (TERM_PREFIX_NAME, _) => {
+                return Err(syntax_error(
+                    "Prefixed names are not allowed in SPARQL results",
+                ))
+            }
+            (TERM_VARIABLE | TERM_ANY | TERM_REPEAT, _) => {
+                return Err(syntax_error(
+                    "Variables, ANY and REPEAT terms are not allowed in SPARQL results",
+                ))
+            }
+            _ => {
+                return Err(syntax_error(format!(
+                    "Unexpected RDF_Term field {id} of type {field_type}"
+                )))
+            }
+        };
+        if self.field_header(&mut last_id)?.is_some() {
+            return Err(syntax_error("A RDF_Term must have a single field"));
+        }
+        Ok(term)
+    }
+
+    /// Decodes a `RDF_Literal`
+    fn literal(&mut self) -> Result<Literal, DecodeError> {
+        let (mut value, mut language, mut datatype) = (None, None, None);
+        let mut last_id = 0;
+        while let Some((id, field_type)) = self.field_header(&mut last_id)? {
+            match (id, field_type) {
+                (1, BINARY) => value = Some(self.string()?),
+                (2, BINARY) => language = Some(self.string()?),
+                (3, BINARY) => datatype = Some(self.string()?),
+                (4, _) => {
+                    return Err(syntax_error(
+                        "Prefixed name datatypes are not allowed in SPARQL results",
+                    ))
+                }
+                _ => self.skip(field_type, 0)?,
+            }
+        }
+        let value = value.ok_or_else(|| syntax_error("A RDF_Literal must have a lexical form"))?;
+        Ok(
+            if let Some(language) = language.filter(|language| !language.is_empty()) {
+                Literal::new_language_tagged_literal(value, language)
+                    .map_err(|e| syntax_error(format!("Invalid language tag '{language}': {e}")))?
+            } else if let Some(datatype) = datatype.filter(|datatype| !datatype.is_empty()) {
+                Literal::new_typed_literal(
+                    value,
+                    NamedNode::new(datatype).map_err(|e| {
+                        syntax_error(format!("Invalid datatype IRI '{datatype}': {e}"))
+                    })?,
+                )
+            } else {
+                Literal::new_simple_literal(value)
+            },
+        )
+    }
+
+    /// Decodes a `RDF_Decimal` i.e. `value * 10^-scale`
+    fn decimal(&mut self) -> Result<Literal, DecodeError> {
+        let (mut value, mut scale) = (None, None);
+        let mut last_id = 0;
+        while let Some((id, field_type)) = self.field_header(&mut last_id)? {
+            match (id, field_type) {
+                (1, I64) => value = Some(self.i64()?),
+                (2, I32) => scale = Some(self.i32()?),
+                _ => self.skip(field_type, 0)?,
+            }
+        }
+        let (Some(value), Some(scale)) = (value, scale) else {
+            return Err(syntax_error("A RDF_Decimal must have a value and a scale"));
+        };
+        if scale.unsigned_abs() > MAX_DECIMAL_SCALE {
+            return Err(syntax_error(format!("Too large decimal scale {scale}")));
+        }
+        let sign = if value < 0 { "-" } else { "" };
+        let digits = value.unsigned_abs().to_string();
+        let scale_len = usize::try_from(scale.unsigned_abs())
+            .map_err(|_| syntax_error(format!("Too large decimal scale {scale}")))?;
+        let lexical_form = if scale <= 0 {
+            format!("{sign}{digits}{}", "0".repeat(scale_len))
+        } else {
+            let digits = format!(
+                "{}{digits}",
+                "0".repeat((scale_len + 1).saturating_sub(digits.len()))
+            );
+            let (integer, fraction) = digits.split_at(digits.len() - scale_len);
+            format!("{sign}{integer}.{fraction}")
+        };
+        Ok(Literal::new_typed_literal(lexical_form, xsd::DECIMAL))
+    }
+
+    /// Decodes a `RDF_Triple`
+    #[cfg(feature = "rdf-star")]
+    fn triple(&mut self, depth: usize) -> Result<oxrdf::Triple, DecodeError> {
+        let (mut subject, mut predicate, mut object) = (None, None, None);
+        let mut last_id = 0;
+        while let Some((id, field_type)) = self.field_header(&mut last_id)? {
+            match (id, field_type) {
+                (1, STRUCT) => subject = self.term(depth + 1)?,
+                (2, STRUCT) => predicate = self.term(depth + 1)?,
+                (3, STRUCT) => object = self.term(depth + 1)?,
+                _ => self.skip(field_type, 0)?,
+            }
+        }
+        let subject = match subject {
+            Some(Term::NamedNode(subject)) => Subject::NamedNode(subject),
+            Some(Term::BlankNode(subject)) => Subject::BlankNode(subject),
+            Some(Term::Triple(subject)) => Subject::Triple(subject),
+            Some(Term::Literal(_)) | None => {
+                return Err(syntax_error(
+                    "The subject of a triple term must be an IRI, a blank node or a triple",
+                ))
+            }
+        };
+        let Some(Term::NamedNode(predicate)) = predicate else {
+            return Err(syntax_error(
+                "The predicate of a triple term must be an IRI",
+            ));
+        };
+        let Some(object) = object else {
+            return Err(syntax_error("A triple term must have an object"));
+        };
+        Ok(oxrdf::Triple::new(subject, predicate, object))
+    }
+
+    /// Decodes a struct with a required string field with id 1 like `RDF_IRI`, `RDF_BNode` or `RDF_VAR`
+    fn string_struct(&mut self) -> Result<&'a str, DecodeError> {
+        let mut value = None;
+        let mut last_id = 0;
+        while let Some((id, field_type)) = self.field_header(&mut last_id)? {
+            if (id, field_type) == (1, BINARY) {
+                value = Some(self.string()?);
+            } else {
+                self.skip(field_type, 0)?;
+            }
+        }
+        value.ok_or_else(|| syntax_error("Missing required string field"))
+    }
+
+    /// Decodes a field header and returns its id and type or `None` if it is the end of the struct
+    ///
+    /// The field ids that do not fit in a byte are mapped to 0, they are never used by the RDF Thrift schema.
+    fn field_header(&mut self, last_id: &mut i16) -> Result<Option<(u8, u8)>, DecodeError> {
+        let header = self.byte()?;
+        if header == STOP {
+            return Ok(None);
+        }
+        let (delta, field_type) = (header >> 4, header & 0x0F);
+        *last_id = if delta == 0 {
+            i16::try_from(self.i64()?).map_err(|_| syntax_error("Invalid field id"))?
+        } else {
+            last_id
+                .checked_add(delta.into())
+                .ok_or_else(|| syntax_error("Invalid field id"))?
+        };
+        Ok(Some((u8::try_from(*last_id).unwrap_or(0), field_type)))
+    }
+
+    /// Decodes a list or set header and returns its size and element type
+    fn list_header(&mut self) -> Result<(usize, u8), DecodeError> {
+        let header = self.byte()?;
+        let (size, element_type) = (header >> 4, header & 0x0F);
+        let size = if size == 15 {
+            usize::try_from(self.varint()?).map_err(|_| syntax_error("Too large list"))?
+        } else {
+            size.into()
+        };
+        Ok((size, element_type))
+    }
+
+    /// Skips a value of the given type
+    fn skip(&mut self, value_type: u8, depth: usize) -> Result<(), DecodeError> {
+        if depth > MAX_NESTING {
+            return Err(syntax_error("Too many nested Thrift values"));
+        }
+        match value_type {
+            BOOLEAN_TRUE | BOOLEAN_FALSE => (), // The value is in the field type
+            BYTE => {
+                self.byte()?;
+            }
+            I16 | I32 | I64 => {
+                self.varint()?;
+            }
+            DOUBLE => {
+                self.bytes(8)?;
+            }
+            BINARY => {
+                self.binary()?;
+            }
+            LIST | SET => {
+                let (size, element_type) = self.list_header()?;
+                for _ in 0..size {
+                    if matches!(element_type, BOOLEAN_TRUE | BOOLEAN_FALSE) {
+                        // Booleans inside of collections are encoded as a byte
+                        self.byte()?;
+                    } else {
+                        self.skip(element_type, depth + 1)?;
+                    }
+                }
+            }
+            MAP => {
+                let size = self.varint()?;
+                if size > 0 {
+                    let types = self.byte()?;
+                    let (key_type, value_type) = (types >> 4, types & 0x0F);
+                    for _ in 0..size {
+                        self.skip(key_type, depth + 1)?;
+                        self.skip(value_type, depth + 1)?;
+                    }
+                }
+            }
+            STRUCT => {
+                let mut last_id = 0;
+                while let Some((_, field_type)) = self.field_header(&mut last_id)? {
+                    self.skip(field_type, depth + 1)?;
+                }
+            }
+            _ => return Err(syntax_error(format!("Unknown Thrift type {value_type}"))),
+        }
+        Ok(())
+    }
+
+    fn byte(&mut self) -> Result<u8, DecodeError> {
+        let byte = *self
+            .data
+            .get(self.position)
+            .ok_or(DecodeError::Incomplete)?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        let end = self
+            .position
+            .checked_add(len)
+            .ok_or(DecodeError::Incomplete)?;
+        let bytes = self
+            .data
+            .get(self.position..end)
+            .ok_or(DecodeError::Incomplete)?;
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn varint(&mut self) -> Result<u64, DecodeError> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7F) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(syntax_error("Too long varint"))
+    }
+
+    fn i64(&mut self) -> Result<i64, DecodeError> {
+        // Zigzag encoding
+        let value = self.varint()?;
+        let magnitude = i64::try_from(value >> 1).map_err(|_| syntax_error("Invalid varint"))?;
+        Ok(if value & 1 == 0 {
+            magnitude
+        } else {
+            -magnitude - 1
+        })
+    }
+
+    fn i32(&mut self) -> Result<i32, DecodeError> {
+        i32::try_from(self.i64()?).map_err(|_| syntax_error("Too large 32 bits integer"))
+    }
+
+    fn double(&mut self) -> Result<f64, DecodeError> {
+        Ok(f64::from_le_bytes(
+            self.bytes(8)?
+                .try_into()
+                .map_err(|_| syntax_error("Invalid double"))?,
+        ))
+    }
+
+    fn binary(&mut self) -> Result<&'a [u8], DecodeError> {
+        let len = usize::try_from(self.varint()?).map_err(|_| syntax_error("Too large string"))?;
+        self.bytes(len)
+    }
+
+    fn string(&mut self) -> Result<&'a str, DecodeError> {
+        str::from_utf8(self.binary()?)
+            .map_err(|e| syntax_error(format!("Invalid UTF-8 string: {e}")))
+    }
+}
+
+fn double_lexical_form(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".into()
+    } else if value.is_infinite() {
+        if value.is_sign_positive() {
+            "INF"
+        } else {
+            "-INF"
+        }
+        .into()
+    } else {
+        format!("{value:E}")
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    #[test]
+    fn test_roundtrip() -> Result<(), Box<dyn Error>> {
+        let variables = vec![Variable::new("x")?, Variable::new("y")?];
+        let solutions: Vec<Vec<Option<Term>>> = vec![
+            vec![
+                Some(NamedNode::new("http://example.com/x")?.into()),
+                Some(Literal::new_simple_literal("String").into()),
+            ],
+            vec![
+                Some(BlankNode::new("b0")?.into()),
+                Some(Literal::new_language_tagged_literal("String", "en")?.into()),
+            ],
+            vec![
+                None,
+                Some(Literal::new_typed_literal("123", xsd::INTEGER).into()),
+            ],
+            vec![None, None],
+        ];
+        let mut buffer = Vec::new();
+        let serializer = InnerThriftSolutionsSerializer::start(&mut buffer, variables.clone());
+        for solution in &solutions {
+            serializer.write(
+                &mut buffer,
+                variables
+                    .iter()
+                    .zip(solution)
+                    .filter_map(|(v, s)| Some((v.as_ref(), s.as_ref()?.as_ref()))),
+            );
+        }
+        // We parse with a reader to test the buffer refills
+        let (parsed_variables, mut parser) = ReaderThriftSolutionsParser::read(buffer.as_slice())?;
+        assert_eq!(parsed_variables, variables);
+        for solution in solutions {
+            assert_eq!(parser.parse_next()?, Some(solution));
+        }
+        assert_eq!(parser.parse_next()?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_value_encodings() -> Result<(), Box<dyn Error>> {
+        // Jena encodes some literals as values: -2 as an integer, 1.5 as a double and 1.25 as a decimal
+        let mut file = vec![0x19, 0x1C, 0x18, 0x01, b'x', 0x00, 0x00];
+        for term in [
+            [0xA6, 0x03].as_slice(),
+            &[0xB7, 0, 0, 0, 0, 0, 0, 0xF8, 0x3F],
+            &[0xCC, 0x16, 0xFA, 0x01, 0x15, 0x04, 0x00],
+        ] {
+            file.extend_from_slice(&[0x19, 0x1C]);
+            file.extend_from_slice(term);
+            file.extend_from_slice(&[0x00, 0x00]);
+        }
+        let (_, mut parser) = SliceThriftSolutionsParser::read(&file)?;
+        for expected in [
+            Literal::new_typed_literal("-2", xsd::INTEGER),
+            Literal::new_typed_literal("1.5E0", xsd::DOUBLE),
+            Literal::new_typed_literal("1.25", xsd::DECIMAL),
+        ] {
+            assert_eq!(parser.parse_next()?, Some(vec![Some(expected.into())]));
+        }
+        assert_eq!(parser.parse_next()?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bad_thrift() {
+        for bad in [
+            b"".as_slice(),
+            &[0x19, 0x1C, 0x18, 0x01, b'x', 0x00, 0x00, 0x19, 0x1C],
+            &[0x19, 0x1C, 0x18, 0x01, b'?', 0x00, 0x00],
+            &[
+                0x19, 0x2C, 0x18, 0x01, b'x', 0x00, 0x18, 0x01, b'x', 0x00, 0x00,
+            ],
+            &[0x19, 0x1C, 0x18, 0x01, b'x', 0x00, 0x00, 0x19, 0x0C, 0x00],
+            &[
+                0x19, 0x1C, 0x18, 0x01, b'x', 0x00, 0x00, 0x19, 0x1C, 0x00, 0x00,
+            ],
+            &[
+                0x19, 0x1C, 0x18, 0x01, b'x', 0x00, 0x00, 0x19, 0x1C, 0x5C, 0x00, 0x00, 0x00,
+            ],
+        ] {
+            let result = SliceThriftSolutionsParser::read(bad).and_then(|(_, mut parser)| {
+                while parser.parse_next()?.is_some() {}
+                Ok(())
+            });
+            assert!(result.is_err(), "{bad:?}");
+        }
+    }
+}
@@ -0,0 +1,604 @@
+use crate::service::{wrap_service_error, DefaultServiceHandler, ServiceHandler};
+use crate::{
+    QueryEvaluationError, QueryEvaluator, QueryResults, QuerySolution, QuerySolutionIter,
+    QueryableDataset,
+};
+use futures_core::Stream;
+use oxrdf::{NamedNode, Triple, Variable};
+use spargebra::algebra::GraphPattern;
+use spargebra::Query;
+use std::cell::Cell;
+use std::error::Error;
+use std::future::Future;
+use std::panic::resume_unwind;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use std::{mem, vec};
+use tokio::runtime::Handle;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::{spawn_blocking, JoinError, JoinHandle};
+
+/// Number of solutions or triples sent at once from the evaluation thread to the async consumer.
+const BATCH_SIZE: usize = 256;
+
+/// Number of batches buffered while the async consumer is not reading them.
+const BATCH_BUFFER_SIZE: usize = 4;
+
+/// Implementation of [`QueryEvaluator::execute_tokio_async`]
+pub(crate) async fn execute(
+    evaluator: QueryEvaluator,
+    dataset: impl QueryableDataset + Send + 'static,
+    query: Query,
+) -> Result<TokioAsyncQueryResults, QueryEvaluationError> {
+    let (header_sender, header_receiver) = oneshot::channel();
+    let task = spawn_blocking(move || {
+        let _guard = AsyncEvaluationGuard::new();
+        evaluate(&evaluator, dataset, &query, header_sender);
+    });
+    let Ok(header) = header_receiver.await else {
+        // The evaluation task has ended without sending anything
+        join_task(task).await?;
+        return Err(QueryEvaluationError::Cancelled);
+    };
+    let header = header?;
+    Ok(match header {
+        Header::Solutions(variables, receiver) => {
+            TokioAsyncQueryResults::Solutions(TokioAsyncQuerySolutionIter {
+                variables,
+                inner: BatchReceiver::new(receiver, task),
+            })
+        }
+        Header::Boolean(value) => TokioAsyncQueryResults::Boolean(value),
+        Header::Graph(receiver) => TokioAsyncQueryResults::Graph(TokioAsyncQueryTripleIter {
+            inner: BatchReceiver::new(receiver, task),
+        }),
+    })
+}
+
+/// Results of a [SPARQL query](https://www.w3.org/TR/sparql11-query/) evaluated using [`QueryEvaluator::execute_tokio_async`].
+pub enum TokioAsyncQueryResults {
+    /// Results of a [SELECT](https://www.w3.org/TR/sparql11-query/#select) query.
+    Solutions(TokioAsyncQuerySolutionIter),
+    /// Result of a [ASK](https://www.w3.org/TR/sparql11-query/#ask) query.
+    Boolean(bool),
+    /// Results of a [CONSTRUCT](https://www.w3.org/TR/sparql11-query/#construct) or [DESCRIBE](https://www.w3.org/TR/sparql11-query/#describe) query.
+    Graph(TokioAsyncQueryTripleIter),
+}
+
+/// A [`Stream`] of [`QuerySolution`]s.
+///
+/// Can be built using [`QueryEvaluator::execute_tokio_async`].
+/// Dropping it stops the evaluation.
+#[must_use]
+pub struct TokioAsyncQuerySolutionIter {
+    variables: Arc<[Variable]>,
+    inner: BatchReceiver<QuerySolution>,
+}
+
+impl TokioAsyncQuerySolutionIter {
+    /// The variables used in the solutions.
+    #[inline]
+    pub fn variables(&self) -> &[Variable] {
+        &self.variables
+    }
+}
+
+impl Stream for TokioAsyncQuerySolutionIter {
+    type Item = Result<QuerySolution, QueryEvaluationError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next(cx)
+    }
+}
+
+/// A [`Stream`] of the triples that compose a graph solution.
+///
+/// Can be built using [`QueryEvaluator::execute_tokio_async`].
+/// Dropping it stops the evaluation.
+#[must_use]
+pub struct TokioAsyncQueryTripleIter {
+    inner: BatchReceiver<Triple>,
+}
+
+impl Stream for TokioAsyncQueryTripleIter {
+    type Item = Result<Triple, QueryEvaluationError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next(cx)
+    }
+}
+
+/// Async handler for [SPARQL 1.1 Federated Query](https://www.w3.org/TR/sparql11-federated-query/) SERVICEs.
+///
+/// It is the async equivalent of [`ServiceHandler`] and allows to use async HTTP clients to call services.
+/// Should be given to [`QueryEvaluator::with_tokio_async_service_handler`].
+///
+/// ```
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use futures_util::StreamExt;
+/// use oxrdf::{Dataset, Literal, NamedNode, Variable};
+/// use sparesults::QuerySolution;
+/// use spareval::{
+///     QueryEvaluator, QuerySolutionIter, TokioAsyncQueryResults, TokioAsyncServiceHandler,
+/// };
+/// use spargebra::algebra::GraphPattern;
+/// use spargebra::Query;
+/// use std::convert::Infallible;
+/// use std::iter::once;
+/// use std::sync::Arc;
+///
+/// struct TestServiceHandler {}
+///
+/// impl TokioAsyncServiceHandler for TestServiceHandler {
+///     type Error = Infallible;
+///
+///     async fn handle(
+///         &self,
+///         _pattern: GraphPattern,
+///         _base_iri: Option<String>,
+///     ) -> Result<QuerySolutionIter, Self::Error> {
+///         // Always return a single binding foo -> 1
+///         let variables = [Variable::new_unchecked("foo")].into();
+///         Ok(QuerySolutionIter::new(
+///             Arc::clone(&variables),
+///             once(Ok(QuerySolution::from((
+///                 variables,
+///                 vec![Some(Literal::from(1).into())],
+///             )))),
+///         ))
+///     }
+/// }
+///
+/// let evaluator = QueryEvaluator::default().with_tokio_async_service_handler(
+///     NamedNode::new("http://example.com/service")?,
+///     TestServiceHandler {},
+/// );
+/// let query = Query::parse(
+///     "SELECT ?foo WHERE { SERVICE <http://example.com/service> {} }",
+///     None,
+/// )?;
+/// if let TokioAsyncQueryResults::Solutions(mut solutions) =
+///     evaluator.execute_tokio_async(Dataset::new(), &query).await?
+/// {
+///     assert_eq!(
+///         solutions.next().await.unwrap()?.get("foo"),
+///         Some(&Literal::from(1).into())
+///     );
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub trait TokioAsyncServiceHandler: Send + Sync {
+    /// The service evaluation error.
+    type Error: Error + Send + Sync + 'static;
+
+    /// Evaluates a [`Query`](spargebra::Query) against the service.
+    fn handle(
+        &self,
+        pattern: GraphPattern,
+        base_iri: Option<String>,
+    ) -> impl Future<Output = Result<QuerySolutionIter, Self::Error>>;
+
+    /// If the handler is able to evaluate service patterns of the form `Join(Values, pattern)`.
+    ///
+    /// See [`ServiceHandler::supports_bound_join`].
+    #[inline]
+    fn supports_bound_join(&self) -> bool {
+        false
+    }
+}
+
+/// Async default handler for [SPARQL 1.1 Federated Query](https://www.w3.org/TR/sparql11-federated-query/) SERVICEs.
+///
+/// It is the async equivalent of [`DefaultServiceHandler`].
+/// Should be given to [`QueryEvaluator::with_default_tokio_async_service_handler`].
+pub trait TokioAsyncDefaultServiceHandler: Send + Sync {
+    /// The service evaluation error.
+    type Error: Error + Send + Sync + 'static;
+
+    /// Evaluates a [`GraphPattern`] against a given service identified by a [`NamedNode`].
+    fn handle(
+        &self,
+        service_name: NamedNode,
+        pattern: GraphPattern,
+        base_iri: Option<String>,
+    ) -> impl Future<Output = Result<QuerySolutionIter, Self::Error>>;
+
+    /// If the handler is able to evaluate service patterns of the form `Join(Values, pattern)`.
+    ///
+    /// See [`ServiceHandler::supports_bound_join`].
+    #[inline]
+    fn supports_bound_join(&self) -> bool {
+        false
+    }
+}
+
+/// Runs the async service handler futures on the current Tokio runtime
+pub(crate) struct BlockingServiceHandler<H>(pub H);
+
+impl<H: TokioAsyncServiceHandler> ServiceHandler for BlockingServiceHandler<H> {
+    type Error = QueryEvaluationError;
+
+    fn handle(
+        &self,
+        pattern: GraphPattern,
+        base_iri: Option<String>,
+    ) -> Result<QuerySolutionIter, QueryEvaluationError> {
+        current_runtime()?
+            .block_on(self.0.handle(pattern, base_iri))
+            .map_err(wrap_service_error)
+    }
+
+    fn supports_bound_join(&self) -> bool {
+        self.0.supports_bound_join()
+    }
+}
+
+impl<H: TokioAsyncDefaultServiceHandler> DefaultServiceHandler for BlockingServiceHandler<H> {
+    type Error = QueryEvaluationError;
+
+    fn handle(
+        &self,
+        service_name: NamedNode,
+        pattern: GraphPattern,
+        base_iri: Option<String>,
+    ) -> Result<QuerySolutionIter, QueryEvaluationError> {
+        current_runtime()?
+            .block_on(self.0.handle(service_name, pattern, base_iri))
+            .map_err(wrap_service_error)
+    }
+
+    fn supports_bound_join(&self) -> bool {
+        self.0.supports_bound_join()
+    }
+}
+
+thread_local! {
+    static IN_ASYNC_EVALUATION: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Marks the current thread as evaluating a query for [`QueryEvaluator::execute_tokio_async`]
+struct AsyncEvaluationGuard;
+
+impl AsyncEvaluationGuard {
+    fn new() -> Self {
+        IN_ASYNC_EVALUATION.with(|flag| flag.set(true));
+        Self
+    }
+}
+
+impl Drop for AsyncEvaluationGuard {
+    fn drop(&mut self) {
+        IN_ASYNC_EVALUATION.with(|flag| flag.set(false));
+    }
+}
+
+/// The runtime to block on, only available inside of the blocking tasks of [`QueryEvaluator::execute_tokio_async`]
+///
+/// Blocking on a runtime from one of its worker threads would panic.
+fn current_runtime() -> Result<Handle, QueryEvaluationError> {
+    if !IN_ASYNC_EVALUATION.with(Cell::get) {
+        return Err(QueryEvaluationError::Service(
+            "Tokio async service handlers are only supported with QueryEvaluator::execute_tokio_async"
+                .into(),
+        ));
+    }
+    Handle::try_current().map_err(wrap_service_error)
+}
+
+enum Header {
+    Solutions(Arc<[Variable]>, mpsc::Receiver<Batch<QuerySolution>>),
+    Boolean(bool),
+    Graph(mpsc::Receiver<Batch<Triple>>),
+}
+
+type Batch<T> = Vec<Result<T, QueryEvaluationError>>;
+
+/// Evaluates the query and sends the results to the async consumer, stops if the consumer is gone
+fn evaluate(
+    evaluator: &QueryEvaluator,
+    dataset: impl QueryableDataset,
+    query: &Query,
+    header_sender: oneshot::Sender<Result<Header, QueryEvaluationError>>,
+) -> Option<()> {
+    match evaluator.execute(dataset, query) {
+        Ok(QueryResults::Solutions(solutions)) => {
+            let (sender, receiver) = mpsc::channel(BATCH_BUFFER_SIZE);
+            header_sender
+                .send(Ok(Header::Solutions(
+                    solutions.variables().into(),
+                    receiver,
+                )))
+                .ok()?;
+            send_by_batches(solutions, &sender)
+        }
+        Ok(QueryResults::Boolean(value)) => header_sender.send(Ok(Header::Boolean(value))).ok(),
+        Ok(QueryResults::Graph(triples)) => {
+            let (sender, receiver) = mpsc::channel(BATCH_BUFFER_SIZE);
+            header_sender.send(Ok(Header::Graph(receiver))).ok()?;
+            send_by_batches(triples, &sender)
+        }
+        Err(e) => header_sender.send(Err(e)).ok(),
+    }
+}
+
+fn send_by_batches<T>(
+    iter: impl Iterator<Item = Result<T, QueryEvaluationError>>,
+    sender: &mpsc::Sender<Batch<T>>,
+) -> Option<()> {
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+    for result in iter {
+        batch.push(result);
+        if batch.len() == BATCH_SIZE {
+            sender
+                .blocking_send(mem::replace(&mut batch, Vec::with_capacity(BATCH_SIZE)))
+                .ok()?;
+        }
+    }
+    if batch.is_empty() {
+        Some(())
+    } else {
+        sender.blocking_send(batch).ok()
+    }
+}
+
+struct BatchReceiver<T> {
+    receiver: mpsc::Receiver<Batch<T>>,
+    current: vec::IntoIter<Result<T, QueryEvaluationError>>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl<T> BatchReceiver<T> {
+    fn new(receiver: mpsc::Receiver<Batch<T>>, task: JoinHandle<()>) -> Self {
+        Self {
+            receiver,
+            current: Vec::new().into_iter(),
+            task: Some(task),
+        }
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<T, QueryEvaluationError>>> {
+        loop {
+            if let Some(result) = self.current.next() {
+                return Poll::Ready(Some(result));
+            }
+            if let Some(batch) = ready!(self.receiver.poll_recv(cx)) {
+                self.current = batch.into_iter();
+                continue;
+            }
+            // The evaluation is finished, we make sure it has not failed
+            let Some(task) = &mut self.task else {
+                return Poll::Ready(None);
+            };
+            let result = ready!(Pin::new(task).poll(cx));
+            self.task = None;
+            return Poll::Ready(task_result(result).err().map(Err));
+        }
+    }
+}
+
+/// Waits for the evaluation task to end, propagating its panics
+async fn join_task(task: JoinHandle<()>) -> Result<(), QueryEvaluationError> {
+    task_result(task.await)
+}
+
+fn task_result(result: Result<(), JoinError>) -> Result<(), QueryEvaluationError> {
+    match result {
+        Ok(()) => Ok(()),
+        Err(e) if e.is_panic() => resume_unwind(e.into_panic()),
+        Err(_) => Err(QueryEvaluationError::Cancelled),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+    use oxrdf::{Dataset, GraphName, Literal, Quad, Term};
+    use std::io;
+    use std::iter::once;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    fn dataset(size: usize) -> Dataset {
+        let p = NamedNode::new_unchecked("http://example.com/p");
+        (0..size)
+            .map(|i| {
+                Quad::new(
+                    NamedNode::new_unchecked(format!("http://example.com/{i}")),
+                    p.clone(),
+                    Literal::from(i64::try_from(i).unwrap()),
+                    GraphName::DefaultGraph,
+                )
+            })
+            .collect()
+    }
+
+    struct TestServiceHandler {
+        fail: bool,
+    }
+
+    impl TokioAsyncServiceHandler for TestServiceHandler {
+        type Error = io::Error;
+
+        async fn handle(
+            &self,
+            _pattern: GraphPattern,
+            _base_iri: Option<String>,
+        ) -> Result<QuerySolutionIter, io::Error> {
+            tokio::task::yield_now().await;
+            if self.fail {
+                return Err(io::Error::other("service failure"));
+            }
+            let variables: Arc<[Variable]> = [Variable::new_unchecked("foo")].into();
+            Ok(QuerySolutionIter::new(
+                Arc::clone(&variables),
+                once(Ok(QuerySolution::from((
+                    variables,
+                    vec![Some(Literal::from(1).into())],
+                )))),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_solutions_in_several_batches() -> Result<(), Box<dyn Error>> {
+        let size = BATCH_SIZE * (BATCH_BUFFER_SIZE + 2) + 1;
+        let query = Query::parse("SELECT ?o WHERE { ?s ?p ?o } ORDER BY ?o", None)?;
+        let TokioAsyncQueryResults::Solutions(mut solutions) = QueryEvaluator::new()
+            .execute_tokio_async(dataset(size), &query)
+            .await?
+        else {
+            unreachable!("SELECT query")
+        };
+        assert_eq!(solutions.variables(), [Variable::new_unchecked("o")]);
+        let mut count = 0;
+        while let Some(solution) = solutions.next().await {
+            assert_eq!(
+                solution?.get("o"),
+                Some(&Literal::from(i64::try_from(count).unwrap()).into())
+            );
+            count += 1;
+        }
+        assert_eq!(count, size);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_triples_in_several_batches() -> Result<(), Box<dyn Error>> {
+        let size = BATCH_SIZE * (BATCH_BUFFER_SIZE + 2) + 1;
+        let query = Query::parse("CONSTRUCT WHERE { ?s ?p ?o }", None)?;
+        let TokioAsyncQueryResults::Graph(triples) = QueryEvaluator::new()
+            .execute_tokio_async(dataset(size), &query)
+            .await?
+        else {
+            unreachable!("CONSTRUCT query")
+        };
+        let triples = triples.collect::<Vec<_>>().await;
+        assert_eq!(triples.len(), size);
+        for triple in triples {
+            triple?;
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_drop_stops_evaluation() -> Result<(), Box<dyn Error>> {
+        let size = BATCH_SIZE * (BATCH_BUFFER_SIZE + 2) * 10;
+        let calls = Arc::new(AtomicUsize::new(0));
+        let evaluator = {
+            let calls = Arc::clone(&calls);
+            QueryEvaluator::new().with_custom_function(
+                NamedNode::new_unchecked("http://example.com/count"),
+                move |_| {
+                    calls.fetch_add(1, Ordering::Relaxed);
+                    Some(Literal::from(true).into())
+                },
+            )
+        };
+        let query = Query::parse(
+            "SELECT ?s WHERE { ?s ?p ?o BIND(<http://example.com/count>() AS ?c) }",
+            None,
+        )?;
+        let TokioAsyncQueryResults::Solutions(mut solutions) =
+            evaluator.execute_tokio_async(dataset(size), &query).await?
+        else {
+            unreachable!("SELECT query")
+        };
+        drop(evaluator);
+        solutions.next().await.transpose()?;
+        drop(solutions);
+        // The evaluation task owns the last copy of the custom function
+        while Arc::strong_count(&calls) > 1 {
+            spawn_blocking(|| sleep(Duration::from_millis(10))).await?;
+        }
+        assert!(
+            calls.load(Ordering::Relaxed) < size,
+            "the evaluation must stop when the stream is dropped"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_service() -> Result<(), Box<dyn Error>> {
+        let evaluator = QueryEvaluator::new().with_tokio_async_service_handler(
+            NamedNode::new_unchecked("http://example.com/service"),
+            TestServiceHandler { fail: false },
+        );
+        let query = Query::parse(
+            "SELECT ?foo WHERE { SERVICE <http://example.com/service> {} }",
+            None,
+        )?;
+        let TokioAsyncQueryResults::Solutions(solutions) = evaluator
+            .execute_tokio_async(Dataset::new(), &query)
+            .await?
+        else {
+            unreachable!("SELECT query")
+        };
+        let solutions = solutions.collect::<Vec<_>>().await;
+        assert_eq!(solutions.len(), 1);
+        for solution in solutions {
+            assert_eq!(solution?.get("foo"), Some(&Term::from(Literal::from(1))));
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_service_error() -> Result<(), Box<dyn Error>> {
+        let evaluator = QueryEvaluator::new().with_tokio_async_service_handler(
+            NamedNode::new_unchecked("http://example.com/service"),
+            TestServiceHandler { fail: true },
+        );
+        let query = Query::parse(
+            "SELECT ?foo WHERE { SERVICE <http://example.com/service> {} }",
+            None,
+        )?;
+        let error = match evaluator.execute_tokio_async(Dataset::new(), &query).await {
+            Ok(TokioAsyncQueryResults::Solutions(mut solutions)) => {
+                let error = solutions.next().await.and_then(Result::err);
+                assert!(
+                    solutions.next().await.is_none(),
+                    "no more solutions expected"
+                );
+                error
+            }
+            Ok(_) => unreachable!("SELECT query"),
+            Err(error) => Some(error),
+        };
+        assert!(
+            matches!(&error, Some(QueryEvaluationError::Service(e)) if e.to_string() == "service failure"),
+            "unexpected result: {error:?}"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_service_with_sync_execute() -> Result<(), Box<dyn Error>> {
+        let evaluator = QueryEvaluator::new().with_tokio_async_service_handler(
+            NamedNode::new_unchecked("http://example.com/service"),
+            TestServiceHandler { fail: false },
+        );
+        let query = Query::parse(
+            "SELECT ?foo WHERE { SERVICE <http://example.com/service> {} }",
+            None,
+        )?;
+        // Blocking on the runtime from one of its worker threads would panic, we get an error instead
+        let result = evaluator
+            .execute(Dataset::new(), &query)
+            .and_then(|results| {
+                let QueryResults::Solutions(solutions) = results else {
+                    unreachable!("SELECT query")
+                };
+                solutions.collect::<Result<Vec<_>, _>>()
+            });
+        assert!(
+            matches!(result, Err(QueryEvaluationError::Service(_))),
+            "an error is expected"
+        );
+        Ok(())
+    }
+}
@@ -1,18 +1,22 @@
 use oxrdf::{
-    BlankNode, Dataset, GraphNameRef, Literal, NamedNode, QuadRef, SubjectRef, Term, TermRef,
+    BlankNode, Dataset, GraphNameRef, Literal, NamedNode, NamedOrBlankNodeRef, Quad, QuadRef,
+    SubjectRef, Term, TermRef,
 };
 #[cfg(feature = "rdf-star")]
 use oxrdf::{Subject, Triple};
-use oxsdatatypes::{Boolean, DateTime, Decimal, Double, Float, Integer};
+use oxsdatatypes::{Base64Binary, Boolean, DateTime, Decimal, Double, Float, HexBinary, Integer};
 #[cfg(feature = "sep-0002")]
 use oxsdatatypes::{Date, DayTimeDuration, Duration, Time, YearMonthDuration};
 #[cfg(feature = "calendar-ext")]
 use oxsdatatypes::{GDay, GMonth, GMonthDay, GYear, GYearMonth};
 use rustc_hash::FxHashSet;
+use spargebra::algebra::QueryDataset;
 use std::convert::Infallible;
 use std::error::Error;
 use std::hash::{Hash, Hasher};
 use std::iter::empty;
+use std::mem;
+use std::sync::Arc;
 
 /// A [RDF dataset](https://www.w3.org/TR/sparql11-query/#rdfDataset) that can be queried using SPARQL
 pub trait QueryableDataset: Sized + 'static {
@@ -221,6 +225,255 @@ impl QueryableDataset for Dataset {
     }
 }
 
+/// A [RDF dataset](https://www.w3.org/TR/sparql11-query/#rdfDataset) that can be modified using SPARQL updates
+///
+/// It is used by the [`UpdateEvaluator`](crate::UpdateEvaluator).
+///
+/// The named graphs are explicitly created and removed by the `CREATE` and `DROP` operations.
+/// Implementations that do not store empty named graphs might consider a named graph exists if it contains at least one quad.
+pub trait MutableQueryableDataset {
+    /// The dataset the `WHERE` clauses of the updates are evaluated against
+    type QueryableDataset: QueryableDataset;
+
+    /// Error returned by the dataset.
+    type Error: Error + Send + Sync + 'static;
+
+    /// Returns a view of the current dataset content to evaluate the `WHERE` clauses against
+    ///
+    /// If `using` is set, the view must only contain the graphs listed in the `USING` and `USING NAMED` clauses:
+    /// its default graph is the merge of the `USING` graphs and its named graphs are the `USING NAMED` ones.
+    ///
+    /// The view is given back with [`release_queryable_dataset`](Self::release_queryable_dataset)
+    /// before any change is done to the dataset.
+    fn queryable_dataset(
+        &mut self,
+        using: Option<&QueryDataset>,
+    ) -> Result<Self::QueryableDataset, Self::Error>;
+
+    /// Called with the view returned by [`queryable_dataset`](Self::queryable_dataset) when the evaluation of the `WHERE` clause is done
+    ///
+    /// Implementations that move their content into the view can take it back here.
+    #[inline]
+    fn release_queryable_dataset(&mut self, _view: Arc<Self::QueryableDataset>) {}
+
+    /// Adds a quad to the dataset, returns `true` if the quad was not already in it
+    fn insert(&mut self, quad: QuadRef<'_>) -> Result<bool, Self::Error>;
+
+    /// Removes a quad from the dataset, returns `true` if the quad was in it
+    fn remove(&mut self, quad: QuadRef<'_>) -> Result<bool, Self::Error>;
+
+    /// Returns if the dataset contains a given named graph
+    fn contains_named_graph(
+        &self,
+        graph_name: NamedOrBlankNodeRef<'_>,
+    ) -> Result<bool, Self::Error>;
+
+    /// Creates an empty named graph, returns `true` if the graph was not already in the dataset
+    fn insert_named_graph(
+        &mut self,
+        graph_name: NamedOrBlankNodeRef<'_>,
+    ) -> Result<bool, Self::Error>;
+
+    /// Removes a named graph and all its quads, returns `true` if the graph was in the dataset
+    fn remove_named_graph(
+        &mut self,
+        graph_name: NamedOrBlankNodeRef<'_>,
+    ) -> Result<bool, Self::Error>;
+
+    /// Removes all the quads of a graph without removing the graph itself
+    fn clear_graph(&mut self, graph_name: GraphNameRef<'_>) -> Result<(), Self::Error>;
+
+    /// Removes all the quads of the named graphs without removing the graphs themselves
+    fn clear_all_named_graphs(&mut self) -> Result<(), Self::Error>;
+
+    /// Removes all the named graphs and their quads
+    fn remove_all_named_graphs(&mut self) -> Result<(), Self::Error>;
+
+    /// Removes all the quads of all the graphs without removing the named graphs themselves
+    fn clear_all_graphs(&mut self) -> Result<(), Self::Error> {
+        self.clear_graph(GraphNameRef::DefaultGraph)?;
+        self.clear_all_named_graphs()
+    }
+
+    /// Removes all the quads and all the named graphs
+    fn clear(&mut self) -> Result<(), Self::Error> {
+        self.clear_graph(GraphNameRef::DefaultGraph)?;
+        self.remove_all_named_graphs()
+    }
+}
+
+/// The named graphs of a [`Dataset`] are the ones containing at least one quad.
+///
+/// The dataset content is moved into the view used to evaluate the `WHERE` clauses and moved back after,
+/// only the `USING` clauses require a copy of the selected graphs.
+impl MutableQueryableDataset for Dataset {
+    type QueryableDataset = Self;
+    type Error = Infallible;
+
+    fn queryable_dataset(&mut self, using: Option<&QueryDataset>) -> Result<Self, Infallible> {
+        let Some(using) = using else {
+            return Ok(mem::take(self));
+        };
+        let mut view = Self::new();
+        for graph_name in &using.default {
+            for quad in self.quads_for_graph_name(graph_name) {
+                view.insert(QuadRef::new(
+                    quad.subject,
+                    quad.predicate,
+                    quad.object,
+                    GraphNameRef::DefaultGraph,
+                ));
+            }
+        }
+        for graph_name in using.named.iter().flatten() {
+            view.extend(self.quads_for_graph_name(graph_name));
+        }
+        Ok(view)
+    }
+
+    fn release_queryable_dataset(&mut self, view: Arc<Self>) {
+        // Without USING clause, the content has been moved into the view
+        if self.is_empty() {
+            *self = Arc::try_unwrap(view).unwrap_or_else(|view| Self::clone(&view));
+        }
+    }
+
+    fn insert(&mut self, quad: QuadRef<'_>) -> Result<bool, Infallible> {
+        Ok(Dataset::insert(self, quad))
+    }
+
+    fn remove(&mut self, quad: QuadRef<'_>) -> Result<bool, Infallible> {
+        Ok(Dataset::remove(self, quad))
+    }
+
+    fn contains_named_graph(
+        &self,
+        graph_name: NamedOrBlankNodeRef<'_>,
+    ) -> Result<bool, Infallible> {
+        Ok(self.quads_for_graph_name(graph_name).next().is_some())
+    }
+
+    fn insert_named_graph(
+        &mut self,
+        graph_name: NamedOrBlankNodeRef<'_>,
+    ) -> Result<bool, Infallible> {
+        // Empty named graphs are not stored
+        Ok(!MutableQueryableDataset::contains_named_graph(
+            self, graph_name,
+        )?)
+    }
+
+    fn remove_named_graph(
+        &mut self,
+        graph_name: NamedOrBlankNodeRef<'_>,
+    ) -> Result<bool, Infallible> {
+        let quads = self
+            .quads_for_graph_name(graph_name)
+            .map(QuadRef::into_owned)
+            .collect::<Vec<_>>();
+        for quad in &quads {
+            Dataset::remove(self, quad);
+        }
+        Ok(!quads.is_empty())
+    }
+
+    fn clear_graph(&mut self, graph_name: GraphNameRef<'_>) -> Result<(), Infallible> {
+        let quads = self
+            .quads_for_graph_name(graph_name)
+            .map(QuadRef::into_owned)
+            .collect::<Vec<_>>();
+        for quad in &quads {
+            Dataset::remove(self, quad);
+        }
+        Ok(())
+    }
+
+    fn clear_all_named_graphs(&mut self) -> Result<(), Infallible> {
+        let quads = self
+            .iter()
+            .filter(|q| !q.graph_name.is_default_graph())
+            .map(QuadRef::into_owned)
+            .collect::<Vec<Quad>>();
+        for quad in &quads {
+            Dataset::remove(self, quad);
+        }
+        Ok(())
+    }
+
+    fn remove_all_named_graphs(&mut self) -> Result<(), Infallible> {
+        self.clear_all_named_graphs()
+    }
+
+    fn clear(&mut self) -> Result<(), Infallible> {
+        Dataset::clear(self);
+        Ok(())
+    }
+}
+
+impl<D: QueryableDataset> QueryableDataset for Arc<D> {
+    type InternalTerm = D::InternalTerm;
+    type Error = D::Error;
+
+    fn internal_quads_for_pattern(
+        &self,
+        subject: Option<&D::InternalTerm>,
+        predicate: Option<&D::InternalTerm>,
+        object: Option<&D::InternalTerm>,
+        graph_name: Option<Option<&D::InternalTerm>>,
+    ) -> Box<dyn Iterator<Item = Result<InternalQuad<Self>, D::Error>>> {
+        Box::new(
+            (**self)
+                .internal_quads_for_pattern(subject, predicate, object, graph_name)
+                .map(|quad| {
+                    let quad = quad?;
+                    Ok(InternalQuad {
+                        subject: quad.subject,
+                        predicate: quad.predicate,
+                        object: quad.object,
+                        graph_name: quad.graph_name,
+                    })
+                }),
+        )
+    }
+
+    fn internal_named_graphs(&self) -> Box<dyn Iterator<Item = Result<D::InternalTerm, D::Error>>> {
+        (**self).internal_named_graphs()
+    }
+
+    fn contains_internal_graph_name(&self, graph_name: &D::InternalTerm) -> Result<bool, D::Error> {
+        (**self).contains_internal_graph_name(graph_name)
+    }
+
+    fn internalize_term(&self, term: Term) -> Result<D::InternalTerm, D::Error> {
+        (**self).internalize_term(term)
+    }
+
+    fn externalize_term(&self, term: D::InternalTerm) -> Result<Term, D::Error> {
+        (**self).externalize_term(term)
+    }
+
+    fn externalize_expression_term(
+        &self,
+        term: D::InternalTerm,
+    ) -> Result<ExpressionTerm, D::Error> {
+        (**self).externalize_expression_term(term)
+    }
+
+    fn internalize_expression_term(
+        &self,
+        term: ExpressionTerm,
+    ) -> Result<D::InternalTerm, D::Error> {
+        (**self).internalize_expression_term(term)
+    }
+
+    fn internal_term_effective_boolean_value(
+        &self,
+        term: D::InternalTerm,
+    ) -> Result<Option<bool>, D::Error> {
+        (**self).internal_term_effective_boolean_value(term)
+    }
+}
+
 pub struct InternalQuad<D: QueryableDataset> {
     pub subject: D::InternalTerm,
     pub predicate: D::InternalTerm,
@@ -265,6 +518,8 @@ pub enum ExpressionTerm {
     YearMonthDurationLiteral(YearMonthDuration),
     #[cfg(feature = "sep-0002")]
     DayTimeDurationLiteral(DayTimeDuration),
+    HexBinaryLiteral(HexBinary),
+    Base64BinaryLiteral(Base64Binary),
     OtherTypedLiteral {
         value: String,
         datatype: NamedNode,
@@ -315,6 +570,8 @@ impl PartialEq for ExpressionTerm {
             (Self::YearMonthDurationLiteral(l), Self::YearMonthDurationLiteral(r)) => l == r,
             #[cfg(feature = "sep-0002")]
             (Self::DayTimeDurationLiteral(l), Self::DayTimeDurationLiteral(r)) => l == r,
+            (Self::HexBinaryLiteral(l), Self::HexBinaryLiteral(r)) => l == r,
+            (Self::Base64BinaryLiteral(l), Self::Base64BinaryLiteral(r)) => l == r,
             (
                 Self::OtherTypedLiteral {
                     value: lv,
@@ -368,6 +625,8 @@ impl Hash for ExpressionTerm {
             ExpressionTerm::YearMonthDurationLiteral(v) => v.hash(state),
             #[cfg(feature = "sep-0002")]
             ExpressionTerm::DayTimeDurationLiteral(v) => v.hash(state),
+            ExpressionTerm::HexBinaryLiteral(v) => v.hash(state),
+            ExpressionTerm::Base64BinaryLiteral(v) => v.hash(state),
             ExpressionTerm::OtherTypedLiteral { value, datatype } => (value, datatype).hash(state),
             #[cfg(feature = "rdf-star")]
             ExpressionTerm::Triple(v) => v.hash(state),
@@ -434,6 +693,8 @@ impl From<ExpressionTerm> for Term {
             ExpressionTerm::YearMonthDurationLiteral(value) => Literal::from(value).into(),
             #[cfg(feature = "sep-0002")]
             ExpressionTerm::DayTimeDurationLiteral(value) => Literal::from(value).into(),
+            ExpressionTerm::HexBinaryLiteral(value) => Literal::from(value).into(),
+            ExpressionTerm::Base64BinaryLiteral(value) => Literal::from(value).into(),
             ExpressionTerm::OtherTypedLiteral { value, datatype } => {
                 Literal::new_typed_literal(value, datatype).into()
             }
@@ -538,6 +799,12 @@ fn parse_typed_literal(value: &str, datatype: &str) -> Option<ExpressionTerm> {
         "http://www.w3.org/2001/XMLSchema#dayTimeDuration" => {
             ExpressionTerm::DayTimeDurationLiteral(value.parse().ok()?)
         }
+        "http://www.w3.org/2001/XMLSchema#hexBinary" => {
+            ExpressionTerm::HexBinaryLiteral(value.parse().ok()?)
+        }
+        "http://www.w3.org/2001/XMLSchema#base64Binary" => {
+            ExpressionTerm::Base64BinaryLiteral(value.parse().ok()?)
+        }
         _ => return None,
     })
 }
@@ -1,6 +1,7 @@
 use oxrdf::{NamedNode, Term, Variable};
 use std::convert::Infallible;
 use std::error::Error;
+use std::io;
 
 /// A SPARQL evaluation error
 #[derive(Debug, thiserror::Error)]
@@ -30,6 +31,27 @@ pub enum QueryEvaluationError {
     #[cfg(feature = "rdf-star")]
     #[error("The storage provided a triple term that is not a valid RDF-star term")]
     InvalidStorageTripleTerm,
+    /// Error when `CREATE` tries to create an already existing graph
+    #[error("The graph {0} already exists")]
+    GraphAlreadyExists(NamedNode),
+    /// Error when `DROP` or `CLEAR` tries to remove a not existing graph
+    #[error("The graph {0} does not exist")]
+    GraphDoesNotExist(NamedNode),
+    /// Error during `LOAD` evaluation
+    #[error("{0}")]
+    Load(#[source] Box<dyn Error + Send + Sync>),
+    /// `LOAD` is not supported because no [`LoadHandler`](crate::LoadHandler) has been set
+    #[error("The load of {0} is not supported")]
+    UnsupportedLoad(NamedNode),
+    /// The evaluation has been cancelled using a [`CancellationToken`](crate::CancellationToken)
+    #[error("The query evaluation has been cancelled")]
+    Cancelled,
+    /// The operators buffering solutions need more memory than the budget set with [`QueryEvaluator::with_memory_budget`](crate::QueryEvaluator::with_memory_budget)
+    #[error("The query evaluation requires more than {0} bytes of memory")]
+    MemoryBudgetExceeded(usize),
+    /// I/O error while spilling intermediate solutions to disk
+    #[error("Error while spilling intermediate results to disk: {0}")]
+    Spill(#[source] io::Error),
 }
 
 impl From<Infallible> for QueryEvaluationError {
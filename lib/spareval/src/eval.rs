@@ -2,9 +2,13 @@
 use crate::dataset::{ExpressionSubject, ExpressionTriple};
 use crate::dataset::{ExpressionTerm, InternalQuad, QueryableDataset};
 use crate::error::QueryEvaluationError;
-use crate::model::{QuerySolutionIter, QueryTripleIter};
+use crate::memory::{MemoryBudget, MemoryReservation, MemoryTracker, SpillReader, SpillWriter};
+use crate::model::{QuerySolution, QuerySolutionIter, QueryTripleIter};
 use crate::service::ServiceHandlerRegistry;
-use crate::CustomFunctionRegistry;
+use crate::{
+    AggregateFunctionAccumulator, CancellationToken, CustomAggregateFunctionRegistry,
+    CustomFunctionRegistry, ExistsStrategy,
+};
 use json_event_parser::{JsonEvent, ToWriteJsonWriter};
 use md5::{Digest, Md5};
 use oxiri::Iri;
@@ -29,16 +33,31 @@ use sparopt::algebra::{
     MinusAlgorithm, OrderExpression,
 };
 use std::borrow::Cow;
-use std::cell::{Cell, RefCell};
+use std::cell::{Cell, OnceCell, RefCell};
 use std::cmp::Ordering;
+use std::collections::hash_set;
 use std::hash::{Hash, Hasher};
 use std::iter::{empty, once, Peekable};
+use std::mem::{size_of, take};
 use std::rc::Rc;
+use std::sync::mpsc;
 use std::sync::Arc;
 use std::{fmt, io};
 // TODO: make expression raise error when relevant (storage I/O)
 
 const REGEX_SIZE_LIMIT: usize = 1_000_000;
+/// Maximal number of rows of the `VALUES` clauses sent to services during bound joins
+const SERVICE_BINDINGS_BATCH_SIZE: usize = 100;
+/// Minimal number of solutions sorted in memory before being written to disk by `ORDER BY`
+const MIN_SPILLED_RUN_LEN: usize = 1024;
+/// Maximal `LIMIT` (plus `OFFSET`) for which `ORDER BY` only keeps the first solutions in memory instead of sorting all of them
+const MAX_TOP_K_LEN: usize = 10_000;
+/// Number of files the groups not fitting in memory are partitioned into by `GROUP BY`
+const GROUP_SPILL_PARTITIONS: usize = 16;
+/// Number of files the solutions not fitting in memory are partitioned into by `DISTINCT`
+const DISTINCT_SPILL_PARTITIONS: usize = 16;
+/// Number of hash functions of the Bloom filter used by `DISTINCT` when the solutions do not fit in memory
+const BLOOM_FILTER_HASHES: u64 = 4;
 
 /// Wrapper on top of [`QueryableDataset`]
 struct EvalDataset<D: QueryableDataset> {
@@ -147,10 +166,19 @@ impl<D: QueryableDataset> InternalTuple<D> {
         self.inner.get(index).unwrap_or(&None).as_ref()
     }
 
+    /// Rough estimation of the memory used by the tuple, not counting the data owned by the terms
+    pub fn estimated_size(&self) -> usize {
+        size_of::<Self>() + self.inner.capacity() * size_of::<Option<D::InternalTerm>>()
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = Option<D::InternalTerm>> + '_ {
         self.inner.iter().cloned()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.inner.iter().all(Option::is_none)
+    }
+
     pub fn set(&mut self, index: usize, value: D::InternalTerm) {
         if self.inner.len() <= index {
             self.inner.resize(index + 1, None);
@@ -228,13 +256,25 @@ impl<D: QueryableDataset> IntoIterator for InternalTuple<D> {
 type InternalTuplesIterator<D> =
     Box<dyn Iterator<Item = Result<InternalTuple<D>, QueryEvaluationError>>>;
 
+/// Builds a new instance of the dataset on the threads used for parallel evaluation
+pub type DatasetFactory<D> = Arc<dyn Fn() -> D + Send + Sync>;
+
 pub struct SimpleEvaluator<D: QueryableDataset> {
     dataset: EvalDataset<D>,
     base_iri: Option<Rc<Iri<String>>>,
     now: DateTime,
     service_handler: Rc<ServiceHandlerRegistry>,
     custom_functions: Rc<CustomFunctionRegistry>,
+    custom_aggregate_functions: Rc<CustomAggregateFunctionRegistry>,
+    cancellation_token: Option<CancellationToken>,
+    exists_strategy: ExistsStrategy,
+    dataset_factory: Option<DatasetFactory<D>>,
     run_stats: bool,
+    memory: Option<Arc<MemoryTracker>>,
+    /// Error raised inside of an expression that must be returned as a query evaluation error
+    ///
+    /// Expression evaluators only return `None` on error, this is used to carry the errors that must not be hidden like memory budget overflows.
+    expression_error: Rc<RefCell<Option<QueryEvaluationError>>>,
 }
 
 impl<D: QueryableDataset> SimpleEvaluator<D> {
@@ -243,7 +283,12 @@ impl<D: QueryableDataset> SimpleEvaluator<D> {
         base_iri: Option<Rc<Iri<String>>>,
         service_handler: Rc<ServiceHandlerRegistry>,
         custom_functions: Rc<CustomFunctionRegistry>,
+        custom_aggregate_functions: Rc<CustomAggregateFunctionRegistry>,
+        cancellation_token: Option<CancellationToken>,
+        exists_strategy: ExistsStrategy,
+        dataset_factory: Option<DatasetFactory<D>>,
         run_stats: bool,
+        memory_budget: Option<MemoryBudget>,
     ) -> Self {
         Self {
             dataset: EvalDataset {
@@ -253,7 +298,13 @@ impl<D: QueryableDataset> SimpleEvaluator<D> {
             now: DateTime::now(),
             service_handler,
             custom_functions,
+            custom_aggregate_functions,
+            cancellation_token,
+            exists_strategy,
+            dataset_factory,
             run_stats,
+            memory: memory_budget.map(|budget| Arc::new(MemoryTracker::new(budget))),
+            expression_error: Rc::default(),
         }
     }
 
@@ -274,7 +325,7 @@ impl<D: QueryableDataset> SimpleEvaluator<D> {
         (
             Ok(decode_bindings(
                 self.dataset.clone(),
-                eval(from),
+                self.with_expression_errors(eval(from)),
                 Arc::from(variables),
             )),
             stats,
@@ -295,7 +346,7 @@ impl<D: QueryableDataset> SimpleEvaluator<D> {
         // We apply the same table as the or operation:
         // we return true if we get any valid tuple, an error if we get an error and false otherwise
         let mut error = None;
-        for solution in eval(from) {
+        for solution in self.with_expression_errors(eval(from)) {
             if let Err(e) = solution {
                 // We keep the first error
                 error.get_or_insert(e);
@@ -354,7 +405,7 @@ impl<D: QueryableDataset> SimpleEvaluator<D> {
         (
             Ok(QueryTripleIter::new(ConstructIterator {
                 eval: self.clone(),
-                iter: eval(from),
+                iter: self.with_expression_errors(eval(from)),
                 template,
                 buffered_results: Vec::default(),
                 already_emitted_results: FxHashSet::default(),
@@ -381,7 +432,7 @@ impl<D: QueryableDataset> SimpleEvaluator<D> {
         (
             Ok(QueryTripleIter::new(DescribeIterator {
                 eval: self.clone(),
-                tuples_to_describe: eval(from),
+                tuples_to_describe: self.with_expression_errors(eval(from)),
                 nodes_described: FxHashSet::default(),
                 nodes_to_describe: Vec::default(),
                 quads: Box::new(empty()),
@@ -390,6 +441,14 @@ impl<D: QueryableDataset> SimpleEvaluator<D> {
         )
     }
 
+    /// Returns the errors raised during expression evaluation along the solutions
+    fn with_expression_errors(&self, iter: InternalTuplesIterator<D>) -> InternalTuplesIterator<D> {
+        Box::new(ExpressionErrorIterator {
+            inner: iter,
+            error: Rc::clone(&self.expression_error),
+        })
+    }
+
     pub fn graph_pattern_evaluator(
         &self,
         pattern: &GraphPattern,
@@ -397,19 +456,38 @@ impl<D: QueryableDataset> SimpleEvaluator<D> {
     ) -> (
         Rc<dyn Fn(InternalTuple<D>) -> InternalTuplesIterator<D>>,
         Rc<EvalNodeWithStats>,
+    ) {
+        self.limited_graph_pattern_evaluator(pattern, encoded_variables, None)
+    }
+
+    /// Builds an evaluator of which only the `limit` first solutions are going to be consumed
+    fn limited_graph_pattern_evaluator(
+        &self,
+        pattern: &GraphPattern,
+        encoded_variables: &mut Vec<Variable>,
+        limit: Option<usize>,
+    ) -> (
+        Rc<dyn Fn(InternalTuple<D>) -> InternalTuplesIterator<D>>,
+        Rc<EvalNodeWithStats>,
     ) {
         let mut stat_children = Vec::new();
-        let mut evaluator =
-            self.build_graph_pattern_evaluator(pattern, encoded_variables, &mut stat_children);
+        let mut evaluator = self.build_graph_pattern_evaluator(
+            pattern,
+            encoded_variables,
+            &mut stat_children,
+            limit,
+        );
         let stats = Rc::new(EvalNodeWithStats {
-            label: eval_node_label(pattern),
+            label: eval_node_label(pattern, &self.service_handler),
             children: stat_children,
+            input_count: Cell::new(0),
             exec_count: Cell::new(0),
             exec_duration: Cell::new(self.run_stats.then(DayTimeDuration::default)),
         });
         if self.run_stats {
             let stats = Rc::clone(&stats);
             evaluator = Rc::new(move |tuple| {
+                stats.input_count.set(stats.input_count.get() + 1);
                 let start = Timer::now();
                 let inner = evaluator(tuple);
                 let duration = start.elapsed();
@@ -425,14 +503,108 @@ impl<D: QueryableDataset> SimpleEvaluator<D> {
                 })
             })
         }
+        if let Some(cancellation_token) = &self.cancellation_token {
+            // Each plan node checks the cancellation, so blocking operations are also interrupted
+            let cancellation_token = cancellation_token.clone();
+            let inner_evaluator = evaluator;
+            evaluator = Rc::new(move |tuple| {
+                Box::new(CancellableIterator {
+                    inner: Some(inner_evaluator(tuple)),
+                    cancellation_token: cancellation_token.clone(),
+                })
+            })
+        }
         (evaluator, stats)
     }
 
+    /// Wraps the evaluator to evaluate the pattern on an other thread if parallel evaluation is enabled and the input tuple is empty.
+    ///
+    /// The evaluation starts as soon as the returned evaluator is called.
+    fn parallel_graph_pattern_evaluator(
+        &self,
+        pattern: &GraphPattern,
+        evaluator: Rc<dyn Fn(InternalTuple<D>) -> InternalTuplesIterator<D>>,
+        encoded_variables: &[Variable],
+    ) -> Rc<dyn Fn(InternalTuple<D>) -> InternalTuplesIterator<D>> {
+        if self.dataset_factory.is_none() || contains_service(pattern) {
+            // The service handlers can't be sent to other threads
+            return evaluator;
+        }
+        let this = self.clone();
+        let pattern = pattern.clone();
+        let variables = Rc::<[Variable]>::from(encoded_variables);
+        Rc::new(move |from| {
+            if !from.is_empty() {
+                return evaluator(from);
+            }
+            let Some(solutions) = this.spawn_evaluation(&pattern) else {
+                return evaluator(from);
+            };
+            encode_bindings(
+                this.dataset.clone(),
+                Rc::clone(&variables),
+                QuerySolutionIter::new(Arc::from(variables.as_ref()), solutions),
+            )
+        })
+    }
+
+    /// Evaluates the pattern on the rayon thread pool, using a new instance of the dataset.
+    fn spawn_evaluation(
+        &self,
+        pattern: &GraphPattern,
+    ) -> Option<impl Iterator<Item = Result<QuerySolution, QueryEvaluationError>>> {
+        let dataset_factory = Arc::clone(self.dataset_factory.as_ref()?);
+        let pattern = pattern.clone();
+        let base_iri = self.base_iri.as_deref().cloned();
+        let now = self.now;
+        let custom_functions = CustomFunctionRegistry::clone(&self.custom_functions);
+        let custom_aggregate_functions =
+            CustomAggregateFunctionRegistry::clone(&self.custom_aggregate_functions);
+        let cancellation_token = self.cancellation_token.clone();
+        let exists_strategy = self.exists_strategy;
+        let memory = self.memory.clone();
+        let (sender, receiver) = mpsc::channel();
+        rayon_core::spawn(move || {
+            // The evaluation is not parallelized further to avoid to block all the threads of the pool
+            let evaluator = SimpleEvaluator {
+                dataset: EvalDataset {
+                    dataset: Rc::new(dataset_factory()),
+                },
+                base_iri: base_iri.map(Rc::new),
+                now,
+                service_handler: Rc::default(),
+                custom_functions: Rc::new(custom_functions),
+                custom_aggregate_functions: Rc::new(custom_aggregate_functions),
+                cancellation_token,
+                exists_strategy,
+                dataset_factory: None,
+                run_stats: false,
+                memory,
+                expression_error: Rc::default(),
+            };
+            match evaluator.evaluate_select(&pattern, []).0 {
+                Ok(solutions) => {
+                    for solution in solutions {
+                        if sender.send(solution).is_err() {
+                            break; // The results are not needed anymore
+                        }
+                    }
+                }
+                Err(e) => {
+                    // The results might not be needed anymore
+                    drop(sender.send(Err(e)));
+                }
+            }
+        });
+        Some(receiver.into_iter())
+    }
+
     fn build_graph_pattern_evaluator(
         &self,
         pattern: &GraphPattern,
         encoded_variables: &mut Vec<Variable>,
         stat_children: &mut Vec<Rc<EvalNodeWithStats>>,
+        limit: Option<usize>,
     ) -> Rc<dyn Fn(InternalTuple<D>) -> InternalTuplesIterator<D>> {
         match pattern {
             GraphPattern::Values {
@@ -1027,10 +1199,33 @@ impl<D: QueryableDataset> SimpleEvaluator<D> {
                 right,
                 algorithm,
             } => {
-                let (left, left_stats) = self.graph_pattern_evaluator(left, encoded_variables);
+                if let Some((local, service_name, inner, keys)) =
+                    bound_service_join(left, right, algorithm, &self.service_handler)
+                {
+                    return self.bound_service_join_evaluator(
+                        local,
+                        service_name,
+                        inner,
+                        keys,
+                        encoded_variables,
+                        stat_children,
+                    );
+                }
+                let (left_evaluator, left_stats) =
+                    self.graph_pattern_evaluator(left, encoded_variables);
                 stat_children.push(left_stats);
-                let (right, right_stats) = self.graph_pattern_evaluator(right, encoded_variables);
+                let (right_evaluator, right_stats) =
+                    self.graph_pattern_evaluator(right, encoded_variables);
                 stat_children.push(right_stats);
+                let left =
+                    self.parallel_graph_pattern_evaluator(left, left_evaluator, encoded_variables);
+                let right = self.parallel_graph_pattern_evaluator(
+                    right,
+                    right_evaluator,
+                    encoded_variables,
+                );
+                let parallel = self.dataset_factory.is_some();
+                let memory = self.memory.clone();
 
                 match algorithm {
                     JoinAlgorithm::HashBuildLeftProbeRight { keys } => {
@@ -1039,29 +1234,43 @@ impl<D: QueryableDataset> SimpleEvaluator<D> {
                         if keys.is_empty() {
                             // Cartesian product
                             Rc::new(move |from| {
+                                // The probe side is started in parallel of the build side
+                                let started_probe =
+                                    (parallel && from.is_empty()).then(|| probe(from.clone()));
                                 let mut errors = Vec::default();
-                                let built_values = build(from.clone())
-                                    .filter_map(|result| match result {
-                                        Ok(result) => Some(result),
-                                        Err(error) => {
-                                            errors.push(Err(error));
-                                            None
+                                let mut built_values = Vec::new();
+                                let mut reservation =
+                                    memory.as_ref().map(MemoryTracker::reservation);
+                                for result in build(from.clone()) {
+                                    match result {
+                                        Ok(tuple) => {
+                                            if let Err(e) =
+                                                reserve_tuple_memory(&mut reservation, &tuple)
+                                            {
+                                                return Box::new(once(Err(e)));
+                                            }
+                                            built_values.push(tuple);
                                         }
-                                    })
-                                    .collect::<Vec<_>>();
+                                        Err(error) => errors.push(Err(error)),
+                                    }
+                                }
                                 if built_values.is_empty() && errors.is_empty() {
                                     // We don't bother to execute the other side
                                     return Box::new(empty());
                                 }
-                                let mut probe_iter = probe(from).peekable();
+                                let mut probe_iter =
+                                    started_probe.unwrap_or_else(|| probe(from)).peekable();
                                 if probe_iter.peek().is_none() {
                                     // We know it's empty and can discard errors
                                     return Box::new(empty());
                                 }
-                                Box::new(CartesianProductJoinIterator {
-                                    probe_iter,
-                                    built: built_values,
-                                    buffered_results: errors,
+                                Box::new(ReservedIterator {
+                                    inner: CartesianProductJoinIterator {
+                                        probe_iter,
+                                        built: built_values,
+                                        buffered_results: errors,
+                                    },
+                                    reservation,
                                 })
                             })
                         } else {
@@ -1071,30 +1280,43 @@ impl<D: QueryableDataset> SimpleEvaluator<D> {
                                 .map(|v| encode_variable(encoded_variables, v))
                                 .collect::<Vec<_>>();
                             Rc::new(move |from| {
+                                // The probe side is started in parallel of the build side
+                                let started_probe =
+                                    (parallel && from.is_empty()).then(|| probe(from.clone()));
                                 let mut errors = Vec::default();
                                 let mut built_values = InternalTupleSet::new(keys.clone());
-                                built_values.extend(build(from.clone()).filter_map(|result| {
+                                let mut reservation =
+                                    memory.as_ref().map(MemoryTracker::reservation);
+                                for result in build(from.clone()) {
                                     match result {
-                                        Ok(result) => Some(result),
-                                        Err(error) => {
-                                            errors.push(Err(error));
-                                            None
+                                        Ok(tuple) => {
+                                            if let Err(e) =
+                                                reserve_tuple_memory(&mut reservation, &tuple)
+                                            {
+                                                return Box::new(once(Err(e)));
+                                            }
+                                            built_values.insert(tuple);
                                         }
+                                        Err(error) => errors.push(Err(error)),
                                     }
-                                }));
+                                }
                                 if built_values.is_empty() && errors.is_empty() {
                                     // We don't bother to execute the other side
                                     return Box::new(empty());
                                 }
-                                let mut probe_iter = probe(from).peekable();
+                                let mut probe_iter =
+                                    started_probe.unwrap_or_else(|| probe(from)).peekable();
                                 if probe_iter.peek().is_none() {
                                     // We know it's empty and can discard errors
                                     return Box::new(empty());
                                 }
-                                Box::new(HashJoinIterator {
-                                    probe_iter,
-                                    built: built_values,
-                                    buffered_results: errors,
+                                Box::new(ReservedIterator {
+                                    inner: HashJoinIterator {
+                                        probe_iter,
+                                        built: built_values,
+                                        buffered_results: errors,
+                                    },
+                                    reservation,
                                 })
                             })
                         }
@@ -1223,26 +1445,35 @@ impl<D: QueryableDataset> SimpleEvaluator<D> {
                             .iter()
                             .map(|v| encode_variable(encoded_variables, v))
                             .collect::<Vec<_>>();
+                        let memory = self.memory.clone();
                         Rc::new(move |from| {
                             let mut errors = Vec::default();
                             let mut right_values = InternalTupleSet::new(keys.clone());
-                            right_values.extend(right(from.clone()).filter_map(
-                                |result| match result {
-                                    Ok(result) => Some(result),
-                                    Err(error) => {
-                                        errors.push(Err(error));
-                                        None
+                            let mut reservation = memory.as_ref().map(MemoryTracker::reservation);
+                            for result in right(from.clone()) {
+                                match result {
+                                    Ok(tuple) => {
+                                        if let Err(e) =
+                                            reserve_tuple_memory(&mut reservation, &tuple)
+                                        {
+                                            return Box::new(once(Err(e)));
+                                        }
+                                        right_values.insert(tuple);
                                     }
-                                },
-                            ));
+                                    Err(error) => errors.push(Err(error)),
+                                }
+                            }
                             if right_values.is_empty() && errors.is_empty() {
                                 return left(from);
                             }
-                            Box::new(HashLeftJoinIterator {
-                                left_iter: left(from),
-                                right: right_values,
-                                buffered_results: errors,
-                                expression: Rc::clone(&expression),
+                            Box::new(ReservedIterator {
+                                inner: HashLeftJoinIterator {
+                                    left_iter: left(from),
+                                    right: right_values,
+                                    buffered_results: errors,
+                                    expression: Rc::clone(&expression),
+                                },
+                                reservation,
                             })
                         })
                     }
@@ -1256,26 +1487,49 @@ impl<D: QueryableDataset> SimpleEvaluator<D> {
                     encoded_variables,
                     stat_children,
                 );
+                let expression_error = Rc::clone(&self.expression_error);
                 Rc::new(move |from| {
                     let expression = Rc::clone(&expression);
-                    Box::new(child(from).filter(move |tuple| match tuple {
-                        Ok(tuple) => expression(tuple).unwrap_or(false),
-                        Err(_) => true,
+                    let expression_error = Rc::clone(&expression_error);
+                    Box::new(child(from).filter_map(move |tuple| match tuple {
+                        Ok(tuple) => match expression(&tuple) {
+                            Some(true) => Some(Ok(tuple)),
+                            Some(false) => None,
+                            // We return the errors that must not be hidden as soon as possible
+                            None => expression_error.take().map(Err),
+                        },
+                        Err(e) => Some(Err(e)),
                     }))
                 })
             }
             GraphPattern::Union { inner } => {
                 let children = inner
                     .iter()
-                    .map(|child| {
+                    .map(|child_pattern| {
                         let (child, child_stats) =
-                            self.graph_pattern_evaluator(child, encoded_variables);
+                            self.graph_pattern_evaluator(child_pattern, encoded_variables);
                         stat_children.push(child_stats);
-                        child
+                        self.parallel_graph_pattern_evaluator(
+                            child_pattern,
+                            child,
+                            encoded_variables,
+                        )
                     })
                     .collect::<Vec<_>>();
+                let parallel = self.dataset_factory.is_some();
 
                 Rc::new(move |from| {
+                    if parallel && from.is_empty() {
+                        // All the branches are started at the same time
+                        return Box::new(
+                            children
+                                .iter()
+                                .map(|child| child(from.clone()))
+                                .collect::<Vec<_>>()
+                                .into_iter()
+                                .flatten(),
+                        );
+                    }
                     Box::new(UnionIterator {
                         plans: children.clone(),
                         input: from,
@@ -1336,47 +1590,102 @@ impl<D: QueryableDataset> SimpleEvaluator<D> {
                             self.expression_evaluator(expression, encoded_variables, stat_children),
                         ),
                     })
-                    .collect::<Vec<_>>();
+                    .collect::<Rc<[_]>>();
+                if let Some(limit) = limit.filter(|limit| *limit <= MAX_TOP_K_LEN) {
+                    // Top-k: we only keep the smallest solutions instead of sorting all of them
+                    return Rc::new(move |from| {
+                        let mut errors = Vec::default();
+                        let mut values = Vec::new();
+                        for result in child(from) {
+                            match result {
+                                Ok(tuple) => values.push(tuple),
+                                Err(error) => errors.push(Err(error)),
+                            }
+                            if values.len() > 2 * limit {
+                                values.select_nth_unstable_by(limit, |a, b| {
+                                    compare_tuples(&by, a, b)
+                                });
+                                values.truncate(limit);
+                            }
+                        }
+                        values.sort_unstable_by(|a, b| compare_tuples(&by, a, b));
+                        values.truncate(limit);
+                        Box::new(errors.into_iter().chain(values.into_iter().map(Ok)))
+                    });
+                }
+                let dataset = self.dataset.clone();
+                let memory = self.memory.clone();
                 Rc::new(move |from| {
                     let mut errors = Vec::default();
-                    let mut values = child(from)
-                        .filter_map(|result| match result {
-                            Ok(result) => Some(result),
+                    let mut values = Vec::new();
+                    let mut runs = Vec::new();
+                    let mut reservation = memory.as_ref().map(MemoryTracker::reservation);
+                    for result in child(from) {
+                        let tuple = match result {
+                            Ok(tuple) => tuple,
                             Err(error) => {
                                 errors.push(Err(error));
-                                None
+                                continue;
                             }
-                        })
-                        .collect::<Vec<_>>();
-                    values.sort_unstable_by(|a, b| {
-                        for comp in &by {
-                            match comp {
-                                ComparatorFunction::Asc(expression) => {
-                                    match cmp_terms(expression(a).as_ref(), expression(b).as_ref())
-                                    {
-                                        Ordering::Greater => return Ordering::Greater,
-                                        Ordering::Less => return Ordering::Less,
-                                        Ordering::Equal => (),
-                                    }
-                                }
-                                ComparatorFunction::Desc(expression) => {
-                                    match cmp_terms(expression(a).as_ref(), expression(b).as_ref())
-                                    {
-                                        Ordering::Greater => return Ordering::Less,
-                                        Ordering::Less => return Ordering::Greater,
-                                        Ordering::Equal => (),
+                        };
+                        if let (Some(memory), Some(reservation)) = (&memory, &mut reservation) {
+                            let size = tuple.estimated_size();
+                            if !reservation.try_grow(size) {
+                                if values.len() >= MIN_SPILLED_RUN_LEN {
+                                    // External sort: we write the sorted solutions to disk and merge them at the end
+                                    values.sort_unstable_by(|a, b| compare_tuples(&by, a, b));
+                                    match spill_tuples(&dataset, memory, values.drain(..)) {
+                                        Ok(run) => runs.push(run),
+                                        Err(e) => return Box::new(once(Err(e))),
                                     }
+                                    reservation.release();
                                 }
+                                reservation.grow(size);
                             }
                         }
-                        Ordering::Equal
-                    });
-                    Box::new(errors.into_iter().chain(values.into_iter().map(Ok)))
+                        values.push(tuple);
+                    }
+                    values.sort_unstable_by(|a, b| compare_tuples(&by, a, b));
+                    if runs.is_empty() {
+                        return Box::new(errors.into_iter().chain(ReservedIterator {
+                            inner: values.into_iter().map(Ok),
+                            reservation,
+                        }));
+                    }
+                    let mut runs = runs
+                        .into_iter()
+                        .map(|run| -> InternalTuplesIterator<D> {
+                            Box::new(SpilledTuplesIterator {
+                                dataset: dataset.clone(),
+                                reader: run,
+                            })
+                        })
+                        .collect::<Vec<_>>();
+                    runs.push(Box::new(values.into_iter().map(Ok)));
+                    match SortedRunsMergeIterator::new(runs, Rc::clone(&by)) {
+                        Ok(merged) => Box::new(errors.into_iter().chain(ReservedIterator {
+                            inner: merged,
+                            reservation,
+                        })),
+                        Err(e) => Box::new(once(Err(e))),
+                    }
                 })
             }
             GraphPattern::Distinct { inner } => {
                 let (child, child_stats) = self.graph_pattern_evaluator(inner, encoded_variables);
                 stat_children.push(child_stats);
+                // The spilling deduplication does not keep the order of the solutions
+                if let Some(memory) = self.memory.clone().filter(|_| !is_ordered(inner)) {
+                    let dataset = self.dataset.clone();
+                    return Rc::new(move |from| {
+                        Box::new(DistinctIterator::new(
+                            Box::new(child(from).map(|tuple| Ok((tuple?, false)))),
+                            dataset.clone(),
+                            Arc::clone(&memory),
+                            0,
+                        ))
+                    });
+                }
                 Rc::new(move |from| Box::new(hash_deduplicate(child(from))))
             }
             GraphPattern::Reduced { inner } => {
@@ -1394,8 +1703,11 @@ impl<D: QueryableDataset> SimpleEvaluator<D> {
                 start,
                 length,
             } => {
-                let (mut child, child_stats) =
-                    self.graph_pattern_evaluator(inner, encoded_variables);
+                let (mut child, child_stats) = self.limited_graph_pattern_evaluator(
+                    inner,
+                    encoded_variables,
+                    length.map(|length| start.saturating_add(length)),
+                );
                 stat_children.push(child_stats);
                 #[allow(clippy::shadow_same)]
                 let start = *start;
@@ -1409,8 +1721,11 @@ impl<D: QueryableDataset> SimpleEvaluator<D> {
             }
             GraphPattern::Project { inner, variables } => {
                 let mut inner_encoded_variables = variables.clone();
-                let (child, child_stats) =
-                    self.graph_pattern_evaluator(inner, &mut inner_encoded_variables);
+                let (child, child_stats) = self.limited_graph_pattern_evaluator(
+                    inner,
+                    &mut inner_encoded_variables,
+                    limit,
+                );
                 stat_children.push(child_stats);
                 let mapping = variables
                     .iter()
@@ -1471,73 +1786,16 @@ impl<D: QueryableDataset> SimpleEvaluator<D> {
                     .iter()
                     .map(|(variable, _)| encode_variable(encoded_variables, variable))
                     .collect::<Vec<_>>();
-                let dataset = self.dataset.clone();
+                let group = Rc::new(GroupEvaluator {
+                    key_variables,
+                    accumulator_builders,
+                    accumulator_variables,
+                    dataset: self.dataset.clone(),
+                    memory: self.memory.clone(),
+                });
                 Rc::new(move |from| {
                     let tuple_size = from.capacity();
-                    let key_variables = Rc::clone(&key_variables);
-                    let mut errors = Vec::default();
-                    let mut accumulators_for_group = FxHashMap::<
-                        Vec<Option<D::InternalTerm>>,
-                        Vec<AccumulatorWrapper<D>>,
-                    >::default();
-                    if key_variables.is_empty() {
-                        // There is always a single group if there is no GROUP BY
-                        accumulators_for_group.insert(
-                            Vec::new(),
-                            accumulator_builders.iter().map(|c| c()).collect::<Vec<_>>(),
-                        );
-                    }
-                    child(from)
-                        .filter_map(|result| match result {
-                            Ok(result) => Some(result),
-                            Err(error) => {
-                                errors.push(error);
-                                None
-                            }
-                        })
-                        .for_each(|tuple| {
-                            // TODO avoid copy for key?
-                            let key = key_variables
-                                .iter()
-                                .map(|v| tuple.get(*v).cloned())
-                                .collect();
-
-                            let key_accumulators =
-                                accumulators_for_group.entry(key).or_insert_with(|| {
-                                    accumulator_builders.iter().map(|c| c()).collect::<Vec<_>>()
-                                });
-                            for accumulator in key_accumulators {
-                                accumulator.add(&tuple);
-                            }
-                        });
-                    let accumulator_variables = accumulator_variables.clone();
-                    let dataset = dataset.clone();
-                    Box::new(
-                        errors
-                            .into_iter()
-                            .map(Err)
-                            .chain(accumulators_for_group.into_iter().map(
-                                move |(key, accumulators)| {
-                                    let mut result = InternalTuple::with_capacity(tuple_size);
-                                    for (variable, value) in key_variables.iter().zip(key) {
-                                        if let Some(value) = value {
-                                            result.set(*variable, value);
-                                        }
-                                    }
-                                    for (accumulator, variable) in
-                                        accumulators.into_iter().zip(&accumulator_variables)
-                                    {
-                                        if let Some(value) = accumulator.finish() {
-                                            result.set(
-                                                *variable,
-                                                dataset.internalize_expression_term(value)?,
-                                            );
-                                        }
-                                    }
-                                    Ok(result)
-                                },
-                            )),
-                    )
+                    group.aggregate(child(from), tuple_size, 0)
                 })
             }
             GraphPattern::Service {
@@ -1555,7 +1813,7 @@ impl<D: QueryableDataset> SimpleEvaluator<D> {
                     Ok(service_name) => service_name,
                     Err(e) => return error_evaluator(e),
                 };
-                self.build_graph_pattern_evaluator(inner, encoded_variables, &mut Vec::new()); // We call recursively to fill "encoded_variables"
+                self.build_graph_pattern_evaluator(inner, encoded_variables, &mut Vec::new(), None); // We call recursively to fill "encoded_variables"
                 let graph_pattern = spargebra::algebra::GraphPattern::from(inner.as_ref());
                 let variables = Rc::from(encoded_variables.as_slice());
                 let eval = self.clone();
@@ -1610,6 +1868,119 @@ impl<D: QueryableDataset> SimpleEvaluator<D> {
         Ok(encode_bindings(self.dataset.clone(), variables, iter))
     }
 
+    /// Evaluates `local` joined with `SERVICE service_name { inner }` by sending the values of the join keys
+    /// computed by `local` to the service, in `VALUES` clauses of at most [`SERVICE_BINDINGS_BATCH_SIZE`] rows.
+    ///
+    /// If a value is a blank node, it can't be sent to the service and the service is called once without `VALUES`.
+    /// The service results are then joined with the local solutions using a hash join.
+    fn bound_service_join_evaluator(
+        &self,
+        local: &GraphPattern,
+        service_name: &NamedNodePattern,
+        inner: &GraphPattern,
+        keys: &[Variable],
+        encoded_variables: &mut Vec<Variable>,
+        stat_children: &mut Vec<Rc<EvalNodeWithStats>>,
+    ) -> Rc<dyn Fn(InternalTuple<D>) -> InternalTuplesIterator<D>> {
+        let (build, local_stats) = self.graph_pattern_evaluator(local, encoded_variables);
+        stat_children.push(local_stats);
+        let build = self.parallel_graph_pattern_evaluator(local, build, encoded_variables);
+        let service_name = match TupleSelector::from_named_node_pattern(
+            service_name,
+            encoded_variables,
+            &self.dataset,
+        ) {
+            Ok(service_name) => Rc::new(service_name),
+            Err(e) => return error_evaluator(e),
+        };
+        self.build_graph_pattern_evaluator(inner, encoded_variables, &mut Vec::new(), None); // We call recursively to fill "encoded_variables"
+        let inner = Rc::new(spargebra::algebra::GraphPattern::from(inner));
+        let variables = Rc::<[Variable]>::from(encoded_variables.as_slice());
+        let keys = Rc::<[Variable]>::from(keys);
+        let encoded_keys = keys
+            .iter()
+            .map(|v| encode_variable(encoded_variables, v))
+            .collect::<Vec<_>>();
+        let eval = self.clone();
+        Rc::new(move |from| {
+            let mut errors = Vec::default();
+            let mut built_values = InternalTupleSet::new(encoded_keys.clone());
+            let mut bindings = FxHashSet::default();
+            let mut reservation = eval.memory.as_ref().map(MemoryTracker::reservation);
+            for result in build(from.clone()) {
+                let tuple = match result {
+                    Ok(tuple) => tuple,
+                    Err(error) => {
+                        errors.push(Err(error));
+                        continue;
+                    }
+                };
+                if let Err(e) = reserve_tuple_memory(&mut reservation, &tuple) {
+                    return Box::new(once(Err(e)));
+                }
+                match encoded_keys
+                    .iter()
+                    .map(|key| eval.ground_term(tuple.get(*key)))
+                    .collect::<Result<Vec<_>, _>>()
+                {
+                    Ok(binding) => {
+                        bindings.insert(binding);
+                        built_values.insert(tuple);
+                    }
+                    Err(error) => errors.push(Err(error)),
+                }
+            }
+            if built_values.is_empty() && errors.is_empty() {
+                // We don't bother to call the service
+                return Box::new(empty());
+            }
+            let probe_iter: InternalTuplesIterator<D> =
+                if bindings.iter().any(|binding| binding.contains(&None)) {
+                    // The UNDEF rows would overlap with the other ones and duplicate the service solutions,
+                    // so we call the service once without VALUES
+                    match eval.evaluate_service(&service_name, &inner, Rc::clone(&variables), &from)
+                    {
+                        Ok(result) => Box::new(result.filter_map(move |binding| {
+                            binding
+                                .map(|binding| binding.combine_with(&from))
+                                .transpose()
+                        })),
+                        Err(e) => Box::new(once(Err(e))),
+                    }
+                } else {
+                    Box::new(BoundServiceIterator {
+                        eval: eval.clone(),
+                        service_name: Rc::clone(&service_name),
+                        inner: Rc::clone(&inner),
+                        variables: Rc::clone(&variables),
+                        keys: Rc::clone(&keys),
+                        bindings: bindings.into_iter(),
+                        from,
+                        current: Box::new(empty()),
+                    })
+                };
+            Box::new(ReservedIterator {
+                inner: HashJoinIterator {
+                    probe_iter: probe_iter.peekable(),
+                    built: built_values,
+                    buffered_results: errors,
+                },
+                reservation,
+            })
+        })
+    }
+
+    /// Converts a tuple value to a term that can be sent in a `VALUES` clause, blank nodes are mapped to `UNDEF`
+    fn ground_term(
+        &self,
+        term: Option<&D::InternalTerm>,
+    ) -> Result<Option<GroundTerm>, QueryEvaluationError> {
+        let Some(term) = term else {
+            return Ok(None);
+        };
+        Ok(GroundTerm::try_from(self.dataset.externalize_term(term.clone())?).ok())
+    }
+
     fn accumulator_builder(
         &self,
         expression: &AggregateExpression,
@@ -1757,7 +2128,27 @@ impl<D: QueryableDataset> SimpleEvaluator<D> {
                         })
                     }
                 }
-                AggregateFunction::Custom(_) => Box::new(move || AccumulatorWrapper::Failing),
+                AggregateFunction::Custom(function_name) => {
+                    let Some(function) =
+                        self.custom_aggregate_functions.get(function_name).cloned()
+                    else {
+                        return Box::new(move || AccumulatorWrapper::Failing);
+                    };
+                    let evaluator =
+                        self.expression_evaluator(expr, encoded_variables, stat_children);
+                    if *distinct {
+                        Box::new(move || AccumulatorWrapper::DistinctExpression {
+                            evaluator: Rc::clone(&evaluator),
+                            seen: FxHashSet::default(),
+                            accumulator: Some(Box::new(CustomAccumulator(function()))),
+                        })
+                    } else {
+                        Box::new(move || AccumulatorWrapper::Expression {
+                            evaluator: Rc::clone(&evaluator),
+                            accumulator: Some(Box::new(CustomAccumulator(function()))),
+                        })
+                    }
+                }
             },
         }
     }
@@ -1886,7 +2277,47 @@ impl<D: QueryableDataset> SimpleEvaluator<D> {
             Expression::Exists(plan) => {
                 let (eval, stats) = self.graph_pattern_evaluator(plan, encoded_variables);
                 stat_children.push(stats);
-                Rc::new(move |tuple| Some(eval(tuple.clone()).next().is_some().into()))
+                match self.exists_strategy {
+                    ExistsStrategy::Substitution => {
+                        Rc::new(move |tuple| Some(eval(tuple.clone()).next().is_some().into()))
+                    }
+                    ExistsStrategy::FilterScoped => {
+                        // The pattern solutions do not depend on the current tuple, we compute them only once
+                        let solutions = OnceCell::new();
+                        let memory = self.memory.clone();
+                        let expression_error = Rc::clone(&self.expression_error);
+                        Rc::new(move |tuple| {
+                            let solutions = solutions.get_or_init(|| {
+                                let mut reservation =
+                                    memory.as_ref().map(MemoryTracker::reservation);
+                                let mut solutions = Vec::new();
+                                for solution in eval(InternalTuple::with_capacity(tuple.capacity()))
+                                {
+                                    let solution = solution.and_then(|solution| {
+                                        reserve_tuple_memory(&mut reservation, &solution)?;
+                                        Ok(solution)
+                                    });
+                                    match solution {
+                                        Ok(solution) => solutions.push(solution),
+                                        Err(e) => {
+                                            // The error is returned once, the next evaluations are expression errors
+                                            expression_error.borrow_mut().get_or_insert(e);
+                                            return None;
+                                        }
+                                    }
+                                }
+                                Some((solutions, reservation))
+                            });
+                            let (solutions, _) = solutions.as_ref()?;
+                            Some(
+                                solutions
+                                    .iter()
+                                    .any(|solution| solution.combine_with(tuple).is_some())
+                                    .into(),
+                            )
+                        })
+                    }
+                }
             }
             Expression::Or(inner) => {
                 let children = inner
@@ -2379,6 +2810,8 @@ impl<D: QueryableDataset> SimpleEvaluator<D> {
                             ExpressionTerm::DayTimeDurationLiteral(_) => {
                                 xsd::DAY_TIME_DURATION.into()
                             }
+                            ExpressionTerm::HexBinaryLiteral(_) => xsd::HEX_BINARY.into(),
+                            ExpressionTerm::Base64BinaryLiteral(_) => xsd::BASE_64_BINARY.into(),
                             ExpressionTerm::OtherTypedLiteral { datatype, .. } => datatype,
                             ExpressionTerm::NamedNode(_) | ExpressionTerm::BlankNode(_) => {
                                 return None
@@ -3406,6 +3839,36 @@ impl<D: QueryableDataset> SimpleEvaluator<D> {
                                 }))
                             })
                         }
+                        xsd::HEX_BINARY => {
+                            let e = self.expression_evaluator(
+                                &parameters[0],
+                                encoded_variables,
+                                stat_children,
+                            );
+                            Rc::new(move |tuple| {
+                                Some(ExpressionTerm::HexBinaryLiteral(match e(tuple)? {
+                                    ExpressionTerm::HexBinaryLiteral(value) => value,
+                                    ExpressionTerm::Base64BinaryLiteral(value) => value.into(),
+                                    ExpressionTerm::StringLiteral(value) => value.parse().ok()?,
+                                    _ => return None,
+                                }))
+                            })
+                        }
+                        xsd::BASE_64_BINARY => {
+                            let e = self.expression_evaluator(
+                                &parameters[0],
+                                encoded_variables,
+                                stat_children,
+                            );
+                            Rc::new(move |tuple| {
+                                Some(ExpressionTerm::Base64BinaryLiteral(match e(tuple)? {
+                                    ExpressionTerm::HexBinaryLiteral(value) => value.into(),
+                                    ExpressionTerm::Base64BinaryLiteral(value) => value,
+                                    ExpressionTerm::StringLiteral(value) => value.parse().ok()?,
+                                    _ => return None,
+                                }))
+                            })
+                        }
                         // TODO: gYear...
                         _ => Rc::new(|_| None),
                     }
@@ -3488,7 +3951,13 @@ impl<D: QueryableDataset> Clone for SimpleEvaluator<D> {
             now: self.now,
             service_handler: Rc::clone(&self.service_handler),
             custom_functions: Rc::clone(&self.custom_functions),
+            custom_aggregate_functions: Rc::clone(&self.custom_aggregate_functions),
+            cancellation_token: self.cancellation_token.clone(),
+            exists_strategy: self.exists_strategy,
+            dataset_factory: self.dataset_factory.clone(),
             run_stats: self.run_stats,
+            memory: self.memory.clone(),
+            expression_error: Rc::clone(&self.expression_error),
         }
     }
 }
@@ -3917,6 +4386,18 @@ impl Accumulator for MaxAccumulator {
     }
 }
 
+struct CustomAccumulator(Box<dyn AggregateFunctionAccumulator + Send + Sync>);
+
+impl Accumulator for CustomAccumulator {
+    fn add(&mut self, element: ExpressionTerm) {
+        self.0.accumulate(element.into());
+    }
+
+    fn finish(&mut self) -> Option<ExpressionTerm> {
+        self.0.finish().map(Into::into)
+    }
+}
+
 #[allow(clippy::option_option)]
 struct GroupConcatAccumulator {
     concat: Option<String>,
@@ -4116,6 +4597,16 @@ fn equals(a: &ExpressionTerm, b: &ExpressionTerm) -> Option<bool> {
             ExpressionTerm::OtherTypedLiteral { .. } => None,
             _ => Some(false),
         },
+        ExpressionTerm::HexBinaryLiteral(a) => match b {
+            ExpressionTerm::HexBinaryLiteral(b) => Some(a == b),
+            ExpressionTerm::OtherTypedLiteral { .. } => None,
+            _ => Some(false),
+        },
+        ExpressionTerm::Base64BinaryLiteral(a) => match b {
+            ExpressionTerm::Base64BinaryLiteral(b) => Some(a == b),
+            ExpressionTerm::OtherTypedLiteral { .. } => None,
+            _ => Some(false),
+        },
         #[cfg(feature = "rdf-star")]
         ExpressionTerm::Triple(a) => {
             if let ExpressionTerm::Triple(b) = b {
@@ -4378,6 +4869,20 @@ fn partial_cmp_literals(a: &ExpressionTerm, b: &ExpressionTerm) -> Option<Orderi
             ExpressionTerm::DayTimeDurationLiteral(b) => a.partial_cmp(b),
             _ => None,
         },
+        ExpressionTerm::HexBinaryLiteral(a) => {
+            if let ExpressionTerm::HexBinaryLiteral(b) = b {
+                a.partial_cmp(b)
+            } else {
+                None
+            }
+        }
+        ExpressionTerm::Base64BinaryLiteral(a) => {
+            if let ExpressionTerm::Base64BinaryLiteral(b) = b {
+                a.partial_cmp(b)
+            } else {
+                None
+            }
+        }
         _ => None,
     }
 }
@@ -5633,20 +6138,74 @@ impl<D: QueryableDataset> Iterator for HashJoinIterator<D> {
     }
 }
 
-struct HashLeftJoinIterator<D: QueryableDataset> {
-    left_iter: InternalTuplesIterator<D>,
-    right: InternalTupleSet<D>,
-    buffered_results: Vec<Result<InternalTuple<D>, QueryEvaluationError>>,
-    expression: Rc<dyn Fn(&InternalTuple<D>) -> Option<bool>>,
+/// Calls a service once per batch of bindings and returns the service results
+struct BoundServiceIterator<D: QueryableDataset> {
+    eval: SimpleEvaluator<D>,
+    service_name: Rc<TupleSelector<D>>,
+    inner: Rc<spargebra::algebra::GraphPattern>,
+    variables: Rc<[Variable]>,
+    keys: Rc<[Variable]>,
+    bindings: hash_set::IntoIter<Vec<Option<GroundTerm>>>,
+    from: InternalTuple<D>,
+    current: InternalTuplesIterator<D>,
 }
 
-impl<D: QueryableDataset> Iterator for HashLeftJoinIterator<D> {
+impl<D: QueryableDataset> Iterator for BoundServiceIterator<D> {
     type Item = Result<InternalTuple<D>, QueryEvaluationError>;
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            if let Some(result) = self.buffered_results.pop() {
-                return Some(result);
+            if let Some(result) = self.current.next() {
+                if let Some(result) = result
+                    .map(|tuple| tuple.combine_with(&self.from))
+                    .transpose()
+                {
+                    return Some(result);
+                }
+                continue;
+            }
+            let batch = self
+                .bindings
+                .by_ref()
+                .take(SERVICE_BINDINGS_BATCH_SIZE)
+                .collect::<Vec<_>>();
+            if batch.is_empty() {
+                return None;
+            }
+            let pattern = spargebra::algebra::GraphPattern::Join {
+                left: Box::new(spargebra::algebra::GraphPattern::Values {
+                    variables: self.keys.to_vec(),
+                    bindings: batch,
+                }),
+                right: Box::new(self.inner.as_ref().clone()),
+            };
+            match self.eval.evaluate_service(
+                &self.service_name,
+                &pattern,
+                Rc::clone(&self.variables),
+                &self.from,
+            ) {
+                Ok(current) => self.current = current,
+                Err(error) => return Some(Err(error)),
+            }
+        }
+    }
+}
+
+struct HashLeftJoinIterator<D: QueryableDataset> {
+    left_iter: InternalTuplesIterator<D>,
+    right: InternalTupleSet<D>,
+    buffered_results: Vec<Result<InternalTuple<D>, QueryEvaluationError>>,
+    expression: Rc<dyn Fn(&InternalTuple<D>) -> Option<bool>>,
+}
+
+impl<D: QueryableDataset> Iterator for HashLeftJoinIterator<D> {
+    type Item = Result<InternalTuple<D>, QueryEvaluationError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(result) = self.buffered_results.pop() {
+                return Some(result);
             }
             let left_tuple = match self.left_iter.next()? {
                 Ok(left_tuple) => left_tuple,
@@ -6104,6 +6663,20 @@ fn look_in_transitive_closure<T: Clone + Eq + Hash, E, NI: Iterator<Item = Resul
     Ok(false)
 }
 
+/// Checks if the solutions of the pattern are sorted by an `ORDER BY` whose order must be kept
+fn is_ordered(pattern: &GraphPattern) -> bool {
+    match pattern {
+        GraphPattern::OrderBy { .. } => true,
+        GraphPattern::Project { inner, .. }
+        | GraphPattern::Filter { inner, .. }
+        | GraphPattern::Extend { inner, .. }
+        | GraphPattern::Distinct { inner }
+        | GraphPattern::Reduced { inner }
+        | GraphPattern::Slice { inner, .. } => is_ordered(inner),
+        _ => false,
+    }
+}
+
 fn hash_deduplicate<T: Eq + Hash + Clone, E>(
     iter: impl Iterator<Item = Result<T, E>>,
 ) -> impl Iterator<Item = Result<T, E>> {
@@ -6217,6 +6790,139 @@ fn write_hexa_bytes(bytes: &[u8], buffer: &mut String) {
     }
 }
 
+/// Returns the local pattern, the service name, the service pattern and the join keys if the join should be evaluated as a bound join
+///
+/// The service might be on both sides of the join, the local pattern is always evaluated first.
+/// Silent services are excluded because a failure must lead to return all the local solutions.
+/// The service handler must have opted in with [`ServiceHandler::supports_bound_join`](crate::ServiceHandler::supports_bound_join).
+fn bound_service_join<'a>(
+    left: &'a GraphPattern,
+    right: &'a GraphPattern,
+    algorithm: &'a JoinAlgorithm,
+    service_handler: &ServiceHandlerRegistry,
+) -> Option<(
+    &'a GraphPattern,
+    &'a NamedNodePattern,
+    &'a GraphPattern,
+    &'a [Variable],
+)> {
+    let JoinAlgorithm::HashBuildLeftProbeRight { keys } = algorithm;
+    if keys.is_empty() {
+        return None;
+    }
+    let (local, name, inner) = match (left, right) {
+        (
+            _,
+            GraphPattern::Service {
+                name,
+                inner,
+                silent: false,
+            },
+        ) => (left, name, inner),
+        (
+            GraphPattern::Service {
+                name,
+                inner,
+                silent: false,
+            },
+            _,
+        ) if !contains_service(right) => (right, name, inner),
+        _ => return None,
+    };
+    let supports_bound_join = service_handler.supports_bound_join(match name {
+        NamedNodePattern::NamedNode(name) => Some(name),
+        NamedNodePattern::Variable(_) => None,
+    });
+    supports_bound_join.then_some((local, name, inner.as_ref(), keys.as_slice()))
+}
+
+fn contains_service(pattern: &GraphPattern) -> bool {
+    match pattern {
+        GraphPattern::QuadPattern { .. }
+        | GraphPattern::Path { .. }
+        | GraphPattern::Graph { .. }
+        | GraphPattern::Values { .. } => false,
+        GraphPattern::Service { .. } => true,
+        GraphPattern::Join { left, right, .. } | GraphPattern::Minus { left, right, .. } => {
+            contains_service(left) || contains_service(right)
+        }
+        #[cfg(feature = "sep-0006")]
+        GraphPattern::Lateral { left, right } => contains_service(left) || contains_service(right),
+        GraphPattern::LeftJoin {
+            left,
+            right,
+            expression,
+            ..
+        } => {
+            contains_service(left)
+                || contains_service(right)
+                || expression_contains_service(expression)
+        }
+        GraphPattern::Filter { expression, inner }
+        | GraphPattern::Extend {
+            inner, expression, ..
+        } => contains_service(inner) || expression_contains_service(expression),
+        GraphPattern::Union { inner } => inner.iter().any(contains_service),
+        GraphPattern::OrderBy { inner, expression } => {
+            contains_service(inner)
+                || expression.iter().any(|e| match e {
+                    OrderExpression::Asc(e) | OrderExpression::Desc(e) => {
+                        expression_contains_service(e)
+                    }
+                })
+        }
+        GraphPattern::Project { inner, .. }
+        | GraphPattern::Distinct { inner }
+        | GraphPattern::Reduced { inner }
+        | GraphPattern::Slice { inner, .. } => contains_service(inner),
+        GraphPattern::Group {
+            inner, aggregates, ..
+        } => {
+            contains_service(inner)
+                || aggregates.iter().any(|(_, aggregate)| match aggregate {
+                    AggregateExpression::CountSolutions { .. } => false,
+                    AggregateExpression::FunctionCall { expr, .. } => {
+                        expression_contains_service(expr)
+                    }
+                })
+        }
+    }
+}
+
+fn expression_contains_service(expression: &Expression) -> bool {
+    match expression {
+        Expression::NamedNode(_)
+        | Expression::Literal(_)
+        | Expression::Variable(_)
+        | Expression::Bound(_) => false,
+        Expression::Exists(pattern) => contains_service(pattern),
+        Expression::Or(inner)
+        | Expression::And(inner)
+        | Expression::Coalesce(inner)
+        | Expression::FunctionCall(_, inner) => inner.iter().any(expression_contains_service),
+        Expression::Equal(a, b)
+        | Expression::SameTerm(a, b)
+        | Expression::Greater(a, b)
+        | Expression::GreaterOrEqual(a, b)
+        | Expression::Less(a, b)
+        | Expression::LessOrEqual(a, b)
+        | Expression::Add(a, b)
+        | Expression::Subtract(a, b)
+        | Expression::Multiply(a, b)
+        | Expression::Divide(a, b) => {
+            expression_contains_service(a) || expression_contains_service(b)
+        }
+        Expression::UnaryPlus(inner) | Expression::UnaryMinus(inner) | Expression::Not(inner) => {
+            expression_contains_service(inner)
+        }
+        Expression::If(a, b, c) => {
+            expression_contains_service(a)
+                || expression_contains_service(b)
+                || expression_contains_service(c)
+        }
+    }
+}
+
 fn error_evaluator<D: QueryableDataset>(
     error: QueryEvaluationError,
 ) -> Rc<dyn Fn(InternalTuple<D>) -> InternalTuplesIterator<D>> {
@@ -6291,6 +6997,602 @@ impl<D: QueryableDataset> Extend<InternalTuple<D>> for InternalTupleSet<D> {
     }
 }
 
+fn compare_tuples<D: QueryableDataset>(
+    by: &[ComparatorFunction<D>],
+    a: &InternalTuple<D>,
+    b: &InternalTuple<D>,
+) -> Ordering {
+    for comp in by {
+        match comp {
+            ComparatorFunction::Asc(expression) => {
+                match cmp_terms(expression(a).as_ref(), expression(b).as_ref()) {
+                    Ordering::Greater => return Ordering::Greater,
+                    Ordering::Less => return Ordering::Less,
+                    Ordering::Equal => (),
+                }
+            }
+            ComparatorFunction::Desc(expression) => {
+                match cmp_terms(expression(a).as_ref(), expression(b).as_ref()) {
+                    Ordering::Greater => return Ordering::Less,
+                    Ordering::Less => return Ordering::Greater,
+                    Ordering::Equal => (),
+                }
+            }
+        }
+    }
+    Ordering::Equal
+}
+
+/// Merges runs of tuples sorted with the same comparator
+struct SortedRunsMergeIterator<D: QueryableDataset> {
+    runs: Vec<InternalTuplesIterator<D>>,
+    heads: Vec<Option<InternalTuple<D>>>,
+    by: Rc<[ComparatorFunction<D>]>,
+    error: Option<QueryEvaluationError>,
+}
+
+impl<D: QueryableDataset> SortedRunsMergeIterator<D> {
+    fn new(
+        mut runs: Vec<InternalTuplesIterator<D>>,
+        by: Rc<[ComparatorFunction<D>]>,
+    ) -> Result<Self, QueryEvaluationError> {
+        let heads = runs
+            .iter_mut()
+            .map(|run| run.next().transpose())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            runs,
+            heads,
+            by,
+            error: None,
+        })
+    }
+}
+
+impl<D: QueryableDataset> Iterator for SortedRunsMergeIterator<D> {
+    type Item = Result<InternalTuple<D>, QueryEvaluationError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(error) = self.error.take() {
+            return Some(Err(error));
+        }
+        let (index, _) = self
+            .heads
+            .iter()
+            .enumerate()
+            .filter_map(|(i, head)| Some((i, head.as_ref()?)))
+            .min_by(|(_, a), (_, b)| compare_tuples(&self.by, a, b))?;
+        let tuple = self.heads[index].take()?;
+        match self.runs[index].next().transpose() {
+            Ok(head) => self.heads[index] = head,
+            Err(error) => self.error = Some(error),
+        }
+        Some(Ok(tuple))
+    }
+}
+
+/// Evaluates `GROUP BY`.
+///
+/// If the memory budget is exceeded, the tuples of the groups that are not already in memory are partitioned into files
+/// that are aggregated once the in-memory groups have been returned.
+struct GroupEvaluator<D: QueryableDataset> {
+    key_variables: Rc<[usize]>,
+    accumulator_builders: Vec<Box<dyn Fn() -> AccumulatorWrapper<D>>>,
+    accumulator_variables: Vec<usize>,
+    dataset: EvalDataset<D>,
+    memory: Option<Arc<MemoryTracker>>,
+}
+
+impl<D: QueryableDataset> GroupEvaluator<D> {
+    fn aggregate(
+        self: &Rc<Self>,
+        tuples: InternalTuplesIterator<D>,
+        tuple_size: usize,
+        level: u64,
+    ) -> InternalTuplesIterator<D> {
+        let mut errors = Vec::default();
+        let mut accumulators_for_group =
+            FxHashMap::<Vec<Option<D::InternalTerm>>, Vec<AccumulatorWrapper<D>>>::default();
+        if self.key_variables.is_empty() {
+            // There is always a single group if there is no GROUP BY
+            accumulators_for_group.insert(Vec::new(), self.new_accumulators());
+        }
+        let mut reservation = self.memory.as_ref().map(MemoryTracker::reservation);
+        let mut partitions = (0..GROUP_SPILL_PARTITIONS)
+            .map(|_| None)
+            .collect::<Vec<Option<SpillWriter>>>();
+        for result in tuples {
+            let tuple = match result {
+                Ok(tuple) => tuple,
+                Err(error) => {
+                    errors.push(error);
+                    continue;
+                }
+            };
+            // TODO avoid copy for key?
+            let key = self
+                .key_variables
+                .iter()
+                .map(|v| tuple.get(*v).cloned())
+                .collect::<Vec<_>>();
+            if let Some(key_accumulators) = accumulators_for_group.get_mut(&key) {
+                for accumulator in key_accumulators {
+                    accumulator.add(&tuple);
+                }
+                continue;
+            }
+            if let (Some(memory), Some(reservation)) = (&self.memory, &mut reservation) {
+                let size = size_of::<Vec<Option<D::InternalTerm>>>()
+                    + key.len() * size_of::<Option<D::InternalTerm>>()
+                    + self.accumulator_builders.len() * size_of::<AccumulatorWrapper<D>>();
+                if !reservation.try_grow(size) {
+                    if !accumulators_for_group.is_empty() {
+                        if let Err(e) = self.spill(memory, &mut partitions, &key, &tuple, level) {
+                            return Box::new(once(Err(e)));
+                        }
+                        continue;
+                    }
+                    // We always keep a group in memory to make sure the evaluation progresses
+                    reservation.grow(size);
+                }
+            }
+            let mut key_accumulators = self.new_accumulators();
+            for accumulator in &mut key_accumulators {
+                accumulator.add(&tuple);
+            }
+            accumulators_for_group.insert(key, key_accumulators);
+        }
+        let this = Rc::clone(self);
+        let groups = ReservedIterator {
+            inner: accumulators_for_group
+                .into_iter()
+                .map(move |(key, accumulators)| this.finish(key, accumulators, tuple_size)),
+            reservation,
+        };
+        let this = Rc::clone(self);
+        let spilled_groups = partitions.into_iter().flatten().flat_map(move |partition| {
+            match partition.into_reader() {
+                Ok(reader) => this.aggregate(
+                    Box::new(SpilledTuplesIterator {
+                        dataset: this.dataset.clone(),
+                        reader,
+                    }),
+                    tuple_size,
+                    level + 1,
+                ),
+                Err(e) => Box::new(once(Err(QueryEvaluationError::Spill(e)))),
+            }
+        });
+        Box::new(
+            errors
+                .into_iter()
+                .map(Err)
+                .chain(groups)
+                .chain(spilled_groups),
+        )
+    }
+
+    fn new_accumulators(&self) -> Vec<AccumulatorWrapper<D>> {
+        self.accumulator_builders.iter().map(|c| c()).collect()
+    }
+
+    /// Writes the tuple to the partition file of its group
+    fn spill(
+        &self,
+        memory: &MemoryTracker,
+        partitions: &mut [Option<SpillWriter>],
+        key: &[Option<D::InternalTerm>],
+        tuple: &InternalTuple<D>,
+        level: u64,
+    ) -> Result<(), QueryEvaluationError> {
+        // The level is part of the hash to split differently the groups of a partition that is itself spilled
+        let mut hasher = FxHasher::default();
+        level.hash(&mut hasher);
+        key.hash(&mut hasher);
+        let partition =
+            &mut partitions[usize::from(hasher.finish().to_be_bytes()[0]) % GROUP_SPILL_PARTITIONS];
+        let writer = match partition.take() {
+            Some(writer) => writer,
+            None => memory.spill_file().map_err(QueryEvaluationError::Spill)?,
+        };
+        write_spilled_tuple(&self.dataset, partition.insert(writer), tuple)
+    }
+
+    fn finish(
+        &self,
+        key: Vec<Option<D::InternalTerm>>,
+        accumulators: Vec<AccumulatorWrapper<D>>,
+        tuple_size: usize,
+    ) -> Result<InternalTuple<D>, QueryEvaluationError> {
+        let mut result = InternalTuple::with_capacity(tuple_size);
+        for (variable, value) in self.key_variables.iter().zip(key) {
+            if let Some(value) = value {
+                result.set(*variable, value);
+            }
+        }
+        for (accumulator, variable) in accumulators.into_iter().zip(&self.accumulator_variables) {
+            if let Some(value) = accumulator.finish() {
+                result.set(*variable, self.dataset.internalize_expression_term(value)?);
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Evaluates `DISTINCT` when a memory budget is set.
+///
+/// The solutions are returned as soon as they are read.
+/// When the budget is exceeded, a Bloom filter tells which of the solutions not kept in memory are new and can be returned directly.
+/// All the solutions not kept in memory are partitioned into files that are deduplicated once the input is exhausted,
+/// the ones already returned being only used to filter out their duplicates.
+/// The solutions order is not kept so it is not used on top of an `ORDER BY`.
+struct DistinctIterator<D: QueryableDataset> {
+    /// The input solutions, with `true` if they have already been returned
+    input: Box<dyn Iterator<Item = Result<(InternalTuple<D>, bool), QueryEvaluationError>>>,
+    dataset: EvalDataset<D>,
+    memory: Arc<MemoryTracker>,
+    level: u64,
+    seen: FxHashSet<InternalTuple<D>>,
+    reservation: MemoryReservation,
+    bloom_filter: Option<BloomFilter>,
+    /// The files of the already returned and of the pending solutions
+    partitions: Vec<Option<(SpillWriter, SpillWriter)>>,
+    spilled: Option<InternalTuplesIterator<D>>,
+}
+
+impl<D: QueryableDataset> DistinctIterator<D> {
+    fn new(
+        input: Box<dyn Iterator<Item = Result<(InternalTuple<D>, bool), QueryEvaluationError>>>,
+        dataset: EvalDataset<D>,
+        memory: Arc<MemoryTracker>,
+        level: u64,
+    ) -> Self {
+        Self {
+            input,
+            dataset,
+            reservation: memory.reservation(),
+            memory,
+            level,
+            seen: FxHashSet::default(),
+            bloom_filter: None,
+            partitions: (0..DISTINCT_SPILL_PARTITIONS).map(|_| None).collect(),
+            spilled: None,
+        }
+    }
+
+    /// Returns the tuple if it must be returned now
+    fn add(
+        &mut self,
+        tuple: InternalTuple<D>,
+        returned: bool,
+    ) -> Result<Option<InternalTuple<D>>, QueryEvaluationError> {
+        if self.seen.contains(&tuple) {
+            return Ok(None);
+        }
+        if self.bloom_filter.is_none() {
+            let size = tuple.estimated_size();
+            let fits = self.reservation.try_grow(size);
+            if fits || self.seen.is_empty() {
+                if !fits {
+                    // We always keep a solution in memory to make sure the evaluation progresses
+                    self.reservation.grow(size);
+                }
+                self.seen.insert(tuple.clone());
+                return Ok((!returned).then_some(tuple));
+            }
+        }
+        let reservation = &mut self.reservation;
+        let bloom_filter = self.bloom_filter.get_or_insert_with(|| {
+            let bloom_filter = BloomFilter::new(reservation.max_bytes() / 16);
+            reservation.grow(bloom_filter.size());
+            bloom_filter
+        });
+        let mut hasher = FxHasher::default();
+        tuple.hash(&mut hasher);
+        let hash = hasher.finish();
+        if returned || !bloom_filter.contains(hash) {
+            bloom_filter.insert(hash);
+            self.spill(&tuple, true)?;
+            return Ok((!returned).then_some(tuple));
+        }
+        // It might be a duplicate, we check it later
+        self.spill(&tuple, false)?;
+        Ok(None)
+    }
+
+    /// Writes the tuple to its partition file
+    fn spill(
+        &mut self,
+        tuple: &InternalTuple<D>,
+        returned: bool,
+    ) -> Result<(), QueryEvaluationError> {
+        // The level is part of the hash to split differently the tuples of a partition that is itself spilled
+        let mut hasher = FxHasher::default();
+        self.level.hash(&mut hasher);
+        tuple.hash(&mut hasher);
+        let partition = &mut self.partitions
+            [usize::from(hasher.finish().to_be_bytes()[0]) % DISTINCT_SPILL_PARTITIONS];
+        let (returned_writer, pending_writer) = match partition {
+            Some(writers) => writers,
+            None => partition.insert((
+                self.memory
+                    .spill_file()
+                    .map_err(QueryEvaluationError::Spill)?,
+                self.memory
+                    .spill_file()
+                    .map_err(QueryEvaluationError::Spill)?,
+            )),
+        };
+        write_spilled_tuple(
+            &self.dataset,
+            if returned {
+                returned_writer
+            } else {
+                pending_writer
+            },
+            tuple,
+        )
+    }
+
+    /// Deduplicates the spilled partitions one after the other
+    fn spilled_solutions(&mut self) -> InternalTuplesIterator<D> {
+        // The input and the in-memory solutions are not useful anymore
+        self.input = Box::new(empty());
+        self.seen = FxHashSet::default();
+        self.bloom_filter = None;
+        self.reservation.release();
+        let dataset = self.dataset.clone();
+        let memory = Arc::clone(&self.memory);
+        let level = self.level + 1;
+        Box::new(take(&mut self.partitions).into_iter().flatten().flat_map(
+            move |(returned, pending)| -> InternalTuplesIterator<D> {
+                let (returned, pending) = match (returned.into_reader(), pending.into_reader()) {
+                    (Ok(returned), Ok(pending)) => (returned, pending),
+                    (Err(e), _) | (_, Err(e)) => {
+                        return Box::new(once(Err(QueryEvaluationError::Spill(e))));
+                    }
+                };
+                // The already returned solutions are read first to filter out their duplicates
+                let input = SpilledTuplesIterator {
+                    dataset: dataset.clone(),
+                    reader: returned,
+                }
+                .map(|tuple| Ok((tuple?, true)))
+                .chain(
+                    SpilledTuplesIterator {
+                        dataset: dataset.clone(),
+                        reader: pending,
+                    }
+                    .map(|tuple| Ok((tuple?, false))),
+                );
+                Box::new(Self::new(
+                    Box::new(input),
+                    dataset.clone(),
+                    Arc::clone(&memory),
+                    level,
+                ))
+            },
+        ))
+    }
+}
+
+impl<D: QueryableDataset> Iterator for DistinctIterator<D> {
+    type Item = Result<InternalTuple<D>, QueryEvaluationError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(spilled) = &mut self.spilled {
+                return spilled.next();
+            }
+            let Some(result) = self.input.next() else {
+                self.spilled = Some(self.spilled_solutions());
+                continue;
+            };
+            let (tuple, returned) = match result {
+                Ok(result) => result,
+                Err(e) => return Some(Err(e)),
+            };
+            match self.add(tuple, returned) {
+                Ok(Some(tuple)) => return Some(Ok(tuple)),
+                Ok(None) => (),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// A Bloom filter of hashes
+struct BloomFilter {
+    bits: Vec<u64>,
+}
+
+impl BloomFilter {
+    /// Builds a filter of roughly `size` bytes
+    fn new(size: usize) -> Self {
+        Self {
+            bits: vec![0; (size / 8).max(16)],
+        }
+    }
+
+    fn size(&self) -> usize {
+        self.bits.len() * 8
+    }
+
+    fn insert(&mut self, hash: u64) {
+        for (word, mask) in self.bit_positions(hash) {
+            if let Some(word) = self.bits.get_mut(word) {
+                *word |= mask;
+            }
+        }
+    }
+
+    fn contains(&self, hash: u64) -> bool {
+        self.bit_positions(hash)
+            .all(|(word, mask)| self.bits.get(word).is_some_and(|word| word & mask != 0))
+    }
+
+    /// The bits to set, computed by double hashing
+    fn bit_positions(&self, hash: u64) -> impl Iterator<Item = (usize, u64)> {
+        let bit_count = u64::try_from(self.bits.len())
+            .unwrap_or(u64::MAX)
+            .saturating_mul(64);
+        let step = hash.rotate_left(32) | 1;
+        (0..BLOOM_FILTER_HASHES).filter_map(move |i| {
+            let position = hash.wrapping_add(i.wrapping_mul(step)) % bit_count;
+            Some((usize::try_from(position / 64).ok()?, 1 << (position % 64)))
+        })
+    }
+}
+
+/// Writes the tuples to a temporary file
+fn spill_tuples<D: QueryableDataset>(
+    dataset: &EvalDataset<D>,
+    memory: &MemoryTracker,
+    tuples: impl IntoIterator<Item = InternalTuple<D>>,
+) -> Result<SpillReader, QueryEvaluationError> {
+    let mut writer = memory.spill_file().map_err(QueryEvaluationError::Spill)?;
+    for tuple in tuples {
+        write_spilled_tuple(dataset, &mut writer, &tuple)?;
+    }
+    writer.into_reader().map_err(QueryEvaluationError::Spill)
+}
+
+/// Writes a tuple as a line of tab-separated terms, unbound values are empty
+fn write_spilled_tuple<D: QueryableDataset>(
+    dataset: &EvalDataset<D>,
+    writer: &mut SpillWriter,
+    tuple: &InternalTuple<D>,
+) -> Result<(), QueryEvaluationError> {
+    let mut line = String::new();
+    for (i, value) in tuple.iter().enumerate() {
+        if i > 0 {
+            line.push('\t');
+        }
+        if let Some(value) = value {
+            line.push_str(&dataset.externalize_term(value)?.to_string());
+        }
+    }
+    writer
+        .write_line(&line)
+        .map_err(QueryEvaluationError::Spill)
+}
+
+/// Reads back the tuples written with [`write_spilled_tuple`]
+struct SpilledTuplesIterator<D: QueryableDataset> {
+    dataset: EvalDataset<D>,
+    reader: SpillReader,
+}
+
+impl<D: QueryableDataset> Iterator for SpilledTuplesIterator<D> {
+    type Item = Result<InternalTuple<D>, QueryEvaluationError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let line = match self.reader.next_line()? {
+            Ok(line) => line,
+            Err(e) => return Some(Err(QueryEvaluationError::Spill(e))),
+        };
+        let mut tuple = InternalTuple::with_capacity(0);
+        for (i, value) in line.split('\t').enumerate() {
+            if !value.is_empty() {
+                let term = match value.parse::<Term>() {
+                    Ok(term) => term,
+                    Err(e) => {
+                        return Some(Err(QueryEvaluationError::Spill(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            e,
+                        ))))
+                    }
+                };
+                match self.dataset.internalize_term(term) {
+                    Ok(term) => tuple.set(i, term),
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+        }
+        Some(Ok(tuple))
+    }
+}
+
+/// Keeps some memory reserved until the inner iterator is exhausted
+struct ReservedIterator<I: Iterator> {
+    inner: I,
+    reservation: Option<MemoryReservation>,
+}
+
+impl<I: Iterator> Iterator for ReservedIterator<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let next = self.inner.next();
+        if next.is_none() {
+            self.reservation = None;
+        }
+        next
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// Accounts the memory used by a tuple kept by a join, fails if the memory budget is exceeded
+fn reserve_tuple_memory<D: QueryableDataset>(
+    reservation: &mut Option<MemoryReservation>,
+    tuple: &InternalTuple<D>,
+) -> Result<(), QueryEvaluationError> {
+    let Some(reservation) = reservation else {
+        return Ok(());
+    };
+    if reservation.try_grow(tuple.estimated_size()) {
+        Ok(())
+    } else {
+        Err(QueryEvaluationError::MemoryBudgetExceeded(
+            reservation.max_bytes(),
+        ))
+    }
+}
+
+struct ExpressionErrorIterator<D: QueryableDataset> {
+    inner: InternalTuplesIterator<D>,
+    error: Rc<RefCell<Option<QueryEvaluationError>>>,
+}
+
+impl<D: QueryableDataset> Iterator for ExpressionErrorIterator<D> {
+    type Item = Result<InternalTuple<D>, QueryEvaluationError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.inner.next();
+        // The solution has been computed using an erroneous expression, we return the error instead
+        if let Some(error) = self.error.take() {
+            return Some(Err(error));
+        }
+        next
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+struct CancellableIterator<D: QueryableDataset> {
+    inner: Option<InternalTuplesIterator<D>>,
+    cancellation_token: CancellationToken,
+}
+
+impl<D: QueryableDataset> Iterator for CancellableIterator<D> {
+    type Item = Result<InternalTuple<D>, QueryEvaluationError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let inner = self.inner.as_mut()?;
+        if self.cancellation_token.is_cancelled() {
+            // We return the error only once to make sure the evaluation stops
+            self.inner = None;
+            return Some(Err(QueryEvaluationError::Cancelled));
+        }
+        inner.next()
+    }
+}
+
 struct StatsIterator<D: QueryableDataset> {
     inner: InternalTuplesIterator<D>,
     stats: Rc<EvalNodeWithStats>,
@@ -6319,6 +7621,8 @@ impl<D: QueryableDataset> Iterator for StatsIterator<D> {
 pub struct EvalNodeWithStats {
     pub label: String,
     pub children: Vec<Rc<EvalNodeWithStats>>,
+    /// Number of input tuples i.e. number of times the node has been evaluated
+    pub input_count: Cell<usize>,
     pub exec_count: Cell<usize>,
     pub exec_duration: Cell<Option<DayTimeDuration>>,
 }
@@ -6333,6 +7637,8 @@ impl EvalNodeWithStats {
         writer.write_event(JsonEvent::ObjectKey("name".into()))?;
         writer.write_event(JsonEvent::String((&self.label).into()))?;
         if with_stats {
+            writer.write_event(JsonEvent::ObjectKey("number of inputs".into()))?;
+            writer.write_event(JsonEvent::Number(self.input_count.get().to_string().into()))?;
             writer.write_event(JsonEvent::ObjectKey("number of results".into()))?;
             writer.write_event(JsonEvent::Number(self.exec_count.get().to_string().into()))?;
             if let Some(duration) = self.exec_duration.get() {
@@ -6348,6 +7654,51 @@ impl EvalNodeWithStats {
         writer.write_event(JsonEvent::EndArray)?;
         writer.write_event(JsonEvent::EndObject)
     }
+
+    pub fn text_node(
+        &self,
+        writer: &mut impl io::Write,
+        with_stats: bool,
+        prefix: &str,
+        child_prefix: &str,
+    ) -> io::Result<()> {
+        write!(writer, "{prefix}{}", self.label)?;
+        if with_stats {
+            write!(
+                writer,
+                " [inputs: {}, results: {}",
+                self.input_count.get(),
+                self.exec_count.get()
+            )?;
+            if let Some(duration) = self.exec_duration.get() {
+                write!(
+                    writer,
+                    ", duration: {}s",
+                    f32::from(Float::from(duration.as_seconds()))
+                )?;
+            }
+            write!(writer, "]")?;
+        }
+        writeln!(writer)?;
+        for (i, child) in self.children.iter().enumerate() {
+            if i + 1 == self.children.len() {
+                child.text_node(
+                    writer,
+                    with_stats,
+                    &format!("{child_prefix}└─ "),
+                    &format!("{child_prefix}   "),
+                )?;
+            } else {
+                child.text_node(
+                    writer,
+                    with_stats,
+                    &format!("{child_prefix}├─ "),
+                    &format!("{child_prefix}│  "),
+                )?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl fmt::Debug for EvalNodeWithStats {
@@ -6355,6 +7706,7 @@ impl fmt::Debug for EvalNodeWithStats {
         let mut obj = f.debug_struct("Node");
         obj.field("name", &self.label);
         if let Some(exec_duration) = self.exec_duration.get() {
+            obj.field("number of inputs", &self.input_count.get());
             obj.field("number of results", &self.exec_count.get());
             obj.field(
                 "duration in seconds",
@@ -6368,7 +7720,7 @@ impl fmt::Debug for EvalNodeWithStats {
     }
 }
 
-fn eval_node_label(node: &GraphPattern) -> String {
+fn eval_node_label(node: &GraphPattern, service_handler: &ServiceHandlerRegistry) -> String {
     match node {
         GraphPattern::Distinct { .. } => "Distinct(Hash)".to_owned(),
         GraphPattern::Extend {
@@ -6399,12 +7751,22 @@ fn eval_node_label(node: &GraphPattern) -> String {
                 ))
             )
         }
-        GraphPattern::Join { algorithm, .. } => match algorithm {
-            JoinAlgorithm::HashBuildLeftProbeRight { keys } => format!(
-                "LeftJoin(HashBuildLeftProbeRight, keys = {})",
-                format_list(keys)
-            ),
-        },
+        GraphPattern::Join {
+            left,
+            right,
+            algorithm,
+        } => {
+            if bound_service_join(left, right, algorithm, service_handler).is_some() {
+                let JoinAlgorithm::HashBuildLeftProbeRight { keys } = algorithm;
+                return format!("BoundServiceJoin(keys = {})", format_list(keys));
+            }
+            match algorithm {
+                JoinAlgorithm::HashBuildLeftProbeRight { keys } => format!(
+                    "LeftJoin(HashBuildLeftProbeRight, keys = {})",
+                    format_list(keys)
+                ),
+            }
+        }
         #[cfg(feature = "sep-0006")]
         GraphPattern::Lateral { right, .. } => {
             if let GraphPattern::LeftJoin {
@@ -6526,6 +7888,11 @@ impl Timer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{QueryEvaluator, QueryResults, ServiceHandler};
+    use oxrdf::{Dataset, GraphName, Quad};
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{self, AtomicUsize};
+    use std::sync::Mutex;
 
     #[test]
     fn uuid() {
@@ -6538,4 +7905,786 @@ mod tests {
             "{buffer} is not a valid UUID"
         );
     }
+
+    #[derive(Default)]
+    struct ConcatAccumulator(String);
+
+    impl AggregateFunctionAccumulator for ConcatAccumulator {
+        fn accumulate(&mut self, element: Term) {
+            if let Term::Literal(literal) = element {
+                self.0.push_str(literal.value());
+            }
+        }
+
+        fn finish(&mut self) -> Option<Term> {
+            Some(Literal::from(take(&mut self.0)).into())
+        }
+    }
+
+    fn evaluate_concat(query: &str) -> Vec<Option<Term>> {
+        let evaluator = QueryEvaluator::new().with_custom_aggregate_function(
+            NamedNode::new_unchecked("http://example.com/concat"),
+            || Box::<ConcatAccumulator>::default(),
+        );
+        let query = spargebra::Query::parse(query, None).unwrap();
+        let QueryResults::Solutions(solutions) = evaluator.execute(Dataset::new(), &query).unwrap()
+        else {
+            unreachable!()
+        };
+        solutions.map(|s| s.unwrap().get("c").cloned()).collect()
+    }
+
+    #[test]
+    fn custom_aggregate() {
+        assert_eq!(
+            evaluate_concat(
+                "SELECT ?k (<http://example.com/concat>(?v) AS ?c) WHERE { VALUES (?k ?v) { (1 'a') (1 'b') (2 'c') } } GROUP BY ?k ORDER BY ?k"
+            ),
+            [Some(Literal::from("ab").into()), Some(Literal::from("c").into())]
+        );
+        assert_eq!(
+            evaluate_concat(
+                "SELECT (<http://example.com/concat>(DISTINCT ?v) AS ?c) WHERE { VALUES ?v { 'a' 'a' } }"
+            ),
+            [Some(Literal::from("a").into())]
+        );
+        assert_eq!(
+            evaluate_concat(
+                "SELECT (<http://example.com/concat>(?v) AS ?c) WHERE { VALUES ?v { 'a' 'b' } }"
+            ),
+            [None, None]
+        );
+    }
+
+    /// A dataset with the `0` to `count - 1` integers as objects
+    fn integers(count: i64) -> Dataset {
+        let ex = NamedNode::new_unchecked("http://example.com");
+        (0..count)
+            .map(|i| {
+                Quad::new(
+                    ex.clone(),
+                    ex.clone(),
+                    Literal::from(i),
+                    GraphName::DefaultGraph,
+                )
+            })
+            .collect()
+    }
+
+    fn evaluate(
+        evaluator: &QueryEvaluator,
+        dataset: Dataset,
+        query: &str,
+    ) -> Vec<Vec<Option<Term>>> {
+        let query = spargebra::Query::parse(query, None).unwrap();
+        let QueryResults::Solutions(solutions) = evaluator.execute(dataset, &query).unwrap() else {
+            unreachable!()
+        };
+        solutions.map(|s| s.unwrap().values().to_vec()).collect()
+    }
+
+    #[test]
+    fn order_by_with_limit_and_offset() {
+        let count = i64::try_from(2 * MAX_TOP_K_LEN).unwrap();
+        let dataset = integers(count);
+        for (limit, offset) in [
+            (3, 0),
+            (3, 2),
+            // Just below, at and just above the top-k limit
+            (MAX_TOP_K_LEN - 2, 1),
+            (MAX_TOP_K_LEN - 1, 1),
+            (MAX_TOP_K_LEN, 1),
+            (MAX_TOP_K_LEN + 1, 0),
+        ] {
+            let expected = (0..count)
+                .rev()
+                .skip(offset)
+                .take(limit)
+                .map(|i| vec![Some(Literal::from(i).into())])
+                .collect::<Vec<_>>();
+            assert_eq!(
+                evaluate(
+                    &QueryEvaluator::new(),
+                    dataset.clone(),
+                    &format!(
+                        "SELECT ?o WHERE {{ ?s ?p ?o }} ORDER BY DESC(?o) LIMIT {limit} OFFSET {offset}"
+                    )
+                ),
+                expected,
+                "LIMIT {limit} OFFSET {offset}"
+            );
+        }
+    }
+
+    #[test]
+    fn order_by_with_limit_and_ties() {
+        // The keys are 0 for ?o in 0..10, 1 for ?o in 10..20...
+        let solutions = evaluate(
+            &QueryEvaluator::new(),
+            integers(100),
+            "SELECT ?k ?o WHERE { ?s ?p ?o BIND(FLOOR(?o / 10) AS ?k) } ORDER BY ?k LIMIT 15",
+        );
+        assert_eq!(solutions.len(), 15);
+        let objects = solutions
+            .iter()
+            .map(|solution| match &solution[1] {
+                Some(Term::Literal(o)) => o.value().parse::<i64>().unwrap(),
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        let mut first = objects[..10].to_vec();
+        first.sort_unstable();
+        assert_eq!(first, (0..10).collect::<Vec<_>>());
+        let mut last = objects[10..].to_vec();
+        last.sort_unstable();
+        last.dedup();
+        assert_eq!(last.len(), 5);
+        assert!(last.iter().all(|o| (10..20).contains(o)), "{last:?}");
+    }
+
+    #[test]
+    fn slice_through_union_projection_and_optional() {
+        let evaluator = QueryEvaluator::new();
+        let dataset = integers(100);
+        for pattern in [
+            "{ ?s ?p ?o FILTER(?o < 5) } UNION { ?s ?p ?o FILTER(?o >= 95) }",
+            "?s ?p ?o BIND(?o + 1 AS ?x)",
+            "?s ?p ?o OPTIONAL { ?s ?p ?o2 FILTER(?o2 = ?o + 1) }",
+        ] {
+            let all = evaluate(
+                &evaluator,
+                dataset.clone(),
+                &format!("SELECT ?o WHERE {{ {pattern} }}"),
+            );
+            assert_eq!(
+                evaluate(
+                    &evaluator,
+                    dataset.clone(),
+                    &format!("SELECT ?o WHERE {{ {pattern} }} LIMIT 4 OFFSET 3"),
+                ),
+                all[3..7],
+                "{pattern}"
+            );
+        }
+    }
+
+    #[test]
+    fn distinct_with_spill() {
+        // Each key is given by two solutions
+        let query = "SELECT DISTINCT ?k WHERE { ?s ?p ?o BIND(FLOOR(?o / 2) AS ?k) }";
+        let dataset = integers(4000);
+        let mut expected = evaluate(&QueryEvaluator::new(), dataset.clone(), query);
+        assert_eq!(expected.len(), 2000);
+        // The budget is so small that the partitions are spilled again
+        let mut solutions = evaluate(
+            &QueryEvaluator::new().with_memory_budget(1000),
+            dataset,
+            query,
+        );
+        assert_eq!(solutions.len(), expected.len());
+        solutions.sort_unstable_by_key(|solution| solution[0].as_ref().map(Term::to_string));
+        solutions.dedup();
+        assert_eq!(
+            solutions.len(),
+            expected.len(),
+            "Duplicates have been returned"
+        );
+        expected.sort_unstable_by_key(|solution| solution[0].as_ref().map(Term::to_string));
+        assert_eq!(solutions, expected);
+    }
+
+    #[test]
+    fn distinct_with_order_by_and_budget() {
+        let query =
+            "SELECT DISTINCT ?k WHERE { ?s ?p ?o BIND(FLOOR(?o / 2) AS ?k) } ORDER BY DESC(?k)";
+        let dataset = integers(4000);
+        let expected = evaluate(&QueryEvaluator::new(), dataset.clone(), query);
+        assert_eq!(expected.len(), 2000);
+        assert_eq!(
+            evaluate(
+                &QueryEvaluator::new().with_memory_budget(1000),
+                dataset,
+                query,
+            ),
+            expected
+        );
+    }
+
+    #[test]
+    fn bounded_path_expansion() {
+        let ex = |name: &str| NamedNode::new_unchecked(format!("http://example.com/{name}"));
+        let dataset = [
+            ("a", "b", GraphName::DefaultGraph),
+            ("b", "c", GraphName::DefaultGraph),
+            ("c", "d", GraphName::DefaultGraph),
+            ("e", "e", GraphName::DefaultGraph),
+            ("b", "c", ex("g").into()),
+        ]
+        .into_iter()
+        .map(|(s, o, g)| Quad::new(ex(s), ex("p"), ex(o), g))
+        .collect::<Dataset>();
+        let with_expansion = QueryEvaluator::new();
+        let without_expansion = QueryEvaluator::new()
+            .with_optimizer(sparopt::Optimizer::default_passes().without_pass("path-expansion"));
+        for query in [
+            "SELECT * WHERE { ?s <http://example.com/p>? ?o }",
+            "SELECT * WHERE { <http://example.com/a> <http://example.com/p>? ?o }",
+            "SELECT * WHERE { ?s <http://example.com/p>? <http://example.com/c> }",
+            "SELECT * WHERE { <http://example.com/a> <http://example.com/p>? <http://example.com/a> }",
+            "SELECT * WHERE { <http://example.com/a> <http://example.com/p>? <http://example.com/d> }",
+            "SELECT * WHERE { <http://example.com/z> <http://example.com/p>? <http://example.com/z> }",
+            "SELECT * WHERE { ?x <http://example.com/p>? ?x }",
+            "SELECT * WHERE { GRAPH ?g { ?s <http://example.com/p>? ?o } }",
+            "SELECT * WHERE { ?s <http://example.com/p>/<http://example.com/p>? ?o }",
+            "SELECT * WHERE { ?s <http://example.com/p>/(<http://example.com/p>/<http://example.com/p>?)? ?o }",
+        ] {
+            let mut expected = evaluate(&without_expansion, dataset.clone(), query);
+            expected.sort_unstable_by_key(|solution| format!("{solution:?}"));
+            let mut solutions = evaluate(&with_expansion, dataset.clone(), query);
+            solutions.sort_unstable_by_key(|solution| format!("{solution:?}"));
+            assert_eq!(solutions, expected, "{query}");
+        }
+    }
+
+    fn join_dataset() -> Dataset {
+        let ex = |name: String| NamedNode::new_unchecked(format!("http://example.com/{name}"));
+        (0..50_i64)
+            .flat_map(|i| {
+                [
+                    Quad::new(
+                        ex(format!("s{i}")),
+                        ex("p".into()),
+                        Literal::from(i),
+                        GraphName::DefaultGraph,
+                    ),
+                    Quad::new(
+                        ex(format!("s{i}")),
+                        ex("q".into()),
+                        ex(format!("o{}", i % 5)),
+                        GraphName::DefaultGraph,
+                    ),
+                    Quad::new(
+                        ex(format!("o{}", i % 5)),
+                        ex("r".into()),
+                        Literal::from(i % 7),
+                        ex("g".into()),
+                    ),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parallel_execution() {
+        let dataset = join_dataset();
+        let factory_calls = Arc::new(AtomicUsize::new(0));
+        let evaluator = QueryEvaluator::new();
+        for query in [
+            "SELECT * WHERE { { ?s <http://example.com/p> ?o } UNION { ?s <http://example.com/q> ?o } UNION { GRAPH ?g { ?s ?p ?o } } }",
+            "SELECT * WHERE { ?s <http://example.com/q> ?o . GRAPH <http://example.com/g> { ?o <http://example.com/r> ?v } }",
+            "SELECT * WHERE { { SELECT ?s WHERE { ?s <http://example.com/p> ?v FILTER(?v < 5) } } { SELECT ?o WHERE { GRAPH ?g { ?o <http://example.com/r> 3 } } } }",
+            "SELECT ?o (COUNT(*) AS ?c) WHERE { { ?s <http://example.com/q> ?o } UNION { ?o <http://example.com/q> ?s } OPTIONAL { ?s <http://example.com/p> ?v } } GROUP BY ?o",
+            "SELECT * WHERE { ?s <http://example.com/p> ?v MINUS { { ?s <http://example.com/q> <http://example.com/o1> } UNION { ?s <http://example.com/q> <http://example.com/o2> } } }",
+            "SELECT * WHERE { ?s <http://example.com/p> ?v FILTER EXISTS { { ?s ?p 1 } UNION { ?s ?p 2 } } }",
+        ] {
+            let mut expected = evaluate(&evaluator, dataset.clone(), query);
+            expected.sort_unstable_by_key(|solution| format!("{solution:?}"));
+            assert!(!expected.is_empty(), "{query}");
+            let parsed = spargebra::Query::parse(query, None).unwrap();
+            let QueryResults::Solutions(solutions) = evaluator
+                .execute_parallel(
+                    {
+                        let dataset = dataset.clone();
+                        let factory_calls = Arc::clone(&factory_calls);
+                        move || {
+                            factory_calls.fetch_add(1, atomic::Ordering::Relaxed);
+                            dataset.clone()
+                        }
+                    },
+                    &parsed,
+                )
+                .unwrap()
+            else {
+                unreachable!()
+            };
+            let mut solutions = solutions
+                .map(|s| s.unwrap().values().to_vec())
+                .collect::<Vec<_>>();
+            solutions.sort_unstable_by_key(|solution| format!("{solution:?}"));
+            assert_eq!(solutions, expected, "{query}");
+        }
+        // Some parts of the queries have been evaluated on other instances of the dataset
+        assert!(factory_calls.load(atomic::Ordering::Relaxed) > 6);
+    }
+
+    #[test]
+    fn parallel_execution_without_parallelism() {
+        let dataset = join_dataset();
+        let query = spargebra::Query::parse(
+            "SELECT * WHERE { { ?s <http://example.com/p> ?o } UNION { ?s <http://example.com/q> ?o } }",
+            None,
+        )
+        .unwrap();
+        let factory_calls = Arc::new(AtomicUsize::new(0));
+        let factory = {
+            let factory_calls = Arc::clone(&factory_calls);
+            move || {
+                factory_calls.fetch_add(1, atomic::Ordering::Relaxed);
+                dataset.clone()
+            }
+        };
+        // The parallelism is disabled when computing statistics
+        let (results, _) = QueryEvaluator::new()
+            .compute_statistics()
+            .explain_parallel_with_substituted_variables(factory, &query, []);
+        let QueryResults::Solutions(solutions) = results.unwrap() else {
+            unreachable!()
+        };
+        assert_eq!(solutions.count(), 100);
+        assert_eq!(factory_calls.load(atomic::Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn parallel_execution_cancellation() {
+        let dataset = join_dataset();
+        let cancellation_token = CancellationToken::new();
+        let evaluator = QueryEvaluator::new().with_cancellation_token(cancellation_token.clone());
+        let query = spargebra::Query::parse(
+            "SELECT * WHERE { { ?s ?p ?o } UNION { ?s2 ?p2 ?o2 . ?s3 ?p3 ?o3 } }",
+            None,
+        )
+        .unwrap();
+        let QueryResults::Solutions(mut solutions) = evaluator
+            .execute_parallel(move || dataset.clone(), &query)
+            .unwrap()
+        else {
+            unreachable!()
+        };
+        solutions.next().unwrap().unwrap();
+        cancellation_token.cancel();
+        // The other threads stop too and the iteration ends
+        assert!(matches!(
+            solutions.find_map(Result::err),
+            Some(QueryEvaluationError::Cancelled)
+        ));
+    }
+
+    /// Evaluates the service patterns against a dataset and records them
+    struct RecordingServiceHandler {
+        dataset: Dataset,
+        supports_bound_join: bool,
+        patterns: Arc<Mutex<Vec<spargebra::algebra::GraphPattern>>>,
+    }
+
+    impl RecordingServiceHandler {
+        fn new(dataset: Dataset, supports_bound_join: bool) -> Self {
+            Self {
+                dataset,
+                supports_bound_join,
+                patterns: Arc::default(),
+            }
+        }
+    }
+
+    impl ServiceHandler for RecordingServiceHandler {
+        type Error = QueryEvaluationError;
+
+        fn handle(
+            &self,
+            pattern: spargebra::algebra::GraphPattern,
+            base_iri: Option<String>,
+        ) -> Result<QuerySolutionIter, QueryEvaluationError> {
+            if let Ok(mut patterns) = self.patterns.lock() {
+                patterns.push(pattern.clone());
+            }
+            let query = spargebra::Query::Select {
+                dataset: None,
+                pattern,
+                base_iri: base_iri.map(|iri| Iri::parse(iri).unwrap()),
+            };
+            let QueryResults::Solutions(solutions) =
+                QueryEvaluator::new().execute(self.dataset.clone(), &query)?
+            else {
+                unreachable!()
+            };
+            Ok(solutions)
+        }
+
+        fn supports_bound_join(&self) -> bool {
+            self.supports_bound_join
+        }
+    }
+
+    struct FailingServiceHandler;
+
+    impl ServiceHandler for FailingServiceHandler {
+        type Error = QueryEvaluationError;
+
+        fn handle(
+            &self,
+            _: spargebra::algebra::GraphPattern,
+            _: Option<String>,
+        ) -> Result<QuerySolutionIter, QueryEvaluationError> {
+            Err(QueryEvaluationError::Service("service failure".into()))
+        }
+
+        fn supports_bound_join(&self) -> bool {
+            true
+        }
+    }
+
+    /// Evaluates the query with the service as a bound join and as a regular join and returns the sorted solutions
+    /// and the patterns sent to the service in the bound join case
+    fn evaluate_with_service(
+        local: &Dataset,
+        remote: &Dataset,
+        query: &str,
+    ) -> (
+        Vec<Vec<Option<Term>>>,
+        Vec<spargebra::algebra::GraphPattern>,
+    ) {
+        let service_name = NamedNode::new_unchecked("http://example.com/service");
+        let mut results = Vec::new();
+        let mut patterns = Vec::new();
+        for supports_bound_join in [true, false] {
+            let handler = RecordingServiceHandler::new(remote.clone(), supports_bound_join);
+            let recorded = Arc::clone(&handler.patterns);
+            let evaluator =
+                QueryEvaluator::new().with_service_handler(service_name.clone(), handler);
+            let mut solutions = evaluate(&evaluator, local.clone(), query);
+            solutions.sort_unstable_by_key(|solution| format!("{solution:?}"));
+            results.push(solutions);
+            if supports_bound_join {
+                patterns = recorded.lock().unwrap().clone();
+            } else {
+                // Without bound join, the service is called with the pattern of the query
+                assert!(recorded.lock().unwrap().iter().all(|pattern| !matches!(
+                    pattern,
+                    spargebra::algebra::GraphPattern::Join { left, .. }
+                        if matches!(left.as_ref(), spargebra::algebra::GraphPattern::Values { .. })
+                )));
+            }
+        }
+        let expected = results.pop().unwrap();
+        let solutions = results.pop().unwrap();
+        assert_eq!(solutions, expected, "{query}");
+        (solutions, patterns)
+    }
+
+    /// The VALUES bindings sent to the service
+    fn service_bindings(
+        patterns: &[spargebra::algebra::GraphPattern],
+    ) -> Vec<Vec<Option<GroundTerm>>> {
+        patterns
+            .iter()
+            .flat_map(|pattern| {
+                let spargebra::algebra::GraphPattern::Join { left, .. } = pattern else {
+                    unreachable!("Join expected, found {pattern}")
+                };
+                let spargebra::algebra::GraphPattern::Values { bindings, .. } = left.as_ref()
+                else {
+                    unreachable!("VALUES expected, found {left}")
+                };
+                assert!(bindings.len() <= SERVICE_BINDINGS_BATCH_SIZE);
+                bindings.clone()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bound_service_join() {
+        let dataset = join_dataset();
+        for query in [
+            // The local pattern on the left and on the right
+            "SELECT * WHERE { ?s <http://example.com/q> <http://example.com/o1> SERVICE <http://example.com/service> { ?s <http://example.com/p> ?v } }",
+            "SELECT * WHERE { SERVICE <http://example.com/service> { ?s <http://example.com/p> ?v } ?s <http://example.com/q> <http://example.com/o1> }",
+        ] {
+            let (solutions, patterns) = evaluate_with_service(&dataset, &dataset, query);
+            assert_eq!(solutions.len(), 10, "{query}");
+            let mut bindings = service_bindings(&patterns);
+            bindings.sort_unstable_by_key(|binding| format!("{binding:?}"));
+            assert_eq!(bindings.len(), 10, "{query}");
+            assert_eq!(
+                bindings[0],
+                [Some(GroundTerm::NamedNode(NamedNode::new_unchecked(
+                    "http://example.com/s1"
+                )))],
+                "{query}"
+            );
+        }
+    }
+
+    #[test]
+    fn bound_service_join_batches() {
+        let dataset = integers(250);
+        let (solutions, patterns) = evaluate_with_service(
+            &dataset,
+            &dataset,
+            "SELECT * WHERE { ?s ?p ?o SERVICE <http://example.com/service> { ?s ?p ?o } }",
+        );
+        assert_eq!(solutions.len(), 250);
+        assert_eq!(patterns.len(), 3);
+        assert_eq!(service_bindings(&patterns).len(), 250);
+    }
+
+    #[test]
+    fn bound_service_join_blank_node_keys() {
+        let ex = |name: &str| NamedNode::new_unchecked(format!("http://example.com/{name}"));
+        let local = [
+            Quad::new(
+                BlankNode::default(),
+                ex("q"),
+                ex("o"),
+                GraphName::DefaultGraph,
+            ),
+            Quad::new(ex("s"), ex("q"), ex("o"), GraphName::DefaultGraph),
+        ]
+        .into_iter()
+        .collect::<Dataset>();
+        let remote = [
+            Quad::new(
+                BlankNode::default(),
+                ex("p"),
+                ex("v1"),
+                GraphName::DefaultGraph,
+            ),
+            Quad::new(ex("s"), ex("p"), ex("v2"), GraphName::DefaultGraph),
+        ]
+        .into_iter()
+        .collect::<Dataset>();
+        let (solutions, patterns) = evaluate_with_service(
+            &local,
+            &remote,
+            "SELECT ?v WHERE { ?s <http://example.com/q> ?o SERVICE <http://example.com/service> { ?s <http://example.com/p> ?v } }",
+        );
+        // The blank nodes of the service are not the same as the local ones
+        assert_eq!(solutions, [vec![Some(ex("v2").into())]]);
+        // The blank node would be sent as UNDEF, so the service is called without VALUES
+        assert_eq!(patterns.len(), 1);
+        assert!(matches!(
+            patterns[0],
+            spargebra::algebra::GraphPattern::Bgp { .. }
+        ));
+    }
+
+    #[test]
+    fn bound_service_join_silent() {
+        let dataset = join_dataset();
+        let service_name = NamedNode::new_unchecked("http://example.com/service");
+        let query = "SELECT * WHERE { ?s <http://example.com/q> <http://example.com/o1> SERVICE SILENT <http://example.com/service> { ?s <http://example.com/p> ?v } }";
+
+        // Silent services are called with their pattern
+        let handler = RecordingServiceHandler::new(dataset.clone(), true);
+        let patterns = Arc::clone(&handler.patterns);
+        let evaluator = QueryEvaluator::new().with_service_handler(service_name.clone(), handler);
+        assert_eq!(evaluate(&evaluator, dataset.clone(), query).len(), 10);
+        assert!(patterns
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|pattern| matches!(pattern, spargebra::algebra::GraphPattern::Bgp { .. })));
+
+        // A failure leads to return the local solutions
+        let evaluator =
+            QueryEvaluator::new().with_service_handler(service_name.clone(), FailingServiceHandler);
+        let solutions = evaluate(&evaluator, dataset.clone(), query);
+        assert_eq!(solutions.len(), 10);
+        assert!(solutions.iter().all(|solution| solution[1].is_none()));
+
+        // Not if the service is not silent
+        let QueryResults::Solutions(mut solutions) = evaluator
+            .execute(
+                dataset,
+                &spargebra::Query::parse(&query.replace("SILENT ", ""), None).unwrap(),
+            )
+            .unwrap()
+        else {
+            unreachable!()
+        };
+        assert!(solutions.any(|solution| solution.is_err()));
+    }
+
+    /// A new empty directory for the spill files
+    fn spill_directory() -> PathBuf {
+        let path = std::env::temp_dir().join(format!("spareval-test-{:x}", random::<u128>()));
+        std::fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    fn spill_file_count(path: &Path) -> usize {
+        std::fs::read_dir(path).unwrap().count()
+    }
+
+    #[test]
+    fn order_by_with_spill() {
+        let query = "SELECT ?o WHERE { ?s ?p ?o } ORDER BY DESC(FLOOR(?o / 10)) ?o";
+        let dataset = integers(4000);
+        let expected = evaluate(&QueryEvaluator::new(), dataset.clone(), query);
+        assert_eq!(expected.len(), 4000);
+
+        let directory = spill_directory();
+        let evaluator = QueryEvaluator::new()
+            .with_memory_budget(1000)
+            .with_spill_directory(&directory);
+        let query = spargebra::Query::parse(query, None).unwrap();
+        let QueryResults::Solutions(mut solutions) = evaluator.execute(dataset, &query).unwrap()
+        else {
+            unreachable!()
+        };
+        let first = solutions.next().unwrap().unwrap().values().to_vec();
+        assert!(
+            spill_file_count(&directory) > 1,
+            "the sorted runs must be spilled"
+        );
+        let mut all = vec![first];
+        all.extend(solutions.map(|s| s.unwrap().values().to_vec()));
+        assert_eq!(all, expected);
+        assert_eq!(
+            spill_file_count(&directory),
+            0,
+            "the spill files must be removed"
+        );
+        std::fs::remove_dir(directory).unwrap();
+    }
+
+    #[test]
+    fn spill_files_removed_on_drop() {
+        let directory = spill_directory();
+        let evaluator = QueryEvaluator::new()
+            .with_memory_budget(1000)
+            .with_spill_directory(&directory);
+        let query = spargebra::Query::parse("SELECT ?o WHERE { ?s ?p ?o } ORDER BY DESC(?o)", None)
+            .unwrap();
+        let QueryResults::Solutions(mut solutions) =
+            evaluator.execute(integers(4000), &query).unwrap()
+        else {
+            unreachable!()
+        };
+        solutions.next().unwrap().unwrap();
+        assert!(spill_file_count(&directory) > 0, "the runs must be spilled");
+        drop(solutions);
+        assert_eq!(
+            spill_file_count(&directory),
+            0,
+            "the spill files must be removed"
+        );
+        std::fs::remove_dir(directory).unwrap();
+    }
+
+    #[test]
+    fn hash_join_memory_budget_exceeded() {
+        for query in [
+            // Hash join
+            "SELECT * WHERE { ?s ?p ?o { SELECT ?s (?o AS ?o2) WHERE { ?s ?p ?o } } }",
+            // Cartesian product
+            "SELECT * WHERE { ?s ?p ?o { SELECT (?o AS ?o2) WHERE { ?s ?p ?o } } }",
+            // Left join
+            "SELECT * WHERE { ?s ?p ?o OPTIONAL { SELECT ?s (?o AS ?o2) WHERE { ?s ?p ?o } } }",
+        ] {
+            let query = spargebra::Query::parse(query, None).unwrap();
+            let QueryResults::Solutions(mut solutions) = QueryEvaluator::new()
+                .with_memory_budget(1000)
+                .execute(integers(100), &query)
+                .unwrap()
+            else {
+                unreachable!()
+            };
+            assert!(
+                matches!(
+                    solutions.next(),
+                    Some(Err(QueryEvaluationError::MemoryBudgetExceeded(1000)))
+                ),
+                "the memory budget must be exceeded for {query}"
+            );
+            // Without budget the query works
+            let QueryResults::Solutions(solutions) = QueryEvaluator::new()
+                .execute(integers(100), &query)
+                .unwrap()
+            else {
+                unreachable!()
+            };
+            assert_eq!(solutions.count(), 10_000);
+        }
+    }
+
+    #[test]
+    fn exists_strategies() {
+        let ex = NamedNode::new_unchecked("http://example.com");
+        let dataset = Dataset::from_iter([Quad::new(
+            ex.clone(),
+            ex.clone(),
+            ex.clone(),
+            GraphName::DefaultGraph,
+        )]);
+        for (query, substitution, filter_scoped) in [
+            // ?o is only bound inside of the FILTER with substitution
+            (
+                "SELECT ?s WHERE { ?s ?p ?o FILTER EXISTS { ?s ?p ?o2 FILTER(?o = ?o2) } }",
+                1,
+                0,
+            ),
+            (
+                "SELECT ?s WHERE { ?s ?p ?o FILTER NOT EXISTS { ?s ?p ?o2 FILTER(?o = ?o2) } }",
+                0,
+                1,
+            ),
+            // The strategies are equivalent if the outer variables are only used in triple patterns
+            (
+                "SELECT ?s WHERE { ?s ?p ?o FILTER EXISTS { ?o ?p ?s } }",
+                1,
+                1,
+            ),
+            (
+                "SELECT ?s WHERE { ?s ?p ?o FILTER EXISTS { ?o ?p <http://example.com/o> } }",
+                0,
+                0,
+            ),
+        ] {
+            for (strategy, count) in [
+                (ExistsStrategy::Substitution, substitution),
+                (ExistsStrategy::FilterScoped, filter_scoped),
+            ] {
+                let evaluator = QueryEvaluator::new().with_exists_strategy(strategy);
+                assert_eq!(
+                    evaluate(&evaluator, dataset.clone(), query).len(),
+                    count,
+                    "{query} with {strategy:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn filter_scoped_exists_memory_budget_exceeded() {
+        for query in [
+            "SELECT * WHERE { ?s ?p ?o FILTER EXISTS { ?s ?p ?o2 } }",
+            "SELECT * WHERE { ?s ?p ?o BIND(EXISTS { ?s ?p ?o2 } AS ?e) }",
+        ] {
+            let query = spargebra::Query::parse(query, None).unwrap();
+            let QueryResults::Solutions(mut solutions) = QueryEvaluator::new()
+                .with_exists_strategy(ExistsStrategy::FilterScoped)
+                .with_memory_budget(1000)
+                .execute(integers(100), &query)
+                .unwrap()
+            else {
+                unreachable!()
+            };
+            assert!(
+                matches!(
+                    solutions.next(),
+                    Some(Err(QueryEvaluationError::MemoryBudgetExceeded(1000)))
+                ),
+                "the memory budget must be exceeded for {query}"
+            );
+            // Without budget the query works
+            let QueryResults::Solutions(solutions) = QueryEvaluator::new()
+                .with_exists_strategy(ExistsStrategy::FilterScoped)
+                .execute(integers(100), &query)
+                .unwrap()
+            else {
+                unreachable!()
+            };
+            assert_eq!(solutions.count(), 100);
+        }
+    }
 }
@@ -4,29 +4,47 @@
 #![doc(html_favicon_url = "https://raw.githubusercontent.com/oxigraph/oxigraph/main/logo.svg")]
 #![doc(html_logo_url = "https://raw.githubusercontent.com/oxigraph/oxigraph/main/logo.svg")]
 
+#[cfg(feature = "async-tokio")]
+mod async_tokio;
 mod dataset;
 mod error;
 mod eval;
+mod lru;
+mod memory;
 mod model;
 mod service;
+mod update;
+#[cfg(feature = "async-tokio")]
+use crate::async_tokio::BlockingServiceHandler;
+#[cfg(feature = "async-tokio")]
+pub use crate::async_tokio::{
+    TokioAsyncDefaultServiceHandler, TokioAsyncQueryResults, TokioAsyncQuerySolutionIter,
+    TokioAsyncQueryTripleIter, TokioAsyncServiceHandler,
+};
 #[cfg(feature = "rdf-star")]
 pub use crate::dataset::ExpressionTriple;
-pub use crate::dataset::{ExpressionTerm, InternalQuad, QueryableDataset};
+pub use crate::dataset::{ExpressionTerm, InternalQuad, MutableQueryableDataset, QueryableDataset};
 pub use crate::error::QueryEvaluationError;
-use crate::eval::{EvalNodeWithStats, SimpleEvaluator, Timer};
+use crate::eval::{DatasetFactory, EvalNodeWithStats, SimpleEvaluator, Timer};
+use crate::lru::LruCache;
+use crate::memory::MemoryBudget;
 pub use crate::model::{QueryResults, QuerySolution, QuerySolutionIter, QueryTripleIter};
 use crate::service::ServiceHandlerRegistry;
 pub use crate::service::{DefaultServiceHandler, ServiceHandler};
+pub use crate::update::{LoadHandler, UpdateEvaluator};
 use json_event_parser::{JsonEvent, ToWriteJsonWriter};
 use oxrdf::{NamedNode, Term, Variable};
 use oxsdatatypes::{DayTimeDuration, Float};
-use spargebra::Query;
+use spargebra::{FunctionDefinition, Query, SparqlSyntaxError};
 use sparopt::algebra::GraphPattern;
-use sparopt::Optimizer;
+pub use sparopt::{DatasetStatistics, PredicateStatistics, StatisticsProvider};
+use sparopt::{Optimizer, OptimizerFingerprint};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::rc::Rc;
-use std::sync::Arc;
-use std::{fmt, io};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::{fmt, io, mem};
 
 /// Evaluates a query against a given [RDF dataset](https://www.w3.org/TR/rdf11-concepts/#dfn-rdf-dataset)
 ///
@@ -60,8 +78,19 @@ use std::{fmt, io};
 pub struct QueryEvaluator {
     service_handler: ServiceHandlerRegistry,
     custom_functions: CustomFunctionRegistry,
+    custom_aggregate_functions: CustomAggregateFunctionRegistry,
+    custom_functions_metadata: HashMap<NamedNode, CustomFunctionMetadata>,
+    function_definitions: Vec<FunctionDefinition>,
+    graph_pattern_rewriters: GraphPatternRewriterRegistry,
+    cancellation_token: Option<CancellationToken>,
+    exists_strategy: ExistsStrategy,
+    plan_cache: Option<QueryPlanCache>,
+    optimizer: Optimizer,
+    statistics: Option<Arc<dyn StatisticsProvider>>,
     without_optimizations: bool,
     run_stats: bool,
+    memory_budget: Option<usize>,
+    spill_directory: Option<PathBuf>,
 }
 
 impl QueryEvaluator {
@@ -79,6 +108,52 @@ impl QueryEvaluator {
         self.explain(dataset, query).0
     }
 
+    /// Executes a SPARQL query from an async context using [Tokio](https://tokio.rs/).
+    ///
+    /// The evaluation itself is run on Tokio blocking thread pool (using [`spawn_blocking`](tokio::task::spawn_blocking)) and the solutions
+    /// are sent back by batches, so the async worker threads are never blocked by the evaluation.
+    /// If the consumer is slower than the evaluation, the evaluation is paused until the buffered batches are read.
+    /// Dropping the returned stream stops the evaluation.
+    ///
+    /// ```
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use futures_util::StreamExt;
+    /// use oxrdf::{Dataset, GraphName, NamedNode, Quad};
+    /// use spareval::{QueryEvaluator, TokioAsyncQueryResults};
+    /// use spargebra::Query;
+    ///
+    /// let ex = NamedNode::new("http://example.com")?;
+    /// let dataset = Dataset::from_iter([Quad::new(
+    ///     ex.clone(),
+    ///     ex.clone(),
+    ///     ex.clone(),
+    ///     GraphName::DefaultGraph,
+    /// )]);
+    /// let query = Query::parse("SELECT * WHERE { ?s ?p ?o }", None)?;
+    /// if let TokioAsyncQueryResults::Solutions(mut solutions) = QueryEvaluator::new()
+    ///     .execute_tokio_async(dataset, &query)
+    ///     .await?
+    /// {
+    ///     let mut count = 0;
+    ///     while let Some(solution) = solutions.next().await {
+    ///         assert_eq!(solution?["s"], ex.clone().into());
+    ///         count += 1;
+    ///     }
+    ///     assert_eq!(count, 1);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "async-tokio")]
+    pub async fn execute_tokio_async(
+        &self,
+        dataset: impl QueryableDataset + Send + 'static,
+        query: &Query,
+    ) -> Result<TokioAsyncQueryResults, QueryEvaluationError> {
+        async_tokio::execute(self.clone(), dataset, query.clone()).await
+    }
+
     /// Executes a SPARQL query while substituting some variables with the given values.
     ///
     /// Substitution follows [RDF-dev SEP-0007](https://github.com/w3c/sparql-dev/blob/main/SEP/SEP-0007/sep-0007.md).
@@ -131,23 +206,112 @@ impl QueryEvaluator {
         dataset: impl QueryableDataset,
         query: &Query,
         substitutions: impl IntoIterator<Item = (Variable, Term)>,
+    ) -> (Result<QueryResults, QueryEvaluationError>, QueryExplanation) {
+        self.explain_with_dataset_factory(dataset, None, query, substitutions)
+    }
+
+    /// Executes a SPARQL query using several threads.
+    ///
+    /// The branches of the `UNION`s and the two sides of the hash joins are evaluated in parallel
+    /// on the [rayon](https://docs.rs/rayon) global thread pool.
+    /// Each thread uses its own instance of the dataset built with `dataset_factory`.
+    /// All these instances must contain the same data to get correct results.
+    ///
+    /// The parts of the query containing a `SERVICE` call are evaluated on the current thread.
+    /// The parallelism is disabled if [statistics are computed](Self::compute_statistics).
+    /// This method should not be called from a thread of the rayon global thread pool
+    /// because the current thread is blocked while waiting for the results of the other threads.
+    ///
+    /// ```
+    /// use oxrdf::{Dataset, GraphName, NamedNode, Quad};
+    /// use spareval::{QueryEvaluator, QueryResults};
+    /// use spargebra::Query;
+    ///
+    /// let ex = NamedNode::new("http://example.com")?;
+    /// let dataset = Dataset::from_iter([Quad::new(
+    ///     ex.clone(),
+    ///     ex.clone(),
+    ///     ex.clone(),
+    ///     GraphName::DefaultGraph,
+    /// )]);
+    /// let query = Query::parse("SELECT * WHERE { { ?s ?p ?o } UNION { ?o ?p ?s } }", None)?;
+    /// let results = QueryEvaluator::new().execute_parallel(move || dataset.clone(), &query);
+    /// if let QueryResults::Solutions(solutions) = results? {
+    ///     assert_eq!(solutions.collect::<Result<Vec<_>, _>>()?.len(), 2);
+    /// }
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn execute_parallel<D: QueryableDataset>(
+        &self,
+        dataset_factory: impl Fn() -> D + Send + Sync + 'static,
+        query: &Query,
+    ) -> Result<QueryResults, QueryEvaluationError> {
+        self.explain_parallel_with_substituted_variables(dataset_factory, query, [])
+            .0
+    }
+
+    /// Executes a SPARQL query using several threads while substituting some variables with the given values.
+    ///
+    /// See [`execute_parallel`](Self::execute_parallel) for the parallel evaluation
+    /// and [`execute_with_substituted_variables`](Self::execute_with_substituted_variables) for the substitution.
+    pub fn explain_parallel_with_substituted_variables<D: QueryableDataset>(
+        &self,
+        dataset_factory: impl Fn() -> D + Send + Sync + 'static,
+        query: &Query,
+        substitutions: impl IntoIterator<Item = (Variable, Term)>,
+    ) -> (Result<QueryResults, QueryEvaluationError>, QueryExplanation) {
+        let dataset_factory: DatasetFactory<D> = Arc::new(dataset_factory);
+        self.explain_with_dataset_factory(
+            dataset_factory(),
+            (!self.run_stats).then_some(dataset_factory),
+            query,
+            substitutions,
+        )
+    }
+
+    fn explain_with_dataset_factory<D: QueryableDataset>(
+        &self,
+        dataset: D,
+        dataset_factory: Option<DatasetFactory<D>>,
+        query: &Query,
+        substitutions: impl IntoIterator<Item = (Variable, Term)>,
     ) -> (Result<QueryResults, QueryEvaluationError>, QueryExplanation) {
         let start_planning = Timer::now();
+        let substitutions = substitutions.into_iter().collect::<Vec<_>>();
+        let expanded_query;
+        let query =
+            if self.function_definitions.is_empty() && self.graph_pattern_rewriters.is_empty() {
+                query
+            } else {
+                let mut query = query.clone();
+                query.expand_functions(&self.function_definitions);
+                let (Query::Select { pattern, .. }
+                | Query::Construct { pattern, .. }
+                | Query::Describe { pattern, .. }
+                | Query::Ask { pattern, .. }) = &mut query;
+                for rewriter in &self.graph_pattern_rewriters {
+                    *pattern = rewriter(mem::take(pattern));
+                }
+                expanded_query = query;
+                &expanded_query
+            };
         let (results, plan_node_with_stats, planning_duration) = match query {
             Query::Select {
                 pattern, base_iri, ..
             } => {
-                let mut pattern = GraphPattern::from(pattern);
-                if !self.without_optimizations {
-                    pattern = Optimizer::optimize_graph_pattern(pattern);
-                }
+                let pattern = self.plan(query, pattern, &substitutions);
                 let planning_duration = start_planning.elapsed();
                 let (results, explanation) = SimpleEvaluator::new(
                     dataset,
                     base_iri.clone().map(Rc::new),
                     Rc::new(self.service_handler.clone()),
                     Rc::new(self.custom_functions.clone()),
+                    Rc::new(self.custom_aggregate_functions.clone()),
+                    self.cancellation_token.clone(),
+                    self.exists_strategy,
+                    dataset_factory,
                     self.run_stats,
+                    self.memory_budget(),
                 )
                 .evaluate_select(&pattern, substitutions);
                 (
@@ -159,17 +323,19 @@ impl QueryEvaluator {
             Query::Ask {
                 pattern, base_iri, ..
             } => {
-                let mut pattern = GraphPattern::from(pattern);
-                if !self.without_optimizations {
-                    pattern = Optimizer::optimize_graph_pattern(pattern);
-                }
+                let pattern = self.plan(query, pattern, &substitutions);
                 let planning_duration = start_planning.elapsed();
                 let (results, explanation) = SimpleEvaluator::new(
                     dataset,
                     base_iri.clone().map(Rc::new),
                     Rc::new(self.service_handler.clone()),
                     Rc::new(self.custom_functions.clone()),
+                    Rc::new(self.custom_aggregate_functions.clone()),
+                    self.cancellation_token.clone(),
+                    self.exists_strategy,
+                    dataset_factory,
                     self.run_stats,
+                    self.memory_budget(),
                 )
                 .evaluate_ask(&pattern, substitutions);
                 (
@@ -184,17 +350,19 @@ impl QueryEvaluator {
                 base_iri,
                 ..
             } => {
-                let mut pattern = GraphPattern::from(pattern);
-                if !self.without_optimizations {
-                    pattern = Optimizer::optimize_graph_pattern(pattern);
-                }
+                let pattern = self.plan(query, pattern, &substitutions);
                 let planning_duration = start_planning.elapsed();
                 let (results, explanation) = SimpleEvaluator::new(
                     dataset,
                     base_iri.clone().map(Rc::new),
                     Rc::new(self.service_handler.clone()),
                     Rc::new(self.custom_functions.clone()),
+                    Rc::new(self.custom_aggregate_functions.clone()),
+                    self.cancellation_token.clone(),
+                    self.exists_strategy,
+                    dataset_factory,
                     self.run_stats,
+                    self.memory_budget(),
                 )
                 .evaluate_construct(&pattern, template, substitutions);
                 (
@@ -206,17 +374,19 @@ impl QueryEvaluator {
             Query::Describe {
                 pattern, base_iri, ..
             } => {
-                let mut pattern = GraphPattern::from(pattern);
-                if !self.without_optimizations {
-                    pattern = Optimizer::optimize_graph_pattern(pattern);
-                }
+                let pattern = self.plan(query, pattern, &substitutions);
                 let planning_duration = start_planning.elapsed();
                 let (results, explanation) = SimpleEvaluator::new(
                     dataset,
                     base_iri.clone().map(Rc::new),
                     Rc::new(self.service_handler.clone()),
                     Rc::new(self.custom_functions.clone()),
+                    Rc::new(self.custom_aggregate_functions.clone()),
+                    self.cancellation_token.clone(),
+                    self.exists_strategy,
+                    dataset_factory,
                     self.run_stats,
+                    self.memory_budget(),
                 )
                 .evaluate_describe(&pattern, substitutions);
                 (
@@ -263,6 +433,37 @@ impl QueryEvaluator {
         self
     }
 
+    /// Use a given [`TokioAsyncServiceHandler`] to execute [SPARQL 1.1 Federated Query](https://www.w3.org/TR/sparql11-federated-query/) SERVICE calls.
+    ///
+    /// The handler futures are run using the current [Tokio](https://tokio.rs/) runtime.
+    /// Hence, the evaluation must be done using [`execute_tokio_async`](Self::execute_tokio_async).
+    /// With the other execution methods, the SERVICE calls to this handler fail with an error.
+    ///
+    /// See [`TokioAsyncServiceHandler`] for an example.
+    #[cfg(feature = "async-tokio")]
+    #[inline]
+    #[must_use]
+    pub fn with_tokio_async_service_handler(
+        self,
+        service_name: impl Into<NamedNode>,
+        handler: impl TokioAsyncServiceHandler + 'static,
+    ) -> Self {
+        self.with_service_handler(service_name, BlockingServiceHandler(handler))
+    }
+
+    /// Use a given [`TokioAsyncDefaultServiceHandler`] to execute [SPARQL 1.1 Federated Query](https://www.w3.org/TR/sparql11-federated-query/) SERVICE calls if no explicit service handler is defined for the service.
+    ///
+    /// The same restrictions as [`with_tokio_async_service_handler`](Self::with_tokio_async_service_handler) apply.
+    #[cfg(feature = "async-tokio")]
+    #[inline]
+    #[must_use]
+    pub fn with_default_tokio_async_service_handler(
+        self,
+        handler: impl TokioAsyncDefaultServiceHandler + 'static,
+    ) -> Self {
+        self.with_default_service_handler(BlockingServiceHandler(handler))
+    }
+
     #[inline]
     #[must_use]
     pub fn has_default_service_handler(&self) -> bool {
@@ -304,6 +505,411 @@ impl QueryEvaluator {
         self
     }
 
+    /// Adds a custom SPARQL evaluation aggregate function.
+    ///
+    /// The `evaluator` is called to build a new [`AggregateFunctionAccumulator`] for each group.
+    ///
+    /// The parser reads a call `<name>(?v)` as an aggregate if the query is grouped and `?v` is not a group key.
+    /// `<name>(DISTINCT ?v)` is always an aggregate.
+    ///
+    /// Example with a function concatenating the string values:
+    /// ```
+    /// use oxrdf::{Dataset, Literal, NamedNode, Term};
+    /// use spareval::{AggregateFunctionAccumulator, QueryEvaluator, QueryResults};
+    /// use spargebra::Query;
+    ///
+    /// #[derive(Default)]
+    /// struct ConcatAccumulator(String);
+    ///
+    /// impl AggregateFunctionAccumulator for ConcatAccumulator {
+    ///     fn accumulate(&mut self, element: Term) {
+    ///         if let Term::Literal(literal) = element {
+    ///             self.0.push_str(literal.value());
+    ///         }
+    ///     }
+    ///
+    ///     fn finish(&mut self) -> Option<Term> {
+    ///         Some(Literal::from(self.0.as_str()).into())
+    ///     }
+    /// }
+    ///
+    /// let evaluator = QueryEvaluator::new().with_custom_aggregate_function(
+    ///     NamedNode::new("http://example.com/concat")?,
+    ///     || Box::<ConcatAccumulator>::default(),
+    /// );
+    /// let query = Query::parse(
+    ///     "SELECT ?k (<http://example.com/concat>(?v) AS ?c) WHERE { VALUES (?k ?v) { (1 'a') (1 'b') } } GROUP BY ?k",
+    ///     None,
+    /// )?;
+    /// if let QueryResults::Solutions(mut solutions) = evaluator.execute(Dataset::new(), &query)? {
+    ///     assert_eq!(
+    ///         solutions.next().unwrap()?.get("c"),
+    ///         Some(&Literal::from("ab").into())
+    ///     );
+    /// }
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_custom_aggregate_function(
+        mut self,
+        name: NamedNode,
+        evaluator: impl Fn() -> Box<dyn AggregateFunctionAccumulator + Send + Sync>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.custom_aggregate_functions
+            .insert(name, Arc::new(evaluator));
+        self
+    }
+
+    /// Adds a function defined by a SPARQL expression.
+    ///
+    /// Its calls are replaced by its body before evaluation.
+    /// The calls to the previously added definitions in its body are expanded.
+    /// If a function with the same name is already defined, it is replaced.
+    ///
+    /// ```
+    /// use oxrdf::{Dataset, Literal};
+    /// use spareval::{QueryEvaluator, QueryResults};
+    /// use spargebra::{FunctionDefinition, Query};
+    ///
+    /// let mut evaluator = QueryEvaluator::new();
+    /// for definition in FunctionDefinition::parse_list(
+    ///     "DEFINE FUNCTION <http://example.com/grade>(?score) { IF(?score >= 10, 'pass', 'fail') }",
+    ///     None,
+    /// )? {
+    ///     evaluator = evaluator.with_function_definition(definition);
+    /// }
+    /// let query = Query::parse(
+    ///     "SELECT (<http://example.com/grade>(12) AS ?g) WHERE {}",
+    ///     None,
+    /// )?;
+    /// if let QueryResults::Solutions(mut solutions) = evaluator.execute(Dataset::new(), &query)? {
+    ///     assert_eq!(
+    ///         solutions.next().unwrap()?.get("g"),
+    ///         Some(&Literal::from("pass").into())
+    ///     );
+    /// }
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_function_definition(mut self, mut definition: FunctionDefinition) -> Self {
+        definition.expand_functions(&self.function_definitions);
+        self.function_definitions
+            .retain(|d| d.name() != definition.name());
+        self.function_definitions.push(definition);
+        self
+    }
+
+    /// Returns the functions added with [`with_function_definition`](Self::with_function_definition).
+    #[inline]
+    pub fn function_definitions(&self) -> &[FunctionDefinition] {
+        &self.function_definitions
+    }
+
+    /// Adds a function rewriting the graph pattern of the queries before their evaluation.
+    ///
+    /// It allows to plug query optimizations based on external indexes.
+    /// The rewriters are applied in the order they have been added, after the expansion of the [function definitions](Self::with_function_definition).
+    /// They must not change the query results.
+    ///
+    /// ```
+    /// use oxrdf::{Dataset, GraphName, NamedNode, Quad};
+    /// use spareval::{QueryEvaluator, QueryResults};
+    /// use spargebra::algebra::GraphPattern;
+    /// use spargebra::Query;
+    ///
+    /// let ex = NamedNode::new("http://example.com")?;
+    /// let dataset = Dataset::from_iter([Quad::new(
+    ///     ex.clone(),
+    ///     ex.clone(),
+    ///     ex.clone(),
+    ///     GraphName::DefaultGraph,
+    /// )]);
+    /// // A rewriter making all queries return no result
+    /// let evaluator = QueryEvaluator::new().with_graph_pattern_rewriter(|_| GraphPattern::Values {
+    ///     variables: Vec::new(),
+    ///     bindings: Vec::new(),
+    /// });
+    /// let query = Query::parse("SELECT * WHERE { ?s ?p ?o }", None)?;
+    /// if let QueryResults::Solutions(solutions) = evaluator.execute(dataset, &query)? {
+    ///     assert_eq!(solutions.count(), 0);
+    /// }
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_graph_pattern_rewriter(
+        mut self,
+        rewriter: impl Fn(spargebra::algebra::GraphPattern) -> spargebra::algebra::GraphPattern
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.graph_pattern_rewriters.push(Arc::new(rewriter));
+        self
+    }
+
+    /// Sets the metadata of a custom function or custom aggregate function.
+    ///
+    /// The metadata is only informative and returned by [`custom_functions`](Self::custom_functions) and [`custom_aggregate_functions`](Self::custom_aggregate_functions).
+    #[inline]
+    #[must_use]
+    pub fn with_custom_function_metadata(
+        mut self,
+        name: NamedNode,
+        metadata: CustomFunctionMetadata,
+    ) -> Self {
+        self.custom_functions_metadata.insert(name, metadata);
+        self
+    }
+
+    /// Returns the custom functions registered with [`with_custom_function`](Self::with_custom_function) and their metadata.
+    ///
+    /// ```
+    /// use oxrdf::NamedNode;
+    /// use spareval::{CustomFunctionMetadata, QueryEvaluator};
+    ///
+    /// let name = NamedNode::new("http://example.com/identity")?;
+    /// let evaluator = QueryEvaluator::new()
+    ///     .with_custom_function(name.clone(), |args| args.first().cloned())
+    ///     .with_custom_function_metadata(
+    ///         name.clone(),
+    ///         CustomFunctionMetadata::new()
+    ///             .with_arity(1)
+    ///             .with_description("Returns its argument"),
+    ///     );
+    /// let functions = evaluator.custom_functions().collect::<Vec<_>>();
+    /// assert_eq!(functions.len(), 1);
+    /// assert_eq!(functions[0].0, &name);
+    /// assert_eq!(functions[0].1.arity(), Some(1));
+    /// assert_eq!(
+    ///     functions[0].1.description(),
+    ///     Some("Returns its argument")
+    /// );
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn custom_functions(
+        &self,
+    ) -> impl Iterator<Item = (&NamedNode, &CustomFunctionMetadata)> + '_ {
+        self.custom_functions
+            .keys()
+            .map(|name| (name, self.custom_function_metadata(name)))
+    }
+
+    /// Returns the custom aggregate functions registered with [`with_custom_aggregate_function`](Self::with_custom_aggregate_function) and their metadata.
+    pub fn custom_aggregate_functions(
+        &self,
+    ) -> impl Iterator<Item = (&NamedNode, &CustomFunctionMetadata)> + '_ {
+        self.custom_aggregate_functions
+            .keys()
+            .map(|name| (name, self.custom_function_metadata(name)))
+    }
+
+    fn custom_function_metadata(&self, name: &NamedNode) -> &CustomFunctionMetadata {
+        static NO_METADATA: CustomFunctionMetadata = CustomFunctionMetadata {
+            arity: None,
+            description: None,
+        };
+        self.custom_functions_metadata
+            .get(name)
+            .unwrap_or(&NO_METADATA)
+    }
+
+    /// Allows to cancel the query evaluation using the given [`CancellationToken`].
+    ///
+    /// Once [`CancellationToken::cancel`] is called, the evaluation fails with a [`QueryEvaluationError::Cancelled`] error.
+    ///
+    /// ```
+    /// use oxrdf::{Dataset, GraphName, NamedNode, Quad};
+    /// use spareval::{CancellationToken, QueryEvaluationError, QueryEvaluator, QueryResults};
+    /// use spargebra::Query;
+    ///
+    /// let ex = NamedNode::new("http://example.com")?;
+    /// let dataset = Dataset::from_iter([Quad::new(
+    ///     ex.clone(),
+    ///     ex.clone(),
+    ///     ex.clone(),
+    ///     GraphName::DefaultGraph,
+    /// )]);
+    /// let cancellation_token = CancellationToken::new();
+    /// let evaluator = QueryEvaluator::new().with_cancellation_token(cancellation_token.clone());
+    /// let query = Query::parse("SELECT * WHERE { ?s ?p ?o }", None)?;
+    /// if let QueryResults::Solutions(mut solutions) = evaluator.execute(dataset, &query)? {
+    ///     cancellation_token.cancel();
+    ///     assert!(matches!(
+    ///         solutions.next(),
+    ///         Some(Err(QueryEvaluationError::Cancelled))
+    ///     ));
+    /// }
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_cancellation_token(mut self, cancellation_token: CancellationToken) -> Self {
+        self.cancellation_token = Some(cancellation_token);
+        self
+    }
+
+    /// Sets how the `EXISTS` patterns get the values of the variables bound outside of them.
+    ///
+    /// The default is [`ExistsStrategy::Substitution`].
+    /// [`Query::warnings`] returns a [`QueryWarning::ExistsStrategyDependent`](spargebra::QueryWarning::ExistsStrategyDependent) warning
+    /// if the results of a query might depend on this choice.
+    ///
+    /// ```
+    /// use oxrdf::{Dataset, GraphName, NamedNode, Quad};
+    /// use spareval::{ExistsStrategy, QueryEvaluator, QueryResults};
+    /// use spargebra::{Query, QueryWarning};
+    ///
+    /// let ex = NamedNode::new("http://example.com")?;
+    /// let dataset = Dataset::from_iter([Quad::new(
+    ///     ex.clone(),
+    ///     ex.clone(),
+    ///     ex.clone(),
+    ///     GraphName::DefaultGraph,
+    /// )]);
+    /// // ?o is not bound inside of the EXISTS FILTER when the EXISTS pattern is evaluated on its own
+    /// let query = Query::parse(
+    ///     "ASK { ?s ?p ?o FILTER EXISTS { ?s ?p ?o2 FILTER(?o = ?o2) } }",
+    ///     None,
+    /// )?;
+    /// assert!(matches!(
+    ///     query.warnings().as_slice(),
+    ///     [QueryWarning::ExistsStrategyDependent { variable }] if variable.as_str() == "o"
+    /// ));
+    /// let results = QueryEvaluator::new().execute(dataset.clone(), &query)?;
+    /// assert!(matches!(results, QueryResults::Boolean(true)));
+    /// let results = QueryEvaluator::new()
+    ///     .with_exists_strategy(ExistsStrategy::FilterScoped)
+    ///     .execute(dataset, &query)?;
+    /// assert!(matches!(results, QueryResults::Boolean(false)));
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_exists_strategy(mut self, exists_strategy: ExistsStrategy) -> Self {
+        self.exists_strategy = exists_strategy;
+        self
+    }
+
+    /// Caches the optimized query plans in the given [`QueryPlanCache`].
+    ///
+    /// The queries evaluated again with the same cache and the same [optimizer](Self::with_optimizer) are not optimized again.
+    /// Variable substitutions are applied during evaluation, hence the same plan is reused whatever the substituted values are.
+    ///
+    /// ```
+    /// use oxrdf::{Dataset, GraphName, NamedNode, Quad};
+    /// use spareval::{QueryEvaluator, QueryPlanCache, QueryResults};
+    /// use spargebra::Query;
+    ///
+    /// let ex = NamedNode::new("http://example.com")?;
+    /// let dataset = Dataset::from_iter([Quad::new(
+    ///     ex.clone(),
+    ///     ex.clone(),
+    ///     ex.clone(),
+    ///     GraphName::DefaultGraph,
+    /// )]);
+    /// let cache = QueryPlanCache::new(100);
+    /// let evaluator = QueryEvaluator::new().with_plan_cache(cache.clone());
+    /// let query = Query::parse("ASK { ?s ?p ?o }", None)?;
+    /// for _ in 0..2 {
+    ///     let results = evaluator.execute(dataset.clone(), &query)?;
+    ///     assert!(matches!(results, QueryResults::Boolean(true)));
+    /// }
+    /// assert_eq!(cache.len(), 1);
+    /// cache.clear();
+    /// assert!(cache.is_empty());
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_plan_cache(mut self, plan_cache: QueryPlanCache) -> Self {
+        self.plan_cache = Some(plan_cache);
+        self
+    }
+
+    /// Sets the [`Optimizer`] used to optimize the queries before their evaluation.
+    ///
+    /// It allows to disable some of the optimization passes or to add custom ones.
+    /// The [statistics](Self::with_statistics) given to the evaluator are applied to it.
+    ///
+    /// ```
+    /// use oxrdf::{Dataset, GraphName, NamedNode, Quad};
+    /// use spareval::{QueryEvaluator, QueryResults};
+    /// use spargebra::Query;
+    /// use sparopt::Optimizer;
+    ///
+    /// let ex = NamedNode::new("http://example.com")?;
+    /// let dataset = Dataset::from_iter([Quad::new(
+    ///     ex.clone(),
+    ///     ex.clone(),
+    ///     ex.clone(),
+    ///     GraphName::DefaultGraph,
+    /// )]);
+    /// let evaluator = QueryEvaluator::new()
+    ///     .with_optimizer(Optimizer::default_passes().without_pass("join-reordering"));
+    /// let query = Query::parse("ASK { ?s ?p ?o . ?o ?p ?s }", None)?;
+    /// let results = evaluator.execute(dataset, &query)?;
+    /// assert!(matches!(results, QueryResults::Boolean(true)));
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_optimizer(mut self, optimizer: Optimizer) -> Self {
+        self.optimizer = match &self.statistics {
+            Some(statistics) => optimizer.with_statistics(Arc::clone(statistics)),
+            None => optimizer,
+        };
+        self
+    }
+
+    /// Makes the optimizer use the given dataset statistics to order the joins and pick the join algorithms.
+    ///
+    /// They are kept if the [optimizer](Self::with_optimizer) is changed afterward.
+    /// The statistics are not updated by the evaluator, they should be computed again after large changes to the dataset.
+    ///
+    /// ```
+    /// use oxrdf::{Dataset, GraphName, NamedNode, Quad};
+    /// use spareval::{DatasetStatistics, PredicateStatistics, QueryEvaluator, QueryResults};
+    /// use spargebra::Query;
+    /// use std::sync::Arc;
+    ///
+    /// let ex = NamedNode::new("http://example.com")?;
+    /// let dataset = Dataset::from_iter([Quad::new(
+    ///     ex.clone(),
+    ///     ex.clone(),
+    ///     ex.clone(),
+    ///     GraphName::DefaultGraph,
+    /// )]);
+    /// let statistics = DatasetStatistics::from_iter([(
+    ///     ex.clone(),
+    ///     PredicateStatistics {
+    ///         count: 1,
+    ///         distinct_subjects: 1,
+    ///         distinct_objects: 1,
+    ///     },
+    /// )]);
+    /// let evaluator = QueryEvaluator::new().with_statistics(Arc::new(statistics));
+    /// let query = Query::parse(
+    ///     "ASK { ?s <http://example.com> ?o . ?o <http://example.com> ?s }",
+    ///     None,
+    /// )?;
+    /// let results = evaluator.execute(dataset, &query)?;
+    /// assert!(matches!(results, QueryResults::Boolean(true)));
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_statistics(mut self, statistics: Arc<dyn StatisticsProvider>) -> Self {
+        self.optimizer = self.optimizer.with_statistics(Arc::clone(&statistics));
+        self.statistics = Some(statistics);
+        self
+    }
+
     /// Disables query optimizations and runs the query as it is.
     #[inline]
     #[must_use]
@@ -319,11 +925,339 @@ impl QueryEvaluator {
         self.run_stats = true;
         self
     }
+
+    /// Limits the memory used by the operators keeping solutions in memory to roughly `max_bytes` per query.
+    ///
+    /// When the budget is exceeded, `ORDER BY` switches to an external sort and `GROUP BY` and `DISTINCT` write the groups or solutions not fitting in memory to temporary files,
+    /// so they still return all the results.
+    /// `DISTINCT` on top of `ORDER BY` keeps all the solutions in memory to not break their order.
+    /// Hash joins fail with a [`QueryEvaluationError::MemoryBudgetExceeded`] error.
+    ///
+    /// The memory usage is estimated from the number of buffered solutions, the data owned by the terms themselves is not counted.
+    ///
+    /// ```
+    /// use oxrdf::{Dataset, GraphName, Literal, NamedNode, Quad};
+    /// use spareval::{QueryEvaluator, QueryResults};
+    /// use spargebra::Query;
+    ///
+    /// let ex = NamedNode::new("http://example.com")?;
+    /// let dataset = (0..2000)
+    ///     .map(|i| {
+    ///         Quad::new(
+    ///             NamedNode::new_unchecked(format!("http://example.com/{i}")),
+    ///             ex.clone(),
+    ///             Literal::from(i),
+    ///             GraphName::DefaultGraph,
+    ///         )
+    ///     })
+    ///     .collect::<Dataset>();
+    /// let evaluator = QueryEvaluator::new().with_memory_budget(10_000);
+    /// let query = Query::parse(
+    ///     "SELECT ?o (COUNT(?s) AS ?c) WHERE { ?s ?p ?o } GROUP BY ?o ORDER BY ?o",
+    ///     None,
+    /// )?;
+    /// if let QueryResults::Solutions(solutions) = evaluator.execute(dataset, &query)? {
+    ///     let solutions = solutions.collect::<Result<Vec<_>, _>>()?;
+    ///     assert_eq!(solutions.len(), 2000);
+    ///     assert_eq!(solutions[0]["o"], Literal::from(0).into());
+    ///     assert_eq!(solutions[1999]["o"], Literal::from(1999).into());
+    /// }
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_memory_budget(mut self, max_bytes: usize) -> Self {
+        self.memory_budget = Some(max_bytes);
+        self
+    }
+
+    /// Sets the directory where the solutions not fitting in the [memory budget](Self::with_memory_budget) are written.
+    ///
+    /// By default, the system temporary directory is used.
+    #[inline]
+    #[must_use]
+    pub fn with_spill_directory(mut self, path: impl Into<PathBuf>) -> Self {
+        self.spill_directory = Some(path.into());
+        self
+    }
+
+    fn memory_budget(&self) -> Option<MemoryBudget> {
+        Some(MemoryBudget {
+            max_bytes: self.memory_budget?,
+            spill_directory: self.spill_directory.clone(),
+        })
+    }
+
+    fn plan(
+        &self,
+        query: &Query,
+        pattern: &spargebra::algebra::GraphPattern,
+        substitutions: &[(Variable, Term)],
+    ) -> Arc<GraphPattern> {
+        let build = || {
+            let pattern = GraphPattern::from(pattern);
+            Arc::new(if self.without_optimizations {
+                pattern
+            } else {
+                self.optimizer.optimize(pattern)
+            })
+        };
+        match &self.plan_cache {
+            Some(plan_cache) if !self.without_optimizations => {
+                let mut substituted_variables = substitutions
+                    .iter()
+                    .map(|(variable, _)| variable.clone())
+                    .collect::<Vec<_>>();
+                substituted_variables.sort_unstable();
+                substituted_variables.dedup();
+                plan_cache.get_or_insert_with(
+                    PlanCacheKey::Plan {
+                        query: query.to_string(),
+                        substituted_variables,
+                        optimizer: self.optimizer.fingerprint(),
+                    },
+                    build,
+                )
+            }
+            _ => build(),
+        }
+    }
 }
 
 pub(crate) type CustomFunctionRegistry =
     HashMap<NamedNode, Arc<dyn (Fn(&[Term]) -> Option<Term>) + Send + Sync>>;
 
+pub(crate) type CustomAggregateFunctionRegistry = HashMap<
+    NamedNode,
+    Arc<dyn (Fn() -> Box<dyn AggregateFunctionAccumulator + Send + Sync>) + Send + Sync>,
+>;
+
+pub(crate) type GraphPatternRewriterRegistry = Vec<
+    Arc<
+        dyn (Fn(spargebra::algebra::GraphPattern) -> spargebra::algebra::GraphPattern)
+            + Send
+            + Sync,
+    >,
+>;
+
+/// Metadata about a custom function, set with [`QueryEvaluator::with_custom_function_metadata`].
+#[derive(Eq, PartialEq, Debug, Clone, Default)]
+pub struct CustomFunctionMetadata {
+    arity: Option<usize>,
+    description: Option<String>,
+}
+
+impl CustomFunctionMetadata {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of arguments expected by the function.
+    #[inline]
+    #[must_use]
+    pub fn with_arity(mut self, arity: usize) -> Self {
+        self.arity = Some(arity);
+        self
+    }
+
+    /// Sets a human-readable description of the function.
+    #[inline]
+    #[must_use]
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// The number of arguments expected by the function, if known.
+    #[inline]
+    pub fn arity(&self) -> Option<usize> {
+        self.arity
+    }
+
+    /// A human-readable description of the function, if any.
+    #[inline]
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+}
+
+/// The way the `EXISTS` patterns are evaluated, set with [`QueryEvaluator::with_exists_strategy`].
+///
+/// The two strategies only give different results if a variable bound outside of the `EXISTS` is used inside of it
+/// in a `FILTER`, a `BIND`, an `OPTIONAL`, a `MINUS`, a sub-query or an aggregation.
+/// See the findings of the [SPARQL EXISTS community group](https://www.w3.org/community/sparql-exists/).
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash, Default)]
+#[non_exhaustive]
+pub enum ExistsStrategy {
+    /// The values of the current solution are substituted into the `EXISTS` pattern before its evaluation,
+    /// following the [SPARQL 1.1 specification](https://www.w3.org/TR/sparql11-query/#substitution).
+    #[default]
+    Substitution,
+    /// The `EXISTS` pattern is evaluated on its own and is true if one of its solutions is compatible with the current solution.
+    ///
+    /// It is the behavior of some other SPARQL engines and eases the migration of queries written for them.
+    FilterScoped,
+}
+
+/// A cache of parsed queries and optimized query plans, set with [`QueryEvaluator::with_plan_cache`].
+///
+/// The plans are indexed by query, including its dataset specification and base IRI,
+/// by the names of the substituted variables and by the [`Optimizer`] configuration,
+/// hence the cache might be shared between evaluators using different optimizers.
+/// When the cache is full, the least recently used entry is evicted.
+/// Clones of this struct share the same cache.
+#[derive(Clone)]
+pub struct QueryPlanCache {
+    inner: Arc<Mutex<LruCache<PlanCacheKey, PlanCacheValue>>>,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum PlanCacheKey {
+    /// A query string parsed with [`QueryPlanCache::parse_query`]
+    Parsed {
+        query: String,
+        base_iri: Option<String>,
+    },
+    /// An optimized plan
+    Plan {
+        /// The serialization of the query, including its base IRI and dataset specification
+        query: String,
+        /// The sorted names of the substituted variables
+        substituted_variables: Vec<Variable>,
+        optimizer: OptimizerFingerprint,
+    },
+}
+
+enum PlanCacheValue {
+    Parsed(Box<Query>),
+    Plan(Arc<GraphPattern>),
+}
+
+impl QueryPlanCache {
+    /// Builds a cache keeping at most `capacity` parsed queries and plans.
+    #[inline]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(LruCache::new(capacity))),
+        }
+    }
+
+    /// The maximal number of parsed queries and plans kept in the cache.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.content().capacity()
+    }
+
+    /// The number of parsed queries and plans currently in the cache.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.content().len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.content().is_empty()
+    }
+
+    /// Removes all the parsed queries and plans from the cache.
+    ///
+    /// It should be called if the query evaluation behavior changes, for example after an Oxigraph upgrade.
+    #[inline]
+    pub fn clear(&self) {
+        self.content().clear();
+    }
+
+    /// Parses a query, reusing the result of a previous call with the same query string and base IRI.
+    ///
+    /// It allows to skip the parsing of the queries evaluated again and again.
+    ///
+    /// ```
+    /// use spareval::QueryPlanCache;
+    ///
+    /// let cache = QueryPlanCache::new(100);
+    /// let query = cache.parse_query("ASK { ?s ?p ?o }", None)?;
+    /// assert_eq!(cache.parse_query("ASK { ?s ?p ?o }", None)?, query);
+    /// assert_eq!(cache.len(), 1);
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn parse_query(
+        &self,
+        query: &str,
+        base_iri: Option<&str>,
+    ) -> Result<Query, SparqlSyntaxError> {
+        let key = PlanCacheKey::Parsed {
+            query: query.into(),
+            base_iri: base_iri.map(Into::into),
+        };
+        if let Some(PlanCacheValue::Parsed(parsed)) = self.content().get(&key) {
+            return Ok(Query::clone(parsed));
+        }
+        // We parse the query without holding the lock
+        let parsed = Query::parse(query, base_iri)?;
+        self.content()
+            .insert(key, PlanCacheValue::Parsed(Box::new(parsed.clone())));
+        Ok(parsed)
+    }
+
+    fn get_or_insert_with(
+        &self,
+        key: PlanCacheKey,
+        build: impl FnOnce() -> Arc<GraphPattern>,
+    ) -> Arc<GraphPattern> {
+        if let Some(PlanCacheValue::Plan(plan)) = self.content().get(&key) {
+            return Arc::clone(plan);
+        }
+        // We build the plan without holding the lock
+        let plan = build();
+        self.content()
+            .insert(key, PlanCacheValue::Plan(Arc::clone(&plan)));
+        plan
+    }
+
+    fn content(&self) -> MutexGuard<'_, LruCache<PlanCacheKey, PlanCacheValue>> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A token allowing to cancel a query evaluation from an other thread.
+///
+/// See [`QueryEvaluator::with_cancellation_token`].
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the evaluations using this token.
+    #[inline]
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Accumulator of a custom aggregate function registered with [`QueryEvaluator::with_custom_aggregate_function`].
+///
+/// If the aggregated expression fails to evaluate for one of the group solutions, the aggregate evaluation fails
+/// without [`accumulate`](Self::accumulate) being called.
+pub trait AggregateFunctionAccumulator {
+    /// Adds a value to the aggregate.
+    fn accumulate(&mut self, element: Term);
+
+    /// Returns the aggregate value or `None` if it is an error.
+    fn finish(&mut self) -> Option<Term>;
+}
+
 /// The explanation of a query.
 #[derive(Clone)]
 pub struct QueryExplanation {
@@ -347,6 +1281,34 @@ impl QueryExplanation {
         self.inner.json_node(&mut writer, self.with_stats)?;
         writer.write_event(JsonEvent::EndObject)
     }
+
+    /// Writes the explanation as a human-readable tree.
+    ///
+    /// If the statistics have been computed, each node is followed by its number of inputs, its number of results and its evaluation duration.
+    ///
+    /// ```
+    /// use oxrdf::Dataset;
+    /// use spareval::QueryEvaluator;
+    /// use spargebra::Query;
+    ///
+    /// let query = Query::parse("SELECT * WHERE { ?s ?p ?o }", None)?;
+    /// let (results, explanation) = QueryEvaluator::new().explain(Dataset::new(), &query);
+    /// drop(results);
+    /// let mut buffer = Vec::new();
+    /// explanation.write_in_text(&mut buffer)?;
+    /// assert!(String::from_utf8(buffer)?.contains("QuadPattern"));
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn write_in_text(&self, mut writer: impl io::Write) -> io::Result<()> {
+        if let Some(planning_duration) = self.planning_duration {
+            writeln!(
+                writer,
+                "Planning duration: {}s",
+                f32::from(Float::from(planning_duration.as_seconds()))
+            )?;
+        }
+        self.inner.text_node(&mut writer, self.with_stats, "", "")
+    }
 }
 
 impl fmt::Debug for QueryExplanation {
@@ -362,3 +1324,215 @@ impl fmt::Debug for QueryExplanation {
         obj.finish_non_exhaustive()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxrdf::{Dataset, GraphName, Literal, Quad};
+    use std::sync::atomic::AtomicUsize;
+
+    /// An evaluator with a cache and a counter of the optimized plans
+    fn evaluator(capacity: usize) -> (QueryEvaluator, QueryPlanCache, Arc<AtomicUsize>) {
+        let plan_count = Arc::new(AtomicUsize::new(0));
+        let cache = QueryPlanCache::new(capacity);
+        let evaluator = QueryEvaluator::new()
+            .with_optimizer(Optimizer::default_passes().with_pass("count", {
+                let plan_count = Arc::clone(&plan_count);
+                move |pattern| {
+                    plan_count.fetch_add(1, Ordering::Relaxed);
+                    pattern
+                }
+            }))
+            .with_plan_cache(cache.clone());
+        (evaluator, cache, plan_count)
+    }
+
+    fn dataset() -> Dataset {
+        (0..3)
+            .map(|i| {
+                Quad::new(
+                    NamedNode::new_unchecked(format!("http://example.com/{i}")),
+                    NamedNode::new_unchecked("http://example.com/p"),
+                    Literal::from(i),
+                    GraphName::DefaultGraph,
+                )
+            })
+            .collect()
+    }
+
+    fn count_solutions(
+        evaluator: &QueryEvaluator,
+        query: &str,
+        substitutions: impl IntoIterator<Item = (Variable, Term)>,
+    ) -> usize {
+        let query = Query::parse(query, None).unwrap();
+        let QueryResults::Solutions(solutions) = evaluator
+            .execute_with_substituted_variables(dataset(), &query, substitutions)
+            .unwrap()
+        else {
+            unreachable!()
+        };
+        solutions.map(Result::unwrap).count()
+    }
+
+    #[test]
+    fn plan_cache_reuses_plans() {
+        let (evaluator, cache, plan_count) = evaluator(10);
+        let query = "SELECT * WHERE { ?s <http://example.com/p> ?o }";
+        assert_eq!(count_solutions(&evaluator, query, []), 3);
+        assert_eq!(count_solutions(&evaluator, query, []), 3);
+        assert_eq!(plan_count.load(Ordering::Relaxed), 1);
+        assert_eq!(cache.len(), 1);
+
+        // The same plan is used whatever the substituted values are
+        let s = Variable::new_unchecked("s");
+        for i in 0..3 {
+            let subject = NamedNode::new_unchecked(format!("http://example.com/{i}"));
+            assert_eq!(
+                count_solutions(&evaluator, query, [(s.clone(), subject.into())]),
+                1
+            );
+        }
+        assert_eq!(plan_count.load(Ordering::Relaxed), 2);
+        assert_eq!(cache.len(), 2);
+
+        // The dataset specification and the base IRI are part of the key
+        for query in [
+            "SELECT * FROM <http://example.com/g> WHERE { ?s <http://example.com/p> ?o }",
+            "BASE <http://example.com/> SELECT * WHERE { ?s <p> ?o }",
+        ] {
+            assert_eq!(count_solutions(&evaluator, query, []), 3);
+        }
+        assert_eq!(plan_count.load(Ordering::Relaxed), 4);
+        assert_eq!(cache.len(), 4);
+    }
+
+    #[test]
+    fn plan_cache_evicts_least_recently_used() {
+        let (evaluator, cache, plan_count) = evaluator(2);
+        assert_eq!(cache.capacity(), 2);
+        let first = "SELECT * WHERE { ?s <http://example.com/p> 0 }";
+        let second = "SELECT * WHERE { ?s <http://example.com/p> 1 }";
+        let third = "SELECT * WHERE { ?s <http://example.com/p> 2 }";
+        for query in [first, second, first, third] {
+            assert_eq!(count_solutions(&evaluator, query, []), 1);
+        }
+        assert_eq!(plan_count.load(Ordering::Relaxed), 3);
+        assert_eq!(cache.len(), 2);
+
+        // The first query has been used more recently than the second one
+        assert_eq!(count_solutions(&evaluator, first, []), 1);
+        assert_eq!(plan_count.load(Ordering::Relaxed), 3);
+        assert_eq!(count_solutions(&evaluator, second, []), 1);
+        assert_eq!(plan_count.load(Ordering::Relaxed), 4);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn plan_cache_distinguishes_optimizers() {
+        let (evaluator, cache, plan_count) = evaluator(10);
+        let query = "SELECT * WHERE { ?s <http://example.com/p> ?o }";
+        assert_eq!(count_solutions(&evaluator, query, []), 3);
+        let other_evaluator = QueryEvaluator::new()
+            .with_optimizer(Optimizer::empty().with_pass("no-solution", |_| GraphPattern::empty()))
+            .with_plan_cache(cache.clone());
+        assert_eq!(count_solutions(&other_evaluator, query, []), 0);
+        assert_eq!(count_solutions(&evaluator, query, []), 3);
+        assert_eq!(plan_count.load(Ordering::Relaxed), 1);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn plan_cache_parse_query() {
+        let cache = QueryPlanCache::new(10);
+        let query = cache
+            .parse_query("SELECT * WHERE { ?s <p> ?o }", Some("http://example.com/"))
+            .unwrap();
+        assert_eq!(
+            cache
+                .parse_query("SELECT * WHERE { ?s <p> ?o }", Some("http://example.com/"))
+                .unwrap(),
+            query
+        );
+        assert_eq!(cache.len(), 1);
+        // The base IRI is part of the key
+        assert_ne!(
+            cache
+                .parse_query("SELECT * WHERE { ?s <p> ?o }", Some("http://example.org/"))
+                .unwrap(),
+            query
+        );
+        assert_eq!(cache.len(), 2);
+        // Invalid queries are not cached
+        cache.parse_query("SELECT", None).unwrap_err();
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn plan_cache_clear() {
+        let (evaluator, cache, plan_count) = evaluator(10);
+        let query = "SELECT * WHERE { ?s ?p ?o }";
+        assert_eq!(count_solutions(&evaluator, query, []), 3);
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(count_solutions(&evaluator, query, []), 3);
+        assert_eq!(plan_count.load(Ordering::Relaxed), 2);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn plan_cache_without_capacity() {
+        let (evaluator, cache, plan_count) = evaluator(0);
+        let query = "SELECT * WHERE { ?s ?p ?o }";
+        assert_eq!(count_solutions(&evaluator, query, []), 3);
+        assert_eq!(count_solutions(&evaluator, query, []), 3);
+        assert_eq!(plan_count.load(Ordering::Relaxed), 2);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn plan_cache_without_optimizations() {
+        let (evaluator, cache, plan_count) = evaluator(10);
+        let evaluator = evaluator.without_optimizations();
+        assert_eq!(
+            count_solutions(&evaluator, "SELECT * WHERE { ?s ?p ?o }", []),
+            3
+        );
+        assert_eq!(plan_count.load(Ordering::Relaxed), 0);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn custom_optimizer() {
+        let query = "SELECT * WHERE { ?s ?p ?o }";
+        let evaluator = QueryEvaluator::new()
+            .with_optimizer(Optimizer::empty().with_pass("no-solution", |_| GraphPattern::empty()));
+        assert_eq!(count_solutions(&evaluator, query, []), 0);
+        // The optimizer is not used if the optimizations are disabled
+        assert_eq!(
+            count_solutions(&evaluator.without_optimizations(), query, []),
+            3
+        );
+    }
+
+    #[test]
+    fn statistics_kept_with_custom_optimizer() {
+        let statistics: Arc<dyn StatisticsProvider> = Arc::new(DatasetStatistics::default());
+        let evaluator = QueryEvaluator::new()
+            .with_statistics(Arc::clone(&statistics))
+            .with_optimizer(Optimizer::default_passes().without_pass("path-expansion"));
+        assert_eq!(
+            evaluator.optimizer.fingerprint(),
+            Optimizer::default_passes()
+                .without_pass("path-expansion")
+                .with_statistics(statistics)
+                .fingerprint()
+        );
+        assert_ne!(
+            evaluator.optimizer.fingerprint(),
+            Optimizer::default_passes()
+                .without_pass("path-expansion")
+                .fingerprint()
+        );
+    }
+}
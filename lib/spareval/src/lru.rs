@@ -0,0 +1,126 @@
+use rustc_hash::FxHashMap;
+use std::borrow::Borrow;
+use std::hash::Hash;
+
+/// A map keeping at most `capacity` entries and evicting the least recently used one when full.
+///
+/// The entries are stored in a slab and linked in their use order, making all operations constant time.
+pub struct LruCache<K, V> {
+    capacity: usize,
+    indexes: FxHashMap<K, usize>,
+    entries: Vec<LruEntry<K, V>>,
+    /// The most recently used entry
+    head: Option<usize>,
+    /// The least recently used entry
+    tail: Option<usize>,
+}
+
+struct LruEntry<K, V> {
+    key: K,
+    value: V,
+    previous: Option<usize>,
+    next: Option<usize>,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            indexes: FxHashMap::default(),
+            entries: Vec::new(),
+            head: None,
+            tail: None,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.indexes.clear();
+        self.entries.clear();
+        self.head = None;
+        self.tail = None;
+    }
+
+    /// Returns the value of the key and marks it as the most recently used
+    pub fn get<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        let index = *self.indexes.get(key)?;
+        self.unlink(index);
+        self.push_front(index);
+        Some(&self.entries[index].value)
+    }
+
+    /// Inserts the value as the most recently used, evicting the least recently used entry if the cache is full
+    pub fn insert(&mut self, key: K, value: V) {
+        if let Some(&index) = self.indexes.get(&key) {
+            self.entries[index].value = value;
+            self.unlink(index);
+            self.push_front(index);
+            return;
+        }
+        if self.capacity == 0 {
+            return;
+        }
+        let index = if self.entries.len() >= self.capacity {
+            // We reuse the slot of the least recently used entry
+            let Some(index) = self.tail else {
+                return;
+            };
+            self.unlink(index);
+            let entry = &mut self.entries[index];
+            self.indexes.remove(&entry.key);
+            entry.key = key.clone();
+            entry.value = value;
+            index
+        } else {
+            self.entries.push(LruEntry {
+                key: key.clone(),
+                value,
+                previous: None,
+                next: None,
+            });
+            self.entries.len() - 1
+        };
+        self.indexes.insert(key, index);
+        self.push_front(index);
+    }
+
+    fn unlink(&mut self, index: usize) {
+        let (previous, next) = {
+            let entry = &mut self.entries[index];
+            (entry.previous.take(), entry.next.take())
+        };
+        match previous {
+            Some(previous) => self.entries[previous].next = next,
+            None => self.head = next,
+        }
+        match next {
+            Some(next) => self.entries[next].previous = previous,
+            None => self.tail = previous,
+        }
+    }
+
+    fn push_front(&mut self, index: usize) {
+        self.entries[index].next = self.head;
+        if let Some(head) = self.head {
+            self.entries[head].previous = Some(index);
+        }
+        self.head = Some(index);
+        if self.tail.is_none() {
+            self.tail = Some(index);
+        }
+    }
+}
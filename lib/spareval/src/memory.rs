@@ -0,0 +1,227 @@
+use rand::random;
+use std::env::temp_dir;
+use std::fs::{remove_file, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Configuration of the memory used by the operators buffering solutions.
+#[derive(Clone)]
+pub struct MemoryBudget {
+    pub max_bytes: usize,
+    pub spill_directory: Option<PathBuf>,
+}
+
+/// Tracks the estimated memory used by the operators of a query evaluation.
+///
+/// It is shared between the threads used for parallel evaluation.
+pub struct MemoryTracker {
+    budget: MemoryBudget,
+    used: AtomicUsize,
+}
+
+impl MemoryTracker {
+    pub fn new(budget: MemoryBudget) -> Self {
+        Self {
+            budget,
+            used: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn reservation(self: &Arc<Self>) -> MemoryReservation {
+        MemoryReservation {
+            tracker: Arc::clone(self),
+            size: 0,
+        }
+    }
+
+    /// Creates a new temporary file in the spill directory
+    pub fn spill_file(&self) -> io::Result<SpillWriter> {
+        let path = self
+            .budget
+            .spill_directory
+            .clone()
+            .unwrap_or_else(temp_dir)
+            .join(format!("spareval-{:x}.tmp", random::<u128>()));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        Ok(SpillWriter {
+            writer: BufWriter::new(file),
+            path: TempPath(path),
+        })
+    }
+}
+
+/// Memory reserved by an operator, released when dropped.
+pub struct MemoryReservation {
+    tracker: Arc<MemoryTracker>,
+    size: usize,
+}
+
+impl MemoryReservation {
+    /// Reserves `size` more bytes, returns `false` without reserving anything if the budget would be exceeded
+    pub fn try_grow(&mut self, size: usize) -> bool {
+        let max_bytes = self.tracker.budget.max_bytes;
+        if self
+            .tracker
+            .used
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                used.checked_add(size).filter(|used| *used <= max_bytes)
+            })
+            .is_err()
+        {
+            return false;
+        }
+        self.size += size;
+        true
+    }
+
+    /// Reserves `size` more bytes even if the budget is exceeded
+    pub fn grow(&mut self, size: usize) {
+        self.tracker.used.fetch_add(size, Ordering::Relaxed);
+        self.size += size;
+    }
+
+    /// Releases all the reserved memory
+    pub fn release(&mut self) {
+        self.tracker.used.fetch_sub(self.size, Ordering::Relaxed);
+        self.size = 0;
+    }
+
+    pub fn max_bytes(&self) -> usize {
+        self.tracker.budget.max_bytes
+    }
+}
+
+impl Drop for MemoryReservation {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+/// A line-based temporary file being written
+pub struct SpillWriter {
+    writer: BufWriter<File>,
+    path: TempPath,
+}
+
+impl SpillWriter {
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.write_all(b"\n")
+    }
+
+    pub fn into_reader(mut self) -> io::Result<SpillReader> {
+        self.writer.flush()?;
+        Ok(SpillReader {
+            reader: BufReader::new(File::open(&self.path.0)?),
+            path: self.path,
+            buffer: String::new(),
+        })
+    }
+}
+
+/// Reads back the lines of a temporary file, the file is removed when dropped
+pub struct SpillReader {
+    reader: BufReader<File>,
+    #[allow(dead_code)] // Removes the file on drop
+    path: TempPath,
+    buffer: String,
+}
+
+impl SpillReader {
+    /// Returns the next line, without the line jump
+    pub fn next_line(&mut self) -> Option<io::Result<&str>> {
+        self.buffer.clear();
+        match self.reader.read_line(&mut self.buffer) {
+            Ok(0) => None,
+            Ok(_) => Some(Ok(self
+                .buffer
+                .strip_suffix('\n')
+                .unwrap_or(self.buffer.as_str()))),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+struct TempPath(PathBuf);
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        // Nothing more can be done if the removal fails
+        drop(remove_file(&self.0));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, read_dir, remove_dir};
+    use std::path::Path;
+
+    fn tracker(max_bytes: usize) -> (Arc<MemoryTracker>, PathBuf) {
+        let directory = temp_dir().join(format!("spareval-test-{:x}", random::<u128>()));
+        create_dir_all(&directory).unwrap();
+        let tracker = Arc::new(MemoryTracker::new(MemoryBudget {
+            max_bytes,
+            spill_directory: Some(directory.clone()),
+        }));
+        (tracker, directory)
+    }
+
+    fn file_count(directory: &Path) -> usize {
+        read_dir(directory).unwrap().count()
+    }
+
+    #[test]
+    fn reservation() {
+        let (tracker, directory) = tracker(100);
+        let mut first = tracker.reservation();
+        assert!(first.try_grow(60));
+        let mut second = tracker.reservation();
+        assert!(!second.try_grow(50), "the budget is exceeded");
+        assert!(second.try_grow(40));
+        first.release();
+        assert!(second.try_grow(50));
+        drop(second);
+        assert!(first.try_grow(100), "the memory is released on drop");
+        remove_dir(directory).unwrap();
+    }
+
+    #[test]
+    fn spill_round_trip() {
+        let (tracker, directory) = tracker(0);
+        let lines = ["a\tb", "", "\"c\"@en\t\t<http://example.com>", "d"];
+        let mut writer = tracker.spill_file().unwrap();
+        for line in lines {
+            writer.write_line(line).unwrap();
+        }
+        let mut reader = writer.into_reader().unwrap();
+        for line in lines {
+            assert_eq!(reader.next_line().unwrap().unwrap(), line);
+        }
+        assert!(reader.next_line().is_none());
+        assert_eq!(file_count(&directory), 1);
+        drop(reader);
+        assert_eq!(file_count(&directory), 0, "the file is removed on drop");
+        remove_dir(directory).unwrap();
+    }
+
+    #[test]
+    fn spill_writer_removed_on_drop() {
+        let (tracker, directory) = tracker(0);
+        let mut writer = tracker.spill_file().unwrap();
+        writer.write_line("a").unwrap();
+        let other = tracker.spill_file().unwrap();
+        assert_eq!(file_count(&directory), 2, "each spill file is new");
+        drop(writer);
+        assert_eq!(file_count(&directory), 1, "the file is removed on drop");
+        drop(other);
+        assert_eq!(file_count(&directory), 0, "the file is removed on drop");
+        remove_dir(directory).unwrap();
+    }
+}
@@ -12,6 +12,10 @@ use std::sync::Arc;
 ///
 /// Note that you can also use [`DefaultServiceHandler`] if you need to handle any service and not a specific one.
 ///
+/// If [`supports_bound_join`](Self::supports_bound_join) returns `true` and a non-silent `SERVICE` is joined with other patterns,
+/// the evaluator sends the values of the join variables computed by the other patterns to the service by batches,
+/// in a `VALUES` clause joined with the service pattern, so only the relevant remote solutions are transferred.
+///
 /// ```
 /// use oxrdf::{Dataset, Literal, NamedNode, Variable};
 /// use sparesults::QuerySolution;
@@ -70,6 +74,15 @@ pub trait ServiceHandler: Send + Sync {
         pattern: GraphPattern,
         base_iri: Option<String>,
     ) -> Result<QuerySolutionIter, Self::Error>;
+
+    /// If the handler is able to evaluate service patterns of the form `Join(Values, pattern)`.
+    ///
+    /// If `true`, the values of the join variables are sent to the service in a `VALUES` clause.
+    /// If `false` (the default), the service is always called with the pattern written in the query.
+    #[inline]
+    fn supports_bound_join(&self) -> bool {
+        false
+    }
 }
 
 /// Default handler for [SPARQL 1.1 Federated Query](https://www.w3.org/TR/sparql11-federated-query/) SERVICEs.
@@ -79,6 +92,9 @@ pub trait ServiceHandler: Send + Sync {
 ///
 /// Note that you can also use [`ServiceHandler`] if you need to handle a single service and not any service.
 ///
+/// Like with [`ServiceHandler`], if [`supports_bound_join`](Self::supports_bound_join) returns `true`,
+/// the given patterns might contain a `VALUES` clause with the values of the join variables.
+///
 /// ```
 /// use oxrdf::{Dataset, NamedNode, Variable};
 /// use sparesults::QuerySolution;
@@ -136,6 +152,14 @@ pub trait DefaultServiceHandler: Send + Sync {
         pattern: GraphPattern,
         base_iri: Option<String>,
     ) -> Result<QuerySolutionIter, Self::Error>;
+
+    /// If the handler is able to evaluate service patterns of the form `Join(Values, pattern)`.
+    ///
+    /// See [`ServiceHandler::supports_bound_join`].
+    #[inline]
+    fn supports_bound_join(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Clone, Default)]
@@ -166,6 +190,21 @@ impl ServiceHandlerRegistry {
         self.default.is_some()
     }
 
+    /// If the handler of the given service supports bound joins, `None` stands for any service
+    pub fn supports_bound_join(&self, service_name: Option<&NamedNode>) -> bool {
+        let default = self
+            .default
+            .as_ref()
+            .is_some_and(|default| default.supports_bound_join());
+        let Some(service_name) = service_name else {
+            return default && self.handlers.values().all(|h| h.supports_bound_join());
+        };
+        if let Some(handler) = self.handlers.get(service_name) {
+            return handler.supports_bound_join();
+        }
+        default
+    }
+
     pub fn handle(
         &self,
         service_name: NamedNode,
@@ -194,6 +233,10 @@ impl<S: ServiceHandler> ServiceHandler for ErrorConversionServiceHandler<S> {
     ) -> Result<QuerySolutionIter, QueryEvaluationError> {
         self.0.handle(pattern, base_iri).map_err(wrap_service_error)
     }
+
+    fn supports_bound_join(&self) -> bool {
+        self.0.supports_bound_join()
+    }
 }
 
 impl<S: DefaultServiceHandler> DefaultServiceHandler for ErrorConversionServiceHandler<S> {
@@ -209,9 +252,15 @@ impl<S: DefaultServiceHandler> DefaultServiceHandler for ErrorConversionServiceH
             .handle(service_name, pattern, base_iri)
             .map_err(wrap_service_error)
     }
+
+    fn supports_bound_join(&self) -> bool {
+        self.0.supports_bound_join()
+    }
 }
 
-fn wrap_service_error(error: impl Error + Send + Sync + 'static) -> QueryEvaluationError {
+pub(crate) fn wrap_service_error(
+    error: impl Error + Send + Sync + 'static,
+) -> QueryEvaluationError {
     let error: Box<dyn Error + Send + Sync> = Box::new(error);
     match error.downcast() {
         Ok(error) => *error,
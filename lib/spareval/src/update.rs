@@ -0,0 +1,809 @@
+use crate::dataset::MutableQueryableDataset;
+use crate::{QueryEvaluationError, QueryEvaluator, QueryResults, QuerySolution, QueryableDataset};
+use oxiri::Iri;
+use oxrdf::{BlankNode, GraphName, GraphNameRef, NamedNode, Quad, Subject, Term, Triple};
+use rustc_hash::FxHashMap;
+use spargebra::algebra::{GraphPattern, GraphTarget, QueryDataset};
+#[cfg(feature = "rdf-star")]
+use spargebra::term::TriplePattern;
+use spargebra::term::{
+    GraphName as GraphNameTarget, GraphNamePattern, GroundQuad, GroundQuadPattern,
+    NamedNodePattern, Quad as DataQuad, QuadPattern, TermPattern,
+};
+use spargebra::{GraphUpdateOperation, Query, Update};
+use std::error::Error;
+use std::sync::Arc;
+
+/// Evaluates a SPARQL update against a given [`MutableQueryableDataset`]
+///
+/// The `WHERE` clauses are evaluated using a [`QueryEvaluator`] that can be set with [`with_query_evaluator`](Self::with_query_evaluator).
+///
+/// The `USING` and `USING NAMED` clauses are given to [`MutableQueryableDataset::queryable_dataset`].
+///
+/// The operations are applied one after the other and the evaluation is not atomic:
+/// if an operation fails, the changes done by the previous ones are kept.
+/// Wrap the evaluation in a transaction of your dataset if you need atomicity.
+///
+/// ```
+/// use oxrdf::{Dataset, GraphNameRef, NamedNodeRef, QuadRef};
+/// use spareval::UpdateEvaluator;
+/// use spargebra::Update;
+///
+/// let mut dataset = Dataset::new();
+/// let update = Update::parse(
+///     "INSERT DATA { <http://example.com/s> <http://example.com/p> <http://example.com/o> } ;
+///      INSERT { ?s <http://example.com/p2> ?o } WHERE { ?s <http://example.com/p> ?o } ;
+///      DELETE DATA { <http://example.com/s> <http://example.com/p> <http://example.com/o> }",
+///     None,
+/// )?;
+/// UpdateEvaluator::new().execute(&mut dataset, &update)?;
+/// assert_eq!(dataset.len(), 1);
+/// assert!(dataset.contains(QuadRef::new(
+///     NamedNodeRef::new("http://example.com/s")?,
+///     NamedNodeRef::new("http://example.com/p2")?,
+///     NamedNodeRef::new("http://example.com/o")?,
+///     GraphNameRef::DefaultGraph
+/// )));
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[derive(Clone, Default)]
+pub struct UpdateEvaluator {
+    query_evaluator: QueryEvaluator,
+    load_handler: Option<Arc<dyn LoadHandler<Error = QueryEvaluationError>>>,
+}
+
+impl UpdateEvaluator {
+    #[must_use]
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the [`QueryEvaluator`] used to evaluate the `WHERE` clauses.
+    ///
+    /// It allows to use custom functions, `SERVICE` handlers or a cancellation token in the updates.
+    #[must_use]
+    #[inline]
+    pub fn with_query_evaluator(mut self, query_evaluator: QueryEvaluator) -> Self {
+        self.query_evaluator = query_evaluator;
+        self
+    }
+
+    /// Sets the handler fetching the RDF graphs of the `LOAD` operations.
+    ///
+    /// Without handler, the `LOAD` operations fail with [`QueryEvaluationError::UnsupportedLoad`]
+    /// or are ignored if they are `SILENT`.
+    #[must_use]
+    #[inline]
+    pub fn with_load_handler(mut self, handler: impl LoadHandler + 'static) -> Self {
+        self.load_handler = Some(Arc::new(ErrorConversionLoadHandler(handler)));
+        self
+    }
+
+    /// Applies the update operations to the dataset.
+    pub fn execute<D: MutableQueryableDataset>(
+        &self,
+        dataset: &mut D,
+        update: &Update,
+    ) -> Result<(), QueryEvaluationError> {
+        for operation in &update.operations {
+            self.execute_operation(dataset, operation, update.base_iri.as_ref())?;
+        }
+        Ok(())
+    }
+
+    /// Applies a single update operation to the dataset.
+    ///
+    /// `base_iri` is the base IRI of the update the operation is from.
+    pub fn execute_operation<D: MutableQueryableDataset>(
+        &self,
+        dataset: &mut D,
+        operation: &GraphUpdateOperation,
+        base_iri: Option<&Iri<String>>,
+    ) -> Result<(), QueryEvaluationError> {
+        match operation {
+            GraphUpdateOperation::InsertData { data } => eval_insert_data(dataset, data),
+            GraphUpdateOperation::DeleteData { data } => eval_delete_data(dataset, data),
+            GraphUpdateOperation::DeleteInsert {
+                delete,
+                insert,
+                using,
+                pattern,
+            } => {
+                self.eval_delete_insert(dataset, delete, insert, using.as_ref(), pattern, base_iri)
+            }
+            GraphUpdateOperation::Load {
+                silent,
+                source,
+                destination,
+            } => match self.eval_load(dataset, source, destination) {
+                // The dataset errors are never silenced
+                Err(error) if *silent && !matches!(error, QueryEvaluationError::Dataset(_)) => {
+                    Ok(())
+                }
+                result => result,
+            },
+            GraphUpdateOperation::Clear { graph, silent } => eval_clear(dataset, graph, *silent),
+            GraphUpdateOperation::Create { graph, silent } => eval_create(dataset, graph, *silent),
+            GraphUpdateOperation::Drop { graph, silent } => eval_drop(dataset, graph, *silent),
+        }
+    }
+
+    fn eval_delete_insert<D: MutableQueryableDataset>(
+        &self,
+        dataset: &mut D,
+        delete: &[GroundQuadPattern],
+        insert: &[QuadPattern],
+        using: Option<&QueryDataset>,
+        pattern: &GraphPattern,
+        base_iri: Option<&Iri<String>>,
+    ) -> Result<(), QueryEvaluationError> {
+        let view = Arc::new(
+            dataset
+                .queryable_dataset(using)
+                .map_err(wrap_dataset_error)?,
+        );
+        // All the solutions are computed before changing the dataset
+        let solutions = self.eval_where(Arc::clone(&view), pattern, base_iri);
+        dataset.release_queryable_dataset(view);
+        let solutions = solutions?;
+
+        let delete = delete.iter().map(to_quad_pattern).collect::<Vec<_>>();
+        let mut bnodes = FxHashMap::default();
+        for solution in &solutions {
+            for pattern in &delete {
+                if let Some(quad) = fill_quad_pattern(pattern, solution, &mut bnodes) {
+                    dataset.remove(quad.as_ref()).map_err(wrap_dataset_error)?;
+                }
+            }
+        }
+        for solution in &solutions {
+            for pattern in insert {
+                if let Some(quad) = fill_quad_pattern(pattern, solution, &mut bnodes) {
+                    dataset.insert(quad.as_ref()).map_err(wrap_dataset_error)?;
+                }
+            }
+            // Each solution gets its own blank nodes
+            bnodes.clear();
+        }
+        Ok(())
+    }
+
+    fn eval_where(
+        &self,
+        dataset: impl QueryableDataset,
+        pattern: &GraphPattern,
+        base_iri: Option<&Iri<String>>,
+    ) -> Result<Vec<QuerySolution>, QueryEvaluationError> {
+        let QueryResults::Solutions(solutions) = self.query_evaluator.execute(
+            dataset,
+            &Query::Select {
+                dataset: None,
+                pattern: pattern.clone(),
+                base_iri: base_iri.cloned(),
+            },
+        )?
+        else {
+            unreachable!("We provided a SELECT query, we must get back solutions")
+        };
+        solutions.collect()
+    }
+
+    fn eval_load<D: MutableQueryableDataset>(
+        &self,
+        dataset: &mut D,
+        source: &NamedNode,
+        destination: &GraphNameTarget,
+    ) -> Result<(), QueryEvaluationError> {
+        let Some(load_handler) = &self.load_handler else {
+            return Err(QueryEvaluationError::UnsupportedLoad(source.clone()));
+        };
+        let graph_name = convert_graph_name(destination);
+        for triple in load_handler.load(source)? {
+            dataset
+                .insert(triple?.in_graph(graph_name.clone()).as_ref())
+                .map_err(wrap_dataset_error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Handler for the [`LOAD`](https://www.w3.org/TR/sparql11-update/#load) operations of SPARQL updates.
+///
+/// Should be given to [`UpdateEvaluator::with_load_handler`]
+/// before evaluating a SPARQL update that uses `LOAD` operations.
+///
+/// ```
+/// use oxrdf::{Dataset, NamedNode, Triple};
+/// use spareval::{LoadHandler, UpdateEvaluator};
+/// use spargebra::Update;
+/// use std::convert::Infallible;
+/// use std::iter::once;
+///
+/// struct TestLoadHandler {}
+///
+/// impl LoadHandler for TestLoadHandler {
+///     type Error = Infallible;
+///
+///     fn load(
+///         &self,
+///         source: &NamedNode,
+///     ) -> Result<Box<dyn Iterator<Item = Result<Triple, Infallible>>>, Infallible> {
+///         // The loaded graph always contains a single triple
+///         let triple = Triple::new(source.clone(), source.clone(), source.clone());
+///         Ok(Box::new(once(Ok(triple))))
+///     }
+/// }
+///
+/// let mut dataset = Dataset::new();
+/// let update = Update::parse(
+///     "LOAD <http://example.com> INTO GRAPH <http://example.com/g>",
+///     None,
+/// )?;
+/// UpdateEvaluator::new()
+///     .with_load_handler(TestLoadHandler {})
+///     .execute(&mut dataset, &update)?;
+/// assert_eq!(dataset.len(), 1);
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+pub trait LoadHandler: Send + Sync {
+    /// The load error.
+    type Error: Error + Send + Sync + 'static;
+
+    /// Returns the triples of the RDF graph identified by the given IRI.
+    ///
+    /// The blank nodes of the returned triples are inserted as is in the dataset,
+    /// they should be renamed to fresh identifiers to not clash with the existing ones.
+    #[allow(clippy::type_complexity)]
+    fn load(
+        &self,
+        source: &NamedNode,
+    ) -> Result<Box<dyn Iterator<Item = Result<Triple, Self::Error>>>, Self::Error>;
+}
+
+struct ErrorConversionLoadHandler<L>(L);
+
+impl<L: LoadHandler> LoadHandler for ErrorConversionLoadHandler<L> {
+    type Error = QueryEvaluationError;
+
+    fn load(
+        &self,
+        source: &NamedNode,
+    ) -> Result<Box<dyn Iterator<Item = Result<Triple, QueryEvaluationError>>>, QueryEvaluationError>
+    {
+        Ok(Box::new(
+            self.0
+                .load(source)
+                .map_err(wrap_load_error)?
+                .map(|t| t.map_err(wrap_load_error)),
+        ))
+    }
+}
+
+fn eval_insert_data<D: MutableQueryableDataset>(
+    dataset: &mut D,
+    data: &[DataQuad],
+) -> Result<(), QueryEvaluationError> {
+    let mut bnodes = FxHashMap::default();
+    for quad in data {
+        let quad = Quad::new(
+            convert_subject(&quad.subject, &mut bnodes),
+            quad.predicate.clone(),
+            convert_term(&quad.object, &mut bnodes),
+            convert_graph_name(&quad.graph_name),
+        );
+        dataset.insert(quad.as_ref()).map_err(wrap_dataset_error)?;
+    }
+    Ok(())
+}
+
+fn eval_delete_data<D: MutableQueryableDataset>(
+    dataset: &mut D,
+    data: &[GroundQuad],
+) -> Result<(), QueryEvaluationError> {
+    for quad in data {
+        let quad = Quad::new(
+            Subject::from(quad.subject.clone()),
+            quad.predicate.clone(),
+            Term::from(quad.object.clone()),
+            convert_graph_name(&quad.graph_name),
+        );
+        dataset.remove(quad.as_ref()).map_err(wrap_dataset_error)?;
+    }
+    Ok(())
+}
+
+fn eval_create<D: MutableQueryableDataset>(
+    dataset: &mut D,
+    graph_name: &NamedNode,
+    silent: bool,
+) -> Result<(), QueryEvaluationError> {
+    if dataset
+        .insert_named_graph(graph_name.as_ref().into())
+        .map_err(wrap_dataset_error)?
+        || silent
+    {
+        Ok(())
+    } else {
+        Err(QueryEvaluationError::GraphAlreadyExists(graph_name.clone()))
+    }
+}
+
+fn eval_clear<D: MutableQueryableDataset>(
+    dataset: &mut D,
+    graph: &GraphTarget,
+    silent: bool,
+) -> Result<(), QueryEvaluationError> {
+    match graph {
+        GraphTarget::NamedNode(graph_name) => {
+            if dataset
+                .contains_named_graph(graph_name.as_ref().into())
+                .map_err(wrap_dataset_error)?
+            {
+                dataset
+                    .clear_graph(graph_name.as_ref().into())
+                    .map_err(wrap_dataset_error)
+            } else if silent {
+                Ok(())
+            } else {
+                Err(QueryEvaluationError::GraphDoesNotExist(graph_name.clone()))
+            }
+        }
+        GraphTarget::DefaultGraph => dataset
+            .clear_graph(GraphNameRef::DefaultGraph)
+            .map_err(wrap_dataset_error),
+        GraphTarget::NamedGraphs => dataset.clear_all_named_graphs().map_err(wrap_dataset_error),
+        GraphTarget::AllGraphs => dataset.clear_all_graphs().map_err(wrap_dataset_error),
+    }
+}
+
+fn eval_drop<D: MutableQueryableDataset>(
+    dataset: &mut D,
+    graph: &GraphTarget,
+    silent: bool,
+) -> Result<(), QueryEvaluationError> {
+    match graph {
+        GraphTarget::NamedNode(graph_name) => {
+            if dataset
+                .remove_named_graph(graph_name.as_ref().into())
+                .map_err(wrap_dataset_error)?
+                || silent
+            {
+                Ok(())
+            } else {
+                Err(QueryEvaluationError::GraphDoesNotExist(graph_name.clone()))
+            }
+        }
+        GraphTarget::DefaultGraph => dataset
+            .clear_graph(GraphNameRef::DefaultGraph)
+            .map_err(wrap_dataset_error),
+        GraphTarget::NamedGraphs => dataset
+            .remove_all_named_graphs()
+            .map_err(wrap_dataset_error),
+        GraphTarget::AllGraphs => dataset.clear().map_err(wrap_dataset_error),
+    }
+}
+
+fn convert_subject(subject: &Subject, bnodes: &mut FxHashMap<BlankNode, BlankNode>) -> Subject {
+    match subject {
+        Subject::NamedNode(subject) => subject.clone().into(),
+        Subject::BlankNode(subject) => convert_blank_node(subject, bnodes).into(),
+        #[cfg(feature = "rdf-star")]
+        Subject::Triple(subject) => convert_triple(subject, bnodes).into(),
+    }
+}
+
+fn convert_term(term: &Term, bnodes: &mut FxHashMap<BlankNode, BlankNode>) -> Term {
+    match term {
+        Term::NamedNode(term) => term.clone().into(),
+        Term::BlankNode(term) => convert_blank_node(term, bnodes).into(),
+        Term::Literal(term) => term.clone().into(),
+        #[cfg(feature = "rdf-star")]
+        Term::Triple(term) => convert_triple(term, bnodes).into(),
+    }
+}
+
+#[cfg(feature = "rdf-star")]
+fn convert_triple(triple: &Triple, bnodes: &mut FxHashMap<BlankNode, BlankNode>) -> Triple {
+    Triple::new(
+        convert_subject(&triple.subject, bnodes),
+        triple.predicate.clone(),
+        convert_term(&triple.object, bnodes),
+    )
+}
+
+fn convert_blank_node(node: &BlankNode, bnodes: &mut FxHashMap<BlankNode, BlankNode>) -> BlankNode {
+    bnodes.entry(node.clone()).or_default().clone()
+}
+
+fn convert_graph_name(graph_name: &GraphNameTarget) -> GraphName {
+    match graph_name {
+        GraphNameTarget::NamedNode(graph_name) => graph_name.clone().into(),
+        GraphNameTarget::DefaultGraph => GraphName::DefaultGraph,
+    }
+}
+
+fn to_quad_pattern(pattern: &GroundQuadPattern) -> QuadPattern {
+    QuadPattern {
+        subject: pattern.subject.clone().into(),
+        predicate: pattern.predicate.clone(),
+        object: pattern.object.clone().into(),
+        graph_name: pattern.graph_name.clone(),
+    }
+}
+
+fn fill_quad_pattern(
+    pattern: &QuadPattern,
+    solution: &QuerySolution,
+    bnodes: &mut FxHashMap<BlankNode, BlankNode>,
+) -> Option<Quad> {
+    Some(Quad::new(
+        Subject::try_from(fill_term_pattern(&pattern.subject, solution, bnodes)?).ok()?,
+        fill_named_node_pattern(&pattern.predicate, solution)?,
+        fill_term_pattern(&pattern.object, solution, bnodes)?,
+        fill_graph_name_pattern(&pattern.graph_name, solution)?,
+    ))
+}
+
+#[cfg(feature = "rdf-star")]
+fn fill_triple_pattern(
+    pattern: &TriplePattern,
+    solution: &QuerySolution,
+    bnodes: &mut FxHashMap<BlankNode, BlankNode>,
+) -> Option<Triple> {
+    Some(Triple::new(
+        Subject::try_from(fill_term_pattern(&pattern.subject, solution, bnodes)?).ok()?,
+        fill_named_node_pattern(&pattern.predicate, solution)?,
+        fill_term_pattern(&pattern.object, solution, bnodes)?,
+    ))
+}
+
+fn fill_term_pattern(
+    pattern: &TermPattern,
+    solution: &QuerySolution,
+    bnodes: &mut FxHashMap<BlankNode, BlankNode>,
+) -> Option<Term> {
+    Some(match pattern {
+        TermPattern::NamedNode(term) => term.clone().into(),
+        TermPattern::BlankNode(bnode) => convert_blank_node(bnode, bnodes).into(),
+        TermPattern::Literal(term) => term.clone().into(),
+        #[cfg(feature = "rdf-star")]
+        TermPattern::Triple(triple) => fill_triple_pattern(triple, solution, bnodes)?.into(),
+        TermPattern::Variable(v) => solution.get(v)?.clone(),
+    })
+}
+
+fn fill_named_node_pattern(
+    pattern: &NamedNodePattern,
+    solution: &QuerySolution,
+) -> Option<NamedNode> {
+    match pattern {
+        NamedNodePattern::NamedNode(term) => Some(term.clone()),
+        NamedNodePattern::Variable(v) => NamedNode::try_from(solution.get(v)?.clone()).ok(),
+    }
+}
+
+#[allow(clippy::match_wildcard_for_single_variants, unreachable_patterns)]
+fn fill_graph_name_pattern(
+    pattern: &GraphNamePattern,
+    solution: &QuerySolution,
+) -> Option<GraphName> {
+    Some(match pattern {
+        GraphNamePattern::NamedNode(term) => term.clone().into(),
+        GraphNamePattern::DefaultGraph => GraphName::DefaultGraph,
+        GraphNamePattern::Variable(v) => match solution.get(v)? {
+            Term::NamedNode(node) => node.clone().into(),
+            Term::BlankNode(node) => node.clone().into(),
+            _ => return None,
+        },
+    })
+}
+
+fn wrap_dataset_error(error: impl Error + Send + Sync + 'static) -> QueryEvaluationError {
+    QueryEvaluationError::Dataset(Box::new(error))
+}
+
+fn wrap_load_error(error: impl Error + Send + Sync + 'static) -> QueryEvaluationError {
+    let error: Box<dyn Error + Send + Sync> = Box::new(error);
+    match error.downcast() {
+        Ok(error) => *error,
+        Err(error) => QueryEvaluationError::Load(error),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+    use oxrdf::{Dataset, GraphNameRef, NamedNodeRef, QuadRef};
+    use std::collections::HashSet;
+    use std::io;
+    use std::iter::once;
+
+    const EX: &str = "http://example.com/";
+
+    fn ex(name: &str) -> NamedNodeRef<'_> {
+        NamedNodeRef::new_unchecked(name)
+    }
+
+    fn parse(update: &str) -> Update {
+        Update::parse(update, Some(EX)).unwrap()
+    }
+
+    fn execute(dataset: &mut Dataset, update: &str) -> Result<(), QueryEvaluationError> {
+        UpdateEvaluator::new().execute(dataset, &parse(update))
+    }
+
+    fn example_dataset() -> Dataset {
+        [
+            QuadRef::new(
+                ex("http://example.com/s1"),
+                ex("http://example.com/p"),
+                ex("http://example.com/o1"),
+                GraphNameRef::DefaultGraph,
+            ),
+            QuadRef::new(
+                ex("http://example.com/s2"),
+                ex("http://example.com/p"),
+                ex("http://example.com/o2"),
+                GraphNameRef::DefaultGraph,
+            ),
+            QuadRef::new(
+                ex("http://example.com/s1"),
+                ex("http://example.com/p"),
+                ex("http://example.com/o1"),
+                ex("http://example.com/g1"),
+            ),
+            QuadRef::new(
+                ex("http://example.com/s2"),
+                ex("http://example.com/p"),
+                ex("http://example.com/o2"),
+                ex("http://example.com/g2"),
+            ),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    struct TestLoadHandler;
+
+    impl LoadHandler for TestLoadHandler {
+        type Error = io::Error;
+
+        fn load(
+            &self,
+            source: &NamedNode,
+        ) -> Result<Box<dyn Iterator<Item = Result<Triple, io::Error>>>, io::Error> {
+            if source.as_str() == "http://example.com/missing" {
+                return Err(io::Error::new(io::ErrorKind::NotFound, "not found"));
+            }
+            Ok(Box::new(once(Ok(Triple::new(
+                source.clone(),
+                source.clone(),
+                source.clone(),
+            )))))
+        }
+    }
+
+    #[test]
+    fn test_delete_insert_where() -> Result<(), QueryEvaluationError> {
+        let mut dataset = example_dataset();
+        execute(
+            &mut dataset,
+            "DELETE { ?s <p> ?o } INSERT { ?s <p2> ?o . _:b <p3> ?s } WHERE { ?s <p> ?o }",
+        )?;
+        assert_eq!(dataset.len(), 6);
+        assert!(dataset.contains(QuadRef::new(
+            ex("http://example.com/s1"),
+            ex("http://example.com/p2"),
+            ex("http://example.com/o1"),
+            GraphNameRef::DefaultGraph,
+        )));
+        assert!(!dataset.contains(QuadRef::new(
+            ex("http://example.com/s1"),
+            ex("http://example.com/p"),
+            ex("http://example.com/o1"),
+            GraphNameRef::DefaultGraph,
+        )));
+        // Each solution gets its own blank node
+        assert_eq!(
+            dataset
+                .quads_for_predicate(ex("http://example.com/p3"))
+                .map(|q| q.subject)
+                .collect::<HashSet<_>>()
+                .len(),
+            2
+        );
+        // The named graphs are untouched
+        assert_eq!(
+            dataset
+                .quads_for_graph_name(ex("http://example.com/g1"))
+                .count(),
+            1
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_delete_where_in_graph() -> Result<(), QueryEvaluationError> {
+        let mut dataset = example_dataset();
+        execute(&mut dataset, "DELETE WHERE { GRAPH ?g { ?s <p> <o1> } }")?;
+        assert_eq!(dataset.len(), 3);
+        assert_eq!(
+            dataset
+                .quads_for_graph_name(ex("http://example.com/g1"))
+                .count(),
+            0
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_using() -> Result<(), QueryEvaluationError> {
+        let mut dataset = example_dataset();
+        execute(
+            &mut dataset,
+            "INSERT { GRAPH <result> { ?s <from> ?g } } USING <g1> USING NAMED <g2> WHERE { { ?s ?p ?o BIND(<default> AS ?g) } UNION { GRAPH ?g { ?s ?p ?o } } }",
+        )?;
+        let mut results = dataset
+            .quads_for_graph_name(ex("http://example.com/result"))
+            .map(|q| (q.subject.to_string(), q.object.to_string()))
+            .collect::<Vec<_>>();
+        results.sort();
+        assert_eq!(
+            results,
+            [
+                (
+                    "<http://example.com/s1>".to_owned(),
+                    "<http://example.com/default>".to_owned()
+                ),
+                (
+                    "<http://example.com/s2>".to_owned(),
+                    "<http://example.com/g2>".to_owned()
+                )
+            ]
+        );
+        // The other graphs are kept
+        assert_eq!(dataset.len(), 6);
+        Ok(())
+    }
+
+    #[test]
+    fn test_failing_where_keeps_dataset() {
+        let mut dataset = example_dataset();
+        let update = parse("INSERT { ?s <p2> ?o } WHERE { ?s <p> ?o }");
+        let result = UpdateEvaluator::new()
+            .with_query_evaluator(QueryEvaluator::new().with_cancellation_token({
+                let token = crate::CancellationToken::new();
+                token.cancel();
+                token
+            }))
+            .execute(&mut dataset, &update);
+        assert!(
+            matches!(result, Err(QueryEvaluationError::Cancelled)),
+            "the evaluation must be cancelled"
+        );
+        assert_eq!(dataset, example_dataset());
+    }
+
+    #[test]
+    fn test_load() -> Result<(), QueryEvaluationError> {
+        let evaluator = UpdateEvaluator::new().with_load_handler(TestLoadHandler);
+        let mut dataset = Dataset::new();
+        evaluator.execute(
+            &mut dataset,
+            &parse("LOAD <http://example.com/data> INTO GRAPH <http://example.com/g>"),
+        )?;
+        assert!(dataset.contains(QuadRef::new(
+            ex("http://example.com/data"),
+            ex("http://example.com/data"),
+            ex("http://example.com/data"),
+            ex("http://example.com/g"),
+        )));
+
+        let missing = parse("LOAD <http://example.com/missing>");
+        assert!(
+            matches!(
+                evaluator.execute(&mut dataset, &missing),
+                Err(QueryEvaluationError::Load(_))
+            ),
+            "the load error must be returned"
+        );
+        evaluator.execute(
+            &mut dataset,
+            &parse("LOAD SILENT <http://example.com/missing>"),
+        )?;
+        assert!(
+            matches!(
+                UpdateEvaluator::new().execute(&mut dataset, &missing),
+                Err(QueryEvaluationError::UnsupportedLoad(_))
+            ),
+            "LOAD requires a handler"
+        );
+        UpdateEvaluator::new().execute(
+            &mut dataset,
+            &parse("LOAD SILENT <http://example.com/data>"),
+        )?;
+        assert_eq!(dataset.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_clear() -> Result<(), QueryEvaluationError> {
+        let mut dataset = example_dataset();
+        execute(&mut dataset, "CLEAR GRAPH <g1>")?;
+        assert_eq!(dataset.len(), 3);
+        assert!(
+            matches!(
+                execute(&mut dataset, "CLEAR GRAPH <g1>"),
+                Err(QueryEvaluationError::GraphDoesNotExist(_))
+            ),
+            "the graph is now empty hence does not exist"
+        );
+        execute(&mut dataset, "CLEAR SILENT GRAPH <g1>")?;
+        execute(&mut dataset, "CLEAR DEFAULT")?;
+        assert_eq!(dataset.len(), 1);
+        execute(&mut dataset, "CLEAR NAMED")?;
+        assert!(dataset.is_empty());
+
+        let mut dataset = example_dataset();
+        execute(&mut dataset, "CLEAR ALL")?;
+        assert!(dataset.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_create() -> Result<(), QueryEvaluationError> {
+        let mut dataset = example_dataset();
+        execute(&mut dataset, "CREATE GRAPH <g3>")?;
+        assert!(
+            matches!(
+                execute(&mut dataset, "CREATE GRAPH <g1>"),
+                Err(QueryEvaluationError::GraphAlreadyExists(_))
+            ),
+            "the graph already exists"
+        );
+        execute(&mut dataset, "CREATE SILENT GRAPH <g1>")?;
+        assert_eq!(dataset, example_dataset());
+        Ok(())
+    }
+
+    #[test]
+    fn test_drop() -> Result<(), QueryEvaluationError> {
+        let mut dataset = example_dataset();
+        execute(&mut dataset, "DROP GRAPH <g1>")?;
+        assert_eq!(dataset.len(), 3);
+        assert!(
+            matches!(
+                execute(&mut dataset, "DROP GRAPH <g1>"),
+                Err(QueryEvaluationError::GraphDoesNotExist(_))
+            ),
+            "the graph has already been dropped"
+        );
+        execute(&mut dataset, "DROP SILENT GRAPH <g1>")?;
+        execute(&mut dataset, "DROP DEFAULT")?;
+        assert_eq!(dataset.len(), 1);
+        execute(&mut dataset, "DROP NAMED")?;
+        assert!(dataset.is_empty());
+
+        let mut dataset = example_dataset();
+        execute(&mut dataset, "DROP ALL")?;
+        assert!(dataset.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_operations_are_applied_in_order() {
+        let mut dataset = Dataset::new();
+        let result = execute(
+            &mut dataset,
+            "INSERT DATA { <s> <p> <o> } ; DROP GRAPH <g> ; INSERT DATA { <s> <p> <o2> }",
+        );
+        assert!(
+            matches!(result, Err(QueryEvaluationError::GraphDoesNotExist(_))),
+            "the graph does not exist"
+        );
+        // The operations before the failing one are kept
+        assert_eq!(dataset.len(), 1);
+    }
+}
@@ -723,6 +723,14 @@ impl Default for GraphPattern {
     }
 }
 
+impl From<TriplePattern> for GraphPattern {
+    fn from(pattern: TriplePattern) -> Self {
+        Self::Bgp {
+            patterns: vec![pattern],
+        }
+    }
+}
+
 impl GraphPattern {
     /// Formats using the [SPARQL S-Expression syntax](https://jena.apache.org/documentation/notes/sse.html).
     pub(crate) fn fmt_sse(&self, f: &mut impl fmt::Write) -> fmt::Result {
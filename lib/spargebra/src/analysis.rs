@@ -0,0 +1,883 @@
+use crate::algebra::{AggregateExpression, Expression, Function, GraphPattern, OrderExpression};
+use crate::query::Query;
+use crate::term::{BlankNode, Literal, NamedNodePattern, TermPattern, TriplePattern, Variable};
+use oxrdf::vocab::{rdf, xsd};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// A likely mistake in a [`Query`](crate::Query) found by [`Query::warnings`](crate::Query::warnings), [`Query::lint`](crate::Query::lint) or [`SparqlParser::parse_and_lint`](crate::SparqlParser::parse_and_lint).
+///
+/// The query is still valid and is evaluated following the SPARQL semantics but it might not return the results expected by its author.
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
+#[non_exhaustive]
+pub enum QueryWarning {
+    /// An `OPTIONAL` is not [well designed](https://doi.org/10.1145/1567274.1567278):
+    /// the variable is used inside the `OPTIONAL` and outside of it but not in the pattern the `OPTIONAL` is applied to.
+    ///
+    /// The `OPTIONAL` might then bind the variable to a value that is not compatible with the rest of the query, removing the solution.
+    NonWellDesignedOptional { variable: Variable },
+    /// Two patterns without any shared variable are joined.
+    ///
+    /// The join is a cartesian product of the solutions of the two patterns that might be very large.
+    CartesianProduct {
+        left: Vec<Variable>,
+        right: Vec<Variable>,
+    },
+    /// A variable is selected but is never bound by the query pattern.
+    UnboundProjectedVariable { variable: Variable },
+    /// An `EXISTS` uses a variable bound outside of it inside a `FILTER`, a `BIND`, an `OPTIONAL`, a `MINUS`, a sub-query or an aggregation.
+    ///
+    /// The `EXISTS` result then depends on the way the variable value is given to the `EXISTS` pattern:
+    /// by substitution as in the SPARQL 1.1 specification or by filtering the pattern solutions as done by some other engines.
+    /// See the findings of the [SPARQL EXISTS community group](https://www.w3.org/community/sparql-exists/).
+    ExistsStrategyDependent { variable: Variable },
+    /// A `FILTER` uses a variable that is not bound by the pattern it applies to.
+    ///
+    /// The variable is always unbound when the filter is evaluated, the `FILTER` might have been written in the wrong group.
+    UnboundFilterVariable { variable: Variable },
+    /// A comparison is done between values of incompatible types like a number and a string.
+    ///
+    /// It is always false or an error, removing all the solutions of the filtered pattern.
+    IncompatibleComparison { expression: Expression },
+    /// A variable is used only once in the query, it is likely a typo.
+    ///
+    /// It is only returned by [`Query::lint`](crate::Query::lint) because it is also often used on purpose in patterns like `?s ?p ?o`.
+    UnusedVariable { variable: Variable },
+}
+
+impl fmt::Display for QueryWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NonWellDesignedOptional { variable } => write!(
+                f,
+                "The OPTIONAL using {variable} is not well designed: {variable} is also used outside of the OPTIONAL but not in the pattern the OPTIONAL applies to"
+            ),
+            Self::CartesianProduct { left, right } => {
+                f.write_str("Cartesian product between the patterns using")?;
+                for variable in left {
+                    write!(f, " {variable}")?;
+                }
+                f.write_str(" and the patterns using")?;
+                for variable in right {
+                    write!(f, " {variable}")?;
+                }
+                Ok(())
+            }
+            Self::UnboundProjectedVariable { variable } => {
+                write!(f, "The selected variable {variable} is never bound")
+            }
+            Self::ExistsStrategyDependent { variable } => write!(
+                f,
+                "The result of the EXISTS using {variable} depends on the EXISTS evaluation strategy: {variable} is bound outside of the EXISTS and used inside of it in a FILTER, a BIND, an OPTIONAL, a MINUS, a sub-query or an aggregation"
+            ),
+            Self::UnboundFilterVariable { variable } => write!(
+                f,
+                "The FILTER using {variable} is applied to a pattern that does not bind {variable}"
+            ),
+            Self::IncompatibleComparison { expression } => write!(
+                f,
+                "The comparison {expression} is between values of incompatible types, it is always false or an error"
+            ),
+            Self::UnusedVariable { variable } => {
+                write!(f, "The variable {variable} is only used once")
+            }
+        }
+    }
+}
+
+pub(crate) fn query_warnings(query: &Query) -> Vec<QueryWarning> {
+    let mut warnings = Vec::new();
+    match query {
+        Query::Construct {
+            template, pattern, ..
+        } => {
+            // The template variables are used outside of the pattern
+            let mut template_nodes = HashSet::new();
+            for triple in template {
+                add_triple_pattern_nodes(triple, &mut template_nodes);
+            }
+            let outside = template_nodes
+                .into_iter()
+                .filter_map(|node| match node {
+                    BgpNode::Variable(v) => Some(v),
+                    BgpNode::BlankNode(_) => None,
+                })
+                .collect();
+            analyze(pattern, &outside, &mut warnings);
+        }
+        Query::Select { pattern, .. }
+        | Query::Describe { pattern, .. }
+        | Query::Ask { pattern, .. } => analyze(pattern, &HashSet::new(), &mut warnings),
+    }
+    warnings
+}
+
+pub(crate) fn query_lints(query: &Query) -> Vec<QueryWarning> {
+    let mut warnings = query_warnings(query);
+    let mut uses = HashMap::new();
+    match query {
+        Query::Construct {
+            template, pattern, ..
+        } => {
+            for triple in template {
+                count_triple_pattern_uses(triple, &mut uses);
+            }
+            count_pattern_uses(pattern, &mut uses);
+        }
+        Query::Select { pattern, .. }
+        | Query::Describe { pattern, .. }
+        | Query::Ask { pattern, .. } => count_pattern_uses(pattern, &mut uses),
+    }
+    let mut unused = uses
+        .into_iter()
+        .filter_map(|(variable, count)| (count == 1).then_some(variable))
+        .collect::<Vec<_>>();
+    unused.sort();
+    warnings.extend(
+        unused
+            .into_iter()
+            .map(|variable| QueryWarning::UnusedVariable {
+                variable: variable.clone(),
+            }),
+    );
+    warnings
+}
+
+/// Looks for warnings in `pattern`, `outside` being the variables used in the rest of the query
+fn analyze<'a>(
+    pattern: &'a GraphPattern,
+    outside: &HashSet<&'a Variable>,
+    warnings: &mut Vec<QueryWarning>,
+) {
+    match pattern {
+        GraphPattern::Bgp { patterns } => bgp_cartesian_products(patterns, warnings),
+        GraphPattern::Path { .. } | GraphPattern::Values { .. } | GraphPattern::Service { .. } => {}
+        GraphPattern::Join { left, right } => {
+            let left_variables = in_scope_variables(left);
+            let right_variables = in_scope_variables(right);
+            if !left_variables.is_empty()
+                && !right_variables.is_empty()
+                && left_variables.is_disjoint(&right_variables)
+            {
+                warnings.push(QueryWarning::CartesianProduct {
+                    left: sorted(&left_variables),
+                    right: sorted(&right_variables),
+                });
+            }
+            analyze(left, &union(outside, &right_variables), warnings);
+            analyze(right, &union(outside, &left_variables), warnings);
+        }
+        GraphPattern::LeftJoin {
+            left,
+            right,
+            expression,
+        } => {
+            let left_variables = in_scope_variables(left);
+            let right_variables = in_scope_variables(right);
+            if let Some(expression) = expression {
+                let variables = union(&left_variables, &right_variables);
+                exists_strategy_dependencies(expression, &variables, warnings);
+                filter_warnings(expression, &variables, warnings);
+            }
+            let mut variables = right_variables
+                .iter()
+                .filter(|v| outside.contains(*v) && !left_variables.contains(*v))
+                .collect::<Vec<_>>();
+            variables.sort();
+            for variable in variables {
+                warnings.push(QueryWarning::NonWellDesignedOptional {
+                    variable: (*variable).clone(),
+                });
+            }
+            analyze(left, &union(outside, &right_variables), warnings);
+            analyze(right, &union(outside, &left_variables), warnings);
+        }
+        #[cfg(feature = "sep-0006")]
+        GraphPattern::Lateral { left, right } => {
+            let left_variables = in_scope_variables(left);
+            analyze(left, &union(outside, &in_scope_variables(right)), warnings);
+            analyze(right, &union(outside, &left_variables), warnings);
+        }
+        GraphPattern::Union { left, right } => {
+            analyze(left, outside, warnings);
+            analyze(right, outside, warnings);
+        }
+        GraphPattern::Minus { left, right } => {
+            analyze(left, outside, warnings);
+            // The right side is only compared with the left side
+            analyze(right, &in_scope_variables(left), warnings);
+        }
+        GraphPattern::Graph { name, inner } => {
+            let mut outside = outside.clone();
+            if let NamedNodePattern::Variable(name) = name {
+                outside.insert(name);
+            }
+            analyze(inner, &outside, warnings);
+        }
+        GraphPattern::Filter { inner, expr } => {
+            let inner_variables = in_scope_variables(inner);
+            exists_strategy_dependencies(expr, &inner_variables, warnings);
+            filter_warnings(expr, &inner_variables, warnings);
+            analyze(inner, outside, warnings)
+        }
+        GraphPattern::Extend {
+            inner, expression, ..
+        } => {
+            exists_strategy_dependencies(expression, &in_scope_variables(inner), warnings);
+            analyze(inner, outside, warnings)
+        }
+        GraphPattern::OrderBy { inner, expression } => {
+            let inner_variables = in_scope_variables(inner);
+            for expression in expression {
+                let (OrderExpression::Asc(expression) | OrderExpression::Desc(expression)) =
+                    expression;
+                exists_strategy_dependencies(expression, &inner_variables, warnings);
+            }
+            analyze(inner, outside, warnings)
+        }
+        GraphPattern::Distinct { inner }
+        | GraphPattern::Reduced { inner }
+        | GraphPattern::Slice { inner, .. } => analyze(inner, outside, warnings),
+        GraphPattern::Group {
+            inner, variables, ..
+        } => {
+            // Only the grouping variables are visible outside of the group
+            analyze(
+                inner,
+                &variables.iter().filter(|v| outside.contains(v)).collect(),
+                warnings,
+            );
+        }
+        GraphPattern::Project { inner, variables } => {
+            let inner_variables = in_scope_variables(inner);
+            for variable in variables {
+                if !inner_variables.contains(variable) {
+                    warnings.push(QueryWarning::UnboundProjectedVariable {
+                        variable: variable.clone(),
+                    });
+                }
+            }
+            analyze(
+                inner,
+                &variables.iter().filter(|v| outside.contains(v)).collect(),
+                warnings,
+            );
+        }
+    }
+}
+
+/// Emits a warning for each variable of `outer` that makes the result of an `EXISTS` of `expression` depend on the evaluation strategy
+fn exists_strategy_dependencies(
+    expression: &Expression,
+    outer: &HashSet<&Variable>,
+    warnings: &mut Vec<QueryWarning>,
+) {
+    let mut exists = Vec::new();
+    lookup_expression(expression, &mut HashSet::new(), &mut exists);
+    for pattern in exists {
+        let mut dependent = HashSet::new();
+        exists_dependent_variables(pattern, outer, &mut dependent);
+        for variable in sorted(&dependent) {
+            warnings.push(QueryWarning::ExistsStrategyDependent { variable });
+        }
+    }
+}
+
+/// Adds to `dependent` the variables of `outer` whose value is used by `pattern` in a different way
+/// whether it is substituted into `pattern` or only compared to the `pattern` solutions
+fn exists_dependent_variables<'a>(
+    pattern: &'a GraphPattern,
+    outer: &HashSet<&'a Variable>,
+    dependent: &mut HashSet<&'a Variable>,
+) {
+    let mut add = |variables: &HashSet<&Variable>, excluded: &HashSet<&Variable>| {
+        dependent.extend(
+            outer
+                .iter()
+                .filter(|v| variables.contains(*v) && !excluded.contains(*v))
+                .copied(),
+        )
+    };
+    match pattern {
+        GraphPattern::Bgp { .. }
+        | GraphPattern::Path { .. }
+        | GraphPattern::Values { .. }
+        | GraphPattern::Service { .. } => (),
+        GraphPattern::Join { left, right } | GraphPattern::Union { left, right } => {
+            exists_dependent_variables(left, outer, dependent);
+            exists_dependent_variables(right, outer, dependent);
+        }
+        #[cfg(feature = "sep-0006")]
+        GraphPattern::Lateral { left, right } => {
+            exists_dependent_variables(left, outer, dependent);
+            exists_dependent_variables(right, outer, dependent);
+        }
+        GraphPattern::LeftJoin {
+            left,
+            right,
+            expression,
+        } => {
+            let left_variables = in_scope_variables(left);
+            let right_variables = in_scope_variables(right);
+            if let Some(expression) = expression {
+                add(
+                    &expression_variables(expression),
+                    &union(&left_variables, &right_variables),
+                );
+            }
+            add(&right_variables, &left_variables);
+            exists_dependent_variables(left, outer, dependent);
+            exists_dependent_variables(right, outer, dependent);
+        }
+        GraphPattern::Minus { left, right } => {
+            add(&in_scope_variables(right), &in_scope_variables(left));
+            exists_dependent_variables(left, outer, dependent);
+        }
+        GraphPattern::Filter { expr, inner } => {
+            add(&expression_variables(expr), &in_scope_variables(inner));
+            exists_dependent_variables(inner, outer, dependent);
+        }
+        GraphPattern::Extend {
+            inner,
+            variable,
+            expression,
+        } => {
+            let mut variables = expression_variables(expression);
+            variables.insert(variable);
+            add(&variables, &in_scope_variables(inner));
+            exists_dependent_variables(inner, outer, dependent);
+        }
+        GraphPattern::Slice { inner, .. } => {
+            // The kept solutions depend on the substituted values
+            add(&in_scope_variables(inner), &HashSet::new());
+            exists_dependent_variables(inner, outer, dependent);
+        }
+        GraphPattern::Group {
+            inner,
+            variables,
+            aggregates,
+        } => {
+            let mut hidden = in_scope_variables(inner);
+            hidden.extend(aggregates.iter().map(|(v, _)| v));
+            add(&hidden, &variables.iter().collect());
+            exists_dependent_variables(
+                inner,
+                &variables.iter().filter(|v| outer.contains(v)).collect(),
+                dependent,
+            );
+        }
+        GraphPattern::Project { inner, variables } => {
+            add(&in_scope_variables(inner), &variables.iter().collect());
+            exists_dependent_variables(
+                inner,
+                &variables.iter().filter(|v| outer.contains(v)).collect(),
+                dependent,
+            );
+        }
+        GraphPattern::Graph { inner, .. }
+        | GraphPattern::OrderBy { inner, .. }
+        | GraphPattern::Distinct { inner }
+        | GraphPattern::Reduced { inner } => exists_dependent_variables(inner, outer, dependent),
+    }
+}
+
+/// Emits the warnings about the variables used by the filter `expression` that are not in `bound` and about its comparisons that are always false
+fn filter_warnings(
+    expression: &Expression,
+    bound: &HashSet<&Variable>,
+    warnings: &mut Vec<QueryWarning>,
+) {
+    let mut variables = HashSet::new();
+    let mut exists = Vec::new();
+    lookup_expression(expression, &mut variables, &mut exists);
+    // The EXISTS patterns bind their own variables
+    for pattern in exists {
+        for variable in in_scope_variables(pattern) {
+            variables.remove(variable);
+        }
+    }
+    for variable in sorted(
+        &variables
+            .into_iter()
+            .filter(|v| !bound.contains(v))
+            .collect(),
+    ) {
+        warnings.push(QueryWarning::UnboundFilterVariable { variable });
+    }
+    incompatible_comparisons(expression, warnings);
+}
+
+fn incompatible_comparisons(expression: &Expression, warnings: &mut Vec<QueryWarning>) {
+    if let Expression::Equal(a, b)
+    | Expression::Greater(a, b)
+    | Expression::GreaterOrEqual(a, b)
+    | Expression::Less(a, b)
+    | Expression::LessOrEqual(a, b) = expression
+    {
+        if let (Some(a_kind), Some(b_kind)) = (value_kind(a), value_kind(b)) {
+            if a_kind != b_kind {
+                warnings.push(QueryWarning::IncompatibleComparison {
+                    expression: expression.clone(),
+                });
+            }
+        }
+    }
+    // The EXISTS patterns are not analyzed
+    for child in child_expressions(expression) {
+        incompatible_comparisons(child, warnings);
+    }
+}
+
+/// The kinds of values that are never equal or comparable to each other
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum ValueKind {
+    Iri,
+    Boolean,
+    Numeric,
+    String,
+    DateTime,
+}
+
+/// The kind of values the expression always returns, if known
+fn value_kind(expression: &Expression) -> Option<ValueKind> {
+    match expression {
+        Expression::NamedNode(_) => Some(ValueKind::Iri),
+        Expression::Literal(literal) => literal_kind(literal),
+        Expression::Or(..)
+        | Expression::And(..)
+        | Expression::Equal(..)
+        | Expression::SameTerm(..)
+        | Expression::Greater(..)
+        | Expression::GreaterOrEqual(..)
+        | Expression::Less(..)
+        | Expression::LessOrEqual(..)
+        | Expression::In(..)
+        | Expression::Not(_)
+        | Expression::Exists(_)
+        | Expression::Bound(_) => Some(ValueKind::Boolean),
+        Expression::FunctionCall(function, _) => match function {
+            Function::Str
+            | Function::Lang
+            | Function::Concat
+            | Function::UCase
+            | Function::LCase
+            | Function::EncodeForUri
+            | Function::StrUuid
+            | Function::Md5
+            | Function::Sha1
+            | Function::Sha256
+            | Function::Sha384
+            | Function::Sha512 => Some(ValueKind::String),
+            Function::StrLen
+            | Function::Year
+            | Function::Month
+            | Function::Day
+            | Function::Hours
+            | Function::Minutes
+            | Function::Seconds
+            | Function::Rand => Some(ValueKind::Numeric),
+            Function::LangMatches
+            | Function::Contains
+            | Function::StrStarts
+            | Function::StrEnds
+            | Function::IsIri
+            | Function::IsBlank
+            | Function::IsLiteral
+            | Function::IsNumeric
+            | Function::Regex => Some(ValueKind::Boolean),
+            Function::Datatype | Function::Iri | Function::Uuid => Some(ValueKind::Iri),
+            Function::Now => Some(ValueKind::DateTime),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn literal_kind(literal: &Literal) -> Option<ValueKind> {
+    let datatype = literal.datatype();
+    if datatype == xsd::STRING || datatype == rdf::LANG_STRING {
+        Some(ValueKind::String)
+    } else if datatype == xsd::BOOLEAN {
+        Some(ValueKind::Boolean)
+    } else if datatype == xsd::DATE_TIME {
+        Some(ValueKind::DateTime)
+    } else if [
+        xsd::INTEGER,
+        xsd::DECIMAL,
+        xsd::FLOAT,
+        xsd::DOUBLE,
+        xsd::INT,
+        xsd::LONG,
+        xsd::SHORT,
+        xsd::BYTE,
+        xsd::NON_NEGATIVE_INTEGER,
+        xsd::POSITIVE_INTEGER,
+        xsd::NON_POSITIVE_INTEGER,
+        xsd::NEGATIVE_INTEGER,
+        xsd::UNSIGNED_LONG,
+        xsd::UNSIGNED_INT,
+        xsd::UNSIGNED_SHORT,
+        xsd::UNSIGNED_BYTE,
+    ]
+    .contains(&datatype)
+    {
+        Some(ValueKind::Numeric)
+    } else {
+        None
+    }
+}
+
+/// The direct sub-expressions of `expression`, the `EXISTS` patterns are not included
+fn child_expressions(expression: &Expression) -> Vec<&Expression> {
+    match expression {
+        Expression::NamedNode(_)
+        | Expression::Literal(_)
+        | Expression::Variable(_)
+        | Expression::Bound(_)
+        | Expression::Exists(_) => Vec::new(),
+        Expression::Or(a, b)
+        | Expression::And(a, b)
+        | Expression::Equal(a, b)
+        | Expression::SameTerm(a, b)
+        | Expression::Greater(a, b)
+        | Expression::GreaterOrEqual(a, b)
+        | Expression::Less(a, b)
+        | Expression::LessOrEqual(a, b)
+        | Expression::Add(a, b)
+        | Expression::Subtract(a, b)
+        | Expression::Multiply(a, b)
+        | Expression::Divide(a, b) => vec![a, b],
+        Expression::UnaryPlus(e) | Expression::UnaryMinus(e) | Expression::Not(e) => vec![e],
+        Expression::In(a, l) => {
+            let mut children = vec![a.as_ref()];
+            children.extend(l);
+            children
+        }
+        Expression::If(a, b, c) => vec![a, b, c],
+        Expression::Coalesce(l) | Expression::FunctionCall(_, l) => l.iter().collect(),
+    }
+}
+
+/// Counts the number of times each variable is used in `pattern`
+fn count_pattern_uses<'a>(pattern: &'a GraphPattern, uses: &mut HashMap<&'a Variable, usize>) {
+    match pattern {
+        GraphPattern::Bgp { patterns } => {
+            for triple in patterns {
+                count_triple_pattern_uses(triple, uses);
+            }
+        }
+        GraphPattern::Path {
+            subject, object, ..
+        } => {
+            count_term_pattern_uses(subject, uses);
+            count_term_pattern_uses(object, uses);
+        }
+        GraphPattern::Join { left, right }
+        | GraphPattern::Union { left, right }
+        | GraphPattern::Minus { left, right } => {
+            count_pattern_uses(left, uses);
+            count_pattern_uses(right, uses);
+        }
+        #[cfg(feature = "sep-0006")]
+        GraphPattern::Lateral { left, right } => {
+            count_pattern_uses(left, uses);
+            count_pattern_uses(right, uses);
+        }
+        GraphPattern::LeftJoin {
+            left,
+            right,
+            expression,
+        } => {
+            count_pattern_uses(left, uses);
+            count_pattern_uses(right, uses);
+            if let Some(expression) = expression {
+                count_expression_uses(expression, uses);
+            }
+        }
+        GraphPattern::Filter { expr, inner } => {
+            count_expression_uses(expr, uses);
+            count_pattern_uses(inner, uses);
+        }
+        GraphPattern::Graph { name, inner } | GraphPattern::Service { name, inner, .. } => {
+            if let NamedNodePattern::Variable(name) = name {
+                *uses.entry(name).or_default() += 1;
+            }
+            count_pattern_uses(inner, uses);
+        }
+        GraphPattern::Extend {
+            inner,
+            variable,
+            expression,
+        } => {
+            *uses.entry(variable).or_default() += 1;
+            count_expression_uses(expression, uses);
+            count_pattern_uses(inner, uses);
+        }
+        GraphPattern::Values { variables, .. } => {
+            for variable in variables {
+                *uses.entry(variable).or_default() += 1;
+            }
+        }
+        GraphPattern::OrderBy { inner, expression } => {
+            for expression in expression {
+                let (OrderExpression::Asc(expression) | OrderExpression::Desc(expression)) =
+                    expression;
+                count_expression_uses(expression, uses);
+            }
+            count_pattern_uses(inner, uses);
+        }
+        GraphPattern::Project { inner, variables } => {
+            for variable in variables {
+                *uses.entry(variable).or_default() += 1;
+            }
+            count_pattern_uses(inner, uses);
+        }
+        GraphPattern::Distinct { inner }
+        | GraphPattern::Reduced { inner }
+        | GraphPattern::Slice { inner, .. } => count_pattern_uses(inner, uses),
+        GraphPattern::Group {
+            inner,
+            variables,
+            aggregates,
+        } => {
+            for variable in variables {
+                *uses.entry(variable).or_default() += 1;
+            }
+            for (variable, aggregate) in aggregates {
+                *uses.entry(variable).or_default() += 1;
+                if let AggregateExpression::FunctionCall { expr, .. } = aggregate {
+                    count_expression_uses(expr, uses);
+                }
+            }
+            count_pattern_uses(inner, uses);
+        }
+    }
+}
+
+fn count_expression_uses<'a>(expression: &'a Expression, uses: &mut HashMap<&'a Variable, usize>) {
+    match expression {
+        Expression::Variable(variable) | Expression::Bound(variable) => {
+            *uses.entry(variable).or_default() += 1;
+        }
+        Expression::Exists(pattern) => count_pattern_uses(pattern, uses),
+        _ => {
+            for child in child_expressions(expression) {
+                count_expression_uses(child, uses);
+            }
+        }
+    }
+}
+
+fn count_triple_pattern_uses<'a>(
+    pattern: &'a TriplePattern,
+    uses: &mut HashMap<&'a Variable, usize>,
+) {
+    count_term_pattern_uses(&pattern.subject, uses);
+    if let NamedNodePattern::Variable(variable) = &pattern.predicate {
+        *uses.entry(variable).or_default() += 1;
+    }
+    count_term_pattern_uses(&pattern.object, uses);
+}
+
+fn count_term_pattern_uses<'a>(pattern: &'a TermPattern, uses: &mut HashMap<&'a Variable, usize>) {
+    match pattern {
+        TermPattern::Variable(variable) => {
+            *uses.entry(variable).or_default() += 1;
+        }
+        #[cfg(feature = "rdf-star")]
+        TermPattern::Triple(triple) => count_triple_pattern_uses(triple, uses),
+        TermPattern::NamedNode(_) | TermPattern::Literal(_) | TermPattern::BlankNode(_) => (),
+    }
+}
+
+fn expression_variables(expression: &Expression) -> HashSet<&Variable> {
+    let mut variables = HashSet::new();
+    lookup_expression(expression, &mut variables, &mut Vec::new());
+    variables
+}
+
+/// Adds to `variables` the variables used in `expression` and to `exists` its `EXISTS` patterns
+fn lookup_expression<'a>(
+    expression: &'a Expression,
+    variables: &mut HashSet<&'a Variable>,
+    exists: &mut Vec<&'a GraphPattern>,
+) {
+    match expression {
+        Expression::NamedNode(_) | Expression::Literal(_) => (),
+        Expression::Variable(v) | Expression::Bound(v) => {
+            variables.insert(v);
+        }
+        Expression::Or(a, b)
+        | Expression::And(a, b)
+        | Expression::Equal(a, b)
+        | Expression::SameTerm(a, b)
+        | Expression::Greater(a, b)
+        | Expression::GreaterOrEqual(a, b)
+        | Expression::Less(a, b)
+        | Expression::LessOrEqual(a, b)
+        | Expression::Add(a, b)
+        | Expression::Subtract(a, b)
+        | Expression::Multiply(a, b)
+        | Expression::Divide(a, b) => {
+            lookup_expression(a, variables, exists);
+            lookup_expression(b, variables, exists);
+        }
+        Expression::UnaryPlus(e) | Expression::UnaryMinus(e) | Expression::Not(e) => {
+            lookup_expression(e, variables, exists)
+        }
+        Expression::In(a, l) => {
+            lookup_expression(a, variables, exists);
+            for e in l {
+                lookup_expression(e, variables, exists);
+            }
+        }
+        Expression::If(a, b, c) => {
+            lookup_expression(a, variables, exists);
+            lookup_expression(b, variables, exists);
+            lookup_expression(c, variables, exists);
+        }
+        Expression::Coalesce(l) | Expression::FunctionCall(_, l) => {
+            for e in l {
+                lookup_expression(e, variables, exists);
+            }
+        }
+        Expression::Exists(p) => {
+            variables.extend(in_scope_variables(p));
+            exists.push(p);
+        }
+    }
+}
+
+/// Emits a warning for each group of triple patterns that is not connected to the previous ones by a variable or a blank node
+fn bgp_cartesian_products(patterns: &[TriplePattern], warnings: &mut Vec<QueryWarning>) {
+    let mut components = Vec::<HashSet<BgpNode<'_>>>::new();
+    for pattern in patterns {
+        let mut nodes = HashSet::new();
+        add_triple_pattern_nodes(pattern, &mut nodes);
+        if nodes.is_empty() {
+            continue; // No variable, the pattern is only a filter
+        }
+        let (connected, mut others) = components
+            .into_iter()
+            .partition::<Vec<_>, _>(|component| !component.is_disjoint(&nodes));
+        for component in connected {
+            nodes.extend(component);
+        }
+        others.push(nodes);
+        components = others;
+    }
+    let mut left = Vec::new();
+    for (i, component) in components.iter().enumerate() {
+        let right = sorted(
+            &component
+                .iter()
+                .filter_map(|node| match node {
+                    BgpNode::Variable(v) => Some(*v),
+                    BgpNode::BlankNode(_) => None,
+                })
+                .collect(),
+        );
+        if i > 0 {
+            warnings.push(QueryWarning::CartesianProduct {
+                left: left.clone(),
+                right: right.clone(),
+            });
+        }
+        left.extend(right);
+        left.sort();
+    }
+}
+
+#[derive(Eq, PartialEq, Hash)]
+enum BgpNode<'a> {
+    Variable(&'a Variable),
+    BlankNode(&'a BlankNode),
+}
+
+fn add_triple_pattern_nodes<'a>(pattern: &'a TriplePattern, nodes: &mut HashSet<BgpNode<'a>>) {
+    add_term_pattern_nodes(&pattern.subject, nodes);
+    if let NamedNodePattern::Variable(v) = &pattern.predicate {
+        nodes.insert(BgpNode::Variable(v));
+    }
+    add_term_pattern_nodes(&pattern.object, nodes);
+}
+
+fn add_term_pattern_nodes<'a>(pattern: &'a TermPattern, nodes: &mut HashSet<BgpNode<'a>>) {
+    match pattern {
+        TermPattern::Variable(v) => {
+            nodes.insert(BgpNode::Variable(v));
+        }
+        TermPattern::BlankNode(b) => {
+            nodes.insert(BgpNode::BlankNode(b));
+        }
+        #[cfg(feature = "rdf-star")]
+        TermPattern::Triple(t) => add_triple_pattern_nodes(t, nodes),
+        TermPattern::NamedNode(_) | TermPattern::Literal(_) => (),
+    }
+}
+
+fn in_scope_variables(pattern: &GraphPattern) -> HashSet<&Variable> {
+    let mut variables = HashSet::new();
+    pattern.on_in_scope_variable(|v| {
+        variables.insert(v);
+    });
+    variables
+}
+
+fn union<'a>(a: &HashSet<&'a Variable>, b: &HashSet<&'a Variable>) -> HashSet<&'a Variable> {
+    a.union(b).copied().collect()
+}
+
+fn sorted(variables: &HashSet<&Variable>) -> Vec<Variable> {
+    let mut variables = variables.iter().map(|v| (*v).clone()).collect::<Vec<_>>();
+    variables.sort();
+    variables
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exists_strategy_dependent_variables(query: &str) -> Vec<String> {
+        query_warnings(&Query::parse(query, None).unwrap())
+            .into_iter()
+            .filter_map(|warning| match warning {
+                QueryWarning::ExistsStrategyDependent { variable } => Some(variable.into_string()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn exists_strategy_dependent() {
+        for query in [
+            "ASK { ?s ?p ?o FILTER EXISTS { ?s ?p ?o2 FILTER(?o = ?o2) } }",
+            "ASK { ?s ?p ?o FILTER EXISTS { ?s ?p ?o2 BIND(?o AS ?o3) } }",
+            "ASK { ?s ?p ?o FILTER EXISTS { ?s ?p ?o2 OPTIONAL { ?o2 ?p ?o } } }",
+            "ASK { ?s ?p ?o FILTER EXISTS { ?s ?p ?o2 MINUS { ?o2 ?p ?o } } }",
+            "ASK { ?s ?p ?o FILTER EXISTS { SELECT ?s ?p WHERE { ?s ?p ?o } } }",
+            "ASK { ?s ?p ?o FILTER NOT EXISTS { ?s ?p ?o2 FILTER(?o = ?o2) } }",
+            "SELECT * WHERE { ?s ?p ?o BIND(EXISTS { ?s ?p ?o2 FILTER(?o = ?o2) } AS ?e) }",
+        ] {
+            assert_eq!(exists_strategy_dependent_variables(query), ["o"], "{query}");
+        }
+    }
+
+    #[test]
+    fn exists_strategy_independent() {
+        for query in [
+            "ASK { ?s ?p ?o FILTER EXISTS { ?s ?p ?o } }",
+            "ASK { ?s ?p ?o FILTER EXISTS { ?s ?p ?o2 FILTER(?o2 = 1) } }",
+            "ASK { ?s ?p ?o FILTER EXISTS { ?s ?p ?o2 OPTIONAL { ?o2 ?p ?o3 } } }",
+            "ASK { ?s ?p ?o FILTER EXISTS { ?s ?p ?o MINUS { ?o ?p ?s } } }",
+            "ASK { ?s ?p ?o FILTER EXISTS { SELECT ?s ?p ?o WHERE { ?s ?p ?o } } }",
+            "ASK { ?s ?p ?o FILTER EXISTS { ?s2 ?p2 ?o2 FILTER(?o2 = 1) } }",
+        ] {
+            assert_eq!(
+                exists_strategy_dependent_variables(query),
+                Vec::<String>::new(),
+                "{query}"
+            );
+        }
+    }
+}
@@ -0,0 +1,832 @@
+//! A fluent API to build [`Query`] objects without concatenating strings.
+//!
+//! The IRIs and variable names are validated when they are built and the literals are escaped when the query is serialized,
+//! so user-provided values can't change the structure of the query.
+//!
+//! ```
+//! use spargebra::algebra::{Expression, OrderExpression};
+//! use spargebra::builder::{iri, literal, triple, var, Select};
+//! use spargebra::Query;
+//!
+//! let name = "Alice\" } DROP ALL { \"";
+//! let query = Select::new()
+//!     .var(var("s")?)
+//!     .where_(triple(var("s")?, iri("http://schema.org/name")?, literal(name)))
+//!     .optional(triple(var("s")?, iri("http://schema.org/age")?, var("age")?))
+//!     .order_by(OrderExpression::Desc(Expression::from(var("age")?)))
+//!     .limit(10)
+//!     .build();
+//! assert_eq!(
+//!     query.to_string(),
+//!     r#"SELECT ?s WHERE { ?s <http://schema.org/name> "Alice\" } DROP ALL { \"" . OPTIONAL { ?s <http://schema.org/age> ?age . } } ORDER BY DESC(?age) LIMIT 10"#
+//! );
+//! assert_eq!(Query::parse(&query.to_string(), None)?, query);
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::algebra::{
+    AggregateExpression, Expression, GraphPattern, OrderExpression, QueryDataset,
+};
+use crate::parser::new_join;
+use crate::query::Query;
+use crate::term::{Literal, NamedNode, NamedNodePattern, TermPattern, TriplePattern, Variable};
+use oxrdf::{IriParseError, VariableNameParseError};
+
+/// Builds a [`Variable`] after validating its name.
+#[inline]
+pub fn var(name: impl Into<String>) -> Result<Variable, VariableNameParseError> {
+    Variable::new(name)
+}
+
+/// Builds a [`NamedNode`] after validating its IRI.
+#[inline]
+pub fn iri(iri: impl Into<String>) -> Result<NamedNode, IriParseError> {
+    NamedNode::new(iri)
+}
+
+/// Builds a simple [`Literal`].
+#[inline]
+pub fn literal(value: impl Into<String>) -> Literal {
+    Literal::new_simple_literal(value)
+}
+
+/// Builds a [`TriplePattern`] to be given to [`Select::where_`] and the other pattern methods.
+#[inline]
+pub fn triple(
+    subject: impl Into<TermPattern>,
+    predicate: impl Into<NamedNodePattern>,
+    object: impl Into<TermPattern>,
+) -> TriplePattern {
+    TriplePattern::new(subject, predicate, object)
+}
+
+/// A [SELECT](https://www.w3.org/TR/sparql11-query/#select) query builder.
+///
+/// ```
+/// use spargebra::algebra::{AggregateExpression, AggregateFunction};
+/// use spargebra::builder::{iri, triple, var, Select};
+///
+/// let query = Select::new()
+///     .var(var("type")?)
+///     .aggregate(
+///         AggregateExpression::FunctionCall {
+///             name: AggregateFunction::Count,
+///             expr: var("s")?.into(),
+///             distinct: true,
+///         },
+///         var("count")?,
+///     )
+///     .where_(triple(var("s")?, iri("http://www.w3.org/1999/02/22-rdf-syntax-ns#type")?, var("type")?))
+///     .group_by(var("type")?)
+///     .build();
+/// assert_eq!(
+///     query.to_string(),
+///     "SELECT ?type ?count WHERE { {SELECT (COUNT(DISTINCT ?s) AS ?count) ?type WHERE { ?s <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> ?type . } GROUP BY ?type} }"
+/// );
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+#[must_use]
+pub struct Select {
+    where_clause: WhereClause,
+    projection: Vec<Variable>,
+    expressions: Vec<(Expression, Variable)>,
+    distinct: bool,
+    reduced: bool,
+    group_by: Vec<Variable>,
+    aggregates: Vec<(Variable, AggregateExpression)>,
+    having: Option<Expression>,
+    order_by: Vec<OrderExpression>,
+    offset: usize,
+    limit: Option<usize>,
+}
+
+impl Select {
+    /// Builds a new `SELECT *` query with an empty `WHERE` clause.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a variable to the projection.
+    ///
+    /// If no variable is projected, all the variables are returned like with `SELECT *`.
+    #[inline]
+    pub fn var(mut self, variable: Variable) -> Self {
+        self.projection.push(variable);
+        self
+    }
+
+    /// Adds variables to the projection.
+    #[inline]
+    pub fn vars(mut self, variables: impl IntoIterator<Item = Variable>) -> Self {
+        self.projection.extend(variables);
+        self
+    }
+
+    /// Adds to the projection the result of an expression, like `(expression AS ?variable)`.
+    #[inline]
+    pub fn expression(mut self, expression: impl Into<Expression>, variable: Variable) -> Self {
+        self.expressions.push((expression.into(), variable.clone()));
+        self.projection.push(variable);
+        self
+    }
+
+    /// Adds to the projection the result of an aggregate, like `(COUNT(*) AS ?variable)`.
+    ///
+    /// The solutions are grouped by the [`group_by`](Self::group_by) variables, in a single group if there are none.
+    #[inline]
+    pub fn aggregate(mut self, aggregate: AggregateExpression, variable: Variable) -> Self {
+        self.aggregates.push((variable.clone(), aggregate));
+        self.projection.push(variable);
+        self
+    }
+
+    /// Removes the duplicated solutions, like `SELECT DISTINCT`.
+    #[inline]
+    pub fn distinct(mut self) -> Self {
+        self.distinct = true;
+        self
+    }
+
+    /// Allows removing the duplicated solutions, like `SELECT REDUCED`.
+    #[inline]
+    pub fn reduced(mut self) -> Self {
+        self.reduced = true;
+        self
+    }
+
+    /// Adds a [`NamedNode`] to the default graph of the query dataset, like `FROM`.
+    #[inline]
+    pub fn default_graph(mut self, graph: NamedNode) -> Self {
+        self.where_clause.default_graph(graph);
+        self
+    }
+
+    /// Adds a [`NamedNode`] to the named graphs of the query dataset, like `FROM NAMED`.
+    #[inline]
+    pub fn named_graph(mut self, graph: NamedNode) -> Self {
+        self.where_clause.named_graph(graph);
+        self
+    }
+
+    /// Joins a pattern to the `WHERE` clause.
+    ///
+    /// The consecutive triple patterns are merged into a single basic graph pattern.
+    #[inline]
+    pub fn where_(mut self, pattern: impl Into<GraphPattern>) -> Self {
+        self.where_clause.join(pattern.into());
+        self
+    }
+
+    /// Adds an `OPTIONAL` pattern to the `WHERE` clause.
+    #[inline]
+    pub fn optional(mut self, pattern: impl Into<GraphPattern>) -> Self {
+        self.where_clause.optional(pattern.into());
+        self
+    }
+
+    /// Adds a `MINUS` pattern to the `WHERE` clause.
+    #[inline]
+    pub fn minus(mut self, pattern: impl Into<GraphPattern>) -> Self {
+        self.where_clause.minus(pattern.into());
+        self
+    }
+
+    /// Adds a `FILTER` to the `WHERE` clause.
+    ///
+    /// Like in the SPARQL syntax, the filters apply to the complete `WHERE` clause.
+    #[inline]
+    pub fn filter(mut self, expression: impl Into<Expression>) -> Self {
+        self.where_clause.filter(expression.into());
+        self
+    }
+
+    /// Adds a `BIND` to the `WHERE` clause.
+    #[inline]
+    pub fn bind(mut self, expression: impl Into<Expression>, variable: Variable) -> Self {
+        self.where_clause.bind(expression.into(), variable);
+        self
+    }
+
+    /// Adds a variable to the `GROUP BY` clause.
+    #[inline]
+    pub fn group_by(mut self, variable: Variable) -> Self {
+        self.group_by.push(variable);
+        self
+    }
+
+    /// Adds a `HAVING` condition.
+    #[inline]
+    pub fn having(mut self, expression: impl Into<Expression>) -> Self {
+        self.having = Some(and(self.having.take(), expression.into()));
+        self
+    }
+
+    /// Adds a condition to the `ORDER BY` clause.
+    #[inline]
+    pub fn order_by(mut self, expression: OrderExpression) -> Self {
+        self.order_by.push(expression);
+        self
+    }
+
+    /// Sets the `OFFSET`.
+    #[inline]
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Sets the `LIMIT`.
+    #[inline]
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Builds the [`Query`].
+    pub fn build(self) -> Query {
+        let (mut pattern, dataset) = self.where_clause.build();
+        if !self.group_by.is_empty() || !self.aggregates.is_empty() {
+            pattern = GraphPattern::Group {
+                inner: Box::new(pattern),
+                variables: self.group_by,
+                aggregates: self.aggregates,
+            };
+        }
+        if let Some(expr) = self.having {
+            pattern = GraphPattern::Filter {
+                expr,
+                inner: Box::new(pattern),
+            };
+        }
+        for (expression, variable) in self.expressions {
+            pattern = GraphPattern::Extend {
+                inner: Box::new(pattern),
+                variable,
+                expression,
+            };
+        }
+        if !self.order_by.is_empty() {
+            pattern = GraphPattern::OrderBy {
+                inner: Box::new(pattern),
+                expression: self.order_by,
+            };
+        }
+        let mut variables = self.projection;
+        if variables.is_empty() {
+            // Like the parser does for SELECT *
+            pattern.on_in_scope_variable(|v| {
+                if !variables.contains(v) {
+                    variables.push(v.clone());
+                }
+            });
+            variables.sort();
+        }
+        pattern = GraphPattern::Project {
+            inner: Box::new(pattern),
+            variables,
+        };
+        if self.distinct {
+            pattern = GraphPattern::Distinct {
+                inner: Box::new(pattern),
+            };
+        } else if self.reduced {
+            pattern = GraphPattern::Reduced {
+                inner: Box::new(pattern),
+            };
+        }
+        if self.offset > 0 || self.limit.is_some() {
+            pattern = GraphPattern::Slice {
+                inner: Box::new(pattern),
+                start: self.offset,
+                length: self.limit,
+            };
+        }
+        Query::Select {
+            dataset,
+            pattern,
+            base_iri: None,
+        }
+    }
+}
+
+/// A [CONSTRUCT](https://www.w3.org/TR/sparql11-query/#construct) query builder.
+///
+/// ```
+/// use spargebra::builder::{iri, triple, var, Construct};
+///
+/// let query = Construct::new([triple(var("s")?, iri("http://schema.org/name")?, var("name")?)])
+///     .where_(triple(var("s")?, iri("http://xmlns.com/foaf/0.1/name")?, var("name")?))
+///     .build();
+/// assert_eq!(
+///     query.to_string(),
+///     "CONSTRUCT { ?s <http://schema.org/name> ?name . } WHERE { SELECT * WHERE { ?s <http://xmlns.com/foaf/0.1/name> ?name . } }"
+/// );
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct Construct {
+    template: Vec<TriplePattern>,
+    where_clause: WhereClause,
+}
+
+impl Construct {
+    /// Builds a new `CONSTRUCT` query from its template, with an empty `WHERE` clause.
+    #[inline]
+    pub fn new(template: impl IntoIterator<Item = TriplePattern>) -> Self {
+        Self {
+            template: template.into_iter().collect(),
+            where_clause: WhereClause::default(),
+        }
+    }
+
+    /// Adds a [`NamedNode`] to the default graph of the query dataset, like `FROM`.
+    #[inline]
+    pub fn default_graph(mut self, graph: NamedNode) -> Self {
+        self.where_clause.default_graph(graph);
+        self
+    }
+
+    /// Adds a [`NamedNode`] to the named graphs of the query dataset, like `FROM NAMED`.
+    #[inline]
+    pub fn named_graph(mut self, graph: NamedNode) -> Self {
+        self.where_clause.named_graph(graph);
+        self
+    }
+
+    /// Joins a pattern to the `WHERE` clause.
+    #[inline]
+    pub fn where_(mut self, pattern: impl Into<GraphPattern>) -> Self {
+        self.where_clause.join(pattern.into());
+        self
+    }
+
+    /// Adds an `OPTIONAL` pattern to the `WHERE` clause.
+    #[inline]
+    pub fn optional(mut self, pattern: impl Into<GraphPattern>) -> Self {
+        self.where_clause.optional(pattern.into());
+        self
+    }
+
+    /// Adds a `MINUS` pattern to the `WHERE` clause.
+    #[inline]
+    pub fn minus(mut self, pattern: impl Into<GraphPattern>) -> Self {
+        self.where_clause.minus(pattern.into());
+        self
+    }
+
+    /// Adds a `FILTER` to the `WHERE` clause.
+    #[inline]
+    pub fn filter(mut self, expression: impl Into<Expression>) -> Self {
+        self.where_clause.filter(expression.into());
+        self
+    }
+
+    /// Adds a `BIND` to the `WHERE` clause.
+    #[inline]
+    pub fn bind(mut self, expression: impl Into<Expression>, variable: Variable) -> Self {
+        self.where_clause.bind(expression.into(), variable);
+        self
+    }
+
+    /// Builds the [`Query`].
+    pub fn build(self) -> Query {
+        let (pattern, dataset) = self.where_clause.build();
+        Query::Construct {
+            template: self.template,
+            dataset,
+            pattern,
+            base_iri: None,
+        }
+    }
+}
+
+/// An [ASK](https://www.w3.org/TR/sparql11-query/#ask) query builder.
+///
+/// ```
+/// use spargebra::builder::{iri, triple, var, Ask};
+///
+/// let query = Ask::new()
+///     .where_(triple(iri("http://example.com/alice")?, iri("http://xmlns.com/foaf/0.1/knows")?, var("o")?))
+///     .build();
+/// assert_eq!(
+///     query.to_string(),
+///     "ASK WHERE { SELECT * WHERE { <http://example.com/alice> <http://xmlns.com/foaf/0.1/knows> ?o . } }"
+/// );
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+#[must_use]
+pub struct Ask {
+    where_clause: WhereClause,
+}
+
+impl Ask {
+    /// Builds a new `ASK` query with an empty `WHERE` clause.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a [`NamedNode`] to the default graph of the query dataset, like `FROM`.
+    #[inline]
+    pub fn default_graph(mut self, graph: NamedNode) -> Self {
+        self.where_clause.default_graph(graph);
+        self
+    }
+
+    /// Adds a [`NamedNode`] to the named graphs of the query dataset, like `FROM NAMED`.
+    #[inline]
+    pub fn named_graph(mut self, graph: NamedNode) -> Self {
+        self.where_clause.named_graph(graph);
+        self
+    }
+
+    /// Joins a pattern to the `WHERE` clause.
+    #[inline]
+    pub fn where_(mut self, pattern: impl Into<GraphPattern>) -> Self {
+        self.where_clause.join(pattern.into());
+        self
+    }
+
+    /// Adds an `OPTIONAL` pattern to the `WHERE` clause.
+    #[inline]
+    pub fn optional(mut self, pattern: impl Into<GraphPattern>) -> Self {
+        self.where_clause.optional(pattern.into());
+        self
+    }
+
+    /// Adds a `MINUS` pattern to the `WHERE` clause.
+    #[inline]
+    pub fn minus(mut self, pattern: impl Into<GraphPattern>) -> Self {
+        self.where_clause.minus(pattern.into());
+        self
+    }
+
+    /// Adds a `FILTER` to the `WHERE` clause.
+    #[inline]
+    pub fn filter(mut self, expression: impl Into<Expression>) -> Self {
+        self.where_clause.filter(expression.into());
+        self
+    }
+
+    /// Adds a `BIND` to the `WHERE` clause.
+    #[inline]
+    pub fn bind(mut self, expression: impl Into<Expression>, variable: Variable) -> Self {
+        self.where_clause.bind(expression.into(), variable);
+        self
+    }
+
+    /// Builds the [`Query`].
+    pub fn build(self) -> Query {
+        let (pattern, dataset) = self.where_clause.build();
+        Query::Ask {
+            dataset,
+            pattern,
+            base_iri: None,
+        }
+    }
+}
+
+/// The `WHERE` clause and the dataset shared by all the query builders
+#[derive(Clone, Debug, Default)]
+struct WhereClause {
+    pattern: GraphPattern,
+    filter: Option<Expression>,
+    dataset: Option<QueryDataset>,
+}
+
+impl WhereClause {
+    fn default_graph(&mut self, graph: NamedNode) {
+        self.dataset().default.push(graph);
+    }
+
+    fn named_graph(&mut self, graph: NamedNode) {
+        self.dataset()
+            .named
+            .get_or_insert_with(Vec::new)
+            .push(graph);
+    }
+
+    fn dataset(&mut self) -> &mut QueryDataset {
+        self.dataset.get_or_insert_with(|| QueryDataset {
+            default: Vec::new(),
+            named: Some(Vec::new()),
+        })
+    }
+
+    fn join(&mut self, pattern: GraphPattern) {
+        self.pattern = new_join(std::mem::take(&mut self.pattern), pattern);
+    }
+
+    fn optional(&mut self, pattern: GraphPattern) {
+        let (right, expression) = if let GraphPattern::Filter { expr, inner } = pattern {
+            (*inner, Some(expr))
+        } else {
+            (pattern, None)
+        };
+        self.pattern = GraphPattern::LeftJoin {
+            left: Box::new(std::mem::take(&mut self.pattern)),
+            right: Box::new(right),
+            expression,
+        };
+    }
+
+    fn minus(&mut self, pattern: GraphPattern) {
+        self.pattern = GraphPattern::Minus {
+            left: Box::new(std::mem::take(&mut self.pattern)),
+            right: Box::new(pattern),
+        };
+    }
+
+    fn filter(&mut self, expression: Expression) {
+        self.filter = Some(and(self.filter.take(), expression));
+    }
+
+    fn bind(&mut self, expression: Expression, variable: Variable) {
+        self.pattern = GraphPattern::Extend {
+            inner: Box::new(std::mem::take(&mut self.pattern)),
+            variable,
+            expression,
+        };
+    }
+
+    fn build(self) -> (GraphPattern, Option<QueryDataset>) {
+        let pattern = if let Some(expr) = self.filter {
+            GraphPattern::Filter {
+                expr,
+                inner: Box::new(self.pattern),
+            }
+        } else {
+            self.pattern
+        };
+        (pattern, self.dataset)
+    }
+}
+
+fn and(left: Option<Expression>, right: Expression) -> Expression {
+    if let Some(left) = left {
+        Expression::And(Box::new(left), Box::new(right))
+    } else {
+        right
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algebra::AggregateFunction;
+
+    fn ex(name: &str) -> NamedNode {
+        iri(format!("http://example.com/{name}")).unwrap()
+    }
+
+    fn v(name: &str) -> Variable {
+        var(name).unwrap()
+    }
+
+    fn round_trip(query: &Query) -> Query {
+        Query::parse(&query.to_string(), None).unwrap()
+    }
+
+    /// The `CONSTRUCT` and `ASK` patterns are serialized as `SELECT *` sub-queries
+    fn with_star_projection(pattern: &GraphPattern) -> GraphPattern {
+        let mut variables = Vec::new();
+        pattern.on_in_scope_variable(|v| {
+            if !variables.contains(v) {
+                variables.push(v.clone());
+            }
+        });
+        variables.sort();
+        GraphPattern::Project {
+            inner: Box::new(pattern.clone()),
+            variables,
+        }
+    }
+
+    fn select_pattern(query: &Query) -> &GraphPattern {
+        let Query::Select { pattern, .. } = query else {
+            unreachable!()
+        };
+        pattern
+    }
+
+    fn projection(query: &Query) -> &[Variable] {
+        let mut pattern = select_pattern(query);
+        loop {
+            match pattern {
+                GraphPattern::Project { variables, .. } => return variables,
+                GraphPattern::Slice { inner, .. }
+                | GraphPattern::Distinct { inner }
+                | GraphPattern::Reduced { inner } => pattern = inner,
+                _ => return &[],
+            }
+        }
+    }
+
+    /// The grouped pattern, the group keys and the aggregates without their variables
+    fn grouping(query: &Query) -> Option<(&GraphPattern, &[Variable], Vec<&AggregateExpression>)> {
+        let mut pattern = select_pattern(query);
+        loop {
+            match pattern {
+                GraphPattern::Group {
+                    inner,
+                    variables,
+                    aggregates,
+                } => {
+                    return Some((
+                        inner,
+                        variables,
+                        aggregates.iter().map(|(_, a)| a).collect(),
+                    ))
+                }
+                GraphPattern::Project { inner, .. }
+                | GraphPattern::Extend { inner, .. }
+                | GraphPattern::Filter { inner, .. }
+                | GraphPattern::OrderBy { inner, .. }
+                | GraphPattern::Slice { inner, .. } => pattern = inner,
+                _ => return None,
+            }
+        }
+    }
+
+    #[test]
+    fn select_round_trip() {
+        for query in [
+            Select::new().build(),
+            Select::new()
+                .var(v("s"))
+                .vars([v("name"), v("age")])
+                .distinct()
+                .default_graph(ex("g1"))
+                .named_graph(ex("g2"))
+                .where_(triple(v("s"), ex("name"), v("name")))
+                .where_(triple(v("s"), ex("knows"), v("o")))
+                .optional(GraphPattern::Filter {
+                    expr: Expression::Bound(v("age")),
+                    inner: Box::new(triple(v("s"), ex("age"), v("age")).into()),
+                })
+                .minus(triple(v("s"), ex("banned"), literal("true")))
+                .bind(Expression::from(literal("x")), v("x"))
+                .filter(Expression::Bound(v("name")))
+                .filter(Expression::Not(Box::new(Expression::Bound(v("x")))))
+                .order_by(OrderExpression::Desc(v("age").into()))
+                .order_by(OrderExpression::Asc(v("name").into()))
+                .offset(5)
+                .limit(10)
+                .build(),
+            Select::new()
+                .reduced()
+                .where_(triple(v("s"), v("p"), v("o")))
+                .offset(1)
+                .build(),
+            Select::new()
+                .var(v("s"))
+                .expression(Expression::from(v("o")), v("value"))
+                .where_(triple(v("s"), v("p"), v("o")))
+                .build(),
+        ] {
+            assert_eq!(round_trip(&query), query, "{query}");
+        }
+    }
+
+    #[test]
+    fn select_with_aggregates_round_trip() {
+        // The parser binds the aggregates to random variables, we compare the groups and the projections
+        for query in [
+            Select::new()
+                .var(v("type"))
+                .aggregate(
+                    AggregateExpression::FunctionCall {
+                        name: AggregateFunction::Count,
+                        expr: v("s").into(),
+                        distinct: true,
+                    },
+                    v("count"),
+                )
+                .aggregate(
+                    AggregateExpression::CountSolutions { distinct: false },
+                    v("all"),
+                )
+                .where_(triple(v("s"), ex("type"), v("type")))
+                .group_by(v("type"))
+                .having(Expression::Bound(v("type")))
+                .order_by(OrderExpression::Asc(v("type").into()))
+                .limit(3)
+                .build(),
+            Select::new()
+                .aggregate(
+                    AggregateExpression::CountSolutions { distinct: true },
+                    v("c"),
+                )
+                .where_(triple(v("s"), v("p"), v("o")))
+                .build(),
+        ] {
+            let parsed = round_trip(&query);
+            assert_eq!(grouping(&parsed), grouping(&query), "{query}");
+            assert_eq!(projection(&parsed), projection(&query), "{query}");
+        }
+    }
+
+    #[test]
+    fn construct_round_trip() {
+        let query = Construct::new([
+            triple(v("s"), ex("name"), v("name")),
+            triple(v("s"), ex("type"), ex("Person")),
+        ])
+        .default_graph(ex("g"))
+        .where_(triple(v("s"), ex("label"), v("name")))
+        .optional(triple(v("s"), ex("age"), v("age")))
+        .minus(triple(v("s"), ex("banned"), literal("true")))
+        .bind(Expression::from(v("name")), v("n"))
+        .filter(Expression::Bound(v("name")))
+        .build();
+        let Query::Construct {
+            template,
+            dataset,
+            pattern,
+            base_iri,
+        } = query.clone()
+        else {
+            unreachable!()
+        };
+        assert_eq!(
+            round_trip(&query),
+            Query::Construct {
+                template,
+                dataset,
+                pattern: with_star_projection(&pattern),
+                base_iri,
+            }
+        );
+    }
+
+    #[test]
+    fn ask_round_trip() {
+        for query in [
+            Ask::new().build(),
+            Ask::new()
+                .named_graph(ex("g"))
+                .where_(triple(ex("alice"), ex("knows"), v("o")))
+                .optional(triple(v("o"), ex("name"), v("name")))
+                .minus(triple(v("o"), ex("banned"), literal("true")))
+                .bind(Expression::from(v("name")), v("n"))
+                .filter(Expression::Bound(v("n")))
+                .build(),
+        ] {
+            let Query::Ask {
+                dataset,
+                pattern,
+                base_iri,
+            } = query.clone()
+            else {
+                unreachable!()
+            };
+            assert_eq!(
+                round_trip(&query),
+                Query::Ask {
+                    dataset,
+                    pattern: with_star_projection(&pattern),
+                    base_iri,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn literals_are_escaped() {
+        for value in [
+            "\" } DROP ALL { \"",
+            "'''\"\"\"",
+            "line\nbreak\ttab\\",
+            "} ; DELETE WHERE { ?s ?p ?o }",
+        ] {
+            let query = Select::new()
+                .where_(triple(v("s"), ex("name"), literal(value)))
+                .build();
+            assert_eq!(round_trip(&query), query, "{query}");
+            let Query::Select { pattern, .. } = query else {
+                unreachable!()
+            };
+            assert_eq!(
+                pattern,
+                GraphPattern::Project {
+                    inner: Box::new(GraphPattern::Bgp {
+                        patterns: vec![triple(v("s"), ex("name"), literal(value))]
+                    }),
+                    variables: vec![v("s")]
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_names_are_rejected() {
+        var("s p").unwrap_err();
+        var("?s").unwrap_err();
+        iri("not an iri").unwrap_err();
+        iri("http://example.com/> . ?s ?p <http://example.com/").unwrap_err();
+    }
+}
@@ -0,0 +1,335 @@
+use crate::algebra::{AggregateExpression, Expression, Function, GraphPattern, OrderExpression};
+use crate::parser::{parse_function_definitions, SparqlSyntaxError};
+use crate::term::{NamedNode, Variable};
+use std::fmt;
+
+/// A SPARQL function defined by an expression using the `DEFINE FUNCTION` prologue extension.
+///
+/// Defined functions are expanded when parsing: the function calls are replaced by the function body
+/// where the parameters are replaced by the call arguments.
+/// The arguments might be evaluated multiple times.
+///
+/// The body can only use the function parameters as variables and can't contain `BOUND` or `EXISTS`.
+///
+/// ```
+/// use spargebra::{FunctionDefinition, Query};
+///
+/// let definitions = FunctionDefinition::parse_list(
+///     "PREFIX ex: <http://example.com/> DEFINE FUNCTION ex:grade(?score) { IF(?score >= 10, 'pass', 'fail') }",
+///     None,
+/// )?;
+/// assert_eq!(definitions[0].name().as_str(), "http://example.com/grade");
+///
+/// // A definition in the query prologue
+/// let query = Query::parse(
+///     "PREFIX ex: <http://example.com/> DEFINE FUNCTION ex:grade(?score) { IF(?score >= 10, 'pass', 'fail') } SELECT (ex:grade(?s) AS ?g) WHERE { ?x ex:score ?s }",
+///     None,
+/// )?;
+/// let expanded = Query::parse(
+///     "SELECT (IF(?s >= 10, 'pass', 'fail') AS ?g) WHERE { ?x <http://example.com/score> ?s }",
+///     None,
+/// )?;
+/// assert_eq!(query, expanded);
+///
+/// // The same expansion using the previously parsed definitions
+/// let mut query = Query::parse(
+///     "PREFIX ex: <http://example.com/> SELECT (ex:grade(?s) AS ?g) WHERE { ?x ex:score ?s }",
+///     None,
+/// )?;
+/// query.expand_functions(&definitions);
+/// assert_eq!(query, expanded);
+/// # Ok::<_, spargebra::SparqlSyntaxError>(())
+/// ```
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
+pub struct FunctionDefinition {
+    name: NamedNode,
+    parameters: Vec<Variable>,
+    body: Expression,
+}
+
+impl FunctionDefinition {
+    /// Parses a list of `DEFINE FUNCTION` declarations, possibly mixed with `BASE` and `PREFIX` declarations.
+    ///
+    /// A definition might call the functions defined before it.
+    pub fn parse_list(
+        definitions: &str,
+        base_iri: Option<&str>,
+    ) -> Result<Vec<Self>, SparqlSyntaxError> {
+        parse_function_definitions(definitions, base_iri)
+    }
+
+    pub(crate) fn new(
+        name: NamedNode,
+        parameters: Vec<Variable>,
+        body: Expression,
+    ) -> Result<Self, &'static str> {
+        for (i, parameter) in parameters.iter().enumerate() {
+            if parameters[..i].contains(parameter) {
+                return Err("The function parameters must be distinct");
+            }
+        }
+        validate_body(&body, &parameters)?;
+        Ok(Self {
+            name,
+            parameters,
+            body,
+        })
+    }
+
+    /// The function name.
+    #[inline]
+    pub fn name(&self) -> &NamedNode {
+        &self.name
+    }
+
+    /// The function parameters.
+    #[inline]
+    pub fn parameters(&self) -> &[Variable] {
+        &self.parameters
+    }
+
+    /// The expression computing the function value from the parameters.
+    #[inline]
+    pub fn body(&self) -> &Expression {
+        &self.body
+    }
+
+    /// Returns the body of the function where the parameters are replaced by the given arguments.
+    ///
+    /// Returns `None` if the number of arguments is not the number of parameters.
+    pub fn call(&self, arguments: &[Expression]) -> Option<Expression> {
+        (arguments.len() == self.parameters.len())
+            .then(|| substitute(&self.body, &self.parameters, arguments))
+    }
+
+    /// Expands in the body the calls to the given functions.
+    pub fn expand_functions(&mut self, definitions: &[Self]) {
+        expand_expression(&mut self.body, definitions);
+    }
+}
+
+impl fmt::Display for FunctionDefinition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DEFINE FUNCTION {}(", self.name)?;
+        for (i, parameter) in self.parameters.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{parameter}")?;
+        }
+        write!(f, ") {{ {} }}", self.body)
+    }
+}
+
+fn validate_body(expression: &Expression, parameters: &[Variable]) -> Result<(), &'static str> {
+    match expression {
+        Expression::NamedNode(_) | Expression::Literal(_) => Ok(()),
+        Expression::Variable(v) => {
+            if parameters.contains(v) {
+                Ok(())
+            } else {
+                Err("The function body can only use the function parameters as variables")
+            }
+        }
+        Expression::Bound(_) => Err("BOUND is not allowed in function bodies"),
+        Expression::Exists(_) => Err("EXISTS is not allowed in function bodies"),
+        Expression::Or(a, b)
+        | Expression::And(a, b)
+        | Expression::Equal(a, b)
+        | Expression::SameTerm(a, b)
+        | Expression::Greater(a, b)
+        | Expression::GreaterOrEqual(a, b)
+        | Expression::Less(a, b)
+        | Expression::LessOrEqual(a, b)
+        | Expression::Add(a, b)
+        | Expression::Subtract(a, b)
+        | Expression::Multiply(a, b)
+        | Expression::Divide(a, b) => {
+            validate_body(a, parameters)?;
+            validate_body(b, parameters)
+        }
+        Expression::UnaryPlus(e) | Expression::UnaryMinus(e) | Expression::Not(e) => {
+            validate_body(e, parameters)
+        }
+        Expression::If(a, b, c) => {
+            validate_body(a, parameters)?;
+            validate_body(b, parameters)?;
+            validate_body(c, parameters)
+        }
+        Expression::In(a, l) => {
+            validate_body(a, parameters)?;
+            l.iter().try_for_each(|e| validate_body(e, parameters))
+        }
+        Expression::Coalesce(l) | Expression::FunctionCall(_, l) => {
+            l.iter().try_for_each(|e| validate_body(e, parameters))
+        }
+    }
+}
+
+fn substitute(
+    expression: &Expression,
+    parameters: &[Variable],
+    arguments: &[Expression],
+) -> Expression {
+    let boxed = |e: &Expression| Box::new(substitute(e, parameters, arguments));
+    let list = |l: &Vec<Expression>| {
+        l.iter()
+            .map(|e| substitute(e, parameters, arguments))
+            .collect::<Vec<_>>()
+    };
+    match expression {
+        Expression::Variable(v) => parameters
+            .iter()
+            .position(|p| p == v)
+            .map_or_else(|| expression.clone(), |i| arguments[i].clone()),
+        Expression::NamedNode(_)
+        | Expression::Literal(_)
+        | Expression::Bound(_)
+        | Expression::Exists(_) => expression.clone(),
+        Expression::Or(a, b) => Expression::Or(boxed(a), boxed(b)),
+        Expression::And(a, b) => Expression::And(boxed(a), boxed(b)),
+        Expression::Equal(a, b) => Expression::Equal(boxed(a), boxed(b)),
+        Expression::SameTerm(a, b) => Expression::SameTerm(boxed(a), boxed(b)),
+        Expression::Greater(a, b) => Expression::Greater(boxed(a), boxed(b)),
+        Expression::GreaterOrEqual(a, b) => Expression::GreaterOrEqual(boxed(a), boxed(b)),
+        Expression::Less(a, b) => Expression::Less(boxed(a), boxed(b)),
+        Expression::LessOrEqual(a, b) => Expression::LessOrEqual(boxed(a), boxed(b)),
+        Expression::In(a, b) => Expression::In(boxed(a), list(b)),
+        Expression::Add(a, b) => Expression::Add(boxed(a), boxed(b)),
+        Expression::Subtract(a, b) => Expression::Subtract(boxed(a), boxed(b)),
+        Expression::Multiply(a, b) => Expression::Multiply(boxed(a), boxed(b)),
+        Expression::Divide(a, b) => Expression::Divide(boxed(a), boxed(b)),
+        Expression::UnaryPlus(e) => Expression::UnaryPlus(boxed(e)),
+        Expression::UnaryMinus(e) => Expression::UnaryMinus(boxed(e)),
+        Expression::Not(e) => Expression::Not(boxed(e)),
+        Expression::If(a, b, c) => Expression::If(boxed(a), boxed(b), boxed(c)),
+        Expression::Coalesce(a) => Expression::Coalesce(list(a)),
+        Expression::FunctionCall(f, a) => Expression::FunctionCall(f.clone(), list(a)),
+    }
+}
+
+pub(crate) fn expand_pattern(pattern: &mut GraphPattern, definitions: &[FunctionDefinition]) {
+    match pattern {
+        GraphPattern::Bgp { .. } | GraphPattern::Path { .. } | GraphPattern::Values { .. } => (),
+        GraphPattern::Join { left, right }
+        | GraphPattern::Union { left, right }
+        | GraphPattern::Minus { left, right } => {
+            expand_pattern(left, definitions);
+            expand_pattern(right, definitions);
+        }
+        #[cfg(feature = "sep-0006")]
+        GraphPattern::Lateral { left, right } => {
+            expand_pattern(left, definitions);
+            expand_pattern(right, definitions);
+        }
+        GraphPattern::LeftJoin {
+            left,
+            right,
+            expression,
+        } => {
+            expand_pattern(left, definitions);
+            expand_pattern(right, definitions);
+            if let Some(expression) = expression {
+                expand_expression(expression, definitions);
+            }
+        }
+        GraphPattern::Filter { expr, inner } => {
+            expand_expression(expr, definitions);
+            expand_pattern(inner, definitions);
+        }
+        GraphPattern::Extend {
+            inner, expression, ..
+        } => {
+            expand_pattern(inner, definitions);
+            expand_expression(expression, definitions);
+        }
+        GraphPattern::OrderBy { inner, expression } => {
+            expand_pattern(inner, definitions);
+            for expression in expression {
+                match expression {
+                    OrderExpression::Asc(e) | OrderExpression::Desc(e) => {
+                        expand_expression(e, definitions)
+                    }
+                }
+            }
+        }
+        GraphPattern::Group {
+            inner, aggregates, ..
+        } => {
+            expand_pattern(inner, definitions);
+            for (_, aggregate) in aggregates {
+                match aggregate {
+                    AggregateExpression::CountSolutions { .. } => (),
+                    AggregateExpression::FunctionCall { expr, .. } => {
+                        expand_expression(expr, definitions)
+                    }
+                }
+            }
+        }
+        GraphPattern::Graph { inner, .. }
+        | GraphPattern::Project { inner, .. }
+        | GraphPattern::Distinct { inner }
+        | GraphPattern::Reduced { inner }
+        | GraphPattern::Slice { inner, .. }
+        | GraphPattern::Service { inner, .. } => expand_pattern(inner, definitions),
+    }
+}
+
+fn expand_expression(expression: &mut Expression, definitions: &[FunctionDefinition]) {
+    match expression {
+        Expression::NamedNode(_)
+        | Expression::Literal(_)
+        | Expression::Variable(_)
+        | Expression::Bound(_) => (),
+        Expression::Or(a, b)
+        | Expression::And(a, b)
+        | Expression::Equal(a, b)
+        | Expression::SameTerm(a, b)
+        | Expression::Greater(a, b)
+        | Expression::GreaterOrEqual(a, b)
+        | Expression::Less(a, b)
+        | Expression::LessOrEqual(a, b)
+        | Expression::Add(a, b)
+        | Expression::Subtract(a, b)
+        | Expression::Multiply(a, b)
+        | Expression::Divide(a, b) => {
+            expand_expression(a, definitions);
+            expand_expression(b, definitions);
+        }
+        Expression::UnaryPlus(e) | Expression::UnaryMinus(e) | Expression::Not(e) => {
+            expand_expression(e, definitions)
+        }
+        Expression::If(a, b, c) => {
+            expand_expression(a, definitions);
+            expand_expression(b, definitions);
+            expand_expression(c, definitions);
+        }
+        Expression::In(a, l) => {
+            expand_expression(a, definitions);
+            for e in l {
+                expand_expression(e, definitions);
+            }
+        }
+        Expression::Coalesce(l) => {
+            for e in l {
+                expand_expression(e, definitions);
+            }
+        }
+        Expression::Exists(p) => expand_pattern(p, definitions),
+        Expression::FunctionCall(function, arguments) => {
+            for e in arguments.iter_mut() {
+                expand_expression(e, definitions);
+            }
+            if let Function::Custom(name) = function {
+                // The expanded body is not expanded again to avoid infinite recursions
+                if let Some(expanded) = definitions
+                    .iter()
+                    .find(|d| d.name == *name)
+                    .and_then(|d| d.call(arguments))
+                {
+                    *expression = expanded;
+                }
+            }
+        }
+    }
+}
@@ -5,11 +5,18 @@
 #![doc(html_logo_url = "https://raw.githubusercontent.com/oxigraph/oxigraph/main/logo.svg")]
 
 pub mod algebra;
+mod analysis;
+pub mod builder;
+mod function;
 mod parser;
 mod query;
+mod serializer;
 pub mod term;
 mod update;
 
-pub use parser::SparqlSyntaxError;
+pub use analysis::QueryWarning;
+pub use function::FunctionDefinition;
+pub use parser::{SparqlParser, SparqlSyntaxError};
 pub use query::*;
+pub use serializer::QuerySerializer;
 pub use update::*;
@@ -1,5 +1,7 @@
 #![allow(clippy::ignored_unit_patterns)]
 use crate::algebra::*;
+use crate::analysis::QueryWarning;
+use crate::function::FunctionDefinition;
 use crate::query::*;
 use crate::term::*;
 use crate::update::*;
@@ -31,6 +33,73 @@ pub fn parse_update(update: &str, base_iri: Option<&str>) -> Result<Update, Spar
     })
 }
 
+/// A SPARQL parser.
+///
+/// ```
+/// use spargebra::{QueryWarning, SparqlParser};
+///
+/// let (query, warnings) = SparqlParser::new()
+///     .with_base_iri("http://example.com/")?
+///     .parse_and_lint("SELECT ?s WHERE { ?s <p> ?o FILTER(?x) }")?;
+/// assert_eq!(
+///     query.to_string(),
+///     "BASE <http://example.com/>\nSELECT ?s WHERE { ?s <http://example.com/p> ?o . FILTER(?x) }"
+/// );
+/// assert!(warnings.contains(&QueryWarning::UnboundFilterVariable {
+///     variable: spargebra::term::Variable::new("x")?
+/// }));
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[derive(Clone, Debug, Default)]
+#[must_use]
+pub struct SparqlParser {
+    base_iri: Option<Iri<String>>,
+}
+
+impl SparqlParser {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the base IRI used to resolve the relative IRIs.
+    #[inline]
+    pub fn with_base_iri(mut self, base_iri: impl Into<String>) -> Result<Self, IriParseError> {
+        self.base_iri = Some(Iri::parse(base_iri.into())?);
+        Ok(self)
+    }
+
+    /// Parses a SPARQL query.
+    pub fn parse_query(&self, query: &str) -> Result<Query, SparqlSyntaxError> {
+        parse_query(query, self.base_iri.as_ref().map(Iri::as_str))
+    }
+
+    /// Parses a SPARQL update.
+    pub fn parse_update(&self, update: &str) -> Result<Update, SparqlSyntaxError> {
+        parse_update(update, self.base_iri.as_ref().map(Iri::as_str))
+    }
+
+    /// Parses a SPARQL query and returns it with its [lint warnings](Query::lint).
+    pub fn parse_and_lint(
+        &self,
+        query: &str,
+    ) -> Result<(Query, Vec<QueryWarning>), SparqlSyntaxError> {
+        let query = self.parse_query(query)?;
+        let warnings = query.lint();
+        Ok((query, warnings))
+    }
+}
+
+/// Parses a list of SPARQL function definitions with an optional base IRI to resolve relative IRIs in them.
+pub fn parse_function_definitions(
+    definitions: &str,
+    base_iri: Option<&str>,
+) -> Result<Vec<FunctionDefinition>, SparqlSyntaxError> {
+    let mut state = ParserState::from_base_iri(base_iri)?;
+    parser::FunctionDefinitionsUnit(definitions, &mut state)
+        .map_err(|e| SparqlSyntaxError(ParseErrorKind::Syntax(e)))
+}
+
 /// Error returned during SPARQL parsing.
 #[derive(Debug, thiserror::Error)]
 #[error(transparent)]
@@ -313,7 +382,7 @@ enum PartialGraphPattern {
     Other(GraphPattern),
 }
 
-fn new_join(l: GraphPattern, r: GraphPattern) -> GraphPattern {
+pub(crate) fn new_join(l: GraphPattern, r: GraphPattern) -> GraphPattern {
     // Avoid to output empty BGPs
     if let GraphPattern::Bgp { patterns: pl } = &l {
         if pl.is_empty() {
@@ -386,11 +455,11 @@ impl Selection {
 }
 
 fn build_select(
-    select: Selection,
+    mut select: Selection,
     r#where: GraphPattern,
     mut group: Option<(Vec<Variable>, Vec<(Expression, Variable)>)>,
-    having: Option<Expression>,
-    order_by: Option<Vec<OrderExpression>>,
+    mut having: Option<Expression>,
+    mut order_by: Option<Vec<OrderExpression>>,
     offset_limit: Option<(usize, Option<usize>)>,
     values: Option<GraphPattern>,
     state: &mut ParserState,
@@ -399,7 +468,41 @@ fn build_select(
     let mut with_aggregate = false;
 
     // GROUP BY
-    let aggregates = state.aggregates.pop().unwrap_or_default();
+    let mut aggregates = state.aggregates.pop().unwrap_or_default();
+    if group.is_some() || !aggregates.is_empty() {
+        // In a grouped query, custom function calls on ungrouped variables are custom aggregates
+        let mut grouped = aggregates
+            .iter()
+            .map(|(v, _)| v.clone())
+            .collect::<HashSet<_>>();
+        if let Some((clauses, _)) = &group {
+            grouped.extend(clauses.iter().cloned());
+        }
+        let mut resolve = |e| resolve_custom_aggregates(e, &grouped, &mut aggregates);
+        if let SelectionVariables::Explicit(sel_items) = select.variables {
+            select.variables = SelectionVariables::Explicit(
+                sel_items
+                    .into_iter()
+                    .map(|sel_item| match sel_item {
+                        SelectionMember::Variable(v) => SelectionMember::Variable(v),
+                        SelectionMember::Expression(e, v) => {
+                            SelectionMember::Expression(resolve(e), v)
+                        }
+                    })
+                    .collect(),
+            );
+        }
+        having = having.map(&mut resolve);
+        order_by = order_by.map(|order_by| {
+            order_by
+                .into_iter()
+                .map(|e| match e {
+                    OrderExpression::Asc(e) => OrderExpression::Asc(resolve(e)),
+                    OrderExpression::Desc(e) => OrderExpression::Desc(resolve(e)),
+                })
+                .collect()
+        });
+    }
     if group.is_none() && !aggregates.is_empty() {
         group = Some((vec![], vec![]));
     }
@@ -528,6 +631,117 @@ fn build_select(
     Ok(m)
 }
 
+fn resolve_custom_aggregates(
+    expression: Expression,
+    grouped: &HashSet<Variable>,
+    aggregates: &mut Vec<(Variable, AggregateExpression)>,
+) -> Expression {
+    let mut resolve = |e| resolve_custom_aggregates(e, grouped, aggregates);
+    match expression {
+        Expression::FunctionCall(Function::Custom(name), mut parameters)
+            if parameters.len() == 1 && uses_ungrouped_variable(&parameters[0], grouped) =>
+        {
+            let agg = AggregateExpression::FunctionCall {
+                name: AggregateFunction::Custom(name),
+                expr: parameters.pop().unwrap(),
+                distinct: false,
+            };
+            let variable = aggregates
+                .iter()
+                .find_map(|(v, a)| (a == &agg).then_some(v))
+                .cloned()
+                .unwrap_or_else(|| {
+                    let new_var = variable();
+                    aggregates.push((new_var.clone(), agg));
+                    new_var
+                });
+            variable.into()
+        }
+        Expression::NamedNode(_)
+        | Expression::Literal(_)
+        | Expression::Variable(_)
+        | Expression::Bound(_)
+        | Expression::Exists(_) => expression,
+        Expression::Or(a, b) => Expression::Or(Box::new(resolve(*a)), Box::new(resolve(*b))),
+        Expression::And(a, b) => Expression::And(Box::new(resolve(*a)), Box::new(resolve(*b))),
+        Expression::Equal(a, b) => Expression::Equal(Box::new(resolve(*a)), Box::new(resolve(*b))),
+        Expression::SameTerm(a, b) => {
+            Expression::SameTerm(Box::new(resolve(*a)), Box::new(resolve(*b)))
+        }
+        Expression::Greater(a, b) => {
+            Expression::Greater(Box::new(resolve(*a)), Box::new(resolve(*b)))
+        }
+        Expression::GreaterOrEqual(a, b) => {
+            Expression::GreaterOrEqual(Box::new(resolve(*a)), Box::new(resolve(*b)))
+        }
+        Expression::Less(a, b) => Expression::Less(Box::new(resolve(*a)), Box::new(resolve(*b))),
+        Expression::LessOrEqual(a, b) => {
+            Expression::LessOrEqual(Box::new(resolve(*a)), Box::new(resolve(*b)))
+        }
+        Expression::In(a, b) => {
+            Expression::In(Box::new(resolve(*a)), b.into_iter().map(resolve).collect())
+        }
+        Expression::Add(a, b) => Expression::Add(Box::new(resolve(*a)), Box::new(resolve(*b))),
+        Expression::Subtract(a, b) => {
+            Expression::Subtract(Box::new(resolve(*a)), Box::new(resolve(*b)))
+        }
+        Expression::Multiply(a, b) => {
+            Expression::Multiply(Box::new(resolve(*a)), Box::new(resolve(*b)))
+        }
+        Expression::Divide(a, b) => {
+            Expression::Divide(Box::new(resolve(*a)), Box::new(resolve(*b)))
+        }
+        Expression::UnaryPlus(e) => Expression::UnaryPlus(Box::new(resolve(*e))),
+        Expression::UnaryMinus(e) => Expression::UnaryMinus(Box::new(resolve(*e))),
+        Expression::Not(e) => Expression::Not(Box::new(resolve(*e))),
+        Expression::If(a, b, c) => Expression::If(
+            Box::new(resolve(*a)),
+            Box::new(resolve(*b)),
+            Box::new(resolve(*c)),
+        ),
+        Expression::Coalesce(l) => Expression::Coalesce(l.into_iter().map(resolve).collect()),
+        Expression::FunctionCall(f, parameters) => {
+            Expression::FunctionCall(f, parameters.into_iter().map(resolve).collect())
+        }
+    }
+}
+
+fn uses_ungrouped_variable(expression: &Expression, grouped: &HashSet<Variable>) -> bool {
+    match expression {
+        Expression::NamedNode(_) | Expression::Literal(_) | Expression::Exists(_) => false,
+        Expression::Variable(var) | Expression::Bound(var) => !grouped.contains(var),
+        Expression::UnaryPlus(e) | Expression::UnaryMinus(e) | Expression::Not(e) => {
+            uses_ungrouped_variable(e, grouped)
+        }
+        Expression::Or(a, b)
+        | Expression::And(a, b)
+        | Expression::Equal(a, b)
+        | Expression::SameTerm(a, b)
+        | Expression::Greater(a, b)
+        | Expression::GreaterOrEqual(a, b)
+        | Expression::Less(a, b)
+        | Expression::LessOrEqual(a, b)
+        | Expression::Add(a, b)
+        | Expression::Subtract(a, b)
+        | Expression::Multiply(a, b)
+        | Expression::Divide(a, b) => {
+            uses_ungrouped_variable(a, grouped) || uses_ungrouped_variable(b, grouped)
+        }
+        Expression::In(a, b) => {
+            uses_ungrouped_variable(a, grouped)
+                || b.iter().any(|b| uses_ungrouped_variable(b, grouped))
+        }
+        Expression::Coalesce(parameters) | Expression::FunctionCall(_, parameters) => parameters
+            .iter()
+            .any(|p| uses_ungrouped_variable(p, grouped)),
+        Expression::If(a, b, c) => {
+            uses_ungrouped_variable(a, grouped)
+                || uses_ungrouped_variable(b, grouped)
+                || uses_ungrouped_variable(c, grouped)
+        }
+    }
+}
+
 fn are_variables_bound(expression: &Expression, variables: &HashSet<Variable>) -> bool {
     match expression {
         Expression::NamedNode(_)
@@ -666,6 +880,7 @@ pub struct ParserState {
     used_bnodes: HashSet<BlankNode>,
     currently_used_bnodes: HashSet<BlankNode>,
     aggregates: Vec<Vec<(Variable, AggregateExpression)>>,
+    function_definitions: Vec<FunctionDefinition>,
 }
 
 impl ParserState {
@@ -683,6 +898,7 @@ impl ParserState {
             used_bnodes: HashSet::default(),
             currently_used_bnodes: HashSet::default(),
             aggregates: Vec::new(),
+            function_definitions: Vec::new(),
         })
     }
 
@@ -706,6 +922,33 @@ impl ParserState {
                 new_var
             }))
     }
+
+    fn define_function(
+        &mut self,
+        name: NamedNode,
+        parameters: Vec<Variable>,
+        body: Expression,
+    ) -> Result<(), &'static str> {
+        if self.function_definitions.iter().any(|d| *d.name() == name) {
+            return Err("The function is already defined");
+        }
+        self.function_definitions
+            .push(FunctionDefinition::new(name, parameters, body)?);
+        Ok(())
+    }
+
+    fn function_call(
+        &self,
+        name: NamedNode,
+        arguments: Vec<Expression>,
+    ) -> Result<Expression, &'static str> {
+        let Some(definition) = self.function_definitions.iter().find(|d| *d.name() == name) else {
+            return Ok(Expression::FunctionCall(Function::Custom(name), arguments));
+        };
+        definition
+            .call(&arguments)
+            .ok_or("The defined function is called with a wrong number of arguments")
+    }
 }
 
 fn unescape_iriref(mut input: &str) -> Result<String, &'static str> {
@@ -786,7 +1029,11 @@ parser! {
 
         pub rule UpdateInit() -> Vec<GraphUpdateOperation> = Update()
 
-        rule Prologue() = (BaseDecl() _ / PrefixDecl() _)* {}
+        pub rule FunctionDefinitionsUnit() -> Vec<FunctionDefinition> = _ Prologue() _ {
+            take(&mut state.function_definitions)
+        }
+
+        rule Prologue() = (BaseDecl() _ / PrefixDecl() _ / FunctionDecl() _)* {}
 
         rule BaseDecl() = i("BASE") _ i:IRIREF() {
             state.base_iri = Some(i)
@@ -796,6 +1043,11 @@ parser! {
             state.namespaces.insert(ns.into(), i.into_inner());
         }
 
+        rule FunctionDecl() = i("DEFINE") _ i("FUNCTION") _ name:iri() _ "(" _ parameters:FunctionDecl_parameter() ** ("," _) ")" _ "{" _ body:Expression() _ "}" {?
+            state.define_function(name, parameters, body)
+        }
+        rule FunctionDecl_parameter() -> Variable = v:Var() _ { v }
+
         rule SelectQuery() -> Query = s:SelectClause() _ d:DatasetClauses() _ w:WhereClause() _ g:GroupClause()? _ h:HavingClause()? _ o:OrderClause()? _ l:LimitOffsetClauses()? _ v:ValuesClause() {?
             Ok(Query::Select {
                 dataset: d,
@@ -1321,8 +1573,8 @@ parser! {
 
         rule Constraint() -> Expression = BrackettedExpression() / FunctionCall() / BuiltInCall()
 
-        rule FunctionCall() -> Expression = f: iri() _ a: ArgList() {
-            Expression::FunctionCall(Function::Custom(f), a)
+        rule FunctionCall() -> Expression = CustomAggregate() / f: iri() _ a: ArgList() {?
+            state.function_call(f, a)
         }
 
         rule ArgList() -> Vec<Expression> =
@@ -1908,14 +2160,22 @@ parser! {
             i("GROUP_CONCAT") _ "(" _ i("DISTINCT") _ expr:Expression() _ ";" _ i("SEPARATOR") _ "=" _ s:String() _ ")" { AggregateExpression::FunctionCall { name: AggregateFunction::GroupConcat { separator: Some(s) }, expr, distinct: true } } /
             i("GROUP_CONCAT") _ "(" _ i("DISTINCT") _ expr:Expression() _ ")" { AggregateExpression::FunctionCall { name: AggregateFunction::GroupConcat { separator: None }, expr, distinct: true } } /
             i("GROUP_CONCAT") _ "(" _ expr:Expression() _ ";" _ i("SEPARATOR") _ "=" _ s:String() _ ")" { AggregateExpression::FunctionCall { name: AggregateFunction::GroupConcat { separator: Some(s) }, expr, distinct: true } } /
-            i("GROUP_CONCAT") _ "(" _ expr:Expression() _ ")" { AggregateExpression::FunctionCall { name: AggregateFunction::GroupConcat { separator: None }, expr, distinct: false } } /
-            name:iri() _ "(" _ i("DISTINCT") _ expr:Expression() _ ")" { AggregateExpression::FunctionCall { name: AggregateFunction::Custom(name), expr, distinct: true } } /
-            name:iri() _ "(" _ expr:Expression() _ ")" { AggregateExpression::FunctionCall { name: AggregateFunction::Custom(name), expr, distinct: false } }
+            i("GROUP_CONCAT") _ "(" _ expr:Expression() _ ")" { AggregateExpression::FunctionCall { name: AggregateFunction::GroupConcat { separator: None }, expr, distinct: false } }
+        rule CustomAggregate() -> Expression = name:CustomAggregateName() _ "(" _ i("DISTINCT") _ expr:Expression() _ ")" {?
+            state.new_aggregation(AggregateExpression::FunctionCall { name: AggregateFunction::Custom(name), expr, distinct: true }).map(Into::into)
+        }
+        rule CustomAggregateName() -> NamedNode = name:iri() {?
+            if state.function_definitions.iter().any(|d| *d.name() == name) {
+                Err("The defined function is called with a wrong number of arguments")
+            } else {
+                Ok(name)
+            }
+        }
 
-        rule iriOrFunction() -> Expression = i: iri() _ a: ArgList()? {
+        rule iriOrFunction() -> Expression = CustomAggregate() / i: iri() _ a: ArgList()? {?
             match a {
-                Some(a) => Expression::FunctionCall(Function::Custom(i), a),
-                None => i.into()
+                Some(a) => state.function_call(i, a),
+                None => Ok(i.into())
             }
         }
 
@@ -2090,3 +2350,145 @@ parser! {
         }
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::panic)]
+mod tests {
+    use super::*;
+
+    fn parse_aggregates(query: &str) -> Vec<AggregateExpression> {
+        let Query::Select { pattern: root, .. } = parse_query(query, None).unwrap() else {
+            panic!("{query} is not a SELECT query")
+        };
+        let mut pattern = &root;
+        loop {
+            match pattern {
+                GraphPattern::Project { inner, .. }
+                | GraphPattern::Extend { inner, .. }
+                | GraphPattern::Filter { inner, .. }
+                | GraphPattern::OrderBy { inner, .. } => pattern = inner,
+                GraphPattern::Group { aggregates, .. } => {
+                    return aggregates.iter().map(|(_, a)| a.clone()).collect()
+                }
+                _ => return Vec::new(),
+            }
+        }
+    }
+
+    fn custom_aggregate(distinct: bool) -> AggregateExpression {
+        AggregateExpression::FunctionCall {
+            name: AggregateFunction::Custom(NamedNode::new_unchecked("http://example.com/agg")),
+            expr: Variable::new_unchecked("v").into(),
+            distinct,
+        }
+    }
+
+    #[test]
+    fn custom_aggregate_with_group_by() {
+        assert_eq!(
+            parse_aggregates(
+                "SELECT ?g (<http://example.com/agg>(?v) AS ?c) WHERE { ?g ?p ?v } GROUP BY ?g"
+            ),
+            [custom_aggregate(false)]
+        );
+        assert_eq!(
+            parse_aggregates(
+                "SELECT ?g WHERE { ?g ?p ?v } GROUP BY ?g HAVING(<http://example.com/agg>(?v))"
+            ),
+            [custom_aggregate(false)]
+        );
+        assert_eq!(
+            parse_aggregates(
+                "SELECT ?g WHERE { ?g ?p ?v } GROUP BY ?g ORDER BY DESC(<http://example.com/agg>(?v))"
+            ),
+            [custom_aggregate(false)]
+        );
+    }
+
+    #[test]
+    fn custom_aggregate_with_other_aggregate() {
+        assert_eq!(
+            parse_aggregates(
+                "SELECT (COUNT(*) AS ?n) (STR(<http://example.com/agg>(?v)) AS ?c) WHERE { ?s ?p ?v }"
+            ),
+            [
+                AggregateExpression::CountSolutions { distinct: false },
+                custom_aggregate(false)
+            ]
+        );
+    }
+
+    #[test]
+    fn custom_aggregate_with_distinct() {
+        assert_eq!(
+            parse_aggregates(
+                "SELECT (<http://example.com/agg>(DISTINCT ?v) AS ?c) WHERE { ?s ?p ?v }"
+            ),
+            [custom_aggregate(true)]
+        );
+        assert_eq!(
+            parse_aggregates(
+                "SELECT ?s WHERE { ?s ?p ?v } GROUP BY ?s HAVING <http://example.com/agg>(DISTINCT ?v)"
+            ),
+            [custom_aggregate(true)]
+        );
+        parse_query(
+            "SELECT * WHERE { ?s ?p ?v FILTER(<http://example.com/agg>(DISTINCT ?v)) }",
+            None,
+        )
+        .unwrap_err();
+    }
+
+    #[test]
+    fn custom_function_is_not_an_aggregate() {
+        // Not a grouped query
+        assert_eq!(
+            parse_aggregates("SELECT (<http://example.com/agg>(?v) AS ?c) WHERE { ?s ?p ?v }"),
+            []
+        );
+        // Only group keys as argument
+        assert_eq!(
+            parse_aggregates(
+                "SELECT (<http://example.com/agg>(?g) AS ?c) WHERE { ?g ?p ?v } GROUP BY ?g"
+            ),
+            []
+        );
+        // More than one argument
+        parse_query(
+            "SELECT (<http://example.com/agg>(?v, 1) AS ?c) WHERE { ?g ?p ?v } GROUP BY ?g",
+            None,
+        )
+        .unwrap_err();
+    }
+
+    #[test]
+    fn parse_and_lint() {
+        let parser = SparqlParser::new()
+            .with_base_iri("http://example.com/")
+            .unwrap();
+        let text = "SELECT ?s ?unbound WHERE { ?s <p> ?o . ?s2 <p> ?o2 }";
+        let (query, warnings) = parser.parse_and_lint(text).unwrap();
+        assert_eq!(
+            query,
+            parse_query(text, Some("http://example.com/")).unwrap()
+        );
+        assert_eq!(warnings, query.lint());
+        assert!(warnings.contains(&QueryWarning::UnboundProjectedVariable {
+            variable: Variable::new_unchecked("unbound")
+        }));
+        assert!(warnings
+            .iter()
+            .any(|warning| matches!(warning, QueryWarning::CartesianProduct { .. })));
+
+        let (_, warnings) = parser
+            .parse_and_lint("SELECT ?s WHERE { ?s <p> ?o . ?o <q> ?s }")
+            .unwrap();
+        assert_eq!(warnings, []);
+
+        parser.parse_and_lint("SELECT ?s WHERE {").unwrap_err();
+        SparqlParser::new()
+            .parse_and_lint("SELECT * WHERE { ?s <p> ?o }")
+            .unwrap_err();
+        SparqlParser::new().with_base_iri("not an IRI").unwrap_err();
+    }
+}
@@ -1,4 +1,6 @@
 use crate::algebra::*;
+use crate::analysis::{query_lints, query_warnings, QueryWarning};
+use crate::function::{expand_pattern, FunctionDefinition};
 use crate::parser::{parse_query, SparqlSyntaxError};
 use crate::term::*;
 use oxiri::Iri;
@@ -97,6 +99,62 @@ impl Query {
         }
     }
 
+    /// Looks for likely mistakes in the query like non-well-designed `OPTIONAL`s, cartesian products or unbound selected variables.
+    ///
+    /// ```
+    /// use spargebra::{Query, QueryWarning};
+    ///
+    /// let query = Query::parse(
+    ///     "SELECT ?s ?o WHERE { ?s ?p ?o . ?a ?b ?c }",
+    ///     None,
+    /// )?;
+    /// assert!(matches!(
+    ///     query.warnings().as_slice(),
+    ///     [QueryWarning::CartesianProduct { .. }]
+    /// ));
+    /// # Ok::<_, spargebra::SparqlSyntaxError>(())
+    /// ```
+    pub fn warnings(&self) -> Vec<QueryWarning> {
+        query_warnings(self)
+    }
+
+    /// Returns the [`warnings`](Self::warnings) of the query and also the findings of stricter checks that are more likely to be false positives,
+    /// like the variables only used once.
+    ///
+    /// ```
+    /// use spargebra::Query;
+    ///
+    /// let query = Query::parse(
+    ///     "SELECT ?name WHERE { ?s <http://schema.org/name> ?name ; <http://schema.org/age> ?age FILTER(?agee > 18 && STRLEN(?name) > \"2\") }",
+    ///     None,
+    /// )?;
+    /// assert_eq!(
+    ///     query.lint().iter().map(ToString::to_string).collect::<Vec<_>>(),
+    ///     [
+    ///         "The FILTER using ?agee is applied to a pattern that does not bind ?agee",
+    ///         "The comparison (STRLEN(?name) > \"2\") is between values of incompatible types, it is always false or an error",
+    ///         "The variable ?age is only used once",
+    ///         "The variable ?agee is only used once"
+    ///     ]
+    /// );
+    /// # Ok::<_, spargebra::SparqlSyntaxError>(())
+    /// ```
+    pub fn lint(&self) -> Vec<QueryWarning> {
+        query_lints(self)
+    }
+
+    /// Expands the calls to the given functions.
+    ///
+    /// The calls with a number of arguments different from the number of the function parameters are kept as is.
+    pub fn expand_functions(&mut self, definitions: &[FunctionDefinition]) {
+        match self {
+            Self::Select { pattern, .. }
+            | Self::Construct { pattern, .. }
+            | Self::Describe { pattern, .. }
+            | Self::Ask { pattern, .. } => expand_pattern(pattern, definitions),
+        }
+    }
+
     /// Formats using the [SPARQL S-Expression syntax](https://jena.apache.org/documentation/notes/sse.html).
     pub fn to_sse(&self) -> String {
         let mut buffer = String::new();
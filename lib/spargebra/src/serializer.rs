@@ -0,0 +1,647 @@
+use crate::algebra::{AggregateExpression, Expression, GraphPattern, OrderExpression};
+use crate::query::Query;
+use oxiri::{Iri, IriParseError};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A configurable serializer of [`Query`] to the SPARQL syntax.
+///
+/// Contrary to the [`Display`](std::fmt::Display) implementation of [`Query`] that writes the query on a single line with full IRIs,
+/// it can pretty-print the query with indentation, fold IRIs into prefixed names,
+/// and normalize the query to get the same text for queries only different by the order of their triple patterns.
+///
+/// ```
+/// use spargebra::{Query, QuerySerializer};
+///
+/// let query = Query::parse(
+///     "SELECT ?s WHERE { ?s <http://schema.org/name> ?name OPTIONAL { ?s <http://schema.org/age> ?age } }",
+///     None,
+/// )?;
+/// let serializer = QuerySerializer::new().with_prefix("schema", "http://schema.org/")?;
+/// assert_eq!(
+///     serializer.serialize_to_string(&query),
+///     "PREFIX schema: <http://schema.org/>\nSELECT ?s WHERE {\n  ?s schema:name ?name .\n  OPTIONAL {\n    ?s schema:age ?age .\n  }\n}"
+/// );
+/// assert_eq!(
+///     serializer.single_line().with_lowercase_keywords().serialize_to_string(&query),
+///     "prefix schema: <http://schema.org/> select ?s where { ?s schema:name ?name . optional { ?s schema:age ?age . } }"
+/// );
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct QuerySerializer {
+    prefixes: BTreeMap<String, Iri<String>>,
+    indentation: Option<usize>,
+    normalize: bool,
+    lowercase_keywords: bool,
+}
+
+impl Default for QuerySerializer {
+    fn default() -> Self {
+        Self {
+            prefixes: BTreeMap::new(),
+            indentation: Some(2),
+            normalize: false,
+            lowercase_keywords: false,
+        }
+    }
+}
+
+impl QuerySerializer {
+    /// Builds a new [`QuerySerializer`] that pretty-prints with an indentation of 2 spaces.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Folds the IRIs starting with `prefix_iri` into prefixed names.
+    ///
+    /// Only the prefixes actually used are declared in the output.
+    #[inline]
+    pub fn with_prefix(
+        mut self,
+        prefix_name: impl Into<String>,
+        prefix_iri: impl Into<String>,
+    ) -> Result<Self, IriParseError> {
+        self.prefixes
+            .insert(prefix_name.into(), Iri::parse(prefix_iri.into())?);
+        Ok(self)
+    }
+
+    /// Sets the number of spaces used to indent each group (2 by default).
+    #[inline]
+    pub fn with_indentation(mut self, indentation: usize) -> Self {
+        self.indentation = Some(indentation);
+        self
+    }
+
+    /// Writes the query on a single line, for example to normalize queries written to logs.
+    #[inline]
+    pub fn single_line(mut self) -> Self {
+        self.indentation = None;
+        self
+    }
+
+    /// Sorts the triple patterns of the basic graph patterns and of the `CONSTRUCT` template.
+    ///
+    /// The order of these triple patterns does not change the query results.
+    ///
+    /// ```
+    /// use spargebra::{Query, QuerySerializer};
+    ///
+    /// let serializer = QuerySerializer::new().single_line().normalized();
+    /// assert_eq!(
+    ///     serializer.serialize_to_string(&Query::parse("SELECT * WHERE { ?s <http://example.com/b> ?o ; <http://example.com/a> ?o }", None)?),
+    ///     serializer.serialize_to_string(&Query::parse("SELECT * WHERE { ?s <http://example.com/a> ?o ; <http://example.com/b> ?o }", None)?)
+    /// );
+    /// # Ok::<_, spargebra::SparqlSyntaxError>(())
+    /// ```
+    #[inline]
+    pub fn normalized(mut self) -> Self {
+        self.normalize = true;
+        self
+    }
+
+    /// Writes the keywords and the built-in function names in lower case instead of upper case.
+    #[inline]
+    pub fn with_lowercase_keywords(mut self) -> Self {
+        self.lowercase_keywords = true;
+        self
+    }
+
+    /// Serializes the query.
+    pub fn serialize_to_string(&self, query: &Query) -> String {
+        let text = if self.normalize {
+            let mut query = query.clone();
+            normalize_query(&mut query);
+            query.to_string()
+        } else {
+            query.to_string()
+        };
+        // The base IRI is written on the first line
+        let body = if query.base_iri().is_some() {
+            text.split_once('\n')
+                .map_or(text.as_str(), |(_, body)| body)
+        } else {
+            text.as_str()
+        };
+        let mut writer = QueryWriter {
+            serializer: self,
+            output: String::new(),
+            used_prefixes: BTreeSet::new(),
+            depth: 0,
+            line_start: true,
+            pending_space: false,
+            break_after_group: false,
+            in_values: false,
+            values_depth: None,
+            last: Last::None,
+        };
+        writer.write_body(body);
+
+        let separator = if self.indentation.is_some() {
+            "\n"
+        } else {
+            " "
+        };
+        let mut output = String::new();
+        if let Some(base_iri) = query.base_iri() {
+            output.push_str(&self.keyword("BASE"));
+            output.push_str(" <");
+            output.push_str(base_iri.as_str());
+            output.push('>');
+            output.push_str(separator);
+        }
+        for prefix_name in writer.used_prefixes {
+            if let Some(prefix_iri) = self.prefixes.get(prefix_name) {
+                output.push_str(&self.keyword("PREFIX"));
+                output.push(' ');
+                output.push_str(prefix_name);
+                output.push_str(": <");
+                output.push_str(prefix_iri.as_str());
+                output.push('>');
+                output.push_str(separator);
+            }
+        }
+        output.push_str(&writer.output);
+        output
+    }
+
+    fn keyword(&self, keyword: &str) -> String {
+        if self.lowercase_keywords {
+            keyword.to_ascii_lowercase()
+        } else {
+            keyword.to_owned()
+        }
+    }
+}
+
+/// The kind of the last token written
+#[derive(PartialEq, Eq)]
+enum Last {
+    None,
+    Keyword,
+    Term,
+    Punctuation(u8),
+}
+
+/// Rewrites the single line query syntax produced by the [`Display`](std::fmt::Display) implementations
+#[allow(clippy::struct_excessive_bools)]
+struct QueryWriter<'a> {
+    serializer: &'a QuerySerializer,
+    output: String,
+    used_prefixes: BTreeSet<&'a str>,
+    depth: usize,
+    line_start: bool,
+    pending_space: bool,
+    break_after_group: bool,
+    in_values: bool,
+    values_depth: Option<usize>,
+    last: Last,
+}
+
+impl<'a> QueryWriter<'a> {
+    fn write_body(&mut self, body: &str) {
+        let bytes = body.as_bytes();
+        let mut i = 0;
+        while let Some(&c) = bytes.get(i) {
+            if c.is_ascii_whitespace() {
+                self.pending_space = true;
+                i += 1;
+                continue;
+            }
+            let end = token_end(bytes, i);
+            let token = &body[i..end];
+            i = end;
+            if self.break_after_group {
+                self.break_after_group = false;
+                if !matches!(token, ")" | "," | "UNION" | "WHERE") {
+                    self.new_line();
+                }
+            }
+            match c {
+                b'{' => {
+                    if self.last == Last::Punctuation(b')') && !self.in_values {
+                        // A group following a BIND or a FILTER
+                        self.new_line();
+                    }
+                    self.write(token);
+                    self.depth += 1;
+                    if self.in_values {
+                        self.in_values = false;
+                        self.values_depth = Some(self.depth);
+                    }
+                    self.new_line();
+                }
+                b'}' => {
+                    if self.values_depth == Some(self.depth) {
+                        self.values_depth = None;
+                    }
+                    self.depth = self.depth.saturating_sub(1);
+                    self.new_line();
+                    self.write(token);
+                    self.break_after_group = true;
+                }
+                b'.' => {
+                    self.write(token);
+                    self.new_line();
+                }
+                b'(' => {
+                    if self.values_depth == Some(self.depth) && self.last == Last::Punctuation(b')')
+                    {
+                        // A new row of VALUES
+                        self.new_line();
+                    }
+                    self.write(token);
+                }
+                b'<' if token.len() > 1 && token != "<<" && token != "<=" => {
+                    self.write_iri(&token[1..token.len() - 1]);
+                    self.last = Last::Term;
+                    continue;
+                }
+                _ if c.is_ascii_alphabetic() => {
+                    match token {
+                        "FILTER" | "BIND" | "OPTIONAL" | "MINUS" | "GRAPH" | "SERVICE"
+                        | "LATERAL" | "VALUES"
+                            if self.depth > 0 =>
+                        {
+                            self.new_line()
+                        }
+                        "GROUP" | "HAVING" | "ORDER" | "OFFSET" | "LIMIT" => self.new_line(),
+                        _ => (),
+                    }
+                    if token == "VALUES" {
+                        self.in_values = true;
+                    }
+                    let keyword = self.serializer.keyword(token);
+                    self.write(&keyword);
+                    self.last = Last::Keyword;
+                    continue;
+                }
+                _ => self.write(token),
+            }
+            self.last = match token.as_bytes() {
+                [b'?' | b'$', _, ..] | [b'"' | b'_' | b'0'..=b'9', ..] => Last::Term,
+                [c, ..] => Last::Punctuation(*c),
+                [] => Last::None,
+            };
+        }
+    }
+
+    fn write_iri(&mut self, iri: &str) {
+        let prefix = self
+            .serializer
+            .prefixes
+            .iter()
+            .filter(|(_, prefix_iri)| {
+                iri.strip_prefix(prefix_iri.as_str())
+                    .is_some_and(is_valid_local_name)
+            })
+            .max_by_key(|(_, prefix_iri)| prefix_iri.as_str().len());
+        if let Some((prefix_name, prefix_iri)) = prefix {
+            self.used_prefixes.insert(prefix_name);
+            self.write(&format!(
+                "{prefix_name}:{}",
+                &iri[prefix_iri.as_str().len()..]
+            ));
+        } else {
+            self.write(&format!("<{iri}>"));
+        }
+    }
+
+    fn write(&mut self, token: &str) {
+        if self.line_start {
+            if let Some(indentation) = self.serializer.indentation {
+                for _ in 0..self.depth * indentation {
+                    self.output.push(' ');
+                }
+            }
+            self.line_start = false;
+        } else if self.pending_space {
+            self.output.push(' ');
+        }
+        self.pending_space = false;
+        self.output.push_str(token);
+    }
+
+    fn new_line(&mut self) {
+        if self.line_start {
+            return;
+        }
+        if self.serializer.indentation.is_some() {
+            self.output.push('\n');
+            self.line_start = true;
+        }
+        self.pending_space = true;
+    }
+}
+
+/// Returns the end of the token starting at `start`
+fn token_end(bytes: &[u8], start: usize) -> usize {
+    let next = |i: usize| bytes.get(i).copied();
+    let scan = |mut i: usize, f: fn(u8) -> bool| {
+        while next(i).is_some_and(f) {
+            i += 1;
+        }
+        i
+    };
+    match bytes[start] {
+        b'<' => match next(start + 1) {
+            Some(b'<' | b'=') => start + 2,
+            Some(b' ') | None => start + 1,
+            Some(_) => (scan(start + 1, |c| c != b'>') + 1).min(bytes.len()),
+        },
+        b'"' => {
+            let mut i = start + 1;
+            while let Some(c) = next(i) {
+                i += if c == b'\\' { 2 } else { 1 };
+                if c == b'"' {
+                    break;
+                }
+            }
+            if next(i) == Some(b'@') {
+                i = scan(i + 1, |c| c.is_ascii_alphanumeric() || c == b'-');
+            }
+            i
+        }
+        b'?' | b'$' if next(start + 1).is_some_and(is_name_char) => scan(start + 1, is_name_char),
+        b'_' if next(start + 1) == Some(b':') => {
+            let mut i = scan(start + 2, |c| is_name_char(c) || c == b'-' || c == b'.');
+            while bytes[i - 1] == b'.' {
+                i -= 1;
+            }
+            i
+        }
+        c if c.is_ascii_alphabetic() => scan(start, |c| c.is_ascii_alphanumeric() || c == b'_'),
+        c if c.is_ascii_digit() => scan(start, |c| c.is_ascii_digit()),
+        _ => start + 1,
+    }
+}
+
+fn is_name_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_' || !c.is_ascii()
+}
+
+/// Conservative subset of the SPARQL `PN_LOCAL` production that does not require escaping
+fn is_valid_local_name(local_name: &str) -> bool {
+    let mut chars = local_name.chars();
+    let Some(first) = chars.next() else {
+        return true;
+    };
+    (first.is_ascii_alphanumeric() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        && !local_name.ends_with('.')
+}
+
+fn normalize_query(query: &mut Query) {
+    match query {
+        Query::Construct {
+            template, pattern, ..
+        } => {
+            template.sort_by_cached_key(ToString::to_string);
+            normalize_pattern(pattern);
+        }
+        Query::Select { pattern, .. }
+        | Query::Describe { pattern, .. }
+        | Query::Ask { pattern, .. } => normalize_pattern(pattern),
+    }
+}
+
+fn normalize_pattern(pattern: &mut GraphPattern) {
+    match pattern {
+        GraphPattern::Bgp { patterns } => patterns.sort_by_cached_key(ToString::to_string),
+        GraphPattern::Path { .. } | GraphPattern::Values { .. } => (),
+        GraphPattern::Join { left, right }
+        | GraphPattern::Union { left, right }
+        | GraphPattern::Minus { left, right } => {
+            normalize_pattern(left);
+            normalize_pattern(right);
+        }
+        #[cfg(feature = "sep-0006")]
+        GraphPattern::Lateral { left, right } => {
+            normalize_pattern(left);
+            normalize_pattern(right);
+        }
+        GraphPattern::LeftJoin {
+            left,
+            right,
+            expression,
+        } => {
+            normalize_pattern(left);
+            normalize_pattern(right);
+            if let Some(expression) = expression {
+                normalize_expression(expression);
+            }
+        }
+        GraphPattern::Filter { expr, inner } => {
+            normalize_expression(expr);
+            normalize_pattern(inner);
+        }
+        GraphPattern::Extend {
+            inner, expression, ..
+        } => {
+            normalize_pattern(inner);
+            normalize_expression(expression);
+        }
+        GraphPattern::OrderBy { inner, expression } => {
+            normalize_pattern(inner);
+            for expression in expression {
+                match expression {
+                    OrderExpression::Asc(e) | OrderExpression::Desc(e) => normalize_expression(e),
+                }
+            }
+        }
+        GraphPattern::Group {
+            inner, aggregates, ..
+        } => {
+            normalize_pattern(inner);
+            for (_, aggregate) in aggregates {
+                match aggregate {
+                    AggregateExpression::CountSolutions { .. } => (),
+                    AggregateExpression::FunctionCall { expr, .. } => normalize_expression(expr),
+                }
+            }
+        }
+        GraphPattern::Graph { inner, .. }
+        | GraphPattern::Project { inner, .. }
+        | GraphPattern::Distinct { inner }
+        | GraphPattern::Reduced { inner }
+        | GraphPattern::Slice { inner, .. }
+        | GraphPattern::Service { inner, .. } => normalize_pattern(inner),
+    }
+}
+
+fn normalize_expression(expression: &mut Expression) {
+    match expression {
+        Expression::NamedNode(_)
+        | Expression::Literal(_)
+        | Expression::Variable(_)
+        | Expression::Bound(_) => (),
+        Expression::Or(a, b)
+        | Expression::And(a, b)
+        | Expression::Equal(a, b)
+        | Expression::SameTerm(a, b)
+        | Expression::Greater(a, b)
+        | Expression::GreaterOrEqual(a, b)
+        | Expression::Less(a, b)
+        | Expression::LessOrEqual(a, b)
+        | Expression::Add(a, b)
+        | Expression::Subtract(a, b)
+        | Expression::Multiply(a, b)
+        | Expression::Divide(a, b) => {
+            normalize_expression(a);
+            normalize_expression(b);
+        }
+        Expression::UnaryPlus(e) | Expression::UnaryMinus(e) | Expression::Not(e) => {
+            normalize_expression(e)
+        }
+        Expression::If(a, b, c) => {
+            normalize_expression(a);
+            normalize_expression(b);
+            normalize_expression(c);
+        }
+        Expression::In(a, l) => {
+            normalize_expression(a);
+            for e in l {
+                normalize_expression(e);
+            }
+        }
+        Expression::Coalesce(l) | Expression::FunctionCall(_, l) => {
+            for e in l {
+                normalize_expression(e);
+            }
+        }
+        Expression::Exists(p) => normalize_pattern(p),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUERIES: [&str; 16] = [
+        "SELECT ?s WHERE { ?s <http://example.com/p> ?o OPTIONAL { ?o <http://example.com/q> ?v FILTER(?v > 2) } }",
+        "SELECT DISTINCT ?s ?o WHERE { { ?s <http://example.com/p> ?o } UNION { ?o <http://example.com/p> ?s } MINUS { ?s a <http://example.com/C> } } ORDER BY DESC(?s) ?o OFFSET 2 LIMIT 10",
+        "SELECT REDUCED * WHERE { GRAPH ?g { ?s ?p ?o } BIND(STR(?o) AS ?str) FILTER(REGEX(?str, \"^a.b{2}\", \"i\")) }",
+        "SELECT * WHERE { VALUES (?s ?o) { (<http://example.com/a> 1) (UNDEF \"x\") } ?s ?p ?o }",
+        "SELECT * WHERE { VALUES ?s { <http://example.com/a> <http://example.com/b> } }",
+        "SELECT ?s WHERE { ?s <http://example.com/p> ?o } GROUP BY ?s HAVING(BOUND(?s))",
+        "SELECT * WHERE { ?s <http://example.com/p>/<http://example.com/q>* | ^<http://example.com/r> ?o . ?o !(<http://example.com/a>|^<http://example.com/b>) ?v }",
+        "SELECT * WHERE { ?s <http://example.com/p> \"a { b } . c ; FILTER\", \"chat\"@en-US, \"x\\\"y\"@fr, \"1.5\"^^<http://www.w3.org/2001/XMLSchema#decimal>, -12, 1.5, true }",
+        "SELECT * WHERE { ?s <http://example.com/p> ?o FILTER(?o <= 3 && (?o >= 1 || ?o != 2) && ?o IN (1, 2) && !EXISTS { ?o ?p ?s }) }",
+        "SELECT * WHERE { { SELECT ?s WHERE { ?s ?p ?o } LIMIT 1 } ?s <http://example.com/p> ?v }",
+        "SELECT * WHERE { SERVICE SILENT <http://example.com/sparql> { ?s ?p ?o } }",
+        "SELECT ?v WHERE { ?s <http://example.com/p> ?o BIND(COALESCE(IF(?o < 1, ?o, 0), <http://example.com/f>(?o, \"a\")) AS ?v) }",
+        "CONSTRUCT { ?s <http://example.com/q> ?o . ?o a <http://example.com/C> } FROM <http://example.com/g> WHERE { ?s <http://example.com/p> ?o }",
+        "DESCRIBE ?s FROM NAMED <http://example.com/g> WHERE { GRAPH <http://example.com/g> { ?s ?p ?o } }",
+        "ASK { <http://example.com/a> <http://example.com/p> ?o }",
+        "BASE <http://example.com/> SELECT * WHERE { <a> <p> ?o }",
+    ];
+
+    fn serializers() -> Vec<QuerySerializer> {
+        vec![
+            QuerySerializer::new(),
+            QuerySerializer::new().with_indentation(4),
+            QuerySerializer::new().single_line(),
+            QuerySerializer::new().with_lowercase_keywords(),
+            QuerySerializer::new().normalized(),
+            QuerySerializer::new()
+                .with_prefix("ex", "http://example.com/")
+                .unwrap()
+                .with_prefix("xsd", "http://www.w3.org/2001/XMLSchema#")
+                .unwrap(),
+            QuerySerializer::new()
+                .single_line()
+                .with_lowercase_keywords()
+                .with_prefix("ex", "http://example.com/")
+                .unwrap(),
+        ]
+    }
+
+    #[test]
+    fn round_trip() {
+        for query in QUERIES {
+            let query = Query::parse(query, None).unwrap();
+            let mut normalized = query.clone();
+            normalize_query(&mut normalized);
+            for serializer in serializers() {
+                // The Display implementation is the reference serialization
+                let expected = Query::parse(
+                    &if serializer.normalize {
+                        &normalized
+                    } else {
+                        &query
+                    }
+                    .to_string(),
+                    None,
+                )
+                .unwrap();
+                let serialization = serializer.serialize_to_string(&query);
+                assert_eq!(
+                    Query::parse(&serialization, None).unwrap(),
+                    expected,
+                    "{serializer:?}\n{serialization}"
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "rdf-star")]
+    #[test]
+    fn quoted_triples_round_trip() {
+        let query = Query::parse(
+            "SELECT * WHERE { << ?s <http://example.com/p> ?o >> <http://example.com/q> ?v FILTER(?v <= 1) }",
+            None,
+        )
+        .unwrap();
+        for serializer in serializers() {
+            let serialization = serializer.serialize_to_string(&query);
+            assert_eq!(
+                Query::parse(&serialization, None).unwrap(),
+                Query::parse(&query.to_string(), None).unwrap(),
+                "{serialization}"
+            );
+        }
+    }
+
+    #[test]
+    fn normalized_round_trip() {
+        let serializer = QuerySerializer::new().normalized();
+        let query = Query::parse(
+            "CONSTRUCT { ?s <http://example.com/b> ?o . ?s <http://example.com/a> ?o } WHERE { ?s <http://example.com/b> ?o ; <http://example.com/a> ?o }",
+            None,
+        )
+        .unwrap();
+        let expected = Query::parse(
+            "CONSTRUCT { ?s <http://example.com/a> ?o . ?s <http://example.com/b> ?o } WHERE { ?s <http://example.com/a> ?o ; <http://example.com/b> ?o }",
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            Query::parse(&serializer.serialize_to_string(&query), None).unwrap(),
+            Query::parse(&expected.to_string(), None).unwrap()
+        );
+    }
+
+    #[test]
+    fn prefixes() {
+        let serializer = QuerySerializer::new()
+            .single_line()
+            .with_prefix("ex", "http://example.com/")
+            .unwrap()
+            .with_prefix("exn", "http://example.com/ns#")
+            .unwrap()
+            .with_prefix("unused", "http://example.org/")
+            .unwrap();
+        let query = Query::parse(
+            "SELECT * WHERE { <http://example.com/a> <http://example.com/ns#p> <http://example.com/a/b> }",
+            None,
+        )
+        .unwrap();
+        // The longest prefix is used, the local names that would need escaping are kept as IRIs
+        assert_eq!(
+            serializer.serialize_to_string(&query),
+            "PREFIX ex: <http://example.com/> PREFIX exn: <http://example.com/ns#> SELECT * WHERE { ex:a exn:p <http://example.com/a/b> . }"
+        );
+    }
+}
@@ -0,0 +1,87 @@
+//! Transformation of the geometries expressed in an [EPSG](https://epsg.org/) reference system into CRS84.
+
+use geo::{Coord, CoordsIter, Geometry, MapCoords};
+use proj4rs::transform::transform;
+use proj4rs::Proj;
+
+const EPSG_PREFIX: &str = "http://www.opengis.net/def/crs/EPSG/0/";
+
+/// The CRS84 reference system: WGS 84 with the longitude first
+const CRS84: &str = "+proj=longlat +datum=WGS84 +no_defs";
+
+/// Converts a geometry in the reference system with the given IRI into CRS84
+///
+/// Returns `None` if the reference system is not a known EPSG one.
+pub fn to_crs84(system: &str, geometry: Geometry) -> Option<Geometry> {
+    let code = system.strip_prefix(EPSG_PREFIX)?.parse::<u16>().ok()?;
+    let source = Proj::from_epsg_code(code).ok()?;
+    let target = Proj::from_proj_string(CRS84).ok()?;
+    // proj4rs works with radians and ignores the EPSG axis order that is latitude first for geographic systems
+    let mut geometry = if source.is_latlong() {
+        geometry.map_coords(|Coord { x, y }| Coord {
+            x: y.to_radians(),
+            y: x.to_radians(),
+        })
+    } else {
+        geometry
+    };
+    transform(&source, &target, &mut geometry).ok()?;
+    let geometry = geometry.map_coords(|Coord { x, y }| Coord {
+        x: x.to_degrees(),
+        y: y.to_degrees(),
+    });
+    geometry
+        .coords_iter()
+        .all(|Coord { x, y }| x.is_finite() && y.is_finite())
+        .then_some(geometry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::Point;
+
+    fn to_crs84_point(system: &str, x: f64, y: f64) -> Option<(f64, f64)> {
+        let Geometry::Point(point) = to_crs84(system, Point::new(x, y).into())? else {
+            return None;
+        };
+        Some(point.x_y())
+    }
+
+    fn assert_near((x, y): (f64, f64), (expected_x, expected_y): (f64, f64)) {
+        assert!(
+            (x - expected_x).abs() < 1e-6 && (y - expected_y).abs() < 1e-6,
+            "({x} {y}) is not ({expected_x} {expected_y})"
+        );
+    }
+
+    #[test]
+    fn test_geographic_axis_order() {
+        assert_near(
+            to_crs84_point("http://www.opengis.net/def/crs/EPSG/0/4326", 48.85, 2.35).unwrap(),
+            (2.35, 48.85),
+        );
+    }
+
+    #[test]
+    fn test_projected() {
+        assert_near(
+            to_crs84_point(
+                "http://www.opengis.net/def/crs/EPSG/0/3857",
+                111_319.490_793_273_57,
+                5_621_521.486_192_066,
+            )
+            .unwrap(),
+            (1., 45.),
+        );
+    }
+
+    #[test]
+    fn test_unknown_system() {
+        assert_eq!(to_crs84_point("http://example.com/crs", 1., 2.), None);
+        assert_eq!(
+            to_crs84_point("http://www.opengis.net/def/crs/EPSG/0/0", 1., 2.),
+            None
+        );
+    }
+}
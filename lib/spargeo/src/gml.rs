@@ -0,0 +1,247 @@
+//! Parser of the [GML 3.2](https://www.ogc.org/standard/gml/) geometries of `geo:gmlLiteral`.
+//!
+//! Only the `Point`, `LineString`, `Polygon`, `MultiPoint`, `MultiCurve`, `MultiSurface` and `MultiGeometry` elements are supported.
+
+use geo::{
+    Coord, Geometry, GeometryCollection, LineString, MultiLineString, MultiPoint, MultiPolygon,
+    Point, Polygon,
+};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::name::{Namespace, ResolveResult};
+use quick_xml::NsReader;
+
+const GML_NAMESPACE: ResolveResult<'static> =
+    ResolveResult::Bound(Namespace(b"http://www.opengis.net/gml/3.2"));
+
+/// The identifiers of the CRS84 reference system, the only one supported
+const CRS84: [&str; 3] = [
+    "http://www.opengis.net/def/crs/OGC/1.3/CRS84",
+    "urn:ogc:def:crs:OGC:1.3:CRS84",
+    "urn:ogc:def:crs:OGC::CRS84",
+];
+
+/// A GML element with the attributes relevant to geometries
+#[derive(Default)]
+struct Element {
+    local_name: String,
+    srs_name: Option<String>,
+    srs_dimension: Option<usize>,
+    children: Vec<Element>,
+    text: String,
+}
+
+pub fn parse_gml_literal(value: &str) -> Option<Geometry> {
+    geometry(&parse_element(value)?, 2)
+}
+
+/// Reads the XML tree of the literal, all elements must be in the GML namespace
+fn parse_element(value: &str) -> Option<Element> {
+    let mut reader = NsReader::from_str(value);
+    reader.config_mut().expand_empty_elements = true;
+    let mut stack = Vec::<Element>::new();
+    loop {
+        match reader.read_resolved_event().ok()? {
+            (namespace, Event::Start(start)) => {
+                if namespace != GML_NAMESPACE {
+                    return None;
+                }
+                stack.push(start_element(&start)?);
+            }
+            (_, Event::End(_)) => {
+                let element = stack.pop()?;
+                let Some(parent) = stack.last_mut() else {
+                    return Some(element);
+                };
+                parent.children.push(element);
+            }
+            (_, Event::Text(text)) => {
+                let text = text.unescape().ok()?;
+                if let Some(current) = stack.last_mut() {
+                    current.text.push_str(&text);
+                } else if !text.trim().is_empty() {
+                    return None; // Text outside of the root element
+                }
+            }
+            (_, Event::CData(_) | Event::Eof) => return None,
+            _ => (), // XML declaration, comments and processing instructions
+        }
+    }
+}
+
+fn start_element(start: &BytesStart<'_>) -> Option<Element> {
+    let mut element = Element {
+        local_name: String::from_utf8(start.local_name().as_ref().to_vec()).ok()?,
+        ..Element::default()
+    };
+    for attribute in start.attributes() {
+        let attribute = attribute.ok()?;
+        match attribute.key.as_ref() {
+            b"srsName" => element.srs_name = Some(attribute.unescape_value().ok()?.into_owned()),
+            b"srsDimension" => {
+                element.srs_dimension = Some(attribute.unescape_value().ok()?.trim().parse().ok()?)
+            }
+            _ => (),
+        }
+    }
+    Some(element)
+}
+
+/// Checks the element reference system and returns its coordinates dimension
+fn check_srs(element: &Element, parent_dimension: usize) -> Option<usize> {
+    if let Some(srs_name) = &element.srs_name {
+        if !CRS84.contains(&srs_name.as_str()) {
+            return None; // We only support CRS84
+        }
+    }
+    Some(element.srs_dimension.unwrap_or(parent_dimension))
+}
+
+fn geometry(element: &Element, dimension: usize) -> Option<Geometry> {
+    Some(match element.local_name.as_str() {
+        "Point" => point(element, dimension)?.into(),
+        "LineString" => line_string(element, dimension)?.into(),
+        "Polygon" => polygon(element, dimension)?.into(),
+        "MultiPoint" => MultiPoint::new(members(
+            element,
+            dimension,
+            "pointMember",
+            "pointMembers",
+            point,
+        )?)
+        .into(),
+        "MultiCurve" => MultiLineString::new(members(
+            element,
+            dimension,
+            "curveMember",
+            "curveMembers",
+            line_string,
+        )?)
+        .into(),
+        "MultiSurface" => MultiPolygon::new(members(
+            element,
+            dimension,
+            "surfaceMember",
+            "surfaceMembers",
+            polygon,
+        )?)
+        .into(),
+        "MultiGeometry" => Geometry::GeometryCollection(GeometryCollection::from(members(
+            element,
+            dimension,
+            "geometryMember",
+            "geometryMembers",
+            geometry,
+        )?)),
+        _ => return None,
+    })
+}
+
+fn point(element: &Element, dimension: usize) -> Option<Point> {
+    if element.local_name != "Point" {
+        return None;
+    }
+    let [coord] = positions(element, check_srs(element, dimension)?)?[..] else {
+        return None;
+    };
+    Some(coord.into())
+}
+
+fn line_string(element: &Element, dimension: usize) -> Option<LineString> {
+    if element.local_name != "LineString" {
+        return None;
+    }
+    let coords = positions(element, check_srs(element, dimension)?)?;
+    (coords.len() >= 2).then(|| coords.into())
+}
+
+fn polygon(element: &Element, dimension: usize) -> Option<Polygon> {
+    if element.local_name != "Polygon" {
+        return None;
+    }
+    let dimension = check_srs(element, dimension)?;
+    let mut exterior = None;
+    let mut interiors = Vec::new();
+    for child in &element.children {
+        let [ring] = child.children.as_slice() else {
+            return None;
+        };
+        let ring = linear_ring(ring, dimension)?;
+        match child.local_name.as_str() {
+            "exterior" if exterior.is_none() => exterior = Some(ring),
+            "interior" => interiors.push(ring),
+            _ => return None,
+        }
+    }
+    Some(Polygon::new(exterior?, interiors))
+}
+
+fn linear_ring(element: &Element, dimension: usize) -> Option<LineString> {
+    if element.local_name != "LinearRing" {
+        return None;
+    }
+    let coords = positions(element, check_srs(element, dimension)?)?;
+    (coords.len() >= 4 && coords.first() == coords.last()).then(|| coords.into())
+}
+
+/// Parses the members of a multi geometry, either wrapped one by one or all together
+fn members<T>(
+    element: &Element,
+    dimension: usize,
+    member_name: &str,
+    members_name: &str,
+    parse: impl Fn(&Element, usize) -> Option<T>,
+) -> Option<Vec<T>> {
+    let dimension = check_srs(element, dimension)?;
+    let mut members = Vec::new();
+    for child in &element.children {
+        if child.local_name == member_name {
+            let [member] = child.children.as_slice() else {
+                return None;
+            };
+            members.push(parse(member, dimension)?);
+        } else if child.local_name == members_name {
+            for member in &child.children {
+                members.push(parse(member, dimension)?);
+            }
+        } else {
+            return None;
+        }
+    }
+    Some(members)
+}
+
+/// Reads the coordinates from `gml:pos` or `gml:posList` elements
+fn positions(element: &Element, dimension: usize) -> Option<Vec<Coord>> {
+    let mut coords = Vec::new();
+    for child in &element.children {
+        let child_coords = coordinates(&child.text, child.srs_dimension.unwrap_or(dimension))?;
+        if child.local_name != "posList" && (child.local_name != "pos" || child_coords.len() != 1) {
+            return None;
+        }
+        coords.extend(child_coords);
+    }
+    Some(coords)
+}
+
+/// Parses a whitespace separated list of numbers, only the first two ones of each tuple are kept
+fn coordinates(text: &str, dimension: usize) -> Option<Vec<Coord>> {
+    if dimension < 2 {
+        return None;
+    }
+    let values = text
+        .split_ascii_whitespace()
+        .map(|value| value.parse::<f64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    if values.len() % dimension != 0 {
+        return None;
+    }
+    values
+        .chunks_exact(dimension)
+        .map(|tuple| {
+            Some(Coord {
+                x: *tuple.first()?,
+                y: *tuple.get(1)?,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,371 @@
+use crate::{extract_argument, geosparql, geosparql_functions};
+use geo::BoundingRect;
+use oxigraph::model::{Literal, NamedNodeRef, QuadRef, Term, TermRef};
+use oxigraph::sparql::QueryOptions;
+use oxigraph::store::{QuadChange, StorageError, Store};
+use rstar::primitives::{GeomWithData, Rectangle};
+use rstar::{RTree, AABB};
+use spargebra::algebra::{Expression, Function, GraphPattern};
+use spargebra::term::{GroundTerm, NamedNodePattern, TermPattern, Variable};
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// The predicates whose literal objects are indexed
+const INDEXED_PREDICATES: [NamedNodeRef<'static>; 3] =
+    [geosparql::AS_WKT, geosparql::AS_GEO_JSON, geosparql::AS_GML];
+
+/// The functions that may only return `true` if the bounding boxes of their arguments intersect
+const INTERSECTING_FUNCTIONS: [NamedNodeRef<'static>; 7] = [
+    geosparql_functions::SF_EQUALS,
+    geosparql_functions::SF_INTERSECTS,
+    geosparql_functions::SF_TOUCHES,
+    geosparql_functions::SF_CROSSES,
+    geosparql_functions::SF_WITHIN,
+    geosparql_functions::SF_CONTAINS,
+    geosparql_functions::SF_OVERLAPS,
+];
+
+type IndexedGeometry = GeomWithData<Rectangle<[f64; 2]>, Literal>;
+
+/// An [R-tree](https://en.wikipedia.org/wiki/R-tree) of the bounding boxes of the `geo:asWKT`, `geo:asGeoJSON` and `geo:asGML` literals of a [`Store`].
+///
+/// Once registered with [`register_spatial_index`], the filters like `FILTER(geof:sfIntersects(?g, "POINT(1 2)"^^geo:wktLiteral))`
+/// are evaluated only against the geometries whose bounding box intersects the constant one
+/// if `?g` is the object of a `geo:asWKT`, `geo:asGeoJSON` or `geo:asGML` triple pattern.
+/// `geof:sfEquals`, `geof:sfTouches`, `geof:sfCrosses`, `geof:sfWithin`, `geof:sfContains` and `geof:sfOverlaps` are also supported.
+///
+/// ```
+/// use oxigraph::model::*;
+/// use oxigraph::sparql::{QueryOptions, QueryResults};
+/// use oxigraph::store::Store;
+/// use spargeo::{register_geosparql_functions, register_spatial_index, SpatialIndex};
+///
+/// let store = Store::new()?;
+/// let index = SpatialIndex::for_store(&store)?;
+/// let options = register_spatial_index(
+///     register_geosparql_functions(QueryOptions::default()),
+///     index,
+/// );
+///
+/// store.update(
+///     "PREFIX geo: <http://www.opengis.net/ont/geosparql#>
+///     INSERT DATA {
+///         <http://example.com/a> geo:asWKT 'POINT(1 1)'^^geo:wktLiteral .
+///         <http://example.com/b> geo:asWKT 'POINT(10 10)'^^geo:wktLiteral
+///     }",
+/// )?;
+/// if let QueryResults::Solutions(solutions) = store.query_opt(
+///     "PREFIX geo: <http://www.opengis.net/ont/geosparql#>
+///     PREFIX geof: <http://www.opengis.net/def/function/geosparql/>
+///     SELECT ?s WHERE {
+///         ?s geo:asWKT ?g
+///         FILTER(geof:sfIntersects(?g, 'POLYGON((0 0, 2 0, 2 2, 0 2, 0 0))'^^geo:wktLiteral))
+///     }",
+///     options,
+/// )? {
+///     let solutions = solutions.collect::<Result<Vec<_>, _>>()?;
+///     assert_eq!(solutions.len(), 1);
+///     assert_eq!(
+///         solutions[0].get("s"),
+///         Some(&NamedNode::new("http://example.com/a")?.into())
+///     );
+/// }
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[derive(Default)]
+pub struct SpatialIndex {
+    content: RwLock<SpatialIndexContent>,
+}
+
+#[derive(Default)]
+struct SpatialIndexContent {
+    tree: RTree<IndexedGeometry>,
+    /// Number of indexed quads using each literal
+    counts: HashMap<Literal, usize>,
+}
+
+impl SpatialIndex {
+    /// Builds the index of the geometries of the store and keeps it up to date with the store transactions.
+    ///
+    /// <div class="warning">
+    ///
+    /// The loads done with the [`bulk_loader`](Store::bulk_loader) are not seen by the index.
+    /// Call [`rebuild`](Self::rebuild) after them.</div>
+    pub fn for_store(store: &Store) -> Result<Arc<Self>, StorageError> {
+        let index = Arc::new(Self::default());
+        // We register the listener before reading the store to not miss any change
+        let listener_index = Arc::clone(&index);
+        store.on_commit(move |changes| {
+            for change in changes {
+                match change {
+                    QuadChange::Insert(quad) => listener_index.insert(quad.as_ref()),
+                    QuadChange::Remove(quad) => listener_index.remove(quad.as_ref()),
+                    QuadChange::InsertNamedGraph(_) | QuadChange::RemoveNamedGraph(_) => (),
+                }
+            }
+        });
+        index.rebuild(store)?;
+        Ok(index)
+    }
+
+    /// Replaces the index content with the geometries currently in the store.
+    pub fn rebuild(&self, store: &Store) -> Result<(), StorageError> {
+        let mut content = self.content.write().unwrap_or_else(PoisonError::into_inner);
+        *content = SpatialIndexContent::default();
+        for predicate in INDEXED_PREDICATES {
+            for quad in store.quads_for_pattern(None, Some(predicate), None, None) {
+                content.insert(quad?.as_ref());
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds to the index the object of a `geo:asWKT`, `geo:asGeoJSON` or `geo:asGML` quad.
+    ///
+    /// The other quads are ignored.
+    pub fn insert(&self, quad: QuadRef<'_>) {
+        self.content
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(quad);
+    }
+
+    /// Removes from the index the object of a `geo:asWKT`, `geo:asGeoJSON` or `geo:asGML` quad.
+    ///
+    /// The other quads are ignored.
+    pub fn remove(&self, quad: QuadRef<'_>) {
+        self.content
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(quad);
+    }
+
+    /// Returns the indexed literals whose bounding box intersects the one of the given geometry literal.
+    ///
+    /// Returns `None` if the literal is not a supported geometry or is empty.
+    fn candidates(&self, literal: &Literal) -> Option<Vec<Literal>> {
+        let envelope = envelope(literal)?;
+        let content = self.content.read().unwrap_or_else(PoisonError::into_inner);
+        Some(
+            content
+                .tree
+                .locate_in_envelope_intersecting(&envelope)
+                .map(|geometry| geometry.data.clone())
+                .collect(),
+        )
+    }
+
+    /// Joins the patterns filtered by a spatial function with the candidate values from the index
+    fn rewrite(&self, pattern: GraphPattern) -> GraphPattern {
+        match pattern {
+            GraphPattern::Filter { expr, inner } => {
+                let mut inner = self.rewrite(*inner);
+                let mut constraints = Vec::new();
+                add_spatial_constraints(&expr, &mut constraints);
+                for (variable, literal) in constraints {
+                    if !binds_indexed_literal(&inner, variable) {
+                        continue;
+                    }
+                    let Some(candidates) = self.candidates(literal) else {
+                        continue;
+                    };
+                    inner = GraphPattern::Join {
+                        left: Box::new(inner),
+                        right: Box::new(GraphPattern::Values {
+                            variables: vec![variable.clone()],
+                            bindings: candidates
+                                .into_iter()
+                                .map(|candidate| vec![Some(GroundTerm::Literal(candidate))])
+                                .collect(),
+                        }),
+                    };
+                }
+                GraphPattern::Filter {
+                    expr,
+                    inner: Box::new(inner),
+                }
+            }
+            GraphPattern::Join { left, right } => GraphPattern::Join {
+                left: Box::new(self.rewrite(*left)),
+                right: Box::new(self.rewrite(*right)),
+            },
+            GraphPattern::LeftJoin {
+                left,
+                right,
+                expression,
+            } => GraphPattern::LeftJoin {
+                left: Box::new(self.rewrite(*left)),
+                right: Box::new(self.rewrite(*right)),
+                expression,
+            },
+            GraphPattern::Lateral { left, right } => GraphPattern::Lateral {
+                left: Box::new(self.rewrite(*left)),
+                right: Box::new(self.rewrite(*right)),
+            },
+            GraphPattern::Union { left, right } => GraphPattern::Union {
+                left: Box::new(self.rewrite(*left)),
+                right: Box::new(self.rewrite(*right)),
+            },
+            GraphPattern::Minus { left, right } => GraphPattern::Minus {
+                left: Box::new(self.rewrite(*left)),
+                right: Box::new(self.rewrite(*right)),
+            },
+            GraphPattern::Graph { name, inner } => GraphPattern::Graph {
+                name,
+                inner: Box::new(self.rewrite(*inner)),
+            },
+            GraphPattern::Extend {
+                inner,
+                variable,
+                expression,
+            } => GraphPattern::Extend {
+                inner: Box::new(self.rewrite(*inner)),
+                variable,
+                expression,
+            },
+            GraphPattern::OrderBy { inner, expression } => GraphPattern::OrderBy {
+                inner: Box::new(self.rewrite(*inner)),
+                expression,
+            },
+            GraphPattern::Project { inner, variables } => GraphPattern::Project {
+                inner: Box::new(self.rewrite(*inner)),
+                variables,
+            },
+            GraphPattern::Distinct { inner } => GraphPattern::Distinct {
+                inner: Box::new(self.rewrite(*inner)),
+            },
+            GraphPattern::Reduced { inner } => GraphPattern::Reduced {
+                inner: Box::new(self.rewrite(*inner)),
+            },
+            GraphPattern::Slice {
+                inner,
+                start,
+                length,
+            } => GraphPattern::Slice {
+                inner: Box::new(self.rewrite(*inner)),
+                start,
+                length,
+            },
+            GraphPattern::Group {
+                inner,
+                variables,
+                aggregates,
+            } => GraphPattern::Group {
+                inner: Box::new(self.rewrite(*inner)),
+                variables,
+                aggregates,
+            },
+            // The SERVICE calls are evaluated against other datasets
+            GraphPattern::Bgp { .. }
+            | GraphPattern::Path { .. }
+            | GraphPattern::Values { .. }
+            | GraphPattern::Service { .. } => pattern,
+        }
+    }
+}
+
+impl SpatialIndexContent {
+    fn insert(&mut self, quad: QuadRef<'_>) {
+        let Some(literal) = indexed_literal(quad) else {
+            return;
+        };
+        if let Some(count) = self.counts.get_mut(&literal) {
+            *count += 1;
+            return;
+        }
+        let Some(envelope) = envelope(&literal) else {
+            return; // Not a supported geometry
+        };
+        self.tree.insert(GeomWithData::new(
+            Rectangle::from_aabb(envelope),
+            literal.clone(),
+        ));
+        self.counts.insert(literal, 1);
+    }
+
+    fn remove(&mut self, quad: QuadRef<'_>) {
+        let Some(literal) = indexed_literal(quad) else {
+            return;
+        };
+        let Some(count) = self.counts.get_mut(&literal) else {
+            return;
+        };
+        *count -= 1;
+        if *count > 0 {
+            return;
+        }
+        self.counts.remove(&literal);
+        if let Some(envelope) = envelope(&literal) {
+            self.tree
+                .remove(&GeomWithData::new(Rectangle::from_aabb(envelope), literal));
+        }
+    }
+}
+
+/// Makes the queries evaluated with the given [`QueryOptions`] use the [`SpatialIndex`]
+pub fn register_spatial_index(options: QueryOptions, index: Arc<SpatialIndex>) -> QueryOptions {
+    options.with_graph_pattern_rewriter(move |pattern| index.rewrite(pattern))
+}
+
+fn indexed_literal(quad: QuadRef<'_>) -> Option<Literal> {
+    let TermRef::Literal(literal) = quad.object else {
+        return None;
+    };
+    is_indexed_predicate(quad.predicate).then(|| literal.into_owned())
+}
+
+fn is_indexed_predicate(predicate: NamedNodeRef<'_>) -> bool {
+    INDEXED_PREDICATES.contains(&predicate)
+}
+
+fn envelope(literal: &Literal) -> Option<AABB<[f64; 2]>> {
+    let rect = extract_argument(&Term::from(literal.clone()))?.bounding_rect()?;
+    Some(AABB::from_corners(
+        [rect.min().x, rect.min().y],
+        [rect.max().x, rect.max().y],
+    ))
+}
+
+/// Finds in the conjunction of filters the spatial functions applied to a variable and a constant
+fn add_spatial_constraints<'a>(
+    expression: &'a Expression,
+    constraints: &mut Vec<(&'a Variable, &'a Literal)>,
+) {
+    match expression {
+        Expression::And(left, right) => {
+            add_spatial_constraints(left, constraints);
+            add_spatial_constraints(right, constraints);
+        }
+        Expression::FunctionCall(Function::Custom(name), arguments)
+            if INTERSECTING_FUNCTIONS.iter().any(|f| f == name) =>
+        {
+            if let [Expression::Variable(variable), Expression::Literal(literal)]
+            | [Expression::Literal(literal), Expression::Variable(variable)] =
+                arguments.as_slice()
+            {
+                constraints.push((variable, literal));
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Checks that the variable is always bound by the pattern to the object of an indexed triple
+fn binds_indexed_literal(pattern: &GraphPattern, variable: &Variable) -> bool {
+    match pattern {
+        GraphPattern::Bgp { patterns } => patterns.iter().any(|pattern| {
+            matches!(&pattern.predicate, NamedNodePattern::NamedNode(p) if is_indexed_predicate(p.as_ref()))
+                && matches!(&pattern.object, TermPattern::Variable(v) if v == variable)
+        }),
+        GraphPattern::Join { left, right } => {
+            binds_indexed_literal(left, variable) || binds_indexed_literal(right, variable)
+        }
+        GraphPattern::LeftJoin { left, .. }
+        | GraphPattern::Lateral { left, .. }
+        | GraphPattern::Minus { left, .. } => binds_indexed_literal(left, variable),
+        GraphPattern::Filter { inner, .. }
+        | GraphPattern::Graph { inner, .. }
+        | GraphPattern::Extend { inner, .. } => binds_indexed_literal(inner, variable),
+        _ => false,
+    }
+}
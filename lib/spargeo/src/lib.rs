@@ -4,13 +4,24 @@
 #![doc(html_favicon_url = "https://raw.githubusercontent.com/oxigraph/oxigraph/main/logo.svg")]
 #![doc(html_logo_url = "https://raw.githubusercontent.com/oxigraph/oxigraph/main/logo.svg")]
 
-use geo::{Geometry, Relate};
+#[cfg(feature = "crs")]
+mod crs;
+mod gml;
+mod index;
+
+#[cfg(feature = "crs")]
+use crate::crs::to_crs84;
+use crate::gml::parse_gml_literal;
+pub use crate::index::{register_spatial_index, SpatialIndex};
+use geo::{
+    BooleanOps, BoundingRect, Coord, Geometry, GeometryCollection, MultiPolygon, Rect, Relate,
+};
 use geojson::GeoJson;
 use oxigraph::model::{Literal, NamedNodeRef, Term};
 use oxigraph::sparql::QueryOptions;
-use spareval::QueryEvaluator;
+use spareval::{AggregateFunctionAccumulator, QueryEvaluator};
 use std::str::FromStr;
-use wkt::TryFromWkt;
+use wkt::{ToWkt, TryFromWkt};
 
 /// Registers GeoSPARQL extension functions in the [`QueryOptions`]
 pub fn register_geosparql_functions(options: QueryOptions) -> QueryOptions {
@@ -26,6 +37,35 @@ pub fn register_geosparql_functions(options: QueryOptions) -> QueryOptions {
         .with_custom_function(geosparql_functions::SF_WITHIN.into(), geof_sf_within)
         .with_custom_function(geosparql_functions::SF_CONTAINS.into(), geof_sf_contains)
         .with_custom_function(geosparql_functions::SF_OVERLAPS.into(), geof_sf_overlaps)
+        .with_custom_aggregate_function(geosparql_functions::AGG_BOUNDING_BOX.into(), || {
+            Box::<BoundingBoxAccumulator>::default()
+        })
+        .with_custom_aggregate_function(geosparql_functions::AGG_UNION.into(), || {
+            Box::<UnionAccumulator>::default()
+        })
+}
+
+/// Converts a `geo:wktLiteral`, `geo:geoJSONLiteral` or `geo:gmlLiteral` to a [GeoJSON](https://geojson.org/) geometry serialization
+///
+/// Returns `None` if the literal is not a valid geometry of one of these datatypes.
+///
+/// ```
+/// use oxigraph::model::{Literal, NamedNodeRef};
+/// use spargeo::literal_to_geo_json;
+///
+/// let point = Literal::new_typed_literal(
+///     "POINT(1 2)",
+///     NamedNodeRef::new("http://www.opengis.net/ont/geosparql#wktLiteral")?,
+/// );
+/// assert_eq!(
+///     literal_to_geo_json(&point).as_deref(),
+///     Some(r#"{"coordinates":[1.0,2.0],"type":"Point"}"#)
+/// );
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+pub fn literal_to_geo_json(literal: &Literal) -> Option<String> {
+    let geometry = extract_literal(literal)?;
+    Some(geojson::Geometry::new(geojson::Value::from(&geometry)).to_string())
 }
 
 /// Registers GeoSPARQL extension functions in the [`QueryEvaluator`]
@@ -42,6 +82,12 @@ pub fn add_geosparql_functions(evaluator: QueryEvaluator) -> QueryEvaluator {
         .with_custom_function(geosparql_functions::SF_WITHIN.into(), geof_sf_within)
         .with_custom_function(geosparql_functions::SF_CONTAINS.into(), geof_sf_contains)
         .with_custom_function(geosparql_functions::SF_OVERLAPS.into(), geof_sf_overlaps)
+        .with_custom_aggregate_function(geosparql_functions::AGG_BOUNDING_BOX.into(), || {
+            Box::<BoundingBoxAccumulator>::default()
+        })
+        .with_custom_aggregate_function(geosparql_functions::AGG_UNION.into(), || {
+            Box::<UnionAccumulator>::default()
+        })
 }
 
 /// List of GeoSPARQL functions supported and registered by [`register_geosparql_functions`]
@@ -56,6 +102,12 @@ pub const GEOSPARQL_EXTENSION_FUNCTIONS: [NamedNodeRef<'static>; 8] = [
     geosparql_functions::SF_OVERLAPS,
 ];
 
+/// List of GeoSPARQL aggregate functions supported and registered by [`register_geosparql_functions`]
+pub const GEOSPARQL_EXTENSION_AGGREGATE_FUNCTIONS: [NamedNodeRef<'static>; 2] = [
+    geosparql_functions::AGG_BOUNDING_BOX,
+    geosparql_functions::AGG_UNION,
+];
+
 fn geof_sf_equals(args: &[Term]) -> Option<Term> {
     binary_geo_fn(args, |a, b| a.relate(&b).is_equal_topo())
 }
@@ -98,33 +150,171 @@ fn binary_geo_fn<R: Into<Literal>>(
     Some(operation(left, right).into().into())
 }
 
+/// Accumulator of `geof:aggBoundingBox`: the minimum bounding rectangle of all the geometries
+#[derive(Default)]
+struct BoundingBoxAccumulator {
+    bounding_box: Option<Rect>,
+    failed: bool,
+}
+
+impl AggregateFunctionAccumulator for BoundingBoxAccumulator {
+    fn accumulate(&mut self, element: Term) {
+        if self.failed {
+            return;
+        }
+        let Some(geometry) = extract_argument(&element) else {
+            self.failed = true;
+            return;
+        };
+        let Some(rect) = geometry.bounding_rect() else {
+            return; // Empty geometry
+        };
+        self.bounding_box = Some(if let Some(current) = self.bounding_box {
+            Rect::new(
+                Coord {
+                    x: current.min().x.min(rect.min().x),
+                    y: current.min().y.min(rect.min().y),
+                },
+                Coord {
+                    x: current.max().x.max(rect.max().x),
+                    y: current.max().y.max(rect.max().y),
+                },
+            )
+        } else {
+            rect
+        });
+    }
+
+    fn finish(&mut self) -> Option<Term> {
+        if self.failed {
+            return None;
+        }
+        Some(wkt_literal(&self.bounding_box?.to_polygon().into()))
+    }
+}
+
+/// Accumulator of `geof:aggUnion`: the union of all the geometries
+///
+/// The areal geometries are merged together, the other ones are kept as they are in a geometry collection.
+#[derive(Default)]
+struct UnionAccumulator {
+    areas: Option<MultiPolygon>,
+    others: Vec<Geometry>,
+    failed: bool,
+}
+
+impl AggregateFunctionAccumulator for UnionAccumulator {
+    fn accumulate(&mut self, element: Term) {
+        if self.failed {
+            return;
+        }
+        let Some(geometry) = extract_argument(&element) else {
+            self.failed = true;
+            return;
+        };
+        self.add(geometry);
+    }
+
+    fn finish(&mut self) -> Option<Term> {
+        if self.failed {
+            return None;
+        }
+        let areas = self.areas.take();
+        let others = std::mem::take(&mut self.others);
+        let geometry = match (areas, others.is_empty()) {
+            (Some(areas), true) => simplify_multi_polygon(areas),
+            (None, true) => Geometry::GeometryCollection(GeometryCollection::default()),
+            (areas, false) => Geometry::GeometryCollection(GeometryCollection::from(
+                areas
+                    .map(simplify_multi_polygon)
+                    .into_iter()
+                    .chain(others)
+                    .collect::<Vec<_>>(),
+            )),
+        };
+        Some(wkt_literal(&geometry))
+    }
+}
+
+impl UnionAccumulator {
+    fn add(&mut self, geometry: Geometry) {
+        let area = match geometry {
+            Geometry::Polygon(g) => MultiPolygon::new(vec![g]),
+            Geometry::MultiPolygon(g) => g,
+            Geometry::Rect(g) => MultiPolygon::new(vec![g.to_polygon()]),
+            Geometry::Triangle(g) => MultiPolygon::new(vec![g.to_polygon()]),
+            Geometry::GeometryCollection(g) => {
+                for g in g {
+                    self.add(g);
+                }
+                return;
+            }
+            g => {
+                self.others.push(g);
+                return;
+            }
+        };
+        self.areas = Some(if let Some(areas) = &self.areas {
+            areas.union(&area)
+        } else {
+            area
+        });
+    }
+}
+
+fn simplify_multi_polygon(mut geometry: MultiPolygon) -> Geometry {
+    if geometry.0.len() == 1 {
+        geometry.0.remove(0).into()
+    } else {
+        geometry.into()
+    }
+}
+
+fn wkt_literal(geometry: &Geometry) -> Term {
+    Literal::new_typed_literal(geometry.wkt_string(), geosparql::WKT_LITERAL).into()
+}
+
 // Parse
 fn extract_argument(term: &Term) -> Option<Geometry> {
     let Term::Literal(literal) = term else {
         return None;
     };
+    extract_literal(literal)
+}
+
+fn extract_literal(literal: &Literal) -> Option<Geometry> {
     if literal.datatype() == geosparql::WKT_LITERAL {
         parse_wkt_literal(literal.value().trim())
     } else if literal.datatype() == geosparql::GEO_JSON_LITERAL {
         parse_geo_json_literal(literal.value().trim())
+    } else if literal.datatype() == geosparql::GML_LITERAL {
+        parse_gml_literal(literal.value())
     } else {
         None
     }
 }
 
 // Parse a WKT literal including reference system http://www.opengis.net/def/crs/OGC/1.3/CRS84
+// Other EPSG reference systems are converted to CRS84 if the "crs" feature is enabled
 fn parse_wkt_literal(value: &str) -> Option<Geometry> {
-    let mut value = value.trim_start();
-    if let Some(val) = value.strip_prefix('<') {
-        // We have a reference system
-        let (system, val) = val.split_once('>').unwrap_or((val, ""));
-        if system != "http://www.opengis.net/def/crs/OGC/1.3/CRS84" {
-            // We only support CRS84
-            return None;
-        }
-        value = val.trim_start();
+    let value = value.trim_start();
+    let Some(val) = value.strip_prefix('<') else {
+        return Geometry::try_from_wkt_str(value).ok();
+    };
+    // We have a reference system
+    let (system, val) = val.split_once('>').unwrap_or((val, ""));
+    let geometry = Geometry::try_from_wkt_str(val.trim_start()).ok()?;
+    if system == "http://www.opengis.net/def/crs/OGC/1.3/CRS84" {
+        return Some(geometry);
+    }
+    #[cfg(feature = "crs")]
+    {
+        to_crs84(system, geometry)
+    }
+    #[cfg(not(feature = "crs"))]
+    {
+        None // We only support CRS84
     }
-    Geometry::try_from_wkt_str(value).ok()
 }
 
 fn parse_geo_json_literal(value: &str) -> Option<Geometry> {
@@ -135,8 +325,16 @@ mod geosparql {
     //! [GeoSpatial](https://opengeospatial.github.io/ogc-geosparql/) vocabulary.
     use oxigraph::model::NamedNodeRef;
 
+    pub const AS_GEO_JSON: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.opengis.net/ont/geosparql#asGeoJSON");
+    pub const AS_GML: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.opengis.net/ont/geosparql#asGML");
+    pub const AS_WKT: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.opengis.net/ont/geosparql#asWKT");
     pub const GEO_JSON_LITERAL: NamedNodeRef<'_> =
         NamedNodeRef::new_unchecked("http://www.opengis.net/ont/geosparql#geoJSONLiteral");
+    pub const GML_LITERAL: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.opengis.net/ont/geosparql#gmlLiteral");
     pub const WKT_LITERAL: NamedNodeRef<'_> =
         NamedNodeRef::new_unchecked("http://www.opengis.net/ont/geosparql#wktLiteral");
 }
@@ -145,6 +343,10 @@ mod geosparql_functions {
     //! [GeoSpatial](https://opengeospatial.github.io/ogc-geosparql/) functions vocabulary.
     use oxigraph::model::NamedNodeRef;
 
+    pub const AGG_BOUNDING_BOX: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.opengis.net/def/function/geosparql/aggBoundingBox");
+    pub const AGG_UNION: NamedNodeRef<'_> =
+        NamedNodeRef::new_unchecked("http://www.opengis.net/def/function/geosparql/aggUnion");
     pub const SF_CONTAINS: NamedNodeRef<'_> =
         NamedNodeRef::new_unchecked("http://www.opengis.net/def/function/geosparql/sfContains");
     pub const SF_CROSSES: NamedNodeRef<'_> =
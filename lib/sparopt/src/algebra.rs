@@ -1642,7 +1642,7 @@ impl From<&OrderExpression> for AlOrderExpression {
     }
 }
 
-fn new_var() -> Variable {
+pub(crate) fn new_var() -> Variable {
     Variable::new_unchecked(format!("{:x}", random::<u128>()))
 }
 
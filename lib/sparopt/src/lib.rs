@@ -4,8 +4,10 @@
 #![doc(html_favicon_url = "https://raw.githubusercontent.com/oxigraph/oxigraph/main/logo.svg")]
 #![doc(html_logo_url = "https://raw.githubusercontent.com/oxigraph/oxigraph/main/logo.svg")]
 
-pub use crate::optimizer::Optimizer;
+pub use crate::optimizer::{Optimizer, OptimizerFingerprint};
+pub use crate::statistics::{DatasetStatistics, PredicateStatistics, StatisticsProvider};
 
 pub mod algebra;
 mod optimizer;
+mod statistics;
 mod type_inference;
@@ -1,21 +1,348 @@
 use crate::algebra::{
-    Expression, GraphPattern, JoinAlgorithm, LeftJoinAlgorithm, MinusAlgorithm, OrderExpression,
+    new_var, AggregateExpression, Expression, Function, GraphPattern, JoinAlgorithm,
+    LeftJoinAlgorithm, MinusAlgorithm, OrderExpression,
 };
+use crate::statistics::StatisticsProvider;
 use crate::type_inference::{
     infer_expression_type, infer_graph_pattern_types, VariableType, VariableTypes,
 };
-use oxrdf::Variable;
+use oxrdf::{NamedNode, Variable};
 use spargebra::algebra::PropertyPathExpression;
+#[cfg(feature = "rdf-star")]
+use spargebra::term::GroundTriplePattern;
 use spargebra::term::{GroundTermPattern, NamedNodePattern};
+use std::borrow::Cow;
 use std::cmp::{max, min};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 
-pub struct Optimizer;
+/// A query optimizer, made of a pipeline of named passes applied one after the other.
+///
+/// The default pipeline is made of the `"normalization"`, `"path-expansion"`, `"subquery-flattening"`,
+/// `"common-subexpression-elimination"`, `"join-reordering"`, `"filter-pushing"` and `"limit-pushing"` passes.
+///
+/// Passes might be removed, for example to find which one causes a plan regression, or added:
+/// ```
+/// use sparopt::algebra::GraphPattern;
+/// use sparopt::Optimizer;
+/// use spargebra::Query;
+///
+/// let Query::Select { pattern, .. } =
+///     Query::parse("SELECT * WHERE { ?s ?p ?o . ?s ?p2 ?o2 }", None)?
+/// else {
+///     unreachable!()
+/// };
+/// let optimizer = Optimizer::default_passes()
+///     .without_pass("join-reordering")
+///     .with_pass("identity", |pattern: GraphPattern| pattern);
+/// assert_eq!(
+///     optimizer.pass_names().collect::<Vec<_>>(),
+///     [
+///         "normalization",
+///         "path-expansion",
+///         "subquery-flattening",
+///         "common-subexpression-elimination",
+///         "filter-pushing",
+///         "limit-pushing",
+///         "identity"
+///     ]
+/// );
+/// optimizer.optimize(GraphPattern::from(&pattern));
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[derive(Clone)]
+pub struct Optimizer {
+    passes: Vec<OptimizerPass>,
+}
+
+/// Identifies the behavior of an [`Optimizer`], returned by [`Optimizer::fingerprint`].
+///
+/// Two optimizers with equal fingerprints return the same patterns.
+/// The built-in passes are compared using their name and parameters,
+/// the custom passes and the statistics are compared using their address.
+#[derive(Clone)]
+pub struct OptimizerFingerprint {
+    /// The passes are kept to make sure the compared addresses are not reused
+    passes: Vec<OptimizerPass>,
+}
+
+impl PartialEq for OptimizerFingerprint {
+    fn eq(&self, other: &Self) -> bool {
+        self.passes.len() == other.passes.len()
+            && self
+                .passes
+                .iter()
+                .zip(&other.passes)
+                .all(|(a, b)| a.identity() == b.identity())
+    }
+}
+
+impl Eq for OptimizerFingerprint {}
+
+impl Hash for OptimizerFingerprint {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for pass in &self.passes {
+            pass.identity().hash(state);
+        }
+    }
+}
+
+impl fmt::Debug for OptimizerFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.passes.iter().map(|pass| &pass.name))
+            .finish()
+    }
+}
+
+const DEFAULT_PATH_EXPANSION_LIMIT: usize = 8;
+
+#[derive(Clone)]
+struct OptimizerPass {
+    name: Cow<'static, str>,
+    kind: PassKind,
+    run: Arc<dyn Fn(GraphPattern) -> GraphPattern + Send + Sync>,
+}
+
+#[derive(Clone)]
+enum PassKind {
+    /// A built-in pass without parameter
+    Builtin,
+    /// The built-in `"path-expansion"` pass with its limit
+    PathExpansion(usize),
+    /// The built-in `"join-reordering"` pass with its statistics
+    JoinReordering(Option<Arc<dyn StatisticsProvider>>),
+    /// A pass added with [`Optimizer::with_pass`], identified by its function
+    Custom,
+}
+
+impl OptimizerPass {
+    /// The name, kind and parameter of the pass, the functions and statistics are identified by their address
+    fn identity(&self) -> (&str, u8, usize) {
+        let (kind, parameter) = match &self.kind {
+            PassKind::Builtin => (0, 0),
+            PassKind::PathExpansion(limit) => (1, *limit),
+            PassKind::JoinReordering(statistics) => (
+                2,
+                statistics.as_ref().map_or(0, |statistics| {
+                    Arc::as_ptr(statistics).cast::<()>() as usize
+                }),
+            ),
+            PassKind::Custom => (3, Arc::as_ptr(&self.run).cast::<()>() as usize),
+        };
+        (&self.name, kind, parameter)
+    }
+}
+
+impl Default for Optimizer {
+    #[inline]
+    fn default() -> Self {
+        Self::default_passes()
+    }
+}
 
 impl Optimizer {
+    /// Optimizes the pattern using the default passes.
     pub fn optimize_graph_pattern(pattern: GraphPattern) -> GraphPattern {
-        let pattern = Self::normalize_pattern(pattern, &VariableTypes::default());
-        let pattern = Self::reorder_joins(pattern, &VariableTypes::default());
-        Self::push_filters(pattern, Vec::new(), &VariableTypes::default())
+        Self::default_passes().optimize(pattern)
+    }
+
+    /// The optimizer with the default passes.
+    pub fn default_passes() -> Self {
+        Self::empty()
+            .with_pass_of_kind("normalization", PassKind::Builtin, |pattern| {
+                Self::normalize_pattern(pattern, &VariableTypes::default())
+            })
+            .with_pass_of_kind(
+                "path-expansion",
+                PassKind::PathExpansion(DEFAULT_PATH_EXPANSION_LIMIT),
+                |pattern| Self::expand_paths(pattern, DEFAULT_PATH_EXPANSION_LIMIT),
+            )
+            .with_pass_of_kind("subquery-flattening", PassKind::Builtin, |pattern| {
+                Self::flatten_subqueries(pattern, true)
+            })
+            .with_pass_of_kind(
+                "common-subexpression-elimination",
+                PassKind::Builtin,
+                |pattern| Self::eliminate_common_subexpressions(pattern, false),
+            )
+            .with_pass_of_kind(
+                "join-reordering",
+                PassKind::JoinReordering(None),
+                |pattern| Self::reorder_joins(pattern, &VariableTypes::default(), None),
+            )
+            .with_pass_of_kind("filter-pushing", PassKind::Builtin, |pattern| {
+                Self::push_filters(pattern, Vec::new(), &VariableTypes::default())
+            })
+            .with_pass_of_kind("limit-pushing", PassKind::Builtin, Self::push_limits)
+    }
+
+    /// An optimizer without any pass, returning the patterns as they are.
+    pub fn empty() -> Self {
+        Self { passes: Vec::new() }
+    }
+
+    /// Adds a pass at the end of the pipeline.
+    ///
+    /// The pass must return a pattern with the exact same results as its input.
+    #[must_use]
+    pub fn with_pass(
+        self,
+        name: impl Into<Cow<'static, str>>,
+        pass: impl Fn(GraphPattern) -> GraphPattern + Send + Sync + 'static,
+    ) -> Self {
+        self.with_pass_of_kind(name, PassKind::Custom, pass)
+    }
+
+    fn with_pass_of_kind(
+        mut self,
+        name: impl Into<Cow<'static, str>>,
+        kind: PassKind,
+        pass: impl Fn(GraphPattern) -> GraphPattern + Send + Sync + 'static,
+    ) -> Self {
+        self.passes.push(OptimizerPass {
+            name: name.into(),
+            kind,
+            run: Arc::new(pass),
+        });
+        self
+    }
+
+    /// Removes the passes with the given name from the pipeline.
+    #[must_use]
+    pub fn without_pass(mut self, name: &str) -> Self {
+        self.passes.retain(|pass| pass.name != name);
+        self
+    }
+
+    /// Makes the `"join-reordering"` passes use the given statistics to estimate the cardinality of the patterns.
+    ///
+    /// The joins are then ordered using these estimates and a hash join is used instead of a for loop join when it is expected to be cheaper.
+    ///
+    /// ```
+    /// use oxrdf::NamedNode;
+    /// use sparopt::algebra::GraphPattern;
+    /// use sparopt::{DatasetStatistics, Optimizer, PredicateStatistics};
+    /// use spargebra::Query;
+    /// use std::sync::Arc;
+    ///
+    /// let statistics = DatasetStatistics::from_iter([(
+    ///     NamedNode::new("http://schema.org/name")?,
+    ///     PredicateStatistics {
+    ///         count: 1000,
+    ///         distinct_subjects: 1000,
+    ///         distinct_objects: 900,
+    ///     },
+    /// )]);
+    /// let Query::Select { pattern, .. } = Query::parse(
+    ///     "SELECT * WHERE { ?s <http://schema.org/name> ?n ; <http://schema.org/knows> ?o }",
+    ///     None,
+    /// )?
+    /// else {
+    ///     unreachable!()
+    /// };
+    /// Optimizer::default_passes()
+    ///     .with_statistics(Arc::new(statistics))
+    ///     .optimize(GraphPattern::from(&pattern));
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[must_use]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn with_statistics(mut self, statistics: Arc<dyn StatisticsProvider>) -> Self {
+        for pass in &mut self.passes {
+            if pass.name == "join-reordering" {
+                pass.kind = PassKind::JoinReordering(Some(Arc::clone(&statistics)));
+                let statistics = Arc::clone(&statistics);
+                pass.run = Arc::new(move |pattern| {
+                    Self::reorder_joins(
+                        pattern,
+                        &VariableTypes::default(),
+                        Some(statistics.as_ref()),
+                    )
+                });
+            }
+        }
+        self
+    }
+
+    /// Sets the maximal number of triple patterns a property path might be rewritten into by the `"path-expansion"` passes.
+    ///
+    /// The default limit is 8. The paths with an unbounded repetition like `ex:p*` are never rewritten.
+    ///
+    /// ```
+    /// use sparopt::algebra::GraphPattern;
+    /// use sparopt::Optimizer;
+    /// use spargebra::Query;
+    ///
+    /// let Query::Select { pattern, .. } = Query::parse(
+    ///     "SELECT * WHERE { ?s <http://schema.org/name>|<http://schema.org/alternateName> ?o }",
+    ///     None,
+    /// )?
+    /// else {
+    ///     unreachable!()
+    /// };
+    /// let optimized = Optimizer::default_passes()
+    ///     .with_path_expansion_limit(1)
+    ///     .optimize(GraphPattern::from(&pattern));
+    /// let GraphPattern::Project { inner, .. } = optimized else {
+    ///     unreachable!()
+    /// };
+    /// assert!(matches!(*inner, GraphPattern::Path { .. }));
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[must_use]
+    pub fn with_path_expansion_limit(mut self, limit: usize) -> Self {
+        for pass in &mut self.passes {
+            if pass.name == "path-expansion" {
+                pass.kind = PassKind::PathExpansion(limit);
+                pass.run = Arc::new(move |pattern| Self::expand_paths(pattern, limit));
+            }
+        }
+        self
+    }
+
+    /// The names of the passes of the pipeline, in their execution order.
+    pub fn pass_names(&self) -> impl Iterator<Item = &str> {
+        self.passes.iter().map(|pass| pass.name.as_ref())
+    }
+
+    /// Identifies the passes of the pipeline and their parameters, for example to cache the optimized patterns.
+    ///
+    /// ```
+    /// use sparopt::algebra::GraphPattern;
+    /// use sparopt::Optimizer;
+    ///
+    /// assert_eq!(
+    ///     Optimizer::default_passes().fingerprint(),
+    ///     Optimizer::default_passes().fingerprint()
+    /// );
+    /// assert_ne!(
+    ///     Optimizer::default_passes().fingerprint(),
+    ///     Optimizer::default_passes()
+    ///         .with_path_expansion_limit(1)
+    ///         .fingerprint()
+    /// );
+    /// let optimizer = Optimizer::default_passes().with_pass("identity", |pattern: GraphPattern| pattern);
+    /// assert_eq!(optimizer.fingerprint(), optimizer.clone().fingerprint());
+    /// assert_ne!(
+    ///     optimizer.fingerprint(),
+    ///     Optimizer::default_passes()
+    ///         .with_pass("identity", |pattern: GraphPattern| pattern)
+    ///         .fingerprint()
+    /// );
+    /// ```
+    pub fn fingerprint(&self) -> OptimizerFingerprint {
+        OptimizerFingerprint {
+            passes: self.passes.clone(),
+        }
+    }
+
+    /// Optimizes the pattern by running all the passes of the pipeline.
+    pub fn optimize(&self, pattern: GraphPattern) -> GraphPattern {
+        self.passes
+            .iter()
+            .fold(pattern, |pattern, pass| (pass.run)(pattern))
     }
 
     /// Normalize the pattern, discarding any join ordering information
@@ -476,7 +803,229 @@ impl Optimizer {
         }
     }
 
-    fn reorder_joins(pattern: GraphPattern, input_types: &VariableTypes) -> GraphPattern {
+    /// Rewrites the property paths without unbounded repetition into joins and unions of triple patterns
+    ///
+    /// Bounded repetitions are written with `?`, e.g. `ex:p/(ex:p/ex:p?)?` for paths of 1 to 3 `ex:p`.
+    /// Paths requiring more than `limit` triple patterns are kept as they are.
+    fn expand_paths(pattern: GraphPattern, limit: usize) -> GraphPattern {
+        match pattern {
+            GraphPattern::Path {
+                subject,
+                path,
+                object,
+                graph_name,
+            } => {
+                let mut budget = limit;
+                let Some(expanded) =
+                    expand_path(&subject, &path, &object, graph_name.as_ref(), &mut budget)
+                else {
+                    return GraphPattern::Path {
+                        subject,
+                        path,
+                        object,
+                        graph_name,
+                    };
+                };
+                // We hide the variables introduced for the intermediate nodes
+                GraphPattern::project(
+                    expanded,
+                    path_end_variables(&subject, &object, graph_name.as_ref()),
+                )
+            }
+            GraphPattern::Service { .. } => {
+                // We leave this problem to the remote SPARQL endpoint
+                pattern
+            }
+            _ => map_graph_pattern_children(
+                pattern,
+                &mut |p| Self::expand_paths(p, limit),
+                &mut |e| Self::expand_paths_in_expression(e, limit),
+            ),
+        }
+    }
+
+    fn expand_paths_in_expression(expression: Expression, limit: usize) -> Expression {
+        map_expression_children(
+            expression,
+            &mut |p| Self::expand_paths(p, limit),
+            &mut |e| Self::expand_paths_in_expression(e, limit),
+        )
+    }
+
+    /// Evaluates once the sub-patterns and sub-expressions that would be evaluated multiple times
+    ///
+    /// Duplicated conjuncts and disjuncts are always removed because `&&` and `||` are idempotent.
+    /// Duplicated UNION branches are only removed if the cardinality of the solutions does not matter,
+    /// i.e. below DISTINCT and REDUCED, inside EXISTS and on the right side of MINUS.
+    /// The duplicates calling non-deterministic functions like `RAND()` or `BNODE()` are kept.
+    fn eliminate_common_subexpressions(pattern: GraphPattern, set_semantics: bool) -> GraphPattern {
+        match pattern {
+            GraphPattern::Union { inner } => {
+                let mut branches = Vec::with_capacity(inner.len());
+                for branch in inner {
+                    let branch = Self::eliminate_common_subexpressions(branch, set_semantics);
+                    if !set_semantics
+                        || !is_deterministic_pattern(&branch)
+                        || !branches.contains(&branch)
+                    {
+                        branches.push(branch);
+                    }
+                }
+                match <[_; 1]>::try_from(branches) {
+                    Ok([branch]) => branch,
+                    Err(branches) => GraphPattern::union_all(branches),
+                }
+            }
+            GraphPattern::Distinct { inner } => {
+                GraphPattern::distinct(Self::eliminate_common_subexpressions(*inner, true))
+            }
+            GraphPattern::Reduced { inner } => {
+                GraphPattern::reduced(Self::eliminate_common_subexpressions(*inner, true))
+            }
+            GraphPattern::Minus {
+                left,
+                right,
+                algorithm,
+            } => GraphPattern::minus(
+                Self::eliminate_common_subexpressions(*left, set_semantics),
+                Self::eliminate_common_subexpressions(*right, true),
+                algorithm,
+            ),
+            GraphPattern::Slice { .. } | GraphPattern::Group { .. } => {
+                // The number of solutions matters again
+                map_graph_pattern_children(
+                    pattern,
+                    &mut |p| Self::eliminate_common_subexpressions(p, false),
+                    &mut Self::eliminate_common_subexpressions_in_expression,
+                )
+            }
+            GraphPattern::Service { .. } => {
+                // We leave this problem to the remote SPARQL endpoint
+                pattern
+            }
+            _ => map_graph_pattern_children(
+                pattern,
+                &mut |p| Self::eliminate_common_subexpressions(p, set_semantics),
+                &mut Self::eliminate_common_subexpressions_in_expression,
+            ),
+        }
+    }
+
+    fn eliminate_common_subexpressions_in_expression(expression: Expression) -> Expression {
+        match expression {
+            Expression::Or(inner) => Expression::or_all(deduplicate_expressions(
+                inner
+                    .into_iter()
+                    .map(Self::eliminate_common_subexpressions_in_expression),
+            )),
+            Expression::And(inner) => Expression::and_all(deduplicate_expressions(
+                inner
+                    .into_iter()
+                    .map(Self::eliminate_common_subexpressions_in_expression),
+            )),
+            Expression::Exists(inner) => Expression::exists(
+                // Only the existence of a solution matters
+                Self::eliminate_common_subexpressions(*inner, true),
+            ),
+            _ => map_expression_children(
+                expression,
+                &mut |p| Self::eliminate_common_subexpressions(p, false),
+                &mut Self::eliminate_common_subexpressions_in_expression,
+            ),
+        }
+    }
+
+    /// Removes the projections of the nested subqueries that do not hide any variable
+    ///
+    /// The projection at the root of the query is kept because it defines the query output.
+    fn flatten_subqueries(pattern: GraphPattern, is_root: bool) -> GraphPattern {
+        match pattern {
+            GraphPattern::Project { inner, variables } => {
+                let inner = match Self::flatten_subqueries(*inner, false) {
+                    GraphPattern::Project {
+                        inner,
+                        variables: inner_variables,
+                    } if variables.iter().all(|v| inner_variables.contains(v)) => {
+                        // Only the outer projection matters
+                        *inner
+                    }
+                    inner => inner,
+                };
+                let inner = inline_variable_renaming(inner, &variables);
+                let mut hides_variables = false;
+                inner.lookup_used_variables(&mut |v| {
+                    if !variables.contains(v) {
+                        hides_variables = true;
+                    }
+                });
+                if is_root || hides_variables {
+                    GraphPattern::project(inner, variables)
+                } else {
+                    inner
+                }
+            }
+            GraphPattern::Distinct { .. }
+            | GraphPattern::Reduced { .. }
+            | GraphPattern::Slice { .. }
+            | GraphPattern::OrderBy { .. } => map_graph_pattern_children(
+                pattern,
+                &mut |p| Self::flatten_subqueries(p, is_root),
+                &mut Self::flatten_subqueries_in_expression,
+            ),
+            GraphPattern::Service { .. } => {
+                // We leave this problem to the remote SPARQL endpoint
+                pattern
+            }
+            _ => map_graph_pattern_children(
+                pattern,
+                &mut |p| Self::flatten_subqueries(p, false),
+                &mut Self::flatten_subqueries_in_expression,
+            ),
+        }
+    }
+
+    fn flatten_subqueries_in_expression(expression: Expression) -> Expression {
+        map_expression_children(
+            expression,
+            &mut |p| Self::flatten_subqueries(p, false),
+            &mut Self::flatten_subqueries_in_expression,
+        )
+    }
+
+    /// Pushes the slices down the patterns that do not change the number of solutions
+    /// and copies them to the patterns for which only the first solutions are needed.
+    fn push_limits(pattern: GraphPattern) -> GraphPattern {
+        match pattern {
+            GraphPattern::Slice {
+                inner,
+                start,
+                length,
+            } => push_slice(Self::push_limits(*inner), start, length),
+            GraphPattern::Service { .. } => {
+                // We leave this problem to the remote SPARQL endpoint
+                pattern
+            }
+            _ => map_graph_pattern_children(
+                pattern,
+                &mut Self::push_limits,
+                &mut Self::push_limits_in_expression,
+            ),
+        }
+    }
+
+    fn push_limits_in_expression(expression: Expression) -> Expression {
+        map_expression_children(
+            expression,
+            &mut Self::push_limits,
+            &mut Self::push_limits_in_expression,
+        )
+    }
+
+    fn reorder_joins(
+        pattern: GraphPattern,
+        input_types: &VariableTypes,
+        statistics: Option<&dyn StatisticsProvider>,
+    ) -> GraphPattern {
         match pattern {
             GraphPattern::QuadPattern { .. }
             | GraphPattern::Path { .. }
@@ -510,7 +1059,9 @@ impl Optimizer {
                     .enumerate()
                     .filter(|(_, v)| **v)
                     .map(|(i, _)| i)
-                    .min_by_key(|i| estimate_graph_pattern_size(&to_reorder[*i], input_types))
+                    .min_by_key(|i| {
+                        estimate_graph_pattern_size(&to_reorder[*i], input_types, statistics)
+                    })
                 {
                     not_yet_reordered_ids[next_entry_id] = false; // It's now done
                     let mut output = to_reorder[next_entry_id].clone();
@@ -525,42 +1076,30 @@ impl Optimizer {
                             has_common_variables(&output_types, &to_reorder_types[*i], input_types)
                         })
                         .min_by_key(|i| {
-                            // Estimation of the join cost
-                            if cfg!(feature = "sep-0006")
-                                && is_fit_for_for_loop_join(
-                                    &to_reorder[*i],
-                                    input_types,
-                                    &output_types,
-                                )
-                            {
-                                estimate_lateral_cost(
-                                    &output,
-                                    &output_types,
-                                    &to_reorder[*i],
-                                    input_types,
-                                )
-                            } else {
-                                estimate_join_cost(
-                                    &output,
-                                    &to_reorder[*i],
-                                    &JoinAlgorithm::HashBuildLeftProbeRight {
-                                        keys: join_key_variables(
-                                            &output_types,
-                                            &to_reorder_types[*i],
-                                            input_types,
-                                        ),
-                                    },
-                                    input_types,
-                                )
-                            }
+                            estimate_join_with_best_algorithm(
+                                &output,
+                                &output_types,
+                                &to_reorder[*i],
+                                &to_reorder_types[*i],
+                                input_types,
+                                statistics,
+                            )
+                            .0
                         })
                     {
                         not_yet_reordered_ids[next_id] = false; // It's now done
                         let next = to_reorder[next_id].clone();
                         #[cfg(feature = "sep-0006")]
                         {
-                            output = if is_fit_for_for_loop_join(&next, input_types, &output_types)
-                            {
+                            let (_, use_for_loop_join) = estimate_join_with_best_algorithm(
+                                &output,
+                                &output_types,
+                                &next,
+                                &to_reorder_types[next_id],
+                                input_types,
+                                statistics,
+                            );
+                            output = if use_for_loop_join {
                                 GraphPattern::lateral(output, next)
                             } else {
                                 GraphPattern::join(
@@ -602,8 +1141,8 @@ impl Optimizer {
                             &infer_graph_pattern_types(&right, input_types.clone()),
                             input_types,
                         );
-                        if estimate_graph_pattern_size(&left, input_types)
-                            <= estimate_graph_pattern_size(&right, input_types)
+                        if estimate_graph_pattern_size(&left, input_types, statistics)
+                            <= estimate_graph_pattern_size(&right, input_types, statistics)
                         {
                             GraphPattern::join(
                                 left,
@@ -624,8 +1163,8 @@ impl Optimizer {
             GraphPattern::Lateral { left, right } => {
                 let left_types = infer_graph_pattern_types(&left, input_types.clone());
                 GraphPattern::lateral(
-                    Self::reorder_joins(*left, input_types),
-                    Self::reorder_joins(*right, &left_types),
+                    Self::reorder_joins(*left, input_types, statistics),
+                    Self::reorder_joins(*right, &left_types, statistics),
                 )
             }
             GraphPattern::LeftJoin {
@@ -634,9 +1173,9 @@ impl Optimizer {
                 expression,
                 ..
             } => {
-                let left = Self::reorder_joins(*left, input_types);
+                let left = Self::reorder_joins(*left, input_types, statistics);
                 let left_types = infer_graph_pattern_types(&left, input_types.clone());
-                let right = Self::reorder_joins(*right, input_types);
+                let right = Self::reorder_joins(*right, input_types, statistics);
                 let right_types = infer_graph_pattern_types(&right, input_types.clone());
                 #[cfg(feature = "sep-0006")]
                 {
@@ -664,9 +1203,9 @@ impl Optimizer {
                 )
             }
             GraphPattern::Minus { left, right, .. } => {
-                let left = Self::reorder_joins(*left, input_types);
+                let left = Self::reorder_joins(*left, input_types, statistics);
                 let left_types = infer_graph_pattern_types(&left, input_types.clone());
-                let right = Self::reorder_joins(*right, input_types);
+                let right = Self::reorder_joins(*right, input_types, statistics);
                 let right_types = infer_graph_pattern_types(&right, input_types.clone());
                 GraphPattern::minus(
                     left,
@@ -681,35 +1220,42 @@ impl Optimizer {
                 expression,
                 variable,
             } => GraphPattern::extend(
-                Self::reorder_joins(*inner, input_types),
+                Self::reorder_joins(*inner, input_types, statistics),
                 variable,
                 expression,
             ),
-            GraphPattern::Filter { inner, expression } => {
-                GraphPattern::filter(Self::reorder_joins(*inner, input_types), expression)
-            }
+            GraphPattern::Filter { inner, expression } => GraphPattern::filter(
+                Self::reorder_joins(*inner, input_types, statistics),
+                expression,
+            ),
             GraphPattern::Union { inner } => GraphPattern::union_all(
                 inner
                     .into_iter()
-                    .map(|c| Self::reorder_joins(c, input_types)),
+                    .map(|c| Self::reorder_joins(c, input_types, statistics)),
             ),
             GraphPattern::Slice {
                 inner,
                 start,
                 length,
-            } => GraphPattern::slice(Self::reorder_joins(*inner, input_types), start, length),
+            } => GraphPattern::slice(
+                Self::reorder_joins(*inner, input_types, statistics),
+                start,
+                length,
+            ),
             GraphPattern::Distinct { inner } => {
-                GraphPattern::distinct(Self::reorder_joins(*inner, input_types))
+                GraphPattern::distinct(Self::reorder_joins(*inner, input_types, statistics))
             }
             GraphPattern::Reduced { inner } => {
-                GraphPattern::reduced(Self::reorder_joins(*inner, input_types))
-            }
-            GraphPattern::Project { inner, variables } => {
-                GraphPattern::project(Self::reorder_joins(*inner, input_types), variables)
-            }
-            GraphPattern::OrderBy { inner, expression } => {
-                GraphPattern::order_by(Self::reorder_joins(*inner, input_types), expression)
+                GraphPattern::reduced(Self::reorder_joins(*inner, input_types, statistics))
             }
+            GraphPattern::Project { inner, variables } => GraphPattern::project(
+                Self::reorder_joins(*inner, input_types, statistics),
+                variables,
+            ),
+            GraphPattern::OrderBy { inner, expression } => GraphPattern::order_by(
+                Self::reorder_joins(*inner, input_types, statistics),
+                expression,
+            ),
             GraphPattern::Service { .. } => {
                 // We don't do join reordering inside of SERVICE calls, we don't know about cardinalities
                 pattern
@@ -719,7 +1265,7 @@ impl Optimizer {
                 variables,
                 aggregates,
             } => GraphPattern::group(
-                Self::reorder_joins(*inner, input_types),
+                Self::reorder_joins(*inner, input_types, statistics),
                 variables,
                 aggregates,
             ),
@@ -889,7 +1435,11 @@ fn join_key_variables(
         .collect()
 }
 
-fn estimate_graph_pattern_size(pattern: &GraphPattern, input_types: &VariableTypes) -> usize {
+fn estimate_graph_pattern_size(
+    pattern: &GraphPattern,
+    input_types: &VariableTypes,
+    statistics: Option<&dyn StatisticsProvider>,
+) -> usize {
     match pattern {
         GraphPattern::Values { bindings, .. } => bindings.len(),
         GraphPattern::QuadPattern {
@@ -897,11 +1447,27 @@ fn estimate_graph_pattern_size(pattern: &GraphPattern, input_types: &VariableTyp
             predicate,
             object,
             ..
-        } => estimate_triple_pattern_size(
-            is_term_pattern_bound(subject, input_types),
-            is_named_node_pattern_bound(predicate, input_types),
-            is_term_pattern_bound(object, input_types),
-        ),
+        } => {
+            let subject_bound = is_term_pattern_bound(subject, input_types);
+            let object_bound = is_term_pattern_bound(object, input_types);
+            if let (Some(statistics), NamedNodePattern::NamedNode(predicate)) =
+                (statistics, predicate)
+            {
+                if let Some(size) = estimate_triple_pattern_size_from_statistics(
+                    subject_bound,
+                    predicate,
+                    object_bound,
+                    statistics,
+                ) {
+                    return size;
+                }
+            }
+            estimate_triple_pattern_size(
+                subject_bound,
+                is_named_node_pattern_bound(predicate, input_types),
+                object_bound,
+            )
+        }
         GraphPattern::Path {
             subject,
             path,
@@ -911,6 +1477,7 @@ fn estimate_graph_pattern_size(pattern: &GraphPattern, input_types: &VariableTyp
             is_term_pattern_bound(subject, input_types),
             path,
             is_term_pattern_bound(object, input_types),
+            statistics,
         ),
         GraphPattern::Graph { graph_name } => {
             if is_named_node_pattern_bound(graph_name, input_types) {
@@ -923,7 +1490,7 @@ fn estimate_graph_pattern_size(pattern: &GraphPattern, input_types: &VariableTyp
             left,
             right,
             algorithm,
-        } => estimate_join_cost(left, right, algorithm, input_types),
+        } => estimate_join_cost(left, right, algorithm, input_types, statistics),
         GraphPattern::LeftJoin {
             left,
             right,
@@ -931,13 +1498,14 @@ fn estimate_graph_pattern_size(pattern: &GraphPattern, input_types: &VariableTyp
             ..
         } => match algorithm {
             LeftJoinAlgorithm::HashBuildRightProbeLeft { keys } => {
-                let left_size = estimate_graph_pattern_size(left, input_types);
+                let left_size = estimate_graph_pattern_size(left, input_types, statistics);
                 max(
                     left_size,
                     left_size
                         .saturating_mul(estimate_graph_pattern_size(
                             right,
                             &infer_graph_pattern_types(right, input_types.clone()),
+                            statistics,
                         ))
                         .saturating_div(1_000_usize.saturating_pow(keys.len().try_into().unwrap())),
                 )
@@ -949,26 +1517,34 @@ fn estimate_graph_pattern_size(pattern: &GraphPattern, input_types: &VariableTyp
             &infer_graph_pattern_types(left, input_types.clone()),
             right,
             input_types,
+            statistics,
         ),
         GraphPattern::Union { inner } => inner
             .iter()
-            .map(|inner| estimate_graph_pattern_size(inner, input_types))
+            .map(|inner| estimate_graph_pattern_size(inner, input_types, statistics))
             .fold(0, usize::saturating_add),
-        GraphPattern::Minus { left, .. } => estimate_graph_pattern_size(left, input_types),
+        GraphPattern::Minus { left, .. } => {
+            estimate_graph_pattern_size(left, input_types, statistics)
+        }
         GraphPattern::Filter { inner, .. }
         | GraphPattern::Extend { inner, .. }
         | GraphPattern::OrderBy { inner, .. }
         | GraphPattern::Project { inner, .. }
         | GraphPattern::Distinct { inner, .. }
         | GraphPattern::Reduced { inner, .. }
-        | GraphPattern::Group { inner, .. }
-        | GraphPattern::Service { inner, .. } => estimate_graph_pattern_size(inner, input_types),
+        | GraphPattern::Group { inner, .. } => {
+            estimate_graph_pattern_size(inner, input_types, statistics)
+        }
+        GraphPattern::Service { inner, .. } => {
+            // The statistics are about the local dataset, not the remote one
+            estimate_graph_pattern_size(inner, input_types, None)
+        }
         GraphPattern::Slice {
             inner,
             start,
             length,
         } => {
-            let inner = estimate_graph_pattern_size(inner, input_types);
+            let inner = estimate_graph_pattern_size(inner, input_types, statistics);
             if let Some(length) = length {
                 min(inner, *length - *start)
             } else {
@@ -978,16 +1554,55 @@ fn estimate_graph_pattern_size(pattern: &GraphPattern, input_types: &VariableTyp
     }
 }
 
+/// Estimates the cost of joining `right` to `left` and returns if a for loop join should be used to do it
+///
+/// Without statistics a for loop join is used as soon as `right` is fit for it.
+/// With statistics the cheapest of the for loop join and of the hash join is picked.
+fn estimate_join_with_best_algorithm(
+    left: &GraphPattern,
+    left_types: &VariableTypes,
+    right: &GraphPattern,
+    right_types: &VariableTypes,
+    input_types: &VariableTypes,
+    statistics: Option<&dyn StatisticsProvider>,
+) -> (usize, bool) {
+    let hash_join_cost = || {
+        estimate_join_cost(
+            left,
+            right,
+            &JoinAlgorithm::HashBuildLeftProbeRight {
+                keys: join_key_variables(left_types, right_types, input_types),
+            },
+            input_types,
+            statistics,
+        )
+    };
+    if cfg!(feature = "sep-0006") && is_fit_for_for_loop_join(right, input_types, left_types) {
+        let for_loop_join_cost =
+            estimate_lateral_cost(left, left_types, right, input_types, statistics);
+        if statistics.is_some() {
+            let hash_join_cost = hash_join_cost();
+            if hash_join_cost < for_loop_join_cost {
+                return (hash_join_cost, false);
+            }
+        }
+        (for_loop_join_cost, true)
+    } else {
+        (hash_join_cost(), false)
+    }
+}
+
 fn estimate_join_cost(
     left: &GraphPattern,
     right: &GraphPattern,
     algorithm: &JoinAlgorithm,
     input_types: &VariableTypes,
+    statistics: Option<&dyn StatisticsProvider>,
 ) -> usize {
     match algorithm {
         JoinAlgorithm::HashBuildLeftProbeRight { keys } => {
-            estimate_graph_pattern_size(left, input_types)
-                .saturating_mul(estimate_graph_pattern_size(right, input_types))
+            estimate_graph_pattern_size(left, input_types, statistics)
+                .saturating_mul(estimate_graph_pattern_size(right, input_types, statistics))
                 .saturating_div(1_000_usize.saturating_pow(keys.len().try_into().unwrap()))
         }
     }
@@ -997,9 +1612,10 @@ fn estimate_lateral_cost(
     left_types: &VariableTypes,
     right: &GraphPattern,
     input_types: &VariableTypes,
+    statistics: Option<&dyn StatisticsProvider>,
 ) -> usize {
-    estimate_graph_pattern_size(left, input_types)
-        .saturating_mul(estimate_graph_pattern_size(right, left_types))
+    estimate_graph_pattern_size(left, input_types, statistics)
+        .saturating_mul(estimate_graph_pattern_size(right, left_types, statistics))
 }
 
 fn estimate_triple_pattern_size(
@@ -1019,28 +1635,60 @@ fn estimate_triple_pattern_size(
     }
 }
 
-fn estimate_path_size(start_bound: bool, path: &PropertyPathExpression, end_bound: bool) -> usize {
+/// Estimates the size of a triple pattern with a constant predicate from the dataset statistics
+///
+/// The values are assumed to be uniformly distributed between the subjects and between the objects.
+fn estimate_triple_pattern_size_from_statistics(
+    subject_bound: bool,
+    predicate: &NamedNode,
+    object_bound: bool,
+    statistics: &dyn StatisticsProvider,
+) -> Option<usize> {
+    let count = statistics.predicate_count(predicate)?;
+    if count == 0 {
+        return Some(0);
+    }
+    Some(match (subject_bound, object_bound) {
+        (true, true) => 1,
+        (true, false) => max(count / max(statistics.distinct_subjects(predicate)?, 1), 1),
+        (false, true) => max(count / max(statistics.distinct_objects(predicate)?, 1), 1),
+        (false, false) => count,
+    })
+}
+
+fn estimate_path_size(
+    start_bound: bool,
+    path: &PropertyPathExpression,
+    end_bound: bool,
+    statistics: Option<&dyn StatisticsProvider>,
+) -> usize {
     match path {
-        PropertyPathExpression::NamedNode(_) => {
-            estimate_triple_pattern_size(start_bound, true, end_bound)
+        PropertyPathExpression::NamedNode(p) => statistics
+            .and_then(|statistics| {
+                estimate_triple_pattern_size_from_statistics(start_bound, p, end_bound, statistics)
+            })
+            .unwrap_or_else(|| estimate_triple_pattern_size(start_bound, true, end_bound)),
+        PropertyPathExpression::Reverse(p) => {
+            estimate_path_size(end_bound, p, start_bound, statistics)
         }
-        PropertyPathExpression::Reverse(p) => estimate_path_size(end_bound, p, start_bound),
         PropertyPathExpression::Sequence(a, b) => {
             // We do a for loop join in the best direction
             min(
-                estimate_path_size(start_bound, a, false)
-                    .saturating_mul(estimate_path_size(true, b, end_bound)),
-                estimate_path_size(start_bound, a, true)
-                    .saturating_mul(estimate_path_size(false, b, end_bound)),
+                estimate_path_size(start_bound, a, false, statistics)
+                    .saturating_mul(estimate_path_size(true, b, end_bound, statistics)),
+                estimate_path_size(start_bound, a, true, statistics)
+                    .saturating_mul(estimate_path_size(false, b, end_bound, statistics)),
             )
         }
-        PropertyPathExpression::Alternative(a, b) => estimate_path_size(start_bound, a, end_bound)
-            .saturating_add(estimate_path_size(start_bound, b, end_bound)),
+        PropertyPathExpression::Alternative(a, b) => {
+            estimate_path_size(start_bound, a, end_bound, statistics)
+                .saturating_add(estimate_path_size(start_bound, b, end_bound, statistics))
+        }
         PropertyPathExpression::ZeroOrMore(p) => {
             if start_bound && end_bound {
                 1
             } else if start_bound || end_bound {
-                estimate_path_size(start_bound, p, end_bound).saturating_mul(1000)
+                estimate_path_size(start_bound, p, end_bound, statistics).saturating_mul(1000)
             } else {
                 1_000_000_000
             }
@@ -1049,14 +1697,14 @@ fn estimate_path_size(start_bound: bool, path: &PropertyPathExpression, end_boun
             if start_bound && end_bound {
                 1
             } else {
-                estimate_path_size(start_bound, p, end_bound).saturating_mul(1000)
+                estimate_path_size(start_bound, p, end_bound, statistics).saturating_mul(1000)
             }
         }
         PropertyPathExpression::ZeroOrOne(p) => {
             if start_bound && end_bound {
                 1
             } else if start_bound || end_bound {
-                estimate_path_size(start_bound, p, end_bound)
+                estimate_path_size(start_bound, p, end_bound, statistics)
             } else {
                 1_000_000_000
             }
@@ -1086,3 +1734,921 @@ fn is_named_node_pattern_bound(pattern: &NamedNodePattern, input_types: &Variabl
         NamedNodePattern::Variable(v) => !input_types.get(v).undef,
     }
 }
+
+/// Builds the triple patterns equivalent to the path or returns `None` if it is not possible within the budget
+///
+/// The alternatives are deduplicated like the property path evaluator does.
+fn expand_path(
+    subject: &GroundTermPattern,
+    path: &PropertyPathExpression,
+    object: &GroundTermPattern,
+    graph_name: Option<&NamedNodePattern>,
+    budget: &mut usize,
+) -> Option<GraphPattern> {
+    match path {
+        PropertyPathExpression::NamedNode(p) => {
+            *budget = budget.checked_sub(1)?;
+            Some(GraphPattern::QuadPattern {
+                subject: subject.clone(),
+                predicate: p.clone().into(),
+                object: object.clone(),
+                graph_name: graph_name.cloned(),
+            })
+        }
+        PropertyPathExpression::Reverse(p) => expand_path(object, p, subject, graph_name, budget),
+        PropertyPathExpression::Sequence(a, b) => {
+            let middle = GroundTermPattern::from(new_var());
+            Some(GraphPattern::join(
+                expand_path(subject, a, &middle, graph_name, budget)?,
+                expand_path(&middle, b, object, graph_name, budget)?,
+                JoinAlgorithm::default(),
+            ))
+        }
+        PropertyPathExpression::Alternative(a, b) => {
+            Some(GraphPattern::distinct(GraphPattern::project(
+                GraphPattern::union(
+                    expand_path(subject, a, object, graph_name, budget)?,
+                    expand_path(subject, b, object, graph_name, budget)?,
+                ),
+                path_end_variables(subject, object, graph_name),
+            )))
+        }
+        PropertyPathExpression::NegatedPropertySet(ps) => {
+            *budget = budget.checked_sub(1)?;
+            let predicate = new_var();
+            Some(GraphPattern::filter(
+                GraphPattern::QuadPattern {
+                    subject: subject.clone(),
+                    predicate: predicate.clone().into(),
+                    object: object.clone(),
+                    graph_name: graph_name.cloned(),
+                },
+                Expression::and_all(
+                    ps.iter().map(|p| {
+                        !Expression::same_term(predicate.clone().into(), p.clone().into())
+                    }),
+                ),
+            ))
+        }
+        PropertyPathExpression::ZeroOrOne(p) => {
+            Some(GraphPattern::distinct(GraphPattern::project(
+                GraphPattern::union(
+                    zero_length_path(subject, object, graph_name, budget)?,
+                    expand_path(subject, p, object, graph_name, budget)?,
+                ),
+                path_end_variables(subject, object, graph_name),
+            )))
+        }
+        PropertyPathExpression::ZeroOrMore(_) | PropertyPathExpression::OneOrMore(_) => None,
+    }
+}
+
+/// Builds the pattern of the paths of length zero: the subject and the object are the same node of the graph
+///
+/// Like the property path evaluator, the node must be the subject or the object of a triple of the graph.
+fn zero_length_path(
+    subject: &GroundTermPattern,
+    object: &GroundTermPattern,
+    graph_name: Option<&NamedNodePattern>,
+    budget: &mut usize,
+) -> Option<GraphPattern> {
+    // The node to look for in the graph and the variable to bind to it
+    let (node, other_end) = match (subject, object) {
+        #[cfg(feature = "rdf-star")]
+        (GroundTermPattern::Triple(_), _) | (_, GroundTermPattern::Triple(_)) => return None,
+        (_, GroundTermPattern::Variable(v)) => (subject, Some(v)),
+        (GroundTermPattern::Variable(v), _) => (object, Some(v)),
+        _ if subject == object => (subject, None),
+        _ => return Some(GraphPattern::empty()),
+    };
+    *budget = budget.checked_sub(2)?;
+    let mut variables = Vec::new();
+    add_term_pattern_variables(node, &mut variables);
+    if let Some(NamedNodePattern::Variable(v)) = graph_name {
+        variables.push(v.clone());
+    }
+    let nodes = GraphPattern::distinct(GraphPattern::project(
+        GraphPattern::union(
+            GraphPattern::QuadPattern {
+                subject: node.clone(),
+                predicate: new_var().into(),
+                object: new_var().into(),
+                graph_name: graph_name.cloned(),
+            },
+            GraphPattern::QuadPattern {
+                subject: new_var().into(),
+                predicate: new_var().into(),
+                object: node.clone(),
+                graph_name: graph_name.cloned(),
+            },
+        ),
+        variables,
+    ));
+    Some(match other_end {
+        Some(v) if GroundTermPattern::Variable(v.clone()) != *node => {
+            GraphPattern::extend(nodes, v.clone(), term_pattern_to_expression(node)?)
+        }
+        _ => nodes,
+    })
+}
+
+#[cfg_attr(not(feature = "rdf-star"), allow(clippy::unnecessary_wraps))]
+fn term_pattern_to_expression(pattern: &GroundTermPattern) -> Option<Expression> {
+    match pattern {
+        GroundTermPattern::NamedNode(n) => Some(n.clone().into()),
+        GroundTermPattern::Literal(l) => Some(l.clone().into()),
+        GroundTermPattern::Variable(v) => Some(v.clone().into()),
+        #[cfg(feature = "rdf-star")]
+        GroundTermPattern::Triple(_) => None,
+    }
+}
+
+fn path_end_variables(
+    subject: &GroundTermPattern,
+    object: &GroundTermPattern,
+    graph_name: Option<&NamedNodePattern>,
+) -> Vec<Variable> {
+    let mut variables = Vec::new();
+    add_term_pattern_variables(subject, &mut variables);
+    add_term_pattern_variables(object, &mut variables);
+    if let Some(NamedNodePattern::Variable(v)) = graph_name {
+        if !variables.contains(v) {
+            variables.push(v.clone());
+        }
+    }
+    variables
+}
+
+fn add_term_pattern_variables(pattern: &GroundTermPattern, variables: &mut Vec<Variable>) {
+    match pattern {
+        GroundTermPattern::Variable(v) => {
+            if !variables.contains(v) {
+                variables.push(v.clone());
+            }
+        }
+        #[cfg(feature = "rdf-star")]
+        GroundTermPattern::Triple(t) => {
+            add_term_pattern_variables(&t.subject, variables);
+            if let NamedNodePattern::Variable(v) = &t.predicate {
+                if !variables.contains(v) {
+                    variables.push(v.clone());
+                }
+            }
+            add_term_pattern_variables(&t.object, variables);
+        }
+        GroundTermPattern::NamedNode(_) | GroundTermPattern::Literal(_) => (),
+    }
+}
+
+fn push_slice(pattern: GraphPattern, start: usize, length: Option<usize>) -> GraphPattern {
+    match pattern {
+        GraphPattern::Project { inner, variables } => {
+            GraphPattern::project(push_slice(*inner, start, length), variables)
+        }
+        GraphPattern::Extend {
+            inner,
+            variable,
+            expression,
+        } => GraphPattern::extend(push_slice(*inner, start, length), variable, expression),
+        GraphPattern::Union { inner } => {
+            let Some(length) = length else {
+                return GraphPattern::slice(GraphPattern::Union { inner }, start, None);
+            };
+            // Each branch is evaluated one after the other, so we only need the first ones of each branch
+            let limit = start.saturating_add(length);
+            GraphPattern::slice(
+                GraphPattern::Union {
+                    inner: inner
+                        .into_iter()
+                        .map(|branch| limit_first_solutions(branch, limit))
+                        .collect(),
+                },
+                start,
+                Some(length),
+            )
+        }
+        GraphPattern::LeftJoin {
+            left,
+            right,
+            expression,
+            algorithm,
+        } => {
+            let Some(length) = length else {
+                return GraphPattern::slice(
+                    GraphPattern::LeftJoin {
+                        left,
+                        right,
+                        expression,
+                        algorithm,
+                    },
+                    start,
+                    None,
+                );
+            };
+            // Each left solution gives at least one solution
+            GraphPattern::slice(
+                GraphPattern::LeftJoin {
+                    left: Box::new(limit_first_solutions(*left, start.saturating_add(length))),
+                    right,
+                    expression,
+                    algorithm,
+                },
+                start,
+                Some(length),
+            )
+        }
+        _ => GraphPattern::slice(pattern, start, length),
+    }
+}
+
+fn limit_first_solutions(pattern: GraphPattern, limit: usize) -> GraphPattern {
+    if let GraphPattern::Slice {
+        start: 0,
+        length: Some(length),
+        ..
+    } = &pattern
+    {
+        if *length <= limit {
+            // Already limited
+            return pattern;
+        }
+    }
+    push_slice(pattern, 0, Some(limit))
+}
+
+/// Rebuilds the pattern after applying the given functions to its direct children
+fn map_graph_pattern_children(
+    pattern: GraphPattern,
+    on_pattern: &mut impl FnMut(GraphPattern) -> GraphPattern,
+    on_expression: &mut impl FnMut(Expression) -> Expression,
+) -> GraphPattern {
+    match pattern {
+        GraphPattern::QuadPattern { .. }
+        | GraphPattern::Path { .. }
+        | GraphPattern::Graph { .. }
+        | GraphPattern::Values { .. } => pattern,
+        GraphPattern::Join {
+            left,
+            right,
+            algorithm,
+        } => GraphPattern::Join {
+            left: Box::new(on_pattern(*left)),
+            right: Box::new(on_pattern(*right)),
+            algorithm,
+        },
+        GraphPattern::LeftJoin {
+            left,
+            right,
+            expression,
+            algorithm,
+        } => GraphPattern::LeftJoin {
+            left: Box::new(on_pattern(*left)),
+            right: Box::new(on_pattern(*right)),
+            expression: on_expression(expression),
+            algorithm,
+        },
+        #[cfg(feature = "sep-0006")]
+        GraphPattern::Lateral { left, right } => GraphPattern::Lateral {
+            left: Box::new(on_pattern(*left)),
+            right: Box::new(on_pattern(*right)),
+        },
+        GraphPattern::Filter { inner, expression } => GraphPattern::Filter {
+            inner: Box::new(on_pattern(*inner)),
+            expression: on_expression(expression),
+        },
+        GraphPattern::Union { inner } => GraphPattern::Union {
+            inner: inner.into_iter().map(on_pattern).collect(),
+        },
+        GraphPattern::Extend {
+            inner,
+            variable,
+            expression,
+        } => GraphPattern::Extend {
+            inner: Box::new(on_pattern(*inner)),
+            variable,
+            expression: on_expression(expression),
+        },
+        GraphPattern::Minus {
+            left,
+            right,
+            algorithm,
+        } => GraphPattern::Minus {
+            left: Box::new(on_pattern(*left)),
+            right: Box::new(on_pattern(*right)),
+            algorithm,
+        },
+        GraphPattern::OrderBy { inner, expression } => GraphPattern::OrderBy {
+            inner: Box::new(on_pattern(*inner)),
+            expression: expression
+                .into_iter()
+                .map(|e| match e {
+                    OrderExpression::Asc(e) => OrderExpression::Asc(on_expression(e)),
+                    OrderExpression::Desc(e) => OrderExpression::Desc(on_expression(e)),
+                })
+                .collect(),
+        },
+        GraphPattern::Project { inner, variables } => GraphPattern::Project {
+            inner: Box::new(on_pattern(*inner)),
+            variables,
+        },
+        GraphPattern::Distinct { inner } => GraphPattern::Distinct {
+            inner: Box::new(on_pattern(*inner)),
+        },
+        GraphPattern::Reduced { inner } => GraphPattern::Reduced {
+            inner: Box::new(on_pattern(*inner)),
+        },
+        GraphPattern::Slice {
+            inner,
+            start,
+            length,
+        } => GraphPattern::Slice {
+            inner: Box::new(on_pattern(*inner)),
+            start,
+            length,
+        },
+        GraphPattern::Group {
+            inner,
+            variables,
+            aggregates,
+        } => GraphPattern::Group {
+            inner: Box::new(on_pattern(*inner)),
+            variables,
+            aggregates: aggregates
+                .into_iter()
+                .map(|(v, a)| {
+                    (
+                        v,
+                        match a {
+                            AggregateExpression::CountSolutions { distinct } => {
+                                AggregateExpression::CountSolutions { distinct }
+                            }
+                            AggregateExpression::FunctionCall {
+                                name,
+                                expr,
+                                distinct,
+                            } => AggregateExpression::FunctionCall {
+                                name,
+                                expr: on_expression(expr),
+                                distinct,
+                            },
+                        },
+                    )
+                })
+                .collect(),
+        },
+        GraphPattern::Service {
+            name,
+            inner,
+            silent,
+        } => GraphPattern::Service {
+            name,
+            inner: Box::new(on_pattern(*inner)),
+            silent,
+        },
+    }
+}
+
+/// Rebuilds the expression after applying the given functions to its direct children
+fn map_expression_children(
+    expression: Expression,
+    on_pattern: &mut impl FnMut(GraphPattern) -> GraphPattern,
+    on_expression: &mut impl FnMut(Expression) -> Expression,
+) -> Expression {
+    match expression {
+        Expression::NamedNode(_)
+        | Expression::Literal(_)
+        | Expression::Variable(_)
+        | Expression::Bound(_) => expression,
+        Expression::Or(inner) => Expression::Or(inner.into_iter().map(on_expression).collect()),
+        Expression::And(inner) => Expression::And(inner.into_iter().map(on_expression).collect()),
+        Expression::Coalesce(inner) => {
+            Expression::Coalesce(inner.into_iter().map(on_expression).collect())
+        }
+        Expression::FunctionCall(name, args) => {
+            Expression::FunctionCall(name, args.into_iter().map(on_expression).collect())
+        }
+        Expression::Equal(left, right) => Expression::Equal(
+            Box::new(on_expression(*left)),
+            Box::new(on_expression(*right)),
+        ),
+        Expression::SameTerm(left, right) => Expression::SameTerm(
+            Box::new(on_expression(*left)),
+            Box::new(on_expression(*right)),
+        ),
+        Expression::Greater(left, right) => Expression::Greater(
+            Box::new(on_expression(*left)),
+            Box::new(on_expression(*right)),
+        ),
+        Expression::GreaterOrEqual(left, right) => Expression::GreaterOrEqual(
+            Box::new(on_expression(*left)),
+            Box::new(on_expression(*right)),
+        ),
+        Expression::Less(left, right) => Expression::Less(
+            Box::new(on_expression(*left)),
+            Box::new(on_expression(*right)),
+        ),
+        Expression::LessOrEqual(left, right) => Expression::LessOrEqual(
+            Box::new(on_expression(*left)),
+            Box::new(on_expression(*right)),
+        ),
+        Expression::Add(left, right) => Expression::Add(
+            Box::new(on_expression(*left)),
+            Box::new(on_expression(*right)),
+        ),
+        Expression::Subtract(left, right) => Expression::Subtract(
+            Box::new(on_expression(*left)),
+            Box::new(on_expression(*right)),
+        ),
+        Expression::Multiply(left, right) => Expression::Multiply(
+            Box::new(on_expression(*left)),
+            Box::new(on_expression(*right)),
+        ),
+        Expression::Divide(left, right) => Expression::Divide(
+            Box::new(on_expression(*left)),
+            Box::new(on_expression(*right)),
+        ),
+        Expression::UnaryPlus(inner) => Expression::UnaryPlus(Box::new(on_expression(*inner))),
+        Expression::UnaryMinus(inner) => Expression::UnaryMinus(Box::new(on_expression(*inner))),
+        Expression::Not(inner) => Expression::Not(Box::new(on_expression(*inner))),
+        Expression::Exists(inner) => Expression::Exists(Box::new(on_pattern(*inner))),
+        Expression::If(cond, then, els) => Expression::If(
+            Box::new(on_expression(*cond)),
+            Box::new(on_expression(*then)),
+            Box::new(on_expression(*els)),
+        ),
+    }
+}
+
+/// Removes the duplicated expressions, except the non-deterministic ones that might evaluate to different values
+fn deduplicate_expressions(expressions: impl IntoIterator<Item = Expression>) -> Vec<Expression> {
+    let mut output = Vec::new();
+    for expression in expressions {
+        if !is_deterministic_expression(&expression) || !output.contains(&expression) {
+            output.push(expression);
+        }
+    }
+    output
+}
+
+/// Checks that the pattern always returns the same solutions on the same dataset
+fn is_deterministic_pattern(pattern: &GraphPattern) -> bool {
+    match pattern {
+        GraphPattern::QuadPattern { .. }
+        | GraphPattern::Path { .. }
+        | GraphPattern::Graph { .. }
+        | GraphPattern::Values { .. } => true,
+        GraphPattern::Join { left, right, .. } | GraphPattern::Minus { left, right, .. } => {
+            is_deterministic_pattern(left) && is_deterministic_pattern(right)
+        }
+        #[cfg(feature = "sep-0006")]
+        GraphPattern::Lateral { left, right } => {
+            is_deterministic_pattern(left) && is_deterministic_pattern(right)
+        }
+        GraphPattern::LeftJoin {
+            left,
+            right,
+            expression,
+            ..
+        } => {
+            is_deterministic_pattern(left)
+                && is_deterministic_pattern(right)
+                && is_deterministic_expression(expression)
+        }
+        GraphPattern::Filter { inner, expression }
+        | GraphPattern::Extend {
+            inner, expression, ..
+        } => is_deterministic_pattern(inner) && is_deterministic_expression(expression),
+        GraphPattern::Union { inner } => inner.iter().all(is_deterministic_pattern),
+        GraphPattern::OrderBy { inner, expression } => {
+            is_deterministic_pattern(inner)
+                && expression.iter().all(|e| match e {
+                    OrderExpression::Asc(e) | OrderExpression::Desc(e) => {
+                        is_deterministic_expression(e)
+                    }
+                })
+        }
+        GraphPattern::Project { inner, .. }
+        | GraphPattern::Distinct { inner }
+        | GraphPattern::Reduced { inner }
+        | GraphPattern::Slice { inner, .. } => is_deterministic_pattern(inner),
+        GraphPattern::Group {
+            inner, aggregates, ..
+        } => {
+            is_deterministic_pattern(inner)
+                && aggregates.iter().all(|(_, a)| match a {
+                    AggregateExpression::CountSolutions { .. } => true,
+                    AggregateExpression::FunctionCall { expr, .. } => {
+                        is_deterministic_expression(expr)
+                    }
+                })
+        }
+        // The remote endpoint might return different results
+        GraphPattern::Service { .. } => false,
+    }
+}
+
+/// Checks that the expression always evaluates to the same value on the same solution
+fn is_deterministic_expression(expression: &Expression) -> bool {
+    match expression {
+        Expression::NamedNode(_)
+        | Expression::Literal(_)
+        | Expression::Variable(_)
+        | Expression::Bound(_) => true,
+        Expression::Or(inner) | Expression::And(inner) | Expression::Coalesce(inner) => {
+            inner.iter().all(is_deterministic_expression)
+        }
+        Expression::FunctionCall(name, args) => {
+            // We do not know if the custom functions are deterministic
+            !matches!(
+                name,
+                Function::Rand
+                    | Function::BNode
+                    | Function::Uuid
+                    | Function::StrUuid
+                    | Function::Custom(_)
+            ) && args.iter().all(is_deterministic_expression)
+        }
+        Expression::Equal(left, right)
+        | Expression::SameTerm(left, right)
+        | Expression::Greater(left, right)
+        | Expression::GreaterOrEqual(left, right)
+        | Expression::Less(left, right)
+        | Expression::LessOrEqual(left, right)
+        | Expression::Add(left, right)
+        | Expression::Subtract(left, right)
+        | Expression::Multiply(left, right)
+        | Expression::Divide(left, right) => {
+            is_deterministic_expression(left) && is_deterministic_expression(right)
+        }
+        Expression::UnaryPlus(inner) | Expression::UnaryMinus(inner) | Expression::Not(inner) => {
+            is_deterministic_expression(inner)
+        }
+        Expression::Exists(inner) => is_deterministic_pattern(inner),
+        Expression::If(cond, then, els) => {
+            is_deterministic_expression(cond)
+                && is_deterministic_expression(then)
+                && is_deterministic_expression(els)
+        }
+    }
+}
+
+/// Replaces `PROJECT(EXTEND(P, ?new, ?old), V)` with `PROJECT(P[?old -> ?new], V)` if `?old` is not projected
+///
+/// It is only done on basic graph patterns to avoid dealing with variable scoping.
+fn inline_variable_renaming(pattern: GraphPattern, projected: &[Variable]) -> GraphPattern {
+    match pattern {
+        GraphPattern::Extend {
+            inner,
+            variable,
+            expression: Expression::Variable(source),
+        } if source != variable
+            && !projected.contains(&source)
+            && is_basic_graph_pattern(&inner) =>
+        {
+            let mut is_variable_used = false;
+            inner.lookup_used_variables(&mut |v| {
+                if *v == variable {
+                    is_variable_used = true;
+                }
+            });
+            if is_variable_used {
+                GraphPattern::Extend {
+                    inner,
+                    variable,
+                    expression: Expression::Variable(source),
+                }
+            } else {
+                rename_variable(*inner, &source, &variable)
+            }
+        }
+        _ => pattern,
+    }
+}
+
+fn is_basic_graph_pattern(pattern: &GraphPattern) -> bool {
+    match pattern {
+        GraphPattern::QuadPattern { .. } | GraphPattern::Path { .. } => true,
+        GraphPattern::Join { left, right, .. } => {
+            is_basic_graph_pattern(left) && is_basic_graph_pattern(right)
+        }
+        _ => false,
+    }
+}
+
+fn rename_variable(pattern: GraphPattern, from: &Variable, to: &Variable) -> GraphPattern {
+    match pattern {
+        GraphPattern::QuadPattern {
+            subject,
+            predicate,
+            object,
+            graph_name,
+        } => GraphPattern::QuadPattern {
+            subject: rename_term_pattern_variable(subject, from, to),
+            predicate: rename_named_node_pattern_variable(predicate, from, to),
+            object: rename_term_pattern_variable(object, from, to),
+            graph_name: graph_name.map(|g| rename_named_node_pattern_variable(g, from, to)),
+        },
+        GraphPattern::Path {
+            subject,
+            path,
+            object,
+            graph_name,
+        } => GraphPattern::Path {
+            subject: rename_term_pattern_variable(subject, from, to),
+            path,
+            object: rename_term_pattern_variable(object, from, to),
+            graph_name: graph_name.map(|g| rename_named_node_pattern_variable(g, from, to)),
+        },
+        GraphPattern::Join {
+            left,
+            right,
+            algorithm,
+        } => GraphPattern::Join {
+            left: Box::new(rename_variable(*left, from, to)),
+            right: Box::new(rename_variable(*right, from, to)),
+            algorithm,
+        },
+        _ => pattern,
+    }
+}
+
+fn rename_term_pattern_variable(
+    pattern: GroundTermPattern,
+    from: &Variable,
+    to: &Variable,
+) -> GroundTermPattern {
+    match pattern {
+        GroundTermPattern::Variable(v) if v == *from => GroundTermPattern::Variable(to.clone()),
+        #[cfg(feature = "rdf-star")]
+        GroundTermPattern::Triple(t) => GroundTermPattern::Triple(Box::new(GroundTriplePattern {
+            subject: rename_term_pattern_variable(t.subject, from, to),
+            predicate: rename_named_node_pattern_variable(t.predicate, from, to),
+            object: rename_term_pattern_variable(t.object, from, to),
+        })),
+        _ => pattern,
+    }
+}
+
+fn rename_named_node_pattern_variable(
+    pattern: NamedNodePattern,
+    from: &Variable,
+    to: &Variable,
+) -> NamedNodePattern {
+    match pattern {
+        NamedNodePattern::Variable(v) if v == *from => NamedNodePattern::Variable(to.clone()),
+        _ => pattern,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spargebra::Query;
+
+    fn pattern(query: &str) -> GraphPattern {
+        let Query::Select { pattern, .. } = Query::parse(query, None).unwrap() else {
+            unreachable!()
+        };
+        Optimizer::normalize_pattern(GraphPattern::from(&pattern), &VariableTypes::default())
+    }
+
+    fn flatten(query: &str) -> GraphPattern {
+        Optimizer::flatten_subqueries(pattern(query), true)
+    }
+
+    fn eliminate(query: &str) -> GraphPattern {
+        Optimizer::eliminate_common_subexpressions(pattern(query), false)
+    }
+
+    #[test]
+    fn flatten_subquery_without_hidden_variables() {
+        assert_eq!(
+            flatten("SELECT ?s WHERE { { SELECT ?s ?p ?o WHERE { ?s ?p ?o } } ?s ?p ?o }"),
+            pattern("SELECT ?s WHERE { ?s ?p ?o . ?s ?p ?o }")
+        );
+    }
+
+    #[test]
+    fn flatten_keeps_subquery_hiding_variables() {
+        let query = "SELECT ?s WHERE { { SELECT ?s WHERE { ?s ?p ?o } } ?s ?p2 ?o2 }";
+        assert_eq!(flatten(query), pattern(query));
+    }
+
+    #[test]
+    fn flatten_merges_nested_projections() {
+        assert_eq!(
+            flatten(
+                "SELECT ?s ?q WHERE { { SELECT ?s WHERE { { SELECT ?s ?o WHERE { ?s ?p ?o } } } } ?s ?q ?r }"
+            ),
+            pattern("SELECT ?s ?q WHERE { { SELECT ?s WHERE { ?s ?p ?o } } ?s ?q ?r }")
+        );
+    }
+
+    #[test]
+    fn flatten_inlines_variable_renaming() {
+        assert_eq!(
+            flatten("SELECT ?x WHERE { { SELECT ?x WHERE { ?s ?p ?o BIND(?s AS ?x) } } }"),
+            pattern("SELECT ?x WHERE { ?x ?p ?o }")
+        );
+    }
+
+    #[test]
+    fn eliminate_duplicated_conjuncts() {
+        assert_eq!(
+            eliminate("SELECT * WHERE { ?s ?p ?o FILTER(?o > 1 && ?o > 1 || ?o = 0) }"),
+            pattern("SELECT * WHERE { ?s ?p ?o FILTER(?o > 1 || ?o = 0) }")
+        );
+    }
+
+    #[test]
+    fn eliminate_duplicated_union_branches_below_distinct() {
+        assert_eq!(
+            eliminate("SELECT DISTINCT ?s WHERE { { ?s ?p ?o } UNION { ?s ?p ?o } }"),
+            pattern("SELECT DISTINCT ?s WHERE { ?s ?p ?o }")
+        );
+    }
+
+    #[test]
+    fn eliminate_keeps_duplicated_union_branches_with_bag_semantics() {
+        for query in [
+            "SELECT ?s WHERE { { ?s ?p ?o } UNION { ?s ?p ?o } }",
+            "SELECT DISTINCT ?c WHERE { { SELECT (COUNT(*) AS ?c) WHERE { { ?s ?p ?o } UNION { ?s ?p ?o } } } }",
+        ] {
+            // We parse only once because the aggregates get random variable names
+            let pattern = pattern(query);
+            assert_eq!(
+                Optimizer::eliminate_common_subexpressions(pattern.clone(), false),
+                pattern,
+                "{query}"
+            );
+        }
+    }
+
+    #[test]
+    fn eliminate_keeps_non_deterministic_duplicates() {
+        for query in [
+            "SELECT * WHERE { ?s ?p ?o FILTER(RAND() < 0.5 && RAND() < 0.5) }",
+            "SELECT DISTINCT ?r WHERE { { BIND(RAND() AS ?r) } UNION { BIND(RAND() AS ?r) } }",
+            "SELECT DISTINCT ?b WHERE { { BIND(BNODE() AS ?b) } UNION { BIND(BNODE() AS ?b) } }",
+            "SELECT DISTINCT ?u WHERE { { BIND(UUID() AS ?u) } UNION { BIND(UUID() AS ?u) } }",
+            "SELECT DISTINCT ?u WHERE { { BIND(STRUUID() AS ?u) } UNION { BIND(STRUUID() AS ?u) } }",
+        ] {
+            assert_eq!(eliminate(query), pattern(query), "{query}");
+        }
+    }
+
+    #[test]
+    fn push_limits_through_projection_and_union() {
+        let GraphPattern::Project { inner, variables } =
+            pattern("SELECT ?s WHERE { { ?s ?p ?o } UNION { ?s ?q ?o } }")
+        else {
+            unreachable!()
+        };
+        let GraphPattern::Union { inner: branches } = *inner else {
+            unreachable!()
+        };
+        // The branches only need to return the first OFFSET + LIMIT solutions
+        let expected = GraphPattern::project(
+            GraphPattern::slice(
+                GraphPattern::Union {
+                    inner: branches
+                        .into_iter()
+                        .map(|branch| GraphPattern::slice(branch, 0, Some(3)))
+                        .collect(),
+                },
+                1,
+                Some(2),
+            ),
+            variables,
+        );
+        assert_eq!(
+            Optimizer::push_limits(pattern(
+                "SELECT ?s WHERE { { ?s ?p ?o } UNION { ?s ?q ?o } } LIMIT 2 OFFSET 1"
+            )),
+            expected
+        );
+    }
+
+    #[test]
+    fn passes_run_in_order() {
+        let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let optimizer =
+            ["first", "second", "third"]
+                .into_iter()
+                .fold(Optimizer::empty(), |optimizer, name| {
+                    let calls = Arc::clone(&calls);
+                    optimizer.with_pass(name, move |pattern| {
+                        calls.lock().unwrap().push(name);
+                        GraphPattern::distinct(pattern)
+                    })
+                });
+        let input = pattern("SELECT * WHERE { ?s ?p ?o }");
+        assert_eq!(
+            optimizer.optimize(input.clone()),
+            GraphPattern::distinct(GraphPattern::distinct(GraphPattern::distinct(
+                input.clone()
+            )))
+        );
+        assert_eq!(*calls.lock().unwrap(), ["first", "second", "third"]);
+        assert_eq!(
+            optimizer
+                .without_pass("second")
+                .pass_names()
+                .collect::<Vec<_>>(),
+            ["first", "third"]
+        );
+        assert_eq!(Optimizer::empty().optimize(input.clone()), input);
+    }
+
+    #[test]
+    fn without_pass() {
+        let query = "SELECT * WHERE { ?s ?p ?o . ?s ?p2 ?o2 }";
+        let all_passes = Optimizer::default_passes()
+            .pass_names()
+            .map(ToOwned::to_owned)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            Optimizer::default_passes().optimize(pattern(query)),
+            Optimizer::optimize_graph_pattern(pattern(query))
+        );
+        // An unknown pass name is ignored
+        assert_eq!(
+            Optimizer::default_passes()
+                .without_pass("unknown")
+                .pass_names()
+                .collect::<Vec<_>>(),
+            all_passes
+        );
+        // Without any pass, the pattern is kept as it is
+        let without_all_passes = all_passes
+            .iter()
+            .fold(Optimizer::default_passes(), |optimizer, name| {
+                optimizer.without_pass(name)
+            });
+        assert_eq!(without_all_passes.pass_names().count(), 0);
+        assert_eq!(without_all_passes.optimize(pattern(query)), pattern(query));
+        // The configuration methods don't add back a removed pass
+        assert!(!Optimizer::default_passes()
+            .without_pass("path-expansion")
+            .with_path_expansion_limit(100)
+            .pass_names()
+            .any(|name| name == "path-expansion"));
+    }
+
+    #[test]
+    fn path_expansion_limit_configuration() {
+        let query = "SELECT * WHERE { ?s <http://example.com/p>|<http://example.com/q> ?o }";
+        assert!(!contains_path(
+            &Optimizer::default_passes().optimize(pattern(query))
+        ));
+        assert!(contains_path(
+            &Optimizer::default_passes()
+                .with_path_expansion_limit(1)
+                .optimize(pattern(query))
+        ));
+        assert!(contains_path(
+            &Optimizer::default_passes()
+                .without_pass("path-expansion")
+                .optimize(pattern(query))
+        ));
+    }
+
+    fn contains_path(pattern: &GraphPattern) -> bool {
+        let mut found = false;
+        map_graph_pattern_children(
+            pattern.clone(),
+            &mut |p| {
+                found |= matches!(p, GraphPattern::Path { .. }) || contains_path(&p);
+                p
+            },
+            &mut |e| e,
+        );
+        found
+    }
+
+    #[test]
+    fn expand_bounded_paths() {
+        for query in [
+            "SELECT * WHERE { ?s <http://example.com/p>? ?o }",
+            "SELECT * WHERE { <http://example.com/a> <http://example.com/p>? ?o }",
+            "SELECT * WHERE { ?s <http://example.com/p>? <http://example.com/a> }",
+            "SELECT * WHERE { ?s <http://example.com/p>? ?s }",
+            "SELECT * WHERE { GRAPH ?g { ?s <http://example.com/p>? ?o } }",
+            // Between 1 and 3 times
+            "SELECT * WHERE { ?s <http://example.com/p>/(<http://example.com/p>/<http://example.com/p>?)? ?o }",
+        ] {
+            let expanded = Optimizer::expand_paths(pattern(query), DEFAULT_PATH_EXPANSION_LIMIT);
+            assert!(!contains_path(&expanded), "{query}: {expanded:?}");
+        }
+    }
+
+    #[test]
+    fn expand_paths_within_limit() {
+        // Each optional repetition needs 3 triple patterns, the first step is already a triple pattern after the normalization
+        let query = "SELECT * WHERE { ?s <http://example.com/p>/(<http://example.com/p>/<http://example.com/p>?)? ?o }";
+        assert!(!contains_path(&Optimizer::expand_paths(pattern(query), 6)));
+        assert!(contains_path(&Optimizer::expand_paths(pattern(query), 5)));
+        let query = "SELECT * WHERE { ?s <http://example.com/p>* ?o }";
+        assert!(contains_path(&Optimizer::expand_paths(pattern(query), 100)));
+    }
+}
@@ -0,0 +1,118 @@
+use oxrdf::NamedNode;
+use std::collections::HashMap;
+
+/// Provides statistics about a dataset to the [`Optimizer`](crate::Optimizer).
+///
+/// They are used to estimate the cardinality of the triple patterns in order to pick the join order and the join algorithms.
+/// Each method returns `None` if the value is unknown, the optimizer then falls back to its default heuristics.
+///
+/// It is implemented by [`DatasetStatistics`] and might be implemented by any dataset able to compute them cheaply.
+pub trait StatisticsProvider: Send + Sync {
+    /// The number of triples with the given predicate.
+    fn predicate_count(&self, predicate: &NamedNode) -> Option<usize>;
+
+    /// The number of distinct subjects of the triples with the given predicate.
+    fn distinct_subjects(&self, predicate: &NamedNode) -> Option<usize>;
+
+    /// The number of distinct objects of the triples with the given predicate.
+    fn distinct_objects(&self, predicate: &NamedNode) -> Option<usize>;
+}
+
+/// Statistics about all the predicates of a dataset.
+///
+/// The predicates without statistics are considered as not used in the dataset.
+///
+/// ```
+/// use oxrdf::NamedNode;
+/// use sparopt::{DatasetStatistics, PredicateStatistics, StatisticsProvider};
+///
+/// let name = NamedNode::new("http://schema.org/name")?;
+/// let mut statistics = DatasetStatistics::new();
+/// statistics.insert(
+///     name.clone(),
+///     PredicateStatistics {
+///         count: 10,
+///         distinct_subjects: 8,
+///         distinct_objects: 5,
+///     },
+/// );
+/// assert_eq!(statistics.predicate_count(&name), Some(10));
+/// assert_eq!(
+///     statistics.predicate_count(&NamedNode::new("http://schema.org/knows")?),
+///     Some(0)
+/// );
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DatasetStatistics {
+    predicates: HashMap<NamedNode, PredicateStatistics>,
+}
+
+/// The statistics about a predicate stored in [`DatasetStatistics`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct PredicateStatistics {
+    /// The number of triples with the predicate.
+    pub count: usize,
+    /// The number of distinct subjects of the triples with the predicate.
+    pub distinct_subjects: usize,
+    /// The number of distinct objects of the triples with the predicate.
+    pub distinct_objects: usize,
+}
+
+impl DatasetStatistics {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the statistics of a predicate.
+    #[inline]
+    pub fn insert(&mut self, predicate: NamedNode, statistics: PredicateStatistics) {
+        self.predicates.insert(predicate, statistics);
+    }
+
+    /// The statistics of a predicate, if it is used in the dataset.
+    #[inline]
+    pub fn get(&self, predicate: &NamedNode) -> Option<&PredicateStatistics> {
+        self.predicates.get(predicate)
+    }
+
+    /// The number of predicates with statistics.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.predicates.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.predicates.is_empty()
+    }
+
+    /// Iterates on the predicates and their statistics, in no particular order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&NamedNode, &PredicateStatistics)> {
+        self.predicates.iter()
+    }
+}
+
+impl StatisticsProvider for DatasetStatistics {
+    fn predicate_count(&self, predicate: &NamedNode) -> Option<usize> {
+        Some(self.get(predicate).map_or(0, |s| s.count))
+    }
+
+    fn distinct_subjects(&self, predicate: &NamedNode) -> Option<usize> {
+        Some(self.get(predicate).map_or(0, |s| s.distinct_subjects))
+    }
+
+    fn distinct_objects(&self, predicate: &NamedNode) -> Option<usize> {
+        Some(self.get(predicate).map_or(0, |s| s.distinct_objects))
+    }
+}
+
+impl FromIterator<(NamedNode, PredicateStatistics)> for DatasetStatistics {
+    fn from_iter<I: IntoIterator<Item = (NamedNode, PredicateStatistics)>>(iter: I) -> Self {
+        Self {
+            predicates: iter.into_iter().collect(),
+        }
+    }
+}
@@ -0,0 +1,426 @@
+use crate::error::SparqlClientError;
+use oxhttp::model::{Body, HeaderName, Method, Request, Response, Status, Url};
+use oxrdf::{GraphNameRef, NamedNodeRef, TripleRef};
+use oxrdfio::{RdfFormat, RdfParser, RdfSerializer, ReaderQuadParser};
+use sparesults::{
+    QueryResultsFormat, QueryResultsParser, ReaderQueryResultsParserOutput, ReaderSolutionsParser,
+};
+use std::io;
+use std::thread::sleep;
+use std::time::Duration;
+
+/// The formats accepted for query results, JSON and N-Triples are preferred because they are the most widely supported
+const QUERY_ACCEPT: &str = "application/sparql-results+json, application/sparql-results+xml;q=0.9, text/tab-separated-values;q=0.8, application/n-triples, text/turtle;q=0.9, application/rdf+xml;q=0.8";
+const GRAPH_ACCEPT: &str = "application/n-triples, text/turtle;q=0.9, application/rdf+xml;q=0.8";
+
+/// A blocking client for the [SPARQL 1.1 Protocol](https://www.w3.org/TR/sparql11-protocol/)
+/// and the [SPARQL 1.1 Graph Store HTTP Protocol](https://www.w3.org/TR/sparql11-http-rdf-update/).
+///
+/// Redirections are not followed by default, use [`with_redirection_limit`](Self::with_redirection_limit) to change it.
+///
+/// ```no_run
+/// use oxrdf::{GraphNameRef, NamedNodeRef, TripleRef};
+/// use sparql_client::SparqlClient;
+///
+/// let client = SparqlClient::new().with_retries(2);
+/// let ex = NamedNodeRef::new("http://example.com")?;
+/// client.update(
+///     "http://localhost:7878/update",
+///     "INSERT DATA { <http://example.com> <http://example.com> <http://example.com> }",
+/// )?;
+/// client.put_graph(
+///     "http://localhost:7878/store",
+///     GraphNameRef::NamedNode(ex),
+///     [TripleRef::new(ex, ex, ex)],
+/// )?;
+/// for quad in client.get_graph("http://localhost:7878/store", GraphNameRef::NamedNode(ex))? {
+///     println!("{}", quad?);
+/// }
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+pub struct SparqlClient {
+    client: oxhttp::Client,
+    retries: usize,
+    retry_delay: Duration,
+}
+
+impl SparqlClient {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the global timeout of each HTTP request (connection, request sending and response reading).
+    ///
+    /// By default, there is no timeout.
+    #[inline]
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client = self.client.with_global_timeout(timeout);
+        self
+    }
+
+    /// Sets the maximal number of HTTP redirections to follow.
+    ///
+    /// By default, redirections are not followed.
+    #[inline]
+    #[must_use]
+    pub fn with_redirection_limit(mut self, redirection_limit: usize) -> Self {
+        self.client = self.client.with_redirection_limit(redirection_limit);
+        self
+    }
+
+    /// Sets the `User-Agent` header sent with the requests.
+    #[inline]
+    pub fn with_user_agent(
+        mut self,
+        user_agent: impl Into<String>,
+    ) -> Result<Self, SparqlClientError> {
+        self.client = self
+            .client
+            .with_user_agent(user_agent)
+            .map_err(SparqlClientError::invalid_request)?;
+        Ok(self)
+    }
+
+    /// Sets the number of times a request is retried if it fails because of an I/O error (connection failure, timeout...)
+    /// or if the server returns a `429 Too Many Requests`, `502 Bad Gateway`, `503 Service Unavailable` or `504 Gateway Timeout` status.
+    ///
+    /// By default, the requests are not retried.
+    ///
+    /// Beware that an update might be applied twice if it is retried after the server received it.
+    #[inline]
+    #[must_use]
+    pub fn with_retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Sets the delay before the first retry. It is doubled after each retry.
+    ///
+    /// By default, it is one second.
+    #[inline]
+    #[must_use]
+    pub fn with_retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// Evaluates a SPARQL query against the given SPARQL endpoint.
+    ///
+    /// The query is sent in the request body using the `application/sparql-query` content type.
+    /// The query results format is negotiated with the server.
+    pub fn query(&self, endpoint: &str, query: &str) -> Result<QueryResponse, SparqlClientError> {
+        self.query_with_dataset(endpoint, query, [], [])
+    }
+
+    /// Evaluates a SPARQL query against the given SPARQL endpoint with the given [RDF dataset](https://www.w3.org/TR/sparql11-query/#rdfDataset).
+    ///
+    /// The graphs are sent using the `default-graph-uri` and `named-graph-uri` [protocol parameters](https://www.w3.org/TR/sparql11-protocol/#dataset).
+    /// Following the protocol, they override the `FROM` and `FROM NAMED` clauses of the query.
+    ///
+    /// ```no_run
+    /// use oxrdf::NamedNodeRef;
+    /// use sparql_client::SparqlClient;
+    ///
+    /// let graph = NamedNodeRef::new("http://example.com/graph")?;
+    /// SparqlClient::new().query_with_dataset(
+    ///     "http://localhost:7878/query",
+    ///     "SELECT * WHERE { ?s ?p ?o }",
+    ///     [graph],
+    ///     [],
+    /// )?;
+    /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn query_with_dataset<'a>(
+        &self,
+        endpoint: &str,
+        query: &str,
+        default_graph_uris: impl IntoIterator<Item = NamedNodeRef<'a>>,
+        named_graph_uris: impl IntoIterator<Item = NamedNodeRef<'a>>,
+    ) -> Result<QueryResponse, SparqlClientError> {
+        let url = dataset_url(
+            endpoint,
+            ("default-graph-uri", default_graph_uris),
+            ("named-graph-uri", named_graph_uris),
+        )?;
+        let response = self.send(|| {
+            Ok(Request::builder(Method::POST, url.clone())
+                .with_header(HeaderName::ACCEPT, QUERY_ACCEPT)
+                .map_err(SparqlClientError::invalid_request)?
+                .with_header(HeaderName::CONTENT_TYPE, "application/sparql-query")
+                .map_err(SparqlClientError::invalid_request)?
+                .with_body(query.to_owned()))
+        })?;
+        let content_type = content_type(&response, endpoint)?;
+        if let Some(format) = QueryResultsFormat::from_media_type(&content_type) {
+            return Ok(
+                match QueryResultsParser::from_format(format).for_reader(response.into_body())? {
+                    ReaderQueryResultsParserOutput::Solutions(solutions) => {
+                        QueryResponse::Solutions(Box::new(solutions))
+                    }
+                    ReaderQueryResultsParserOutput::Boolean(value) => QueryResponse::Boolean(value),
+                },
+            );
+        }
+        if let Some(format) = RdfFormat::from_media_type(&content_type) {
+            return Ok(QueryResponse::Graph(Box::new(graph_parser(
+                format,
+                endpoint,
+                response.into_body(),
+            )?)));
+        }
+        Err(SparqlClientError::UnsupportedContentType {
+            url: endpoint.into(),
+            content_type,
+        })
+    }
+
+    /// Executes a SPARQL update against the given SPARQL endpoint.
+    ///
+    /// The update is sent in the request body using the `application/sparql-update` content type.
+    pub fn update(&self, endpoint: &str, update: &str) -> Result<(), SparqlClientError> {
+        self.update_with_dataset(endpoint, update, [], [])
+    }
+
+    /// Executes a SPARQL update against the given SPARQL endpoint with the given [RDF dataset](https://www.w3.org/TR/sparql11-update/#deleteInsert).
+    ///
+    /// The graphs are sent using the `using-graph-uri` and `using-named-graph-uri` [protocol parameters](https://www.w3.org/TR/sparql11-protocol/#update-dataset).
+    /// Following the protocol, they are used as `USING` and `USING NAMED` clauses of the update operations.
+    pub fn update_with_dataset<'a>(
+        &self,
+        endpoint: &str,
+        update: &str,
+        using_graph_uris: impl IntoIterator<Item = NamedNodeRef<'a>>,
+        using_named_graph_uris: impl IntoIterator<Item = NamedNodeRef<'a>>,
+    ) -> Result<(), SparqlClientError> {
+        let url = dataset_url(
+            endpoint,
+            ("using-graph-uri", using_graph_uris),
+            ("using-named-graph-uri", using_named_graph_uris),
+        )?;
+        self.send(|| {
+            Ok(Request::builder(Method::POST, url.clone())
+                .with_header(HeaderName::CONTENT_TYPE, "application/sparql-update")
+                .map_err(SparqlClientError::invalid_request)?
+                .with_body(update.to_owned()))
+        })?;
+        Ok(())
+    }
+
+    /// Retrieves the content of a graph from a graph store.
+    ///
+    /// The returned quads are all in the default graph.
+    pub fn get_graph(
+        &self,
+        graph_store: &str,
+        graph_name: GraphNameRef<'_>,
+    ) -> Result<ReaderQuadParser<Body>, SparqlClientError> {
+        let url = graph_url(graph_store, graph_name)?;
+        let response = self.send(|| {
+            Ok(Request::builder(Method::GET, url.clone())
+                .with_header(HeaderName::ACCEPT, GRAPH_ACCEPT)
+                .map_err(SparqlClientError::invalid_request)?
+                .build())
+        })?;
+        let content_type = content_type(&response, url.as_str())?;
+        let format = RdfFormat::from_media_type(&content_type).ok_or_else(|| {
+            SparqlClientError::UnsupportedContentType {
+                url: url.to_string(),
+                content_type,
+            }
+        })?;
+        graph_parser(format, url.as_str(), response.into_body())
+    }
+
+    /// Replaces the content of a graph of a graph store by the given triples.
+    pub fn put_graph<'a>(
+        &self,
+        graph_store: &str,
+        graph_name: GraphNameRef<'_>,
+        triples: impl IntoIterator<Item = impl Into<TripleRef<'a>>>,
+    ) -> Result<(), SparqlClientError> {
+        let url = graph_url(graph_store, graph_name)?;
+        let payload = serialize_triples(triples)?;
+        self.send(|| graph_request(Method::PUT, &url, &payload))?;
+        Ok(())
+    }
+
+    /// Adds the given triples to a graph of a graph store.
+    pub fn post_graph<'a>(
+        &self,
+        graph_store: &str,
+        graph_name: GraphNameRef<'_>,
+        triples: impl IntoIterator<Item = impl Into<TripleRef<'a>>>,
+    ) -> Result<(), SparqlClientError> {
+        let url = graph_url(graph_store, graph_name)?;
+        let payload = serialize_triples(triples)?;
+        self.send(|| graph_request(Method::POST, &url, &payload))?;
+        Ok(())
+    }
+
+    /// Deletes a graph of a graph store.
+    ///
+    /// Following the protocol, an error is returned if the graph does not exist.
+    pub fn delete_graph(
+        &self,
+        graph_store: &str,
+        graph_name: GraphNameRef<'_>,
+    ) -> Result<(), SparqlClientError> {
+        let url = graph_url(graph_store, graph_name)?;
+        self.send(|| Ok(Request::builder(Method::DELETE, url.clone()).build()))?;
+        Ok(())
+    }
+
+    /// Sends the request built by `build_request`, retrying it if needed, and returns the successful response.
+    fn send(
+        &self,
+        build_request: impl Fn() -> Result<Request, SparqlClientError>,
+    ) -> Result<Response, SparqlClientError> {
+        let mut remaining_retries = self.retries;
+        let mut retry_delay = self.retry_delay;
+        loop {
+            let request = build_request()?;
+            let url = request.url().to_string();
+            let error = match self.client.request(request) {
+                Ok(response) if response.status().is_successful() => return Ok(response),
+                Ok(response) => {
+                    let status = response.status();
+                    let is_retryable = is_retryable(status);
+                    let error = SparqlClientError::Http {
+                        status,
+                        url,
+                        message: response.into_body().to_string()?,
+                    };
+                    if !is_retryable {
+                        return Err(error);
+                    }
+                    error
+                }
+                Err(error) => error.into(),
+            };
+            if remaining_retries == 0 {
+                return Err(error);
+            }
+            remaining_retries -= 1;
+            sleep(retry_delay);
+            retry_delay = retry_delay.saturating_mul(2);
+        }
+    }
+}
+
+impl Default for SparqlClient {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            client: oxhttp::Client::new()
+                .with_user_agent(concat!("sparql-client/", env!("CARGO_PKG_VERSION")))
+                .unwrap(),
+            retries: 0,
+            retry_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+/// The results of a query returned by [`SparqlClient::query`].
+pub enum QueryResponse {
+    /// Results of a [SELECT](https://www.w3.org/TR/sparql11-query/#select) query.
+    Solutions(Box<ReaderSolutionsParser<Body>>),
+    /// Result of a [ASK](https://www.w3.org/TR/sparql11-query/#ask) query.
+    Boolean(bool),
+    /// Results of a [CONSTRUCT](https://www.w3.org/TR/sparql11-query/#construct) or [DESCRIBE](https://www.w3.org/TR/sparql11-query/#describe) query.
+    ///
+    /// The returned quads are all in the default graph.
+    Graph(Box<ReaderQuadParser<Body>>),
+}
+
+fn is_retryable(status: Status) -> bool {
+    // oxhttp has no constant for 429 Too Many Requests
+    u16::from(status) == 429
+        || status == Status::BAD_GATEWAY
+        || status == Status::SERVICE_UNAVAILABLE
+        || status == Status::GATEWAY_TIMEOUT
+}
+
+fn parse_url(url: &str) -> Result<Url, SparqlClientError> {
+    Url::parse(url).map_err(SparqlClientError::invalid_request)
+}
+
+/// Adds the dataset protocol parameters to the endpoint URL
+fn dataset_url<'a>(
+    endpoint: &str,
+    (default_parameter, default_graphs): (&str, impl IntoIterator<Item = NamedNodeRef<'a>>),
+    (named_parameter, named_graphs): (&str, impl IntoIterator<Item = NamedNodeRef<'a>>),
+) -> Result<Url, SparqlClientError> {
+    let mut url = parse_url(endpoint)?;
+    let mut default_graphs = default_graphs.into_iter().peekable();
+    let mut named_graphs = named_graphs.into_iter().peekable();
+    if default_graphs.peek().is_some() || named_graphs.peek().is_some() {
+        let mut query_pairs = url.query_pairs_mut();
+        for graph in default_graphs {
+            query_pairs.append_pair(default_parameter, graph.as_str());
+        }
+        for graph in named_graphs {
+            query_pairs.append_pair(named_parameter, graph.as_str());
+        }
+    }
+    Ok(url)
+}
+
+/// Builds the URL of a graph following the [indirect graph identification](https://www.w3.org/TR/sparql11-http-rdf-update/#indirect-graph-identification)
+fn graph_url(graph_store: &str, graph_name: GraphNameRef<'_>) -> Result<Url, SparqlClientError> {
+    let mut url = parse_url(graph_store)?;
+    match graph_name {
+        GraphNameRef::NamedNode(graph_name) => {
+            url.query_pairs_mut()
+                .append_pair("graph", graph_name.as_str());
+        }
+        GraphNameRef::DefaultGraph => {
+            url.query_pairs_mut().append_key_only("default");
+        }
+        GraphNameRef::BlankNode(graph_name) => {
+            return Err(SparqlClientError::invalid_request(format!(
+                "The blank node {graph_name} can't be used as a graph store graph name"
+            )))
+        }
+    }
+    Ok(url)
+}
+
+fn content_type(response: &Response, url: &str) -> Result<String, SparqlClientError> {
+    Ok(response
+        .header(&HeaderName::CONTENT_TYPE)
+        .ok_or_else(|| SparqlClientError::MissingContentType { url: url.into() })?
+        .to_str()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+        .to_owned())
+}
+
+fn serialize_triples<'a>(
+    triples: impl IntoIterator<Item = impl Into<TripleRef<'a>>>,
+) -> Result<Vec<u8>, SparqlClientError> {
+    let mut serializer = RdfSerializer::from_format(RdfFormat::NTriples).for_writer(Vec::new());
+    for triple in triples {
+        serializer.serialize_triple(triple)?;
+    }
+    Ok(serializer.finish()?)
+}
+
+fn graph_request(method: Method, url: &Url, payload: &[u8]) -> Result<Request, SparqlClientError> {
+    Ok(Request::builder(method, url.clone())
+        .with_header(HeaderName::CONTENT_TYPE, RdfFormat::NTriples.media_type())
+        .map_err(SparqlClientError::invalid_request)?
+        .with_body(payload.to_vec()))
+}
+
+fn graph_parser(
+    format: RdfFormat,
+    base_iri: &str,
+    body: Body,
+) -> Result<ReaderQuadParser<Body>, SparqlClientError> {
+    Ok(RdfParser::from_format(format)
+        .with_base_iri(base_iri)
+        .map_err(SparqlClientError::invalid_request)?
+        .without_named_graphs()
+        .for_reader(body))
+}
@@ -0,0 +1,58 @@
+use oxhttp::model::Status;
+use oxrdfio::RdfParseError;
+use sparesults::QueryResultsParseError;
+use std::error::Error;
+use std::io;
+
+/// An error returned by a [`SparqlClient`](crate::SparqlClient) operation.
+#[derive(Debug, thiserror::Error)]
+pub enum SparqlClientError {
+    /// The request can't be built (invalid URL, invalid header value...).
+    #[error("Invalid request: {0}")]
+    InvalidRequest(#[source] Box<dyn Error + Send + Sync + 'static>),
+    /// I/O error during the HTTP exchange (connection refused, timeout...).
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// The server returned an error status code.
+    #[error("Error {status} returned by {url} with payload:\n{message}")]
+    Http {
+        status: Status,
+        url: String,
+        message: String,
+    },
+    /// The server returned no content type.
+    #[error("No Content-Type returned by {url}")]
+    MissingContentType { url: String },
+    /// The server returned a content type not supported by the operation.
+    #[error("Unsupported Content-Type returned by {url}: {content_type}")]
+    UnsupportedContentType { url: String, content_type: String },
+    /// The returned query results are invalid.
+    #[error(transparent)]
+    QueryResultsParse(#[from] QueryResultsParseError),
+    /// The returned RDF graph is invalid.
+    #[error(transparent)]
+    RdfParse(#[from] RdfParseError),
+}
+
+impl SparqlClientError {
+    pub(crate) fn invalid_request(
+        error: impl Into<Box<dyn Error + Send + Sync + 'static>>,
+    ) -> Self {
+        Self::InvalidRequest(error.into())
+    }
+}
+
+impl From<SparqlClientError> for io::Error {
+    #[inline]
+    fn from(error: SparqlClientError) -> Self {
+        match error {
+            SparqlClientError::Io(error) => error,
+            SparqlClientError::QueryResultsParse(error) => error.into(),
+            SparqlClientError::RdfParse(error) => error.into(),
+            SparqlClientError::InvalidRequest(_) => Self::new(io::ErrorKind::InvalidInput, error),
+            SparqlClientError::Http { .. }
+            | SparqlClientError::MissingContentType { .. }
+            | SparqlClientError::UnsupportedContentType { .. } => Self::other(error),
+        }
+    }
+}
@@ -0,0 +1,224 @@
+#![cfg(test)]
+#![allow(clippy::panic_in_result_fn)]
+
+use oxhttp::model::{HeaderName, Method, Request, Response, Status};
+use oxhttp::Server;
+use oxrdf::{GraphNameRef, NamedNode, NamedNodeRef, Term, TripleRef};
+use sparql_client::{QueryResponse, SparqlClient, SparqlClientError};
+use std::error::Error;
+use std::io::Read;
+use std::net::{Ipv4Addr, SocketAddr, TcpListener};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+/// Starts a local HTTP server and returns its base URL
+fn serve(
+    handler: impl Fn(&mut Request) -> Response + Send + Sync + 'static,
+) -> Result<String, Box<dyn Error>> {
+    let address = TcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, 0)))?.local_addr()?;
+    Server::new(handler)
+        .with_global_timeout(Duration::from_secs(10))
+        .bind(address)
+        .spawn()?;
+    Ok(format!("http://{address}"))
+}
+
+fn response(content_type: &'static str, body: &str) -> Response {
+    Response::builder(Status::OK)
+        .with_header(HeaderName::CONTENT_TYPE, content_type)
+        .unwrap()
+        .with_body(body.to_owned())
+}
+
+#[test]
+fn test_select() -> Result<(), Box<dyn Error>> {
+    let url = serve(|request| {
+        let mut query = String::new();
+        request.body_mut().read_to_string(&mut query).unwrap();
+        assert_eq!(
+            request
+                .header(&HeaderName::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok()),
+            Some("application/sparql-query")
+        );
+        assert_eq!(query, "SELECT ?s WHERE { ?s ?p ?o }");
+        response(
+            "application/sparql-results+json; charset=utf-8",
+            r#"{"head":{"vars":["s"]},"results":{"bindings":[{"s":{"type":"uri","value":"http://example.com/s"}}]}}"#,
+        )
+    })?;
+    let QueryResponse::Solutions(solutions) =
+        SparqlClient::new().query(&format!("{url}/query"), "SELECT ?s WHERE { ?s ?p ?o }")?
+    else {
+        return Err("Solutions expected".into());
+    };
+    let solutions = solutions.collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(solutions.len(), 1);
+    assert_eq!(
+        solutions[0].get("s"),
+        Some(&Term::from(NamedNode::new("http://example.com/s")?))
+    );
+    Ok(())
+}
+
+#[test]
+fn test_construct() -> Result<(), Box<dyn Error>> {
+    let url = serve(|_| response("text/turtle", "<s> <p> <o> ."))?;
+    let endpoint = format!("{url}/query");
+    let QueryResponse::Graph(quads) =
+        SparqlClient::new().query(&endpoint, "CONSTRUCT WHERE { ?s ?p ?o }")?
+    else {
+        return Err("Graph expected".into());
+    };
+    // Relative IRIs are resolved against the endpoint URL
+    assert_eq!(
+        quads.collect::<Result<Vec<_>, _>>()?,
+        [TripleRef::new(
+            NamedNodeRef::new(&format!("{url}/s"))?,
+            NamedNodeRef::new(&format!("{url}/p"))?,
+            NamedNodeRef::new(&format!("{url}/o"))?,
+        )
+        .in_graph(GraphNameRef::DefaultGraph)
+        .into_owned()]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_retries() -> Result<(), Box<dyn Error>> {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let url = serve({
+        let calls = Arc::clone(&calls);
+        move |_| match calls.fetch_add(1, Ordering::Relaxed) {
+            0 => Response::builder(Status::try_from(429).unwrap()).build(),
+            1 => Response::builder(Status::SERVICE_UNAVAILABLE).build(),
+            _ => response(
+                "application/sparql-results+json",
+                r#"{"head":{},"boolean":true}"#,
+            ),
+        }
+    })?;
+    let endpoint = format!("{url}/query");
+
+    // Without retries, the error is returned
+    let SparqlClientError::Http { status, .. } = SparqlClient::new()
+        .query(&endpoint, "ASK {}")
+        .err()
+        .unwrap()
+    else {
+        return Err("HTTP error expected".into());
+    };
+    assert_eq!(u16::from(status), 429);
+
+    let client = SparqlClient::new()
+        .with_retries(1)
+        .with_retry_delay(Duration::from_millis(1));
+    assert!(matches!(
+        client.query(&endpoint, "ASK {}")?,
+        QueryResponse::Boolean(true)
+    ));
+    assert_eq!(calls.load(Ordering::Relaxed), 3);
+    Ok(())
+}
+
+#[test]
+fn test_graph_store() -> Result<(), Box<dyn Error>> {
+    let graph = Arc::new(Mutex::new(None));
+    let url = serve({
+        let graph = Arc::clone(&graph);
+        move |request| {
+            assert_eq!(
+                request.url().query(),
+                Some("graph=http%3A%2F%2Fexample.com%2Fg")
+            );
+            let mut graph = graph.lock().unwrap_or_else(PoisonError::into_inner);
+            if *request.method() == Method::PUT {
+                let mut content = String::new();
+                request.body_mut().read_to_string(&mut content).unwrap();
+                *graph = Some(content);
+                Response::builder(Status::CREATED).build()
+            } else if *request.method() == Method::GET {
+                match &*graph {
+                    Some(content) => response("application/n-triples", content),
+                    None => Response::builder(Status::NOT_FOUND).build(),
+                }
+            } else if *request.method() == Method::DELETE && graph.take().is_some() {
+                Response::builder(Status::NO_CONTENT).build()
+            } else {
+                Response::builder(Status::NOT_FOUND).build()
+            }
+        }
+    })?;
+    let graph_store = format!("{url}/store");
+    let client = SparqlClient::new();
+    let g = GraphNameRef::NamedNode(NamedNodeRef::new("http://example.com/g")?);
+    let ex = NamedNodeRef::new("http://example.com/s")?;
+    let triple = TripleRef::new(ex, ex, ex);
+
+    client.put_graph(&graph_store, g, [triple])?;
+    assert_eq!(
+        client
+            .get_graph(&graph_store, g)?
+            .collect::<Result<Vec<_>, _>>()?,
+        [triple.in_graph(GraphNameRef::DefaultGraph).into_owned()]
+    );
+    client.delete_graph(&graph_store, g)?;
+    let SparqlClientError::Http { status, .. } = client.delete_graph(&graph_store, g).unwrap_err()
+    else {
+        return Err("HTTP error expected".into());
+    };
+    assert_eq!(status, Status::NOT_FOUND);
+    Ok(())
+}
+
+#[test]
+fn test_query_dataset() -> Result<(), Box<dyn Error>> {
+    let url = serve(|request| {
+        assert_eq!(
+            request.url().query(),
+            Some("default-graph-uri=http%3A%2F%2Fexample.com%2Fg1&default-graph-uri=http%3A%2F%2Fexample.com%2Fg2&named-graph-uri=http%3A%2F%2Fexample.com%2Fg3")
+        );
+        response(
+            "application/sparql-results+json",
+            r#"{"head":{},"boolean":true}"#,
+        )
+    })?;
+    let response = SparqlClient::new().query_with_dataset(
+        &format!("{url}/query"),
+        "ASK {}",
+        [
+            NamedNodeRef::new("http://example.com/g1")?,
+            NamedNodeRef::new("http://example.com/g2")?,
+        ],
+        [NamedNodeRef::new("http://example.com/g3")?],
+    )?;
+    assert!(matches!(response, QueryResponse::Boolean(true)));
+    Ok(())
+}
+
+#[test]
+fn test_update_dataset() -> Result<(), Box<dyn Error>> {
+    let url = serve(|request| {
+        let mut update = String::new();
+        request.body_mut().read_to_string(&mut update).unwrap();
+        assert_eq!(update, "DELETE WHERE { ?s ?p ?o }");
+        match request.url().query() {
+            None => Response::builder(Status::NO_CONTENT).build(),
+            Some("using-named-graph-uri=http%3A%2F%2Fexample.com%2Fg") => {
+                Response::builder(Status::OK).build()
+            }
+            Some(_) => Response::builder(Status::BAD_REQUEST).build(),
+        }
+    })?;
+    let endpoint = format!("{url}/update");
+    let client = SparqlClient::new();
+    client.update(&endpoint, "DELETE WHERE { ?s ?p ?o }")?;
+    client.update_with_dataset(
+        &endpoint,
+        "DELETE WHERE { ?s ?p ?o }",
+        [],
+        [NamedNodeRef::new("http://example.com/g")?],
+    )?;
+    Ok(())
+}
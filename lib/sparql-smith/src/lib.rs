@@ -3,6 +3,7 @@ use std::fmt;
 use std::iter::once;
 use std::ops::ControlFlow;
 
+#[cfg(not(feature = "rdf-star"))]
 pub const DATA_TRIG: &str = "
 @prefix : <http://example.org/> .
 @prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
@@ -26,8 +27,36 @@ pub const DATA_TRIG: &str = "
 }
 ";
 
+#[cfg(feature = "rdf-star")]
+pub const DATA_TRIG: &str = "
+@prefix : <http://example.org/> .
+@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+
+:1 :2 :3 , :4 ;
+   :5 true , 1 , '1'^^xsd:decimal , '1'^^xsd:double .
+
+:3 :2 :4 ;
+   :5 false , 0 , '0'^^xsd:decimal , '0'^^xsd:double .
+
+<< :1 :2 :3 >> :4 << :3 :2 'foo' >> .
+
+:1 {
+    :1 :2 :3 , 'foo' .
+}
+
+:2 {
+    :3 :2 :1 , false , 'bar'@en .
+}
+
+:3 {
+    :3 :2 :1 , true .
+    << << :1 :2 :3 >> :5 1 >> :4 :2 .
+}
+";
+
 const NUMBER_OF_NAMED_NODES: u8 = 5;
 const NUMBER_OF_VARIABLES: u8 = 4;
+const NUMBER_OF_BLANK_NODES: u8 = 3;
 const LITERALS: [&str; 54] = [
     "\"foo\"",
     "\"foo\"^^<http://www.w3.org/2001/XMLSchema#string>",
@@ -89,7 +118,28 @@ pub struct Query {
     inner: QueryContent,
 }
 
-#[derive(Arbitrary)]
+/// The relative frequencies of the query forms generated by [`Query::arbitrary_with_weights`].
+///
+/// By default, all the query forms are equally frequent.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct QueryWeights {
+    pub select: u32,
+    pub construct: u32,
+    pub describe: u32,
+    pub ask: u32,
+}
+
+impl Default for QueryWeights {
+    fn default() -> Self {
+        Self {
+            select: 1,
+            construct: 1,
+            describe: 1,
+            ask: 1,
+        }
+    }
+}
+
 struct QueryContent {
     // [1]  QueryUnit  ::=  Query
     // [2]  Query      ::=  Prologue ( SelectQuery | ConstructQuery | DescribeQuery | AskQuery ) ValuesClause
@@ -97,7 +147,6 @@ struct QueryContent {
     values_clause: ValuesClause,
 }
 
-#[derive(Arbitrary)]
 enum QueryVariant {
     Select(SelectQuery),
     Construct(ConstructQuery),
@@ -105,16 +154,71 @@ enum QueryVariant {
     Ask(AskQuery),
 }
 
-impl<'a> Arbitrary<'a> for Query {
-    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+impl QueryVariant {
+    fn arbitrary_with_weights(u: &mut Unstructured<'_>, weights: QueryWeights) -> Result<Self> {
+        let total = weights
+            .select
+            .checked_add(weights.construct)
+            .and_then(|t| t.checked_add(weights.describe))
+            .and_then(|t| t.checked_add(weights.ask))
+            .ok_or(Error::IncorrectFormat)?;
+        let Some(max) = total.checked_sub(1) else {
+            return Err(Error::IncorrectFormat); // All weights are 0
+        };
+        let mut choice = u.int_in_range(0..=max)?;
+        if choice < weights.select {
+            return Ok(Self::Select(SelectQuery::arbitrary(u)?));
+        }
+        choice -= weights.select;
+        if choice < weights.construct {
+            return Ok(Self::Construct(ConstructQuery::arbitrary(u)?));
+        }
+        choice -= weights.construct;
+        if choice < weights.describe {
+            return Ok(Self::Describe(DescribeQuery::arbitrary(u)?));
+        }
+        Ok(Self::Ask(AskQuery::arbitrary(u)?))
+    }
+}
+
+impl Query {
+    /// Generates a query whose form (`SELECT`, `CONSTRUCT`, `DESCRIBE` or `ASK`) is chosen according to the given weights.
+    ///
+    /// ```
+    /// use arbitrary::Unstructured;
+    /// use sparql_smith::{Query, QueryWeights};
+    ///
+    /// let weights = QueryWeights {
+    ///     select: 0,
+    ///     construct: 1,
+    ///     describe: 0,
+    ///     ask: 0,
+    /// };
+    /// let query = Query::arbitrary_with_weights(&mut Unstructured::new(&[0; 64]), weights)?;
+    /// assert!(query.to_string().starts_with("CONSTRUCT"));
+    /// # Ok::<_, arbitrary::Error>(())
+    /// ```
+    pub fn arbitrary_with_weights(u: &mut Unstructured<'_>, weights: QueryWeights) -> Result<Self> {
         Ok(Self {
-            inner: QueryContent::arbitrary(u)?,
+            inner: QueryContent {
+                variant: QueryVariant::arbitrary_with_weights(u, weights)?,
+                values_clause: ValuesClause::arbitrary(u)?,
+            },
         })
     }
+}
 
-    fn arbitrary_take_rest(u: Unstructured<'a>) -> Result<Self> {
+impl<'a> Arbitrary<'a> for Query {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Self::arbitrary_with_weights(u, QueryWeights::default())
+    }
+
+    fn arbitrary_take_rest(mut u: Unstructured<'a>) -> Result<Self> {
         Ok(Self {
-            inner: QueryContent::arbitrary_take_rest(u)?,
+            inner: QueryContent {
+                variant: QueryVariant::arbitrary_with_weights(&mut u, QueryWeights::default())?,
+                values_clause: ValuesClause::arbitrary_take_rest(u)?,
+            },
         })
     }
 
@@ -273,7 +377,7 @@ impl fmt::Display for ConstructQuery {
 struct DescribeQuery {
     // [11]  	DescribeQuery	  ::=  	'DESCRIBE' ( VarOrIri+ | '*' ) DatasetClause* WhereClause? SolutionModifier
     describe: DescribeList,
-    where_clause: WhereClause,
+    where_clause: Option<WhereClause>,
     solution_modifier: SolutionModifier,
 }
 
@@ -298,7 +402,10 @@ impl fmt::Display for DescribeQuery {
                 }
             }
         }
-        write!(f, "{}{}", self.where_clause, self.solution_modifier)
+        if let Some(where_clause) = &self.where_clause {
+            write!(f, "{where_clause}")?;
+        }
+        write!(f, "{}", self.solution_modifier)
     }
 }
 
@@ -1187,6 +1294,8 @@ enum DataBlockValue {
     Iri(Iri),
     Literal(Literal),
     Undef,
+    #[cfg(feature = "rdf-star")]
+    QuotedTripleData(Box<QuotedTripleData>),
 }
 
 impl fmt::Display for DataBlockValue {
@@ -1195,6 +1304,8 @@ impl fmt::Display for DataBlockValue {
             Self::Iri(i) => write!(f, "{i}"),
             Self::Literal(l) => write!(f, "{l}"),
             Self::Undef => f.write_str("UNDEF"),
+            #[cfg(feature = "rdf-star")]
+            Self::QuotedTripleData(t) => write!(f, "{t}"),
         }
     }
 }
@@ -1515,12 +1626,26 @@ struct PropertyListPathNotEmpty {
     others: Vec<Option<PropertyListPathElement>>,
 }
 
-#[derive(Arbitrary)]
 enum PropertyListPathNotEmptyVerb {
     VerbPath(VerbPath),
     VerbSimple(VerbSimple),
 }
 
+impl Arbitrary<'_> for PropertyListPathNotEmptyVerb {
+    fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self> {
+        // We favor property paths to exercise their evaluation
+        Ok(if u.ratio(3, 4)? {
+            Self::VerbPath(VerbPath::arbitrary(u)?)
+        } else {
+            Self::VerbSimple(VerbSimple::arbitrary(u)?)
+        })
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        arbitrary::size_hint::and(<u8 as Arbitrary>::size_hint(depth), (1, None))
+    }
+}
+
 #[derive(Arbitrary)]
 struct PropertyListPathElement {
     predicate: PropertyListPathNotEmptyVerb,
@@ -1854,6 +1979,8 @@ enum VarOrTerm {
     // [106]   VarOrTerm   ::=   Var | GraphTerm
     Var(Var),
     GraphTerm(GraphTerm),
+    #[cfg(feature = "rdf-star")]
+    QuotedTriple(Box<QuotedTriple>),
 }
 
 impl fmt::Display for VarOrTerm {
@@ -1861,6 +1988,70 @@ impl fmt::Display for VarOrTerm {
         match self {
             Self::Var(v) => write!(f, "{v}"),
             Self::GraphTerm(t) => write!(f, "{t}"),
+            #[cfg(feature = "rdf-star")]
+            Self::QuotedTriple(t) => write!(f, "{t}"),
+        }
+    }
+}
+
+#[cfg(feature = "rdf-star")]
+#[derive(Arbitrary)]
+struct QuotedTriple {
+    // [174]   QuotedTriple   ::=   '<<' VarOrTerm Verb VarOrTerm '>>'
+    subject: VarOrTerm,
+    predicate: Verb,
+    object: VarOrTerm,
+}
+
+#[cfg(feature = "rdf-star")]
+impl fmt::Display for QuotedTriple {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "<< {} {} {} >>",
+            self.subject, self.predicate, self.object
+        )
+    }
+}
+
+#[cfg(feature = "rdf-star")]
+#[derive(Arbitrary)]
+struct QuotedTripleData {
+    // [175]   QuotedTripleData   ::=   '<<' DataValueTerm ( iri | 'a' ) DataValueTerm '>>'
+    subject: DataValueTerm,
+    predicate: Option<Iri>,
+    object: DataValueTerm,
+}
+
+#[cfg(feature = "rdf-star")]
+impl fmt::Display for QuotedTripleData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<< {} ", self.subject)?;
+        if let Some(predicate) = &self.predicate {
+            write!(f, "{predicate}")?;
+        } else {
+            f.write_str(" a ")?;
+        }
+        write!(f, " {} >>", self.object)
+    }
+}
+
+#[cfg(feature = "rdf-star")]
+#[derive(Arbitrary)]
+enum DataValueTerm {
+    // [176]   DataValueTerm   ::=   iri | RDFLiteral | NumericLiteral | BooleanLiteral | QuotedTripleData
+    Iri(Iri),
+    Literal(Literal),
+    QuotedTripleData(Box<QuotedTripleData>),
+}
+
+#[cfg(feature = "rdf-star")]
+impl fmt::Display for DataValueTerm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Iri(i) => write!(f, "{i}"),
+            Self::Literal(l) => write!(f, "{l}"),
+            Self::QuotedTripleData(t) => write!(f, "{t}"),
         }
     }
 }
@@ -1909,8 +2100,8 @@ enum GraphTerm {
     // [109]   GraphTerm   ::=   iri | RDFLiteral | NumericLiteral | BooleanLiteral | BlankNode | NIL
     Iri(Iri),
     Literal(Literal),
+    BlankNode(BlankNode),
     Nil,
-    // TODO: BlankNode
 }
 
 impl fmt::Display for GraphTerm {
@@ -1918,6 +2109,7 @@ impl fmt::Display for GraphTerm {
         match self {
             Self::Iri(iri) => write!(f, "{iri}"),
             Self::Literal(l) => write!(f, "{l}"),
+            Self::BlankNode(b) => write!(f, "{b}"),
             Self::Nil => f.write_str(" () "),
         }
     }
@@ -2077,6 +2269,8 @@ enum PrimaryExpression {
     IriOrFunction(IriOrFunction),
     Literal(Literal),
     Var(Var),
+    #[cfg(feature = "rdf-star")]
+    ExprQuotedTriple(Box<ExprQuotedTriple>),
 }
 
 impl fmt::Display for PrimaryExpression {
@@ -2087,6 +2281,50 @@ impl fmt::Display for PrimaryExpression {
             Self::IriOrFunction(e) => write!(f, "{e}"),
             Self::Literal(e) => write!(f, "{e}"),
             Self::Var(e) => write!(f, "{e}"),
+            #[cfg(feature = "rdf-star")]
+            Self::ExprQuotedTriple(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+#[cfg(feature = "rdf-star")]
+#[derive(Arbitrary)]
+struct ExprQuotedTriple {
+    // [178]   ExprQuotedTriple   ::=   '<<' ExprVarOrTerm Verb ExprVarOrTerm '>>'
+    subject: ExprVarOrTerm,
+    predicate: Verb,
+    object: ExprVarOrTerm,
+}
+
+#[cfg(feature = "rdf-star")]
+impl fmt::Display for ExprQuotedTriple {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "<< {} {} {} >>",
+            self.subject, self.predicate, self.object
+        )
+    }
+}
+
+#[cfg(feature = "rdf-star")]
+#[derive(Arbitrary)]
+enum ExprVarOrTerm {
+    // [177]   ExprVarOrTerm   ::=   iri | RDFLiteral | NumericLiteral | BooleanLiteral | Var | ExprQuotedTriple
+    Iri(Iri),
+    Literal(Literal),
+    Var(Var),
+    ExprQuotedTriple(Box<ExprQuotedTriple>),
+}
+
+#[cfg(feature = "rdf-star")]
+impl fmt::Display for ExprVarOrTerm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Iri(i) => write!(f, "{i}"),
+            Self::Literal(l) => write!(f, "{l}"),
+            Self::Var(v) => write!(f, "{v}"),
+            Self::ExprQuotedTriple(t) => write!(f, "{t}"),
         }
     }
 }
@@ -2164,11 +2402,13 @@ enum BuiltInCall {
     Lang(Box<Expression>),
     LangMatches(Box<Expression>, Box<Expression>),
     Datatype(Box<Expression>),
+    Aggregate(Aggregate),
     Bound(Var),
     Iri(Box<Expression>),
     Uri(Box<Expression>),
-    BNode(Box<Expression>), // TODO: no arg
-    // TODO Rand,
+    BNode(Option<Box<Expression>>),
+    #[cfg(feature = "nondeterministic-functions")]
+    Rand,
     Abs(Box<Expression>),
     Ceil(Box<Expression>),
     Floor(Box<Expression>),
@@ -2193,9 +2433,12 @@ enum BuiltInCall {
     Seconds(Box<Expression>),
     Timezone(Box<Expression>),
     Tz(Box<Expression>),
-    // TODO NOW,
-    // TODO UUID
-    // TODO STRUUID
+    #[cfg(feature = "nondeterministic-functions")]
+    Now,
+    #[cfg(feature = "nondeterministic-functions")]
+    Uuid,
+    #[cfg(feature = "nondeterministic-functions")]
+    StrUuid,
     MD5(Box<Expression>),
     SHA1(Box<Expression>),
     SHA256(Box<Expression>),
@@ -2207,12 +2450,23 @@ enum BuiltInCall {
     StrDt(Box<Expression>, Box<Expression>),
     SameTerm(Box<Expression>, Box<Expression>),
     IsIri(Box<Expression>),
+    IsUri(Box<Expression>),
     IsBlank(Box<Expression>),
     IsLiteral(Box<Expression>),
     IsNumeric(Box<Expression>),
     Regex(RegexExpression),
     Exists(ExistsFunc),
     NotExists(NotExistsFunc),
+    #[cfg(feature = "rdf-star")]
+    Triple(Box<Expression>, Box<Expression>, Box<Expression>),
+    #[cfg(feature = "rdf-star")]
+    Subject(Box<Expression>),
+    #[cfg(feature = "rdf-star")]
+    Predicate(Box<Expression>),
+    #[cfg(feature = "rdf-star")]
+    Object(Box<Expression>),
+    #[cfg(feature = "rdf-star")]
+    IsTriple(Box<Expression>),
 }
 
 impl fmt::Display for BuiltInCall {
@@ -2222,10 +2476,14 @@ impl fmt::Display for BuiltInCall {
             Self::Lang(v) => write!(f, "LANG({v})"),
             Self::LangMatches(a, b) => write!(f, "LangMatches({a}, {b})"),
             Self::Datatype(v) => write!(f, "DATATYPE({v})"),
+            Self::Aggregate(a) => write!(f, "{a}"),
             Self::Bound(v) => write!(f, "BOUND({v})"),
             Self::Iri(v) => write!(f, "IRI({v})"),
             Self::Uri(e) => write!(f, "URI({e})"),
-            Self::BNode(v) => write!(f, "BNODE({v})"),
+            Self::BNode(Some(v)) => write!(f, "BNODE({v})"),
+            Self::BNode(None) => f.write_str("BNODE()"),
+            #[cfg(feature = "nondeterministic-functions")]
+            Self::Rand => f.write_str("RAND()"),
             Self::Abs(e) => write!(f, "ABS({e})"),
             Self::Ceil(e) => write!(f, "CEIL({e})"),
             Self::Floor(e) => write!(f, "FLOOR({e})"),
@@ -2248,8 +2506,14 @@ impl fmt::Display for BuiltInCall {
             Self::Seconds(e) => write!(f, "SECONDS({e})"),
             Self::Timezone(e) => write!(f, "TIMEZONE({e})"),
             Self::Tz(e) => write!(f, "TZ({e})"),
+            #[cfg(feature = "nondeterministic-functions")]
+            Self::Now => f.write_str("NOW()"),
+            #[cfg(feature = "nondeterministic-functions")]
+            Self::Uuid => f.write_str("UUID()"),
+            #[cfg(feature = "nondeterministic-functions")]
+            Self::StrUuid => f.write_str("STRUUID()"),
             Self::MD5(e) => write!(f, "MD5({e})"),
-            Self::SHA1(e) => write!(f, "SH1({e})"),
+            Self::SHA1(e) => write!(f, "SHA1({e})"),
             Self::SHA256(e) => write!(f, "SHA256({e})"),
             Self::SHA384(e) => write!(f, "SHA384({e})"),
             Self::SHA512(e) => write!(f, "SHA512({e})"),
@@ -2259,6 +2523,7 @@ impl fmt::Display for BuiltInCall {
             Self::StrDt(a, b) => write!(f, "STRDT({a}, {b})"),
             Self::SameTerm(a, b) => write!(f, "sameTerm({a}, {b})"),
             Self::IsIri(e) => write!(f, "isIRI({e})"),
+            Self::IsUri(e) => write!(f, "isURI({e})"),
             Self::IsBlank(e) => write!(f, "isBlank({e})"),
             Self::IsLiteral(e) => write!(f, "isLiteral({e})"),
             Self::IsNumeric(e) => write!(f, "isNumeric({e})"),
@@ -2267,6 +2532,16 @@ impl fmt::Display for BuiltInCall {
             Self::Substring(e) => write!(f, "{e}"),
             Self::StrReplace(e) => write!(f, "{e}"),
             Self::Regex(e) => write!(f, "{e}"),
+            #[cfg(feature = "rdf-star")]
+            Self::Triple(a, b, c) => write!(f, "TRIPLE({a}, {b}, {c})"),
+            #[cfg(feature = "rdf-star")]
+            Self::Subject(e) => write!(f, "SUBJECT({e})"),
+            #[cfg(feature = "rdf-star")]
+            Self::Predicate(e) => write!(f, "PREDICATE({e})"),
+            #[cfg(feature = "rdf-star")]
+            Self::Object(e) => write!(f, "OBJECT({e})"),
+            #[cfg(feature = "rdf-star")]
+            Self::IsTriple(e) => write!(f, "isTRIPLE({e})"),
         }
     }
 }
@@ -2318,7 +2593,7 @@ struct StrReplaceExpression {
 
 impl fmt::Display for StrReplaceExpression {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "SUBSTR({}, {}, {}", self.arg1, self.arg2, self.arg3)?;
+        write!(f, "REPLACE({}, {}, {}", self.arg1, self.arg2, self.arg3)?;
         if let Some(arg4) = &self.arg4 {
             write!(f, ", {arg4}")?;
         }
@@ -2350,6 +2625,71 @@ impl fmt::Display for NotExistsFunc {
     }
 }
 
+#[derive(Arbitrary)]
+enum Aggregate {
+    // [127]   Aggregate   ::=     'COUNT' '(' 'DISTINCT'? ( '*' | Expression ) ')'
+    //   | 'SUM' '(' 'DISTINCT'? Expression ')'
+    //   | 'MIN' '(' 'DISTINCT'? Expression ')'
+    //   | 'MAX' '(' 'DISTINCT'? Expression ')'
+    //   | 'AVG' '(' 'DISTINCT'? Expression ')'
+    //   | 'SAMPLE' '(' 'DISTINCT'? Expression ')'
+    //   | 'GROUP_CONCAT' '(' 'DISTINCT'? Expression ( ';' 'SEPARATOR' '=' String )? ')'
+    Count(bool, Option<Box<Expression>>),
+    Sum(bool, Box<Expression>),
+    Min(bool, Box<Expression>),
+    Max(bool, Box<Expression>),
+    Avg(bool, Box<Expression>),
+    Sample(bool, Box<Expression>),
+    GroupConcat(bool, Box<Expression>, Option<Separator>),
+}
+
+impl fmt::Display for Aggregate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, distinct) = match self {
+            Self::Count(d, _) => ("COUNT", d),
+            Self::Sum(d, _) => ("SUM", d),
+            Self::Min(d, _) => ("MIN", d),
+            Self::Max(d, _) => ("MAX", d),
+            Self::Avg(d, _) => ("AVG", d),
+            Self::Sample(d, _) => ("SAMPLE", d),
+            Self::GroupConcat(d, _, _) => ("GROUP_CONCAT", d),
+        };
+        write!(f, "{name}(")?;
+        if *distinct {
+            f.write_str("DISTINCT ")?;
+        }
+        match self {
+            Self::Count(_, Some(e))
+            | Self::Sum(_, e)
+            | Self::Min(_, e)
+            | Self::Max(_, e)
+            | Self::Avg(_, e)
+            | Self::Sample(_, e)
+            | Self::GroupConcat(_, e, None) => write!(f, "{e}"),
+            Self::Count(_, None) => f.write_str("*"),
+            Self::GroupConcat(_, e, Some(s)) => write!(f, "{e}; SEPARATOR = {s}"),
+        }?;
+        f.write_str(")")
+    }
+}
+
+#[derive(Arbitrary)]
+enum Separator {
+    Empty,
+    Space,
+    Comma,
+}
+
+impl fmt::Display for Separator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Empty => "\"\"",
+            Self::Space => "\" \"",
+            Self::Comma => "\", \"",
+        })
+    }
+}
+
 #[derive(Arbitrary)]
 struct IriOrFunction {
     // [128]   iriOrFunction   ::=   iri ArgList?
@@ -2395,6 +2735,37 @@ impl fmt::Display for Literal {
     }
 }
 
+struct BlankNode {
+    // [138]   BlankNode   ::=   BLANK_NODE_LABEL | ANON
+    label: Option<u8>,
+}
+
+impl Arbitrary<'_> for BlankNode {
+    fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self> {
+        Ok(Self {
+            label: if u.arbitrary()? {
+                Some(u.int_in_range(1..=NUMBER_OF_BLANK_NODES)?)
+            } else {
+                None
+            },
+        })
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <(bool, u8) as Arbitrary>::size_hint(depth)
+    }
+}
+
+impl fmt::Display for BlankNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(label) = self.label {
+            write!(f, " _:b{label} ")
+        } else {
+            f.write_str(" [] ")
+        }
+    }
+}
+
 struct Iri {
     // [136]   iri   ::=   IRIREF | PrefixedName
     value: u8,
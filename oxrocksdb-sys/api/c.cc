@@ -313,6 +313,12 @@ void rocksdb_transactiondb_ingest_external_files_with_status(
   SaveStatus(statusptr, db->rep->IngestExternalFiles(args));
 }
 
+unsigned char rocksdb_transactiondb_property_int_cf(
+    rocksdb_transactiondb_t* db, rocksdb_column_family_handle_t* column_family,
+    const char* propname, uint64_t* out_val) {
+  return db->rep->GetIntProperty(column_family->rep, Slice(propname), out_val);
+}
+
 void rocksdb_transactiondb_create_checkpoint_with_status(
     rocksdb_transactiondb_t* db, const char* checkpoint_dir,
     rocksdb_status_t* statusptr) {
@@ -130,6 +130,10 @@ rocksdb_transactiondb_ingest_external_files_with_status(
     rocksdb_transactiondb_t* db, const rocksdb_ingestexternalfilearg_t* list,
     const size_t list_len, rocksdb_status_t* statusptr);
 
+extern ROCKSDB_LIBRARY_API unsigned char rocksdb_transactiondb_property_int_cf(
+    rocksdb_transactiondb_t* db, rocksdb_column_family_handle_t* column_family,
+    const char* propname, uint64_t* out_val);
+
 extern ROCKSDB_LIBRARY_API void
 rocksdb_transactiondb_create_checkpoint_with_status(
     rocksdb_transactiondb_t* db, const char* checkpoint_dir,
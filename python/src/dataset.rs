@@ -267,6 +267,7 @@ impl QuadIter {
 /// The following algorithms are supported:
 ///
 /// * :py:attr:`CanonicalizationAlgorithm.UNSTABLE`: an unstable algorithm preferred by PyOxigraph.
+/// * :py:attr:`CanonicalizationAlgorithm.RDFC_1_0`: the `RDFC-1.0 <https://www.w3.org/TR/rdf-canon/>`_ algorithm using SHA-256.
 #[pyclass(frozen, name = "CanonicalizationAlgorithm", module = "pyoxigraph")]
 #[derive(Clone)]
 pub struct PyCanonicalizationAlgorithm {
@@ -283,11 +284,20 @@ impl PyCanonicalizationAlgorithm {
         inner: CanonicalizationAlgorithm::Unstable,
     };
 
+    /// The `RDFC-1.0 <https://www.w3.org/TR/rdf-canon/>`_ algorithm using SHA-256.
+    ///
+    /// The blank nodes are renamed to ``c14n0``, ``c14n1``...
+    #[classattr]
+    const RDFC_1_0: Self = Self {
+        inner: CanonicalizationAlgorithm::Rdfc10,
+    };
+
     fn __repr__(&self) -> String {
         format!(
             "<CanonicalizationAlgorithm {}>",
             match self.inner {
                 CanonicalizationAlgorithm::Unstable => "unstable",
+                CanonicalizationAlgorithm::Rdfc10 => "rdfc-1.0",
                 _ => "unknown",
             }
         )
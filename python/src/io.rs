@@ -5,7 +5,7 @@ use pyo3::exceptions::{PyDeprecationWarning, PySyntaxError, PyValueError};
 use pyo3::intern;
 use pyo3::prelude::*;
 use pyo3::pybacked::{PyBackedBytes, PyBackedStr};
-use std::cmp::max;
+use std::cmp::{max, min};
 use std::collections::BTreeMap;
 use std::ffi::{CString, OsStr};
 use std::fs::File;
@@ -23,6 +23,8 @@ use std::sync::OnceLock;
 /// * `TriG <https://www.w3.org/TR/trig/>`_ (:py:attr:`RdfFormat.TRIG`)
 /// * `N3 <https://w3c.github.io/N3/spec/>`_ (:py:attr:`RdfFormat.N3`)
 /// * `RDF/XML <https://www.w3.org/TR/rdf-syntax-grammar/>`_ (:py:attr:`RdfFormat.RDF_XML`)
+/// * `Microdata <https://html.spec.whatwg.org/multipage/microdata.html>`_ (:py:attr:`RdfFormat.MICRODATA`)
+/// * `RDFa <https://www.w3.org/TR/rdfa-core/>`_ (:py:attr:`RdfFormat.RDFA`)
 ///
 /// It supports also some media type and extension aliases.
 /// For example, ``application/turtle`` could also be used for `Turtle <https://www.w3.org/TR/turtle/>`_
@@ -40,7 +42,9 @@ use std::sync::OnceLock;
 /// :type without_named_graphs: bool, optional
 /// :param rename_blank_nodes: Renames the blank nodes identifiers from the ones set in the serialization to random ids. This allows to avoid identifier conflicts when merging graphs together.
 /// :type rename_blank_nodes: bool, optional
-/// :return: an iterator of RDF triples or quads depending on the format.
+/// :param buffer_size: The maximal number of bytes read from the input at once. If :py:const:`None`, the parser chooses it.
+/// :type buffer_size: int or None, optional
+/// :return: a lazy iterator of RDF triples or quads depending on the format. It reads the input only when new quads are requested.
 /// :rtype: QuadParser
 /// :raises ValueError: if the format is not supported.
 /// :raises SyntaxError: if the provided data is invalid.
@@ -48,8 +52,14 @@ use std::sync::OnceLock;
 ///
 /// >>> list(parse(input=b'<foo> <p> "1" .', format=RdfFormat.TURTLE, base_iri="http://example.com/"))
 /// [<Quad subject=<NamedNode value=http://example.com/foo> predicate=<NamedNode value=http://example.com/p> object=<Literal value=1 datatype=<NamedNode value=http://www.w3.org/2001/XMLSchema#string>> graph_name=<DefaultGraph>>]
+///
+/// The parser can be used as a context manager to release the input as soon as the parsing is stopped:
+///
+/// >>> with parse(input=b'<foo> <p> "1" .', format=RdfFormat.TURTLE, base_iri="http://example.com/") as quads:
+/// ...     next(quads)
+/// <Quad subject=<NamedNode value=http://example.com/foo> predicate=<NamedNode value=http://example.com/p> object=<Literal value=1 datatype=<NamedNode value=http://www.w3.org/2001/XMLSchema#string>> graph_name=<DefaultGraph>>
 #[pyfunction]
-#[pyo3(signature = (input = None, format = None, *, path = None, base_iri = None, without_named_graphs = false, rename_blank_nodes = false))]
+#[pyo3(signature = (input = None, format = None, *, path = None, base_iri = None, without_named_graphs = false, rename_blank_nodes = false, buffer_size = None))]
 pub fn parse(
     input: Option<PyReadableInput>,
     format: Option<PyRdfFormatInput>,
@@ -57,9 +67,16 @@ pub fn parse(
     base_iri: Option<&str>,
     without_named_graphs: bool,
     rename_blank_nodes: bool,
+    buffer_size: Option<usize>,
     py: Python<'_>,
 ) -> PyResult<PyQuadParser> {
-    let input = PyReadable::from_args(&path, input, py)?;
+    if buffer_size == Some(0) {
+        return Err(PyValueError::new_err("buffer_size must be greater than 0"));
+    }
+    let input = BoundedReader {
+        inner: PyReadable::from_args(&path, input, py)?,
+        max_read_size: buffer_size,
+    };
     let format = lookup_rdf_format(format, path.as_deref())?;
     let mut parser = RdfParser::from_format(format);
     if let Some(base_iri) = base_iri {
@@ -74,7 +91,7 @@ pub fn parse(
         parser = parser.rename_blank_nodes();
     }
     Ok(PyQuadParser {
-        inner: parser.for_reader(input),
+        inner: Some(parser.for_reader(input)),
         file_path: path,
     })
 }
@@ -89,6 +106,8 @@ pub fn parse(
 /// * `TriG <https://www.w3.org/TR/trig/>`_ (:py:attr:`RdfFormat.TRIG`)
 /// * `N3 <https://w3c.github.io/N3/spec/>`_ (:py:attr:`RdfFormat.N3`)
 /// * `RDF/XML <https://www.w3.org/TR/rdf-syntax-grammar/>`_ (:py:attr:`RdfFormat.RDF_XML`)
+/// * `Microdata <https://html.spec.whatwg.org/multipage/microdata.html>`_ (:py:attr:`RdfFormat.MICRODATA`)
+/// * `RDFa <https://www.w3.org/TR/rdfa-core/>`_ (:py:attr:`RdfFormat.RDFA`)
 ///
 /// It supports also some media type and extension aliases.
 /// For example, ``application/turtle`` could also be used for `Turtle <https://www.w3.org/TR/turtle/>`_
@@ -180,7 +199,8 @@ pub fn serialize<'py>(
 /// <Quad subject=<NamedNode value=http://example.com/foo> predicate=<NamedNode value=http://example.com/p> object=<Literal value=1 datatype=<NamedNode value=http://www.w3.org/2001/XMLSchema#string>> graph_name=<DefaultGraph>>
 #[pyclass(name = "QuadParser", module = "pyoxigraph")]
 pub struct PyQuadParser {
-    inner: ReaderQuadParser<PyReadable>,
+    /// `None` if the parser has been closed
+    inner: Option<ReaderQuadParser<BoundedReader<PyReadable>>>,
     file_path: Option<PathBuf>,
 }
 
@@ -204,7 +224,10 @@ impl PyQuadParser {
     /// {'ex': 'http://example.com/'}
     #[getter]
     pub fn prefixes(&self) -> BTreeMap<&str, &str> {
-        self.inner.prefixes().collect()
+        self.inner
+            .as_ref()
+            .map(|inner| inner.prefixes().collect())
+            .unwrap_or_default()
     }
 
     /// The base IRI considered at the current step of the parsing.
@@ -220,7 +243,43 @@ impl PyQuadParser {
     /// 'http://example.com/'
     #[getter]
     pub fn base_iri(&self) -> Option<&str> {
-        self.inner.base_iri()
+        self.inner.as_ref()?.base_iri()
+    }
+
+    /// Stops the parsing and releases the input (e.g. closes the opened file).
+    ///
+    /// The iteration ends after a call to this method.
+    /// It is called automatically when the parser is used as a context manager.
+    ///
+    /// :rtype: None
+    ///
+    /// >>> quads = parse(input=b'<foo> <p> "1" . <foo> <p> "2" .', format=RdfFormat.TURTLE, base_iri="http://example.com/")
+    /// >>> next(quads)
+    /// <Quad subject=<NamedNode value=http://example.com/foo> predicate=<NamedNode value=http://example.com/p> object=<Literal value=1 datatype=<NamedNode value=http://www.w3.org/2001/XMLSchema#string>> graph_name=<DefaultGraph>>
+    /// >>> quads.close()
+    /// >>> list(quads)
+    /// []
+    pub fn close(&mut self) {
+        self.inner = None;
+    }
+
+    /// :rtype: QuadParser
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// :type exc_type: type[BaseException] or None
+    /// :type exc_value: BaseException or None
+    /// :type traceback: types.TracebackType or None
+    /// :rtype: None
+    #[allow(unused_variables)]
+    fn __exit__(
+        &mut self,
+        exc_type: &Bound<'_, PyAny>,
+        exc_value: &Bound<'_, PyAny>,
+        traceback: &Bound<'_, PyAny>,
+    ) {
+        self.close()
     }
 
     fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
@@ -228,14 +287,20 @@ impl PyQuadParser {
     }
 
     fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<PyQuad>> {
-        py.allow_threads(|| {
-            Ok(self
-                .inner
-                .next()
-                .transpose()
-                .map_err(|e| map_parse_error(e, self.file_path.clone()))?
-                .map(PyQuad::from))
-        })
+        let Some(inner) = &mut self.inner else {
+            return Ok(None);
+        };
+        let file_path = &self.file_path;
+        let result = py.allow_threads(|| inner.next().transpose());
+        match result {
+            Ok(Some(quad)) => Ok(Some(quad.into())),
+            Ok(None) => {
+                // We release the input as soon as possible
+                self.inner = None;
+                Ok(None)
+            }
+            Err(e) => Err(map_parse_error(e, file_path.clone())),
+        }
     }
 }
 
@@ -249,6 +314,8 @@ impl PyQuadParser {
 /// * `TriG <https://www.w3.org/TR/trig/>`_ (:py:attr:`RdfFormat.TRIG`)
 /// * `N3 <https://w3c.github.io/N3/spec/>`_ (:py:attr:`RdfFormat.N3`)
 /// * `RDF/XML <https://www.w3.org/TR/rdf-syntax-grammar/>`_ (:py:attr:`RdfFormat.RDF_XML`)
+/// * `Microdata <https://html.spec.whatwg.org/multipage/microdata.html>`_ (:py:attr:`RdfFormat.MICRODATA`)
+/// * `RDFa <https://www.w3.org/TR/rdfa-core/>`_ (:py:attr:`RdfFormat.RDFA`)
 ///
 /// >>> RdfFormat.N3.media_type
 /// 'text/n3'
@@ -275,6 +342,17 @@ impl PyRdfFormat {
     const N_TRIPLES: Self = Self {
         inner: RdfFormat::NTriples,
     };
+    /// `Microdata <https://html.spec.whatwg.org/multipage/microdata.html>`_
+    #[classattr]
+    const MICRODATA: Self = Self {
+        inner: RdfFormat::Microdata,
+    };
+    /// `RDFa <https://www.w3.org/TR/rdfa-core/>`_
+    #[cfg(feature = "rdfa")]
+    #[classattr]
+    const RDFA: Self = Self {
+        inner: RdfFormat::Rdfa,
+    };
     /// `RDF/XML <https://www.w3.org/TR/rdf-syntax-grammar/>`_
     #[classattr]
     const RDF_XML: Self = Self {
@@ -450,6 +528,23 @@ impl Read for PyReadable {
     }
 }
 
+/// Bounds the number of bytes read from the inner reader at once
+pub struct BoundedReader<R: Read> {
+    inner: R,
+    max_read_size: Option<usize>,
+}
+
+impl<R: Read> Read for BoundedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Some(max_read_size) = self.max_read_size {
+            let len = min(buf.len(), max_read_size);
+            self.inner.read(&mut buf[..len])
+        } else {
+            self.inner.read(buf)
+        }
+    }
+}
+
 #[derive(FromPyObject)]
 pub enum PyReadableInput {
     String(PyBackedStr),
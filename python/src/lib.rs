@@ -7,12 +7,14 @@
 mod dataset;
 mod io;
 mod model;
+mod rdflib;
 mod sparql;
 mod store;
 
 use crate::dataset::*;
 use crate::io::*;
 use crate::model::*;
+use crate::rdflib::*;
 use crate::sparql::*;
 use crate::store::*;
 use pyo3::prelude::*;
@@ -23,10 +25,10 @@ pub mod pyoxigraph {
     use super::*;
     #[pymodule_export]
     use super::{
-        parse, parse_query_results, serialize, PyBlankNode, PyCanonicalizationAlgorithm, PyDataset,
-        PyDefaultGraph, PyLiteral, PyNamedNode, PyQuad, PyQuadParser, PyQueryBoolean,
-        PyQueryResultsFormat, PyQuerySolution, PyQuerySolutions, PyQueryTriples, PyRdfFormat,
-        PyStore, PyTriple, PyVariable,
+        from_rdflib, parse, parse_query_results, serialize, to_rdflib, PyBlankNode, PyBulkWriter,
+        PyCanonicalizationAlgorithm, PyDataset, PyDefaultGraph, PyLiteral, PyNamedNode, PyQuad,
+        PyQuadParser, PyQueryBoolean, PyQueryResultsFormat, PyQuerySolution, PyQuerySolutions,
+        PyQueryTriples, PyRdfFormat, PyStore, PyTransaction, PyTriple, PyVariable,
     };
 
     #[pymodule_init]
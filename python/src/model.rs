@@ -1,6 +1,10 @@
 use oxigraph::model::vocab::{rdf, xsd};
 use oxigraph::model::*;
+#[cfg(feature = "geosparql")]
+use pyo3::exceptions::PyAttributeError;
 use pyo3::exceptions::{PyIndexError, PyValueError};
+#[cfg(feature = "geosparql")]
+use pyo3::intern;
 use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyInt, PyTuple};
 use pyo3::IntoPyObjectExt;
@@ -334,6 +338,22 @@ impl PyLiteral {
         self.inner.datatype().into_owned().into()
     }
 
+    /// :return: the `geo interface <https://gist.github.com/sgillies/2217756>`_ representation of the literal if it is a ``geo:wktLiteral``, ``geo:geoJSONLiteral`` or ``geo:gmlLiteral`` geometry.
+    ///          It allows to give the literal to libraries like `Shapely <https://shapely.readthedocs.io/>`_.
+    /// :rtype: dict[str, typing.Any]
+    /// :raises AttributeError: if the literal is not a geometry.
+    ///
+    /// >>> Literal('POINT(1 2)', datatype=NamedNode('http://www.opengis.net/ont/geosparql#wktLiteral')).__geo_interface__
+    /// {'coordinates': [1.0, 2.0], 'type': 'Point'}
+    #[cfg(feature = "geosparql")]
+    #[getter]
+    fn __geo_interface__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let geo_json = spargeo::literal_to_geo_json(&self.inner)
+            .ok_or_else(|| PyAttributeError::new_err("The literal is not a geometry"))?;
+        py.import("json")?
+            .call_method1(intern!(py, "loads"), (geo_json,))
+    }
+
     fn __str__(&self) -> String {
         self.inner.to_string()
     }
@@ -0,0 +1,753 @@
+use crate::model::*;
+use crate::store::{map_storage_error, PyStore};
+use oxigraph::model::vocab::xsd;
+use oxigraph::model::*;
+use oxigraph::store::{self, StorageError, Store};
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::ffi::c_str;
+use pyo3::prelude::*;
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{PyDict, PyList, PyTuple, PyType};
+use pyo3::{intern, IntoPyObjectExt};
+use std::collections::HashSet;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Identifier used by rdflib for the default graph of its datasets
+const RDFLIB_DEFAULT_GRAPH_ID: &str = "urn:x-rdflib:default";
+
+static URI_REF: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+static BNODE: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+static LITERAL: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+static VARIABLE: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+static GRAPH: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+static DATASET: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+/// The rdflib ``Store`` subclass built from [`PyRdflibStore`]
+static RDFLIB_STORE: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+
+/// Value returned by the rdflib ``Store.open`` method if the store is ready to be used
+const RDFLIB_VALID_STORE: i32 = 1;
+
+/// Converts a pyoxigraph term, triple, quad or store to its `rdflib <https://rdflib.readthedocs.io/>`_ equivalent.
+///
+/// Triples are converted to tuples of 3 terms and quads to tuples of 4 terms where the default graph is identified with rdflib ``DATASET_DEFAULT_GRAPH_ID``.
+/// The rdflib classes are imported only once so this function is suitable to convert a lot of terms.
+///
+/// A :py:class:`Store` is not copied: it is wrapped into an rdflib ``Dataset`` reading and writing the store directly.
+/// It allows to run rdflib-based code on top of an Oxigraph store.
+/// The store quads are converted straight to rdflib terms without building pyoxigraph terms first.
+/// The rdflib namespace bindings are only kept in memory and the SPARQL queries given to rdflib are evaluated by rdflib,
+/// use :py:func:`Store.query` to evaluate them with Oxigraph.
+///
+/// :param value: the value to convert.
+/// :type value: NamedNode or BlankNode or Literal or DefaultGraph or Variable or Triple or Quad or Store
+/// :return: the rdflib term, tuple of terms or dataset.
+/// :rtype: typing.Any
+/// :raises ImportError: if rdflib is not installed.
+/// :raises ValueError: if the value contains a triple term, rdflib does not support them.
+#[pyfunction]
+pub fn to_rdflib<'py>(value: PyRdflibConvertible, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    match value {
+        PyRdflibConvertible::Triple(triple) => {
+            let triple = Triple::from(triple);
+            PyTuple::new(
+                py,
+                [
+                    term_to_rdflib(triple.subject.as_ref().into(), py)?,
+                    named_node_to_rdflib(triple.predicate.as_ref(), py)?,
+                    term_to_rdflib(triple.object.as_ref(), py)?,
+                ],
+            )?
+            .into_bound_py_any(py)
+        }
+        PyRdflibConvertible::Quad(quad) => {
+            let quad = Quad::from(quad);
+            PyTuple::new(
+                py,
+                [
+                    term_to_rdflib(quad.subject.as_ref().into(), py)?,
+                    named_node_to_rdflib(quad.predicate.as_ref(), py)?,
+                    term_to_rdflib(quad.object.as_ref(), py)?,
+                    graph_name_to_rdflib(quad.graph_name.as_ref(), py)?,
+                ],
+            )?
+            .into_bound_py_any(py)
+        }
+        PyRdflibConvertible::Term(term) => term_to_rdflib(Term::from(term).as_ref(), py),
+        PyRdflibConvertible::DefaultGraph(_) => {
+            graph_name_to_rdflib(GraphNameRef::DefaultGraph, py)
+        }
+        PyRdflibConvertible::Variable(variable) => VARIABLE
+            .import(py, "rdflib", "Variable")?
+            .call1((Variable::from(variable).as_str(),)),
+        PyRdflibConvertible::Store(store) => {
+            let store = rdflib_store_type(py)?.call1((store,))?;
+            let kwargs = PyDict::new(py);
+            kwargs.set_item(intern!(py, "store"), store)?;
+            kwargs.set_item(intern!(py, "default_union"), false)?;
+            DATASET
+                .import(py, "rdflib", "Dataset")?
+                .call((), Some(&kwargs))
+        }
+    }
+}
+
+/// Converts an `rdflib <https://rdflib.readthedocs.io/>`_ term or tuple of terms to its pyoxigraph equivalent.
+///
+/// Tuples of 3 terms are converted to :py:class:`Triple` and tuples of 4 terms to :py:class:`Quad`.
+/// The graph of a quad could be given as an identifier or as an rdflib ``Graph``. rdflib ``DATASET_DEFAULT_GRAPH_ID`` is converted to :py:class:`DefaultGraph`.
+///
+/// Combined with :py:func:`Store.extend`, it allows to load the content of an rdflib graph without going through a serialization:
+/// ``store.extend(from_rdflib(t) for t in graph)``.
+///
+/// :param value: the value to convert.
+/// :type value: typing.Any
+/// :return: the pyoxigraph term, triple or quad.
+/// :rtype: NamedNode or BlankNode or Literal or DefaultGraph or Variable or Triple or Quad
+/// :raises ImportError: if rdflib is not installed.
+/// :raises TypeError: if the value is not an rdflib term or a tuple of them.
+/// :raises ValueError: if the value is not a valid RDF term, for example an invalid IRI.
+#[pyfunction]
+pub fn from_rdflib<'py>(value: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    let py = value.py();
+    if let Ok(tuple) = value.downcast::<PyTuple>() {
+        return match tuple.len() {
+            3 => PyTriple::from(Triple::new(
+                term_from_rdflib(&tuple.get_item(0)?)?.try_into_subject()?,
+                term_from_rdflib(&tuple.get_item(1)?)?.try_into_named_node()?,
+                term_from_rdflib(&tuple.get_item(2)?)?.try_into_term()?,
+            ))
+            .into_bound_py_any(py),
+            4 => PyQuad::from(Quad::new(
+                term_from_rdflib(&tuple.get_item(0)?)?.try_into_subject()?,
+                term_from_rdflib(&tuple.get_item(1)?)?.try_into_named_node()?,
+                term_from_rdflib(&tuple.get_item(2)?)?.try_into_term()?,
+                graph_name_from_rdflib(&tuple.get_item(3)?)?,
+            ))
+            .into_bound_py_any(py),
+            len => Err(PyValueError::new_err(format!(
+                "Only tuples of 3 or 4 terms could be converted, found a tuple of {len} elements"
+            ))),
+        };
+    }
+    if value.is_instance(VARIABLE.import(py, "rdflib", "Variable")?)? {
+        return PyVariable::from(
+            Variable::new(value.extract::<String>()?)
+                .map_err(|e| PyValueError::new_err(e.to_string()))?,
+        )
+        .into_bound_py_any(py);
+    }
+    if value.is_instance(GRAPH.import(py, "rdflib", "Graph")?)? {
+        return PyGraphName::from(graph_name_from_rdflib(value)?).into_bound_py_any(py);
+    }
+    match term_from_rdflib(value)? {
+        RdflibTerm::Term(term) => PyTerm::from(term).into_bound_py_any(py),
+        RdflibTerm::DefaultGraph => {
+            PyGraphName::from(GraphName::DefaultGraph).into_bound_py_any(py)
+        }
+    }
+}
+
+#[derive(FromPyObject)]
+pub enum PyRdflibConvertible {
+    Triple(PyTriple),
+    Quad(PyQuad),
+    Term(PyTerm),
+    DefaultGraph(PyDefaultGraph),
+    Variable(PyVariable),
+    Store(PyStore),
+}
+
+/// Implementation of the rdflib ``Store`` API on top of an Oxigraph store.
+///
+/// The rdflib ``Store`` class is pure Python so the adapter exposed to rdflib is a Python subclass of both this class and rdflib ``Store``,
+/// built by [`rdflib_store_type`].
+#[pyclass(frozen, subclass, name = "RdflibStore", module = "pyoxigraph")]
+pub struct PyRdflibStore {
+    store: Store,
+    /// The (prefix, namespace) bindings
+    bindings: Mutex<Vec<(String, String)>>,
+}
+
+#[pymethods]
+impl PyRdflibStore {
+    #[new]
+    fn new(store: PyStore) -> Self {
+        Self {
+            store: store.into(),
+            bindings: Mutex::default(),
+        }
+    }
+
+    #[classattr]
+    fn context_aware() -> bool {
+        true
+    }
+
+    #[classattr]
+    fn formula_aware() -> bool {
+        false
+    }
+
+    #[classattr]
+    fn graph_aware() -> bool {
+        true
+    }
+
+    #[classattr]
+    fn transaction_aware() -> bool {
+        false
+    }
+
+    /// The wrapped store is already open
+    #[allow(unused_variables)]
+    #[pyo3(signature = (configuration, create = false))]
+    fn open(&self, configuration: &Bound<'_, PyAny>, create: bool) -> i32 {
+        RDFLIB_VALID_STORE
+    }
+
+    #[allow(unused_variables)]
+    #[pyo3(signature = (commit_pending_transaction = false))]
+    fn close(&self, commit_pending_transaction: bool) {}
+
+    #[pyo3(signature = (triple, context, quoted = false))]
+    fn add(
+        &self,
+        triple: &Bound<'_, PyTuple>,
+        context: &Bound<'_, PyAny>,
+        quoted: bool,
+        py: Python<'_>,
+    ) -> PyResult<()> {
+        if quoted {
+            return Err(PyValueError::new_err(
+                "Oxigraph stores do not support rdflib formulas",
+            ));
+        }
+        let quad = quad_from_rdflib(triple, context)?;
+        py.allow_threads(|| self.store.insert(&quad).map_err(map_storage_error))?;
+        Ok(())
+    }
+
+    #[pyo3(name = "addN")]
+    fn add_n(&self, quads: &Bound<'_, PyAny>, py: Python<'_>) -> PyResult<()> {
+        let quads = quads
+            .try_iter()?
+            .map(|quad| {
+                let quad = quad?;
+                let quad = quad.downcast::<PyTuple>()?;
+                if quad.len() != 4 {
+                    return Err(PyValueError::new_err(
+                        "rdflib addN expects tuples of 4 elements",
+                    ));
+                }
+                quad_from_rdflib(&quad.get_slice(0, 3), &quad.get_item(3)?)
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        py.allow_threads(|| self.store.extend(quads).map_err(map_storage_error))
+    }
+
+    #[pyo3(signature = (triple_pattern, context = None))]
+    fn remove(
+        &self,
+        triple_pattern: &Bound<'_, PyTuple>,
+        context: Option<&Bound<'_, PyAny>>,
+        py: Python<'_>,
+    ) -> PyResult<()> {
+        let (subject, predicate, object) = pattern_from_rdflib(triple_pattern)?;
+        let graph_name = context.map(graph_name_from_rdflib).transpose()?;
+        py.allow_threads(|| {
+            self.store.transaction(|mut transaction| {
+                let quads = transaction
+                    .quads_for_pattern(
+                        subject.as_ref().map(Into::into),
+                        predicate.as_ref().map(Into::into),
+                        object.as_ref().map(Into::into),
+                        graph_name.as_ref().map(Into::into),
+                    )
+                    .collect::<Result<Vec<_>, _>>()?;
+                for quad in &quads {
+                    transaction.remove(quad)?;
+                }
+                Ok::<_, StorageError>(())
+            })
+        })
+        .map_err(map_storage_error)
+    }
+
+    /// Returns an iterator of ``((subject, predicate, object), contexts)`` tuples
+    #[pyo3(signature = (triple_pattern, context = None))]
+    fn triples(
+        slf: &Bound<'_, Self>,
+        triple_pattern: &Bound<'_, PyTuple>,
+        context: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<RdflibTripleIter> {
+        let (subject, predicate, object) = pattern_from_rdflib(triple_pattern)?;
+        let graph_name = context.map(graph_name_from_rdflib).transpose()?;
+        Ok(RdflibTripleIter {
+            inner: slf.get().store.quads_for_pattern(
+                subject.as_ref().map(Into::into),
+                predicate.as_ref().map(Into::into),
+                object.as_ref().map(Into::into),
+                graph_name.as_ref().map(Into::into),
+            ),
+            store: slf.get().store.clone(),
+            adapter: slf.clone().into_any().unbind(),
+            context: context.map(|context| context.clone().unbind()),
+            seen: HashSet::new(),
+        })
+    }
+
+    /// Called by the ``__len__`` method of the rdflib store that takes an extra ``context`` argument
+    #[pyo3(name = "_len", signature = (context = None))]
+    fn len(&self, context: Option<&Bound<'_, PyAny>>, py: Python<'_>) -> PyResult<usize> {
+        let Some(context) = context else {
+            return py.allow_threads(|| self.store.len().map_err(map_storage_error));
+        };
+        let graph_name = graph_name_from_rdflib(context)?;
+        py.allow_threads(|| {
+            self.store
+                .quads_for_pattern(None, None, None, Some(graph_name.as_ref()))
+                .try_fold(0, |count, quad| quad.map(|_| count + 1))
+                .map_err(map_storage_error)
+        })
+    }
+
+    /// Returns the graphs containing the triple or all the graphs if no triple is given
+    #[pyo3(signature = (triple = None))]
+    fn contexts<'py>(
+        slf: &Bound<'py, Self>,
+        triple: Option<&Bound<'py, PyTuple>>,
+    ) -> PyResult<Bound<'py, PyList>> {
+        let py = slf.py();
+        let store = &slf.get().store;
+        let graph_names = if let Some(triple) = triple {
+            let (subject, predicate, object) = pattern_from_rdflib(triple)?;
+            py.allow_threads(|| {
+                store
+                    .quads_for_pattern(
+                        subject.as_ref().map(Into::into),
+                        predicate.as_ref().map(Into::into),
+                        object.as_ref().map(Into::into),
+                        None,
+                    )
+                    .map(|quad| quad.map(|quad| quad.graph_name))
+                    .collect::<Result<HashSet<_>, _>>()
+            })
+        } else {
+            py.allow_threads(|| {
+                let mut graph_names = store
+                    .named_graphs()
+                    .map(|graph_name| graph_name.map(GraphName::from))
+                    .collect::<Result<HashSet<_>, _>>()?;
+                if store
+                    .quads_for_pattern(None, None, None, Some(GraphNameRef::DefaultGraph))
+                    .next()
+                    .transpose()?
+                    .is_some()
+                {
+                    graph_names.insert(GraphName::DefaultGraph);
+                }
+                Ok(graph_names)
+            })
+        }
+        .map_err(map_storage_error)?;
+        PyList::new(
+            py,
+            graph_names
+                .iter()
+                .map(|graph_name| rdflib_graph(slf.as_any(), graph_name.as_ref()))
+                .collect::<PyResult<Vec<_>>>()?,
+        )
+    }
+
+    fn add_graph(&self, graph: &Bound<'_, PyAny>, py: Python<'_>) -> PyResult<()> {
+        let graph_name = graph_name_from_rdflib(graph)?;
+        py.allow_threads(|| match &graph_name {
+            GraphName::NamedNode(graph_name) => self.store.insert_named_graph(graph_name),
+            GraphName::BlankNode(graph_name) => self.store.insert_named_graph(graph_name),
+            GraphName::DefaultGraph => Ok(false),
+        })
+        .map_err(map_storage_error)?;
+        Ok(())
+    }
+
+    fn remove_graph(&self, graph: &Bound<'_, PyAny>, py: Python<'_>) -> PyResult<()> {
+        let graph_name = graph_name_from_rdflib(graph)?;
+        py.allow_threads(|| match &graph_name {
+            GraphName::NamedNode(graph_name) => self.store.remove_named_graph(graph_name),
+            GraphName::BlankNode(graph_name) => self.store.remove_named_graph(graph_name),
+            GraphName::DefaultGraph => self
+                .store
+                .clear_graph(GraphNameRef::DefaultGraph)
+                .map(|()| true),
+        })
+        .map_err(map_storage_error)?;
+        Ok(())
+    }
+
+    #[pyo3(signature = (prefix, namespace, r#override = true))]
+    fn bind(&self, prefix: String, namespace: String, r#override: bool) {
+        let mut bindings = lock_bindings(&self.bindings);
+        if r#override {
+            bindings.retain(|(p, n)| *p != prefix && *n != namespace);
+        } else if bindings
+            .iter()
+            .any(|(p, n)| *p == prefix || *n == namespace)
+        {
+            return;
+        }
+        bindings.push((prefix, namespace));
+    }
+
+    fn prefix(&self, namespace: &str) -> Option<String> {
+        lock_bindings(&self.bindings)
+            .iter()
+            .find(|(_, n)| n == namespace)
+            .map(|(p, _)| p.clone())
+    }
+
+    fn namespace<'py>(&self, prefix: &str, py: Python<'py>) -> PyResult<Option<Bound<'py, PyAny>>> {
+        let Some(namespace) = lock_bindings(&self.bindings)
+            .iter()
+            .find(|(p, _)| p == prefix)
+            .map(|(_, n)| n.clone())
+        else {
+            return Ok(None);
+        };
+        Ok(Some(
+            URI_REF
+                .import(py, "rdflib", "URIRef")?
+                .call1((namespace,))?,
+        ))
+    }
+
+    fn namespaces<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let bindings = lock_bindings(&self.bindings).clone();
+        PyList::new(
+            py,
+            bindings
+                .into_iter()
+                .map(|(prefix, namespace)| {
+                    Ok((
+                        prefix,
+                        URI_REF
+                            .import(py, "rdflib", "URIRef")?
+                            .call1((namespace,))?,
+                    ))
+                })
+                .collect::<PyResult<Vec<_>>>()?,
+        )
+    }
+}
+
+fn lock_bindings(bindings: &Mutex<Vec<(String, String)>>) -> MutexGuard<'_, Vec<(String, String)>> {
+    bindings.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Returns the Python class inheriting from both [`PyRdflibStore`] and rdflib ``Store``, building it on first use
+///
+/// Our class comes first so that its methods override the rdflib ``Store`` default ones.
+/// ``__len__`` is defined in Python because rdflib gives it a ``context`` argument.
+fn rdflib_store_type(py: Python<'_>) -> PyResult<&Bound<'_, PyType>> {
+    RDFLIB_STORE
+        .get_or_try_init(py, || {
+            let globals = PyDict::new(py);
+            globals.set_item("RdflibStore", py.get_type::<PyRdflibStore>())?;
+            globals.set_item(
+                "Store",
+                py.import("rdflib.store")?.getattr(intern!(py, "Store"))?,
+            )?;
+            py.run(
+                c_str!(
+                    "class OxigraphStore(RdflibStore, Store):
+    __module__ = 'pyoxigraph'
+
+    def __len__(self, context=None):
+        return self._len(context)
+"
+                ),
+                Some(&globals),
+                None,
+            )?;
+            Ok::<_, PyErr>(
+                globals
+                    .get_item("OxigraphStore")?
+                    .ok_or_else(|| PyTypeError::new_err("The rdflib store class is not defined"))?
+                    .downcast_into::<PyType>()?
+                    .unbind(),
+            )
+        })
+        .map(|store_type| store_type.bind(py))
+}
+
+/// Iterator returned by the rdflib ``Store.triples`` method
+#[pyclass(unsendable, module = "pyoxigraph")]
+pub struct RdflibTripleIter {
+    inner: store::QuadIter,
+    store: Store,
+    /// The rdflib store, used to build the rdflib graphs
+    adapter: Py<PyAny>,
+    /// The graph given to ``triples`` if any
+    context: Option<Py<PyAny>>,
+    /// The already returned triples if the iteration is over all the graphs
+    seen: HashSet<Triple>,
+}
+
+#[pymethods]
+impl RdflibTripleIter {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__<'py>(&mut self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyTuple>>> {
+        loop {
+            let Some(quad) = self.inner.next() else {
+                return Ok(None);
+            };
+            let quad = quad.map_err(map_storage_error)?;
+            let contexts = if let Some(context) = &self.context {
+                PyTuple::new(py, [context.bind(py)])?
+            } else {
+                // A triple might be in multiple graphs, we return it once with all its graphs
+                let triple = Triple::from(quad.clone());
+                if self.seen.contains(&triple) {
+                    continue;
+                }
+                let store = &self.store;
+                let graph_names = py
+                    .allow_threads(|| {
+                        store
+                            .quads_for_pattern(
+                                Some(triple.subject.as_ref()),
+                                Some(triple.predicate.as_ref()),
+                                Some(triple.object.as_ref()),
+                                None,
+                            )
+                            .map(|quad| quad.map(|quad| quad.graph_name))
+                            .collect::<Result<Vec<_>, _>>()
+                    })
+                    .map_err(map_storage_error)?;
+                self.seen.insert(triple);
+                PyTuple::new(
+                    py,
+                    graph_names
+                        .iter()
+                        .map(|graph_name| rdflib_graph(self.adapter.bind(py), graph_name.as_ref()))
+                        .collect::<PyResult<Vec<_>>>()?,
+                )?
+            };
+            let triple = PyTuple::new(
+                py,
+                [
+                    term_to_rdflib(quad.subject.as_ref().into(), py)?,
+                    named_node_to_rdflib(quad.predicate.as_ref(), py)?,
+                    term_to_rdflib(quad.object.as_ref(), py)?,
+                ],
+            )?;
+            return Ok(Some(PyTuple::new(
+                py,
+                [triple.into_any(), contexts.into_any()],
+            )?));
+        }
+    }
+}
+
+fn named_node_to_rdflib<'py>(
+    node: NamedNodeRef<'_>,
+    py: Python<'py>,
+) -> PyResult<Bound<'py, PyAny>> {
+    URI_REF
+        .import(py, "rdflib", "URIRef")?
+        .call1((node.as_str(),))
+}
+
+fn term_to_rdflib<'py>(term: TermRef<'_>, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    match term {
+        TermRef::NamedNode(node) => named_node_to_rdflib(node, py),
+        TermRef::BlankNode(node) => BNODE.import(py, "rdflib", "BNode")?.call1((node.as_str(),)),
+        TermRef::Literal(literal) => {
+            let kwargs = PyDict::new(py);
+            if let Some(language) = literal.language() {
+                kwargs.set_item(intern!(py, "lang"), language)?;
+            } else if literal.datatype() != xsd::STRING {
+                // rdflib considers literals with the xsd:string datatype different from plain literals
+                kwargs.set_item(
+                    intern!(py, "datatype"),
+                    named_node_to_rdflib(literal.datatype(), py)?,
+                )?;
+            }
+            LITERAL
+                .import(py, "rdflib", "Literal")?
+                .call((literal.value(),), Some(&kwargs))
+        }
+        TermRef::Triple(_) => Err(PyValueError::new_err(
+            "rdflib does not support triple terms",
+        )),
+    }
+}
+
+fn graph_name_to_rdflib<'py>(
+    graph_name: GraphNameRef<'_>,
+    py: Python<'py>,
+) -> PyResult<Bound<'py, PyAny>> {
+    match graph_name {
+        GraphNameRef::NamedNode(node) => named_node_to_rdflib(node, py),
+        GraphNameRef::BlankNode(node) => term_to_rdflib(node.into(), py),
+        GraphNameRef::DefaultGraph => {
+            named_node_to_rdflib(NamedNodeRef::new_unchecked(RDFLIB_DEFAULT_GRAPH_ID), py)
+        }
+    }
+}
+
+enum RdflibTerm {
+    Term(Term),
+    DefaultGraph,
+}
+
+impl RdflibTerm {
+    fn try_into_term(self) -> PyResult<Term> {
+        match self {
+            Self::Term(term) => Ok(term),
+            Self::DefaultGraph => Err(PyValueError::new_err(
+                "The default graph identifier is only allowed as a graph name",
+            )),
+        }
+    }
+
+    fn try_into_subject(self) -> PyResult<Subject> {
+        match self.try_into_term()? {
+            Term::NamedNode(node) => Ok(node.into()),
+            Term::BlankNode(node) => Ok(node.into()),
+            Term::Literal(_) | Term::Triple(_) => Err(PyValueError::new_err(
+                "A subject must be an IRI or a blank node",
+            )),
+        }
+    }
+
+    fn try_into_named_node(self) -> PyResult<NamedNode> {
+        if let Term::NamedNode(node) = self.try_into_term()? {
+            Ok(node)
+        } else {
+            Err(PyValueError::new_err("A predicate must be an IRI"))
+        }
+    }
+}
+
+fn term_from_rdflib(value: &Bound<'_, PyAny>) -> PyResult<RdflibTerm> {
+    let py = value.py();
+    if value.is_instance(URI_REF.import(py, "rdflib", "URIRef")?)? {
+        let iri = value.extract::<String>()?;
+        if iri == RDFLIB_DEFAULT_GRAPH_ID {
+            return Ok(RdflibTerm::DefaultGraph);
+        }
+        return Ok(RdflibTerm::Term(
+            NamedNode::new(iri)
+                .map_err(|e| PyValueError::new_err(e.to_string()))?
+                .into(),
+        ));
+    }
+    if value.is_instance(BNODE.import(py, "rdflib", "BNode")?)? {
+        return Ok(RdflibTerm::Term(
+            BlankNode::new(value.extract::<String>()?)
+                .map_err(|e| PyValueError::new_err(e.to_string()))?
+                .into(),
+        ));
+    }
+    if value.is_instance(LITERAL.import(py, "rdflib", "Literal")?)? {
+        let lexical_form = value.extract::<String>()?;
+        let language = value
+            .getattr(intern!(py, "language"))?
+            .extract::<Option<String>>()?;
+        let datatype = value
+            .getattr(intern!(py, "datatype"))?
+            .extract::<Option<String>>()?;
+        return Ok(RdflibTerm::Term(
+            if let Some(language) = language {
+                Literal::new_language_tagged_literal(lexical_form, language)
+                    .map_err(|e| PyValueError::new_err(e.to_string()))?
+            } else if let Some(datatype) = datatype {
+                Literal::new_typed_literal(
+                    lexical_form,
+                    NamedNode::new(datatype).map_err(|e| PyValueError::new_err(e.to_string()))?,
+                )
+            } else {
+                Literal::new_simple_literal(lexical_form)
+            }
+            .into(),
+        ));
+    }
+    Err(PyTypeError::new_err(format!(
+        "{} is not an rdflib term",
+        value.repr()?
+    )))
+}
+
+fn graph_name_from_rdflib(value: &Bound<'_, PyAny>) -> PyResult<GraphName> {
+    let py = value.py();
+    if value.is_none() {
+        return Ok(GraphName::DefaultGraph);
+    }
+    if value.is_instance(GRAPH.import(py, "rdflib", "Graph")?)? {
+        return graph_name_from_rdflib(&value.getattr(intern!(py, "identifier"))?);
+    }
+    match term_from_rdflib(value)? {
+        RdflibTerm::Term(Term::NamedNode(node)) => Ok(node.into()),
+        RdflibTerm::Term(Term::BlankNode(node)) => Ok(node.into()),
+        RdflibTerm::DefaultGraph => Ok(GraphName::DefaultGraph),
+        RdflibTerm::Term(Term::Literal(_) | Term::Triple(_)) => Err(PyValueError::new_err(
+            "A graph name must be an IRI or a blank node",
+        )),
+    }
+}
+
+fn quad_from_rdflib(triple: &Bound<'_, PyTuple>, context: &Bound<'_, PyAny>) -> PyResult<Quad> {
+    if triple.len() != 3 {
+        return Err(PyValueError::new_err("rdflib triples must have 3 elements"));
+    }
+    Ok(Quad::new(
+        term_from_rdflib(&triple.get_item(0)?)?.try_into_subject()?,
+        term_from_rdflib(&triple.get_item(1)?)?.try_into_named_node()?,
+        term_from_rdflib(&triple.get_item(2)?)?.try_into_term()?,
+        graph_name_from_rdflib(context)?,
+    ))
+}
+
+#[allow(clippy::type_complexity)]
+fn pattern_from_rdflib(
+    pattern: &Bound<'_, PyTuple>,
+) -> PyResult<(Option<Subject>, Option<NamedNode>, Option<Term>)> {
+    if pattern.len() != 3 {
+        return Err(PyValueError::new_err(
+            "rdflib triple patterns must have 3 elements",
+        ));
+    }
+    let element = |i| {
+        let element = pattern.get_item(i)?;
+        if element.is_none() {
+            Ok(None)
+        } else {
+            term_from_rdflib(&element).map(Some)
+        }
+    };
+    Ok((
+        element(0)?.map(RdflibTerm::try_into_subject).transpose()?,
+        element(1)?
+            .map(RdflibTerm::try_into_named_node)
+            .transpose()?,
+        element(2)?.map(RdflibTerm::try_into_term).transpose()?,
+    ))
+}
+
+/// Builds the rdflib ``Graph`` with the given name stored in the rdflib store
+fn rdflib_graph<'py>(
+    store: &Bound<'py, PyAny>,
+    graph_name: GraphNameRef<'_>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let py = store.py();
+    let kwargs = PyDict::new(py);
+    kwargs.set_item(intern!(py, "store"), store)?;
+    kwargs.set_item(
+        intern!(py, "identifier"),
+        graph_name_to_rdflib(graph_name, py)?,
+    )?;
+    GRAPH.import(py, "rdflib", "Graph")?.call((), Some(&kwargs))
+}
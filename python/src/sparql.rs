@@ -1,15 +1,21 @@
 use crate::io::*;
 use crate::model::*;
 use crate::store::map_storage_error;
+#[cfg(feature = "arrow")]
+use arrow::pyarrow::IntoPyArrow;
+#[cfg(feature = "arrow")]
+use arrow::record_batch::{RecordBatchIterator, RecordBatchReader};
 use oxigraph::io::RdfSerializer;
-use oxigraph::model::Term;
+use oxigraph::model::{NamedNode, Term};
+#[cfg(feature = "arrow")]
+use oxigraph::sparql::results::ArrowSolutionsConverter;
 use oxigraph::sparql::results::{
     QueryResultsFormat, QueryResultsParseError, QueryResultsParser, QueryResultsSerializer,
     ReaderQueryResultsParserOutput, ReaderSolutionsParser,
 };
 use oxigraph::sparql::{
-    EvaluationError, Query, QueryOptions, QueryResults, QuerySolution, QuerySolutionIter,
-    QueryTripleIter, Variable,
+    AggregateFunctionAccumulator, EvaluationError, Query, QueryOptions, QueryResults,
+    QuerySolution, QuerySolutionIter, QueryTripleIter, ServiceHandler, Variable,
 };
 use pyo3::exceptions::{PyRuntimeError, PySyntaxError, PyValueError};
 use pyo3::prelude::*;
@@ -22,6 +28,7 @@ use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::vec::IntoIter;
 
 pub fn parse_query(
@@ -78,6 +85,8 @@ pub fn parse_query(
 
 pub fn query_options_from_python(
     custom_functions: Option<HashMap<PyNamedNode, PyObject>>,
+    custom_aggregate_functions: Option<HashMap<PyNamedNode, PyObject>>,
+    service_handler: Option<PyObject>,
 ) -> QueryOptions {
     let mut options = QueryOptions::default();
     #[cfg(feature = "geosparql")]
@@ -105,9 +114,89 @@ pub fn query_options_from_python(
             })
         }
     }
+    if let Some(custom_aggregate_functions) = custom_aggregate_functions {
+        for (name, function) in custom_aggregate_functions {
+            options = options.with_custom_aggregate_function(name.into(), move || {
+                Box::new(PyAggregateFunctionAccumulator {
+                    accumulator: Python::with_gil(|py| function.call0(py)).ok(),
+                })
+            })
+        }
+    }
+    if let Some(service_handler) = service_handler {
+        options = options.with_service_handler(PyServiceHandler {
+            handler: service_handler,
+        });
+    }
     options
 }
 
+/// Wraps a Python object with ``accumulate`` and ``finish`` methods.
+///
+/// If a Python call fails, the aggregate is an error.
+struct PyAggregateFunctionAccumulator {
+    accumulator: Option<PyObject>,
+}
+
+impl AggregateFunctionAccumulator for PyAggregateFunctionAccumulator {
+    fn accumulate(&mut self, element: Term) {
+        let Some(accumulator) = &self.accumulator else {
+            return;
+        };
+        let result = Python::with_gil(|py| {
+            accumulator.call_method1(py, "accumulate", (PyTerm::from(element),))
+        });
+        if result.is_err() {
+            self.accumulator = None;
+        }
+    }
+
+    fn finish(&mut self) -> Option<Term> {
+        Python::with_gil(|py| {
+            Some(
+                self.accumulator
+                    .take()?
+                    .call_method0(py, "finish")
+                    .ok()?
+                    .extract::<Option<PyTerm>>(py)
+                    .ok()??
+                    .into(),
+            )
+        })
+    }
+}
+
+/// Evaluates the ``SERVICE`` calls with a Python function returning :py:class:`QuerySolutions`.
+struct PyServiceHandler {
+    handler: PyObject,
+}
+
+impl ServiceHandler for PyServiceHandler {
+    type Error = PyErr;
+
+    fn handle(&self, service_name: NamedNode, query: Query) -> PyResult<QueryResults> {
+        Python::with_gil(|py| {
+            let results = self
+                .handler
+                .call1(py, (PyNamedNode::from(service_name), query.to_string()))?;
+            let mut results = results
+                .downcast_bound::<PyQuerySolutions>(py)?
+                .try_borrow_mut()?;
+            let variables = results
+                .variables()
+                .into_iter()
+                .map(Variable::from)
+                .collect::<Arc<[_]>>();
+            // We collect the solutions to not require the GIL during the rest of the evaluation
+            let mut solutions = Vec::new();
+            while let Some(solution) = results.__next__(py)? {
+                solutions.push(Ok(solution.inner.values().to_vec()));
+            }
+            Ok(QuerySolutionIter::new(variables, solutions.into_iter()).into())
+        })
+    }
+}
+
 pub fn query_results_to_python(
     py: Python<'_>,
     results: QueryResults,
@@ -280,6 +369,7 @@ impl PyQuerySolutions {
     /// * `JSON <https://www.w3.org/TR/sparql11-results-json/>`_ (:py:attr:`QueryResultsFormat.JSON`)
     /// * `CSV <https://www.w3.org/TR/sparql11-results-csv-tsv/>`_ (:py:attr:`QueryResultsFormat.CSV`)
     /// * `TSV <https://www.w3.org/TR/sparql11-results-csv-tsv/>`_ (:py:attr:`QueryResultsFormat.TSV`)
+    /// * `Thrift <https://jena.apache.org/documentation/io/rdf-binary.html>`_ (:py:attr:`QueryResultsFormat.THRIFT`)
     ///
     /// It supports also some media type and extension aliases.
     /// For example, ``application/json`` could also be used for `JSON <https://www.w3.org/TR/sparql11-results-json/>`_.
@@ -343,6 +433,59 @@ impl PyQuerySolutions {
         )
     }
 
+    /// Converts the remaining query solutions into a `pyarrow.Table <https://arrow.apache.org/docs/python/generated/pyarrow.Table.html>`_.
+    ///
+    /// Each variable becomes a column.
+    /// If all the values bound to a variable are IRIs, strings, integers, floating point numbers, booleans, date times or dates, the column has the matching Arrow type.
+    /// Otherwise, the column contains the N-Triples serialization of the terms.
+    /// Unbound values are nulls.
+    ///
+    /// The `pyarrow <https://arrow.apache.org/docs/python/>`_ package must be installed.
+    ///
+    /// :return: a ``pyarrow.Table`` with a column per variable.
+    /// :rtype: typing.Any
+    /// :raises ImportError: if ``pyarrow`` is not installed.
+    /// :raises OSError: if an error happens while evaluating the query or reading the results.
+    #[cfg(feature = "arrow")]
+    fn to_arrow(&mut self, py: Python<'_>) -> PyResult<PyObject> {
+        let (variables, solutions) = py.allow_threads(|| {
+            PyResult::Ok(match &mut self.inner {
+                PyQuerySolutionsVariant::Query(inner) => (
+                    inner.0.variables().to_vec(),
+                    (&mut inner.0)
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(map_evaluation_error)?,
+                ),
+                PyQuerySolutionsVariant::Reader { iter, file_path } => (
+                    iter.variables().to_vec(),
+                    iter.collect::<Result<Vec<_>, _>>()
+                        .map_err(|e| map_query_results_parse_error(e, file_path.clone()))?,
+                ),
+            })
+        })?;
+        let batch = ArrowSolutionsConverter::new(variables)
+            .convert(&solutions)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let schema = batch.schema();
+        let reader: Box<dyn RecordBatchReader + Send> =
+            Box::new(RecordBatchIterator::new([Ok(batch)], schema));
+        reader.into_pyarrow(py)?.call_method0(py, "read_all")
+    }
+
+    /// Converts the remaining query solutions into a `pandas.DataFrame <https://pandas.pydata.org/docs/reference/api/pandas.DataFrame.html>`_.
+    ///
+    /// The conversion goes through :py:meth:`to_arrow` and follows the same rules.
+    /// The `pyarrow <https://arrow.apache.org/docs/python/>`_ and `pandas <https://pandas.pydata.org/>`_ packages must be installed.
+    ///
+    /// :return: a ``pandas.DataFrame`` with a column per variable.
+    /// :rtype: typing.Any
+    /// :raises ImportError: if ``pyarrow`` or ``pandas`` are not installed.
+    /// :raises OSError: if an error happens while evaluating the query or reading the results.
+    #[cfg(feature = "arrow")]
+    fn to_pandas(&mut self, py: Python<'_>) -> PyResult<PyObject> {
+        self.to_arrow(py)?.call_method0(py, "to_pandas")
+    }
+
     fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
         slf
     }
@@ -386,6 +529,7 @@ impl PyQueryBoolean {
     /// * `JSON <https://www.w3.org/TR/sparql11-results-json/>`_ (:py:attr:`QueryResultsFormat.JSON`)
     /// * `CSV <https://www.w3.org/TR/sparql11-results-csv-tsv/>`_ (:py:attr:`QueryResultsFormat.CSV`)
     /// * `TSV <https://www.w3.org/TR/sparql11-results-csv-tsv/>`_ (:py:attr:`QueryResultsFormat.TSV`)
+    /// * `Thrift <https://jena.apache.org/documentation/io/rdf-binary.html>`_ (:py:attr:`QueryResultsFormat.THRIFT`)
     ///
     /// It supports also some media type and extension aliases.
     /// For example, ``application/json`` could also be used for `JSON <https://www.w3.org/TR/sparql11-results-json/>`_.
@@ -521,7 +665,9 @@ impl PyQueryTriples {
 ///
 /// * `XML <https://www.w3.org/TR/rdf-sparql-XMLres/>`_ (:py:attr:`QueryResultsFormat.XML`)
 /// * `JSON <https://www.w3.org/TR/sparql11-results-json/>`_ (:py:attr:`QueryResultsFormat.JSON`)
+/// * `CSV <https://www.w3.org/TR/sparql11-results-csv-tsv/>`_ (:py:attr:`QueryResultsFormat.CSV`), all values are parsed as plain strings
 /// * `TSV <https://www.w3.org/TR/sparql11-results-csv-tsv/>`_ (:py:attr:`QueryResultsFormat.TSV`)
+/// * `Thrift <https://jena.apache.org/documentation/io/rdf-binary.html>`_ (:py:attr:`QueryResultsFormat.THRIFT`)
 ///
 /// It supports also some media type and extension aliases.
 /// For example, ``application/json`` could also be used for `JSON <https://www.w3.org/TR/sparql11-results-json/>`_.
@@ -578,6 +724,7 @@ pub fn parse_query_results(
 /// * `JSON <https://www.w3.org/TR/sparql11-results-json/>`_ (:py:attr:`QueryResultsFormat.JSON`)
 /// * `CSV <https://www.w3.org/TR/sparql11-results-csv-tsv/>`_ (:py:attr:`QueryResultsFormat.CSV`)
 /// * `TSV <https://www.w3.org/TR/sparql11-results-csv-tsv/>`_ (:py:attr:`QueryResultsFormat.TSV`)
+/// * `Thrift <https://jena.apache.org/documentation/io/rdf-binary.html>`_ (:py:attr:`QueryResultsFormat.THRIFT`)
 #[pyclass(frozen, name = "QueryResultsFormat", module = "pyoxigraph", eq, hash)]
 #[derive(Clone, Copy, Eq, PartialEq, Hash)]
 pub struct PyQueryResultsFormat {
@@ -601,6 +748,11 @@ impl PyQueryResultsFormat {
     const TSV: Self = Self {
         inner: QueryResultsFormat::Tsv,
     };
+    /// `Apache Jena SPARQL Query Results Thrift Format <https://jena.apache.org/documentation/io/rdf-binary.html>`_
+    #[classattr]
+    const THRIFT: Self = Self {
+        inner: QueryResultsFormat::Thrift,
+    };
     /// `SPARQL Query Results XML Format <https://www.w3.org/TR/rdf-sparql-XMLres/>`_
     #[classattr]
     const XML: Self = Self {
@@ -750,9 +902,12 @@ pub fn map_evaluation_error(error: EvaluationError) -> PyErr {
         EvaluationError::GraphParsing(error) => map_parse_error(error, None),
         EvaluationError::ResultsParsing(error) => map_query_results_parse_error(error, None),
         EvaluationError::ResultsSerialization(error) => error.into(),
-        EvaluationError::Service(error) => match error.downcast::<io::Error>() {
-            Ok(error) => (*error).into(),
-            Err(error) => PyRuntimeError::new_err(error.to_string()),
+        EvaluationError::Service(error) => match error.downcast::<PyErr>() {
+            Ok(error) => *error,
+            Err(error) => match error.downcast::<io::Error>() {
+                Ok(error) => (*error).into(),
+                Err(error) => PyRuntimeError::new_err(error.to_string()),
+            },
         },
         _ => PyRuntimeError::new_err(error.to_string()),
     }
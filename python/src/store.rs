@@ -5,14 +5,21 @@ use crate::io::{
 use crate::model::*;
 use crate::sparql::*;
 use oxigraph::io::{RdfParser, RdfSerializer};
-use oxigraph::model::GraphNameRef;
-use oxigraph::sparql::{QueryResults, Update};
-use oxigraph::store::{self, LoaderError, SerializerError, StorageError, Store};
+use oxigraph::model::{GraphNameRef, Quad, QuadRef};
+use oxigraph::sparql::{EvaluationError, QueryOptions, QueryResults, Update};
+use oxigraph::store::{self, LoaderError, SerializerError, StorageError, Store, Transaction};
 use pyo3::exceptions::{PyRuntimeError, PyValueError};
 use pyo3::prelude::*;
 use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt;
+use std::mem::take;
 use std::path::PathBuf;
 
+/// Number of quads buffered by a :py:class:`BulkWriter` before writing them to the store
+const BULK_WRITER_BATCH_SIZE: usize = 100_000;
+
 /// RDF store.
 ///
 /// It encodes a `RDF dataset <https://www.w3.org/TR/rdf11-concepts/#dfn-rdf-dataset>`_ and allows to query it using SPARQL.
@@ -43,6 +50,12 @@ pub struct PyStore {
     inner: Store,
 }
 
+impl From<PyStore> for Store {
+    fn from(store: PyStore) -> Self {
+        store.inner
+    }
+}
+
 #[pymethods]
 impl PyStore {
     #[cfg(not(target_family = "wasm"))]
@@ -157,6 +170,26 @@ impl PyStore {
         Ok(())
     }
 
+    /// Returns a writer adding quads to this store using the bulk loader.
+    ///
+    /// The writer is designed to stream a large number of quads as fast as possible **without** transactional guarantees.
+    /// It is meant to be used as a context manager: the buffered quads are written at the end of the ``with`` block.
+    ///
+    /// :return: the new writer.
+    /// :rtype: BulkWriter
+    ///
+    /// >>> store = Store()
+    /// >>> with store.bulk_writer() as writer:
+    /// ...     writer.add(Quad(NamedNode('http://example.com'), NamedNode('http://example.com/p'), Literal('1')))
+    /// >>> list(store)
+    /// [<Quad subject=<NamedNode value=http://example.com> predicate=<NamedNode value=http://example.com/p> object=<Literal value=1 datatype=<NamedNode value=http://www.w3.org/2001/XMLSchema#string>> graph_name=<DefaultGraph>>]
+    fn bulk_writer(&self) -> PyBulkWriter {
+        PyBulkWriter {
+            store: self.inner.clone(),
+            buffer: Vec::new(),
+        }
+    }
+
     /// Removes a quad from the store.
     ///
     /// :param quad: the quad to remove.
@@ -230,6 +263,10 @@ impl PyStore {
     /// :type substitutions: dict[Variable, NamedNode or BlankNode or Literal or Triple] or None, optional
     /// :param custom_functions: dictionary of custom functions mapping function names to their definition. Custom functions takes for input some :py:class:`Term`s and return a :py:class:`Term` or :py:const:`None`.
     /// :type custom_functions: dict[NamedNode, typing.Callable[[NamedNode or BlankNode or Literal or Triple, ...], NamedNode or BlankNode or Literal or Triple or None]] or None, optional
+    /// :param custom_aggregate_functions: dictionary of custom aggregate functions mapping function names to their definition. The definition is called for each group to build an accumulator object with an ``accumulate`` method called with each :py:class:`Term` of the group and a ``finish`` method returning the aggregated :py:class:`Term` or :py:const:`None`.
+    /// :type custom_aggregate_functions: dict[NamedNode, typing.Callable[[], typing.Any]] or None, optional
+    /// :param service_handler: function evaluating the ``SERVICE`` calls. It takes for input the service name as a :py:class:`NamedNode` and the SPARQL query to evaluate as a :py:class:`str` and returns some :py:class:`QuerySolutions`, for example built with :py:func:`parse_query_results`. By default, ``SERVICE`` calls are done using HTTP.
+    /// :type service_handler: typing.Callable[[NamedNode, str], QuerySolutions] or None, optional
     /// :return: a :py:class:`bool` for ``ASK`` queries, an iterator of :py:class:`Triple` for ``CONSTRUCT`` and ``DESCRIBE`` queries and an iterator of :py:class:`QuerySolution` for ``SELECT`` queries.
     /// :rtype: QuerySolutions or QueryBoolean or QueryTriples
     /// :raises SyntaxError: if the provided query is invalid.
@@ -255,7 +292,7 @@ impl PyStore {
     /// >>> store.add(Quad(NamedNode('http://example.com'), NamedNode('http://example.com/p'), Literal('1')))
     /// >>> bool(store.query('ASK { ?s ?p ?o }'))
     /// True
-    #[pyo3(signature = (query, *, base_iri = None, use_default_graph_as_union = false, default_graph = None, named_graphs = None, substitutions = None, custom_functions = None))]
+    #[pyo3(signature = (query, *, base_iri = None, use_default_graph_as_union = false, default_graph = None, named_graphs = None, substitutions = None, custom_functions = None, custom_aggregate_functions = None, service_handler = None))]
     fn query<'py>(
         &self,
         query: &str,
@@ -265,14 +302,10 @@ impl PyStore {
         named_graphs: Option<&Bound<'_, PyAny>>,
         substitutions: Option<HashMap<PyVariable, PyTerm>>,
         custom_functions: Option<HashMap<PyNamedNode, PyObject>>,
+        custom_aggregate_functions: Option<HashMap<PyNamedNode, PyObject>>,
+        service_handler: Option<PyObject>,
         py: Python<'py>,
     ) -> PyResult<Bound<'py, PyAny>> {
-        pub struct UngilQueryResults(QueryResults);
-
-        #[allow(unsafe_code)]
-        // SAFETY: To derive Ungil
-        unsafe impl Send for UngilQueryResults {}
-
         let query = parse_query(
             query,
             base_iri,
@@ -281,7 +314,11 @@ impl PyStore {
             named_graphs,
             py,
         )?;
-        let options = query_options_from_python(custom_functions);
+        let options = query_options_from_python(
+            custom_functions,
+            custom_aggregate_functions,
+            service_handler,
+        );
         let substitutions = substitutions
             .unwrap_or_default()
             .into_iter()
@@ -311,6 +348,10 @@ impl PyStore {
     /// :type base_iri: str or None, optional
     /// :param custom_functions: dictionary of custom functions mapping function names to their definition. Custom functions takes for input some :py:class:`Term`s and return a :py:class:`Term` or :py:const:`None`.
     /// :type custom_functions: dict[NamedNode, typing.Callable[[NamedNode or BlankNode or Literal or Triple, ...], NamedNode or BlankNode or Literal or Triple or None]] or None, optional
+    /// :param custom_aggregate_functions: dictionary of custom aggregate functions mapping function names to their definition. The definition is called for each group to build an accumulator object with an ``accumulate`` method called with each :py:class:`Term` of the group and a ``finish`` method returning the aggregated :py:class:`Term` or :py:const:`None`.
+    /// :type custom_aggregate_functions: dict[NamedNode, typing.Callable[[], typing.Any]] or None, optional
+    /// :param service_handler: function evaluating the ``SERVICE`` calls. It takes for input the service name as a :py:class:`NamedNode` and the SPARQL query to evaluate as a :py:class:`str` and returns some :py:class:`QuerySolutions`, for example built with :py:func:`parse_query_results`. By default, ``SERVICE`` calls are done using HTTP.
+    /// :type service_handler: typing.Callable[[NamedNode, str], QuerySolutions] or None, optional
     /// :rtype: None
     /// :raises SyntaxError: if the provided update is invalid.
     /// :raises OSError: if an error happens while reading the store.
@@ -337,16 +378,22 @@ impl PyStore {
     /// >>> store.update('DELETE WHERE { <http://example.com> ?p ?o }')
     /// >>> list(store)
     /// []
-    #[pyo3(signature = (update, *, base_iri = None, custom_functions = None))]
+    #[pyo3(signature = (update, *, base_iri = None, custom_functions = None, custom_aggregate_functions = None, service_handler = None))]
     fn update(
         &self,
         update: &str,
         base_iri: Option<&str>,
         custom_functions: Option<HashMap<PyNamedNode, PyObject>>,
+        custom_aggregate_functions: Option<HashMap<PyNamedNode, PyObject>>,
+        service_handler: Option<PyObject>,
         py: Python<'_>,
     ) -> PyResult<()> {
         py.allow_threads(|| {
-            let options = query_options_from_python(custom_functions);
+            let options = query_options_from_python(
+                custom_functions,
+                custom_aggregate_functions,
+                service_handler,
+            );
             let update =
                 Update::parse(update, base_iri).map_err(|e| map_evaluation_error(e.into()))?;
             self.inner
@@ -355,6 +402,27 @@ impl PyStore {
         })
     }
 
+    /// Starts a transaction on the store.
+    ///
+    /// The returned :py:class:`Transaction` is meant to be used as a context manager:
+    /// its changes are written atomically to the store at the end of the ``with`` block or discarded if an exception is raised.
+    ///
+    /// :return: the new transaction.
+    /// :rtype: Transaction
+    ///
+    /// >>> store = Store()
+    /// >>> with store.transaction() as transaction:
+    /// ...     transaction.add(Quad(NamedNode('http://example.com'), NamedNode('http://example.com/p'), Literal('1')))
+    /// ...     transaction.update('INSERT { ?s ?p "2" } WHERE { ?s ?p "1" }')
+    /// >>> len(store)
+    /// 2
+    fn transaction(&self) -> PyTransaction {
+        PyTransaction {
+            store: self.inner.clone(),
+            operations: Some(Vec::new()),
+        }
+    }
+
     /// Loads an RDF serialization into the store.
     ///
     /// Loads are applied in a transactional manner: either the full operation succeeds or nothing is written to the database.
@@ -420,7 +488,8 @@ impl PyStore {
             }
             self.inner
                 .load_from_reader(parser, input)
-                .map_err(|e| map_loader_error(e, path))
+                .map_err(|e| map_loader_error(e, path))?;
+            Ok(())
         })
     }
 
@@ -490,7 +559,8 @@ impl PyStore {
             self.inner
                 .bulk_loader()
                 .load_from_reader(parser, input)
-                .map_err(|e| map_loader_error(e, path))
+                .map_err(|e| map_loader_error(e, path))?;
+            Ok(())
         })
     }
 
@@ -840,6 +910,407 @@ impl GraphNameIter {
     }
 }
 
+/// A transaction on a :py:class:`Store` returned by :py:func:`Store.transaction`.
+///
+/// The changes done through the transaction are recorded and written atomically to the store on :py:func:`commit`.
+/// Until then, they are only visible to the queries evaluated with :py:func:`query`.
+/// When the transaction is used as a context manager, it is committed at the end of the ``with`` block or rolled back if an exception is raised.
+///
+/// Beware, the recorded changes are applied again for each query evaluation and SPARQL updates are only evaluated on commit.
+///
+/// >>> store = Store()
+/// >>> with store.transaction() as transaction:
+/// ...     transaction.add(Quad(NamedNode('http://example.com'), NamedNode('http://example.com/p'), Literal('1')))
+/// ...     [solution['o'] for solution in transaction.query('SELECT ?o WHERE { ?s ?p ?o }')]
+/// [<Literal value=1 datatype=<NamedNode value=http://www.w3.org/2001/XMLSchema#string>>]
+/// >>> len(store)
+/// 1
+#[pyclass(name = "Transaction", module = "pyoxigraph")]
+pub struct PyTransaction {
+    store: Store,
+    /// The changes done in the transaction, `None` if it has been committed or rolled back
+    operations: Option<Vec<TransactionOperation>>,
+}
+
+#[pymethods]
+impl PyTransaction {
+    /// Adds a quad to the store in the transaction.
+    ///
+    /// :param quad: the quad to add.
+    /// :type quad: Quad
+    /// :rtype: None
+    /// :raises ValueError: if the transaction is already committed or rolled back.
+    fn add(&mut self, quad: &PyQuad) -> PyResult<()> {
+        self.operations()?.push(TransactionOperation::Insert(
+            QuadRef::from(quad).into_owned(),
+        ));
+        Ok(())
+    }
+
+    /// Removes a quad from the store in the transaction.
+    ///
+    /// :param quad: the quad to remove.
+    /// :type quad: Quad
+    /// :rtype: None
+    /// :raises ValueError: if the transaction is already committed or rolled back.
+    fn remove(&mut self, quad: &PyQuad) -> PyResult<()> {
+        self.operations()?.push(TransactionOperation::Remove(
+            QuadRef::from(quad).into_owned(),
+        ));
+        Ok(())
+    }
+
+    /// Executes a `SPARQL 1.1 query <https://www.w3.org/TR/sparql11-query/>`_ seeing the changes done in the transaction.
+    ///
+    /// The results are computed eagerly.
+    ///
+    /// :param query: the query to execute.
+    /// :type query: str
+    /// :param base_iri: the base IRI used to resolve the relative IRIs in the SPARQL query or :py:const:`None` if relative IRI resolution should not be done.
+    /// :type base_iri: str or None, optional
+    /// :param use_default_graph_as_union: if the SPARQL query should look for triples in all the dataset graphs by default (i.e. without `GRAPH` operations). Disabled by default.
+    /// :type use_default_graph_as_union: bool, optional
+    /// :param default_graph: list of the graphs that should be used as the query default graph. By default, the store default graph is used.
+    /// :type default_graph: NamedNode or BlankNode or DefaultGraph or list[NamedNode or BlankNode or DefaultGraph] or None, optional
+    /// :param named_graphs: list of the named graphs that could be used in SPARQL `GRAPH` clause. By default, all the store named graphs are available.
+    /// :type named_graphs: list[NamedNode or BlankNode] or None, optional
+    /// :param custom_functions: dictionary of custom functions mapping function names to their definition. Custom functions takes for input some :py:class:`Term`s and return a :py:class:`Term` or :py:const:`None`.
+    /// :type custom_functions: dict[NamedNode, typing.Callable[[NamedNode or BlankNode or Literal or Triple, ...], NamedNode or BlankNode or Literal or Triple or None]] or None, optional
+    /// :param custom_aggregate_functions: dictionary of custom aggregate functions mapping function names to their definition. See :py:func:`Store.query`.
+    /// :type custom_aggregate_functions: dict[NamedNode, typing.Callable[[], typing.Any]] or None, optional
+    /// :param service_handler: function evaluating the ``SERVICE`` calls. See :py:func:`Store.query`.
+    /// :type service_handler: typing.Callable[[NamedNode, str], QuerySolutions] or None, optional
+    /// :return: a :py:class:`bool` for ``ASK`` queries, an iterator of :py:class:`Triple` for ``CONSTRUCT`` and ``DESCRIBE`` queries and an iterator of :py:class:`QuerySolution` for ``SELECT`` queries.
+    /// :rtype: QuerySolutions or QueryBoolean or QueryTriples
+    /// :raises SyntaxError: if the provided query is invalid.
+    /// :raises ValueError: if the transaction is already committed or rolled back.
+    /// :raises OSError: if an error happens while reading the store.
+    ///
+    /// >>> store = Store()
+    /// >>> transaction = store.transaction()
+    /// >>> transaction.update('INSERT DATA { <http://example.com> <http://example.com/p> "1" }')
+    /// >>> bool(transaction.query('ASK { ?s ?p ?o }'))
+    /// True
+    /// >>> bool(store.query('ASK { ?s ?p ?o }'))
+    /// False
+    #[pyo3(signature = (query, *, base_iri = None, use_default_graph_as_union = false, default_graph = None, named_graphs = None, custom_functions = None, custom_aggregate_functions = None, service_handler = None))]
+    fn query<'py>(
+        &self,
+        query: &str,
+        base_iri: Option<&str>,
+        use_default_graph_as_union: bool,
+        default_graph: Option<&Bound<'_, PyAny>>,
+        named_graphs: Option<&Bound<'_, PyAny>>,
+        custom_functions: Option<HashMap<PyNamedNode, PyObject>>,
+        custom_aggregate_functions: Option<HashMap<PyNamedNode, PyObject>>,
+        service_handler: Option<PyObject>,
+        py: Python<'py>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let query = parse_query(
+            query,
+            base_iri,
+            use_default_graph_as_union,
+            default_graph,
+            named_graphs,
+            py,
+        )?;
+        let options = query_options_from_python(
+            custom_functions,
+            custom_aggregate_functions,
+            service_handler,
+        );
+        let operations = self
+            .operations
+            .as_deref()
+            .ok_or_else(finished_transaction_error)?;
+        let store = &self.store;
+        let result = py.allow_threads(|| {
+            store.transaction(|mut transaction| {
+                apply_operations(&mut transaction, operations)?;
+                let results = transaction.query_opt(query.clone(), options.clone())?;
+                // We never write the changes, they are only written on commit
+                Err::<Infallible, _>(TransactionQueryError::Rollback(UngilQueryResults(results)))
+            })
+        });
+        match result {
+            Err(TransactionQueryError::Rollback(results)) => query_results_to_python(py, results.0),
+            Err(TransactionQueryError::Evaluation(error)) => Err(map_evaluation_error(error)),
+            Ok(never) => match never {},
+        }
+    }
+
+    /// Executes a `SPARQL 1.1 update <https://www.w3.org/TR/sparql11-update/>`_ in the transaction.
+    ///
+    /// The update is parsed immediately but only evaluated by the queries done in the transaction and on commit.
+    ///
+    /// :param update: the update to execute.
+    /// :type update: str
+    /// :param base_iri: the base IRI used to resolve the relative IRIs in the SPARQL update or :py:const:`None` if relative IRI resolution should not be done.
+    /// :type base_iri: str or None, optional
+    /// :param custom_functions: dictionary of custom functions mapping function names to their definition. Custom functions takes for input some :py:class:`Term`s and return a :py:class:`Term` or :py:const:`None`.
+    /// :type custom_functions: dict[NamedNode, typing.Callable[[NamedNode or BlankNode or Literal or Triple, ...], NamedNode or BlankNode or Literal or Triple or None]] or None, optional
+    /// :param custom_aggregate_functions: dictionary of custom aggregate functions mapping function names to their definition. See :py:func:`Store.query`.
+    /// :type custom_aggregate_functions: dict[NamedNode, typing.Callable[[], typing.Any]] or None, optional
+    /// :param service_handler: function evaluating the ``SERVICE`` calls. See :py:func:`Store.query`.
+    /// :type service_handler: typing.Callable[[NamedNode, str], QuerySolutions] or None, optional
+    /// :rtype: None
+    /// :raises SyntaxError: if the provided update is invalid.
+    /// :raises ValueError: if the transaction is already committed or rolled back.
+    #[pyo3(signature = (update, *, base_iri = None, custom_functions = None, custom_aggregate_functions = None, service_handler = None))]
+    fn update(
+        &mut self,
+        update: &str,
+        base_iri: Option<&str>,
+        custom_functions: Option<HashMap<PyNamedNode, PyObject>>,
+        custom_aggregate_functions: Option<HashMap<PyNamedNode, PyObject>>,
+        service_handler: Option<PyObject>,
+        py: Python<'_>,
+    ) -> PyResult<()> {
+        let update = py
+            .allow_threads(|| Update::parse(update, base_iri))
+            .map_err(|e| map_evaluation_error(e.into()))?;
+        let options = query_options_from_python(
+            custom_functions,
+            custom_aggregate_functions,
+            service_handler,
+        );
+        self.operations()?
+            .push(TransactionOperation::Update(update, options));
+        Ok(())
+    }
+
+    /// Writes atomically the changes done in the transaction to the store.
+    ///
+    /// The transaction can't be used anymore after this call.
+    ///
+    /// :rtype: None
+    /// :raises ValueError: if the transaction is already committed or rolled back.
+    /// :raises OSError: if an error happens while writing to the store.
+    ///
+    /// >>> store = Store()
+    /// >>> transaction = store.transaction()
+    /// >>> transaction.add(Quad(NamedNode('http://example.com'), NamedNode('http://example.com/p'), Literal('1')))
+    /// >>> transaction.commit()
+    /// >>> len(store)
+    /// 1
+    fn commit(&mut self, py: Python<'_>) -> PyResult<()> {
+        let operations = take(self.operations()?);
+        self.operations = None;
+        let store = &self.store;
+        py.allow_threads(|| {
+            store.transaction(|mut transaction| apply_operations(&mut transaction, &operations))
+        })
+        .map_err(map_evaluation_error)
+    }
+
+    /// Discards the changes done in the transaction.
+    ///
+    /// The transaction can't be used anymore after this call.
+    ///
+    /// :rtype: None
+    /// :raises ValueError: if the transaction is already committed or rolled back.
+    ///
+    /// >>> store = Store()
+    /// >>> transaction = store.transaction()
+    /// >>> transaction.add(Quad(NamedNode('http://example.com'), NamedNode('http://example.com/p'), Literal('1')))
+    /// >>> transaction.rollback()
+    /// >>> len(store)
+    /// 0
+    fn rollback(&mut self) -> PyResult<()> {
+        self.operations()?;
+        self.operations = None;
+        Ok(())
+    }
+
+    /// :rtype: Transaction
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// :type exc_type: type[BaseException] or None
+    /// :type exc_value: BaseException or None
+    /// :type traceback: types.TracebackType or None
+    /// :rtype: None
+    #[allow(unused_variables)]
+    fn __exit__(
+        &mut self,
+        exc_type: &Bound<'_, PyAny>,
+        exc_value: &Bound<'_, PyAny>,
+        traceback: &Bound<'_, PyAny>,
+        py: Python<'_>,
+    ) -> PyResult<()> {
+        if self.operations.is_none() {
+            // Already committed or rolled back inside of the block
+            return Ok(());
+        }
+        if exc_type.is_none() {
+            self.commit(py)
+        } else {
+            self.rollback()
+        }
+    }
+}
+
+impl PyTransaction {
+    fn operations(&mut self) -> PyResult<&mut Vec<TransactionOperation>> {
+        self.operations
+            .as_mut()
+            .ok_or_else(finished_transaction_error)
+    }
+}
+
+fn finished_transaction_error() -> PyErr {
+    PyValueError::new_err("The transaction has already been committed or rolled back")
+}
+
+enum TransactionOperation {
+    Insert(Quad),
+    Remove(Quad),
+    Update(Update, QueryOptions),
+}
+
+fn apply_operations(
+    transaction: &mut Transaction<'_>,
+    operations: &[TransactionOperation],
+) -> Result<(), EvaluationError> {
+    for operation in operations {
+        match operation {
+            TransactionOperation::Insert(quad) => {
+                transaction.insert(quad)?;
+            }
+            TransactionOperation::Remove(quad) => {
+                transaction.remove(quad)?;
+            }
+            TransactionOperation::Update(update, options) => {
+                transaction.update_opt(update.clone(), options.clone())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Error used to roll back the transaction in which a query has been evaluated
+enum TransactionQueryError {
+    Evaluation(EvaluationError),
+    Rollback(UngilQueryResults),
+}
+
+impl From<EvaluationError> for TransactionQueryError {
+    fn from(error: EvaluationError) -> Self {
+        Self::Evaluation(error)
+    }
+}
+
+impl From<StorageError> for TransactionQueryError {
+    fn from(error: StorageError) -> Self {
+        Self::Evaluation(error.into())
+    }
+}
+
+impl fmt::Debug for TransactionQueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Evaluation(error) => fmt::Debug::fmt(error, f),
+            Self::Rollback(_) => f.write_str("Rollback"),
+        }
+    }
+}
+
+impl fmt::Display for TransactionQueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Evaluation(error) => fmt::Display::fmt(error, f),
+            Self::Rollback(_) => f.write_str("The query transaction is rolled back"),
+        }
+    }
+}
+
+impl Error for TransactionQueryError {}
+
+struct UngilQueryResults(QueryResults);
+
+#[allow(unsafe_code)]
+// SAFETY: To derive Ungil
+unsafe impl Send for UngilQueryResults {}
+
+/// A writer adding quads to a :py:class:`Store` using the bulk loader, returned by :py:func:`Store.bulk_writer`.
+///
+/// The quads are buffered and written to the store by batches **without** transactional guarantees.
+/// The buffered quads are written on :py:func:`flush` and, when the writer is used as a context manager, at the end of the ``with`` block.
+///
+/// >>> store = Store()
+/// >>> with store.bulk_writer() as writer:
+/// ...     writer.extend([Quad(NamedNode('http://example.com'), NamedNode('http://example.com/p'), Literal(str(i))) for i in range(10)])
+/// >>> len(store)
+/// 10
+#[pyclass(name = "BulkWriter", module = "pyoxigraph")]
+pub struct PyBulkWriter {
+    store: Store,
+    buffer: Vec<Quad>,
+}
+
+#[pymethods]
+impl PyBulkWriter {
+    /// Adds a quad to the store.
+    ///
+    /// :param quad: the quad to add.
+    /// :type quad: Quad
+    /// :rtype: None
+    /// :raises OSError: if an error happens during the quad insertion.
+    fn add(&mut self, quad: &PyQuad, py: Python<'_>) -> PyResult<()> {
+        self.buffer.push(QuadRef::from(quad).into_owned());
+        if self.buffer.len() >= BULK_WRITER_BATCH_SIZE {
+            self.flush(py)?;
+        }
+        Ok(())
+    }
+
+    /// Adds a set of quads to the store.
+    ///
+    /// :param quads: the quads to add.
+    /// :type quads: collections.abc.Iterable[Quad]
+    /// :rtype: None
+    /// :raises OSError: if an error happens during the quad insertion.
+    fn extend(&mut self, quads: &Bound<'_, PyAny>, py: Python<'_>) -> PyResult<()> {
+        for quad in quads.try_iter()? {
+            self.add(&quad?.extract()?, py)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the buffered quads to the store.
+    ///
+    /// :rtype: None
+    /// :raises OSError: if an error happens during the quad insertion.
+    fn flush(&mut self, py: Python<'_>) -> PyResult<()> {
+        let quads = take(&mut self.buffer);
+        let store = &self.store;
+        py.allow_threads(|| store.bulk_loader().load_quads(quads))
+            .map_err(map_storage_error)?;
+        Ok(())
+    }
+
+    /// :rtype: BulkWriter
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// :type exc_type: type[BaseException] or None
+    /// :type exc_value: BaseException or None
+    /// :type traceback: types.TracebackType or None
+    /// :rtype: None
+    #[allow(unused_variables)]
+    fn __exit__(
+        &mut self,
+        exc_type: &Bound<'_, PyAny>,
+        exc_value: &Bound<'_, PyAny>,
+        traceback: &Bound<'_, PyAny>,
+        py: Python<'_>,
+    ) -> PyResult<()> {
+        self.flush(py)
+    }
+}
+
 pub fn map_storage_error(error: StorageError) -> PyErr {
     match error {
         StorageError::Io(error) => error.into(),
@@ -162,6 +162,43 @@ class TestParse(unittest.TestCase):
             ),
         )
 
+    def test_parse_lazy(self) -> None:
+        quads = parse(
+            BytesIO(b'<foo> <p> "1" .\n<foo> <p> "2" .\n<foo> <p> "3" .\n'),
+            RdfFormat.N_TRIPLES,
+            base_iri="http://example.com/",
+            buffer_size=16,
+        )
+        self.assertEqual(next(quads).object, Literal("1"))
+        self.assertEqual(next(quads).object, Literal("2"))
+
+    def test_parse_buffer_size_zero(self) -> None:
+        with self.assertRaises(ValueError) as _:
+            parse(b"", RdfFormat.N_TRIPLES, buffer_size=0)
+
+    def test_parse_close(self) -> None:
+        quads = parse('<foo> <p> "1" .\n<foo> <p> "2" .\n', RdfFormat.N_TRIPLES, base_iri="http://example.com/")
+        next(quads)
+        quads.close()
+        self.assertEqual(list(quads), [])
+        self.assertEqual(quads.prefixes, {})
+
+    def test_parse_context_manager(self) -> None:
+        with NamedTemporaryFile(suffix=".ttl") as fp:
+            fp.write('<foo> <p> "éù" .'.encode())
+            fp.flush()
+            with parse(path=fp.name, base_iri="http://example.com/") as quads:
+                self.assertEqual(list(quads), [EXAMPLE_TRIPLE])
+            self.assertEqual(list(quads), [])
+
+    def test_parse_cancel(self) -> None:
+        with self.assertRaises(ValueError) as _, parse(
+            '<foo> <p> "1" .\n<foo> <p> "2" .\n', RdfFormat.N_TRIPLES, base_iri="http://example.com/"
+        ) as quads:
+            for _ in quads:
+                raise ValueError("cancel")
+        self.assertEqual(list(quads), [])
+
 
 class TestSerialize(unittest.TestCase):
     def test_serialize_to_bytes(self) -> None:
@@ -116,6 +116,15 @@ class TestLiteral(unittest.TestCase):
         self.assertEqual(Literal(-math.inf), Literal("-INF", datatype=XSD_DOUBLE))
         self.assertEqual(Literal(-math.nan), Literal("NaN", datatype=XSD_DOUBLE))
 
+    def test_geo_interface(self) -> None:
+        wkt_literal = NamedNode("http://www.opengis.net/ont/geosparql#wktLiteral")
+        self.assertEqual(
+            Literal("POINT(1 2)", datatype=wkt_literal).__geo_interface__,
+            {"type": "Point", "coordinates": [1.0, 2.0]},
+        )
+        self.assertFalse(hasattr(Literal("POINT(1 2)"), "__geo_interface__"))
+        self.assertFalse(hasattr(Literal("foo", datatype=wkt_literal), "__geo_interface__"))
+
     def test_string(self) -> None:
         self.assertEqual(str(Literal("foo")), '"foo"')
         self.assertEqual(str(Literal("foo", language="en")), '"foo"@en')
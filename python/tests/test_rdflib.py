@@ -0,0 +1,109 @@
+import importlib
+import importlib.util
+import unittest
+from typing import Any
+
+from pyoxigraph import (
+    BlankNode,
+    DefaultGraph,
+    Literal,
+    NamedNode,
+    Quad,
+    Store,
+    Triple,
+    Variable,
+    from_rdflib,
+    to_rdflib,
+)
+
+XSD_INTEGER = NamedNode("http://www.w3.org/2001/XMLSchema#integer")
+
+foo = NamedNode("http://foo")
+bar = NamedNode("http://bar")
+graph = NamedNode("http://graph")
+
+
+@unittest.skipIf(importlib.util.find_spec("rdflib") is None, "rdflib is not installed")
+class TestRdflib(unittest.TestCase):
+    def setUp(self) -> None:
+        self.rdflib: Any = importlib.import_module("rdflib")
+
+    def test_terms_round_trip(self) -> None:
+        for term in (
+            foo,
+            BlankNode("b1"),
+            Literal("foo"),
+            Literal("foo", language="en"),
+            Literal("1", datatype=XSD_INTEGER),
+            Variable("v"),
+            DefaultGraph(),
+        ):
+            self.assertEqual(from_rdflib(to_rdflib(term)), term)
+
+    def test_to_rdflib(self) -> None:
+        self.assertEqual(to_rdflib(foo), self.rdflib.URIRef("http://foo"))
+        self.assertEqual(to_rdflib(Literal("foo")), self.rdflib.Literal("foo"))
+        self.assertEqual(
+            to_rdflib(Literal("foo", language="en")),
+            self.rdflib.Literal("foo", lang="en"),
+        )
+        self.assertEqual(
+            to_rdflib(Triple(foo, bar, Literal("1", datatype=XSD_INTEGER))),
+            (self.rdflib.URIRef("http://foo"), self.rdflib.URIRef("http://bar"), self.rdflib.Literal(1)),
+        )
+
+    def test_to_rdflib_triple_term(self) -> None:
+        with self.assertRaises(ValueError):
+            to_rdflib(Triple(foo, bar, Triple(foo, bar, foo)))
+
+    def test_from_rdflib_graph(self) -> None:
+        rdflib_graph = self.rdflib.Graph()
+        rdflib_graph.add((self.rdflib.URIRef("http://foo"), self.rdflib.URIRef("http://bar"), self.rdflib.Literal(1)))
+        store = Store()
+        store.extend(from_rdflib((*t, graph)) for t in rdflib_graph)
+        self.assertEqual(list(store), [Quad(foo, bar, Literal(1), graph)])
+
+    def test_from_rdflib_dataset(self) -> None:
+        dataset = self.rdflib.Dataset()
+        dataset.add((self.rdflib.URIRef("http://foo"), self.rdflib.URIRef("http://bar"), self.rdflib.Literal("a")))
+        self.assertEqual(
+            [from_rdflib(q) for q in dataset.quads()],
+            [Quad(foo, bar, Literal("a"), DefaultGraph())],
+        )
+
+    def test_to_rdflib_store(self) -> None:
+        store = Store()
+        store.add(Quad(foo, bar, Literal("a")))
+        store.add(Quad(foo, bar, Literal("b"), graph))
+        dataset = to_rdflib(store)
+        self.assertIsInstance(dataset, self.rdflib.Dataset)
+        self.assertEqual(
+            {from_rdflib(q) for q in dataset.quads()},
+            {Quad(foo, bar, Literal("a")), Quad(foo, bar, Literal("b"), graph)},
+        )
+        self.assertEqual(len(dataset.graph(to_rdflib(graph))), 1)
+
+        # The changes done with rdflib are written to the store
+        dataset.add((to_rdflib(bar), to_rdflib(foo), self.rdflib.Literal("c")))
+        dataset.graph(to_rdflib(graph)).remove((None, None, None))
+        self.assertEqual(set(store), {Quad(foo, bar, Literal("a")), Quad(bar, foo, Literal("c"))})
+        self.assertEqual(
+            list(dataset.query("SELECT ?o WHERE { <http://bar> ?p ?o }")),
+            [(self.rdflib.Literal("c"),)],
+        )
+
+    def test_to_rdflib_store_namespaces(self) -> None:
+        dataset = to_rdflib(Store())
+        dataset.bind("ex", "http://example.com/")
+        self.assertEqual(dataset.store.namespace("ex"), self.rdflib.URIRef("http://example.com/"))
+        self.assertEqual(dataset.store.prefix("http://example.com/"), "ex")
+
+    def test_from_rdflib_invalid(self) -> None:
+        with self.assertRaises(TypeError):
+            from_rdflib("http://foo")
+        with self.assertRaises(ValueError):
+            from_rdflib((self.rdflib.Literal("a"), self.rdflib.URIRef("http://bar"), self.rdflib.Literal("a")))
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -1,4 +1,5 @@
 import gc
+import importlib.util
 import sys
 import unittest
 from io import BytesIO, StringIO, UnsupportedOperation
@@ -152,6 +153,18 @@ class TestStore(unittest.TestCase):
         self.assertEqual(s, foo)
         self.assertEqual(o, baz)
 
+    @unittest.skipIf(importlib.util.find_spec("pyarrow") is None, "pyarrow is not installed")
+    def test_select_query_to_arrow(self) -> None:
+        xsd_integer = NamedNode("http://www.w3.org/2001/XMLSchema#integer")
+        store = Store()
+        store.add(Quad(foo, bar, Literal("1", datatype=xsd_integer)))
+        store.add(Quad(baz, bar, Literal("2", datatype=xsd_integer)))
+        solutions: Any = store.query("SELECT ?s ?o WHERE { ?s ?p ?o } ORDER BY ?o")
+        table = solutions.to_arrow()
+        self.assertEqual(table.column_names, ["s", "o"])
+        self.assertEqual(table.column("s").to_pylist(), [foo.value, baz.value])
+        self.assertEqual(table.column("o").to_pylist(), [1, 2])
+
     def test_select_query_union_default_graph(self) -> None:
         store = Store()
         store.add(Quad(foo, bar, baz, graph))
@@ -211,6 +224,54 @@ class TestStore(unittest.TestCase):
         self.assertEqual(solution["c"], Literal("http://foohttp://bar"))
         self.assertIsNone(solution["f"], None)
 
+    def test_select_query_with_custom_aggregate_functions(self) -> None:
+        class Sum:
+            def __init__(self) -> None:
+                self.total = 0
+
+            def accumulate(self, term: Any) -> None:
+                self.total += int(term.value)
+
+            def finish(self) -> Literal:
+                return Literal(self.total)
+
+        store = Store()
+        store.add(Quad(foo, bar, Literal(1)))
+        store.add(Quad(foo, baz, Literal(2)))
+        results: Any = store.query(
+            "SELECT (<http://example.com/sum>(?o) AS ?total) WHERE { ?s ?p ?o } GROUP BY ?s",
+            custom_aggregate_functions={NamedNode("http://example.com/sum"): Sum},
+        )
+        self.assertEqual(next(results)["total"], Literal(3))
+
+    def test_select_query_with_service_handler(self) -> None:
+        service_store = Store()
+        service_store.add(Quad(foo, bar, baz))
+
+        def handler(service_name: NamedNode, query: str) -> Any:
+            self.assertEqual(service_name, NamedNode("http://example.com/service"))
+            return service_store.query(query)
+
+        store = Store()
+        results: Any = store.query(
+            "SELECT ?o WHERE { SERVICE <http://example.com/service> { ?s ?p ?o } }",
+            service_handler=handler,
+        )
+        self.assertEqual([s["o"] for s in results], [baz])
+
+    def test_select_query_with_failing_service_handler(self) -> None:
+        def handler(_service_name: NamedNode, _query: str) -> Any:
+            raise ValueError("service failure")
+
+        store = Store()
+        with self.assertRaises(ValueError):
+            list(
+                store.query(
+                    "SELECT ?o WHERE { SERVICE <http://example.com/service> { ?s ?p ?o } }",
+                    service_handler=handler,
+                )
+            )
+
     def test_select_query_with_substitution(self) -> None:
         store = Store()
         store.add(Quad(foo, bar, baz))
@@ -293,6 +354,48 @@ class TestStore(unittest.TestCase):
         )
         self.assertEqual(len(list(results)), 1)
 
+    def test_transaction(self) -> None:
+        store = Store()
+        with store.transaction() as transaction:
+            transaction.add(Quad(foo, bar, baz))
+            transaction.update("INSERT { ?s ?p <http://foo> } WHERE { ?s ?p <http://baz> }")
+            self.assertEqual(len(store), 0)
+        self.assertEqual(len(store), 2)
+
+    def test_transaction_query(self) -> None:
+        store = Store()
+        store.add(Quad(foo, bar, baz))
+        with store.transaction() as transaction:
+            transaction.remove(Quad(foo, bar, baz))
+            transaction.add(Quad(bar, bar, baz))
+            results: Any = transaction.query("SELECT ?s WHERE { ?s ?p ?o }")
+            self.assertEqual([s["s"] for s in results], [bar])
+            self.assertEqual(list(store), [Quad(foo, bar, baz)])
+        self.assertEqual(list(store), [Quad(bar, bar, baz)])
+
+    def test_transaction_rollback(self) -> None:
+        store = Store()
+        with self.assertRaises(ValueError), store.transaction() as transaction:
+            transaction.add(Quad(foo, bar, baz))
+            raise ValueError("rollback")
+        self.assertEqual(len(store), 0)
+
+    def test_transaction_after_commit(self) -> None:
+        store = Store()
+        transaction = store.transaction()
+        transaction.add(Quad(foo, bar, baz))
+        transaction.commit()
+        self.assertEqual(len(store), 1)
+        with self.assertRaises(ValueError):
+            transaction.add(Quad(foo, bar, baz))
+
+    def test_bulk_writer(self) -> None:
+        store = Store()
+        with store.bulk_writer() as writer:
+            writer.add(Quad(foo, bar, baz))
+            writer.extend((Quad(foo, bar, baz, graph), Quad(foo, bar, baz, DefaultGraph())))
+        self.assertEqual(len(store), 2)
+
     def test_load_ntriples_to_default_graph(self) -> None:
         store = Store()
         store.load(
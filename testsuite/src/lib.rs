@@ -4,6 +4,7 @@ pub mod evaluator;
 pub mod files;
 pub mod manifest;
 pub mod parser_evaluator;
+pub mod protocol;
 pub mod report;
 pub mod sparql_evaluator;
 mod vocab;
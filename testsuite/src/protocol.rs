@@ -0,0 +1,381 @@
+//! Implementation of the [SPARQL 1.1 protocol test suite](https://w3c.github.io/rdf-tests/sparql/sparql11/protocol/).
+//!
+//! The W3C test suite only describes the tests in prose so they are written here as HTTP exchanges.
+//! The server under test is a function handling a request, each test is run against a new server with an empty store.
+
+use anyhow::{ensure, Context, Result};
+use oxhttp::model::{HeaderName, Method, Request, Response, Status};
+use std::io::read_to_string;
+
+type ProtocolTest = fn(&dyn Fn(Request) -> Response) -> Result<()>;
+
+const TESTS: &[(&str, ProtocolTest)] = &[
+    (
+        "query_dataset_default_graphs_post",
+        query_dataset_default_graphs_post,
+    ),
+    (
+        "query_dataset_named_graphs_form",
+        query_dataset_named_graphs_form,
+    ),
+    (
+        "query_dataset_in_url_and_form",
+        query_dataset_in_url_and_form,
+    ),
+    ("bad_query_method", bad_query_method),
+    ("bad_multiple_queries", bad_multiple_queries),
+    (
+        "bad_multiple_queries_url_and_body",
+        bad_multiple_queries_url_and_body,
+    ),
+    ("bad_query_wrong_media_type", bad_query_wrong_media_type),
+    ("bad_query_missing_form_type", bad_query_missing_form_type),
+    (
+        "bad_query_missing_direct_type",
+        bad_query_missing_direct_type,
+    ),
+    ("bad_query_non_utf8", bad_query_non_utf8),
+    ("bad_query_non_utf8_direct", bad_query_non_utf8_direct),
+    ("bad_query_syntax", bad_query_syntax),
+    (
+        "bad_query_invalid_default_graph_uri",
+        bad_query_invalid_default_graph_uri,
+    ),
+    ("bad_query_with_update", bad_query_with_update),
+    ("bad_update_get", bad_update_get),
+    ("bad_update_method", bad_update_method),
+    ("bad_multiple_updates", bad_multiple_updates),
+    ("bad_update_wrong_media_type", bad_update_wrong_media_type),
+    ("bad_update_missing_form_type", bad_update_missing_form_type),
+    ("bad_update_non_utf8", bad_update_non_utf8),
+    ("bad_update_syntax", bad_update_syntax),
+    ("bad_update_dataset_conflict", bad_update_dataset_conflict),
+    (
+        "bad_update_create_existing_graph",
+        bad_update_create_existing_graph,
+    ),
+    ("update_dataset_default_graph", update_dataset_default_graph),
+];
+
+/// Runs the protocol test suite against the servers built by `new_server`
+#[allow(clippy::panic_in_result_fn)]
+pub fn check_protocol_testsuite<S: Fn(Request) -> Response>(
+    new_server: impl Fn() -> Result<S>,
+) -> Result<()> {
+    let mut errors = Vec::default();
+    for (name, test) in TESTS {
+        if let Err(error) = test(&new_server()?) {
+            errors.push(format!("{name}: failed with error {error:?}"))
+        }
+    }
+
+    assert!(
+        errors.is_empty(),
+        "{} failing tests:\n{}\n",
+        errors.len(),
+        errors.join("\n")
+    );
+    Ok(())
+}
+
+fn query_dataset_default_graphs_post(server: &dyn Fn(Request) -> Response) -> Result<()> {
+    load_graph(server)?;
+    let request = Request::builder(
+        Method::POST,
+        "http://localhost/query?default-graph-uri=http://localhost/store/1".parse()?,
+    )
+    .with_header(HeaderName::CONTENT_TYPE, "application/sparql-query")?
+    .with_header(HeaderName::ACCEPT, "text/csv")?
+    .with_body("SELECT ?s WHERE { ?s ?p ?o }");
+    check_body(server, request, "s\r\nhttp://example.com/s\r\n")
+}
+
+fn query_dataset_named_graphs_form(server: &dyn Fn(Request) -> Response) -> Result<()> {
+    load_graph(server)?;
+    let request = Request::builder(Method::POST, "http://localhost/query".parse()?)
+        .with_header(
+            HeaderName::CONTENT_TYPE,
+            "application/x-www-form-urlencoded",
+        )?
+        .with_header(HeaderName::ACCEPT, "text/csv")?
+        .with_body("query=SELECT%20?g%20WHERE%20{%20GRAPH%20?g%20{%20?s%20?p%20?o%20}%20}&named-graph-uri=http%3A%2F%2Flocalhost%2Fstore%2F1");
+    check_body(server, request, "g\r\nhttp://localhost/store/1\r\n")
+}
+
+fn query_dataset_in_url_and_form(server: &dyn Fn(Request) -> Response) -> Result<()> {
+    load_graph(server)?;
+    let request = Request::builder(
+        Method::POST,
+        "http://localhost/query?default-graph-uri=http://localhost/store/1".parse()?,
+    )
+    .with_header(
+        HeaderName::CONTENT_TYPE,
+        "application/x-www-form-urlencoded",
+    )?
+    .with_header(HeaderName::ACCEPT, "text/csv")?
+    .with_body("query=SELECT%20?s%20WHERE%20{%20?s%20?p%20?o%20}&default-graph-uri=http%3A%2F%2Fexample.com%2Fempty");
+    check_body(server, request, "s\r\nhttp://example.com/s\r\n")
+}
+
+fn bad_query_method(server: &dyn Fn(Request) -> Response) -> Result<()> {
+    let request = Request::builder(
+        Method::PUT,
+        "http://localhost/query?query=ASK%20{}".parse()?,
+    )
+    .build();
+    let response = check_status(server, request, Status::METHOD_NOT_ALLOWED)?;
+    check_allow(&response, "GET, POST")
+}
+
+fn bad_multiple_queries(server: &dyn Fn(Request) -> Response) -> Result<()> {
+    let request = Request::builder(
+        Method::GET,
+        "http://localhost/query?query=ASK%20{}&query=SELECT%20*%20{}".parse()?,
+    )
+    .build();
+    check_status(server, request, Status::BAD_REQUEST)?;
+    Ok(())
+}
+
+fn bad_multiple_queries_url_and_body(server: &dyn Fn(Request) -> Response) -> Result<()> {
+    let request = Request::builder(
+        Method::POST,
+        "http://localhost/query?query=ASK%20{}".parse()?,
+    )
+    .with_header(HeaderName::CONTENT_TYPE, "application/sparql-query")?
+    .with_body("SELECT * {}");
+    check_status(server, request, Status::BAD_REQUEST)?;
+    Ok(())
+}
+
+fn bad_query_wrong_media_type(server: &dyn Fn(Request) -> Response) -> Result<()> {
+    let request = Request::builder(Method::POST, "http://localhost/query".parse()?)
+        .with_header(HeaderName::CONTENT_TYPE, "text/plain")?
+        .with_body("ASK {}");
+    check_status(server, request, Status::UNSUPPORTED_MEDIA_TYPE)?;
+    Ok(())
+}
+
+fn bad_query_missing_form_type(server: &dyn Fn(Request) -> Response) -> Result<()> {
+    let request = Request::builder(Method::POST, "http://localhost/query".parse()?)
+        .with_body("query=ASK%20%7B%7D");
+    check_status(server, request, Status::BAD_REQUEST)?;
+    Ok(())
+}
+
+fn bad_query_missing_direct_type(server: &dyn Fn(Request) -> Response) -> Result<()> {
+    let request =
+        Request::builder(Method::POST, "http://localhost/query".parse()?).with_body("ASK {}");
+    check_status(server, request, Status::BAD_REQUEST)?;
+    Ok(())
+}
+
+fn bad_query_non_utf8(server: &dyn Fn(Request) -> Response) -> Result<()> {
+    let request = Request::builder(Method::POST, "http://localhost/query".parse()?)
+        .with_header(
+            HeaderName::CONTENT_TYPE,
+            "application/x-www-form-urlencoded",
+        )?
+        .with_body("query=ASK%20%7B%20%3Chttp%3A%2F%2Fexample.com%2F%E9%3E%20%3Fp%20%3Fo%20%7D");
+    check_status(server, request, Status::BAD_REQUEST)?;
+    Ok(())
+}
+
+fn bad_query_non_utf8_direct(server: &dyn Fn(Request) -> Response) -> Result<()> {
+    let request = Request::builder(Method::POST, "http://localhost/query".parse()?)
+        .with_header(HeaderName::CONTENT_TYPE, "application/sparql-query")?
+        .with_body(b"ASK { <http://example.com/\xe9> ?p ?o }".to_vec());
+    check_status(server, request, Status::BAD_REQUEST)?;
+    Ok(())
+}
+
+fn bad_query_syntax(server: &dyn Fn(Request) -> Response) -> Result<()> {
+    let request = Request::builder(
+        Method::GET,
+        "http://localhost/query?query=ASK%20%7B".parse()?,
+    )
+    .build();
+    check_status(server, request, Status::BAD_REQUEST)?;
+    Ok(())
+}
+
+fn bad_query_invalid_default_graph_uri(server: &dyn Fn(Request) -> Response) -> Result<()> {
+    let request = Request::builder(
+        Method::GET,
+        "http://localhost/query?query=ASK%20%7B%7D&default-graph-uri=foo".parse()?,
+    )
+    .build();
+    check_status(server, request, Status::BAD_REQUEST)?;
+    Ok(())
+}
+
+fn bad_query_with_update(server: &dyn Fn(Request) -> Response) -> Result<()> {
+    let request = Request::builder(Method::POST, "http://localhost/query".parse()?)
+        .with_header(
+            HeaderName::CONTENT_TYPE,
+            "application/x-www-form-urlencoded",
+        )?
+        .with_body("update=CLEAR%20ALL");
+    check_status(server, request, Status::BAD_REQUEST)?;
+    Ok(())
+}
+
+fn bad_update_get(server: &dyn Fn(Request) -> Response) -> Result<()> {
+    let request = Request::builder(
+        Method::GET,
+        "http://localhost/update?update=CLEAR%20ALL".parse()?,
+    )
+    .build();
+    let response = check_status(server, request, Status::METHOD_NOT_ALLOWED)?;
+    check_allow(&response, "POST")
+}
+
+fn bad_update_method(server: &dyn Fn(Request) -> Response) -> Result<()> {
+    let request = Request::builder(Method::PUT, "http://localhost/update".parse()?)
+        .with_header(HeaderName::CONTENT_TYPE, "application/sparql-update")?
+        .with_body("CLEAR ALL");
+    let response = check_status(server, request, Status::METHOD_NOT_ALLOWED)?;
+    check_allow(&response, "POST")
+}
+
+fn bad_multiple_updates(server: &dyn Fn(Request) -> Response) -> Result<()> {
+    let request = Request::builder(Method::POST, "http://localhost/update".parse()?)
+        .with_header(
+            HeaderName::CONTENT_TYPE,
+            "application/x-www-form-urlencoded",
+        )?
+        .with_body("update=CLEAR%20NAMED&update=CLEAR%20DEFAULT");
+    check_status(server, request, Status::BAD_REQUEST)?;
+    Ok(())
+}
+
+fn bad_update_wrong_media_type(server: &dyn Fn(Request) -> Response) -> Result<()> {
+    let request = Request::builder(Method::POST, "http://localhost/update".parse()?)
+        .with_header(HeaderName::CONTENT_TYPE, "text/plain")?
+        .with_body("CLEAR ALL");
+    check_status(server, request, Status::UNSUPPORTED_MEDIA_TYPE)?;
+    Ok(())
+}
+
+fn bad_update_missing_form_type(server: &dyn Fn(Request) -> Response) -> Result<()> {
+    let request = Request::builder(Method::POST, "http://localhost/update".parse()?)
+        .with_body("update=CLEAR%20ALL");
+    check_status(server, request, Status::BAD_REQUEST)?;
+    Ok(())
+}
+
+fn bad_update_non_utf8(server: &dyn Fn(Request) -> Response) -> Result<()> {
+    let request = Request::builder(Method::POST, "http://localhost/update".parse()?)
+        .with_header(
+            HeaderName::CONTENT_TYPE,
+            "application/x-www-form-urlencoded",
+        )?
+        .with_body("update=CLEAR%20GRAPH%20%3Chttp%3A%2F%2Fexample.com%2F%E9%3E");
+    check_status(server, request, Status::BAD_REQUEST)?;
+    Ok(())
+}
+
+fn bad_update_syntax(server: &dyn Fn(Request) -> Response) -> Result<()> {
+    let request = Request::builder(Method::POST, "http://localhost/update".parse()?)
+        .with_header(HeaderName::CONTENT_TYPE, "application/sparql-update")?
+        .with_body("CLEAR XYZ");
+    check_status(server, request, Status::BAD_REQUEST)?;
+    Ok(())
+}
+
+fn bad_update_dataset_conflict(server: &dyn Fn(Request) -> Response) -> Result<()> {
+    let request = Request::builder(
+        Method::POST,
+        "http://localhost/update?using-named-graph-uri=http://example.com/".parse()?,
+    )
+    .with_header(HeaderName::CONTENT_TYPE, "application/sparql-update")?
+    .with_body("DELETE { ?s ?p ?o } USING <http://example.com/g> WHERE { ?s ?p ?o }");
+    check_status(server, request, Status::BAD_REQUEST)?;
+    Ok(())
+}
+
+fn bad_update_create_existing_graph(server: &dyn Fn(Request) -> Response) -> Result<()> {
+    let request = Request::builder(Method::POST, "http://localhost/update".parse()?)
+        .with_header(HeaderName::CONTENT_TYPE, "application/sparql-update")?
+        .with_body("CREATE GRAPH <http://example.com/g>");
+    check_status(server, request, Status::NO_CONTENT)?;
+    let request = Request::builder(Method::POST, "http://localhost/update".parse()?)
+        .with_header(HeaderName::CONTENT_TYPE, "application/sparql-update")?
+        .with_body("CREATE GRAPH <http://example.com/g>");
+    check_status(server, request, Status::BAD_REQUEST)?;
+    Ok(())
+}
+
+fn update_dataset_default_graph(server: &dyn Fn(Request) -> Response) -> Result<()> {
+    load_graph(server)?;
+    let request = Request::builder(Method::POST, "http://localhost/update".parse()?)
+        .with_header(
+            HeaderName::CONTENT_TYPE,
+            "application/x-www-form-urlencoded",
+        )?
+        .with_body("update=INSERT%20%7B%20%3Fs%20%3Fp%20%3Fo%20%7D%20WHERE%20%7B%20%3Fs%20%3Fp%20%3Fo%20%7D&using-graph-uri=http%3A%2F%2Flocalhost%2Fstore%2F1");
+    check_status(server, request, Status::NO_CONTENT)?;
+    let request = Request::builder(
+        Method::GET,
+        "http://localhost/query?query=SELECT%20?s%20WHERE%20{%20?s%20?p%20?o%20}".parse()?,
+    )
+    .with_header(HeaderName::ACCEPT, "text/csv")?
+    .build();
+    check_body(server, request, "s\r\nhttp://example.com/s\r\n")
+}
+
+/// Creates the graph `http://localhost/store/1` using the graph store protocol
+fn load_graph(server: &dyn Fn(Request) -> Response) -> Result<()> {
+    let request = Request::builder(Method::PUT, "http://localhost/store/1".parse()?)
+        .with_header(HeaderName::CONTENT_TYPE, "text/turtle")?
+        .with_body("<http://example.com/s> <http://example.com/p> <http://example.com/o> .");
+    check_status(server, request, Status::CREATED)?;
+    Ok(())
+}
+
+fn check_status(
+    server: &dyn Fn(Request) -> Response,
+    request: Request,
+    expected_status: Status,
+) -> Result<Response> {
+    let mut response = server(request);
+    let body = read_to_string(response.body_mut())?;
+    ensure!(
+        response.status() == expected_status,
+        "Expected status {expected_status}, found {} with message: {body}",
+        response.status()
+    );
+    Ok(response)
+}
+
+fn check_body(
+    server: &dyn Fn(Request) -> Response,
+    request: Request,
+    expected_body: &str,
+) -> Result<()> {
+    let mut response = server(request);
+    let body = read_to_string(response.body_mut())?;
+    ensure!(
+        response.status() == Status::OK,
+        "Expected status {}, found {} with message: {body}",
+        Status::OK,
+        response.status()
+    );
+    ensure!(
+        body == expected_body,
+        "Expected body {expected_body:?}, found {body:?}"
+    );
+    Ok(())
+}
+
+fn check_allow(response: &Response, expected_methods: &str) -> Result<()> {
+    let allow = response
+        .header(&HeaderName::ALLOW)
+        .context("No Allow header in the 405 response")?
+        .to_str()?;
+    ensure!(
+        allow == expected_methods,
+        "Expected the allowed methods {expected_methods}, found {allow}"
+    );
+    Ok(())
+}